@@ -21,6 +21,17 @@ fn create_mock_email() -> EmailMessage {
         snippet: Some("This is a preview of the email content...".to_string()),
         body_text: Some("This is the plain text body of the email.".to_string()),
         body_html: Some("<div>This is the <b>HTML</b> body of the email.</div>".to_string()),
+        rfc_message_id: Some("<msg123456@example.com>".to_string()),
+        language: None,
+        is_signed: false,
+        is_encrypted: false,
+        signer: None,
+        authentication: None,
+        inline_images: Vec::new(),
+        priority: Default::default(),
+        delivery_report: None,
+        read_receipt: None,
+        trackers: Vec::new(),
     }
 }
 
@@ -34,6 +45,8 @@ fn create_mock_draft() -> DraftEmail {
         thread_id: Some("thread123456".to_string()),
         in_reply_to: Some("<original-message@example.com>".to_string()),
         references: Some("<original-message@example.com> <another-message@example.com>".to_string()),
+        priority: None,
+        read_receipt_to: None,
     }
 }
 
@@ -51,18 +64,37 @@ fn create_mock_calendar_event() -> CalendarEvent {
                 display_name: Some("Attendee One".to_string()),
                 response_status: Some("accepted".to_string()),
                 optional: None,
+                time_zone: None,
+                self_: None,
             },
             Attendee {
                 email: "attendee2@example.com".to_string(),
                 display_name: Some("Attendee Two".to_string()),
                 response_status: Some("needsAction".to_string()),
                 optional: None,
+                time_zone: None,
+                self_: None,
             },
         ],
         conference_data: None,
+        join_links: Vec::new(),
         html_link: None,
         creator: None,
         organizer: None,
+        attachments: Vec::new(),
+        recurrence: Vec::new(),
+        transparency: None,
+        time_zone: None,
+        all_day: false,
+        working_location: None,
+        event_type: None,
+        color_id: None,
+        category: None,
+        reminders: None,
+        private_properties: Default::default(),
+        guests_can_modify: None,
+        guests_can_invite_others: None,
+        guests_can_see_other_guests: None,
     }
 }
 
@@ -72,6 +104,8 @@ fn mock_config() -> Config {
         client_secret: "test_client_secret".to_string(),
         refresh_token: "test_refresh_token".to_string(),
         access_token: Some("test_access_token".to_string()),
+        token_refresh_threshold: 300,
+        token_expiry_buffer: 60,
     }
 }
 
@@ -152,6 +186,8 @@ fn token_operations_benchmarks(c: &mut Criterion) {
         client_secret: "test_client_secret".to_string(),
         refresh_token: "test_refresh_token".to_string(),
         access_token: None,
+        token_refresh_threshold: 300,
+        token_expiry_buffer: 60,
     };
     
     group.bench_function("token_manager_with_token", |b| {
@@ -262,7 +298,7 @@ fn search_operations_benchmarks(c: &mut Criterion) {
         b.iter(|| {
             black_box(
                 emails.iter().filter(|e| {
-                    e.subject.as_ref().map_or(false, |s| s.contains("50"))
+                    e.subject.as_ref().is_some_and(|s| s.contains("50"))
                 }).count()
             )
         })
@@ -311,7 +347,7 @@ fn api_request_simulation_benchmarks(c: &mut Criterion) {
             |_| {
                 // Simulate error handling
                 let error = GmailApiError::NetworkError("Connection timeout".to_string());
-                let result: Result<String, GmailApiError> = Err(error);
+                let result: Result<String, GmailApiError> = black_box(Err(error));
                 
                 // Common error handling pattern - provide fallback or handle error
                 black_box(result.unwrap_or_else(|e| format!("Error: {}", e)))
@@ -0,0 +1,359 @@
+use crate::calendar_api::{CalendarClient, CalendarEvent, CalendarList};
+use crate::drive_api::{DriveClient, DriveFile, DrivePermission, StorageQuota};
+use crate::errors::{CalendarResult, DriveResult, GmailResult, PeopleResult, TasksResult};
+use crate::gmail_api::{
+    DraftEmail, EmailMessage, GmailService, HistoryChanges, MessageCount, MessageMetadata,
+};
+use crate::people_api::{Contact, ContactList, PeopleClient};
+use crate::tasks_api::{Task, TaskList, TasksClient};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+// API abstractions
+//
+// These traits describe the Google operations the MCP tools and CLI commands
+// rely on. The production clients implement them by delegating to their
+// inherent methods; in-memory implementations for tests are available in
+// `test_util` behind the `test-util` feature.
+
+/// Gmail operations
+#[async_trait]
+pub trait GmailApi: Send + Sync {
+    /// List messages, optionally filtered by a Gmail search query
+    async fn list_messages(
+        &self,
+        max_results: u32,
+        query: Option<&str>,
+    ) -> GmailResult<Vec<EmailMessage>>;
+
+    /// List the IDs of the most recent messages, optionally filtered by a
+    /// Gmail search query
+    async fn list_message_ids(
+        &self,
+        max_results: u32,
+        query: Option<&str>,
+    ) -> GmailResult<Vec<String>>;
+
+    /// Whether a request was left out because the client's deadline had
+    /// passed, so that results it returned may be incomplete
+    fn deadline_reached(&self) -> bool {
+        false
+    }
+
+    /// Count the messages matching an optional Gmail search query, exactly
+    /// up to `exact_limit` matches and estimated beyond
+    async fn count_messages(
+        &self,
+        query: Option<&str>,
+        exact_limit: u32,
+    ) -> GmailResult<MessageCount>;
+
+    /// Get a single message with its decoded bodies
+    async fn get_message_details(&self, message_id: &str) -> GmailResult<EmailMessage>;
+
+    /// Get a message's sender, subject, arrival time and labels without its
+    /// content
+    async fn get_message_metadata(&self, message_id: &str) -> GmailResult<MessageMetadata>;
+
+    /// Get the mailbox's current history ID
+    async fn get_history_id(&self) -> GmailResult<String>;
+
+    /// List messages added and deleted since a history ID
+    async fn list_history(&self, start_history_id: &str) -> GmailResult<HistoryChanges>;
+
+    /// List labels as the raw JSON returned by the API
+    async fn list_labels(&self) -> GmailResult<String>;
+
+    /// Get the ID of the label with the given name, creating it if missing
+    async fn get_or_create_label(&self, name: &str) -> GmailResult<String>;
+
+    /// Add and remove labels on a message
+    async fn modify_labels(
+        &self,
+        message_id: &str,
+        add_label_ids: &[String],
+        remove_label_ids: &[String],
+    ) -> GmailResult<()>;
+
+    /// Add and remove labels on every message in a thread, returning the IDs
+    /// of the thread's messages
+    async fn modify_thread_labels(
+        &self,
+        thread_id: &str,
+        add_label_ids: &[String],
+        remove_label_ids: &[String],
+    ) -> GmailResult<Vec<String>>;
+
+    /// Move every message in a thread to the trash, returning the IDs of the
+    /// thread's messages
+    async fn trash_thread(&self, thread_id: &str) -> GmailResult<Vec<String>>;
+
+    /// Return the account email address and total message count
+    async fn check_connection(&self) -> GmailResult<(String, u64)>;
+
+    /// Create a draft, returning its ID
+    async fn create_draft(&self, draft: &DraftEmail) -> GmailResult<String>;
+
+    /// Send an email immediately, returning the ID of the sent message
+    async fn send_message(&self, draft: &DraftEmail) -> GmailResult<String>;
+}
+
+/// Google Calendar operations
+#[async_trait]
+pub trait CalendarApi: Send + Sync {
+    /// List the calendars the user has access to
+    async fn list_calendars(&self) -> CalendarResult<CalendarList>;
+
+    /// List events in a calendar, optionally limited to a time window
+    async fn list_events(
+        &self,
+        calendar_id: &str,
+        max_results: Option<u32>,
+        time_min: Option<DateTime<Utc>>,
+        time_max: Option<DateTime<Utc>>,
+    ) -> CalendarResult<Vec<CalendarEvent>>;
+
+    /// Create an event, returning it as stored by the calendar
+    async fn create_event(
+        &self,
+        calendar_id: &str,
+        event: CalendarEvent,
+    ) -> CalendarResult<CalendarEvent>;
+
+    /// Get a single event
+    async fn get_event(&self, calendar_id: &str, event_id: &str) -> CalendarResult<CalendarEvent>;
+}
+
+/// Google People (contacts) operations
+#[async_trait]
+pub trait PeopleApi: Send + Sync {
+    /// List the user's contacts
+    async fn list_contacts(&self, max_results: Option<u32>) -> PeopleResult<ContactList>;
+
+    /// Search contacts by name, email address, phone number or organization
+    async fn search_contacts(
+        &self,
+        query: &str,
+        max_results: Option<u32>,
+    ) -> PeopleResult<ContactList>;
+
+    /// Get a single contact by resource name, e.g. `people/c123`
+    async fn get_contact(&self, resource_name: &str) -> PeopleResult<Contact>;
+}
+
+/// Google Drive operations
+#[async_trait]
+pub trait DriveApi: Send + Sync {
+    /// Get a file's metadata
+    async fn get_file(&self, file_id: &str) -> DriveResult<DriveFile>;
+
+    /// Get who a file is shared with
+    async fn list_permissions(&self, file_id: &str) -> DriveResult<Vec<DrivePermission>>;
+
+    /// Download a file's content, failing if it is larger than `max_bytes`
+    async fn download_file(&self, file_id: &str, max_bytes: u64) -> DriveResult<Vec<u8>>;
+
+    /// Get the account's storage quota
+    async fn get_storage_quota(&self) -> DriveResult<StorageQuota>;
+}
+
+/// Google Tasks operations
+#[async_trait]
+pub trait TasksApi: Send + Sync {
+    /// List the user's task lists
+    async fn list_task_lists(&self, max_results: Option<u32>) -> TasksResult<Vec<TaskList>>;
+
+    /// List the tasks in a list, optionally including completed ones
+    async fn list_tasks(
+        &self,
+        task_list_id: &str,
+        show_completed: bool,
+        max_results: Option<u32>,
+    ) -> TasksResult<Vec<Task>>;
+
+    /// Create a task, returning it as stored by Google
+    async fn create_task(&self, task_list_id: &str, task: &Task) -> TasksResult<Task>;
+
+    /// Mark a task as completed
+    async fn complete_task(&self, task_list_id: &str, task_id: &str) -> TasksResult<Task>;
+}
+
+#[async_trait]
+impl GmailApi for GmailService {
+    async fn list_messages(
+        &self,
+        max_results: u32,
+        query: Option<&str>,
+    ) -> GmailResult<Vec<EmailMessage>> {
+        GmailService::list_messages(self, max_results, query).await
+    }
+
+    async fn list_message_ids(
+        &self,
+        max_results: u32,
+        query: Option<&str>,
+    ) -> GmailResult<Vec<String>> {
+        GmailService::list_message_ids(self, max_results, query).await
+    }
+
+    fn deadline_reached(&self) -> bool {
+        self.deadline().was_reached()
+    }
+
+    async fn count_messages(
+        &self,
+        query: Option<&str>,
+        exact_limit: u32,
+    ) -> GmailResult<MessageCount> {
+        GmailService::count_messages(self, query, exact_limit).await
+    }
+
+    async fn get_message_details(&self, message_id: &str) -> GmailResult<EmailMessage> {
+        GmailService::get_message_details(self, message_id).await
+    }
+
+    async fn get_message_metadata(&self, message_id: &str) -> GmailResult<MessageMetadata> {
+        GmailService::get_message_metadata(self, message_id, &[]).await
+    }
+
+    async fn get_history_id(&self) -> GmailResult<String> {
+        GmailService::get_history_id(self).await
+    }
+
+    async fn list_history(&self, start_history_id: &str) -> GmailResult<HistoryChanges> {
+        GmailService::list_history(self, start_history_id).await
+    }
+
+    async fn list_labels(&self) -> GmailResult<String> {
+        GmailService::list_labels(self).await
+    }
+
+    async fn get_or_create_label(&self, name: &str) -> GmailResult<String> {
+        GmailService::get_or_create_label(self, name).await
+    }
+
+    async fn modify_labels(
+        &self,
+        message_id: &str,
+        add_label_ids: &[String],
+        remove_label_ids: &[String],
+    ) -> GmailResult<()> {
+        GmailService::modify_labels(self, message_id, add_label_ids, remove_label_ids).await
+    }
+
+    async fn modify_thread_labels(
+        &self,
+        thread_id: &str,
+        add_label_ids: &[String],
+        remove_label_ids: &[String],
+    ) -> GmailResult<Vec<String>> {
+        GmailService::modify_thread_labels(self, thread_id, add_label_ids, remove_label_ids).await
+    }
+
+    async fn trash_thread(&self, thread_id: &str) -> GmailResult<Vec<String>> {
+        GmailService::trash_thread(self, thread_id).await
+    }
+
+    async fn check_connection(&self) -> GmailResult<(String, u64)> {
+        GmailService::check_connection(self).await
+    }
+
+    async fn create_draft(&self, draft: &DraftEmail) -> GmailResult<String> {
+        GmailService::create_draft(self, draft).await
+    }
+
+    async fn send_message(&self, draft: &DraftEmail) -> GmailResult<String> {
+        GmailService::send_message(self, draft).await
+    }
+}
+
+#[async_trait]
+impl CalendarApi for CalendarClient {
+    async fn list_calendars(&self) -> CalendarResult<CalendarList> {
+        CalendarClient::list_calendars(self).await
+    }
+
+    async fn list_events(
+        &self,
+        calendar_id: &str,
+        max_results: Option<u32>,
+        time_min: Option<DateTime<Utc>>,
+        time_max: Option<DateTime<Utc>>,
+    ) -> CalendarResult<Vec<CalendarEvent>> {
+        CalendarClient::list_events(self, calendar_id, max_results, time_min, time_max).await
+    }
+
+    async fn create_event(
+        &self,
+        calendar_id: &str,
+        event: CalendarEvent,
+    ) -> CalendarResult<CalendarEvent> {
+        CalendarClient::create_event(self, calendar_id, event).await
+    }
+
+    async fn get_event(&self, calendar_id: &str, event_id: &str) -> CalendarResult<CalendarEvent> {
+        CalendarClient::get_event(self, calendar_id, event_id).await
+    }
+}
+
+#[async_trait]
+impl PeopleApi for PeopleClient {
+    async fn list_contacts(&self, max_results: Option<u32>) -> PeopleResult<ContactList> {
+        PeopleClient::list_contacts(self, max_results).await
+    }
+
+    async fn search_contacts(
+        &self,
+        query: &str,
+        max_results: Option<u32>,
+    ) -> PeopleResult<ContactList> {
+        PeopleClient::search_contacts(self, query, max_results).await
+    }
+
+    async fn get_contact(&self, resource_name: &str) -> PeopleResult<Contact> {
+        PeopleClient::get_contact(self, resource_name).await
+    }
+}
+
+#[async_trait]
+impl TasksApi for TasksClient {
+    async fn list_task_lists(&self, max_results: Option<u32>) -> TasksResult<Vec<TaskList>> {
+        TasksClient::list_task_lists(self, max_results).await
+    }
+
+    async fn list_tasks(
+        &self,
+        task_list_id: &str,
+        show_completed: bool,
+        max_results: Option<u32>,
+    ) -> TasksResult<Vec<Task>> {
+        TasksClient::list_tasks(self, task_list_id, show_completed, max_results).await
+    }
+
+    async fn create_task(&self, task_list_id: &str, task: &Task) -> TasksResult<Task> {
+        TasksClient::create_task(self, task_list_id, task).await
+    }
+
+    async fn complete_task(&self, task_list_id: &str, task_id: &str) -> TasksResult<Task> {
+        TasksClient::complete_task(self, task_list_id, task_id).await
+    }
+}
+
+#[async_trait]
+impl DriveApi for DriveClient {
+    async fn get_file(&self, file_id: &str) -> DriveResult<DriveFile> {
+        DriveClient::get_file(self, file_id).await
+    }
+
+    async fn list_permissions(&self, file_id: &str) -> DriveResult<Vec<DrivePermission>> {
+        DriveClient::list_permissions(self, file_id).await
+    }
+
+    async fn download_file(&self, file_id: &str, max_bytes: u64) -> DriveResult<Vec<u8>> {
+        DriveClient::download_file(self, file_id, max_bytes).await
+    }
+
+    async fn get_storage_quota(&self) -> DriveResult<StorageQuota> {
+        DriveClient::get_storage_quota(self).await
+    }
+}
@@ -0,0 +1,437 @@
+use crate::errors::{GmailApiError, GmailResult};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+// Attachment support
+//
+// The Gmail API returns attachment content as a base64url string inside a
+// JSON object (`{"size": 1234, "data": "..."}`), so a 300 MB attachment is a
+// 400 MB response. Rather than parse that with serde, `AttachmentDecoder`
+// scans the response as it streams in and decodes the `data` string in
+// chunks, which lets `GmailService::download_attachment` write attachments
+// to disk with bounded memory.
+
+/// Default maximum attachment size, in bytes (512 MiB)
+pub const DEFAULT_MAX_ATTACHMENT_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Default size up to which inline images are embedded in HTML bodies, in
+/// bytes (64 KiB)
+pub const DEFAULT_INLINE_IMAGE_MAX_BYTES: u64 = 64 * 1024;
+
+// Longest JSON prefix accepted before the `data` field is found
+const MAX_HEADER_BYTES: usize = 64 * 1024;
+
+/// An attachment on a message
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct AttachmentInfo {
+    pub attachment_id: String,
+    pub filename: String,
+    pub mime_type: String,
+    /// Size in bytes as reported by Gmail
+    pub size: u64,
+}
+
+/// A file to attach to an outgoing email
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutgoingAttachment {
+    pub filename: String,
+    pub mime_type: String,
+    /// Content, written as base64 when saved
+    #[serde(
+        serialize_with = "serialize_base64",
+        deserialize_with = "deserialize_base64"
+    )]
+    pub data: Vec<u8>,
+}
+
+fn serialize_base64<S: serde::Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&base64::encode(data))
+}
+
+fn deserialize_base64<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<u8>, D::Error> {
+    let encoded = String::deserialize(deserializer)?;
+    base64::decode(encoded).map_err(serde::de::Error::custom)
+}
+
+/// Find the attachments in a message payload, including nested parts
+pub fn find_attachments(payload: &Value) -> Vec<AttachmentInfo> {
+    let mut attachments = Vec::new();
+    collect_attachments(payload, &mut attachments);
+    attachments
+}
+
+fn collect_attachments(part: &Value, attachments: &mut Vec<AttachmentInfo>) {
+    if let Some(attachment_id) = part["body"]["attachmentId"].as_str() {
+        attachments.push(AttachmentInfo {
+            attachment_id: attachment_id.to_string(),
+            filename: part["filename"].as_str().unwrap_or_default().to_string(),
+            mime_type: part["mimeType"]
+                .as_str()
+                .unwrap_or("application/octet-stream")
+                .to_string(),
+            size: part["body"]["size"].as_u64().unwrap_or(0),
+        });
+    }
+
+    for child in part["parts"].as_array().into_iter().flatten() {
+        collect_attachments(child, attachments);
+    }
+}
+
+/// An image an HTML body shows through a `cid:` reference to another part of
+/// the message
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InlineImage {
+    /// Content-ID of the image part, without angle brackets
+    pub content_id: String,
+    pub filename: Option<String>,
+    pub mime_type: String,
+    /// Size in bytes as reported by Gmail
+    pub size: u64,
+    /// Whether the `cid:` references were replaced with a `data:` URI
+    pub embedded: bool,
+    /// ID to download an image that was not embedded with get_attachment
+    pub attachment_id: Option<String>,
+}
+
+// The Content-ID header of a part, without angle brackets
+fn content_id(part: &Value) -> Option<String> {
+    part["headers"].as_array()?.iter().find(|header| {
+        header["name"]
+            .as_str()
+            .is_some_and(|name| name.eq_ignore_ascii_case("Content-ID"))
+    })?["value"]
+        .as_str()
+        .map(|id| {
+            id.trim()
+                .trim_start_matches('<')
+                .trim_end_matches('>')
+                .to_string()
+        })
+        .filter(|id| !id.is_empty())
+}
+
+fn collect_content_parts<'a>(part: &'a Value, parts: &mut Vec<(String, &'a Value)>) {
+    if let Some(id) = content_id(part) {
+        parts.push((id, part));
+    }
+    for child in part["parts"].as_array().into_iter().flatten() {
+        collect_content_parts(child, parts);
+    }
+}
+
+/// Resolve the `cid:` references in an HTML body to the image parts of the
+/// message. Images Gmail sent inline of up to `max_bytes` replace their
+/// references as `data:` URIs; the others are listed with an attachment ID
+/// so they can be downloaded. Parts the body does not refer to are left to
+/// `find_attachments`.
+pub fn resolve_inline_images(
+    payload: &Value,
+    html: &mut String,
+    max_bytes: u64,
+) -> Vec<InlineImage> {
+    let mut parts = Vec::new();
+    collect_content_parts(payload, &mut parts);
+
+    let mut images = Vec::new();
+    for (id, part) in parts {
+        let reference = format!("cid:{}", id);
+        if !html.contains(&reference) {
+            continue;
+        }
+        let mime_type = part["mimeType"]
+            .as_str()
+            .unwrap_or("application/octet-stream")
+            .to_string();
+        let size = part["body"]["size"].as_u64().unwrap_or(0);
+        // Gmail sends base64url, which data URIs do not accept
+        let data = part["body"]["data"]
+            .as_str()
+            .filter(|_| size <= max_bytes && mime_type.starts_with("image/"))
+            .and_then(|data| base64::decode_config(data, base64::URL_SAFE).ok());
+        if let Some(data) = &data {
+            let uri = format!("data:{};base64,{}", mime_type, base64::encode(data));
+            *html = html.replace(&reference, &uri);
+        }
+        images.push(InlineImage {
+            content_id: id,
+            filename: part["filename"]
+                .as_str()
+                .filter(|name| !name.is_empty())
+                .map(String::from),
+            mime_type,
+            size,
+            embedded: data.is_some(),
+            attachment_id: part["body"]["attachmentId"].as_str().map(String::from),
+        });
+    }
+    images
+}
+
+/// Returns the size up to which inline images are embedded in HTML bodies,
+/// in bytes.
+///
+/// Environment variable: INLINE_IMAGE_MAX_BYTES
+pub fn get_inline_image_max_bytes() -> u64 {
+    std::env::var("INLINE_IMAGE_MAX_BYTES")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_INLINE_IMAGE_MAX_BYTES)
+}
+
+/// Called with the number of bytes written so far and the expected total,
+/// when known
+pub type ProgressCallback = Box<dyn FnMut(u64, Option<u64>) + Send>;
+
+/// Options for `GmailService::download_attachment`
+pub struct DownloadOptions {
+    /// Downloads stop with an error once this many bytes have been decoded
+    pub max_size: u64,
+    pub progress: Option<ProgressCallback>,
+}
+
+impl Default for DownloadOptions {
+    fn default() -> Self {
+        Self {
+            max_size: get_max_attachment_bytes(),
+            progress: None,
+        }
+    }
+}
+
+impl DownloadOptions {
+    pub fn with_max_size(mut self, max_size: u64) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    pub fn with_progress<F>(mut self, progress: F) -> Self
+    where
+        F: FnMut(u64, Option<u64>) + Send + 'static,
+    {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+}
+
+/// Returns the maximum attachment size in bytes.
+///
+/// Environment variable: ATTACHMENT_MAX_BYTES
+pub fn get_max_attachment_bytes() -> u64 {
+    std::env::var("ATTACHMENT_MAX_BYTES")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_MAX_ATTACHMENT_BYTES)
+}
+
+/// Returns the directory downloaded attachments are saved to.
+///
+/// Environment variable: ATTACHMENT_DIR
+pub fn get_attachment_dir() -> PathBuf {
+    match std::env::var("ATTACHMENT_DIR") {
+        Ok(dir) if !dir.trim().is_empty() => PathBuf::from(dir),
+        _ => {
+            let mut dir = dirs::download_dir().unwrap_or_else(std::env::temp_dir);
+            dir.push("gmail-mcp-rs");
+            dir.push("attachments");
+            dir
+        }
+    }
+}
+
+/// Make an attachment filename safe to create inside the attachment
+/// directory, stripping any path components
+pub fn safe_filename(filename: &str) -> String {
+    let name: String = filename
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .map(|c| if c.is_control() || c == ':' { '_' } else { c })
+        .collect();
+
+    match name.trim() {
+        "" | "." | ".." => "attachment".to_string(),
+        name => name.to_string(),
+    }
+}
+
+/// Path for saving `filename` in `dir` that does not overwrite an existing
+/// file, e.g. `report (1).pdf` when `report.pdf` exists
+pub fn unique_attachment_path(dir: &Path, filename: &str) -> PathBuf {
+    let filename = safe_filename(filename);
+    let candidate = dir.join(&filename);
+    if !candidate.exists() {
+        return candidate;
+    }
+
+    let (stem, extension) = match filename.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, Some(extension)),
+        _ => (filename.as_str(), None),
+    };
+    (1..)
+        .map(|n| match extension {
+            Some(extension) => dir.join(format!("{} ({}).{}", stem, n, extension)),
+            None => dir.join(format!("{} ({})", stem, n)),
+        })
+        .find(|path| !path.exists())
+        .expect("unbounded range always finds a free name")
+}
+
+enum DecoderState {
+    Header,
+    Data,
+    Done,
+}
+
+/// Incremental decoder for an attachment response body
+pub struct AttachmentDecoder {
+    state: DecoderState,
+    header: Vec<u8>,
+    pending: Vec<u8>,
+    declared_size: Option<u64>,
+}
+
+impl Default for AttachmentDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AttachmentDecoder {
+    pub fn new() -> Self {
+        Self {
+            state: DecoderState::Header,
+            header: Vec::new(),
+            pending: Vec::new(),
+            declared_size: None,
+        }
+    }
+
+    /// The `size` field of the response, if it came before the data
+    pub fn declared_size(&self) -> Option<u64> {
+        self.declared_size
+    }
+
+    /// Decode the next chunk of the response, appending content to `out`
+    pub fn feed(&mut self, chunk: &[u8], out: &mut Vec<u8>) -> GmailResult<()> {
+        match self.state {
+            DecoderState::Header => {
+                self.header.extend_from_slice(chunk);
+                if self.declared_size.is_none() {
+                    self.declared_size = find_number_field(&self.header, b"\"size\"");
+                }
+
+                match find_string_start(&self.header, b"\"data\"") {
+                    Some(start) => {
+                        let rest = self.header.split_off(start);
+                        self.header.clear();
+                        self.state = DecoderState::Data;
+                        self.feed_data(&rest, out)
+                    }
+                    None if self.header.len() > MAX_HEADER_BYTES => {
+                        Err(GmailApiError::MessageFormatError(
+                            "Attachment response has no 'data' field".to_string(),
+                        ))
+                    }
+                    None => Ok(()),
+                }
+            }
+            DecoderState::Data => self.feed_data(chunk, out),
+            DecoderState::Done => Ok(()),
+        }
+    }
+
+    fn feed_data(&mut self, chunk: &[u8], out: &mut Vec<u8>) -> GmailResult<()> {
+        let (data, done) = match chunk.iter().position(|&b| b == b'"') {
+            Some(end) => (&chunk[..end], true),
+            None => (chunk, false),
+        };
+
+        // JSON may escape characters or wrap long strings; neither belongs
+        // to the base64 content
+        self.pending.extend(
+            data.iter()
+                .copied()
+                .filter(|b| !b.is_ascii_whitespace() && *b != b'\\'),
+        );
+
+        let complete = if done {
+            self.pending.len()
+        } else {
+            self.pending.len() / 4 * 4
+        };
+        if complete > 0 {
+            base64::decode_config_buf(&self.pending[..complete], base64::URL_SAFE, out).map_err(
+                |e| GmailApiError::MessageFormatError(format!("Invalid attachment data: {}", e)),
+            )?;
+            self.pending.drain(..complete);
+        }
+
+        if done {
+            self.state = DecoderState::Done;
+        }
+        Ok(())
+    }
+
+    /// Check that the whole `data` field was received
+    pub fn finish(&self) -> GmailResult<()> {
+        match self.state {
+            DecoderState::Done => Ok(()),
+            DecoderState::Header => Err(GmailApiError::MessageFormatError(
+                "Attachment response has no 'data' field".to_string(),
+            )),
+            DecoderState::Data => Err(GmailApiError::NetworkError(
+                "Attachment response ended before the data was complete".to_string(),
+            )),
+        }
+    }
+}
+
+// Find `"key": "` and return the index just after the opening quote
+fn find_string_start(buf: &[u8], key: &[u8]) -> Option<usize> {
+    let mut i = find_value_start(buf, key)?;
+    if buf.get(i) == Some(&b'"') {
+        i += 1;
+        Some(i)
+    } else {
+        None
+    }
+}
+
+// Parse `"key": 123` once the number is complete
+fn find_number_field(buf: &[u8], key: &[u8]) -> Option<u64> {
+    let start = find_value_start(buf, key)?;
+    let len = buf[start..]
+        .iter()
+        .take_while(|b| b.is_ascii_digit())
+        .count();
+    // Wait for a terminator so that a number split across chunks is not cut
+    if len == 0 || start + len >= buf.len() {
+        return None;
+    }
+    std::str::from_utf8(&buf[start..start + len])
+        .ok()?
+        .parse()
+        .ok()
+}
+
+// Find `"key"` followed by a colon and return the index of the value
+fn find_value_start(buf: &[u8], key: &[u8]) -> Option<usize> {
+    let key_pos = buf.windows(key.len()).position(|window| window == key)?;
+    let mut i = key_pos + key.len();
+    while buf.get(i)?.is_ascii_whitespace() {
+        i += 1;
+    }
+    if buf[i] != b':' {
+        return None;
+    }
+    i += 1;
+    while buf.get(i)?.is_ascii_whitespace() {
+        i += 1;
+    }
+    Some(i)
+}
@@ -0,0 +1,178 @@
+use crate::calendar_api::{BusyPeriod, CalendarBusy, CalendarEvent};
+use crate::event_rsvps::is_resource;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+// Attendee availability
+//
+// Suggesting a new time for a meeting needs to know when the people in it
+// are busy. Their free/busy is read for a few hours either side of the
+// event, in one query. Free/busy only says when a calendar is busy, not with
+// what, so the event itself is busy time for every attendee who has not
+// declined it; that time is taken out, leaving their other commitments.
+// Someone is busy during the event when busy time overlaps it and runs past
+// it. A meeting wholly inside the event cannot be told apart from it, and
+// one right before or after is merged with it, so they show as busy too.
+// Attendees who do not share their calendar with the user are unknown.
+
+/// Hours either side of the event whose free/busy is read
+pub const AVAILABILITY_MARGIN_HOURS: i64 = 4;
+
+/// The time around an event whose free/busy is read
+pub fn availability_window(event: &CalendarEvent) -> (DateTime<Utc>, DateTime<Utc>) {
+    let margin = Duration::hours(AVAILABILITY_MARGIN_HOURS);
+    (event.start_time - margin, event.end_time + margin)
+}
+
+/// Calendars to query: the attendees' addresses, without rooms or repeats
+pub fn availability_calendars(event: &CalendarEvent) -> Vec<String> {
+    let mut calendars: Vec<String> = Vec::new();
+    for attendee in &event.attendees {
+        let email = attendee.email.to_lowercase();
+        if !is_resource(&email) && !calendars.contains(&email) {
+            calendars.push(email);
+        }
+    }
+    calendars
+}
+
+/// Whether an attendee can make the event's time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AvailabilityStatus {
+    Free,
+    Busy,
+    /// Their calendar is not shared with the user
+    Unknown,
+}
+
+/// An attendee's free/busy around the event
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AttendeeAvailability {
+    pub email: String,
+    pub display_name: Option<String>,
+    pub response_status: Option<String>,
+    pub status: AvailabilityStatus,
+    /// Busy times in the window other than the event
+    pub busy: Vec<BusyPeriod>,
+    /// Why their free/busy could not be read
+    pub error: Option<String>,
+}
+
+/// A time in the window when every attendee whose free/busy is known is free
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FreeSlot {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+/// The attendees' free/busy around an event
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EventAvailability {
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+    pub attendees: Vec<AttendeeAvailability>,
+    /// Times in the window, at least as long as the event, when all known
+    /// attendees are free; the event's own time counts as free
+    pub free_for_all: Vec<FreeSlot>,
+}
+
+// Busy periods with the event's time taken out
+fn without_event(busy: &[BusyPeriod], event: &CalendarEvent) -> Vec<BusyPeriod> {
+    let mut periods = Vec::new();
+    for period in busy {
+        if period.end <= event.start_time || period.start >= event.end_time {
+            periods.push(period.clone());
+            continue;
+        }
+        if period.start < event.start_time {
+            periods.push(BusyPeriod {
+                start: period.start,
+                end: event.start_time,
+            });
+        }
+        if period.end > event.end_time {
+            periods.push(BusyPeriod {
+                start: event.end_time,
+                end: period.end,
+            });
+        }
+    }
+    periods
+}
+
+impl EventAvailability {
+    /// Match the free/busy read for `availability_calendars` to the event's
+    /// attendees
+    pub fn new(event: &CalendarEvent, calendars: &[CalendarBusy]) -> Self {
+        let (window_start, window_end) = availability_window(event);
+        // A free event does not make its attendees busy
+        let blocks_time = event.transparency.as_deref() != Some("transparent");
+
+        let mut attendees = Vec::new();
+        let mut known_busy = Vec::new();
+        for attendee in &event.attendees {
+            let email = attendee.email.to_lowercase();
+            if is_resource(&email) {
+                continue;
+            }
+            let calendar = calendars
+                .iter()
+                .find(|calendar| calendar.calendar_id.eq_ignore_ascii_case(&email));
+            let error = match calendar {
+                Some(calendar) => calendar.error.clone(),
+                None => Some("notFound".to_string()),
+            };
+
+            let (status, busy) = match calendar.filter(|_| error.is_none()) {
+                Some(calendar) => {
+                    let attending =
+                        blocks_time && attendee.response_status.as_deref() != Some("declined");
+                    let conflict = calendar.busy.iter().any(|period| {
+                        period.start < event.end_time
+                            && period.end > event.start_time
+                            && (!attending
+                                || period.start < event.start_time
+                                || period.end > event.end_time)
+                    });
+                    let busy = if attending {
+                        without_event(&calendar.busy, event)
+                    } else {
+                        calendar.busy.clone()
+                    };
+                    let status = if conflict {
+                        AvailabilityStatus::Busy
+                    } else {
+                        AvailabilityStatus::Free
+                    };
+                    (status, busy)
+                }
+                None => (AvailabilityStatus::Unknown, Vec::new()),
+            };
+            known_busy.extend(busy.iter().cloned());
+            attendees.push(AttendeeAvailability {
+                email: attendee.email.clone(),
+                display_name: attendee.display_name.clone(),
+                response_status: attendee.response_status.clone(),
+                status,
+                busy,
+                error,
+            });
+        }
+
+        let length = event.end_time - event.start_time;
+        let free_for_all =
+            crate::time_blocking::free_slots((window_start, window_end), &known_busy)
+                .into_iter()
+                .filter(|(start, end)| *end - *start >= length)
+                .map(|(start, end)| FreeSlot { start, end })
+                .collect();
+
+        Self {
+            window_start,
+            window_end,
+            attendees,
+            free_for_all,
+        }
+    }
+}
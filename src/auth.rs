@@ -1,4 +1,4 @@
-use crate::config::{get_token_expiry_buffer_seconds, get_token_expiry_seconds, get_token_refresh_threshold_seconds, Config, OAUTH_TOKEN_URL};
+use crate::config::{get_token_expiry_buffer_seconds, get_token_expiry_seconds, get_token_refresh_threshold_seconds, get_oauth_token_url, Config};
 use crate::errors::{GmailApiError, GmailResult};
 use log::{debug, error, info, warn};
 use reqwest::Client;
@@ -321,7 +321,8 @@ impl TokenManager {
         ];
 
         // Log request details for troubleshooting (but hide credentials)
-        debug!("Requesting token from {}", OAUTH_TOKEN_URL);
+        let token_url = get_oauth_token_url();
+        debug!("Requesting token from {}", token_url);
         // Securely log truncated credential information - never log full credentials
         if log::log_enabled!(log::Level::Debug) {
             let client_id_trunc = if self.client_id.len() > 8 {
@@ -349,7 +350,7 @@ impl TokenManager {
 
         // Send token refresh request
         let response = match client
-            .post(OAUTH_TOKEN_URL)
+            .post(&token_url)
             .form(&params)
             .send()
             .await
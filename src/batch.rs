@@ -0,0 +1,156 @@
+use crate::errors::Error;
+use crate::utils::error_data;
+use serde::Serialize;
+use serde_json::Value;
+
+// Batch tool results
+//
+// Tools that act on many items report each item's outcome in `items`, with
+// counts and an overall status alongside, so a failure for one item can't
+// be mistaken for success of the whole batch. Items not attempted before
+// the tool's deadline are reported as skipped, and the report is marked as
+// timed out.
+
+/// Overall outcome of a batch operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchStatus {
+    /// Every item succeeded (including an empty batch)
+    Success,
+    /// Some items succeeded and some failed or were skipped
+    PartialSuccess,
+    /// No item succeeded
+    Failure,
+}
+
+/// Outcome of one item in a batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemStatus {
+    Ok,
+    Error,
+    /// Not attempted because the tool's deadline passed
+    Skipped,
+}
+
+/// Why one item in a batch failed
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchItemError {
+    pub message: String,
+    pub code: u32,
+    pub category: &'static str,
+    pub retryable: bool,
+    pub retry_after: Option<u64>,
+}
+
+/// Result for one item in a batch
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchItem {
+    pub id: String,
+    pub status: ItemStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<BatchItemError>,
+}
+
+/// Per-item results of a batch operation with aggregate counts
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchReport {
+    pub status: BatchStatus,
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    #[serde(skip_serializing_if = "is_zero")]
+    pub skipped: usize,
+    /// Set when the deadline passed before every item was attempted
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub timed_out: bool,
+    pub items: Vec<BatchItem>,
+}
+
+fn is_zero(count: &usize) -> bool {
+    *count == 0
+}
+
+impl Default for BatchReport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BatchReport {
+    pub fn new() -> Self {
+        Self {
+            status: BatchStatus::Success,
+            total: 0,
+            succeeded: 0,
+            failed: 0,
+            skipped: 0,
+            timed_out: false,
+            items: Vec::new(),
+        }
+    }
+
+    /// Record an item that succeeded
+    pub fn push_success(&mut self, id: impl Into<String>, result: Value) {
+        self.succeeded += 1;
+        self.push(BatchItem {
+            id: id.into(),
+            status: ItemStatus::Ok,
+            result: Some(result),
+            error: None,
+        });
+    }
+
+    /// Record an item that failed, classified like MCP error data
+    pub fn push_failure(&mut self, id: impl Into<String>, err: impl Into<Error>) {
+        let err = err.into();
+        let message = err.to_string();
+        let data = error_data(err);
+        self.failed += 1;
+        self.push(BatchItem {
+            id: id.into(),
+            status: ItemStatus::Error,
+            result: None,
+            error: Some(BatchItemError {
+                message,
+                code: data.code,
+                category: data.category,
+                retryable: data.retryable,
+                retry_after: data.retry_after,
+            }),
+        });
+    }
+
+    /// Record an item left out because the tool's deadline passed
+    pub fn push_skipped(&mut self, id: impl Into<String>) {
+        self.skipped += 1;
+        self.timed_out = true;
+        self.push(BatchItem {
+            id: id.into(),
+            status: ItemStatus::Skipped,
+            result: None,
+            error: None,
+        });
+    }
+
+    fn push(&mut self, item: BatchItem) {
+        self.items.push(item);
+        self.total = self.items.len();
+        self.status = match (self.succeeded, self.failed + self.skipped) {
+            (_, 0) => BatchStatus::Success,
+            (0, _) => BatchStatus::Failure,
+            _ => BatchStatus::PartialSuccess,
+        };
+    }
+
+    /// Serialize the report, adding tool-specific fields at the top level
+    pub fn to_json(&self, extra: serde_json::Map<String, Value>) -> Value {
+        let mut value = serde_json::to_value(self).unwrap_or_default();
+        if let Value::Object(map) = &mut value {
+            map.extend(extra);
+        }
+        value
+    }
+}
@@ -0,0 +1,403 @@
+use crate::config::get_max_body_bytes;
+use crate::gmail_api::{decode_body, EmailMessage};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+// Bounce parsing
+//
+// When mail cannot be delivered, the server that gave up sends a delivery
+// status notification (DSN, RFC 3464) back: a `multipart/report` message
+// with `report-type=delivery-status` holding a note for people, a
+// `message/delivery-status` part for programs and usually the headers of the
+// message that bounced. The machine-readable part is a block of fields about
+// the message followed by one block per recipient, giving the action taken
+// (`failed`, `delayed`, ...), an RFC 3463 status code such as `5.1.1` and
+// often the remote server's own reply as a diagnostic code.
+//
+// Read receipts (message disposition notifications, RFC 8098) come back in
+// the same shape with `report-type=disposition-notification`, once the
+// recipient's client agrees to send one for a message that asked for it
+// with a Disposition-Notification-To header. Most clients ask the reader
+// first and many never send them, so a missing receipt means nothing.
+
+/// Gmail query for the senders of delivery status notifications
+pub const BOUNCE_QUERY: &str = "from:(mailer-daemon OR postmaster)";
+
+/// Number of bounce candidates checked when no limit is given
+pub const DEFAULT_BOUNCE_MAX_RESULTS: u32 = 50;
+
+/// What happened to delivery to one recipient
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RecipientStatus {
+    /// Final-Recipient address
+    pub recipient: String,
+    /// Original-Recipient address, when it differs from the final one
+    pub original_recipient: Option<String>,
+    /// `failed`, `delayed`, `delivered`, `relayed` or `expanded`
+    pub action: Option<String>,
+    /// RFC 3463 status code, e.g. `5.1.1`
+    pub status: Option<String>,
+    /// Whether the failure is permanent, so retrying will not help
+    pub permanent: bool,
+    /// What the status code means, e.g. "Mailbox full"
+    pub reason: Option<String>,
+    /// The remote server's reply, e.g. `550 5.1.1 User unknown`
+    pub diagnostic_code: Option<String>,
+    pub remote_mta: Option<String>,
+}
+
+impl RecipientStatus {
+    pub fn failed(&self) -> bool {
+        self.action.as_deref() == Some("failed")
+    }
+}
+
+/// The parsed delivery status notification in a bounce message
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct DeliveryReport {
+    /// Server that wrote the report
+    pub reporting_mta: Option<String>,
+    pub arrival_date: Option<String>,
+    /// Message-ID of the message that bounced, if its headers came back
+    pub original_message_id: Option<String>,
+    /// Subject of the message that bounced, if its headers came back
+    pub original_subject: Option<String>,
+    pub recipients: Vec<RecipientStatus>,
+}
+
+// RFC 3463 meanings of the subject and detail of a status code
+const STATUS_REASONS: &[(&str, &str)] = &[
+    ("1.0", "Bad address"),
+    ("1.1", "Bad destination mailbox address"),
+    ("1.2", "Bad destination system address"),
+    ("1.3", "Bad destination mailbox address syntax"),
+    ("1.6", "Destination mailbox has moved"),
+    ("1.10", "Recipient domain does not accept mail"),
+    ("2.0", "Mailbox problem"),
+    ("2.1", "Mailbox disabled, not accepting messages"),
+    ("2.2", "Mailbox full"),
+    ("2.3", "Message length exceeds administrative limit"),
+    ("3.0", "Mail system problem"),
+    ("3.4", "Message too big for system"),
+    ("4.0", "Network or routing problem"),
+    ("4.1", "No answer from host"),
+    ("4.4", "Unable to route"),
+    ("4.7", "Delivery time expired"),
+    ("5.0", "Mail delivery protocol problem"),
+    ("6.0", "Message content problem"),
+    ("7.0", "Delivery not authorized"),
+    ("7.1", "Delivery not authorized, message refused"),
+];
+
+/// What a status code such as `5.1.1` means, falling back to its class
+pub fn status_reason(status: &str) -> Option<String> {
+    let (class, detail) = status.trim().split_once('.')?;
+    if let Some((_, reason)) = STATUS_REASONS.iter().find(|(code, _)| *code == detail) {
+        return Some(reason.to_string());
+    }
+    match class {
+        "2" => Some("Delivered".to_string()),
+        "4" => Some("Temporary failure".to_string()),
+        "5" => Some("Permanent failure".to_string()),
+        _ => None,
+    }
+}
+
+// Split header-style text into blocks separated by blank lines, each a list
+// of field names and unfolded values
+fn field_blocks(text: &str) -> Vec<Vec<(String, String)>> {
+    let mut blocks = Vec::new();
+    let mut block: Vec<(String, String)> = Vec::new();
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            if !block.is_empty() {
+                blocks.push(std::mem::take(&mut block));
+            }
+        } else if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = block.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            block.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+    if !block.is_empty() {
+        blocks.push(block);
+    }
+    blocks
+}
+
+fn field<'a>(block: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    block
+        .iter()
+        .find(|(field, _)| field == name)
+        .map(|(_, value)| value.as_str())
+        .filter(|value| !value.is_empty())
+}
+
+// Drop the type from a typed field such as `dns; mx.example.com` or
+// `smtp; 550 5.1.1 User unknown`
+fn untyped(value: &str) -> String {
+    let value = value.split_once(';').map_or(value, |(_, value)| value);
+    value.trim().to_string()
+}
+
+// The address in a recipient field such as `rfc822; <bob@example.com>`
+fn address(value: &str) -> String {
+    untyped(value)
+        .trim_matches(|c| c == '<' || c == '>')
+        .to_string()
+}
+
+impl DeliveryReport {
+    /// Parse the text of a `message/delivery-status` part. `None` when it
+    /// reports on no recipients.
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut blocks = field_blocks(text).into_iter();
+        let message_fields = blocks.next()?;
+        let recipients: Vec<RecipientStatus> = blocks
+            .filter_map(|block| {
+                let recipient = address(field(&block, "final-recipient")?);
+                let original_recipient = field(&block, "original-recipient")
+                    .map(address)
+                    .filter(|original| !original.eq_ignore_ascii_case(&recipient));
+                let status = field(&block, "status")
+                    .and_then(|status| status.split_whitespace().next())
+                    .map(String::from);
+                Some(RecipientStatus {
+                    recipient,
+                    original_recipient,
+                    action: field(&block, "action").map(|action| action.to_lowercase()),
+                    permanent: status.as_deref().is_some_and(|s| s.starts_with('5')),
+                    reason: status.as_deref().and_then(status_reason),
+                    status,
+                    diagnostic_code: field(&block, "diagnostic-code").map(untyped),
+                    remote_mta: field(&block, "remote-mta").map(untyped),
+                })
+            })
+            .collect();
+        if recipients.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            reporting_mta: field(&message_fields, "reporting-mta").map(untyped),
+            arrival_date: field(&message_fields, "arrival-date").map(String::from),
+            original_message_id: None,
+            original_subject: None,
+            recipients,
+        })
+    }
+
+    /// Find and parse the delivery status notification in a message
+    /// payload, if it is a bounce
+    pub fn from_payload(payload: &Value) -> Option<Self> {
+        let report = find_report(payload, "delivery-status")?;
+        let parts = report["parts"].as_array()?;
+        let mut delivery_report = parts
+            .iter()
+            .filter(|part| {
+                matches!(
+                    part["mimeType"].as_str(),
+                    Some("message/delivery-status" | "message/global-delivery-status")
+                )
+            })
+            .find_map(|part| part_text(part).as_deref().and_then(Self::parse))?;
+
+        // The headers of the message that bounced, when they were returned
+        if let Some(headers) = original_headers(parts) {
+            delivery_report.original_message_id = field(&headers, "message-id").map(String::from);
+            delivery_report.original_subject = field(&headers, "subject").map(String::from);
+        }
+
+        Some(delivery_report)
+    }
+
+    /// Recipients delivery to whom failed
+    pub fn failed_recipients(&self) -> Vec<&str> {
+        self.recipients
+            .iter()
+            .filter(|recipient| recipient.failed())
+            .map(|recipient| recipient.recipient.as_str())
+            .collect()
+    }
+}
+
+// The `multipart/report` part holding a report of the given type, e.g.
+// `delivery-status`
+fn find_report<'a>(part: &'a Value, report_type: &str) -> Option<&'a Value> {
+    let children = part["parts"].as_array()?;
+    let is_report = part["mimeType"].as_str() == Some("multipart/report")
+        && children.iter().any(|child| {
+            child["mimeType"]
+                .as_str()
+                .is_some_and(|mime_type| mime_type.ends_with(report_type))
+        });
+    if is_report {
+        return Some(part);
+    }
+    children
+        .iter()
+        .find_map(|child| find_report(child, report_type))
+}
+
+// The headers of the original message returned with a report, lowercased
+fn original_headers(parts: &[Value]) -> Option<Vec<(String, String)>> {
+    parts
+        .iter()
+        .find_map(|part| match part["mimeType"].as_str() {
+            Some("text/rfc822-headers") => part_text(part)
+                .map(|text| field_blocks(&text).into_iter().next().unwrap_or_default()),
+            Some("message/rfc822" | "message/global") => {
+                let headers = part["parts"][0]["headers"]
+                    .as_array()
+                    .or_else(|| part["headers"].as_array())?;
+                Some(
+                    headers
+                        .iter()
+                        .filter_map(|header| {
+                            Some((
+                                header["name"].as_str()?.to_lowercase(),
+                                header["value"].as_str()?.to_string(),
+                            ))
+                        })
+                        .collect(),
+                )
+            }
+            _ => None,
+        })
+}
+
+/// A read receipt: what a recipient's mail client did with a message that
+/// asked for one
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ReadReceipt {
+    /// Final-Recipient address of whoever the receipt is from
+    pub recipient: Option<String>,
+    /// `displayed` when the message was read; otherwise e.g. `deleted`
+    pub disposition: String,
+    /// Whether the message was displayed to the recipient
+    pub read: bool,
+    /// Whether the client sent the receipt without asking the recipient
+    pub sent_automatically: bool,
+    /// Mail client that sent the receipt
+    pub reporting_ua: Option<String>,
+    /// Message-ID of the message the receipt is for
+    pub original_message_id: Option<String>,
+    /// Subject of the message the receipt is for, if its headers came back
+    pub original_subject: Option<String>,
+}
+
+impl ReadReceipt {
+    /// Parse the text of a `message/disposition-notification` part
+    pub fn parse(text: &str) -> Option<Self> {
+        let fields: Vec<(String, String)> = field_blocks(text).into_iter().flatten().collect();
+        // e.g. `manual-action/MDN-sent-manually; displayed`
+        let (mode, disposition) = field(&fields, "disposition")?
+            .split_once(';')
+            .map(|(mode, disposition)| (mode.trim(), disposition.trim()))?;
+        let disposition = disposition
+            .split(['/', ' '])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        if disposition.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            recipient: field(&fields, "final-recipient").map(address),
+            read: disposition == "displayed",
+            disposition,
+            sent_automatically: mode.to_lowercase().ends_with("mdn-sent-automatically"),
+            reporting_ua: field(&fields, "reporting-ua").map(untyped),
+            original_message_id: field(&fields, "original-message-id").map(String::from),
+            original_subject: None,
+        })
+    }
+
+    /// Find and parse the read receipt in a message payload, if it is one
+    pub fn from_payload(payload: &Value) -> Option<Self> {
+        let report = find_report(payload, "disposition-notification")?;
+        let parts = report["parts"].as_array()?;
+        let mut receipt = parts
+            .iter()
+            .filter(|part| {
+                matches!(
+                    part["mimeType"].as_str(),
+                    Some(
+                        "message/disposition-notification"
+                            | "message/global-disposition-notification"
+                    )
+                )
+            })
+            .find_map(|part| part_text(part).as_deref().and_then(Self::parse))?;
+
+        if let Some(headers) = original_headers(parts) {
+            if receipt.original_message_id.is_none() {
+                receipt.original_message_id = field(&headers, "message-id").map(String::from);
+            }
+            receipt.original_subject = field(&headers, "subject").map(String::from);
+        }
+
+        Some(receipt)
+    }
+}
+
+fn part_text(part: &Value) -> Option<String> {
+    decode_body(part["body"]["data"].as_str()?, get_max_body_bytes())
+}
+
+/// One bounce message and its report
+#[derive(Serialize, Debug, Clone)]
+pub struct Bounce {
+    pub message_id: String,
+    pub thread_id: String,
+    pub date: Option<String>,
+    #[serde(flatten)]
+    pub report: DeliveryReport,
+}
+
+/// Bounces received over a period
+#[derive(Serialize, Debug, Clone)]
+pub struct BounceList {
+    /// Gmail query the candidates were listed with
+    pub query: String,
+    pub messages_checked: usize,
+    /// Every recipient that failed, lowercased and sorted
+    pub failed_recipients: Vec<String>,
+    /// Bounces, newest first
+    pub bounces: Vec<Bounce>,
+}
+
+impl BounceList {
+    /// Collect the bounces among listed messages; others are skipped
+    pub fn from_messages(query: &str, messages: Vec<EmailMessage>) -> Self {
+        let messages_checked = messages.len();
+        let bounces: Vec<Bounce> = messages
+            .into_iter()
+            .filter_map(|message| {
+                Some(Bounce {
+                    report: message.delivery_report?,
+                    message_id: message.id,
+                    thread_id: message.thread_id,
+                    date: message.date,
+                })
+            })
+            .collect();
+        let failed_recipients: BTreeSet<String> = bounces
+            .iter()
+            .flat_map(|bounce| bounce.report.failed_recipients())
+            .map(str::to_lowercase)
+            .collect();
+
+        Self {
+            query: query.to_string(),
+            messages_checked,
+            failed_recipients: failed_recipients.into_iter().collect(),
+            bounces,
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use crate::calendar_api::{BusyPeriod, CalendarEvent};
+use chrono::Duration;
+
+// Buffer events
+//
+// Travel or commute time around a meeting is kept as buffer events just
+// before and after it. Buffers leave the time free, so others can still book
+// it when they must, but slot finding can be told to keep clear of them.
+// They are recognised by their category, kept in the event's private
+// extended properties like the categories event rules give.
+
+/// Category of buffer events
+pub const BUFFER_CATEGORY: &str = "Buffer";
+
+/// Longest buffer on either side of a meeting, in minutes
+pub const MAX_BUFFER_MINUTES: u32 = 240;
+
+/// Most events read when looking for buffers in a day
+pub const BUFFER_MAX_EVENTS: u32 = 250;
+
+/// Which side of a meeting a buffer is on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferSide {
+    Before,
+    After,
+}
+
+impl BufferSide {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Before => "before",
+            Self::After => "after",
+        }
+    }
+}
+
+/// Check the buffer lengths asked for: at least one side, neither longer
+/// than MAX_BUFFER_MINUTES
+pub fn validate_buffer_minutes(before: u32, after: u32) -> Result<(), String> {
+    if before == 0 && after == 0 {
+        return Err("Give minutes_before, minutes_after or both".to_string());
+    }
+    if before.max(after) > MAX_BUFFER_MINUTES {
+        return Err(format!(
+            "Buffers are at most {} minutes on each side",
+            MAX_BUFFER_MINUTES
+        ));
+    }
+    Ok(())
+}
+
+/// The buffer event on one side of a meeting. Buffers have no reminders and
+/// leave the time free.
+pub fn buffer_event(meeting: &CalendarEvent, side: BufferSide, minutes: u32) -> CalendarEvent {
+    let length = Duration::minutes(i64::from(minutes));
+    let (summary, start_time, end_time) = match side {
+        BufferSide::Before => (
+            format!("Buffer before {}", meeting.summary),
+            meeting.start_time - length,
+            meeting.start_time,
+        ),
+        BufferSide::After => (
+            format!("Buffer after {}", meeting.summary),
+            meeting.end_time,
+            meeting.end_time + length,
+        ),
+    };
+    let description = match &meeting.id {
+        Some(id) => format!("Travel and commute time for event {}", id),
+        None => "Travel and commute time".to_string(),
+    };
+    CalendarEvent {
+        summary,
+        description: Some(description),
+        start_time,
+        end_time,
+        transparency: Some("transparent".to_string()),
+        time_zone: meeting.time_zone.clone(),
+        category: Some(BUFFER_CATEGORY.to_string()),
+        reminders: Some(Vec::new()),
+        ..Default::default()
+    }
+}
+
+/// The buffer events around a meeting, leaving out a side of no minutes
+pub fn buffer_events(
+    meeting: &CalendarEvent,
+    minutes_before: u32,
+    minutes_after: u32,
+) -> Vec<(BufferSide, CalendarEvent)> {
+    [
+        (BufferSide::Before, minutes_before),
+        (BufferSide::After, minutes_after),
+    ]
+    .into_iter()
+    .filter(|(_, minutes)| *minutes > 0)
+    .map(|(side, minutes)| (side, buffer_event(meeting, side, minutes)))
+    .collect()
+}
+
+/// Whether an event is a buffer
+pub fn is_buffer(event: &CalendarEvent) -> bool {
+    event.category.as_deref() == Some(BUFFER_CATEGORY)
+}
+
+/// The times taken by buffer events, which free/busy leaves out since
+/// buffers keep the time free
+pub fn buffer_busy(events: &[CalendarEvent]) -> Vec<BusyPeriod> {
+    events
+        .iter()
+        .filter(|event| is_buffer(event))
+        .map(|event| BusyPeriod {
+            start: event.start_time,
+            end: event.end_time,
+        })
+        .collect()
+}
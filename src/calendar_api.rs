@@ -1,22 +1,36 @@
 use crate::auth::TokenManager;
-use crate::config::Config;
-use chrono::{DateTime, Utc};
+use crate::config::{get_calendar_api_base_url, Config};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
 use log::{debug, error};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use uuid::Uuid;
 
-const CALENDAR_API_BASE_URL: &str = "https://www.googleapis.com/calendar/v3";
 
-use crate::errors::{CalendarApiError, CalendarResult};
+use crate::errors::{retry_after_header, CalendarApiError, CalendarResult, RateLimitInfo};
+use crate::etag_cache::EtagCache;
+use crate::utils::api_client_builder;
 
 // Alias for backward compatibility within this module
 type Result<T> = CalendarResult<T>;
 
+/// Private extended property holding an event's category
+pub const CATEGORY_PROPERTY: &str = "category";
+
+/// Private extended property tagging the events this server creates
+pub const CREATED_BY_PROPERTY: &str = "createdBy";
+
+/// Value of CREATED_BY_PROPERTY on the events this server creates
+pub const CREATED_BY_VALUE: &str = "mcp-gmailcal";
+
+/// Private extended property linking an event to the email it came from
+pub const SOURCE_MESSAGE_PROPERTY: &str = "sourceMessageId";
+
 // Calendar event representation
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CalendarEvent {
     pub id: Option<String>,
     pub summary: String,
@@ -26,9 +40,131 @@ pub struct CalendarEvent {
     pub end_time: DateTime<Utc>,
     pub attendees: Vec<Attendee>,
     pub conference_data: Option<ConferenceData>,
+    /// Links to join the event's video call, from its conference data or
+    /// found in its description and location
+    #[serde(default)]
+    pub join_links: Vec<JoinLink>,
     pub html_link: Option<String>,
     pub creator: Option<EventOrganizer>,
     pub organizer: Option<EventOrganizer>,
+    /// Files attached to the event, such as an agenda in Google Drive
+    #[serde(default)]
+    pub attachments: Vec<EventAttachment>,
+    /// RRULE, EXRULE, RDATE and EXDATE lines of a recurring event
+    #[serde(default)]
+    pub recurrence: Vec<String>,
+    /// "transparent" when the event leaves the time free, "opaque" or unset
+    /// when it blocks it
+    #[serde(default)]
+    pub transparency: Option<String>,
+    /// IANA time zone of the event's start, which a recurring event repeats
+    /// in. Events are created in UTC when unset.
+    #[serde(default)]
+    pub time_zone: Option<String>,
+    /// Whether the event takes whole days. An all-day event runs from
+    /// midnight UTC on its first day to midnight UTC after its last.
+    #[serde(default)]
+    pub all_day: bool,
+    /// Where the user works, if this is a working location event
+    #[serde(default)]
+    pub working_location: Option<WorkingLocation>,
+    /// Kind of event, such as "default", "birthday", "focusTime" or
+    /// "workingLocation"
+    #[serde(default)]
+    pub event_type: Option<String>,
+    /// Color of the event, an ID from "1" to "11" in the Calendar palette, or
+    /// unset for the calendar's color
+    #[serde(default)]
+    pub color_id: Option<String>,
+    /// Category given to the event by event rules, kept in its private
+    /// extended properties
+    #[serde(default)]
+    pub category: Option<String>,
+    /// Reminders for the event, or unset for the calendar's default
+    /// reminders. Empty for no reminders.
+    #[serde(default)]
+    pub reminders: Option<Vec<EventReminder>>,
+    /// Private extended properties other than the category, such as
+    /// CREATED_BY_PROPERTY. Only this calendar's copy of the event has them.
+    #[serde(default)]
+    pub private_properties: HashMap<String, String>,
+    /// Whether guests can change the event. Unset on a new event for
+    /// Calendar's default of false.
+    #[serde(default)]
+    pub guests_can_modify: Option<bool>,
+    /// Whether guests can invite others. Unset on a new event for Calendar's
+    /// default of true.
+    #[serde(default)]
+    pub guests_can_invite_others: Option<bool>,
+    /// Whether guests can see who else is invited. Unset on a new event for
+    /// Calendar's default of true.
+    #[serde(default)]
+    pub guests_can_see_other_guests: Option<bool>,
+}
+
+impl CalendarEvent {
+    /// The event's start as sent to the Calendar API
+    pub fn start(&self) -> EventDateTime {
+        EventDateTime::new(self.start_time, self.all_day, self.time_zone.clone())
+    }
+
+    /// The event's end as sent to the Calendar API
+    pub fn end(&self) -> EventDateTime {
+        EventDateTime::new(self.end_time, self.all_day, self.time_zone.clone())
+    }
+}
+
+/// The start or end of an event in the Calendar API: a date for an all-day
+/// event, a date and time otherwise
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventDateTime {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date_time: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_zone: Option<String>,
+}
+
+impl EventDateTime {
+    /// A boundary at `time`, or on its UTC date for an all-day event, in
+    /// `time_zone` or UTC when unset
+    pub fn new(time: DateTime<Utc>, all_day: bool, time_zone: Option<String>) -> Self {
+        Self {
+            date: all_day.then(|| time.date_naive()),
+            date_time: (!all_day).then_some(time),
+            time_zone: Some(time_zone.unwrap_or_else(|| "UTC".to_string())),
+        }
+    }
+
+    pub fn is_all_day(&self) -> bool {
+        self.date_time.is_none() && self.date.is_some()
+    }
+
+    /// The boundary as a time, midnight UTC on its date for an all-day event
+    pub fn to_utc(&self) -> Option<DateTime<Utc>> {
+        self.date_time
+            .or_else(|| Some(self.date?.and_time(NaiveTime::MIN).and_utc()))
+    }
+}
+
+/// A reminder before an event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventReminder {
+    /// "popup" or "email"
+    pub method: String,
+    /// Minutes before the event's start
+    pub minutes: i64,
+}
+
+/// Where the user works for the time of a working location event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkingLocation {
+    /// "homeOffice", "officeLocation" or "customLocation"
+    pub location_type: String,
+    /// Name of the office or other place, if given
+    pub label: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,6 +180,22 @@ pub struct Attendee {
     pub display_name: Option<String>,
     pub response_status: Option<String>,
     pub optional: Option<bool>,
+    /// IANA time zone of the attendee's calendar, when it is shared with the
+    /// user
+    #[serde(default)]
+    pub time_zone: Option<String>,
+    /// Whether this attendee is the owner of the calendar the event was read
+    /// from, which on the primary calendar is the user
+    #[serde(default)]
+    pub self_: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventAttachment {
+    pub title: Option<String>,
+    pub file_url: String,
+    pub file_id: Option<String>,
+    pub mime_type: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -65,6 +217,48 @@ pub struct EntryPoint {
     pub label: Option<String>,
 }
 
+/// A link to join an event's video call
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JoinLink {
+    /// "google_meet", "zoom", "teams", "webex", or the conference
+    /// solution's name for other video entry points
+    pub provider: String,
+    pub url: String,
+    /// Where the link was found: "conference_data", "description" or
+    /// "location"
+    pub source: String,
+}
+
+/// A time when a calendar is busy, from a free/busy query
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BusyPeriod {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+/// The busy times of one calendar in a free/busy query, or why they could
+/// not be read
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalendarBusy {
+    pub calendar_id: String,
+    pub busy: Vec<BusyPeriod>,
+    pub error: Option<String>,
+}
+
+/// A push notification channel watching a calendar's events. Calendar posts
+/// to the channel's address whenever one of them changes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotificationChannel {
+    pub id: String,
+    /// ID of the watched resource, needed with the channel ID to stop it
+    pub resource_id: String,
+    pub resource_uri: Option<String>,
+    /// Token sent back with every notification
+    pub token: Option<String>,
+    /// When Calendar stops sending notifications
+    pub expiration: Option<DateTime<Utc>>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CalendarList {
     pub calendars: Vec<CalendarInfo>,
@@ -79,6 +273,23 @@ pub struct CalendarInfo {
     pub primary: Option<bool>,
 }
 
+/// The user's Calendar settings
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CalendarSettings {
+    /// IANA time zone of the primary calendar
+    pub time_zone: Option<String>,
+    /// Day the week starts on, such as "monday"
+    pub week_start: Option<String>,
+    /// Length of new events in minutes
+    pub default_event_length: Option<i64>,
+    pub format_24_hour_time: Option<bool>,
+    pub date_field_order: Option<String>,
+    pub locale: Option<String>,
+    pub hide_weekends: Option<bool>,
+    /// Every setting by ID, as the API gives it
+    pub all: std::collections::BTreeMap<String, String>,
+}
+
 // Calendar API client
 #[derive(Debug, Clone)]
 pub struct CalendarClient {
@@ -88,7 +299,9 @@ pub struct CalendarClient {
 
 impl CalendarClient {
     pub fn new(config: &Config) -> Self {
-        let client = Client::new();
+        let client = api_client_builder()
+            .build()
+            .unwrap_or_else(|_| Client::new());
         // Reuse the Gmail token manager since they share the same OAuth scope
         let token_manager = Arc::new(Mutex::new(TokenManager::new(config)));
 
@@ -108,63 +321,40 @@ impl CalendarClient {
             .await
             .map_err(|e| CalendarApiError::AuthError(e.to_string()))?;
 
-        let url = format!("{}/users/me/calendarList", CALENDAR_API_BASE_URL);
+        let url = format!("{}/users/me/calendarList", get_calendar_api_base_url());
         debug!("Listing calendars from: {}", url);
 
-        let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", token))
-            .send()
+        let response = EtagCache::global()
+            .send(
+                self.client
+                    .get(&url)
+                    .header("Authorization", format!("Bearer {}", token)),
+            )
             .await
             .map_err(|e| CalendarApiError::NetworkError(e.to_string()))?;
 
-        let status = response.status();
-        if !status.is_success() {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "<no response body>".to_string());
+        if !response.status.is_success() {
+            if let Some(info) = RateLimitInfo::from_response(
+                response.status.as_u16(),
+                response.retry_after.as_deref(),
+                &response.body,
+            ) {
+                return Err(CalendarApiError::RateLimited(info));
+            }
             return Err(CalendarApiError::ApiError(format!(
                 "Failed to list calendars. Status: {}, Error: {}",
-                status, error_text
+                response.status, response.body
             )));
         }
 
-        let json_response = response
-            .json::<serde_json::Value>()
-            .await
+        let json_response = serde_json::from_str::<serde_json::Value>(&response.body)
             .map_err(|e| CalendarApiError::ParseError(e.to_string()))?;
 
         let mut calendars = Vec::new();
 
         if let Some(items) = json_response.get("items").and_then(|v| v.as_array()) {
             for item in items {
-                let id = item
-                    .get("id")
-                    .and_then(|v| v.as_str())
-                    .ok_or_else(|| CalendarApiError::ParseError("Missing calendar id".to_string()))?
-                    .to_string();
-
-                let summary = item
-                    .get("summary")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("Unknown Calendar")
-                    .to_string();
-
-                let description = item
-                    .get("description")
-                    .and_then(|v| v.as_str())
-                    .map(|s| s.to_string());
-
-                let primary = item.get("primary").and_then(|v| v.as_bool());
-
-                calendars.push(CalendarInfo {
-                    id,
-                    summary,
-                    description,
-                    primary,
-                });
+                calendars.push(parse_calendar_info(item)?);
             }
         }
 
@@ -179,6 +369,99 @@ impl CalendarClient {
         })
     }
 
+    // Get the user's Calendar settings, such as their time zone and the
+    // length of new events
+    pub async fn get_settings(&self) -> Result<CalendarSettings> {
+        let token = self
+            .token_manager
+            .lock()
+            .await
+            .get_token(&self.client)
+            .await
+            .map_err(|e| CalendarApiError::AuthError(e.to_string()))?;
+
+        let url = format!("{}/users/me/settings", get_calendar_api_base_url());
+        debug!("Getting calendar settings from: {}", url);
+
+        let response = EtagCache::global()
+            .send(
+                self.client
+                    .get(&url)
+                    .header("Authorization", format!("Bearer {}", token)),
+            )
+            .await
+            .map_err(|e| CalendarApiError::NetworkError(e.to_string()))?;
+
+        if !response.status.is_success() {
+            if let Some(info) = RateLimitInfo::from_response(
+                response.status.as_u16(),
+                response.retry_after.as_deref(),
+                &response.body,
+            ) {
+                return Err(CalendarApiError::RateLimited(info));
+            }
+            return Err(CalendarApiError::ApiError(format!(
+                "Failed to get calendar settings. Status: {}, Error: {}",
+                response.status, response.body
+            )));
+        }
+
+        let json_response = serde_json::from_str::<serde_json::Value>(&response.body)
+            .map_err(|e| CalendarApiError::ParseError(e.to_string()))?;
+
+        Ok(parse_calendar_settings(&json_response))
+    }
+
+    // Add a calendar, such as a public holiday calendar, to the user's
+    // calendar list
+    pub async fn subscribe_calendar(&self, calendar_id: &str) -> Result<CalendarInfo> {
+        let token = self
+            .token_manager
+            .lock()
+            .await
+            .get_token(&self.client)
+            .await
+            .map_err(|e| CalendarApiError::AuthError(e.to_string()))?;
+
+        let url = format!("{}/users/me/calendarList", get_calendar_api_base_url());
+        debug!("Subscribing to calendar {}", calendar_id);
+
+        let response = self
+            .client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&serde_json::json!({ "id": calendar_id }))
+            .send()
+            .await
+            .map_err(|e| CalendarApiError::NetworkError(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let retry_after = retry_after_header(response.headers());
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "<no response body>".to_string());
+            if let Some(info) =
+                RateLimitInfo::from_response(status.as_u16(), retry_after.as_deref(), &error_text)
+            {
+                return Err(CalendarApiError::RateLimited(info));
+            }
+            return Err(CalendarApiError::ApiError(format!(
+                "Failed to subscribe to calendar {}. Status: {}, Error: {}",
+                calendar_id, status, error_text
+            )));
+        }
+
+        let json_response = response
+            .json::<serde_json::Value>()
+            .await
+            .map_err(|e| CalendarApiError::ParseError(e.to_string()))?;
+
+        parse_calendar_info(&json_response)
+    }
+
     // Get events from a specific calendar
     pub async fn list_events(
         &self,
@@ -187,16 +470,20 @@ impl CalendarClient {
         time_min: Option<DateTime<Utc>>,
         time_max: Option<DateTime<Utc>>,
     ) -> Result<Vec<CalendarEvent>> {
-        let token = self
-            .token_manager
-            .lock()
-            .await
-            .get_token(&self.client)
+        self.list_events_with_properties(calendar_id, max_results, time_min, time_max, &[])
             .await
-            .map_err(|e| CalendarApiError::AuthError(e.to_string()))?;
-
-        let mut url = format!("{}/calendars/{}/events", CALENDAR_API_BASE_URL, calendar_id);
+    }
 
+    // List events as list_events does, keeping only those with all of the
+    // given private extended properties
+    pub async fn list_events_with_properties(
+        &self,
+        calendar_id: &str,
+        max_results: Option<u32>,
+        time_min: Option<DateTime<Utc>>,
+        time_max: Option<DateTime<Utc>>,
+        private_properties: &[(String, String)],
+    ) -> Result<Vec<CalendarEvent>> {
         // Build query parameters
         let mut query_parts = Vec::new();
 
@@ -214,12 +501,58 @@ impl CalendarClient {
             query_parts.push(format!("timeMax={}", encoded_time));
         }
 
+        for (key, value) in private_properties {
+            let property = urlencoding::encode(&format!("{}={}", key, value)).into_owned();
+            query_parts.push(format!("privateExtendedProperty={}", property));
+        }
+
         // Add single events mode to expand recurring events
         query_parts.push("singleEvents=true".to_string());
 
         // Order by start time
         query_parts.push("orderBy=startTime".to_string());
 
+        self.fetch_events(calendar_id, query_parts).await
+    }
+
+    // Get the recurring events of a calendar, each listed once with its
+    // recurrence rules rather than expanded into instances
+    pub async fn list_recurring_events(
+        &self,
+        calendar_id: &str,
+        max_results: Option<u32>,
+    ) -> Result<Vec<CalendarEvent>> {
+        let mut query_parts = Vec::new();
+
+        if let Some(max) = max_results {
+            query_parts.push(format!("maxResults={}", max));
+        }
+
+        query_parts.push("singleEvents=false".to_string());
+
+        let events = self.fetch_events(calendar_id, query_parts).await?;
+        Ok(events
+            .into_iter()
+            .filter(|event| !event.recurrence.is_empty())
+            .collect())
+    }
+
+    // Request events from a calendar with the given query parameters
+    async fn fetch_events(
+        &self,
+        calendar_id: &str,
+        query_parts: Vec<String>,
+    ) -> Result<Vec<CalendarEvent>> {
+        let token = self
+            .token_manager
+            .lock()
+            .await
+            .get_token(&self.client)
+            .await
+            .map_err(|e| CalendarApiError::AuthError(e.to_string()))?;
+
+        let mut url = format!("{}/events", calendar_url(calendar_id));
+
         if !query_parts.is_empty() {
             url = format!("{}?{}", url, query_parts.join("&"));
         }
@@ -236,10 +569,16 @@ impl CalendarClient {
 
         let status = response.status();
         if !status.is_success() {
+            let retry_after = retry_after_header(response.headers());
             let error_text = response
                 .text()
                 .await
                 .unwrap_or_else(|_| "<no response body>".to_string());
+            if let Some(info) =
+                RateLimitInfo::from_response(status.as_u16(), retry_after.as_deref(), &error_text)
+            {
+                return Err(CalendarApiError::RateLimited(info));
+            }
             return Err(CalendarApiError::ApiError(format!(
                 "Failed to list events. Status: {}, Error: {}",
                 status, error_text
@@ -281,10 +620,11 @@ impl CalendarClient {
             .await
             .map_err(|e| CalendarApiError::AuthError(e.to_string()))?;
 
-        let url = format!("{}/calendars/{}/events", CALENDAR_API_BASE_URL, calendar_id);
+        let url = format!("{}/events", calendar_url(calendar_id));
         debug!("Creating new event in calendar {}", calendar_id);
 
         // Convert our CalendarEvent to Google Calendar API format
+        let (start, end) = (event.start(), event.end());
         let mut event_data = serde_json::Map::new();
         event_data.insert(
             "summary".to_string(),
@@ -299,29 +639,105 @@ impl CalendarClient {
             event_data.insert("location".to_string(), serde_json::Value::String(loc));
         }
 
-        // Add start time
-        let mut start = serde_json::Map::new();
-        start.insert(
-            "dateTime".to_string(),
-            serde_json::Value::String(event.start_time.to_rfc3339()),
-        );
-        start.insert(
-            "timeZone".to_string(),
-            serde_json::Value::String("UTC".to_string()),
-        );
-        event_data.insert("start".to_string(), serde_json::Value::Object(start));
+        // Add start and end times, as dates for an all-day event
+        for (name, boundary) in [("start", start), ("end", end)] {
+            event_data.insert(name.to_string(), serde_json::json!(boundary));
+        }
 
-        // Add end time
-        let mut end = serde_json::Map::new();
-        end.insert(
-            "dateTime".to_string(),
-            serde_json::Value::String(event.end_time.to_rfc3339()),
+        // Add recurrence rules and transparency if set
+        if !event.recurrence.is_empty() {
+            let recurrence = event
+                .recurrence
+                .into_iter()
+                .map(serde_json::Value::String)
+                .collect();
+            event_data.insert(
+                "recurrence".to_string(),
+                serde_json::Value::Array(recurrence),
+            );
+        }
+
+        if let Some(transparency) = event.transparency {
+            event_data.insert(
+                "transparency".to_string(),
+                serde_json::Value::String(transparency),
+            );
+        }
+
+        if let Some(event_type) = event.event_type {
+            event_data.insert(
+                "eventType".to_string(),
+                serde_json::Value::String(event_type),
+            );
+        }
+
+        if let Some(color_id) = event.color_id {
+            event_data.insert("colorId".to_string(), serde_json::Value::String(color_id));
+        }
+
+        // Keep the category and other private properties, and tag the event
+        // as created by this server
+        let mut private = serde_json::Map::new();
+        for (key, value) in event.private_properties {
+            private.insert(key, serde_json::Value::String(value));
+        }
+        if let Some(category) = event.category {
+            private.insert(
+                CATEGORY_PROPERTY.to_string(),
+                serde_json::Value::String(category),
+            );
+        }
+        private.insert(
+            CREATED_BY_PROPERTY.to_string(),
+            serde_json::Value::String(CREATED_BY_VALUE.to_string()),
         );
-        end.insert(
-            "timeZone".to_string(),
-            serde_json::Value::String("UTC".to_string()),
+        event_data.insert(
+            "extendedProperties".to_string(),
+            serde_json::json!({ "private": private }),
         );
-        event_data.insert("end".to_string(), serde_json::Value::Object(end));
+
+        // Guest permissions are left to Calendar's defaults unless set
+        for (name, permission) in [
+            ("guestsCanModify", event.guests_can_modify),
+            ("guestsCanInviteOthers", event.guests_can_invite_others),
+            ("guestsCanSeeOtherGuests", event.guests_can_see_other_guests),
+        ] {
+            if let Some(permission) = permission {
+                event_data.insert(name.to_string(), serde_json::Value::Bool(permission));
+            }
+        }
+
+        if let Some(reminders) = event.reminders {
+            event_data.insert(
+                "reminders".to_string(),
+                serde_json::json!({ "useDefault": false, "overrides": reminders }),
+            );
+        }
+
+        // Working location events must be public
+        if let Some(working_location) = event.working_location {
+            let mut place = serde_json::Map::new();
+            if let Some(label) = working_location.label {
+                place.insert("label".to_string(), serde_json::Value::String(label));
+            }
+            let mut properties = serde_json::Map::new();
+            properties.insert(
+                "type".to_string(),
+                serde_json::Value::String(working_location.location_type.clone()),
+            );
+            properties.insert(
+                working_location.location_type,
+                serde_json::Value::Object(place),
+            );
+            event_data.insert(
+                "visibility".to_string(),
+                serde_json::Value::String("public".to_string()),
+            );
+            event_data.insert(
+                "workingLocationProperties".to_string(),
+                serde_json::Value::Object(properties),
+            );
+        }
 
         // Add attendees if any
         if !event.attendees.is_empty() {
@@ -383,10 +799,16 @@ impl CalendarClient {
 
         let status = response.status();
         if !status.is_success() {
+            let retry_after = retry_after_header(response.headers());
             let error_text = response
                 .text()
                 .await
                 .unwrap_or_else(|_| "<no response body>".to_string());
+            if let Some(info) =
+                RateLimitInfo::from_response(status.as_u16(), retry_after.as_deref(), &error_text)
+            {
+                return Err(CalendarApiError::RateLimited(info));
+            }
             return Err(CalendarApiError::ApiError(format!(
                 "Failed to create event. Status: {}, Error: {}",
                 status, error_text
@@ -403,6 +825,17 @@ impl CalendarClient {
 
     // Get a specific event
     pub async fn get_event(&self, calendar_id: &str, event_id: &str) -> Result<CalendarEvent> {
+        let json_response = self.get_event_json(calendar_id, event_id).await?;
+        self.parse_event(&json_response)
+    }
+
+    // Get a specific event as the API returns it, with fields CalendarEvent
+    // does not keep, such as its etag
+    pub async fn get_event_json(
+        &self,
+        calendar_id: &str,
+        event_id: &str,
+    ) -> Result<serde_json::Value> {
         let token = self
             .token_manager
             .lock()
@@ -411,10 +844,7 @@ impl CalendarClient {
             .await
             .map_err(|e| CalendarApiError::AuthError(e.to_string()))?;
 
-        let url = format!(
-            "{}/calendars/{}/events/{}",
-            CALENDAR_API_BASE_URL, calendar_id, event_id
-        );
+        let url = format!("{}/events/{}", calendar_url(calendar_id), event_id);
         debug!("Getting event {} from calendar {}", event_id, calendar_id);
 
         let response = self
@@ -427,16 +857,93 @@ impl CalendarClient {
 
         let status = response.status();
         if !status.is_success() {
+            let retry_after = retry_after_header(response.headers());
             let error_text = response
                 .text()
                 .await
                 .unwrap_or_else(|_| "<no response body>".to_string());
+            if let Some(info) =
+                RateLimitInfo::from_response(status.as_u16(), retry_after.as_deref(), &error_text)
+            {
+                return Err(CalendarApiError::RateLimited(info));
+            }
             return Err(CalendarApiError::ApiError(format!(
                 "Failed to get event. Status: {}, Error: {}",
                 status, error_text
             )));
         }
 
+        response
+            .json::<serde_json::Value>()
+            .await
+            .map_err(|e| CalendarApiError::ParseError(e.to_string()))
+    }
+
+    // Change some fields of an event, leaving the others as they are. With an
+    // etag the change is only made if the event has not changed since it was
+    // read. `send_updates` is "all", "externalOnly" or "none", for who among
+    // the attendees is notified.
+    pub async fn patch_event(
+        &self,
+        calendar_id: &str,
+        event_id: &str,
+        patch: &serde_json::Value,
+        etag: Option<&str>,
+        send_updates: &str,
+    ) -> Result<CalendarEvent> {
+        let token = self
+            .token_manager
+            .lock()
+            .await
+            .get_token(&self.client)
+            .await
+            .map_err(|e| CalendarApiError::AuthError(e.to_string()))?;
+
+        let url = format!(
+            "{}/events/{}?sendUpdates={}",
+            calendar_url(calendar_id),
+            event_id,
+            urlencoding::encode(send_updates)
+        );
+        debug!("Patching event {} in calendar {}", event_id, calendar_id);
+
+        let mut request = self
+            .client
+            .patch(&url)
+            .header("Authorization", format!("Bearer {}", token))
+            .json(patch);
+        if let Some(etag) = etag {
+            request = request.header("If-Match", etag);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| CalendarApiError::NetworkError(e.to_string()))?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::PRECONDITION_FAILED {
+            return Err(CalendarApiError::ApiError(format!(
+                "Event {} changed while it was being updated; try again",
+                event_id
+            )));
+        }
+        if !status.is_success() {
+            let retry_after = retry_after_header(response.headers());
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "<no response body>".to_string());
+            if let Some(info) =
+                RateLimitInfo::from_response(status.as_u16(), retry_after.as_deref(), &error_text)
+            {
+                return Err(CalendarApiError::RateLimited(info));
+            }
+            return Err(CalendarApiError::ApiError(format!(
+                "Failed to update event. Status: {}, Error: {}",
+                status, error_text
+            )));
+        }
+
         let json_response = response
             .json::<serde_json::Value>()
             .await
@@ -445,6 +952,330 @@ impl CalendarClient {
         self.parse_event(&json_response)
     }
 
+    // Get the times a calendar is busy between two times. Events that leave
+    // the time free are not counted.
+    pub async fn free_busy(
+        &self,
+        calendar_id: &str,
+        time_min: DateTime<Utc>,
+        time_max: DateTime<Utc>,
+    ) -> Result<Vec<BusyPeriod>> {
+        let calendars = self
+            .free_busy_calendars(&[calendar_id.to_string()], time_min, time_max)
+            .await?;
+        match calendars.into_iter().next() {
+            Some(CalendarBusy {
+                error: Some(reason),
+                ..
+            }) => Err(CalendarApiError::ApiError(format!(
+                "Free/busy unavailable for calendar {}: {}",
+                calendar_id, reason
+            ))),
+            Some(calendar) => Ok(calendar.busy),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    // Get the times several calendars are busy between two times, in one
+    // query. An attendee's email address is the ID of their primary calendar;
+    // one that is not shared with the user comes back with an error rather
+    // than failing the query.
+    pub async fn free_busy_calendars(
+        &self,
+        calendar_ids: &[String],
+        time_min: DateTime<Utc>,
+        time_max: DateTime<Utc>,
+    ) -> Result<Vec<CalendarBusy>> {
+        let token = self
+            .token_manager
+            .lock()
+            .await
+            .get_token(&self.client)
+            .await
+            .map_err(|e| CalendarApiError::AuthError(e.to_string()))?;
+
+        let url = format!("{}/freeBusy", get_calendar_api_base_url());
+        debug!("Querying free/busy for calendars {:?}", calendar_ids);
+
+        let items: Vec<_> = calendar_ids
+            .iter()
+            .map(|id| serde_json::json!({ "id": id }))
+            .collect();
+        let request = serde_json::json!({
+            "timeMin": time_min.to_rfc3339(),
+            "timeMax": time_max.to_rfc3339(),
+            "items": items,
+        });
+        let response = self
+            .client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| CalendarApiError::NetworkError(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let retry_after = retry_after_header(response.headers());
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "<no response body>".to_string());
+            if let Some(info) =
+                RateLimitInfo::from_response(status.as_u16(), retry_after.as_deref(), &error_text)
+            {
+                return Err(CalendarApiError::RateLimited(info));
+            }
+            return Err(CalendarApiError::ApiError(format!(
+                "Failed to query free/busy. Status: {}, Error: {}",
+                status, error_text
+            )));
+        }
+
+        let json_response = response
+            .json::<serde_json::Value>()
+            .await
+            .map_err(|e| CalendarApiError::ParseError(e.to_string()))?;
+
+        let mut calendars = Vec::new();
+        for calendar_id in calendar_ids {
+            let calendar = json_response
+                .get("calendars")
+                .and_then(|v| v.get(calendar_id));
+
+            // A calendar that could not be read is reported with its errors
+            let error = calendar
+                .and_then(|v| v.get("errors"))
+                .and_then(|v| v.as_array())
+                .and_then(|errors| errors.first())
+                .and_then(|error| error.get("reason"))
+                .and_then(|v| v.as_str())
+                .map(str::to_string);
+
+            let mut busy = Vec::new();
+            if let Some(periods) = calendar
+                .and_then(|v| v.get("busy"))
+                .and_then(|v| v.as_array())
+            {
+                for period in periods {
+                    let time = |name: &str| {
+                        period
+                            .get(name)
+                            .and_then(|v| v.as_str())
+                            .and_then(|v| DateTime::parse_from_rfc3339(v).ok())
+                            .map(|v| v.with_timezone(&Utc))
+                            .ok_or_else(|| {
+                                CalendarApiError::ParseError(format!("Invalid busy {}", name))
+                            })
+                    };
+                    busy.push(BusyPeriod {
+                        start: time("start")?,
+                        end: time("end")?,
+                    });
+                }
+            }
+            calendars.push(CalendarBusy {
+                calendar_id: calendar_id.clone(),
+                busy,
+                error,
+            });
+        }
+
+        Ok(calendars)
+    }
+
+    // Get the time zone of a calendar. An attendee's email address is the ID
+    // of their primary calendar, readable only if they share it with the user.
+    pub async fn get_time_zone(&self, calendar_id: &str) -> Result<String> {
+        let token = self
+            .token_manager
+            .lock()
+            .await
+            .get_token(&self.client)
+            .await
+            .map_err(|e| CalendarApiError::AuthError(e.to_string()))?;
+
+        let url = calendar_url(calendar_id);
+        debug!("Getting time zone of calendar {}", calendar_id);
+
+        let response = self
+            .client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .await
+            .map_err(|e| CalendarApiError::NetworkError(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let retry_after = retry_after_header(response.headers());
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "<no response body>".to_string());
+            if let Some(info) =
+                RateLimitInfo::from_response(status.as_u16(), retry_after.as_deref(), &error_text)
+            {
+                return Err(CalendarApiError::RateLimited(info));
+            }
+            return Err(CalendarApiError::ApiError(format!(
+                "Failed to get calendar. Status: {}, Error: {}",
+                status, error_text
+            )));
+        }
+
+        let json_response = response
+            .json::<serde_json::Value>()
+            .await
+            .map_err(|e| CalendarApiError::ParseError(e.to_string()))?;
+
+        json_response
+            .get("timeZone")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+            .ok_or_else(|| CalendarApiError::ParseError("Calendar has no time zone".to_string()))
+    }
+
+    // Open a push notification channel posting to `address` whenever an
+    // event on the calendar changes. The channel lasts `ttl_seconds`, or
+    // Calendar's default of a week when unset.
+    pub async fn watch_events(
+        &self,
+        calendar_id: &str,
+        address: &str,
+        channel_token: Option<&str>,
+        ttl_seconds: Option<u64>,
+    ) -> Result<NotificationChannel> {
+        let token = self
+            .token_manager
+            .lock()
+            .await
+            .get_token(&self.client)
+            .await
+            .map_err(|e| CalendarApiError::AuthError(e.to_string()))?;
+
+        let url = format!("{}/events/watch", calendar_url(calendar_id));
+        debug!("Watching events of calendar {}", calendar_id);
+
+        let mut request = serde_json::json!({
+            "id": Uuid::new_v4().to_string(),
+            "type": "web_hook",
+            "address": address,
+        });
+        if let Some(channel_token) = channel_token {
+            request["token"] = serde_json::json!(channel_token);
+        }
+        if let Some(ttl_seconds) = ttl_seconds {
+            request["params"] = serde_json::json!({ "ttl": ttl_seconds.to_string() });
+        }
+        let response = self
+            .client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| CalendarApiError::NetworkError(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let retry_after = retry_after_header(response.headers());
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "<no response body>".to_string());
+            if let Some(info) =
+                RateLimitInfo::from_response(status.as_u16(), retry_after.as_deref(), &error_text)
+            {
+                return Err(CalendarApiError::RateLimited(info));
+            }
+            return Err(CalendarApiError::ApiError(format!(
+                "Failed to watch calendar. Status: {}, Error: {}",
+                status, error_text
+            )));
+        }
+
+        let json_response = response
+            .json::<serde_json::Value>()
+            .await
+            .map_err(|e| CalendarApiError::ParseError(e.to_string()))?;
+
+        let field = |name: &str| {
+            json_response
+                .get(name)
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+        };
+        let id = field("id")
+            .ok_or_else(|| CalendarApiError::ParseError("Channel has no ID".to_string()))?;
+        let resource_id = field("resourceId").ok_or_else(|| {
+            CalendarApiError::ParseError("Channel has no resource ID".to_string())
+        })?;
+        // The expiration is given in milliseconds since the epoch
+        let expiration = field("expiration")
+            .and_then(|ms| ms.parse::<i64>().ok())
+            .and_then(DateTime::from_timestamp_millis);
+        Ok(NotificationChannel {
+            id,
+            resource_id,
+            resource_uri: field("resourceUri"),
+            token: field("token"),
+            expiration,
+        })
+    }
+
+    // Stop a push notification channel, so Calendar no longer posts to its
+    // address
+    pub async fn stop_channel(&self, channel_id: &str, resource_id: &str) -> Result<()> {
+        let token = self
+            .token_manager
+            .lock()
+            .await
+            .get_token(&self.client)
+            .await
+            .map_err(|e| CalendarApiError::AuthError(e.to_string()))?;
+
+        let url = format!("{}/channels/stop", get_calendar_api_base_url());
+        debug!("Stopping notification channel {}", channel_id);
+
+        let request = serde_json::json!({
+            "id": channel_id,
+            "resourceId": resource_id,
+        });
+        let response = self
+            .client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| CalendarApiError::NetworkError(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let retry_after = retry_after_header(response.headers());
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "<no response body>".to_string());
+            if let Some(info) =
+                RateLimitInfo::from_response(status.as_u16(), retry_after.as_deref(), &error_text)
+            {
+                return Err(CalendarApiError::RateLimited(info));
+            }
+            return Err(CalendarApiError::ApiError(format!(
+                "Failed to stop channel. Status: {}, Error: {}",
+                status, error_text
+            )));
+        }
+
+        Ok(())
+    }
+
     // Helper to parse Google Calendar event format into our CalendarEvent struct
     fn parse_event(&self, item: &serde_json::Value) -> Result<CalendarEvent> {
         let id = item
@@ -468,28 +1299,17 @@ impl CalendarClient {
             .and_then(|v| v.as_str())
             .map(|s| s.to_string());
 
-        // Parse datetime structures
-        let start_time = item
-            .get("start")
-            .and_then(|v| v.get("dateTime"))
-            .and_then(|v| v.as_str())
+        // Parse datetime structures, or the dates of an all-day event
+        let start = parse_event_boundary(item, "start")?;
+        let end = parse_event_boundary(item, "end")?;
+        let all_day = start.is_all_day();
+        let start_dt = start
+            .to_utc()
             .ok_or_else(|| CalendarApiError::ParseError("Missing start time".to_string()))?;
-
-        let end_time = item
-            .get("end")
-            .and_then(|v| v.get("dateTime"))
-            .and_then(|v| v.as_str())
+        let end_dt = end
+            .to_utc()
             .ok_or_else(|| CalendarApiError::ParseError("Missing end time".to_string()))?;
 
-        // Parse RFC3339 format to DateTime<Utc>
-        let start_dt = DateTime::parse_from_rfc3339(start_time)
-            .map_err(|e| CalendarApiError::ParseError(format!("Invalid start time: {}", e)))?
-            .with_timezone(&Utc);
-
-        let end_dt = DateTime::parse_from_rfc3339(end_time)
-            .map_err(|e| CalendarApiError::ParseError(format!("Invalid end time: {}", e)))?
-            .with_timezone(&Utc);
-
         // Parse attendees
         let mut attendees = Vec::new();
         if let Some(attendee_list) = item.get("attendees").and_then(|v| v.as_array()) {
@@ -513,12 +1333,16 @@ impl CalendarClient {
                     .map(|s| s.to_string());
 
                 let optional = attendee.get("optional").and_then(|v| v.as_bool());
+                let self_ = attendee.get("self").and_then(|v| v.as_bool());
 
                 attendees.push(Attendee {
                     email,
                     display_name,
                     response_status,
                     optional,
+                    // Not part of the event; see timezones::add_attendee_time_zones
+                    time_zone: None,
+                    self_,
                 });
             }
         }
@@ -570,6 +1394,125 @@ impl CalendarClient {
             None
         };
 
+        let join_links = crate::join_links::find_join_links(
+            conference_data.as_ref(),
+            description.as_deref(),
+            location.as_deref(),
+        );
+
+        // Parse attachments
+        let attachments = item
+            .get("attachments")
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|attachment| {
+                let text = |name: &str| {
+                    attachment
+                        .get(name)
+                        .and_then(|v| v.as_str())
+                        .map(|s| s.to_string())
+                };
+                Some(EventAttachment {
+                    title: text("title"),
+                    file_url: text("fileUrl")?,
+                    file_id: text("fileId"),
+                    mime_type: text("mimeType"),
+                })
+            })
+            .collect();
+
+        // Parse recurrence rules, transparency and the time zone of the start
+        let recurrence = item
+            .get("recurrence")
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|rule| rule.as_str().map(|s| s.to_string()))
+            .collect();
+
+        let transparency = item
+            .get("transparency")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+
+        let time_zone = start.time_zone;
+
+        let event_type = item
+            .get("eventType")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+
+        let color_id = item
+            .get("colorId")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+
+        let private = item
+            .get("extendedProperties")
+            .and_then(|v| v.get("private"))
+            .and_then(|v| v.as_object());
+        let category = private
+            .and_then(|v| v.get(CATEGORY_PROPERTY))
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+        let private_properties = private
+            .into_iter()
+            .flatten()
+            .filter(|(key, _)| *key != CATEGORY_PROPERTY)
+            .filter_map(|(key, value)| Some((key.clone(), value.as_str()?.to_string())))
+            .collect();
+
+        // Calendar leaves out guest permissions that have their default value
+        let guests_can_modify = Some(
+            item.get("guestsCanModify")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+        );
+        let guests_can_invite_others = Some(
+            item.get("guestsCanInviteOthers")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(true),
+        );
+        let guests_can_see_other_guests = Some(
+            item.get("guestsCanSeeOtherGuests")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(true),
+        );
+
+        // Reminders only differ from the calendar's default when useDefault
+        // is false
+        let reminders = item
+            .get("reminders")
+            .filter(|v| v.get("useDefault").and_then(|v| v.as_bool()) == Some(false))
+            .map(|v| {
+                v.get("overrides")
+                    .and_then(|v| v.as_array())
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|reminder| {
+                        Some(EventReminder {
+                            method: reminder.get("method")?.as_str()?.to_string(),
+                            minutes: reminder.get("minutes")?.as_i64()?,
+                        })
+                    })
+                    .collect()
+            });
+
+        // Parse where the user works, for a working location event
+        let working_location = item.get("workingLocationProperties").and_then(|props| {
+            let location_type = props.get("type").and_then(|v| v.as_str())?;
+            let place = props.get(location_type);
+            let label = place
+                .and_then(|p| p.get("label").or_else(|| p.get("buildingId")))
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string());
+            Some(WorkingLocation {
+                location_type: location_type.to_string(),
+                label,
+            })
+        });
+
         // Parse html link
         let html_link = item
             .get("htmlLink")
@@ -613,9 +1556,113 @@ impl CalendarClient {
             end_time: end_dt,
             attendees,
             conference_data,
+            join_links,
             html_link,
             creator,
             organizer,
+            attachments,
+            recurrence,
+            transparency,
+            time_zone,
+            all_day,
+            working_location,
+            event_type,
+            color_id,
+            category,
+            reminders,
+            private_properties,
+            guests_can_modify,
+            guests_can_invite_others,
+            guests_can_see_other_guests,
+        })
+    }
+}
+
+// URL of a calendar. IDs such as those of holiday calendars contain `#`, so
+// they are percent-encoded.
+fn calendar_url(calendar_id: &str) -> String {
+    format!(
+        "{}/calendars/{}",
+        get_calendar_api_base_url(),
+        urlencoding::encode(calendar_id)
+    )
+}
+
+// Parse a calendar list entry into our CalendarInfo struct
+fn parse_calendar_info(item: &serde_json::Value) -> Result<CalendarInfo> {
+    let id = item
+        .get("id")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| CalendarApiError::ParseError("Missing calendar id".to_string()))?
+        .to_string();
+
+    let summary = item
+        .get("summary")
+        .and_then(|v| v.as_str())
+        .unwrap_or("Unknown Calendar")
+        .to_string();
+
+    let description = item
+        .get("description")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+
+    let primary = item.get("primary").and_then(|v| v.as_bool());
+
+    Ok(CalendarInfo {
+        id,
+        summary,
+        description,
+        primary,
+    })
+}
+
+// Settings come as a list of IDs with string values
+fn parse_calendar_settings(json: &serde_json::Value) -> CalendarSettings {
+    let all: std::collections::BTreeMap<String, String> = json
+        .get("items")
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| {
+                    let id = item.get("id")?.as_str()?;
+                    let value = item.get("value")?.as_str()?;
+                    Some((id.to_string(), value.to_string()))
+                })
+                .collect()
         })
+        .unwrap_or_default();
+    let setting = |id: &str| all.get(id).map(|value| value.trim());
+    let flag = |id: &str| setting(id).and_then(|value| value.parse::<bool>().ok());
+
+    // Weeks start on Sunday ("0"), Monday ("1") or Saturday ("6")
+    let week_start = setting("weekStart").and_then(|value| match value {
+        "0" => Some("sunday"),
+        "1" => Some("monday"),
+        "6" => Some("saturday"),
+        _ => None,
+    });
+
+    CalendarSettings {
+        time_zone: setting("timezone").map(str::to_string),
+        week_start: week_start.map(str::to_string),
+        default_event_length: setting("defaultEventLength")
+            .and_then(|value| value.parse::<i64>().ok())
+            .filter(|minutes| *minutes > 0),
+        format_24_hour_time: flag("format24HourTime"),
+        date_field_order: setting("dateFieldOrder").map(str::to_string),
+        locale: setting("locale").map(str::to_string),
+        hide_weekends: flag("hideWeekends"),
+        all: all.clone(),
     }
 }
+
+// An event's start or end
+fn parse_event_boundary(item: &serde_json::Value, name: &str) -> Result<EventDateTime> {
+    let boundary = item
+        .get(name)
+        .ok_or_else(|| CalendarApiError::ParseError(format!("Missing {} time", name)))?;
+    EventDateTime::deserialize(boundary)
+        .map_err(|e| CalendarApiError::ParseError(format!("Invalid {} time: {}", name, e)))
+}
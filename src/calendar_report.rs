@@ -0,0 +1,320 @@
+use crate::calendar_api::CalendarEvent;
+use crate::time_blocking::{DEFAULT_WORK_END_HOUR, DEFAULT_WORK_START_HOUR};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::Serialize;
+use std::collections::HashMap;
+
+// Calendar report
+//
+// Like Google Calendar's Time Insights, the report shows where the time in a
+// period went: hours in meetings each day and week, meetings that run
+// straight into each other, who the user meets with most, and how much of
+// the working week is meetings or focus time. A meeting is an event with at
+// least one other guest that the user hasn't declined and that blocks the
+// time. Days and weeks are in the user's time zone, weeks starting Monday,
+// and working time is 9:00 to 17:00 on weekdays.
+
+/// Period covered when none is given
+pub const DEFAULT_CALENDAR_REPORT_RANGE: &str = "30d";
+
+/// Longest period a report covers, in days
+pub const CALENDAR_REPORT_MAX_DAYS: i64 = 366;
+
+/// Most events read for a report
+pub const CALENDAR_REPORT_MAX_EVENTS: u32 = 2500;
+
+/// Number of co-attendees listed
+pub const TOP_CO_ATTENDEES: usize = 10;
+
+/// A meeting starting at most this many minutes after the one before ends
+/// follows it back to back
+pub const BACK_TO_BACK_GAP_MINUTES: i64 = 5;
+
+/// Turn a period such as "7d", "2w", "3m" or "1y" (days, weeks, months of 30
+/// days or years of 365) into its length
+pub fn parse_report_range(range: &str) -> Result<Duration, String> {
+    let compact = range.trim().to_lowercase().replace(' ', "");
+    let split = compact
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(compact.len());
+    let (amount, unit) = compact.split_at(split);
+    let invalid = || {
+        format!(
+            "Invalid time range '{}': expected a number of days, weeks, months or years, \
+             such as 7d, 2w, 3m or 1y",
+            range.trim()
+        )
+    };
+    let amount: i64 = amount
+        .parse()
+        .ok()
+        .filter(|amount| *amount > 0)
+        .ok_or_else(invalid)?;
+    let unit_days = match unit {
+        "d" | "day" | "days" => 1,
+        "w" | "week" | "weeks" => 7,
+        "m" | "month" | "months" => 30,
+        "y" | "year" | "years" => 365,
+        _ => return Err(invalid()),
+    };
+    match amount.checked_mul(unit_days) {
+        Some(days) if days <= CALENDAR_REPORT_MAX_DAYS => Ok(Duration::days(days)),
+        _ => Err(format!(
+            "Time range '{}' is too long: at most {} days are covered",
+            range.trim(),
+            CALENDAR_REPORT_MAX_DAYS
+        )),
+    }
+}
+
+/// Meeting time on one day
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DayUsage {
+    pub date: NaiveDate,
+    pub meetings: usize,
+    pub meeting_minutes: i64,
+}
+
+/// Meeting time in one week
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WeekUsage {
+    /// The Monday the week starts on
+    pub week_start: NaiveDate,
+    pub meetings: usize,
+    pub meeting_minutes: i64,
+    pub meeting_hours: f64,
+}
+
+/// Someone the user meets with
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CoAttendee {
+    pub email: String,
+    pub name: Option<String>,
+    pub meetings: usize,
+    pub minutes: i64,
+}
+
+/// Where the time in a period went
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CalendarReport {
+    pub range_start: DateTime<Utc>,
+    pub range_end: DateTime<Utc>,
+    pub time_zone: String,
+    pub meetings: usize,
+    pub meeting_minutes: i64,
+    pub meeting_hours: f64,
+    pub average_meeting_minutes: i64,
+    /// Meetings starting right as the one before ends
+    pub back_to_back: usize,
+    /// Minutes of focus time events
+    pub focus_minutes: i64,
+    /// Minutes of working hours in the period
+    pub working_minutes: i64,
+    /// Percentage of working time in meetings
+    pub meeting_share: f64,
+    /// Percentage of working time blocked as focus time
+    pub focus_share: f64,
+    /// Days with meetings, in order
+    pub by_day: Vec<DayUsage>,
+    /// Every week of the period, in order
+    pub by_week: Vec<WeekUsage>,
+    pub top_co_attendees: Vec<CoAttendee>,
+}
+
+fn hours(minutes: i64) -> f64 {
+    (minutes as f64 / 6.0).round() / 10.0
+}
+
+fn percent(part: i64, whole: i64) -> f64 {
+    if whole <= 0 {
+        0.0
+    } else {
+        (part as f64 * 1000.0 / whole as f64).round() / 10.0
+    }
+}
+
+// Whether the event keeps the time busy for the user
+fn blocks_time(event: &CalendarEvent, user_email: &str) -> bool {
+    !event.all_day
+        && event.transparency.as_deref() != Some("transparent")
+        && event.working_location.is_none()
+        && !event.attendees.iter().any(|attendee| {
+            attendee.email.eq_ignore_ascii_case(user_email)
+                && attendee.response_status.as_deref() == Some("declined")
+        })
+}
+
+// Guests of an event other than the user and rooms or other resources
+fn other_guests<'a>(
+    event: &'a CalendarEvent,
+    user_email: &'a str,
+) -> impl Iterator<Item = &'a crate::calendar_api::Attendee> {
+    event.attendees.iter().filter(move |attendee| {
+        !attendee.email.eq_ignore_ascii_case(user_email)
+            && !attendee.email.ends_with("resource.calendar.google.com")
+    })
+}
+
+/// Minutes of working hours, 9:00 to 17:00 on weekdays, between two times
+pub fn working_minutes(start: DateTime<Utc>, end: DateTime<Utc>, time_zone: Tz) -> i64 {
+    let work_start = NaiveTime::from_hms_opt(DEFAULT_WORK_START_HOUR, 0, 0).unwrap_or_default();
+    let work_end = NaiveTime::from_hms_opt(DEFAULT_WORK_END_HOUR, 0, 0).unwrap_or_default();
+    let last = end.with_timezone(&time_zone).date_naive();
+    let mut date = start.with_timezone(&time_zone).date_naive();
+    let mut minutes = 0;
+    while date <= last {
+        if !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+            let day = [work_start, work_end].map(|time| {
+                crate::utils::local_to_utc(date.and_time(time), time_zone, &time.to_string())
+            });
+            if let [Ok(day_start), Ok(day_end)] = day {
+                let from = day_start.max(start);
+                let to = day_end.min(end);
+                if to > from {
+                    minutes += (to - from).num_minutes();
+                }
+            }
+        }
+        date += Duration::days(1);
+    }
+    minutes
+}
+
+impl CalendarReport {
+    /// Report on the events from `start` to `end` for the user, counting the
+    /// part of each event inside the period
+    pub fn new(
+        (start, end): (DateTime<Utc>, DateTime<Utc>),
+        events: &[CalendarEvent],
+        user_email: &str,
+        time_zone: Tz,
+    ) -> Self {
+        let clipped = |event: &CalendarEvent| {
+            let from = event.start_time.max(start);
+            let to = event.end_time.min(end);
+            (to > from).then_some((from, to))
+        };
+        let mut meetings: Vec<(&CalendarEvent, DateTime<Utc>, DateTime<Utc>)> = events
+            .iter()
+            .filter(|event| blocks_time(event, user_email))
+            .filter(|event| event.event_type.as_deref() != Some("focusTime"))
+            .filter(|event| other_guests(event, user_email).next().is_some())
+            .filter_map(|event| clipped(event).map(|(from, to)| (event, from, to)))
+            .collect();
+        meetings.sort_by_key(|(_, from, _)| *from);
+
+        let focus_minutes = events
+            .iter()
+            .filter(|event| blocks_time(event, user_email))
+            .filter(|event| event.event_type.as_deref() == Some("focusTime"))
+            .filter_map(clipped)
+            .map(|(from, to)| (to - from).num_minutes())
+            .sum();
+
+        let local_date = |time: DateTime<Utc>| time.with_timezone(&time_zone).date_naive();
+        let monday = |date: NaiveDate| date.week(Weekday::Mon).first_day();
+        let mut by_day: Vec<DayUsage> = Vec::new();
+        let mut by_week: Vec<WeekUsage> = Vec::new();
+        // The period's last moment, so one ending at midnight doesn't add a day
+        let last_date = local_date(end - Duration::seconds(1));
+        let mut week = monday(local_date(start));
+        while week <= last_date {
+            by_week.push(WeekUsage {
+                week_start: week,
+                meetings: 0,
+                meeting_minutes: 0,
+                meeting_hours: 0.0,
+            });
+            week += Duration::weeks(1);
+        }
+
+        let mut co_attendees: HashMap<String, CoAttendee> = HashMap::new();
+        let mut back_to_back = 0;
+        let mut meeting_minutes = 0;
+        let mut previous_end: Option<DateTime<Utc>> = None;
+        for (event, from, to) in &meetings {
+            let minutes = (*to - *from).num_minutes();
+            meeting_minutes += minutes;
+
+            let date = local_date(*from);
+            match by_day.iter_mut().find(|day| day.date == date) {
+                Some(day) => {
+                    day.meetings += 1;
+                    day.meeting_minutes += minutes;
+                }
+                None => by_day.push(DayUsage {
+                    date,
+                    meetings: 1,
+                    meeting_minutes: minutes,
+                }),
+            }
+            if let Some(week) = by_week
+                .iter_mut()
+                .find(|week| week.week_start == monday(date))
+            {
+                week.meetings += 1;
+                week.meeting_minutes += minutes;
+            }
+
+            if let Some(previous_end) = previous_end {
+                let gap = (*from - previous_end).num_minutes();
+                if (0..=BACK_TO_BACK_GAP_MINUTES).contains(&gap) {
+                    back_to_back += 1;
+                }
+            }
+            previous_end = Some(previous_end.map_or(*to, |end| end.max(*to)));
+
+            for guest in other_guests(event, user_email) {
+                let entry = co_attendees
+                    .entry(guest.email.to_lowercase())
+                    .or_insert_with(|| CoAttendee {
+                        email: guest.email.to_lowercase(),
+                        name: None,
+                        meetings: 0,
+                        minutes: 0,
+                    });
+                entry.meetings += 1;
+                entry.minutes += minutes;
+                if entry.name.is_none() {
+                    entry.name = guest.display_name.clone();
+                }
+            }
+        }
+        for week in &mut by_week {
+            week.meeting_hours = hours(week.meeting_minutes);
+        }
+
+        let mut top_co_attendees: Vec<CoAttendee> = co_attendees.into_values().collect();
+        top_co_attendees.sort_by(|a, b| {
+            b.meetings
+                .cmp(&a.meetings)
+                .then(b.minutes.cmp(&a.minutes))
+                .then_with(|| a.email.cmp(&b.email))
+        });
+        top_co_attendees.truncate(TOP_CO_ATTENDEES);
+
+        let working_minutes = working_minutes(start, end, time_zone);
+        Self {
+            range_start: start,
+            range_end: end,
+            time_zone: time_zone.name().to_string(),
+            meetings: meetings.len(),
+            meeting_minutes,
+            meeting_hours: hours(meeting_minutes),
+            average_meeting_minutes: if meetings.is_empty() {
+                0
+            } else {
+                meeting_minutes / meetings.len() as i64
+            },
+            back_to_back,
+            focus_minutes,
+            working_minutes,
+            meeting_share: percent(meeting_minutes, working_minutes),
+            focus_share: percent(focus_minutes, working_minutes),
+            by_day,
+            by_week,
+            top_co_attendees,
+        }
+    }
+}
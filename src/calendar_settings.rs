@@ -0,0 +1,75 @@
+use crate::calendar_api::CalendarSettings;
+use chrono::Duration;
+use chrono_tz::Tz;
+use serde::Serialize;
+
+// Calendar settings
+//
+// The user's Calendar settings say which time zone their calendar is shown
+// in, which day their week starts on and how long new events last. Calendar
+// tools take their defaults from them: dates in words and agenda days are
+// read in the calendar's time zone, and an event created without an end
+// lasts the default length. USER_TIMEZONE, when set, still takes precedence
+// over the calendar's time zone. Settings that cannot be read leave UTC,
+// weeks starting on Monday and hour-long events.
+
+/// Length of new events when the settings give none
+pub const DEFAULT_EVENT_LENGTH_MINUTES: i64 = 60;
+
+/// Day weeks start on when the settings give none
+pub const DEFAULT_WEEK_START: &str = "monday";
+
+/// The defaults calendar tools use
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CalendarDefaults {
+    #[serde(serialize_with = "serialize_time_zone")]
+    pub time_zone: Tz,
+    /// Where the time zone comes from: "USER_TIMEZONE", "calendar" or "default"
+    pub time_zone_source: String,
+    pub week_start: String,
+    pub default_event_length_minutes: i64,
+}
+
+fn serialize_time_zone<S: serde::Serializer>(
+    time_zone: &Tz,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(time_zone.name())
+}
+
+impl CalendarDefaults {
+    /// The defaults from the user's settings, if they could be read, and the
+    /// time zone configured in USER_TIMEZONE, if any
+    pub fn new(settings: Option<&CalendarSettings>, configured_time_zone: Option<Tz>) -> Self {
+        let calendar_time_zone = settings
+            .and_then(|settings| settings.time_zone.as_deref())
+            .and_then(|name| name.parse::<Tz>().ok());
+        let (time_zone, time_zone_source) = match (configured_time_zone, calendar_time_zone) {
+            (Some(time_zone), _) => (time_zone, "USER_TIMEZONE"),
+            (None, Some(time_zone)) => (time_zone, "calendar"),
+            (None, None) => (chrono_tz::UTC, "default"),
+        };
+        Self {
+            time_zone,
+            time_zone_source: time_zone_source.to_string(),
+            week_start: settings
+                .and_then(|settings| settings.week_start.clone())
+                .unwrap_or_else(|| DEFAULT_WEEK_START.to_string()),
+            default_event_length_minutes: settings
+                .and_then(|settings| settings.default_event_length)
+                .unwrap_or(DEFAULT_EVENT_LENGTH_MINUTES),
+        }
+    }
+
+    /// How long an event created without an end lasts
+    pub fn default_event_length(&self) -> Duration {
+        Duration::minutes(self.default_event_length_minutes)
+    }
+}
+
+/// The user's settings with the defaults taken from them
+#[derive(Debug, Clone, Serialize)]
+pub struct CalendarSettingsReport {
+    pub settings: CalendarSettings,
+    pub defaults: CalendarDefaults,
+}
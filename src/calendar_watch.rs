@@ -0,0 +1,38 @@
+// Calendar push notifications
+//
+// A notification channel asks Calendar to post to a webhook whenever an
+// event on a calendar changes, so an outside automation can react to the
+// events managed through this server. Notifications only say that something
+// changed; the receiver lists the events again to see what. Calendar only
+// posts to HTTPS addresses, and a channel stops on its own when it expires,
+// so it must be opened again to keep watching.
+
+/// Longest token Calendar sends back with each notification, in characters
+pub const MAX_CHANNEL_TOKEN_LENGTH: usize = 256;
+
+/// Check the address notifications are posted to
+pub fn validate_webhook_url(webhook_url: &str) -> Result<(), String> {
+    let parsed = url::Url::parse(webhook_url)
+        .map_err(|e| format!("Invalid webhook_url '{}': {}", webhook_url, e))?;
+    if parsed.scheme() != "https" {
+        return Err(format!(
+            "webhook_url must be an HTTPS address, got '{}'",
+            webhook_url
+        ));
+    }
+    if parsed.host_str().is_none() {
+        return Err(format!("webhook_url '{}' has no host", webhook_url));
+    }
+    Ok(())
+}
+
+/// Check the token sent back with each notification
+pub fn validate_channel_token(token: &str) -> Result<(), String> {
+    if token.chars().count() > MAX_CHANNEL_TOKEN_LENGTH {
+        return Err(format!(
+            "token is longer than {} characters",
+            MAX_CHANNEL_TOKEN_LENGTH
+        ));
+    }
+    Ok(())
+}
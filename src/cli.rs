@@ -1,4 +1,8 @@
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+/// Most days ahead the `cal` commands read, about ten years
+pub const CAL_MAX_DAYS: i64 = 3660;
 
 #[derive(Parser, Debug, PartialEq)]
 #[clap(name = "Gmail MCP Server")]
@@ -12,6 +16,28 @@ pub struct Cli {
     /// Force use of stderr-only logging (no file logging)
     #[clap(long, short, action)]
     pub memory_only: bool,
+
+    /// Check configuration, credentials and each Google API when the server starts, logging a readiness summary
+    #[clap(long, action)]
+    pub preflight: bool,
+
+    /// Answer API requests from local JSON fixtures instead of Google (no credentials or network needed)
+    #[clap(long, action, global = true)]
+    pub mock: bool,
+
+    /// Directory of fixture files for --mock, overriding the built-in fixtures
+    #[clap(long, value_name = "DIR", global = true, requires = "mock")]
+    pub mock_fixtures: Option<PathBuf>,
+
+    /// Record real API responses as sanitized fixtures in DIR, for replay with --mock-fixtures
+    #[cfg(feature = "record")]
+    #[clap(long, value_name = "DIR", global = true, conflicts_with = "mock")]
+    pub record: Option<PathBuf>,
+
+    /// Text to replace with REDACTED in recorded fixtures, e.g. your email address (repeatable)
+    #[cfg(feature = "record")]
+    #[clap(long, value_name = "TEXT", global = true, requires = "record")]
+    pub record_redact: Vec<String>,
 }
 
 #[derive(Subcommand, Debug, PartialEq)]
@@ -22,9 +48,165 @@ pub enum Commands {
 
     /// Run the OAuth authentication flow to get new credentials
     #[clap(name = "auth")]
-    Auth,
+    Auth {
+        /// Extra OAuth scopes to request along with the default ones, comma-separated
+        #[clap(long, value_name = "SCOPE", value_delimiter = ',')]
+        scopes: Vec<String>,
+    },
 
     /// Test the current credentials
     #[clap(name = "test")]
     Test,
+
+    /// Diagnose configuration, credentials, scopes, network and clock problems
+    #[clap(name = "doctor")]
+    Doctor,
+
+    /// Start an interactive prompt for invoking MCP tools with JSON arguments
+    #[clap(name = "repl")]
+    Repl,
+
+    /// Work with Gmail directly from the command line
+    #[clap(name = "mail")]
+    Mail {
+        #[clap(subcommand)]
+        command: MailCommands,
+    },
+
+    /// Work with Google Calendar directly from the command line
+    #[clap(name = "cal")]
+    Cal {
+        #[clap(subcommand)]
+        command: CalCommands,
+    },
+
+    /// Work with Google Contacts directly from the command line
+    #[clap(name = "contacts")]
+    Contacts {
+        #[clap(subcommand)]
+        command: ContactsCommands,
+    },
+}
+
+#[derive(Subcommand, Debug, PartialEq)]
+pub enum MailCommands {
+    /// List recent emails
+    #[clap(name = "list")]
+    List {
+        /// Maximum number of emails to return
+        #[clap(long, short = 'n', default_value_t = 10)]
+        max_results: u32,
+
+        /// Optional Gmail search query to filter the list
+        #[clap(long, short)]
+        query: Option<String>,
+    },
+
+    /// Search emails using Gmail search syntax
+    #[clap(name = "search")]
+    Search {
+        /// Gmail search query (e.g. "is:unread from:example.com")
+        query: String,
+
+        /// Maximum number of emails to return
+        #[clap(long, short = 'n', default_value_t = 10)]
+        max_results: u32,
+    },
+
+    /// Show a single email
+    #[clap(name = "get")]
+    Get {
+        /// The ID of the message to retrieve
+        message_id: String,
+    },
+
+    /// Send an email immediately
+    #[clap(name = "send")]
+    Send {
+        /// Recipient address(es), comma-separated
+        #[clap(long)]
+        to: String,
+
+        /// Subject line
+        #[clap(long)]
+        subject: String,
+
+        /// Plain text body
+        #[clap(long)]
+        body: String,
+
+        /// CC recipient(s), comma-separated
+        #[clap(long)]
+        cc: Option<String>,
+
+        /// BCC recipient(s), comma-separated
+        #[clap(long)]
+        bcc: Option<String>,
+
+        /// Google Drive file ID to attach, or link if too large (repeatable)
+        #[clap(long = "drive-file")]
+        drive_files: Vec<String>,
+
+        /// Priority to mark the email with: high, normal or low
+        #[clap(long)]
+        priority: Option<String>,
+
+        /// Ask the recipients for a read receipt
+        #[clap(long)]
+        read_receipt: bool,
+    },
+}
+
+#[derive(Subcommand, Debug, PartialEq)]
+pub enum CalCommands {
+    /// Show upcoming events
+    #[clap(name = "agenda")]
+    Agenda {
+        /// Calendar to read from
+        #[clap(long, short, default_value = "primary")]
+        calendar_id: String,
+
+        /// Number of days ahead to include
+        #[clap(long, short, default_value_t = 1, value_parser = clap::value_parser!(u32).range(..=CAL_MAX_DAYS))]
+        days: u32,
+
+        /// Maximum number of events to return
+        #[clap(long, short = 'n', default_value_t = 25)]
+        max_results: u32,
+    },
+
+    /// Export upcoming events as an iCalendar (.ics) file
+    #[clap(name = "export")]
+    Export {
+        /// Calendar to read from
+        #[clap(long, short, default_value = "primary")]
+        calendar_id: String,
+
+        /// Number of days ahead to include
+        #[clap(long, short, default_value_t = 30, value_parser = clap::value_parser!(u32).range(..=CAL_MAX_DAYS))]
+        days: u32,
+
+        /// Only export events with these words in their title, description
+        /// or location
+        #[clap(long, short)]
+        query: Option<String>,
+
+        /// File to write; the calendar is printed when not given
+        #[clap(long, short)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug, PartialEq)]
+pub enum ContactsCommands {
+    /// Search contacts by name, email or other attributes
+    #[clap(name = "search")]
+    Search {
+        /// Search query
+        query: String,
+
+        /// Maximum number of contacts to return
+        #[clap(long, short = 'n')]
+        max_results: Option<u32>,
+    },
 }
\ No newline at end of file
@@ -0,0 +1,191 @@
+use crate::calendar_api::CalendarClient;
+use crate::cli::{CalCommands, ContactsCommands, MailCommands};
+use crate::config::Config;
+use crate::drive_api::{get_drive_attachment_max_bytes, prepare_drive_attachments, DriveClient};
+use crate::gmail_api::{attachment_budget, DraftEmail, GmailService};
+use crate::people_api::PeopleClient;
+use crate::priority::PriorityLevel;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+// Direct (non-MCP) command implementations. Each command uses the same service
+// clients as the MCP tools and returns pretty-printed JSON for display.
+
+fn load_config() -> Result<Config, String> {
+    Config::from_env().map_err(|e| format!("Failed to load credentials: {}", e))
+}
+
+// The end of a window `days` ahead of `now`
+fn days_ahead(now: DateTime<Utc>, days: u32) -> Result<DateTime<Utc>, String> {
+    now.checked_add_signed(Duration::days(i64::from(days)))
+        .ok_or_else(|| format!("--days {} reaches past the latest supported date", days))
+}
+
+fn to_pretty_json<T: Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string_pretty(value).map_err(|e| format!("Failed to serialize result: {}", e))
+}
+
+/// Run a `mail` subcommand
+pub async fn run_mail(command: MailCommands) -> Result<String, String> {
+    let config = load_config()?;
+    let service = GmailService::new(&config)
+        .map_err(|e| format!("Failed to create Gmail service: {}", e))?;
+
+    match command {
+        MailCommands::List { max_results, query } => {
+            let messages = service
+                .list_messages(max_results, query.as_deref())
+                .await
+                .map_err(|e| format!("Failed to list emails: {}", e))?;
+            to_pretty_json(&messages)
+        }
+        MailCommands::Search { query, max_results } => {
+            let messages = service
+                .list_messages(max_results, Some(&query))
+                .await
+                .map_err(|e| format!("Failed to search emails: {}", e))?;
+            to_pretty_json(&messages)
+        }
+        MailCommands::Get { message_id } => {
+            let email = service
+                .get_message_details(&message_id)
+                .await
+                .map_err(|e| format!("Failed to get email {}: {}", message_id, e))?;
+            to_pretty_json(&email)
+        }
+        MailCommands::Send {
+            to,
+            subject,
+            body,
+            cc,
+            bcc,
+            drive_files,
+            priority,
+            read_receipt,
+        } => {
+            if to.trim().is_empty() {
+                return Err("Recipient (--to) is required to send an email".to_string());
+            }
+            let priority = priority
+                .map(|value| {
+                    PriorityLevel::parse(&value).ok_or_else(|| {
+                        format!("Invalid priority '{}': use high, normal or low", value)
+                    })
+                })
+                .transpose()?;
+            let read_receipt_to = if read_receipt {
+                Some(service.get_email_address().await.map_err(|e| {
+                    format!("Failed to look up your address for read receipts: {}", e)
+                })?)
+            } else {
+                None
+            };
+
+            let mut draft = DraftEmail {
+                to,
+                subject,
+                body,
+                cc,
+                bcc,
+                thread_id: None,
+                in_reply_to: None,
+                references: None,
+                priority,
+                read_receipt_to,
+            };
+
+            let drive_attachments = prepare_drive_attachments(
+                &DriveClient::new(&config),
+                &drive_files,
+                &draft.recipient_addresses(),
+                get_drive_attachment_max_bytes().min(attachment_budget(&draft.body)),
+            )
+            .await
+            .map_err(|e| format!("Failed to prepare Drive files: {}", e))?;
+            draft.body = drive_attachments.body_with_links(&draft.body);
+
+            let message_id = service
+                .send_message_with_attachments(&draft, &drive_attachments.files)
+                .await
+                .map_err(|e| format!("Failed to send email: {}", e))?;
+            let mut result = serde_json::json!({
+                "status": "success",
+                "message_id": message_id,
+            });
+            if !drive_files.is_empty() {
+                result["drive_files"] = serde_json::json!(drive_attachments);
+            }
+            to_pretty_json(&result)
+        }
+    }
+}
+
+/// Run a `cal` subcommand
+pub async fn run_cal(command: CalCommands) -> Result<String, String> {
+    let config = load_config()?;
+    let client = CalendarClient::new(&config);
+
+    match command {
+        CalCommands::Agenda {
+            calendar_id,
+            days,
+            max_results,
+        } => {
+            let now = Utc::now();
+            let end = days_ahead(now, days)?;
+            let events = client
+                .list_events(&calendar_id, Some(max_results), Some(now), Some(end))
+                .await
+                .map_err(|e| format!("Failed to list events from {}: {}", calendar_id, e))?;
+            to_pretty_json(&events)
+        }
+        CalCommands::Export {
+            calendar_id,
+            days,
+            query,
+            output,
+        } => {
+            let now = Utc::now();
+            let end = days_ahead(now, days)?;
+            let events = client
+                .list_events(
+                    &calendar_id,
+                    Some(crate::ics::ICS_MAX_EVENTS),
+                    Some(now),
+                    Some(end),
+                )
+                .await
+                .map_err(|e| format!("Failed to list events from {}: {}", calendar_id, e))?;
+            let events = crate::ics::filter_agenda(events, (now, end), query.as_deref());
+            let ics = crate::ics::to_ics(&events, &calendar_id, now);
+            match output {
+                Some(path) => {
+                    crate::ics::write_ics(&path, &ics)
+                        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+                    to_pretty_json(&serde_json::json!({
+                        "path": path.display().to_string(),
+                        "calendar_id": calendar_id,
+                        "events": events.len(),
+                    }))
+                }
+                None => Ok(ics),
+            }
+        }
+    }
+}
+
+/// Run a `contacts` subcommand
+pub async fn run_contacts(command: ContactsCommands) -> Result<String, String> {
+    let config = load_config()?;
+    let client = PeopleClient::new(&config);
+
+    match command {
+        ContactsCommands::Search { query, max_results } => {
+            let contacts = client
+                .search_contacts(&query, max_results)
+                .await
+                .map_err(|e| format!("Failed to search contacts: {}", e))?;
+            to_pretty_json(&contacts)
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use crate::calendar_api::CalendarEvent;
+use crate::gmail_api::EmailMessage;
+use crate::people_api::{find_contact_by_email, Contact};
+use crate::thread_view::parse_date;
+use crate::utils::{parse_email_addresses, strip_quoted_text};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+// Compose context
+//
+// Before drafting to someone it helps to know what was last said, who they
+// are and when you will next meet. The recent mail exchanged with them, their
+// contact record and the calendar events you both attend are gathered into
+// one payload. Mail is reduced to what each message adds, as in the clean
+// thread view, so the payload stays small enough to seed a prompt.
+
+/// Exchanges returned when no number is given
+pub const DEFAULT_COMPOSE_EXCHANGES: u32 = 5;
+
+/// Days ahead searched for shared events
+pub const COMPOSE_EVENT_DAYS: i64 = 30;
+
+/// Most calendar events checked for the recipient
+pub const COMPOSE_MAX_EVENTS: u32 = 100;
+
+/// Gmail query for mail from or to an address
+pub fn exchange_query(address: &str) -> String {
+    format!("from:{} OR to:{}", address, address)
+}
+
+/// A recent message from or to the recipient
+#[derive(Debug, Clone, Serialize)]
+pub struct Exchange {
+    pub id: String,
+    pub thread_id: String,
+    /// "received" for mail from the recipient, "sent" for mail to them
+    pub direction: String,
+    pub subject: Option<String>,
+    pub date: Option<String>,
+    pub timestamp: Option<DateTime<Utc>>,
+    /// The message without the earlier mail it quotes
+    pub content: String,
+}
+
+/// Everything known about a recipient that helps write to them
+#[derive(Debug, Clone, Serialize)]
+pub struct ComposeContext {
+    pub to: String,
+    pub contact: Option<Contact>,
+    /// Most recent first
+    pub exchanges: Vec<Exchange>,
+    /// Soonest first
+    pub upcoming_events: Vec<CalendarEvent>,
+}
+
+impl ComposeContext {
+    /// Gather the context for writing to `to` from messages matching
+    /// `exchange_query`, contact search results and calendar events. Anything
+    /// not involving the address is left out, as are events that have ended.
+    pub fn new(
+        to: &str,
+        messages: &[EmailMessage],
+        max_exchanges: usize,
+        contacts: &[Contact],
+        events: &[CalendarEvent],
+        now: DateTime<Utc>,
+    ) -> Self {
+        let involves = |header: &Option<String>| {
+            header
+                .as_deref()
+                .is_some_and(|header| parse_email_addresses(header).iter().any(|a| a == to))
+        };
+
+        let mut exchanges: Vec<Exchange> = messages
+            .iter()
+            .filter_map(|message| {
+                let direction = if involves(&message.from) {
+                    "received"
+                } else if involves(&message.to) {
+                    "sent"
+                } else {
+                    return None;
+                };
+                let body = message
+                    .body_text
+                    .as_deref()
+                    .or(message.snippet.as_deref())
+                    .unwrap_or_default();
+                Some(Exchange {
+                    id: message.id.clone(),
+                    thread_id: message.thread_id.clone(),
+                    direction: direction.to_string(),
+                    subject: message.subject.clone(),
+                    date: message.date.clone(),
+                    timestamp: message.date.as_deref().and_then(parse_date),
+                    content: strip_quoted_text(body),
+                })
+            })
+            .collect();
+        exchanges.sort_by_key(|exchange| std::cmp::Reverse(exchange.timestamp));
+        exchanges.truncate(max_exchanges);
+
+        let contact = find_contact_by_email(contacts, to).cloned();
+
+        let mut upcoming_events: Vec<CalendarEvent> = events
+            .iter()
+            .filter(|event| event.end_time > now)
+            .filter(|event| {
+                event
+                    .attendees
+                    .iter()
+                    .map(|attendee| &attendee.email)
+                    .chain(event.organizer.as_ref().map(|organizer| &organizer.email))
+                    .any(|email| email.eq_ignore_ascii_case(to))
+            })
+            .cloned()
+            .collect();
+        upcoming_events.sort_by_key(|event| event.start_time);
+
+        Self {
+            to: to.to_string(),
+            contact,
+            exchanges,
+            upcoming_events,
+        }
+    }
+}
@@ -2,6 +2,7 @@ use crate::errors::ConfigError;
 use dotenv::dotenv;
 use log::debug;
 use std::env;
+use std::path::PathBuf;
 
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -67,7 +68,92 @@ impl Config {
 
 // API URL constants
 pub const GMAIL_API_BASE_URL: &str = "https://gmail.googleapis.com/gmail/v1";
+pub const CALENDAR_API_BASE_URL: &str = "https://www.googleapis.com/calendar/v3";
+pub const PEOPLE_API_BASE_URL: &str = "https://people.googleapis.com/v1";
+pub const TASKS_API_BASE_URL: &str = "https://tasks.googleapis.com/tasks/v1";
+pub const DRIVE_API_BASE_URL: &str = "https://www.googleapis.com/drive/v3";
 pub const OAUTH_TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+pub const TOKEN_INFO_URL: &str = "https://oauth2.googleapis.com/tokeninfo";
+
+// API URL resolution
+//
+// Each base URL can be overridden through an environment variable of the same
+// name. This is how mock mode redirects the API clients to local fixtures.
+
+fn url_from_env(var: &str, default: &str) -> String {
+    std::env::var(var)
+        .ok()
+        .filter(|s| !s.trim().is_empty())
+        .map(|s| s.trim_end_matches('/').to_string())
+        .unwrap_or_else(|| default.to_string())
+}
+
+/// Returns the Gmail API base URL.
+///
+/// Environment variable: GMAIL_API_BASE_URL
+pub fn get_gmail_api_base_url() -> String {
+    url_from_env("GMAIL_API_BASE_URL", GMAIL_API_BASE_URL)
+}
+
+/// Returns the Gmail upload base URL, used for resumable uploads. Defaults to
+/// the Gmail API base URL with `/upload` before its path, which is where
+/// Google serves uploads.
+///
+/// Environment variable: GMAIL_UPLOAD_BASE_URL
+pub fn get_gmail_upload_base_url() -> String {
+    let api_url = get_gmail_api_base_url();
+    let host_start = api_url.find("://").map(|i| i + 3).unwrap_or(0);
+    let path_start = api_url[host_start..]
+        .find('/')
+        .map(|i| host_start + i)
+        .unwrap_or(api_url.len());
+    let (origin, path) = api_url.split_at(path_start);
+    let default = format!("{}/upload{}", origin, path);
+    url_from_env("GMAIL_UPLOAD_BASE_URL", &default)
+}
+
+/// Returns the Calendar API base URL.
+///
+/// Environment variable: CALENDAR_API_BASE_URL
+pub fn get_calendar_api_base_url() -> String {
+    url_from_env("CALENDAR_API_BASE_URL", CALENDAR_API_BASE_URL)
+}
+
+/// Returns the People API base URL.
+///
+/// Environment variable: PEOPLE_API_BASE_URL
+pub fn get_people_api_base_url() -> String {
+    url_from_env("PEOPLE_API_BASE_URL", PEOPLE_API_BASE_URL)
+}
+
+/// Returns the Tasks API base URL.
+///
+/// Environment variable: TASKS_API_BASE_URL
+pub fn get_tasks_api_base_url() -> String {
+    url_from_env("TASKS_API_BASE_URL", TASKS_API_BASE_URL)
+}
+
+/// Returns the Drive API base URL.
+///
+/// Environment variable: DRIVE_API_BASE_URL
+pub fn get_drive_api_base_url() -> String {
+    url_from_env("DRIVE_API_BASE_URL", DRIVE_API_BASE_URL)
+}
+
+/// Returns the OAuth token endpoint used to refresh access tokens.
+///
+/// Environment variable: OAUTH_TOKEN_URL
+pub fn get_oauth_token_url() -> String {
+    url_from_env("OAUTH_TOKEN_URL", OAUTH_TOKEN_URL)
+}
+
+/// Returns the endpoint used to look up the scopes and lifetime of an
+/// access token.
+///
+/// Environment variable: TOKEN_INFO_URL
+pub fn get_token_info_url() -> String {
+    url_from_env("TOKEN_INFO_URL", TOKEN_INFO_URL)
+}
 
 // Configuration utility functions
 
@@ -111,3 +197,80 @@ pub fn get_token_refresh_threshold_seconds() -> u64 {
         .and_then(|s| s.parse::<u64>().ok())
         .unwrap_or(300) // Default 5 minutes if not configured
 }
+
+/// Default maximum size of a decoded message body, in bytes (1 MiB)
+pub const DEFAULT_MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// Returns the maximum size in bytes of a decoded message body.
+///
+/// Longer bodies are only decoded up to this size and end with a note that
+/// they were truncated, so that batch analysis of very large emails keeps
+/// memory bounded. Default is 1 MiB if not configured.
+///
+/// Environment variable: MESSAGE_BODY_MAX_BYTES
+pub fn get_max_body_bytes() -> usize {
+    std::env::var("MESSAGE_BODY_MAX_BYTES")
+        .ok()
+        .and_then(|s| s.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_MAX_BODY_BYTES)
+}
+
+/// Returns whether HTML message bodies are sanitized before they are
+/// returned, with scripts, event handlers and tracking pixels removed.
+///
+/// Default is true if not configured.
+///
+/// Environment variable: SANITIZE_HTML
+pub fn is_html_sanitizing_enabled() -> bool {
+    std::env::var("SANITIZE_HTML")
+        .map(|s| s.to_lowercase() == "true" || s == "1")
+        .unwrap_or(true)
+}
+
+/// Returns the time zone dates in words such as "tomorrow 3pm" are read in.
+///
+/// Takes an IANA name such as "Europe/Berlin". Default is UTC if not
+/// configured or not a known time zone.
+///
+/// Environment variable: USER_TIMEZONE
+pub fn get_user_time_zone() -> chrono_tz::Tz {
+    get_configured_time_zone().unwrap_or(chrono_tz::UTC)
+}
+
+/// Returns the time zone set in USER_TIMEZONE, if any.
+///
+/// Calendar tools use it in place of the primary calendar's time zone.
+///
+/// Environment variable: USER_TIMEZONE
+pub fn get_configured_time_zone() -> Option<chrono_tz::Tz> {
+    std::env::var("USER_TIMEZONE")
+        .ok()
+        .and_then(|s| s.trim().parse::<chrono_tz::Tz>().ok())
+}
+
+/// Returns whether the server checks its configuration, credentials and each
+/// Google API when it starts.
+///
+/// The checks run in the background and only log a readiness summary, so a
+/// failing check never stops the server. Default is off if not configured.
+///
+/// Environment variable: STARTUP_PREFLIGHT
+pub fn get_startup_preflight() -> bool {
+    std::env::var("STARTUP_PREFLIGHT")
+        .map(|s| s.to_lowercase() == "true" || s == "1")
+        .unwrap_or(false)
+}
+
+/// Returns the directory where state is kept across restarts, if any.
+///
+/// When set, cached API responses are saved there, and the local message
+/// index and its sync state default to it, so that a restarted server
+/// doesn't have to fetch everything again. Not set by default.
+///
+/// Environment variable: STATE_DIR
+pub fn get_state_dir() -> Option<PathBuf> {
+    std::env::var("STATE_DIR")
+        .ok()
+        .filter(|s| !s.trim().is_empty())
+        .map(PathBuf::from)
+}
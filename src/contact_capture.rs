@@ -0,0 +1,337 @@
+use crate::people_api::{Contact, EmailAddress, Organization, PersonName, PhoneNumber};
+use crate::utils::parse_email_addresses;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+// Contact capture
+//
+// The signature at the end of an email usually says who the sender is: their
+// name, job title, company and phone numbers. Parsing it gives a proposed
+// contact, which is shown first and only saved when confirmed. A sender who
+// is already a contact is updated, but only with what the contact lacks:
+// numbers and addresses are added, and a name or organization is only set
+// where there is none, so nothing the user entered is overwritten.
+
+/// Person fields read to update a contact from a signature
+pub const CAPTURE_PERSON_FIELDS: &[&str] =
+    &["names", "emailAddresses", "phoneNumbers", "organizations"];
+
+/// Labels that mark a phone number, and the type each stands for
+const PHONE_LABELS: &[(&str, Option<&str>)] = &[
+    ("cell", Some("mobile")),
+    ("direct", Some("work")),
+    ("m", Some("mobile")),
+    ("mob", Some("mobile")),
+    ("mobile", Some("mobile")),
+    ("o", Some("work")),
+    ("office", Some("work")),
+    ("p", None),
+    ("phone", None),
+    ("t", None),
+    ("tel", None),
+    ("w", Some("work")),
+    ("work", Some("work")),
+];
+
+/// Characters a signature puts between items on one line
+const SEPARATORS: &[char] = &['|', '•', '·'];
+
+/// What a signature says about its sender
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SignatureDetails {
+    pub name: Option<String>,
+    pub title: Option<String>,
+    pub organization: Option<String>,
+    pub phone_numbers: Vec<PhoneNumber>,
+    pub email_addresses: Vec<String>,
+}
+
+// A phone number with an optional label such as "M:" or "Tel.", and its type
+fn parse_phone(text: &str) -> Option<PhoneNumber> {
+    let (label, number) = match text.split_once([':', '.']) {
+        Some((label, number))
+            if PHONE_LABELS
+                .iter()
+                .any(|(known, _)| known.eq_ignore_ascii_case(label.trim())) =>
+        {
+            (Some(label.trim().to_lowercase()), number.trim())
+        }
+        _ => (None, text.trim()),
+    };
+    let digits = number.chars().filter(|c| c.is_ascii_digit()).count();
+    let phone_like = number
+        .chars()
+        .all(|c| c.is_ascii_digit() || " +()-./".contains(c));
+    if !phone_like || !(7..=15).contains(&digits) {
+        return None;
+    }
+    let type_ = label.and_then(|label| {
+        PHONE_LABELS
+            .iter()
+            .find(|(known, _)| *known == label)
+            .and_then(|(_, type_)| type_.map(str::to_string))
+    });
+    Some(PhoneNumber {
+        value: number.to_string(),
+        type_,
+    })
+}
+
+// Whether a line looks like a person's name: two to four capitalized words
+fn looks_like_name(text: &str) -> bool {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    (2..=4).contains(&words.len())
+        && words.iter().all(|word| {
+            word.chars().next().is_some_and(char::is_uppercase)
+                && word.chars().all(|c| c.is_alphabetic() || "-'.".contains(c))
+        })
+}
+
+/// Read the sender's details from a signature found by
+/// `utils::extract_signature`. `sender_name` is the name in the From header,
+/// used to recognize the line holding the name.
+pub fn parse_signature(signature: &str, sender_name: Option<&str>) -> SignatureDetails {
+    let mut details = SignatureDetails::default();
+    let mut text = Vec::new();
+    for part in signature.lines().flat_map(|line| line.split(SEPARATORS)) {
+        let part = part.trim();
+        if part.is_empty() {
+            continue;
+        }
+        if let Some(phone) = parse_phone(part) {
+            details.phone_numbers.push(phone);
+        } else if part.contains('@') {
+            details.email_addresses.extend(parse_email_addresses(part));
+        } else if part.starts_with("http") || part.starts_with("www.") {
+            continue;
+        } else if details.name.is_none()
+            && (sender_name.is_some_and(|name| name.eq_ignore_ascii_case(part))
+                || (text.is_empty() && looks_like_name(part)))
+        {
+            details.name = Some(part.to_string());
+        } else if !part.chars().any(|c| c.is_ascii_digit()) {
+            // Lines with digits are postal addresses
+            text.push(part.to_string());
+        }
+    }
+
+    // A title and company on one line, as in "CTO, Example Corp" or
+    // "CTO at Example Corp"
+    if let [line] = text.as_slice() {
+        if let Some((title, organization)) =
+            line.split_once(" at ").or_else(|| line.rsplit_once(", "))
+        {
+            text = vec![title.trim().to_string(), organization.trim().to_string()];
+        }
+    }
+    let mut text = text.into_iter();
+    details.title = text.next();
+    details.organization = text.next();
+    details
+}
+
+/// The contact a signature proposes for the sender of an email
+pub fn propose_contact(
+    address: &str,
+    sender_name: Option<&str>,
+    details: &SignatureDetails,
+) -> Contact {
+    let display_name = details
+        .name
+        .clone()
+        .or(sender_name.map(str::to_string))
+        .filter(|name| !name.is_empty());
+    let name = display_name.map(|display_name| {
+        let (given_name, family_name) = match display_name.rsplit_once(' ') {
+            Some((given, family)) => (Some(given.to_string()), Some(family.to_string())),
+            None => (Some(display_name.clone()), None),
+        };
+        PersonName {
+            display_name,
+            given_name,
+            family_name,
+        }
+    });
+
+    let mut email_addresses = vec![EmailAddress {
+        value: address.to_string(),
+        type_: None,
+    }];
+    for email in &details.email_addresses {
+        if !email_addresses
+            .iter()
+            .any(|known| known.value.eq_ignore_ascii_case(email))
+        {
+            email_addresses.push(EmailAddress {
+                value: email.clone(),
+                type_: None,
+            });
+        }
+    }
+
+    let organizations = if details.title.is_some() || details.organization.is_some() {
+        vec![Organization {
+            name: details.organization.clone(),
+            title: details.title.clone(),
+        }]
+    } else {
+        Vec::new()
+    };
+
+    Contact {
+        resource_name: String::new(),
+        name,
+        email_addresses,
+        phone_numbers: details.phone_numbers.clone(),
+        organizations,
+        photos: Vec::new(),
+    }
+}
+
+/// The person to create for a proposed contact
+pub fn new_person(contact: &Contact) -> Value {
+    let mut person = json!({
+        "emailAddresses": contact
+            .email_addresses
+            .iter()
+            .map(|email| json!({ "value": email.value }))
+            .collect::<Vec<_>>(),
+    });
+    if let Some(name) = &contact.name {
+        person["names"] = json!([{
+            "givenName": name.given_name,
+            "familyName": name.family_name,
+        }]);
+    }
+    if !contact.phone_numbers.is_empty() {
+        person["phoneNumbers"] = contact.phone_numbers.iter().map(phone_value).collect();
+    }
+    if let Some(organization) = contact.organizations.first() {
+        person["organizations"] = json!([{
+            "name": organization.name,
+            "title": organization.title,
+        }]);
+    }
+    person
+}
+
+fn phone_value(phone: &PhoneNumber) -> Value {
+    match &phone.type_ {
+        Some(type_) => json!({ "value": phone.value, "type": type_ }),
+        None => json!({ "value": phone.value }),
+    }
+}
+
+// The digits of a phone number, to compare numbers written differently
+fn phone_digits(number: &str) -> String {
+    number.chars().filter(|c| c.is_ascii_digit()).collect()
+}
+
+/// A change to an existing contact, as the API returns it, that adds what a
+/// proposed contact has and it lacks
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContactMerge {
+    /// The fields to write, with the contact's etag
+    pub update: Value,
+    /// The person fields the update changes
+    pub fields: Vec<&'static str>,
+    /// What is added, in words
+    pub changes: Vec<String>,
+}
+
+/// Merge a proposed contact into an existing one as the API returns it
+pub fn merge_into_person(person: &Value, proposed: &Contact) -> ContactMerge {
+    let mut update = json!({});
+    if let Some(etag) = person.get("etag") {
+        update["etag"] = etag.clone();
+    }
+    let mut fields = Vec::new();
+    let mut changes = Vec::new();
+    let existing = |field: &str| person[field].as_array().cloned().unwrap_or_default();
+
+    if let Some(name) = &proposed.name {
+        if existing("names").is_empty() {
+            update["names"] = json!([{
+                "givenName": name.given_name,
+                "familyName": name.family_name,
+            }]);
+            fields.push("names");
+            changes.push(format!("set name {}", name.display_name));
+        }
+    }
+
+    let mut emails = existing("emailAddresses");
+    for email in &proposed.email_addresses {
+        let known = emails.iter().any(|known| {
+            known["value"]
+                .as_str()
+                .is_some_and(|value| value.eq_ignore_ascii_case(&email.value))
+        });
+        if !known {
+            emails.push(json!({ "value": email.value }));
+            changes.push(format!("add email {}", email.value));
+        }
+    }
+    if emails.len() > existing("emailAddresses").len() {
+        update["emailAddresses"] = json!(emails);
+        fields.push("emailAddresses");
+    }
+
+    let mut phones = existing("phoneNumbers");
+    for phone in &proposed.phone_numbers {
+        let known = phones.iter().any(|known| {
+            known["value"]
+                .as_str()
+                .is_some_and(|value| phone_digits(value) == phone_digits(&phone.value))
+        });
+        if !known {
+            phones.push(phone_value(phone));
+            changes.push(format!("add phone {}", phone.value));
+        }
+    }
+    if phones.len() > existing("phoneNumbers").len() {
+        update["phoneNumbers"] = json!(phones);
+        fields.push("phoneNumbers");
+    }
+
+    if let Some(organization) = proposed.organizations.first() {
+        if existing("organizations").is_empty() {
+            update["organizations"] = json!([{
+                "name": organization.name,
+                "title": organization.title,
+            }]);
+            fields.push("organizations");
+            let described = [organization.title.as_deref(), organization.name.as_deref()]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join(", ");
+            changes.push(format!("set organization {}", described));
+        }
+    }
+
+    ContactMerge {
+        update,
+        fields,
+        changes,
+    }
+}
+
+/// What was, or would be, saved from an email's signature
+#[derive(Debug, Clone, Serialize)]
+pub struct ContactCapture {
+    pub message_id: String,
+    /// The signature the details were read from
+    pub signature: Option<String>,
+    /// "create" for a new contact, "update" to add to an existing one, or
+    /// "none" when the contact already has everything
+    pub action: String,
+    /// What an update adds
+    pub changes: Vec<String>,
+    /// The contact as proposed, or as saved once confirmed
+    pub contact: Contact,
+    /// The sender's contact before any update
+    pub existing: Option<Contact>,
+    /// Whether the contact was saved
+    pub saved: bool,
+}
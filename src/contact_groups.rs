@@ -0,0 +1,186 @@
+use crate::people_api::Contact;
+use serde::Serialize;
+
+// Contacts by company
+//
+// Contacts are grouped by the company they work for: the organization on
+// their contact record, or failing that the domain of their email address.
+// Addresses at personal mail providers say nothing about an employer, so
+// contacts with only those are left ungrouped. A company is looked up by
+// email domain, subdomains included, or by part of the organization's name.
+
+/// Most contacts read to build the groups
+pub const CONTACT_GROUP_SCAN: u32 = 1000;
+
+/// Domains of personal mail providers, which are not companies
+pub const PERSONAL_EMAIL_DOMAINS: &[&str] = &[
+    "aol.com",
+    "gmail.com",
+    "gmx.com",
+    "gmx.de",
+    "googlemail.com",
+    "hotmail.com",
+    "icloud.com",
+    "live.com",
+    "mac.com",
+    "me.com",
+    "msn.com",
+    "outlook.com",
+    "proton.me",
+    "protonmail.com",
+    "yahoo.com",
+    "yandex.com",
+];
+
+/// The lowercased domain of an email address
+pub fn email_domain(address: &str) -> Option<String> {
+    address
+        .rsplit_once('@')
+        .map(|(_, domain)| domain.trim().to_lowercase())
+        .filter(|domain| !domain.is_empty())
+}
+
+/// Whether a domain belongs to a personal mail provider
+pub fn is_personal_domain(domain: &str) -> bool {
+    PERSONAL_EMAIL_DOMAINS.contains(&domain)
+}
+
+/// Whether `domain` is `wanted` or one of its subdomains
+fn domain_matches(domain: &str, wanted: &str) -> bool {
+    domain == wanted || domain.ends_with(&format!(".{}", wanted))
+}
+
+/// How contacts were put together
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupKind {
+    Organization,
+    Domain,
+}
+
+/// A contact in a group
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GroupMember {
+    pub resource_name: String,
+    pub name: Option<String>,
+    pub email: Option<String>,
+    pub title: Option<String>,
+}
+
+/// The contacts at one company
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ContactGroup {
+    /// The organization's name, or the email domain
+    pub name: String,
+    pub kind: GroupKind,
+    pub count: usize,
+    /// Email domains the contacts use, other than personal ones
+    pub domains: Vec<String>,
+    pub contacts: Vec<GroupMember>,
+}
+
+/// Contacts grouped by company, largest group first
+#[derive(Debug, Clone, Serialize)]
+pub struct ContactGroups {
+    /// The domain or organization looked up, if any
+    pub query: Option<String>,
+    pub contacts_scanned: usize,
+    pub groups: Vec<ContactGroup>,
+    /// Contacts with neither an organization nor a work email address
+    pub ungrouped: usize,
+}
+
+impl ContactGroups {
+    /// Group contacts by organization or email domain, keeping only those
+    /// matching `query` if given: a domain such as "example.com", or part of
+    /// an organization's name
+    pub fn new(contacts: &[Contact], query: Option<&str>) -> Self {
+        let query = query
+            .map(|query| query.trim().trim_start_matches('@').to_lowercase())
+            .filter(|query| !query.is_empty());
+
+        let mut groups: Vec<ContactGroup> = Vec::new();
+        let mut ungrouped = 0;
+        for contact in contacts {
+            let organization = contact
+                .organizations
+                .iter()
+                .find_map(|organization| organization.name.as_deref())
+                .map(str::trim)
+                .filter(|name| !name.is_empty());
+            let domains: Vec<String> = contact
+                .email_addresses
+                .iter()
+                .filter_map(|email| email_domain(&email.value))
+                .filter(|domain| !is_personal_domain(domain))
+                .collect();
+
+            if let Some(query) = &query {
+                let by_name = organization
+                    .is_some_and(|organization| organization.to_lowercase().contains(query));
+                let by_domain = domains.iter().any(|domain| domain_matches(domain, query));
+                if !by_name && !by_domain {
+                    continue;
+                }
+            }
+
+            let (name, kind) = match (organization, domains.first()) {
+                (Some(organization), _) => (organization.to_string(), GroupKind::Organization),
+                (None, Some(domain)) => (domain.clone(), GroupKind::Domain),
+                (None, None) => {
+                    ungrouped += 1;
+                    continue;
+                }
+            };
+            let member = GroupMember {
+                resource_name: contact.resource_name.clone(),
+                name: contact.name.as_ref().map(|name| name.display_name.clone()),
+                email: contact
+                    .email_addresses
+                    .first()
+                    .map(|email| email.value.clone()),
+                title: contact
+                    .organizations
+                    .iter()
+                    .find_map(|organization| organization.title.clone()),
+            };
+
+            let index = match groups
+                .iter()
+                .position(|group| group.kind == kind && group.name.eq_ignore_ascii_case(&name))
+            {
+                Some(index) => index,
+                None => {
+                    groups.push(ContactGroup {
+                        name,
+                        kind,
+                        count: 0,
+                        domains: Vec::new(),
+                        contacts: Vec::new(),
+                    });
+                    groups.len() - 1
+                }
+            };
+            let group = &mut groups[index];
+            group.count += 1;
+            group.contacts.push(member);
+            for domain in domains {
+                if !group.domains.contains(&domain) {
+                    group.domains.push(domain);
+                }
+            }
+        }
+        groups.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+        });
+
+        Self {
+            query,
+            contacts_scanned: contacts.len(),
+            groups,
+            ungrouped,
+        }
+    }
+}
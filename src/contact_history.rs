@@ -0,0 +1,249 @@
+use crate::calendar_api::CalendarEvent;
+use crate::event_search::involves;
+use crate::gmail_api::MessageMetadata;
+use crate::people_api::{find_contact_by_email, Contact};
+use crate::sender_report::split_sender;
+use crate::utils::parse_email_addresses;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+// Contact history
+//
+// A relationship summary answers "how well do I know this person?": who they
+// are from their contact record, how often mail goes back and forth, what was
+// last said, and when you last met. Mail is read as metadata only and
+// counted over a recent period; a message counts as exchanged when it comes
+// from the person or was sent by the user to them, so mail from others that
+// merely copies them both is left out.
+
+/// Days over which mail and meetings are counted
+pub const CONTACT_HISTORY_DAYS: i64 = 90;
+
+/// Days ahead searched for the next meeting
+pub const CONTACT_HISTORY_UPCOMING_DAYS: i64 = 30;
+
+/// Recent messages returned when no number is given
+pub const DEFAULT_CONTACT_HISTORY_MESSAGES: u32 = 5;
+
+/// Most messages scanned for one summary
+pub const CONTACT_HISTORY_MAX_SCAN: u32 = 500;
+
+/// Most calendar events checked for meetings
+pub const CONTACT_HISTORY_MAX_EVENTS: u32 = 250;
+
+/// Headers fetched besides those `MessageMetadata` always holds
+pub const CONTACT_HISTORY_HEADERS: &[&str] = &["To", "Cc"];
+
+/// Gmail query for mail exchanged with an address over the counted period
+pub fn history_query(address: &str) -> String {
+    format!(
+        "{{from:{} to:{} cc:{}}} newer_than:{}d",
+        address, address, address, CONTACT_HISTORY_DAYS
+    )
+}
+
+/// A message exchanged with the person
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HistoryMessage {
+    pub id: String,
+    pub thread_id: String,
+    /// "received" for mail from the person, "sent" for mail to them
+    pub direction: String,
+    pub subject: Option<String>,
+    pub snippet: Option<String>,
+    pub received_at: Option<DateTime<Utc>>,
+}
+
+/// How much mail is exchanged with the person over the counted period
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EmailFrequency {
+    pub days: i64,
+    pub received: usize,
+    pub sent: usize,
+    /// Messages either way per week, to one decimal place
+    pub per_week: f64,
+    /// Whether every message in the period was counted; otherwise the
+    /// counts are of the most recent ones only
+    pub complete: bool,
+}
+
+/// What is known of the user's relationship with one person
+#[derive(Debug, Clone, Serialize)]
+pub struct ContactHistory {
+    pub email: String,
+    /// From their contact record, or the name they send mail under
+    pub name: Option<String>,
+    pub contact: Option<Contact>,
+    pub frequency: EmailFrequency,
+    pub last_received: Option<DateTime<Utc>>,
+    pub last_sent: Option<DateTime<Utc>>,
+    /// Most recent first
+    pub recent_messages: Vec<HistoryMessage>,
+    /// Meetings with the person over the counted period
+    pub meetings: usize,
+    pub last_meeting: Option<CalendarEvent>,
+    pub next_meeting: Option<CalendarEvent>,
+    /// The relationship in a sentence or two
+    pub summary: String,
+}
+
+impl ContactHistory {
+    /// Build the history of `email` from messages matching `history_query`,
+    /// contact search results and the primary calendar's events around
+    /// `now`. `scan_limit` is how many messages were asked for; reaching it
+    /// means older ones in the period were not counted.
+    pub fn new(
+        email: &str,
+        messages: &[MessageMetadata],
+        scan_limit: usize,
+        max_messages: usize,
+        contacts: &[Contact],
+        events: &[CalendarEvent],
+        now: DateTime<Utc>,
+    ) -> Self {
+        let since = now - Duration::days(CONTACT_HISTORY_DAYS);
+        let mut sender_name = None;
+        let mut exchanged: Vec<HistoryMessage> = messages
+            .iter()
+            .filter_map(|message| {
+                let sender = message.from.as_deref().and_then(split_sender);
+                let direction = match sender {
+                    Some((address, name)) if address.eq_ignore_ascii_case(email) => {
+                        if sender_name.is_none() {
+                            sender_name = name;
+                        }
+                        "received"
+                    }
+                    _ if message.label_ids.iter().any(|label| label == "SENT")
+                        && CONTACT_HISTORY_HEADERS.iter().any(|header| {
+                            message
+                                .headers
+                                .get(&header.to_lowercase())
+                                .is_some_and(|value| {
+                                    parse_email_addresses(value)
+                                        .iter()
+                                        .any(|address| address.eq_ignore_ascii_case(email))
+                                })
+                        }) =>
+                    {
+                        "sent"
+                    }
+                    _ => return None,
+                };
+                Some(HistoryMessage {
+                    id: message.id.clone(),
+                    thread_id: message.thread_id.clone(),
+                    direction: direction.to_string(),
+                    subject: message.subject.clone(),
+                    snippet: message.snippet.clone(),
+                    received_at: message.received_at,
+                })
+            })
+            .collect();
+        exchanged.sort_by_key(|message| std::cmp::Reverse(message.received_at));
+
+        let count = |direction: &str| {
+            exchanged
+                .iter()
+                .filter(|message| message.direction == direction)
+                .count()
+        };
+        let last = |direction: &str| {
+            exchanged
+                .iter()
+                .find(|message| message.direction == direction)
+                .and_then(|message| message.received_at)
+        };
+        let (received, sent) = (count("received"), count("sent"));
+        let weeks = CONTACT_HISTORY_DAYS as f64 / 7.0;
+        let frequency = EmailFrequency {
+            days: CONTACT_HISTORY_DAYS,
+            received,
+            sent,
+            per_week: ((received + sent) as f64 / weeks * 10.0).round() / 10.0,
+            complete: messages.len() < scan_limit,
+        };
+        let (last_received, last_sent) = (last("received"), last("sent"));
+        exchanged.truncate(max_messages);
+
+        let contact = find_contact_by_email(contacts, email).cloned();
+        let name = contact
+            .as_ref()
+            .and_then(|contact| contact.name.as_ref())
+            .map(|name| name.display_name.clone())
+            .or(sender_name);
+
+        let with_person: Vec<&CalendarEvent> = events
+            .iter()
+            .filter(|event| involves(event, email) && event.end_time > since)
+            .collect();
+        let meetings = with_person
+            .iter()
+            .filter(|event| event.start_time <= now)
+            .count();
+        let last_meeting = with_person
+            .iter()
+            .filter(|event| event.start_time <= now)
+            .max_by_key(|event| event.start_time)
+            .map(|event| (*event).clone());
+        let next_meeting = with_person
+            .iter()
+            .filter(|event| event.start_time > now)
+            .min_by_key(|event| event.start_time)
+            .map(|event| (*event).clone());
+
+        let mut history = Self {
+            email: email.to_string(),
+            name,
+            contact,
+            frequency,
+            last_received,
+            last_sent,
+            recent_messages: exchanged,
+            meetings,
+            last_meeting,
+            next_meeting,
+            summary: String::new(),
+        };
+        history.summary = history.describe();
+        history
+    }
+
+    fn describe(&self) -> String {
+        let who = self.name.clone().unwrap_or_else(|| self.email.clone());
+        let frequency = &self.frequency;
+        let total = frequency.received + frequency.sent;
+        let mut sentences = vec![if total == 0 {
+            format!(
+                "No email exchanged with {} in the last {} days.",
+                who, frequency.days
+            )
+        } else {
+            let total = if frequency.complete {
+                total.to_string()
+            } else {
+                format!("At least {}", total)
+            };
+            format!(
+                "{} emails exchanged with {} in the last {} days ({} received, {} sent), about {} a week.",
+                total, who, frequency.days, frequency.received, frequency.sent, frequency.per_week
+            )
+        }];
+        match &self.last_meeting {
+            Some(event) => sentences.push(format!(
+                "Last met on {} ({}).",
+                event.start_time.format("%Y-%m-%d"),
+                event.summary
+            )),
+            None => sentences.push(format!("No meetings in the last {} days.", frequency.days)),
+        }
+        if let Some(event) = &self.next_meeting {
+            sentences.push(format!(
+                "Next meeting on {} ({}).",
+                event.start_time.format("%Y-%m-%d"),
+                event.summary
+            ));
+        }
+        sentences.join(" ")
+    }
+}
@@ -0,0 +1,90 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+// Contact notes
+//
+// Facts picked up from email can be kept against a person in the notes of
+// their contact, which the People API calls the biography. Each note is
+// appended as a dated line, so the notes read as a log and nothing written
+// there before is lost. The contact is read and written back with its etag,
+// so notes added elsewhere in the meantime are never overwritten.
+
+/// Person fields read to append a note
+pub const CONTACT_NOTE_FIELDS: &[&str] = &["names", "biographies"];
+
+/// Longest note accepted at once
+pub const MAX_CONTACT_NOTE_CHARS: usize = 1000;
+
+/// Check a note and make it a single line
+pub fn parse_note(note: &str) -> Result<String, String> {
+    let note = note.split_whitespace().collect::<Vec<_>>().join(" ");
+    if note.is_empty() {
+        return Err("The note is empty".to_string());
+    }
+    if note.chars().count() > MAX_CONTACT_NOTE_CHARS {
+        return Err(format!(
+            "The note is longer than {} characters",
+            MAX_CONTACT_NOTE_CHARS
+        ));
+    }
+    Ok(note)
+}
+
+/// The notes of a contact as the API returns it
+pub fn current_notes(person: &Value) -> Option<&str> {
+    person["biographies"]
+        .as_array()?
+        .first()?
+        .get("value")?
+        .as_str()
+}
+
+/// The notes with `note` added on a line of its own, dated `date`, or `None`
+/// if the notes already say the same
+pub fn append_note(existing: Option<&str>, note: &str, date: NaiveDate) -> Option<String> {
+    let existing = existing.unwrap_or_default().trim_end();
+    if existing.lines().any(|line| {
+        line.trim() == note
+            || line
+                .split_once(": ")
+                .is_some_and(|(_, text)| text.trim() == note)
+    }) {
+        return None;
+    }
+    let line = format!("{}: {}", date.format("%Y-%m-%d"), note);
+    Some(if existing.is_empty() {
+        line
+    } else {
+        format!("{}\n{}", existing, line)
+    })
+}
+
+/// The update to a contact as the API returns it that sets its notes,
+/// keeping its etag and any other biographies
+pub fn notes_update(person: &Value, notes: &str) -> Value {
+    let mut biographies = person["biographies"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    match biographies.first_mut() {
+        Some(biography) => biography["value"] = json!(notes),
+        None => biographies.push(json!({ "value": notes, "contentType": "TEXT_PLAIN" })),
+    }
+    let mut update = json!({ "biographies": biographies });
+    if let Some(etag) = person.get("etag") {
+        update["etag"] = etag.clone();
+    }
+    update
+}
+
+/// The outcome of adding a note to a contact
+#[derive(Debug, Clone, Serialize)]
+pub struct ContactNoteUpdate {
+    pub resource_name: String,
+    pub name: Option<String>,
+    /// Whether the note was added; it is not when the notes already hold it
+    pub appended: bool,
+    /// The contact's notes now
+    pub notes: String,
+}
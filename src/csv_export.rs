@@ -0,0 +1,189 @@
+use crate::gmail_api::MessageMetadata;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// CSV export
+//
+// Search results can be exported as CSV (RFC 4180) for pivoting in a
+// spreadsheet. Only message metadata is fetched, so large exports stay
+// cheap; whether a message has attachments comes from a second search with
+// `has:attachment` rather than from each message's content.
+
+/// Messages exported when no limit is given
+pub const DEFAULT_CSV_MAX_RESULTS: u32 = 500;
+
+/// Most messages exported at once
+pub const CSV_MAX_RESULTS: u32 = 5000;
+
+/// File name of the export when none is given
+pub const DEFAULT_CSV_FILENAME: &str = "emails.csv";
+
+/// Separator between the names in the labels column
+pub const LABEL_SEPARATOR: &str = "; ";
+
+/// A column of the export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvField {
+    Id,
+    ThreadId,
+    Date,
+    From,
+    To,
+    Subject,
+    Snippet,
+    Labels,
+    HasAttachment,
+}
+
+/// Columns exported when none are asked for
+pub const DEFAULT_CSV_FIELDS: [CsvField; 5] = [
+    CsvField::Date,
+    CsvField::From,
+    CsvField::Subject,
+    CsvField::Labels,
+    CsvField::HasAttachment,
+];
+
+impl CsvField {
+    pub const ALL: [CsvField; 9] = [
+        CsvField::Id,
+        CsvField::ThreadId,
+        CsvField::Date,
+        CsvField::From,
+        CsvField::To,
+        CsvField::Subject,
+        CsvField::Snippet,
+        CsvField::Labels,
+        CsvField::HasAttachment,
+    ];
+
+    /// The column's name, as used in the header row
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Id => "id",
+            Self::ThreadId => "thread_id",
+            Self::Date => "date",
+            Self::From => "from",
+            Self::To => "to",
+            Self::Subject => "subject",
+            Self::Snippet => "snippet",
+            Self::Labels => "labels",
+            Self::HasAttachment => "has_attachment",
+        }
+    }
+
+    /// Parse a column name, ignoring case
+    pub fn parse(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase().replace('-', "_");
+        Self::ALL.into_iter().find(|field| field.name() == name)
+    }
+}
+
+/// The columns asked for, in the order given, or the default ones
+pub fn parse_fields(fields: Option<&[String]>) -> Result<Vec<CsvField>, String> {
+    let fields = match fields.filter(|fields| !fields.is_empty()) {
+        Some(fields) => fields,
+        None => return Ok(DEFAULT_CSV_FIELDS.to_vec()),
+    };
+    let mut parsed = Vec::with_capacity(fields.len());
+    for name in fields {
+        let field = CsvField::parse(name).ok_or_else(|| {
+            let names: Vec<&str> = CsvField::ALL.iter().map(|field| field.name()).collect();
+            format!("Unknown field '{}': use {}", name, names.join(", "))
+        })?;
+        if !parsed.contains(&field) {
+            parsed.push(field);
+        }
+    }
+    Ok(parsed)
+}
+
+/// Characters that make a spreadsheet read a cell as a formula when it
+/// starts with one
+const FORMULA_PREFIXES: [char; 6] = ['=', '+', '-', '@', '\t', '\r'];
+
+/// A value quoted as CSV needs: in double quotes, with quotes doubled, when
+/// it holds a comma, quote or line break. A value a spreadsheet would run as
+/// a formula is prefixed with `'` and quoted, so that it is shown as text.
+pub fn csv_escape(value: &str) -> String {
+    if value.starts_with(FORMULA_PREFIXES) {
+        format!("\"'{}\"", value.replace('"', "\"\""))
+    } else if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// The messages as CSV with a header row, lines ending in CRLF. Label IDs
+/// are written as their names where known.
+pub fn to_csv(
+    fields: &[CsvField],
+    messages: &[MessageMetadata],
+    label_names: &HashMap<String, String>,
+    with_attachments: &HashSet<String>,
+) -> String {
+    let mut csv = String::new();
+    let header: Vec<&str> = fields.iter().map(|field| field.name()).collect();
+    csv.push_str(&header.join(","));
+    csv.push_str("\r\n");
+
+    for message in messages {
+        let row: Vec<String> = fields
+            .iter()
+            .map(|field| {
+                let value = match field {
+                    CsvField::Id => message.id.clone(),
+                    CsvField::ThreadId => message.thread_id.clone(),
+                    CsvField::Date => message
+                        .received_at
+                        .map(|date| date.to_rfc3339())
+                        .unwrap_or_default(),
+                    CsvField::From => message.from.clone().unwrap_or_default(),
+                    CsvField::To => message.headers.get("to").cloned().unwrap_or_default(),
+                    CsvField::Subject => message.subject.clone().unwrap_or_default(),
+                    CsvField::Snippet => message.snippet.clone().unwrap_or_default(),
+                    CsvField::Labels => message
+                        .label_ids
+                        .iter()
+                        .map(|id| label_names.get(id).unwrap_or(id).as_str())
+                        .collect::<Vec<_>>()
+                        .join(LABEL_SEPARATOR),
+                    CsvField::HasAttachment => with_attachments.contains(&message.id).to_string(),
+                };
+                csv_escape(&value)
+            })
+            .collect();
+        csv.push_str(&row.join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+/// Returns the directory CSV exports are written to.
+///
+/// Environment variable: CSV_EXPORT_DIR
+pub fn get_csv_export_dir() -> PathBuf {
+    match std::env::var("CSV_EXPORT_DIR") {
+        Ok(dir) if !dir.trim().is_empty() => PathBuf::from(dir),
+        _ => {
+            let mut dir = dirs::download_dir().unwrap_or_else(std::env::temp_dir);
+            dir.push("gmail-mcp-rs");
+            dir.push("exports");
+            dir
+        }
+    }
+}
+
+/// Write an export, replacing any earlier file of the same name in one step
+pub fn write_csv(path: &Path, csv: &str) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let partial = path.with_extension("csv.partial");
+    let mut file = std::fs::File::create(&partial)?;
+    file.write_all(csv.as_bytes())?;
+    file.sync_all()?;
+    std::fs::rename(&partial, path)
+}
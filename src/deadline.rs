@@ -0,0 +1,89 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// Tool deadlines
+//
+// MCP clients stop waiting for a tool call after a while, and a tool that
+// makes many requests (analyzing a batch of emails, creating drafts for a
+// mail merge, listing a large search) can run past that point, leaving the
+// client with nothing. Each tool call gets a deadline that is passed to the
+// API clients; once it passes they stop issuing requests, and the tool
+// returns the results it already has, marked as timed out.
+
+/// Default time a tool has before it stops issuing requests, in seconds
+pub const DEFAULT_TOOL_TIMEOUT_SECONDS: u64 = 50;
+
+/// The point after which a tool call stops issuing requests
+#[derive(Debug, Clone)]
+pub struct Deadline {
+    at: Option<Instant>,
+    // Shared by clones, so the tool learns that a client stopped early
+    reached: Arc<AtomicBool>,
+}
+
+impl Default for Deadline {
+    fn default() -> Self {
+        Self::never()
+    }
+}
+
+impl Deadline {
+    /// A deadline `timeout` from now
+    pub fn after(timeout: Duration) -> Self {
+        Self {
+            at: Some(Instant::now() + timeout),
+            reached: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// A deadline that never passes
+    pub fn never() -> Self {
+        Self {
+            at: None,
+            reached: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// The deadline for a tool call starting now, as configured
+    pub fn for_tool() -> Self {
+        match get_tool_timeout() {
+            Some(timeout) => Self::after(timeout),
+            None => Self::never(),
+        }
+    }
+
+    /// Time left before the deadline, if it has one
+    pub fn remaining(&self) -> Option<Duration> {
+        self.at
+            .map(|at| at.saturating_duration_since(Instant::now()))
+    }
+
+    /// Whether the deadline has passed. A passed deadline is remembered, so
+    /// call this before each request that could be left out.
+    pub fn expired(&self) -> bool {
+        let expired = self.at.is_some_and(|at| Instant::now() >= at);
+        if expired {
+            self.reached.store(true, Ordering::Relaxed);
+        }
+        expired
+    }
+
+    /// Whether any request was left out because the deadline had passed
+    pub fn was_reached(&self) -> bool {
+        self.reached.load(Ordering::Relaxed)
+    }
+}
+
+/// Returns how long a tool may issue requests for, or None for no limit.
+///
+/// Default is 50 seconds if not configured; 0 turns the limit off.
+///
+/// Environment variable: TOOL_TIMEOUT_SECONDS
+pub fn get_tool_timeout() -> Option<Duration> {
+    let seconds = std::env::var("TOOL_TIMEOUT_SECONDS")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_TOOL_TIMEOUT_SECONDS);
+    (seconds > 0).then(|| Duration::from_secs(seconds))
+}
@@ -0,0 +1,46 @@
+use crate::calendar_api::CalendarEvent;
+use serde::Serialize;
+
+// Declined events
+//
+// Calendar lists events the user has declined along with the rest, so
+// without a look at the attendee list they read like meetings the user will
+// go to. The user's own entry is the attendee marked `self`; events without
+// one, such as those the user created without guests, can't be declined.
+// Listings leave declined events out unless asked for them, and flag them
+// when they are kept.
+
+/// Whether the user has declined the event
+pub fn is_declined(event: &CalendarEvent) -> bool {
+    event.attendees.iter().any(|attendee| {
+        attendee.self_ == Some(true) && attendee.response_status.as_deref() == Some("declined")
+    })
+}
+
+/// An event in a listing, flagged when the user has declined it
+#[derive(Debug, Clone, Serialize)]
+pub struct ListedEvent {
+    #[serde(flatten)]
+    pub event: CalendarEvent,
+    pub declined: bool,
+}
+
+/// Leave out the events the user has declined, unless `include_declined`
+pub fn retain_declined(events: &mut Vec<CalendarEvent>, include_declined: bool) {
+    if !include_declined {
+        events.retain(|event| !is_declined(event));
+    }
+}
+
+/// The events of a listing with their declined flags, leaving out declined
+/// events unless `include_declined`
+pub fn flag_declined(mut events: Vec<CalendarEvent>, include_declined: bool) -> Vec<ListedEvent> {
+    retain_declined(&mut events, include_declined);
+    events
+        .into_iter()
+        .map(|event| ListedEvent {
+            declined: is_declined(&event),
+            event,
+        })
+        .collect()
+}
@@ -0,0 +1,81 @@
+use crate::gmail_api::EmailMessage;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+// Duplicate collapsing
+//
+// Mail sent to several of the user's aliases, or copied under other labels by
+// forwarding rules, can arrive as separate Gmail messages that share one
+// RFC 822 Message-ID. Collapsing keeps the first copy listed of each message,
+// and groups what is left by thread so a conversation reads as one result.
+
+/// The messages of one thread among a set of results
+#[derive(Debug, Clone, Serialize)]
+pub struct ThreadGroup {
+    pub thread_id: String,
+    /// Subject of the first message listed in the thread
+    pub subject: Option<String>,
+    pub message_count: usize,
+    /// The thread's messages in the order they were listed
+    pub messages: Vec<EmailMessage>,
+}
+
+/// Results with duplicate messages collapsed, grouped by thread
+#[derive(Debug, Clone, Serialize)]
+pub struct DedupedMessages {
+    pub messages_listed: usize,
+    pub duplicates_removed: usize,
+    /// Threads in the order their first message was listed
+    pub threads: Vec<ThreadGroup>,
+}
+
+// What makes two messages copies of each other. Messages without a
+// Message-ID header are only duplicates of themselves.
+fn dedupe_key(message: &EmailMessage) -> String {
+    match message
+        .rfc_message_id
+        .as_deref()
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+    {
+        Some(id) => format!("message-id:{}", id),
+        None => format!("gmail-id:{}", message.id),
+    }
+}
+
+impl DedupedMessages {
+    /// Drop all but the first copy of each message and group the rest by
+    /// thread, keeping the order they were listed in
+    pub fn from_messages(messages: Vec<EmailMessage>) -> Self {
+        let messages_listed = messages.len();
+        let mut seen = HashSet::new();
+        let mut thread_index: HashMap<String, usize> = HashMap::new();
+        let mut threads: Vec<ThreadGroup> = Vec::new();
+
+        for message in messages {
+            if !seen.insert(dedupe_key(&message)) {
+                continue;
+            }
+            let index = *thread_index
+                .entry(message.thread_id.clone())
+                .or_insert_with(|| {
+                    threads.push(ThreadGroup {
+                        thread_id: message.thread_id.clone(),
+                        subject: message.subject.clone(),
+                        message_count: 0,
+                        messages: Vec::new(),
+                    });
+                    threads.len() - 1
+                });
+            let thread = &mut threads[index];
+            thread.message_count += 1;
+            thread.messages.push(message);
+        }
+
+        Self {
+            messages_listed,
+            duplicates_removed: messages_listed - seen.len(),
+            threads,
+        }
+    }
+}
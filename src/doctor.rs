@@ -0,0 +1,363 @@
+use crate::auth::TokenManager;
+use crate::config::{get_token_info_url, Config, OAUTH_TOKEN_URL};
+use crate::errors::ConfigError;
+use crate::oauth::{
+    CALENDAR_WRITE_SCOPE, CONTACTS_WRITE_SCOPE, DRIVE_READ_SCOPE, GMAIL_SCOPE, TASKS_SCOPE,
+};
+use crate::token_cache::TokenCacheConfig;
+use chrono::{DateTime, Utc};
+use log::debug;
+use reqwest::Client;
+use serde::Serialize;
+use std::fs::OpenOptions;
+use std::path::Path;
+use std::time::Duration;
+
+/// Google endpoints the server talks to, checked for reachability
+const GOOGLE_ENDPOINTS: &[(&str, &str)] = &[
+    ("Gmail API", "https://gmail.googleapis.com/"),
+    ("Calendar API", "https://www.googleapis.com/"),
+    ("People API", "https://people.googleapis.com/"),
+    ("Tasks API", "https://tasks.googleapis.com/"),
+    ("OAuth token endpoint", OAUTH_TOKEN_URL),
+];
+
+/// Clock skew (in seconds) above which a warning is reported
+pub const CLOCK_SKEW_WARN_SECONDS: i64 = 60;
+
+/// Clock skew (in seconds) above which the check fails; OAuth tokens are
+/// rejected well before this point
+pub const CLOCK_SKEW_FAIL_SECONDS: i64 = 300;
+
+/// Scopes required by each group of MCP tools
+pub const TOOL_SCOPES: &[(&str, &str)] = &[
+    ("Gmail tools", GMAIL_SCOPE),
+    ("Calendar tools", CALENDAR_WRITE_SCOPE),
+    ("Contacts tools", CONTACTS_WRITE_SCOPE),
+    ("Tasks tools", TASKS_SCOPE),
+    ("Drive attachments", DRIVE_READ_SCOPE),
+];
+
+/// Outcome of a single doctor check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+    Skip,
+}
+
+impl CheckStatus {
+    fn symbol(&self) -> &'static str {
+        match self {
+            CheckStatus::Pass => "✅",
+            CheckStatus::Warn => "⚠️",
+            CheckStatus::Fail => "❌",
+            CheckStatus::Skip => "⏭️",
+        }
+    }
+}
+
+/// Result of a single doctor check, with an actionable fix for failures
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResult {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+    pub fix: Option<String>,
+}
+
+impl CheckResult {
+    pub(crate) fn new(name: &str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    pub(crate) fn with_fix(mut self, fix: impl Into<String>) -> Self {
+        self.fix = Some(fix.into());
+        self
+    }
+}
+
+/// Run all diagnostic checks and return their results in order
+pub async fn run_checks() -> Vec<CheckResult> {
+    let mut results = Vec::new();
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(15))
+        .connect_timeout(Duration::from_secs(10))
+        .build()
+        .unwrap_or_else(|_| Client::new());
+
+    // Configuration comes first since the token checks depend on it
+    let config = match Config::from_env() {
+        Ok(config) => {
+            results.push(CheckResult::new(
+                "Configuration",
+                CheckStatus::Pass,
+                "GMAIL_CLIENT_ID, GMAIL_CLIENT_SECRET and GMAIL_REFRESH_TOKEN are set",
+            ));
+            Some(config)
+        }
+        Err(err) => {
+            let fix = match &err {
+                ConfigError::MissingEnvVar(var) => format!(
+                    "Set {} in your .env file or shell, or run `mcp-gmailcal auth` to generate credentials",
+                    var
+                ),
+                ConfigError::EnvError(_) => {
+                    "Check that your .env file is readable and properly formatted".to_string()
+                }
+            };
+            results.push(
+                CheckResult::new("Configuration", CheckStatus::Fail, err.to_string()).with_fix(fix),
+            );
+            None
+        }
+    };
+
+    // Network reachability and clock skew share the same probe requests
+    let mut server_time = None;
+    for (name, url) in GOOGLE_ENDPOINTS {
+        match client.get(*url).send().await {
+            Ok(response) => {
+                if server_time.is_none() {
+                    server_time = response
+                        .headers()
+                        .get(reqwest::header::DATE)
+                        .and_then(|v| v.to_str().ok())
+                        .and_then(parse_http_date);
+                }
+                results.push(CheckResult::new(
+                    &format!("Network: {}", name),
+                    CheckStatus::Pass,
+                    format!("{} reachable (HTTP {})", url, response.status().as_u16()),
+                ));
+            }
+            Err(e) => results.push(
+                CheckResult::new(
+                    &format!("Network: {}", name),
+                    CheckStatus::Fail,
+                    format!("Could not reach {}: {}", url, e),
+                )
+                .with_fix("Check your internet connection, proxy settings and firewall rules"),
+            ),
+        }
+    }
+
+    results.push(match server_time {
+        Some(server_time) => check_clock_skew((Utc::now() - server_time).num_seconds()),
+        None => CheckResult::new(
+            "Clock skew",
+            CheckStatus::Skip,
+            "No Google server time available to compare against",
+        ),
+    });
+
+    // Token validity and scope coverage
+    match config {
+        Some(config) => {
+            let mut token_manager = TokenManager::new(&config);
+            match token_manager.get_token(&client).await {
+                Ok(token) => {
+                    results.push(CheckResult::new(
+                        "Access token",
+                        CheckStatus::Pass,
+                        "Refresh token is valid and an access token was obtained",
+                    ));
+                    results.push(check_scopes(&client, &token).await);
+                }
+                Err(e) => {
+                    results.push(
+                        CheckResult::new("Access token", CheckStatus::Fail, e.to_string())
+                            .with_fix("Your refresh token may be expired or revoked. Run `mcp-gmailcal auth` to obtain a new one"),
+                    );
+                    results.push(CheckResult::new(
+                        "Scope coverage",
+                        CheckStatus::Skip,
+                        "Requires a valid access token",
+                    ));
+                }
+            }
+        }
+        None => {
+            results.push(CheckResult::new(
+                "Access token",
+                CheckStatus::Skip,
+                "Requires a valid configuration",
+            ));
+            results.push(CheckResult::new(
+                "Scope coverage",
+                CheckStatus::Skip,
+                "Requires a valid configuration",
+            ));
+        }
+    }
+
+    // Writable paths for log files and the token cache
+    results.push(check_writable_dir(
+        "Log directory",
+        Path::new("."),
+        "Run from a writable directory, or pass --memory-only to log to stderr only",
+    ));
+
+    if let Ok(cache_config) = TokenCacheConfig::from_env() {
+        if cache_config.enabled {
+            let dir = cache_config
+                .cache_file_path
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| Path::new(".").to_path_buf());
+            results.push(check_writable_dir(
+                "Token cache directory",
+                &dir,
+                "Set TOKEN_CACHE_FILE to a writable location or disable TOKEN_CACHE_ENABLED",
+            ));
+        }
+    }
+
+    results
+}
+
+/// Parse an HTTP `Date` header value (RFC 2822 / RFC 7231 format)
+pub fn parse_http_date(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(value)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Evaluate the difference between the local clock and Google's clock
+pub fn check_clock_skew(skew_seconds: i64) -> CheckResult {
+    let abs = skew_seconds.abs();
+    let detail = format!("Local clock differs from Google by {} seconds", skew_seconds);
+    let fix = "Enable automatic time synchronization (NTP) on this machine";
+
+    if abs >= CLOCK_SKEW_FAIL_SECONDS {
+        CheckResult::new("Clock skew", CheckStatus::Fail, detail).with_fix(fix)
+    } else if abs >= CLOCK_SKEW_WARN_SECONDS {
+        CheckResult::new("Clock skew", CheckStatus::Warn, detail).with_fix(fix)
+    } else {
+        CheckResult::new("Clock skew", CheckStatus::Pass, detail)
+    }
+}
+
+/// Return the tool groups whose required scope is not in the granted scope list
+pub fn missing_scopes(granted: &str) -> Vec<(&'static str, &'static str)> {
+    let granted: Vec<&str> = granted.split_whitespace().collect();
+    TOOL_SCOPES
+        .iter()
+        .filter(|(_, scope)| !granted.contains(scope))
+        .copied()
+        .collect()
+}
+
+// Look up the scopes granted to the token and compare against the tool requirements
+async fn check_scopes(client: &Client, token: &str) -> CheckResult {
+    let response = match client
+        .get(get_token_info_url())
+        .query(&[("access_token", token)])
+        .send()
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => {
+            return CheckResult::new(
+                "Scope coverage",
+                CheckStatus::Warn,
+                format!("Could not query token info: {}", e),
+            )
+        }
+    };
+
+    let info = match response.json::<serde_json::Value>().await {
+        Ok(info) => info,
+        Err(e) => {
+            return CheckResult::new(
+                "Scope coverage",
+                CheckStatus::Warn,
+                format!("Could not parse token info: {}", e),
+            )
+        }
+    };
+
+    let granted = info.get("scope").and_then(|s| s.as_str()).unwrap_or("");
+    debug!("Granted scopes: {}", granted);
+
+    let missing = missing_scopes(granted);
+    if missing.is_empty() {
+        CheckResult::new(
+            "Scope coverage",
+            CheckStatus::Pass,
+            "Token grants all scopes required by the enabled tools",
+        )
+    } else {
+        let detail = missing
+            .iter()
+            .map(|(tools, scope)| format!("{} need {}", tools, scope))
+            .collect::<Vec<_>>()
+            .join("; ");
+        let scopes = missing
+            .iter()
+            .map(|(_, scope)| *scope)
+            .collect::<Vec<_>>()
+            .join(",");
+        CheckResult::new("Scope coverage", CheckStatus::Fail, detail).with_fix(format!(
+            "Run `{}` and grant all requested permissions on the consent screen",
+            crate::utils::scope_fix_command(&scopes)
+        ))
+    }
+}
+
+// Check that a file can be created in the given directory
+fn check_writable_dir(name: &str, dir: &Path, fix: &str) -> CheckResult {
+    let probe = dir.join(format!(".mcp-gmailcal-doctor-{}", std::process::id()));
+    match OpenOptions::new().write(true).create_new(true).open(&probe) {
+        Ok(_) => {
+            let _ = std::fs::remove_file(&probe);
+            CheckResult::new(
+                name,
+                CheckStatus::Pass,
+                format!("{} is writable", dir.display()),
+            )
+        }
+        Err(e) => CheckResult::new(
+            name,
+            CheckStatus::Fail,
+            format!("Cannot write to {}: {}", dir.display(), e),
+        )
+        .with_fix(fix),
+    }
+}
+
+/// Print a human-readable report, returning true if no check failed
+pub fn print_report(results: &[CheckResult]) -> bool {
+    for result in results {
+        println!("{} {}: {}", result.status.symbol(), result.name, result.detail);
+        if let Some(fix) = &result.fix {
+            println!("   ↳ Fix: {}", fix);
+        }
+    }
+
+    let failures = results
+        .iter()
+        .filter(|r| r.status == CheckStatus::Fail)
+        .count();
+    let warnings = results
+        .iter()
+        .filter(|r| r.status == CheckStatus::Warn)
+        .count();
+
+    println!();
+    if failures == 0 {
+        println!("✅ All checks passed ({} warnings)", warnings);
+    } else {
+        println!("❌ {} checks failed, {} warnings", failures, warnings);
+    }
+
+    failures == 0
+}
@@ -0,0 +1,70 @@
+use crate::gmail_api::DraftEmail;
+use crate::priority::PriorityLevel;
+use crate::utils::parse_email_addresses;
+use serde::Deserialize;
+use serde_json::Value;
+
+// Batch drafts
+//
+// Mail-merge style flows, such as personalized follow-ups to several people,
+// make many drafts at once. Each draft is checked on its own, so one that is
+// written wrongly is reported with the rest still created.
+
+/// Most drafts created in one batch
+pub const MAX_BATCH_DRAFTS: usize = 100;
+
+// A draft as given to a batch tool, with the fields create_draft_email takes
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DraftItem {
+    to: String,
+    subject: String,
+    body: String,
+    cc: Option<String>,
+    bcc: Option<String>,
+    thread_id: Option<String>,
+    in_reply_to: Option<String>,
+    references: Option<String>,
+    priority: Option<String>,
+}
+
+/// What identifies an item of a batch in its results: the recipient, or its
+/// position when it has none
+pub fn draft_item_id(index: usize, value: &Value) -> String {
+    value["to"]
+        .as_str()
+        .map(str::trim)
+        .filter(|to| !to.is_empty())
+        .map(String::from)
+        .unwrap_or_else(|| format!("#{}", index + 1))
+}
+
+/// Read a draft from a batch item, which needs "to", "subject" and "body"
+/// and may have the other fields create_draft_email takes
+pub fn parse_draft(value: &Value) -> Result<DraftEmail, String> {
+    let item: DraftItem =
+        serde_json::from_value(value.clone()).map_err(|e| format!("Invalid draft: {}", e))?;
+    if parse_email_addresses(&item.to).is_empty() {
+        return Err(format!("No recipient address in to '{}'", item.to));
+    }
+    let priority = match item.priority {
+        Some(value) => Some(
+            PriorityLevel::parse(&value)
+                .ok_or_else(|| format!("Invalid priority '{}': use high, normal or low", value))?,
+        ),
+        None => None,
+    };
+
+    Ok(DraftEmail {
+        to: item.to,
+        subject: item.subject,
+        body: item.body,
+        cc: item.cc,
+        bcc: item.bcc,
+        thread_id: item.thread_id,
+        in_reply_to: item.in_reply_to,
+        references: item.references,
+        priority,
+        read_receipt_to: None,
+    })
+}
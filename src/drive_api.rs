@@ -0,0 +1,438 @@
+use crate::api::DriveApi;
+use crate::attachments::OutgoingAttachment;
+use crate::auth::TokenManager;
+use crate::config::{get_drive_api_base_url, Config};
+use crate::errors::{retry_after_header, DriveApiError, DriveResult, RateLimitInfo};
+use crate::etag_cache::EtagCache;
+use crate::utils::api_client_builder;
+use log::{debug, info, warn};
+use reqwest::{Client, StatusCode};
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+// Drive files on outgoing emails
+//
+// Drive files can be added to drafts and sent emails by ID. Small files are
+// downloaded and attached. Files too large to attach, and Google Docs,
+// Sheets and Slides (which have no file to download), are added to the body
+// as links instead; since a link is only useful to recipients who can open
+// it, the file's sharing settings are checked against the recipients and
+// anyone left out is reported back to the sender.
+
+// Alias for backward compatibility within this module
+type Result<T> = DriveResult<T>;
+
+/// Default limit on the total size of Drive files attached to one email, in
+/// bytes (10 MiB). Gmail rejects messages over 25 MB, and attachments grow by
+/// a third when encoded.
+pub const DEFAULT_DRIVE_ATTACHMENT_MAX_BYTES: u64 = 10 * 1024 * 1024;
+
+// MIME type prefix of Google Docs, Sheets, Slides and other native files
+const GOOGLE_APPS_MIME_PREFIX: &str = "application/vnd.google-apps.";
+
+/// Returns the most Drive file content attached to one email, in bytes;
+/// files beyond it are linked instead.
+///
+/// Environment variable: DRIVE_ATTACHMENT_MAX_BYTES
+pub fn get_drive_attachment_max_bytes() -> u64 {
+    std::env::var("DRIVE_ATTACHMENT_MAX_BYTES")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_DRIVE_ATTACHMENT_MAX_BYTES)
+}
+
+// Drive file representation
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DriveFile {
+    pub id: String,
+    pub name: String,
+    pub mime_type: String,
+    /// Size in bytes; Google Docs and other native files have none
+    pub size: Option<u64>,
+    pub web_view_link: Option<String>,
+    /// Whether the user may download the file's content
+    pub can_download: bool,
+}
+
+impl DriveFile {
+    /// Whether this is a Google Doc, Sheet, Slides or other native file,
+    /// which can only be shared as a link
+    pub fn is_google_apps_file(&self) -> bool {
+        self.mime_type.starts_with(GOOGLE_APPS_MIME_PREFIX)
+    }
+}
+
+// Drive sharing permission representation
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DrivePermission {
+    /// `user`, `group`, `domain` or `anyone`
+    pub kind: String,
+    pub role: String,
+    pub email_address: Option<String>,
+    pub domain: Option<String>,
+}
+
+/// Storage used by the account, which Gmail, Drive and Photos share
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StorageQuota {
+    /// Bytes available in all; none for unlimited storage
+    pub limit: Option<u64>,
+    /// Bytes used across Gmail, Drive and Photos
+    pub usage: u64,
+    pub usage_in_drive: Option<u64>,
+    pub usage_in_drive_trash: Option<u64>,
+}
+
+impl StorageQuota {
+    /// Share of the limit used, as a percentage to one decimal place
+    pub fn used_percent(&self) -> Option<f64> {
+        self.limit
+            .filter(|limit| *limit > 0)
+            .map(|limit| (self.usage as f64 * 1000.0 / limit as f64).round() / 10.0)
+    }
+}
+
+/// Recipients who cannot open a file shared with the given permissions
+pub fn recipients_without_access(
+    permissions: &[DrivePermission],
+    recipients: &[String],
+) -> Vec<String> {
+    if permissions.iter().any(|p| p.kind == "anyone") {
+        return Vec::new();
+    }
+
+    recipients
+        .iter()
+        .filter(|recipient| {
+            let domain = recipient.rsplit('@').next().unwrap_or_default();
+            !permissions.iter().any(|p| match p.kind.as_str() {
+                "user" | "group" => p
+                    .email_address
+                    .as_deref()
+                    .is_some_and(|email| email.eq_ignore_ascii_case(recipient)),
+                "domain" => p
+                    .domain
+                    .as_deref()
+                    .is_some_and(|d| d.eq_ignore_ascii_case(domain)),
+                _ => false,
+            })
+        })
+        .cloned()
+        .collect()
+}
+
+// Drive API client
+#[derive(Debug, Clone)]
+pub struct DriveClient {
+    client: Client,
+    token_manager: Arc<Mutex<TokenManager>>,
+}
+
+impl DriveClient {
+    pub fn new(config: &Config) -> Self {
+        let client = api_client_builder()
+            .build()
+            .unwrap_or_else(|_| Client::new());
+        // Reuse the Gmail token manager since they share the same OAuth flow
+        let token_manager = Arc::new(Mutex::new(TokenManager::new(config)));
+
+        Self {
+            client,
+            token_manager,
+        }
+    }
+
+    async fn token(&self) -> Result<String> {
+        self.token_manager
+            .lock()
+            .await
+            .get_token(&self.client)
+            .await
+            .map_err(|e| DriveApiError::AuthError(e.to_string()))
+    }
+
+    // Get JSON from the Drive API, turning error statuses into errors
+    async fn get_json(&self, url: &str, file_id: &str, action: &str) -> Result<Value> {
+        let token = self.token().await?;
+        let response = EtagCache::global()
+            .send(
+                self.client
+                    .get(url)
+                    .header("Authorization", format!("Bearer {}", token)),
+            )
+            .await
+            .map_err(|e| DriveApiError::NetworkError(e.to_string()))?;
+
+        if !response.status.is_success() {
+            return Err(status_error(
+                response.status,
+                response.retry_after.as_deref(),
+                &response.body,
+                file_id,
+                action,
+            ));
+        }
+
+        serde_json::from_str(&response.body).map_err(|e| DriveApiError::ParseError(e.to_string()))
+    }
+
+    // Get a file's metadata
+    pub async fn get_file(&self, file_id: &str) -> Result<DriveFile> {
+        let url = format!(
+            "{}/files/{}?fields=id,name,mimeType,size,webViewLink,capabilities/canDownload\
+            &supportsAllDrives=true",
+            get_drive_api_base_url(),
+            file_id
+        );
+        debug!("Getting Drive file: {}", url);
+
+        let json = self.get_json(&url, file_id, "get file").await?;
+        parse_file(&json)
+    }
+
+    // Get who a file is shared with. This needs permission to see the
+    // file's sharing settings, which viewers may not have.
+    pub async fn list_permissions(&self, file_id: &str) -> Result<Vec<DrivePermission>> {
+        let url = format!(
+            "{}/files/{}/permissions?fields=permissions(type,role,emailAddress,domain)\
+            &supportsAllDrives=true",
+            get_drive_api_base_url(),
+            file_id
+        );
+        debug!("Listing Drive permissions: {}", url);
+
+        let json = self.get_json(&url, file_id, "list permissions").await?;
+        Ok(json["permissions"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|permission| DrivePermission {
+                kind: permission["type"].as_str().unwrap_or_default().to_string(),
+                role: permission["role"].as_str().unwrap_or_default().to_string(),
+                email_address: permission["emailAddress"].as_str().map(str::to_string),
+                domain: permission["domain"].as_str().map(str::to_string),
+            })
+            .collect())
+    }
+
+    // Get the account's storage quota
+    pub async fn get_storage_quota(&self) -> Result<StorageQuota> {
+        let url = format!("{}/about?fields=storageQuota", get_drive_api_base_url());
+        debug!("Getting Drive storage quota: {}", url);
+
+        let json = self.get_json(&url, "about", "get storage quota").await?;
+        parse_storage_quota(&json["storageQuota"])
+    }
+
+    // Download a file's content, failing if it is larger than `max_bytes`
+    pub async fn download_file(&self, file_id: &str, max_bytes: u64) -> Result<Vec<u8>> {
+        let token = self.token().await?;
+        let url = format!(
+            "{}/files/{}?alt=media&supportsAllDrives=true",
+            get_drive_api_base_url(),
+            file_id
+        );
+        debug!("Downloading Drive file: {}", url);
+
+        let response = self
+            .client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .await
+            .map_err(|e| DriveApiError::NetworkError(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let retry_after = retry_after_header(response.headers());
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "<no response body>".to_string());
+            return Err(status_error(
+                status,
+                retry_after.as_deref(),
+                &error_text,
+                file_id,
+                "download file",
+            ));
+        }
+
+        let data = response
+            .bytes()
+            .await
+            .map_err(|e| DriveApiError::NetworkError(e.to_string()))?;
+        if data.len() as u64 > max_bytes {
+            return Err(DriveApiError::NotAttachable(format!(
+                "{} is larger than the {} byte limit",
+                file_id, max_bytes
+            )));
+        }
+        Ok(data.to_vec())
+    }
+}
+
+// Map a non-success status to the matching error
+fn status_error(
+    status: StatusCode,
+    retry_after: Option<&str>,
+    body: &str,
+    file_id: &str,
+    action: &str,
+) -> DriveApiError {
+    if let Some(info) = RateLimitInfo::from_response(status.as_u16(), retry_after, body) {
+        return DriveApiError::RateLimited(info);
+    }
+    if status == StatusCode::NOT_FOUND {
+        return DriveApiError::FileNotFound(file_id.to_string());
+    }
+    DriveApiError::ApiError(format!(
+        "Failed to {}. Status: {}, Error: {}",
+        action, status, body
+    ))
+}
+
+fn parse_file(json: &Value) -> Result<DriveFile> {
+    let id = json["id"]
+        .as_str()
+        .ok_or_else(|| DriveApiError::ParseError("Drive file is missing 'id'".to_string()))?;
+
+    Ok(DriveFile {
+        id: id.to_string(),
+        name: json["name"].as_str().unwrap_or(id).to_string(),
+        mime_type: json["mimeType"]
+            .as_str()
+            .unwrap_or("application/octet-stream")
+            .to_string(),
+        size: json_u64(&json["size"]),
+        web_view_link: json["webViewLink"].as_str().map(str::to_string),
+        can_download: json["capabilities"]["canDownload"]
+            .as_bool()
+            .unwrap_or(true),
+    })
+}
+
+/// Drive files prepared for an email
+#[derive(Debug, Default, Serialize)]
+pub struct DriveAttachments {
+    /// Files downloaded and attached to the email
+    pub attached: Vec<DriveFile>,
+    /// Files added to the body as links
+    pub linked: Vec<DriveFile>,
+    /// Problems to show the sender, such as recipients who cannot open a link
+    pub warnings: Vec<String>,
+    /// Content of the attached files
+    #[serde(skip)]
+    pub files: Vec<OutgoingAttachment>,
+}
+
+impl DriveAttachments {
+    /// The email body with links to the linked files appended
+    pub fn body_with_links(&self, body: &str) -> String {
+        if self.linked.is_empty() {
+            return body.to_string();
+        }
+
+        let mut body = body.trim_end().to_string();
+        body.push_str("\n\n");
+        for file in &self.linked {
+            body.push_str(&format!(
+                "{}: {}\n",
+                file.name,
+                file.web_view_link.as_deref().unwrap_or_default()
+            ));
+        }
+        body
+    }
+}
+
+/// Fetch Drive files for an email, attaching those that fit within
+/// `max_bytes` in total and linking the rest.
+///
+/// `recipients` are checked against the sharing settings of linked files;
+/// any who cannot open one are listed in the warnings.
+pub async fn prepare_drive_attachments<D>(
+    drive: &D,
+    file_ids: &[String],
+    recipients: &[String],
+    max_bytes: u64,
+) -> Result<DriveAttachments>
+where
+    D: DriveApi + ?Sized,
+{
+    let mut prepared = DriveAttachments::default();
+    let mut remaining = max_bytes;
+
+    for file_id in file_ids {
+        let file = drive.get_file(file_id).await?;
+
+        let fits = file.size.is_some_and(|size| size <= remaining);
+        if !file.is_google_apps_file() && file.can_download && fits {
+            let data = drive.download_file(&file.id, remaining).await?;
+            remaining -= data.len() as u64;
+            info!("Attaching Drive file {} ({} bytes)", file.id, data.len());
+            prepared.files.push(OutgoingAttachment {
+                filename: file.name.clone(),
+                mime_type: file.mime_type.clone(),
+                data,
+            });
+            prepared.attached.push(file);
+            continue;
+        }
+
+        if file.web_view_link.is_none() {
+            return Err(DriveApiError::NotAttachable(format!(
+                "'{}' is too large to attach and has no link to share",
+                file.name
+            )));
+        }
+
+        match drive.list_permissions(&file.id).await {
+            Ok(permissions) => {
+                let missing = recipients_without_access(&permissions, recipients);
+                if !missing.is_empty() {
+                    prepared.warnings.push(format!(
+                        "{} cannot open '{}'; share it with them before sending",
+                        missing.join(", "),
+                        file.name
+                    ));
+                }
+            }
+            Err(DriveApiError::ApiError(e)) => {
+                warn!("Could not check sharing of Drive file {}: {}", file.id, e);
+                prepared.warnings.push(format!(
+                    "Could not check who can open '{}'; make sure the recipients have access",
+                    file.name
+                ));
+            }
+            Err(e) => return Err(e),
+        }
+
+        info!("Linking Drive file {}", file.id);
+        prepared.linked.push(file);
+    }
+
+    Ok(prepared)
+}
+
+// Drive sends sizes as strings
+fn json_u64(value: &Value) -> Option<u64> {
+    match value {
+        Value::String(number) => number.parse().ok(),
+        number => number.as_u64(),
+    }
+}
+
+fn parse_storage_quota(json: &Value) -> Result<StorageQuota> {
+    let usage = json_u64(&json["usage"])
+        .ok_or_else(|| DriveApiError::ParseError("Storage quota is missing 'usage'".to_string()))?;
+
+    Ok(StorageQuota {
+        limit: json_u64(&json["limit"]),
+        usage,
+        usage_in_drive: json_u64(&json["usageInDrive"]),
+        usage_in_drive_trash: json_u64(&json["usageInDriveTrash"]),
+    })
+}
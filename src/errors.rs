@@ -1,5 +1,9 @@
+use chrono::{DateTime, Duration, FixedOffset, Utc};
 use reqwest;
+use serde::Serialize;
+use serde_json::Value;
 use std::env;
+use std::fmt;
 use thiserror::Error;
 
 /// Error type for configuration issues
@@ -45,9 +49,21 @@ pub enum GmailApiError {
 
     #[error("Rate limit error: {0}")]
     RateLimitError(String),
+
+    #[error("{0}")]
+    RateLimited(RateLimitInfo),
     
     #[error("Token cache error: {0}")]
     CacheError(String),
+
+    #[error("Attachment error: {0}")]
+    AttachmentError(String),
+
+    #[error("{0}")]
+    PolicyViolation(PolicyViolation),
+
+    #[error("Message too large: {0}")]
+    MessageTooLarge(String),
 }
 
 /// Type alias for Gmail API results
@@ -70,11 +86,67 @@ pub enum PeopleApiError {
 
     #[error("Parse error: {0}")]
     ParseError(String),
+
+    #[error("{0}")]
+    RateLimited(RateLimitInfo),
 }
 
 /// Type alias for People API results
 pub type PeopleResult<T> = std::result::Result<T, PeopleApiError>;
 
+/// Error type for Tasks API operations
+#[derive(Debug, Error)]
+pub enum TasksApiError {
+    #[error("Network error: {0}")]
+    NetworkError(String),
+
+    #[error("Authentication error: {0}")]
+    AuthError(String),
+
+    #[error("Tasks API error: {0}")]
+    ApiError(String),
+
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+
+    #[error("Parse error: {0}")]
+    ParseError(String),
+
+    #[error("{0}")]
+    RateLimited(RateLimitInfo),
+}
+
+/// Type alias for Tasks API results
+pub type TasksResult<T> = std::result::Result<T, TasksApiError>;
+
+/// Error type for Drive API operations
+#[derive(Debug, Error)]
+pub enum DriveApiError {
+    #[error("Network error: {0}")]
+    NetworkError(String),
+
+    #[error("Authentication error: {0}")]
+    AuthError(String),
+
+    #[error("Drive API error: {0}")]
+    ApiError(String),
+
+    #[error("Drive file not found: {0}")]
+    FileNotFound(String),
+
+    #[error("Drive file cannot be attached: {0}")]
+    NotAttachable(String),
+
+    #[error("Parse error: {0}")]
+    ParseError(String),
+
+    #[error("{0}")]
+    RateLimited(RateLimitInfo),
+}
+
+/// Type alias for Drive API results
+pub type DriveResult<T> = std::result::Result<T, DriveApiError>;
+
 /// Error type for Calendar API operations
 #[derive(Debug, Error)]
 pub enum CalendarApiError {
@@ -96,6 +168,9 @@ pub enum CalendarApiError {
     #[error("Rate limit error: {0}")]
     RateLimitError(String),
 
+    #[error("{0}")]
+    RateLimited(RateLimitInfo),
+
     #[error("Parse error: {0}")]
     ParseError(String),
 }
@@ -103,6 +178,375 @@ pub enum CalendarApiError {
 /// Type alias for Calendar API results
 pub type CalendarResult<T> = std::result::Result<T, CalendarApiError>;
 
+/// Error type for the local message index
+#[derive(Debug, Error)]
+pub enum LocalIndexError {
+    #[error("Local index database error: {0}")]
+    DatabaseError(String),
+
+    #[error("Query cannot be answered from the local index: {0}")]
+    UnsupportedQuery(String),
+
+    #[error("Local index sync failed: {0}")]
+    SyncError(#[from] GmailApiError),
+}
+
+/// Type alias for local index results
+pub type LocalIndexResult<T> = std::result::Result<T, LocalIndexError>;
+
+/// Error type for the email template library
+#[derive(Debug, Error)]
+pub enum TemplateError {
+    #[error("Template not found: {0}")]
+    NotFound(String),
+
+    #[error("Invalid template: {0}")]
+    InvalidTemplate(String),
+
+    #[error("Missing template variables: {}", .0.join(", "))]
+    MissingVariables(Vec<String>),
+
+    #[error("Template storage error: {0}")]
+    StorageError(String),
+}
+
+/// Type alias for template results
+pub type TemplateResult<T> = std::result::Result<T, TemplateError>;
+
+/// Error type for the calendar event rules
+#[derive(Debug, Error)]
+pub enum RuleError {
+    #[error("Event rule not found: {0}")]
+    NotFound(String),
+
+    #[error("Invalid event rule: {0}")]
+    InvalidRule(String),
+
+    #[error("Event rule storage error: {0}")]
+    StorageError(String),
+}
+
+/// Type alias for event rule results
+pub type RuleResult<T> = std::result::Result<T, RuleError>;
+
+/// Error type for the mail rules
+#[derive(Debug, Error)]
+pub enum MailRuleError {
+    #[error("Invalid mail rule: {0}")]
+    InvalidRule(String),
+
+    #[error("Mail rule storage error: {0}")]
+    StorageError(String),
+}
+
+/// Type alias for mail rule results
+pub type MailRuleResult<T> = std::result::Result<T, MailRuleError>;
+
+/// Error type for saved searches
+#[derive(Debug, Error)]
+pub enum SavedSearchError {
+    #[error("Saved search not found: {0}")]
+    NotFound(String),
+
+    #[error("Invalid saved search: {0}")]
+    InvalidSearch(String),
+
+    #[error("Saved search storage error: {0}")]
+    StorageError(String),
+}
+
+/// Type alias for saved search results
+pub type SavedSearchResult<T> = std::result::Result<T, SavedSearchError>;
+
+/// Error type for claiming an idempotency key; holds the tool and the key
+#[derive(Debug, Error)]
+pub enum IdempotencyError {
+    #[error("A {0} call with idempotency_key '{1}' is still running; retry once it finishes")]
+    InProgress(String, String),
+
+    #[error("idempotency_key '{1}' was already used for a {0} call with different arguments; use a new key")]
+    Conflict(String, String),
+}
+
+/// Tool input that failed validation, found before any request is made
+#[derive(Debug, Error)]
+#[error("{}", describe_invalid_fields(.0))]
+pub struct ValidationError(pub Vec<InvalidField>);
+
+/// One input field that failed validation, and why
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InvalidField {
+    pub field: String,
+    pub problem: String,
+}
+
+fn describe_invalid_fields(fields: &[InvalidField]) -> String {
+    fields
+        .iter()
+        .map(|invalid| format!("Invalid {}: {}", invalid.field, invalid.problem))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// An email the sending policy doesn't allow to be sent
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum PolicyViolation {
+    #[error("Sending policy: {count} recipients is more than the {max} allowed in one email")]
+    TooManyRecipients { count: usize, max: usize },
+
+    #[error(
+        "Sending policy: {} not in the allowed recipient domains ({})",
+        .addresses.join(", "),
+        .allowed.join(", ")
+    )]
+    DomainNotAllowed {
+        addresses: Vec<String>,
+        allowed: Vec<String>,
+    },
+
+    #[error(
+        "Sending policy: the limit of {max} emails in 24 hours is reached; \
+        the next can be sent in {retry_after} seconds"
+    )]
+    DailyLimitReached { max: usize, retry_after: u64 },
+
+    #[error(
+        "Sending policy: emails with only BCC recipients are not allowed; add a To or CC recipient"
+    )]
+    BccOnly,
+}
+
+impl PolicyViolation {
+    /// Name of the rule that was broken
+    pub fn rule(&self) -> &'static str {
+        match self {
+            PolicyViolation::TooManyRecipients { .. } => "max_recipients",
+            PolicyViolation::DomainNotAllowed { .. } => "allowed_domains",
+            PolicyViolation::DailyLimitReached { .. } => "daily_limit",
+            PolicyViolation::BccOnly => "bcc_only",
+        }
+    }
+}
+
+/// Any error from the API clients, configuration, local index, templates,
+/// event rules, input validation or the sending policy, so that callers can
+/// handle them in one place
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+
+    #[error(transparent)]
+    Gmail(#[from] GmailApiError),
+
+    #[error(transparent)]
+    Calendar(#[from] CalendarApiError),
+
+    #[error(transparent)]
+    People(#[from] PeopleApiError),
+
+    #[error(transparent)]
+    Tasks(#[from] TasksApiError),
+
+    #[error(transparent)]
+    Drive(#[from] DriveApiError),
+
+    #[error(transparent)]
+    LocalIndex(#[from] LocalIndexError),
+
+    #[error(transparent)]
+    Template(#[from] TemplateError),
+
+    #[error(transparent)]
+    Rule(#[from] RuleError),
+
+    #[error(transparent)]
+    MailRule(#[from] MailRuleError),
+
+    #[error(transparent)]
+    SavedSearch(#[from] SavedSearchError),
+
+    #[error(transparent)]
+    Validation(#[from] ValidationError),
+
+    #[error(transparent)]
+    Policy(#[from] PolicyViolation),
+}
+
+/// Type alias for results that may fail with any crate error
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Which Google quota a rate limit error applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaScope {
+    /// The project's daily quota, which resets at midnight Pacific Time
+    Daily,
+    /// The per-user rate limit, which recovers within a minute or so
+    PerUser,
+    /// The project's overall rate limit
+    Project,
+    /// The response did not say which limit was hit
+    Unknown,
+}
+
+impl QuotaScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuotaScope::Daily => "daily",
+            QuotaScope::PerUser => "per_user",
+            QuotaScope::Project => "project",
+            QuotaScope::Unknown => "unknown",
+        }
+    }
+}
+
+/// Details of a 429 or quota error response from a Google API
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimitInfo {
+    pub scope: QuotaScope,
+    /// Seconds to wait from the `Retry-After` header, when sent
+    pub retry_after: Option<u64>,
+    /// The `reason` Google gave, such as `userRateLimitExceeded`
+    pub reason: Option<String>,
+    pub message: String,
+}
+
+impl RateLimitInfo {
+    /// Parse an error response, returning `None` unless it is a rate limit or
+    /// quota error. Google reports these as 429, or as 403 with a quota reason.
+    pub fn from_response(status: u16, retry_after: Option<&str>, body: &str) -> Option<Self> {
+        let error = serde_json::from_str::<Value>(body)
+            .ok()
+            .and_then(|value| value.get("error").cloned())
+            .unwrap_or(Value::Null);
+        let reasons: Vec<&str> = error["errors"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .chain(error["details"].as_array().into_iter().flatten())
+            .filter_map(|e| e["reason"].as_str())
+            .collect();
+        let message = error["message"]
+            .as_str()
+            .unwrap_or(body)
+            .trim()
+            .to_string();
+        let quota_limit = error["details"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|d| d["metadata"]["quota_limit"].as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let is_quota_reason = reasons.iter().any(|r| {
+            matches!(
+                *r,
+                "rateLimitExceeded"
+                    | "userRateLimitExceeded"
+                    | "dailyLimitExceeded"
+                    | "quotaExceeded"
+                    | "RATE_LIMIT_EXCEEDED"
+            )
+        });
+        if status != 429 && !(status == 403 && is_quota_reason) {
+            return None;
+        }
+
+        let text = format!("{} {}", message, quota_limit).to_lowercase();
+        let scope = if reasons.contains(&"dailyLimitExceeded")
+            || text.contains("per day")
+            || text.contains("perday")
+            || text.contains("daily")
+        {
+            QuotaScope::Daily
+        } else if reasons.contains(&"userRateLimitExceeded")
+            || text.contains("per user")
+            || text.contains("peruser")
+            || text.contains("user-rate")
+            || text.contains("user rate")
+        {
+            QuotaScope::PerUser
+        } else if !reasons.is_empty() {
+            QuotaScope::Project
+        } else {
+            QuotaScope::Unknown
+        };
+
+        Some(Self {
+            scope,
+            retry_after: retry_after.and_then(|value| parse_retry_after(value, Utc::now())),
+            reason: reasons.first().map(|r| r.to_string()),
+            message,
+        })
+    }
+
+    /// Seconds to wait before retrying: the server's `Retry-After` when sent,
+    /// otherwise the time until the daily quota resets or a short default
+    pub fn recommended_wait(&self) -> u64 {
+        self.retry_after.unwrap_or_else(|| match self.scope {
+            QuotaScope::Daily => seconds_until_quota_reset(Utc::now()),
+            _ => crate::utils::DEFAULT_RATE_LIMIT_RETRY_SECONDS,
+        })
+    }
+}
+
+impl fmt::Display for RateLimitInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Rate limit exceeded ({} quota): {}",
+            self.scope.as_str(),
+            self.message
+        )
+    }
+}
+
+/// Whether an error response says the access token was not granted a scope
+/// the request needs. Google reports this as 403 with the reason
+/// `insufficientPermissions`, or `ACCESS_TOKEN_SCOPE_INSUFFICIENT` in newer
+/// APIs.
+pub fn is_insufficient_scope(error_text: &str) -> bool {
+    error_text.contains("insufficientPermissions")
+        || error_text.contains("ACCESS_TOKEN_SCOPE_INSUFFICIENT")
+        || error_text.contains("insufficient authentication scopes")
+}
+
+/// The raw `Retry-After` header of a response, if any
+pub fn retry_after_header(headers: &reqwest::header::HeaderMap) -> Option<String> {
+    headers
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string())
+}
+
+/// Parse a `Retry-After` header given in seconds or as an HTTP date
+pub fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<u64> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(seconds);
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some((date.with_timezone(&Utc) - now).num_seconds().max(0) as u64)
+}
+
+/// Seconds until Google's daily quotas reset at midnight Pacific Time.
+///
+/// Pacific Standard Time is used all year, so during daylight saving time
+/// this is an hour later than the actual reset.
+pub fn seconds_until_quota_reset(now: DateTime<Utc>) -> u64 {
+    let pacific = FixedOffset::west_opt(8 * 3600).expect("valid offset");
+    let local = now.with_timezone(&pacific);
+    let midnight = (local.date_naive() + Duration::days(1))
+        .and_hms_opt(0, 0, 0)
+        .expect("valid time");
+    let reset = midnight
+        .and_local_timezone(pacific)
+        .single()
+        .expect("fixed offsets are unambiguous");
+    (reset.with_timezone(&Utc) - now).num_seconds().max(0) as u64
+}
+
 /// MCP error codes for different error scenarios
 pub mod error_codes {
     // General errors
@@ -140,6 +584,18 @@ impl From<reqwest::Error> for PeopleApiError {
     }
 }
 
+impl From<reqwest::Error> for TasksApiError {
+    fn from(err: reqwest::Error) -> Self {
+        TasksApiError::NetworkError(format!("Network error: {}", err))
+    }
+}
+
+impl From<reqwest::Error> for DriveApiError {
+    fn from(err: reqwest::Error) -> Self {
+        DriveApiError::NetworkError(format!("Network error: {}", err))
+    }
+}
+
 impl From<reqwest::Error> for CalendarApiError {
     fn from(err: reqwest::Error) -> Self {
         CalendarApiError::NetworkError(format!("Network error: {}", err))
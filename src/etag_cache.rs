@@ -0,0 +1,359 @@
+use crate::errors::retry_after_header;
+use log::{debug, warn};
+use reqwest::header::{ETAG, IF_NONE_MATCH};
+use reqwest::{Method, RequestBuilder, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+// Conditional request cache
+//
+// Contacts, calendar lists and label settings rarely change, but they are
+// fetched on almost every tool call. Google returns an ETag with these
+// resources; sending it back as `If-None-Match` gets an empty 304 response
+// when nothing changed, which is cheaper in both bandwidth and quota. The
+// cache keeps the last body seen for each URL so that a 304 can be answered
+// locally. With a state directory set, the cache is saved there so that it
+// survives restarts.
+//
+// The saved file holds the URLs requested and the bodies returned for them:
+// contact details, calendar names and label settings. It is not encrypted,
+// so it is written readable only by its owner (mode 0600 on Unix). Changes
+// are saved together a moment after the first one, off the async runtime,
+// so the last changes before the server exits may not be saved.
+
+/// Default number of responses kept in the cache
+pub const DEFAULT_ETAG_CACHE_ENTRIES: usize = 256;
+
+/// Name of the file the shared cache is saved to in the state directory
+pub const ETAG_CACHE_FILE: &str = "etag-cache.json";
+
+/// How long after a change the cache is saved, so that the changes made in
+/// the meantime are saved with it
+pub const ETAG_CACHE_SAVE_DELAY: Duration = Duration::from_millis(500);
+
+struct Entry {
+    etag: String,
+    body: String,
+    last_used: u64,
+}
+
+// A cached response as saved to disk
+#[derive(Serialize, Deserialize)]
+struct SavedEntry {
+    key: String,
+    etag: String,
+    body: String,
+}
+
+/// A response from `EtagCache::send`
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    /// The status of the resource, `200 OK` when served from the cache
+    pub status: StatusCode,
+    pub body: String,
+    /// True when the server answered 304 Not Modified
+    pub from_cache: bool,
+    /// The `Retry-After` header, kept for rate limit errors
+    pub retry_after: Option<String>,
+}
+
+/// Hit and miss counts for an `EtagCache`
+#[derive(Serialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct EtagCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+}
+
+/// Response bodies keyed by request, revalidated with their ETags
+pub struct EtagCache {
+    entries: Arc<Mutex<HashMap<String, Entry>>>,
+    capacity: usize,
+    clock: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    /// File the cache is saved to after it changes
+    file: Option<PathBuf>,
+    /// Set while changes are waiting to be saved
+    save_pending: Arc<AtomicBool>,
+}
+
+impl EtagCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(HashMap::new())),
+            capacity,
+            clock: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            file: None,
+            save_pending: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// A cache saved to `file`, starting from the responses saved there.
+    ///
+    /// A missing or unreadable file starts an empty cache.
+    pub fn with_file(capacity: usize, file: PathBuf) -> Self {
+        let mut cache = Self::new(capacity);
+        match std::fs::read_to_string(&file) {
+            Ok(contents) => match serde_json::from_str::<Vec<SavedEntry>>(&contents) {
+                Ok(saved) => {
+                    // Saved least recently used first, so keep the newest
+                    let skip = saved.len().saturating_sub(capacity);
+                    let mut entries = cache.lock();
+                    for (last_used, entry) in saved.into_iter().skip(skip).enumerate() {
+                        entries.insert(
+                            entry.key,
+                            Entry {
+                                etag: entry.etag,
+                                body: entry.body,
+                                last_used: last_used as u64,
+                            },
+                        );
+                    }
+                    cache.clock.store(entries.len() as u64, Ordering::Relaxed);
+                    debug!(
+                        "Loaded {} cached responses from {}",
+                        entries.len(),
+                        file.display()
+                    );
+                    drop(entries);
+                }
+                Err(e) => warn!("Ignoring unreadable ETag cache {}: {}", file.display(), e),
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => warn!("Could not read ETag cache {}: {}", file.display(), e),
+        }
+        cache.file = Some(file);
+        cache
+    }
+
+    /// The cache shared by the API clients, saved in the state directory
+    /// when one is set
+    pub fn global() -> &'static EtagCache {
+        static CACHE: OnceLock<EtagCache> = OnceLock::new();
+        CACHE.get_or_init(|| match crate::config::get_state_dir() {
+            Some(dir) => EtagCache::with_file(get_etag_cache_entries(), dir.join(ETAG_CACHE_FILE)),
+            None => EtagCache::new(get_etag_cache_entries()),
+        })
+    }
+
+    /// Send a request, revalidating a cached response when there is one.
+    ///
+    /// Only GET requests are cached. Error statuses are returned as-is for the
+    /// caller to map.
+    pub async fn send(&self, request: RequestBuilder) -> reqwest::Result<CachedResponse> {
+        let (client, request) = request.build_split();
+        let mut request = request?;
+
+        if request.method() != Method::GET || !is_etag_cache_enabled() {
+            return read_response(client.execute(request).await?).await;
+        }
+
+        let key = request.url().to_string();
+        if let Some(etag) = self.cached_etag(&key) {
+            if let Ok(value) = etag.parse() {
+                request.headers_mut().insert(IF_NONE_MATCH, value);
+            }
+        }
+
+        let response = client.execute(request).await?;
+        if response.status() == StatusCode::NOT_MODIFIED {
+            if let Some(body) = self.cached_body(&key) {
+                debug!("Not modified, using cached response for {}", key);
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(CachedResponse {
+                    status: StatusCode::OK,
+                    body,
+                    from_cache: true,
+                    retry_after: None,
+                });
+            }
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let header_etag = response
+            .headers()
+            .get(ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string());
+        let response = read_response(response).await?;
+
+        if response.status.is_success() {
+            // Some Google resources carry their ETag in the body instead
+            let etag = header_etag.or_else(|| body_etag(&response.body));
+            match etag {
+                Some(etag) => self.store(key, etag, response.body.clone()),
+                None => self.remove(&key),
+            }
+        }
+
+        Ok(response)
+    }
+
+    pub fn stats(&self) -> EtagCacheStats {
+        EtagCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.lock().len(),
+        }
+    }
+
+    /// Drop all cached responses
+    pub fn clear(&self) {
+        self.lock().clear();
+        self.save();
+    }
+
+    /// Save changes still waiting to be saved now, if the cache has a file
+    pub fn flush(&self) {
+        if let Some(file) = &self.file {
+            if self.save_pending.swap(false, Ordering::AcqRel) {
+                save_entries(&self.entries, file);
+            }
+        }
+    }
+
+    // Schedule saving the cache to its file, if it has one, unless a save is
+    // already waiting. Outside a Tokio runtime it is saved straight away.
+    fn save(&self) {
+        let Some(file) = &self.file else {
+            return;
+        };
+        if self.save_pending.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            self.flush();
+            return;
+        };
+        let entries = Arc::clone(&self.entries);
+        let pending = Arc::clone(&self.save_pending);
+        let file = file.clone();
+        runtime.spawn(async move {
+            tokio::time::sleep(ETAG_CACHE_SAVE_DELAY).await;
+            let _ = tokio::task::spawn_blocking(move || {
+                // Already saved by `flush` when no longer pending
+                if pending.swap(false, Ordering::AcqRel) {
+                    save_entries(&entries, &file);
+                }
+            })
+            .await;
+        });
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Entry>> {
+        // The map is always left consistent, so a poisoned lock is still usable
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::Relaxed)
+    }
+
+    fn cached_etag(&self, key: &str) -> Option<String> {
+        self.lock().get(key).map(|entry| entry.etag.clone())
+    }
+
+    fn cached_body(&self, key: &str) -> Option<String> {
+        let now = self.tick();
+        let mut entries = self.lock();
+        let entry = entries.get_mut(key)?;
+        entry.last_used = now;
+        Some(entry.body.clone())
+    }
+
+    fn store(&self, key: String, etag: String, body: String) {
+        if self.capacity == 0 {
+            return;
+        }
+        let now = self.tick();
+        let mut entries = self.lock();
+        if !entries.contains_key(&key) && entries.len() >= self.capacity {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(
+            key,
+            Entry {
+                etag,
+                body,
+                last_used: now,
+            },
+        );
+        drop(entries);
+        self.save();
+    }
+
+    fn remove(&self, key: &str) {
+        if self.lock().remove(key).is_some() {
+            self.save();
+        }
+    }
+}
+
+// Write the entries to `file`, least recently used first. Saving is best
+// effort: a cache that can't be saved still works for this run.
+fn save_entries(entries: &Mutex<HashMap<String, Entry>>, file: &Path) {
+    let saved = {
+        let entries = entries.lock().unwrap_or_else(|e| e.into_inner());
+        let mut saved: Vec<(&String, &Entry)> = entries.iter().collect();
+        saved.sort_by_key(|(_, entry)| entry.last_used);
+        let saved: Vec<SavedEntry> = saved
+            .into_iter()
+            .map(|(key, entry)| SavedEntry {
+                key: key.clone(),
+                etag: entry.etag.clone(),
+                body: entry.body.clone(),
+            })
+            .collect();
+        saved
+    };
+    if let Err(e) = crate::persist::store_json(file, &saved) {
+        warn!("Could not save ETag cache to {}: {}", file.display(), e);
+    }
+}
+
+async fn read_response(response: reqwest::Response) -> reqwest::Result<CachedResponse> {
+    let retry_after = retry_after_header(response.headers());
+    Ok(CachedResponse {
+        status: response.status(),
+        body: response.text().await?,
+        from_cache: false,
+        retry_after,
+    })
+}
+
+fn body_etag(body: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(body).ok()?;
+    value.get("etag")?.as_str().map(|s| s.to_string())
+}
+
+/// Returns whether conditional requests are enabled (default true).
+///
+/// Environment variable: ETAG_CACHE_ENABLED
+pub fn is_etag_cache_enabled() -> bool {
+    std::env::var("ETAG_CACHE_ENABLED")
+        .map(|s| s.to_lowercase() == "true" || s == "1")
+        .unwrap_or(true)
+}
+
+/// Returns the number of responses kept in the shared cache.
+///
+/// Environment variable: ETAG_CACHE_ENTRIES
+pub fn get_etag_cache_entries() -> usize {
+    std::env::var("ETAG_CACHE_ENTRIES")
+        .ok()
+        .and_then(|s| s.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_ETAG_CACHE_ENTRIES)
+}
@@ -0,0 +1,76 @@
+use crate::calendar_api::{
+    CalendarEvent, CATEGORY_PROPERTY, CREATED_BY_PROPERTY, CREATED_BY_VALUE,
+    SOURCE_MESSAGE_PROPERTY,
+};
+use std::collections::HashMap;
+
+// Event properties
+//
+// Private extended properties are key-value pairs kept on an event that only
+// the calendar they were set on can see. The server tags every event it
+// creates with createdBy=mcp-gmailcal, so its own events can be listed apart
+// from the rest, and links an event made from an email to the message with
+// sourceMessageId. Other properties can be set to tag events for later
+// searches.
+
+/// Longest key Calendar keeps, in characters
+pub const MAX_PROPERTY_KEY_LENGTH: usize = 44;
+
+/// Longest value Calendar keeps, in characters
+pub const MAX_PROPERTY_VALUE_LENGTH: usize = 1024;
+
+/// Keys the server manages, which can't be set by hand
+pub const RESERVED_PROPERTIES: [&str; 2] = [CATEGORY_PROPERTY, CREATED_BY_PROPERTY];
+
+/// Check properties to be set on an event
+pub fn validate_properties(properties: &HashMap<String, String>) -> Result<(), String> {
+    if properties.is_empty() {
+        return Err("No properties given".to_string());
+    }
+    for (key, value) in properties {
+        if key.trim().is_empty() {
+            return Err("Property keys can't be empty".to_string());
+        }
+        if RESERVED_PROPERTIES.contains(&key.as_str()) {
+            return Err(format!(
+                "Property '{}' is managed by the server and can't be set",
+                key
+            ));
+        }
+        if key.chars().count() > MAX_PROPERTY_KEY_LENGTH {
+            return Err(format!(
+                "Property key '{}' is longer than {} characters",
+                key, MAX_PROPERTY_KEY_LENGTH
+            ));
+        }
+        if value.chars().count() > MAX_PROPERTY_VALUE_LENGTH {
+            return Err(format!(
+                "The value of property '{}' is longer than {} characters",
+                key, MAX_PROPERTY_VALUE_LENGTH
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// The properties that link an event to the email it came from
+pub fn source_message_properties(message_id: &str) -> HashMap<String, String> {
+    HashMap::from([(SOURCE_MESSAGE_PROPERTY.to_string(), message_id.to_string())])
+}
+
+/// The ID of the email an event was created from, if it was linked to one
+pub fn source_message_id(event: &CalendarEvent) -> Option<&str> {
+    event
+        .private_properties
+        .get(SOURCE_MESSAGE_PROPERTY)
+        .map(String::as_str)
+        .filter(|id| !id.trim().is_empty())
+}
+
+/// The filter that lists only the events this server created
+pub fn created_by_server_filter() -> Vec<(String, String)> {
+    vec![(
+        CREATED_BY_PROPERTY.to_string(),
+        CREATED_BY_VALUE.to_string(),
+    )]
+}
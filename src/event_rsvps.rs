@@ -0,0 +1,132 @@
+use crate::calendar_api::CalendarEvent;
+use crate::people_api::{find_contact_by_email, Contact};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+// Event RSVPs
+//
+// Organizers chasing responses need to know who has answered an invitation
+// and how. Attendees are grouped by response status, with names taken from
+// their contact records, or from the event when they are not contacts.
+// Rooms and other resources are booked rather than invited, so they are
+// left out.
+
+/// Most attendees whose contact records are looked up
+pub const RSVP_MAX_CONTACTS: usize = 50;
+
+/// Domain of the addresses Calendar gives rooms and other resources
+pub const RESOURCE_DOMAIN: &str = "resource.calendar.google.com";
+
+/// Whether an attendee address belongs to a room or other resource
+pub fn is_resource(email: &str) -> bool {
+    email
+        .rsplit_once('@')
+        .is_some_and(|(_, domain)| domain.eq_ignore_ascii_case(RESOURCE_DOMAIN))
+}
+
+/// Addresses of the people invited to an event, in the order it lists them
+pub fn invitee_addresses(event: &CalendarEvent) -> Vec<String> {
+    event
+        .attendees
+        .iter()
+        .map(|attendee| attendee.email.to_lowercase())
+        .filter(|email| !is_resource(email))
+        .collect()
+}
+
+/// Someone invited to the event
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RsvpAttendee {
+    pub email: String,
+    pub name: Option<String>,
+    pub optional: bool,
+    pub organizer: bool,
+}
+
+/// Number of attendees with each response
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RsvpCounts {
+    pub accepted: usize,
+    pub declined: usize,
+    pub tentative: usize,
+    pub no_response: usize,
+    pub total: usize,
+}
+
+/// The responses to an event's invitation
+#[derive(Debug, Clone, Serialize)]
+pub struct RsvpReport {
+    pub event_id: Option<String>,
+    pub summary: String,
+    pub start_time: DateTime<Utc>,
+    pub counts: RsvpCounts,
+    pub accepted: Vec<RsvpAttendee>,
+    pub declined: Vec<RsvpAttendee>,
+    pub tentative: Vec<RsvpAttendee>,
+    pub no_response: Vec<RsvpAttendee>,
+    /// Addresses of required attendees who have not responded, to chase
+    pub awaiting_response: Vec<String>,
+}
+
+impl RsvpReport {
+    /// Group an event's attendees by response, naming them from the contact
+    /// search results where possible
+    pub fn new(event: &CalendarEvent, contacts: &[Contact]) -> Self {
+        let mut report = Self {
+            event_id: event.id.clone(),
+            summary: event.summary.clone(),
+            start_time: event.start_time,
+            counts: RsvpCounts::default(),
+            accepted: Vec::new(),
+            declined: Vec::new(),
+            tentative: Vec::new(),
+            no_response: Vec::new(),
+            awaiting_response: Vec::new(),
+        };
+
+        for attendee in &event.attendees {
+            if is_resource(&attendee.email) {
+                continue;
+            }
+            let email = attendee.email.to_lowercase();
+            let name = find_contact_by_email(contacts, &email)
+                .and_then(|contact| contact.name.as_ref())
+                .map(|name| name.display_name.clone())
+                .or_else(|| attendee.display_name.clone());
+            let rsvp = RsvpAttendee {
+                organizer: event
+                    .organizer
+                    .as_ref()
+                    .is_some_and(|organizer| organizer.email.eq_ignore_ascii_case(&email)),
+                optional: attendee.optional.unwrap_or(false),
+                email,
+                name,
+            };
+
+            report.counts.total += 1;
+            match attendee.response_status.as_deref() {
+                Some("accepted") => {
+                    report.counts.accepted += 1;
+                    report.accepted.push(rsvp);
+                }
+                Some("declined") => {
+                    report.counts.declined += 1;
+                    report.declined.push(rsvp);
+                }
+                Some("tentative") => {
+                    report.counts.tentative += 1;
+                    report.tentative.push(rsvp);
+                }
+                // "needsAction", or no status at all
+                _ => {
+                    report.counts.no_response += 1;
+                    if !rsvp.optional {
+                        report.awaiting_response.push(rsvp.email.clone());
+                    }
+                    report.no_response.push(rsvp);
+                }
+            }
+        }
+        report
+    }
+}
@@ -0,0 +1,355 @@
+use crate::calendar_api::{CalendarEvent, CATEGORY_PROPERTY};
+use crate::errors::{RuleError, RuleResult};
+use crate::event_rsvps::is_resource;
+use crate::persist;
+use chrono::{DateTime, Utc};
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+
+// Event rules
+//
+// Rules color and categorize calendar events by what they are about, such as
+// making every 1:1 green or labelling customer calls. They are kept in a JSON
+// file in the user's config directory, in order. A rule matches an event by
+// keywords in its title or description, by its attendees, or by how many
+// people are in it, and every condition given must hold. The first rule
+// matching an event decides its color and category. The category is kept in
+// the event's private extended properties, so only the user sees it.
+
+/// Days of events categorized when no end is given
+pub const DEFAULT_CATEGORIZE_DAYS: i64 = 30;
+
+/// Most events categorized in one calendar at a time
+pub const CATEGORIZE_MAX_EVENTS: u32 = 250;
+
+/// Calendar event colors, by ID and name
+pub const EVENT_COLORS: [(&str, &str); 11] = [
+    ("1", "lavender"),
+    ("2", "sage"),
+    ("3", "grape"),
+    ("4", "flamingo"),
+    ("5", "banana"),
+    ("6", "tangerine"),
+    ("7", "peacock"),
+    ("8", "graphite"),
+    ("9", "blueberry"),
+    ("10", "basil"),
+    ("11", "tomato"),
+];
+
+// Everyday color names for the palette's colors
+const COLOR_ALIASES: [(&str, &str); 11] = [
+    ("purple", "3"),
+    ("pink", "4"),
+    ("yellow", "5"),
+    ("orange", "6"),
+    ("turquoise", "7"),
+    ("cyan", "7"),
+    ("gray", "8"),
+    ("grey", "8"),
+    ("blue", "9"),
+    ("green", "10"),
+    ("red", "11"),
+];
+
+/// The color ID for a color ID, palette name or everyday color name
+pub fn parse_color(color: &str) -> Option<&'static str> {
+    let color = color.trim().to_lowercase();
+    EVENT_COLORS
+        .iter()
+        .find(|(id, name)| *id == color || *name == color)
+        .map(|(id, _)| *id)
+        .or_else(|| {
+            COLOR_ALIASES
+                .iter()
+                .find(|(name, _)| *name == color)
+                .map(|(_, id)| *id)
+        })
+}
+
+/// The palette name of a color ID
+pub fn color_name(color_id: &str) -> Option<&'static str> {
+    EVENT_COLORS
+        .iter()
+        .find(|(id, _)| *id == color_id)
+        .map(|(_, name)| *name)
+}
+
+/// A rule giving matching events a color, a category or both
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventRule {
+    pub name: String,
+    /// Words or phrases, any of which in the title or description matches
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+    /// Addresses or "@domain"s, any of which among the attendees matches
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attendees: Vec<String>,
+    /// Matches events with guests and at most this many people, counting the
+    /// user, so 2 matches 1:1s
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_attendees: Option<usize>,
+    /// Color ID from "1" to "11"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+}
+
+impl EventRule {
+    fn matches_keywords(&self, event: &CalendarEvent) -> bool {
+        if self.keywords.is_empty() {
+            return true;
+        }
+        let text = format!(
+            "{}\n{}",
+            event.summary,
+            event.description.as_deref().unwrap_or_default()
+        )
+        .to_lowercase();
+        self.keywords
+            .iter()
+            .any(|keyword| text.contains(&keyword.trim().to_lowercase()))
+    }
+
+    fn matches_attendees(&self, event: &CalendarEvent) -> bool {
+        if self.attendees.is_empty() {
+            return true;
+        }
+        event.attendees.iter().any(|attendee| {
+            let email = attendee.email.to_lowercase();
+            self.attendees.iter().any(|pattern| {
+                let pattern = pattern.trim().to_lowercase();
+                if pattern.starts_with('@') {
+                    email.ends_with(&pattern)
+                } else {
+                    email == pattern
+                }
+            })
+        })
+    }
+
+    fn matches_size(&self, event: &CalendarEvent) -> bool {
+        let Some(max) = self.max_attendees else {
+            return true;
+        };
+        let people = event
+            .attendees
+            .iter()
+            .filter(|attendee| !is_resource(&attendee.email))
+            .count();
+        (2..=max).contains(&people)
+    }
+
+    /// Whether every condition of the rule holds for the event
+    pub fn matches(&self, event: &CalendarEvent) -> bool {
+        self.matches_keywords(event) && self.matches_attendees(event) && self.matches_size(event)
+    }
+
+    /// The change that gives the event the rule's color and category, if it
+    /// does not have them already
+    pub fn patch(&self, event: &CalendarEvent) -> Option<serde_json::Value> {
+        let mut patch = serde_json::Map::new();
+        if let Some(color_id) = &self.color_id {
+            if event.color_id.as_ref() != Some(color_id) {
+                patch.insert("colorId".to_string(), json!(color_id));
+            }
+        }
+        if let Some(category) = &self.category {
+            if event.category.as_ref() != Some(category) {
+                patch.insert(
+                    "extendedProperties".to_string(),
+                    json!({ "private": { CATEGORY_PROPERTY: category } }),
+                );
+            }
+        }
+        if patch.is_empty() {
+            None
+        } else {
+            Some(serde_json::Value::Object(patch))
+        }
+    }
+
+    fn validate(&mut self) -> RuleResult<()> {
+        self.name = self.name.trim().to_string();
+        if self.name.is_empty() {
+            return Err(RuleError::InvalidRule(
+                "Rule name cannot be empty".to_string(),
+            ));
+        }
+        self.keywords.retain(|keyword| !keyword.trim().is_empty());
+        self.attendees
+            .retain(|attendee| !attendee.trim().is_empty());
+        if self.keywords.is_empty() && self.attendees.is_empty() && self.max_attendees.is_none() {
+            return Err(RuleError::InvalidRule(format!(
+                "Rule '{}' needs keywords, attendees or max_attendees to match events by",
+                self.name
+            )));
+        }
+        if self.max_attendees.is_some_and(|max| max < 2) {
+            return Err(RuleError::InvalidRule(format!(
+                "Rule '{}' needs max_attendees of at least 2",
+                self.name
+            )));
+        }
+        if let Some(color) = &self.color_id {
+            let color_id = parse_color(color).ok_or_else(|| {
+                RuleError::InvalidRule(format!(
+                    "Unknown color '{}': use an ID from 1 to 11 or a name such as green",
+                    color
+                ))
+            })?;
+            self.color_id = Some(color_id.to_string());
+        }
+        self.category = self
+            .category
+            .take()
+            .map(|category| category.trim().to_string())
+            .filter(|category| !category.is_empty());
+        if self.color_id.is_none() && self.category.is_none() {
+            return Err(RuleError::InvalidRule(format!(
+                "Rule '{}' needs a color or category to give events",
+                self.name
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// The first rule matching an event
+pub fn match_rule<'a>(rules: &'a [EventRule], event: &CalendarEvent) -> Option<&'a EventRule> {
+    rules.iter().find(|rule| rule.matches(event))
+}
+
+/// What categorizing did to an event
+#[derive(Debug, Clone, Serialize)]
+pub struct CategorizedEvent {
+    pub event_id: String,
+    pub summary: String,
+    pub start_time: DateTime<Utc>,
+    pub rule: String,
+    pub color_id: Option<String>,
+    pub color: Option<String>,
+    pub category: Option<String>,
+    /// "updated", "would_update" for a dry run, "unchanged" or "failed"
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl CategorizedEvent {
+    pub fn new(event: &CalendarEvent, rule: &EventRule, status: &str) -> Self {
+        let color_id = rule.color_id.clone().or_else(|| event.color_id.clone());
+        Self {
+            event_id: event.id.clone().unwrap_or_default(),
+            summary: event.summary.clone(),
+            start_time: event.start_time,
+            rule: rule.name.clone(),
+            color: color_id.as_deref().and_then(color_name).map(str::to_string),
+            color_id,
+            category: rule.category.clone().or_else(|| event.category.clone()),
+            status: status.to_string(),
+            error: None,
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct RuleFile {
+    rules: Vec<EventRule>,
+}
+
+/// Event rules stored in a JSON file, in the order they are applied
+#[derive(Debug, Clone)]
+pub struct EventRuleStore {
+    path: PathBuf,
+}
+
+impl EventRuleStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// The store at `EVENT_RULES_FILE`, or the default location
+    pub fn from_env() -> Self {
+        Self::new(get_event_rules_path())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// All rules, in the order they are applied
+    pub fn list(&self) -> RuleResult<Vec<EventRule>> {
+        Ok(self.load()?.rules)
+    }
+
+    /// Save a rule, replacing any with the same name in its place or adding
+    /// it after the others. Returns the rule as saved and whether one was
+    /// replaced.
+    pub fn save(&self, mut rule: EventRule) -> RuleResult<(EventRule, bool)> {
+        rule.validate()?;
+
+        let mut file = self.load()?;
+        let existing = file
+            .rules
+            .iter_mut()
+            .find(|existing| existing.name.eq_ignore_ascii_case(&rule.name));
+        let replaced = existing.is_some();
+        info!(
+            "{} event rule '{}'",
+            if replaced { "Replacing" } else { "Saving" },
+            rule.name
+        );
+        match existing {
+            Some(existing) => *existing = rule.clone(),
+            None => file.rules.push(rule.clone()),
+        }
+        self.store(&file)?;
+        Ok((rule, replaced))
+    }
+
+    /// Delete a rule by name, ignoring case
+    pub fn delete(&self, name: &str) -> RuleResult<()> {
+        let mut file = self.load()?;
+        let before = file.rules.len();
+        file.rules
+            .retain(|rule| !rule.name.eq_ignore_ascii_case(name.trim()));
+        if file.rules.len() == before {
+            return Err(RuleError::NotFound(name.to_string()));
+        }
+        info!("Deleting event rule '{}'", name);
+        self.store(&file)
+    }
+
+    fn load(&self) -> RuleResult<RuleFile> {
+        persist::load_json(&self.path).map_err(|e| self.storage_error(e))
+    }
+
+    fn store(&self, file: &RuleFile) -> RuleResult<()> {
+        persist::store_json(&self.path, file).map_err(|e| self.storage_error(e))
+    }
+
+    fn storage_error(&self, err: impl std::fmt::Display) -> RuleError {
+        RuleError::StorageError(format!("{}: {}", self.path.display(), err))
+    }
+}
+
+// Get default event rule file location (platform-specific)
+fn default_event_rules_path() -> PathBuf {
+    let mut path = dirs::config_dir().unwrap_or_else(std::env::temp_dir);
+    path.push("gmail-mcp-rs");
+    path.push("event_rules.json");
+    path
+}
+
+/// Returns the path of the event rule file.
+///
+/// Environment variable: EVENT_RULES_FILE
+pub fn get_event_rules_path() -> PathBuf {
+    std::env::var("EVENT_RULES_FILE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| default_event_rules_path())
+}
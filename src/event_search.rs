@@ -0,0 +1,107 @@
+use crate::calendar_api::{CalendarEvent, CalendarInfo};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+// Event search by attendee
+//
+// Calendar has no query for the people on an event, so finding meetings with
+// someone means listing the events of every calendar in the time range and
+// keeping those the person attends or organizes. An event shared by several
+// calendars is listed once, for the first calendar it was found in.
+
+/// Days ahead searched when no end of the time range is given
+pub const DEFAULT_EVENT_SEARCH_DAYS: i64 = 90;
+
+/// Most events listed from each calendar
+pub const EVENT_SEARCH_MAX_PER_CALENDAR: u32 = 250;
+
+/// Events returned when no number is given
+pub const DEFAULT_EVENT_SEARCH_RESULTS: u32 = 10;
+
+/// Whether `email` attends or organizes the event
+pub fn involves(event: &CalendarEvent, email: &str) -> bool {
+    event
+        .attendees
+        .iter()
+        .map(|attendee| &attendee.email)
+        .chain(event.organizer.as_ref().map(|organizer| &organizer.email))
+        .any(|address| address.eq_ignore_ascii_case(email))
+}
+
+/// An event with the person, and how they take part in it
+#[derive(Debug, Clone, Serialize)]
+pub struct EventWithPerson {
+    pub calendar_id: String,
+    pub calendar_summary: String,
+    /// The person's response: "accepted", "declined", "tentative" or
+    /// "needsAction"
+    pub response_status: Option<String>,
+    pub organizer: bool,
+    pub event: CalendarEvent,
+}
+
+/// Events shared with one person across the user's calendars
+#[derive(Debug, Clone, Serialize)]
+pub struct EventsWith {
+    pub person_email: String,
+    pub time_min: DateTime<Utc>,
+    pub time_max: DateTime<Utc>,
+    /// IDs of the calendars whose events were checked
+    pub calendars_searched: Vec<String>,
+    /// Soonest first
+    pub events: Vec<EventWithPerson>,
+    /// Whether more events matched than were returned
+    pub truncated: bool,
+}
+
+impl EventsWith {
+    /// Gather the events involving `person_email` from the events listed for
+    /// each calendar, keeping the first `max_results`. Events outside the
+    /// time range are left out.
+    pub fn new(
+        person_email: &str,
+        (time_min, time_max): (DateTime<Utc>, DateTime<Utc>),
+        calendars: &[(CalendarInfo, Vec<CalendarEvent>)],
+        max_results: usize,
+    ) -> Self {
+        let mut events: Vec<EventWithPerson> = Vec::new();
+        for (calendar, calendar_events) in calendars {
+            for event in calendar_events {
+                let already_listed =
+                    event.id.is_some() && events.iter().any(|listed| listed.event.id == event.id);
+                let in_range = event.end_time > time_min && event.start_time < time_max;
+                if already_listed || !in_range || !involves(event, person_email) {
+                    continue;
+                }
+                events.push(EventWithPerson {
+                    calendar_id: calendar.id.clone(),
+                    calendar_summary: calendar.summary.clone(),
+                    response_status: event
+                        .attendees
+                        .iter()
+                        .find(|attendee| attendee.email.eq_ignore_ascii_case(person_email))
+                        .and_then(|attendee| attendee.response_status.clone()),
+                    organizer: event.organizer.as_ref().is_some_and(|organizer| {
+                        organizer.email.eq_ignore_ascii_case(person_email)
+                    }),
+                    event: event.clone(),
+                });
+            }
+        }
+        events.sort_by_key(|found| found.event.start_time);
+        let truncated = events.len() > max_results;
+        events.truncate(max_results);
+
+        Self {
+            person_email: person_email.to_string(),
+            time_min,
+            time_max,
+            calendars_searched: calendars
+                .iter()
+                .map(|(calendar, _)| calendar.id.clone())
+                .collect(),
+            events,
+            truncated,
+        }
+    }
+}
@@ -0,0 +1,276 @@
+use crate::calendar_api::EventReminder;
+use crate::errors::{TemplateError, TemplateResult};
+use crate::persist;
+use crate::templates::{fill_placeholders, placeholder_names};
+use chrono::Duration;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+// Event templates
+//
+// Templates for recurring kinds of meetings, such as interviews or 1:1s, are
+// kept in a JSON file in the user's config directory, next to the email
+// templates. They give the event's title, description, location, guests,
+// length and reminders, so that every meeting of a kind is set up the same
+// way. Any of the texts, guests included, may contain `{{name}}`
+// placeholders, filled from variables when an event is created from the
+// template.
+
+/// Most minutes before an event a reminder can be, four weeks
+pub const MAX_REMINDER_MINUTES: i64 = 40320;
+
+/// A reusable event with `{{name}}` placeholders
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventTemplate {
+    pub name: String,
+    pub summary: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attendees: Vec<String>,
+    /// Length of the event when no end is given; the calendar's default
+    /// event length when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_minutes: Option<i64>,
+    /// Reminders for the event; the calendar's default reminders when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reminders: Option<Vec<EventReminder>>,
+}
+
+/// A template as listed, with the variables it needs
+#[derive(Debug, Clone, Serialize)]
+pub struct EventTemplateSummary {
+    pub name: String,
+    pub summary: String,
+    pub duration_minutes: Option<i64>,
+    pub attendees: usize,
+    pub variables: Vec<String>,
+}
+
+/// The parts of an event after filling a template's placeholders
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderedEventTemplate {
+    pub summary: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub attendees: Vec<String>,
+    pub duration: Option<Duration>,
+    pub reminders: Option<Vec<EventReminder>>,
+}
+
+/// Parse a reminder such as "10m", "popup 1h" or "email 1 day", a method
+/// and a time before the event. Reminders pop up unless "email" is given.
+pub fn parse_reminder(input: &str) -> Result<EventReminder, String> {
+    let text = input.trim().to_lowercase();
+    let (method, length) = match text.split_once(char::is_whitespace) {
+        Some((method @ ("popup" | "email"), length)) => (method, length.trim()),
+        _ => ("popup", text.as_str()),
+    };
+    let length: String = length.chars().filter(|c| !c.is_whitespace()).collect();
+    let days = ["days", "day", "d"]
+        .iter()
+        .find_map(|unit| length.strip_suffix(unit))
+        .and_then(|amount| amount.parse::<i64>().ok());
+    let minutes = match days {
+        Some(days) => Some(days * 24 * 60),
+        None if length == "0" || length == "0m" => Some(0),
+        None => crate::time_blocking::parse_task_duration(&length)
+            .map(|duration| duration.num_minutes()),
+    };
+    match minutes {
+        Some(minutes) if (0..=MAX_REMINDER_MINUTES).contains(&minutes) => Ok(EventReminder {
+            method: method.to_string(),
+            minutes,
+        }),
+        _ => Err(format!(
+            "Invalid reminder '{}': expected a time up to four weeks before, such as '10m', 'popup 1h' or 'email 1d'",
+            input
+        )),
+    }
+}
+
+impl EventTemplate {
+    fn fields(&self) -> impl Iterator<Item = &str> {
+        [
+            Some(&self.summary),
+            self.description.as_ref(),
+            self.location.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        .chain(self.attendees.iter())
+        .map(String::as_str)
+    }
+
+    /// Names of the placeholders used in the template, sorted
+    pub fn variables(&self) -> Vec<String> {
+        placeholder_names(self.fields())
+    }
+
+    /// Fill every placeholder, failing with the names of any missing variables
+    pub fn render(
+        &self,
+        variables: &HashMap<String, String>,
+    ) -> TemplateResult<RenderedEventTemplate> {
+        let mut missing = BTreeSet::new();
+        let mut fill = |text: &str| fill_placeholders(text, variables, &mut missing);
+        let rendered = RenderedEventTemplate {
+            summary: fill(&self.summary),
+            description: self.description.as_deref().map(&mut fill),
+            location: self.location.as_deref().map(&mut fill),
+            attendees: self
+                .attendees
+                .iter()
+                .map(|attendee| fill(attendee))
+                .filter(|attendee| !attendee.trim().is_empty())
+                .collect(),
+            duration: self.duration_minutes.map(Duration::minutes),
+            reminders: self.reminders.clone(),
+        };
+
+        if missing.is_empty() {
+            Ok(rendered)
+        } else {
+            Err(TemplateError::MissingVariables(
+                missing.into_iter().collect(),
+            ))
+        }
+    }
+
+    pub fn summary(&self) -> EventTemplateSummary {
+        EventTemplateSummary {
+            name: self.name.clone(),
+            summary: self.summary.clone(),
+            duration_minutes: self.duration_minutes,
+            attendees: self.attendees.len(),
+            variables: self.variables(),
+        }
+    }
+
+    fn validate(&self) -> TemplateResult<()> {
+        if self.name.trim().is_empty() {
+            return Err(TemplateError::InvalidTemplate(
+                "Template name cannot be empty".to_string(),
+            ));
+        }
+        if self.summary.trim().is_empty() {
+            return Err(TemplateError::InvalidTemplate(format!(
+                "Event template '{}' needs a summary",
+                self.name
+            )));
+        }
+        if self.duration_minutes.is_some_and(|minutes| minutes <= 0) {
+            return Err(TemplateError::InvalidTemplate(format!(
+                "Event template '{}' needs a positive duration",
+                self.name
+            )));
+        }
+        let invalid_reminder = self.reminders.iter().flatten().find(|reminder| {
+            !matches!(reminder.method.as_str(), "popup" | "email")
+                || !(0..=MAX_REMINDER_MINUTES).contains(&reminder.minutes)
+        });
+        if let Some(reminder) = invalid_reminder {
+            return Err(TemplateError::InvalidTemplate(format!(
+                "Event template '{}' has an invalid reminder: {} {} minutes before",
+                self.name, reminder.method, reminder.minutes
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct EventTemplateFile {
+    templates: Vec<EventTemplate>,
+}
+
+/// Event templates stored in a JSON file
+#[derive(Debug, Clone)]
+pub struct EventTemplateStore {
+    path: PathBuf,
+}
+
+impl EventTemplateStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// The store at `EVENT_TEMPLATES_FILE`, or the default location
+    pub fn from_env() -> Self {
+        Self::new(get_event_templates_path())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// All templates, sorted by name
+    pub fn list(&self) -> TemplateResult<Vec<EventTemplate>> {
+        let mut templates = self.load()?.templates;
+        templates.sort_by_key(|template| template.name.to_lowercase());
+        Ok(templates)
+    }
+
+    /// Find a template by name, ignoring case
+    pub fn get(&self, name: &str) -> TemplateResult<EventTemplate> {
+        self.load()?
+            .templates
+            .into_iter()
+            .find(|template| template.name.eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| TemplateError::NotFound(name.to_string()))
+    }
+
+    /// Save a template, replacing any with the same name. Returns whether a
+    /// template was replaced.
+    pub fn save(&self, mut template: EventTemplate) -> TemplateResult<bool> {
+        template.name = template.name.trim().to_string();
+        template.validate()?;
+
+        let mut file = self.load()?;
+        let before = file.templates.len();
+        file.templates
+            .retain(|existing| !existing.name.eq_ignore_ascii_case(&template.name));
+        let replaced = file.templates.len() < before;
+        info!(
+            "{} event template '{}'",
+            if replaced { "Replacing" } else { "Saving" },
+            template.name
+        );
+        file.templates.push(template);
+        self.store(&file)?;
+        Ok(replaced)
+    }
+
+    fn load(&self) -> TemplateResult<EventTemplateFile> {
+        persist::load_json(&self.path).map_err(|e| self.storage_error(e))
+    }
+
+    fn store(&self, file: &EventTemplateFile) -> TemplateResult<()> {
+        persist::store_json(&self.path, file).map_err(|e| self.storage_error(e))
+    }
+
+    fn storage_error(&self, err: impl std::fmt::Display) -> TemplateError {
+        TemplateError::StorageError(format!("{}: {}", self.path.display(), err))
+    }
+}
+
+// Get default event template file location (platform-specific)
+fn default_event_templates_path() -> PathBuf {
+    let mut path = dirs::config_dir().unwrap_or_else(std::env::temp_dir);
+    path.push("gmail-mcp-rs");
+    path.push("event_templates.json");
+    path
+}
+
+/// Returns the path of the event template file.
+///
+/// Environment variable: EVENT_TEMPLATES_FILE
+pub fn get_event_templates_path() -> PathBuf {
+    std::env::var("EVENT_TEMPLATES_FILE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| default_event_templates_path())
+}
@@ -0,0 +1,66 @@
+use crate::gmail_api::MessageMetadata;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+// Follow-up tracking
+//
+// A conversation is waiting on the other party when the user sent its last
+// message. Recently sent mail is listed, and the thread of each is checked
+// for later messages from anyone else. Drafts are ignored, since an unsent
+// reply does not answer anything.
+
+/// Days of sent mail checked when none is given
+pub const DEFAULT_AWAITING_REPLY_DAYS: u32 = 14;
+
+/// Most sent messages checked
+pub const AWAITING_REPLY_MAX_MESSAGES: u32 = 500;
+
+/// Headers to fetch, besides the defaults, to say who a reply is awaited from
+pub const AWAITING_REPLY_HEADERS: &[&str] = &["To"];
+
+/// Gmail query for the mail the user sent in the last `days` days
+pub fn sent_mail_query(days: u32) -> String {
+    format!("in:sent newer_than:{}d", days)
+}
+
+/// A conversation whose last message was sent by the user
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AwaitingReply {
+    pub thread_id: String,
+    /// The user's last message
+    pub message_id: String,
+    pub subject: Option<String>,
+    /// Who the last message was sent to
+    pub to: Option<String>,
+    pub sent_at: Option<DateTime<Utc>>,
+    /// Whole days since the last message was sent
+    pub days_waiting: Option<i64>,
+    pub message_count: usize,
+}
+
+/// The wait for a reply in a thread, given the metadata of its messages, or
+/// `None` if the other party wrote last
+pub fn awaiting_reply(thread: &[MessageMetadata], now: DateTime<Utc>) -> Option<AwaitingReply> {
+    let has_label =
+        |message: &MessageMetadata, label: &str| message.label_ids.iter().any(|id| id == label);
+    let messages: Vec<&MessageMetadata> = thread
+        .iter()
+        .filter(|message| !has_label(message, "DRAFT"))
+        .collect();
+
+    // Gmail lists thread messages oldest first
+    let last = *messages.last()?;
+    if !has_label(last, "SENT") {
+        return None;
+    }
+
+    Some(AwaitingReply {
+        thread_id: last.thread_id.clone(),
+        message_id: last.id.clone(),
+        subject: last.subject.clone(),
+        to: last.headers.get("to").cloned(),
+        sent_at: last.received_at,
+        days_waiting: last.received_at.map(|sent| (now - sent).num_days()),
+        message_count: messages.len(),
+    })
+}
@@ -0,0 +1,89 @@
+use crate::gmail_api::{AutoForwarding, ForwardingAddress};
+
+// Auto-forwarding
+//
+// Gmail forwards new mail only to forwarding addresses whose owners have
+// confirmed them, which happens in Gmail's settings. Turning auto-forwarding
+// on sends every new email to another mailbox, so the change is worked out
+// and shown first and only made when confirmed. The disposition says what
+// happens to the original once forwarded.
+
+/// What can happen to a message once forwarded, as the API names it
+pub const DISPOSITIONS: [&str; 4] = ["leaveInInbox", "archive", "trash", "markRead"];
+
+/// Disposition used when turning forwarding on without one
+pub const DEFAULT_DISPOSITION: &str = "leaveInInbox";
+
+/// The API name of a disposition written as the API does or in words, such
+/// as "leave_in_inbox", "Mark read" or "archive"
+pub fn parse_disposition(disposition: &str) -> Option<&'static str> {
+    let key: String = disposition
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect::<String>()
+        .to_lowercase();
+    DISPOSITIONS
+        .iter()
+        .find(|name| name.to_lowercase() == key)
+        .copied()
+}
+
+/// Whether an address is a forwarding address its owner has confirmed
+pub fn is_verified(addresses: &[ForwardingAddress], email: &str) -> bool {
+    addresses.iter().any(|address| {
+        address.forwarding_email.eq_ignore_ascii_case(email)
+            && address.verification_status.as_deref() == Some("accepted")
+    })
+}
+
+/// The setting that turns forwarding on or off. Turning it on forwards to
+/// the address given, or else the one set before, which must be verified,
+/// keeping the earlier disposition unless another is given.
+pub fn plan_auto_forwarding(
+    current: &AutoForwarding,
+    addresses: &[ForwardingAddress],
+    enabled: bool,
+    email_address: Option<&str>,
+    disposition: Option<&str>,
+) -> Result<AutoForwarding, String> {
+    if !enabled {
+        return Ok(AutoForwarding {
+            enabled: false,
+            email_address: None,
+            disposition: None,
+        });
+    }
+
+    let email = email_address
+        .map(str::trim)
+        .filter(|email| !email.is_empty())
+        .or(current.email_address.as_deref())
+        .ok_or_else(|| "An email_address to forward to is required".to_string())?;
+    if !is_verified(addresses, email) {
+        return Err(format!(
+            "'{}' is not a verified forwarding address; add it and confirm it in Gmail's forwarding settings first",
+            email
+        ));
+    }
+
+    let disposition = match disposition {
+        Some(disposition) => parse_disposition(disposition).ok_or_else(|| {
+            format!(
+                "Unknown disposition '{}': use one of {}",
+                disposition,
+                DISPOSITIONS.join(", ")
+            )
+        })?,
+        None => current
+            .disposition
+            .as_deref()
+            .and_then(parse_disposition)
+            .unwrap_or(DEFAULT_DISPOSITION),
+    };
+
+    Ok(AutoForwarding {
+        enabled: true,
+        email_address: Some(email.to_string()),
+        disposition: Some(disposition.to_string()),
+    })
+}
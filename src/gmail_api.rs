@@ -1,15 +1,48 @@
+use crate::attachments::{
+    find_attachments, get_inline_image_max_bytes, resolve_inline_images, AttachmentDecoder,
+    AttachmentInfo, DownloadOptions, InlineImage, OutgoingAttachment,
+};
 use crate::auth::TokenManager;
+use crate::bounces::{DeliveryReport, ReadReceipt};
 use crate::config::Config;
-use crate::config::GMAIL_API_BASE_URL;
-use crate::errors::{GmailApiError, GmailResult};
+use crate::config::{
+    get_gmail_api_base_url, get_gmail_upload_base_url, get_max_body_bytes, get_token_info_url,
+    is_html_sanitizing_enabled,
+};
+use crate::deadline::Deadline;
+use crate::errors::{retry_after_header, GmailApiError, GmailResult, RateLimitInfo};
+use crate::etag_cache::EtagCache;
+use crate::gmail_models::{Draft, HistoryList, Message, MessageList, MessagePart, Thread};
+use crate::label_tree::{
+    ancestor_names, find_label, normalize_label_name, parse_labels, LabelCreation, LabelInfo,
+};
+use crate::priority::{is_priority_header, Priority, PriorityLevel};
+use crate::rate_limit::RateLimiter;
+use crate::resumable_upload::{self, get_resumable_threshold, get_upload_chunk_bytes};
+use crate::secure_mail::{self, SignerInfo};
+use crate::send_policy::{SendLog, SendPolicy};
+use crate::sender_auth::AuthenticationResults;
+use crate::token_info::TokenInfo;
+use crate::utils::{
+    api_client_builder, find_trackers, parse_email_addresses, sanitize_html, Tracker, USER_AGENT,
+};
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
 use log::{debug, error, info};
 use reqwest::Client;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
 use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
 
 // Email message model
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct EmailMessage {
     pub id: String,
     pub thread_id: String,
@@ -20,8 +53,50 @@ pub struct EmailMessage {
     pub snippet: Option<String>,
     pub body_text: Option<String>,
     pub body_html: Option<String>,
+    /// The RFC 822 Message-ID header, shared by every copy of a message
+    #[serde(default)]
+    pub rfc_message_id: Option<String>,
+    /// ISO 639-1 code of the language the message is written in, if known
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Whether the message carries a PGP or S/MIME signature
+    #[serde(default)]
+    pub is_signed: bool,
+    /// Whether the message is PGP or S/MIME encrypted, in which case no body
+    /// text is given
+    #[serde(default)]
+    pub is_encrypted: bool,
+    /// Who a signed message claims to be signed by; the signature is not
+    /// verified
+    #[serde(default)]
+    pub signer: Option<SignerInfo>,
+    /// SPF, DKIM and DMARC results recorded by the receiving server
+    #[serde(default)]
+    pub authentication: Option<AuthenticationResults>,
+    /// Images the HTML body refers to by `cid:`; small ones sent with the
+    /// message are embedded in `body_html` as `data:` URIs
+    #[serde(default)]
+    pub inline_images: Vec<InlineImage>,
+    /// Priority set by the sender and whether Gmail marked it important
+    #[serde(default)]
+    pub priority: Priority,
+    /// The delivery status notification, when the message is a bounce
+    #[serde(default)]
+    pub delivery_report: Option<DeliveryReport>,
+    /// The read receipt, when the message is one
+    #[serde(default)]
+    pub read_receipt: Option<ReadReceipt>,
+    /// Tracking pixels and tracked links found in the bodies
+    #[serde(default)]
+    pub trackers: Vec<Tracker>,
 }
 
+/// Largest message Gmail accepts, as sent with attachments encoded
+pub const MAX_MESSAGE_BYTES: usize = 25 * 1024 * 1024;
+
+// Room left in a message for its headers and MIME boundaries
+const MESSAGE_OVERHEAD_BYTES: usize = 64 * 1024;
+
 // Draft email model for creating new emails
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct DraftEmail {
@@ -33,14 +108,178 @@ pub struct DraftEmail {
     pub thread_id: Option<String>,
     pub in_reply_to: Option<String>,
     pub references: Option<String>,
+    /// Adds X-Priority and Importance headers when set
+    #[serde(default)]
+    pub priority: Option<PriorityLevel>,
+    /// Asks for a read receipt to be sent to this address with a
+    /// Disposition-Notification-To header
+    #[serde(default)]
+    pub read_receipt_to: Option<String>,
+}
+
+impl DraftEmail {
+    /// Addresses of all recipients, including CC and BCC, lowercased
+    pub fn recipient_addresses(&self) -> Vec<String> {
+        [Some(&self.to), self.cc.as_ref(), self.bcc.as_ref()]
+            .into_iter()
+            .flatten()
+            .flat_map(|list| parse_email_addresses(list))
+            .collect()
+    }
+}
+
+/// Mailbox changes reported by the Gmail History API
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HistoryChanges {
+    /// History ID to start from on the next call
+    pub history_id: String,
+    /// IDs of messages added since the start history ID
+    pub added: Vec<String>,
+    /// IDs of messages deleted since the start history ID
+    pub deleted: Vec<String>,
+}
+
+/// Sender, subject, arrival time and labels of a message, listed without
+/// its content
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MessageMetadata {
+    pub id: String,
+    pub thread_id: String,
+    pub from: Option<String>,
+    pub subject: Option<String>,
+    pub snippet: Option<String>,
+    /// When Gmail received the message, or the Date header if that is unknown
+    pub received_at: Option<DateTime<Utc>>,
+    pub label_ids: Vec<String>,
+    /// Any other headers asked for, by lowercased name
+    pub headers: HashMap<String, String>,
+}
+
+/// Number of messages matching a search
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct MessageCount {
+    pub count: u64,
+    /// Whether every match was counted; otherwise `count` is Gmail's estimate
+    pub exact: bool,
+}
+
+/// The mailbox's address and size
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GmailProfile {
+    pub email_address: String,
+    #[serde(default)]
+    pub messages_total: u64,
+    #[serde(default)]
+    pub threads_total: u64,
+    /// The mailbox's current history ID
+    #[serde(default, deserialize_with = "crate::gmail_models::string_or_number")]
+    pub history_id: Option<String>,
+}
+
+/// Someone with delegated access to a mailbox
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Delegate {
+    pub delegate_email: String,
+    /// "accepted", "pending", "rejected" or "expired"
+    #[serde(default)]
+    pub verification_status: Option<String>,
+}
+
+/// An address mail can be forwarded to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForwardingAddress {
+    pub forwarding_email: String,
+    /// "accepted" once the address owner has confirmed it, else "pending"
+    #[serde(default)]
+    pub verification_status: Option<String>,
+}
+
+/// Whether new mail is forwarded automatically, where to, and what happens
+/// to the original
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoForwarding {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email_address: Option<String>,
+    /// "leaveInInbox", "archive", "trash" or "markRead"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disposition: Option<String>,
+}
+
+/// How the mailbox is offered to IMAP mail apps
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImapSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Whether messages are expunged as soon as they are marked deleted
+    #[serde(default)]
+    pub auto_expunge: bool,
+    /// "archive", "trash" or "deleteForever"
+    #[serde(default)]
+    pub expunge_behavior: Option<String>,
+    /// Most messages an IMAP folder shows; 0 for no limit
+    #[serde(default)]
+    pub max_folder_size: u32,
+}
+
+/// How the mailbox is offered to POP mail apps
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PopSettings {
+    /// "disabled", "fromNowOn" or "allMail"
+    #[serde(default)]
+    pub access_window: Option<String>,
+    /// "leaveInInbox", "archive", "trash" or "markRead"
+    #[serde(default)]
+    pub disposition: Option<String>,
+}
+
+/// The language Gmail is shown in
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageSettings {
+    /// A BCP 47 language tag such as "en-GB"
+    #[serde(default)]
+    pub display_language: Option<String>,
+}
+
+/// Most matches `count_messages` pages through by default before settling
+/// for Gmail's estimate
+pub const DEFAULT_EXACT_COUNT_LIMIT: u32 = 1000;
+
+// Most copies of one message `find_by_rfc_message_id` returns
+const MAX_MESSAGE_ID_MATCHES: u32 = 10;
+
+/// The mailbox of the account itself
+pub const DEFAULT_MAILBOX: &str = "me";
+
+// Path of a resource in a mailbox. Addresses are percent-encoded, since they
+// may contain `+`.
+fn user_path(user_id: &str, path: &str) -> String {
+    format!("/users/{}{}", urlencoding::encode(user_id), path)
 }
 
 // Alias for backward compatibility within this module
 type Result<T> = GmailResult<T>;
 
+/// Gmail API client. Methods take `&self` and clones share one access token,
+/// so a service can serve concurrent tool calls.
+#[derive(Clone)]
 pub struct GmailService {
     client: Client,
-    token_manager: TokenManager,
+    // Locked only while the token is checked or refreshed
+    token_manager: Arc<Mutex<TokenManager>>,
+    // Mailbox requests are made for: "me", or the address of a mailbox the
+    // account has delegated access to
+    user_id: String,
+    // Once passed, multi-request methods stop issuing requests
+    deadline: Deadline,
 }
 
 impl GmailService {
@@ -49,12 +288,11 @@ impl GmailService {
 
         // Create HTTP client with reasonable timeouts
         debug!("Creating HTTP client with timeouts");
-        let client = Client::builder()
+        let client = api_client_builder()
             .timeout(Duration::from_secs(60)) // Longer timeout for Gmail API
             .connect_timeout(Duration::from_secs(30))
             .pool_idle_timeout(Duration::from_secs(90))
             .pool_max_idle_per_host(5)
-            .user_agent("mcp-gmailcal/0.1.0")
             .build()
             .map_err(|e| {
                 error!("Failed to create HTTP client: {}", e);
@@ -63,25 +301,107 @@ impl GmailService {
 
         debug!("HTTP client created successfully");
 
-        let token_manager = TokenManager::new(config);
+        let token_manager = Arc::new(Mutex::new(TokenManager::new(config)));
 
         Ok(Self {
             client,
             token_manager,
+            user_id: DEFAULT_MAILBOX.to_string(),
+            deadline: Deadline::never(),
+        })
+    }
+
+    /// Make requests for another mailbox, such as a shared or delegated
+    /// mailbox the account has access to. `"me"` is the account's own.
+    pub fn with_mailbox(mut self, mailbox: &str) -> Self {
+        self.user_id = mailbox.to_string();
+        self
+    }
+
+    /// The mailbox requests are made for
+    pub fn mailbox(&self) -> &str {
+        &self.user_id
+    }
+
+    /// Stop issuing further requests in methods that make many once
+    /// `deadline` passes, returning the results gathered so far
+    pub fn with_deadline(mut self, deadline: Deadline) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// The deadline requests are issued within
+    pub fn deadline(&self) -> &Deadline {
+        &self.deadline
+    }
+
+    // Path of a resource in the mailbox
+    fn user_path(&self, path: &str) -> String {
+        user_path(&self.user_id, path)
+    }
+
+    // Get a valid access token, refreshing it if needed. Concurrent callers
+    // wait for a single refresh.
+    async fn access_token(&self) -> Result<String> {
+        self.token_manager
+            .lock()
+            .await
+            .get_token(&self.client)
+            .await
+    }
+
+    // Get a requester for concurrent requests, refreshing the token first
+    async fn shared_requester(&self) -> Result<SharedRequester> {
+        let token = self.access_token().await?;
+        Ok(SharedRequester {
+            client: self.client.clone(),
+            token,
+            user_id: self.user_id.clone(),
         })
     }
 
+    // Upload a raw message with Gmail's resumable upload protocol
+    async fn upload_message<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        metadata: &Value,
+        message: &str,
+    ) -> Result<T> {
+        let token = self.access_token().await?;
+        let url = format!("{}{}", get_gmail_upload_base_url(), endpoint);
+        resumable_upload::upload(
+            &self.client,
+            &token,
+            &url,
+            metadata,
+            message.as_bytes(),
+            get_upload_chunk_bytes(),
+        )
+        .await
+    }
+
     // Helper function to make authenticated requests to Gmail API
     async fn request<T: for<'de> Deserialize<'de>>(
-        &mut self,
+        &self,
+        method: reqwest::Method,
+        endpoint: &str,
+        query: Option<&[(&str, &str)]>,
+    ) -> Result<T> {
+        self.request_with_body(method, endpoint, query, None).await
+    }
+
+    // Helper function to make authenticated requests with an optional JSON body
+    async fn request_with_body<T: for<'de> Deserialize<'de>>(
+        &self,
         method: reqwest::Method,
         endpoint: &str,
         query: Option<&[(&str, &str)]>,
+        body: Option<&Value>,
     ) -> Result<T> {
         // Get valid access token
-        let token = self.token_manager.get_token(&self.client).await?;
+        let token = self.access_token().await?;
 
-        let url = format!("{}{}", GMAIL_API_BASE_URL, endpoint);
+        let url = format!("{}{}", get_gmail_api_base_url(), endpoint);
         debug!("Making request to: {}", url);
 
         // Build request with authorization header
@@ -91,15 +411,21 @@ impl GmailService {
             .request(method, &url)
             .header("Authorization", format!("Bearer {}", token))
             .header("Accept", "application/json")
-            .header("User-Agent", "mcp-gmailcal/0.1.0");
+            .header("User-Agent", USER_AGENT);
 
         // Add query parameters if provided
         if let Some(q) = query {
             req_builder = req_builder.query(q);
         }
 
+        // Add JSON body if provided
+        if let Some(b) = body {
+            req_builder = req_builder.json(b);
+        }
+
         // Send request
         debug!("Sending request to Gmail API");
+        let _permit = RateLimiter::global().acquire().await;
         let response = req_builder.send().await.map_err(|e| {
             error!("Network error sending request: {}", e);
             GmailApiError::NetworkError(e.to_string())
@@ -110,50 +436,55 @@ impl GmailService {
         // Handle response status
         let status = response.status();
         if !status.is_success() {
-            let status_code = status.as_u16();
+            let retry_after = retry_after_header(response.headers());
             let error_text = response
                 .text()
                 .await
                 .unwrap_or_else(|_| "<no response body>".to_string());
-
-            // Map common error codes to appropriate error types
-            return match status_code {
-                401 | 403 => Err(GmailApiError::AuthError(format!(
-                    "Authentication failed. Status: {}, Error: {}",
-                    status, error_text
-                ))),
-                404 => Err(GmailApiError::MessageRetrievalError(format!(
-                    "Resource not found. Status: {}, Error: {}",
-                    status, error_text
-                ))),
-                429 => Err(GmailApiError::RateLimitError(format!(
-                    "Rate limit exceeded. Status: {}, Error: {}",
-                    status, error_text
-                ))),
-                _ => Err(GmailApiError::ApiError(format!(
-                    "API request failed. Status: {}, Error: {}",
-                    status, error_text
-                ))),
-            };
+            return Err(error_for_status(status, retry_after.as_deref(), error_text));
         }
 
-        // Parse JSON response
-        response.json::<T>().await.map_err(|e| {
+        // Parse JSON response, treating an empty body (e.g. 204 No Content) as null
+        let text = response.text().await.map_err(|e| {
+            GmailApiError::NetworkError(format!("Failed to get response body: {}", e))
+        })?;
+        let text = if text.trim().is_empty() { "null" } else { &text };
+        serde_json::from_str::<T>(text).map_err(|e| {
             GmailApiError::MessageFormatError(format!("Failed to parse response: {}", e))
         })
     }
 
     // Helper function to make a request and return the raw JSON response
     async fn request_raw(
-        &mut self,
+        &self,
+        method: reqwest::Method,
+        endpoint: &str,
+        query: Option<&[(&str, &str)]>,
+    ) -> Result<String> {
+        self.request_raw_inner(method, endpoint, query, false).await
+    }
+
+    // Like `request_raw`, but revalidates a cached response with its ETag
+    async fn request_raw_conditional(
+        &self,
+        method: reqwest::Method,
+        endpoint: &str,
+        query: Option<&[(&str, &str)]>,
+    ) -> Result<String> {
+        self.request_raw_inner(method, endpoint, query, true).await
+    }
+
+    async fn request_raw_inner(
+        &self,
         method: reqwest::Method,
         endpoint: &str,
         query: Option<&[(&str, &str)]>,
+        conditional: bool,
     ) -> Result<String> {
         // Get valid access token
-        let token = self.token_manager.get_token(&self.client).await?;
+        let token = self.access_token().await?;
 
-        let url = format!("{}{}", GMAIL_API_BASE_URL, endpoint);
+        let url = format!("{}{}", get_gmail_api_base_url(), endpoint);
         debug!("Making raw request to: {}", url);
 
         // Build request with authorization header
@@ -163,7 +494,7 @@ impl GmailService {
             .request(method, &url)
             .header("Authorization", format!("Bearer {}", token))
             .header("Accept", "application/json")
-            .header("User-Agent", "mcp-gmailcal/0.1.0");
+            .header("User-Agent", USER_AGENT);
 
         // Add query parameters if provided
         if let Some(q) = query {
@@ -172,49 +503,51 @@ impl GmailService {
 
         // Send request
         debug!("Sending raw request to Gmail API");
-        let response = req_builder.send().await.map_err(|e| {
-            error!("Network error sending raw request: {}", e);
-            GmailApiError::NetworkError(e.to_string())
-        })?;
-
-        debug!("Raw response received with status: {}", response.status());
+        let _permit = RateLimiter::global().acquire().await;
+        let json_text = if conditional {
+            let response = EtagCache::global().send(req_builder).await.map_err(|e| {
+                error!("Network error sending raw request: {}", e);
+                GmailApiError::NetworkError(e.to_string())
+            })?;
 
-        // Handle response status
-        let status = response.status();
-        if !status.is_success() {
-            let status_code = status.as_u16();
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "<no response body>".to_string());
+            debug!(
+                "Raw response received with status: {} (cached: {})",
+                response.status, response.from_cache
+            );
+            if !response.status.is_success() {
+                return Err(error_for_status(
+                    response.status,
+                    response.retry_after.as_deref(),
+                    response.body,
+                ));
+            }
+            response.body
+        } else {
+            let response = req_builder.send().await.map_err(|e| {
+                error!("Network error sending raw request: {}", e);
+                GmailApiError::NetworkError(e.to_string())
+            })?;
 
-            // Map common error codes to appropriate error types
-            return match status_code {
-                401 | 403 => Err(GmailApiError::AuthError(format!(
-                    "Authentication failed. Status: {}, Error: {}",
-                    status, error_text
-                ))),
-                404 => Err(GmailApiError::MessageRetrievalError(format!(
-                    "Resource not found. Status: {}, Error: {}",
-                    status, error_text
-                ))),
-                429 => Err(GmailApiError::RateLimitError(format!(
-                    "Rate limit exceeded. Status: {}, Error: {}",
-                    status, error_text
-                ))),
-                _ => Err(GmailApiError::ApiError(format!(
-                    "API request failed. Status: {}, Error: {}",
-                    status, error_text
-                ))),
-            };
-        }
+            debug!("Raw response received with status: {}", response.status());
+
+            // Handle response status
+            let status = response.status();
+            if !status.is_success() {
+                let retry_after = retry_after_header(response.headers());
+                let error_text = response
+                    .text()
+                    .await
+                    .unwrap_or_else(|_| "<no response body>".to_string());
+                return Err(error_for_status(status, retry_after.as_deref(), error_text));
+            }
 
-        // Get raw JSON as string
-        debug!("Reading response body");
-        let json_text = response.text().await.map_err(|e| {
-            error!("Failed to get response body: {}", e);
-            GmailApiError::NetworkError(format!("Failed to get response body: {}", e))
-        })?;
+            // Get raw JSON as string
+            debug!("Reading response body");
+            response.text().await.map_err(|e| {
+                error!("Failed to get response body: {}", e);
+                GmailApiError::NetworkError(format!("Failed to get response body: {}", e))
+            })?
+        };
 
         // Log a preview of the response
         let preview = if json_text.len() > 200 {
@@ -246,13 +579,13 @@ impl GmailService {
     }
 
     /// Get a message by ID and return as raw JSON
-    pub async fn get_message_raw(&mut self, message_id: &str) -> Result<String> {
+    pub async fn get_message_raw(&self, message_id: &str) -> Result<String> {
         debug!("Getting raw message with ID: {}", message_id);
 
         // Log request details
         let request_details = format!(
-            "Request details: User ID: 'me', Message ID: '{}', Format: 'full'",
-            message_id
+            "Request details: User ID: '{}', Message ID: '{}', Format: 'full'",
+            self.user_id, message_id
         );
         info!("{}", request_details);
 
@@ -260,17 +593,13 @@ impl GmailService {
         let query = [("format", "full")];
 
         // Execute request
-        let endpoint = format!("/users/me/messages/{}", message_id);
+        let endpoint = self.user_path(&format!("/messages/{}", message_id));
         self.request_raw(reqwest::Method::GET, &endpoint, Some(&query))
             .await
     }
 
     /// List messages and return raw JSON response
-    pub async fn list_messages_raw(
-        &mut self,
-        max_results: u32,
-        query: Option<&str>,
-    ) -> Result<String> {
+    pub async fn list_messages_raw(&self, max_results: u32, query: Option<&str>) -> Result<String> {
         debug!(
             "Listing raw messages with max_results={}, query={:?}",
             max_results, query
@@ -280,7 +609,7 @@ impl GmailService {
         let max_results_str = max_results.to_string();
 
         // Execute request
-        let endpoint = "/users/me/messages";
+        let endpoint = &self.user_path("/messages");
 
         // Handle query parameter differently to avoid lifetime issues
         if let Some(q) = query {
@@ -295,126 +624,164 @@ impl GmailService {
         }
     }
 
+    /// Find the messages with an RFC 822 Message-ID, given with or without
+    /// angle brackets. There is usually one, but a message sent to yourself
+    /// or imported twice has a copy for each.
+    pub async fn find_by_rfc_message_id(&self, rfc_message_id: &str) -> Result<Vec<EmailMessage>> {
+        let wanted = bare_message_id(rfc_message_id);
+        if wanted.is_empty() {
+            return Err(GmailApiError::MessageFormatError(
+                "A Message-ID is required".to_string(),
+            ));
+        }
+        debug!("Finding messages with Message-ID <{}>", wanted);
+
+        let query = format!("rfc822msgid:{}", wanted);
+        let messages = self
+            .list_messages(MAX_MESSAGE_ID_MATCHES, Some(&query))
+            .await?;
+        Ok(messages
+            .into_iter()
+            .filter(|message| {
+                message
+                    .rfc_message_id
+                    .as_deref()
+                    .is_some_and(|id| bare_message_id(id).eq_ignore_ascii_case(wanted))
+            })
+            .collect())
+    }
+
     /// Get message details with all metadata and content
-    pub async fn get_message_details(&mut self, message_id: &str) -> Result<EmailMessage> {
-        use base64;
+    pub async fn get_message_details(&self, message_id: &str) -> Result<EmailMessage> {
+        debug!("Getting message details with ID: {}", message_id);
 
-        // First get the full message
-        let message_json = self.get_message_raw(message_id).await?;
+        // Parse the response directly rather than through the pretty-printed
+        // raw JSON, which would hold another copy of the encoded body
+        let endpoint = self.user_path(&format!("/messages/{}", message_id));
+        let parsed: Value = self
+            .request(reqwest::Method::GET, &endpoint, Some(&[("format", "full")]))
+            .await?;
 
-        // Parse the JSON
-        let parsed: serde_json::Value = serde_json::from_str(&message_json).map_err(|e| {
-            GmailApiError::MessageFormatError(format!("Failed to parse message JSON: {}", e))
-        })?;
+        Self::parse_message_details(&parsed)
+    }
 
-        // Extract the basic message data
-        let id = parsed["id"]
-            .as_str()
-            .ok_or_else(|| {
-                GmailApiError::MessageFormatError("Message missing 'id' field".to_string())
-            })?
-            .to_string();
+    /// Get every message in a thread with all metadata and content
+    pub async fn get_thread(&self, thread_id: &str) -> Result<Vec<EmailMessage>> {
+        debug!("Getting thread with ID: {}", thread_id);
+
+        let endpoint = self.user_path(&format!("/threads/{}", thread_id));
+        let thread: Value = self
+            .request(reqwest::Method::GET, &endpoint, Some(&[("format", "full")]))
+            .await?;
+
+        thread["messages"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(Self::parse_message_details)
+            .collect()
+    }
 
-        let thread_id = parsed["threadId"]
-            .as_str()
-            .ok_or_else(|| {
-                GmailApiError::MessageFormatError("Message missing 'threadId' field".to_string())
-            })?
-            .to_string();
-
-        // Extract metadata
-        let mut subject = None;
-        let mut from = None;
-        let mut to = None;
-        let mut date = None;
-        let mut snippet = None;
+    // Extract metadata and content from a full-format message resource
+    fn parse_message_details(parsed: &Value) -> Result<EmailMessage> {
+        let max_body_bytes = get_max_body_bytes();
+
+        let message = Message::deserialize(parsed).map_err(|e| {
+            GmailApiError::MessageFormatError(format!("Failed to parse message: {}", e))
+        })?;
+        let thread_id = message.thread_id.clone().ok_or_else(|| {
+            GmailApiError::MessageFormatError("Message missing 'threadId' field".to_string())
+        })?;
+        let empty = MessagePart::default();
+        let payload = message.payload.as_ref().unwrap_or(&empty);
+
+        // Extract metadata. Only the topmost authentication headers, added by
+        // Gmail, are trusted.
+        let header = |name: &str| payload.header(name).map(String::from);
+        let subject = header("Subject");
+        let from = header("From");
+        let to = header("To");
+        let date = header("Date");
+        let rfc_message_id = header("Message-ID");
+        let authentication_results = payload.header("Authentication-Results");
+        let received_spf = payload.header("Received-SPF");
+        let priority_headers: Vec<(&str, &str)> = payload
+            .headers
+            .iter()
+            .filter(|header| is_priority_header(&header.name))
+            .map(|header| (header.name.as_str(), header.value.as_str()))
+            .collect();
+        let mut snippet = message.snippet.clone();
+
+        // Extract message body parts, from inside the signature wrapper of a
+        // signed message and any nested multipart parts
+        let content = payload.signed_content();
         let mut body_text = None;
         let mut body_html = None;
-
-        // Extract snippet if available
-        if let Some(s) = parsed.get("snippet").and_then(|s| s.as_str()) {
-            snippet = Some(s.to_string());
-        }
-
-        // Process payload to extract headers and body parts
-        if let Some(payload) = parsed.get("payload") {
-            // Extract headers
-            if let Some(headers) = payload.get("headers").and_then(|h| h.as_array()) {
-                for header in headers {
-                    if let (Some(name), Some(value)) = (
-                        header.get("name").and_then(|n| n.as_str()),
-                        header.get("value").and_then(|v| v.as_str()),
-                    ) {
-                        match name {
-                            "Subject" => subject = Some(value.to_string()),
-                            "From" => from = Some(value.to_string()),
-                            "To" => to = Some(value.to_string()),
-                            "Date" => date = Some(value.to_string()),
-                            _ => {}
-                        }
-                    }
-                }
+        for part in content.leaf_parts() {
+            let body = match part.mime_type() {
+                "text/plain" => &mut body_text,
+                "text/html" => &mut body_html,
+                _ => continue,
+            };
+            if let Some(text) = part.decoded_body(max_body_bytes) {
+                *body = Some(text);
             }
+        }
 
-            // Extract message body parts
-            if let Some(parts) = payload.get("parts").and_then(|p| p.as_array()) {
-                // Process each part
-                for part in parts {
-                    if let Some(mime_type) = part.get("mimeType").and_then(|m| m.as_str()) {
-                        // Handle text parts
-                        if mime_type == "text/plain" || mime_type == "text/html" {
-                            if let Some(body) = part.get("body") {
-                                if let Some(data) = body.get("data").and_then(|d| d.as_str()) {
-                                    // Decode base64
-                                    if let Ok(decoded) =
-                                        base64::decode(data.replace('-', "+").replace('_', "/"))
-                                    {
-                                        if let Ok(text) = String::from_utf8(decoded) {
-                                            match mime_type {
-                                                "text/plain" => body_text = Some(text),
-                                                "text/html" => body_html = Some(text),
-                                                _ => {}
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
+        // Check for body directly in payload (for simple messages), taking it
+        // as text unless it is HTML
+        if body_text.is_none() && body_html.is_none() {
+            if let Some(text) = content.decoded_body(max_body_bytes) {
+                match content.mime_type() {
+                    "text/html" => body_html = Some(text),
+                    _ => body_text = Some(text),
                 }
             }
+        }
 
-            // Check for body directly in payload (for simple messages)
-            if body_text.is_none() && body_html.is_none() {
-                if let Some(body) = payload.get("body") {
-                    if let Some(data) = body.get("data").and_then(|d| d.as_str()) {
-                        // Decode base64
-                        if let Ok(decoded) =
-                            base64::decode(data.replace('-', "+").replace('_', "/"))
-                        {
-                            if let Ok(text) = String::from_utf8(decoded) {
-                                if let Some(mime_type) =
-                                    payload.get("mimeType").and_then(|m| m.as_str())
-                                {
-                                    match mime_type {
-                                        "text/plain" => body_text = Some(text),
-                                        "text/html" => body_html = Some(text),
-                                        // Default to text if we can't determine
-                                        _ => body_text = Some(text),
-                                    }
-                                } else {
-                                    body_text = Some(text);
-                                }
-                            }
-                        }
-                    }
-                }
+        let security = parsed
+            .get("payload")
+            .map(|payload| secure_mail::inspect(payload, body_text.as_deref()))
+            .unwrap_or_default();
+
+        // Never present ciphertext, or a binary S/MIME wrapper, as the body
+        if security.hides_body() {
+            body_text = None;
+            body_html = None;
+            if security.is_encrypted {
+                snippet = None;
             }
         }
+        let signer = security.signer(from.as_deref());
+        let authentication = AuthenticationResults::parse(authentication_results, received_spf);
+
+        let priority = Priority::from_headers(&priority_headers, message.has_label("IMPORTANT"));
+        let delivery_report = parsed.get("payload").and_then(DeliveryReport::from_payload);
+        let read_receipt = parsed.get("payload").and_then(ReadReceipt::from_payload);
+
+        // Point `cid:` references at the image parts they name
+        let mut inline_images = Vec::new();
+        if let (Some(payload), Some(html)) = (parsed.get("payload"), body_html.as_mut()) {
+            inline_images = resolve_inline_images(payload, html, get_inline_image_max_bytes());
+        }
+
+        // Look for trackers before sanitizing removes the pixels
+        let trackers = find_trackers(body_html.as_deref(), body_text.as_deref());
+
+        // Make the HTML safe for clients to render, unless turned off
+        if is_html_sanitizing_enabled() {
+            body_html = body_html.map(|html| sanitize_html(&html));
+        }
+
+        let language = body_text
+            .as_deref()
+            .or(snippet.as_deref())
+            .and_then(crate::utils::detect_language);
 
         // Create the EmailMessage
         Ok(EmailMessage {
-            id,
+            id: message.id,
             thread_id,
             subject,
             from,
@@ -423,222 +790,1439 @@ impl GmailService {
             snippet,
             body_text,
             body_html,
+            rfc_message_id,
+            language,
+            is_signed: security.is_signed,
+            is_encrypted: security.is_encrypted,
+            signer,
+            authentication,
+            inline_images,
+            priority,
+            delivery_report,
+            read_receipt,
+            trackers,
         })
     }
 
     /// List messages and parse metadata into structured EmailMessage objects
     pub async fn list_messages(
-        &mut self,
+        &self,
         max_results: u32,
         query: Option<&str>,
     ) -> Result<Vec<EmailMessage>> {
-        // First get the list of message IDs
-        let raw_json = self.list_messages_raw(max_results, query).await?;
-
-        // Parse the raw JSON
-        let parsed: serde_json::Value = serde_json::from_str(&raw_json).map_err(|e| {
-            GmailApiError::MessageFormatError(format!("Failed to parse message list: {}", e))
-        })?;
-
-        // Extract messages array
-        let messages = parsed["messages"].as_array().ok_or_else(|| {
-            GmailApiError::MessageFormatError("Missing 'messages' array in response".to_string())
-        })?;
-
-        // Create EmailMessage structs by fetching details for each message ID
-        let mut result = Vec::new();
-
-        for message in messages {
-            let id = message["id"].as_str().ok_or_else(|| {
-                GmailApiError::MessageFormatError("Message missing 'id' field".to_string())
-            })?;
+        debug!(
+            "Listing messages with max_results={}, query={:?}",
+            max_results, query
+        );
 
-            // Get full message details
-            match self.get_message_details(id).await {
-                Ok(email) => {
-                    result.push(email);
+        let requester = self.shared_requester().await?;
+        let limiter = RateLimiter::global();
+        let deadline = &self.deadline;
+
+        // Each page of IDs needs the previous page's token, so pages are
+        // listed in sequence while the messages already listed are fetched
+        // concurrently. `buffered` keeps the results in listing order.
+        let (id_sender, ids) = futures::channel::mpsc::unbounded::<String>();
+        let list_ids = async {
+            let mut listed = 0u32;
+            let mut page_token: Option<String> = None;
+            while listed < max_results && !deadline.expired() {
+                let page = requester
+                    .message_id_page(max_results - listed, query, page_token.as_deref())
+                    .await?;
+                for id in page.ids {
+                    listed += 1;
+                    // The receiver only goes away if fetching was abandoned
+                    let _ = id_sender.unbounded_send(id);
                 }
-                Err(e) => {
-                    // Log error but continue with other messages
-                    error!("Failed to get details for message {}: {}", id, e);
+                match page.next_page_token {
+                    Some(token) => page_token = Some(token),
+                    None => break,
                 }
             }
-
-            // Limit to 3 messages to avoid timeout during development
-            if result.len() >= 3 {
-                debug!("Reached limit of 3 messages, stopping fetch to avoid timeout");
-                break;
+            // Close the channel so that fetching can finish
+            drop(id_sender);
+            Ok::<(), GmailApiError>(())
+        };
+        let fetch_messages = ids
+            .take_while(|_| futures::future::ready(!deadline.expired()))
+            .map(|id| {
+                let requester = &requester;
+                async move {
+                    let result = requester.get_message_details(&id).await;
+                    (id, result)
+                }
+            })
+            .buffered(limiter.max_concurrent())
+            .collect::<Vec<_>>();
+
+        let (listed, fetched) = tokio::join!(list_ids, fetch_messages);
+        listed?;
+
+        let mut result = Vec::with_capacity(fetched.len());
+        for (id, details) in fetched {
+            match details {
+                Ok(email) => result.push(email),
+                // Log error but continue with other messages
+                Err(e) => error!("Failed to get details for message {}: {}", id, e),
             }
         }
 
         Ok(result)
     }
 
-    /// List labels and return raw JSON response
-    pub async fn list_labels(&mut self) -> Result<String> {
-        debug!("Listing labels");
+    /// List the IDs of the most recent messages matching an optional query,
+    /// following result pages until `max_results` IDs are collected
+    pub async fn list_message_ids(
+        &self,
+        max_results: u32,
+        query: Option<&str>,
+    ) -> Result<Vec<String>> {
+        debug!(
+            "Listing message IDs with max_results={}, query={:?}",
+            max_results, query
+        );
 
-        let endpoint = "/users/me/labels";
-        self.request_raw(reqwest::Method::GET, endpoint, None).await
-    }
+        let requester = self.shared_requester().await?;
+        let mut ids = Vec::new();
+        let mut page_token: Option<String> = None;
 
-    /// Check connection by getting profile and return raw JSON response
-    pub async fn check_connection_raw(&mut self) -> Result<String> {
-        debug!("Checking connection raw");
+        while (ids.len() as u32) < max_results && !self.deadline.expired() {
+            let page = requester
+                .message_id_page(max_results - ids.len() as u32, query, page_token.as_deref())
+                .await?;
+            ids.extend(page.ids);
 
-        let endpoint = "/users/me/profile";
-        self.request_raw(reqwest::Method::GET, endpoint, None).await
+            match page.next_page_token {
+                Some(token) => page_token = Some(token),
+                None => break,
+            }
+        }
+
+        ids.truncate(max_results as usize);
+        Ok(ids)
     }
 
-    /// Check connection by getting profile and return email and message count
-    pub async fn check_connection(&mut self) -> Result<(String, u64)> {
-        debug!("Checking connection");
+    /// Get the sender, subject, arrival time and labels of a message without
+    /// its content, along with any `extra_headers`
+    pub async fn get_message_metadata(
+        &self,
+        message_id: &str,
+        extra_headers: &[&str],
+    ) -> Result<MessageMetadata> {
+        debug!("Getting metadata for message {}", message_id);
+
+        let requester = self.shared_requester().await?;
+        requester
+            .get_message_metadata(message_id, extra_headers)
+            .await
+    }
 
-        let endpoint = "/users/me/profile";
+    /// List the sender, subject, arrival time and labels of the most recent
+    /// messages matching an optional query, without fetching their content,
+    /// along with any `extra_headers`. Messages that cannot be fetched are
+    /// logged and left out.
+    pub async fn list_message_metadata(
+        &self,
+        max_results: u32,
+        query: Option<&str>,
+        extra_headers: &[&str],
+    ) -> Result<Vec<MessageMetadata>> {
+        debug!(
+            "Listing message metadata with max_results={}, query={:?}, extra_headers={:?}",
+            max_results, query, extra_headers
+        );
 
-        #[derive(Deserialize)]
-        struct Profile {
-            #[serde(rename = "emailAddress")]
-            email_address: String,
-            #[serde(rename = "messagesTotal")]
-            messages_total: Option<u64>,
+        let ids = self.list_message_ids(max_results, query).await?;
+        let requester = self.shared_requester().await?;
+        let fetched = futures::stream::iter(ids)
+            .take_while(|_| futures::future::ready(!self.deadline.expired()))
+            .map(|id| {
+                let requester = &requester;
+                async move {
+                    let result = requester.get_message_metadata(&id, extra_headers).await;
+                    (id, result)
+                }
+            })
+            .buffered(RateLimiter::global().max_concurrent())
+            .collect::<Vec<_>>()
+            .await;
+
+        let mut result = Vec::with_capacity(fetched.len());
+        for (id, metadata) in fetched {
+            match metadata {
+                Ok(metadata) => result.push(metadata),
+                Err(e) => error!("Failed to get metadata for message {}: {}", id, e),
+            }
         }
+        Ok(result)
+    }
 
-        let profile: Profile = self.request(reqwest::Method::GET, endpoint, None).await?;
-
-        let email = profile.email_address;
-        let messages_total = profile.messages_total.unwrap_or(0);
+    /// Get the sender, subject, arrival time and labels of every message in
+    /// each of the given threads, along with any `extra_headers`. Threads that
+    /// cannot be fetched are logged and left out.
+    pub async fn list_thread_metadata(
+        &self,
+        thread_ids: &[String],
+        extra_headers: &[&str],
+    ) -> Result<Vec<Vec<MessageMetadata>>> {
+        debug!(
+            "Listing metadata of {} threads with extra_headers={:?}",
+            thread_ids.len(),
+            extra_headers
+        );
 
-        Ok((email, messages_total))
+        let requester = self.shared_requester().await?;
+        let fetched = futures::stream::iter(thread_ids.to_vec())
+            .take_while(|_| futures::future::ready(!self.deadline.expired()))
+            .map(|id| {
+                let requester = &requester;
+                async move {
+                    let result = requester.get_thread_metadata(&id, extra_headers).await;
+                    (id, result)
+                }
+            })
+            .buffered(RateLimiter::global().max_concurrent())
+            .collect::<Vec<_>>()
+            .await;
+
+        let mut result = Vec::with_capacity(fetched.len());
+        for (id, messages) in fetched {
+            match messages {
+                Ok(messages) => result.push(messages),
+                Err(e) => error!("Failed to get metadata for thread {}: {}", id, e),
+            }
+        }
+        Ok(result)
     }
 
-    /// Create a draft email in Gmail
-    pub async fn create_draft(&mut self, draft: &DraftEmail) -> Result<String> {
-        debug!("Creating draft email to: {}", draft.to);
-
-        // Construct the RFC 5322 formatted message
-        let mut message = format!(
-            "From: me\r\n\
-             To: {}\r\n\
-             Subject: {}\r\n",
-            draft.to, draft.subject
+    /// Count the messages matching an optional query without fetching them.
+    ///
+    /// Matches are counted exactly by paging through their IDs, up to
+    /// `exact_limit`; beyond that the count is Gmail's `resultSizeEstimate`,
+    /// which can be far off for broad queries.
+    pub async fn count_messages(
+        &self,
+        query: Option<&str>,
+        exact_limit: u32,
+    ) -> Result<MessageCount> {
+        debug!(
+            "Counting messages with query={:?}, exact_limit={}",
+            query, exact_limit
         );
 
-        // Add optional CC and BCC fields
-        if let Some(cc) = &draft.cc {
-            message.push_str(&format!("Cc: {}\r\n", cc));
+        let requester = self.shared_requester().await?;
+        let mut counted = 0u32;
+        let mut page_token: Option<String> = None;
+
+        loop {
+            // A page is always listed, even with no room left, for its estimate
+            let page = requester
+                .message_id_page(
+                    exact_limit.saturating_sub(counted).max(1),
+                    query,
+                    page_token.as_deref(),
+                )
+                .await?;
+            counted += page.ids.len() as u32;
+
+            match page.next_page_token {
+                Some(token) if counted < exact_limit => page_token = Some(token),
+                Some(_) => {
+                    let estimate = page.result_size_estimate.unwrap_or_default();
+                    return Ok(MessageCount {
+                        count: estimate.max(counted as u64),
+                        exact: false,
+                    });
+                }
+                None => {
+                    return Ok(MessageCount {
+                        count: counted as u64,
+                        exact: true,
+                    })
+                }
+            }
         }
+    }
 
-        if let Some(bcc) = &draft.bcc {
-            message.push_str(&format!("Bcc: {}\r\n", bcc));
-        }
+    /// Get the mailbox's current history ID, the starting point for
+    /// `list_history`
+    pub async fn get_history_id(&self) -> Result<String> {
+        debug!("Getting current history ID");
 
-        // Add threading headers for replies
-        if let Some(in_reply_to) = &draft.in_reply_to {
-            message.push_str(&format!("In-Reply-To: {}\r\n", in_reply_to));
-        }
+        self.get_profile().await?.history_id.ok_or_else(|| {
+            GmailApiError::MessageFormatError("Profile is missing 'historyId'".to_string())
+        })
+    }
 
-        if let Some(references) = &draft.references {
-            message.push_str(&format!("References: {}\r\n", references));
-        }
+    /// List messages added and deleted since `start_history_id`
+    ///
+    /// Gmail only keeps history for about a week; an expired start ID fails
+    /// with `MessageRetrievalError` and the caller should resynchronize.
+    pub async fn list_history(&self, start_history_id: &str) -> Result<HistoryChanges> {
+        debug!("Listing history since {}", start_history_id);
 
-        // Add body
-        message.push_str("\r\n");
-        message.push_str(&draft.body);
+        let mut changes = HistoryChanges {
+            history_id: start_history_id.to_string(),
+            ..Default::default()
+        };
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let mut params = vec![
+                ("startHistoryId", start_history_id),
+                ("historyTypes", "messageAdded"),
+                ("historyTypes", "messageDeleted"),
+            ];
+            if let Some(token) = &page_token {
+                params.push(("pageToken", token.as_str()));
+            }
 
-        // Base64 encode the message
-        // Encode the message as base64url format for Gmail API
-        // Note: For large messages with attachments or nested MIME structures,
-        // we would need enhanced handling to process them in chunks or parts
-        // This handles the basic email case - more complex handling would be needed for
-        // large attachments, nested content, or multipart messages exceeding size limits
-        let encoded_message = base64::encode(message.as_bytes())
-            .replace('+', "-")
-            .replace('/', "_");
-
-        // Log the message size for debugging large messages
-        debug!("Encoded message size: {} bytes", encoded_message.len());
-
-        // Create the JSON payload
-        let mut message_payload = serde_json::json!({
-            "raw": encoded_message
-        });
+            let page: HistoryList = self
+                .request(
+                    reqwest::Method::GET,
+                    &self.user_path("/history"),
+                    Some(&params),
+                )
+                .await?;
+
+            for record in page.history {
+                for added in record.messages_added {
+                    let id = added.message.id;
+                    changes.deleted.retain(|deleted| *deleted != id);
+                    if !changes.added.contains(&id) {
+                        changes.added.push(id);
+                    }
+                }
+                for deleted in record.messages_deleted {
+                    let id = deleted.message.id;
+                    changes.added.retain(|added| *added != id);
+                    if !changes.deleted.contains(&id) {
+                        changes.deleted.push(id);
+                    }
+                }
+            }
 
-        // Add thread_id if specified
-        if let Some(thread_id) = &draft.thread_id {
-            message_payload = serde_json::json!({
-                "raw": encoded_message,
-                "threadId": thread_id
-            });
+            if let Some(history_id) = page.history_id {
+                changes.history_id = history_id;
+            }
+
+            match page.next_page_token {
+                Some(token) => page_token = Some(token),
+                None => break,
+            }
         }
 
-        let payload = serde_json::json!({
-            "message": message_payload
-        });
+        Ok(changes)
+    }
 
-        // Make the request to create a draft
-        let endpoint = "/users/me/drafts";
+    /// List the attachments on a message
+    pub async fn list_attachments(&self, message_id: &str) -> Result<Vec<AttachmentInfo>> {
+        debug!("Listing attachments for message {}", message_id);
 
-        // Get valid access token
-        let token = self.token_manager.get_token(&self.client).await?;
+        let message_json = self.get_message_raw(message_id).await?;
+        let parsed: Value = serde_json::from_str(&message_json).map_err(|e| {
+            GmailApiError::MessageFormatError(format!("Failed to parse message JSON: {}", e))
+        })?;
 
-        let url = format!("{}{}", GMAIL_API_BASE_URL, endpoint);
-        debug!("Creating draft at: {}", url);
+        Ok(find_attachments(&parsed["payload"]))
+    }
 
-        // Send the request
-        let response = self
+    /// Get message details together with the attachments on the message,
+    /// from a single request
+    pub async fn get_message_with_attachments(
+        &self,
+        message_id: &str,
+    ) -> Result<(EmailMessage, Vec<AttachmentInfo>)> {
+        debug!("Getting message with attachments, ID: {}", message_id);
+
+        let endpoint = self.user_path(&format!("/messages/{}", message_id));
+        let parsed: Value = self
+            .request(reqwest::Method::GET, &endpoint, Some(&[("format", "full")]))
+            .await?;
+
+        let email = Self::parse_message_details(&parsed)?;
+        Ok((email, find_attachments(&parsed["payload"])))
+    }
+
+    /// Download an attachment to `dest`, returning the number of bytes written
+    ///
+    /// The response is decoded as it arrives and written to a `.part` file
+    /// that is renamed to `dest` once complete, so memory use stays bounded
+    /// however large the attachment is. The download fails, leaving no file
+    /// behind, if the attachment is larger than `options.max_size`.
+    pub async fn download_attachment(
+        &self,
+        message_id: &str,
+        attachment_id: &str,
+        dest: &Path,
+        mut options: DownloadOptions,
+    ) -> Result<u64> {
+        debug!(
+            "Downloading attachment {} of message {} to {}",
+            attachment_id,
+            message_id,
+            dest.display()
+        );
+
+        let token = self.access_token().await?;
+        let url = format!(
+            "{}{}/messages/{}/attachments/{}",
+            get_gmail_api_base_url(),
+            self.user_path(""),
+            message_id,
+            attachment_id
+        );
+
+        let mut response = self
             .client
-            .post(&url)
+            .get(&url)
             .header("Authorization", format!("Bearer {}", token))
-            .header("Content-Type", "application/json")
-            .json(&payload)
+            .header("Accept", "application/json")
             .send()
             .await
-            .map_err(|e| {
-                error!("Network error creating draft: {}", e);
-                GmailApiError::NetworkError(e.to_string())
-            })?;
+            .map_err(|e| GmailApiError::NetworkError(e.to_string()))?;
 
-        // Handle response
         let status = response.status();
-        debug!("Draft creation response status: {}", status);
-
         if !status.is_success() {
+            let retry_after = retry_after_header(response.headers());
             let error_text = response
                 .text()
                 .await
                 .unwrap_or_else(|_| "<no response body>".to_string());
-
-            error!("Failed to create draft: {}", error_text);
-            return Err(GmailApiError::ApiError(format!(
-                "Failed to create draft. Status: {}, Error: {}",
-                status, error_text
-            )));
+            return Err(error_for_status(status, retry_after.as_deref(), error_text));
         }
 
-        // Parse the response to get the draft ID
-        let response_text = response.text().await.map_err(|e| {
-            error!("Failed to get response body: {}", e);
-            GmailApiError::NetworkError(format!("Failed to get response body: {}", e))
-        })?;
+        // base64 is 4/3 the size of the content it encodes
+        let estimated_size = response.content_length().map(|len| len / 4 * 3);
+        if let Some(size) = estimated_size {
+            if size > options.max_size.saturating_add(1024) {
+                return Err(attachment_too_large(options.max_size));
+            }
+        }
+
+        let part_path = dest.with_file_name(format!(
+            "{}.part",
+            dest.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "attachment".to_string())
+        ));
+        if let Some(parent) = dest.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| attachment_io_error(parent, e))?;
+        }
+        let mut file = tokio::fs::File::create(&part_path)
+            .await
+            .map_err(|e| attachment_io_error(&part_path, e))?;
+
+        let mut decoder = AttachmentDecoder::new();
+        let mut buffer = Vec::new();
+        let mut written: u64 = 0;
+
+        let result: Result<()> = async {
+            while let Some(chunk) = response
+                .chunk()
+                .await
+                .map_err(|e| GmailApiError::NetworkError(e.to_string()))?
+            {
+                buffer.clear();
+                decoder.feed(&chunk, &mut buffer)?;
+                if buffer.is_empty() {
+                    continue;
+                }
+
+                written += buffer.len() as u64;
+                if written > options.max_size {
+                    return Err(attachment_too_large(options.max_size));
+                }
+                file.write_all(&buffer)
+                    .await
+                    .map_err(|e| attachment_io_error(&part_path, e))?;
+
+                if let Some(progress) = options.progress.as_mut() {
+                    progress(written, decoder.declared_size().or(estimated_size));
+                }
+            }
+            decoder.finish()?;
+            file.flush()
+                .await
+                .map_err(|e| attachment_io_error(&part_path, e))
+        }
+        .await;
+
+        drop(file);
+        if let Err(e) = result {
+            let _ = tokio::fs::remove_file(&part_path).await;
+            return Err(e);
+        }
+
+        tokio::fs::rename(&part_path, dest)
+            .await
+            .map_err(|e| attachment_io_error(dest, e))?;
+
+        info!("Saved attachment {} ({} bytes)", dest.display(), written);
+        Ok(written)
+    }
+
+    /// List labels and return raw JSON response
+    pub async fn list_labels(&self) -> Result<String> {
+        debug!("Listing labels");
+
+        let endpoint = &self.user_path("/labels");
+        self.request_raw_conditional(reqwest::Method::GET, endpoint, None)
+            .await
+    }
+
+    /// Get the ID of the label with the given name, creating it if missing.
+    /// Names are matched case-insensitively, as Gmail does.
+    pub async fn get_or_create_label(&self, name: &str) -> Result<String> {
+        self.create_label(name).await.map(|creation| creation.id)
+    }
+
+    /// Create a label unless it exists, creating first any parents a nested
+    /// name such as "Projects/Apollo" sits in. Names are matched
+    /// case-insensitively, as Gmail does.
+    pub async fn create_label(&self, name: &str) -> Result<LabelCreation> {
+        debug!("Looking up label {}", name);
+
+        let name = normalize_label_name(name).map_err(GmailApiError::MessageFormatError)?;
+        let labels = parse_labels(&self.list_labels().await?).map_err(|e| {
+            GmailApiError::MessageFormatError(format!("Failed to parse labels: {}", e))
+        })?;
+
+        let mut created = Vec::new();
+        let mut id = String::new();
+        for level in ancestor_names(&name).into_iter().chain([name.clone()]) {
+            if let Some(label) = find_label(&labels, &level) {
+                id = label.id.clone();
+                continue;
+            }
+
+            info!("Creating label {}", level);
+            let body = serde_json::json!({
+                "name": level,
+                "labelListVisibility": "labelShow",
+                "messageListVisibility": "show"
+            });
+            let label: LabelInfo = self
+                .request_with_body(
+                    reqwest::Method::POST,
+                    &self.user_path("/labels"),
+                    None,
+                    Some(&body),
+                )
+                .await?;
+            id = label.id;
+            created.push(level);
+        }
+
+        Ok(LabelCreation { id, name, created })
+    }
+
+    /// List the people with delegated access to the mailbox
+    pub async fn list_delegates(&self) -> Result<Vec<Delegate>> {
+        debug!("Listing delegates of mailbox {}", self.user_id);
+
+        #[derive(Deserialize)]
+        struct DelegateList {
+            #[serde(default)]
+            delegates: Vec<Delegate>,
+        }
+
+        let list: DelegateList = self
+            .request(
+                reqwest::Method::GET,
+                &self.user_path("/settings/delegates"),
+                None,
+            )
+            .await?;
+        Ok(list.delegates)
+    }
+
+    /// Give someone delegated access to the mailbox. Google only allows this
+    /// for Workspace accounts, through a service account with domain-wide
+    /// authority.
+    pub async fn add_delegate(&self, delegate_email: &str) -> Result<Delegate> {
+        info!(
+            "Adding delegate {} to mailbox {}",
+            delegate_email, self.user_id
+        );
+
+        let body = serde_json::json!({ "delegateEmail": delegate_email });
+        self.request_with_body(
+            reqwest::Method::POST,
+            &self.user_path("/settings/delegates"),
+            None,
+            Some(&body),
+        )
+        .await
+    }
+
+    /// List the addresses the mailbox can forward to
+    pub async fn list_forwarding_addresses(&self) -> Result<Vec<ForwardingAddress>> {
+        debug!("Listing forwarding addresses of mailbox {}", self.user_id);
+
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct ForwardingAddressList {
+            #[serde(default)]
+            forwarding_addresses: Vec<ForwardingAddress>,
+        }
+
+        let list: ForwardingAddressList = self
+            .request(
+                reqwest::Method::GET,
+                &self.user_path("/settings/forwardingAddresses"),
+                None,
+            )
+            .await?;
+        Ok(list.forwarding_addresses)
+    }
+
+    /// Get the mailbox's auto-forwarding setting
+    pub async fn get_auto_forwarding(&self) -> Result<AutoForwarding> {
+        debug!("Getting auto-forwarding of mailbox {}", self.user_id);
+
+        self.request(
+            reqwest::Method::GET,
+            &self.user_path("/settings/autoForwarding"),
+            None,
+        )
+        .await
+    }
+
+    /// Change the mailbox's auto-forwarding setting. Google only forwards to
+    /// verified forwarding addresses.
+    pub async fn update_auto_forwarding(
+        &self,
+        auto_forwarding: &AutoForwarding,
+    ) -> Result<AutoForwarding> {
+        info!(
+            "Updating auto-forwarding of mailbox {}: {:?}",
+            self.user_id, auto_forwarding
+        );
+
+        let body = serde_json::to_value(auto_forwarding)
+            .map_err(|e| GmailApiError::MessageFormatError(e.to_string()))?;
+        self.request_with_body(
+            reqwest::Method::PUT,
+            &self.user_path("/settings/autoForwarding"),
+            None,
+            Some(&body),
+        )
+        .await
+    }
+
+    /// Get the mailbox's IMAP settings
+    pub async fn get_imap_settings(&self) -> Result<ImapSettings> {
+        debug!("Getting IMAP settings of mailbox {}", self.user_id);
+
+        self.request(
+            reqwest::Method::GET,
+            &self.user_path("/settings/imap"),
+            None,
+        )
+        .await
+    }
+
+    /// Get the mailbox's POP settings
+    pub async fn get_pop_settings(&self) -> Result<PopSettings> {
+        debug!("Getting POP settings of mailbox {}", self.user_id);
+
+        self.request(reqwest::Method::GET, &self.user_path("/settings/pop"), None)
+            .await
+    }
+
+    /// Get the language Gmail is shown in for the mailbox
+    pub async fn get_language_settings(&self) -> Result<LanguageSettings> {
+        debug!("Getting language settings of mailbox {}", self.user_id);
+
+        self.request(
+            reqwest::Method::GET,
+            &self.user_path("/settings/language"),
+            None,
+        )
+        .await
+    }
+
+    /// Add and remove labels on a message
+    pub async fn modify_labels(
+        &self,
+        message_id: &str,
+        add_label_ids: &[String],
+        remove_label_ids: &[String],
+    ) -> Result<()> {
+        debug!(
+            "Modifying labels on {}: add {:?}, remove {:?}",
+            message_id, add_label_ids, remove_label_ids
+        );
+
+        let endpoint = self.user_path(&format!("/messages/{}/modify", message_id));
+        let body = serde_json::json!({
+            "addLabelIds": add_label_ids,
+            "removeLabelIds": remove_label_ids
+        });
+        let _: Value = self
+            .request_with_body(reqwest::Method::POST, &endpoint, None, Some(&body))
+            .await?;
+        Ok(())
+    }
+
+    /// Add and remove labels on every message in a thread, returning the IDs
+    /// of the thread's messages
+    pub async fn modify_thread_labels(
+        &self,
+        thread_id: &str,
+        add_label_ids: &[String],
+        remove_label_ids: &[String],
+    ) -> Result<Vec<String>> {
+        debug!(
+            "Modifying labels on thread {}: add {:?}, remove {:?}",
+            thread_id, add_label_ids, remove_label_ids
+        );
+
+        let endpoint = self.user_path(&format!("/threads/{}/modify", thread_id));
+        let body = serde_json::json!({
+            "addLabelIds": add_label_ids,
+            "removeLabelIds": remove_label_ids
+        });
+        let thread: Thread = self
+            .request_with_body(reqwest::Method::POST, &endpoint, None, Some(&body))
+            .await?;
+        Ok(thread.message_ids())
+    }
+
+    /// Move every message in a thread to the trash, returning the IDs of the
+    /// thread's messages
+    pub async fn trash_thread(&self, thread_id: &str) -> Result<Vec<String>> {
+        debug!("Trashing thread {}", thread_id);
+
+        let endpoint = self.user_path(&format!("/threads/{}/trash", thread_id));
+        let thread: Thread = self
+            .request_with_body(reqwest::Method::POST, &endpoint, None, None)
+            .await?;
+        Ok(thread.message_ids())
+    }
+
+    /// Check connection by getting profile and return raw JSON response
+    pub async fn check_connection_raw(&self) -> Result<String> {
+        debug!("Checking connection raw");
+
+        let endpoint = &self.user_path("/profile");
+        self.request_raw(reqwest::Method::GET, endpoint, None).await
+    }
+
+    /// Get the mailbox's profile: its address, message and thread totals and
+    /// current history ID
+    pub async fn get_profile(&self) -> Result<GmailProfile> {
+        debug!("Getting profile");
+
+        let endpoint = &self.user_path("/profile");
+        self.request(reqwest::Method::GET, endpoint, None).await
+    }
+
+    /// Check connection by getting profile and return email and message count
+    pub async fn check_connection(&self) -> Result<(String, u64)> {
+        debug!("Checking connection");
+
+        let profile = self.get_profile().await?;
+        Ok((profile.email_address, profile.messages_total))
+    }
+
+    /// The address of the signed-in account
+    pub async fn get_email_address(&self) -> Result<String> {
+        let (email, _) = self.check_connection().await?;
+        Ok(email)
+    }
+
+    /// Look up the current access token with Google's tokeninfo endpoint:
+    /// its remaining lifetime, granted scopes and account
+    pub async fn token_info(&self) -> Result<TokenInfo> {
+        debug!("Looking up access token info");
+
+        let token = self.access_token().await?;
+        let response = self
+            .client
+            .get(get_token_info_url())
+            .query(&[("access_token", token.as_str())])
+            .send()
+            .await
+            .map_err(|e| GmailApiError::NetworkError(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "<no response body>".to_string());
+            return Err(GmailApiError::AuthError(format!(
+                "Failed to look up access token. Status: {}, Error: {}",
+                status, error_text
+            )));
+        }
+
+        let json = response
+            .json::<Value>()
+            .await
+            .map_err(|e| GmailApiError::MessageFormatError(e.to_string()))?;
+        Ok(TokenInfo::from_response(&json, Utc::now()))
+    }
+
+    /// Create a draft email in Gmail
+    pub async fn create_draft(&self, draft: &DraftEmail) -> Result<String> {
+        self.create_draft_with_attachments(draft, &[]).await
+    }
+
+    /// Create a draft email in Gmail with files attached
+    pub async fn create_draft_with_attachments(
+        &self,
+        draft: &DraftEmail,
+        attachments: &[OutgoingAttachment],
+    ) -> Result<String> {
+        debug!(
+            "Creating draft email to: {} with {} attachment(s)",
+            draft.to,
+            attachments.len()
+        );
+
+        let message = checked_message(draft, attachments)?;
+        let endpoint = self.user_path("/drafts");
+
+        // Upload large messages in chunks that can be retried on their own
+        if message.len() > get_resumable_threshold() {
+            let metadata = serde_json::json!({ "message": message_metadata(draft) });
+            let created: Draft = self.upload_message(&endpoint, &metadata, &message).await?;
+            debug!("Draft created successfully with ID: {}", created.id);
+            return Ok(created.id);
+        }
+        let payload = draft_payload(draft, &message);
+
+        // Get valid access token
+        let token = self.access_token().await?;
+
+        let url = format!("{}{}", get_gmail_api_base_url(), endpoint);
+        debug!("Creating draft at: {}", url);
+
+        // Send the request
+        let response = self
+            .client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| {
+                error!("Network error creating draft: {}", e);
+                GmailApiError::NetworkError(e.to_string())
+            })?;
+
+        // Handle response
+        let status = response.status();
+        debug!("Draft creation response status: {}", status);
+
+        if !status.is_success() {
+            let retry_after = retry_after_header(response.headers());
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "<no response body>".to_string());
+
+            error!("Failed to create draft: {}", error_text);
+            if let Some(info) =
+                RateLimitInfo::from_response(status.as_u16(), retry_after.as_deref(), &error_text)
+            {
+                return Err(GmailApiError::RateLimited(info));
+            }
+            return Err(GmailApiError::ApiError(format!(
+                "Failed to create draft. Status: {}, Error: {}",
+                status, error_text
+            )));
+        }
+
+        // Parse the response to get the draft ID
+        let response_text = response.text().await.map_err(|e| {
+            error!("Failed to get response body: {}", e);
+            GmailApiError::NetworkError(format!("Failed to get response body: {}", e))
+        })?;
 
         // Parse the JSON response
-        let response_json: serde_json::Value =
-            serde_json::from_str(&response_text).map_err(|e| {
+        let draft_id = serde_json::from_str::<Draft>(&response_text)
+            .map_err(|e| {
                 error!("Failed to parse draft response: {}", e);
                 GmailApiError::MessageFormatError(format!("Failed to parse draft response: {}", e))
-            })?;
-
-        // Extract the draft ID
-        let draft_id = response_json["id"]
-            .as_str()
-            .ok_or_else(|| {
-                GmailApiError::MessageFormatError("Draft response missing 'id' field".to_string())
             })?
-            .to_string();
+            .id;
 
         debug!("Draft created successfully with ID: {}", draft_id);
 
         Ok(draft_id)
     }
+
+    /// Create several drafts, up to the rate limiter's number at a time,
+    /// returning each draft's ID or why it failed in the order given. Drafts
+    /// not yet started when the deadline passes are left out.
+    pub async fn create_drafts(&self, drafts: &[DraftEmail]) -> Result<Vec<Result<String>>> {
+        debug!("Creating {} drafts", drafts.len());
+
+        let requester = self.shared_requester().await?;
+        let created = futures::stream::iter(drafts.to_vec())
+            .take_while(|_| futures::future::ready(!self.deadline.expired()))
+            .map(|draft| {
+                let requester = &requester;
+                async move { requester.create_draft(&draft).await }
+            })
+            .buffered(RateLimiter::global().max_concurrent())
+            .collect::<Vec<_>>()
+            .await;
+        Ok(created)
+    }
+
+    /// Send several emails one at a time with a pause between them,
+    /// returning each sent message's ID or why it failed in the order given.
+    /// Emails not yet sent when the deadline passes are left out.
+    pub async fn send_messages(
+        &self,
+        drafts: &[DraftEmail],
+        interval: Duration,
+    ) -> Vec<Result<String>> {
+        debug!("Sending {} emails {:?} apart", drafts.len(), interval);
+
+        let mut sent = Vec::with_capacity(drafts.len());
+        for (index, draft) in drafts.iter().enumerate() {
+            if index > 0 {
+                tokio::time::sleep(interval).await;
+            }
+            if self.deadline.expired() {
+                break;
+            }
+            sent.push(self.send_message(draft).await);
+        }
+        sent
+    }
+
+    /// Send an email immediately, returning the ID of the sent message
+    pub async fn send_message(&self, draft: &DraftEmail) -> Result<String> {
+        self.send_message_with_attachments(draft, &[]).await
+    }
+
+    /// Send an email with files attached, returning the ID of the sent message
+    pub async fn send_message_with_attachments(
+        &self,
+        draft: &DraftEmail,
+        attachments: &[OutgoingAttachment],
+    ) -> Result<String> {
+        debug!(
+            "Sending email to: {} with {} attachment(s)",
+            draft.to,
+            attachments.len()
+        );
+
+        // Refuse emails the sending policy doesn't allow, taking a place
+        // under the daily limit that is given back if the send fails
+        let reserved = SendPolicy::from_env()
+            .reserve(draft, SendLog::global())
+            .map_err(GmailApiError::PolicyViolation)?;
+
+        let sent: Result<Message> = async {
+            let message = checked_message(draft, attachments)?;
+            let endpoint = self.user_path("/messages/send");
+            if message.len() > get_resumable_threshold() {
+                self.upload_message(&endpoint, &message_metadata(draft), &message)
+                    .await
+            } else {
+                let mut payload = message_metadata(draft);
+                payload["raw"] = serde_json::json!(encode_message(&message));
+                self.request_with_body(reqwest::Method::POST, &endpoint, None, Some(&payload))
+                    .await
+            }
+        }
+        .await;
+        let message_id = match sent {
+            Ok(sent) => sent.id,
+            Err(err) => {
+                if let Some(at) = reserved {
+                    SendLog::global().release(at);
+                }
+                return Err(err);
+            }
+        };
+
+        info!("Email sent successfully with ID: {}", message_id);
+        Ok(message_id)
+    }
+}
+
+fn attachment_too_large(max_size: u64) -> GmailApiError {
+    GmailApiError::AttachmentError(format!(
+        "Attachment is larger than the {} byte limit",
+        max_size
+    ))
+}
+
+fn attachment_io_error(path: &Path, err: std::io::Error) -> GmailApiError {
+    GmailApiError::AttachmentError(format!("Failed to write {}: {}", path.display(), err))
+}
+
+/// A Message-ID without the angle brackets and space around it
+pub fn bare_message_id(id: &str) -> &str {
+    id.trim()
+        .trim_start_matches('<')
+        .trim_end_matches('>')
+        .trim()
+}
+
+/// Decode a base64url message body, decoding at most `max_bytes`.
+///
+/// Longer bodies are cut at a character boundary and end with a note giving
+/// their full size; the rest of the encoded data is never decoded. Returns
+/// `None` if the data is not valid base64 or, for complete bodies, UTF-8.
+pub fn decode_body(data: &str, max_bytes: usize) -> Option<String> {
+    // Gmail uses the URL-safe alphabet, but accept standard base64 too
+    // without copying data that needs no conversion
+    let data: Cow<str> = if data.contains(['+', '/']) {
+        Cow::Owned(data.replace('+', "-").replace('/', "_"))
+    } else {
+        Cow::Borrowed(data)
+    };
+    let data = data.trim_end_matches('=');
+
+    // Four base64 characters encode three bytes
+    let full_size = data.len() / 4 * 3 + (data.len() % 4).saturating_sub(1);
+    if full_size <= max_bytes {
+        let decoded = base64::decode_config(data, base64::URL_SAFE_NO_PAD).ok()?;
+        return String::from_utf8(decoded).ok();
+    }
+
+    let prefix = data.get(..max_bytes / 3 * 4)?;
+    let decoded = base64::decode_config(prefix, base64::URL_SAFE_NO_PAD).ok()?;
+    let mut text = match String::from_utf8(decoded) {
+        Ok(text) => text,
+        // The cut may fall inside a multi-byte character
+        Err(e) => {
+            let valid = e.utf8_error().valid_up_to();
+            let mut bytes = e.into_bytes();
+            bytes.truncate(valid);
+            String::from_utf8(bytes).ok()?
+        }
+    };
+    debug!(
+        "Truncated message body from {} to {} bytes",
+        full_size,
+        text.len()
+    );
+    text.push_str(&format!(
+        "\n\n[Message body truncated: showing {} of {} bytes]",
+        text.len(),
+        full_size
+    ));
+    Some(text)
+}
+
+// One page of a message listing
+struct MessageIdPage {
+    ids: Vec<String>,
+    next_page_token: Option<String>,
+    result_size_estimate: Option<u64>,
+}
+
+// An authenticated Gmail client for the concurrent requests of one call,
+// holding the token obtained when the call started
+struct SharedRequester {
+    client: Client,
+    token: String,
+    user_id: String,
+}
+
+impl SharedRequester {
+    fn user_path(&self, path: &str) -> String {
+        user_path(&self.user_id, path)
+    }
+
+    async fn get_json<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        query: &[(&str, &str)],
+    ) -> Result<T> {
+        let _permit = RateLimiter::global().acquire().await;
+
+        let url = format!("{}{}", get_gmail_api_base_url(), endpoint);
+        debug!("Making shared request to: {}", url);
+        let response = self
+            .client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", self.token))
+            .header("Accept", "application/json")
+            .query(query)
+            .send()
+            .await
+            .map_err(|e| GmailApiError::NetworkError(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let retry_after = retry_after_header(response.headers());
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "<no response body>".to_string());
+            return Err(error_for_status(status, retry_after.as_deref(), error_text));
+        }
+
+        response.json::<T>().await.map_err(|e| {
+            GmailApiError::MessageFormatError(format!("Failed to parse response: {}", e))
+        })
+    }
+
+    async fn post_json<T: DeserializeOwned>(&self, endpoint: &str, body: &Value) -> Result<T> {
+        let _permit = RateLimiter::global().acquire().await;
+
+        let url = format!("{}{}", get_gmail_api_base_url(), endpoint);
+        debug!("Making shared POST request to: {}", url);
+        let response = self
+            .client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", self.token))
+            .header("Accept", "application/json")
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| GmailApiError::NetworkError(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let retry_after = retry_after_header(response.headers());
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "<no response body>".to_string());
+            return Err(error_for_status(status, retry_after.as_deref(), error_text));
+        }
+
+        response.json::<T>().await.map_err(|e| {
+            GmailApiError::MessageFormatError(format!("Failed to parse response: {}", e))
+        })
+    }
+
+    async fn create_draft(&self, draft: &DraftEmail) -> Result<String> {
+        let created: Draft = self
+            .post_json(
+                &self.user_path("/drafts"),
+                &draft_payload(draft, &checked_message(draft, &[])?),
+            )
+            .await?;
+        Ok(created.id)
+    }
+
+    // List up to `max_results` message IDs, starting at `page_token`
+    async fn message_id_page(
+        &self,
+        max_results: u32,
+        query: Option<&str>,
+        page_token: Option<&str>,
+    ) -> Result<MessageIdPage> {
+        // Gmail returns at most 500 IDs per page
+        let page_size = max_results.min(500).to_string();
+        let mut params = vec![("maxResults", page_size.as_str())];
+        if let Some(q) = query {
+            params.push(("q", q));
+        }
+        if let Some(token) = page_token {
+            params.push(("pageToken", token));
+        }
+
+        let page: MessageList = self.get_json(&self.user_path("/messages"), &params).await?;
+
+        let mut ids: Vec<String> = page
+            .messages
+            .into_iter()
+            .map(|message| message.id)
+            .collect();
+        ids.truncate(max_results as usize);
+
+        Ok(MessageIdPage {
+            ids,
+            next_page_token: page.next_page_token,
+            result_size_estimate: page.result_size_estimate,
+        })
+    }
+
+    async fn get_message_details(&self, message_id: &str) -> Result<EmailMessage> {
+        let endpoint = self.user_path(&format!("/messages/{}", message_id));
+        let message: Value = self.get_json(&endpoint, &[("format", "full")]).await?;
+        GmailService::parse_message_details(&message)
+    }
+
+    async fn get_message_metadata(
+        &self,
+        message_id: &str,
+        extra_headers: &[&str],
+    ) -> Result<MessageMetadata> {
+        let endpoint = self.user_path(&format!("/messages/{}", message_id));
+        let message = self
+            .get_json(&endpoint, &metadata_query(extra_headers))
+            .await?;
+        Ok(parse_message_metadata(message, extra_headers))
+    }
+
+    async fn get_thread_metadata(
+        &self,
+        thread_id: &str,
+        extra_headers: &[&str],
+    ) -> Result<Vec<MessageMetadata>> {
+        let endpoint = self.user_path(&format!("/threads/{}", thread_id));
+        let thread: Thread = self
+            .get_json(&endpoint, &metadata_query(extra_headers))
+            .await?;
+        Ok(thread
+            .messages
+            .into_iter()
+            .map(|message| parse_message_metadata(message, extra_headers))
+            .collect())
+    }
+}
+
+// Body of a request creating a draft from its raw message
+fn draft_payload(draft: &DraftEmail, message: &str) -> Value {
+    let mut payload = message_metadata(draft);
+    payload["raw"] = serde_json::json!(encode_message(message));
+    serde_json::json!({ "message": payload })
+}
+
+// Message fields other than its content: the draft's thread, if it has one
+fn message_metadata(draft: &DraftEmail) -> Value {
+    let mut metadata = serde_json::json!({});
+    if let Some(thread_id) = &draft.thread_id {
+        metadata["threadId"] = serde_json::json!(thread_id);
+    }
+    metadata
+}
+
+// Query for metadata-format responses with the headers `MessageMetadata`
+// holds
+fn metadata_query<'a>(extra_headers: &[&'a str]) -> Vec<(&'static str, &'a str)> {
+    let mut query = vec![
+        ("format", "metadata"),
+        ("metadataHeaders", "From"),
+        ("metadataHeaders", "Subject"),
+        ("metadataHeaders", "Date"),
+    ];
+    query.extend(extra_headers.iter().map(|name| ("metadataHeaders", *name)));
+    query
+}
+
+fn parse_message_metadata(message: Message, extra_headers: &[&str]) -> MessageMetadata {
+    let received_at = message.received_at().or_else(|| {
+        message
+            .header("Date")
+            .and_then(|date| DateTime::parse_from_rfc2822(date).ok())
+            .map(|date| date.with_timezone(&Utc))
+    });
+    let header = |name: &str| message.header(name).map(String::from);
+
+    MessageMetadata {
+        thread_id: message
+            .thread_id
+            .clone()
+            .unwrap_or_else(|| message.id.clone()),
+        from: header("From"),
+        subject: header("Subject"),
+        received_at,
+        headers: extra_headers
+            .iter()
+            .filter_map(|name| Some((name.to_lowercase(), header(name)?)))
+            .collect(),
+        id: message.id,
+        snippet: message.snippet,
+        label_ids: message.label_ids,
+    }
+}
+
+// Map a non-success HTTP status from the Gmail API to the matching error type
+pub(crate) fn error_for_status(
+    status: reqwest::StatusCode,
+    retry_after: Option<&str>,
+    error_text: String,
+) -> GmailApiError {
+    // Quota errors come as 429, or as 403 with a quota reason in the body
+    if let Some(info) = RateLimitInfo::from_response(status.as_u16(), retry_after, &error_text) {
+        return GmailApiError::RateLimited(info);
+    }
+
+    match status.as_u16() {
+        401 | 403 => GmailApiError::AuthError(format!(
+            "Authentication failed. Status: {}, Error: {}",
+            status, error_text
+        )),
+        404 => GmailApiError::MessageRetrievalError(format!(
+            "Resource not found. Status: {}, Error: {}",
+            status, error_text
+        )),
+        429 => GmailApiError::RateLimitError(format!(
+            "Rate limit exceeded. Status: {}, Error: {}",
+            status, error_text
+        )),
+        _ => GmailApiError::ApiError(format!(
+            "API request failed. Status: {}, Error: {}",
+            status, error_text
+        )),
+    }
+}
+
+/// Build the RFC 5322 message for a draft and encode it as base64url for the Gmail API
+pub fn encode_raw_message(draft: &DraftEmail) -> String {
+    encode_raw_message_with_attachments(draft, &[])
+}
+
+/// Build the message for a draft with files attached and encode it as
+/// base64url. Without attachments this is a plain text message; with them,
+/// the body and each file become parts of a `multipart/mixed` message.
+pub fn encode_raw_message_with_attachments(
+    draft: &DraftEmail,
+    attachments: &[OutgoingAttachment],
+) -> String {
+    encode_message(&build_raw_message(draft, attachments))
+}
+
+/// Raw bytes of attachments that fit in a message with `body` within Gmail's
+/// size limit, allowing for headers and for attachments growing by a third
+/// when base64-encoded
+pub fn attachment_budget(body: &str) -> u64 {
+    let available = MAX_MESSAGE_BYTES.saturating_sub(body.len() + MESSAGE_OVERHEAD_BYTES);
+    // Every 3 bytes become 4 characters, in lines of 76 plus a line break
+    (available as u64 * 3 * 76) / (4 * 78)
+}
+
+// Build a message, refusing one too large for Gmail to accept with an
+// explanation rather than leaving Gmail to reject the upload
+fn checked_message(draft: &DraftEmail, attachments: &[OutgoingAttachment]) -> Result<String> {
+    let message = build_raw_message(draft, attachments);
+    if message.len() > MAX_MESSAGE_BYTES {
+        return Err(message_too_large(message.len(), attachments));
+    }
+    Ok(message)
+}
+
+fn message_too_large(size: usize, attachments: &[OutgoingAttachment]) -> GmailApiError {
+    let megabytes = |bytes: usize| bytes as f64 / (1024.0 * 1024.0);
+    let mut explanation = format!(
+        "The email is {:.1} MB once encoded, over Gmail's {} MB limit.",
+        megabytes(size),
+        MAX_MESSAGE_BYTES / (1024 * 1024)
+    );
+    if !attachments.is_empty() {
+        let mut largest: Vec<&OutgoingAttachment> = attachments.iter().collect();
+        largest.sort_by_key(|attachment| std::cmp::Reverse(attachment.data.len()));
+        let listed: Vec<String> = largest
+            .iter()
+            .take(3)
+            .map(|attachment| {
+                format!(
+                    "{} ({:.1} MB)",
+                    attachment.filename,
+                    megabytes(attachment.data.len())
+                )
+            })
+            .collect();
+        explanation.push_str(&format!(" Largest attachments: {}.", listed.join(", ")));
+    }
+    explanation.push_str(
+        " Upload large files to Google Drive and pass them as drive_file_ids, \
+        which links files too large to attach, or send fewer or smaller files.",
+    );
+    GmailApiError::MessageTooLarge(explanation)
+}
+
+// Encode a message as base64url for the Gmail API
+fn encode_message(message: &str) -> String {
+    let encoded_message = base64::encode(message.as_bytes())
+        .replace('+', "-")
+        .replace('/', "_");
+
+    // Log the message size for debugging large messages
+    debug!("Encoded message size: {} bytes", encoded_message.len());
+
+    encoded_message
+}
+
+// The RFC 5322 message for a draft, with any files attached
+fn build_raw_message(draft: &DraftEmail, attachments: &[OutgoingAttachment]) -> String {
+    // Construct the RFC 5322 formatted message
+    let mut message = format!(
+        "From: me\r\n\
+         To: {}\r\n\
+         Subject: {}\r\n",
+        draft.to, draft.subject
+    );
+
+    // Add optional CC and BCC fields
+    if let Some(cc) = &draft.cc {
+        message.push_str(&format!("Cc: {}\r\n", cc));
+    }
+
+    if let Some(bcc) = &draft.bcc {
+        message.push_str(&format!("Bcc: {}\r\n", bcc));
+    }
+
+    // Add threading headers for replies
+    if let Some(in_reply_to) = &draft.in_reply_to {
+        message.push_str(&format!("In-Reply-To: {}\r\n", in_reply_to));
+    }
+
+    if let Some(references) = &draft.references {
+        message.push_str(&format!("References: {}\r\n", references));
+    }
+
+    if let Some(priority) = draft.priority {
+        message.push_str(&priority.headers());
+    }
+
+    if let Some(address) = &draft.read_receipt_to {
+        message.push_str(&format!("Disposition-Notification-To: {}\r\n", address));
+    }
+
+    if attachments.is_empty() {
+        // Add body
+        message.push_str("\r\n");
+        message.push_str(&draft.body);
+    } else {
+        let boundary = format!("mcp-gmailcal-{}", uuid::Uuid::new_v4().simple());
+        message.push_str(&format!(
+            "MIME-Version: 1.0\r\n\
+             Content-Type: multipart/mixed; boundary=\"{}\"\r\n\r\n",
+            boundary
+        ));
+
+        message.push_str(&format!(
+            "--{}\r\nContent-Type: text/plain; charset=\"UTF-8\"\r\n\r\n{}\r\n",
+            boundary, draft.body
+        ));
+
+        for attachment in attachments {
+            // Quotes and line breaks would end the header value early
+            let filename: String = attachment
+                .filename
+                .chars()
+                .filter(|c| !matches!(c, '"' | '\r' | '\n'))
+                .collect();
+            message.push_str(&format!(
+                "--{}\r\n\
+                 Content-Type: {}; name=\"{}\"\r\n\
+                 Content-Disposition: attachment; filename=\"{}\"\r\n\
+                 Content-Transfer-Encoding: base64\r\n\r\n",
+                boundary, attachment.mime_type, filename, filename
+            ));
+
+            // MIME limits lines to 76 characters
+            let encoded = base64::encode(&attachment.data);
+            for line in encoded.as_bytes().chunks(76) {
+                message.push_str(std::str::from_utf8(line).unwrap_or_default());
+                message.push_str("\r\n");
+            }
+        }
+
+        message.push_str(&format!("--{}--\r\n", boundary));
+    }
+
+    message
 }
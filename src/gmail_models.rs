@@ -0,0 +1,222 @@
+use crate::gmail_api::decode_body;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+
+// Gmail API resources
+//
+// Typed forms of the message, part, header, thread, draft and history
+// resources the Gmail API returns, so that responses are read through serde
+// rather than by walking JSON by hand. Fields Gmail leaves out of a response,
+// depending on the format requested, default to empty. Labels are read as
+// `label_tree::LabelInfo`.
+
+/// A message resource, in any format
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Message {
+    pub id: String,
+    #[serde(default)]
+    pub thread_id: Option<String>,
+    #[serde(default)]
+    pub label_ids: Vec<String>,
+    #[serde(default)]
+    pub snippet: Option<String>,
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub history_id: Option<String>,
+    /// Milliseconds since the epoch, sent as a string
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub internal_date: Option<String>,
+    #[serde(default)]
+    pub payload: Option<MessagePart>,
+}
+
+impl Message {
+    /// The first value of a top-level header, matching its name
+    /// case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.payload.as_ref()?.header(name)
+    }
+
+    /// When Gmail received the message
+    pub fn received_at(&self) -> Option<DateTime<Utc>> {
+        self.internal_date
+            .as_deref()?
+            .parse::<i64>()
+            .ok()
+            .and_then(DateTime::from_timestamp_millis)
+    }
+
+    pub fn has_label(&self, label_id: &str) -> bool {
+        self.label_ids.iter().any(|id| id == label_id)
+    }
+}
+
+/// One part of a message's MIME tree; the payload is the root part
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessagePart {
+    #[serde(default)]
+    pub part_id: Option<String>,
+    #[serde(default)]
+    pub mime_type: Option<String>,
+    #[serde(default)]
+    pub filename: Option<String>,
+    #[serde(default)]
+    pub headers: Vec<Header>,
+    #[serde(default)]
+    pub body: Option<MessagePartBody>,
+    #[serde(default)]
+    pub parts: Vec<MessagePart>,
+}
+
+impl MessagePart {
+    /// The first value of a header on this part, matching its name
+    /// case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|header| header.name.eq_ignore_ascii_case(name))
+            .map(|header| header.value.as_str())
+    }
+
+    pub fn mime_type(&self) -> &str {
+        self.mime_type.as_deref().unwrap_or("")
+    }
+
+    pub fn is_multipart(&self) -> bool {
+        self.mime_type().starts_with("multipart/")
+    }
+
+    /// The part holding the readable content: the first part of a
+    /// multipart/signed part, or this part itself
+    pub fn signed_content(&self) -> &MessagePart {
+        match self.parts.first() {
+            Some(content) if self.mime_type() == "multipart/signed" => content,
+            _ => self,
+        }
+    }
+
+    /// The leaf parts under this part, in order, looking through nested
+    /// multipart parts such as the multipart/alternative inside a
+    /// multipart/related body with inline images
+    pub fn leaf_parts(&self) -> Vec<&MessagePart> {
+        let mut leaves = Vec::new();
+        for child in &self.parts {
+            if child.is_multipart() {
+                leaves.extend(child.leaf_parts());
+            } else {
+                leaves.push(child);
+            }
+        }
+        leaves
+    }
+
+    /// The part's inline body decoded as text, at most `max_bytes` of it
+    pub fn decoded_body(&self, max_bytes: usize) -> Option<String> {
+        decode_body(self.body.as_ref()?.data.as_deref()?, max_bytes)
+    }
+}
+
+/// The body of a message part: inline data, or a reference to an attachment
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessagePartBody {
+    #[serde(default)]
+    pub attachment_id: Option<String>,
+    #[serde(default)]
+    pub size: u64,
+    /// base64url-encoded content
+    #[serde(default)]
+    pub data: Option<String>,
+}
+
+/// A header on a message part
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Header {
+    pub name: String,
+    /// Left out by Gmail for headers with an empty value
+    #[serde(default)]
+    pub value: String,
+}
+
+/// A thread resource
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Thread {
+    pub id: String,
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub history_id: Option<String>,
+    #[serde(default)]
+    pub messages: Vec<Message>,
+}
+
+impl Thread {
+    pub fn message_ids(&self) -> Vec<String> {
+        self.messages
+            .iter()
+            .map(|message| message.id.clone())
+            .collect()
+    }
+}
+
+/// A draft resource, as returned when one is created. Only the draft's own
+/// ID is read; the message is fetched separately when needed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Draft {
+    pub id: String,
+}
+
+/// One page of a message listing, holding only message and thread IDs
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageList {
+    /// Left out when nothing matches
+    #[serde(default)]
+    pub messages: Vec<Message>,
+    #[serde(default)]
+    pub next_page_token: Option<String>,
+    #[serde(default)]
+    pub result_size_estimate: Option<u64>,
+}
+
+/// One page of a mailbox's history
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryList {
+    #[serde(default)]
+    pub history: Vec<HistoryRecord>,
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub history_id: Option<String>,
+    #[serde(default)]
+    pub next_page_token: Option<String>,
+}
+
+/// The messages added to and deleted from the mailbox in one change
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryRecord {
+    #[serde(default)]
+    pub messages_added: Vec<HistoryMessage>,
+    #[serde(default)]
+    pub messages_deleted: Vec<HistoryMessage>,
+}
+
+/// A message named in a history record
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct HistoryMessage {
+    pub message: Message,
+}
+
+// History IDs and dates are documented as strings but are sometimes sent as
+// numbers
+pub(crate) fn string_or_number<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    Ok(
+        match Option::<serde_json::Value>::deserialize(deserializer)? {
+            Some(serde_json::Value::String(value)) => Some(value),
+            Some(serde_json::Value::Number(value)) => Some(value.to_string()),
+            _ => None,
+        },
+    )
+}
@@ -0,0 +1,152 @@
+use crate::calendar_api::{CalendarEvent, CalendarInfo};
+use serde::Serialize;
+
+// Holiday and birthday calendars
+//
+// Google publishes a public holiday calendar for each region, and keeps a
+// calendar of the birthdays of the user's contacts. They are subscribed to
+// by adding them to the user's calendar list, after which their events are
+// merged into the daily agenda, marked as holidays or birthdays so they are
+// not mistaken for meetings.
+
+/// Ending shared by the IDs of Google's regional holiday calendars
+pub const HOLIDAY_CALENDAR_SUFFIX: &str = "#holiday@group.v.calendar.google.com";
+
+/// ID of the calendar of contacts' birthdays
+pub const BIRTHDAY_CALENDAR_ID: &str = "addressbook#contacts@group.v.calendar.google.com";
+
+/// Regional holiday calendars: region code, name, and calendar ID prefix
+pub const HOLIDAY_REGIONS: &[(&str, &str, &str)] = &[
+    ("au", "Australia", "en.australian"),
+    ("br", "Brazil", "en.brazilian"),
+    ("ca", "Canada", "en.canadian"),
+    ("cn", "China", "en.china"),
+    ("de", "Germany", "en.german"),
+    ("es", "Spain", "en.spain"),
+    ("fr", "France", "en.french"),
+    ("ie", "Ireland", "en.irish"),
+    ("in", "India", "en.indian"),
+    ("it", "Italy", "en.italian"),
+    ("jp", "Japan", "en.japanese"),
+    ("mx", "Mexico", "en.mexican"),
+    ("nl", "Netherlands", "en.dutch"),
+    ("nz", "New Zealand", "en.new_zealand"),
+    ("sg", "Singapore", "en.singapore"),
+    ("uk", "United Kingdom", "en.uk"),
+    ("us", "United States", "en.usa"),
+    ("za", "South Africa", "en.sa"),
+];
+
+/// ID of the holiday calendar for a region's calendar ID prefix
+pub fn holiday_calendar_id(prefix: &str) -> String {
+    format!("{}{}", prefix, HOLIDAY_CALENDAR_SUFFIX)
+}
+
+/// What an agenda entry is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AgendaItemType {
+    Event,
+    Holiday,
+    Birthday,
+}
+
+impl AgendaItemType {
+    /// Whether an event from a calendar is a holiday, a birthday or an
+    /// ordinary event
+    pub fn of(calendar_id: &str, event: &CalendarEvent) -> Self {
+        if calendar_id == BIRTHDAY_CALENDAR_ID || event.event_type.as_deref() == Some("birthday") {
+            Self::Birthday
+        } else if is_holiday_calendar(calendar_id) {
+            Self::Holiday
+        } else {
+            Self::Event
+        }
+    }
+}
+
+/// Whether a calendar is one of Google's holiday calendars
+pub fn is_holiday_calendar(calendar_id: &str) -> bool {
+    calendar_id.ends_with(HOLIDAY_CALENDAR_SUFFIX)
+}
+
+/// Whether a calendar holds holidays or birthdays rather than events
+pub fn is_special_calendar(calendar_id: &str) -> bool {
+    calendar_id == BIRTHDAY_CALENDAR_ID || is_holiday_calendar(calendar_id)
+}
+
+/// The calendar ID for "birthdays", a region code or name such as "uk" or
+/// "New Zealand", or a calendar ID given as is
+pub fn resolve_calendar(input: &str) -> Result<String, String> {
+    let input = input.trim();
+    if input.contains('@') {
+        return Ok(input.to_string());
+    }
+    if ["birthdays", "birthday", "contacts"]
+        .iter()
+        .any(|name| name.eq_ignore_ascii_case(input))
+    {
+        return Ok(BIRTHDAY_CALENDAR_ID.to_string());
+    }
+    HOLIDAY_REGIONS
+        .iter()
+        .find(|(code, name, _)| code.eq_ignore_ascii_case(input) || name.eq_ignore_ascii_case(input))
+        .map(|(_, _, prefix)| holiday_calendar_id(prefix))
+        .ok_or_else(|| {
+            format!(
+                "Unknown calendar '{}': expected \"birthdays\", a region such as \"uk\" or \"Germany\", or a calendar ID",
+                input
+            )
+        })
+}
+
+/// A holiday or birthday calendar that can be subscribed to
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SpecialCalendar {
+    pub calendar_id: String,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub calendar_type: AgendaItemType,
+    /// Region code, for a holiday calendar
+    pub region: Option<String>,
+    /// Whether the calendar is in the user's calendar list
+    pub subscribed: bool,
+}
+
+/// The birthday calendar and the regional holiday calendars, those matching
+/// `region` by code or name if given, marked with whether the user has
+/// subscribed to them
+pub fn special_calendars(
+    subscribed: &[CalendarInfo],
+    region: Option<&str>,
+) -> Vec<SpecialCalendar> {
+    let is_subscribed = |id: &str| subscribed.iter().any(|calendar| calendar.id == id);
+    let mut calendars = Vec::new();
+    if region.is_none() {
+        calendars.push(SpecialCalendar {
+            calendar_id: BIRTHDAY_CALENDAR_ID.to_string(),
+            name: "Birthdays".to_string(),
+            calendar_type: AgendaItemType::Birthday,
+            region: None,
+            subscribed: is_subscribed(BIRTHDAY_CALENDAR_ID),
+        });
+    }
+    let region = region.map(|region| region.trim().to_lowercase());
+    for (code, name, prefix) in HOLIDAY_REGIONS {
+        let matches = region
+            .as_ref()
+            .is_none_or(|region| *code == region || name.to_lowercase().contains(region.as_str()));
+        if !matches {
+            continue;
+        }
+        let calendar_id = holiday_calendar_id(prefix);
+        calendars.push(SpecialCalendar {
+            subscribed: is_subscribed(&calendar_id),
+            calendar_id,
+            name: format!("Holidays in {}", name),
+            calendar_type: AgendaItemType::Holiday,
+            region: Some(code.to_string()),
+        });
+    }
+    calendars
+}
@@ -0,0 +1,202 @@
+use crate::calendar_api::CalendarEvent;
+use chrono::{DateTime, Utc};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// ICS export
+//
+// An agenda can be exported as an iCalendar (RFC 5545) file so that events
+// picked out through the server can be subscribed to from other calendar
+// apps. Exporting again to the same file replaces it in one step, so an app
+// following the file as a feed never reads it half written. Working
+// location events say where the user is rather than what they are doing, so
+// they are left out.
+
+/// Days of events exported when no end is given
+pub const ICS_DEFAULT_DAYS: i64 = 30;
+
+/// Most events exported from a calendar
+pub const ICS_MAX_EVENTS: u32 = 250;
+
+/// File name of the export when none is given
+pub const DEFAULT_ICS_FILENAME: &str = "agenda.ics";
+
+/// Identifies this server as the producer of exported calendars
+const PRODUCT_ID: &str = "-//2389 Research//gmailcal-mcp-rs//EN";
+
+/// Returns the directory ICS exports are written to.
+///
+/// Environment variable: ICS_EXPORT_DIR
+pub fn get_ics_export_dir() -> PathBuf {
+    match std::env::var("ICS_EXPORT_DIR") {
+        Ok(dir) if !dir.trim().is_empty() => PathBuf::from(dir),
+        _ => {
+            let mut dir = dirs::download_dir().unwrap_or_else(std::env::temp_dir);
+            dir.push("gmail-mcp-rs");
+            dir.push("calendar");
+            dir
+        }
+    }
+}
+
+/// The events to export: those overlapping `range` and matching every word
+/// of `query` in their title, description or location, if given, leaving
+/// out working location events
+pub fn filter_agenda(
+    events: Vec<CalendarEvent>,
+    range: (DateTime<Utc>, DateTime<Utc>),
+    query: Option<&str>,
+) -> Vec<CalendarEvent> {
+    let words: Vec<String> = query
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(|word| word.to_lowercase())
+        .collect();
+    events
+        .into_iter()
+        .filter(|event| event.working_location.is_none())
+        .filter(|event| event.start_time < range.1 && event.end_time > range.0)
+        .filter(|event| {
+            let text = [
+                Some(event.summary.as_str()),
+                event.description.as_deref(),
+                event.location.as_deref(),
+            ]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+            words.iter().all(|word| text.contains(word.as_str()))
+        })
+        .collect()
+}
+
+/// Escape a TEXT value
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Fold a content line into lines of at most 75 octets, each continuation
+/// starting with a space, and end it with CRLF
+fn fold_line(line: &str, out: &mut String) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+fn format_utc(time: DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// iCalendar participation status for a Calendar response status
+fn participation_status(response_status: Option<&str>) -> &'static str {
+    match response_status {
+        Some("accepted") => "ACCEPTED",
+        Some("declined") => "DECLINED",
+        Some("tentative") => "TENTATIVE",
+        _ => "NEEDS-ACTION",
+    }
+}
+
+/// Quote a parameter value such as a name, which may contain `,`, `;` or `:`
+fn quote_param(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "'"))
+}
+
+/// Write events as an iCalendar file named `name`, stamped with `now`
+pub fn to_ics(events: &[CalendarEvent], name: &str, now: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODUCT_ID),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", escape_text(name)),
+    ];
+
+    for event in events {
+        lines.push("BEGIN:VEVENT".to_string());
+        let uid = event
+            .id
+            .clone()
+            .unwrap_or_else(|| format!("{}-{}", format_utc(event.start_time), event.summary));
+        lines.push(format!("UID:{}@gmailcal-mcp-rs", escape_text(&uid)));
+        lines.push(format!("DTSTAMP:{}", format_utc(now)));
+        if event.all_day {
+            let date = |time: DateTime<Utc>| time.format("%Y%m%d").to_string();
+            lines.push(format!("DTSTART;VALUE=DATE:{}", date(event.start_time)));
+            lines.push(format!("DTEND;VALUE=DATE:{}", date(event.end_time)));
+        } else {
+            lines.push(format!("DTSTART:{}", format_utc(event.start_time)));
+            lines.push(format!("DTEND:{}", format_utc(event.end_time)));
+        }
+        lines.push(format!("SUMMARY:{}", escape_text(&event.summary)));
+        if let Some(description) = &event.description {
+            lines.push(format!("DESCRIPTION:{}", escape_text(description)));
+        }
+        if let Some(location) = &event.location {
+            lines.push(format!("LOCATION:{}", escape_text(location)));
+        }
+        if let Some(link) = &event.html_link {
+            lines.push(format!("URL:{}", link));
+        }
+        let transparent = event.transparency.as_deref() == Some("transparent");
+        lines.push(format!(
+            "TRANSP:{}",
+            if transparent { "TRANSPARENT" } else { "OPAQUE" }
+        ));
+        if let Some(organizer) = &event.organizer {
+            let name = organizer
+                .display_name
+                .as_deref()
+                .map(|name| format!(";CN={}", quote_param(name)))
+                .unwrap_or_default();
+            lines.push(format!("ORGANIZER{}:mailto:{}", name, organizer.email));
+        }
+        for attendee in &event.attendees {
+            let name = attendee
+                .display_name
+                .as_deref()
+                .map(|name| format!(";CN={}", quote_param(name)))
+                .unwrap_or_default();
+            lines.push(format!(
+                "ATTENDEE{};PARTSTAT={}:mailto:{}",
+                name,
+                participation_status(attendee.response_status.as_deref()),
+                attendee.email
+            ));
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut ics = String::new();
+    for line in &lines {
+        fold_line(line, &mut ics);
+    }
+    ics
+}
+
+/// Write an export to `path`, replacing any earlier one in a single step
+pub fn write_ics(path: &Path, ics: &str) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let partial = path.with_extension("ics.partial");
+    let mut file = std::fs::File::create(&partial)?;
+    file.write_all(ics.as_bytes())?;
+    file.sync_all()?;
+    std::fs::rename(&partial, path)
+}
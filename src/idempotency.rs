@@ -0,0 +1,276 @@
+use crate::errors::IdempotencyError;
+use crate::persist;
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+// Idempotency keys
+//
+// An MCP client that gives up waiting on a tool call may retry it, and for a
+// tool that sends mail or creates a draft or event the retry does the work a
+// second time. Write tools take an optional idempotency key: the result of
+// the first successful call with a key is saved, and later calls with the
+// same key get that result back without doing anything. A call made while
+// another with the same key is still running fails, to be retried once the
+// first finishes, and so does a call whose arguments differ from those the
+// key was first used with. Results are kept in a JSON file, so a restarted server
+// still knows them, until they expire.
+
+/// Name of the file results are saved in, in the state directory
+pub const IDEMPOTENCY_FILE: &str = "idempotency.json";
+
+/// How long a result is kept for repeats, in seconds
+pub const DEFAULT_IDEMPOTENCY_TTL_SECONDS: u64 = 24 * 60 * 60;
+
+/// Most results kept; the oldest are dropped first
+pub const MAX_IDEMPOTENCY_KEYS: usize = 1000;
+
+/// Longest idempotency key accepted
+pub const MAX_IDEMPOTENCY_KEY_CHARS: usize = 256;
+
+// The result of a call, as saved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SavedResult {
+    tool: String,
+    key: String,
+    // Hash of the call's arguments; results saved before it was kept have
+    // none
+    #[serde(default)]
+    arguments_hash: Option<String>,
+    result: String,
+    saved_at: DateTime<Utc>,
+}
+
+/// What to do with a call that has an idempotency key
+#[derive(Debug)]
+pub enum Claim<'a> {
+    /// The key was used before; return its result
+    Replay(String),
+    /// The key is new; run the call and finish the guard with its result
+    Run(IdempotencyGuard<'a>),
+}
+
+/// Saved results of calls made with idempotency keys
+#[derive(Debug)]
+pub struct IdempotencyStore {
+    path: PathBuf,
+    ttl: Duration,
+    // Keys of the calls still running, and the lock for the file
+    running: Mutex<HashSet<(String, String)>>,
+}
+
+impl IdempotencyStore {
+    pub fn new(path: PathBuf, ttl: Duration) -> Self {
+        Self {
+            path,
+            ttl,
+            running: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// The store shared by the tools, at `IDEMPOTENCY_FILE` or the default
+    /// location
+    pub fn global() -> &'static IdempotencyStore {
+        static STORE: OnceLock<IdempotencyStore> = OnceLock::new();
+        STORE.get_or_init(|| IdempotencyStore::new(get_idempotency_path(), get_idempotency_ttl()))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Claim `key` for a call to `tool` with `arguments`. Keys are separate
+    /// for each tool. Fails if a call with the key is still running, or if
+    /// the key was used with other arguments.
+    pub fn claim(
+        &self,
+        tool: &str,
+        key: &str,
+        arguments: &Value,
+    ) -> Result<Claim<'_>, IdempotencyError> {
+        let id = (tool.to_string(), key.to_string());
+        let arguments_hash = hash_arguments(arguments);
+        let mut running = self.running.lock().unwrap_or_else(|e| e.into_inner());
+        if running.contains(&id) {
+            return Err(IdempotencyError::InProgress(id.0, id.1));
+        }
+        if let Some(saved) = self
+            .load()
+            .into_iter()
+            .find(|saved| saved.tool == tool && saved.key == key)
+        {
+            if saved
+                .arguments_hash
+                .is_some_and(|saved_hash| saved_hash != arguments_hash)
+            {
+                warn!(
+                    "idempotency_key '{}' was reused for {} with different arguments",
+                    key, tool
+                );
+                return Err(IdempotencyError::Conflict(id.0, id.1));
+            }
+            info!(
+                "Returning the saved result of {} for idempotency_key '{}'",
+                tool, key
+            );
+            return Ok(Claim::Replay(saved.result));
+        }
+        running.insert(id.clone());
+        Ok(Claim::Run(IdempotencyGuard {
+            store: Some(self),
+            id,
+            arguments_hash,
+        }))
+    }
+
+    // Saved results that have not expired. A missing or unreadable file has
+    // none.
+    fn load(&self) -> Vec<SavedResult> {
+        let saved: Vec<SavedResult> = persist::load_json(&self.path).unwrap_or_else(|e| {
+            warn!(
+                "Ignoring unreadable idempotency results at {}: {}",
+                self.path.display(),
+                e
+            );
+            Vec::new()
+        });
+        let ttl = chrono::Duration::from_std(self.ttl).unwrap_or(chrono::Duration::MAX);
+        let now = Utc::now();
+        saved
+            .into_iter()
+            .filter(|saved| now.signed_duration_since(saved.saved_at) < ttl)
+            .collect()
+    }
+
+    // Save a result, dropping expired ones and the oldest beyond the limit.
+    // The call has already succeeded, so a failure to save is only logged.
+    fn save(&self, tool: &str, key: &str, arguments_hash: &str, result: &str) {
+        let mut saved = self.load();
+        saved.retain(|saved| !(saved.tool == tool && saved.key == key));
+        saved.push(SavedResult {
+            tool: tool.to_string(),
+            key: key.to_string(),
+            arguments_hash: Some(arguments_hash.to_string()),
+            result: result.to_string(),
+            saved_at: Utc::now(),
+        });
+        if saved.len() > MAX_IDEMPOTENCY_KEYS {
+            saved.drain(..saved.len() - MAX_IDEMPOTENCY_KEYS);
+        }
+        if let Err(e) = self.store(&saved) {
+            warn!(
+                "Failed to save the result of {} for idempotency_key '{}' to {}: {}",
+                tool,
+                key,
+                self.path.display(),
+                e
+            );
+        }
+    }
+
+    fn store(&self, saved: &[SavedResult]) -> std::io::Result<()> {
+        persist::store_json(&self.path, saved)
+    }
+}
+
+/// A claimed idempotency key. Finishing the guard saves the call's result;
+/// dropping it without finishing, when the call fails, frees the key for a
+/// retry.
+#[derive(Debug)]
+pub struct IdempotencyGuard<'a> {
+    store: Option<&'a IdempotencyStore>,
+    id: (String, String),
+    arguments_hash: String,
+}
+
+impl IdempotencyGuard<'_> {
+    /// A guard for a call without an idempotency key, which saves nothing
+    pub fn none() -> Self {
+        Self {
+            store: None,
+            id: (String::new(), String::new()),
+            arguments_hash: String::new(),
+        }
+    }
+
+    /// Save the call's result for repeats, and return it
+    pub fn finish(self, result: String) -> String {
+        if let Some(store) = self.store {
+            let _running = store.running.lock().unwrap_or_else(|e| e.into_inner());
+            store.save(&self.id.0, &self.id.1, &self.arguments_hash, &result);
+        }
+        result
+    }
+}
+
+impl Drop for IdempotencyGuard<'_> {
+    fn drop(&mut self) {
+        if let Some(store) = self.store {
+            store
+                .running
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(&self.id);
+        }
+    }
+}
+
+// A hash of the arguments as canonical JSON, with object keys sorted, so
+// that the same arguments always hash the same. FNV-1a is stable across
+// builds, unlike the standard library's hasher.
+fn hash_arguments(arguments: &Value) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in canonical_json(arguments).bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
+
+fn canonical_json(value: &Value) -> String {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            let fields: Vec<String> = entries
+                .into_iter()
+                .map(|(key, value)| {
+                    format!("{}:{}", Value::from(key.as_str()), canonical_json(value))
+                })
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(canonical_json).collect();
+            format!("[{}]", items.join(","))
+        }
+        other => other.to_string(),
+    }
+}
+
+/// Returns the path of the file idempotency results are saved in.
+///
+/// Environment variable: IDEMPOTENCY_FILE
+pub fn get_idempotency_path() -> PathBuf {
+    std::env::var("IDEMPOTENCY_FILE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| persist::default_path(dirs::cache_dir(), IDEMPOTENCY_FILE))
+}
+
+/// Returns how long results are kept for repeated calls.
+///
+/// Default is 24 hours if not configured.
+///
+/// Environment variable: IDEMPOTENCY_TTL_SECONDS
+pub fn get_idempotency_ttl() -> Duration {
+    let seconds = std::env::var("IDEMPOTENCY_TTL_SECONDS")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_IDEMPOTENCY_TTL_SECONDS);
+    Duration::from_secs(seconds)
+}
@@ -0,0 +1,131 @@
+use crate::calendar_api::Attendee;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+// Event invitations
+//
+// Adding or removing guests edits the attendee list as the API returns it,
+// so that what this server does not model, such as attendee comments, extra
+// guests and resources, is written back unchanged. Only the attendee list is
+// patched, and only if the event has not changed since it was read.
+
+/// Who among the attendees Calendar notifies of a change
+pub const SEND_UPDATES_OPTIONS: &[&str] = &["all", "externalOnly", "none"];
+
+/// Check a `sendUpdates` choice, defaulting to notifying everyone
+pub fn parse_send_updates(value: Option<&str>) -> Result<String, String> {
+    match value.map(str::trim) {
+        None => Ok("all".to_string()),
+        Some(value) => SEND_UPDATES_OPTIONS
+            .iter()
+            .find(|option| option.eq_ignore_ascii_case(value))
+            .map(|option| option.to_string())
+            .ok_or_else(|| {
+                format!(
+                    "Invalid send_updates '{}': expected one of {}",
+                    value,
+                    SEND_UPDATES_OPTIONS.join(", ")
+                )
+            }),
+    }
+}
+
+fn attendee_email(attendee: &Value) -> Option<&str> {
+    attendee.get("email").and_then(|v| v.as_str())
+}
+
+/// An attendee list with guests added or removed
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttendeeEdit {
+    /// The new list, in the form the API takes it
+    pub attendees: Vec<Value>,
+    pub added: Vec<String>,
+    /// Addresses to add that were already on the list
+    pub already_invited: Vec<String>,
+    pub removed: Vec<String>,
+    /// Addresses to remove that were not on the list
+    pub not_invited: Vec<String>,
+}
+
+impl AttendeeEdit {
+    /// Whether the list is different from before
+    pub fn changed(&self) -> bool {
+        !self.added.is_empty() || !self.removed.is_empty()
+    }
+}
+
+/// Add guests to an attendee list as the API returns it, marking them
+/// optional if asked. Guests already on the list are left as they are.
+pub fn add_to_attendees(existing: &[Value], emails: &[String], optional: bool) -> AttendeeEdit {
+    let mut edit = AttendeeEdit {
+        attendees: existing.to_vec(),
+        added: Vec::new(),
+        already_invited: Vec::new(),
+        removed: Vec::new(),
+        not_invited: Vec::new(),
+    };
+    for email in emails {
+        let invited = edit
+            .attendees
+            .iter()
+            .filter_map(attendee_email)
+            .any(|address| address.eq_ignore_ascii_case(email));
+        if invited {
+            edit.already_invited.push(email.clone());
+            continue;
+        }
+        let mut attendee = json!({ "email": email });
+        if optional {
+            attendee["optional"] = json!(true);
+        }
+        edit.attendees.push(attendee);
+        edit.added.push(email.clone());
+    }
+    edit
+}
+
+/// Remove guests from an attendee list as the API returns it
+pub fn remove_from_attendees(existing: &[Value], emails: &[String]) -> AttendeeEdit {
+    let mut edit = AttendeeEdit {
+        attendees: Vec::new(),
+        added: Vec::new(),
+        already_invited: Vec::new(),
+        removed: Vec::new(),
+        not_invited: Vec::new(),
+    };
+    for attendee in existing {
+        let removed = attendee_email(attendee).and_then(|address| {
+            emails
+                .iter()
+                .find(|email| email.eq_ignore_ascii_case(address))
+        });
+        match removed {
+            Some(email) => edit.removed.push(email.clone()),
+            None => edit.attendees.push(attendee.clone()),
+        }
+    }
+    edit.not_invited = emails
+        .iter()
+        .filter(|email| !edit.removed.contains(email))
+        .cloned()
+        .collect();
+    edit
+}
+
+/// The outcome of changing an event's guests
+#[derive(Debug, Clone, Serialize)]
+pub struct InviteUpdate {
+    pub event_id: String,
+    pub summary: String,
+    pub added: Vec<String>,
+    pub already_invited: Vec<String>,
+    pub removed: Vec<String>,
+    pub not_invited: Vec<String>,
+    /// Who was notified: "all", "externalOnly" or "none". Nobody is when
+    /// nothing changed.
+    pub send_updates: String,
+    /// Whether the event was updated
+    pub updated: bool,
+    /// The guests now invited
+    pub attendees: Vec<Attendee>,
+}
@@ -0,0 +1,94 @@
+use crate::calendar_api::{ConferenceData, JoinLink};
+
+// Join links
+//
+// Only meetings created with Google Meet, or with a conferencing add-on,
+// carry their video call in the event's conference data. Invitations sent
+// from Zoom, Teams or Webex put the link in the description or location
+// instead, often as HTML. Links to those services' meetings are found there
+// and listed with the native video entry points, once each, so that every
+// event with a call has a link to join it whatever created it. Other links
+// to the same services, such as to download an app, are left out.
+
+/// The video call service a link joins, if it is a meeting link of one of
+/// the services recognised
+pub fn meeting_provider(url: &str) -> Option<&'static str> {
+    let parsed = url::Url::parse(url).ok()?;
+    let host = parsed.host_str()?.to_lowercase();
+    let path = parsed.path().to_lowercase();
+    let on = |domain: &str| host == domain || host.ends_with(&format!(".{}", domain));
+
+    if host == "meet.google.com" && path.len() > 1 {
+        Some("google_meet")
+    } else if (on("zoom.us") || on("zoomgov.com"))
+        && ["/j/", "/s/", "/w/", "/my/", "/wc/join/"]
+            .iter()
+            .any(|prefix| path.starts_with(prefix))
+    {
+        Some("zoom")
+    } else if (host == "teams.microsoft.com" && path.starts_with("/l/meetup-join/"))
+        || (host == "teams.live.com" && path.starts_with("/meet/"))
+    {
+        Some("teams")
+    } else if on("webex.com")
+        && (path.starts_with("/meet/")
+            || path.starts_with("/join/")
+            || path.starts_with("/wbxmjs/joinservice/")
+            || path.ends_with("/j.php"))
+    {
+        Some("webex")
+    } else {
+        None
+    }
+}
+
+/// Links to join an event's video call: the video entry points of its
+/// conference data, then meeting links found in its description and location
+pub fn find_join_links(
+    conference_data: Option<&ConferenceData>,
+    description: Option<&str>,
+    location: Option<&str>,
+) -> Vec<JoinLink> {
+    let mut links: Vec<JoinLink> = Vec::new();
+    let mut add = |url: String, provider: String, source: &str| {
+        if !links.iter().any(|link| link.url.eq_ignore_ascii_case(&url)) {
+            links.push(JoinLink {
+                provider,
+                url,
+                source: source.to_string(),
+            });
+        }
+    };
+
+    if let Some(conference) = conference_data {
+        let solution = conference
+            .conference_solution
+            .as_ref()
+            .map(|solution| solution.name.clone());
+        for entry_point in &conference.entry_points {
+            if entry_point.entry_point_type != "video" {
+                continue;
+            }
+            let provider = meeting_provider(&entry_point.uri)
+                .map(str::to_string)
+                .or_else(|| solution.clone())
+                .unwrap_or_else(|| "other".to_string());
+            add(entry_point.uri.clone(), provider, "conference_data");
+        }
+    }
+
+    for (text, source) in [(description, "description"), (location, "location")] {
+        let Some(text) = text else {
+            continue;
+        };
+        // Descriptions may be HTML or plain text, so look for both, reading
+        // the `&` of links written out in HTML as it is
+        let plain = text.replace("&amp;", "&");
+        for link in crate::utils::extract_links(Some(text), Some(&plain)) {
+            if let Some(provider) = meeting_provider(&link.url) {
+                add(link.url, provider.to_string(), source);
+            }
+        }
+    }
+    links
+}
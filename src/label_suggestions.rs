@@ -0,0 +1,187 @@
+use crate::gmail_api::MessageMetadata;
+use crate::label_tree::{short_name, LabelInfo};
+use crate::utils::parse_email_addresses;
+use serde::Serialize;
+use std::collections::HashSet;
+
+// Label suggestions
+//
+// People tend to file mail from the same sender, or from the same company,
+// under the same labels, so the labels on earlier mail from a sender are a
+// good guess for a new message. A label whose name appears in the subject is
+// a weaker hint. Only the user's own labels are suggested, never Gmail's
+// system labels or labels the message already has.
+
+/// Number of earlier messages from the sender's address or domain looked at
+pub const DEFAULT_SENDER_HISTORY: u32 = 50;
+
+/// Number of suggestions returned
+pub const DEFAULT_LABEL_SUGGESTIONS: usize = 5;
+
+// How much each hint adds to a suggestion's score, which is at most 1
+const SENDER_WEIGHT: f64 = 0.6;
+const DOMAIN_WEIGHT: f64 = 0.25;
+const NAME_WEIGHT: f64 = 0.15;
+
+// Domains shared by unrelated people, where only mail from the same address
+// says anything about a sender
+const PUBLIC_MAIL_DOMAINS: &[&str] = &[
+    "gmail.com",
+    "googlemail.com",
+    "outlook.com",
+    "hotmail.com",
+    "live.com",
+    "yahoo.com",
+    "icloud.com",
+    "me.com",
+    "aol.com",
+    "proton.me",
+    "protonmail.com",
+];
+
+/// A label that could be put on a message, and why
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LabelSuggestion {
+    pub label_id: String,
+    pub name: String,
+    /// From 0 to 1, higher for a better fit
+    pub score: f64,
+    /// Earlier messages from the same address that have the label
+    pub sender_messages: usize,
+    /// Earlier messages from others at the same domain that have the label
+    pub domain_messages: usize,
+    /// Whether the subject mentions the label's name
+    pub name_match: bool,
+    pub reasons: Vec<String>,
+}
+
+/// The sender's address and, unless it is a public mail domain, the domain
+/// whose earlier mail is also looked at
+pub fn sender_scope(from: &str) -> Option<(String, Option<String>)> {
+    let address = parse_email_addresses(from).into_iter().next()?;
+    let domain = address
+        .rsplit_once('@')
+        .map(|(_, domain)| domain.to_string())
+        .filter(|domain| !PUBLIC_MAIL_DOMAINS.contains(&domain.as_str()));
+    Some((address, domain))
+}
+
+/// Gmail search for the earlier mail suggestions are based on
+pub fn history_query(from: &str) -> Option<String> {
+    let (address, domain) = sender_scope(from)?;
+    Some(format!("from:{}", domain.unwrap_or(address)))
+}
+
+// Whether the subject mentions a label's short name, or its singular
+fn mentions_label(subject: &str, name: &str) -> bool {
+    let subject = subject.to_lowercase();
+    let name = short_name(name).trim().to_lowercase();
+    let singular = name.strip_suffix('s').unwrap_or(&name);
+    singular.chars().count() >= 3 && subject.contains(singular)
+}
+
+/// Labels for a message, best first, from the labels on earlier mail in
+/// `history` and the message's subject
+pub fn suggest_labels(
+    message: &MessageMetadata,
+    history: &[MessageMetadata],
+    labels: &[LabelInfo],
+) -> Vec<LabelSuggestion> {
+    let scope = message.from.as_deref().and_then(sender_scope);
+    let subject = message.subject.as_deref().unwrap_or_default();
+    let current: HashSet<&str> = message.label_ids.iter().map(String::as_str).collect();
+
+    // Split the earlier mail into mail from the sender and from their domain
+    let mut from_sender = Vec::new();
+    let mut from_domain = Vec::new();
+    if let Some((address, domain)) = &scope {
+        for earlier in history.iter().filter(|earlier| earlier.id != message.id) {
+            let Some((earlier_address, _)) = earlier.from.as_deref().and_then(sender_scope) else {
+                continue;
+            };
+            if &earlier_address == address {
+                from_sender.push(earlier);
+            } else if domain
+                .as_deref()
+                .is_some_and(|domain| earlier_address.ends_with(&format!("@{}", domain)))
+            {
+                from_domain.push(earlier);
+            }
+        }
+    }
+
+    let count_with = |messages: &[&MessageMetadata], label_id: &str| {
+        messages
+            .iter()
+            .filter(|earlier| earlier.label_ids.iter().any(|id| id == label_id))
+            .count()
+    };
+    let share = |count: usize, total: usize| {
+        if total == 0 {
+            0.0
+        } else {
+            count as f64 / total as f64
+        }
+    };
+
+    let mut suggestions: Vec<LabelSuggestion> = labels
+        .iter()
+        .filter(|label| !label.is_system() && !current.contains(label.id.as_str()))
+        .filter_map(|label| {
+            let sender_messages = count_with(&from_sender, &label.id);
+            let domain_messages = count_with(&from_domain, &label.id);
+            let name_match = mentions_label(subject, &label.name);
+
+            let score = SENDER_WEIGHT * share(sender_messages, from_sender.len())
+                + DOMAIN_WEIGHT * share(domain_messages, from_domain.len())
+                + if name_match { NAME_WEIGHT } else { 0.0 };
+            if score == 0.0 {
+                return None;
+            }
+
+            let mut reasons = Vec::new();
+            if let Some((address, domain)) = &scope {
+                if sender_messages > 0 {
+                    reasons.push(format!(
+                        "On {} of {} earlier messages from {}",
+                        sender_messages,
+                        from_sender.len(),
+                        address
+                    ));
+                }
+                if let Some(domain) = domain.as_deref().filter(|_| domain_messages > 0) {
+                    reasons.push(format!(
+                        "On {} of {} messages from others at {}",
+                        domain_messages,
+                        from_domain.len(),
+                        domain
+                    ));
+                }
+            }
+            if name_match {
+                reasons.push(format!(
+                    "The subject mentions \"{}\"",
+                    short_name(&label.name)
+                ));
+            }
+
+            Some(LabelSuggestion {
+                label_id: label.id.clone(),
+                name: label.name.clone(),
+                score: (score * 100.0).round() / 100.0,
+                sender_messages,
+                domain_messages,
+                name_match,
+                reasons,
+            })
+        })
+        .collect();
+
+    suggestions.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    suggestions
+}
@@ -0,0 +1,175 @@
+use serde::{Deserialize, Serialize};
+
+// Label hierarchy
+//
+// Gmail has no parent field on labels: a label is nested by its name, with
+// "Projects/Apollo" shown inside "Projects". A nested label can exist without
+// its parent, for instance when the parent was deleted or the label was made
+// through the API, and Gmail then shows it at the top level under its full
+// name. These helpers read the nesting from the names, build the tree shown in
+// Gmail's sidebar, and give the parents a new nested label needs.
+
+/// Separator between the levels of a nested label's name
+pub const LABEL_SEPARATOR: char = '/';
+
+/// A label as listed by the Gmail API
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LabelInfo {
+    pub id: String,
+    pub name: String,
+    /// "system" for Gmail's own labels, "user" for the user's
+    #[serde(rename = "type", default)]
+    pub label_type: Option<String>,
+}
+
+impl LabelInfo {
+    pub fn is_system(&self) -> bool {
+        self.label_type.as_deref() == Some("system")
+    }
+}
+
+/// A label with the labels nested inside it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LabelNode {
+    /// None for a parent that only exists as part of a nested label's name
+    pub id: Option<String>,
+    /// The full name, such as "Projects/Apollo"
+    pub name: String,
+    /// The last part of the name, such as "Apollo"
+    pub short_name: String,
+    pub children: Vec<LabelNode>,
+}
+
+/// The result of creating a label along with any missing parents
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LabelCreation {
+    pub id: String,
+    pub name: String,
+    /// Names of the labels created, parents first; empty when the label
+    /// already existed
+    pub created: Vec<String>,
+}
+
+/// Parse the labels of a Gmail labels listing
+pub fn parse_labels(json: &str) -> Result<Vec<LabelInfo>, serde_json::Error> {
+    #[derive(Deserialize)]
+    struct LabelList {
+        #[serde(default)]
+        labels: Vec<LabelInfo>,
+    }
+
+    serde_json::from_str::<LabelList>(json).map(|list| list.labels)
+}
+
+/// The levels of a label's name, such as ["Projects", "Apollo"]
+pub fn label_path(name: &str) -> Vec<&str> {
+    name.split(LABEL_SEPARATOR).map(str::trim).collect()
+}
+
+/// Tidy a label name for creating it, trimming each level, or fail if any
+/// level is empty
+pub fn normalize_label_name(name: &str) -> Result<String, String> {
+    let path = label_path(name);
+    if path.iter().any(|level| level.is_empty()) {
+        return Err(format!(
+            "Invalid label name '{}': every level between '{}' must have a name",
+            name, LABEL_SEPARATOR
+        ));
+    }
+    Ok(path.join(&LABEL_SEPARATOR.to_string()))
+}
+
+/// The name of the label a nested label sits in
+pub fn parent_name(name: &str) -> Option<&str> {
+    name.rsplit_once(LABEL_SEPARATOR)
+        .map(|(parent, _)| parent)
+        .filter(|parent| !parent.is_empty())
+}
+
+/// The last level of a label's name
+pub fn short_name(name: &str) -> &str {
+    name.rsplit_once(LABEL_SEPARATOR)
+        .map_or(name, |(_, short)| short)
+}
+
+/// Names of every label a nested label sits in, outermost first, so
+/// "A/B/C" gives ["A", "A/B"]
+pub fn ancestor_names(name: &str) -> Vec<String> {
+    let mut ancestors = Vec::new();
+    let mut current = name;
+    while let Some(parent) = parent_name(current) {
+        ancestors.push(parent.to_string());
+        current = parent;
+    }
+    ancestors.reverse();
+    ancestors
+}
+
+/// Find a label by name, ignoring case as Gmail does
+pub fn find_label<'a>(labels: &'a [LabelInfo], name: &str) -> Option<&'a LabelInfo> {
+    labels
+        .iter()
+        .find(|label| label.name.eq_ignore_ascii_case(name))
+}
+
+/// The labels nested directly inside a label
+pub fn child_labels<'a>(labels: &'a [LabelInfo], name: &str) -> Vec<&'a LabelInfo> {
+    labels
+        .iter()
+        .filter(|label| {
+            parent_name(&label.name).is_some_and(|parent| parent.eq_ignore_ascii_case(name))
+        })
+        .collect()
+}
+
+/// Arrange labels into the tree Gmail shows, sorted by name at each level.
+/// Parents missing from the list are added without an ID so that their
+/// children stay together. System labels are left out unless asked for, and
+/// are never nested.
+pub fn build_label_tree(labels: &[LabelInfo], include_system: bool) -> Vec<LabelNode> {
+    let mut roots: Vec<LabelNode> = Vec::new();
+    let mut sorted: Vec<&LabelInfo> = labels
+        .iter()
+        .filter(|label| include_system || !label.is_system())
+        .collect();
+    sorted.sort_by_key(|label| label.name.to_lowercase());
+
+    for label in sorted {
+        if label.is_system() {
+            roots.push(LabelNode {
+                id: Some(label.id.clone()),
+                name: label.name.clone(),
+                short_name: label.name.clone(),
+                children: Vec::new(),
+            });
+            continue;
+        }
+
+        let parts: Vec<&str> = label.name.split(LABEL_SEPARATOR).collect();
+        let mut level = &mut roots;
+        for (depth, part) in parts.iter().enumerate() {
+            let path = parts[..=depth].join(&LABEL_SEPARATOR.to_string());
+            let index = match level
+                .iter()
+                .position(|node| node.name.eq_ignore_ascii_case(&path))
+            {
+                Some(index) => index,
+                None => {
+                    level.push(LabelNode {
+                        id: None,
+                        name: path,
+                        short_name: part.to_string(),
+                        children: Vec::new(),
+                    });
+                    level.len() - 1
+                }
+            };
+            let node = &mut level[index];
+            if depth == parts.len() - 1 {
+                node.id = Some(label.id.clone());
+            }
+            level = &mut node.children;
+        }
+    }
+    roots
+}
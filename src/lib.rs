@@ -28,27 +28,108 @@ pub mod token_cache;
  * sophisticated mocking of the API endpoints and more comprehensive tests.
  */
 // Core functionality
+pub mod batch;
 pub mod errors;
+pub mod etag_cache;
 pub mod logging;
+pub mod persist;
+pub mod rate_limit;
 pub mod utils;
+pub mod validation;
 
 // API clients
+pub mod api;
+pub mod attachments;
 pub mod calendar_api;
+pub mod drive_api;
 pub mod gmail_api;
+pub mod gmail_models;
+#[cfg(feature = "local-index")]
+pub mod local_index;
 pub mod people_api;
+pub mod tasks_api;
 
 // Server implementation
+pub mod attendee_availability;
+pub mod bounces;
+pub mod buffers;
+pub mod calendar_report;
+pub mod calendar_settings;
+pub mod calendar_watch;
 pub mod cli;
+pub mod commands;
+pub mod compose_context;
+pub mod contact_capture;
+pub mod contact_groups;
+pub mod contact_history;
+pub mod contact_notes;
+pub mod csv_export;
+pub mod deadline;
+pub mod declined;
+pub mod dedupe;
+pub mod doctor;
+pub mod draft_batch;
+pub mod event_properties;
+pub mod event_rsvps;
+pub mod event_rules;
+pub mod event_search;
+pub mod event_templates;
+pub mod follow_ups;
+pub mod forwarding;
+pub mod holidays;
+pub mod ics;
+pub mod idempotency;
+pub mod invites;
+pub mod join_links;
+pub mod label_suggestions;
+pub mod label_tree;
+pub mod listing_detail;
+pub mod mail_merge;
+pub mod mail_rules;
+pub mod mail_settings;
+pub mod mail_webhooks;
+pub mod markdown;
+pub mod meeting_brief;
+pub mod mock;
+pub mod my_availability;
+pub mod newsletters;
+pub mod office_hours;
+pub mod outbox;
 pub mod oauth;
+pub mod phishing;
+pub mod preflight;
+pub mod priority;
 pub mod prompts;
+#[cfg(feature = "record")]
+pub mod recording;
+pub mod reminders;
+pub mod repl;
+pub mod resumable_upload;
+pub mod saved_searches;
+pub mod secure_mail;
+pub mod send_policy;
+pub mod sender_auth;
+pub mod sender_report;
 pub mod server;
+pub mod templates;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+pub mod thread_view;
+pub mod time_blocking;
+pub mod timezones;
+pub mod token_info;
+pub mod triage;
+pub mod weekly_review;
+pub mod working_location;
 
 // ===== Re-exports =====
 
 // Error handling and results
 pub use crate::errors::{
-    error_codes, CalendarApiError, CalendarResult, ConfigError, GmailApiError, GmailResult,
-    PeopleApiError, PeopleResult,
+    error_codes, CalendarApiError, CalendarResult, ConfigError, DriveApiError, DriveResult, Error,
+    GmailApiError, GmailResult, LocalIndexError, LocalIndexResult, MailRuleError, MailRuleResult,
+    PeopleApiError, PeopleResult, QuotaScope, RateLimitInfo, RuleError, RuleResult,
+    SavedSearchError, SavedSearchResult, TasksApiError, TasksResult, TemplateError, TemplateResult,
 };
 
 // Configuration and constants
@@ -62,24 +143,33 @@ pub use crate::auth::TokenManager;
 pub use crate::token_cache::{TokenCache, TokenCacheConfig, CachedToken};
 
 // Gmail API types
-pub use crate::gmail_api::{DraftEmail, EmailMessage, GmailService};
+pub use crate::gmail_api::{
+    AutoForwarding, DraftEmail, EmailMessage, ForwardingAddress, GmailProfile, GmailService,
+    HistoryChanges, MessageCount,
+};
 
 // People API types
 pub use crate::people_api::{
     Contact, ContactList, EmailAddress, Organization, PeopleClient, PersonName, PhoneNumber, Photo,
 };
 
+// Tasks API types
+pub use crate::tasks_api::{Task, TaskList, TasksClient};
+
+// Drive API types
+pub use crate::drive_api::{DriveClient, DriveFile, DrivePermission, StorageQuota};
+
 // Calendar API types
 pub use crate::calendar_api::{
     Attendee, CalendarClient, CalendarEvent, CalendarInfo, CalendarList, ConferenceData,
-    ConferenceSolution, EntryPoint, EventOrganizer,
+    ConferenceSolution, EntryPoint, EventOrganizer, JoinLink,
 };
 
 // Utils and prompts
 pub use crate::prompts::*;
 pub use crate::utils::{
-    decode_base64, encode_base64_url_safe, error_codes as utils_error_codes, map_gmail_error,
-    parse_max_results, to_mcp_error,
+    decode_base64, encode_base64_url_safe, error_codes as utils_error_codes, error_data,
+    map_gmail_error, map_to_mcp_error, parse_max_results, to_mcp_error, to_mcp_error_with_data, ErrorData,
 };
 
 // Server implementation
@@ -0,0 +1,86 @@
+use crate::gmail_api::EmailMessage;
+
+// Listing detail
+//
+// Listings return each message's snippet and both bodies, which can use up
+// much of a caller's context when only a glance at each message is needed.
+// Callers can shorten snippets and leave out either or both bodies per
+// request, trading detail for room.
+
+/// Which message bodies a listing includes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BodyDetail {
+    /// Neither body, only the snippet and headers
+    None,
+    /// The plain text body only
+    Text,
+    /// The plain text and HTML bodies
+    #[default]
+    Both,
+}
+
+impl BodyDetail {
+    /// Parse "none", "text" or "both", ignoring case
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "none" => Some(Self::None),
+            "text" => Some(Self::Text),
+            "both" => Some(Self::Both),
+            _ => None,
+        }
+    }
+}
+
+/// How much of each message a listing includes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ListingDetail {
+    /// Longest snippet in characters, or no limit
+    pub snippet_length: Option<usize>,
+    pub include_body: BodyDetail,
+}
+
+impl ListingDetail {
+    /// The detail asked for by a tool's arguments
+    pub fn from_args(
+        snippet_length: Option<usize>,
+        include_body: Option<&str>,
+    ) -> Result<Self, String> {
+        let include_body = match include_body {
+            Some(value) => BodyDetail::parse(value).ok_or_else(|| {
+                format!(
+                    "Unknown include_body '{}': use one of none, text, both",
+                    value
+                )
+            })?,
+            None => BodyDetail::default(),
+        };
+        Ok(Self {
+            snippet_length,
+            include_body,
+        })
+    }
+
+    /// Shorten the snippet and drop the bodies left out of a message
+    pub fn apply(&self, message: &mut EmailMessage) {
+        if let (Some(limit), Some(snippet)) = (self.snippet_length, message.snippet.as_mut()) {
+            *snippet = truncate_chars(snippet, limit);
+        }
+        match self.include_body {
+            BodyDetail::None => {
+                message.body_text = None;
+                message.body_html = None;
+            }
+            BodyDetail::Text => message.body_html = None,
+            BodyDetail::Both => {}
+        }
+    }
+}
+
+/// The first `limit` characters of some text, marked with "…" when cut
+pub fn truncate_chars(text: &str, limit: usize) -> String {
+    if text.chars().count() <= limit {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(limit).collect();
+    format!("{}…", cut.trim_end())
+}
@@ -0,0 +1,466 @@
+use crate::api::GmailApi;
+use crate::errors::{GmailApiError, LocalIndexError, LocalIndexResult};
+use crate::gmail_api::EmailMessage;
+use crate::persist;
+use chrono::{DateTime, Utc};
+use log::{debug, info, warn};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+// Local message index
+//
+// A SQLite database with an FTS5 table holding the metadata and snippets of
+// recent messages. The index is filled by a full sync of the newest messages
+// and then kept current through the Gmail History API, so that common searches
+// can be answered without a round trip or API quota.
+//
+// Only free text and the `from:`, `to:` and `subject:` operators can be
+// answered locally. Anything else, or any search while the index is stale,
+// goes to Gmail instead; see the `search_local` tool.
+
+/// Configuration for the local index
+#[derive(Debug, Clone)]
+pub struct LocalIndexConfig {
+    pub enabled: bool,
+    pub path: PathBuf,
+    /// How long after a sync the index is still used to answer searches
+    pub max_age: Duration,
+    /// Number of recent messages fetched by a full sync
+    pub sync_limit: u32,
+}
+
+impl LocalIndexConfig {
+    /// Create a LocalIndexConfig from environment variables
+    ///
+    /// Environment variables: LOCAL_INDEX_ENABLED (default true),
+    /// LOCAL_INDEX_FILE, LOCAL_INDEX_MAX_AGE_SECONDS (default 300) and
+    /// LOCAL_INDEX_SYNC_LIMIT (default 200)
+    pub fn from_env() -> Self {
+        let enabled = std::env::var("LOCAL_INDEX_ENABLED")
+            .map(|s| s.to_lowercase() == "true" || s == "1")
+            .unwrap_or(true);
+
+        let path = std::env::var("LOCAL_INDEX_FILE")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| persist::default_path(dirs::cache_dir(), "local-index.sqlite"));
+
+        let max_age = std::env::var("LOCAL_INDEX_MAX_AGE_SECONDS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .unwrap_or(300);
+
+        let sync_limit = std::env::var("LOCAL_INDEX_SYNC_LIMIT")
+            .ok()
+            .and_then(|s| s.parse::<u32>().ok())
+            .unwrap_or(200);
+
+        Self {
+            enabled,
+            path,
+            max_age: Duration::from_secs(max_age),
+            sync_limit,
+        }
+    }
+}
+
+/// What a sync changed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SyncStats {
+    /// Whether the index was rebuilt from scratch
+    pub full: bool,
+    pub added: usize,
+    pub removed: usize,
+}
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS messages (
+        id TEXT PRIMARY KEY,
+        thread_id TEXT NOT NULL,
+        subject TEXT,
+        sender TEXT,
+        recipients TEXT,
+        date TEXT,
+        timestamp INTEGER,
+        snippet TEXT
+    );
+    CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts USING fts5(
+        id UNINDEXED, subject, sender, recipients, snippet
+    );
+    CREATE TABLE IF NOT EXISTS sync_state (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+";
+
+const HISTORY_ID_KEY: &str = "history_id";
+const LAST_SYNCED_KEY: &str = "last_synced";
+
+fn db_error(err: rusqlite::Error) -> LocalIndexError {
+    LocalIndexError::DatabaseError(err.to_string())
+}
+
+/// SQLite index of message metadata
+pub struct LocalIndex {
+    conn: Mutex<Connection>,
+}
+
+impl LocalIndex {
+    /// Open or create the index at `path`
+    pub fn open(path: &Path) -> LocalIndexResult<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| LocalIndexError::DatabaseError(e.to_string()))?;
+        }
+        Self::init(Connection::open(path).map_err(db_error)?)
+    }
+
+    /// Create an index that lives only as long as this value
+    pub fn open_in_memory() -> LocalIndexResult<Self> {
+        Self::init(Connection::open_in_memory().map_err(db_error)?)
+    }
+
+    fn init(conn: Connection) -> LocalIndexResult<Self> {
+        // A background sync may hold the write lock briefly
+        conn.busy_timeout(Duration::from_secs(5))
+            .map_err(db_error)?;
+        conn.execute_batch(SCHEMA).map_err(db_error)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        self.conn
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Add or replace a message
+    pub fn upsert(&self, message: &EmailMessage) -> LocalIndexResult<()> {
+        let timestamp = message
+            .date
+            .as_deref()
+            .and_then(|date| DateTime::parse_from_rfc2822(date).ok())
+            .map(|date| date.timestamp());
+
+        let mut conn = self.conn();
+        let tx = conn.transaction().map_err(db_error)?;
+        tx.execute("DELETE FROM messages_fts WHERE id = ?1", [&message.id])
+            .map_err(db_error)?;
+        tx.execute(
+            "INSERT OR REPLACE INTO messages
+                (id, thread_id, subject, sender, recipients, date, timestamp, snippet)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                message.id,
+                message.thread_id,
+                message.subject,
+                message.from,
+                message.to,
+                message.date,
+                timestamp,
+                message.snippet,
+            ],
+        )
+        .map_err(db_error)?;
+        tx.execute(
+            "INSERT INTO messages_fts (id, subject, sender, recipients, snippet)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                message.id,
+                message.subject,
+                message.from,
+                message.to,
+                message.snippet,
+            ],
+        )
+        .map_err(db_error)?;
+        tx.commit().map_err(db_error)
+    }
+
+    /// Remove a message if it is indexed
+    pub fn remove(&self, message_id: &str) -> LocalIndexResult<()> {
+        let mut conn = self.conn();
+        let tx = conn.transaction().map_err(db_error)?;
+        tx.execute("DELETE FROM messages_fts WHERE id = ?1", [message_id])
+            .map_err(db_error)?;
+        tx.execute("DELETE FROM messages WHERE id = ?1", [message_id])
+            .map_err(db_error)?;
+        tx.commit().map_err(db_error)
+    }
+
+    /// Remove all messages and sync state
+    pub fn clear(&self) -> LocalIndexResult<()> {
+        self.conn()
+            .execute_batch(
+                "DELETE FROM messages_fts; DELETE FROM messages; DELETE FROM sync_state;",
+            )
+            .map_err(db_error)
+    }
+
+    /// Number of indexed messages
+    pub fn message_count(&self) -> LocalIndexResult<usize> {
+        self.conn()
+            .query_row("SELECT COUNT(*) FROM messages", [], |row| row.get(0))
+            .map_err(db_error)
+    }
+
+    fn state(&self, key: &str) -> LocalIndexResult<Option<String>> {
+        self.conn()
+            .query_row(
+                "SELECT value FROM sync_state WHERE key = ?1",
+                [key],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(db_error)
+    }
+
+    fn set_state(&self, key: &str, value: &str) -> LocalIndexResult<()> {
+        self.conn()
+            .execute(
+                "INSERT OR REPLACE INTO sync_state (key, value) VALUES (?1, ?2)",
+                [key, value],
+            )
+            .map(|_| ())
+            .map_err(db_error)
+    }
+
+    /// History ID the index was last synced to
+    pub fn history_id(&self) -> LocalIndexResult<Option<String>> {
+        self.state(HISTORY_ID_KEY)
+    }
+
+    /// When the index was last synced
+    pub fn last_synced(&self) -> LocalIndexResult<Option<DateTime<Utc>>> {
+        Ok(self.state(LAST_SYNCED_KEY)?.and_then(|value| {
+            DateTime::parse_from_rfc3339(&value)
+                .ok()
+                .map(|date| date.with_timezone(&Utc))
+        }))
+    }
+
+    /// Whether the index has never been synced or was last synced more than
+    /// `max_age` ago
+    pub fn is_stale(&self, max_age: Duration) -> LocalIndexResult<bool> {
+        Ok(match self.last_synced()? {
+            Some(synced) => {
+                let age = Utc::now().signed_duration_since(synced);
+                age.to_std().map(|age| age > max_age).unwrap_or(false)
+            }
+            None => true,
+        })
+    }
+
+    fn mark_synced(&self, history_id: &str) -> LocalIndexResult<()> {
+        self.set_state(HISTORY_ID_KEY, history_id)?;
+        self.set_state(LAST_SYNCED_KEY, &Utc::now().to_rfc3339())
+    }
+
+    /// Search indexed messages, newest first. The returned messages carry
+    /// metadata and snippets only; use `get_email` for the bodies.
+    ///
+    /// Fails with `UnsupportedQuery` for Gmail operators the index cannot
+    /// answer.
+    pub fn search(&self, query: &str, max_results: u32) -> LocalIndexResult<Vec<EmailMessage>> {
+        let fts_query = to_fts_query(query)
+            .ok_or_else(|| LocalIndexError::UnsupportedQuery(query.to_string()))?;
+        debug!("Local index query '{}' -> '{}'", query, fts_query);
+
+        let conn = self.conn();
+        let mut statement = conn
+            .prepare(
+                "SELECT m.id, m.thread_id, m.subject, m.sender, m.recipients, m.date, m.snippet
+                 FROM messages_fts
+                 JOIN messages m ON m.id = messages_fts.id
+                 WHERE messages_fts MATCH ?1
+                 ORDER BY m.timestamp DESC
+                 LIMIT ?2",
+            )
+            .map_err(db_error)?;
+
+        let rows = statement
+            .query_map(params![fts_query, max_results], |row| {
+                Ok(EmailMessage {
+                    id: row.get(0)?,
+                    thread_id: row.get(1)?,
+                    subject: row.get(2)?,
+                    from: row.get(3)?,
+                    to: row.get(4)?,
+                    date: row.get(5)?,
+                    snippet: row.get(6)?,
+                    body_text: None,
+                    body_html: None,
+                    rfc_message_id: None,
+                    language: None,
+                    is_signed: false,
+                    is_encrypted: false,
+                    signer: None,
+                    authentication: None,
+                    inline_images: Vec::new(),
+                    priority: Default::default(),
+                    delivery_report: None,
+                    read_receipt: None,
+                    trackers: Vec::new(),
+                })
+            })
+            .map_err(db_error)?;
+
+        rows.collect::<Result<Vec<_>, _>>().map_err(db_error)
+    }
+
+    /// Bring the index up to date
+    ///
+    /// The first sync, and any sync after Gmail has expired the stored
+    /// history ID, fetches the newest `sync_limit` messages. Later syncs apply
+    /// only the changes reported by the History API.
+    pub async fn sync<G>(&self, gmail: &G, sync_limit: u32) -> LocalIndexResult<SyncStats>
+    where
+        G: GmailApi + ?Sized,
+    {
+        if let Some(history_id) = self.history_id()? {
+            match gmail.list_history(&history_id).await {
+                Ok(changes) => {
+                    let mut stats = SyncStats::default();
+                    for id in &changes.added {
+                        match gmail.get_message_details(id).await {
+                            Ok(message) => {
+                                self.upsert(&message)?;
+                                stats.added += 1;
+                            }
+                            // Added and then deleted before this sync
+                            Err(GmailApiError::MessageRetrievalError(_)) => {}
+                            Err(e) => return Err(e.into()),
+                        }
+                    }
+                    for id in &changes.deleted {
+                        self.remove(id)?;
+                        stats.removed += 1;
+                    }
+                    self.mark_synced(&changes.history_id)?;
+                    debug!("Local index incremental sync: {:?}", stats);
+                    return Ok(stats);
+                }
+                Err(GmailApiError::MessageRetrievalError(e)) => {
+                    info!("Local index history expired, resyncing: {}", e);
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        self.full_sync(gmail, sync_limit).await
+    }
+
+    async fn full_sync<G>(&self, gmail: &G, sync_limit: u32) -> LocalIndexResult<SyncStats>
+    where
+        G: GmailApi + ?Sized,
+    {
+        // Take the history ID first so that changes made during the sync are
+        // picked up by the next one
+        let history_id = gmail.get_history_id().await?;
+        let ids = gmail.list_message_ids(sync_limit, None).await?;
+
+        let mut messages = Vec::with_capacity(ids.len());
+        for id in &ids {
+            match gmail.get_message_details(id).await {
+                Ok(message) => messages.push(message),
+                Err(e) => warn!("Skipping message {} in local index sync: {}", id, e),
+            }
+        }
+        // A listing cut short would replace the index with part of it
+        if gmail.deadline_reached() {
+            return Err(GmailApiError::NetworkError(
+                "Listing messages was cut short by the deadline; the index was left as it was"
+                    .to_string(),
+            )
+            .into());
+        }
+
+        self.clear()?;
+        for message in &messages {
+            self.upsert(message)?;
+        }
+        self.mark_synced(&history_id)?;
+
+        let stats = SyncStats {
+            full: true,
+            added: messages.len(),
+            removed: 0,
+        };
+        info!("Local index full sync: {:?}", stats);
+        Ok(stats)
+    }
+}
+
+/// Translate a Gmail search query into an FTS5 query
+///
+/// Supports free text, quoted phrases and the `from:`, `to:` and `subject:`
+/// operators, all of which must match. Returns `None` for anything else, such
+/// as `is:unread`, `-word` or `OR`, which only Gmail can answer.
+pub fn to_fts_query(query: &str) -> Option<String> {
+    let mut terms = Vec::new();
+
+    for token in tokenize(query) {
+        let (column, text) = match token.split_once(':') {
+            Some((operator, value)) if !token.starts_with('"') => {
+                let column = match operator.to_lowercase().as_str() {
+                    "from" => "sender",
+                    "to" => "recipients",
+                    "subject" => "subject",
+                    _ => return None,
+                };
+                (Some(column), value.trim_matches('"').to_string())
+            }
+            _ => (None, token.trim_matches('"').to_string()),
+        };
+
+        if text.is_empty()
+            || token.starts_with('-')
+            || token == "OR"
+            || text.contains(['(', ')', '{', '}'])
+        {
+            return None;
+        }
+
+        let phrase = format!("\"{}\"", text.replace('"', "\"\""));
+        terms.push(match column {
+            Some(column) => format!("{} : {}", column, phrase),
+            None => phrase,
+        });
+    }
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+// Split a query on whitespace, keeping quoted phrases (including
+// `subject:"two words"`) together
+fn tokenize(query: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+
+    for c in query.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                current.push(c);
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
@@ -36,7 +36,7 @@ pub fn setup_logging(log_level: LevelFilter, log_file: Option<&str>) -> std::io:
             simplelog::TerminalMode::Stderr,
             simplelog::ColorChoice::Auto,
         )
-        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+        .map_err(std::io::Error::other)?;
 
         log::info!("Logging initialized to stderr only (memory mode)");
         log::debug!("Debug logging enabled");
@@ -77,7 +77,7 @@ pub fn setup_logging(log_level: LevelFilter, log_file: Option<&str>) -> std::io:
             simplelog::ColorChoice::Auto,
         ),
     ])
-    .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+    .map_err(std::io::Error::other)?;
 
     log::info!("Logging initialized to file: {} and stderr", log_path);
     log::debug!("Debug logging enabled");
@@ -0,0 +1,98 @@
+use crate::errors::TemplateResult;
+use crate::gmail_api::DraftEmail;
+use crate::templates::EmailTemplate;
+use std::collections::HashMap;
+use std::time::Duration;
+
+// Mail merge
+//
+// A mail merge fills a saved template once for each recipient from that
+// recipient's fields, such as {"email": "ada@example.com", "first_name":
+// "Ada"}, and saves the results as drafts or sends them. Sending is spaced
+// out to stay clear of Gmail's sending limits, and every recipient is
+// reported on its own so one with a missing field doesn't stop the rest.
+
+/// Most recipients in one mail merge
+pub const MAX_MERGE_RECIPIENTS: usize = crate::draft_batch::MAX_BATCH_DRAFTS;
+
+/// Default pause between emails sent by a mail merge, in milliseconds
+pub const DEFAULT_SEND_INTERVAL_MS: u64 = 1000;
+
+/// Fields that give a recipient's address, tried in order
+pub const RECIPIENT_FIELDS: [&str; 2] = ["to", "email"];
+
+/// What a mail merge does with each filled template
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeMode {
+    /// Save each email as a draft to review and send by hand
+    Draft,
+    /// Send each email
+    Send,
+}
+
+impl MergeMode {
+    /// Parse "draft" or "send", ignoring case
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "draft" | "drafts" => Some(Self::Draft),
+            "send" => Some(Self::Send),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Draft => "draft",
+            Self::Send => "send",
+        }
+    }
+}
+
+/// The recipient's address from their fields, if given
+pub fn recipient_address(fields: &HashMap<String, String>) -> Option<&str> {
+    RECIPIENT_FIELDS
+        .iter()
+        .filter_map(|name| fields.get(*name))
+        .map(|value| value.trim())
+        .find(|value| !value.is_empty())
+}
+
+/// What identifies a recipient in the results: their address, or their
+/// position when they have none
+pub fn recipient_id(index: usize, fields: &HashMap<String, String>) -> String {
+    recipient_address(fields)
+        .map(String::from)
+        .unwrap_or_else(|| format!("#{}", index + 1))
+}
+
+/// Fill the template for one recipient. Their fields fill the placeholders,
+/// and their address, if given, replaces the template's recipients.
+pub fn merge_email(
+    template: &EmailTemplate,
+    fields: &HashMap<String, String>,
+) -> TemplateResult<DraftEmail> {
+    let to = recipient_address(fields).map(String::from);
+    template.render(fields)?.into_draft(to, None)
+}
+
+/// Fill the template for every recipient, in order
+pub fn merge_emails(
+    template: &EmailTemplate,
+    recipients: &[HashMap<String, String>],
+) -> Vec<TemplateResult<DraftEmail>> {
+    recipients
+        .iter()
+        .map(|fields| merge_email(template, fields))
+        .collect()
+}
+
+/// Returns the pause between emails sent by a mail merge.
+///
+/// Environment variable: MAIL_MERGE_SEND_INTERVAL_MS
+pub fn get_send_interval() -> Duration {
+    let millis = std::env::var("MAIL_MERGE_SEND_INTERVAL_MS")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_SEND_INTERVAL_MS);
+    Duration::from_millis(millis)
+}
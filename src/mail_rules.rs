@@ -0,0 +1,447 @@
+use crate::api::GmailApi;
+use crate::errors::{MailRuleError, MailRuleResult, Result};
+use crate::gmail_api::{DraftEmail, EmailMessage, MessageMetadata};
+use crate::label_tree::{find_label, LabelInfo};
+use crate::persist;
+use crate::utils::parse_email_addresses;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+// Mail rules
+//
+// Rules automate what Gmail's own filters can't: a rule matches mail by its
+// sender, words in its subject or the labels it has, and then labels,
+// archives or forwards it, or flags it to notify the user. Rules are read
+// from a JSON file edited by hand, and every condition a rule gives must
+// hold. Unlike event rules, every matching rule applies, as with Gmail's
+// filters.
+//
+// run_rules applies them to the mail matching a search, or to the mail that
+// has arrived since it last ran, read from the History API from a history ID
+// kept in the state directory. A dry run reports what would be done.
+
+/// Name of the mail rule file, in the config directory
+pub const MAIL_RULES_FILE: &str = "mail_rules.json";
+
+/// Name of the file the last history ID seen is kept in, in the state
+/// directory
+pub const MAIL_RULES_STATE_FILE: &str = "mail_rules_state.json";
+
+/// Query for the mail checked when there is no history to read new mail from
+pub const NEW_MAIL_FALLBACK_QUERY: &str = "in:inbox newer_than:1d";
+
+/// Most messages checked in one run
+pub const MAX_RULE_MESSAGES: u32 = 500;
+
+/// A rule acting on the mail it matches
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MailRule {
+    pub name: String,
+    /// Addresses or "@domain"s, any of which as the sender matches
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub from: Vec<String>,
+    /// Words or phrases, any of which in the subject matches
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subject: Vec<String>,
+    /// Label names, any of which on the message matches
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    /// Label to apply, created if missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Remove matching mail from the inbox
+    #[serde(default)]
+    pub archive: bool,
+    /// Address to forward matching mail to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forward_to: Option<String>,
+    /// Report matching mail under `notifications`
+    #[serde(default)]
+    pub notify: bool,
+}
+
+impl MailRule {
+    fn matches_from(&self, message: &MessageMetadata) -> bool {
+        if self.from.is_empty() {
+            return true;
+        }
+        let Some(sender) = message
+            .from
+            .as_deref()
+            .and_then(|from| parse_email_addresses(from).into_iter().next())
+        else {
+            return false;
+        };
+        let sender = sender.to_lowercase();
+        self.from.iter().any(|pattern| {
+            let pattern = pattern.trim().to_lowercase();
+            if pattern.starts_with('@') {
+                sender.ends_with(&pattern)
+            } else {
+                sender == pattern
+            }
+        })
+    }
+
+    fn matches_subject(&self, message: &MessageMetadata) -> bool {
+        if self.subject.is_empty() {
+            return true;
+        }
+        let subject = message
+            .subject
+            .as_deref()
+            .unwrap_or_default()
+            .to_lowercase();
+        self.subject
+            .iter()
+            .any(|keyword| subject.contains(&keyword.trim().to_lowercase()))
+    }
+
+    fn matches_labels(&self, message: &MessageMetadata, labels: &[LabelInfo]) -> bool {
+        if self.labels.is_empty() {
+            return true;
+        }
+        self.labels.iter().any(|name| {
+            let id = find_label(labels, name.trim())
+                .map(|label| label.id.as_str())
+                .unwrap_or(name.trim());
+            message
+                .label_ids
+                .iter()
+                .any(|label_id| label_id.eq_ignore_ascii_case(id))
+        })
+    }
+
+    /// Whether every condition of the rule holds for the message, given the
+    /// mailbox's labels to look label names up in
+    pub fn matches(&self, message: &MessageMetadata, labels: &[LabelInfo]) -> bool {
+        self.matches_from(message)
+            && self.matches_subject(message)
+            && self.matches_labels(message, labels)
+    }
+
+    fn validate(&mut self) -> MailRuleResult<()> {
+        self.name = self.name.trim().to_string();
+        if self.name.is_empty() {
+            return Err(MailRuleError::InvalidRule(
+                "Rule name cannot be empty".to_string(),
+            ));
+        }
+        self.from.retain(|from| !from.trim().is_empty());
+        self.subject.retain(|keyword| !keyword.trim().is_empty());
+        self.labels.retain(|label| !label.trim().is_empty());
+        if self.from.is_empty() && self.subject.is_empty() && self.labels.is_empty() {
+            return Err(MailRuleError::InvalidRule(format!(
+                "Rule '{}' needs from, subject or labels to match mail by",
+                self.name
+            )));
+        }
+        self.label = self
+            .label
+            .take()
+            .map(|label| label.trim().to_string())
+            .filter(|label| !label.is_empty());
+        self.forward_to = self
+            .forward_to
+            .take()
+            .map(|to| to.trim().to_string())
+            .filter(|to| !to.is_empty());
+        if let Some(to) = &self.forward_to {
+            if parse_email_addresses(to).len() != 1 {
+                return Err(MailRuleError::InvalidRule(format!(
+                    "Rule '{}' has an invalid forward_to address '{}'",
+                    self.name, to
+                )));
+            }
+        }
+        if self.label.is_none() && !self.archive && self.forward_to.is_none() && !self.notify {
+            return Err(MailRuleError::InvalidRule(format!(
+                "Rule '{}' needs an action: label, archive, forward_to or notify",
+                self.name
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// What the rules matching a message do to it
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleMatch {
+    pub message_id: String,
+    pub from: Option<String>,
+    pub subject: Option<String>,
+    /// Names of the matching rules, in file order
+    pub rules: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub add_labels: Vec<String>,
+    pub archive: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub forward_to: Vec<String>,
+    pub notify: bool,
+    /// "applied", "would_apply" for a dry run, or "failed"
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Combine the actions of every rule matching a message, or None when no
+/// rule matches it
+pub fn match_rules(
+    rules: &[MailRule],
+    message: &MessageMetadata,
+    labels: &[LabelInfo],
+) -> Option<RuleMatch> {
+    let matching: Vec<&MailRule> = rules
+        .iter()
+        .filter(|rule| rule.matches(message, labels))
+        .collect();
+    if matching.is_empty() {
+        return None;
+    }
+
+    let mut result = RuleMatch {
+        message_id: message.id.clone(),
+        from: message.from.clone(),
+        subject: message.subject.clone(),
+        rules: Vec::new(),
+        add_labels: Vec::new(),
+        archive: false,
+        forward_to: Vec::new(),
+        notify: false,
+        status: "would_apply".to_string(),
+        error: None,
+    };
+    for rule in matching {
+        result.rules.push(rule.name.clone());
+        if let Some(label) = &rule.label {
+            if !result
+                .add_labels
+                .iter()
+                .any(|added| added.eq_ignore_ascii_case(label))
+            {
+                result.add_labels.push(label.clone());
+            }
+        }
+        if let Some(to) = &rule.forward_to {
+            if !result
+                .forward_to
+                .iter()
+                .any(|added| added.eq_ignore_ascii_case(to))
+            {
+                result.forward_to.push(to.clone());
+            }
+        }
+        result.archive |= rule.archive;
+        result.notify |= rule.notify;
+    }
+    Some(result)
+}
+
+/// Whether the rules should look at a message: mail that was received, not
+/// drafts or mail the user sent
+pub fn is_incoming(message: &MessageMetadata) -> bool {
+    let has = |label: &str| message.label_ids.iter().any(|id| id == label);
+    !has("DRAFT") && (!has("SENT") || has("INBOX"))
+}
+
+/// An email forwarding a message to an address, with the original's headers
+/// above its body
+pub fn forward_draft(email: &EmailMessage, to: &str) -> DraftEmail {
+    let subject = email.subject.as_deref().unwrap_or_default().trim();
+    let lower = subject.to_lowercase();
+    let subject = if lower.starts_with("fwd:") || lower.starts_with("fw:") {
+        subject.to_string()
+    } else {
+        format!("Fwd: {}", subject).trim_end().to_string()
+    };
+
+    let original = email
+        .body_text
+        .clone()
+        .filter(|text| !text.trim().is_empty())
+        .or_else(|| {
+            email
+                .body_html
+                .as_deref()
+                .map(crate::markdown::html_to_markdown)
+        })
+        .unwrap_or_default();
+    let mut body = "---------- Forwarded message ---------\n".to_string();
+    for (name, value) in [
+        ("From", &email.from),
+        ("Date", &email.date),
+        ("Subject", &email.subject),
+        ("To", &email.to),
+    ] {
+        if let Some(value) = value {
+            body.push_str(&format!("{}: {}\n", name, value));
+        }
+    }
+    body.push('\n');
+    body.push_str(&original);
+
+    DraftEmail {
+        to: to.to_string(),
+        subject,
+        body,
+        cc: None,
+        bcc: None,
+        thread_id: None,
+        in_reply_to: None,
+        references: None,
+        priority: None,
+        read_receipt_to: None,
+    }
+}
+
+/// Carry out a match's labelling, archiving and forwarding
+pub async fn apply_match<G>(gmail: &G, rule_match: &RuleMatch) -> Result<()>
+where
+    G: GmailApi + ?Sized,
+{
+    let mut add = Vec::with_capacity(rule_match.add_labels.len());
+    for label in &rule_match.add_labels {
+        add.push(gmail.get_or_create_label(label).await?);
+    }
+    let remove = if rule_match.archive {
+        vec!["INBOX".to_string()]
+    } else {
+        Vec::new()
+    };
+    if !add.is_empty() || !remove.is_empty() {
+        gmail
+            .modify_labels(&rule_match.message_id, &add, &remove)
+            .await?;
+    }
+
+    if !rule_match.forward_to.is_empty() {
+        let email = gmail.get_message_details(&rule_match.message_id).await?;
+        for to in &rule_match.forward_to {
+            let message_id = gmail.send_message(&forward_draft(&email, to)).await?;
+            debug!(
+                "Forwarded {} to {} as {}",
+                rule_match.message_id, to, message_id
+            );
+        }
+    }
+
+    info!(
+        "Applied rules {:?} to {}",
+        rule_match.rules, rule_match.message_id
+    );
+    Ok(())
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct RuleFile {
+    rules: Vec<MailRule>,
+}
+
+/// Mail rules read from a JSON file, in the order they are listed
+#[derive(Debug, Clone)]
+pub struct MailRuleStore {
+    path: PathBuf,
+}
+
+impl MailRuleStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// The store at `MAIL_RULES_FILE`, or the default location
+    pub fn from_env() -> Self {
+        Self::new(get_mail_rules_path())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// All rules, in file order. Fails naming the first rule that is
+    /// invalid, so that a mistake in the file never half applies.
+    pub fn list(&self) -> MailRuleResult<Vec<MailRule>> {
+        let mut file: RuleFile =
+            persist::load_json(&self.path).map_err(|e| self.storage_error(e))?;
+        for rule in &mut file.rules {
+            rule.validate()?;
+        }
+        Ok(file.rules)
+    }
+
+    fn storage_error(&self, err: impl std::fmt::Display) -> MailRuleError {
+        MailRuleError::StorageError(format!("{}: {}", self.path.display(), err))
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct RuleState {
+    history_id: Option<String>,
+}
+
+/// The history ID new mail was last read from, kept between runs
+#[derive(Debug, Clone)]
+pub struct RuleHistory {
+    path: PathBuf,
+}
+
+impl RuleHistory {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// The history at `MAIL_RULES_STATE_FILE`, or the default location
+    pub fn from_env() -> Self {
+        Self::new(get_mail_rules_state_path())
+    }
+
+    /// The history ID saved by the last run. A missing or unreadable file
+    /// has none.
+    pub fn history_id(&self) -> Option<String> {
+        let state: RuleState = persist::load_json(&self.path)
+            .map_err(|e| {
+                warn!(
+                    "Ignoring unreadable mail rule state at {}: {}",
+                    self.path.display(),
+                    e
+                )
+            })
+            .ok()?;
+        state.history_id
+    }
+
+    /// Save the history ID to read new mail from on the next run
+    pub fn save(&self, history_id: &str) -> std::io::Result<()> {
+        let state = RuleState {
+            history_id: Some(history_id.to_string()),
+        };
+        persist::store_json(&self.path, &state)
+    }
+}
+
+// Get default mail rule file location (platform-specific)
+fn default_mail_rules_path() -> PathBuf {
+    let mut path = dirs::config_dir().unwrap_or_else(std::env::temp_dir);
+    path.push("gmail-mcp-rs");
+    path.push(MAIL_RULES_FILE);
+    path
+}
+
+/// Returns the path of the mail rule file.
+///
+/// Environment variable: MAIL_RULES_FILE
+pub fn get_mail_rules_path() -> PathBuf {
+    std::env::var("MAIL_RULES_FILE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| default_mail_rules_path())
+}
+
+/// Returns the path of the file the last history ID seen by run_rules is
+/// kept in.
+///
+/// Environment variable: MAIL_RULES_STATE_FILE
+pub fn get_mail_rules_state_path() -> PathBuf {
+    std::env::var("MAIL_RULES_STATE_FILE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| persist::default_path(dirs::cache_dir(), MAIL_RULES_STATE_FILE))
+}
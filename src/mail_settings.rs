@@ -0,0 +1,61 @@
+use crate::gmail_api::{ImapSettings, PopSettings};
+
+// Mail app settings
+//
+// Mail apps such as Outlook, Apple Mail and Thunderbird reach Gmail over IMAP
+// or POP, and several of Gmail's settings for them explain why an app shows
+// no mail, only some of it, or loses mail that was deleted. These checks turn
+// the settings into plain notes for answering "why isn't my mail syncing?".
+
+/// Notes on what the IMAP and POP settings mean for mail apps, the ones that
+/// stop apps syncing first
+pub fn sync_notes(imap: &ImapSettings, pop: &PopSettings) -> Vec<String> {
+    let pop_enabled = pop
+        .access_window
+        .as_deref()
+        .is_some_and(|window| window != "disabled");
+    let mut notes = Vec::new();
+
+    if !imap.enabled {
+        notes.push(
+            "IMAP is off, so mail apps set up with IMAP cannot sync; turn it on in Gmail's \
+             Forwarding and POP/IMAP settings"
+                .to_string(),
+        );
+    }
+    if !pop_enabled {
+        notes.push("POP is off, so mail apps set up with POP cannot download mail".to_string());
+    }
+    if imap.enabled && imap.max_folder_size > 0 {
+        notes.push(format!(
+            "IMAP folders show only the latest {} messages, so older mail is missing from mail apps",
+            imap.max_folder_size
+        ));
+    }
+    if pop.access_window.as_deref() == Some("fromNowOn") {
+        notes.push(
+            "POP only downloads mail that arrived after it was turned on, not older mail"
+                .to_string(),
+        );
+    }
+    if imap.enabled && imap.expunge_behavior.as_deref() == Some("deleteForever") {
+        notes.push(
+            "Messages deleted in a mail app over IMAP are deleted forever, not moved to the trash"
+                .to_string(),
+        );
+    }
+    if pop_enabled {
+        match pop.disposition.as_deref() {
+            Some("trash") => notes.push(
+                "Mail downloaded over POP is moved to the trash, so it leaves Gmail after 30 days"
+                    .to_string(),
+            ),
+            Some("archive") => notes.push(
+                "Mail downloaded over POP is archived, so it leaves the inbox in Gmail and in other apps"
+                    .to_string(),
+            ),
+            _ => {}
+        }
+    }
+    notes
+}
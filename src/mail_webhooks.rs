@@ -0,0 +1,331 @@
+use crate::api::GmailApi;
+use crate::errors::{GmailApiError, GmailResult};
+use crate::gmail_api::GmailService;
+use crate::label_tree::parse_labels;
+use crate::mail_rules::{
+    is_incoming, match_rules, MailRule, MailRuleStore, RuleHistory, RuleMatch,
+};
+use crate::persist;
+use crate::reminders::email_link;
+use log::{debug, error, info, warn};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::time::Duration;
+
+// Mail webhooks
+//
+// With a webhook URL set, the server polls Gmail's history for new mail while
+// it runs and posts the mail matching a mail rule with notify set to the
+// webhook, as a Slack message or as JSON for any other receiver. Only the
+// notify rules are used, and their other actions are left to run_rules. The
+// history ID reached is kept in its own state file, apart from run_rules',
+// and only moves on once the webhook has taken the mail, so that mail is
+// posted again on the next poll when the receiver was down.
+
+/// Name of the file the last history ID polled is kept in, in the state
+/// directory
+pub const MAIL_WEBHOOK_STATE_FILE: &str = "mail_webhook_state.json";
+
+/// Default seconds between polls for new mail
+pub const DEFAULT_POLL_SECONDS: u64 = 60;
+
+/// Fewest seconds between polls, to stay well within Gmail's quota
+pub const MIN_POLL_SECONDS: u64 = 10;
+
+/// Most matching messages posted to the webhook at once; more are posted in
+/// several batches
+pub const MAX_POLL_MESSAGES: usize = 100;
+
+// Seconds to wait for the webhook to answer
+const WEBHOOK_TIMEOUT_SECONDS: u64 = 10;
+
+/// What the webhook expects to be posted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookFormat {
+    /// A Slack incoming webhook message
+    Slack,
+    /// The matching messages as JSON
+    Generic,
+}
+
+impl WebhookFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "slack" => Some(Self::Slack),
+            "generic" | "json" => Some(Self::Generic),
+            _ => None,
+        }
+    }
+
+    /// Slack for Slack's webhook addresses, otherwise generic
+    pub fn for_url(url: &str) -> Self {
+        match url::Url::parse(url) {
+            Ok(url) if url.host_str() == Some("hooks.slack.com") => Self::Slack,
+            _ => Self::Generic,
+        }
+    }
+}
+
+/// Where and how often new mail is posted
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebhookConfig {
+    pub url: String,
+    pub format: WebhookFormat,
+    pub poll_interval: Duration,
+}
+
+impl WebhookConfig {
+    /// The webhook set in the environment, or None when no URL is set
+    pub fn from_env() -> Option<Self> {
+        let url = get_mail_webhook_url()?;
+        let format = get_mail_webhook_format().unwrap_or_else(|| WebhookFormat::for_url(&url));
+        Some(Self {
+            url,
+            format,
+            poll_interval: get_mail_webhook_poll_interval(),
+        })
+    }
+}
+
+/// The new mail found by a poll
+#[derive(Debug, Clone)]
+pub struct NewMail {
+    /// History ID to poll from next
+    pub history_id: String,
+    /// Messages matching a notify rule
+    pub matches: Vec<RuleMatch>,
+}
+
+/// Find the mail added since `since` that matches a notify rule, looking at
+/// every message added, since the history ID returned moves past them all.
+/// Without a history ID to start from, and when Gmail's history from it has
+/// expired, nothing is reported and polling starts again from the current
+/// history ID, so that old mail is never posted.
+pub async fn poll_new_mail<G>(
+    gmail: &G,
+    rules: &[MailRule],
+    since: Option<&str>,
+) -> GmailResult<NewMail>
+where
+    G: GmailApi + ?Sized,
+{
+    let rules: Vec<MailRule> = rules.iter().filter(|rule| rule.notify).cloned().collect();
+    let start_over = |history_id| NewMail {
+        history_id,
+        matches: Vec::new(),
+    };
+
+    let Some(since) = since else {
+        debug!("Starting to poll for new mail");
+        return Ok(start_over(gmail.get_history_id().await?));
+    };
+    let changes = match gmail.list_history(since).await {
+        Ok(changes) => changes,
+        Err(GmailApiError::MessageRetrievalError(e)) => {
+            warn!("Mail history expired, polling from now: {}", e);
+            return Ok(start_over(gmail.get_history_id().await?));
+        }
+        Err(err) => return Err(err),
+    };
+    if changes.added.is_empty() || rules.is_empty() {
+        return Ok(start_over(changes.history_id));
+    }
+
+    let labels = parse_labels(&gmail.list_labels().await?)
+        .map_err(|e| GmailApiError::MessageFormatError(e.to_string()))?;
+    let mut matches = Vec::new();
+    for id in &changes.added {
+        let message = match gmail.get_message_metadata(id).await {
+            Ok(message) => message,
+            // Deleted since it arrived
+            Err(err) => {
+                debug!("Skipping new message {}: {}", id, err);
+                continue;
+            }
+        };
+        if !is_incoming(&message) {
+            continue;
+        }
+        if let Some(rule_match) = match_rules(&rules, &message, &labels) {
+            matches.push(rule_match);
+        }
+    }
+    Ok(NewMail {
+        history_id: changes.history_id,
+        matches,
+    })
+}
+
+/// The body posted to the webhook for the matching messages
+pub fn payload(format: WebhookFormat, matches: &[RuleMatch]) -> Value {
+    match format {
+        WebhookFormat::Slack => {
+            let heading = match matches.len() {
+                1 => "New email matching your mail rules:".to_string(),
+                count => format!("{} new emails matching your mail rules:", count),
+            };
+            let lines: Vec<String> = matches
+                .iter()
+                .map(|m| {
+                    format!(
+                        "• <{}|{}> from {} ({})",
+                        email_link(&m.message_id),
+                        slack_escape(m.subject.as_deref().unwrap_or("(no subject)")),
+                        slack_escape(m.from.as_deref().unwrap_or("unknown sender")),
+                        slack_escape(&m.rules.join(", "))
+                    )
+                })
+                .collect();
+            json!({ "text": format!("{}\n{}", heading, lines.join("\n")) })
+        }
+        WebhookFormat::Generic => {
+            let messages: Vec<Value> = matches
+                .iter()
+                .map(|m| {
+                    json!({
+                        "message_id": m.message_id,
+                        "from": m.from,
+                        "subject": m.subject,
+                        "rules": m.rules,
+                        "link": email_link(&m.message_id),
+                    })
+                })
+                .collect();
+            json!({
+                "event": "new_mail",
+                "count": messages.len(),
+                "messages": messages,
+            })
+        }
+    }
+}
+
+// Escape the characters Slack treats as markup
+fn slack_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Post the matching messages to the webhook
+pub async fn deliver(
+    client: &reqwest::Client,
+    config: &WebhookConfig,
+    matches: &[RuleMatch],
+) -> std::result::Result<(), String> {
+    let response = client
+        .post(&config.url)
+        .json(&payload(config.format, matches))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reach the mail webhook: {}", e))?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("Mail webhook answered with status {}", status));
+    }
+    Ok(())
+}
+
+/// Poll once for new mail and post what matches, moving the saved history
+/// ID on once the webhook has taken it. Returns how many messages were
+/// posted.
+pub async fn poll_once(
+    gmail: &GmailService,
+    client: &reqwest::Client,
+    config: &WebhookConfig,
+    history: &RuleHistory,
+) -> std::result::Result<usize, String> {
+    let rules = MailRuleStore::from_env()
+        .list()
+        .map_err(|e| e.to_string())?;
+    let since = history.history_id();
+    let new_mail = poll_new_mail(gmail, &rules, since.as_deref())
+        .await
+        .map_err(|e| e.to_string())?;
+    if !new_mail.matches.is_empty() {
+        for batch in new_mail.matches.chunks(MAX_POLL_MESSAGES) {
+            deliver(client, config, batch).await?;
+        }
+        info!(
+            "Posted {} new emails to the mail webhook",
+            new_mail.matches.len()
+        );
+    }
+    history
+        .save(&new_mail.history_id)
+        .map_err(|e| format!("Failed to save the mail webhook history: {}", e))?;
+    Ok(new_mail.matches.len())
+}
+
+/// Poll for new mail in the background while the server runs, when a
+/// webhook is set, with the server's Gmail client. Returns whether polling
+/// started.
+pub fn start(gmail: GmailService) -> bool {
+    let Some(config) = WebhookConfig::from_env() else {
+        return false;
+    };
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(WEBHOOK_TIMEOUT_SECONDS))
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new());
+    let history = RuleHistory::new(get_mail_webhook_state_path());
+    info!(
+        "Polling for new mail every {}s for the mail webhook",
+        config.poll_interval.as_secs()
+    );
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(config.poll_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if let Err(e) = poll_once(&gmail, &client, &config, &history).await {
+                error!("Mail webhook poll failed: {}", e);
+            }
+        }
+    });
+    true
+}
+
+/// Returns the URL new mail matching a notify rule is posted to.
+///
+/// Environment variable: MAIL_WEBHOOK_URL
+pub fn get_mail_webhook_url() -> Option<String> {
+    std::env::var("MAIL_WEBHOOK_URL")
+        .ok()
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty())
+}
+
+/// Returns the format posted to the webhook: "slack" or "generic". Unset,
+/// it follows from the URL.
+///
+/// Environment variable: MAIL_WEBHOOK_FORMAT
+pub fn get_mail_webhook_format() -> Option<WebhookFormat> {
+    std::env::var("MAIL_WEBHOOK_FORMAT")
+        .ok()
+        .and_then(|format| WebhookFormat::parse(&format))
+}
+
+/// Returns how often to poll for new mail.
+///
+/// Default is 60 seconds if not configured, and at least 10.
+///
+/// Environment variable: MAIL_WEBHOOK_POLL_SECONDS
+pub fn get_mail_webhook_poll_interval() -> Duration {
+    let seconds = std::env::var("MAIL_WEBHOOK_POLL_SECONDS")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_POLL_SECONDS)
+        .max(MIN_POLL_SECONDS);
+    Duration::from_secs(seconds)
+}
+
+/// Returns the path of the file the last history ID polled for the mail
+/// webhook is kept in.
+///
+/// Environment variable: MAIL_WEBHOOK_STATE_FILE
+pub fn get_mail_webhook_state_path() -> PathBuf {
+    std::env::var("MAIL_WEBHOOK_STATE_FILE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| persist::default_path(dirs::cache_dir(), MAIL_WEBHOOK_STATE_FILE))
+}
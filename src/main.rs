@@ -1,9 +1,25 @@
 use clap::Parser;
 use log::{debug, error, info, LevelFilter};
 use mcp_attr::server::serve_stdio;
-use mcp_gmailcal::{cli::{Cli, Commands}, oauth, setup_logging, GmailServer};
+use mcp_gmailcal::{
+    cli::{Cli, Commands},
+    commands, config, doctor,
+    mock::{self, MockServer},
+    mail_webhooks, oauth, outbox, preflight, repl, setup_logging, GmailServer,
+};
 use std::env;
 
+// Print the output of a direct CLI command, exiting non-zero on failure
+fn print_command_result(result: Result<String, String>) {
+    match result {
+        Ok(output) => println!("{}", output),
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
 // Main function to start the MCP server
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -23,11 +39,44 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!("Running in read-only mode with in-memory logging");
     }
 
+    // In mock mode, serve API requests from local fixtures. The server must stay
+    // alive for the lifetime of the process.
+    let _mock_server = if cli.mock {
+        let server = MockServer::start(cli.mock_fixtures.clone()).await?;
+        server.install();
+        eprintln!(
+            "Running in mock mode using {}",
+            mock::describe_fixtures(cli.mock_fixtures.as_deref())
+        );
+        Some(server)
+    } else {
+        None
+    };
+
+    // When recording, route API requests through a proxy that saves sanitized
+    // copies of the responses
+    #[cfg(feature = "record")]
+    let _recording_proxy = match &cli.record {
+        Some(dir) => {
+            use mcp_gmailcal::recording::{RecordingProxy, Sanitizer, Upstreams};
+
+            let sanitizer = cli
+                .record_redact
+                .iter()
+                .fold(Sanitizer::new(), |sanitizer, text| sanitizer.redact(text));
+            let proxy = RecordingProxy::start(dir.clone(), Upstreams::default(), sanitizer).await?;
+            proxy.install();
+            eprintln!("Recording API responses to {}", dir.display());
+            Some(proxy)
+        }
+        None => None,
+    };
+
     // Determine which command to run
     match cli.command {
-        Some(Commands::Auth) => {
+        Some(Commands::Auth { scopes }) => {
             println!("Starting OAuth authentication flow...");
-            if let Err(e) = oauth::run_oauth_flow().await {
+            if let Err(e) = oauth::run_oauth_flow_with_scopes(&scopes).await {
                 eprintln!("Authentication failed: {}", e);
                 std::process::exit(1);
             }
@@ -48,6 +97,33 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
             return Ok(());
         }
+        Some(Commands::Doctor) => {
+            println!("Running diagnostics...\n");
+            let results = doctor::run_checks().await;
+            if !doctor::print_report(&results) {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Commands::Repl) => {
+            if let Err(e) = repl::run_repl().await {
+                eprintln!("❌ {}", e);
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Commands::Mail { command }) => {
+            print_command_result(commands::run_mail(command).await);
+            return Ok(());
+        }
+        Some(Commands::Cal { command }) => {
+            print_command_result(commands::run_cal(command).await);
+            return Ok(());
+        }
+        Some(Commands::Contacts { command }) => {
+            print_command_result(commands::run_contacts(command).await);
+            return Ok(());
+        }
         Some(Commands::Server) | None => {
             // Continue with server startup
         }
@@ -66,10 +142,34 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("Logs will be saved to {}", log_file);
     debug!("Debug logging enabled");
 
+    // Check readiness in the background so that a slow or failing check
+    // never delays or stops the server
+    if cli.preflight || config::get_startup_preflight() {
+        info!("Running startup preflight checks");
+        tokio::spawn(async {
+            let results = preflight::run_preflight().await;
+            preflight::log_report(&results);
+        });
+    }
+
     // Start the MCP server
     debug!("Creating GmailServer instance");
     let server = GmailServer::new();
 
+    // The background work uses the server's Gmail client. Without a working
+    // configuration it can't run, and the tools report the error.
+    match server.gmail_service() {
+        Ok(gmail) => {
+            // Send the emails an earlier run left waiting in the outbox
+            outbox::resume(outbox::Outbox::global(), gmail.clone());
+
+            // Post new mail matching the notify rules to the webhook, when
+            // one is set
+            mail_webhooks::start(gmail);
+        }
+        Err(e) => error!("Not starting background mail work: {}", e),
+    }
+
     // Run the server
     info!("Starting MCP server with stdio interface");
     let result = serve_stdio(server).await;
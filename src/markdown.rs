@@ -0,0 +1,337 @@
+use crate::attachments::AttachmentInfo;
+use crate::gmail_api::EmailMessage;
+use crate::utils::{
+    decode_html_entities, decode_numeric_entities, find_tag_end, is_remote_url, is_safe_url,
+    parse_tag,
+};
+
+// Markdown rendering
+//
+// An email can be rendered as a Markdown document for pasting into notes or
+// handing to tools that read Markdown: the subject as a title, a table of
+// its headers, the body, and lists of its attachments and inline images.
+//
+// The HTML body is preferred since it keeps links and images; it is turned
+// into Markdown by keeping headings, paragraphs, lists, emphasis, links and
+// images and dropping all other markup. Mail with only a plain text body has
+// that as its body as it is.
+
+// Elements dropped along with their content
+const SKIPPED_ELEMENTS: &[&str] = &["head", "script", "style", "title", "template"];
+
+// Elements that start and end a block of their own
+const BLOCK_ELEMENTS: &[&str] = &[
+    "p",
+    "div",
+    "table",
+    "tr",
+    "ul",
+    "ol",
+    "blockquote",
+    "section",
+    "article",
+    "header",
+    "footer",
+    "pre",
+];
+
+/// Render an email, with the attachments found in its payload, as Markdown
+pub fn render_email(email: &EmailMessage, attachments: &[AttachmentInfo]) -> String {
+    let subject = email
+        .subject
+        .as_deref()
+        .map(str::trim)
+        .filter(|subject| !subject.is_empty())
+        .unwrap_or("(no subject)");
+    let mut markdown = format!("# {}\n\n", single_line(subject));
+
+    markdown.push_str("| Field | Value |\n| --- | --- |\n");
+    let headers = [
+        ("From", email.from.as_deref()),
+        ("To", email.to.as_deref()),
+        ("Date", email.date.as_deref()),
+        ("Message-ID", email.rfc_message_id.as_deref()),
+    ];
+    for (field, value) in headers {
+        if let Some(value) = value {
+            markdown.push_str(&format!("| {} | {} |\n", field, table_cell(value)));
+        }
+    }
+    markdown.push('\n');
+
+    let body = email
+        .body_html
+        .as_deref()
+        .map(html_to_markdown)
+        .filter(|body| !body.is_empty())
+        .or_else(|| {
+            email
+                .body_text
+                .as_deref()
+                .map(|text| text.trim().replace("\r\n", "\n"))
+                .filter(|text| !text.is_empty())
+        });
+    match body {
+        Some(body) => markdown.push_str(&body),
+        None if email.is_encrypted => {
+            markdown.push_str("_This message is encrypted, so its body is not shown._")
+        }
+        None => markdown.push_str("_This message has no body._"),
+    }
+    markdown.push('\n');
+
+    // Inline images are listed on their own rather than as attachments
+    let files: Vec<&AttachmentInfo> = attachments
+        .iter()
+        .filter(|attachment| {
+            !email
+                .inline_images
+                .iter()
+                .any(|image| image.attachment_id.as_deref() == Some(&attachment.attachment_id))
+        })
+        .collect();
+    if !files.is_empty() {
+        markdown.push_str("\n## Attachments\n\n");
+        for file in files {
+            let name = if file.filename.is_empty() {
+                "(unnamed)"
+            } else {
+                &file.filename
+            };
+            markdown.push_str(&format!(
+                "- {} ({}, {}) — attachment ID `{}`\n",
+                single_line(name),
+                file.mime_type,
+                format_size(file.size),
+                file.attachment_id
+            ));
+        }
+    }
+
+    if !email.inline_images.is_empty() {
+        markdown.push_str("\n## Inline images\n\n");
+        for image in &email.inline_images {
+            let name = image.filename.as_deref().unwrap_or(&image.content_id);
+            markdown.push_str(&format!(
+                "- [{}](cid:{}) ({}, {})",
+                single_line(name),
+                image.content_id,
+                image.mime_type,
+                format_size(image.size)
+            ));
+            if let Some(attachment_id) = &image.attachment_id {
+                markdown.push_str(&format!(" — attachment ID `{}`", attachment_id));
+            }
+            markdown.push('\n');
+        }
+    }
+
+    markdown
+}
+
+/// Convert an HTML body to Markdown
+///
+/// Headings, paragraphs, line breaks, list items, rules, bold and italic text,
+/// links and images are kept; other markup is dropped and whitespace is
+/// collapsed as a browser would. Only links with safe URLs are kept, and
+/// images become links to where they are loaded from, except embedded ones,
+/// which are named by their alt text alone.
+pub fn html_to_markdown(html: &str) -> String {
+    let lower = html.to_ascii_lowercase();
+    let mut markdown = String::with_capacity(html.len() / 2);
+    // The URL of each open link, if it is kept
+    let mut links: Vec<Option<String>> = Vec::new();
+    let mut pos = 0;
+    while let Some(found) = html[pos..].find('<') {
+        let tag_start = pos + found;
+        push_text(&mut markdown, &html[pos..tag_start]);
+        let rest = &lower[tag_start + 1..];
+
+        if let Some(comment) = rest.strip_prefix("!--") {
+            pos = comment
+                .find("-->")
+                .map_or(html.len(), |i| tag_start + 4 + i + 3);
+            continue;
+        }
+        if !rest.starts_with(|c: char| c.is_ascii_alphabetic() || matches!(c, '/' | '!' | '?')) {
+            // A `<` in text rather than a tag
+            push_text(&mut markdown, "<");
+            pos = tag_start + 1;
+            continue;
+        }
+        let Some(tag_end) = find_tag_end(html, tag_start) else {
+            pos = html.len();
+            break;
+        };
+        pos = tag_end + 1;
+        if rest.starts_with('!') || rest.starts_with('?') {
+            continue;
+        }
+
+        let tag = parse_tag(&html[tag_start + 1..tag_end]);
+        let name = tag.name.as_str();
+        if SKIPPED_ELEMENTS.contains(&name) {
+            if !tag.closing && !tag.self_closing {
+                let close = format!("</{}", name);
+                pos = lower[pos..]
+                    .find(&close)
+                    .and_then(|i| lower[pos + i..].find('>').map(|j| pos + i + j + 1))
+                    .unwrap_or(html.len());
+            }
+            continue;
+        }
+
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                block_break(&mut markdown);
+                if !tag.closing {
+                    let level = (name.as_bytes()[1] - b'0') as usize;
+                    markdown.push_str(&"#".repeat(level));
+                    markdown.push(' ');
+                }
+            }
+            "br" => line_break(&mut markdown),
+            "li" if !tag.closing => {
+                line_break(&mut markdown);
+                markdown.push_str("- ");
+            }
+            "hr" => {
+                block_break(&mut markdown);
+                markdown.push_str("---");
+                block_break(&mut markdown);
+            }
+            "b" | "strong" => markdown.push_str("**"),
+            "i" | "em" => markdown.push('_'),
+            "td" | "th" if tag.closing => push_text(&mut markdown, " "),
+            "a" if tag.closing => {
+                if let Some(Some(href)) = links.pop() {
+                    markdown.push_str(&format!("]({})", href));
+                }
+            }
+            "a" => {
+                let href = tag
+                    .attribute("href")
+                    .map(|href| decode_numeric_entities(&decode_html_entities(href)))
+                    .map(|href| href.trim().to_string())
+                    .filter(|href| !href.is_empty() && !href.starts_with('#'))
+                    .filter(|href| is_safe_url(href, false));
+                if href.is_some() {
+                    markdown.push('[');
+                }
+                if !tag.self_closing {
+                    links.push(href);
+                }
+            }
+            "img" if !tag.closing => {
+                let alt = tag
+                    .attribute("alt")
+                    .map(|alt| single_line(&decode_html_entities(alt)))
+                    .unwrap_or_default();
+                let label = if alt.trim().is_empty() {
+                    "Image".to_string()
+                } else {
+                    format!("Image: {}", alt.trim())
+                };
+                let src = tag
+                    .attribute("src")
+                    .map(|src| decode_html_entities(src).trim().to_string())
+                    .filter(|src| {
+                        is_remote_url(src) || src.to_ascii_lowercase().starts_with("cid:")
+                    });
+                match src {
+                    Some(src) => markdown.push_str(&format!("[{}]({})", label, src)),
+                    None => markdown.push_str(&format!("[{}]", label)),
+                }
+            }
+            _ if BLOCK_ELEMENTS.contains(&name) => block_break(&mut markdown),
+            _ => {}
+        }
+    }
+    push_text(&mut markdown, &html[pos..]);
+
+    tidy(&markdown)
+}
+
+// Append text with its whitespace collapsed, as a browser would show it
+fn push_text(markdown: &mut String, text: &str) {
+    let text = decode_numeric_entities(&decode_html_entities(text));
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !markdown.is_empty() && !markdown.ends_with([' ', '\n']) {
+                markdown.push(' ');
+            }
+        } else {
+            markdown.push(c);
+        }
+    }
+}
+
+// End the current line
+fn line_break(markdown: &mut String) {
+    trim_trailing_spaces(markdown);
+    if !markdown.is_empty() {
+        markdown.push('\n');
+    }
+}
+
+// End the current block, leaving a blank line after it
+fn block_break(markdown: &mut String) {
+    trim_trailing_spaces(markdown);
+    if markdown.is_empty() || markdown.ends_with("\n\n") {
+        return;
+    }
+    markdown.push_str(if markdown.ends_with('\n') {
+        "\n"
+    } else {
+        "\n\n"
+    });
+}
+
+fn trim_trailing_spaces(markdown: &mut String) {
+    let trimmed = markdown.trim_end_matches(' ').len();
+    markdown.truncate(trimmed);
+}
+
+// Trim each line and allow at most one blank line in a row
+fn tidy(markdown: &str) -> String {
+    let mut tidied = String::with_capacity(markdown.len());
+    let mut blank_lines = 0;
+    for line in markdown.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            blank_lines += 1;
+            if blank_lines > 1 {
+                continue;
+            }
+        } else {
+            blank_lines = 0;
+        }
+        tidied.push_str(line);
+        tidied.push('\n');
+    }
+    tidied.trim().to_string()
+}
+
+// Put text on one line, for a title or list item
+fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// Escape a value for a table cell
+fn table_cell(value: &str) -> String {
+    single_line(value).replace('|', "\\|")
+}
+
+// A size in bytes as people would write it
+fn format_size(bytes: u64) -> String {
+    const KB: f64 = 1024.0;
+    const MB: f64 = 1024.0 * 1024.0;
+    let size = bytes as f64;
+    if size >= MB {
+        format!("{:.1} MB", size / MB)
+    } else if size >= KB {
+        format!("{:.1} KB", size / KB)
+    } else {
+        format!("{} B", bytes)
+    }
+}
@@ -0,0 +1,244 @@
+use crate::calendar_api::CalendarEvent;
+use crate::compose_context::exchange_query;
+use crate::gmail_api::MessageMetadata;
+use crate::people_api::{find_contact_by_email, Contact};
+use crate::utils::{extract_links, parse_email_addresses};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+// Meeting brief
+//
+// Preparing for a meeting means knowing who will be there, what was recently
+// discussed with them and what is on the agenda. The brief puts the event,
+// its attendees with their contact records, the latest threads with any of
+// them, and the documents attached to or linked from the event in one packet.
+
+/// Most recent messages with the attendees checked
+pub const MEETING_BRIEF_MAX_MESSAGES: u32 = 50;
+
+/// Number of recent threads listed
+pub const MEETING_BRIEF_THREAD_LIMIT: usize = 10;
+
+/// Most attendees whose contact records are looked up
+pub const MEETING_BRIEF_MAX_CONTACTS: usize = 20;
+
+/// Headers to fetch, besides the defaults, to tell which attendees a message
+/// involves
+pub const MEETING_BRIEF_HEADERS: &[&str] = &["To", "Cc"];
+
+/// Hosts of links in an event description taken as agenda documents
+pub const AGENDA_DOCUMENT_DOMAINS: &[&str] = &["docs.google.com", "drive.google.com"];
+
+/// Addresses of everyone invited to or organizing an event other than the
+/// user, lowercased, in the order the event lists them
+pub fn other_attendees(event: &CalendarEvent, user_email: &str) -> Vec<String> {
+    let mut addresses: Vec<String> = Vec::new();
+    let emails = event
+        .organizer
+        .iter()
+        .map(|organizer| &organizer.email)
+        .chain(event.attendees.iter().map(|attendee| &attendee.email));
+    for email in emails {
+        let email = email.to_lowercase();
+        if !email.eq_ignore_ascii_case(user_email) && !addresses.contains(&email) {
+            addresses.push(email);
+        }
+    }
+    addresses
+}
+
+/// Gmail query for mail from or to any of the addresses, or `None` if there
+/// are none
+pub fn attendee_query(addresses: &[String]) -> Option<String> {
+    if addresses.is_empty() {
+        return None;
+    }
+    let terms: Vec<String> = addresses
+        .iter()
+        .map(|address| exchange_query(address))
+        .collect();
+    Some(terms.join(" OR "))
+}
+
+/// Someone invited to the meeting
+#[derive(Debug, Clone, Serialize)]
+pub struct BriefAttendee {
+    pub email: String,
+    pub display_name: Option<String>,
+    /// "accepted", "declined", "tentative" or "needsAction"
+    pub response_status: Option<String>,
+    pub optional: bool,
+    pub organizer: bool,
+    pub contact: Option<Contact>,
+}
+
+/// A document to read before the meeting
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AgendaDocument {
+    pub title: Option<String>,
+    pub url: String,
+    /// "attachment" for files attached to the event, "description" for links
+    /// in its description
+    pub source: String,
+}
+
+/// A recent conversation with some of the attendees
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RecentThread {
+    pub thread_id: String,
+    pub subject: Option<String>,
+    /// Messages of the thread among those checked
+    pub message_count: usize,
+    pub last_activity: Option<DateTime<Utc>>,
+    /// The attendees who sent or received mail in the thread
+    pub attendees: Vec<String>,
+    /// Snippet of the latest message
+    pub snippet: Option<String>,
+}
+
+/// Everything needed to prepare for a meeting
+#[derive(Debug, Clone, Serialize)]
+pub struct MeetingBrief {
+    pub event_id: Option<String>,
+    pub summary: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    /// Minutes until the meeting starts, negative once it has
+    pub starts_in_minutes: i64,
+    /// Link to join the meeting's video call, if it has one, from its
+    /// conference data or its description or location
+    pub conference_link: Option<String>,
+    /// Everyone but the user
+    pub attendees: Vec<BriefAttendee>,
+    pub agenda_documents: Vec<AgendaDocument>,
+    /// Most recent first
+    pub recent_threads: Vec<RecentThread>,
+}
+
+impl MeetingBrief {
+    /// Build the brief for an event from the contact search results and the
+    /// metadata of recent messages with its attendees, fetched with
+    /// `MEETING_BRIEF_HEADERS`. Messages involving none of the attendees are
+    /// left out.
+    pub fn new(
+        event: &CalendarEvent,
+        user_email: &str,
+        contacts: &[Contact],
+        messages: &[MessageMetadata],
+        now: DateTime<Utc>,
+    ) -> Self {
+        let addresses = other_attendees(event, user_email);
+        let attendees = addresses
+            .iter()
+            .map(|address| {
+                let attendee = event
+                    .attendees
+                    .iter()
+                    .find(|attendee| attendee.email.eq_ignore_ascii_case(address));
+                let organizer = event
+                    .organizer
+                    .as_ref()
+                    .filter(|organizer| organizer.email.eq_ignore_ascii_case(address));
+                BriefAttendee {
+                    email: address.clone(),
+                    display_name: attendee
+                        .and_then(|attendee| attendee.display_name.clone())
+                        .or_else(|| organizer.and_then(|organizer| organizer.display_name.clone())),
+                    response_status: attendee.and_then(|attendee| attendee.response_status.clone()),
+                    optional: attendee
+                        .and_then(|attendee| attendee.optional)
+                        .unwrap_or(false),
+                    organizer: organizer.is_some(),
+                    contact: find_contact_by_email(contacts, address).cloned(),
+                }
+            })
+            .collect();
+
+        let mut agenda_documents: Vec<AgendaDocument> = event
+            .attachments
+            .iter()
+            .map(|attachment| AgendaDocument {
+                title: attachment.title.clone(),
+                url: attachment.file_url.clone(),
+                source: "attachment".to_string(),
+            })
+            .collect();
+        if let Some(description) = event.description.as_deref() {
+            for link in extract_links(Some(description), Some(description)) {
+                let is_document = link
+                    .domain
+                    .as_deref()
+                    .is_some_and(|domain| AGENDA_DOCUMENT_DOMAINS.contains(&domain));
+                if is_document && !agenda_documents.iter().any(|doc| doc.url == link.url) {
+                    agenda_documents.push(AgendaDocument {
+                        title: link.anchor_text,
+                        url: link.url,
+                        source: "description".to_string(),
+                    });
+                }
+            }
+        }
+
+        let mut recent_threads: Vec<RecentThread> = Vec::new();
+        for message in messages {
+            let involved: Vec<String> = [
+                message.from.as_deref(),
+                message.headers.get("to").map(String::as_str),
+                message.headers.get("cc").map(String::as_str),
+            ]
+            .into_iter()
+            .flatten()
+            .flat_map(parse_email_addresses)
+            .filter(|address| addresses.contains(address))
+            .collect();
+            if involved.is_empty() {
+                continue;
+            }
+            let thread = match recent_threads
+                .iter_mut()
+                .position(|thread| thread.thread_id == message.thread_id)
+            {
+                Some(index) => &mut recent_threads[index],
+                None => {
+                    recent_threads.push(RecentThread {
+                        thread_id: message.thread_id.clone(),
+                        subject: message.subject.clone(),
+                        message_count: 0,
+                        last_activity: None,
+                        attendees: Vec::new(),
+                        snippet: None,
+                    });
+                    recent_threads.last_mut().unwrap()
+                }
+            };
+            thread.message_count += 1;
+            if thread.last_activity.is_none() || message.received_at > thread.last_activity {
+                thread.last_activity = message.received_at;
+                thread.snippet = message.snippet.clone();
+            }
+            for address in involved {
+                if !thread.attendees.contains(&address) {
+                    thread.attendees.push(address);
+                }
+            }
+        }
+        recent_threads.sort_by_key(|thread| std::cmp::Reverse(thread.last_activity));
+        recent_threads.truncate(MEETING_BRIEF_THREAD_LIMIT);
+
+        Self {
+            event_id: event.id.clone(),
+            summary: event.summary.clone(),
+            description: event.description.clone(),
+            location: event.location.clone(),
+            start_time: event.start_time,
+            end_time: event.end_time,
+            starts_in_minutes: (event.start_time - now).num_minutes(),
+            conference_link: event.join_links.first().map(|link| link.url.clone()),
+            attendees,
+            agenda_documents,
+            recent_threads,
+        }
+    }
+}
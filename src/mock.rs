@@ -0,0 +1,688 @@
+use axum::body::Bytes;
+use axum::extract::{DefaultBodyLimit, FromRef, Path as UrlPath, State};
+use axum::http::{header, HeaderMap, Method, StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post, put};
+use axum::{Json, Router};
+use log::{debug, info, warn};
+use serde_json::{json, Value};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::task::JoinHandle;
+use tower_http::compression::CompressionLayer;
+use uuid::Uuid;
+
+// Offline mock mode
+//
+// Mock mode starts a local HTTP server that answers Google API requests from
+// JSON fixtures and points the API clients at it through the base URL
+// environment overrides in `config`. Because the production clients are used
+// unchanged, request building and response parsing behave exactly as they do
+// against Google.
+//
+// Fixture files mirror the decoded request path under the mock server, with
+// `:` replaced by `_` so that the names are portable:
+//
+//   GET  /gmail/v1/users/me/messages        -> gmail/v1/users/me/messages.json
+//   GET  /people/v1/people:searchContacts   -> people/v1/people_searchContacts.json
+//   POST /calendar/v3/calendars/primary/events
+//                                           -> calendar/v3/calendars/primary/events.post.json
+//
+// File downloads (`?alt=media`) are answered from a fixture with `.media` in
+// place of `.json`, whose contents are returned as is:
+//
+//   GET  /drive/v3/files/mock-file-001?alt=media
+//                                           -> drive/v3/files/mock-file-001.media
+//
+// Other query parameters are ignored. Write requests without a fixture echo the
+// request body back with a generated `id`. Like Google, the server compresses
+// responses for clients that accept gzip or deflate.
+//
+// Resumable uploads are supported too: `POST /upload/...?uploadType=resumable`
+// opens a session, chunks are `PUT` to the session URL it returns, and once
+// the whole message has arrived the session answers like any other write,
+// echoing its metadata with a generated `id` and the message size as
+// `sizeEstimate`. Tests can make chunks fail part way through to exercise
+// retries.
+
+/// Path prefix for the mock Gmail API
+pub const MOCK_GMAIL_PREFIX: &str = "/gmail/v1";
+/// Path prefix for the mock Calendar API
+pub const MOCK_CALENDAR_PREFIX: &str = "/calendar/v3";
+/// Path prefix for the mock People API
+pub const MOCK_PEOPLE_PREFIX: &str = "/people/v1";
+/// Path prefix for the mock Tasks API
+pub const MOCK_TASKS_PREFIX: &str = "/tasks/v1";
+/// Path prefix for the mock Drive API
+pub const MOCK_DRIVE_PREFIX: &str = "/drive/v3";
+/// Path of the mock OAuth token endpoint
+pub const MOCK_TOKEN_PATH: &str = "/token";
+/// Path of the mock token info endpoint
+pub const MOCK_TOKEN_INFO_PATH: &str = "/tokeninfo";
+/// Path prefix of mock upload session URLs
+pub const MOCK_UPLOAD_SESSION_PREFIX: &str = "/upload-sessions";
+/// Largest request body the mock server accepts, the most Gmail accepts
+/// for an uploaded message
+pub const MOCK_MAX_UPLOAD_BYTES: usize = 35 * 1024 * 1024;
+
+/// Fixtures compiled into the binary, used when no fixture directory is given
+/// or a file is missing from it
+const BUILTIN_FIXTURES: &[(&str, &str)] = &[
+    (
+        "gmail/v1/users/me/messages.json",
+        include_str!("../fixtures/mock/gmail/v1/users/me/messages.json"),
+    ),
+    (
+        "gmail/v1/users/me/messages/mock-msg-001.json",
+        include_str!("../fixtures/mock/gmail/v1/users/me/messages/mock-msg-001.json"),
+    ),
+    (
+        "gmail/v1/users/me/messages/mock-msg-002.json",
+        include_str!("../fixtures/mock/gmail/v1/users/me/messages/mock-msg-002.json"),
+    ),
+    (
+        "gmail/v1/users/me/messages/mock-msg-002/attachments/mock-att-001.json",
+        include_str!(
+            "../fixtures/mock/gmail/v1/users/me/messages/mock-msg-002/attachments/mock-att-001.json"
+        ),
+    ),
+    (
+        "gmail/v1/users/me/messages/mock-msg-003.json",
+        include_str!("../fixtures/mock/gmail/v1/users/me/messages/mock-msg-003.json"),
+    ),
+    (
+        "gmail/v1/users/me/threads/mock-thread-001.json",
+        include_str!("../fixtures/mock/gmail/v1/users/me/threads/mock-thread-001.json"),
+    ),
+    (
+        "gmail/v1/users/me/threads/mock-thread-002.json",
+        include_str!("../fixtures/mock/gmail/v1/users/me/threads/mock-thread-002.json"),
+    ),
+    (
+        "gmail/v1/users/me/threads/mock-thread-001/modify.post.json",
+        include_str!("../fixtures/mock/gmail/v1/users/me/threads/mock-thread-001/modify.post.json"),
+    ),
+    (
+        "gmail/v1/users/me/threads/mock-thread-001/trash.post.json",
+        include_str!("../fixtures/mock/gmail/v1/users/me/threads/mock-thread-001/trash.post.json"),
+    ),
+    (
+        "gmail/v1/users/me/labels.json",
+        include_str!("../fixtures/mock/gmail/v1/users/me/labels.json"),
+    ),
+    (
+        "gmail/v1/users/me/profile.json",
+        include_str!("../fixtures/mock/gmail/v1/users/me/profile.json"),
+    ),
+    (
+        "gmail/v1/users/me/settings/imap.json",
+        include_str!("../fixtures/mock/gmail/v1/users/me/settings/imap.json"),
+    ),
+    (
+        "gmail/v1/users/me/settings/language.json",
+        include_str!("../fixtures/mock/gmail/v1/users/me/settings/language.json"),
+    ),
+    (
+        "gmail/v1/users/me/settings/pop.json",
+        include_str!("../fixtures/mock/gmail/v1/users/me/settings/pop.json"),
+    ),
+    (
+        "calendar/v3/users/me/calendarList.json",
+        include_str!("../fixtures/mock/calendar/v3/users/me/calendarList.json"),
+    ),
+    (
+        "calendar/v3/users/me/settings.json",
+        include_str!("../fixtures/mock/calendar/v3/users/me/settings.json"),
+    ),
+    (
+        "calendar/v3/calendars/primary/events.json",
+        include_str!("../fixtures/mock/calendar/v3/calendars/primary/events.json"),
+    ),
+    (
+        "people/v1/people/me/connections.json",
+        include_str!("../fixtures/mock/people/v1/people/me/connections.json"),
+    ),
+    (
+        "people/v1/people_searchContacts.json",
+        include_str!("../fixtures/mock/people/v1/people_searchContacts.json"),
+    ),
+    (
+        "people/v1/people/c001.json",
+        include_str!("../fixtures/mock/people/v1/people/c001.json"),
+    ),
+    (
+        "people/v1/people/c002.json",
+        include_str!("../fixtures/mock/people/v1/people/c002.json"),
+    ),
+    (
+        "tasks/v1/users/@me/lists.json",
+        include_str!("../fixtures/mock/tasks/v1/users/@me/lists.json"),
+    ),
+    (
+        "tasks/v1/lists/@default/tasks.json",
+        include_str!("../fixtures/mock/tasks/v1/lists/@default/tasks.json"),
+    ),
+    (
+        "tasks/v1/lists/@default/tasks/mock-task-001.patch.json",
+        include_str!("../fixtures/mock/tasks/v1/lists/@default/tasks/mock-task-001.patch.json"),
+    ),
+    (
+        "drive/v3/about.json",
+        include_str!("../fixtures/mock/drive/v3/about.json"),
+    ),
+    (
+        "drive/v3/files/mock-file-001.json",
+        include_str!("../fixtures/mock/drive/v3/files/mock-file-001.json"),
+    ),
+    (
+        "drive/v3/files/mock-file-001.media",
+        include_str!("../fixtures/mock/drive/v3/files/mock-file-001.media"),
+    ),
+    (
+        "drive/v3/files/mock-file-002.json",
+        include_str!("../fixtures/mock/drive/v3/files/mock-file-002.json"),
+    ),
+    (
+        "drive/v3/files/mock-file-002/permissions.json",
+        include_str!("../fixtures/mock/drive/v3/files/mock-file-002/permissions.json"),
+    ),
+    (
+        "drive/v3/files/mock-file-003.json",
+        include_str!("../fixtures/mock/drive/v3/files/mock-file-003.json"),
+    ),
+    (
+        "drive/v3/files/mock-file-003/permissions.json",
+        include_str!("../fixtures/mock/drive/v3/files/mock-file-003/permissions.json"),
+    ),
+];
+
+/// Source of fixture files for the mock server
+#[derive(Debug, Clone, Default)]
+pub struct FixtureStore {
+    dir: Option<PathBuf>,
+    strict: bool,
+}
+
+impl FixtureStore {
+    /// Create a store that reads from `dir` first, falling back to the
+    /// built-in fixtures
+    pub fn new(dir: Option<PathBuf>) -> Self {
+        Self { dir, strict: false }
+    }
+
+    /// Create a store that only reads from `dir`, for replaying recorded
+    /// interactions without the built-in fixtures leaking into results
+    pub fn strict(dir: PathBuf) -> Self {
+        Self {
+            dir: Some(dir),
+            strict: true,
+        }
+    }
+
+    /// Look up a fixture by its relative file name
+    pub fn get(&self, name: &str) -> Option<String> {
+        if let Some(dir) = &self.dir {
+            let path = dir.join(name);
+            match std::fs::read_to_string(&path) {
+                Ok(contents) => return Some(contents),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => warn!("Failed to read fixture {}: {}", path.display(), e),
+            }
+        }
+
+        if self.strict {
+            return None;
+        }
+
+        BUILTIN_FIXTURES
+            .iter()
+            .find(|(builtin, _)| *builtin == name)
+            .map(|(_, contents)| contents.to_string())
+    }
+}
+
+/// Map a request method and path to the fixture file that answers it
+pub fn fixture_name(method: &Method, path: &str) -> String {
+    let key = decode_path(path).trim_matches('/').replace(':', "_");
+    if method == Method::GET {
+        format!("{}.json", key)
+    } else {
+        format!("{}.{}.json", key, method.as_str().to_lowercase())
+    }
+}
+
+/// Map a file download request path to the fixture holding the file content
+pub fn media_fixture_name(path: &str) -> String {
+    format!(
+        "{}.media",
+        decode_path(path).trim_matches('/').replace(':', "_")
+    )
+}
+
+// Decode percent-encoded characters in a request path, such as the `@` of a
+// calendar ID
+fn decode_path(path: &str) -> String {
+    urlencoding::decode(path)
+        .map(|decoded| decoded.into_owned())
+        .unwrap_or_else(|_| path.to_string())
+}
+
+// Whether a request downloads file content rather than JSON
+fn is_media_request(uri: &Uri) -> bool {
+    uri.query()
+        .is_some_and(|query| query.split('&').any(|param| param == "alt=media"))
+}
+
+/// A resumable upload in progress or finished
+struct UploadSession {
+    metadata: Value,
+    total: Option<usize>,
+    received: Vec<u8>,
+    // Set once the whole message has arrived
+    response: Option<Value>,
+}
+
+/// Resumable upload sessions, and how many chunks to fail
+#[derive(Default)]
+struct Uploads {
+    sessions: Mutex<HashMap<String, UploadSession>>,
+    failures: AtomicUsize,
+}
+
+#[derive(Clone)]
+struct MockState {
+    store: Arc<FixtureStore>,
+    uploads: Arc<Uploads>,
+}
+
+impl FromRef<MockState> for Arc<FixtureStore> {
+    fn from_ref(state: &MockState) -> Self {
+        state.store.clone()
+    }
+}
+
+impl FromRef<MockState> for Arc<Uploads> {
+    fn from_ref(state: &MockState) -> Self {
+        state.uploads.clone()
+    }
+}
+
+/// A running mock server; the server stops when this is dropped
+pub struct MockServer {
+    addr: SocketAddr,
+    handle: JoinHandle<()>,
+    uploads: Arc<Uploads>,
+}
+
+impl MockServer {
+    /// Start a mock server on an ephemeral local port
+    pub async fn start(fixtures_dir: Option<PathBuf>) -> std::io::Result<Self> {
+        if let Some(dir) = &fixtures_dir {
+            check_fixture_dir(dir)?;
+        }
+        Self::start_with_store(FixtureStore::new(fixtures_dir)).await
+    }
+
+    /// Start a server that answers only from fixtures in `dir`, such as those
+    /// captured by the recording proxy; requests without a fixture get a 404
+    pub async fn replay(dir: PathBuf) -> std::io::Result<Self> {
+        check_fixture_dir(&dir)?;
+        Self::start_with_store(FixtureStore::strict(dir)).await
+    }
+
+    async fn start_with_store(store: FixtureStore) -> std::io::Result<Self> {
+        let uploads = Arc::new(Uploads::default());
+        let state = MockState {
+            store: Arc::new(store),
+            uploads: uploads.clone(),
+        };
+        let app = Router::new()
+            .route(MOCK_TOKEN_PATH, post(token_handler))
+            .route(MOCK_TOKEN_INFO_PATH, get(token_info_handler))
+            .route("/upload/*path", post(upload_start_handler))
+            .route(
+                &format!("{}/:id", MOCK_UPLOAD_SESSION_PREFIX),
+                put(upload_chunk_handler),
+            )
+            .fallback(fixture_handler)
+            // Accept uploads as large as Gmail does, rather than axum's 2 MB
+            .layer(DefaultBodyLimit::max(MOCK_MAX_UPLOAD_BYTES))
+            .layer(CompressionLayer::new())
+            .with_state(state);
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let handle = tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, app).await {
+                warn!("Mock server stopped: {}", e);
+            }
+        });
+
+        info!("Mock server listening on http://{}", addr);
+        Ok(Self {
+            addr,
+            handle,
+            uploads,
+        })
+    }
+
+    /// Base URL of the server, e.g. `http://127.0.0.1:4321`
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Make the next `count` upload chunks fail part way through, as on a
+    /// flaky connection: the first half of each is kept and the server
+    /// answers 503
+    pub fn fail_upload_chunks(&self, count: usize) {
+        self.uploads.failures.store(count, Ordering::SeqCst);
+    }
+
+    /// Point the API clients at this server and install placeholder
+    /// credentials so that no real Google account is needed
+    pub fn install(&self) {
+        let url = self.url();
+        std::env::set_var(
+            "GMAIL_API_BASE_URL",
+            format!("{}{}", url, MOCK_GMAIL_PREFIX),
+        );
+        std::env::set_var(
+            "CALENDAR_API_BASE_URL",
+            format!("{}{}", url, MOCK_CALENDAR_PREFIX),
+        );
+        std::env::set_var(
+            "PEOPLE_API_BASE_URL",
+            format!("{}{}", url, MOCK_PEOPLE_PREFIX),
+        );
+        std::env::set_var(
+            "TASKS_API_BASE_URL",
+            format!("{}{}", url, MOCK_TASKS_PREFIX),
+        );
+        std::env::set_var(
+            "DRIVE_API_BASE_URL",
+            format!("{}{}", url, MOCK_DRIVE_PREFIX),
+        );
+        std::env::set_var("OAUTH_TOKEN_URL", format!("{}{}", url, MOCK_TOKEN_PATH));
+        std::env::set_var("TOKEN_INFO_URL", format!("{}{}", url, MOCK_TOKEN_INFO_PATH));
+
+        std::env::set_var("GMAIL_CLIENT_ID", "mock-client-id");
+        std::env::set_var("GMAIL_CLIENT_SECRET", "mock-client-secret");
+        std::env::set_var("GMAIL_REFRESH_TOKEN", "mock-refresh-token");
+        std::env::remove_var("GMAIL_ACCESS_TOKEN");
+
+        // Never mix mock tokens or messages with a real token cache or index
+        std::env::set_var("TOKEN_CACHE_ENABLED", "false");
+        std::env::set_var("LOCAL_INDEX_ENABLED", "false");
+    }
+}
+
+fn check_fixture_dir(dir: &Path) -> std::io::Result<()> {
+    if dir.is_dir() {
+        Ok(())
+    } else {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("Fixture directory {} does not exist", dir.display()),
+        ))
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+async fn token_handler() -> Json<Value> {
+    Json(json!({
+        "access_token": "mock-access-token",
+        "expires_in": 3600,
+        "token_type": "Bearer"
+    }))
+}
+
+// The mock access token lasts an hour and has every scope the tools need
+async fn token_info_handler() -> Json<Value> {
+    let scopes: Vec<&str> = crate::doctor::TOOL_SCOPES
+        .iter()
+        .map(|(_, scope)| *scope)
+        .collect();
+    let expires_at = chrono::Utc::now() + chrono::Duration::seconds(3600);
+    Json(json!({
+        "azp": "mock-client-id",
+        "aud": "mock-client-id",
+        "scope": scopes.join(" "),
+        "exp": expires_at.timestamp().to_string(),
+        "expires_in": "3600",
+        "email": "demo@example.com",
+        "email_verified": "true",
+        "access_type": "offline"
+    }))
+}
+
+async fn fixture_handler(
+    State(store): State<Arc<FixtureStore>>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let media = method == Method::GET && is_media_request(&uri);
+    let name = if media {
+        media_fixture_name(uri.path())
+    } else {
+        fixture_name(&method, uri.path())
+    };
+    debug!("Mock {} {} -> {}", method, uri.path(), name);
+
+    if let Some(contents) = store.get(&name) {
+        // Support conditional requests the way Google does
+        let etag = fixture_etag(&contents);
+        let if_none_match = headers
+            .get(header::IF_NONE_MATCH)
+            .and_then(|v| v.to_str().ok());
+        if method == Method::GET && if_none_match == Some(etag.as_str()) {
+            return (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response();
+        }
+        let content_type = if media {
+            "application/octet-stream"
+        } else {
+            "application/json"
+        };
+        return (
+            [
+                (header::CONTENT_TYPE, content_type.to_string()),
+                (header::ETAG, etag),
+            ],
+            contents,
+        )
+            .into_response();
+    }
+
+    if method != Method::GET {
+        return Json(echo_with_id(&body)).into_response();
+    }
+
+    (
+        StatusCode::NOT_FOUND,
+        Json(json!({
+            "error": {
+                "code": 404,
+                "message": format!("No mock fixture for {} (expected {})", uri.path(), name),
+                "status": "NOT_FOUND"
+            }
+        })),
+    )
+        .into_response()
+}
+
+// Open a resumable upload session, answering with its URL
+async fn upload_start_handler(
+    State(uploads): State<Arc<Uploads>>,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let resumable = uri.query().is_some_and(|query| {
+        query
+            .split('&')
+            .any(|param| param == "uploadType=resumable")
+    });
+    if !resumable {
+        return upload_error(
+            StatusCode::BAD_REQUEST,
+            "Only resumable uploads are supported",
+        );
+    }
+
+    let id = Uuid::new_v4().simple().to_string();
+    let total = headers
+        .get("X-Upload-Content-Length")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<usize>().ok());
+    let metadata = serde_json::from_slice::<Value>(&body).unwrap_or_else(|_| json!({}));
+    debug!(
+        "Mock upload {} started for {} ({:?} bytes)",
+        id,
+        uri.path(),
+        total
+    );
+    uploads
+        .sessions
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(
+            id.clone(),
+            UploadSession {
+                metadata,
+                total,
+                received: Vec::new(),
+                response: None,
+            },
+        );
+
+    let host = headers
+        .get(header::HOST)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("127.0.0.1");
+    let location = format!("http://{}{}/{}", host, MOCK_UPLOAD_SESSION_PREFIX, id);
+    (StatusCode::OK, [(header::LOCATION, location)]).into_response()
+}
+
+// Take a chunk of an upload, or with a `*` range report how much has arrived
+async fn upload_chunk_handler(
+    State(uploads): State<Arc<Uploads>>,
+    UrlPath(id): UrlPath<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let mut sessions = uploads.sessions.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(session) = sessions.get_mut(&id) else {
+        return upload_error(StatusCode::NOT_FOUND, "No such upload session");
+    };
+    if let Some(response) = &session.response {
+        return Json(response.clone()).into_response();
+    }
+
+    let range = headers
+        .get(header::CONTENT_RANGE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    let Some((span, total)) = range
+        .strip_prefix("bytes ")
+        .and_then(|range| range.split_once('/'))
+    else {
+        return upload_error(StatusCode::BAD_REQUEST, "Missing Content-Range");
+    };
+    if let Ok(total) = total.parse::<usize>() {
+        session.total = Some(total);
+    }
+
+    if span != "*" {
+        let start = span
+            .split_once('-')
+            .and_then(|(start, _)| start.parse::<usize>().ok());
+        if start != Some(session.received.len()) {
+            return upload_error(
+                StatusCode::BAD_REQUEST,
+                &format!(
+                    "Chunk {} does not start at byte {}",
+                    span,
+                    session.received.len()
+                ),
+            );
+        }
+        let fail = uploads
+            .failures
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .is_ok();
+        if fail {
+            session.received.extend_from_slice(&body[..body.len() / 2]);
+            return upload_error(StatusCode::SERVICE_UNAVAILABLE, "Simulated upload failure");
+        }
+        session.received.extend_from_slice(&body);
+    }
+
+    if Some(session.received.len()) == session.total {
+        let mut response = echo_with_id(&serde_json::to_vec(&session.metadata).unwrap_or_default());
+        response["sizeEstimate"] = json!(session.received.len());
+        session.response = Some(response.clone());
+        return Json(response).into_response();
+    }
+
+    // Google answers 308 with the bytes received so far while incomplete
+    let mut response = StatusCode::PERMANENT_REDIRECT.into_response();
+    if !session.received.is_empty() {
+        let received = format!("bytes=0-{}", session.received.len() - 1);
+        if let Ok(value) = received.parse() {
+            response.headers_mut().insert(header::RANGE, value);
+        }
+    }
+    response
+}
+
+fn upload_error(status: StatusCode, message: &str) -> Response {
+    (
+        status,
+        Json(json!({
+            "error": {
+                "code": status.as_u16(),
+                "message": message,
+                "status": status.canonical_reason().unwrap_or("ERROR")
+            }
+        })),
+    )
+        .into_response()
+}
+
+// A fixture's ETag changes whenever its contents do
+fn fixture_etag(contents: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    contents.hash(&mut hasher);
+    format!("\"{:016x}\"", hasher.finish())
+}
+
+// Echo a write request's JSON body back with a generated resource id
+fn echo_with_id(body: &[u8]) -> Value {
+    let mut value = serde_json::from_slice::<Value>(body).unwrap_or_else(|_| json!({}));
+    if !value.is_object() {
+        value = json!({});
+    }
+    if value.get("id").is_none() {
+        value["id"] = json!(format!("mock-{}", Uuid::new_v4().simple()));
+    }
+    value
+}
+
+/// Describe where fixtures are loaded from, for display
+pub fn describe_fixtures(dir: Option<&Path>) -> String {
+    match dir {
+        Some(dir) => format!("{} (with built-in fallbacks)", dir.display()),
+        None => "built-in fixtures".to_string(),
+    }
+}
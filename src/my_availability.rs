@@ -0,0 +1,316 @@
+use crate::calendar_api::{BusyPeriod, CalendarBusy, CalendarInfo};
+use crate::holidays::is_special_calendar;
+use crate::time_blocking::{align_up, free_slots};
+use crate::utils::{local_to_utc, parse_date_expression};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveTime, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::Serialize;
+
+// Merged availability
+//
+// The user's free time is what is left of their working hours once the busy
+// time of every calendar of theirs is taken out: the primary calendar, work
+// calendars and calendars shared with them, read in one free/busy query.
+// Holiday and birthday calendars are left out, as their events don't take
+// up time. Free windows are given both as times and as text to paste into an
+// email, one line per day, in the user's time zone.
+
+/// Period covered when no time range is given
+pub const DEFAULT_AVAILABILITY_RANGE: &str = "7d";
+
+/// Longest period that can be covered
+pub const MAX_AVAILABILITY_DAYS: i64 = 31;
+
+/// Most calendars one free/busy query can read
+pub const MAX_AVAILABILITY_CALENDARS: usize = 50;
+
+/// Free windows shorter than this are left out
+pub const MIN_FREE_MINUTES: i64 = 30;
+
+/// Parse the period to cover: a number of days or weeks from today such as
+/// "3d" or "2w", a day such as "tomorrow" or "2026-10-20", or two of them
+/// joined by "to" or "until", such as "monday to friday". A day given
+/// without a time is covered to its end. Time already gone is left out.
+pub fn parse_range(
+    input: &str,
+    now: DateTime<Utc>,
+    time_zone: Tz,
+) -> Result<(DateTime<Utc>, DateTime<Utc>), String> {
+    let input = input.trim().to_lowercase();
+    let today = now.with_timezone(&time_zone).date_naive();
+    let day_start =
+        |date: NaiveDate| local_to_utc(date.and_time(NaiveTime::MIN), time_zone, &date.to_string());
+    // A date without a time stands for the whole day
+    let is_midnight = |time: DateTime<Utc>| time.with_timezone(&time_zone).time() == NaiveTime::MIN;
+    let day_end = |time: DateTime<Utc>| {
+        let date = time.with_timezone(&time_zone).date_naive();
+        day_start(date + Duration::days(1))
+    };
+
+    let split = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    let (amount, unit) = input.split_at(split);
+    let too_long = || {
+        format!(
+            "Invalid time range '{}': cover at most {} days",
+            input, MAX_AVAILABILITY_DAYS
+        )
+    };
+    let unit_days = match unit.trim() {
+        "d" | "day" | "days" if !amount.is_empty() => Some(1),
+        "w" | "week" | "weeks" if !amount.is_empty() => Some(7),
+        _ => None,
+    };
+
+    let (start, end) = if let Some(unit_days) = unit_days {
+        // Checked before any date arithmetic, which huge amounts overflow
+        let days = amount
+            .parse::<i64>()
+            .ok()
+            .and_then(|amount| amount.checked_mul(unit_days))
+            .ok_or_else(too_long)?;
+        if days < 1 {
+            return Err(format!(
+                "Invalid time range '{}': cover at least a day",
+                input
+            ));
+        }
+        if days > MAX_AVAILABILITY_DAYS {
+            return Err(too_long());
+        }
+        (now, day_start(today + Duration::days(days))?)
+    } else {
+        let (from, to) = input
+            .split_once(" until ")
+            .or_else(|| input.split_once(" to "))
+            .unwrap_or((input.as_str(), ""));
+        let start = parse_date_expression(from, now, time_zone)?;
+        // The end is read from the start, so that "monday to friday" is the
+        // friday after that monday
+        let end = if to.is_empty() {
+            day_end(start)?
+        } else {
+            let end = parse_date_expression(to, start, time_zone)?;
+            if is_midnight(end) {
+                day_end(end)?
+            } else {
+                end
+            }
+        };
+        (start, end)
+    };
+
+    let start = start.max(now);
+    if end <= start {
+        return Err(format!(
+            "Invalid time range '{}': it has to end in the future",
+            input
+        ));
+    }
+    if end - start > Duration::days(MAX_AVAILABILITY_DAYS) {
+        return Err(too_long());
+    }
+    Ok((start, end))
+}
+
+/// The IDs of the calendars to read: those asked for by ID or name, with
+/// "primary" for the primary calendar, or else every calendar in the list
+/// other than holiday and birthday calendars, the primary one first
+pub fn select_calendars(list: &[CalendarInfo], asked: &[String]) -> Result<Vec<String>, String> {
+    let mut selected: Vec<String> = Vec::new();
+    let mut add = |id: &str| {
+        if !selected.iter().any(|added| added == id) {
+            selected.push(id.to_string());
+        }
+    };
+
+    if asked.is_empty() {
+        let mut calendars: Vec<&CalendarInfo> = list
+            .iter()
+            .filter(|calendar| !is_special_calendar(&calendar.id))
+            .collect();
+        calendars.sort_by_key(|calendar| calendar.primary != Some(true));
+        calendars.into_iter().for_each(|calendar| add(&calendar.id));
+    } else {
+        for name in asked {
+            let name = name.trim();
+            let found = list.iter().find(|calendar| {
+                if name.eq_ignore_ascii_case("primary") {
+                    calendar.primary == Some(true)
+                } else {
+                    calendar.id == name || calendar.summary.eq_ignore_ascii_case(name)
+                }
+            });
+            match found {
+                Some(calendar) => add(&calendar.id),
+                None if name.eq_ignore_ascii_case("primary") => add("primary"),
+                None => return Err(format!("No calendar '{}' in your calendar list", name)),
+            }
+        }
+    }
+
+    if selected.len() > MAX_AVAILABILITY_CALENDARS {
+        return Err(format!(
+            "Too many calendars: free/busy can read at most {} at once",
+            MAX_AVAILABILITY_CALENDARS
+        ));
+    }
+    Ok(selected)
+}
+
+/// Busy periods in time order, with overlapping and touching ones joined
+pub fn merge_busy(busy: &[BusyPeriod]) -> Vec<BusyPeriod> {
+    let mut busy = busy.to_vec();
+    busy.sort_by_key(|period| period.start);
+    let mut merged: Vec<BusyPeriod> = Vec::new();
+    for period in busy {
+        match merged.last_mut() {
+            Some(last) if period.start <= last.end => last.end = last.end.max(period.end),
+            _ => merged.push(period),
+        }
+    }
+    merged
+}
+
+/// Working hours on each day of a period, weekends left out unless
+/// included, cut to the period
+pub fn working_windows(
+    range: (DateTime<Utc>, DateTime<Utc>),
+    time_zone: Tz,
+    hours: (NaiveTime, NaiveTime),
+    include_weekends: bool,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let first = range.0.with_timezone(&time_zone).date_naive();
+    let last = range.1.with_timezone(&time_zone).date_naive();
+    first
+        .iter_days()
+        .take_while(|date| *date <= last)
+        .filter(|date| include_weekends || !matches!(date.weekday(), Weekday::Sat | Weekday::Sun))
+        .filter_map(|date| {
+            let start = local_to_utc(date.and_time(hours.0), time_zone, "").ok()?;
+            let end = local_to_utc(date.and_time(hours.1), time_zone, "").ok()?;
+            let (start, end) = (start.max(range.0), end.min(range.1));
+            (start < end).then_some((start, end))
+        })
+        .collect()
+}
+
+/// A free window, in the user's time zone
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FreeWindow {
+    pub start: DateTime<FixedOffset>,
+    pub end: DateTime<FixedOffset>,
+    pub minutes: i64,
+}
+
+/// A calendar whose busy time was read, or why it could not be
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AvailabilityCalendar {
+    pub id: String,
+    pub summary: Option<String>,
+    pub busy_periods: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The user's free time across their calendars
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MyAvailability {
+    pub time_zone: String,
+    pub time_min: DateTime<Utc>,
+    pub time_max: DateTime<Utc>,
+    pub calendars: Vec<AvailabilityCalendar>,
+    /// Busy time across the calendars, merged
+    pub busy: Vec<BusyPeriod>,
+    /// Free windows in working hours of at least `MIN_FREE_MINUTES`
+    pub free: Vec<FreeWindow>,
+    pub total_free_minutes: i64,
+    /// The free windows as text to paste into an email
+    pub text: String,
+}
+
+impl MyAvailability {
+    pub fn new(
+        range: (DateTime<Utc>, DateTime<Utc>),
+        time_zone: Tz,
+        hours: (NaiveTime, NaiveTime),
+        include_weekends: bool,
+        list: &[CalendarInfo],
+        free_busy: &[CalendarBusy],
+    ) -> Self {
+        let calendars = free_busy
+            .iter()
+            .map(|calendar| AvailabilityCalendar {
+                id: calendar.calendar_id.clone(),
+                summary: list
+                    .iter()
+                    .find(|info| info.id == calendar.calendar_id)
+                    .map(|info| info.summary.clone()),
+                busy_periods: calendar.busy.len(),
+                error: calendar.error.clone(),
+            })
+            .collect();
+        let all_busy: Vec<BusyPeriod> = free_busy
+            .iter()
+            .flat_map(|calendar| calendar.busy.iter().cloned())
+            .collect();
+        let busy = merge_busy(&all_busy);
+
+        let offset = |time: DateTime<Utc>| time.with_timezone(&time_zone).fixed_offset();
+        let free: Vec<FreeWindow> = working_windows(range, time_zone, hours, include_weekends)
+            .into_iter()
+            .flat_map(|window| free_slots(window, &busy))
+            .map(|(start, end)| (align_up(start), end))
+            .filter(|(start, end)| *end - *start >= Duration::minutes(MIN_FREE_MINUTES))
+            .map(|(start, end)| FreeWindow {
+                start: offset(start),
+                end: offset(end),
+                minutes: (end - start).num_minutes(),
+            })
+            .collect();
+        let total_free_minutes = free.iter().map(|window| window.minutes).sum();
+        let text = availability_text(&free, time_zone);
+
+        Self {
+            time_zone: time_zone.name().to_string(),
+            time_min: range.0,
+            time_max: range.1,
+            calendars,
+            busy,
+            free,
+            total_free_minutes,
+            text,
+        }
+    }
+}
+
+/// Free windows as lines of text, one per day, such as
+/// "Thu 15 Oct: 09:00–10:30, 14:00–17:00"
+pub fn availability_text(free: &[FreeWindow], time_zone: Tz) -> String {
+    if free.is_empty() {
+        return "I don't have any free time in that period.".to_string();
+    }
+    let mut lines: Vec<(NaiveDate, Vec<String>)> = Vec::new();
+    for window in free {
+        let date = window.start.date_naive();
+        let times = format!(
+            "{}–{}",
+            window.start.format("%H:%M"),
+            window.end.format("%H:%M")
+        );
+        match lines.last_mut() {
+            Some((last, times_on_day)) if *last == date => times_on_day.push(times),
+            _ => lines.push((date, vec![times])),
+        }
+    }
+    let mut text = format!("I'm free at these times ({}):\n", time_zone.name());
+    for (date, times) in lines {
+        text.push_str(&format!(
+            "- {}: {}\n",
+            date.format("%a %-d %b"),
+            times.join(", ")
+        ));
+    }
+    text.trim_end().to_string()
+}
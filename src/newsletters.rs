@@ -0,0 +1,188 @@
+use crate::gmail_api::MessageMetadata;
+use crate::sender_report::split_sender;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+
+// Newsletter digest
+//
+// Mailing lists and bulk senders mark their mail with a List-Id header or
+// `Precedence: bulk`. The digest groups such mail by list, or by sender when
+// there is no List-Id, with the latest subjects and snippets of each, and the
+// ways to unsubscribe given in the List-Unsubscribe header (RFC 2369), including
+// whether the sender supports one-click unsubscribe (RFC 8058).
+
+/// Headers to fetch, besides the defaults, to detect and unsubscribe from
+/// bulk mail
+pub const BULK_MAIL_HEADERS: &[&str] = &[
+    "List-Id",
+    "Precedence",
+    "List-Unsubscribe",
+    "List-Unsubscribe-Post",
+];
+
+/// Number of messages listed for each sender in a digest
+pub const DIGEST_MESSAGES_PER_SENDER: usize = 5;
+
+/// Ways to unsubscribe from a list
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct UnsubscribeOptions {
+    /// Address to email to unsubscribe, as a `mailto:` link
+    pub mailto: Option<String>,
+    /// Web page to unsubscribe at
+    pub url: Option<String>,
+    /// Whether POSTing to `url` unsubscribes without any further steps
+    pub one_click: bool,
+}
+
+impl UnsubscribeOptions {
+    /// Read the List-Unsubscribe and List-Unsubscribe-Post headers of a
+    /// message, if it has any way to unsubscribe
+    pub fn from_message(message: &MessageMetadata) -> Option<Self> {
+        let header = message.headers.get("list-unsubscribe")?;
+        let mut options = Self::default();
+        for link in header.split(',') {
+            let link = link.trim().trim_start_matches('<').trim_end_matches('>');
+            let lower = link.to_lowercase();
+            if lower.starts_with("mailto:") {
+                options.mailto.get_or_insert_with(|| link.to_string());
+            } else if lower.starts_with("https://") || lower.starts_with("http://") {
+                options.url.get_or_insert_with(|| link.to_string());
+            }
+        }
+        options.one_click = options.url.is_some()
+            && message
+                .headers
+                .get("list-unsubscribe-post")
+                .is_some_and(|post| post.contains("List-Unsubscribe=One-Click"));
+
+        (options.mailto.is_some() || options.url.is_some()).then_some(options)
+    }
+}
+
+/// Whether a message was sent to a mailing list or in bulk
+pub fn is_bulk_mail(message: &MessageMetadata) -> bool {
+    message.headers.contains_key("list-id")
+        || message.headers.get("precedence").is_some_and(|precedence| {
+            matches!(
+                precedence.trim().to_lowercase().as_str(),
+                "bulk" | "list" | "junk"
+            )
+        })
+}
+
+// The identifier of a List-Id header, e.g. `news.example.com` in
+// `Example News <news.example.com>`
+fn list_id(message: &MessageMetadata) -> Option<String> {
+    let header = message.headers.get("list-id")?;
+    let id = match header.rsplit_once('<') {
+        Some((_, id)) => id.trim_end_matches('>'),
+        None => header,
+    };
+    Some(id.trim().to_lowercase()).filter(|id| !id.is_empty())
+}
+
+/// A message in a newsletter digest
+#[derive(Debug, Clone, Serialize)]
+pub struct DigestMessage {
+    pub id: String,
+    pub subject: Option<String>,
+    pub snippet: Option<String>,
+    pub received_at: Option<DateTime<Utc>>,
+    pub unread: bool,
+}
+
+/// Bulk mail from one list or sender
+#[derive(Debug, Clone, Serialize)]
+pub struct NewsletterSender {
+    /// Lowercased address of the most recent message
+    pub address: String,
+    pub name: Option<String>,
+    pub list_id: Option<String>,
+    pub count: usize,
+    pub unread: usize,
+    pub last_received: Option<DateTime<Utc>>,
+    /// How to unsubscribe, from the most recent message that says
+    pub unsubscribe: Option<UnsubscribeOptions>,
+    /// The most recent messages, newest first
+    pub messages: Vec<DigestMessage>,
+}
+
+/// Bulk mail received over a period, grouped by list or sender
+#[derive(Debug, Clone, Serialize)]
+pub struct NewsletterDigest {
+    /// Gmail query the messages were listed with
+    pub query: String,
+    pub messages_scanned: usize,
+    /// Whether the scan stopped at its message limit, leaving out older mail
+    pub truncated: bool,
+    pub bulk_messages: usize,
+    /// Lists and senders with the most messages first
+    pub senders: Vec<NewsletterSender>,
+}
+
+impl NewsletterDigest {
+    /// Pick out the bulk mail among `messages` and group it by list, or by
+    /// sender for bulk mail without a List-Id
+    pub fn from_messages(query: &str, messages: &[MessageMetadata], truncated: bool) -> Self {
+        let mut bulk: Vec<&MessageMetadata> = messages
+            .iter()
+            .filter(|message| is_bulk_mail(message))
+            .collect();
+        // Newest first, so each group starts with its most recent message
+        bulk.sort_by_key(|message| std::cmp::Reverse(message.received_at));
+
+        let mut groups: HashMap<String, NewsletterSender> = HashMap::new();
+        for message in &bulk {
+            let Some((address, name)) = message.from.as_deref().and_then(split_sender) else {
+                continue;
+            };
+            let list_id = list_id(message);
+            let key = list_id.clone().unwrap_or_else(|| address.clone());
+            let sender = groups.entry(key).or_insert_with(|| NewsletterSender {
+                address,
+                name,
+                list_id,
+                count: 0,
+                unread: 0,
+                last_received: message.received_at,
+                unsubscribe: None,
+                messages: Vec::new(),
+            });
+
+            let unread = message.label_ids.iter().any(|label| label == "UNREAD");
+            sender.count += 1;
+            if unread {
+                sender.unread += 1;
+            }
+            if sender.unsubscribe.is_none() {
+                sender.unsubscribe = UnsubscribeOptions::from_message(message);
+            }
+            if sender.messages.len() < DIGEST_MESSAGES_PER_SENDER {
+                sender.messages.push(DigestMessage {
+                    id: message.id.clone(),
+                    subject: message.subject.clone(),
+                    snippet: message.snippet.clone(),
+                    received_at: message.received_at,
+                    unread,
+                });
+            }
+        }
+
+        let mut senders: Vec<NewsletterSender> = groups.into_values().collect();
+        senders.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then(b.last_received.cmp(&a.last_received))
+                .then(a.address.cmp(&b.address))
+        });
+
+        Self {
+            query: query.to_string(),
+            messages_scanned: messages.len(),
+            truncated,
+            bulk_messages: bulk.len(),
+            senders,
+        }
+    }
+}
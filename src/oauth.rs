@@ -18,12 +18,15 @@ use std::sync::Arc;
 use tokio::sync::Mutex;
 use url::Url;
 
-// OAuth scopes needed for Gmail, Calendar, and People API access
-const GMAIL_SCOPE: &str = "https://mail.google.com/";
-const CALENDAR_READ_SCOPE: &str = "https://www.googleapis.com/auth/calendar.readonly";
-const CALENDAR_WRITE_SCOPE: &str = "https://www.googleapis.com/auth/calendar";
-const CONTACTS_READ_SCOPE: &str = "https://www.googleapis.com/auth/contacts.readonly";
-const DIRECTORY_READ_SCOPE: &str = "https://www.googleapis.com/auth/directory.readonly";
+// OAuth scopes needed for Gmail, Calendar, People, Tasks and Drive API access
+pub const GMAIL_SCOPE: &str = "https://mail.google.com/";
+pub const CALENDAR_READ_SCOPE: &str = "https://www.googleapis.com/auth/calendar.readonly";
+pub const CALENDAR_WRITE_SCOPE: &str = "https://www.googleapis.com/auth/calendar";
+pub const CONTACTS_READ_SCOPE: &str = "https://www.googleapis.com/auth/contacts.readonly";
+pub const CONTACTS_WRITE_SCOPE: &str = "https://www.googleapis.com/auth/contacts";
+pub const DIRECTORY_READ_SCOPE: &str = "https://www.googleapis.com/auth/directory.readonly";
+pub const TASKS_SCOPE: &str = "https://www.googleapis.com/auth/tasks";
+pub const DRIVE_READ_SCOPE: &str = "https://www.googleapis.com/auth/drive.readonly";
 const OAUTH_AUTH_URL: &str = "https://accounts.google.com/o/oauth2/auth";
 const OAUTH_TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
 
@@ -71,8 +74,38 @@ struct TokenResponse {
     scope: Option<String>,
 }
 
+/// The scopes the OAuth flow requests: those for Gmail, Calendar, People,
+/// Tasks and Drive, then any `extra_scopes` not among them
+pub fn requested_scopes(extra_scopes: &[String]) -> Vec<String> {
+    let mut scopes: Vec<String> = [
+        GMAIL_SCOPE,
+        CALENDAR_READ_SCOPE,
+        CALENDAR_WRITE_SCOPE,
+        CONTACTS_READ_SCOPE,
+        CONTACTS_WRITE_SCOPE,
+        DIRECTORY_READ_SCOPE,
+        TASKS_SCOPE,
+        DRIVE_READ_SCOPE,
+    ]
+    .iter()
+    .map(|scope| scope.to_string())
+    .collect();
+    for scope in extra_scopes {
+        let scope = scope.trim();
+        if !scope.is_empty() && !scopes.iter().any(|s| s == scope) {
+            scopes.push(scope.to_string());
+        }
+    }
+    scopes
+}
+
 // Run the OAuth flow to get a new refresh token
 pub async fn run_oauth_flow() -> Result<(), String> {
+    run_oauth_flow_with_scopes(&[]).await
+}
+
+// Run the OAuth flow, also requesting `extra_scopes`
+pub async fn run_oauth_flow_with_scopes(extra_scopes: &[String]) -> Result<(), String> {
     // Attempt to load existing credentials
     let _ = dotenv();
 
@@ -114,19 +147,9 @@ pub async fn run_oauth_flow() -> Result<(), String> {
         complete: false,
     }));
 
-    // Build the authorization URL with Gmail, Calendar, and People API scopes
-    let auth_url = build_auth_url(
-        &client_id,
-        &redirect_uri,
-        &state_token,
-        &[
-            GMAIL_SCOPE.to_string(),
-            CALENDAR_READ_SCOPE.to_string(),
-            CALENDAR_WRITE_SCOPE.to_string(),
-            CONTACTS_READ_SCOPE.to_string(),
-            DIRECTORY_READ_SCOPE.to_string(),
-        ],
-    )?;
+    // Build the authorization URL with Gmail, Calendar, People, Tasks and Drive API scopes
+    let scopes = requested_scopes(extra_scopes);
+    let auth_url = build_auth_url(&client_id, &redirect_uri, &state_token, &scopes)?;
 
     // Start the local web server to handle the OAuth callback
     let server_handle = start_oauth_server(port, host.clone(), oauth_state.clone());
@@ -149,6 +172,22 @@ pub async fn run_oauth_flow() -> Result<(), String> {
     let tokens =
         exchange_code_for_tokens(&client_id, &client_secret, &auth_code, &redirect_uri).await?;
 
+    // Permissions left unticked on the consent screen are not granted
+    if let Some(granted) = &tokens.scope {
+        let granted: Vec<&str> = granted.split_whitespace().collect();
+        let missing: Vec<&str> = scopes
+            .iter()
+            .map(String::as_str)
+            .filter(|scope| !granted.contains(scope))
+            .collect();
+        if !missing.is_empty() {
+            println!(
+                "⚠️  These scopes were not granted: {}. Tools that need them will fail until you run auth again and allow every permission.",
+                missing.join(", ")
+            );
+        }
+    }
+
     // Update the .env file with the new tokens
     println!("Updating credentials in .env file...");
     update_env_file(
@@ -542,7 +581,7 @@ pub async fn test_credentials() -> Result<String, String> {
     let config = Config::from_env().map_err(|e| format!("Failed to load credentials: {}", e))?;
 
     // Create a Gmail service client
-    let mut service = crate::gmail_api::GmailService::new(&config)
+    let service = crate::gmail_api::GmailService::new(&config)
         .map_err(|e| format!("Failed to create Gmail service: {}", e))?;
 
     // Try to check the connection
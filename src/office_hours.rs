@@ -0,0 +1,256 @@
+use crate::calendar_api::CalendarEvent;
+use crate::utils::local_to_utc;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::Serialize;
+
+// Office hours
+//
+// Office hours are a weekly block of time when others are welcome to drop
+// in. They are created as a recurring event with a weekly RRULE in the
+// user's time zone, so the block stays at the same local time across
+// daylight saving changes, and marked transparent so the time still shows
+// as free to anyone booking a meeting. Recurring events whose title mentions
+// office hours are listed as such, including those created in Calendar.
+
+/// Title given to office hours when none is chosen
+pub const DEFAULT_OFFICE_HOURS_SUMMARY: &str = "Office hours";
+
+/// Most recurring events checked when listing office hours
+pub const OFFICE_HOURS_MAX_EVENTS: u32 = 250;
+
+/// Days of the week, from Monday
+const WEEK: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+/// Parse days of the week such as "Tuesday, Thursday", "mon wed fri" or
+/// "weekdays", in week order
+pub fn parse_weekdays(input: &str) -> Result<Vec<Weekday>, String> {
+    let mut days: Vec<Weekday> = Vec::new();
+    let words = input
+        .split(|c: char| c.is_whitespace() || c == ',' || c == '/')
+        .filter(|word| !word.is_empty() && !word.eq_ignore_ascii_case("and"));
+    for word in words {
+        if word.eq_ignore_ascii_case("weekdays") {
+            days.extend(&WEEK[..5]);
+        } else {
+            days.push(word.parse::<Weekday>().map_err(|_| {
+                format!(
+                    "Invalid day '{}': expected days of the week such as 'Tuesday, Thursday' or 'weekdays'",
+                    word
+                )
+            })?);
+        }
+    }
+    if days.is_empty() {
+        return Err("No days of the week given".to_string());
+    }
+    days.sort_by_key(|day| day.num_days_from_monday());
+    days.dedup();
+    Ok(days)
+}
+
+/// Two-letter day code used in RRULE BYDAY lists
+fn rrule_day(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+/// RRULE repeating every week on `days`, up to and including `until` if set
+pub fn weekly_rule(days: &[Weekday], until: Option<DateTime<Utc>>) -> String {
+    let by_day: Vec<&str> = days.iter().map(|day| rrule_day(*day)).collect();
+    let mut rule = format!("RRULE:FREQ=WEEKLY;BYDAY={}", by_day.join(","));
+    if let Some(until) = until {
+        rule.push_str(&format!(";UNTIL={}", until.format("%Y%m%dT%H%M%SZ")));
+    }
+    rule
+}
+
+/// The days and end of a weekly RRULE, or `None` for other rules
+pub fn parse_weekly_rule(rule: &str) -> Option<(Vec<Weekday>, Option<String>)> {
+    let parts = rule.strip_prefix("RRULE:")?;
+    let mut weekly = false;
+    let mut days = Vec::new();
+    let mut until = None;
+    for part in parts.split(';') {
+        match part.split_once('=')? {
+            ("FREQ", frequency) => weekly = frequency == "WEEKLY",
+            ("BYDAY", by_day) => {
+                for code in by_day.split(',') {
+                    days.push(WEEK.into_iter().find(|day| rrule_day(*day) == code)?);
+                }
+            }
+            ("UNTIL", end) => until = Some(end.to_string()),
+            _ => {}
+        }
+    }
+    weekly.then_some((days, until))
+}
+
+/// A weekly block of office hours to create
+#[derive(Debug, Clone)]
+pub struct OfficeHoursRequest {
+    pub summary: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub days: Vec<Weekday>,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    /// First day the office hours may take place
+    pub starting: NaiveDate,
+    /// Last day the office hours may take place, if they end
+    pub until: Option<NaiveDate>,
+    pub time_zone: Tz,
+}
+
+impl OfficeHoursRequest {
+    /// The recurring event for the office hours, starting on the first of
+    /// the days on or after `starting`
+    pub fn to_event(&self) -> Result<CalendarEvent, String> {
+        if self.end <= self.start {
+            return Err("Office hours must end after they start".to_string());
+        }
+        let first_day = (0..7)
+            .map(|offset| self.starting + Duration::days(offset))
+            .find(|day| self.days.contains(&day.weekday()))
+            .ok_or_else(|| "No days of the week given".to_string())?;
+        if self.until.is_some_and(|until| until < first_day) {
+            return Err(format!(
+                "Office hours would end before their first day, {}",
+                first_day
+            ));
+        }
+
+        let start_time = local_to_utc(
+            first_day.and_time(self.start),
+            self.time_zone,
+            &self.start.to_string(),
+        )?;
+        let end_time = local_to_utc(
+            first_day.and_time(self.end),
+            self.time_zone,
+            &self.end.to_string(),
+        )?;
+        let until = match self.until {
+            Some(until) => Some(local_to_utc(
+                until.and_time(self.end),
+                self.time_zone,
+                &until.to_string(),
+            )?),
+            None => None,
+        };
+
+        Ok(CalendarEvent {
+            summary: self.summary.clone(),
+            description: self.description.clone(),
+            location: self.location.clone(),
+            start_time,
+            end_time,
+            recurrence: vec![weekly_rule(&self.days, until)],
+            transparency: Some("transparent".to_string()),
+            time_zone: Some(self.time_zone.name().to_string()),
+            ..Default::default()
+        })
+    }
+}
+
+/// Whether an event is office hours: it repeats weekly and its title
+/// mentions office hours
+pub fn is_office_hours(event: &CalendarEvent) -> bool {
+    event.summary.to_lowercase().contains("office hours")
+        && event
+            .recurrence
+            .iter()
+            .any(|rule| parse_weekly_rule(rule).is_some())
+}
+
+/// A recurring block of office hours on a calendar
+#[derive(Debug, Clone, Serialize)]
+pub struct OfficeHours {
+    pub event_id: Option<String>,
+    pub calendar_id: String,
+    pub summary: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    /// Days of the week, such as "Tuesday"
+    pub days: Vec<String>,
+    /// Local start and end times, such as "14:00"
+    pub start_time: String,
+    pub end_time: String,
+    pub time_zone: String,
+    /// When the office hours end, as given in the RRULE, if they do
+    pub until: Option<String>,
+    /// Whether the block leaves the time free for others to book
+    pub transparent: bool,
+    pub recurrence: Vec<String>,
+    pub html_link: Option<String>,
+}
+
+impl OfficeHours {
+    /// The office hours a recurring event holds, if it repeats weekly
+    pub fn from_event(calendar_id: &str, event: &CalendarEvent) -> Option<Self> {
+        let (mut days, until) = event
+            .recurrence
+            .iter()
+            .find_map(|rule| parse_weekly_rule(rule))?;
+        let time_zone: Tz = event
+            .time_zone
+            .as_deref()
+            .and_then(|name| name.parse().ok())
+            .unwrap_or(Tz::UTC);
+        let start = event.start_time.with_timezone(&time_zone);
+        if days.is_empty() {
+            // Without BYDAY the event repeats on the day it starts
+            days.push(start.weekday());
+        }
+
+        Some(Self {
+            event_id: event.id.clone(),
+            calendar_id: calendar_id.to_string(),
+            summary: event.summary.clone(),
+            description: event.description.clone(),
+            location: event.location.clone(),
+            days: days
+                .iter()
+                .map(|day| weekday_name(*day).to_string())
+                .collect(),
+            start_time: start.format("%H:%M").to_string(),
+            end_time: event
+                .end_time
+                .with_timezone(&time_zone)
+                .format("%H:%M")
+                .to_string(),
+            time_zone: time_zone.name().to_string(),
+            until,
+            transparent: event.transparency.as_deref() == Some("transparent"),
+            recurrence: event.recurrence.clone(),
+            html_link: event.html_link.clone(),
+        })
+    }
+}
+
+fn weekday_name(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    }
+}
@@ -0,0 +1,386 @@
+use crate::attachments::OutgoingAttachment;
+use crate::gmail_api::{DraftEmail, GmailService};
+use crate::persist;
+use chrono::{DateTime, Utc};
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use uuid::Uuid;
+
+// Undo send
+//
+// An email sent by `send_email` waits in a local outbox for the undo delay
+// before it is sent, and `cancel_send` can take it back until then. Each
+// queued email has a task that sleeps until its send time and then sends it
+// unless it was cancelled. The outbox is kept in a JSON file, so emails still
+// queued when the server stops are scheduled again when it next starts, and
+// sent, failed and cancelled emails stay listed for a while afterwards.
+
+/// Name of the outbox file, in the state directory
+pub const OUTBOX_FILE: &str = "outbox.json";
+
+/// Default time an email waits in the outbox before it is sent, in seconds
+pub const DEFAULT_SEND_DELAY_SECONDS: u64 = 30;
+
+/// Longest undo delay accepted, in seconds
+pub const MAX_SEND_DELAY_SECONDS: u64 = 60 * 60;
+
+/// Most sent, failed and cancelled emails kept in the outbox; the oldest are
+/// dropped first
+pub const MAX_FINISHED_ENTRIES: usize = 100;
+
+/// Where an email in the outbox is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutboxStatus {
+    /// Waiting for its send time, and can still be cancelled
+    Queued,
+    /// Being sent
+    Sending,
+    Sent,
+    Failed,
+    Cancelled,
+}
+
+/// An email in the outbox
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxEntry {
+    pub id: String,
+    pub email: DraftEmail,
+    /// Files attached to the email, such as Drive files, kept so that it is
+    /// sent as it was queued
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<OutgoingAttachment>,
+    pub status: OutboxStatus,
+    pub queued_at: DateTime<Utc>,
+    pub send_at: DateTime<Utc>,
+    /// Gmail message ID, once sent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_id: Option<String>,
+    /// Why sending failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl OutboxEntry {
+    /// The entry as the tools show it, with attachments by name and size
+    /// rather than their content
+    pub fn summary(&self) -> Value {
+        let mut summary = json!(self);
+        if !self.attachments.is_empty() {
+            let attachments: Vec<Value> = self
+                .attachments
+                .iter()
+                .map(|file| {
+                    json!({
+                        "filename": file.filename,
+                        "mime_type": file.mime_type,
+                        "size": file.data.len(),
+                    })
+                })
+                .collect();
+            summary["attachments"] = json!(attachments);
+        }
+        summary
+    }
+}
+
+/// Emails waiting to be sent, and those recently finished
+#[derive(Debug)]
+pub struct Outbox {
+    path: PathBuf,
+    // Held while the file is read and written
+    lock: Mutex<()>,
+}
+
+impl Outbox {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// The outbox shared by the tools, at `OUTBOX_FILE` or the default
+    /// location
+    pub fn global() -> &'static Outbox {
+        static OUTBOX: OnceLock<Outbox> = OnceLock::new();
+        OUTBOX.get_or_init(|| Outbox::new(get_outbox_path()))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Every email in the outbox, oldest first
+    pub fn entries(&self) -> Vec<OutboxEntry> {
+        let _lock = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        self.load()
+    }
+
+    /// Queue an email to be sent `delay` from now
+    pub fn queue(&self, email: DraftEmail, delay: Duration) -> Result<OutboxEntry, String> {
+        self.queue_with_attachments(email, Vec::new(), delay)
+    }
+
+    /// Queue an email with files attached to be sent `delay` from now
+    pub fn queue_with_attachments(
+        &self,
+        email: DraftEmail,
+        attachments: Vec<OutgoingAttachment>,
+        delay: Duration,
+    ) -> Result<OutboxEntry, String> {
+        let queued_at = Utc::now();
+        let entry = OutboxEntry {
+            id: format!("outbox-{}", Uuid::new_v4().simple()),
+            email,
+            attachments,
+            status: OutboxStatus::Queued,
+            queued_at,
+            send_at: queued_at + chrono::Duration::from_std(delay).unwrap_or_default(),
+            message_id: None,
+            error: None,
+        };
+        self.update(|entries| {
+            entries.push(entry.clone());
+            Ok(())
+        })?;
+        info!("Queued email {} to send at {}", entry.id, entry.send_at);
+        Ok(entry)
+    }
+
+    /// Cancel a queued email. Fails if there is no such email, or it has
+    /// already been sent or cancelled.
+    pub fn cancel(&self, id: &str) -> Result<OutboxEntry, String> {
+        let mut cancelled = None;
+        self.update(|entries| {
+            let entry = entries
+                .iter_mut()
+                .find(|entry| entry.id == id)
+                .ok_or_else(|| format!("No email '{}' in the outbox", id))?;
+            if entry.status != OutboxStatus::Queued {
+                return Err(format!(
+                    "Email '{}' can no longer be cancelled: it is {}",
+                    id,
+                    status_name(entry.status)
+                ));
+            }
+            entry.status = OutboxStatus::Cancelled;
+            cancelled = Some(entry.clone());
+            Ok(())
+        })?;
+        info!("Cancelled sending email {}", id);
+        cancelled.ok_or_else(|| format!("No email '{}' in the outbox", id))
+    }
+
+    // Mark a queued email as sending and return it, or None if it was
+    // cancelled
+    fn start_sending(&self, id: &str) -> Option<OutboxEntry> {
+        let mut sending = None;
+        let result = self.update(|entries| {
+            if let Some(entry) = entries
+                .iter_mut()
+                .find(|entry| entry.id == id && entry.status == OutboxStatus::Queued)
+            {
+                entry.status = OutboxStatus::Sending;
+                sending = Some(entry.clone());
+            }
+            Ok(())
+        });
+        if let Err(e) = result {
+            error!("{}", e);
+            return None;
+        }
+        sending
+    }
+
+    // Record how sending an email went
+    fn finish(&self, id: &str, result: Result<String, String>) -> Option<OutboxEntry> {
+        let mut finished = None;
+        let saved = self.update(|entries| {
+            if let Some(entry) = entries.iter_mut().find(|entry| entry.id == id) {
+                match &result {
+                    Ok(message_id) => {
+                        entry.status = OutboxStatus::Sent;
+                        entry.message_id = Some(message_id.clone());
+                    }
+                    Err(e) => {
+                        entry.status = OutboxStatus::Failed;
+                        entry.error = Some(e.clone());
+                    }
+                }
+                finished = Some(entry.clone());
+            }
+            Ok(())
+        });
+        if let Err(e) = saved {
+            error!("Failed to record the outcome of email {}: {}", id, e);
+        }
+        finished
+    }
+
+    // Emails to schedule when the server starts. Emails that were being sent
+    // when it stopped may or may not have gone out, so they are marked
+    // failed rather than sent again.
+    fn recover(&self) -> Vec<OutboxEntry> {
+        let mut queued = Vec::new();
+        let result = self.update(|entries| {
+            for entry in entries.iter_mut() {
+                match entry.status {
+                    OutboxStatus::Queued => queued.push(entry.clone()),
+                    OutboxStatus::Sending => {
+                        entry.status = OutboxStatus::Failed;
+                        entry.error = Some(
+                            "The server stopped while sending; check Sent mail before resending"
+                                .to_string(),
+                        );
+                    }
+                    _ => {}
+                }
+            }
+            Ok(())
+        });
+        if let Err(e) = result {
+            error!("{}", e);
+        }
+        queued
+    }
+
+    // Load the outbox, change it and save it, under the lock
+    fn update(
+        &self,
+        change: impl FnOnce(&mut Vec<OutboxEntry>) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let _lock = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut entries = self.load();
+        change(&mut entries)?;
+        prune(&mut entries);
+        self.store(&entries).map_err(|e| {
+            format!(
+                "Failed to save the outbox to {}: {}",
+                self.path.display(),
+                e
+            )
+        })
+    }
+
+    // Emails in the outbox. A missing or unreadable file has none.
+    fn load(&self) -> Vec<OutboxEntry> {
+        persist::load_json(&self.path).unwrap_or_else(|e| {
+            warn!(
+                "Ignoring unreadable outbox at {}: {}",
+                self.path.display(),
+                e
+            );
+            Vec::new()
+        })
+    }
+
+    fn store(&self, entries: &[OutboxEntry]) -> std::io::Result<()> {
+        persist::store_json(&self.path, entries)
+    }
+}
+
+// Drop the oldest finished emails beyond the limit
+fn prune(entries: &mut Vec<OutboxEntry>) {
+    let finished = entries
+        .iter()
+        .filter(|entry| is_finished(entry.status))
+        .count();
+    let mut excess = finished.saturating_sub(MAX_FINISHED_ENTRIES);
+    entries.retain(|entry| {
+        if excess > 0 && is_finished(entry.status) {
+            excess -= 1;
+            false
+        } else {
+            true
+        }
+    });
+}
+
+fn is_finished(status: OutboxStatus) -> bool {
+    matches!(
+        status,
+        OutboxStatus::Sent | OutboxStatus::Failed | OutboxStatus::Cancelled
+    )
+}
+
+fn status_name(status: OutboxStatus) -> &'static str {
+    match status {
+        OutboxStatus::Queued => "queued",
+        OutboxStatus::Sending => "being sent",
+        OutboxStatus::Sent => "sent",
+        OutboxStatus::Failed => "failed",
+        OutboxStatus::Cancelled => "cancelled",
+    }
+}
+
+/// Send an email now unless it was cancelled, returning its final state
+pub async fn send_entry(outbox: &Outbox, gmail: &GmailService, id: &str) -> Option<OutboxEntry> {
+    let entry = match outbox.start_sending(id) {
+        Some(entry) => entry,
+        None => {
+            debug!("Email {} was cancelled before its send time", id);
+            return None;
+        }
+    };
+    let result = gmail
+        .send_message_with_attachments(&entry.email, &entry.attachments)
+        .await
+        .map_err(|e| e.to_string());
+    match &result {
+        Ok(message_id) => info!("Sent email {} as message {}", id, message_id),
+        Err(e) => error!("Failed to send email {}: {}", id, e),
+    }
+    outbox.finish(id, result)
+}
+
+/// Send a queued email at its send time, in the background
+pub fn schedule(outbox: &'static Outbox, gmail: GmailService, entry: &OutboxEntry) {
+    let id = entry.id.clone();
+    let wait = (entry.send_at - Utc::now()).to_std().unwrap_or_default();
+    tokio::spawn(async move {
+        tokio::time::sleep(wait).await;
+        send_entry(outbox, &gmail, &id).await;
+    });
+}
+
+/// Schedule the emails left queued when the server last stopped, to be sent
+/// with the server's Gmail client. Returns how many were scheduled.
+pub fn resume(outbox: &'static Outbox, gmail: GmailService) -> usize {
+    let queued = outbox.recover();
+    for entry in &queued {
+        schedule(outbox, gmail.clone(), entry);
+    }
+    if !queued.is_empty() {
+        info!("Scheduled {} queued emails from the outbox", queued.len());
+    }
+    queued.len()
+}
+
+/// Returns the path of the outbox file.
+///
+/// Environment variable: OUTBOX_FILE
+pub fn get_outbox_path() -> PathBuf {
+    std::env::var("OUTBOX_FILE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| persist::default_path(dirs::config_dir(), OUTBOX_FILE))
+}
+
+/// Returns how long emails wait in the outbox before they are sent. Zero
+/// sends them at once.
+///
+/// Default is 30 seconds if not configured, and at most an hour.
+///
+/// Environment variable: SEND_UNDO_SECONDS
+pub fn get_send_delay() -> Duration {
+    let seconds = std::env::var("SEND_UNDO_SECONDS")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_SEND_DELAY_SECONDS)
+        .min(MAX_SEND_DELAY_SECONDS);
+    Duration::from_secs(seconds)
+}
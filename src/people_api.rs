@@ -1,13 +1,14 @@
 use crate::auth::TokenManager;
-use crate::config::Config;
-use crate::errors::{PeopleApiError, PeopleResult};
+use crate::config::{get_people_api_base_url, Config};
+use crate::errors::{retry_after_header, PeopleApiError, PeopleResult, RateLimitInfo};
+use crate::etag_cache::EtagCache;
+use crate::utils::api_client_builder;
 use log::{debug, error};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
-const PEOPLE_API_BASE_URL: &str = "https://people.googleapis.com/v1";
 
 // Alias for backward compatibility within this module
 type Result<T> = PeopleResult<T>;
@@ -61,6 +62,17 @@ pub struct ContactList {
     pub total_items: Option<u32>,
 }
 
+// Find the contact with an email address, ignoring case. Contact search also
+// matches names and parts of addresses, so its results need checking.
+pub fn find_contact_by_email<'a>(contacts: &'a [Contact], address: &str) -> Option<&'a Contact> {
+    contacts.iter().find(|contact| {
+        contact
+            .email_addresses
+            .iter()
+            .any(|email| email.value.eq_ignore_ascii_case(address))
+    })
+}
+
 // People API client
 #[derive(Debug, Clone)]
 pub struct PeopleClient {
@@ -70,7 +82,9 @@ pub struct PeopleClient {
 
 impl PeopleClient {
     pub fn new(config: &Config) -> Self {
-        let client = Client::new();
+        let client = api_client_builder()
+            .build()
+            .unwrap_or_else(|_| Client::new());
         // Reuse the Gmail token manager since they share the same OAuth flow
         let token_manager = Arc::new(Mutex::new(TokenManager::new(config)));
 
@@ -90,7 +104,7 @@ impl PeopleClient {
             .await
             .map_err(|e| PeopleApiError::AuthError(e.to_string()))?;
 
-        let mut url = format!("{}/people/me/connections", PEOPLE_API_BASE_URL);
+        let mut url = format!("{}/people/me/connections", get_people_api_base_url());
 
         // Build query parameters
         let mut query_parts = Vec::new();
@@ -115,29 +129,30 @@ impl PeopleClient {
 
         debug!("Listing contacts from: {}", url);
 
-        let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", token))
-            .send()
+        let response = EtagCache::global()
+            .send(
+                self.client
+                    .get(&url)
+                    .header("Authorization", format!("Bearer {}", token)),
+            )
             .await
             .map_err(|e| PeopleApiError::NetworkError(e.to_string()))?;
 
-        let status = response.status();
-        if !status.is_success() {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "<no response body>".to_string());
+        if !response.status.is_success() {
+            if let Some(info) = RateLimitInfo::from_response(
+                response.status.as_u16(),
+                response.retry_after.as_deref(),
+                &response.body,
+            ) {
+                return Err(PeopleApiError::RateLimited(info));
+            }
             return Err(PeopleApiError::ApiError(format!(
                 "Failed to list contacts. Status: {}, Error: {}",
-                status, error_text
+                response.status, response.body
             )));
         }
 
-        let json_response = response
-            .json::<serde_json::Value>()
-            .await
+        let json_response = serde_json::from_str::<serde_json::Value>(&response.body)
             .map_err(|e| PeopleApiError::ParseError(e.to_string()))?;
 
         let mut contacts = Vec::new();
@@ -184,7 +199,7 @@ impl PeopleClient {
             .await
             .map_err(|e| PeopleApiError::AuthError(e.to_string()))?;
 
-        let mut url = format!("{}/people:searchContacts", PEOPLE_API_BASE_URL);
+        let mut url = format!("{}/people:searchContacts", get_people_api_base_url());
 
         // Build query parameters
         let mut query_parts = Vec::new();
@@ -222,10 +237,16 @@ impl PeopleClient {
 
         let status = response.status();
         if !status.is_success() {
+            let retry_after = retry_after_header(response.headers());
             let error_text = response
                 .text()
                 .await
                 .unwrap_or_else(|_| "<no response body>".to_string());
+            if let Some(info) =
+                RateLimitInfo::from_response(status.as_u16(), retry_after.as_deref(), &error_text)
+            {
+                return Err(PeopleApiError::RateLimited(info));
+            }
             return Err(PeopleApiError::ApiError(format!(
                 "Failed to search contacts. Status: {}, Error: {}",
                 status, error_text
@@ -279,7 +300,7 @@ impl PeopleClient {
             .await
             .map_err(|e| PeopleApiError::AuthError(e.to_string()))?;
 
-        let mut url = format!("{}/{}", PEOPLE_API_BASE_URL, resource_name);
+        let mut url = format!("{}/{}", get_people_api_base_url(), resource_name);
 
         // Build query parameters for fields
         let fields = [
@@ -293,6 +314,58 @@ impl PeopleClient {
 
         debug!("Getting contact: {}", url);
 
+        let response = EtagCache::global()
+            .send(
+                self.client
+                    .get(&url)
+                    .header("Authorization", format!("Bearer {}", token)),
+            )
+            .await
+            .map_err(|e| PeopleApiError::NetworkError(e.to_string()))?;
+
+        if !response.status.is_success() {
+            if let Some(info) = RateLimitInfo::from_response(
+                response.status.as_u16(),
+                response.retry_after.as_deref(),
+                &response.body,
+            ) {
+                return Err(PeopleApiError::RateLimited(info));
+            }
+            return Err(PeopleApiError::ApiError(format!(
+                "Failed to get contact. Status: {}, Error: {}",
+                response.status, response.body
+            )));
+        }
+
+        let json_response = serde_json::from_str::<serde_json::Value>(&response.body)
+            .map_err(|e| PeopleApiError::ParseError(e.to_string()))?;
+
+        self.parse_contact(&json_response)
+    }
+
+    // Get a contact as the API returns it, with the given person fields and
+    // those Contact does not keep, such as its etag
+    pub async fn get_contact_json(
+        &self,
+        resource_name: &str,
+        person_fields: &[&str],
+    ) -> Result<serde_json::Value> {
+        let token = self
+            .token_manager
+            .lock()
+            .await
+            .get_token(&self.client)
+            .await
+            .map_err(|e| PeopleApiError::AuthError(e.to_string()))?;
+
+        let url = format!(
+            "{}/{}?personFields={}",
+            get_people_api_base_url(),
+            resource_name,
+            person_fields.join(",")
+        );
+        debug!("Getting contact: {}", url);
+
         let response = self
             .client
             .get(&url)
@@ -303,16 +376,154 @@ impl PeopleClient {
 
         let status = response.status();
         if !status.is_success() {
+            let retry_after = retry_after_header(response.headers());
             let error_text = response
                 .text()
                 .await
                 .unwrap_or_else(|_| "<no response body>".to_string());
+            if let Some(info) =
+                RateLimitInfo::from_response(status.as_u16(), retry_after.as_deref(), &error_text)
+            {
+                return Err(PeopleApiError::RateLimited(info));
+            }
             return Err(PeopleApiError::ApiError(format!(
                 "Failed to get contact. Status: {}, Error: {}",
                 status, error_text
             )));
         }
 
+        response
+            .json::<serde_json::Value>()
+            .await
+            .map_err(|e| PeopleApiError::ParseError(e.to_string()))
+    }
+
+    // Change the given person fields of a contact, leaving the others as they
+    // are. `person` carries the etag the contact was read with, and the change
+    // is only made if the contact has not changed since.
+    pub async fn update_contact(
+        &self,
+        resource_name: &str,
+        person: &serde_json::Value,
+        update_fields: &[&str],
+    ) -> Result<Contact> {
+        let token = self
+            .token_manager
+            .lock()
+            .await
+            .get_token(&self.client)
+            .await
+            .map_err(|e| PeopleApiError::AuthError(e.to_string()))?;
+
+        let fields = [
+            "names",
+            "emailAddresses",
+            "phoneNumbers",
+            "organizations",
+            "photos",
+        ];
+        let url = format!(
+            "{}/{}:updateContact?updatePersonFields={}&personFields={}",
+            get_people_api_base_url(),
+            resource_name,
+            update_fields.join(","),
+            fields.join(",")
+        );
+        debug!("Updating contact: {}", url);
+
+        let response = self
+            .client
+            .patch(&url)
+            .header("Authorization", format!("Bearer {}", token))
+            .json(person)
+            .send()
+            .await
+            .map_err(|e| PeopleApiError::NetworkError(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let retry_after = retry_after_header(response.headers());
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "<no response body>".to_string());
+            // A stale etag is rejected as a failed precondition
+            if error_text.contains("FAILED_PRECONDITION") {
+                return Err(PeopleApiError::ApiError(format!(
+                    "Contact {} changed while it was being updated; try again",
+                    resource_name
+                )));
+            }
+            if let Some(info) =
+                RateLimitInfo::from_response(status.as_u16(), retry_after.as_deref(), &error_text)
+            {
+                return Err(PeopleApiError::RateLimited(info));
+            }
+            return Err(PeopleApiError::ApiError(format!(
+                "Failed to update contact. Status: {}, Error: {}",
+                status, error_text
+            )));
+        }
+
+        let json_response = response
+            .json::<serde_json::Value>()
+            .await
+            .map_err(|e| PeopleApiError::ParseError(e.to_string()))?;
+
+        self.parse_contact(&json_response)
+    }
+
+    // Create a contact from a People API `Person` resource
+    pub async fn create_contact(&self, person: &serde_json::Value) -> Result<Contact> {
+        let token = self
+            .token_manager
+            .lock()
+            .await
+            .get_token(&self.client)
+            .await
+            .map_err(|e| PeopleApiError::AuthError(e.to_string()))?;
+
+        let fields = [
+            "names",
+            "emailAddresses",
+            "phoneNumbers",
+            "organizations",
+            "photos",
+        ];
+        let url = format!(
+            "{}/people:createContact?personFields={}",
+            get_people_api_base_url(),
+            fields.join(",")
+        );
+        debug!("Creating contact: {}", url);
+
+        let response = self
+            .client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", token))
+            .json(person)
+            .send()
+            .await
+            .map_err(|e| PeopleApiError::NetworkError(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let retry_after = retry_after_header(response.headers());
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "<no response body>".to_string());
+            if let Some(info) =
+                RateLimitInfo::from_response(status.as_u16(), retry_after.as_deref(), &error_text)
+            {
+                return Err(PeopleApiError::RateLimited(info));
+            }
+            return Err(PeopleApiError::ApiError(format!(
+                "Failed to create contact. Status: {}, Error: {}",
+                status, error_text
+            )));
+        }
+
         let json_response = response
             .json::<serde_json::Value>()
             .await
@@ -321,8 +532,8 @@ impl PeopleClient {
         self.parse_contact(&json_response)
     }
 
-    // Helper method to parse a contact from API response
-    fn parse_contact(&self, data: &serde_json::Value) -> Result<Contact> {
+    /// Parse a contact from a People API `Person` resource
+    pub fn parse_contact(&self, data: &serde_json::Value) -> Result<Contact> {
         let resource_name = data
             .get("resourceName")
             .and_then(|v| v.as_str())
@@ -0,0 +1,60 @@
+use log::debug;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// State files
+//
+// Saved searches, templates, rules, the outbox and the logs behind send
+// limits and idempotency keys are each kept in a JSON file. They are all read
+// and written the same way: a missing file is empty, and a file is replaced
+// whole so that a crash mid-write never leaves it truncated.
+
+/// Returns where a state file is kept by default: in the state directory
+/// when one is set, otherwise in `gmail-mcp-rs` under `base` (the platform
+/// cache or config directory, or the temporary directory when there is none).
+pub fn default_path(base: Option<PathBuf>, file: &str) -> PathBuf {
+    if let Some(dir) = crate::config::get_state_dir() {
+        return dir.join(file);
+    }
+    let mut path = base.unwrap_or_else(std::env::temp_dir);
+    path.push("gmail-mcp-rs");
+    path.push(file);
+    path
+}
+
+/// Read a JSON file. A missing file reads as the default value; a file that
+/// can't be parsed is an `InvalidData` error.
+pub fn load_json<T: DeserializeOwned + Default>(path: &Path) -> std::io::Result<T> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            debug!("No file at {}", path.display());
+            return Ok(T::default());
+        }
+        Err(e) => return Err(e),
+    };
+    Ok(serde_json::from_str(&contents)?)
+}
+
+/// Write `value` to a JSON file, creating its directory if needed
+pub fn store_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> std::io::Result<()> {
+    let contents = serde_json::to_string_pretty(value)?;
+    write_file(path, &contents)
+}
+
+// Replace a file through a uniquely named temporary file, so that a crash
+// mid-write leaves the previous contents and concurrent saves don't share a
+// temporary file. The temporary file is created readable only by its owner.
+fn write_file(file: &Path, contents: &str) -> std::io::Result<()> {
+    let dir = match file.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    std::fs::create_dir_all(dir)?;
+    let mut temp = tempfile::NamedTempFile::new_in(dir)?;
+    temp.write_all(contents.as_bytes())?;
+    temp.persist(file).map_err(|e| e.error)?;
+    Ok(())
+}
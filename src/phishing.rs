@@ -0,0 +1,296 @@
+use crate::gmail_api::EmailMessage;
+use crate::sender_auth::{domains_aligned, SenderVerdict, SenderVerification};
+use crate::utils::{extract_links, parse_email_addresses};
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+// Phishing heuristics
+//
+// A phishing risk report weighs signals that can be read from the message
+// alone, without looking anything up: whether the sender is authenticated,
+// whether the display name points at a different domain than the address,
+// whether links hide where they go, and whether the text pushes the reader to
+// act in a hurry. Each signal adds to a score out of 100. A high score is a
+// reason for caution, not proof; a low one is not proof of safety either.
+
+/// Score from which a message is rated high risk
+pub const HIGH_RISK_SCORE: u32 = 60;
+
+/// Score from which a message is rated medium risk
+pub const MEDIUM_RISK_SCORE: u32 = 30;
+
+// Most that all link signals, and all urgency phrases, add to a score
+const MAX_LINK_SCORE: u32 = 40;
+const MAX_URGENCY_SCORE: u32 = 20;
+
+/// Link shorteners, which hide where a link goes
+const URL_SHORTENERS: &[&str] = &[
+    "bit.ly",
+    "tinyurl.com",
+    "t.co",
+    "goo.gl",
+    "ow.ly",
+    "is.gd",
+    "buff.ly",
+    "rebrand.ly",
+    "cutt.ly",
+    "shorturl.at",
+];
+
+/// Top-level domains that are cheap to register and common in phishing, or
+/// easy to mistake for file names
+const SUSPICIOUS_TLDS: &[&str] = &["zip", "mov", "top", "xyz", "tk", "gq", "ml", "cf", "click"];
+
+/// Phrases that pressure the reader to act without thinking
+const URGENCY_PHRASES: &[&str] = &[
+    "urgent",
+    "immediately",
+    "act now",
+    "within 24 hours",
+    "final notice",
+    "account suspended",
+    "account will be closed",
+    "verify your account",
+    "confirm your identity",
+    "unusual activity",
+    "password expires",
+    "payment failed",
+    "update your payment",
+    "gift card",
+    "wire transfer",
+];
+
+/// Overall rating of a phishing risk report
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskLevel {
+    Low,
+    Medium,
+    High,
+}
+
+/// One reason a message looks like phishing
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RiskSignal {
+    /// `sender_authentication`, `display_name_mismatch`, `suspicious_link`
+    /// or `urgency`
+    pub kind: String,
+    /// Points added to the risk score
+    pub weight: u32,
+    pub detail: String,
+}
+
+/// Phishing risk report for one message
+#[derive(Serialize, Debug, Clone)]
+pub struct PhishingAssessment {
+    pub message_id: String,
+    pub from: Option<String>,
+    pub subject: Option<String>,
+    /// From 0 (no signals) to 100
+    pub risk_score: u32,
+    pub risk_level: RiskLevel,
+    pub sender_verdict: SenderVerdict,
+    /// Hosts of all the links in the message
+    pub link_domains: Vec<String>,
+    /// The signals behind the score, heaviest first
+    pub signals: Vec<RiskSignal>,
+}
+
+fn signal(kind: &str, weight: u32, detail: String) -> RiskSignal {
+    RiskSignal {
+        kind: kind.to_string(),
+        weight,
+        detail,
+    }
+}
+
+fn host_of(url: &str) -> Option<String> {
+    url::Url::parse(url)
+        .ok()?
+        .host_str()
+        .map(|host| host.trim_start_matches("www.").to_lowercase())
+}
+
+// The domain that visible link text claims to lead to, if it reads as a URL
+// or a bare domain such as `paypal.com`
+fn claimed_host(text: &str) -> Option<String> {
+    let text = text.trim().trim_end_matches('/');
+    if text.contains(char::is_whitespace) || !text.contains('.') {
+        return None;
+    }
+    if text.starts_with("http://") || text.starts_with("https://") {
+        return host_of(text);
+    }
+    let host = text.split('/').next()?.trim_start_matches("www.");
+    let tld = host.rsplit('.').next()?;
+    (tld.len() >= 2 && tld.chars().all(|c| c.is_ascii_alphabetic())).then(|| host.to_lowercase())
+}
+
+// What makes a link's destination hard to trust, if anything
+fn link_problem(url: &str, host: &str) -> Option<String> {
+    let authority = url.split("://").nth(1)?.split('/').next()?;
+    if authority.contains('@') {
+        return Some(format!(
+            "{} hides its destination {} behind a user name",
+            url, host
+        ));
+    }
+    if host.parse::<std::net::IpAddr>().is_ok() || host.starts_with('[') {
+        return Some(format!("{} points at a bare IP address", url));
+    }
+    if host.split('.').any(|label| label.starts_with("xn--")) {
+        return Some(format!(
+            "{} uses an internationalized domain that may imitate another",
+            host
+        ));
+    }
+    if URL_SHORTENERS.contains(&host) {
+        return Some(format!(
+            "{} is a link shortener hiding the destination",
+            host
+        ));
+    }
+    let tld = host.rsplit('.').next().unwrap_or_default();
+    if SUSPICIOUS_TLDS.contains(&tld) {
+        return Some(format!("{} uses the .{} top-level domain", host, tld));
+    }
+    None
+}
+
+fn risk_level(score: u32) -> RiskLevel {
+    if score >= HIGH_RISK_SCORE {
+        RiskLevel::High
+    } else if score >= MEDIUM_RISK_SCORE {
+        RiskLevel::Medium
+    } else {
+        RiskLevel::Low
+    }
+}
+
+impl PhishingAssessment {
+    /// Weigh the phishing signals in a message
+    pub fn from_message(message: &EmailMessage) -> Self {
+        let verification = SenderVerification::from_message(message);
+        let mut signals = Vec::new();
+
+        match verification.verdict {
+            SenderVerdict::Failed => signals.push(signal(
+                "sender_authentication",
+                40,
+                format!(
+                    "Sender authentication failed: {}",
+                    verification.reasons.join(", ")
+                ),
+            )),
+            SenderVerdict::Unverified => signals.push(signal(
+                "sender_authentication",
+                15,
+                "Nothing ties the message to the domain of its From address".to_string(),
+            )),
+            SenderVerdict::Unknown => signals.push(signal(
+                "sender_authentication",
+                10,
+                "The message carries no authentication results".to_string(),
+            )),
+            SenderVerdict::Authenticated => {}
+        }
+
+        // A display name such as "support@bank.example" or "bank.example
+        // Support" on mail from another domain
+        if let (Some(from), Some(from_domain)) =
+            (message.from.as_deref(), verification.from_domain.as_deref())
+        {
+            if let Some((name, _)) = from.rsplit_once('<') {
+                let name = name.trim().trim_matches('"');
+                let claimed = parse_email_addresses(name)
+                    .into_iter()
+                    .filter_map(|address| address.rsplit('@').next().map(String::from))
+                    .chain(name.split_whitespace().filter_map(claimed_host))
+                    .find(|domain| !domains_aligned(domain, from_domain));
+                if let Some(claimed) = claimed {
+                    signals.push(signal(
+                        "display_name_mismatch",
+                        25,
+                        format!(
+                            "Display name \"{}\" suggests {} but the address is at {}",
+                            name, claimed, from_domain
+                        ),
+                    ));
+                }
+            }
+        }
+
+        let mut link_signals = Vec::new();
+        let mut link_domains = BTreeSet::new();
+        let mut reported = BTreeSet::new();
+        let links = extract_links(message.body_html.as_deref(), message.body_text.as_deref());
+        for link in &links {
+            let Some(host) = link.domain.clone() else {
+                continue;
+            };
+            if let Some(claimed) = link.anchor_text.as_deref().and_then(claimed_host) {
+                if !domains_aligned(&host, &claimed) {
+                    link_signals.push(signal(
+                        "suspicious_link",
+                        20,
+                        format!("Link text shows {} but leads to {}", claimed, host),
+                    ));
+                }
+            }
+            if let Some(problem) = link_problem(&link.url, &host) {
+                if reported.insert(host.clone()) {
+                    link_signals.push(signal("suspicious_link", 15, problem));
+                }
+            }
+            link_domains.insert(host);
+        }
+        let mut link_score = 0;
+        for mut link_signal in link_signals {
+            link_signal.weight = link_signal.weight.min(MAX_LINK_SCORE - link_score);
+            link_score += link_signal.weight;
+            signals.push(link_signal);
+        }
+
+        let text = format!(
+            "{}\n{}",
+            message.subject.as_deref().unwrap_or_default(),
+            message
+                .body_text
+                .as_deref()
+                .or(message.snippet.as_deref())
+                .unwrap_or_default()
+        )
+        .to_lowercase();
+        let phrases: Vec<&str> = URGENCY_PHRASES
+            .iter()
+            .copied()
+            .filter(|phrase| text.contains(phrase))
+            .collect();
+        if !phrases.is_empty() {
+            signals.push(signal(
+                "urgency",
+                (phrases.len() as u32 * 5).min(MAX_URGENCY_SCORE),
+                format!("Pressing language: {}", phrases.join(", ")),
+            ));
+        }
+
+        signals.retain(|signal| signal.weight > 0);
+        signals.sort_by_key(|signal| std::cmp::Reverse(signal.weight));
+        let risk_score = signals
+            .iter()
+            .map(|signal| signal.weight)
+            .sum::<u32>()
+            .min(100);
+
+        Self {
+            message_id: message.id.clone(),
+            from: message.from.clone(),
+            subject: message.subject.clone(),
+            risk_score,
+            risk_level: risk_level(risk_score),
+            sender_verdict: verification.verdict,
+            link_domains: link_domains.into_iter().collect(),
+            signals,
+        }
+    }
+}
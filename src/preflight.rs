@@ -0,0 +1,187 @@
+use crate::auth::TokenManager;
+use crate::calendar_api::CalendarClient;
+use crate::config::Config;
+use crate::doctor::{CheckResult, CheckStatus};
+use crate::drive_api::DriveClient;
+use crate::errors::is_insufficient_scope;
+use crate::gmail_api::GmailService;
+use crate::oauth::{
+    CALENDAR_WRITE_SCOPE, CONTACTS_WRITE_SCOPE, DRIVE_READ_SCOPE, GMAIL_SCOPE, TASKS_SCOPE,
+};
+use crate::people_api::PeopleClient;
+use crate::tasks_api::TasksClient;
+use crate::utils::scope_fix_command;
+use log::{error, info, warn};
+use reqwest::Client;
+use std::time::Duration;
+
+// Startup preflight
+//
+// Problems with the configuration or credentials otherwise only show up when
+// the first tool call fails, often well into a conversation. When enabled,
+// the server runs these checks as it starts: loading the configuration,
+// refreshing the access token and making one cheap request to each Google
+// API. They never stop the server; the results are logged with a readiness
+// summary.
+
+/// The services checked, with the scope each needs
+const SERVICES: &[(&str, &str)] = &[
+    ("Gmail API", GMAIL_SCOPE),
+    ("Calendar API", CALENDAR_WRITE_SCOPE),
+    ("People API", CONTACTS_WRITE_SCOPE),
+    ("Tasks API", TASKS_SCOPE),
+    ("Drive API", DRIVE_READ_SCOPE),
+];
+
+/// Run the preflight checks and return their results in order
+pub async fn run_preflight() -> Vec<CheckResult> {
+    let mut results = Vec::new();
+
+    let config = match Config::from_env() {
+        Ok(config) => {
+            results.push(CheckResult::new(
+                "Configuration",
+                CheckStatus::Pass,
+                "OAuth configuration loaded",
+            ));
+            config
+        }
+        Err(err) => {
+            results.push(
+                CheckResult::new("Configuration", CheckStatus::Fail, err.to_string())
+                    .with_fix("Run `mcp-gmailcal doctor` for details"),
+            );
+            skip_services(&mut results, "Requires a valid configuration");
+            return results;
+        }
+    };
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(15))
+        .build()
+        .unwrap_or_else(|_| Client::new());
+    let mut token_manager = TokenManager::new(&config);
+    match token_manager.get_token(&client).await {
+        Ok(_) => results.push(CheckResult::new(
+            "Access token",
+            CheckStatus::Pass,
+            "Access token refreshed",
+        )),
+        Err(err) => {
+            results.push(
+                CheckResult::new("Access token", CheckStatus::Fail, err.to_string()).with_fix(
+                    "Your refresh token may be expired or revoked. Run `mcp-gmailcal auth` to obtain a new one",
+                ),
+            );
+            skip_services(&mut results, "Requires a valid access token");
+            return results;
+        }
+    }
+
+    // One cheap read per service
+    let gmail = match GmailService::new(&config) {
+        Ok(gmail) => gmail
+            .get_profile()
+            .await
+            .map(|profile| format!("Signed in as {}", profile.email_address))
+            .map_err(|err| err.to_string()),
+        Err(err) => Err(err.to_string()),
+    };
+    let calendar = CalendarClient::new(&config)
+        .list_calendars()
+        .await
+        .map(|list| format!("{} calendars", list.calendars.len()))
+        .map_err(|err| err.to_string());
+    let people = PeopleClient::new(&config)
+        .list_contacts(Some(1))
+        .await
+        .map(|_| "Contacts readable".to_string())
+        .map_err(|err| err.to_string());
+    let tasks = TasksClient::new(&config)
+        .list_task_lists(Some(1))
+        .await
+        .map(|_| "Task lists readable".to_string())
+        .map_err(|err| err.to_string());
+    let drive = DriveClient::new(&config)
+        .get_storage_quota()
+        .await
+        .map(|_| "Drive readable".to_string())
+        .map_err(|err| err.to_string());
+
+    for ((name, scope), outcome) in SERVICES.iter().zip([gmail, calendar, people, tasks, drive]) {
+        results.push(service_result(name, scope, outcome));
+    }
+
+    results
+}
+
+// The result for one service. A missing scope only disables that service's
+// tools, so it is a warning rather than a failure.
+fn service_result(name: &str, scope: &str, outcome: Result<String, String>) -> CheckResult {
+    match outcome {
+        Ok(detail) => CheckResult::new(name, CheckStatus::Pass, detail),
+        Err(err) if is_insufficient_scope(&err) => CheckResult::new(
+            name,
+            CheckStatus::Warn,
+            format!("The access token was not granted {}", scope),
+        )
+        .with_fix(format!("Run `{}`", scope_fix_command(scope))),
+        Err(err) => CheckResult::new(name, CheckStatus::Fail, err),
+    }
+}
+
+fn skip_services(results: &mut Vec<CheckResult>, reason: &str) {
+    for (name, _) in SERVICES {
+        results.push(CheckResult::new(name, CheckStatus::Skip, reason));
+    }
+}
+
+/// Summarize the results in one line, e.g. "Ready: 7 of 7 checks passed"
+pub fn summarize(results: &[CheckResult]) -> String {
+    let count = |status| results.iter().filter(|r| r.status == status).count();
+    let passed = count(CheckStatus::Pass);
+    let failed: Vec<&str> = results
+        .iter()
+        .filter(|r| r.status == CheckStatus::Fail)
+        .map(|r| r.name.as_str())
+        .collect();
+
+    let mut summary = if failed.is_empty() {
+        format!("Ready: {} of {} checks passed", passed, results.len())
+    } else {
+        format!(
+            "Not ready: {} of {} checks passed, failed: {}",
+            passed,
+            results.len(),
+            failed.join(", ")
+        )
+    };
+    let warnings = count(CheckStatus::Warn);
+    if warnings > 0 {
+        summary.push_str(&format!(", {} warnings", warnings));
+    }
+    summary
+}
+
+/// Log each result and the readiness summary
+pub fn log_report(results: &[CheckResult]) {
+    for result in results {
+        let fix = result
+            .fix
+            .as_ref()
+            .map(|fix| format!(" (fix: {})", fix))
+            .unwrap_or_default();
+        match result.status {
+            CheckStatus::Fail => error!("Preflight {}: {}{}", result.name, result.detail, fix),
+            CheckStatus::Warn => warn!("Preflight {}: {}{}", result.name, result.detail, fix),
+            _ => info!("Preflight {}: {}", result.name, result.detail),
+        }
+    }
+
+    let summary = summarize(results);
+    if results.iter().any(|r| r.status == CheckStatus::Fail) {
+        error!("Preflight {}", summary);
+    } else {
+        info!("Preflight {}", summary);
+    }
+}
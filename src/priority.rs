@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+
+// Message priority
+//
+// Mail clients mark urgent mail with one of several headers: X-Priority
+// (`1 (Highest)` to `5 (Lowest)`), Importance (`high`, `normal`, `low`),
+// Priority (`urgent`, `normal`, `non-urgent`, RFC 2156) or Outlook's
+// X-MSMail-Priority. Gmail ignores them all and instead decides itself which
+// mail is important, recorded as the IMPORTANT label. Both are reported, since
+// a sender's own flag and Gmail's judgement often disagree.
+
+/// Headers that carry a sender's priority, most trusted first
+const PRIORITY_HEADERS: &[&str] = &["X-Priority", "Importance", "Priority", "X-MSMail-Priority"];
+
+/// Priority a sender gave a message
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PriorityLevel {
+    High,
+    Normal,
+    Low,
+}
+
+impl PriorityLevel {
+    /// Read a priority header value, e.g. `1 (Highest)`, `high` or
+    /// `non-urgent`, or a priority a user asked for
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.split_whitespace().next()?.to_lowercase();
+        match value.as_str() {
+            "1" | "2" | "high" | "highest" | "urgent" => Some(Self::High),
+            "3" | "normal" | "medium" => Some(Self::Normal),
+            "4" | "5" | "low" | "lowest" | "non-urgent" => Some(Self::Low),
+            _ => None,
+        }
+    }
+
+    /// X-Priority and Importance headers for an outgoing message, each
+    /// ending in CRLF
+    pub fn headers(self) -> String {
+        let (x_priority, importance) = match self {
+            Self::High => ("1 (Highest)", "high"),
+            Self::Normal => ("3 (Normal)", "normal"),
+            Self::Low => ("5 (Lowest)", "low"),
+        };
+        format!(
+            "X-Priority: {}\r\nImportance: {}\r\n",
+            x_priority, importance
+        )
+    }
+}
+
+/// Whether a header is one that carries a sender's priority
+pub fn is_priority_header(name: &str) -> bool {
+    PRIORITY_HEADERS
+        .iter()
+        .any(|header| name.eq_ignore_ascii_case(header))
+}
+
+/// How urgent a message is, by its sender and by Gmail
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Priority {
+    /// Priority from the sender's headers; `None` when they set none
+    pub level: Option<PriorityLevel>,
+    /// Whether Gmail marked the message important
+    pub important: bool,
+    /// The X-Priority header as sent
+    pub x_priority: Option<String>,
+    /// The Importance header as sent
+    pub importance: Option<String>,
+}
+
+impl Priority {
+    /// Read the priority from a message's headers, given as name and value,
+    /// and whether it has the IMPORTANT label
+    pub fn from_headers(headers: &[(&str, &str)], important: bool) -> Self {
+        let header = |name: &str| {
+            headers
+                .iter()
+                .find(|(header, _)| header.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.trim().to_string())
+        };
+        Self {
+            level: PRIORITY_HEADERS
+                .iter()
+                .find_map(|name| header(name).as_deref().and_then(PriorityLevel::parse)),
+            important,
+            x_priority: header("X-Priority"),
+            importance: header("Importance"),
+        }
+    }
+}
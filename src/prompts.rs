@@ -154,6 +154,8 @@ When extracting tasks and action items from emails, look for:
    - Add context for clarity
 
 Present tasks in a structured list format that can be easily transferred to a task management system. If dates are mentioned, format them consistently (YYYY-MM-DD) to facilitate calendar integration.
+
+Tasks can be saved to Google Tasks with the create_task tool, passing the due date and the ID of the email they came from.
 "#;
 
 /// Meeting extraction prompt
@@ -372,3 +374,55 @@ When helping users draft effective emails, follow these guidelines:
 
 Adapt these guidelines based on the specific purpose, audience, and context of the email being drafted.
 "#;
+
+/// Email translation prompt
+pub const EMAIL_TRANSLATION_PROMPT: &str = r#"
+When translating an email for the user, follow these guidelines:
+
+1. Source Language:
+   - Use the detected language given with the email as a starting point, and correct it if the text says otherwise
+   - Point out passages written in a different language from the rest, such as quoted replies
+   - If the email is already in the user's language, say so instead of translating it
+
+2. Target Language:
+   - Translate into the language the user asked for, or the language they are writing to you in
+   - Keep names, addresses, product names, code and URLs as they are
+
+3. Faithful Translation:
+   - Preserve the meaning, tone and level of formality of the original
+   - Keep dates, times, amounts and units exact, noting any that read differently across locales (e.g. 03/04 or 1.000,00)
+   - Translate idioms by their meaning rather than word for word, noting any that do not carry over
+   - Keep the structure of the email: greeting, paragraphs, lists and signature
+
+4. Context for Replying:
+   - Note cultural conventions that affect how a reply should be written, such as expected formality
+   - Offer to draft a reply in the sender's language when one seems expected
+
+Present the translation first, followed by brief notes only where they help the user understand or respond to the email.
+"#;
+
+/// Weekly review prompt
+pub const WEEKLY_REVIEW_PROMPT: &str = r#"
+When reviewing the user's week from their mail and calendar, follow these guidelines:
+
+1. Overview:
+   - Summarize how much mail came in and went out, and which days were busiest
+   - Note how much of the week went to meetings and which meetings stood out
+   - Compare with what a typical week looks like if the user has said so
+
+2. Key Conversations:
+   - Describe what each key thread was about and where it stands
+   - Name the people most involved and any decisions that were made
+   - Point out threads that seem to need the user's input
+
+3. Outstanding Items:
+   - List the starred messages as things still to do, oldest first
+   - List the sent emails still awaiting a reply, with how long each has waited
+   - Suggest which items to follow up on or close out first
+
+4. Looking Ahead:
+   - Suggest priorities for the coming week based on the open items
+   - Flag unread mail that may still matter
+
+Keep the review short and scannable, with headings for each section and no more than a few bullet points under each.
+"#;
@@ -0,0 +1,99 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::sync::{Mutex, Semaphore, SemaphorePermit};
+use tokio::time::Instant;
+
+// Client-side rate limiting
+//
+// Gmail enforces a per-user quota on requests per second. Large listings
+// fetch many messages concurrently, so every Gmail request first takes a
+// permit from the shared `RateLimiter`, which caps both the number of
+// requests in flight and how quickly new ones start.
+
+/// Default number of concurrent API requests
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 8;
+
+/// Default number of API requests started per second
+pub const DEFAULT_REQUESTS_PER_SECOND: u32 = 40;
+
+/// Limits concurrent requests and spaces out their start times
+pub struct RateLimiter {
+    permits: Semaphore,
+    max_concurrent: usize,
+    interval: Duration,
+    next_start: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    /// A `requests_per_second` of zero disables spacing
+    pub fn new(max_concurrent: usize, requests_per_second: u32) -> Self {
+        let max_concurrent = max_concurrent.max(1);
+        let interval = if requests_per_second == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs(1) / requests_per_second
+        };
+
+        Self {
+            permits: Semaphore::new(max_concurrent),
+            max_concurrent,
+            interval,
+            next_start: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// The limiter shared by the API clients
+    pub fn global() -> &'static RateLimiter {
+        static LIMITER: OnceLock<RateLimiter> = OnceLock::new();
+        LIMITER.get_or_init(|| {
+            RateLimiter::new(get_max_concurrent_requests(), get_requests_per_second())
+        })
+    }
+
+    /// Maximum number of requests in flight at once
+    pub fn max_concurrent(&self) -> usize {
+        self.max_concurrent
+    }
+
+    /// Wait until a request may start; the request is counted as in flight
+    /// until the permit is dropped
+    pub async fn acquire(&self) -> SemaphorePermit<'_> {
+        let permit = self
+            .permits
+            .acquire()
+            .await
+            .expect("rate limiter semaphore is never closed");
+
+        if !self.interval.is_zero() {
+            let start = {
+                let mut next_start = self.next_start.lock().await;
+                let start = (*next_start).max(Instant::now());
+                *next_start = start + self.interval;
+                start
+            };
+            tokio::time::sleep_until(start).await;
+        }
+
+        permit
+    }
+}
+
+/// Returns the maximum number of concurrent API requests.
+///
+/// Environment variable: API_MAX_CONCURRENT_REQUESTS
+pub fn get_max_concurrent_requests() -> usize {
+    std::env::var("API_MAX_CONCURRENT_REQUESTS")
+        .ok()
+        .and_then(|s| s.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_MAX_CONCURRENT_REQUESTS)
+}
+
+/// Returns the maximum number of API requests started per second.
+///
+/// Environment variable: API_REQUESTS_PER_SECOND
+pub fn get_requests_per_second() -> u32 {
+    std::env::var("API_REQUESTS_PER_SECOND")
+        .ok()
+        .and_then(|s| s.parse::<u32>().ok())
+        .unwrap_or(DEFAULT_REQUESTS_PER_SECOND)
+}
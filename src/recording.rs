@@ -0,0 +1,389 @@
+use crate::config;
+use crate::mock::{
+    fixture_name, MOCK_CALENDAR_PREFIX, MOCK_GMAIL_PREFIX, MOCK_PEOPLE_PREFIX, MOCK_TASKS_PREFIX,
+    MOCK_DRIVE_PREFIX, MOCK_TOKEN_PATH,
+};
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{header, HeaderMap, Method, StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use axum::{Json, Router};
+use log::{debug, info, warn};
+use serde_json::{json, Value};
+use std::io::Write;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+
+// Record/replay of Google API interactions
+//
+// The recording proxy sits between the API clients and Google in the same way
+// the mock server does: it listens on the mock path prefixes, forwards every
+// request to the real API and writes each successful JSON response to a
+// fixture file named by `mock::fixture_name`. A directory of recordings can
+// therefore be replayed with `MockServer::replay` (or `--mock --mock-fixtures`)
+// and the production parsing code sees exactly what Google returned.
+//
+// Responses are sanitized before they are written: OAuth tokens are redacted
+// and any configured strings (such as the account's email address) are
+// replaced, including inside base64 encoded message bodies. Token endpoint
+// responses are forwarded but never recorded. Each interaction is also logged
+// to `interactions.jsonl` in the recording directory.
+//
+// Query parameters are not part of the fixture name, so when the same path is
+// requested more than once the last response wins.
+
+/// Replacement for redacted values in recorded fixtures
+pub const REDACTED: &str = "REDACTED";
+
+/// Name of the interaction log written alongside the fixtures
+pub const INTERACTION_LOG: &str = "interactions.jsonl";
+
+/// JSON keys whose string values are always redacted
+const REDACTED_KEYS: &[&str] = &["access_token", "refresh_token", "id_token", "client_secret"];
+
+/// JSON keys holding base64url encoded message content in Gmail responses
+const ENCODED_KEYS: &[&str] = &["data", "raw"];
+
+/// Real API endpoints the recording proxy forwards to
+#[derive(Debug, Clone)]
+pub struct Upstreams {
+    pub gmail: String,
+    pub calendar: String,
+    pub people: String,
+    pub tasks: String,
+    pub drive: String,
+    pub token: String,
+}
+
+impl Default for Upstreams {
+    /// Use the currently configured endpoints. This must be called before the
+    /// proxy is installed, since installing replaces them.
+    fn default() -> Self {
+        Self {
+            gmail: config::get_gmail_api_base_url(),
+            calendar: config::get_calendar_api_base_url(),
+            people: config::get_people_api_base_url(),
+            tasks: config::get_tasks_api_base_url(),
+            drive: config::get_drive_api_base_url(),
+            token: config::get_oauth_token_url(),
+        }
+    }
+}
+
+/// Removes credentials and personal data from recorded responses
+#[derive(Debug, Clone, Default)]
+pub struct Sanitizer {
+    replacements: Vec<(String, String)>,
+}
+
+impl Sanitizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace every occurrence of `from` with `to`
+    pub fn replace(mut self, from: &str, to: &str) -> Self {
+        if !from.is_empty() {
+            self.replacements.push((from.to_string(), to.to_string()));
+        }
+        self
+    }
+
+    /// Replace every occurrence of `text` with [`REDACTED`]
+    pub fn redact(self, text: &str) -> Self {
+        self.replace(text, REDACTED)
+    }
+
+    /// Apply the string replacements to plain text
+    pub fn sanitize_str(&self, text: &str) -> String {
+        self.replacements
+            .iter()
+            .fold(text.to_string(), |text, (from, to)| text.replace(from, to))
+    }
+
+    /// Sanitize a JSON response in place
+    pub fn sanitize(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    match value {
+                        Value::String(_) if REDACTED_KEYS.contains(&key.as_str()) => {
+                            *value = Value::String(REDACTED.to_string());
+                        }
+                        Value::String(encoded) if ENCODED_KEYS.contains(&key.as_str()) => {
+                            *encoded = self.sanitize_encoded(encoded);
+                        }
+                        _ => self.sanitize(value),
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.sanitize(item)),
+            Value::String(text) => *text = self.sanitize_str(text),
+            _ => {}
+        }
+    }
+
+    // Gmail message bodies are base64url encoded, so replacements have to be
+    // applied to the decoded text. Binary content is left untouched.
+    fn sanitize_encoded(&self, encoded: &str) -> String {
+        let decoded = base64::decode_config(encoded, base64::URL_SAFE)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok());
+
+        match decoded {
+            Some(text) => {
+                let sanitized = self.sanitize_str(&text);
+                if sanitized == text {
+                    encoded.to_string()
+                } else {
+                    base64::encode_config(sanitized.as_bytes(), base64::URL_SAFE)
+                }
+            }
+            None => encoded.to_string(),
+        }
+    }
+}
+
+struct ProxyState {
+    client: reqwest::Client,
+    dir: PathBuf,
+    upstreams: Upstreams,
+    sanitizer: Sanitizer,
+}
+
+impl ProxyState {
+    // Map a proxy path to the real URL, and whether the response is recorded
+    fn upstream_url(&self, path: &str) -> Option<(String, bool)> {
+        if path == MOCK_TOKEN_PATH {
+            return Some((self.upstreams.token.clone(), false));
+        }
+
+        [
+            (MOCK_GMAIL_PREFIX, &self.upstreams.gmail),
+            (MOCK_CALENDAR_PREFIX, &self.upstreams.calendar),
+            (MOCK_PEOPLE_PREFIX, &self.upstreams.people),
+            (MOCK_TASKS_PREFIX, &self.upstreams.tasks),
+            (MOCK_DRIVE_PREFIX, &self.upstreams.drive),
+        ]
+        .iter()
+        .find_map(|(prefix, base)| {
+            path.strip_prefix(prefix)
+                .map(|rest| (format!("{}{}", base.trim_end_matches('/'), rest), true))
+        })
+    }
+
+    fn record(&self, method: &Method, uri: &Uri, status: u16, body: &[u8]) -> std::io::Result<()> {
+        let name = fixture_name(method, uri.path());
+        let query = uri.query().map(|query| self.sanitizer.sanitize_str(query));
+
+        let fixture = match serde_json::from_slice::<Value>(body) {
+            Ok(mut value) if (200..300).contains(&status) => {
+                self.sanitizer.sanitize(&mut value);
+                let path = self.dir.join(&name);
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(&path, serde_json::to_string_pretty(&value)?)?;
+                Some(name)
+            }
+            _ => {
+                warn!(
+                    "Not recording {} {}: status {} or non-JSON body",
+                    method,
+                    uri.path(),
+                    status
+                );
+                None
+            }
+        };
+
+        let entry = json!({
+            "method": method.as_str(),
+            "path": uri.path(),
+            "query": query,
+            "status": status,
+            "fixture": fixture,
+        });
+        let mut log = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.dir.join(INTERACTION_LOG))?;
+        writeln!(log, "{}", entry)
+    }
+}
+
+/// A running recording proxy; the proxy stops when this is dropped
+pub struct RecordingProxy {
+    addr: SocketAddr,
+    dir: PathBuf,
+    handle: JoinHandle<()>,
+}
+
+impl RecordingProxy {
+    /// Start a proxy on an ephemeral local port that records into `dir`,
+    /// creating it if needed
+    pub async fn start(
+        dir: PathBuf,
+        upstreams: Upstreams,
+        sanitizer: Sanitizer,
+    ) -> std::io::Result<Self> {
+        std::fs::create_dir_all(&dir)?;
+
+        let state = Arc::new(ProxyState {
+            client: reqwest::Client::new(),
+            dir: dir.clone(),
+            upstreams,
+            sanitizer,
+        });
+        let app = Router::new().fallback(proxy_handler).with_state(state);
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let handle = tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, app).await {
+                warn!("Recording proxy stopped: {}", e);
+            }
+        });
+
+        info!(
+            "Recording proxy listening on http://{}, writing to {}",
+            addr,
+            dir.display()
+        );
+        Ok(Self { addr, dir, handle })
+    }
+
+    /// Base URL of the proxy, e.g. `http://127.0.0.1:4321`
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Directory recordings are written to
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Point the API clients at this proxy. Credentials are left alone, since
+    /// requests are forwarded to the real APIs.
+    pub fn install(&self) {
+        let url = self.url();
+        std::env::set_var(
+            "GMAIL_API_BASE_URL",
+            format!("{}{}", url, MOCK_GMAIL_PREFIX),
+        );
+        std::env::set_var(
+            "CALENDAR_API_BASE_URL",
+            format!("{}{}", url, MOCK_CALENDAR_PREFIX),
+        );
+        std::env::set_var(
+            "PEOPLE_API_BASE_URL",
+            format!("{}{}", url, MOCK_PEOPLE_PREFIX),
+        );
+        std::env::set_var(
+            "TASKS_API_BASE_URL",
+            format!("{}{}", url, MOCK_TASKS_PREFIX),
+        );
+        std::env::set_var(
+            "DRIVE_API_BASE_URL",
+            format!("{}{}", url, MOCK_DRIVE_PREFIX),
+        );
+        std::env::set_var("OAUTH_TOKEN_URL", format!("{}{}", url, MOCK_TOKEN_PATH));
+    }
+}
+
+impl Drop for RecordingProxy {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+async fn proxy_handler(
+    State(state): State<Arc<ProxyState>>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let Some((url, record)) = state.upstream_url(uri.path()) else {
+        return google_error(
+            StatusCode::NOT_FOUND,
+            &format!("No upstream API for {}", uri.path()),
+        );
+    };
+    let url = match uri.query() {
+        Some(query) => format!("{}?{}", url, query),
+        None => url,
+    };
+    debug!("Recording {} {} -> {}", method, uri.path(), url);
+
+    let upstream_method = match reqwest::Method::from_bytes(method.as_str().as_bytes()) {
+        Ok(method) => method,
+        Err(e) => return google_error(StatusCode::METHOD_NOT_ALLOWED, &e.to_string()),
+    };
+    let mut request = state
+        .client
+        .request(upstream_method, &url)
+        .body(body.to_vec());
+    for name in [header::AUTHORIZATION, header::CONTENT_TYPE, header::ACCEPT] {
+        if let Some(value) = headers.get(&name).and_then(|value| value.to_str().ok()) {
+            request = request.header(name.as_str(), value);
+        }
+    }
+
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(e) => {
+            return google_error(
+                StatusCode::BAD_GATEWAY,
+                &format!("Failed to reach {}: {}", url, e),
+            )
+        }
+    };
+    let status = response.status().as_u16();
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("application/json")
+        .to_string();
+    let bytes = match response.bytes().await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            return google_error(
+                StatusCode::BAD_GATEWAY,
+                &format!("Failed to read response from {}: {}", url, e),
+            )
+        }
+    };
+
+    if record {
+        if let Err(e) = state.record(&method, &uri, status, &bytes) {
+            warn!("Failed to record {} {}: {}", method, uri.path(), e);
+        }
+    }
+
+    // The live caller always gets the unsanitized response
+    let status = StatusCode::from_u16(status).unwrap_or(StatusCode::BAD_GATEWAY);
+    (
+        status,
+        [(header::CONTENT_TYPE, content_type)],
+        bytes.to_vec(),
+    )
+        .into_response()
+}
+
+fn google_error(status: StatusCode, message: &str) -> Response {
+    (
+        status,
+        Json(json!({
+            "error": {
+                "code": status.as_u16(),
+                "message": message,
+                "status": status.canonical_reason().unwrap_or("UNKNOWN")
+            }
+        })),
+    )
+        .into_response()
+}
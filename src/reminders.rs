@@ -0,0 +1,172 @@
+use crate::api::{CalendarApi, GmailApi};
+use crate::calendar_api::CalendarEvent;
+use crate::errors::Result;
+use chrono::{DateTime, Duration, Utc};
+use log::{debug, info};
+use serde::Serialize;
+
+// Follow-up reminders
+//
+// A reminder labels an email and creates a short calendar event at the
+// requested time whose description links back to it, so the user is
+// notified by their calendar and can find the email again from the label.
+// Archiving the email as well gives snooze-like behaviour: it leaves the
+// inbox until the reminder comes up.
+
+/// Label applied to emails with a reminder when none is given
+pub const DEFAULT_FOLLOW_UP_LABEL: &str = "Follow-up";
+
+/// Length of the calendar event created for a reminder
+pub const REMINDER_EVENT_MINUTES: i64 = 15;
+
+/// What to remind the user about, and when
+#[derive(Debug, Clone)]
+pub struct ReminderRequest {
+    pub message_id: String,
+    pub remind_at: DateTime<Utc>,
+    /// Extra text for the event description
+    pub note: Option<String>,
+    /// Label to apply, `DEFAULT_FOLLOW_UP_LABEL` when not given
+    pub label: Option<String>,
+    /// Remove the email from the inbox until the reminder
+    pub archive: bool,
+    pub calendar_id: String,
+}
+
+/// A reminder that was set up
+#[derive(Debug, Clone, Serialize)]
+pub struct Reminder {
+    pub message_id: String,
+    pub subject: Option<String>,
+    pub remind_at: DateTime<Utc>,
+    pub label: String,
+    pub label_id: String,
+    pub archived: bool,
+    pub calendar_id: String,
+    pub event_id: Option<String>,
+    pub event_link: Option<String>,
+    pub email_link: String,
+}
+
+/// Link that opens an email in Gmail's web interface
+pub fn email_link(message_id: &str) -> String {
+    format!("https://mail.google.com/mail/u/0/#all/{}", message_id)
+}
+
+/// Parse a reminder time given in RFC3339, such as `2024-03-01T09:00:00Z`,
+/// or relative to `now` as a number of minutes, hours, days or weeks, such
+/// as `30m`, `2h`, `3d` or `1w`
+pub fn parse_remind_at(
+    when: &str,
+    now: DateTime<Utc>,
+) -> std::result::Result<DateTime<Utc>, String> {
+    let when = when.trim();
+    if let Ok(time) = DateTime::parse_from_rfc3339(when) {
+        return Ok(time.with_timezone(&Utc));
+    }
+
+    let relative = when.strip_prefix("in ").unwrap_or(when).replace(' ', "");
+    let split = relative
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(relative.len());
+    let (amount, unit) = relative.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| invalid_time(when))?;
+    let offset = match unit {
+        "m" | "min" | "mins" | "minute" | "minutes" => Duration::try_minutes(amount),
+        "h" | "hour" | "hours" => Duration::try_hours(amount),
+        "d" | "day" | "days" => Duration::try_days(amount),
+        "w" | "week" | "weeks" => Duration::try_weeks(amount),
+        _ => return Err(invalid_time(when)),
+    };
+    // Amounts too large for a date are as invalid as an unknown unit
+    offset
+        .and_then(|offset| now.checked_add_signed(offset))
+        .ok_or_else(|| invalid_time(when))
+}
+
+fn invalid_time(when: &str) -> String {
+    format!(
+        "Invalid reminder time '{}': expected RFC3339 (e.g. 2024-03-01T09:00:00Z) \
+        or a relative time such as 30m, 2h, 3d or 1w",
+        when
+    )
+}
+
+/// Set up a reminder for an email.
+///
+/// The calendar event is created before the email is labelled, so a failure
+/// leaves the mailbox unchanged.
+pub async fn create_reminder<G, C>(
+    gmail: &G,
+    calendar: &C,
+    request: ReminderRequest,
+) -> Result<Reminder>
+where
+    G: GmailApi + ?Sized,
+    C: CalendarApi + ?Sized,
+{
+    let email = gmail.get_message_details(&request.message_id).await?;
+    let subject = email.subject.clone();
+    let link = email_link(&email.id);
+    debug!(
+        "Creating reminder for {} at {}",
+        email.id, request.remind_at
+    );
+
+    let mut description = String::new();
+    if let Some(note) = &request.note {
+        description.push_str(note);
+        description.push_str("\n\n");
+    }
+    description.push_str(&format!(
+        "Follow up on the email \"{}\"",
+        subject.as_deref().unwrap_or("(no subject)")
+    ));
+    if let Some(from) = &email.from {
+        description.push_str(&format!(" from {}", from));
+    }
+    description.push_str(&format!(".\n\nOpen in Gmail: {}", link));
+
+    let event = CalendarEvent {
+        summary: format!(
+            "Follow up: {}",
+            subject.as_deref().unwrap_or("(no subject)")
+        ),
+        description: Some(description),
+        start_time: request.remind_at,
+        end_time: request.remind_at + Duration::minutes(REMINDER_EVENT_MINUTES),
+        private_properties: crate::event_properties::source_message_properties(&request.message_id),
+        ..Default::default()
+    };
+    let event = calendar.create_event(&request.calendar_id, event).await?;
+
+    let label = request
+        .label
+        .unwrap_or_else(|| DEFAULT_FOLLOW_UP_LABEL.to_string());
+    let label_id = gmail.get_or_create_label(&label).await?;
+    let remove = if request.archive {
+        vec!["INBOX".to_string()]
+    } else {
+        Vec::new()
+    };
+    gmail
+        .modify_labels(&email.id, std::slice::from_ref(&label_id), &remove)
+        .await?;
+
+    info!(
+        "Reminder for {} set for {} (event {:?})",
+        email.id, request.remind_at, event.id
+    );
+    Ok(Reminder {
+        message_id: email.id,
+        subject,
+        remind_at: request.remind_at,
+        label,
+        label_id,
+        archived: request.archive,
+        calendar_id: request.calendar_id,
+        event_id: event.id,
+        event_link: event.html_link,
+        email_link: link,
+    })
+}
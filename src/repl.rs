@@ -0,0 +1,177 @@
+use crate::server::GmailServer;
+use mcp_attr::client::McpClient;
+use mcp_attr::schema::{CallToolRequestParams, CallToolResult, CallToolResultContentItem, Tool};
+use serde_json::{Map, Value};
+use std::io::Write;
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+const REPL_HELP: &str = r#"Commands:
+  tools                    List available tools
+  describe <tool>          Show a tool's description and argument schema
+  <tool> [json-arguments]  Invoke a tool, e.g. list_emails {"max_results": 3}
+  help                     Show this help
+  quit | exit              Leave the REPL"#;
+
+/// A parsed line of REPL input
+#[derive(Debug, PartialEq)]
+pub enum ReplCommand {
+    Empty,
+    Help,
+    Quit,
+    ListTools,
+    Describe(String),
+    Call {
+        name: String,
+        arguments: Option<Map<String, Value>>,
+    },
+}
+
+/// Parse a line of REPL input into a command
+///
+/// Tool arguments must be a JSON object; anything after the tool name is
+/// parsed as JSON.
+pub fn parse_line(line: &str) -> Result<ReplCommand, String> {
+    let line = line.trim();
+    if line.is_empty() {
+        return Ok(ReplCommand::Empty);
+    }
+
+    let (head, rest) = match line.split_once(char::is_whitespace) {
+        Some((head, rest)) => (head, rest.trim()),
+        None => (line, ""),
+    };
+
+    match head {
+        "help" | "?" => Ok(ReplCommand::Help),
+        "quit" | "exit" => Ok(ReplCommand::Quit),
+        "tools" | "list" => Ok(ReplCommand::ListTools),
+        "describe" => {
+            if rest.is_empty() {
+                Err("Usage: describe <tool>".to_string())
+            } else {
+                Ok(ReplCommand::Describe(rest.to_string()))
+            }
+        }
+        name => {
+            let arguments = if rest.is_empty() {
+                None
+            } else {
+                match serde_json::from_str::<Value>(rest) {
+                    Ok(Value::Object(map)) => Some(map),
+                    Ok(_) => return Err("Tool arguments must be a JSON object".to_string()),
+                    Err(e) => return Err(format!("Invalid JSON arguments: {}", e)),
+                }
+            };
+            Ok(ReplCommand::Call {
+                name: name.to_string(),
+                arguments,
+            })
+        }
+    }
+}
+
+/// Render a tool call result for display, pretty-printing JSON text content
+pub fn format_tool_result(result: &CallToolResult) -> String {
+    let mut output = Vec::new();
+    if result.is_error == Some(true) {
+        output.push("Tool reported an error:".to_string());
+    }
+
+    for item in &result.content {
+        match item {
+            CallToolResultContentItem::TextContent(text) => {
+                let rendered = serde_json::from_str::<Value>(&text.text)
+                    .ok()
+                    .and_then(|v| serde_json::to_string_pretty(&v).ok())
+                    .unwrap_or_else(|| text.text.clone());
+                output.push(rendered);
+            }
+            CallToolResultContentItem::ImageContent(image) => {
+                output.push(format!("<image: {}>", image.mime_type));
+            }
+            CallToolResultContentItem::EmbeddedResource(_) => {
+                output.push("<embedded resource>".to_string());
+            }
+        }
+    }
+
+    output.join("\n")
+}
+
+fn format_tool_summary(tool: &Tool) -> String {
+    let summary = tool
+        .description
+        .as_deref()
+        .and_then(|d| d.lines().map(str::trim).find(|l| !l.is_empty()))
+        .unwrap_or("");
+    format!("  {:<28} {}", tool.name, summary)
+}
+
+/// Run an interactive REPL against an in-process MCP server
+pub async fn run_repl() -> Result<(), String> {
+    let client = McpClient::with_server(GmailServer::new())
+        .await
+        .map_err(|e| format!("Failed to start in-process MCP server: {}", e))?;
+
+    let tools = client
+        .tools_list(None)
+        .await
+        .map_err(|e| format!("Failed to list tools: {}", e))?
+        .tools;
+
+    println!(
+        "Gmail MCP REPL - {} tools available. Type 'help' for commands.",
+        tools.len()
+    );
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    loop {
+        print!("mcp> ");
+        let _ = std::io::stdout().flush();
+
+        let line = match lines.next_line().await {
+            Ok(Some(line)) => line,
+            Ok(None) => break,
+            Err(e) => return Err(format!("Failed to read input: {}", e)),
+        };
+
+        match parse_line(&line) {
+            Ok(ReplCommand::Empty) => {}
+            Ok(ReplCommand::Help) => println!("{}", REPL_HELP),
+            Ok(ReplCommand::Quit) => break,
+            Ok(ReplCommand::ListTools) => {
+                for tool in &tools {
+                    println!("{}", format_tool_summary(tool));
+                }
+            }
+            Ok(ReplCommand::Describe(name)) => match tools.iter().find(|t| t.name == name) {
+                Some(tool) => {
+                    println!("{}\n", tool.description.as_deref().unwrap_or("").trim());
+                    println!(
+                        "Arguments schema:\n{}",
+                        serde_json::to_string_pretty(&tool.input_schema).unwrap_or_default()
+                    );
+                }
+                None => println!("Unknown tool: {}", name),
+            },
+            Ok(ReplCommand::Call { name, arguments }) => {
+                if !tools.iter().any(|t| t.name == name) {
+                    println!("Unknown tool: {}. Type 'tools' to list available tools.", name);
+                    continue;
+                }
+
+                let params = CallToolRequestParams { name, arguments };
+                match client.tools_call(params).await {
+                    Ok(result) => println!("{}", format_tool_result(&result)),
+                    Err(e) => match e.error_object() {
+                        Some(obj) => println!("Error {}: {}", obj.code.0, obj.message),
+                        None => println!("Error: {}", e),
+                    },
+                }
+            }
+            Err(e) => println!("{}", e),
+        }
+    }
+
+    Ok(())
+}
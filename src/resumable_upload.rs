@@ -0,0 +1,258 @@
+use crate::errors::{retry_after_header, GmailApiError, GmailResult};
+use crate::gmail_api::error_for_status;
+use crate::rate_limit::RateLimiter;
+use log::{debug, info, warn};
+use reqwest::header::{HeaderMap, LOCATION, RANGE};
+use reqwest::{Client, StatusCode};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::time::Duration;
+
+// Resumable uploads
+//
+// Messages over the resumable threshold are uploaded with Gmail's resumable
+// upload protocol rather than in one request. The upload opens a session with
+// the message's metadata, then sends the raw message in chunks. When a chunk
+// fails, the session is asked how much of the message it holds and the upload
+// carries on from there, so a flaky connection costs a chunk rather than the
+// whole message. Each chunk is tried a few times, with a growing pause
+// between attempts, before the upload gives up.
+
+/// Default size above which messages are uploaded in chunks, in bytes
+pub const DEFAULT_RESUMABLE_THRESHOLD_BYTES: usize = 5 * 1024 * 1024;
+
+/// Chunk sizes are rounded up to a multiple of this, as Google requires
+pub const UPLOAD_CHUNK_ALIGNMENT: usize = 256 * 1024;
+
+/// Default size of each uploaded chunk, in bytes
+pub const DEFAULT_UPLOAD_CHUNK_BYTES: usize = 8 * UPLOAD_CHUNK_ALIGNMENT;
+
+/// Most times one chunk is tried before the upload fails
+pub const MAX_CHUNK_ATTEMPTS: u32 = 4;
+
+// Pause before the first retry of a chunk, doubled for each later one
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(250);
+
+// What a request in an upload session did
+enum Progress {
+    // The session holds this many bytes of the message
+    Received(usize),
+    // The message is uploaded; Gmail's response body
+    Complete(String),
+    // The request failed in a way worth retrying
+    Interrupted(String),
+}
+
+/// Upload a raw RFC 822 message to a Gmail upload endpoint in chunks of
+/// `chunk_size` bytes, with `metadata` (such as the thread ID) as the
+/// resource body, and parse Gmail's response
+pub async fn upload<T: DeserializeOwned>(
+    client: &Client,
+    token: &str,
+    url: &str,
+    metadata: &Value,
+    message: &[u8],
+    chunk_size: usize,
+) -> GmailResult<T> {
+    let total = message.len();
+    let chunk_size = align_chunk_size(chunk_size);
+    let session = start_session(client, token, url, metadata, total).await?;
+    info!(
+        "Uploading {} byte message in chunks of {} bytes",
+        total, chunk_size
+    );
+
+    let mut offset = 0;
+    let mut attempts = 0;
+    loop {
+        let end = (offset + chunk_size).min(total);
+        let range = format!("bytes {}-{}/{}", offset, end.saturating_sub(1), total);
+        debug!("Uploading {}", range);
+        let reason = match put(
+            client,
+            token,
+            &session,
+            range,
+            message[offset..end].to_vec(),
+        )
+        .await?
+        {
+            Progress::Complete(text) => return parse_response(&text),
+            Progress::Received(received) if received > offset => {
+                offset = received.min(total);
+                attempts = 0;
+                continue;
+            }
+            Progress::Received(_) => "the chunk was not received".to_string(),
+            Progress::Interrupted(reason) => reason,
+        };
+
+        attempts += 1;
+        if attempts >= MAX_CHUNK_ATTEMPTS {
+            return Err(GmailApiError::NetworkError(format!(
+                "Upload failed at byte {} of {} after {} attempts: {}",
+                offset, total, attempts, reason
+            )));
+        }
+        let delay = RETRY_BASE_DELAY * 2u32.pow(attempts - 1);
+        warn!(
+            "Upload interrupted at byte {} of {} ({}); retrying in {}ms",
+            offset,
+            total,
+            reason,
+            delay.as_millis()
+        );
+        tokio::time::sleep(delay).await;
+
+        // Part of the chunk may have arrived, so carry on from wherever the
+        // session got to
+        match put(
+            client,
+            token,
+            &session,
+            format!("bytes */{}", total),
+            Vec::new(),
+        )
+        .await?
+        {
+            Progress::Complete(text) => return parse_response(&text),
+            Progress::Received(received) => offset = received.min(total),
+            Progress::Interrupted(reason) => debug!("Upload status unknown: {}", reason),
+        }
+    }
+}
+
+/// Round a chunk size up to a whole number of alignment units, and at least
+/// one
+pub fn align_chunk_size(chunk_size: usize) -> usize {
+    chunk_size.max(1).div_ceil(UPLOAD_CHUNK_ALIGNMENT) * UPLOAD_CHUNK_ALIGNMENT
+}
+
+// Open an upload session, returning its URL
+async fn start_session(
+    client: &Client,
+    token: &str,
+    url: &str,
+    metadata: &Value,
+    total: usize,
+) -> GmailResult<String> {
+    debug!("Starting resumable upload at: {}", url);
+    let _permit = RateLimiter::global().acquire().await;
+    let response = client
+        .post(url)
+        .query(&[("uploadType", "resumable")])
+        .header("Authorization", format!("Bearer {}", token))
+        .header("X-Upload-Content-Type", "message/rfc822")
+        .header("X-Upload-Content-Length", total.to_string())
+        .json(metadata)
+        .send()
+        .await
+        .map_err(|e| GmailApiError::NetworkError(e.to_string()))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let retry_after = retry_after_header(response.headers());
+        let error_text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "<no response body>".to_string());
+        return Err(error_for_status(status, retry_after.as_deref(), error_text));
+    }
+
+    response
+        .headers()
+        .get(LOCATION)
+        .and_then(|location| location.to_str().ok())
+        .map(String::from)
+        .ok_or_else(|| {
+            GmailApiError::ApiError("Gmail did not return an upload session URL".to_string())
+        })
+}
+
+// Send part of the message, or with an empty body and a `*` range, ask how
+// much of it the session holds
+async fn put(
+    client: &Client,
+    token: &str,
+    session: &str,
+    range: String,
+    body: Vec<u8>,
+) -> GmailResult<Progress> {
+    let _permit = RateLimiter::global().acquire().await;
+    let response = match client
+        .put(session)
+        .header("Authorization", format!("Bearer {}", token))
+        .header("Content-Range", range)
+        .body(body)
+        .send()
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => return Ok(Progress::Interrupted(e.to_string())),
+    };
+
+    let status = response.status();
+    // Google answers 308 while the upload is incomplete
+    if status == StatusCode::PERMANENT_REDIRECT {
+        return Ok(Progress::Received(received_bytes(response.headers())));
+    }
+    if status.is_success() {
+        return Ok(match response.text().await {
+            Ok(text) => Progress::Complete(text),
+            Err(e) => Progress::Interrupted(e.to_string()),
+        });
+    }
+    if status.is_server_error() || status == StatusCode::REQUEST_TIMEOUT {
+        return Ok(Progress::Interrupted(format!("status {}", status)));
+    }
+
+    let retry_after = retry_after_header(response.headers());
+    let error_text = response
+        .text()
+        .await
+        .unwrap_or_else(|_| "<no response body>".to_string());
+    Err(error_for_status(status, retry_after.as_deref(), error_text))
+}
+
+// Bytes the session holds, from a `Range: bytes=0-N` header; none without one
+fn received_bytes(headers: &HeaderMap) -> usize {
+    headers
+        .get(RANGE)
+        .and_then(|range| range.to_str().ok())
+        .and_then(|range| range.rsplit_once('-'))
+        .and_then(|(_, last)| last.trim().parse::<usize>().ok())
+        .map(|last| last + 1)
+        .unwrap_or(0)
+}
+
+fn parse_response<T: DeserializeOwned>(text: &str) -> GmailResult<T> {
+    serde_json::from_str(text).map_err(|e| {
+        GmailApiError::MessageFormatError(format!("Failed to parse upload response: {}", e))
+    })
+}
+
+/// Returns the size above which messages are uploaded in chunks, in bytes.
+///
+/// Default is 5 MB if not configured.
+///
+/// Environment variable: RESUMABLE_UPLOAD_THRESHOLD_BYTES
+pub fn get_resumable_threshold() -> usize {
+    std::env::var("RESUMABLE_UPLOAD_THRESHOLD_BYTES")
+        .ok()
+        .and_then(|s| s.trim().parse::<usize>().ok())
+        .unwrap_or(DEFAULT_RESUMABLE_THRESHOLD_BYTES)
+}
+
+/// Returns the size of each uploaded chunk, rounded up to a multiple of
+/// 256 KB.
+///
+/// Default is 2 MB if not configured.
+///
+/// Environment variable: UPLOAD_CHUNK_BYTES
+pub fn get_upload_chunk_bytes() -> usize {
+    let chunk_size = std::env::var("UPLOAD_CHUNK_BYTES")
+        .ok()
+        .and_then(|s| s.trim().parse::<usize>().ok())
+        .unwrap_or(DEFAULT_UPLOAD_CHUNK_BYTES);
+    align_chunk_size(chunk_size)
+}
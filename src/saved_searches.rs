@@ -0,0 +1,135 @@
+use crate::errors::{SavedSearchError, SavedSearchResult};
+use crate::persist;
+use chrono::{DateTime, Utc};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+// Saved searches
+//
+// Gmail queries used again and again for triage, such as "waiting on me" or
+// "this week's invoices", can be saved under a name and run by that name in
+// later sessions. They are kept in a JSON file in the state directory, or
+// the user's config directory when none is set.
+
+/// Name of the saved search file, in the state directory
+pub const SAVED_SEARCHES_FILE: &str = "saved_searches.json";
+
+/// A Gmail search query saved under a name
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub name: String,
+    pub query: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub saved_at: DateTime<Utc>,
+}
+
+impl SavedSearch {
+    pub fn new(name: &str, query: &str, description: Option<String>) -> Self {
+        Self {
+            name: name.trim().to_string(),
+            query: query.trim().to_string(),
+            description: description.filter(|d| !d.trim().is_empty()),
+            saved_at: Utc::now(),
+        }
+    }
+
+    fn validate(&self) -> SavedSearchResult<()> {
+        if self.name.is_empty() {
+            return Err(SavedSearchError::InvalidSearch(
+                "Saved search name cannot be empty".to_string(),
+            ));
+        }
+        if self.query.is_empty() {
+            return Err(SavedSearchError::InvalidSearch(format!(
+                "Saved search '{}' needs a query",
+                self.name
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct SavedSearchFile {
+    searches: Vec<SavedSearch>,
+}
+
+/// Saved searches stored in a JSON file
+#[derive(Debug, Clone)]
+pub struct SavedSearchStore {
+    path: PathBuf,
+}
+
+impl SavedSearchStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// The store at `SAVED_SEARCHES_FILE`, or the default location
+    pub fn from_env() -> Self {
+        Self::new(get_saved_searches_path())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// All saved searches, sorted by name
+    pub fn list(&self) -> SavedSearchResult<Vec<SavedSearch>> {
+        let mut searches = self.load()?.searches;
+        searches.sort_by_key(|search| search.name.to_lowercase());
+        Ok(searches)
+    }
+
+    /// Find a saved search by name, ignoring case
+    pub fn get(&self, name: &str) -> SavedSearchResult<SavedSearch> {
+        self.load()?
+            .searches
+            .into_iter()
+            .find(|search| search.name.eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| SavedSearchError::NotFound(name.to_string()))
+    }
+
+    /// Save a search, replacing any with the same name. Returns whether a
+    /// search was replaced.
+    pub fn save(&self, search: SavedSearch) -> SavedSearchResult<bool> {
+        search.validate()?;
+
+        let mut file = self.load()?;
+        let before = file.searches.len();
+        file.searches
+            .retain(|existing| !existing.name.eq_ignore_ascii_case(&search.name));
+        let replaced = file.searches.len() < before;
+        info!(
+            "{} saved search '{}'",
+            if replaced { "Replacing" } else { "Saving" },
+            search.name
+        );
+        file.searches.push(search);
+        self.store(&file)?;
+        Ok(replaced)
+    }
+
+    fn load(&self) -> SavedSearchResult<SavedSearchFile> {
+        persist::load_json(&self.path).map_err(|e| self.storage_error(e))
+    }
+
+    fn store(&self, file: &SavedSearchFile) -> SavedSearchResult<()> {
+        persist::store_json(&self.path, file).map_err(|e| self.storage_error(e))
+    }
+
+    fn storage_error(&self, err: impl std::fmt::Display) -> SavedSearchError {
+        SavedSearchError::StorageError(format!("{}: {}", self.path.display(), err))
+    }
+}
+
+/// Returns the path of the saved search file.
+///
+/// Environment variable: SAVED_SEARCHES_FILE
+pub fn get_saved_searches_path() -> PathBuf {
+    std::env::var("SAVED_SEARCHES_FILE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| persist::default_path(dirs::config_dir(), SAVED_SEARCHES_FILE))
+}
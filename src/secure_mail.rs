@@ -0,0 +1,208 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+// Signed and encrypted mail
+//
+// PGP/MIME (RFC 3156) and S/MIME (RFC 8551) wrap a message in a
+// multipart/signed or multipart/encrypted part, or in S/MIME's opaque
+// application/pkcs7-mime; inline PGP armors the body text itself. Signatures
+// are detected but not verified, so the signer is whoever the From header
+// claims to be. Encrypted and opaque bodies are binary or ciphertext and are
+// never presented as text.
+
+const PGP_MESSAGE: &str = "-----BEGIN PGP MESSAGE-----";
+const PGP_SIGNED_MESSAGE: &str = "-----BEGIN PGP SIGNED MESSAGE-----";
+
+/// The signature on a signed message, as far as it can be read without
+/// verifying it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SignerInfo {
+    /// `pgp` or `smime`
+    pub protocol: String,
+    /// Address from the From header, which the signature claims to be from
+    pub address: Option<String>,
+    /// Hash algorithm named by the message, e.g. `pgp-sha256` or `sha-256`
+    pub micalg: Option<String>,
+}
+
+/// How a message is signed or encrypted
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MessageSecurity {
+    pub is_signed: bool,
+    pub is_encrypted: bool,
+    /// `pgp` or `smime`, when the message is signed or encrypted
+    pub protocol: Option<&'static str>,
+    pub micalg: Option<String>,
+    /// Whether the content is wrapped in a binary S/MIME structure, so that
+    /// the body cannot be read even when it is only signed
+    pub opaque: bool,
+}
+
+impl MessageSecurity {
+    /// Whether the body parts hold ciphertext or binary data rather than text
+    pub fn hides_body(&self) -> bool {
+        self.is_encrypted || self.opaque
+    }
+
+    /// Who the message claims to be signed by, if it is signed
+    pub fn signer(&self, from: Option<&str>) -> Option<SignerInfo> {
+        if !self.is_signed {
+            return None;
+        }
+        Some(SignerInfo {
+            protocol: self.protocol.unwrap_or("pgp").to_string(),
+            address: from
+                .map(crate::utils::parse_email_addresses)
+                .and_then(|addresses| addresses.into_iter().next()),
+            micalg: self.micalg.clone(),
+        })
+    }
+}
+
+// A parameter of a part's Content-Type header, e.g. `protocol` in
+// `multipart/signed; protocol="application/pgp-signature"`
+fn content_type_param(part: &Value, param: &str) -> Option<String> {
+    let header = part
+        .get("headers")?
+        .as_array()?
+        .iter()
+        .find(|header| {
+            header
+                .get("name")
+                .and_then(|name| name.as_str())
+                .is_some_and(|name| name.eq_ignore_ascii_case("Content-Type"))
+        })?
+        .get("value")?
+        .as_str()?;
+    header.split(';').skip(1).find_map(|pair| {
+        let (name, value) = pair.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case(param)
+            .then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+fn mime_type(part: &Value) -> String {
+    part.get("mimeType")
+        .and_then(|m| m.as_str())
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+fn subpart_types(part: &Value) -> Vec<String> {
+    part.get("parts")
+        .and_then(|p| p.as_array())
+        .map(|parts| parts.iter().map(mime_type).collect())
+        .unwrap_or_default()
+}
+
+// Whether a MIME type is `application/<subtype>`, or its older
+// `application/x-<subtype>` form
+fn is_application_type(mime_type: &str, subtype: &str) -> bool {
+    mime_type == format!("application/{}", subtype)
+        || mime_type == format!("application/x-{}", subtype)
+}
+
+// Security of a single MIME part, without looking inside its children
+fn inspect_part(part: &Value) -> MessageSecurity {
+    let mime_type = mime_type(part);
+    let protocol = content_type_param(part, "protocol")
+        .unwrap_or_default()
+        .to_lowercase();
+    let subparts = subpart_types(part);
+    let has_subpart = |subtype: &str| subparts.iter().any(|t| is_application_type(t, subtype));
+
+    match mime_type.as_str() {
+        "multipart/signed" => {
+            let smime = protocol.contains("pkcs7-signature") || has_subpart("pkcs7-signature");
+            MessageSecurity {
+                is_signed: true,
+                protocol: Some(if smime { "smime" } else { "pgp" }),
+                micalg: content_type_param(part, "micalg"),
+                ..Default::default()
+            }
+        }
+        "multipart/encrypted" => MessageSecurity {
+            is_encrypted: true,
+            protocol: Some(
+                if protocol.contains("pgp") || has_subpart("pgp-encrypted") {
+                    "pgp"
+                } else {
+                    "smime"
+                },
+            ),
+            ..Default::default()
+        },
+        t if is_application_type(t, "pkcs7-mime") => {
+            let smime_type = content_type_param(part, "smime-type")
+                .unwrap_or_default()
+                .to_lowercase();
+            MessageSecurity {
+                is_signed: smime_type == "signed-data",
+                // Enveloped data is the default when smime-type is missing
+                is_encrypted: smime_type.is_empty()
+                    || smime_type == "enveloped-data"
+                    || smime_type == "authenveloped-data",
+                protocol: Some("smime"),
+                opaque: true,
+                ..Default::default()
+            }
+        }
+        _ => MessageSecurity::default(),
+    }
+}
+
+/// Detect how a message is signed or encrypted from the structure of its
+/// payload and, for inline PGP, from its plain text body. Mailing lists that
+/// add a footer wrap a signed message in multipart/mixed, so the payload's
+/// direct parts are checked as well.
+pub fn inspect(payload: &Value, body_text: Option<&str>) -> MessageSecurity {
+    let mut security = inspect_part(payload);
+    if !security.is_signed && !security.is_encrypted && mime_type(payload) == "multipart/mixed" {
+        if let Some(found) = payload
+            .get("parts")
+            .and_then(|p| p.as_array())
+            .into_iter()
+            .flatten()
+            .map(inspect_part)
+            .find(|found| found.is_signed || found.is_encrypted)
+        {
+            security = found;
+        }
+    }
+
+    if security.protocol.is_none() {
+        if let Some(text) = body_text.map(str::trim_start) {
+            if text.starts_with(PGP_MESSAGE) {
+                security.is_encrypted = true;
+                security.protocol = Some("pgp");
+            } else if text.starts_with(PGP_SIGNED_MESSAGE) {
+                security.is_signed = true;
+                security.protocol = Some("pgp");
+                // The armor header names the hash, e.g. `Hash: SHA256`
+                security.micalg = text
+                    .lines()
+                    .skip(1)
+                    .take_while(|line| !line.trim().is_empty())
+                    .find_map(|line| line.strip_prefix("Hash:"))
+                    .map(|hash| format!("pgp-{}", hash.trim().to_lowercase()));
+            }
+        }
+    }
+    security
+}
+
+/// The part holding the readable content of a message: the first part of a
+/// multipart/signed payload, or the payload itself
+pub fn signed_content(payload: &Value) -> &Value {
+    if mime_type(payload) == "multipart/signed" {
+        if let Some(content) = payload
+            .get("parts")
+            .and_then(|p| p.as_array())
+            .and_then(|parts| parts.first())
+        {
+            return content;
+        }
+    }
+    payload
+}
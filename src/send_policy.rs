@@ -0,0 +1,301 @@
+use crate::errors::PolicyViolation;
+use crate::gmail_api::DraftEmail;
+use crate::persist;
+use crate::utils::parse_email_addresses;
+use chrono::{DateTime, Duration, Utc};
+use log::warn;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+// Sending policy
+//
+// Limits on the email the server sends, set in the environment so that an
+// assistant cannot change them: how many recipients one email can have,
+// which domains they can be at, how many emails can be sent in a day, and
+// whether an email can go to BCC recipients only. Every send is checked
+// against the policy just before it is made, whichever tool or command makes
+// it, and `send_email` also checks when it queues an email. No limits are
+// set by default.
+
+/// Name of the file sends are counted in for the daily limit, in the state
+/// directory
+pub const SEND_LOG_FILE: &str = "send-log.json";
+
+/// Limits on the email that can be sent
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SendPolicy {
+    /// Most recipients in one email, counting To, CC and BCC
+    pub max_recipients: Option<usize>,
+    /// Domains recipients must be at, or any when empty. A domain also
+    /// allows its subdomains.
+    pub allowed_domains: Vec<String>,
+    /// Most emails sent in any 24 hours
+    pub daily_limit: Option<usize>,
+    /// Refuse emails with BCC recipients and no To or CC recipient
+    pub forbid_bcc_only: bool,
+}
+
+impl SendPolicy {
+    /// The policy set in the environment
+    pub fn from_env() -> Self {
+        Self {
+            max_recipients: get_send_max_recipients(),
+            allowed_domains: get_send_allowed_domains(),
+            daily_limit: get_send_daily_limit(),
+            forbid_bcc_only: get_send_forbid_bcc_only(),
+        }
+    }
+
+    /// Check an email against every rule, counting earlier sends from `log`
+    pub fn check(&self, email: &DraftEmail, log: &SendLog) -> Result<(), PolicyViolation> {
+        self.check_recipients(email)?;
+        if self.daily_limit.is_some() {
+            self.check_daily_limit(&log.sent_in_last_day(), Utc::now())?;
+        }
+        Ok(())
+    }
+
+    /// Check an email against every rule just before it is sent, counting
+    /// the send in `log` when there is a daily limit. Returns the time the
+    /// send was counted at, to release if the send then fails.
+    pub fn reserve(
+        &self,
+        email: &DraftEmail,
+        log: &SendLog,
+    ) -> Result<Option<DateTime<Utc>>, PolicyViolation> {
+        self.check_recipients(email)?;
+        if self.daily_limit.is_none() {
+            return Ok(None);
+        }
+        log.reserve(self, Utc::now()).map(Some)
+    }
+
+    /// Check an email's recipients against the policy
+    pub fn check_recipients(&self, email: &DraftEmail) -> Result<(), PolicyViolation> {
+        let recipients = email.recipient_addresses();
+        if let Some(max) = self.max_recipients {
+            if recipients.len() > max {
+                return Err(PolicyViolation::TooManyRecipients {
+                    count: recipients.len(),
+                    max,
+                });
+            }
+        }
+
+        if !self.allowed_domains.is_empty() {
+            let outside: Vec<String> = recipients
+                .iter()
+                .filter(|address| !domain_allowed(address, &self.allowed_domains))
+                .cloned()
+                .collect();
+            if !outside.is_empty() {
+                return Err(PolicyViolation::DomainNotAllowed {
+                    addresses: outside,
+                    allowed: self.allowed_domains.clone(),
+                });
+            }
+        }
+
+        if self.forbid_bcc_only {
+            let visible = [Some(&email.to), email.cc.as_ref()]
+                .into_iter()
+                .flatten()
+                .any(|list| !parse_email_addresses(list).is_empty());
+            if !visible && !recipients.is_empty() {
+                return Err(PolicyViolation::BccOnly);
+            }
+        }
+        Ok(())
+    }
+
+    /// Check the daily limit, given the times of the sends in the 24 hours
+    /// before `now`
+    pub fn check_daily_limit(
+        &self,
+        sent: &[DateTime<Utc>],
+        now: DateTime<Utc>,
+    ) -> Result<(), PolicyViolation> {
+        let Some(max) = self.daily_limit else {
+            return Ok(());
+        };
+        let mut recent: Vec<DateTime<Utc>> = sent
+            .iter()
+            .copied()
+            .filter(|at| now.signed_duration_since(*at) < Duration::days(1))
+            .collect();
+        if recent.len() < max {
+            return Ok(());
+        }
+        // The next send is allowed once enough of these are a day old
+        recent.sort();
+        let frees_at = recent[recent.len() - max] + Duration::days(1);
+        Err(PolicyViolation::DailyLimitReached {
+            max,
+            retry_after: (frees_at - now).num_seconds().max(1) as u64,
+        })
+    }
+}
+
+// Whether an address is at one of the domains or their subdomains
+fn domain_allowed(address: &str, allowed: &[String]) -> bool {
+    let Some((_, domain)) = address.rsplit_once('@') else {
+        return false;
+    };
+    let domain = domain.to_lowercase();
+    allowed.iter().any(|allowed| {
+        domain == *allowed
+            || domain
+                .strip_suffix(allowed.as_str())
+                .is_some_and(|prefix| prefix.ends_with('.'))
+    })
+}
+
+/// Times of recent sends, kept for the daily limit
+#[derive(Debug)]
+pub struct SendLog {
+    path: PathBuf,
+    // Held while the file is read and written
+    lock: Mutex<()>,
+}
+
+impl SendLog {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// The log shared by every send, at `SEND_LOG_FILE` or the default
+    /// location
+    pub fn global() -> &'static SendLog {
+        static LOG: OnceLock<SendLog> = OnceLock::new();
+        LOG.get_or_init(|| SendLog::new(get_send_log_path()))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Times of the sends in the last 24 hours
+    pub fn sent_in_last_day(&self) -> Vec<DateTime<Utc>> {
+        let _lock = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        self.load()
+    }
+
+    /// Count a send about to be made at `now` if the policy's daily limit
+    /// allows it. The limit is checked and the send counted under one hold
+    /// of the lock, so that sends made at the same time can't together go
+    /// past it. A failure to save is only logged.
+    pub fn reserve(
+        &self,
+        policy: &SendPolicy,
+        now: DateTime<Utc>,
+    ) -> Result<DateTime<Utc>, PolicyViolation> {
+        let _lock = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut sent = self.load();
+        policy.check_daily_limit(&sent, now)?;
+        sent.push(now);
+        self.save(&sent);
+        Ok(now)
+    }
+
+    /// Stop counting a send reserved at `at` that failed
+    pub fn release(&self, at: DateTime<Utc>) {
+        let _lock = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut sent = self.load();
+        if let Some(index) = sent.iter().position(|sent_at| *sent_at == at) {
+            sent.remove(index);
+            self.save(&sent);
+        }
+    }
+
+    // Store the log, only logging a failure: sends are not refused for it
+    fn save(&self, sent: &[DateTime<Utc>]) {
+        if let Err(e) = self.store(sent) {
+            warn!(
+                "Failed to save the send log to {}: {}",
+                self.path.display(),
+                e
+            );
+        }
+    }
+
+    // Sends in the last 24 hours. A missing or unreadable file has none.
+    fn load(&self) -> Vec<DateTime<Utc>> {
+        let sent: Vec<DateTime<Utc>> = persist::load_json(&self.path).unwrap_or_else(|e| {
+            warn!(
+                "Ignoring unreadable send log at {}: {}",
+                self.path.display(),
+                e
+            );
+            Vec::new()
+        });
+        let now = Utc::now();
+        sent.into_iter()
+            .filter(|at| now.signed_duration_since(*at) < Duration::days(1))
+            .collect()
+    }
+
+    fn store(&self, sent: &[DateTime<Utc>]) -> std::io::Result<()> {
+        persist::store_json(&self.path, sent)
+    }
+}
+
+/// Returns the path of the file sends are counted in for the daily limit.
+///
+/// Environment variable: SEND_LOG_FILE
+pub fn get_send_log_path() -> PathBuf {
+    std::env::var("SEND_LOG_FILE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| persist::default_path(dirs::cache_dir(), SEND_LOG_FILE))
+}
+
+// A positive number from the environment, or None when unset or invalid
+fn get_limit(name: &str) -> Option<usize> {
+    std::env::var(name)
+        .ok()
+        .and_then(|s| s.trim().parse::<usize>().ok())
+        .filter(|limit| *limit > 0)
+}
+
+/// Returns the most recipients allowed in one email, if limited.
+///
+/// Environment variable: SEND_MAX_RECIPIENTS
+pub fn get_send_max_recipients() -> Option<usize> {
+    get_limit("SEND_MAX_RECIPIENTS")
+}
+
+/// Returns the domains recipients must be at, lowercased, or none when any
+/// domain is allowed.
+///
+/// Environment variable: SEND_ALLOWED_DOMAINS (comma-separated)
+pub fn get_send_allowed_domains() -> Vec<String> {
+    std::env::var("SEND_ALLOWED_DOMAINS")
+        .map(|domains| {
+            domains
+                .split(',')
+                .map(|domain| domain.trim().trim_start_matches('@').to_lowercase())
+                .filter(|domain| !domain.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Returns the most emails that can be sent in 24 hours, if limited.
+///
+/// Environment variable: SEND_DAILY_LIMIT
+pub fn get_send_daily_limit() -> Option<usize> {
+    get_limit("SEND_DAILY_LIMIT")
+}
+
+/// Returns whether emails with only BCC recipients are refused.
+///
+/// Default is false if not configured.
+///
+/// Environment variable: SEND_FORBID_BCC_ONLY
+pub fn get_send_forbid_bcc_only() -> bool {
+    std::env::var("SEND_FORBID_BCC_ONLY")
+        .map(|s| s.to_lowercase() == "true" || s == "1")
+        .unwrap_or(false)
+}
@@ -0,0 +1,253 @@
+use crate::gmail_api::EmailMessage;
+use crate::utils::parse_email_addresses;
+use serde::{Deserialize, Serialize};
+
+// Sender authentication
+//
+// The receiving server records whether a message passed SPF, DKIM and DMARC
+// in an Authentication-Results header (RFC 8601), and often the SPF result in
+// a Received-SPF header as well. Gmail adds its own Authentication-Results
+// above any that came with the message, so only the first is trusted; headers
+// further down could have been written by the sender. DMARC passes only when
+// SPF or DKIM passes for a domain aligned with the From address, so a passing
+// check for some other domain proves little about who wrote the message.
+
+/// The result of one authentication method
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AuthCheck {
+    /// e.g. `pass`, `fail`, `softfail`, `neutral` or `none`
+    pub result: String,
+    /// Domain the result applies to: the envelope sender's for SPF, the
+    /// signing domain for DKIM and the From domain for DMARC
+    pub domain: Option<String>,
+}
+
+impl AuthCheck {
+    pub fn passed(&self) -> bool {
+        self.result == "pass"
+    }
+
+    pub fn failed(&self) -> bool {
+        matches!(self.result.as_str(), "fail" | "permerror")
+    }
+}
+
+/// SPF, DKIM and DMARC results recorded by the receiving server
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct AuthenticationResults {
+    /// Server that checked the message, e.g. `mx.google.com`
+    pub authserv_id: Option<String>,
+    pub spf: Option<AuthCheck>,
+    pub dkim: Option<AuthCheck>,
+    pub dmarc: Option<AuthCheck>,
+}
+
+// Drop RFC 5322 comments, e.g. `(google.com: domain of ... designates ...)`
+fn strip_comments(value: &str) -> String {
+    let mut depth = 0usize;
+    value
+        .chars()
+        .filter(|c| match c {
+            '(' => {
+                depth += 1;
+                false
+            }
+            ')' => {
+                depth = depth.saturating_sub(1);
+                false
+            }
+            _ => depth == 0,
+        })
+        .collect()
+}
+
+// The domain of an address or domain property value, e.g. `example.com` for
+// `bounces@example.com` or `@example.com`
+fn domain_of(value: &str) -> Option<String> {
+    let domain = value.rsplit('@').next()?.trim().trim_end_matches('.');
+    (!domain.is_empty()).then(|| domain.to_lowercase())
+}
+
+impl AuthenticationResults {
+    /// Read the first Authentication-Results header and, for SPF when that
+    /// has none, the first Received-SPF header. `None` when neither says
+    /// anything.
+    pub fn parse(authentication_results: Option<&str>, received_spf: Option<&str>) -> Option<Self> {
+        let mut results = Self::default();
+
+        if let Some(header) = authentication_results {
+            let header = strip_comments(header);
+            let mut statements = header.split(';');
+            results.authserv_id = statements
+                .next()
+                .and_then(|id| id.split_whitespace().next())
+                .map(|id| id.to_lowercase());
+
+            for statement in statements {
+                let mut tokens = statement.split_whitespace();
+                let Some((method, result)) = tokens.next().and_then(|t| t.split_once('=')) else {
+                    continue;
+                };
+                let properties: Vec<(&str, &str)> =
+                    tokens.filter_map(|t| t.split_once('=')).collect();
+                let property = |names: &[&str]| {
+                    names.iter().find_map(|name| {
+                        properties
+                            .iter()
+                            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                            .and_then(|(_, value)| domain_of(value))
+                    })
+                };
+                let check = AuthCheck {
+                    result: result.to_lowercase(),
+                    domain: match method.to_lowercase().as_str() {
+                        "spf" => property(&["smtp.mailfrom", "smtp.helo"]),
+                        "dkim" => property(&["header.d", "header.i"]),
+                        "dmarc" => property(&["header.from"]),
+                        _ => None,
+                    },
+                };
+
+                let slot = match method.to_lowercase().as_str() {
+                    "spf" => &mut results.spf,
+                    "dkim" => &mut results.dkim,
+                    "dmarc" => &mut results.dmarc,
+                    _ => continue,
+                };
+                // With several DKIM signatures, one that passes is what counts
+                if slot.as_ref().is_none_or(|current| !current.passed()) {
+                    *slot = Some(check);
+                }
+            }
+        }
+
+        if results.spf.is_none() {
+            if let Some(header) = received_spf {
+                let header = strip_comments(header);
+                let mut tokens = header.split_whitespace();
+                if let Some(result) = tokens.next() {
+                    let domain = tokens
+                        .flat_map(|t| t.split(';'))
+                        .filter_map(|t| t.split_once('='))
+                        .find(|(key, _)| key.eq_ignore_ascii_case("envelope-from"))
+                        .and_then(|(_, value)| domain_of(value.trim_matches('"')));
+                    results.spf = Some(AuthCheck {
+                        result: result.to_lowercase(),
+                        domain,
+                    });
+                }
+            }
+        }
+
+        (results.spf.is_some() || results.dkim.is_some() || results.dmarc.is_some())
+            .then_some(results)
+    }
+}
+
+/// Whether two domains belong to the same organization, judged loosely as
+/// one being the other or a subdomain of it
+pub fn domains_aligned(a: &str, b: &str) -> bool {
+    let (a, b) = (a.to_lowercase(), b.to_lowercase());
+    a == b || a.ends_with(&format!(".{}", b)) || b.ends_with(&format!(".{}", a))
+}
+
+/// Overall judgement of whether a message comes from who it says
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SenderVerdict {
+    /// DMARC passed, or DKIM passed for the From domain
+    Authenticated,
+    /// Nothing failed outright, but nothing ties the message to the From
+    /// domain either
+    Unverified,
+    /// DMARC failed, or both SPF and DKIM failed
+    Failed,
+    /// The receiving server recorded no results
+    Unknown,
+}
+
+/// How well a message's sender is authenticated
+#[derive(Serialize, Debug, Clone)]
+pub struct SenderVerification {
+    pub message_id: String,
+    pub from: Option<String>,
+    pub from_domain: Option<String>,
+    pub verdict: SenderVerdict,
+    pub spf: Option<AuthCheck>,
+    pub dkim: Option<AuthCheck>,
+    pub dmarc: Option<AuthCheck>,
+    /// Whether SPF passed for a domain aligned with the From domain
+    pub spf_aligned: bool,
+    /// Whether DKIM passed for a domain aligned with the From domain
+    pub dkim_aligned: bool,
+    /// What the verdict is based on
+    pub reasons: Vec<String>,
+}
+
+impl SenderVerification {
+    /// Judge a message from the authentication results it carries
+    pub fn from_message(message: &EmailMessage) -> Self {
+        let from_domain = message
+            .from
+            .as_deref()
+            .and_then(|from| parse_email_addresses(from).into_iter().next())
+            .and_then(|address| domain_of(&address));
+        let results = message.authentication.clone().unwrap_or_default();
+        let aligned = |check: &Option<AuthCheck>| {
+            check.as_ref().is_some_and(|check| {
+                check.passed()
+                    && match (&check.domain, &from_domain) {
+                        (Some(domain), Some(from)) => domains_aligned(domain, from),
+                        _ => false,
+                    }
+            })
+        };
+        let spf_aligned = aligned(&results.spf);
+        let dkim_aligned = aligned(&results.dkim);
+
+        let mut reasons = Vec::new();
+        for (name, check) in [
+            ("SPF", &results.spf),
+            ("DKIM", &results.dkim),
+            ("DMARC", &results.dmarc),
+        ] {
+            match check {
+                Some(check) => reasons.push(match &check.domain {
+                    Some(domain) => format!("{} {} for {}", name, check.result, domain),
+                    None => format!("{} {}", name, check.result),
+                }),
+                None => reasons.push(format!("No {} result", name)),
+            }
+        }
+
+        let failed = |check: &Option<AuthCheck>| check.as_ref().is_some_and(AuthCheck::failed);
+        let verdict = if message.authentication.is_none() {
+            reasons = vec!["The message has no authentication results".to_string()];
+            SenderVerdict::Unknown
+        } else if failed(&results.dmarc) || (failed(&results.spf) && failed(&results.dkim)) {
+            SenderVerdict::Failed
+        } else if results.dmarc.as_ref().is_some_and(AuthCheck::passed) || dkim_aligned {
+            SenderVerdict::Authenticated
+        } else {
+            if (results.spf.as_ref().is_some_and(AuthCheck::passed) && !spf_aligned)
+                || (results.dkim.as_ref().is_some_and(AuthCheck::passed) && !dkim_aligned)
+            {
+                reasons.push("Checks passed for a domain other than the From address".to_string());
+            }
+            SenderVerdict::Unverified
+        };
+
+        Self {
+            message_id: message.id.clone(),
+            from: message.from.clone(),
+            from_domain,
+            verdict,
+            spf: results.spf,
+            dkim: results.dkim,
+            dmarc: results.dmarc,
+            spf_aligned,
+            dkim_aligned,
+            reasons,
+        }
+    }
+}
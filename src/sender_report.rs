@@ -0,0 +1,154 @@
+use crate::gmail_api::MessageMetadata;
+use crate::utils::parse_email_addresses;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+
+// Sender analytics
+//
+// A sender report groups the mail received over a period by sender, with
+// how much of it is still unread and when each sender last wrote. Senders
+// who write often but are rarely read are good candidates for a filter or
+// for unsubscribing.
+
+/// Period covered by a sender report when none is given
+pub const DEFAULT_SENDER_REPORT_RANGE: &str = "30d";
+
+/// Number of senders in a report when no limit is given
+pub const DEFAULT_SENDER_REPORT_LIMIT: usize = 20;
+
+/// Most messages scanned for one report
+pub const DEFAULT_SENDER_REPORT_MAX_MESSAGES: u32 = 1000;
+
+/// Messages received from one sender
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SenderStats {
+    /// Lowercased email address
+    pub address: String,
+    /// Display name from the most recent message, if any
+    pub name: Option<String>,
+    pub count: u64,
+    pub unread: u64,
+    /// Share of the sender's messages that are unread, from 0 to 1
+    pub unread_ratio: f64,
+    pub last_contact: Option<DateTime<Utc>>,
+}
+
+/// Senders of the mail received over a period, most frequent first
+#[derive(Debug, Clone, Serialize)]
+pub struct SenderReport {
+    /// Gmail query the messages were listed with
+    pub query: String,
+    pub messages_scanned: usize,
+    /// Whether the scan stopped at its message limit, leaving out older mail
+    pub truncated: bool,
+    pub total_senders: usize,
+    pub senders: Vec<SenderStats>,
+}
+
+/// Turn a period such as `7d`, `2w`, `3m` or `1y` (days, weeks, months or
+/// years) into a Gmail query for the mail received in it, excluding the
+/// user's own messages
+pub fn parse_time_range(range: &str) -> std::result::Result<String, String> {
+    let range = range.trim();
+    let compact = range.replace(' ', "");
+    let split = compact
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(compact.len());
+    let (amount, unit) = compact.split_at(split);
+    let amount: u32 = amount
+        .parse()
+        .ok()
+        .filter(|amount| *amount > 0)
+        .ok_or_else(|| invalid_range(range))?;
+
+    // Gmail's newer_than: understands days, months and years
+    let newer_than = match unit {
+        "d" | "day" | "days" => format!("{}d", amount),
+        "w" | "week" | "weeks" => format!("{}d", amount * 7),
+        "m" | "month" | "months" => format!("{}m", amount),
+        "y" | "year" | "years" => format!("{}y", amount),
+        _ => return Err(invalid_range(range)),
+    };
+    Ok(format!("newer_than:{} -from:me", newer_than))
+}
+
+fn invalid_range(range: &str) -> String {
+    format!(
+        "Invalid time range '{}': expected a number of days, weeks, months or years, \
+         such as 7d, 2w, 3m or 1y",
+        range
+    )
+}
+
+// Split a From header into its address and display name
+pub(crate) fn split_sender(from: &str) -> Option<(String, Option<String>)> {
+    let address = parse_email_addresses(from).into_iter().next()?;
+    let name = from
+        .split_once('<')
+        .map(|(name, _)| name.trim().trim_matches('"').trim())
+        .filter(|name| !name.is_empty())
+        .map(String::from);
+    Some((address, name))
+}
+
+impl SenderReport {
+    /// Group messages by sender, keeping the `limit` senders with the most
+    /// messages. Ties go to the sender heard from most recently.
+    pub fn from_messages(
+        query: &str,
+        messages: &[MessageMetadata],
+        limit: usize,
+        truncated: bool,
+    ) -> Self {
+        let mut by_sender: HashMap<String, SenderStats> = HashMap::new();
+        for message in messages {
+            let Some((address, name)) = message.from.as_deref().and_then(split_sender) else {
+                continue;
+            };
+            let stats = by_sender
+                .entry(address.clone())
+                .or_insert_with(|| SenderStats {
+                    address,
+                    name: None,
+                    count: 0,
+                    unread: 0,
+                    unread_ratio: 0.0,
+                    last_contact: None,
+                });
+
+            stats.count += 1;
+            if message.label_ids.iter().any(|label| label == "UNREAD") {
+                stats.unread += 1;
+            }
+            // Prefer the name on the most recent message
+            if stats.last_contact.is_none() || message.received_at > stats.last_contact {
+                stats.last_contact = message.received_at;
+                stats.name = name.or(stats.name.take());
+            } else if stats.name.is_none() {
+                stats.name = name;
+            }
+        }
+
+        let mut senders: Vec<SenderStats> = by_sender.into_values().collect();
+        for stats in &mut senders {
+            stats.unread_ratio = stats.unread as f64 / stats.count as f64;
+        }
+        senders.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then(b.last_contact.cmp(&a.last_contact))
+                .then(a.address.cmp(&b.address))
+        });
+
+        let total_senders = senders.len();
+        senders.truncate(limit);
+        Self {
+            query: query.to_string(),
+            messages_scanned: messages.len(),
+            truncated,
+            total_senders,
+            senders,
+        }
+    }
+}
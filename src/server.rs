@@ -2,12 +2,19 @@ use log::{debug, error, info};
 use mcp_attr::server::{mcp_server, McpServer};
 use mcp_attr::{Error as McpError, Result as McpResult};
 use serde_json::json;
+use std::sync::{Arc, Mutex};
 
+use crate::batch::BatchReport;
 use crate::config::Config;
-use crate::errors::ConfigError;
-use crate::errors::GmailApiError;
+use crate::errors::IdempotencyError;
 use crate::gmail_api::GmailService;
+use crate::idempotency::{Claim, IdempotencyGuard, IdempotencyStore, MAX_IDEMPOTENCY_KEY_CHARS};
+use crate::outbox::Outbox;
 use crate::utils::error_codes;
+use crate::validation::{
+    Validator, MAX_BODY_BYTES, MAX_DESCRIPTION_CHARS, MAX_LIST_ITEMS, MAX_QUERY_CHARS,
+    MAX_SUBJECT_CHARS, MAX_SUMMARY_CHARS,
+};
 
 // Helper functions
 mod helpers {
@@ -17,9 +24,21 @@ mod helpers {
 
 // Error codes have been moved to the utils module
 
+// Outcome of trying to answer a search from the local index
+#[cfg(feature = "local-index")]
+enum LocalSearch {
+    Answered(Vec<crate::gmail_api::EmailMessage>),
+    Missed { stale: bool },
+}
+
 // MCP server for accessing Gmail API
 #[derive(Clone)]
-pub struct GmailServer;
+pub struct GmailServer {
+    // Gmail client shared by every tool call, created on first use so that a
+    // configuration error is reported by the call rather than at startup.
+    // Clones share its HTTP connections and access token.
+    gmail: Arc<Mutex<Option<GmailService>>>,
+}
 
 impl Default for GmailServer {
     fn default() -> Self {
@@ -29,7 +48,23 @@ impl Default for GmailServer {
 
 impl GmailServer {
     pub fn new() -> Self {
-        GmailServer {}
+        GmailServer {
+            gmail: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// The Gmail client shared by the server's tools, created from the
+    /// environment's configuration on first use. The outbox and mail
+    /// webhooks are given it too, so that they share its access token.
+    pub fn gmail_service(&self) -> crate::errors::Result<GmailService> {
+        let mut gmail = self.gmail.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(service) = gmail.as_ref() {
+            return Ok(service.clone());
+        }
+        let config = Config::from_env()?;
+        let service = GmailService::new(&config)?;
+        *gmail = Some(service.clone());
+        Ok(service)
     }
 
     // Private method to initialize the Calendar service
@@ -37,73 +72,412 @@ impl GmailServer {
         // Load the config
         let config = Config::from_env().map_err(|e| {
             error!("Failed to load OAuth configuration: {}", e);
-            self.to_mcp_error(
-                &format!("Configuration error: {}", e),
-                error_codes::CONFIG_ERROR,
-            )
+            self.map_error(e)
         })?;
 
         // Create the calendar client
         Ok(crate::calendar_api::CalendarClient::new(&config))
     }
 
+    // Private method to read the defaults for calendar tools from the user's
+    // Calendar settings, falling back to the configured ones when the settings
+    // cannot be read
+    async fn calendar_defaults(
+        &self,
+        service: &crate::calendar_api::CalendarClient,
+    ) -> crate::calendar_settings::CalendarDefaults {
+        let settings = match service.get_settings().await {
+            Ok(settings) => Some(settings),
+            Err(err) => {
+                error!("Using default calendar settings: {}", err);
+                None
+            }
+        };
+        crate::calendar_settings::CalendarDefaults::new(
+            settings.as_ref(),
+            crate::config::get_configured_time_zone(),
+        )
+    }
+
     // Private method to initialize the People API service
     async fn init_people_service(&self) -> Result<crate::people_api::PeopleClient, McpError> {
         // Load the config
         let config = Config::from_env().map_err(|e| {
             error!("Failed to load OAuth configuration: {}", e);
-            self.to_mcp_error(
-                &format!("Configuration error: {}", e),
-                error_codes::CONFIG_ERROR,
-            )
+            self.map_error(e)
         })?;
 
         // Create the people client
         Ok(crate::people_api::PeopleClient::new(&config))
     }
 
+    // Private method to initialize the Tasks API service
+    async fn init_tasks_service(&self) -> Result<crate::tasks_api::TasksClient, McpError> {
+        // Load the config
+        let config = Config::from_env().map_err(|e| {
+            error!("Failed to load OAuth configuration: {}", e);
+            self.map_error(e)
+        })?;
+
+        // Create the tasks client
+        Ok(crate::tasks_api::TasksClient::new(&config))
+    }
+
+    // Private method to initialize the Drive API service
+    async fn init_drive_service(&self) -> Result<crate::drive_api::DriveClient, McpError> {
+        // Load the config
+        let config = Config::from_env().map_err(|e| {
+            error!("Failed to load OAuth configuration: {}", e);
+            self.map_error(e)
+        })?;
+
+        // Create the drive client
+        Ok(crate::drive_api::DriveClient::new(&config))
+    }
+
     // Helper function to create detailed McpError with appropriate error code and context
     fn to_mcp_error(&self, message: &str, code: u32) -> McpError {
         // Delegate to the utility function
         crate::utils::to_mcp_error(message, code)
     }
 
-    // Helper function to map GmailApiError to detailed McpError with specific codes
-    fn map_gmail_error(&self, err: GmailApiError) -> McpError {
+    // Fail with every invalid input field, before any request is made
+    fn validate(&self, validator: Validator) -> McpResult<()> {
+        validator.check().map_err(|err| {
+            error!("{}", err);
+            self.map_error(err)
+        })
+    }
+
+    // Parse a date argument in the user's time zone, failing as invalid input
+    // for the field
+    fn parse_date_arg(
+        &self,
+        field: &str,
+        value: &str,
+        now: chrono::DateTime<chrono::Utc>,
+        time_zone: chrono_tz::Tz,
+    ) -> McpResult<chrono::DateTime<chrono::Utc>> {
+        crate::validation::parse_date(field, value, now, time_zone).map_err(|err| {
+            error!("{}", err);
+            self.map_error(err)
+        })
+    }
+
+    // Claim a write tool's idempotency key for a call with `arguments`.
+    // Without a key the call always runs.
+    fn claim_idempotency_key(
+        &self,
+        tool: &str,
+        key: Option<&str>,
+        arguments: serde_json::Value,
+    ) -> McpResult<Claim<'static>> {
+        let Some(key) = key else {
+            return Ok(Claim::Run(IdempotencyGuard::none()));
+        };
+        self.validate(Validator::new().required("idempotency_key", key).max_chars(
+            "idempotency_key",
+            key,
+            MAX_IDEMPOTENCY_KEY_CHARS,
+        ))?;
+        IdempotencyStore::global()
+            .claim(tool, key, &arguments)
+            .map_err(|err| {
+                error!("{}", err);
+                let data = crate::utils::ErrorData::new(error_codes::GENERAL_ERROR);
+                let data = match err {
+                    IdempotencyError::InProgress(..) => {
+                        data.with_category("in_progress").retryable(Some(5))
+                    }
+                    IdempotencyError::Conflict(..) => data.with_category("conflict"),
+                };
+                crate::utils::to_mcp_error_with_data(&err.to_string(), data)
+            })
+    }
+
+    // Helper function to map any crate error to detailed McpError with specific codes
+    fn map_error(&self, err: impl Into<crate::errors::Error>) -> McpError {
         // Delegate to the utility function
-        crate::utils::map_gmail_error(err)
+        crate::utils::map_to_mcp_error(err)
+    }
+
+    // Serialize search results along with where they came from
+    fn search_result_json(
+        &self,
+        source: &str,
+        messages: &[crate::gmail_api::EmailMessage],
+    ) -> McpResult<String> {
+        serde_json::to_string(&json!({ "source": source, "messages": messages })).map_err(|e| {
+            let error_msg = format!("Failed to serialize message list: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })
+    }
+
+    // Work out how much of each listed message to return
+    fn listing_detail(
+        &self,
+        snippet_length: Option<serde_json::Value>,
+        include_body: Option<&str>,
+    ) -> McpResult<crate::listing_detail::ListingDetail> {
+        // A length that cannot be read leaves snippets whole
+        let snippet_length = snippet_length
+            .map(|v| helpers::parse_max_results(Some(v), u32::MAX) as usize)
+            .filter(|&length| length < u32::MAX as usize);
+        crate::listing_detail::ListingDetail::from_args(snippet_length, include_body).map_err(|e| {
+            error!("{}", e);
+            self.to_mcp_error(&e, error_codes::MESSAGE_FORMAT_ERROR)
+        })
+    }
+
+    // Serialize a message list, collapsing duplicates and grouping by thread
+    // when asked to
+    fn message_list_json(
+        &self,
+        mut messages: Vec<crate::gmail_api::EmailMessage>,
+        deduplicate: bool,
+        detail: crate::listing_detail::ListingDetail,
+    ) -> McpResult<String> {
+        for message in &mut messages {
+            detail.apply(message);
+        }
+        let result = if deduplicate {
+            serde_json::to_string(&crate::dedupe::DedupedMessages::from_messages(messages))
+        } else {
+            serde_json::to_string(&messages)
+        };
+        result.map_err(|e| {
+            let error_msg = format!("Failed to serialize message list: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })
+    }
+
+    // Serialize the result of a thread operation
+    fn thread_result_json(&self, result: serde_json::Value) -> McpResult<String> {
+        serde_json::to_string_pretty(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize thread result: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })
+    }
+
+    // Try to answer a search from the local index. Index problems are logged
+    // rather than returned, since Gmail can always answer instead.
+    #[cfg(feature = "local-index")]
+    fn search_local_index(&self, query: &str, max_results: u32) -> LocalSearch {
+        use crate::errors::LocalIndexError;
+        use crate::local_index::{LocalIndex, LocalIndexConfig};
+
+        let config = LocalIndexConfig::from_env();
+        if !config.enabled {
+            return LocalSearch::Missed { stale: false };
+        }
+
+        let index = match LocalIndex::open(&config.path) {
+            Ok(index) => index,
+            Err(e) => {
+                error!("Failed to open local index: {}", e);
+                return LocalSearch::Missed { stale: false };
+            }
+        };
+
+        match index.is_stale(config.max_age) {
+            Ok(false) => {}
+            Ok(true) => {
+                debug!("Local index is stale");
+                return LocalSearch::Missed { stale: true };
+            }
+            Err(e) => {
+                error!("Failed to read local index state: {}", e);
+                return LocalSearch::Missed { stale: false };
+            }
+        }
+
+        match index.search(query, max_results) {
+            Ok(messages) => LocalSearch::Answered(messages),
+            Err(LocalIndexError::UnsupportedQuery(_)) => {
+                debug!("Query '{}' needs Gmail search", query);
+                LocalSearch::Missed { stale: false }
+            }
+            Err(e) => {
+                error!("Local index search failed: {}", e);
+                LocalSearch::Missed { stale: false }
+            }
+        }
+    }
+
+    // Sync the local index without holding up the current request
+    #[cfg(feature = "local-index")]
+    async fn spawn_index_refresh(&self) {
+        use crate::local_index::{LocalIndex, LocalIndexConfig};
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        static REFRESHING: AtomicBool = AtomicBool::new(false);
+
+        if REFRESHING.swap(true, Ordering::SeqCst) {
+            debug!("Local index refresh already running");
+            return;
+        }
+        // The sync outlives the call, so it uses the client without the tool
+        // deadline
+        let Ok(service) = self.gmail_service() else {
+            REFRESHING.store(false, Ordering::SeqCst);
+            return;
+        };
+        tokio::spawn(async move {
+            let config = LocalIndexConfig::from_env();
+            let result = match LocalIndex::open(&config.path) {
+                Ok(index) => index.sync(&service, config.sync_limit).await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(stats) => info!("Local index refreshed: {:?}", stats),
+                Err(e) => error!("Failed to refresh local index: {}", e),
+            }
+            REFRESHING.store(false, Ordering::SeqCst);
+        });
     }
 
-    // Helper function to initialize Gmail service with detailed error handling
+    // Helper function to get the shared Gmail service with detailed error
+    // handling
     async fn init_gmail_service(&self) -> McpResult<GmailService> {
-        // Load configuration
-        let config = Config::from_env().map_err(|err| {
-            let msg = match err {
-                ConfigError::MissingEnvVar(var) => {
-                    format!(
-                        "Missing environment variable: {}. \
-                        This variable is required for Gmail authentication. \
-                        Please ensure you have set up your .env file correctly or exported the variable in your shell. \
-                        Create an OAuth2 client in the Google Cloud Console to obtain these credentials.", 
-                        var
-                    )
+        // Issue requests within the tool's deadline
+        self.gmail_service()
+            .map(|service| service.with_deadline(crate::deadline::Deadline::for_tool()))
+            .map_err(|err| {
+                error!("Failed to create Gmail service: {}", err);
+                self.map_error(err)
+            })
+    }
+
+    // Create a Gmail service for a mailbox: the account's own, or the one at
+    // the address given when the account has delegated access to it
+    async fn init_mailbox_service(&self, mailbox: Option<String>) -> McpResult<GmailService> {
+        let mailbox = mailbox
+            .map(|mailbox| mailbox.trim().to_string())
+            .filter(|mailbox| {
+                !mailbox.is_empty()
+                    && !mailbox.eq_ignore_ascii_case(crate::gmail_api::DEFAULT_MAILBOX)
+            });
+        let address = match mailbox {
+            Some(mailbox) => match crate::utils::parse_email_addresses(&mailbox).as_slice() {
+                [address] => Some(address.clone()),
+                _ => {
+                    let error_msg = format!("'{}' is not a single mailbox address", mailbox);
+                    error!("{}", error_msg);
+                    return Err(self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR));
                 }
-                ConfigError::EnvError(e) => {
-                    format!(
-                        "Environment variable error: {}. \
-                        There was a problem reading the environment variables needed for Gmail authentication. \
-                        Check permissions on your .env file and ensure it's properly formatted without special characters or quotes.", 
-                        e
-                    )
-                },
-            };
-            self.to_mcp_error(&msg, error_codes::CONFIG_ERROR)
+            },
+            None => None,
+        };
+
+        let service = self.init_gmail_service().await?;
+        Ok(match address {
+            Some(address) => {
+                debug!("Using mailbox {}", address);
+                service.with_mailbox(&address)
+            }
+            None => service,
+        })
+    }
+
+    // Add or remove an event's guests, patching only its attendee list
+    async fn update_invites(
+        &self,
+        calendar_id: Option<String>,
+        event_id: String,
+        emails: Vec<String>,
+        send_updates: Option<String>,
+        edit: impl FnOnce(&[serde_json::Value], &[String]) -> crate::invites::AttendeeEdit,
+    ) -> McpResult<String> {
+        let calendar_id = calendar_id.unwrap_or_else(|| "primary".to_string());
+        let send_updates =
+            crate::invites::parse_send_updates(send_updates.as_deref()).map_err(|e| {
+                error!("{}", e);
+                self.to_mcp_error(&e, error_codes::MESSAGE_FORMAT_ERROR)
+            })?;
+
+        let mut addresses: Vec<String> = Vec::new();
+        for entry in &emails {
+            let parsed = crate::utils::parse_email_addresses(entry);
+            if parsed.is_empty() {
+                let error_msg = format!("'{}' is not an email address", entry);
+                error!("{}", error_msg);
+                return Err(self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR));
+            }
+            for address in parsed {
+                if !addresses.contains(&address) {
+                    addresses.push(address);
+                }
+            }
+        }
+        if addresses.is_empty() {
+            let error_msg = "No email addresses given".to_string();
+            error!("{}", error_msg);
+            return Err(self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR));
+        }
+
+        let service = self.init_calendar_service().await?;
+        let current = service
+            .get_event_json(&calendar_id, &event_id)
+            .await
+            .map_err(|err| {
+                error!(
+                    "Failed to get event {} from calendar {}: {}",
+                    event_id, calendar_id, err
+                );
+                self.map_error(err)
+            })?;
+        let existing = current
+            .get("attendees")
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default();
+        let edit = edit(&existing, &addresses);
+
+        // Leave the event alone, and send no notifications, if nothing changes
+        let event = if edit.changed() {
+            let patch = json!({ "attendees": edit.attendees });
+            service
+                .patch_event(
+                    &calendar_id,
+                    &event_id,
+                    &patch,
+                    current.get("etag").and_then(|v| v.as_str()),
+                    &send_updates,
+                )
+                .await
+        } else {
+            service.get_event(&calendar_id, &event_id).await
+        }
+        .map_err(|err| {
+            error!(
+                "Failed to update the attendees of event {} in calendar {}: {}",
+                event_id, calendar_id, err
+            );
+            self.map_error(err)
         })?;
 
-        // Create Gmail service
-        GmailService::new(&config).map_err(|err| {
-            error!("Failed to create Gmail service: {}", err);
-            self.map_gmail_error(err)
+        let update = crate::invites::InviteUpdate {
+            event_id,
+            summary: event.summary,
+            updated: edit.changed(),
+            send_updates: if edit.changed() {
+                send_updates
+            } else {
+                "none".to_string()
+            },
+            added: edit.added,
+            already_invited: edit.already_invited,
+            removed: edit.removed,
+            not_invited: edit.not_invited,
+            attendees: event.attendees,
+        };
+        serde_json::to_string_pretty(&update).map_err(|e| {
+            let error_msg = format!("Failed to serialize attendee update: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
         })
     }
 }
@@ -197,6 +571,14 @@ impl McpServer for GmailServer {
         Ok(crate::prompts::EMAIL_DRAFTING_PROMPT)
     }
 
+    /// Email Translation Prompt
+    ///
+    /// Guide to translating emails written in other languages
+    #[prompt]
+    async fn email_translation_prompt(&self) -> McpResult<&str> {
+        Ok(crate::prompts::EMAIL_TRANSLATION_PROMPT)
+    }
+
     /// Get a list of emails from the inbox
     ///
     /// Returns emails with subject, sender, recipient, date and snippet information.
@@ -204,33 +586,63 @@ impl McpServer for GmailServer {
     /// Args:
     ///   max_results: Optional maximum number of results to return (default: 10). Can be a number (3) or a string ("3").
     ///   query: Optional Gmail search query string (e.g. "is:unread from:example.com")
+    ///   deduplicate: Optional flag to collapse copies of the same message (one RFC Message-ID
+    ///     delivered to several labels or aliases) and group the results by thread (default: false).
+    ///     The result is then an object with "messages_listed", "duplicates_removed" and "threads".
+    ///   newer_than: Optional age such as "7d", "2w", "3m" or "1y" (days, weeks, months or years).
+    ///     Only mail newer than this is listed.
+    ///   older_than: Optional age in the same form. Only mail older than this is listed.
+    ///   snippet_length: Optional longest snippet in characters; longer ones are cut and end
+    ///     with "…" (default: no limit). Can be a number (80) or a string ("80").
+    ///   include_body: Optional bodies to include: "none", "text" for the plain text body only,
+    ///     or "both" for the plain text and HTML bodies (default: "both")
+    ///   mailbox: Optional address of a shared or delegated mailbox to use instead of your own
     #[tool]
+    #[allow(clippy::too_many_arguments)]
     async fn list_emails(
         &self,
         max_results: Option<serde_json::Value>,
         query: Option<String>,
+        deduplicate: Option<bool>,
+        newer_than: Option<String>,
+        older_than: Option<String>,
+        snippet_length: Option<serde_json::Value>,
+        include_body: Option<String>,
+        mailbox: Option<String>,
     ) -> McpResult<String> {
         info!("=== START list_emails MCP command ===");
         debug!(
-            "list_emails called with max_results={:?}, query={:?}",
-            max_results, query
+            "list_emails called with max_results={:?}, query={:?}, deduplicate={:?}, newer_than={:?}, older_than={:?}, snippet_length={:?}, include_body={:?}",
+            max_results, query, deduplicate, newer_than, older_than, snippet_length, include_body
         );
 
+        self.validate(Validator::new().optional(query.as_deref(), |v, query| {
+            v.max_chars("query", query, MAX_QUERY_CHARS)
+        }))?;
+
         // Convert max_results using the helper function (default: 10)
         let max = helpers::parse_max_results(max_results, 10);
+        let detail = self.listing_detail(snippet_length, include_body.as_deref())?;
+
+        // Add the relative time filters to the search query
+        let query = crate::utils::with_relative_time(
+            query.as_deref(),
+            newer_than.as_deref(),
+            older_than.as_deref(),
+        )
+        .map_err(|e| {
+            error!("{}", e);
+            self.to_mcp_error(&e, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
 
         // Get the Gmail service
-        let mut service = self.init_gmail_service().await?;
+        let service = self.init_mailbox_service(mailbox).await?;
 
         // Get messages with full metadata
         let result = match service.list_messages(max, query.as_deref()).await {
             Ok(messages) => {
                 // Convert to JSON
-                serde_json::to_string(&messages).map_err(|e| {
-                    let error_msg = format!("Failed to serialize message list: {}", e);
-                    error!("{}", error_msg);
-                    self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
-                })?
+                self.message_list_json(messages, deduplicate.unwrap_or(false), detail)?
             }
             Err(err) => {
                 let query_info = query.as_deref().unwrap_or("none");
@@ -245,7 +657,7 @@ impl McpServer for GmailServer {
                     max, query_info
                 );
 
-                return Err(self.map_gmail_error(err));
+                return Err(self.map_error(err));
             }
         };
 
@@ -258,13 +670,16 @@ impl McpServer for GmailServer {
     ///
     /// Args:
     ///   message_id: The ID of the message to retrieve
+    ///   mailbox: Optional address of a shared or delegated mailbox to use instead of your own
     #[tool]
-    async fn get_email(&self, message_id: String) -> McpResult<String> {
+    async fn get_email(&self, message_id: String, mailbox: Option<String>) -> McpResult<String> {
         info!("=== START get_email MCP command ===");
         debug!("get_email called with message_id={}", message_id);
 
+        self.validate(Validator::new().required("message_id", &message_id))?;
+
         // Get the Gmail service
-        let mut service = self.init_gmail_service().await?;
+        let service = self.init_mailbox_service(mailbox).await?;
 
         // Get detailed message directly using the helper method
         let email = match service.get_message_details(&message_id).await {
@@ -281,7 +696,7 @@ impl McpServer for GmailServer {
                     message_id
                 );
 
-                return Err(self.map_gmail_error(err));
+                return Err(self.map_error(err));
             }
         };
 
@@ -295,859 +710,7189 @@ impl McpServer for GmailServer {
         info!("=== END get_email MCP command (success) ===");
         Ok(result)
     }
-    /// Search for emails using a Gmail search query
+
+    /// Find an email by its Message-ID header
     ///
-    /// Returns emails with subject, sender, recipient, date and snippet information.
+    /// Maps an RFC 822 Message-ID, such as one recorded by a ticket system or found in
+    /// another message's In-Reply-To or References header, to the Gmail message it
+    /// identifies. Brackets are optional. Returns an object with "rfc_message_id",
+    /// "count" and the matching "messages": usually one, but a message sent to
+    /// yourself has a copy for each direction.
     ///
     /// Args:
-    ///   query: Gmail search query string (e.g. "is:unread from:example.com")
-    ///   max_results: Optional maximum number of results (default: 10). Can be a number (3) or a string ("3").
+    ///   message_id_header: The Message-ID, e.g. "<CAF123@mail.example.com>"
     #[tool]
-    async fn search_emails(
-        &self,
-        query: String,
-        max_results: Option<serde_json::Value>,
-    ) -> McpResult<String> {
-        info!("=== START search_emails MCP command ===");
+    async fn find_by_rfc_message_id(&self, message_id_header: String) -> McpResult<String> {
+        info!("=== START find_by_rfc_message_id MCP command ===");
         debug!(
-            "search_emails called with query={:?}, max_results={:?}",
-            query, max_results
+            "find_by_rfc_message_id called with message_id_header={}",
+            message_id_header
         );
 
-        // Get the parsed max_results value
-        let max = helpers::parse_max_results(max_results, 10);
-
-        // Get the Gmail service
-        let mut service = self.init_gmail_service().await?;
+        self.validate(Validator::new().required("message_id_header", &message_id_header))?;
 
-        // Get messages with full metadata
-        let result = match service.list_messages(max, Some(&query)).await {
-            Ok(messages) => {
-                // Convert to JSON
-                serde_json::to_string(&messages).map_err(|e| {
-                    let error_msg = format!("Failed to serialize message list: {}", e);
-                    error!("{}", error_msg);
-                    self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
-                })?
-            }
-            Err(err) => {
+        let service = self.init_gmail_service().await?;
+        let messages = service
+            .find_by_rfc_message_id(&message_id_header)
+            .await
+            .map_err(|err| {
                 error!(
-                    "Failed to search emails with query='{}', max_results={}: {}",
-                    query, max, err
+                    "Failed to find message with Message-ID '{}': {}",
+                    message_id_header, err
                 );
+                self.map_error(err)
+            })?;
 
-                // Create detailed contextual error with specific advice for search queries
-                error!("Context: Failed to search emails with query: '{}'", query);
-
-                return Err(self.map_gmail_error(err));
-            }
-        };
+        let result = json!({
+            "rfc_message_id": format!(
+                "<{}>",
+                crate::gmail_api::bare_message_id(&message_id_header)
+            ),
+            "count": messages.len(),
+            "messages": messages
+        });
+        let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize messages: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
 
-        info!("=== END search_emails MCP command (success) ===");
-        Ok(result)
+        info!("=== END find_by_rfc_message_id MCP command (success) ===");
+        Ok(result_json)
     }
 
-    /// Get a list of email labels
+    /// Get a thread without the repeated quoting
     ///
-    /// Returns the raw JSON response from the Gmail API without any transformation or modification.
+    /// Each reply usually quotes everything before it, so reading a thread message by
+    /// message repeats the same text many times. This returns only what each message
+    /// adds: quoted lines, "On ... wrote:" attributions and forwarded originals are
+    /// removed.
+    ///
+    /// Returns an object with "thread_id", "subject", "message_count", the
+    /// "participants" (address, name and messages_sent), "started_at",
+    /// "last_activity" and the "messages" oldest first, each with "from", "to",
+    /// "date", a UTC "timestamp", its own "content" and "quotes_removed".
+    ///
+    /// Args:
+    ///   thread_id: The ID of the thread to read
+    ///   mailbox: Optional address of a shared or delegated mailbox to use instead of your own
     #[tool]
-    async fn list_labels(&self) -> McpResult<String> {
-        debug!("list_labels called");
+    async fn get_thread_clean(
+        &self,
+        thread_id: String,
+        mailbox: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START get_thread_clean MCP command ===");
+        debug!("get_thread_clean called with thread_id={}", thread_id);
 
-        // Get the Gmail service
-        let mut service = self.init_gmail_service().await?;
+        self.validate(Validator::new().required("thread_id", &thread_id))?;
 
-        // Get labels
-        match service.list_labels().await {
-            Ok(labels) => Ok(labels),
-            Err(err) => {
-                error!("Failed to list labels: {}", err);
+        let service = self.init_mailbox_service(mailbox).await?;
+        let messages = service.get_thread(&thread_id).await.map_err(|err| {
+            error!(
+                "Failed to get thread with thread_id='{}': {}",
+                thread_id, err
+            );
+            self.map_error(err)
+        })?;
 
-                // Provide detailed error with troubleshooting steps
-                // Include detailed context in the error log
-                error!("Context: Failed to retrieve Gmail labels. This operation requires read access permissions.");
+        let thread = crate::thread_view::CleanThread::from_messages(&thread_id, &messages);
+        let result_json = serde_json::to_string_pretty(&thread).map_err(|e| {
+            let error_msg = format!("Failed to serialize thread: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
 
-                Err(self.map_gmail_error(err))
-            }
-        }
+        info!("=== END get_thread_clean MCP command (success) ===");
+        Ok(result_json)
     }
 
-    /// Check connection status with Gmail API
+    /// Find text in a thread
     ///
-    /// Tests the connection to Gmail API by retrieving the user's profile.
-    /// Returns the raw JSON response from the Gmail API without any transformation or modification.
+    /// Locates a statement in a long conversation without reading all of it.
+    /// Each message is searched for what it adds, with quoted replies removed
+    /// as in get_thread_clean, so text is found once in the message that said
+    /// it. Case is ignored and line breaks match spaces.
+    ///
+    /// Returns an object with "thread_id", "subject", "text", "message_count",
+    /// "match_count" and the "matches" oldest first, each with "message_id",
+    /// "from", "date", "timestamp", the "offset" and "length" of the match in
+    /// characters of the message's content from get_thread_clean, and a
+    /// "snippet" of the text around it.
+    ///
+    /// Args:
+    ///   thread_id: The ID of the thread to search
+    ///   text: The words to find
+    ///   max_results: Optional maximum number of matches to return (default: 20)
+    ///   mailbox: Optional address of a shared or delegated mailbox to use instead of your own
     #[tool]
-    async fn check_connection(&self) -> McpResult<String> {
-        info!("=== START check_connection MCP command ===");
-        debug!("check_connection called");
+    async fn search_in_thread(
+        &self,
+        thread_id: String,
+        text: String,
+        max_results: Option<serde_json::Value>,
+        mailbox: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START search_in_thread MCP command ===");
+        debug!(
+            "search_in_thread called with thread_id={}, text={}",
+            thread_id, text
+        );
 
-        // Get the Gmail service
-        let mut service = self.init_gmail_service().await?;
+        self.validate(
+            Validator::new()
+                .required("thread_id", &thread_id)
+                .required("text", &text)
+                .max_chars("text", &text, MAX_QUERY_CHARS),
+        )?;
 
-        // Get profile as raw JSON
-        let profile_json = match service.check_connection_raw().await {
-            Ok(json) => json,
-            Err(err) => {
-                error!("Connection check failed: {}", err);
+        let max_results = helpers::parse_max_results(
+            max_results,
+            crate::thread_view::DEFAULT_THREAD_SEARCH_MATCHES,
+        );
 
-                // Provide helpful information on connectivity issues
-                // Include detailed context in the error log
-                error!("Context: Failed to connect to Gmail API. This is a basic connectivity test failure.");
+        let service = self.init_mailbox_service(mailbox).await?;
+        let messages = service.get_thread(&thread_id).await.map_err(|err| {
+            error!(
+                "Failed to get thread with thread_id='{}': {}",
+                thread_id, err
+            );
+            self.map_error(err)
+        })?;
 
-                return Err(self.map_gmail_error(err));
-            }
-        };
+        let thread = crate::thread_view::CleanThread::from_messages(&thread_id, &messages);
+        let mut matches = thread.search(&text);
+        let match_count = matches.len();
+        matches.truncate(max_results as usize);
 
-        info!("=== END check_connection MCP command (success) ===");
-        Ok(profile_json)
+        let result = json!({
+            "thread_id": thread.thread_id,
+            "subject": thread.subject,
+            "text": text,
+            "message_count": thread.message_count,
+            "match_count": match_count,
+            "matches": matches
+        });
+
+        let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize thread matches: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END search_in_thread MCP command (success) ===");
+        Ok(result_json)
     }
 
-    /// Analyze an email to extract key information
+    /// Gather context for writing to someone
     ///
-    /// Takes an email ID and performs a detailed analysis on its content.
-    /// Extracts information like action items, meeting details, contact information,
-    /// sentiment, priority, and suggested next steps.
+    /// Collects what is useful before drafting an email to a recipient: the latest
+    /// messages exchanged with them, quoted text removed, their contact record, and
+    /// the events on the primary calendar over the next 30 days that they attend or
+    /// organize. Use the result to seed a drafting prompt.
+    ///
+    /// Returns an object with "to", "contact" (null if they are not a contact),
+    /// "exchanges" most recent first, each with "direction" ("received" or "sent"),
+    /// "subject", "date" and "content", and "upcoming_events" soonest first.
     ///
     /// Args:
-    ///   message_id: The ID of the message to analyze
-    ///   analysis_type: Optional type of analysis to perform. Can be "general", "tasks",
-    ///                  "meetings", "contacts", or "all". Default is "general".
+    ///   to: The recipient's email address
+    ///   max_exchanges: Optional number of recent messages to include (default: 5)
     #[tool]
-    async fn analyze_email(
+    async fn compose_context(
         &self,
-        message_id: String,
+        to: String,
+        max_exchanges: Option<serde_json::Value>,
+    ) -> McpResult<String> {
+        info!("=== START compose_context MCP command ===");
+        debug!(
+            "compose_context called with to={}, max_exchanges={:?}",
+            to, max_exchanges
+        );
+
+        self.validate(Validator::new().email("to", &to))?;
+        // Validated as exactly one address
+        let address = crate::utils::parse_email_addresses(&to)
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        let max_exchanges = helpers::parse_max_results(
+            max_exchanges,
+            crate::compose_context::DEFAULT_COMPOSE_EXCHANGES,
+        );
+
+        let service = self.init_gmail_service().await?;
+        let query = crate::compose_context::exchange_query(&address);
+        let messages = service
+            .list_messages(max_exchanges, Some(&query))
+            .await
+            .map_err(|err| {
+                error!("Failed to list messages with {}: {}", address, err);
+                self.map_error(err)
+            })?;
+
+        let people_client = self.init_people_service().await?;
+        let contacts = people_client
+            .search_contacts(&address, Some(10))
+            .await
+            .map_err(|err| {
+                error!("Failed to search contacts for {}: {}", address, err);
+                self.map_error(err)
+            })?;
+
+        let now = chrono::Utc::now();
+        let calendar_client = self.init_calendar_service().await?;
+        let events = calendar_client
+            .list_events(
+                "primary",
+                Some(crate::compose_context::COMPOSE_MAX_EVENTS),
+                Some(now),
+                Some(now + chrono::Duration::days(crate::compose_context::COMPOSE_EVENT_DAYS)),
+            )
+            .await
+            .map_err(|err| {
+                error!("Failed to list upcoming events: {}", err);
+                self.map_error(err)
+            })?;
+
+        let context = crate::compose_context::ComposeContext::new(
+            &address,
+            &messages,
+            max_exchanges as usize,
+            &contacts.contacts,
+            &events,
+            now,
+        );
+        let result_json = serde_json::to_string_pretty(&context).map_err(|e| {
+            let error_msg = format!("Failed to serialize compose context: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END compose_context MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Summarize your relationship with someone
+    ///
+    /// Combines their contact record, the mail exchanged with them over the last 90
+    /// days and your meetings with them on the primary calendar into one summary:
+    /// how often you write to each other, what was last said, and when you last and
+    /// next meet. Only mail from the person, or that you sent to them, is counted.
+    ///
+    /// Returns an object with "email", "name", "contact" (null if they are not a
+    /// contact), "frequency" with "received", "sent" and "per_week", "last_received",
+    /// "last_sent", "recent_messages" most recent first, "meetings" (the number in the
+    /// last 90 days), "last_meeting", "next_meeting" and a one-line "summary".
+    ///
+    /// Args:
+    ///   email: The person's email address
+    ///   max_messages: Optional number of recent messages to include (default: 5)
+    #[tool]
+    async fn contact_history(
+        &self,
+        email: String,
+        max_messages: Option<serde_json::Value>,
+    ) -> McpResult<String> {
+        info!("=== START contact_history MCP command ===");
+        debug!(
+            "contact_history called with email={}, max_messages={:?}",
+            email, max_messages
+        );
+
+        self.validate(Validator::new().email("email", &email))?;
+        // Validated as exactly one address
+        let address = crate::utils::parse_email_addresses(&email)
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        let max_messages = helpers::parse_max_results(
+            max_messages,
+            crate::contact_history::DEFAULT_CONTACT_HISTORY_MESSAGES,
+        );
+
+        let service = self.init_gmail_service().await?;
+        let query = crate::contact_history::history_query(&address);
+        let messages = service
+            .list_message_metadata(
+                crate::contact_history::CONTACT_HISTORY_MAX_SCAN,
+                Some(&query),
+                crate::contact_history::CONTACT_HISTORY_HEADERS,
+            )
+            .await
+            .map_err(|err| {
+                error!("Failed to list messages with {}: {}", address, err);
+                self.map_error(err)
+            })?;
+
+        let people_client = self.init_people_service().await?;
+        let contacts = people_client
+            .search_contacts(&address, Some(10))
+            .await
+            .map_err(|err| {
+                error!("Failed to search contacts for {}: {}", address, err);
+                self.map_error(err)
+            })?;
+
+        let now = chrono::Utc::now();
+        let calendar_client = self.init_calendar_service().await?;
+        let events = calendar_client
+            .list_events(
+                "primary",
+                Some(crate::contact_history::CONTACT_HISTORY_MAX_EVENTS),
+                Some(now - chrono::Duration::days(crate::contact_history::CONTACT_HISTORY_DAYS)),
+                Some(
+                    now + chrono::Duration::days(
+                        crate::contact_history::CONTACT_HISTORY_UPCOMING_DAYS,
+                    ),
+                ),
+            )
+            .await
+            .map_err(|err| {
+                error!("Failed to list events: {}", err);
+                self.map_error(err)
+            })?;
+
+        let history = crate::contact_history::ContactHistory::new(
+            &address,
+            &messages,
+            crate::contact_history::CONTACT_HISTORY_MAX_SCAN as usize,
+            max_messages as usize,
+            &contacts.contacts,
+            &events,
+            now,
+        );
+        let result_json = serde_json::to_string_pretty(&history).map_err(|e| {
+            let error_msg = format!("Failed to serialize contact history: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END contact_history MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Check whether an email really comes from its sender
+    ///
+    /// Summarizes the SPF, DKIM and DMARC results Gmail recorded for the message, and
+    /// whether they pass for the domain of the From address. Useful when triaging
+    /// suspected phishing.
+    ///
+    /// Returns an object with "verdict" ("authenticated", "unverified", "failed" or
+    /// "unknown"), the "spf", "dkim" and "dmarc" results, "spf_aligned", "dkim_aligned"
+    /// and the "reasons" for the verdict.
+    ///
+    /// Args:
+    ///   message_id: The ID of the message to check
+    #[tool]
+    async fn verify_sender(&self, message_id: String) -> McpResult<String> {
+        info!("=== START verify_sender MCP command ===");
+        debug!("verify_sender called with message_id={}", message_id);
+
+        self.validate(Validator::new().required("message_id", &message_id))?;
+
+        let service = self.init_gmail_service().await?;
+        let email = service
+            .get_message_details(&message_id)
+            .await
+            .map_err(|err| {
+                error!(
+                    "Failed to get email to verify with message_id='{}': {}",
+                    message_id, err
+                );
+                self.map_error(err)
+            })?;
+
+        let verification = crate::sender_auth::SenderVerification::from_message(&email);
+        let result = serde_json::to_string(&verification).map_err(|e| {
+            let error_msg = format!("Failed to serialize sender verification: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END verify_sender MCP command (success) ===");
+        Ok(result)
+    }
+
+    /// Assess how likely an email is to be phishing
+    ///
+    /// Combines the sender's SPF, DKIM and DMARC results, display names that point at a
+    /// different domain than the address, links that hide where they lead, and pressing
+    /// language into a risk score from 0 to 100. Everything is read from the message
+    /// itself; no links are followed and no outside services are called.
+    ///
+    /// Returns an object with "risk_score", "risk_level" ("low", "medium" or "high"),
+    /// "sender_verdict", "link_domains" and the "signals" behind the score, each with a
+    /// "kind", "weight" and "detail".
+    ///
+    /// Args:
+    ///   message_id: The ID of the message to assess
+    #[tool]
+    async fn assess_phishing_risk(&self, message_id: String) -> McpResult<String> {
+        info!("=== START assess_phishing_risk MCP command ===");
+        debug!("assess_phishing_risk called with message_id={}", message_id);
+
+        self.validate(Validator::new().required("message_id", &message_id))?;
+
+        let service = self.init_gmail_service().await?;
+        let email = service
+            .get_message_details(&message_id)
+            .await
+            .map_err(|err| {
+                error!(
+                    "Failed to get email to assess with message_id='{}': {}",
+                    message_id, err
+                );
+                self.map_error(err)
+            })?;
+
+        let assessment = crate::phishing::PhishingAssessment::from_message(&email);
+        let result = serde_json::to_string(&assessment).map_err(|e| {
+            let error_msg = format!("Failed to serialize phishing assessment: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END assess_phishing_risk MCP command (success) ===");
+        Ok(result)
+    }
+
+    /// List the links in an email
+    ///
+    /// Finds the web links in the HTML and plain text bodies of a message, once each, so
+    /// they do not have to be picked out of the HTML by hand. Each link has its "url",
+    /// "domain", "anchor_text" from the HTML, "is_tracker" for click-tracking services and
+    /// campaign parameters, and "is_redirect" with the "redirect_target" for links that
+    /// forward to another URL.
+    ///
+    /// Returns an object with "message_id", "count" and "links".
+    ///
+    /// Args:
+    ///   message_id: The ID of the message to list the links of
+    #[tool]
+    async fn extract_links(&self, message_id: String) -> McpResult<String> {
+        info!("=== START extract_links MCP command ===");
+        debug!("extract_links called with message_id={}", message_id);
+
+        self.validate(Validator::new().required("message_id", &message_id))?;
+
+        let service = self.init_gmail_service().await?;
+        let email = service
+            .get_message_details(&message_id)
+            .await
+            .map_err(|err| {
+                error!(
+                    "Failed to get email to extract links from with message_id='{}': {}",
+                    message_id, err
+                );
+                self.map_error(err)
+            })?;
+
+        let links =
+            crate::utils::extract_links(email.body_html.as_deref(), email.body_text.as_deref());
+        let result = json!({
+            "message_id": email.id,
+            "count": links.len(),
+            "links": links,
+        });
+
+        info!("=== END extract_links MCP command (success) ===");
+        Ok(result.to_string())
+    }
+
+    /// Render an email as Markdown
+    ///
+    /// Produces a clean Markdown document for pasting into notes or feeding to other
+    /// tools: the subject as a title, a table of the From, To, Date and Message-ID
+    /// headers, the body converted from HTML (or the plain text body), and lists of
+    /// the attachments and of the inline images as `cid:` links.
+    ///
+    /// Returns an object with "message_id", "subject" and "markdown".
+    ///
+    /// Args:
+    ///   message_id: The ID of the message to render
+    ///   mailbox: Optional address of a shared or delegated mailbox to use instead of your own
+    #[tool]
+    async fn render_email_markdown(
+        &self,
+        message_id: String,
+        mailbox: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START render_email_markdown MCP command ===");
+        debug!(
+            "render_email_markdown called with message_id={}",
+            message_id
+        );
+
+        self.validate(Validator::new().required("message_id", &message_id))?;
+
+        let service = self.init_mailbox_service(mailbox).await?;
+        let (email, attachments) = service
+            .get_message_with_attachments(&message_id)
+            .await
+            .map_err(|err| {
+                error!(
+                    "Failed to get email to render with message_id='{}': {}",
+                    message_id, err
+                );
+                self.map_error(err)
+            })?;
+
+        let markdown = crate::markdown::render_email(&email, &attachments);
+        let result = json!({
+            "message_id": email.id,
+            "subject": email.subject,
+            "markdown": markdown,
+        });
+
+        info!("=== END render_email_markdown MCP command (success) ===");
+        Ok(result.to_string())
+    }
+
+    /// List the attachments on an email
+    ///
+    /// Returns each attachment's attachment_id, filename, mime_type and size in bytes.
+    ///
+    /// Args:
+    ///   message_id: The ID of the message
+    #[tool]
+    async fn list_attachments(&self, message_id: String) -> McpResult<String> {
+        info!("=== START list_attachments MCP command ===");
+        debug!("list_attachments called with message_id={}", message_id);
+
+        self.validate(Validator::new().required("message_id", &message_id))?;
+
+        let service = self.init_gmail_service().await?;
+        let attachments = service
+            .list_attachments(&message_id)
+            .await
+            .map_err(|err| {
+                error!(
+                    "Failed to list attachments for message_id='{}': {}",
+                    message_id, err
+                );
+                self.map_error(err)
+            })?;
+
+        let result = serde_json::to_string(&attachments).map_err(|e| {
+            let error_msg = format!("Failed to serialize attachment list: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END list_attachments MCP command (success) ===");
+        Ok(result)
+    }
+
+    /// Save an email attachment to disk
+    ///
+    /// The attachment is streamed to the attachment directory (ATTACHMENT_DIR) without
+    /// being held in memory, and is rejected if larger than ATTACHMENT_MAX_BYTES.
+    /// Returns the saved path and size in bytes.
+    ///
+    /// Args:
+    ///   message_id: The ID of the message
+    ///   attachment_id: The attachment ID from list_attachments
+    ///   filename: Optional file name to save as (defaults to the attachment's own name)
+    #[tool]
+    async fn get_attachment(
+        &self,
+        message_id: String,
+        attachment_id: String,
+        filename: Option<String>,
+    ) -> McpResult<String> {
+        use crate::attachments::{get_attachment_dir, unique_attachment_path, DownloadOptions};
+
+        info!("=== START get_attachment MCP command ===");
+        debug!(
+            "get_attachment called with message_id={}, attachment_id={}, filename={:?}",
+            message_id, attachment_id, filename
+        );
+
+        self.validate(
+            Validator::new()
+                .required("message_id", &message_id)
+                .required("attachment_id", &attachment_id),
+        )?;
+
+        let service = self.init_gmail_service().await?;
+
+        let filename = match filename {
+            Some(filename) => filename,
+            None => service
+                .list_attachments(&message_id)
+                .await
+                .map_err(|err| self.map_error(err))?
+                .into_iter()
+                .find(|attachment| attachment.attachment_id == attachment_id)
+                .map(|attachment| attachment.filename)
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| "attachment".to_string()),
+        };
+        let dest = unique_attachment_path(&get_attachment_dir(), &filename);
+
+        // Log progress every 10 MiB
+        let mut next_report: u64 = 10 * 1024 * 1024;
+        let options = DownloadOptions::default().with_progress(move |written, total| {
+            if written >= next_report {
+                info!("Downloaded {} of {:?} bytes", written, total);
+                next_report += 10 * 1024 * 1024;
+            }
+        });
+
+        let size = service
+            .download_attachment(&message_id, &attachment_id, &dest, options)
+            .await
+            .map_err(|err| {
+                error!(
+                    "Failed to download attachment '{}' of message_id='{}': {}",
+                    attachment_id, message_id, err
+                );
+                self.map_error(err)
+            })?;
+
+        info!("=== END get_attachment MCP command (success) ===");
+        Ok(json!({ "path": dest.display().to_string(), "size": size }).to_string())
+    }
+
+    /// Search for emails using a Gmail search query
+    ///
+    /// Returns emails with subject, sender, recipient, date and snippet information.
+    ///
+    /// Args:
+    ///   query: Gmail search query string (e.g. "is:unread from:example.com")
+    ///   max_results: Optional maximum number of results (default: 10). Can be a number (3) or a string ("3").
+    ///   deduplicate: Optional flag to collapse copies of the same message and group the results
+    ///     by thread, as for list_emails (default: false)
+    ///   snippet_length: Optional longest snippet in characters, as for list_emails
+    ///     (default: no limit)
+    ///   include_body: Optional bodies to include: "none", "text" or "both", as for
+    ///     list_emails (default: "both")
+    ///   mailbox: Optional address of a shared or delegated mailbox to use instead of your own
+    #[tool]
+    async fn search_emails(
+        &self,
+        query: String,
+        max_results: Option<serde_json::Value>,
+        deduplicate: Option<bool>,
+        snippet_length: Option<serde_json::Value>,
+        include_body: Option<String>,
+        mailbox: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START search_emails MCP command ===");
+        debug!(
+            "search_emails called with query={:?}, max_results={:?}, deduplicate={:?}, snippet_length={:?}, include_body={:?}",
+            query, max_results, deduplicate, snippet_length, include_body
+        );
+
+        self.validate(Validator::new().max_chars("query", &query, MAX_QUERY_CHARS))?;
+
+        // Get the parsed max_results value
+        let max = helpers::parse_max_results(max_results, 10);
+        let detail = self.listing_detail(snippet_length, include_body.as_deref())?;
+
+        // Get the Gmail service
+        let service = self.init_mailbox_service(mailbox).await?;
+
+        // Get messages with full metadata
+        let result = match service.list_messages(max, Some(&query)).await {
+            Ok(messages) => {
+                // Convert to JSON
+                self.message_list_json(messages, deduplicate.unwrap_or(false), detail)?
+            }
+            Err(err) => {
+                error!(
+                    "Failed to search emails with query='{}', max_results={}: {}",
+                    query, max, err
+                );
+
+                // Create detailed contextual error with specific advice for search queries
+                error!("Context: Failed to search emails with query: '{}'", query);
+
+                return Err(self.map_error(err));
+            }
+        };
+
+        info!("=== END search_emails MCP command (success) ===");
+        Ok(result)
+    }
+
+    /// Count the emails matching a Gmail search query
+    ///
+    /// Counts matches without fetching any message content, to answer questions
+    /// like "how many unread newsletters do I have?". Up to exact_limit matches are
+    /// counted exactly; beyond that the count is Gmail's estimate and "exact" is false.
+    ///
+    /// Returns an object with "query", "count" and "exact".
+    ///
+    /// Args:
+    ///   query: Gmail search query string (e.g. "is:unread category:promotions"). An
+    ///          empty query counts every message.
+    ///   exact_limit: Optional number of matches to count exactly (default: 1000). Can
+    ///                be a number (500) or a string ("500").
+    ///   mailbox: Optional address of a shared or delegated mailbox to use instead of your own
+    #[tool]
+    async fn count_emails(
+        &self,
+        query: String,
+        exact_limit: Option<serde_json::Value>,
+        mailbox: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START count_emails MCP command ===");
+        debug!(
+            "count_emails called with query={:?}, exact_limit={:?}",
+            query, exact_limit
+        );
+
+        self.validate(Validator::new().max_chars("query", &query, MAX_QUERY_CHARS))?;
+
+        let exact_limit =
+            helpers::parse_max_results(exact_limit, crate::gmail_api::DEFAULT_EXACT_COUNT_LIMIT);
+        let search = Some(query.trim()).filter(|q| !q.is_empty());
+
+        let service = self.init_mailbox_service(mailbox).await?;
+        let count = service
+            .count_messages(search, exact_limit)
+            .await
+            .map_err(|err| {
+                error!("Failed to count emails with query='{}': {}", query, err);
+                self.map_error(err)
+            })?;
+
+        info!("=== END count_emails MCP command (success) ===");
+        Ok(json!({ "query": query, "count": count.count, "exact": count.exact }).to_string())
+    }
+
+    /// Save a Gmail search query under a name
+    ///
+    /// Frequently used triage queries, such as "waiting on me" or "this week's
+    /// invoices", can be saved once and run by name with run_saved_search in later
+    /// sessions. Saving a search with an existing name replaces it.
+    ///
+    /// Args:
+    ///   name: Name of the search, e.g. "invoices-this-week"
+    ///   query: Gmail search query string (e.g. "subject:invoice newer_than:7d")
+    ///   description: Optional description of what the search finds
+    #[tool]
+    async fn save_search(
+        &self,
+        name: String,
+        query: String,
+        description: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START save_search MCP command ===");
+        debug!("save_search called with name={}, query={}", name, query);
+
+        self.validate(
+            Validator::new()
+                .required("name", &name)
+                .required("query", &query)
+                .max_chars("query", &query, MAX_QUERY_CHARS),
+        )?;
+
+        let search = crate::saved_searches::SavedSearch::new(&name, &query, description);
+        let store = crate::saved_searches::SavedSearchStore::from_env();
+        let replaced = store.save(search.clone()).map_err(|err| {
+            error!("Failed to save search: {}", err);
+            self.map_error(err)
+        })?;
+
+        let result = json!({
+            "status": "success",
+            "replaced": replaced,
+            "search": search
+        });
+
+        let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize saved search: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END save_search MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// List saved searches
+    ///
+    /// Returns each saved search's name, query, description and when it was saved.
+    #[tool]
+    async fn list_saved_searches(&self) -> McpResult<String> {
+        info!("=== START list_saved_searches MCP command ===");
+
+        let store = crate::saved_searches::SavedSearchStore::from_env();
+        let searches = store.list().map_err(|err| {
+            error!("Failed to list saved searches: {}", err);
+            self.map_error(err)
+        })?;
+
+        let result = json!({
+            "count": searches.len(),
+            "searches": searches
+        });
+
+        let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize saved searches: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END list_saved_searches MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Run a saved search
+    ///
+    /// Runs the query saved under the name, as search_emails would.
+    ///
+    /// Returns an object with "name", "query", "count" and "messages", the matching
+    /// emails as search_emails returns them.
+    ///
+    /// Args:
+    ///   name: Name of the saved search
+    ///   max_results: Optional maximum number of results (default: 10). Can be a number (3) or a string ("3").
+    ///   snippet_length: Optional longest snippet in characters, as for list_emails
+    ///     (default: no limit)
+    ///   include_body: Optional bodies to include: "none", "text" or "both", as for
+    ///     list_emails (default: "both")
+    ///   mailbox: Optional address of a shared or delegated mailbox to use instead of your own
+    #[tool]
+    async fn run_saved_search(
+        &self,
+        name: String,
+        max_results: Option<serde_json::Value>,
+        snippet_length: Option<serde_json::Value>,
+        include_body: Option<String>,
+        mailbox: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START run_saved_search MCP command ===");
+        debug!(
+            "run_saved_search called with name={}, max_results={:?}",
+            name, max_results
+        );
+
+        self.validate(Validator::new().required("name", &name))?;
+
+        let max = helpers::parse_max_results(max_results, 10);
+        let detail = self.listing_detail(snippet_length, include_body.as_deref())?;
+
+        let store = crate::saved_searches::SavedSearchStore::from_env();
+        let search = store.get(&name).map_err(|err| {
+            error!("Failed to find saved search {}: {}", name, err);
+            self.map_error(err)
+        })?;
+
+        let service = self.init_mailbox_service(mailbox).await?;
+        let mut messages = service
+            .list_messages(max, Some(&search.query))
+            .await
+            .map_err(|err| {
+                error!(
+                    "Failed to run saved search {} with query='{}': {}",
+                    search.name, search.query, err
+                );
+                self.map_error(err)
+            })?;
+        for message in &mut messages {
+            detail.apply(message);
+        }
+
+        let result = json!({
+            "name": search.name,
+            "query": search.query,
+            "count": messages.len(),
+            "messages": messages
+        });
+
+        let result_json = serde_json::to_string(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize saved search results: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END run_saved_search MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Export the emails matching a search as CSV
+    ///
+    /// Produces one row per message, for pivoting inbox data in a spreadsheet. Only
+    /// message metadata is fetched. Labels are given by name, separated by "; ", and
+    /// dates are RFC 3339. With a filename the CSV is written to CSV_EXPORT_DIR and
+    /// its path returned, replacing any earlier export of that name; otherwise the
+    /// CSV is returned inline as "csv".
+    ///
+    /// Args:
+    ///   query: Gmail search query string (e.g. "label:receipts after:2024/01/01"). An
+    ///          empty query exports the most recent messages.
+    ///   fields: Optional columns in order, from "id", "thread_id", "date", "from", "to",
+    ///           "subject", "snippet", "labels" and "has_attachment" (default: date, from,
+    ///           subject, labels, has_attachment)
+    ///   filename: Optional name of a file to write the CSV to instead of returning it
+    ///   max_results: Optional number of messages to export (default: 500, at most 5000).
+    ///                Can be a number (100) or a string ("100").
+    ///   mailbox: Optional address of a shared or delegated mailbox to use instead of your own
+    #[tool]
+    async fn export_emails_csv(
+        &self,
+        query: String,
+        fields: Option<Vec<String>>,
+        filename: Option<String>,
+        max_results: Option<serde_json::Value>,
+        mailbox: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START export_emails_csv MCP command ===");
+        debug!(
+            "export_emails_csv called with query={:?}, fields={:?}, filename={:?}, max_results={:?}",
+            query, fields, filename, max_results
+        );
+
+        self.validate(Validator::new().max_chars("query", &query, MAX_QUERY_CHARS))?;
+
+        let fields = crate::csv_export::parse_fields(fields.as_deref()).map_err(|e| {
+            error!("{}", e);
+            self.to_mcp_error(&e, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+        let max =
+            helpers::parse_max_results(max_results, crate::csv_export::DEFAULT_CSV_MAX_RESULTS)
+                .min(crate::csv_export::CSV_MAX_RESULTS);
+        let search = Some(query.trim()).filter(|q| !q.is_empty());
+
+        let service = self.init_mailbox_service(mailbox).await?;
+        let extra_headers: &[&str] = if fields.contains(&crate::csv_export::CsvField::To) {
+            &["To"]
+        } else {
+            &[]
+        };
+        let messages = service
+            .list_message_metadata(max, search, extra_headers)
+            .await
+            .map_err(|err| {
+                error!("Failed to list emails with query='{}': {}", query, err);
+                self.map_error(err)
+            })?;
+
+        // Label names and attachments are only looked up when exported
+        let mut label_names = std::collections::HashMap::new();
+        if fields.contains(&crate::csv_export::CsvField::Labels) {
+            let labels = service.list_labels().await.map_err(|err| {
+                error!("Failed to list labels: {}", err);
+                self.map_error(err)
+            })?;
+            let labels = crate::label_tree::parse_labels(&labels).map_err(|e| {
+                let error_msg = format!("Failed to parse labels: {}", e);
+                error!("{}", error_msg);
+                self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+            })?;
+            label_names.extend(labels.into_iter().map(|label| (label.id, label.name)));
+        }
+        let mut with_attachments = std::collections::HashSet::new();
+        if fields.contains(&crate::csv_export::CsvField::HasAttachment) {
+            let attachment_query = match search {
+                Some(search) => format!("({}) has:attachment", search),
+                None => "has:attachment".to_string(),
+            };
+            let ids = service
+                .list_message_ids(max, Some(&attachment_query))
+                .await
+                .map_err(|err| {
+                    error!(
+                        "Failed to list emails with query='{}': {}",
+                        attachment_query, err
+                    );
+                    self.map_error(err)
+                })?;
+            with_attachments.extend(ids);
+        }
+
+        let csv = crate::csv_export::to_csv(&fields, &messages, &label_names, &with_attachments);
+        let field_names: Vec<&str> = fields.iter().map(|field| field.name()).collect();
+        let mut result = json!({
+            "query": query,
+            "count": messages.len(),
+            "fields": field_names
+        });
+        match filename {
+            Some(filename) => {
+                let mut filename = crate::attachments::safe_filename(&filename);
+                if !filename.to_lowercase().ends_with(".csv") {
+                    filename.push_str(".csv");
+                }
+                let path = crate::csv_export::get_csv_export_dir().join(filename);
+                crate::csv_export::write_csv(&path, &csv).map_err(|e| {
+                    let error_msg = format!("Failed to write {}: {}", path.display(), e);
+                    error!("{}", error_msg);
+                    self.to_mcp_error(&error_msg, error_codes::GENERAL_ERROR)
+                })?;
+                result["path"] = json!(path.display().to_string());
+            }
+            None => result["csv"] = json!(csv),
+        }
+
+        let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize CSV export: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END export_emails_csv MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Report who sends the most email
+    ///
+    /// Groups the mail received over a period by sender, giving each sender's
+    /// message count, how many are unread, the unread ratio and when they last
+    /// wrote. Frequent senders with a high unread ratio are good candidates for
+    /// filters or unsubscribing. Only message metadata is fetched.
+    ///
+    /// Args:
+    ///   time_range: Optional period to cover as days, weeks, months or years, e.g.
+    ///               "7d", "2w", "3m" or "1y". Default is "30d".
+    ///   limit: Optional number of senders to return (default: 20). Can be a number
+    ///          (10) or a string ("10").
+    ///   max_messages: Optional number of recent messages to scan (default: 1000).
+    #[tool]
+    async fn sender_report(
+        &self,
+        time_range: Option<String>,
+        limit: Option<serde_json::Value>,
+        max_messages: Option<u32>,
+    ) -> McpResult<String> {
+        info!("=== START sender_report MCP command ===");
+        debug!(
+            "sender_report called with time_range={:?}, limit={:?}, max_messages={:?}",
+            time_range, limit, max_messages
+        );
+
+        let time_range = time_range
+            .unwrap_or_else(|| crate::sender_report::DEFAULT_SENDER_REPORT_RANGE.to_string());
+        let query = crate::sender_report::parse_time_range(&time_range).map_err(|e| {
+            error!("{}", e);
+            self.to_mcp_error(&e, error_codes::API_ERROR)
+        })?;
+        let limit = helpers::parse_max_results(
+            limit,
+            crate::sender_report::DEFAULT_SENDER_REPORT_LIMIT as u32,
+        );
+        let max_messages =
+            max_messages.unwrap_or(crate::sender_report::DEFAULT_SENDER_REPORT_MAX_MESSAGES);
+
+        let service = self.init_gmail_service().await?;
+        let messages = service
+            .list_message_metadata(max_messages, Some(&query), &[])
+            .await
+            .map_err(|err| {
+                error!("Failed to list messages for sender report: {}", err);
+                self.map_error(err)
+            })?;
+
+        let truncated = messages.len() as u32 >= max_messages;
+        let report = crate::sender_report::SenderReport::from_messages(
+            &query,
+            &messages,
+            limit as usize,
+            truncated,
+        );
+        let result_json = serde_json::to_string_pretty(&report).map_err(|e| {
+            let error_msg = format!("Failed to serialize sender report: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END sender_report MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// List conversations still waiting on a reply
+    ///
+    /// Checks the threads of the emails sent over the last few days and lists those
+    /// where the user sent the last message, so nobody has replied yet. Each entry
+    /// gives the thread and last message IDs, the subject, who it was sent to, when
+    /// it was sent and how many days it has been waiting, longest wait first.
+    ///
+    /// Args:
+    ///   days: Optional number of days of sent mail to check (default: 14)
+    #[tool]
+    async fn awaiting_reply(&self, days: Option<u32>) -> McpResult<String> {
+        info!("=== START awaiting_reply MCP command ===");
+        debug!("awaiting_reply called with days={:?}", days);
+
+        let days = days.unwrap_or(crate::follow_ups::DEFAULT_AWAITING_REPLY_DAYS);
+        self.validate(Validator::new().range("days", days, 1..))?;
+        let query = crate::follow_ups::sent_mail_query(days);
+
+        let service = self.init_gmail_service().await?;
+        let sent = service
+            .list_message_metadata(
+                crate::follow_ups::AWAITING_REPLY_MAX_MESSAGES,
+                Some(&query),
+                &[],
+            )
+            .await
+            .map_err(|err| {
+                error!("Failed to list sent messages: {}", err);
+                self.map_error(err)
+            })?;
+
+        let mut thread_ids: Vec<String> = Vec::new();
+        for message in sent {
+            if !thread_ids.contains(&message.thread_id) {
+                thread_ids.push(message.thread_id);
+            }
+        }
+        let threads = service
+            .list_thread_metadata(&thread_ids, crate::follow_ups::AWAITING_REPLY_HEADERS)
+            .await
+            .map_err(|err| {
+                error!("Failed to get sent threads: {}", err);
+                self.map_error(err)
+            })?;
+
+        let now = chrono::Utc::now();
+        let mut awaiting: Vec<_> = threads
+            .iter()
+            .filter_map(|thread| crate::follow_ups::awaiting_reply(thread, now))
+            .collect();
+        awaiting.sort_by_key(|thread| thread.sent_at);
+
+        info!("=== END awaiting_reply MCP command (success) ===");
+        let result = json!({
+            "query": query,
+            "threads_checked": threads.len(),
+            "awaiting": awaiting
+        });
+        serde_json::to_string_pretty(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize awaiting replies: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })
+    }
+
+    /// Review a week of mail and meetings
+    ///
+    /// Gathers what is needed for a weekly review of the week from Monday 00:00 UTC:
+    /// how much mail was received and sent, in total and per day, the meetings
+    /// attended from the primary calendar, the busiest threads, and the outstanding
+    /// action items, which are the starred messages and the week's sent emails still
+    /// awaiting a reply. Only message metadata is fetched.
+    ///
+    /// Returns an object with the "review" ("week_start", "week_end", "volume",
+    /// "meetings", "meeting_minutes", "action_items" and "key_threads") and an
+    /// "analysis_prompt" for writing it up.
+    ///
+    /// Args:
+    ///   week_offset: Optional week to review relative to this one: 0 for this week,
+    ///                -1 for last week and so on, back to -520. Default is 0.
+    #[tool]
+    async fn weekly_review(&self, week_offset: Option<i64>) -> McpResult<String> {
+        info!("=== START weekly_review MCP command ===");
+        debug!("weekly_review called with week_offset={:?}", week_offset);
+
+        let week_offset = week_offset.unwrap_or(0);
+        self.validate(Validator::new().range(
+            "week_offset",
+            week_offset,
+            -crate::weekly_review::MAX_WEEKS_BACK..=0,
+        ))?;
+        let now = chrono::Utc::now();
+        let Some((start, end)) = crate::weekly_review::week_bounds(week_offset, now) else {
+            let error_msg = format!("Failed to work out the dates of week {}", week_offset);
+            error!("{}", error_msg);
+            return Err(self.to_mcp_error(&error_msg, error_codes::GENERAL_ERROR));
+        };
+        let query = crate::weekly_review::week_query(start, end);
+
+        let service = self.init_gmail_service().await?;
+        let messages = service
+            .list_message_metadata(
+                crate::weekly_review::WEEKLY_REVIEW_MAX_MESSAGES,
+                Some(&query),
+                &[],
+            )
+            .await
+            .map_err(|err| {
+                error!("Failed to list messages of the week: {}", err);
+                self.map_error(err)
+            })?;
+        let starred = service
+            .list_message_metadata(
+                crate::weekly_review::WEEKLY_REVIEW_MAX_STARRED,
+                Some("is:starred"),
+                &[],
+            )
+            .await
+            .map_err(|err| {
+                error!("Failed to list starred messages: {}", err);
+                self.map_error(err)
+            })?;
+
+        let mut sent_threads: Vec<String> = Vec::new();
+        for message in &messages {
+            if message.label_ids.iter().any(|id| id == "SENT")
+                && !sent_threads.contains(&message.thread_id)
+            {
+                sent_threads.push(message.thread_id.clone());
+            }
+        }
+        let threads = service
+            .list_thread_metadata(&sent_threads, crate::follow_ups::AWAITING_REPLY_HEADERS)
+            .await
+            .map_err(|err| {
+                error!("Failed to get sent threads: {}", err);
+                self.map_error(err)
+            })?;
+        let mut awaiting: Vec<_> = threads
+            .iter()
+            .filter_map(|thread| crate::follow_ups::awaiting_reply(thread, now))
+            .collect();
+        awaiting.sort_by_key(|thread| thread.sent_at);
+
+        let user_email = service.get_email_address().await.map_err(|err| {
+            error!("Failed to get the user's email address: {}", err);
+            self.map_error(err)
+        })?;
+        let calendar_client = self.init_calendar_service().await?;
+        let events = calendar_client
+            .list_events(
+                "primary",
+                Some(crate::weekly_review::WEEKLY_REVIEW_MAX_EVENTS),
+                Some(start),
+                Some(end),
+            )
+            .await
+            .map_err(|err| {
+                error!("Failed to list the week's events: {}", err);
+                self.map_error(err)
+            })?;
+
+        let review = crate::weekly_review::WeeklyReview::new(
+            (start, end),
+            &messages,
+            &starred,
+            awaiting,
+            &events,
+            &user_email,
+        );
+        let result = json!({
+            "review": review,
+            "analysis_prompt": crate::prompts::WEEKLY_REVIEW_PROMPT
+        });
+        let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize weekly review: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END weekly_review MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Digest of newsletters and other bulk mail
+    ///
+    /// Finds mailing list and bulk mail received over a period, recognised by its
+    /// List-Id or Precedence headers, and groups it by list or sender. Each sender
+    /// comes with its message and unread counts, its latest subjects and snippets,
+    /// and how to unsubscribe: a mailto address, a web page, and whether the page
+    /// supports one-click unsubscribe. Only message metadata is fetched.
+    ///
+    /// Args:
+    ///   time_range: Optional period to cover as days, weeks, months or years, e.g.
+    ///               "7d", "2w", "3m" or "1y". Default is "30d".
+    ///   max_messages: Optional number of recent messages to scan (default: 1000).
+    #[tool]
+    async fn newsletter_digest(
+        &self,
+        time_range: Option<String>,
+        max_messages: Option<u32>,
+    ) -> McpResult<String> {
+        info!("=== START newsletter_digest MCP command ===");
+        debug!(
+            "newsletter_digest called with time_range={:?}, max_messages={:?}",
+            time_range, max_messages
+        );
+
+        let time_range = time_range
+            .unwrap_or_else(|| crate::sender_report::DEFAULT_SENDER_REPORT_RANGE.to_string());
+        let query = crate::sender_report::parse_time_range(&time_range).map_err(|e| {
+            error!("{}", e);
+            self.to_mcp_error(&e, error_codes::API_ERROR)
+        })?;
+        let max_messages =
+            max_messages.unwrap_or(crate::sender_report::DEFAULT_SENDER_REPORT_MAX_MESSAGES);
+
+        let service = self.init_gmail_service().await?;
+        let messages = service
+            .list_message_metadata(
+                max_messages,
+                Some(&query),
+                crate::newsletters::BULK_MAIL_HEADERS,
+            )
+            .await
+            .map_err(|err| {
+                error!("Failed to list messages for newsletter digest: {}", err);
+                self.map_error(err)
+            })?;
+
+        let truncated = messages.len() as u32 >= max_messages;
+        let digest =
+            crate::newsletters::NewsletterDigest::from_messages(&query, &messages, truncated);
+        let result_json = serde_json::to_string_pretty(&digest).map_err(|e| {
+            let error_msg = format!("Failed to serialize newsletter digest: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END newsletter_digest MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// List bounced emails
+    ///
+    /// Finds the delivery status notifications (bounces) received over a period and
+    /// reports, for each, which recipients failed, the RFC 3463 status code (e.g.
+    /// "5.1.1"), what it means, whether the failure is permanent, and the remote
+    /// server's reply. The subject and Message-ID of the message that bounced are
+    /// included when the bounce returned its headers. `failed_recipients` lists
+    /// every address that failed across all bounces.
+    ///
+    /// Args:
+    ///   time_range: Optional period to cover as days, weeks, months or years, e.g.
+    ///               "7d", "2w", "3m" or "1y". Default is "30d".
+    ///   max_results: Optional maximum number of candidate messages to check
+    ///                (default: 50). Can be a number (3) or a string ("3").
+    #[tool]
+    async fn list_bounces(
+        &self,
+        time_range: Option<String>,
+        max_results: Option<serde_json::Value>,
+    ) -> McpResult<String> {
+        info!("=== START list_bounces MCP command ===");
+        debug!(
+            "list_bounces called with time_range={:?}, max_results={:?}",
+            time_range, max_results
+        );
+
+        let time_range = time_range
+            .unwrap_or_else(|| crate::sender_report::DEFAULT_SENDER_REPORT_RANGE.to_string());
+        let range_query = crate::sender_report::parse_time_range(&time_range).map_err(|e| {
+            error!("{}", e);
+            self.to_mcp_error(&e, error_codes::API_ERROR)
+        })?;
+        let query = format!("{} {}", range_query, crate::bounces::BOUNCE_QUERY);
+        let max_results =
+            helpers::parse_max_results(max_results, crate::bounces::DEFAULT_BOUNCE_MAX_RESULTS);
+
+        let service = self.init_gmail_service().await?;
+        let messages = service
+            .list_messages(max_results, Some(&query))
+            .await
+            .map_err(|err| {
+                error!("Failed to list messages for bounces: {}", err);
+                self.map_error(err)
+            })?;
+
+        let bounces = crate::bounces::BounceList::from_messages(&query, messages);
+        let result_json = serde_json::to_string_pretty(&bounces).map_err(|e| {
+            let error_msg = format!("Failed to serialize bounces: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END list_bounces MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Search emails using the local index when possible
+    ///
+    /// Answers instantly and without API quota from a local index of recent message
+    /// metadata when the index is fresh and the query only uses free text and the
+    /// from:, to: and subject: operators. Otherwise falls back to a Gmail search and
+    /// refreshes the index in the background. Locally answered messages include
+    /// snippets but not bodies; use get_email for the full message.
+    ///
+    /// Returns an object with "source" ("local" or "gmail") and "messages".
+    ///
+    /// Args:
+    ///   query: Search query string (e.g. "from:alice budget")
+    ///   max_results: Optional maximum number of results (default: 10). Can be a number (3) or a string ("3").
+    #[tool]
+    async fn search_local(
+        &self,
+        query: String,
+        max_results: Option<serde_json::Value>,
+    ) -> McpResult<String> {
+        info!("=== START search_local MCP command ===");
+        debug!(
+            "search_local called with query={:?}, max_results={:?}",
+            query, max_results
+        );
+
+        self.validate(Validator::new().max_chars("query", &query, MAX_QUERY_CHARS))?;
+
+        let max = helpers::parse_max_results(max_results, 10);
+
+        #[cfg(feature = "local-index")]
+        let refresh_index = match self.search_local_index(&query, max) {
+            LocalSearch::Answered(messages) => {
+                info!("=== END search_local MCP command (local) ===");
+                return self.search_result_json("local", &messages);
+            }
+            LocalSearch::Missed { stale } => stale,
+        };
+
+        let service = self.init_gmail_service().await?;
+        let messages = service
+            .list_messages(max, Some(&query))
+            .await
+            .map_err(|err| {
+                error!("Failed to search emails with query='{}': {}", query, err);
+                self.map_error(err)
+            })?;
+
+        #[cfg(feature = "local-index")]
+        if refresh_index {
+            self.spawn_index_refresh().await;
+        }
+
+        info!("=== END search_local MCP command (gmail) ===");
+        self.search_result_json("gmail", &messages)
+    }
+
+    /// Get a list of email labels
+    ///
+    /// Returns the raw JSON response from the Gmail API without any transformation or modification.
+    ///
+    /// Args:
+    ///   mailbox: Optional address of a shared or delegated mailbox to use instead of your own
+    #[tool]
+    async fn list_labels(&self, mailbox: Option<String>) -> McpResult<String> {
+        debug!("list_labels called");
+
+        // Get the Gmail service
+        let service = self.init_mailbox_service(mailbox).await?;
+
+        // Get labels
+        match service.list_labels().await {
+            Ok(labels) => Ok(labels),
+            Err(err) => {
+                error!("Failed to list labels: {}", err);
+
+                // Provide detailed error with troubleshooting steps
+                // Include detailed context in the error log
+                error!("Context: Failed to retrieve Gmail labels. This operation requires read access permissions.");
+
+                Err(self.map_error(err))
+            }
+        }
+    }
+
+    /// Show the user's labels as nested in Gmail's sidebar
+    ///
+    /// Labels are nested by name, so "Projects/Apollo" sits inside "Projects".
+    /// Each label comes with its full name, its short name and the labels
+    /// inside it. A parent with no label of its own, whose children were made
+    /// without it, is listed with a null id.
+    ///
+    /// Args:
+    ///   include_system: Optional flag to also list Gmail's own labels, such as INBOX and UNREAD (default false)
+    ///   mailbox: Optional address of a shared or delegated mailbox to use instead of your own
+    #[tool]
+    async fn get_label_tree(
+        &self,
+        include_system: Option<bool>,
+        mailbox: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START get_label_tree MCP command ===");
+        debug!(
+            "get_label_tree called with include_system={:?}, mailbox={:?}",
+            include_system, mailbox
+        );
+
+        let service = self.init_mailbox_service(mailbox).await?;
+        let labels = service.list_labels().await.map_err(|err| {
+            error!("Failed to list labels: {}", err);
+            self.map_error(err)
+        })?;
+        let labels = crate::label_tree::parse_labels(&labels).map_err(|e| {
+            let error_msg = format!("Failed to parse labels: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        let tree = crate::label_tree::build_label_tree(&labels, include_system.unwrap_or(false));
+        let result = json!({
+            "count": labels
+                .iter()
+                .filter(|label| include_system.unwrap_or(false) || !label.is_system())
+                .count(),
+            "labels": tree
+        });
+
+        let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize label tree: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END get_label_tree MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Create a label, along with any labels it is nested in
+    ///
+    /// A nested name such as "Projects/Apollo/Design" creates "Projects" and
+    /// "Projects/Apollo" first if they are missing, so the new label shows up
+    /// in its place in Gmail's sidebar. Existing labels are matched ignoring
+    /// case and left as they are.
+    ///
+    /// Args:
+    ///   name: The label's full name, with "/" between nesting levels
+    ///   mailbox: Optional address of a shared or delegated mailbox to use instead of your own
+    #[tool]
+    async fn create_label(&self, name: String, mailbox: Option<String>) -> McpResult<String> {
+        info!("=== START create_label MCP command ===");
+        debug!(
+            "create_label called with name={}, mailbox={:?}",
+            name, mailbox
+        );
+
+        self.validate(Validator::new().required("name", &name))?;
+
+        if let Err(error_msg) = crate::label_tree::normalize_label_name(&name) {
+            error!("{}", error_msg);
+            return Err(self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR));
+        }
+
+        let service = self.init_mailbox_service(mailbox).await?;
+        let creation = service.create_label(&name).await.map_err(|err| {
+            error!("Failed to create label {}: {}", name, err);
+            self.map_error(err)
+        })?;
+
+        let result = json!({
+            "status": if creation.created.is_empty() { "exists" } else { "created" },
+            "id": creation.id,
+            "name": creation.name,
+            "created": creation.created
+        });
+
+        let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize label: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END create_label MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Suggest labels for an email from how earlier mail was labeled
+    ///
+    /// Looks at the labels on earlier mail from the same sender, and from others
+    /// at the sender's domain unless it is a public one such as gmail.com, and
+    /// at labels whose names the subject mentions. Only your own labels are
+    /// suggested, never system labels or ones the email already has. Each
+    /// suggestion has a score from 0 to 1 and the reasons for it. The result
+    /// also carries the email categorization guide, for choosing a label when
+    /// earlier mail gives no hint.
+    ///
+    /// Args:
+    ///   message_id: The ID of the message to suggest labels for
+    ///   apply: Optional flag to put the top suggestion on the message (default: false)
+    ///   mailbox: Optional address of a shared or delegated mailbox to use instead of your own
+    #[tool]
+    async fn suggest_labels(
+        &self,
+        message_id: String,
+        apply: Option<bool>,
+        mailbox: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START suggest_labels MCP command ===");
+        debug!(
+            "suggest_labels called with message_id={}, apply={:?}",
+            message_id, apply
+        );
+
+        self.validate(Validator::new().required("message_id", &message_id))?;
+
+        let service = self.init_mailbox_service(mailbox).await?;
+        let message = service
+            .get_message_metadata(&message_id, &[])
+            .await
+            .map_err(|err| {
+                error!(
+                    "Failed to get message_id='{}' for label suggestions: {}",
+                    message_id, err
+                );
+                self.map_error(err)
+            })?;
+        let labels = service.list_labels().await.map_err(|err| {
+            error!("Failed to list labels: {}", err);
+            self.map_error(err)
+        })?;
+        let labels = crate::label_tree::parse_labels(&labels).map_err(|e| {
+            let error_msg = format!("Failed to parse labels: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        // Earlier mail from the sender, or their domain
+        let history = match message
+            .from
+            .as_deref()
+            .and_then(crate::label_suggestions::history_query)
+        {
+            Some(query) => service
+                .list_message_metadata(
+                    crate::label_suggestions::DEFAULT_SENDER_HISTORY,
+                    Some(&query),
+                    &[],
+                )
+                .await
+                .map_err(|err| {
+                    error!(
+                        "Failed to list earlier mail with query='{}': {}",
+                        query, err
+                    );
+                    self.map_error(err)
+                })?,
+            None => Vec::new(),
+        };
+
+        let mut suggestions = crate::label_suggestions::suggest_labels(&message, &history, &labels);
+        suggestions.truncate(crate::label_suggestions::DEFAULT_LABEL_SUGGESTIONS);
+
+        let applied = match suggestions.first() {
+            Some(top) if apply.unwrap_or(false) => {
+                service
+                    .modify_labels(&message_id, std::slice::from_ref(&top.label_id), &[])
+                    .await
+                    .map_err(|err| {
+                        error!(
+                            "Failed to label message_id='{}' with '{}': {}",
+                            message_id, top.name, err
+                        );
+                        self.map_error(err)
+                    })?;
+                Some(top.name.clone())
+            }
+            _ => None,
+        };
+
+        let current_labels: Vec<&str> = message
+            .label_ids
+            .iter()
+            .map(|id| {
+                labels
+                    .iter()
+                    .find(|label| &label.id == id)
+                    .map_or(id.as_str(), |label| label.name.as_str())
+            })
+            .collect();
+        let result = json!({
+            "message_id": message_id,
+            "from": message.from,
+            "subject": message.subject,
+            "current_labels": current_labels,
+            "earlier_messages": history.iter().filter(|earlier| earlier.id != message.id).count(),
+            "suggestions": suggestions,
+            "applied": applied,
+            "categorization_prompt": crate::prompts::EMAIL_CATEGORIZATION_PROMPT
+        });
+
+        let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize label suggestions: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END suggest_labels MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Add and remove labels on a whole conversation
+    ///
+    /// Applies the label changes to every message in the thread, which is how
+    /// conversations are usually triaged. Use list_labels to find label IDs;
+    /// system labels include INBOX, UNREAD, STARRED and IMPORTANT, so removing
+    /// INBOX archives the conversation.
+    ///
+    /// Args:
+    ///   thread_id: The ID of the thread, as returned in an email's thread_id
+    ///   add_label_ids: Optional label IDs to add
+    ///   remove_label_ids: Optional label IDs to remove
+    ///   mailbox: Optional address of a shared or delegated mailbox to use instead of your own
+    #[tool]
+    async fn modify_thread_labels(
+        &self,
+        thread_id: String,
+        add_label_ids: Option<Vec<String>>,
+        remove_label_ids: Option<Vec<String>>,
+        mailbox: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START modify_thread_labels MCP command ===");
+        debug!(
+            "modify_thread_labels called with thread_id={}, add_label_ids={:?}, remove_label_ids={:?}",
+            thread_id, add_label_ids, remove_label_ids
+        );
+
+        self.validate(Validator::new().required("thread_id", &thread_id))?;
+
+        let add_label_ids = add_label_ids.unwrap_or_default();
+        let remove_label_ids = remove_label_ids.unwrap_or_default();
+        if add_label_ids.is_empty() && remove_label_ids.is_empty() {
+            let error_msg = "At least one label to add or remove is required";
+            error!("{}", error_msg);
+            return Err(self.to_mcp_error(error_msg, error_codes::MESSAGE_FORMAT_ERROR));
+        }
+
+        let service = self.init_mailbox_service(mailbox).await?;
+        let message_ids = service
+            .modify_thread_labels(&thread_id, &add_label_ids, &remove_label_ids)
+            .await
+            .map_err(|err| {
+                error!("Failed to modify labels on thread {}: {}", thread_id, err);
+                self.map_error(err)
+            })?;
+
+        info!("=== END modify_thread_labels MCP command (success) ===");
+        self.thread_result_json(json!({
+            "status": "success",
+            "thread_id": thread_id,
+            "message_ids": message_ids,
+            "added_label_ids": add_label_ids,
+            "removed_label_ids": remove_label_ids
+        }))
+    }
+
+    /// Move a whole conversation to the trash
+    ///
+    /// Trashes every message in the thread. Trashed messages are deleted by
+    /// Gmail after 30 days and can be restored until then.
+    ///
+    /// Args:
+    ///   thread_id: The ID of the thread, as returned in an email's thread_id
+    ///   mailbox: Optional address of a shared or delegated mailbox to use instead of your own
+    #[tool]
+    async fn trash_thread(&self, thread_id: String, mailbox: Option<String>) -> McpResult<String> {
+        info!("=== START trash_thread MCP command ===");
+        debug!("trash_thread called with thread_id={}", thread_id);
+
+        self.validate(Validator::new().required("thread_id", &thread_id))?;
+
+        let service = self.init_mailbox_service(mailbox).await?;
+        let message_ids = service.trash_thread(&thread_id).await.map_err(|err| {
+            error!("Failed to trash thread {}: {}", thread_id, err);
+            self.map_error(err)
+        })?;
+
+        info!("=== END trash_thread MCP command (success) ===");
+        self.thread_result_json(json!({
+            "status": "success",
+            "thread_id": thread_id,
+            "message_ids": message_ids
+        }))
+    }
+
+    /// Mark a whole conversation as read or unread
+    ///
+    /// Args:
+    ///   thread_id: The ID of the thread, as returned in an email's thread_id
+    ///   unread: Whether to mark the conversation as unread instead. Default is false.
+    ///   mailbox: Optional address of a shared or delegated mailbox to use instead of your own
+    #[tool]
+    async fn mark_thread_read(
+        &self,
+        thread_id: String,
+        unread: Option<bool>,
+        mailbox: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START mark_thread_read MCP command ===");
+        debug!(
+            "mark_thread_read called with thread_id={}, unread={:?}",
+            thread_id, unread
+        );
+
+        self.validate(Validator::new().required("thread_id", &thread_id))?;
+
+        let unread = unread.unwrap_or(false);
+        let unread_label = vec!["UNREAD".to_string()];
+        let (add_label_ids, remove_label_ids) = if unread {
+            (unread_label, Vec::new())
+        } else {
+            (Vec::new(), unread_label)
+        };
+
+        let service = self.init_mailbox_service(mailbox).await?;
+        let message_ids = service
+            .modify_thread_labels(&thread_id, &add_label_ids, &remove_label_ids)
+            .await
+            .map_err(|err| {
+                error!("Failed to mark thread {} as read: {}", thread_id, err);
+                self.map_error(err)
+            })?;
+
+        info!("=== END mark_thread_read MCP command (success) ===");
+        self.thread_result_json(json!({
+            "status": "success",
+            "thread_id": thread_id,
+            "message_ids": message_ids,
+            "unread": unread
+        }))
+    }
+
+    /// List who has delegated access to a mailbox
+    ///
+    /// Delegates can read, send and delete mail in the mailbox on its owner's behalf.
+    /// Google only lets Workspace accounts manage delegates through the API, using a
+    /// service account with domain-wide authority.
+    ///
+    /// Args:
+    ///   mailbox: Optional address of the mailbox (default: your own)
+    #[tool]
+    async fn list_delegates(&self, mailbox: Option<String>) -> McpResult<String> {
+        info!("=== START list_delegates MCP command ===");
+        debug!("list_delegates called with mailbox={:?}", mailbox);
+
+        let service = self.init_mailbox_service(mailbox).await?;
+        let delegates = service.list_delegates().await.map_err(|err| {
+            error!("Failed to list delegates of {}: {}", service.mailbox(), err);
+            self.map_error(err)
+        })?;
+
+        let result = json!({
+            "mailbox": service.mailbox(),
+            "count": delegates.len(),
+            "delegates": delegates
+        });
+
+        let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize delegates: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END list_delegates MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Give someone delegated access to a mailbox
+    ///
+    /// The delegate can then read, send and delete mail in the mailbox on its owner's
+    /// behalf. Google only lets Workspace accounts add delegates through the API, using a
+    /// service account with domain-wide authority, and only within the same organization.
+    ///
+    /// Args:
+    ///   delegate_email: Email address of the person to give access to
+    ///   mailbox: Optional address of the mailbox (default: your own)
+    #[tool]
+    async fn add_delegate(
+        &self,
+        delegate_email: String,
+        mailbox: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START add_delegate MCP command ===");
+        debug!(
+            "add_delegate called with delegate_email={}, mailbox={:?}",
+            delegate_email, mailbox
+        );
+
+        self.validate(Validator::new().email("delegate_email", &delegate_email))?;
+
+        // Validated as exactly one address
+        let address = crate::utils::parse_email_addresses(&delegate_email)
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+
+        let service = self.init_mailbox_service(mailbox).await?;
+        let delegate = service.add_delegate(&address).await.map_err(|err| {
+            error!(
+                "Failed to add delegate {} to {}: {}",
+                address,
+                service.mailbox(),
+                err
+            );
+            self.map_error(err)
+        })?;
+
+        let result = json!({
+            "status": "success",
+            "mailbox": service.mailbox(),
+            "delegate": delegate
+        });
+
+        let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize delegate: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END add_delegate MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// List the addresses mail can be forwarded to, and whether it is
+    ///
+    /// Returns an object with the "mailbox", its "forwarding_addresses", each with
+    /// "forwardingEmail" and "verificationStatus" ("accepted" or "pending"), and the
+    /// current "auto_forwarding" setting: "enabled", "emailAddress" and "disposition".
+    /// Only accepted addresses can be forwarded to.
+    ///
+    /// Args:
+    ///   mailbox: Optional address of a shared or delegated mailbox to use instead of your own
+    #[tool]
+    async fn list_forwarding_addresses(&self, mailbox: Option<String>) -> McpResult<String> {
+        info!("=== START list_forwarding_addresses MCP command ===");
+        debug!(
+            "list_forwarding_addresses called with mailbox={:?}",
+            mailbox
+        );
+
+        let service = self.init_mailbox_service(mailbox).await?;
+        let addresses = service.list_forwarding_addresses().await.map_err(|err| {
+            error!("Failed to list forwarding addresses: {}", err);
+            self.map_error(err)
+        })?;
+        let auto_forwarding = service.get_auto_forwarding().await.map_err(|err| {
+            error!("Failed to get auto-forwarding: {}", err);
+            self.map_error(err)
+        })?;
+
+        let result = json!({
+            "mailbox": service.mailbox(),
+            "forwarding_addresses": addresses,
+            "auto_forwarding": auto_forwarding
+        });
+
+        let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize forwarding addresses: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END list_forwarding_addresses MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Turn automatic forwarding of new mail on or off
+    ///
+    /// Forwarding sends every new email to another mailbox, so nothing changes until
+    /// this is called again with `confirm` set: without it the result shows the
+    /// current and proposed settings. Mail can only be forwarded to a verified
+    /// forwarding address (see list_forwarding_addresses); new addresses are added
+    /// and confirmed in Gmail's settings. Google only allows the change through the
+    /// API for Workspace accounts, using a service account with domain-wide authority
+    /// and the gmail.settings.sharing scope.
+    ///
+    /// Returns an object with the "mailbox", the "current" and "proposed" settings,
+    /// and "applied", whether the change was made.
+    ///
+    /// Args:
+    ///   enabled: Whether to forward new mail
+    ///   email_address: Optional verified forwarding address to forward to (default: the one set before)
+    ///   disposition: Optional treatment of forwarded mail: "leaveInInbox", "archive", "trash" or
+    ///                "markRead" (default: as set before, or leaveInInbox)
+    ///   confirm: Optional. Set to true to make the change (default: false)
+    ///   mailbox: Optional address of a shared or delegated mailbox to use instead of your own
+    #[tool]
+    async fn set_auto_forwarding(
+        &self,
+        enabled: bool,
+        email_address: Option<String>,
+        disposition: Option<String>,
+        confirm: Option<bool>,
+        mailbox: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START set_auto_forwarding MCP command ===");
+        debug!(
+            "set_auto_forwarding called with enabled={}, email_address={:?}, disposition={:?}, confirm={:?}",
+            enabled, email_address, disposition, confirm
+        );
+
+        self.validate(
+            Validator::new().optional(email_address.as_deref(), |v, address| {
+                v.email("email_address", address)
+            }),
+        )?;
+
+        let service = self.init_mailbox_service(mailbox).await?;
+        let current = service.get_auto_forwarding().await.map_err(|err| {
+            error!("Failed to get auto-forwarding: {}", err);
+            self.map_error(err)
+        })?;
+        let addresses = service.list_forwarding_addresses().await.map_err(|err| {
+            error!("Failed to list forwarding addresses: {}", err);
+            self.map_error(err)
+        })?;
+
+        let proposed = crate::forwarding::plan_auto_forwarding(
+            &current,
+            &addresses,
+            enabled,
+            email_address.as_deref(),
+            disposition.as_deref(),
+        )
+        .map_err(|error_msg| {
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        let applied = confirm.unwrap_or(false);
+        let proposed = if applied {
+            service
+                .update_auto_forwarding(&proposed)
+                .await
+                .map_err(|err| {
+                    error!("Failed to update auto-forwarding: {}", err);
+                    self.map_error(err)
+                })?
+        } else {
+            proposed
+        };
+
+        let result = json!({
+            "mailbox": service.mailbox(),
+            "current": current,
+            "proposed": proposed,
+            "applied": applied
+        });
+
+        let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize auto-forwarding: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END set_auto_forwarding MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Get the settings mail apps use to reach the mailbox
+    ///
+    /// Reads the IMAP, POP and language settings, which explain most questions about
+    /// why a mail app such as Outlook or Apple Mail does not sync: IMAP or POP being
+    /// off, folders limited to the latest messages, or what happens to downloaded
+    /// and deleted mail.
+    ///
+    /// Returns an object with the "mailbox", "imap" ("enabled", "autoExpunge",
+    /// "expungeBehavior" and "maxFolderSize"), "pop" ("accessWindow" and
+    /// "disposition"), "language" ("displayLanguage") and "sync_notes", plain
+    /// explanations of the settings that affect syncing.
+    ///
+    /// Args:
+    ///   mailbox: Optional address of a shared or delegated mailbox to use instead of your own
+    #[tool]
+    async fn get_mail_settings(&self, mailbox: Option<String>) -> McpResult<String> {
+        info!("=== START get_mail_settings MCP command ===");
+        debug!("get_mail_settings called with mailbox={:?}", mailbox);
+
+        let service = self.init_mailbox_service(mailbox).await?;
+        let imap = service.get_imap_settings().await.map_err(|err| {
+            error!("Failed to get IMAP settings: {}", err);
+            self.map_error(err)
+        })?;
+        let pop = service.get_pop_settings().await.map_err(|err| {
+            error!("Failed to get POP settings: {}", err);
+            self.map_error(err)
+        })?;
+        let language = service.get_language_settings().await.map_err(|err| {
+            error!("Failed to get language settings: {}", err);
+            self.map_error(err)
+        })?;
+
+        let result = json!({
+            "mailbox": service.mailbox(),
+            "imap": imap,
+            "pop": pop,
+            "language": language,
+            "sync_notes": crate::mail_settings::sync_notes(&imap, &pop)
+        });
+
+        let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize mail settings: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END get_mail_settings MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Check connection status with Gmail API
+    ///
+    /// Tests the connection to Gmail API by retrieving the user's profile.
+    /// Returns the raw JSON response from the Gmail API, with a "token" object
+    /// describing the current access token added: the account "email", its
+    /// "scopes" and the "missing_scopes" some tools need, "expires_in_seconds",
+    /// "expires_at" and "expiring_soon" (under five minutes left). When the
+    /// token can't be looked up, "token" is null and "token_error" says why.
+    /// Use get_profile for the profile with storage quota.
+    #[tool]
+    async fn check_connection(&self) -> McpResult<String> {
+        info!("=== START check_connection MCP command ===");
+        debug!("check_connection called");
+
+        // Get the Gmail service
+        let service = self.init_gmail_service().await?;
+
+        // Get profile as raw JSON
+        let profile_json = match service.check_connection_raw().await {
+            Ok(json) => json,
+            Err(err) => {
+                error!("Connection check failed: {}", err);
+
+                // Provide helpful information on connectivity issues
+                // Include detailed context in the error log
+                error!("Context: Failed to connect to Gmail API. This is a basic connectivity test failure.");
+
+                return Err(self.map_error(err));
+            }
+        };
+        let mut result: serde_json::Value = serde_json::from_str(&profile_json).map_err(|e| {
+            let error_msg = format!("Failed to parse profile: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        // The token only names the account with the email scope, so fall
+        // back to the profile's address
+        match service.token_info().await {
+            Ok(mut token) => {
+                if token.email.is_none() {
+                    token.email = result
+                        .get("emailAddress")
+                        .and_then(|v| v.as_str())
+                        .map(str::to_string);
+                }
+                result["token"] = json!(token);
+            }
+            Err(err) => {
+                error!("Access token info unavailable: {}", err);
+                result["token"] = serde_json::Value::Null;
+                result["token_error"] = json!(err.to_string());
+            }
+        }
+
+        let result_json = serde_json::to_string(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize connection status: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END check_connection MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Get the account's profile and storage quota
+    ///
+    /// Returns an object with "email_address", "messages_total",
+    /// "threads_total" and "history_id" from Gmail, and "storage_quota" from
+    /// Google Drive with the "limit" (null for unlimited storage), "usage",
+    /// "usage_in_drive", "usage_in_drive_trash" and "used_percent", all in
+    /// bytes except the percentage. Gmail, Drive and Photos share the quota.
+    /// When Drive cannot be reached, for instance because the token lacks the
+    /// Drive scope, "storage_quota" is null and "storage_quota_error" says why.
+    #[tool]
+    async fn get_profile(&self) -> McpResult<String> {
+        info!("=== START get_profile MCP command ===");
+        debug!("get_profile called");
+
+        let service = self.init_gmail_service().await?;
+        let profile = service.get_profile().await.map_err(|err| {
+            error!("Failed to get profile: {}", err);
+            self.map_error(err)
+        })?;
+
+        let drive = self.init_drive_service().await?;
+        let (storage_quota, storage_quota_error) = match drive.get_storage_quota().await {
+            Ok(quota) => (
+                Some(json!({
+                    "limit": quota.limit,
+                    "usage": quota.usage,
+                    "usage_in_drive": quota.usage_in_drive,
+                    "usage_in_drive_trash": quota.usage_in_drive_trash,
+                    "used_percent": quota.used_percent()
+                })),
+                None,
+            ),
+            Err(err) => {
+                error!("Storage quota unavailable: {}", err);
+                (None, Some(err.to_string()))
+            }
+        };
+
+        let mut result = json!({
+            "email_address": profile.email_address,
+            "messages_total": profile.messages_total,
+            "threads_total": profile.threads_total,
+            "history_id": profile.history_id,
+            "storage_quota": storage_quota
+        });
+        if let Some(error) = storage_quota_error {
+            result["storage_quota_error"] = json!(error);
+        }
+
+        let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize profile: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END get_profile MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Analyze an email to extract key information
+    ///
+    /// Takes an email ID and performs a detailed analysis on its content.
+    /// Extracts information like action items, meeting details, contact information,
+    /// sentiment, priority, and suggested next steps.
+    ///
+    /// Args:
+    ///   message_id: The ID of the message to analyze
+    ///   analysis_type: Optional type of analysis to perform. Can be "general", "tasks",
+    ///                  "meetings", "contacts", "translate", or "all". Default is "general".
+    ///                  "translate" pairs the body and its detected language with a translation prompt.
+    #[tool]
+    async fn analyze_email(
+        &self,
+        message_id: String,
+        analysis_type: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START analyze_email MCP command ===");
+        debug!(
+            "analyze_email called with message_id={}, analysis_type={:?}",
+            message_id, analysis_type
+        );
+
+        self.validate(Validator::new().required("message_id", &message_id))?;
+
+        // Get the Gmail service
+        let service = self.init_gmail_service().await?;
+
+        // Get the specified email
+        let email = match service.get_message_details(&message_id).await {
+            Ok(msg) => msg,
+            Err(err) => {
+                error!("Failed to get email for analysis: {}", err);
+                return Err(self.map_error(err));
+            }
+        };
+
+        // Determine what type of analysis to perform
+        let analysis = analysis_type.unwrap_or_else(|| "general".to_string());
+
+        // Prepare the analysis result
+        let result = match analysis.to_lowercase().as_str() {
+            "tasks" | "task" => {
+                // Create a structured JSON for task analysis
+                json!({
+                    "email_id": email.id,
+                    "subject": email.subject,
+                    "from": email.from,
+                    "date": email.date,
+                    "analysis_type": "tasks",
+                    "content": email.body_text.unwrap_or_else(|| email.snippet.unwrap_or_default()),
+                    "analysis_prompt": crate::prompts::TASK_EXTRACTION_PROMPT
+                })
+            }
+            "meetings" | "meeting" => {
+                // Create a structured JSON for meeting analysis
+                json!({
+                    "email_id": email.id,
+                    "subject": email.subject,
+                    "from": email.from,
+                    "date": email.date,
+                    "analysis_type": "meetings",
+                    "content": email.body_text.unwrap_or_else(|| email.snippet.unwrap_or_default()),
+                    "analysis_prompt": crate::prompts::MEETING_EXTRACTION_PROMPT
+                })
+            }
+            "contacts" | "contact" => {
+                // Create a structured JSON for contact analysis
+                json!({
+                    "email_id": email.id,
+                    "subject": email.subject,
+                    "from": email.from,
+                    "date": email.date,
+                    "analysis_type": "contacts",
+                    "content": email.body_text.unwrap_or_else(|| email.snippet.unwrap_or_default()),
+                    "analysis_prompt": crate::prompts::CONTACT_EXTRACTION_PROMPT
+                })
+            }
+            "summary" | "summarize" => {
+                // Create a structured JSON for email summarization
+                json!({
+                    "email_id": email.id,
+                    "subject": email.subject,
+                    "from": email.from,
+                    "date": email.date,
+                    "analysis_type": "summary",
+                    "content": email.body_text.unwrap_or_else(|| email.snippet.unwrap_or_default()),
+                    "analysis_prompt": crate::prompts::EMAIL_SUMMARIZATION_PROMPT
+                })
+            }
+            "priority" | "prioritize" => {
+                // Create a structured JSON for email prioritization
+                json!({
+                    "email_id": email.id,
+                    "subject": email.subject,
+                    "from": email.from,
+                    "date": email.date,
+                    "analysis_type": "priority",
+                    "content": email.body_text.unwrap_or_else(|| email.snippet.unwrap_or_default()),
+                    "analysis_prompt": crate::prompts::EMAIL_PRIORITIZATION_PROMPT
+                })
+            }
+            "translate" | "translation" => {
+                // Create a structured JSON for translating the email
+                json!({
+                    "email_id": email.id,
+                    "subject": email.subject,
+                    "from": email.from,
+                    "date": email.date,
+                    "analysis_type": "translate",
+                    "language": email.language,
+                    "content": email.body_text.unwrap_or_else(|| email.snippet.unwrap_or_default()),
+                    "analysis_prompt": crate::prompts::EMAIL_TRANSLATION_PROMPT
+                })
+            }
+            "all" => {
+                // Create comprehensive JSON with all analysis types
+                json!({
+                    "email_id": email.id,
+                    "subject": email.subject,
+                    "from": email.from,
+                    "to": email.to,
+                    "date": email.date,
+                    "analysis_type": "comprehensive",
+                    "content": email.body_text.unwrap_or_else(|| email.snippet.unwrap_or_default()),
+                    "html_content": email.body_html,
+                    "analysis_prompts": {
+                        "general": crate::prompts::EMAIL_ANALYSIS_PROMPT,
+                        "tasks": crate::prompts::TASK_EXTRACTION_PROMPT,
+                        "meetings": crate::prompts::MEETING_EXTRACTION_PROMPT,
+                        "contacts": crate::prompts::CONTACT_EXTRACTION_PROMPT,
+                        "priority": crate::prompts::EMAIL_PRIORITIZATION_PROMPT
+                    }
+                })
+            }
+            _ => {
+                // Default to general analysis
+                json!({
+                    "email_id": email.id,
+                    "subject": email.subject,
+                    "from": email.from,
+                    "date": email.date,
+                    "analysis_type": "general",
+                    "content": email.body_text.unwrap_or_else(|| email.snippet.unwrap_or_default()),
+                    "analysis_prompt": crate::prompts::EMAIL_ANALYSIS_PROMPT
+                })
+            }
+        };
+
+        // Convert to string
+        let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize analysis result: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END analyze_email MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Batch analyze multiple emails
+    ///
+    /// Takes a list of email IDs and performs quick analysis on each one.
+    /// Useful for getting an overview of multiple emails at once.
+    ///
+    /// Args:
+    ///   message_ids: List of email IDs to analyze
+    ///   analysis_type: Optional type of analysis to perform. Can be "summary", "tasks",
+    ///                  "priority", or "category". Default is "summary".
+    #[tool]
+    async fn batch_analyze_emails(
+        &self,
+        message_ids: Vec<String>,
         analysis_type: Option<String>,
     ) -> McpResult<String> {
-        info!("=== START analyze_email MCP command ===");
+        info!("=== START batch_analyze_emails MCP command ===");
+        debug!(
+            "batch_analyze_emails called with {} messages, analysis_type={:?}",
+            message_ids.len(),
+            analysis_type
+        );
+
+        self.validate(Validator::new().max_items("message_ids", &message_ids, MAX_LIST_ITEMS))?;
+
+        // Get the Gmail service
+        let service = self.init_gmail_service().await?;
+
+        // Determine what type of analysis to perform
+        let analysis = analysis_type
+            .unwrap_or_else(|| "summary".to_string())
+            .to_lowercase();
+
+        // Analyze each email, recording the outcome for each one
+        let mut report = BatchReport::new();
+        for id in message_ids {
+            if service.deadline().expired() {
+                report.push_skipped(id);
+                continue;
+            }
+            debug!("Analyzing email {}", id);
+
+            // Get the specified email
+            match service.get_message_details(&id).await {
+                Ok(email) => {
+                    // Prepare analysis based on type
+                    let analysis_prompt = match analysis.as_str() {
+                        "tasks" | "task" => crate::prompts::TASK_EXTRACTION_PROMPT,
+                        "priority" => crate::prompts::EMAIL_PRIORITIZATION_PROMPT,
+                        "category" => crate::prompts::EMAIL_CATEGORIZATION_PROMPT,
+                        _ => crate::prompts::EMAIL_SUMMARIZATION_PROMPT, // Default to summary
+                    };
+
+                    // Create analysis result
+                    let result = json!({
+                        "email_id": email.id,
+                        "subject": email.subject,
+                        "from": email.from,
+                        "date": email.date,
+                        "analysis_type": analysis,
+                        "content": email.body_text.unwrap_or_else(|| email.snippet.unwrap_or_default()),
+                        "analysis_prompt": analysis_prompt
+                    });
+
+                    report.push_success(id, result);
+                }
+                Err(err) => {
+                    // Log error but continue with other emails
+                    error!("Failed to analyze email {}: {}", id, err);
+                    report.push_failure(id, err);
+                }
+            }
+        }
+
+        // Create a batch result
+        let mut extra = serde_json::Map::new();
+        extra.insert("analysis_type".to_string(), json!(analysis));
+        let batch_result = report.to_json(extra);
+
+        // Convert to string
+        let result_json = serde_json::to_string_pretty(&batch_result).map_err(|e| {
+            let error_msg = format!("Failed to serialize batch analysis result: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END batch_analyze_emails MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Create a draft email
+    ///
+    /// Creates a new draft email in Gmail with the specified content.
+    /// The email will be saved as a draft and can be edited before sending.
+    ///
+    /// Args:
+    ///   to: Email address(es) of the recipient(s). Multiple addresses should be comma-separated.
+    ///   subject: Subject line of the email
+    ///   body: Plain text content of the email
+    ///   cc: Optional CC recipient(s). Multiple addresses should be comma-separated.
+    ///   bcc: Optional BCC recipient(s). Multiple addresses should be comma-separated.
+    ///   thread_id: Optional Gmail thread ID to associate this email with
+    ///   in_reply_to: Optional Message-ID that this email is replying to
+    ///   references: Optional comma-separated list of Message-IDs in the email thread
+    ///   drive_file_ids: Optional Google Drive file IDs to include. Files that fit are
+    ///                   attached; larger files and Google Docs are added as links, and
+    ///                   recipients who cannot open a link are reported as warnings.
+    ///   priority: Optional priority to mark the email with: "high", "normal" or "low".
+    ///             Sets the X-Priority and Importance headers.
+    ///   request_read_receipt: Optional flag to ask recipients for a read receipt, sent
+    ///                         to your own address. Receipts that come back appear as
+    ///                         "read_receipt" on the email; many clients never send them.
+    ///   idempotency_key: Optional key identifying this call. A repeated call with the
+    ///                    same key returns the first call's result without creating
+    ///                    another draft.
+    #[tool]
+    #[allow(clippy::too_many_arguments)]
+    async fn create_draft_email(
+        &self,
+        // Required content
+        to: String,
+        subject: String,
+        body: String,
+        // Optional recipients
+        cc: Option<String>,
+        bcc: Option<String>,
+        // Optional threading
+        thread_id: Option<String>,
+        in_reply_to: Option<String>,
+        // Additional options
+        references: Option<String>,
+        drive_file_ids: Option<Vec<String>>,
+        priority: Option<String>,
+        request_read_receipt: Option<bool>,
+        idempotency_key: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START create_draft_email MCP command ===");
+        debug!(
+            "create_draft_email called with to={}, subject={}, cc={:?}, bcc={:?}, thread_id={:?}, in_reply_to={:?}",
+            to, subject, cc, bcc, thread_id, in_reply_to
+        );
+
+        self.validate(
+            Validator::new()
+                .recipients("to", &to)
+                .optional(cc.as_deref(), |v, cc| v.recipients("cc", cc))
+                .optional(bcc.as_deref(), |v, bcc| v.recipients("bcc", bcc))
+                .max_chars("subject", &subject, MAX_SUBJECT_CHARS)
+                .max_bytes("body", &body, MAX_BODY_BYTES),
+        )?;
+
+        let idempotency = match self.claim_idempotency_key(
+            "create_draft_email",
+            idempotency_key.as_deref(),
+            json!({
+                "to": to,
+                "subject": subject,
+                "body": body,
+                "cc": cc,
+                "bcc": bcc,
+                "thread_id": thread_id,
+                "in_reply_to": in_reply_to,
+                "references": references,
+                "drive_file_ids": drive_file_ids,
+                "priority": priority,
+                "request_read_receipt": request_read_receipt,
+            }),
+        )? {
+            Claim::Replay(result) => return Ok(result),
+            Claim::Run(guard) => guard,
+        };
+
+        let priority = match priority {
+            Some(value) => match crate::priority::PriorityLevel::parse(&value) {
+                Some(level) => Some(level),
+                None => {
+                    let error_msg =
+                        format!("Invalid priority '{}': use high, normal or low", value);
+                    error!("{}", error_msg);
+                    return Err(self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR));
+                }
+            },
+            None => None,
+        };
+
+        // Create the draft email object
+        let mut draft = crate::gmail_api::DraftEmail {
+            to,
+            subject,
+            body,
+            cc,
+            bcc,
+            thread_id,
+            in_reply_to,
+            references,
+            priority,
+            read_receipt_to: None,
+        };
+
+        // Fetch any Drive files, attaching or linking them
+        let drive_attachments = match drive_file_ids.filter(|ids| !ids.is_empty()) {
+            Some(file_ids) => {
+                let drive = self.init_drive_service().await?;
+                // Files that would take the email past Gmail's size limit
+                // are linked instead
+                let max_bytes = crate::drive_api::get_drive_attachment_max_bytes()
+                    .min(crate::gmail_api::attachment_budget(&draft.body));
+                let prepared = crate::drive_api::prepare_drive_attachments(
+                    &drive,
+                    &file_ids,
+                    &draft.recipient_addresses(),
+                    max_bytes,
+                )
+                .await
+                .map_err(|err| {
+                    error!("Failed to prepare Drive files: {}", err);
+                    self.map_error(err)
+                })?;
+                draft.body = prepared.body_with_links(&draft.body);
+                Some(prepared)
+            }
+            None => None,
+        };
+
+        // Get the Gmail service
+        let service = self.init_gmail_service().await?;
+
+        // Receipts go to the signed-in account
+        if request_read_receipt.unwrap_or(false) {
+            let address = service.get_email_address().await.map_err(|err| {
+                error!("Failed to look up address for read receipts: {}", err);
+                self.map_error(err)
+            })?;
+            draft.read_receipt_to = Some(address);
+        }
+
+        // Create the draft
+        let files = drive_attachments
+            .as_ref()
+            .map(|prepared| prepared.files.as_slice())
+            .unwrap_or_default();
+        match service.create_draft_with_attachments(&draft, files).await {
+            Ok(draft_id) => {
+                // Create success response
+                let mut result = json!({
+                    "status": "success",
+                    "draft_id": draft_id,
+                    "message": "Draft email created successfully."
+                });
+
+                // Add threading info to response if provided
+                if let Some(ref thread_id_val) = draft.thread_id {
+                    result["thread_id"] = json!(thread_id_val);
+                }
+
+                if let Some(priority) = draft.priority {
+                    result["priority"] = json!(priority);
+                }
+                if let Some(address) = &draft.read_receipt_to {
+                    result["read_receipt_to"] = json!(address);
+                }
+
+                // Say how Drive files were included
+                if let Some(prepared) = &drive_attachments {
+                    result["drive_files"] = json!(prepared);
+                }
+
+                // Convert to string
+                let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
+                    let error_msg = format!("Failed to serialize draft creation result: {}", e);
+                    error!("{}", error_msg);
+                    self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+                })?;
+
+                info!("=== END create_draft_email MCP command (success) ===");
+                Ok(idempotency.finish(result_json))
+            }
+            Err(err) => {
+                error!("Failed to create draft email: {}", err);
+
+                // Create detailed error context for the user
+                error!(
+                    "Context: Failed to create draft email with subject: '{}'",
+                    draft.subject
+                );
+
+                Err(self.map_error(err))
+            }
+        }
+    }
+
+    /// Create several draft emails at once
+    ///
+    /// For mail-merge style flows, such as personalized follow-ups to several
+    /// people. Drafts are created a few at a time, within the API rate limits,
+    /// and each one is reported on its own: a draft that is written wrongly or
+    /// that Gmail rejects doesn't stop the others. Returns "status" ("success",
+    /// "partial_success" or "failure"), counts, and "items" in the order given,
+    /// each identified by its recipient with the draft ID or the error.
+    ///
+    /// Args:
+    ///   drafts: The drafts to create, at most 100. Each is an object with "to", "subject"
+    ///           and "body", and optionally "cc", "bcc", "thread_id", "in_reply_to",
+    ///           "references" and "priority", as for create_draft_email.
+    ///   idempotency_key: Optional key identifying this call. A repeated call with the
+    ///                    same key returns the first call's result without creating
+    ///                    the drafts again.
+    #[tool]
+    async fn create_drafts_batch(
+        &self,
+        drafts: Vec<serde_json::Value>,
+        idempotency_key: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START create_drafts_batch MCP command ===");
+        debug!("create_drafts_batch called with {} drafts", drafts.len());
+
+        let idempotency = match self.claim_idempotency_key(
+            "create_drafts_batch",
+            idempotency_key.as_deref(),
+            json!({ "drafts": drafts }),
+        )? {
+            Claim::Replay(result) => return Ok(result),
+            Claim::Run(guard) => guard,
+        };
+
+        if drafts.len() > crate::draft_batch::MAX_BATCH_DRAFTS {
+            let error_msg = format!(
+                "Too many drafts: {} given, at most {} can be created at once",
+                drafts.len(),
+                crate::draft_batch::MAX_BATCH_DRAFTS
+            );
+            error!("{}", error_msg);
+            return Err(self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR));
+        }
+
+        // Check every draft first, creating only those written correctly
+        let ids: Vec<String> = drafts
+            .iter()
+            .enumerate()
+            .map(|(index, value)| crate::draft_batch::draft_item_id(index, value))
+            .collect();
+        let parsed: Vec<Result<crate::gmail_api::DraftEmail, String>> =
+            drafts.iter().map(crate::draft_batch::parse_draft).collect();
+        let valid: Vec<crate::gmail_api::DraftEmail> = parsed
+            .iter()
+            .filter_map(|draft| draft.as_ref().ok().cloned())
+            .collect();
+
+        let service = self.init_gmail_service().await?;
+        let mut created = service
+            .create_drafts(&valid)
+            .await
+            .map_err(|err| {
+                error!("Failed to create drafts: {}", err);
+                self.map_error(err)
+            })?
+            .into_iter();
+
+        let mut report = BatchReport::new();
+        for (id, draft) in ids.into_iter().zip(parsed) {
+            let draft = match draft {
+                Ok(draft) => draft,
+                Err(e) => {
+                    error!("Skipping draft {}: {}", id, e);
+                    report.push_failure(id, crate::errors::GmailApiError::MessageFormatError(e));
+                    continue;
+                }
+            };
+            match created.next() {
+                Some(Ok(draft_id)) => report.push_success(
+                    id,
+                    json!({ "draft_id": draft_id, "subject": draft.subject }),
+                ),
+                Some(Err(err)) => {
+                    error!("Failed to create draft to {}: {}", id, err);
+                    report.push_failure(id, err);
+                }
+                // Drafts left without a result weren't started before the
+                // deadline
+                None => report.push_skipped(id),
+            }
+        }
+
+        let result_json = serde_json::to_string_pretty(&report.to_json(serde_json::Map::new()))
+            .map_err(|e| {
+                let error_msg = format!("Failed to serialize batch draft result: {}", e);
+                error!("{}", error_msg);
+                self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+            })?;
+
+        info!("=== END create_drafts_batch MCP command (success) ===");
+        Ok(idempotency.finish(result_json))
+    }
+
+    /// List saved email templates
+    ///
+    /// Returns each template's name, description, subject and the variables
+    /// its `{{name}}` placeholders need.
+    #[tool]
+    async fn list_templates(&self) -> McpResult<String> {
+        info!("=== START list_templates MCP command ===");
+
+        let store = crate::templates::TemplateStore::from_env();
+        let templates = store.list().map_err(|err| {
+            error!("Failed to list templates: {}", err);
+            self.map_error(err)
+        })?;
+
+        let summaries: Vec<_> = templates.iter().map(|t| t.summary()).collect();
+        let result = json!({
+            "count": summaries.len(),
+            "templates": summaries
+        });
+
+        let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize templates: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END list_templates MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Save an email template
+    ///
+    /// The subject, body and recipients may contain placeholders such as
+    /// `{{first_name}}`, filled in by create_draft_from_template. Saving a
+    /// template with an existing name replaces it.
+    ///
+    /// Args:
+    ///   name: Name of the template, e.g. "weekly-status"
+    ///   subject: Subject line, which may contain placeholders
+    ///   body: Plain text body, which may contain placeholders
+    ///   to: Optional default recipient(s), used when a draft doesn't give any
+    ///   cc: Optional default CC recipient(s)
+    ///   description: Optional description of when to use the template
+    #[tool]
+    async fn save_template(
+        &self,
+        name: String,
+        subject: String,
+        body: String,
+        to: Option<String>,
+        cc: Option<String>,
+        description: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START save_template MCP command ===");
+        debug!("save_template called with name={}, subject={}", name, subject);
+
+        self.validate(Validator::new().required("name", &name).max_bytes(
+            "body",
+            &body,
+            MAX_BODY_BYTES,
+        ))?;
+
+        let template = crate::templates::EmailTemplate {
+            name,
+            description,
+            subject,
+            body,
+            to,
+            cc,
+        };
+        let summary = template.summary();
+
+        let store = crate::templates::TemplateStore::from_env();
+        let replaced = store.save(template).map_err(|err| {
+            error!("Failed to save template: {}", err);
+            self.map_error(err)
+        })?;
+
+        let result = json!({
+            "status": "success",
+            "replaced": replaced,
+            "template": summary
+        });
+
+        let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize saved template: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END save_template MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Create a draft email from a saved template
+    ///
+    /// Fills the template's placeholders from the given variables and saves
+    /// the result as a Gmail draft. Fails, listing the missing names, if any
+    /// placeholder has no value.
+    ///
+    /// Args:
+    ///   name: Name of the template
+    ///   variables: Values for the template's placeholders, e.g. {"first_name": "Ada"}
+    ///   to: Optional recipient(s), overriding the template's default
+    ///   cc: Optional CC recipient(s), overriding the template's default
+    ///   bcc: Optional BCC recipient(s)
+    ///   thread_id: Optional Gmail thread ID to associate the draft with
+    ///   idempotency_key: Optional key identifying this call. A repeated call with the
+    ///                    same key returns the first call's result without creating
+    ///                    another draft.
+    #[tool]
+    #[allow(clippy::too_many_arguments)]
+    async fn create_draft_from_template(
+        &self,
+        name: String,
+        variables: Option<std::collections::HashMap<String, String>>,
+        to: Option<String>,
+        cc: Option<String>,
+        bcc: Option<String>,
+        thread_id: Option<String>,
+        idempotency_key: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START create_draft_from_template MCP command ===");
+        debug!(
+            "create_draft_from_template called with name={}, variables={:?}, to={:?}",
+            name, variables, to
+        );
+
+        self.validate(
+            Validator::new()
+                .required("name", &name)
+                .optional(to.as_deref(), |v, to| v.recipients("to", to))
+                .optional(cc.as_deref(), |v, cc| v.recipients("cc", cc))
+                .optional(bcc.as_deref(), |v, bcc| v.recipients("bcc", bcc)),
+        )?;
+
+        let idempotency = match self.claim_idempotency_key(
+            "create_draft_from_template",
+            idempotency_key.as_deref(),
+            json!({
+                "name": name,
+                "variables": variables,
+                "to": to,
+                "cc": cc,
+                "bcc": bcc,
+                "thread_id": thread_id,
+            }),
+        )? {
+            Claim::Replay(result) => return Ok(result),
+            Claim::Run(guard) => guard,
+        };
+
+        let store = crate::templates::TemplateStore::from_env();
+        let mut draft = store
+            .get(&name)
+            .and_then(|template| template.render(&variables.unwrap_or_default()))
+            .and_then(|rendered| rendered.into_draft(to, cc))
+            .map_err(|err| {
+                error!("Failed to fill template {}: {}", name, err);
+                self.map_error(err)
+            })?;
+        draft.bcc = bcc;
+        draft.thread_id = thread_id;
+
+        let service = self.init_gmail_service().await?;
+        let draft_id = service.create_draft(&draft).await.map_err(|err| {
+            error!("Failed to create draft from template {}: {}", name, err);
+            self.map_error(err)
+        })?;
+
+        let result = json!({
+            "status": "success",
+            "draft_id": draft_id,
+            "template": name,
+            "to": draft.to,
+            "subject": draft.subject,
+            "body": draft.body
+        });
+
+        let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize draft creation result: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END create_draft_from_template MCP command (success) ===");
+        Ok(idempotency.finish(result_json))
+    }
+
+    /// Fill a saved template for each of several recipients and draft or send the emails
+    ///
+    /// Each recipient is an object of fields that fill the template's `{{name}}`
+    /// placeholders; its "to" or "email" field, if given, is the address the email
+    /// goes to instead of the template's. Drafts are created a few at a time within
+    /// the API rate limits. Emails are sent one at a time with a pause between them
+    /// (MAIL_MERGE_SEND_INTERVAL_MS, default 1000), and only with confirm=true;
+    /// without it the emails are filled and shown but nothing is sent. A recipient
+    /// with a missing field is reported without stopping the others.
+    ///
+    /// Returns "status", counts and "items" in the order given, each identified by
+    /// its recipient with the draft or message ID, or the error.
+    ///
+    /// Args:
+    ///   template: Name of the saved template
+    ///   recipients: The recipients' fields, at most 100, e.g.
+    ///               [{"email": "ada@example.com", "first_name": "Ada"}]
+    ///   mode: Optional "draft" to save drafts or "send" to send the emails (default: "draft")
+    ///   confirm: Optional flag that must be true to send (default: false)
+    ///   idempotency_key: Optional key identifying this call. A repeated call with the
+    ///                    same key returns the first call's result without drafting
+    ///                    or sending the emails again.
+    #[tool]
+    async fn mail_merge(
+        &self,
+        template: String,
+        recipients: Vec<std::collections::HashMap<String, String>>,
+        mode: Option<String>,
+        confirm: Option<bool>,
+        idempotency_key: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START mail_merge MCP command ===");
+        debug!(
+            "mail_merge called with template={}, {} recipients, mode={:?}, confirm={:?}",
+            template,
+            recipients.len(),
+            mode,
+            confirm
+        );
+
+        self.validate(Validator::new().required("template", &template))?;
+
+        let idempotency = match self.claim_idempotency_key(
+            "mail_merge",
+            idempotency_key.as_deref(),
+            json!({
+                "template": template,
+                "recipients": recipients,
+                "mode": mode,
+                "confirm": confirm,
+            }),
+        )? {
+            Claim::Replay(result) => return Ok(result),
+            Claim::Run(guard) => guard,
+        };
+
+        let mode = match mode.as_deref() {
+            Some(value) => crate::mail_merge::MergeMode::parse(value).ok_or_else(|| {
+                let error_msg = format!("Invalid mode '{}': use draft or send", value);
+                error!("{}", error_msg);
+                self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+            })?,
+            None => crate::mail_merge::MergeMode::Draft,
+        };
+        if recipients.len() > crate::mail_merge::MAX_MERGE_RECIPIENTS {
+            let error_msg = format!(
+                "Too many recipients: {} given, at most {} can be merged at once",
+                recipients.len(),
+                crate::mail_merge::MAX_MERGE_RECIPIENTS
+            );
+            error!("{}", error_msg);
+            return Err(self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR));
+        }
+
+        let email_template = crate::templates::TemplateStore::from_env()
+            .get(&template)
+            .map_err(|err| {
+                error!("Failed to load template {}: {}", template, err);
+                self.map_error(err)
+            })?;
+        let ids: Vec<String> = recipients
+            .iter()
+            .enumerate()
+            .map(|(index, fields)| crate::mail_merge::recipient_id(index, fields))
+            .collect();
+        let merged = crate::mail_merge::merge_emails(&email_template, &recipients);
+        let valid: Vec<crate::gmail_api::DraftEmail> = merged
+            .iter()
+            .filter_map(|email| email.as_ref().ok().cloned())
+            .collect();
+
+        // Sending needs confirmation; until then show what would be sent
+        let send = mode == crate::mail_merge::MergeMode::Send;
+        let preview = send && !confirm.unwrap_or(false);
+        let (outcomes, id_field) = if preview {
+            (Vec::new(), "message_id")
+        } else {
+            let service = self.init_gmail_service().await?;
+            if send {
+                let interval = crate::mail_merge::get_send_interval();
+                (service.send_messages(&valid, interval).await, "message_id")
+            } else {
+                let created = service.create_drafts(&valid).await.map_err(|err| {
+                    error!("Failed to create mail merge drafts: {}", err);
+                    self.map_error(err)
+                })?;
+                (created, "draft_id")
+            }
+        };
+
+        let mut outcomes = outcomes.into_iter();
+        let mut report = BatchReport::new();
+        for (id, email) in ids.into_iter().zip(merged) {
+            let email = match email {
+                Ok(email) => email,
+                Err(err) => {
+                    error!("Skipping mail merge recipient {}: {}", id, err);
+                    report.push_failure(id, err);
+                    continue;
+                }
+            };
+            if preview {
+                report.push_success(
+                    id,
+                    json!({ "to": email.to, "subject": email.subject, "body": email.body }),
+                );
+                continue;
+            }
+            match outcomes.next() {
+                Some(Ok(outcome_id)) => report.push_success(
+                    id,
+                    json!({ id_field: outcome_id, "to": email.to, "subject": email.subject }),
+                ),
+                Some(Err(err)) => {
+                    error!(
+                        "Failed to {} mail merge email to {}: {}",
+                        mode.as_str(),
+                        id,
+                        err
+                    );
+                    report.push_failure(id, err);
+                }
+                // Emails left without an outcome weren't started before the
+                // deadline
+                None => report.push_skipped(id),
+            }
+        }
+
+        let mut extra = serde_json::Map::new();
+        extra.insert("template".to_string(), json!(template));
+        extra.insert("mode".to_string(), json!(mode.as_str()));
+        if send {
+            extra.insert("sent".to_string(), json!(!preview));
+        }
+        let result_json = serde_json::to_string_pretty(&report.to_json(extra)).map_err(|e| {
+            let error_msg = format!("Failed to serialize mail merge result: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END mail_merge MCP command (success) ===");
+        Ok(idempotency.finish(result_json))
+    }
+
+    /// Send an email after an undo delay
+    ///
+    /// The email waits in a local outbox for SEND_UNDO_SECONDS (default 30)
+    /// before it is sent, and cancel_send can take it back until then. With a
+    /// delay of 0 it is sent at once. Returns the outbox entry, whose "id" is
+    /// the outbox_id for cancel_send and whose "send_at" is when it goes out.
+    /// Drive files are fetched when the email is queued, so that it is sent
+    /// as it was written. Emails the configured sending policy doesn't allow
+    /// are refused.
+    ///
+    /// Args:
+    ///   to: Email address(es) of the recipient(s). Multiple addresses should be comma-separated.
+    ///       May be empty when there are BCC recipients.
+    ///   subject: Subject line of the email
+    ///   body: Plain text content of the email
+    ///   cc: Optional CC recipient(s). Multiple addresses should be comma-separated.
+    ///   bcc: Optional BCC recipient(s). Multiple addresses should be comma-separated.
+    ///   thread_id: Optional Gmail thread ID to associate this email with
+    ///   in_reply_to: Optional Message-ID that this email is replying to
+    ///   references: Optional comma-separated list of Message-IDs in the email thread
+    ///   drive_file_ids: Optional Google Drive file IDs to include. Files that fit are
+    ///                   attached; larger files and Google Docs are added as links, and
+    ///                   recipients who cannot open a link are reported as warnings.
+    ///   priority: Optional priority to mark the email with: "high", "normal" or "low"
+    ///   request_read_receipt: Optional flag to ask recipients for a read receipt, sent
+    ///                         to your own address
+    ///   idempotency_key: Optional key identifying this call. A repeated call with the
+    ///                    same key returns the first call's result without queuing
+    ///                    the email again.
+    #[tool]
+    #[allow(clippy::too_many_arguments)]
+    async fn send_email(
+        &self,
+        to: String,
+        subject: String,
+        body: String,
+        cc: Option<String>,
+        bcc: Option<String>,
+        thread_id: Option<String>,
+        in_reply_to: Option<String>,
+        references: Option<String>,
+        drive_file_ids: Option<Vec<String>>,
+        priority: Option<String>,
+        request_read_receipt: Option<bool>,
+        idempotency_key: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START send_email MCP command ===");
+        debug!(
+            "send_email called with to={}, subject={}, cc={:?}, bcc={:?}, thread_id={:?}",
+            to, subject, cc, bcc, thread_id
+        );
+
+        // Emails can go to BCC recipients only, unless the sending policy
+        // forbids it
+        let bcc_only =
+            to.trim().is_empty() && bcc.as_deref().is_some_and(|bcc| !bcc.trim().is_empty());
+        self.validate(
+            Validator::new()
+                .optional((!bcc_only).then_some(to.as_str()), |v, to| {
+                    v.recipients("to", to)
+                })
+                .optional(cc.as_deref(), |v, cc| v.recipients("cc", cc))
+                .optional(bcc.as_deref(), |v, bcc| v.recipients("bcc", bcc))
+                .max_chars("subject", &subject, MAX_SUBJECT_CHARS)
+                .max_bytes("body", &body, MAX_BODY_BYTES),
+        )?;
+
+        let idempotency = match self.claim_idempotency_key(
+            "send_email",
+            idempotency_key.as_deref(),
+            json!({
+                "to": to,
+                "subject": subject,
+                "body": body,
+                "cc": cc,
+                "bcc": bcc,
+                "thread_id": thread_id,
+                "in_reply_to": in_reply_to,
+                "references": references,
+                "drive_file_ids": drive_file_ids,
+                "priority": priority,
+                "request_read_receipt": request_read_receipt,
+            }),
+        )? {
+            Claim::Replay(result) => return Ok(result),
+            Claim::Run(guard) => guard,
+        };
+
+        let priority = match priority {
+            Some(value) => match crate::priority::PriorityLevel::parse(&value) {
+                Some(level) => Some(level),
+                None => {
+                    let error_msg =
+                        format!("Invalid priority '{}': use high, normal or low", value);
+                    error!("{}", error_msg);
+                    return Err(self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR));
+                }
+            },
+            None => None,
+        };
+
+        let mut email = crate::gmail_api::DraftEmail {
+            to,
+            subject,
+            body,
+            cc,
+            bcc,
+            thread_id,
+            in_reply_to,
+            references,
+            priority,
+            read_receipt_to: None,
+        };
+
+        // Fetch any Drive files, attaching or linking them
+        let drive_attachments = match drive_file_ids.filter(|ids| !ids.is_empty()) {
+            Some(file_ids) => {
+                let drive = self.init_drive_service().await?;
+                // Files that would take the email past Gmail's size limit
+                // are linked instead
+                let max_bytes = crate::drive_api::get_drive_attachment_max_bytes()
+                    .min(crate::gmail_api::attachment_budget(&email.body));
+                let prepared = crate::drive_api::prepare_drive_attachments(
+                    &drive,
+                    &file_ids,
+                    &email.recipient_addresses(),
+                    max_bytes,
+                )
+                .await
+                .map_err(|err| {
+                    error!("Failed to prepare Drive files: {}", err);
+                    self.map_error(err)
+                })?;
+                email.body = prepared.body_with_links(&email.body);
+                Some(prepared)
+            }
+            None => None,
+        };
+
+        // Receipts go to the signed-in account
+        if request_read_receipt.unwrap_or(false) {
+            let service = self.init_gmail_service().await?;
+            let address = service.get_email_address().await.map_err(|err| {
+                error!("Failed to look up address for read receipts: {}", err);
+                self.map_error(err)
+            })?;
+            email.read_receipt_to = Some(address);
+        }
+
+        // Refuse now what the sending policy would refuse at the send time
+        crate::send_policy::SendPolicy::from_env()
+            .check(&email, crate::send_policy::SendLog::global())
+            .map_err(|err| {
+                error!("{}", err);
+                self.map_error(err)
+            })?;
+
+        // Scheduled sends outlive the call, so they use the client without
+        // the tool deadline
+        let gmail = self.gmail_service().map_err(|err| {
+            error!("Failed to create Gmail service: {}", err);
+            self.map_error(err)
+        })?;
+
+        let outbox = Outbox::global();
+        let delay = crate::outbox::get_send_delay();
+        let files = drive_attachments
+            .as_ref()
+            .map(|prepared| prepared.files.clone())
+            .unwrap_or_default();
+        let mut entry = outbox
+            .queue_with_attachments(email, files, delay)
+            .map_err(|msg| {
+                error!("{}", msg);
+                self.to_mcp_error(&msg, error_codes::GENERAL_ERROR)
+            })?;
+        if delay.is_zero() {
+            // No undo window: send now and report how it went
+            if let Some(sent) = crate::outbox::send_entry(outbox, &gmail, &entry.id).await {
+                entry = sent;
+            }
+            if let Some(err) = &entry.error {
+                let error_msg = format!("Failed to send email: {}", err);
+                error!("{}", error_msg);
+                return Err(self.to_mcp_error(&error_msg, error_codes::API_ERROR));
+            }
+        } else {
+            crate::outbox::schedule(outbox, gmail, &entry);
+        }
+
+        let mut result = entry.summary();
+        // Say how Drive files were included
+        if let Some(prepared) = &drive_attachments {
+            result["drive_files"] = json!(prepared);
+        }
+        let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize outbox entry: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END send_email MCP command (success) ===");
+        Ok(idempotency.finish(result_json))
+    }
+
+    /// Cancel an email waiting in the outbox
+    ///
+    /// Takes back an email sent with send_email before its undo delay ends.
+    /// Fails once the email has been sent.
+    ///
+    /// Args:
+    ///   outbox_id: The "id" send_email returned
+    #[tool]
+    async fn cancel_send(&self, outbox_id: String) -> McpResult<String> {
+        info!("=== START cancel_send MCP command ===");
+        debug!("cancel_send called with outbox_id={}", outbox_id);
+
+        self.validate(Validator::new().required("outbox_id", &outbox_id))?;
+
+        let entry = Outbox::global().cancel(&outbox_id).map_err(|msg| {
+            error!("{}", msg);
+            self.to_mcp_error(&msg, error_codes::GENERAL_ERROR)
+        })?;
+
+        let result_json = serde_json::to_string_pretty(&entry.summary()).map_err(|e| {
+            let error_msg = format!("Failed to serialize outbox entry: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END cancel_send MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// List the outbox
+    ///
+    /// Lists emails sent with send_email that are waiting for their undo delay
+    /// to end, with those recently sent, failed or cancelled. Each has its
+    /// "status" and, once sent, its Gmail "message_id".
+    #[tool]
+    async fn list_outbox(&self) -> McpResult<String> {
+        info!("=== START list_outbox MCP command ===");
+
+        let entries: Vec<serde_json::Value> = Outbox::global()
+            .entries()
+            .iter()
+            .map(|entry| entry.summary())
+            .collect();
+        let result_json = serde_json::to_string_pretty(&entries).map_err(|e| {
+            let error_msg = format!("Failed to serialize outbox: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END list_outbox MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// List contacts
+    ///
+    /// This command retrieves a list of contacts from Google Contacts.
+    ///
+    /// # Parameters
+    ///
+    /// * `max_results` - Optional. The maximum number of contacts to return.
+    ///
+    /// # Returns
+    ///
+    /// A JSON string containing the contact list
+    #[tool]
+    async fn list_contacts(&self, max_results: Option<u32>) -> McpResult<String> {
+        info!("=== START list_contacts MCP command ===");
+        debug!("list_contacts called with max_results={:?}", max_results);
+
+        // Initialize the People API client
+        let people_client = self.init_people_service().await?;
+
+        match people_client.list_contacts(max_results).await {
+            Ok(contacts) => {
+                // Convert to JSON
+                serde_json::to_string(&contacts).map_err(|e| {
+                    let error_msg = format!("Failed to serialize contact list: {}", e);
+                    error!("{}", error_msg);
+                    self.to_mcp_error(&error_msg, error_codes::GENERAL_ERROR)
+                })
+            }
+            Err(err) => {
+                error!("Failed to list contacts: {}", err);
+                Err(self.map_error(err))
+            }
+        }
+    }
+
+    /// List contacts by company
+    ///
+    /// This command groups your contacts by the organization on their contact record,
+    /// or otherwise by the domain of their work email address, with the number of
+    /// contacts at each. Addresses at personal mail providers such as gmail.com are not
+    /// counted as companies.
+    ///
+    /// # Parameters
+    ///
+    /// * `domain_or_org` - Optional. A domain such as "example.com", or part of an
+    ///   organization's name, to list only the contacts at that company.
+    ///
+    /// # Returns
+    ///
+    /// A JSON string with the groups, largest first, each with its name, kind
+    /// ("organization" or "domain"), count, email domains and contacts
+    #[tool]
+    async fn list_contacts_by_company(&self, domain_or_org: Option<String>) -> McpResult<String> {
+        info!("=== START list_contacts_by_company MCP command ===");
+        debug!(
+            "list_contacts_by_company called with domain_or_org={:?}",
+            domain_or_org
+        );
+
+        let people_client = self.init_people_service().await?;
+        let contacts = people_client
+            .list_contacts(Some(crate::contact_groups::CONTACT_GROUP_SCAN))
+            .await
+            .map_err(|err| {
+                error!("Failed to list contacts: {}", err);
+                self.map_error(err)
+            })?;
+
+        let groups =
+            crate::contact_groups::ContactGroups::new(&contacts.contacts, domain_or_org.as_deref());
+        let result_json = serde_json::to_string_pretty(&groups).map_err(|e| {
+            let error_msg = format!("Failed to serialize contact groups: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::GENERAL_ERROR)
+        })?;
+
+        info!("=== END list_contacts_by_company MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Search contacts
+    ///
+    /// This command searches for contacts matching the query.
+    ///
+    /// # Parameters
+    ///
+    /// * `query` - The search query.
+    /// * `max_results` - Optional. The maximum number of contacts to return.
+    ///
+    /// # Returns
+    ///
+    /// A JSON string containing the matching contacts
+    #[tool]
+    async fn search_contacts(&self, query: String, max_results: Option<u32>) -> McpResult<String> {
+        info!("=== START search_contacts MCP command ===");
+        debug!(
+            "search_contacts called with query=\"{}\" and max_results={:?}",
+            query, max_results
+        );
+
+        self.validate(Validator::new().required("query", &query))?;
+
+        // Initialize the People API client
+        let people_client = self.init_people_service().await?;
+
+        match people_client.search_contacts(&query, max_results).await {
+            Ok(contacts) => {
+                // Convert to JSON
+                serde_json::to_string(&contacts).map_err(|e| {
+                    let error_msg = format!("Failed to serialize contact search results: {}", e);
+                    error!("{}", error_msg);
+                    self.to_mcp_error(&error_msg, error_codes::GENERAL_ERROR)
+                })
+            }
+            Err(err) => {
+                error!("Failed to search contacts: {}", err);
+                Err(self.map_error(err))
+            }
+        }
+    }
+
+    /// Get contact
+    ///
+    /// This command retrieves a specific contact by resource name.
+    ///
+    /// # Parameters
+    ///
+    /// * `resource_name` - The resource name of the contact to retrieve.
+    ///
+    /// # Returns
+    ///
+    /// A JSON string containing the contact details
+    #[tool]
+    async fn get_contact(&self, resource_name: String) -> McpResult<String> {
+        info!("=== START get_contact MCP command ===");
+        debug!("get_contact called with resource_name={}", resource_name);
+
+        self.validate(Validator::new().required("resource_name", &resource_name))?;
+
+        // Initialize the People API client
+        let people_client = self.init_people_service().await?;
+
+        match people_client.get_contact(&resource_name).await {
+            Ok(contact) => {
+                // Convert to JSON
+                serde_json::to_string(&contact).map_err(|e| {
+                    let error_msg = format!("Failed to serialize contact: {}", e);
+                    error!("{}", error_msg);
+                    self.to_mcp_error(&error_msg, error_codes::GENERAL_ERROR)
+                })
+            }
+            Err(err) => {
+                error!("Failed to get contact: {}", err);
+                Err(self.map_error(err))
+            }
+        }
+    }
+
+    /// Append a note to a contact
+    ///
+    /// This command adds a dated line to the notes of a contact, keeping what the notes
+    /// already say, so facts learned from emails can be kept against the person. A note
+    /// the contact's notes already hold is not added again. If the contact changes while
+    /// the note is being added, nothing is written and the command can be retried.
+    ///
+    /// # Parameters
+    ///
+    /// * `resource_name` - The resource name of the contact, such as "people/c12345"
+    /// * `note` - The note to add, such as "Prefers calls after 3pm"
+    ///
+    /// # Returns
+    ///
+    /// A JSON string with whether the note was appended and the contact's notes now
+    #[tool]
+    async fn append_contact_note(&self, resource_name: String, note: String) -> McpResult<String> {
+        info!("=== START append_contact_note MCP command ===");
+        debug!(
+            "append_contact_note called with resource_name={}, note={}",
+            resource_name, note
+        );
+
+        self.validate(
+            Validator::new()
+                .required("resource_name", &resource_name)
+                .max_chars("note", &note, MAX_DESCRIPTION_CHARS),
+        )?;
+
+        let note = crate::contact_notes::parse_note(&note).map_err(|error_msg| {
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        let people_client = self.init_people_service().await?;
+        let person = people_client
+            .get_contact_json(&resource_name, crate::contact_notes::CONTACT_NOTE_FIELDS)
+            .await
+            .map_err(|err| {
+                error!("Failed to get contact {}: {}", resource_name, err);
+                self.map_error(err)
+            })?;
+
+        let existing = crate::contact_notes::current_notes(&person);
+        let today = chrono::Utc::now()
+            .with_timezone(&crate::config::get_user_time_zone())
+            .date_naive();
+        let (appended, notes) = match crate::contact_notes::append_note(existing, &note, today) {
+            Some(notes) => {
+                let update = crate::contact_notes::notes_update(&person, &notes);
+                people_client
+                    .update_contact(&resource_name, &update, &["biographies"])
+                    .await
+                    .map_err(|err| {
+                        error!("Failed to update contact {}: {}", resource_name, err);
+                        self.map_error(err)
+                    })?;
+                (true, notes)
+            }
+            None => (false, existing.unwrap_or_default().to_string()),
+        };
+
+        let result = crate::contact_notes::ContactNoteUpdate {
+            name: person["names"][0]["displayName"]
+                .as_str()
+                .map(|name| name.to_string()),
+            resource_name,
+            appended,
+            notes,
+        };
+        let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize contact note: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::GENERAL_ERROR)
+        })?;
+
+        info!("=== END append_contact_note MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Save an email's sender as a contact
+    ///
+    /// This command reads the signature at the end of an email for the sender's name,
+    /// job title, company, phone numbers and other email addresses, and proposes a
+    /// contact from them. Nothing is saved until the command is called again with
+    /// `confirm` set. A sender who is already a contact is updated with only what the
+    /// contact lacks; existing details are never overwritten.
+    ///
+    /// # Parameters
+    ///
+    /// * `message_id` - The ID of the email
+    /// * `confirm` - Optional. Set to true to create or update the contact as proposed
+    ///   (default: false)
+    ///
+    /// # Returns
+    ///
+    /// A JSON string with the signature, the proposed contact, the action ("create",
+    /// "update" or "none"), the changes an update makes, and whether it was saved
+    #[tool]
+    async fn capture_contact_from_email(
+        &self,
+        message_id: String,
+        confirm: Option<bool>,
+    ) -> McpResult<String> {
+        info!("=== START capture_contact_from_email MCP command ===");
+        debug!(
+            "capture_contact_from_email called with message_id={}, confirm={:?}",
+            message_id, confirm
+        );
+
+        self.validate(Validator::new().required("message_id", &message_id))?;
+
+        let service = self.init_gmail_service().await?;
+        let message = service
+            .get_message_details(&message_id)
+            .await
+            .map_err(|err| {
+                error!("Failed to get email {}: {}", message_id, err);
+                self.map_error(err)
+            })?;
+
+        let Some((address, sender_name)) = message
+            .from
+            .as_deref()
+            .and_then(crate::sender_report::split_sender)
+        else {
+            let error_msg = format!("Email {} has no sender address", message_id);
+            error!("{}", error_msg);
+            return Err(self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR));
+        };
+        let body = message
+            .body_text
+            .as_deref()
+            .or(message.snippet.as_deref())
+            .unwrap_or_default();
+        let signature = crate::utils::extract_signature(&crate::utils::strip_quoted_text(body));
+        let details = signature
+            .as_deref()
+            .map(|signature| {
+                crate::contact_capture::parse_signature(signature, sender_name.as_deref())
+            })
+            .unwrap_or_default();
+        let proposed =
+            crate::contact_capture::propose_contact(&address, sender_name.as_deref(), &details);
+
+        let people_client = self.init_people_service().await?;
+        let contacts = people_client
+            .search_contacts(&address, Some(10))
+            .await
+            .map_err(|err| {
+                error!("Failed to search contacts for {}: {}", address, err);
+                self.map_error(err)
+            })?;
+        let existing =
+            crate::people_api::find_contact_by_email(&contacts.contacts, &address).cloned();
+
+        let confirm = confirm.unwrap_or(false);
+        let (action, changes, contact, saved) = match &existing {
+            Some(existing) => {
+                let person = people_client
+                    .get_contact_json(
+                        &existing.resource_name,
+                        crate::contact_capture::CAPTURE_PERSON_FIELDS,
+                    )
+                    .await
+                    .map_err(|err| {
+                        error!("Failed to get contact {}: {}", existing.resource_name, err);
+                        self.map_error(err)
+                    })?;
+                let merge = crate::contact_capture::merge_into_person(&person, &proposed);
+                if merge.fields.is_empty() {
+                    ("none", merge.changes, existing.clone(), false)
+                } else if confirm {
+                    let updated = people_client
+                        .update_contact(&existing.resource_name, &merge.update, &merge.fields)
+                        .await
+                        .map_err(|err| {
+                            error!(
+                                "Failed to update contact {}: {}",
+                                existing.resource_name, err
+                            );
+                            self.map_error(err)
+                        })?;
+                    ("update", merge.changes, updated, true)
+                } else {
+                    ("update", merge.changes, proposed, false)
+                }
+            }
+            None if confirm => {
+                let person = crate::contact_capture::new_person(&proposed);
+                let created = people_client.create_contact(&person).await.map_err(|err| {
+                    error!("Failed to create contact for {}: {}", address, err);
+                    self.map_error(err)
+                })?;
+                ("create", Vec::new(), created, true)
+            }
+            None => ("create", Vec::new(), proposed, false),
+        };
+
+        let capture = crate::contact_capture::ContactCapture {
+            message_id,
+            signature,
+            action: action.to_string(),
+            changes,
+            contact,
+            existing,
+            saved,
+        };
+        let result_json = serde_json::to_string_pretty(&capture).map_err(|e| {
+            let error_msg = format!("Failed to serialize contact capture: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::GENERAL_ERROR)
+        })?;
+
+        info!("=== END capture_contact_from_email MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// List task lists
+    ///
+    /// This command retrieves the user's Google Tasks lists.
+    ///
+    /// # Parameters
+    ///
+    /// * `max_results` - Optional. The maximum number of task lists to return.
+    ///
+    /// # Returns
+    ///
+    /// A JSON string containing the task lists
+    #[tool]
+    async fn list_task_lists(&self, max_results: Option<u32>) -> McpResult<String> {
+        info!("=== START list_task_lists MCP command ===");
+        debug!("list_task_lists called with max_results={:?}", max_results);
+
+        // Initialize the Tasks API client
+        let tasks_client = self.init_tasks_service().await?;
+
+        match tasks_client.list_task_lists(max_results).await {
+            Ok(task_lists) => serde_json::to_string(&task_lists).map_err(|e| {
+                let error_msg = format!("Failed to serialize task lists: {}", e);
+                error!("{}", error_msg);
+                self.to_mcp_error(&error_msg, error_codes::GENERAL_ERROR)
+            }),
+            Err(err) => {
+                error!("Failed to list task lists: {}", err);
+                Err(self.map_error(err))
+            }
+        }
+    }
+
+    /// List tasks
+    ///
+    /// This command retrieves the tasks in a Google Tasks list.
+    ///
+    /// # Parameters
+    ///
+    /// * `task_list_id` - Optional. The ID of the task list. Defaults to the user's default list.
+    /// * `show_completed` - Optional. Whether to include completed tasks. Defaults to false.
+    /// * `max_results` - Optional. The maximum number of tasks to return.
+    ///
+    /// # Returns
+    ///
+    /// A JSON string containing the tasks
+    #[tool]
+    async fn list_tasks(
+        &self,
+        task_list_id: Option<String>,
+        show_completed: Option<bool>,
+        max_results: Option<u32>,
+    ) -> McpResult<String> {
+        info!("=== START list_tasks MCP command ===");
+        debug!(
+            "list_tasks called with task_list_id={:?}, show_completed={:?}, max_results={:?}",
+            task_list_id, show_completed, max_results
+        );
+
+        let task_list_id =
+            task_list_id.unwrap_or_else(|| crate::tasks_api::DEFAULT_TASK_LIST.to_string());
+
+        // Initialize the Tasks API client
+        let tasks_client = self.init_tasks_service().await?;
+
+        match tasks_client
+            .list_tasks(&task_list_id, show_completed.unwrap_or(false), max_results)
+            .await
+        {
+            Ok(tasks) => serde_json::to_string(&tasks).map_err(|e| {
+                let error_msg = format!("Failed to serialize tasks: {}", e);
+                error!("{}", error_msg);
+                self.to_mcp_error(&error_msg, error_codes::GENERAL_ERROR)
+            }),
+            Err(err) => {
+                error!("Failed to list tasks in {}: {}", task_list_id, err);
+                Err(self.map_error(err))
+            }
+        }
+    }
+
+    /// Create a task
+    ///
+    /// This command adds a task to Google Tasks, such as an action item
+    /// extracted from an email with the task extraction prompt.
+    ///
+    /// # Parameters
+    ///
+    /// * `title` - The task title, e.g. "Review the Q3 budget draft".
+    /// * `notes` - Optional. Details of the task.
+    /// * `due` - Optional. Due date as YYYY-MM-DD or RFC3339. Google Tasks only keeps the date.
+    /// * `message_id` - Optional. ID of the email the task came from; a link to it is added to the notes.
+    /// * `task_list_id` - Optional. The ID of the task list. Defaults to the user's default list.
+    /// * `idempotency_key` - Optional. Key identifying this call; a repeated call with the same key returns the first call's result without creating another task.
+    ///
+    /// # Returns
+    ///
+    /// A JSON string containing the created task
+    #[tool]
+    async fn create_task(
+        &self,
+        title: String,
+        notes: Option<String>,
+        due: Option<String>,
+        message_id: Option<String>,
+        task_list_id: Option<String>,
+        idempotency_key: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START create_task MCP command ===");
+        debug!(
+            "create_task called with title=\"{}\", due={:?}, message_id={:?}, task_list_id={:?}",
+            title, due, message_id, task_list_id
+        );
+
+        self.validate(
+            Validator::new()
+                .required("title", &title)
+                .max_chars("title", &title, MAX_SUMMARY_CHARS)
+                .optional(notes.as_deref(), |v, notes| {
+                    v.max_chars("notes", notes, MAX_DESCRIPTION_CHARS)
+                }),
+        )?;
+
+        let idempotency = match self.claim_idempotency_key(
+            "create_task",
+            idempotency_key.as_deref(),
+            json!({
+                "title": title,
+                "notes": notes,
+                "due": due,
+                "message_id": message_id,
+                "task_list_id": task_list_id,
+            }),
+        )? {
+            Claim::Replay(result) => return Ok(result),
+            Claim::Run(guard) => guard,
+        };
+
+        let task_list_id =
+            task_list_id.unwrap_or_else(|| crate::tasks_api::DEFAULT_TASK_LIST.to_string());
+        let due = due
+            .as_deref()
+            .map(crate::tasks_api::parse_due_date)
+            .transpose()
+            .map_err(|err| {
+                error!("{}", err);
+                self.map_error(err)
+            })?;
+
+        // Link back to the source email so the task can be traced to it
+        let notes = match (notes, message_id) {
+            (notes, Some(message_id)) => {
+                let link = format!("Email: {}", crate::reminders::email_link(&message_id));
+                Some(match notes {
+                    Some(notes) => format!("{}\n\n{}", notes, link),
+                    None => link,
+                })
+            }
+            (notes, None) => notes,
+        };
+
+        let task = crate::tasks_api::Task {
+            title,
+            notes,
+            due,
+            ..Default::default()
+        };
+
+        // Initialize the Tasks API client
+        let tasks_client = self.init_tasks_service().await?;
+
+        match tasks_client.create_task(&task_list_id, &task).await {
+            Ok(task) => serde_json::to_string(&task)
+                .map(|result| idempotency.finish(result))
+                .map_err(|e| {
+                    let error_msg = format!("Failed to serialize task: {}", e);
+                    error!("{}", error_msg);
+                    self.to_mcp_error(&error_msg, error_codes::GENERAL_ERROR)
+                }),
+            Err(err) => {
+                error!("Failed to create task in {}: {}", task_list_id, err);
+                Err(self.map_error(err))
+            }
+        }
+    }
+
+    /// Complete a task
+    ///
+    /// This command marks a Google Tasks task as completed.
+    ///
+    /// # Parameters
+    ///
+    /// * `task_id` - The ID of the task to complete.
+    /// * `task_list_id` - Optional. The ID of the task list. Defaults to the user's default list.
+    ///
+    /// # Returns
+    ///
+    /// A JSON string containing the updated task
+    #[tool]
+    async fn complete_task(
+        &self,
+        task_id: String,
+        task_list_id: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START complete_task MCP command ===");
+        debug!(
+            "complete_task called with task_id={}, task_list_id={:?}",
+            task_id, task_list_id
+        );
+
+        self.validate(Validator::new().required("task_id", &task_id))?;
+
+        let task_list_id =
+            task_list_id.unwrap_or_else(|| crate::tasks_api::DEFAULT_TASK_LIST.to_string());
+
+        // Initialize the Tasks API client
+        let tasks_client = self.init_tasks_service().await?;
+
+        match tasks_client.complete_task(&task_list_id, &task_id).await {
+            Ok(task) => serde_json::to_string(&task).map_err(|e| {
+                let error_msg = format!("Failed to serialize task: {}", e);
+                error!("{}", error_msg);
+                self.to_mcp_error(&error_msg, error_codes::GENERAL_ERROR)
+            }),
+            Err(err) => {
+                error!("Failed to complete task {}: {}", task_id, err);
+                Err(self.map_error(err))
+            }
+        }
+    }
+
+    /// List all available calendars
+    ///
+    /// This command retrieves a list of all calendars the user has access to.
+    ///
+    /// # Returns
+    ///
+    /// A JSON string containing the calendar list
+    #[tool]
+    async fn list_calendars(&self) -> McpResult<String> {
+        info!("=== START list_calendars MCP command ===");
+        debug!("list_calendars called");
+
+        // Initialize the calendar service
+        let service = self.init_calendar_service().await?;
+
+        // Get the calendars
+        match service.list_calendars().await {
+            Ok(calendars) => {
+                // Convert to JSON
+                serde_json::to_string(&calendars).map_err(|e| {
+                    let error_msg = format!("Failed to serialize calendar list: {}", e);
+                    error!("{}", error_msg);
+                    self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+                })
+            }
+            Err(err) => {
+                error!("Failed to list calendars: {}", err);
+                Err(self.map_error(err))
+            }
+        }
+    }
+
+    /// Get your Calendar settings
+    ///
+    /// This command reads your Google Calendar settings, such as your calendar's time
+    /// zone, the day your week starts on and the default length of new events, and
+    /// shows the defaults calendar tools take from them. USER_TIMEZONE, when set, takes
+    /// precedence over the calendar's time zone.
+    ///
+    /// # Returns
+    ///
+    /// A JSON string with the "settings" and the "defaults" used for dates in words,
+    /// agendas and new events
+    #[tool]
+    async fn get_calendar_settings(&self) -> McpResult<String> {
+        info!("=== START get_calendar_settings MCP command ===");
+        debug!("get_calendar_settings called");
+
+        let service = self.init_calendar_service().await?;
+        let settings = service.get_settings().await.map_err(|err| {
+            error!("Failed to get calendar settings: {}", err);
+            self.map_error(err)
+        })?;
+
+        let defaults = crate::calendar_settings::CalendarDefaults::new(
+            Some(&settings),
+            crate::config::get_configured_time_zone(),
+        );
+        let report = crate::calendar_settings::CalendarSettingsReport { settings, defaults };
+        let result_json = serde_json::to_string_pretty(&report).map_err(|e| {
+            let error_msg = format!("Failed to serialize calendar settings: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END get_calendar_settings MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// List holiday and birthday calendars
+    ///
+    /// This command lists Google's regional holiday calendars and the calendar of your
+    /// contacts' birthdays, with whether you are subscribed to each.
+    ///
+    /// # Arguments
+    ///
+    /// * `region` - Optional region code or name to look for, such as "uk" or "Zealand".
+    ///   The birthday calendar is only listed when no region is given.
+    ///
+    /// # Returns
+    ///
+    /// A JSON string containing the calendars, each with its ID, type ("holiday" or
+    /// "birthday") and whether it is subscribed
+    #[tool]
+    async fn list_holiday_calendars(&self, region: Option<String>) -> McpResult<String> {
+        info!("=== START list_holiday_calendars MCP command ===");
+        debug!("list_holiday_calendars called with region={:?}", region);
+
+        let service = self.init_calendar_service().await?;
+        let subscribed = service.list_calendars().await.map_err(|err| {
+            error!("Failed to list calendars: {}", err);
+            self.map_error(err)
+        })?;
+
+        let calendars =
+            crate::holidays::special_calendars(&subscribed.calendars, region.as_deref());
+        if calendars.is_empty() {
+            let error_msg = format!(
+                "No holiday calendar for region '{}'",
+                region.unwrap_or_default()
+            );
+            error!("{}", error_msg);
+            return Err(self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR));
+        }
+        let result_json = serde_json::to_string_pretty(&calendars).map_err(|e| {
+            let error_msg = format!("Failed to serialize calendars: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END list_holiday_calendars MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Subscribe to a calendar
+    ///
+    /// This command adds a calendar to your calendar list, such as a regional holiday
+    /// calendar or the calendar of your contacts' birthdays, so its events appear in
+    /// your daily agenda.
+    ///
+    /// # Arguments
+    ///
+    /// * `calendar` - "birthdays", a region code or name such as "uk" or "Germany", or a
+    ///   calendar ID
+    ///
+    /// # Returns
+    ///
+    /// A JSON string containing the calendar added
+    #[tool]
+    async fn subscribe_calendar(&self, calendar: String) -> McpResult<String> {
+        info!("=== START subscribe_calendar MCP command ===");
+        debug!("subscribe_calendar called with calendar={}", calendar);
+
+        self.validate(Validator::new().required("calendar", &calendar))?;
+
+        let calendar_id = crate::holidays::resolve_calendar(&calendar).map_err(|error_msg| {
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        let service = self.init_calendar_service().await?;
+        let subscribed = service
+            .subscribe_calendar(&calendar_id)
+            .await
+            .map_err(|err| {
+                error!("Failed to subscribe to calendar {}: {}", calendar_id, err);
+                self.map_error(err)
+            })?;
+
+        let result_json = serde_json::to_string_pretty(&subscribed).map_err(|e| {
+            let error_msg = format!("Failed to serialize calendar: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END subscribe_calendar MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// List events from a calendar
+    ///
+    /// This command retrieves events from a specified calendar, with options for filtering.
+    /// Each attendee comes with the time zone of their calendar when they share it with
+    /// you, so times can be suggested that suit everyone. Events you have declined are
+    /// left out unless asked for.
+    ///
+    /// # Arguments
+    ///
+    /// * `calendar_id` - The ID of the calendar to get events from (optional, defaults to primary)
+    /// * `max_results` - Optional maximum number of events to return
+    /// * `time_min` - Optional minimum time bound (RFC3339, or words such as "tomorrow")
+    /// * `time_max` - Optional maximum time bound (RFC3339, or words such as "next Monday")
+    /// * `include_declined` - Whether to list events you have declined (default: false)
+    /// * `created_by_server` - Whether to list only the events this server created
+    ///   (default: false)
+    ///
+    /// # Returns
+    ///
+    /// A JSON string containing the event list, each event with "declined" set when you
+    /// have declined it and its "private_properties"
+    #[tool]
+    async fn list_events(
+        &self,
+        calendar_id: Option<String>,
+        max_results: Option<serde_json::Value>,
+        time_min: Option<String>,
+        time_max: Option<String>,
+        include_declined: Option<bool>,
+        created_by_server: Option<bool>,
+    ) -> McpResult<String> {
+        info!("=== START list_events MCP command ===");
+        debug!(
+            "list_events called with calendar_id={:?}, max_results={:?}, time_min={:?}, time_max={:?}, include_declined={:?}, created_by_server={:?}",
+            calendar_id, max_results, time_min, time_max, include_declined, created_by_server
+        );
+
+        self.validate(
+            Validator::new()
+                .optional(time_min.as_deref(), |v, time| v.date("time_min", time))
+                .optional(time_max.as_deref(), |v, time| v.date("time_max", time)),
+        )?;
+
+        // Use primary calendar if not specified
+        let calendar_id = calendar_id.unwrap_or_else(|| "primary".to_string());
+
+        // Convert max_results using the helper function (default: 10)
+        let max = helpers::parse_max_results(max_results, 10);
+
+        // Initialize the calendar service
+        let service = self.init_calendar_service().await?;
+
+        // Parse time bounds if provided, reading dates in words in the user's
+        // time zone
+        let now = chrono::Utc::now();
+        let time_zone = self.calendar_defaults(&service).await.time_zone;
+        let time_min_parsed = match time_min {
+            Some(t) => Some(self.parse_date_arg("time_min", &t, now, time_zone)?),
+            None => None,
+        };
+        let time_max_parsed = match time_max {
+            Some(t) => Some(self.parse_date_arg("time_max", &t, now, time_zone)?),
+            None => None,
+        };
+
+        // Get the events, only those tagged as created by this server if asked
+        let properties = if created_by_server.unwrap_or(false) {
+            crate::event_properties::created_by_server_filter()
+        } else {
+            Vec::new()
+        };
+        match service
+            .list_events_with_properties(
+                &calendar_id,
+                Some(max),
+                time_min_parsed,
+                time_max_parsed,
+                &properties,
+            )
+            .await
+        {
+            Ok(mut events) => {
+                crate::timezones::add_attendee_time_zones(&service, &mut events).await;
+                let events =
+                    crate::declined::flag_declined(events, include_declined.unwrap_or(false));
+
+                // Convert to JSON
+                serde_json::to_string(&events).map_err(|e| {
+                    let error_msg = format!("Failed to serialize events list: {}", e);
+                    error!("{}", error_msg);
+                    self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+                })
+            }
+            Err(err) => {
+                error!(
+                    "Failed to list events from calendar {}: {}",
+                    calendar_id, err
+                );
+                Err(self.map_error(err))
+            }
+        }
+    }
+
+    /// Get the agenda for a day
+    ///
+    /// This command lists a day's events in your time zone, all-day events first, and
+    /// says where you are working that day from your working location. Holidays and
+    /// birthdays from the holiday and birthday calendars you subscribe to are included,
+    /// each marked with its type. Events you have declined are left out unless asked for.
+    ///
+    /// # Arguments
+    ///
+    /// * `date` - Optional day (a date such as 2024-03-01, or words such as "tomorrow").
+    ///   Defaults to today.
+    /// * `calendar_id` - The ID of the calendar (optional, defaults to primary)
+    /// * `include_declined` - Whether to list events you have declined (default: false)
+    ///
+    /// # Returns
+    ///
+    /// A JSON string with the date, "working_location" (null if not set) and the events,
+    /// each with a "type" of "event", "holiday" or "birthday" and "declined" set when you
+    /// have declined it
+    #[tool]
+    async fn daily_agenda(
+        &self,
+        date: Option<String>,
+        calendar_id: Option<String>,
+        include_declined: Option<bool>,
+    ) -> McpResult<String> {
+        info!("=== START daily_agenda MCP command ===");
+        debug!(
+            "daily_agenda called with date={:?}, calendar_id={:?}, include_declined={:?}",
+            date, calendar_id, include_declined
+        );
+
+        self.validate(Validator::new().optional(date.as_deref(), |v, date| v.date("date", date)))?;
+
+        let calendar_id = calendar_id.unwrap_or_else(|| "primary".to_string());
+
+        // The day is read in the user's time zone
+        let service = self.init_calendar_service().await?;
+        let now = chrono::Utc::now();
+        let time_zone = self.calendar_defaults(&service).await.time_zone;
+        let invalid = |error_msg: String| {
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::API_ERROR)
+        };
+        let day = match date {
+            Some(date) => crate::utils::parse_date_expression(&date, now, time_zone)
+                .map_err(|e| invalid(format!("Invalid date: {}", e)))?,
+            None => now,
+        }
+        .with_timezone(&time_zone)
+        .date_naive();
+        let (day_start, day_end) =
+            crate::working_location::day_bounds(day, time_zone).map_err(invalid)?;
+
+        let mut events = service
+            .list_events(
+                &calendar_id,
+                Some(crate::working_location::DAILY_AGENDA_MAX_EVENTS),
+                Some(day_start),
+                Some(day_end),
+            )
+            .await
+            .map_err(|err| {
+                error!(
+                    "Failed to list events from calendar {}: {}",
+                    calendar_id, err
+                );
+                self.map_error(err)
+            })?;
+        crate::declined::retain_declined(&mut events, include_declined.unwrap_or(false));
+
+        // Add the day's holidays and birthdays from the calendars subscribed to
+        let special_ids: Vec<String> = match service.list_calendars().await {
+            Ok(list) => list
+                .calendars
+                .into_iter()
+                .map(|calendar| calendar.id)
+                .filter(|id| crate::holidays::is_special_calendar(id) && *id != calendar_id)
+                .collect(),
+            Err(err) => {
+                error!("Skipping holidays and birthdays: {}", err);
+                Vec::new()
+            }
+        };
+        let listings = special_ids.iter().map(|id| {
+            service.list_events(
+                id,
+                Some(crate::working_location::DAILY_AGENDA_MAX_EVENTS),
+                Some(day_start),
+                Some(day_end),
+            )
+        });
+        let mut calendars = vec![(calendar_id, events)];
+        for (id, result) in special_ids
+            .iter()
+            .zip(futures::future::join_all(listings).await)
+        {
+            match result {
+                Ok(events) => calendars.push((id.clone(), events)),
+                Err(err) => error!("Skipping calendar {}: {}", id, err),
+            }
+        }
+
+        let agenda = crate::working_location::DailyAgenda::new(day, time_zone, calendars)
+            .map_err(invalid)?;
+        let result_json = serde_json::to_string_pretty(&agenda).map_err(|e| {
+            let error_msg = format!("Failed to serialize agenda: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END daily_agenda MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Export an agenda as an ICS file
+    ///
+    /// This command writes a calendar's events for a time range, optionally only those
+    /// matching a search, to an iCalendar (.ics) file that other calendar apps can
+    /// subscribe to or import. Exporting again to the same file name replaces it, so the
+    /// file can be followed as a feed. Files are written to ICS_EXPORT_DIR.
+    ///
+    /// # Arguments
+    ///
+    /// * `calendar_id` - The ID of the calendar (optional, defaults to primary)
+    /// * `time_min` - Optional start of the time range (RFC3339, or words such as "today").
+    ///   Defaults to now.
+    /// * `time_max` - Optional end of the time range (RFC3339, or words such as "next Monday").
+    ///   Defaults to 30 days after the start.
+    /// * `query` - Optional words every exported event must contain in its title,
+    ///   description or location
+    /// * `filename` - Optional file name (default: agenda.ics)
+    ///
+    /// # Returns
+    ///
+    /// A JSON string with the path of the file and the number of events written
+    #[tool]
+    async fn export_agenda_ics(
+        &self,
+        calendar_id: Option<String>,
+        time_min: Option<String>,
+        time_max: Option<String>,
+        query: Option<String>,
+        filename: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START export_agenda_ics MCP command ===");
+        debug!(
+            "export_agenda_ics called with calendar_id={:?}, time_min={:?}, time_max={:?}, query={:?}, filename={:?}",
+            calendar_id, time_min, time_max, query, filename
+        );
+
+        self.validate(
+            Validator::new()
+                .optional(time_min.as_deref(), |v, time| v.date("time_min", time))
+                .optional(time_max.as_deref(), |v, time| v.date("time_max", time)),
+        )?;
+
+        let calendar_id = calendar_id.unwrap_or_else(|| "primary".to_string());
+
+        // Parse the time range, reading dates in words in the user's time zone
+        let service = self.init_calendar_service().await?;
+        let now = chrono::Utc::now();
+        let time_zone = self.calendar_defaults(&service).await.time_zone;
+        let parse_bound =
+            |name: &str, value: &str| self.parse_date_arg(name, value, now, time_zone);
+        let range_start = match time_min {
+            Some(t) => parse_bound("time_min", &t)?,
+            None => now,
+        };
+        let range_end = match time_max {
+            Some(t) => parse_bound("time_max", &t)?,
+            None => range_start + chrono::Duration::days(crate::ics::ICS_DEFAULT_DAYS),
+        };
+        self.validate(Validator::new().after("time_max", range_end, "time_min", range_start))?;
+
+        let mut filename = crate::attachments::safe_filename(
+            filename
+                .as_deref()
+                .unwrap_or(crate::ics::DEFAULT_ICS_FILENAME),
+        );
+        if !filename.to_lowercase().ends_with(".ics") {
+            filename.push_str(".ics");
+        }
+        let path = crate::ics::get_ics_export_dir().join(filename);
+
+        let events = service
+            .list_events(
+                &calendar_id,
+                Some(crate::ics::ICS_MAX_EVENTS),
+                Some(range_start),
+                Some(range_end),
+            )
+            .await
+            .map_err(|err| {
+                error!(
+                    "Failed to list events from calendar {}: {}",
+                    calendar_id, err
+                );
+                self.map_error(err)
+            })?;
+
+        let events = crate::ics::filter_agenda(events, (range_start, range_end), query.as_deref());
+        let name = match &query {
+            Some(query) => format!("{} ({})", calendar_id, query),
+            None => calendar_id.clone(),
+        };
+        let ics = crate::ics::to_ics(&events, &name, now);
+        crate::ics::write_ics(&path, &ics).map_err(|e| {
+            let error_msg = format!("Failed to write {}: {}", path.display(), e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::GENERAL_ERROR)
+        })?;
+
+        info!("=== END export_agenda_ics MCP command (success) ===");
+        Ok(json!({
+            "path": path.display().to_string(),
+            "calendar_id": calendar_id,
+            "events": events.len(),
+            "time_min": range_start,
+            "time_max": range_end,
+        })
+        .to_string())
+    }
+
+    /// Find events with a person
+    ///
+    /// This command searches all your calendars for events that a person attends or
+    /// organizes, answering questions such as "when do I next meet with Alice?" without
+    /// listing every event. Calendars that cannot be read are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `person_email` - The email address of the person
+    /// * `time_min` - Optional start of the time range (RFC3339, or words such as "today").
+    ///   Defaults to now.
+    /// * `time_max` - Optional end of the time range (RFC3339, or words such as "next Monday").
+    ///   Defaults to 90 days after the start.
+    /// * `max_results` - Optional maximum number of events to return (default: 10)
+    ///
+    /// # Returns
+    ///
+    /// A JSON string with the matching events, soonest first, each with its calendar and
+    /// the person's response
+    #[tool]
+    async fn find_events_with(
+        &self,
+        person_email: String,
+        time_min: Option<String>,
+        time_max: Option<String>,
+        max_results: Option<serde_json::Value>,
+    ) -> McpResult<String> {
+        info!("=== START find_events_with MCP command ===");
+        debug!(
+            "find_events_with called with person_email={}, time_min={:?}, time_max={:?}, max_results={:?}",
+            person_email, time_min, time_max, max_results
+        );
+
+        self.validate(
+            Validator::new()
+                .optional(time_min.as_deref(), |v, time| v.date("time_min", time))
+                .optional(time_max.as_deref(), |v, time| v.date("time_max", time)),
+        )?;
+
+        self.validate(Validator::new().email("person_email", &person_email))?;
+        // Validated as exactly one address
+        let address = crate::utils::parse_email_addresses(&person_email)
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        let max = helpers::parse_max_results(
+            max_results,
+            crate::event_search::DEFAULT_EVENT_SEARCH_RESULTS,
+        );
+
+        // Parse the time range, reading dates in words in the user's time zone
+        let service = self.init_calendar_service().await?;
+        let now = chrono::Utc::now();
+        let time_zone = self.calendar_defaults(&service).await.time_zone;
+        let parse_bound =
+            |name: &str, value: &str| self.parse_date_arg(name, value, now, time_zone);
+        let range_start = match time_min {
+            Some(t) => parse_bound("time_min", &t)?,
+            None => now,
+        };
+        let range_end = match time_max {
+            Some(t) => parse_bound("time_max", &t)?,
+            None => {
+                range_start + chrono::Duration::days(crate::event_search::DEFAULT_EVENT_SEARCH_DAYS)
+            }
+        };
+        self.validate(Validator::new().after("time_max", range_end, "time_min", range_start))?;
+
+        let calendars = service.list_calendars().await.map_err(|err| {
+            error!("Failed to list calendars: {}", err);
+            self.map_error(err)
+        })?;
+
+        let listings = calendars.calendars.iter().map(|calendar| {
+            service.list_events(
+                &calendar.id,
+                Some(crate::event_search::EVENT_SEARCH_MAX_PER_CALENDAR),
+                Some(range_start),
+                Some(range_end),
+            )
+        });
+        let calendar_events: Vec<_> = futures::future::join_all(listings)
+            .await
+            .into_iter()
+            .zip(calendars.calendars)
+            .filter_map(|(result, calendar)| match result {
+                Ok(events) => Some((calendar, events)),
+                Err(err) => {
+                    error!("Skipping calendar {}: {}", calendar.id, err);
+                    None
+                }
+            })
+            .collect();
+
+        let found = crate::event_search::EventsWith::new(
+            &address,
+            (range_start, range_end),
+            &calendar_events,
+            max as usize,
+        );
+        let result_json = serde_json::to_string_pretty(&found).map_err(|e| {
+            let error_msg = format!("Failed to serialize events: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END find_events_with MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Report on calendar time
+    ///
+    /// This command shows where the time in a recent period went, like Google Calendar's
+    /// Time Insights: meeting hours per day and per week, how many meetings ran straight
+    /// into the one before, the people met with most, and the share of working hours
+    /// (9:00 to 17:00 on weekdays) spent in meetings or blocked as focus time. A meeting
+    /// is an event on the primary calendar with at least one other guest that you
+    /// haven't declined. Days and weeks are in your calendar's time zone.
+    ///
+    /// # Arguments
+    ///
+    /// * `time_range` - Optional period ending now as days, weeks, months or years, e.g.
+    ///   "7d", "2w", "3m" or "1y" (default: "30d")
+    ///
+    /// # Returns
+    ///
+    /// A JSON string with the meeting totals, "back_to_back", "focus_share",
+    /// "meeting_share", "by_day", "by_week" and "top_co_attendees"
+    #[tool]
+    async fn calendar_report(&self, time_range: Option<String>) -> McpResult<String> {
+        info!("=== START calendar_report MCP command ===");
+        debug!("calendar_report called with time_range={:?}", time_range);
+
+        let time_range = time_range
+            .unwrap_or_else(|| crate::calendar_report::DEFAULT_CALENDAR_REPORT_RANGE.to_string());
+        let length = crate::calendar_report::parse_report_range(&time_range).map_err(|e| {
+            error!("{}", e);
+            self.to_mcp_error(&e, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+        let range_end = chrono::Utc::now();
+        let range_start = range_end - length;
+
+        let user_email = self
+            .init_gmail_service()
+            .await?
+            .get_email_address()
+            .await
+            .map_err(|err| {
+                error!("Failed to get the user's email address: {}", err);
+                self.map_error(err)
+            })?;
+        let service = self.init_calendar_service().await?;
+        let time_zone = self.calendar_defaults(&service).await.time_zone;
+        let events = service
+            .list_events(
+                "primary",
+                Some(crate::calendar_report::CALENDAR_REPORT_MAX_EVENTS),
+                Some(range_start),
+                Some(range_end),
+            )
+            .await
+            .map_err(|err| {
+                error!("Failed to list events for the report: {}", err);
+                self.map_error(err)
+            })?;
+
+        let report = crate::calendar_report::CalendarReport::new(
+            (range_start, range_end),
+            &events,
+            &user_email,
+            time_zone,
+        );
+        let result_json = serde_json::to_string_pretty(&report).map_err(|e| {
+            let error_msg = format!("Failed to serialize calendar report: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END calendar_report MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// List event rules
+    ///
+    /// This command lists the rules categorize_events applies, in the order they are
+    /// tried. The first rule matching an event gives it its color and category.
+    ///
+    /// # Returns
+    ///
+    /// A JSON string with the rules and the file they are kept in
+    #[tool]
+    async fn list_event_rules(&self) -> McpResult<String> {
+        info!("=== START list_event_rules MCP command ===");
+
+        let store = crate::event_rules::EventRuleStore::from_env();
+        let rules = store.list().map_err(|err| {
+            error!("Failed to list event rules: {}", err);
+            self.map_error(err)
+        })?;
+
+        let result = json!({
+            "count": rules.len(),
+            "path": store.path().display().to_string(),
+            "rules": rules
+        });
+
+        let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize event rules: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END list_event_rules MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Save an event rule
+    ///
+    /// This command saves a rule giving matching events a color, a category or both, such
+    /// as making every 1:1 green. Every condition given must hold for an event to match.
+    /// Saving a rule with an existing name replaces it in its place; new rules are tried
+    /// after the others.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the rule, e.g. "1:1s"
+    /// * `keywords` - Optional words or phrases, any of which in the title or description
+    ///   matches
+    /// * `attendees` - Optional addresses or "@domain"s, any of which among the attendees
+    ///   matches
+    /// * `max_attendees` - Optional. Match events with guests and at most this many people,
+    ///   counting you, so 2 matches 1:1s
+    /// * `color` - Optional color: an ID from 1 to 11, a Calendar color name such as
+    ///   "basil", or a name such as "green"
+    /// * `category` - Optional category, kept privately on the event
+    ///
+    /// # Returns
+    ///
+    /// A JSON string with the rule as saved and whether it replaced another
+    #[tool]
+    async fn save_event_rule(
+        &self,
+        name: String,
+        keywords: Option<Vec<String>>,
+        attendees: Option<Vec<String>>,
+        max_attendees: Option<u32>,
+        color: Option<String>,
+        category: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START save_event_rule MCP command ===");
+        debug!(
+            "save_event_rule called with name={}, keywords={:?}, attendees={:?}, max_attendees={:?}, color={:?}, category={:?}",
+            name, keywords, attendees, max_attendees, color, category
+        );
+
+        self.validate(Validator::new().required("name", &name))?;
+
+        let rule = crate::event_rules::EventRule {
+            name,
+            keywords: keywords.unwrap_or_default(),
+            attendees: attendees.unwrap_or_default(),
+            max_attendees: max_attendees.map(|max| max as usize),
+            color_id: color,
+            category,
+        };
+
+        let store = crate::event_rules::EventRuleStore::from_env();
+        let (rule, replaced) = store.save(rule).map_err(|err| {
+            error!("Failed to save event rule: {}", err);
+            self.map_error(err)
+        })?;
+
+        let result = json!({
+            "status": "success",
+            "replaced": replaced,
+            "rule": rule
+        });
+
+        let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize saved event rule: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END save_event_rule MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Delete an event rule
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the rule
+    ///
+    /// # Returns
+    ///
+    /// A JSON string confirming the deletion
+    #[tool]
+    async fn delete_event_rule(&self, name: String) -> McpResult<String> {
+        info!("=== START delete_event_rule MCP command ===");
+        debug!("delete_event_rule called with name={}", name);
+
+        self.validate(Validator::new().required("name", &name))?;
+
+        let store = crate::event_rules::EventRuleStore::from_env();
+        store.delete(&name).map_err(|err| {
+            error!("Failed to delete event rule {}: {}", name, err);
+            self.map_error(err)
+        })?;
+
+        info!("=== END delete_event_rule MCP command (success) ===");
+        Ok(json!({ "status": "success", "deleted": name }).to_string())
+    }
+
+    /// Color and categorize events by the event rules
+    ///
+    /// This command applies the saved event rules to the events in a time range. Each
+    /// event matching a rule is given the first matching rule's color and category, unless
+    /// it already has them. Events that cannot be changed are reported and the others
+    /// still updated.
+    ///
+    /// # Arguments
+    ///
+    /// * `time_min` - Optional start of the time range (RFC3339, or words such as "today").
+    ///   Defaults to now.
+    /// * `time_max` - Optional end of the time range (RFC3339, or words such as "next Monday").
+    ///   Defaults to 30 days after the start.
+    /// * `calendar_id` - The ID of the calendar (optional, defaults to primary)
+    /// * `dry_run` - Optional. Report the changes without making them (default: false)
+    ///
+    /// # Returns
+    ///
+    /// A JSON string with each matching event, the rule it matched and whether it was
+    /// updated
+    #[tool]
+    async fn categorize_events(
+        &self,
+        time_min: Option<String>,
+        time_max: Option<String>,
+        calendar_id: Option<String>,
+        dry_run: Option<bool>,
+    ) -> McpResult<String> {
+        info!("=== START categorize_events MCP command ===");
+        debug!(
+            "categorize_events called with time_min={:?}, time_max={:?}, calendar_id={:?}, dry_run={:?}",
+            time_min, time_max, calendar_id, dry_run
+        );
+
+        self.validate(
+            Validator::new()
+                .optional(time_min.as_deref(), |v, time| v.date("time_min", time))
+                .optional(time_max.as_deref(), |v, time| v.date("time_max", time)),
+        )?;
+
+        let calendar_id = calendar_id.unwrap_or_else(|| "primary".to_string());
+        let dry_run = dry_run.unwrap_or(false);
+
+        let store = crate::event_rules::EventRuleStore::from_env();
+        let rules = store.list().map_err(|err| {
+            error!("Failed to list event rules: {}", err);
+            self.map_error(err)
+        })?;
+        if rules.is_empty() {
+            let error_msg = "No event rules saved; add one with save_event_rule".to_string();
+            error!("{}", error_msg);
+            return Err(self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR));
+        }
+
+        // Parse the time range, reading dates in words in the user's time zone
+        let service = self.init_calendar_service().await?;
+        let now = chrono::Utc::now();
+        let time_zone = self.calendar_defaults(&service).await.time_zone;
+        let parse_bound =
+            |name: &str, value: &str| self.parse_date_arg(name, value, now, time_zone);
+        let range_start = match time_min {
+            Some(t) => parse_bound("time_min", &t)?,
+            None => now,
+        };
+        let range_end = match time_max {
+            Some(t) => parse_bound("time_max", &t)?,
+            None => {
+                range_start + chrono::Duration::days(crate::event_rules::DEFAULT_CATEGORIZE_DAYS)
+            }
+        };
+        self.validate(Validator::new().after("time_max", range_end, "time_min", range_start))?;
+
+        let events = service
+            .list_events(
+                &calendar_id,
+                Some(crate::event_rules::CATEGORIZE_MAX_EVENTS),
+                Some(range_start),
+                Some(range_end),
+            )
+            .await
+            .map_err(|err| {
+                error!("Failed to list events: {}", err);
+                self.map_error(err)
+            })?;
+
+        let mut categorized = Vec::new();
+        for event in &events {
+            let (Some(event_id), Some(rule)) = (
+                event.id.as_deref(),
+                crate::event_rules::match_rule(&rules, event),
+            ) else {
+                continue;
+            };
+            let Some(patch) = rule.patch(event) else {
+                categorized.push(crate::event_rules::CategorizedEvent::new(
+                    event,
+                    rule,
+                    "unchanged",
+                ));
+                continue;
+            };
+            if dry_run {
+                categorized.push(crate::event_rules::CategorizedEvent::new(
+                    event,
+                    rule,
+                    "would_update",
+                ));
+                continue;
+            }
+            match service
+                .patch_event(&calendar_id, event_id, &patch, None, "none")
+                .await
+            {
+                Ok(updated) => categorized.push(crate::event_rules::CategorizedEvent::new(
+                    &updated, rule, "updated",
+                )),
+                Err(err) => {
+                    error!("Failed to categorize event {}: {}", event_id, err);
+                    let mut failed =
+                        crate::event_rules::CategorizedEvent::new(event, rule, "failed");
+                    failed.error = Some(err.to_string());
+                    categorized.push(failed);
+                }
+            }
+        }
+
+        let count = |status: &str| categorized.iter().filter(|e| e.status == status).count();
+        let result = json!({
+            "calendar_id": calendar_id,
+            "time_min": range_start,
+            "time_max": range_end,
+            "dry_run": dry_run,
+            "events_checked": events.len(),
+            "matched": categorized.len(),
+            "updated": count(if dry_run { "would_update" } else { "updated" }),
+            "failed": count("failed"),
+            "events": categorized
+        });
+
+        let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize categorized events: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END categorize_events MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Get a single calendar event
+    ///
+    /// This command retrieves a specific event from a calendar. With
+    /// `include_availability`, it also reads the attendees' free/busy for a few
+    /// hours either side of the event, to ground suggestions for moving it.
+    ///
+    /// # Arguments
+    ///
+    /// * `calendar_id` - The ID of the calendar (optional, defaults to primary)
+    /// * `event_id` - The ID of the event to retrieve
+    /// * `include_availability` - Optional. Add each attendee's free/busy around the
+    ///   event (default: false)
+    ///
+    /// # Returns
+    ///
+    /// A JSON string containing the event details, with an "availability" object
+    /// giving each attendee's status ("free", "busy" or "unknown") and busy times,
+    /// and the times all of them are free, when availability is included
+    #[tool]
+    async fn get_event(
+        &self,
+        calendar_id: Option<String>,
+        event_id: String,
+        include_availability: Option<bool>,
+    ) -> McpResult<String> {
+        info!("=== START get_event MCP command ===");
+        debug!(
+            "get_event called with calendar_id={:?}, event_id={}, include_availability={:?}",
+            calendar_id, event_id, include_availability
+        );
+
+        self.validate(Validator::new().required("event_id", &event_id))?;
+
+        // Use primary calendar if not specified
+        let calendar_id = calendar_id.unwrap_or_else(|| "primary".to_string());
+
+        // Initialize the calendar service
+        let service = self.init_calendar_service().await?;
+
+        // Get the event
+        let event = service
+            .get_event(&calendar_id, &event_id)
+            .await
+            .map_err(|err| {
+                error!(
+                    "Failed to get event {} from calendar {}: {}",
+                    event_id, calendar_id, err
+                );
+                self.map_error(err)
+            })?;
+
+        let serialize_error = |e: serde_json::Error| {
+            let error_msg = format!("Failed to serialize event: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        };
+        if !include_availability.unwrap_or(false) {
+            return serde_json::to_string(&event).map_err(serialize_error);
+        }
+
+        let calendars = crate::attendee_availability::availability_calendars(&event);
+        let free_busy = if calendars.is_empty() {
+            Vec::new()
+        } else {
+            let (window_start, window_end) =
+                crate::attendee_availability::availability_window(&event);
+            service
+                .free_busy_calendars(&calendars, window_start, window_end)
+                .await
+                .map_err(|err| {
+                    error!("Failed to query free/busy of attendees: {}", err);
+                    self.map_error(err)
+                })?
+        };
+        let availability = crate::attendee_availability::EventAvailability::new(&event, &free_busy);
+
+        let mut result = serde_json::to_value(&event).map_err(serialize_error)?;
+        result["availability"] = serde_json::to_value(&availability).map_err(serialize_error)?;
+        let result_json = serde_json::to_string(&result).map_err(serialize_error)?;
+
+        info!("=== END get_event MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Prepare a brief for a meeting
+    ///
+    /// This command gathers what is needed to prepare for an upcoming event: the
+    /// attendees other than you with their response, whether they organize the
+    /// meeting and their contact record, the latest email threads with any of them,
+    /// and the agenda documents attached to the event or linked from its description.
+    ///
+    /// # Arguments
+    ///
+    /// * `calendar_id` - The ID of the calendar (optional, defaults to primary)
+    /// * `event_id` - The ID of the event to prepare for
+    ///
+    /// # Returns
+    ///
+    /// A JSON string with the event details, "starts_in_minutes", "conference_link",
+    /// "attendees", "agenda_documents" and "recent_threads" (most recent first)
+    #[tool]
+    async fn meeting_brief(
+        &self,
+        calendar_id: Option<String>,
+        event_id: String,
+    ) -> McpResult<String> {
+        info!("=== START meeting_brief MCP command ===");
+        debug!(
+            "meeting_brief called with calendar_id={:?}, event_id={}",
+            calendar_id, event_id
+        );
+
+        self.validate(Validator::new().required("event_id", &event_id))?;
+
+        let calendar_id = calendar_id.unwrap_or_else(|| "primary".to_string());
+        let calendar_client = self.init_calendar_service().await?;
+        let event = calendar_client
+            .get_event(&calendar_id, &event_id)
+            .await
+            .map_err(|err| {
+                error!(
+                    "Failed to get event {} from calendar {}: {}",
+                    event_id, calendar_id, err
+                );
+                self.map_error(err)
+            })?;
+
+        let service = self.init_gmail_service().await?;
+        let user_email = service.get_email_address().await.map_err(|err| {
+            error!("Failed to get the user's email address: {}", err);
+            self.map_error(err)
+        })?;
+        let attendees = crate::meeting_brief::other_attendees(&event, &user_email);
+
+        let messages = match crate::meeting_brief::attendee_query(&attendees) {
+            Some(query) => service
+                .list_message_metadata(
+                    crate::meeting_brief::MEETING_BRIEF_MAX_MESSAGES,
+                    Some(&query),
+                    crate::meeting_brief::MEETING_BRIEF_HEADERS,
+                )
+                .await
+                .map_err(|err| {
+                    error!("Failed to list messages with the attendees: {}", err);
+                    self.map_error(err)
+                })?,
+            None => Vec::new(),
+        };
+
+        let people_client = self.init_people_service().await?;
+        let mut contacts = Vec::new();
+        for address in attendees
+            .iter()
+            .take(crate::meeting_brief::MEETING_BRIEF_MAX_CONTACTS)
+        {
+            let found = people_client
+                .search_contacts(address, Some(5))
+                .await
+                .map_err(|err| {
+                    error!("Failed to search contacts for {}: {}", address, err);
+                    self.map_error(err)
+                })?;
+            contacts.extend(found.contacts);
+        }
+
+        let brief = crate::meeting_brief::MeetingBrief::new(
+            &event,
+            &user_email,
+            &contacts,
+            &messages,
+            chrono::Utc::now(),
+        );
+        let result_json = serde_json::to_string_pretty(&brief).map_err(|e| {
+            let error_msg = format!("Failed to serialize meeting brief: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END meeting_brief MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Report the responses to an event
+    ///
+    /// This command groups an event's attendees by their response to the invitation, with
+    /// names from your contacts, so you can see who still needs to answer.
+    ///
+    /// # Arguments
+    ///
+    /// * `calendar_id` - The ID of the calendar (optional, defaults to primary)
+    /// * `event_id` - The ID of the event
+    ///
+    /// # Returns
+    ///
+    /// A JSON string with "counts", the "accepted", "declined", "tentative" and
+    /// "no_response" attendees, and "awaiting_response", the required attendees yet to
+    /// respond
+    #[tool]
+    async fn event_rsvps(
+        &self,
+        calendar_id: Option<String>,
+        event_id: String,
+    ) -> McpResult<String> {
+        info!("=== START event_rsvps MCP command ===");
+        debug!(
+            "event_rsvps called with calendar_id={:?}, event_id={}",
+            calendar_id, event_id
+        );
+
+        self.validate(Validator::new().required("event_id", &event_id))?;
+
+        let calendar_id = calendar_id.unwrap_or_else(|| "primary".to_string());
+        let calendar_client = self.init_calendar_service().await?;
+        let event = calendar_client
+            .get_event(&calendar_id, &event_id)
+            .await
+            .map_err(|err| {
+                error!(
+                    "Failed to get event {} from calendar {}: {}",
+                    event_id, calendar_id, err
+                );
+                self.map_error(err)
+            })?;
+
+        let people_client = self.init_people_service().await?;
+        let mut contacts = Vec::new();
+        for address in crate::event_rsvps::invitee_addresses(&event)
+            .iter()
+            .take(crate::event_rsvps::RSVP_MAX_CONTACTS)
+        {
+            let found = people_client
+                .search_contacts(address, Some(5))
+                .await
+                .map_err(|err| {
+                    error!("Failed to search contacts for {}: {}", address, err);
+                    self.map_error(err)
+                })?;
+            contacts.extend(found.contacts);
+        }
+
+        let report = crate::event_rsvps::RsvpReport::new(&event, &contacts);
+        let result_json = serde_json::to_string_pretty(&report).map_err(|e| {
+            let error_msg = format!("Failed to serialize RSVP report: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END event_rsvps MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Convert a time between time zones
+    ///
+    /// This command converts a time from one time zone to another using the IANA time
+    /// zone database, taking daylight saving time into account on both sides.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - RFC3339 timestamp, or a local time such as "2026-10-15 09:30" in `from_tz`
+    /// * `from_tz` - IANA name of the time zone to convert from, e.g. "America/New_York"
+    /// * `to_tz` - IANA name of the time zone to convert to, e.g. "Europe/Berlin"
+    ///
+    /// # Returns
+    ///
+    /// A JSON string with the "from" and "to" local times (with abbreviation, UTC
+    /// offset and whether daylight saving time applies), the "utc" time, the
+    /// "day_change" between the two local dates, and a "note" for ambiguous times
+    #[tool]
+    async fn convert_time(
+        &self,
+        time: String,
+        from_tz: String,
+        to_tz: String,
+    ) -> McpResult<String> {
+        info!("=== START convert_time MCP command ===");
+        debug!(
+            "convert_time called with time={}, from_tz={}, to_tz={}",
+            time, from_tz, to_tz
+        );
+
+        self.validate(
+            Validator::new()
+                .required("time", &time)
+                .required("from_tz", &from_tz)
+                .required("to_tz", &to_tz),
+        )?;
+
+        let conversion = crate::timezones::parse_time_zone(&from_tz)
+            .and_then(|from| {
+                let to = crate::timezones::parse_time_zone(&to_tz)?;
+                crate::timezones::convert_time(&time, from, to)
+            })
+            .map_err(|error_msg| {
+                error!("{}", error_msg);
+                self.to_mcp_error(&error_msg, error_codes::API_ERROR)
+            })?;
+
+        let result_json = serde_json::to_string_pretty(&conversion).map_err(|e| {
+            let error_msg = format!("Failed to serialize time conversion: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END convert_time MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Create a new calendar event
+    ///
+    /// This command creates a new event in the specified calendar.
+    ///
+    /// # Arguments
+    ///
+    /// * `calendar_id` - The ID of the calendar (optional, defaults to primary)
+    /// * `summary` - The title of the event
+    /// * `description` - Optional description of the event
+    /// * `location` - Optional location of the event
+    /// * `start_time` - Start time in RFC3339, or words such as "tomorrow 3pm"
+    /// * `end_time` - Optional end time in RFC3339, or words such as "tomorrow 4pm".
+    ///   Defaults to the default event length in your calendar settings.
+    /// * `all_day` - Optional. When true, the event takes whole days, from the date of
+    ///   start_time through the date of end_time (default: false)
+    /// * `attendees` - Optional list of attendee emails
+    /// * `source_message_id` - Optional ID of the email the event comes from, kept in the
+    ///   event's private properties as "sourceMessageId"
+    /// * `guests_can_modify` - Optional. Whether guests can change the event (Calendar's
+    ///   default: false)
+    /// * `guests_can_invite_others` - Optional. Whether guests can invite others
+    ///   (Calendar's default: true)
+    /// * `guests_can_see_other_guests` - Optional. Whether guests can see who else is
+    ///   invited (Calendar's default: true)
+    /// * `idempotency_key` - Optional key identifying this call. A repeated call with the
+    ///   same key returns the first call's result without creating another event.
+    ///
+    /// # Returns
+    ///
+    /// A JSON string containing the created event details
+    #[tool]
+    #[allow(clippy::too_many_arguments)]
+    async fn create_event(
+        &self,
+        // Calendar identification
+        calendar_id: Option<String>,
+        // Event core details
+        summary: String,
+        start_time: String,
+        end_time: Option<String>,
+        all_day: Option<bool>,
+        // Optional event details
+        description: Option<String>,
+        location: Option<String>,
+        // Participants
+        attendees: Option<Vec<String>>,
+        // Link back to an email
+        source_message_id: Option<String>,
+        // Guest permissions
+        guests_can_modify: Option<bool>,
+        guests_can_invite_others: Option<bool>,
+        guests_can_see_other_guests: Option<bool>,
+        idempotency_key: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START create_event MCP command ===");
+        debug!(
+            "create_event called with calendar_id={:?}, summary={}, description={:?}, location={:?}, start_time={}, end_time={:?}, all_day={:?}, attendees={:?}, source_message_id={:?}, guests_can_modify={:?}, guests_can_invite_others={:?}, guests_can_see_other_guests={:?}",
+            calendar_id, summary, description, location, start_time, end_time, all_day, attendees, source_message_id, guests_can_modify, guests_can_invite_others, guests_can_see_other_guests
+        );
+
+        self.validate(
+            Validator::new()
+                .required("summary", &summary)
+                .max_chars("summary", &summary, MAX_SUMMARY_CHARS)
+                .date("start_time", &start_time)
+                .optional(end_time.as_deref(), |v, end| v.date("end_time", end))
+                .optional(description.as_deref(), |v, description| {
+                    v.max_chars("description", description, MAX_DESCRIPTION_CHARS)
+                })
+                .optional(attendees.as_deref(), |v, attendees| {
+                    v.emails("attendees", attendees)
+                }),
+        )?;
+
+        let idempotency = match self.claim_idempotency_key(
+            "create_event",
+            idempotency_key.as_deref(),
+            json!({
+                "calendar_id": calendar_id,
+                "summary": summary,
+                "start_time": start_time,
+                "end_time": end_time,
+                "all_day": all_day,
+                "description": description,
+                "location": location,
+                "attendees": attendees,
+                "source_message_id": source_message_id,
+                "guests_can_modify": guests_can_modify,
+                "guests_can_invite_others": guests_can_invite_others,
+                "guests_can_see_other_guests": guests_can_see_other_guests,
+            }),
+        )? {
+            Claim::Replay(result) => return Ok(result),
+            Claim::Run(guard) => guard,
+        };
+
+        // Use primary calendar if not specified
+        let calendar_id = calendar_id.unwrap_or_else(|| "primary".to_string());
+
+        // Initialize the calendar service
+        let service = self.init_calendar_service().await?;
+
+        // Parse start and end times, reading dates in words in the user's time
+        // zone
+        let defaults = self.calendar_defaults(&service).await;
+        let now = chrono::Utc::now();
+        let time_zone = defaults.time_zone;
+        let start_dt = self.parse_date_arg("start_time", &start_time, now, time_zone)?;
+        let end_dt = match end_time {
+            Some(end_time) => self.parse_date_arg("end_time", &end_time, now, time_zone)?,
+            None => start_dt + defaults.default_event_length(),
+        };
+
+        // An all-day event runs from midnight UTC on the start's date, in the
+        // user's time zone, to midnight UTC after the end's date
+        let all_day = all_day.unwrap_or(false);
+        let (start_dt, end_dt) = if all_day {
+            let midnight = |time: chrono::DateTime<chrono::Utc>| {
+                time.with_timezone(&time_zone)
+                    .date_naive()
+                    .and_time(chrono::NaiveTime::MIN)
+                    .and_utc()
+            };
+            let start = midnight(start_dt);
+            let end = midnight(end_dt).max(start) + chrono::Duration::days(1);
+            (start, end)
+        } else {
+            (start_dt, end_dt)
+        };
+
+        // Create attendee objects from email strings
+        let attendee_objs = attendees
+            .unwrap_or_default()
+            .into_iter()
+            .map(|email| crate::calendar_api::Attendee {
+                email,
+                display_name: None,
+                response_status: Some("needsAction".to_string()),
+                optional: None,
+                time_zone: None,
+                self_: None,
+            })
+            .collect();
+
+        // Create the event
+        let event = crate::calendar_api::CalendarEvent {
+            summary,
+            description,
+            location,
+            start_time: start_dt,
+            end_time: end_dt,
+            attendees: attendee_objs,
+            all_day,
+            private_properties: source_message_id
+                .as_deref()
+                .map(crate::event_properties::source_message_properties)
+                .unwrap_or_default(),
+            guests_can_modify,
+            guests_can_invite_others,
+            guests_can_see_other_guests,
+            ..Default::default()
+        };
+
+        // Create the event
+        match service.create_event(&calendar_id, event).await {
+            Ok(created_event) => {
+                // Convert to JSON
+                serde_json::to_string(&created_event)
+                    .map(|result| idempotency.finish(result))
+                    .map_err(|e| {
+                        let error_msg = format!("Failed to serialize created event: {}", e);
+                        error!("{}", error_msg);
+                        self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+                    })
+            }
+            Err(err) => {
+                error!(
+                    "Failed to create event in calendar {}: {}",
+                    calendar_id, err
+                );
+                Err(self.map_error(err))
+            }
+        }
+    }
+
+    /// List saved event templates
+    ///
+    /// This command lists the templates create_event_from_template can use, with the
+    /// variables their `{{name}}` placeholders need.
+    ///
+    /// # Returns
+    ///
+    /// A JSON string with each template's name, summary, length, number of guests and
+    /// variables
+    #[tool]
+    async fn list_event_templates(&self) -> McpResult<String> {
+        info!("=== START list_event_templates MCP command ===");
+
+        let store = crate::event_templates::EventTemplateStore::from_env();
+        let templates = store.list().map_err(|err| {
+            error!("Failed to list event templates: {}", err);
+            self.map_error(err)
+        })?;
+
+        let summaries: Vec<_> = templates.iter().map(|t| t.summary()).collect();
+        let result = json!({
+            "count": summaries.len(),
+            "templates": summaries
+        });
+
+        let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize event templates: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END list_event_templates MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Save an event template
+    ///
+    /// This command saves a template for a recurring kind of meeting, such as an
+    /// interview or a 1:1. The summary, description, location and guests may contain
+    /// placeholders such as `{{candidate}}`, filled in by create_event_from_template.
+    /// Saving a template with an existing name replaces it.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the template, e.g. "interview"
+    /// * `summary` - Title of the events, which may contain placeholders
+    /// * `description` - Optional description, which may contain placeholders
+    /// * `location` - Optional location, which may contain placeholders
+    /// * `attendees` - Optional guest emails, which may be placeholders
+    /// * `duration_minutes` - Optional length of the events. Defaults to the default event
+    ///   length in your calendar settings.
+    /// * `reminders` - Optional reminders such as "10m", "popup 1h" or "email 1d". Defaults
+    ///   to the calendar's default reminders; an empty list means none.
+    ///
+    /// # Returns
+    ///
+    /// A JSON string with the saved template and whether it replaced another
+    #[tool]
+    #[allow(clippy::too_many_arguments)]
+    async fn save_event_template(
+        &self,
+        name: String,
+        summary: String,
+        description: Option<String>,
+        location: Option<String>,
+        attendees: Option<Vec<String>>,
+        duration_minutes: Option<i64>,
+        reminders: Option<Vec<String>>,
+    ) -> McpResult<String> {
+        info!("=== START save_event_template MCP command ===");
+        debug!(
+            "save_event_template called with name={}, summary={}, attendees={:?}, duration_minutes={:?}, reminders={:?}",
+            name, summary, attendees, duration_minutes, reminders
+        );
+
+        self.validate(
+            Validator::new()
+                .required("name", &name)
+                .required("summary", &summary)
+                .max_chars("summary", &summary, MAX_SUMMARY_CHARS),
+        )?;
+
+        let reminders = reminders
+            .map(|reminders| {
+                reminders
+                    .iter()
+                    .map(|reminder| crate::event_templates::parse_reminder(reminder))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .transpose()
+            .map_err(|error_msg| {
+                error!("{}", error_msg);
+                self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+            })?;
+
+        let template = crate::event_templates::EventTemplate {
+            name,
+            summary,
+            description,
+            location,
+            attendees: attendees.unwrap_or_default(),
+            duration_minutes,
+            reminders,
+        };
+
+        let store = crate::event_templates::EventTemplateStore::from_env();
+        let replaced = store.save(template.clone()).map_err(|err| {
+            error!("Failed to save event template: {}", err);
+            self.map_error(err)
+        })?;
+
+        let result = json!({
+            "status": "success",
+            "replaced": replaced,
+            "template": template,
+            "variables": template.variables()
+        });
+
+        let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize saved event template: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END save_event_template MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Create a calendar event from a saved template
+    ///
+    /// This command fills the template's placeholders from the given variables and
+    /// creates the event with the template's description, location, guests and
+    /// reminders. Fails, listing the missing names, if any placeholder has no value.
+    ///
+    /// # Arguments
+    ///
+    /// * `template_name` - Name of the template
+    /// * `variables` - Values for the template's placeholders, e.g. {"candidate": "Ada"}
+    /// * `start_time` - Start time in RFC3339, or words such as "tomorrow 3pm"
+    /// * `end_time` - Optional end time in RFC3339, or words such as "tomorrow 4pm".
+    ///   Defaults to the template's length, or else your calendar's default event length.
+    /// * `calendar_id` - The ID of the calendar (optional, defaults to primary)
+    /// * `idempotency_key` - Optional key identifying this call. A repeated call with the
+    ///   same key returns the first call's result without creating another event.
+    ///
+    /// # Returns
+    ///
+    /// A JSON string containing the created event details
+    #[tool]
+    async fn create_event_from_template(
+        &self,
+        template_name: String,
+        variables: Option<std::collections::HashMap<String, String>>,
+        start_time: String,
+        end_time: Option<String>,
+        calendar_id: Option<String>,
+        idempotency_key: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START create_event_from_template MCP command ===");
+        debug!(
+            "create_event_from_template called with template_name={}, variables={:?}, start_time={}, end_time={:?}, calendar_id={:?}",
+            template_name, variables, start_time, end_time, calendar_id
+        );
+
+        self.validate(
+            Validator::new()
+                .required("template_name", &template_name)
+                .date("start_time", &start_time)
+                .optional(end_time.as_deref(), |v, end| v.date("end_time", end)),
+        )?;
+
+        let idempotency = match self.claim_idempotency_key(
+            "create_event_from_template",
+            idempotency_key.as_deref(),
+            json!({
+                "template_name": template_name,
+                "variables": variables,
+                "start_time": start_time,
+                "end_time": end_time,
+                "calendar_id": calendar_id,
+            }),
+        )? {
+            Claim::Replay(result) => return Ok(result),
+            Claim::Run(guard) => guard,
+        };
+
+        let calendar_id = calendar_id.unwrap_or_else(|| "primary".to_string());
+
+        let store = crate::event_templates::EventTemplateStore::from_env();
+        let rendered = store
+            .get(&template_name)
+            .and_then(|template| template.render(&variables.unwrap_or_default()))
+            .map_err(|err| {
+                error!("Failed to fill event template {}: {}", template_name, err);
+                self.map_error(err)
+            })?;
+
+        // Parse start and end times, reading dates in words in the user's time
+        // zone
+        let service = self.init_calendar_service().await?;
+        let defaults = self.calendar_defaults(&service).await;
+        let now = chrono::Utc::now();
+        let time_zone = defaults.time_zone;
+        let start_dt = self.parse_date_arg("start_time", &start_time, now, time_zone)?;
+        let end_dt = match end_time {
+            Some(end_time) => self.parse_date_arg("end_time", &end_time, now, time_zone)?,
+            None => {
+                start_dt
+                    + rendered
+                        .duration
+                        .unwrap_or_else(|| defaults.default_event_length())
+            }
+        };
+        self.validate(Validator::new().after("end_time", end_dt, "start_time", start_dt))?;
+
+        let attendees = rendered
+            .attendees
+            .into_iter()
+            .map(|email| crate::calendar_api::Attendee {
+                email,
+                display_name: None,
+                response_status: Some("needsAction".to_string()),
+                optional: None,
+                time_zone: None,
+                self_: None,
+            })
+            .collect();
+
+        let event = crate::calendar_api::CalendarEvent {
+            summary: rendered.summary,
+            description: rendered.description,
+            location: rendered.location,
+            start_time: start_dt,
+            end_time: end_dt,
+            attendees,
+            reminders: rendered.reminders,
+            private_properties: std::collections::HashMap::new(),
+            ..Default::default()
+        };
+
+        let created_event = service
+            .create_event(&calendar_id, event)
+            .await
+            .map_err(|err| {
+                error!(
+                    "Failed to create event from template {} in calendar {}: {}",
+                    template_name, calendar_id, err
+                );
+                self.map_error(err)
+            })?;
+
+        let result_json = serde_json::to_string_pretty(&created_event).map_err(|e| {
+            let error_msg = format!("Failed to serialize created event: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END create_event_from_template MCP command (success) ===");
+        Ok(idempotency.finish(result_json))
+    }
+
+    /// Invite more people to an event
+    ///
+    /// This command adds guests to an event's attendee list, leaving the event's other
+    /// details and existing guests' responses as they are.
+    ///
+    /// # Arguments
+    ///
+    /// * `calendar_id` - The ID of the calendar (optional, defaults to primary)
+    /// * `event_id` - The ID of the event
+    /// * `emails` - Email addresses of the people to invite
+    /// * `optional` - Whether their attendance is optional (default: false)
+    /// * `send_updates` - Who to notify: "all" (default), "externalOnly" or "none"
+    ///
+    /// # Returns
+    ///
+    /// A JSON string listing who was added or already invited, and the guests now invited
+    #[tool]
+    async fn add_attendees(
+        &self,
+        calendar_id: Option<String>,
+        event_id: String,
+        emails: Vec<String>,
+        optional: Option<bool>,
+        send_updates: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START add_attendees MCP command ===");
+        debug!(
+            "add_attendees called with calendar_id={:?}, event_id={}, emails={:?}, optional={:?}, send_updates={:?}",
+            calendar_id, event_id, emails, optional, send_updates
+        );
+
+        self.validate(
+            Validator::new()
+                .required("event_id", &event_id)
+                .recipient_lists("emails", &emails),
+        )?;
+
+        let optional = optional.unwrap_or(false);
+        let result = self
+            .update_invites(
+                calendar_id,
+                event_id,
+                emails,
+                send_updates,
+                |existing, emails| crate::invites::add_to_attendees(existing, emails, optional),
+            )
+            .await?;
+
+        info!("=== END add_attendees MCP command (success) ===");
+        Ok(result)
+    }
+
+    /// Uninvite people from an event
+    ///
+    /// This command removes guests from an event's attendee list, leaving the event's other
+    /// details and remaining guests as they are.
+    ///
+    /// # Arguments
+    ///
+    /// * `calendar_id` - The ID of the calendar (optional, defaults to primary)
+    /// * `event_id` - The ID of the event
+    /// * `emails` - Email addresses of the people to remove
+    /// * `send_updates` - Who to notify: "all" (default), "externalOnly" or "none"
+    ///
+    /// # Returns
+    ///
+    /// A JSON string listing who was removed or was not invited, and the guests now invited
+    #[tool]
+    async fn remove_attendees(
+        &self,
+        calendar_id: Option<String>,
+        event_id: String,
+        emails: Vec<String>,
+        send_updates: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START remove_attendees MCP command ===");
+        debug!(
+            "remove_attendees called with calendar_id={:?}, event_id={}, emails={:?}, send_updates={:?}",
+            calendar_id, event_id, emails, send_updates
+        );
+
+        self.validate(
+            Validator::new()
+                .required("event_id", &event_id)
+                .recipient_lists("emails", &emails),
+        )?;
+
+        let result = self
+            .update_invites(
+                calendar_id,
+                event_id,
+                emails,
+                send_updates,
+                crate::invites::remove_from_attendees,
+            )
+            .await?;
+
+        info!("=== END remove_attendees MCP command (success) ===");
+        Ok(result)
+    }
+
+    /// Set private properties on an event
+    ///
+    /// This command tags an event with private extended properties, key-value pairs that
+    /// only your copy of the event has, such as {"project": "apollo"}. Properties already
+    /// on the event are kept unless a key is given again. Events this server creates are
+    /// tagged with "createdBy", which list_events can filter on; that key and "category"
+    /// can't be set here.
+    ///
+    /// # Arguments
+    ///
+    /// * `event_id` - The ID of the event
+    /// * `properties` - The properties to set, keys of at most 44 characters
+    /// * `calendar_id` - The ID of the calendar (optional, defaults to primary)
+    ///
+    /// # Returns
+    ///
+    /// A JSON string with the event ID and all of its private properties
+    #[tool]
+    async fn set_event_properties(
+        &self,
+        event_id: String,
+        properties: std::collections::HashMap<String, String>,
+        calendar_id: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START set_event_properties MCP command ===");
+        debug!(
+            "set_event_properties called with event_id={}, properties={:?}, calendar_id={:?}",
+            event_id, properties, calendar_id
+        );
+
+        self.validate(Validator::new().required("event_id", &event_id))?;
+
+        let calendar_id = calendar_id.unwrap_or_else(|| "primary".to_string());
+        crate::event_properties::validate_properties(&properties).map_err(|e| {
+            error!("{}", e);
+            self.to_mcp_error(&e, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        // Patching merges the keys into those the event already has
+        let service = self.init_calendar_service().await?;
+        let patch = json!({ "extendedProperties": { "private": properties } });
+        let updated = service
+            .patch_event(&calendar_id, &event_id, &patch, None, "none")
+            .await
+            .map_err(|err| {
+                error!("Failed to set properties on event {}: {}", event_id, err);
+                self.map_error(err)
+            })?;
+
+        let result = json!({
+            "event_id": event_id,
+            "calendar_id": calendar_id,
+            "private_properties": updated.private_properties,
+            "category": updated.category,
+        });
+        let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize event properties: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END set_event_properties MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Get the email an event was created from
+    ///
+    /// This command follows an event back to the email behind it. Events created with a
+    /// `source_message_id`, and reminders set with remind_me, keep the email's Gmail
+    /// message ID in their private extended properties.
+    ///
+    /// # Arguments
+    ///
+    /// * `event_id` - The ID of the event
+    /// * `calendar_id` - The ID of the calendar (optional, defaults to primary)
+    ///
+    /// # Returns
+    ///
+    /// A JSON string with the event's "event_id", "summary", the "message_id" and
+    /// Gmail "email_link" of the source email, and the "email" itself
+    #[tool]
+    async fn get_event_source_email(
+        &self,
+        event_id: String,
+        calendar_id: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START get_event_source_email MCP command ===");
         debug!(
-            "analyze_email called with message_id={}, analysis_type={:?}",
-            message_id, analysis_type
+            "get_event_source_email called with event_id={}, calendar_id={:?}",
+            event_id, calendar_id
         );
 
-        // Get the Gmail service
-        let mut service = self.init_gmail_service().await?;
-
-        // Get the specified email
-        let email = match service.get_message_details(&message_id).await {
-            Ok(msg) => msg,
-            Err(err) => {
-                error!("Failed to get email for analysis: {}", err);
-                return Err(self.map_gmail_error(err));
-            }
-        };
+        self.validate(Validator::new().required("event_id", &event_id))?;
 
-        // Determine what type of analysis to perform
-        let analysis = analysis_type.unwrap_or_else(|| "general".to_string());
+        let calendar_id = calendar_id.unwrap_or_else(|| "primary".to_string());
+        let service = self.init_calendar_service().await?;
+        let event = service
+            .get_event(&calendar_id, &event_id)
+            .await
+            .map_err(|err| {
+                error!(
+                    "Failed to get event {} from calendar {}: {}",
+                    event_id, calendar_id, err
+                );
+                self.map_error(err)
+            })?;
 
-        // Prepare the analysis result
-        let result = match analysis.to_lowercase().as_str() {
-            "tasks" | "task" => {
-                // Create a structured JSON for task analysis
-                json!({
-                    "email_id": email.id,
-                    "subject": email.subject,
-                    "from": email.from,
-                    "date": email.date,
-                    "analysis_type": "tasks",
-                    "content": email.body_text.unwrap_or_else(|| email.snippet.unwrap_or_default()),
-                    "analysis_prompt": crate::prompts::TASK_EXTRACTION_PROMPT
-                })
-            }
-            "meetings" | "meeting" => {
-                // Create a structured JSON for meeting analysis
-                json!({
-                    "email_id": email.id,
-                    "subject": email.subject,
-                    "from": email.from,
-                    "date": email.date,
-                    "analysis_type": "meetings",
-                    "content": email.body_text.unwrap_or_else(|| email.snippet.unwrap_or_default()),
-                    "analysis_prompt": crate::prompts::MEETING_EXTRACTION_PROMPT
-                })
-            }
-            "contacts" | "contact" => {
-                // Create a structured JSON for contact analysis
-                json!({
-                    "email_id": email.id,
-                    "subject": email.subject,
-                    "from": email.from,
-                    "date": email.date,
-                    "analysis_type": "contacts",
-                    "content": email.body_text.unwrap_or_else(|| email.snippet.unwrap_or_default()),
-                    "analysis_prompt": crate::prompts::CONTACT_EXTRACTION_PROMPT
-                })
-            }
-            "summary" | "summarize" => {
-                // Create a structured JSON for email summarization
-                json!({
-                    "email_id": email.id,
-                    "subject": email.subject,
-                    "from": email.from,
-                    "date": email.date,
-                    "analysis_type": "summary",
-                    "content": email.body_text.unwrap_or_else(|| email.snippet.unwrap_or_default()),
-                    "analysis_prompt": crate::prompts::EMAIL_SUMMARIZATION_PROMPT
-                })
-            }
-            "priority" | "prioritize" => {
-                // Create a structured JSON for email prioritization
-                json!({
-                    "email_id": email.id,
-                    "subject": email.subject,
-                    "from": email.from,
-                    "date": email.date,
-                    "analysis_type": "priority",
-                    "content": email.body_text.unwrap_or_else(|| email.snippet.unwrap_or_default()),
-                    "analysis_prompt": crate::prompts::EMAIL_PRIORITIZATION_PROMPT
-                })
-            }
-            "all" => {
-                // Create comprehensive JSON with all analysis types
-                json!({
-                    "email_id": email.id,
-                    "subject": email.subject,
-                    "from": email.from,
-                    "to": email.to,
-                    "date": email.date,
-                    "analysis_type": "comprehensive",
-                    "content": email.body_text.unwrap_or_else(|| email.snippet.unwrap_or_default()),
-                    "html_content": email.body_html,
-                    "analysis_prompts": {
-                        "general": crate::prompts::EMAIL_ANALYSIS_PROMPT,
-                        "tasks": crate::prompts::TASK_EXTRACTION_PROMPT,
-                        "meetings": crate::prompts::MEETING_EXTRACTION_PROMPT,
-                        "contacts": crate::prompts::CONTACT_EXTRACTION_PROMPT,
-                        "priority": crate::prompts::EMAIL_PRIORITIZATION_PROMPT
-                    }
-                })
-            }
-            _ => {
-                // Default to general analysis
-                json!({
-                    "email_id": email.id,
-                    "subject": email.subject,
-                    "from": email.from,
-                    "date": email.date,
-                    "analysis_type": "general",
-                    "content": email.body_text.unwrap_or_else(|| email.snippet.unwrap_or_default()),
-                    "analysis_prompt": crate::prompts::EMAIL_ANALYSIS_PROMPT
-                })
+        let message_id = match crate::event_properties::source_message_id(&event) {
+            Some(message_id) => message_id.to_string(),
+            None => {
+                let error_msg = format!("Event {} is not linked to an email", event_id);
+                error!("{}", error_msg);
+                return Err(self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR));
             }
         };
 
-        // Convert to string
+        let gmail = self.init_gmail_service().await?;
+        let email = gmail
+            .get_message_details(&message_id)
+            .await
+            .map_err(|err| {
+                error!(
+                    "Failed to get source email {} of event {}: {}",
+                    message_id, event_id, err
+                );
+                self.map_error(err)
+            })?;
+
+        let result = json!({
+            "event_id": event_id,
+            "summary": event.summary,
+            "message_id": message_id,
+            "email_link": crate::reminders::email_link(&message_id),
+            "email": email,
+        });
         let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
-            let error_msg = format!("Failed to serialize analysis result: {}", e);
+            let error_msg = format!("Failed to serialize source email: {}", e);
             error!("{}", error_msg);
             self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
         })?;
 
-        info!("=== END analyze_email MCP command (success) ===");
+        info!("=== END get_event_source_email MCP command (success) ===");
         Ok(result_json)
     }
 
-    /// Batch analyze multiple emails
+    /// Watch a calendar for changes
     ///
-    /// Takes a list of email IDs and performs quick analysis on each one.
-    /// Useful for getting an overview of multiple emails at once.
+    /// This command opens a push notification channel: Calendar posts to the webhook
+    /// whenever an event on the calendar is created, changed or deleted, so an outside
+    /// automation can react. Notifications don't say what changed; list the events again
+    /// to find out. The channel stops by itself when it expires.
     ///
-    /// Args:
-    ///   message_ids: List of email IDs to analyze
-    ///   analysis_type: Optional type of analysis to perform. Can be "summary", "tasks",
-    ///                  "priority", or "category". Default is "summary".
+    /// # Arguments
+    ///
+    /// * `calendar_id` - The ID of the calendar (optional, defaults to primary)
+    /// * `webhook_url` - HTTPS address to post notifications to
+    /// * `token` - Optional text sent back with every notification in the
+    ///   X-Goog-Channel-Token header, to check notifications are genuine
+    /// * `ttl_seconds` - Optional lifetime of the channel (Calendar's default: a week)
+    ///
+    /// # Returns
+    ///
+    /// A JSON string describing the channel, with the "id" and "resource_id" that
+    /// stop_channel needs and its "expiration"
     #[tool]
-    async fn batch_analyze_emails(
+    async fn watch_calendar(
         &self,
-        message_ids: Vec<String>,
-        analysis_type: Option<String>,
+        calendar_id: Option<String>,
+        webhook_url: String,
+        token: Option<String>,
+        ttl_seconds: Option<u64>,
     ) -> McpResult<String> {
-        info!("=== START batch_analyze_emails MCP command ===");
+        info!("=== START watch_calendar MCP command ===");
         debug!(
-            "batch_analyze_emails called with {} messages, analysis_type={:?}",
-            message_ids.len(),
-            analysis_type
+            "watch_calendar called with calendar_id={:?}, webhook_url={}, ttl_seconds={:?}",
+            calendar_id, webhook_url, ttl_seconds
         );
 
-        // Get the Gmail service
-        let mut service = self.init_gmail_service().await?;
+        self.validate(Validator::new().required("webhook_url", &webhook_url))?;
 
-        // Determine what type of analysis to perform
-        let analysis = analysis_type
-            .unwrap_or_else(|| "summary".to_string())
-            .to_lowercase();
+        let calendar_id = calendar_id.unwrap_or_else(|| "primary".to_string());
+        let validation = crate::calendar_watch::validate_webhook_url(&webhook_url).and_then(|_| {
+            token
+                .as_deref()
+                .map_or(Ok(()), crate::calendar_watch::validate_channel_token)
+        });
+        if let Err(error_msg) = validation {
+            error!("{}", error_msg);
+            return Err(self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR));
+        }
+        if ttl_seconds == Some(0) {
+            let error_msg = "ttl_seconds must be more than 0".to_string();
+            error!("{}", error_msg);
+            return Err(self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR));
+        }
 
-        // Analyze each email
-        let mut results = Vec::new();
-        for id in message_ids {
-            debug!("Analyzing email {}", id);
+        let service = self.init_calendar_service().await?;
+        let channel = service
+            .watch_events(&calendar_id, &webhook_url, token.as_deref(), ttl_seconds)
+            .await
+            .map_err(|err| {
+                error!("Failed to watch calendar {}: {}", calendar_id, err);
+                self.map_error(err)
+            })?;
 
-            // Get the specified email
-            match service.get_message_details(&id).await {
-                Ok(email) => {
-                    // Prepare analysis based on type
-                    let analysis_prompt = match analysis.as_str() {
-                        "tasks" | "task" => crate::prompts::TASK_EXTRACTION_PROMPT,
-                        "priority" => crate::prompts::EMAIL_PRIORITIZATION_PROMPT,
-                        "category" => crate::prompts::EMAIL_CATEGORIZATION_PROMPT,
-                        _ => crate::prompts::EMAIL_SUMMARIZATION_PROMPT, // Default to summary
-                    };
+        let mut result = serde_json::to_value(&channel).map_err(|e| {
+            let error_msg = format!("Failed to serialize channel: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+        result["calendar_id"] = json!(calendar_id);
+        result["webhook_url"] = json!(webhook_url);
+        let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize channel: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
 
-                    // Create analysis result
-                    let result = json!({
-                        "email_id": email.id,
-                        "subject": email.subject,
-                        "from": email.from,
-                        "date": email.date,
-                        "analysis_type": analysis,
-                        "content": email.body_text.unwrap_or_else(|| email.snippet.unwrap_or_default()),
-                        "analysis_prompt": analysis_prompt
-                    });
+        info!("=== END watch_calendar MCP command (success) ===");
+        Ok(result_json)
+    }
 
-                    results.push(result);
-                }
-                Err(err) => {
-                    // Log error but continue with other emails
-                    error!("Failed to analyze email {}: {}", id, err);
+    /// Stop watching a calendar
+    ///
+    /// This command stops a push notification channel opened by watch_calendar, so
+    /// Calendar no longer posts to its webhook.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel_id` - The "id" of the channel
+    /// * `resource_id` - The "resource_id" of the channel
+    ///
+    /// # Returns
+    ///
+    /// A JSON string confirming the channel was stopped
+    #[tool]
+    async fn stop_channel(&self, channel_id: String, resource_id: String) -> McpResult<String> {
+        info!("=== START stop_channel MCP command ===");
+        debug!(
+            "stop_channel called with channel_id={}, resource_id={}",
+            channel_id, resource_id
+        );
 
-                    // Add error entry to results with more detailed information
-                    results.push(json!({
-                        "email_id": id,
-                        "error": format!("Failed to retrieve email: {}", err),
-                        "message": "This email failed processing but other emails in the batch will continue to process",
-                        "status": "error"
-                    }));
-                }
-            }
-        }
+        self.validate(
+            Validator::new()
+                .required("channel_id", &channel_id)
+                .required("resource_id", &resource_id),
+        )?;
 
-        // Create a batch result
-        let batch_result = json!({
-            "analysis_type": analysis,
-            "email_count": results.len(),
-            "results": results
-        });
+        let service = self.init_calendar_service().await?;
+        service
+            .stop_channel(&channel_id, &resource_id)
+            .await
+            .map_err(|err| {
+                error!("Failed to stop channel {}: {}", channel_id, err);
+                self.map_error(err)
+            })?;
 
-        // Convert to string
-        let result_json = serde_json::to_string_pretty(&batch_result).map_err(|e| {
-            let error_msg = format!("Failed to serialize batch analysis result: {}", e);
+        let result = json!({
+            "channel_id": channel_id,
+            "resource_id": resource_id,
+            "stopped": true,
+        });
+        let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize result: {}", e);
             error!("{}", error_msg);
             self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
         })?;
 
-        info!("=== END batch_analyze_emails MCP command (success) ===");
+        info!("=== END stop_channel MCP command (success) ===");
         Ok(result_json)
     }
 
-    /// Create a draft email
+    /// Create recurring office hours
     ///
-    /// Creates a new draft email in Gmail with the specified content.
-    /// The email will be saved as a draft and can be edited before sending.
+    /// This command creates a weekly block of office hours, repeating at the same local
+    /// time in your time zone. The block is marked free so others can still book meetings
+    /// in it.
     ///
-    /// Args:
-    ///   to: Email address(es) of the recipient(s). Multiple addresses should be comma-separated.
-    ///   subject: Subject line of the email
-    ///   body: Plain text content of the email
-    ///   cc: Optional CC recipient(s). Multiple addresses should be comma-separated.
-    ///   bcc: Optional BCC recipient(s). Multiple addresses should be comma-separated.
-    ///   thread_id: Optional Gmail thread ID to associate this email with
-    ///   in_reply_to: Optional Message-ID that this email is replying to
-    ///   references: Optional comma-separated list of Message-IDs in the email thread
+    /// # Arguments
+    ///
+    /// * `days` - Days of the week, such as "Tuesday, Thursday" or "weekdays"
+    /// * `start_time` - Local start time, such as "14:00" or "2pm"
+    /// * `end_time` - Local end time, such as "16:00" or "4pm"
+    /// * `time_zone` - Optional IANA time zone (defaults to USER_TIMEZONE, or your
+    ///   calendar's time zone)
+    /// * `starting` - Optional first day (RFC3339 date, or words such as "next Monday").
+    ///   Defaults to today.
+    /// * `until` - Optional last day, for office hours that end
+    /// * `summary` - Optional title (defaults to "Office hours"). Only titles that mention
+    ///   office hours are listed by list_office_hours.
+    /// * `description` - Optional description, such as what to bring or how to sign up
+    /// * `location` - Optional location or meeting link
+    /// * `calendar_id` - The ID of the calendar (optional, defaults to primary)
+    ///
+    /// # Returns
+    ///
+    /// A JSON string describing the office hours created
     #[tool]
     #[allow(clippy::too_many_arguments)]
-    async fn create_draft_email(
+    async fn create_office_hours(
         &self,
-        // Required content
-        to: String,
-        subject: String,
-        body: String,
-        // Optional recipients
-        cc: Option<String>,
-        bcc: Option<String>,
-        // Optional threading
-        thread_id: Option<String>,
-        in_reply_to: Option<String>,
-        // Additional options
-        references: Option<String>,
+        days: String,
+        start_time: String,
+        end_time: String,
+        time_zone: Option<String>,
+        starting: Option<String>,
+        until: Option<String>,
+        summary: Option<String>,
+        description: Option<String>,
+        location: Option<String>,
+        calendar_id: Option<String>,
     ) -> McpResult<String> {
-        info!("=== START create_draft_email MCP command ===");
-        debug!(
-            "create_draft_email called with to={}, subject={}, cc={:?}, bcc={:?}, thread_id={:?}, in_reply_to={:?}",
-            to, subject, cc, bcc, thread_id, in_reply_to
+        info!("=== START create_office_hours MCP command ===");
+        debug!(
+            "create_office_hours called with days={}, start_time={}, end_time={}, time_zone={:?}, starting={:?}, until={:?}, summary={:?}, calendar_id={:?}",
+            days, start_time, end_time, time_zone, starting, until, summary, calendar_id
         );
 
-        // Validate email addresses
-        if to.is_empty() {
-            let error_msg = "Recipient (to) is required for creating a draft email";
-            error!("{}", error_msg);
-            return Err(self.to_mcp_error(error_msg, error_codes::MESSAGE_FORMAT_ERROR));
-        }
+        self.validate(
+            Validator::new()
+                .required("days", &days)
+                .required("start_time", &start_time)
+                .required("end_time", &end_time)
+                .optional(starting.as_deref(), |v, day| v.date("starting", day))
+                .optional(until.as_deref(), |v, day| v.date("until", day))
+                .optional(summary.as_deref(), |v, summary| {
+                    v.max_chars("summary", summary, MAX_SUMMARY_CHARS)
+                }),
+        )?;
 
-        // Create the draft email object
-        let draft = crate::gmail_api::DraftEmail {
-            to,
-            subject,
-            body,
-            cc,
-            bcc,
-            thread_id,
-            in_reply_to,
-            references,
+        let calendar_id = calendar_id.unwrap_or_else(|| "primary".to_string());
+        let invalid = |error_msg: String| {
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
         };
 
-        // Get the Gmail service
-        let mut service = self.init_gmail_service().await?;
-
-        // Create the draft
-        match service.create_draft(&draft).await {
-            Ok(draft_id) => {
-                // Create success response
-                let mut result = json!({
-                    "status": "success",
-                    "draft_id": draft_id,
-                    "message": "Draft email created successfully."
-                });
-
-                // Add threading info to response if provided
-                if let Some(ref thread_id_val) = draft.thread_id {
-                    result["thread_id"] = json!(thread_id_val);
-                }
+        let service = self.init_calendar_service().await?;
+        let time_zone = match time_zone {
+            Some(name) => crate::timezones::parse_time_zone(&name).map_err(invalid)?,
+            None => self.calendar_defaults(&service).await.time_zone,
+        };
+        let days = crate::office_hours::parse_weekdays(&days).map_err(invalid)?;
+        let parse_time = |name: &str, value: &str| {
+            crate::utils::parse_time_of_day(value).ok_or_else(|| {
+                invalid(format!(
+                    "Invalid {} '{}': expected a time of day such as 14:00 or 2pm",
+                    name, value
+                ))
+            })
+        };
+        let start = parse_time("start_time", &start_time)?;
+        let end = parse_time("end_time", &end_time)?;
 
-                // Convert to string
-                let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
-                    let error_msg = format!("Failed to serialize draft creation result: {}", e);
-                    error!("{}", error_msg);
-                    self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
-                })?;
+        // Days may be given in words, read in the office hours' time zone
+        let now = chrono::Utc::now();
+        let parse_day = |name: &str, value: &str| {
+            crate::utils::parse_date_expression(value, now, time_zone)
+                .map(|day| day.with_timezone(&time_zone).date_naive())
+                .map_err(|e| invalid(format!("Invalid {}: {}", name, e)))
+        };
+        let starting = match starting {
+            Some(day) => parse_day("starting", &day)?,
+            None => now.with_timezone(&time_zone).date_naive(),
+        };
+        let until = match until {
+            Some(day) => Some(parse_day("until", &day)?),
+            None => None,
+        };
 
-                info!("=== END create_draft_email MCP command (success) ===");
-                Ok(result_json)
-            }
-            Err(err) => {
-                error!("Failed to create draft email: {}", err);
+        let request = crate::office_hours::OfficeHoursRequest {
+            summary: summary
+                .unwrap_or_else(|| crate::office_hours::DEFAULT_OFFICE_HOURS_SUMMARY.to_string()),
+            description,
+            location,
+            days,
+            start,
+            end,
+            starting,
+            until,
+            time_zone,
+        };
+        let event = request.to_event().map_err(invalid)?;
 
-                // Create detailed error context for the user
+        let created = service
+            .create_event(&calendar_id, event)
+            .await
+            .map_err(|err| {
                 error!(
-                    "Context: Failed to create draft email with subject: '{}'",
-                    draft.subject
+                    "Failed to create office hours in calendar {}: {}",
+                    calendar_id, err
                 );
+                self.map_error(err)
+            })?;
 
-                Err(self.map_gmail_error(err))
-            }
-        }
+        let office_hours = crate::office_hours::OfficeHours::from_event(&calendar_id, &created);
+        let result_json = serde_json::to_string_pretty(&office_hours).map_err(|e| {
+            let error_msg = format!("Failed to serialize office hours: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END create_office_hours MCP command (success) ===");
+        Ok(result_json)
     }
 
-    /// List contacts
+    /// List office hours
     ///
-    /// This command retrieves a list of contacts from Google Contacts.
+    /// This command lists the weekly events on a calendar whose title mentions office
+    /// hours, with their days, local times and time zone.
     ///
-    /// # Parameters
+    /// # Arguments
     ///
-    /// * `max_results` - Optional. The maximum number of contacts to return.
+    /// * `calendar_id` - The ID of the calendar (optional, defaults to primary)
     ///
     /// # Returns
     ///
-    /// A JSON string containing the contact list
+    /// A JSON string containing the office hours
     #[tool]
-    async fn list_contacts(&self, max_results: Option<u32>) -> McpResult<String> {
-        info!("=== START list_contacts MCP command ===");
-        debug!("list_contacts called with max_results={:?}", max_results);
+    async fn list_office_hours(&self, calendar_id: Option<String>) -> McpResult<String> {
+        info!("=== START list_office_hours MCP command ===");
+        debug!(
+            "list_office_hours called with calendar_id={:?}",
+            calendar_id
+        );
 
-        // Initialize the People API client
-        let people_client = self.init_people_service().await?;
+        let calendar_id = calendar_id.unwrap_or_else(|| "primary".to_string());
 
-        match people_client.list_contacts(max_results).await {
-            Ok(contacts) => {
-                // Convert to JSON
-                serde_json::to_string(&contacts).map_err(|e| {
-                    let error_msg = format!("Failed to serialize contact list: {}", e);
-                    error!("{}", error_msg);
-                    self.to_mcp_error(&error_msg, error_codes::GENERAL_ERROR)
-                })
-            }
-            Err(err) => {
-                error!("Failed to list contacts: {}", err);
-                Err(self.to_mcp_error(
-                    &format!("Failed to list contacts: {}", err),
-                    error_codes::API_ERROR,
-                ))
-            }
-        }
+        let service = self.init_calendar_service().await?;
+        let events = service
+            .list_recurring_events(
+                &calendar_id,
+                Some(crate::office_hours::OFFICE_HOURS_MAX_EVENTS),
+            )
+            .await
+            .map_err(|err| {
+                error!(
+                    "Failed to list recurring events from calendar {}: {}",
+                    calendar_id, err
+                );
+                self.map_error(err)
+            })?;
+
+        let office_hours: Vec<_> = events
+            .iter()
+            .filter(|event| crate::office_hours::is_office_hours(event))
+            .filter_map(|event| crate::office_hours::OfficeHours::from_event(&calendar_id, event))
+            .collect();
+        let result_json = serde_json::to_string_pretty(&office_hours).map_err(|e| {
+            let error_msg = format!("Failed to serialize office hours: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END list_office_hours MCP command (success) ===");
+        Ok(result_json)
     }
 
-    /// Search contacts
+    /// Set your working location
     ///
-    /// This command searches for contacts matching the query.
+    /// This command records where you are working on each day of a date range, as
+    /// working location events on your primary calendar. Weekends are skipped unless
+    /// asked for.
     ///
-    /// # Parameters
+    /// # Arguments
     ///
-    /// * `query` - The search query.
-    /// * `max_results` - Optional. The maximum number of contacts to return.
+    /// * `start_date` - The first day (a date such as 2024-03-01, or words such as
+    ///   "next Monday")
+    /// * `end_date` - Optional last day, included (defaults to the start date)
+    /// * `location` - "home", "office", or the name of another place such as "Lisbon"
+    /// * `label` - Optional name of the office, such as "London HQ"
+    /// * `include_weekends` - Whether to set Saturdays and Sundays too (default: false)
     ///
     /// # Returns
     ///
-    /// A JSON string containing the matching contacts
+    /// A JSON string with the outcome for each day and the working location set
     #[tool]
-    async fn search_contacts(&self, query: String, max_results: Option<u32>) -> McpResult<String> {
-        info!("=== START search_contacts MCP command ===");
+    async fn set_working_location(
+        &self,
+        start_date: String,
+        end_date: Option<String>,
+        location: String,
+        label: Option<String>,
+        include_weekends: Option<bool>,
+    ) -> McpResult<String> {
+        info!("=== START set_working_location MCP command ===");
         debug!(
-            "search_contacts called with query=\"{}\" and max_results={:?}",
-            query, max_results
+            "set_working_location called with start_date={}, end_date={:?}, location={}, label={:?}, include_weekends={:?}",
+            start_date, end_date, location, label, include_weekends
         );
 
-        // Initialize the People API client
-        let people_client = self.init_people_service().await?;
+        self.validate(
+            Validator::new()
+                .date("start_date", &start_date)
+                .optional(end_date.as_deref(), |v, day| v.date("end_date", day))
+                .required("location", &location),
+        )?;
 
-        match people_client.search_contacts(&query, max_results).await {
-            Ok(contacts) => {
-                // Convert to JSON
-                serde_json::to_string(&contacts).map_err(|e| {
-                    let error_msg = format!("Failed to serialize contact search results: {}", e);
-                    error!("{}", error_msg);
-                    self.to_mcp_error(&error_msg, error_codes::GENERAL_ERROR)
-                })
-            }
-            Err(err) => {
-                error!("Failed to search contacts: {}", err);
-                Err(self.to_mcp_error(
-                    &format!("Failed to search contacts: {}", err),
-                    error_codes::API_ERROR,
-                ))
+        let invalid = |error_msg: String| {
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        };
+        let working_location =
+            crate::working_location::parse_working_location(&location, label).map_err(invalid)?;
+
+        // Days are read in the user's time zone
+        let service = self.init_calendar_service().await?;
+        let now = chrono::Utc::now();
+        let time_zone = self.calendar_defaults(&service).await.time_zone;
+        let parse_day = |name: &str, value: &str| {
+            crate::utils::parse_date_expression(value, now, time_zone)
+                .map(|day| day.with_timezone(&time_zone).date_naive())
+                .map_err(|e| invalid(format!("Invalid {}: {}", name, e)))
+        };
+        let first = parse_day("start_date", &start_date)?;
+        let last = match end_date {
+            Some(day) => parse_day("end_date", &day)?,
+            None => first,
+        };
+        let days =
+            crate::working_location::working_days(first, last, include_weekends.unwrap_or(false))
+                .map_err(invalid)?;
+
+        // Working location can only be set on the primary calendar
+        let creations = days.iter().map(|day| {
+            let event = crate::working_location::working_location_event(*day, &working_location);
+            service.create_event("primary", event)
+        });
+        let results = futures::future::join_all(creations).await;
+
+        let mut report = BatchReport::new();
+        for (day, result) in days.iter().zip(results) {
+            match result {
+                Ok(event) => report.push_success(day.to_string(), json!({ "event_id": event.id })),
+                Err(err) => {
+                    error!("Failed to set working location for {}: {}", day, err);
+                    report.push_failure(day.to_string(), err);
+                }
             }
         }
+
+        let mut extra = serde_json::Map::new();
+        extra.insert("working_location".to_string(), json!(working_location));
+        let result_json = serde_json::to_string_pretty(&report.to_json(extra)).map_err(|e| {
+            let error_msg = format!("Failed to serialize working location result: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END set_working_location MCP command (success) ===");
+        Ok(result_json)
     }
 
-    /// Get contact
+    /// Add travel buffers around a meeting
     ///
-    /// This command retrieves a specific contact by resource name.
+    /// This command creates buffer events for travel or commute time just before and
+    /// after an event. Buffers leave the time free and have no reminders; they are
+    /// recognised by their "Buffer" category, and block_time_for_tasks can be told to
+    /// keep clear of them with `respect_buffers=true`.
     ///
-    /// # Parameters
+    /// # Arguments
     ///
-    /// * `resource_name` - The resource name of the contact to retrieve.
+    /// * `event_id` - The ID of the meeting
+    /// * `minutes_before` - Optional minutes of buffer before the meeting (default: 0)
+    /// * `minutes_after` - Optional minutes of buffer after the meeting (default: 0)
+    /// * `calendar_id` - The ID of the calendar (optional, defaults to primary)
     ///
     /// # Returns
     ///
-    /// A JSON string containing the contact details
+    /// A JSON string with a result for each buffer, "before" and "after", with the ID of
+    /// the event created
     #[tool]
-    async fn get_contact(&self, resource_name: String) -> McpResult<String> {
-        info!("=== START get_contact MCP command ===");
-        debug!("get_contact called with resource_name={}", resource_name);
+    async fn add_buffer(
+        &self,
+        event_id: String,
+        minutes_before: Option<u32>,
+        minutes_after: Option<u32>,
+        calendar_id: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START add_buffer MCP command ===");
+        debug!(
+            "add_buffer called with event_id={}, minutes_before={:?}, minutes_after={:?}, calendar_id={:?}",
+            event_id, minutes_before, minutes_after, calendar_id
+        );
 
-        // Initialize the People API client
-        let people_client = self.init_people_service().await?;
+        self.validate(Validator::new().required("event_id", &event_id))?;
 
-        match people_client.get_contact(&resource_name).await {
-            Ok(contact) => {
-                // Convert to JSON
-                serde_json::to_string(&contact).map_err(|e| {
-                    let error_msg = format!("Failed to serialize contact: {}", e);
-                    error!("{}", error_msg);
-                    self.to_mcp_error(&error_msg, error_codes::GENERAL_ERROR)
-                })
-            }
-            Err(err) => {
-                error!("Failed to get contact: {}", err);
-                Err(self.to_mcp_error(
-                    &format!("Failed to get contact: {}", err),
-                    error_codes::API_ERROR,
-                ))
+        let calendar_id = calendar_id.unwrap_or_else(|| "primary".to_string());
+        let minutes_before = minutes_before.unwrap_or(0);
+        let minutes_after = minutes_after.unwrap_or(0);
+        crate::buffers::validate_buffer_minutes(minutes_before, minutes_after).map_err(|e| {
+            error!("{}", e);
+            self.to_mcp_error(&e, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        let service = self.init_calendar_service().await?;
+        let meeting = service
+            .get_event(&calendar_id, &event_id)
+            .await
+            .map_err(|err| {
+                error!("Failed to get event {}: {}", event_id, err);
+                self.map_error(err)
+            })?;
+        if meeting.all_day {
+            let error_msg = format!("Event {} takes whole days, so it has no buffers", event_id);
+            error!("{}", error_msg);
+            return Err(self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR));
+        }
+
+        let buffers = crate::buffers::buffer_events(&meeting, minutes_before, minutes_after);
+        let creations = buffers
+            .iter()
+            .map(|(_, buffer)| service.create_event(&calendar_id, buffer.clone()));
+        let results = futures::future::join_all(creations).await;
+
+        let mut report = BatchReport::new();
+        for ((side, buffer), result) in buffers.iter().zip(results) {
+            match result {
+                Ok(event) => report.push_success(
+                    side.as_str(),
+                    json!({
+                        "event_id": event.id,
+                        "start_time": buffer.start_time,
+                        "end_time": buffer.end_time,
+                    }),
+                ),
+                Err(err) => {
+                    error!(
+                        "Failed to add buffer {} event {}: {}",
+                        side.as_str(),
+                        event_id,
+                        err
+                    );
+                    report.push_failure(side.as_str(), err);
+                }
             }
         }
+
+        let mut extra = serde_json::Map::new();
+        extra.insert("event_id".to_string(), json!(event_id));
+        extra.insert("calendar_id".to_string(), json!(calendar_id));
+        let result_json = serde_json::to_string_pretty(&report.to_json(extra)).map_err(|e| {
+            let error_msg = format!("Failed to serialize buffer result: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END add_buffer MCP command (success) ===");
+        Ok(result_json)
     }
 
-    /// List all available calendars
+    /// Block out time for tasks
     ///
-    /// This command retrieves a list of all calendars the user has access to.
+    /// This command fits tasks into the free time of a day's working hours, in the order
+    /// given, and creates a focus time event for each one. Free time is read from the
+    /// calendar's free/busy information. Tasks that do not fit are returned rather than
+    /// split. Buffer events added by add_buffer leave the time free, so they are only
+    /// kept clear of when asked.
+    ///
+    /// # Arguments
+    ///
+    /// * `tasks` - Tasks with their lengths, such as ["Write report: 90m", "Review PRs (45 min)"]
+    /// * `day` - Optional day (a date such as 2024-03-01, or words such as "tomorrow").
+    ///   Defaults to today, from now on.
+    /// * `calendar_id` - The ID of the calendar (optional, defaults to primary). Focus time
+    ///   is only available on the primary calendar; elsewhere ordinary events are created.
+    /// * `work_start` - Optional start of working hours, such as "9am" (default: 09:00)
+    /// * `work_end` - Optional end of working hours, such as "17:30" (default: 17:00)
+    /// * `respect_buffers` - Whether to keep clear of travel buffers around meetings
+    ///   (default: false)
     ///
     /// # Returns
     ///
-    /// A JSON string containing the calendar list
+    /// A JSON string with the blocks scheduled and the tasks that could not be
     #[tool]
-    async fn list_calendars(&self) -> McpResult<String> {
-        info!("=== START list_calendars MCP command ===");
-        debug!("list_calendars called");
+    async fn block_time_for_tasks(
+        &self,
+        tasks: Vec<String>,
+        day: Option<String>,
+        calendar_id: Option<String>,
+        work_start: Option<String>,
+        work_end: Option<String>,
+        respect_buffers: Option<bool>,
+    ) -> McpResult<String> {
+        info!("=== START block_time_for_tasks MCP command ===");
+        debug!(
+            "block_time_for_tasks called with tasks={:?}, day={:?}, calendar_id={:?}, work_start={:?}, work_end={:?}, respect_buffers={:?}",
+            tasks, day, calendar_id, work_start, work_end, respect_buffers
+        );
 
-        // Initialize the calendar service
-        let service = self.init_calendar_service().await?;
+        self.validate(
+            Validator::new()
+                .max_items("tasks", &tasks, MAX_LIST_ITEMS)
+                .optional(day.as_deref(), |v, day| v.date("day", day)),
+        )?;
 
-        // Get the calendars
-        match service.list_calendars().await {
-            Ok(calendars) => {
-                // Convert to JSON
-                serde_json::to_string(&calendars).map_err(|e| {
-                    let error_msg = format!("Failed to serialize calendar list: {}", e);
-                    error!("{}", error_msg);
-                    self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
-                })
-            }
-            Err(err) => {
-                error!("Failed to list calendars: {}", err);
-                Err(self.to_mcp_error(
-                    &format!("Failed to list calendars: {}", err),
-                    error_codes::API_ERROR,
+        let calendar_id = calendar_id.unwrap_or_else(|| "primary".to_string());
+        let invalid = |error_msg: String| {
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        };
+
+        let tasks = tasks
+            .iter()
+            .map(|task| crate::time_blocking::parse_task(task))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(invalid)?;
+        if tasks.is_empty() {
+            return Err(invalid("No tasks given".to_string()));
+        }
+
+        // The day and working hours are read in the user's time zone
+        let service = self.init_calendar_service().await?;
+        let now = chrono::Utc::now();
+        let time_zone = self.calendar_defaults(&service).await.time_zone;
+        let date = match day {
+            Some(day) => crate::utils::parse_date_expression(&day, now, time_zone)
+                .map_err(|e| invalid(format!("Invalid day: {}", e)))?,
+            None => now,
+        }
+        .with_timezone(&time_zone)
+        .date_naive();
+        let parse_time = |name: &str, value: Option<String>, default_hour: u32| match value {
+            Some(value) => crate::utils::parse_time_of_day(&value).ok_or_else(|| {
+                invalid(format!(
+                    "Invalid {} '{}': expected a time of day such as 09:00 or 9am",
+                    name, value
                 ))
+            }),
+            None => Ok(chrono::NaiveTime::from_hms_opt(default_hour, 0, 0).unwrap_or_default()),
+        };
+        let start = parse_time(
+            "work_start",
+            work_start,
+            crate::time_blocking::DEFAULT_WORK_START_HOUR,
+        )?;
+        let end = parse_time(
+            "work_end",
+            work_end,
+            crate::time_blocking::DEFAULT_WORK_END_HOUR,
+        )?;
+        if end <= start {
+            return Err(invalid("work_end must be after work_start".to_string()));
+        }
+        let window_start =
+            crate::utils::local_to_utc(date.and_time(start), time_zone, &start.to_string())
+                .map_err(invalid)?;
+        let window_end =
+            crate::utils::local_to_utc(date.and_time(end), time_zone, &end.to_string())
+                .map_err(invalid)?;
+        // Time already gone today is not free
+        let window_start = window_start.max(crate::time_blocking::align_up(now));
+
+        let mut busy = if window_start < window_end {
+            service
+                .free_busy(&calendar_id, window_start, window_end)
+                .await
+                .map_err(|err| {
+                    error!(
+                        "Failed to query free/busy for calendar {}: {}",
+                        calendar_id, err
+                    );
+                    self.map_error(err)
+                })?
+        } else {
+            Vec::new()
+        };
+        // Buffers leave the time free, so free/busy doesn't count them
+        if respect_buffers.unwrap_or(false) && window_start < window_end {
+            let events = service
+                .list_events(
+                    &calendar_id,
+                    Some(crate::buffers::BUFFER_MAX_EVENTS),
+                    Some(window_start),
+                    Some(window_end),
+                )
+                .await
+                .map_err(|err| {
+                    error!(
+                        "Failed to list buffers in calendar {}: {}",
+                        calendar_id, err
+                    );
+                    self.map_error(err)
+                })?;
+            busy.extend(crate::buffers::buffer_busy(&events));
+        }
+
+        let slots = crate::time_blocking::free_slots((window_start, window_end), &busy);
+        let (planned, mut unscheduled) = crate::time_blocking::plan_blocks(&tasks, slots);
+
+        let creations = planned.iter().map(|block| {
+            let event =
+                crate::time_blocking::focus_time_event(block, &calendar_id, time_zone.name());
+            service.create_event(&calendar_id, event)
+        });
+        let results = futures::future::join_all(creations).await;
+        let mut scheduled = Vec::new();
+        for (mut block, result) in planned.into_iter().zip(results) {
+            match result {
+                Ok(event) => {
+                    block.event_id = event.id;
+                    scheduled.push(block);
+                }
+                Err(err) => {
+                    error!("Failed to block time for '{}': {}", block.task, err);
+                    unscheduled.push(crate::time_blocking::UnscheduledTask {
+                        task: block.task,
+                        minutes: block.minutes,
+                        reason: format!("Failed to create event: {}", err),
+                    });
+                }
             }
         }
+
+        let plan = crate::time_blocking::TimeBlockPlan {
+            date,
+            time_zone: time_zone.name().to_string(),
+            calendar_id,
+            scheduled,
+            unscheduled,
+        };
+        let result_json = serde_json::to_string_pretty(&plan).map_err(|e| {
+            let error_msg = format!("Failed to serialize time blocks: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END block_time_for_tasks MCP command (success) ===");
+        Ok(result_json)
     }
 
-    /// List events from a calendar
+    /// Show when you are free
     ///
-    /// This command retrieves events from a specified calendar, with options for filtering.
+    /// This command merges the busy time of all your calendars (primary, work and
+    /// shared calendars) into one list of free windows in your working hours, in your
+    /// calendar's time zone. Holiday and birthday calendars are left out. Windows shorter
+    /// than 30 minutes are dropped. Calendars whose busy time could not be read are listed
+    /// with an error rather than failing the command.
     ///
     /// # Arguments
     ///
-    /// * `calendar_id` - The ID of the calendar to get events from (optional, defaults to primary)
-    /// * `max_results` - Optional maximum number of events to return
-    /// * `time_min` - Optional minimum time bound (RFC3339 timestamp)
-    /// * `time_max` - Optional maximum time bound (RFC3339 timestamp)
+    /// * `time_range` - Optional period to cover: days or weeks from today such as "3d" or
+    ///   "2w", a day such as "tomorrow", or a span such as "monday to friday"
+    ///   (default: "7d", at most 31 days)
+    /// * `calendars` - Optional calendars to merge, by ID or name, with "primary" for your
+    ///   primary calendar (default: every calendar in your list)
+    /// * `work_start` - Optional start of working hours, such as "9am" (default: 09:00)
+    /// * `work_end` - Optional end of working hours, such as "17:30" (default: 17:00)
+    /// * `include_weekends` - Whether Saturdays and Sundays count (default: false)
     ///
     /// # Returns
     ///
-    /// A JSON string containing the event list
+    /// A JSON string with the calendars read, the merged "busy" periods, the "free"
+    /// windows with their length in minutes, and "text" listing the free windows by day,
+    /// ready to paste into an email
     #[tool]
-    async fn list_events(
+    async fn my_availability(
         &self,
-        calendar_id: Option<String>,
-        max_results: Option<serde_json::Value>,
-        time_min: Option<String>,
-        time_max: Option<String>,
+        time_range: Option<String>,
+        calendars: Option<Vec<String>>,
+        work_start: Option<String>,
+        work_end: Option<String>,
+        include_weekends: Option<bool>,
     ) -> McpResult<String> {
-        info!("=== START list_events MCP command ===");
+        info!("=== START my_availability MCP command ===");
         debug!(
-            "list_events called with calendar_id={:?}, max_results={:?}, time_min={:?}, time_max={:?}",
-            calendar_id, max_results, time_min, time_max
+            "my_availability called with time_range={:?}, calendars={:?}, work_start={:?}, work_end={:?}, include_weekends={:?}",
+            time_range, calendars, work_start, work_end, include_weekends
         );
 
-        // Use primary calendar if not specified
-        let calendar_id = calendar_id.unwrap_or_else(|| "primary".to_string());
+        let calendars = calendars.unwrap_or_default();
+        self.validate(Validator::new().max_items("calendars", &calendars, MAX_LIST_ITEMS))?;
 
-        // Convert max_results using the helper function (default: 10)
-        let max = helpers::parse_max_results(max_results, 10);
+        let invalid = |error_msg: String| {
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        };
 
-        // Parse time bounds if provided
-        let time_min_parsed = if let Some(t) = time_min {
-            match chrono::DateTime::parse_from_rfc3339(&t) {
-                Ok(dt) => Some(dt.with_timezone(&chrono::Utc)),
-                Err(e) => {
-                    let error_msg = format!("Invalid time_min format (expected RFC3339): {}", e);
-                    error!("{}", error_msg);
-                    return Err(self.to_mcp_error(&error_msg, error_codes::API_ERROR));
-                }
-            }
-        } else {
-            None
+        // The period and working hours are read in the user's time zone
+        let service = self.init_calendar_service().await?;
+        let now = chrono::Utc::now();
+        let time_zone = self.calendar_defaults(&service).await.time_zone;
+        let time_range = time_range
+            .filter(|range| !range.trim().is_empty())
+            .unwrap_or_else(|| crate::my_availability::DEFAULT_AVAILABILITY_RANGE.to_string());
+        let range =
+            crate::my_availability::parse_range(&time_range, now, time_zone).map_err(invalid)?;
+        let parse_time = |name: &str, value: Option<String>, default_hour: u32| match value {
+            Some(value) => crate::utils::parse_time_of_day(&value).ok_or_else(|| {
+                invalid(format!(
+                    "Invalid {} '{}': expected a time of day such as 09:00 or 9am",
+                    name, value
+                ))
+            }),
+            None => Ok(chrono::NaiveTime::from_hms_opt(default_hour, 0, 0).unwrap_or_default()),
         };
+        let start = parse_time(
+            "work_start",
+            work_start,
+            crate::time_blocking::DEFAULT_WORK_START_HOUR,
+        )?;
+        let end = parse_time(
+            "work_end",
+            work_end,
+            crate::time_blocking::DEFAULT_WORK_END_HOUR,
+        )?;
+        if end <= start {
+            return Err(invalid("work_end must be after work_start".to_string()));
+        }
 
-        let time_max_parsed = if let Some(t) = time_max {
-            match chrono::DateTime::parse_from_rfc3339(&t) {
-                Ok(dt) => Some(dt.with_timezone(&chrono::Utc)),
-                Err(e) => {
-                    let error_msg = format!("Invalid time_max format (expected RFC3339): {}", e);
-                    error!("{}", error_msg);
-                    return Err(self.to_mcp_error(&error_msg, error_codes::API_ERROR));
-                }
-            }
+        let list = service.list_calendars().await.map_err(|err| {
+            error!("Failed to list calendars: {}", err);
+            self.map_error(err)
+        })?;
+        let calendar_ids = crate::my_availability::select_calendars(&list.calendars, &calendars)
+            .map_err(|e| {
+                error!("{}", e);
+                self.to_mcp_error(&e, error_codes::API_ERROR)
+            })?;
+        let free_busy = if calendar_ids.is_empty() {
+            Vec::new()
         } else {
-            None
+            service
+                .free_busy_calendars(&calendar_ids, range.0, range.1)
+                .await
+                .map_err(|err| {
+                    error!("Failed to query free/busy of your calendars: {}", err);
+                    self.map_error(err)
+                })?
         };
 
-        // Initialize the calendar service
-        let service = self.init_calendar_service().await?;
+        let availability = crate::my_availability::MyAvailability::new(
+            range,
+            time_zone,
+            (start, end),
+            include_weekends.unwrap_or(false),
+            &list.calendars,
+            &free_busy,
+        );
+        let result_json = serde_json::to_string_pretty(&availability).map_err(|e| {
+            let error_msg = format!("Failed to serialize availability: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
 
-        // Get the events
-        match service
-            .list_events(&calendar_id, Some(max), time_min_parsed, time_max_parsed)
-            .await
-        {
-            Ok(events) => {
-                // Convert to JSON
-                serde_json::to_string(&events).map_err(|e| {
-                    let error_msg = format!("Failed to serialize events list: {}", e);
-                    error!("{}", error_msg);
-                    self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
-                })
-            }
-            Err(err) => {
-                error!(
-                    "Failed to list events from calendar {}: {}",
-                    calendar_id, err
-                );
-                Err(self.to_mcp_error(
-                    &format!(
-                        "Failed to list events from calendar {}: {}",
-                        calendar_id, err
-                    ),
-                    error_codes::API_ERROR,
-                ))
-            }
-        }
+        info!("=== END my_availability MCP command (success) ===");
+        Ok(result_json)
     }
 
-    /// Get a single calendar event
-    ///
-    /// This command retrieves a specific event from a calendar.
+    /// Set a follow-up reminder for an email
     ///
-    /// # Arguments
-    ///
-    /// * `calendar_id` - The ID of the calendar (optional, defaults to primary)
-    /// * `event_id` - The ID of the event to retrieve
+    /// Labels the email and creates a calendar event at the requested time
+    /// that links back to it. With archive set, the email also leaves the
+    /// inbox until the reminder, like snoozing it.
     ///
-    /// # Returns
-    ///
-    /// A JSON string containing the event details
+    /// Args:
+    ///   message_id: The ID of the email to follow up on
+    ///   when: When to be reminded, in RFC3339 format (e.g. "2024-03-01T09:00:00Z")
+    ///         relative to now (e.g. "30m", "2h", "3d", "1w") or in words (e.g. "tomorrow 9am")
+    ///   note: Optional note to include in the reminder
+    ///   label: Optional label to apply. Default is "Follow-up".
+    ///   archive: Whether to remove the email from the inbox. Default is false.
+    ///   calendar_id: Optional calendar for the reminder. Default is "primary".
     #[tool]
-    async fn get_event(&self, calendar_id: Option<String>, event_id: String) -> McpResult<String> {
-        info!("=== START get_event MCP command ===");
+    async fn remind_me(
+        &self,
+        message_id: String,
+        when: String,
+        note: Option<String>,
+        label: Option<String>,
+        archive: Option<bool>,
+        calendar_id: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START remind_me MCP command ===");
         debug!(
-            "get_event called with calendar_id={:?}, event_id={}",
-            calendar_id, event_id
+            "remind_me called with message_id={}, when={}, label={:?}, archive={:?}, calendar_id={:?}",
+            message_id, when, label, archive, calendar_id
         );
 
-        // Use primary calendar if not specified
-        let calendar_id = calendar_id.unwrap_or_else(|| "primary".to_string());
-
-        // Initialize the calendar service
-        let service = self.init_calendar_service().await?;
+        self.validate(
+            Validator::new()
+                .required("message_id", &message_id)
+                .required("when", &when)
+                .optional(note.as_deref(), |v, note| {
+                    v.max_chars("note", note, MAX_DESCRIPTION_CHARS)
+                }),
+        )?;
 
-        // Get the event
-        match service.get_event(&calendar_id, &event_id).await {
-            Ok(event) => {
-                // Convert to JSON
-                serde_json::to_string(&event).map_err(|e| {
-                    let error_msg = format!("Failed to serialize event: {}", e);
-                    error!("{}", error_msg);
-                    self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
-                })
-            }
-            Err(err) => {
-                error!(
-                    "Failed to get event {} from calendar {}: {}",
-                    event_id, calendar_id, err
-                );
-                Err(self.to_mcp_error(
-                    &format!(
-                        "Failed to get event {} from calendar {}: {}",
-                        event_id, calendar_id, err
-                    ),
-                    error_codes::API_ERROR,
-                ))
-            }
+        let calendar = self.init_calendar_service().await?;
+        let now = chrono::Utc::now();
+        let time_zone = self.calendar_defaults(&calendar).await.time_zone;
+        let remind_at = crate::reminders::parse_remind_at(&when, now)
+            .or_else(|e| crate::utils::parse_date_expression(&when, now, time_zone).map_err(|_| e))
+            .map_err(|e| {
+                error!("{}", e);
+                self.to_mcp_error(&e, error_codes::API_ERROR)
+            })?;
+        if remind_at <= now {
+            let error_msg = format!("Reminder time {} is in the past", remind_at.to_rfc3339());
+            error!("{}", error_msg);
+            return Err(self.to_mcp_error(&error_msg, error_codes::API_ERROR));
         }
+
+        let request = crate::reminders::ReminderRequest {
+            message_id,
+            remind_at,
+            note,
+            label,
+            archive: archive.unwrap_or(false),
+            calendar_id: calendar_id.unwrap_or_else(|| "primary".to_string()),
+        };
+
+        let gmail = self.init_gmail_service().await?;
+
+        let reminder = crate::reminders::create_reminder(&gmail, &calendar, request)
+            .await
+            .map_err(|err| {
+                error!("Failed to create reminder: {}", err);
+                self.map_error(err)
+            })?;
+
+        let result_json = serde_json::to_string_pretty(&reminder).map_err(|e| {
+            let error_msg = format!("Failed to serialize reminder: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END remind_me MCP command (success) ===");
+        Ok(result_json)
     }
 
-    /// Create a new calendar event
-    ///
-    /// This command creates a new event in the specified calendar.
+    /// Get the next email to triage, for working through the inbox to zero
     ///
-    /// # Arguments
+    /// Returns the oldest unread email in the inbox, or the oldest read one once
+    /// none is unread, with suggested actions, most likely first, and how many
+    /// inbox emails are left. Deal with it using triage_apply, then call again for
+    /// the next. Returns {"done": true} once the inbox has nothing left.
     ///
-    /// * `calendar_id` - The ID of the calendar (optional, defaults to primary)
-    /// * `summary` - The title of the event
-    /// * `description` - Optional description of the event
-    /// * `location` - Optional location of the event
-    /// * `start_time` - Start time in RFC3339 format
-    /// * `end_time` - End time in RFC3339 format
-    /// * `attendees` - Optional list of attendee emails
+    /// Args:
+    ///   filter: Optional Gmail search query limiting which inbox emails to triage,
+    ///           e.g. "from:example.com" or "category:primary"
+    ///   mailbox: Optional address of a shared or delegated mailbox to use instead of your own
+    #[tool]
+    async fn triage_next(
+        &self,
+        filter: Option<String>,
+        mailbox: Option<String>,
+    ) -> McpResult<String> {
+        info!("=== START triage_next MCP command ===");
+        debug!("triage_next called with filter={:?}", filter);
+
+        self.validate(Validator::new().optional(filter.as_deref(), |v, filter| {
+            v.max_chars("filter", filter, MAX_QUERY_CHARS)
+        }))?;
+
+        let service = self.init_mailbox_service(mailbox).await?;
+        let item = crate::triage::next_item(&service, filter.as_deref())
+            .await
+            .map_err(|err| {
+                error!("Failed to find the next email to triage: {}", err);
+                self.map_error(err)
+            })?;
+
+        let result = match item {
+            Some(item) => serde_json::to_value(&item),
+            None => Ok(json!({ "done": true, "remaining": 0 })),
+        };
+        let result_json = result
+            .and_then(|result| serde_json::to_string_pretty(&result))
+            .map_err(|e| {
+                let error_msg = format!("Failed to serialize triage item: {}", e);
+                error!("{}", error_msg);
+                self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+            })?;
+
+        info!("=== END triage_next MCP command (success) ===");
+        Ok(result_json)
+    }
+
+    /// Apply a triage action to an email
     ///
-    /// # Returns
+    /// Actions:
+    ///   "archive": mark the email read and remove it from the inbox
+    ///   "label": file it under `label`, creating the label if needed, and archive it
+    ///   "reply_draft": save `body` as a draft reply in its thread and mark it read;
+    ///                  it stays in the inbox until dealt with
+    ///   "snooze": archive it with the "Snoozed" label until `until`, when a calendar
+    ///             reminder brings it back to mind
     ///
-    /// A JSON string containing the created event details
+    /// Args:
+    ///   message_id: The ID of the email
+    ///   action: One of "archive", "label", "reply_draft" or "snooze"
+    ///   label: Label to file the email under, for "label"
+    ///   body: Plain text body of the reply, for "reply_draft"
+    ///   until: When to be reminded, for "snooze": RFC3339 (e.g. "2024-03-01T09:00:00Z"),
+    ///          relative to now (e.g. "2h", "3d") or in words (e.g. "tomorrow 9am")
     #[tool]
-    #[allow(clippy::too_many_arguments)]
-    async fn create_event(
+    async fn triage_apply(
         &self,
-        // Calendar identification
-        calendar_id: Option<String>,
-        // Event core details
-        summary: String,
-        start_time: String,
-        end_time: String,
-        // Optional event details
-        description: Option<String>,
-        location: Option<String>,
-        // Participants
-        attendees: Option<Vec<String>>,
+        message_id: String,
+        action: String,
+        label: Option<String>,
+        body: Option<String>,
+        until: Option<String>,
     ) -> McpResult<String> {
-        info!("=== START create_event MCP command ===");
+        info!("=== START triage_apply MCP command ===");
         debug!(
-            "create_event called with calendar_id={:?}, summary={}, description={:?}, location={:?}, start_time={}, end_time={}, attendees={:?}",
-            calendar_id, summary, description, location, start_time, end_time, attendees
+            "triage_apply called with message_id={}, action={}, label={:?}, until={:?}",
+            message_id, action, label, until
         );
 
-        // Use primary calendar if not specified
-        let calendar_id = calendar_id.unwrap_or_else(|| "primary".to_string());
+        self.validate(
+            Validator::new()
+                .required("message_id", &message_id)
+                .required("action", &action)
+                .optional(body.as_deref(), |v, body| {
+                    v.max_bytes("body", body, MAX_BODY_BYTES)
+                }),
+        )?;
 
-        // Parse start and end times
-        let start_dt = match chrono::DateTime::parse_from_rfc3339(&start_time) {
-            Ok(dt) => dt.with_timezone(&chrono::Utc),
-            Err(e) => {
-                let error_msg = format!("Invalid start_time format (expected RFC3339): {}", e);
-                error!("{}", error_msg);
-                return Err(self.to_mcp_error(&error_msg, error_codes::API_ERROR));
-            }
+        use crate::triage::{TriageAction, TriageCommand};
+        let missing = |field: &str| {
+            let error_msg = format!("'{}' is required for the {} action", field, action);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::API_ERROR)
         };
+        let present = |value: Option<String>| value.filter(|v| !v.trim().is_empty());
 
-        let end_dt = match chrono::DateTime::parse_from_rfc3339(&end_time) {
-            Ok(dt) => dt.with_timezone(&chrono::Utc),
-            Err(e) => {
-                let error_msg = format!("Invalid end_time format (expected RFC3339): {}", e);
+        let mut calendar = None;
+        let command = match TriageAction::parse(&action) {
+            Some(TriageAction::Archive) => TriageCommand::Archive,
+            Some(TriageAction::Label) => {
+                TriageCommand::Label(present(label).ok_or_else(|| missing("label"))?)
+            }
+            Some(TriageAction::ReplyDraft) => {
+                TriageCommand::ReplyDraft(present(body).ok_or_else(|| missing("body"))?)
+            }
+            Some(TriageAction::Snooze) => {
+                let until = present(until).ok_or_else(|| missing("until"))?;
+                let service = self.init_calendar_service().await?;
+                let now = chrono::Utc::now();
+                let time_zone = self.calendar_defaults(&service).await.time_zone;
+                let until = crate::reminders::parse_remind_at(&until, now)
+                    .or_else(|e| {
+                        crate::utils::parse_date_expression(&until, now, time_zone).map_err(|_| e)
+                    })
+                    .map_err(|e| {
+                        error!("{}", e);
+                        self.to_mcp_error(&e, error_codes::API_ERROR)
+                    })?;
+                if until <= now {
+                    let error_msg = format!("Snooze time {} is in the past", until.to_rfc3339());
+                    error!("{}", error_msg);
+                    return Err(self.to_mcp_error(&error_msg, error_codes::API_ERROR));
+                }
+                calendar = Some(service);
+                TriageCommand::Snooze(until)
+            }
+            None => {
+                let error_msg = format!(
+                    "Unknown triage action '{}': expected archive, label, reply_draft or snooze",
+                    action
+                );
                 error!("{}", error_msg);
                 return Err(self.to_mcp_error(&error_msg, error_codes::API_ERROR));
             }
         };
 
-        // Create attendee objects from email strings
-        let attendee_objs = attendees
-            .unwrap_or_default()
-            .into_iter()
-            .map(|email| crate::calendar_api::Attendee {
-                email,
-                display_name: None,
-                response_status: Some("needsAction".to_string()),
-                optional: None,
-            })
-            .collect();
+        let gmail = self.init_gmail_service().await?;
+        let outcome = crate::triage::apply(&gmail, calendar.as_ref(), &message_id, command)
+            .await
+            .map_err(|err| {
+                error!("Failed to apply triage action to {}: {}", message_id, err);
+                self.map_error(err)
+            })?;
 
-        // Create the event
-        let event = crate::calendar_api::CalendarEvent {
-            id: None,
-            summary,
-            description,
-            location,
-            start_time: start_dt,
-            end_time: end_dt,
-            attendees: attendee_objs,
-            conference_data: None,
-            html_link: None,
-            creator: None,
-            organizer: None,
-        };
+        let result_json = serde_json::to_string_pretty(&outcome).map_err(|e| {
+            let error_msg = format!("Failed to serialize triage outcome: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
 
-        // Initialize the calendar service
-        let service = self.init_calendar_service().await?;
+        info!("=== END triage_apply MCP command (success) ===");
+        Ok(result_json)
+    }
 
-        // Create the event
-        match service.create_event(&calendar_id, event).await {
-            Ok(created_event) => {
-                // Convert to JSON
-                serde_json::to_string(&created_event).map_err(|e| {
-                    let error_msg = format!("Failed to serialize created event: {}", e);
+    /// Run the mail rules
+    ///
+    /// Applies the rules in the mail rule file to the emails matching a search, or,
+    /// without one, to the mail that arrived since the rules last ran. Each email is
+    /// given the labels, archiving and forwarding of every rule it matches. Emails
+    /// that cannot be changed are reported and the others still handled.
+    ///
+    /// Returns an object with "source" ("query" or "new_mail"), "checked", the
+    /// matching emails with their rules and "status" in "matched", and the emails
+    /// matching rules with notify set in "notifications".
+    ///
+    /// Args:
+    ///   query: Optional Gmail search query for the emails to run the rules on. Without
+    ///          one, the mail received since the last run is used; on the first run, and
+    ///          once Gmail's history has expired, the last day's inbox mail.
+    ///   max_results: Optional most emails to check for a query (default: 50, at most 500).
+    ///                Can be a number (3) or a string ("3").
+    ///   dry_run: Optional. Report what the rules would do without doing it, and without
+    ///            moving on from the mail already seen (default: false)
+    #[tool]
+    async fn run_rules(
+        &self,
+        query: Option<String>,
+        max_results: Option<serde_json::Value>,
+        dry_run: Option<bool>,
+    ) -> McpResult<String> {
+        info!("=== START run_rules MCP command ===");
+        debug!(
+            "run_rules called with query={:?}, max_results={:?}, dry_run={:?}",
+            query, max_results, dry_run
+        );
+
+        self.validate(Validator::new().optional(query.as_deref(), |v, query| {
+            v.max_chars("query", query, MAX_QUERY_CHARS)
+        }))?;
+
+        use crate::mail_rules::{MailRuleStore, RuleHistory, MAX_RULE_MESSAGES};
+        let dry_run = dry_run.unwrap_or(false);
+        let query = query.filter(|q| !q.trim().is_empty());
+
+        let store = MailRuleStore::from_env();
+        let rules = store.list().map_err(|err| {
+            error!("Failed to read mail rules: {}", err);
+            self.map_error(err)
+        })?;
+        if rules.is_empty() {
+            let error_msg = format!(
+                "No mail rules found; add them to {}",
+                store.path().display()
+            );
+            error!("{}", error_msg);
+            return Err(self.to_mcp_error(&error_msg, error_codes::CONFIG_ERROR));
+        }
+
+        let service = self.init_gmail_service().await?;
+        let labels = service
+            .list_labels()
+            .await
+            .map_err(|err| {
+                error!("Failed to list labels: {}", err);
+                self.map_error(err)
+            })
+            .and_then(|json| {
+                crate::label_tree::parse_labels(&json).map_err(|e| {
+                    let error_msg = format!("Failed to parse labels: {}", e);
                     error!("{}", error_msg);
                     self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
                 })
+            })?;
+
+        // The messages to check, and for new mail the history ID to read from
+        // next time
+        let history = RuleHistory::from_env();
+        let mut next_history_id = None;
+        let ids = match &query {
+            Some(query) => {
+                let max = helpers::parse_max_results(max_results, 50).min(MAX_RULE_MESSAGES);
+                service.list_message_ids(max, Some(query)).await
             }
-            Err(err) => {
-                error!(
-                    "Failed to create event in calendar {}: {}",
-                    calendar_id, err
-                );
-                Err(self.to_mcp_error(
-                    &format!(
-                        "Failed to create event in calendar {}: {}",
-                        calendar_id, err
-                    ),
-                    error_codes::API_ERROR,
-                ))
+            None => {
+                let changes = match history.history_id() {
+                    Some(history_id) => match service.list_history(&history_id).await {
+                        Ok(changes) => Some(changes),
+                        Err(crate::errors::GmailApiError::MessageRetrievalError(e)) => {
+                            info!("Mail rule history expired, checking recent mail: {}", e);
+                            None
+                        }
+                        Err(err) => {
+                            error!("Failed to read new mail: {}", err);
+                            return Err(self.map_error(err));
+                        }
+                    },
+                    None => None,
+                };
+                match changes {
+                    Some(mut changes) => {
+                        next_history_id = Some(changes.history_id);
+                        changes.added.truncate(MAX_RULE_MESSAGES as usize);
+                        Ok(changes.added)
+                    }
+                    // Take the history ID first so that mail arriving during
+                    // the run is seen by the next one
+                    None => match service.get_history_id().await {
+                        Ok(history_id) => {
+                            next_history_id = Some(history_id);
+                            service
+                                .list_message_ids(
+                                    MAX_RULE_MESSAGES,
+                                    Some(crate::mail_rules::NEW_MAIL_FALLBACK_QUERY),
+                                )
+                                .await
+                        }
+                        Err(err) => Err(err),
+                    },
+                }
+            }
+        }
+        .map_err(|err| {
+            error!("Failed to list mail for the rules: {}", err);
+            self.map_error(err)
+        })?;
+
+        let mut checked = 0;
+        let mut matched = Vec::new();
+        for id in &ids {
+            let message = match service.get_message_metadata(id, &[]).await {
+                Ok(message) => message,
+                // Deleted since it arrived
+                Err(err) => {
+                    log::warn!("Skipping message {} for the mail rules: {}", id, err);
+                    continue;
+                }
+            };
+            if !crate::mail_rules::is_incoming(&message) {
+                continue;
+            }
+            checked += 1;
+            let Some(mut rule_match) = crate::mail_rules::match_rules(&rules, &message, &labels)
+            else {
+                continue;
+            };
+            if !dry_run {
+                match crate::mail_rules::apply_match(&service, &rule_match).await {
+                    Ok(()) => rule_match.status = "applied".to_string(),
+                    Err(err) => {
+                        error!("Failed to apply mail rules to {}: {}", id, err);
+                        rule_match.status = "failed".to_string();
+                        rule_match.error = Some(err.to_string());
+                    }
+                }
+            }
+            matched.push(rule_match);
+        }
+
+        if !dry_run {
+            if let Some(history_id) = &next_history_id {
+                if let Err(e) = history.save(history_id) {
+                    log::warn!("Failed to save the mail rule history: {}", e);
+                }
             }
         }
+
+        let notifications: Vec<_> = matched
+            .iter()
+            .filter(|rule_match| rule_match.notify)
+            .map(|rule_match| {
+                json!({
+                    "message_id": rule_match.message_id,
+                    "from": rule_match.from,
+                    "subject": rule_match.subject,
+                    "rules": rule_match.rules,
+                })
+            })
+            .collect();
+        let result = json!({
+            "source": if query.is_some() { "query" } else { "new_mail" },
+            "query": query,
+            "dry_run": dry_run,
+            "checked": checked,
+            "matched": matched,
+            "notifications": notifications,
+        });
+
+        let result_json = serde_json::to_string_pretty(&result).map_err(|e| {
+            let error_msg = format!("Failed to serialize rule results: {}", e);
+            error!("{}", error_msg);
+            self.to_mcp_error(&error_msg, error_codes::MESSAGE_FORMAT_ERROR)
+        })?;
+
+        info!("=== END run_rules MCP command (success) ===");
+        Ok(result_json)
     }
 }
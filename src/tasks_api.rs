@@ -0,0 +1,223 @@
+use crate::auth::TokenManager;
+use crate::config::{get_tasks_api_base_url, Config};
+use crate::errors::{RateLimitInfo, TasksApiError, TasksResult};
+use crate::etag_cache::{CachedResponse, EtagCache};
+use crate::utils::api_client_builder;
+use chrono::{DateTime, NaiveDate};
+use log::debug;
+use reqwest::{Client, RequestBuilder};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+// Alias for backward compatibility within this module
+type Result<T> = TasksResult<T>;
+
+/// ID of the user's default task list
+pub const DEFAULT_TASK_LIST: &str = "@default";
+
+// Task list representation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskList {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub updated: Option<String>,
+}
+
+// Task representation. Google sends camelCase names, which are accepted
+// as aliases so that tasks are returned with the same field names as the
+// other API types.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Task {
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub notes: Option<String>,
+    /// `needsAction` or `completed`
+    #[serde(default)]
+    pub status: Option<String>,
+    /// Due date as RFC3339; Google only keeps the date part
+    #[serde(default)]
+    pub due: Option<String>,
+    #[serde(default)]
+    pub completed: Option<String>,
+    #[serde(default)]
+    pub parent: Option<String>,
+    #[serde(default, alias = "webViewLink")]
+    pub web_view_link: Option<String>,
+}
+
+/// Parse a due date given as `YYYY-MM-DD` or RFC3339 into the form the
+/// Tasks API expects. Only the date is kept, since Google discards the time.
+pub fn parse_due_date(due: &str) -> Result<String> {
+    let due = due.trim();
+    let date = NaiveDate::parse_from_str(due, "%Y-%m-%d")
+        .ok()
+        .or_else(|| {
+            DateTime::parse_from_rfc3339(due)
+                .ok()
+                .map(|d| d.date_naive())
+        })
+        .ok_or_else(|| {
+            TasksApiError::InvalidInput(format!(
+                "Invalid due date '{}': expected YYYY-MM-DD or RFC3339",
+                due
+            ))
+        })?;
+    Ok(format!("{}T00:00:00.000Z", date.format("%Y-%m-%d")))
+}
+
+// Tasks API client
+#[derive(Debug, Clone)]
+pub struct TasksClient {
+    client: Client,
+    token_manager: Arc<Mutex<TokenManager>>,
+}
+
+impl TasksClient {
+    pub fn new(config: &Config) -> Self {
+        let client = api_client_builder()
+            .build()
+            .unwrap_or_else(|_| Client::new());
+        // Reuse the Gmail token manager since they share the same OAuth flow
+        let token_manager = Arc::new(Mutex::new(TokenManager::new(config)));
+
+        Self {
+            client,
+            token_manager,
+        }
+    }
+
+    // Send a request with the access token, turning error statuses into errors
+    async fn send(&self, request: RequestBuilder, action: &str) -> Result<CachedResponse> {
+        let token = self
+            .token_manager
+            .lock()
+            .await
+            .get_token(&self.client)
+            .await
+            .map_err(|e| TasksApiError::AuthError(e.to_string()))?;
+
+        let response = EtagCache::global()
+            .send(request.header("Authorization", format!("Bearer {}", token)))
+            .await
+            .map_err(|e| TasksApiError::NetworkError(e.to_string()))?;
+
+        if !response.status.is_success() {
+            if let Some(info) = RateLimitInfo::from_response(
+                response.status.as_u16(),
+                response.retry_after.as_deref(),
+                &response.body,
+            ) {
+                return Err(TasksApiError::RateLimited(info));
+            }
+            return Err(TasksApiError::ApiError(format!(
+                "Failed to {}. Status: {}, Error: {}",
+                action, response.status, response.body
+            )));
+        }
+
+        Ok(response)
+    }
+
+    // Get the user's task lists
+    pub async fn list_task_lists(&self, max_results: Option<u32>) -> Result<Vec<TaskList>> {
+        let mut url = format!("{}/users/@me/lists", get_tasks_api_base_url());
+        if let Some(max) = max_results {
+            url = format!("{}?maxResults={}", url, max);
+        }
+        debug!("Listing task lists from: {}", url);
+
+        let response = self.send(self.client.get(&url), "list task lists").await?;
+        parse_items(&response.body)
+    }
+
+    // Get the tasks in a list
+    pub async fn list_tasks(
+        &self,
+        task_list_id: &str,
+        show_completed: bool,
+        max_results: Option<u32>,
+    ) -> Result<Vec<Task>> {
+        let mut url = format!(
+            "{}/lists/{}/tasks?showCompleted={}",
+            get_tasks_api_base_url(),
+            task_list_id,
+            show_completed
+        );
+        if let Some(max) = max_results {
+            url = format!("{}&maxResults={}", url, max);
+        }
+        debug!("Listing tasks from: {}", url);
+
+        let response = self.send(self.client.get(&url), "list tasks").await?;
+        parse_items(&response.body)
+    }
+
+    // Create a task, returning it as stored by Google
+    pub async fn create_task(&self, task_list_id: &str, task: &Task) -> Result<Task> {
+        if task.title.trim().is_empty() {
+            return Err(TasksApiError::InvalidInput(
+                "Task title cannot be empty".to_string(),
+            ));
+        }
+
+        let url = format!("{}/lists/{}/tasks", get_tasks_api_base_url(), task_list_id);
+        debug!("Creating task in list {}", task_list_id);
+
+        let mut body = json!({ "title": task.title });
+        if let Some(notes) = &task.notes {
+            body["notes"] = json!(notes);
+        }
+        if let Some(due) = &task.due {
+            body["due"] = json!(due);
+        }
+
+        let response = self
+            .send(self.client.post(&url).json(&body), "create task")
+            .await?;
+        parse_task(&response.body)
+    }
+
+    // Mark a task as completed
+    pub async fn complete_task(&self, task_list_id: &str, task_id: &str) -> Result<Task> {
+        let url = format!(
+            "{}/lists/{}/tasks/{}",
+            get_tasks_api_base_url(),
+            task_list_id,
+            task_id
+        );
+        debug!("Completing task {} in list {}", task_id, task_list_id);
+
+        let response = self
+            .send(
+                self.client
+                    .patch(&url)
+                    .json(&json!({ "status": "completed" })),
+                "complete task",
+            )
+            .await?;
+        parse_task(&response.body)
+    }
+}
+
+#[derive(Deserialize)]
+struct ItemsResponse<T> {
+    #[serde(default = "Vec::new")]
+    items: Vec<T>,
+}
+
+fn parse_items<T: DeserializeOwned>(body: &str) -> Result<Vec<T>> {
+    serde_json::from_str::<ItemsResponse<T>>(body)
+        .map(|response| response.items)
+        .map_err(|e| TasksApiError::ParseError(e.to_string()))
+}
+
+fn parse_task(body: &str) -> Result<Task> {
+    serde_json::from_str(body).map_err(|e| TasksApiError::ParseError(e.to_string()))
+}
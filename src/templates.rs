@@ -0,0 +1,300 @@
+use crate::errors::{TemplateError, TemplateResult};
+use crate::gmail_api::DraftEmail;
+use crate::persist;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+// Email templates
+//
+// Templates for recurring emails, such as status updates or introductions,
+// are kept in a JSON file in the user's config directory. Their subject,
+// body and recipients may contain `{{name}}` placeholders, which are filled
+// from variables when a draft is created from the template.
+
+/// A reusable email with `{{name}}` placeholders
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmailTemplate {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub subject: String,
+    pub body: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cc: Option<String>,
+}
+
+/// A template as listed, with the variables it needs
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplateSummary {
+    pub name: String,
+    pub description: Option<String>,
+    pub subject: String,
+    pub variables: Vec<String>,
+}
+
+/// The parts of an email after filling a template's placeholders
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderedTemplate {
+    pub subject: String,
+    pub body: String,
+    pub to: Option<String>,
+    pub cc: Option<String>,
+}
+
+impl RenderedTemplate {
+    /// Build a draft, preferring explicit recipients over the template's
+    pub fn into_draft(self, to: Option<String>, cc: Option<String>) -> TemplateResult<DraftEmail> {
+        let to = to.or(self.to).unwrap_or_default();
+        if to.trim().is_empty() {
+            return Err(TemplateError::InvalidTemplate(
+                "A recipient is required: pass `to` or save the template with one".to_string(),
+            ));
+        }
+
+        Ok(DraftEmail {
+            to,
+            subject: self.subject,
+            body: self.body,
+            cc: cc.or(self.cc),
+            bcc: None,
+            thread_id: None,
+            in_reply_to: None,
+            references: None,
+            priority: None,
+            read_receipt_to: None,
+        })
+    }
+}
+
+// A piece of template text: literal text or a placeholder name
+enum Segment<'a> {
+    Text(&'a str),
+    Placeholder(&'a str),
+}
+
+fn is_variable_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+}
+
+// Split text into literal text and `{{name}}` placeholders. Braces that do
+// not form a placeholder are kept as text.
+fn segments(text: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let name = rest[start + 2..start + 2 + len].trim();
+        if is_variable_name(name) {
+            segments.push(Segment::Text(&rest[..start]));
+            segments.push(Segment::Placeholder(name));
+        } else {
+            segments.push(Segment::Text(&rest[..start + 2 + len + 2]));
+        }
+        rest = &rest[start + 2 + len + 2..];
+    }
+    segments.push(Segment::Text(rest));
+    segments
+}
+
+/// Fill the `{{name}}` placeholders of text from variables, adding the names
+/// of any without a value to `missing`
+pub fn fill_placeholders(
+    text: &str,
+    variables: &HashMap<String, String>,
+    missing: &mut BTreeSet<String>,
+) -> String {
+    let mut filled = String::with_capacity(text.len());
+    for segment in segments(text) {
+        match segment {
+            Segment::Text(text) => filled.push_str(text),
+            Segment::Placeholder(name) => match variables.get(name) {
+                Some(value) => filled.push_str(value),
+                None => {
+                    missing.insert(name.to_string());
+                }
+            },
+        }
+    }
+    filled
+}
+
+/// Names of the placeholders used in any of the texts, sorted
+pub fn placeholder_names<'a>(texts: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let names: BTreeSet<String> = texts
+        .flat_map(segments)
+        .filter_map(|segment| match segment {
+            Segment::Placeholder(name) => Some(name.to_string()),
+            Segment::Text(_) => None,
+        })
+        .collect();
+    names.into_iter().collect()
+}
+
+impl EmailTemplate {
+    fn fields(&self) -> impl Iterator<Item = &str> {
+        [
+            Some(&self.subject),
+            Some(&self.body),
+            self.to.as_ref(),
+            self.cc.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        .map(String::as_str)
+    }
+
+    /// Names of the placeholders used in the template, sorted
+    pub fn variables(&self) -> Vec<String> {
+        placeholder_names(self.fields())
+    }
+
+    /// Fill every placeholder, failing with the names of any missing variables
+    pub fn render(&self, variables: &HashMap<String, String>) -> TemplateResult<RenderedTemplate> {
+        let mut missing = BTreeSet::new();
+        let rendered = RenderedTemplate {
+            subject: fill_placeholders(&self.subject, variables, &mut missing),
+            body: fill_placeholders(&self.body, variables, &mut missing),
+            to: self
+                .to
+                .as_deref()
+                .map(|to| fill_placeholders(to, variables, &mut missing)),
+            cc: self
+                .cc
+                .as_deref()
+                .map(|cc| fill_placeholders(cc, variables, &mut missing)),
+        };
+
+        if missing.is_empty() {
+            Ok(rendered)
+        } else {
+            Err(TemplateError::MissingVariables(
+                missing.into_iter().collect(),
+            ))
+        }
+    }
+
+    pub fn summary(&self) -> TemplateSummary {
+        TemplateSummary {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            subject: self.subject.clone(),
+            variables: self.variables(),
+        }
+    }
+
+    fn validate(&self) -> TemplateResult<()> {
+        if self.name.trim().is_empty() {
+            return Err(TemplateError::InvalidTemplate(
+                "Template name cannot be empty".to_string(),
+            ));
+        }
+        if self.subject.trim().is_empty() && self.body.trim().is_empty() {
+            return Err(TemplateError::InvalidTemplate(format!(
+                "Template '{}' needs a subject or body",
+                self.name
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct TemplateFile {
+    templates: Vec<EmailTemplate>,
+}
+
+/// Templates stored in a JSON file
+#[derive(Debug, Clone)]
+pub struct TemplateStore {
+    path: PathBuf,
+}
+
+impl TemplateStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// The store at `EMAIL_TEMPLATES_FILE`, or the default location
+    pub fn from_env() -> Self {
+        Self::new(get_templates_path())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// All templates, sorted by name
+    pub fn list(&self) -> TemplateResult<Vec<EmailTemplate>> {
+        let mut templates = self.load()?.templates;
+        templates.sort_by_key(|template| template.name.to_lowercase());
+        Ok(templates)
+    }
+
+    /// Find a template by name, ignoring case
+    pub fn get(&self, name: &str) -> TemplateResult<EmailTemplate> {
+        self.load()?
+            .templates
+            .into_iter()
+            .find(|template| template.name.eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| TemplateError::NotFound(name.to_string()))
+    }
+
+    /// Save a template, replacing any with the same name. Returns whether a
+    /// template was replaced.
+    pub fn save(&self, mut template: EmailTemplate) -> TemplateResult<bool> {
+        template.name = template.name.trim().to_string();
+        template.validate()?;
+
+        let mut file = self.load()?;
+        let before = file.templates.len();
+        file.templates
+            .retain(|existing| !existing.name.eq_ignore_ascii_case(&template.name));
+        let replaced = file.templates.len() < before;
+        info!(
+            "{} template '{}'",
+            if replaced { "Replacing" } else { "Saving" },
+            template.name
+        );
+        file.templates.push(template);
+        self.store(&file)?;
+        Ok(replaced)
+    }
+
+    fn load(&self) -> TemplateResult<TemplateFile> {
+        persist::load_json(&self.path).map_err(|e| self.storage_error(e))
+    }
+
+    fn store(&self, file: &TemplateFile) -> TemplateResult<()> {
+        persist::store_json(&self.path, file).map_err(|e| self.storage_error(e))
+    }
+
+    fn storage_error(&self, err: impl std::fmt::Display) -> TemplateError {
+        TemplateError::StorageError(format!("{}: {}", self.path.display(), err))
+    }
+}
+
+// Get default template file location (platform-specific)
+fn default_templates_path() -> PathBuf {
+    let mut path = dirs::config_dir().unwrap_or_else(std::env::temp_dir);
+    path.push("gmail-mcp-rs");
+    path.push("templates.json");
+    path
+}
+
+/// Returns the path of the template file.
+///
+/// Environment variable: EMAIL_TEMPLATES_FILE
+pub fn get_templates_path() -> PathBuf {
+    std::env::var("EMAIL_TEMPLATES_FILE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| default_templates_path())
+}
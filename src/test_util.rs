@@ -0,0 +1,968 @@
+use crate::api::{CalendarApi, DriveApi, GmailApi, PeopleApi, TasksApi};
+use crate::calendar_api::{CalendarEvent, CalendarInfo, CalendarList};
+use crate::deadline::Deadline;
+use crate::drive_api::{DriveFile, DrivePermission, StorageQuota};
+use crate::errors::{
+    CalendarApiError, CalendarResult, DriveApiError, DriveResult, GmailApiError, GmailResult,
+    PeopleApiError, PeopleResult, TasksApiError, TasksResult,
+};
+use crate::gmail_api::{DraftEmail, EmailMessage, HistoryChanges, MessageCount, MessageMetadata};
+use crate::people_api::{Contact, ContactList};
+use crate::tasks_api::{Task, TaskList, DEFAULT_TASK_LIST};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+// In-memory implementations of the API traits for tests
+//
+// The mocks hold their data in public fields or builder methods and mirror the
+// validation the real Google APIs perform, so that code under test sees the
+// same error variants it would in production. Every mock can be made to fail
+// with a specific error through `failing_with`, which applies to all calls.
+
+type ErrorFactory<E> = Box<dyn Fn() -> E + Send + Sync>;
+
+fn matches_query(query: &str, fields: &[Option<&str>]) -> bool {
+    fields
+        .iter()
+        .flatten()
+        .any(|field| field.to_lowercase().contains(query))
+}
+
+/// A message for tests, from `from` about `subject`, in thread
+/// `thread-<id>`, with every other field empty. Other fields are set with
+/// struct update syntax: `EmailMessage { body_text: .., ..message(..) }`.
+pub fn message(id: &str, from: &str, subject: &str) -> EmailMessage {
+    EmailMessage {
+        id: id.to_string(),
+        thread_id: format!("thread-{}", id),
+        subject: Some(subject.to_string()),
+        from: Some(from.to_string()),
+        ..Default::default()
+    }
+}
+
+/// In-memory Gmail mailbox
+pub struct MockGmailApi {
+    /// Messages returned by list and get calls, newest first
+    pub messages: Vec<EmailMessage>,
+    labels: Mutex<Vec<(String, String)>>,
+    message_labels: Mutex<HashMap<String, Vec<String>>>,
+    /// Account address reported by `check_connection`
+    pub email_address: String,
+    /// Message count reported by `check_connection`
+    pub messages_total: u64,
+    drafts: Mutex<Vec<DraftEmail>>,
+    sent: Mutex<Vec<DraftEmail>>,
+    /// Current mailbox history ID, advanced by `add_message` and
+    /// `delete_message`
+    pub history_id: u64,
+    history: Vec<(u64, HistoryEvent)>,
+    history_floor: u64,
+    deadline: Deadline,
+    failure: Option<ErrorFactory<GmailApiError>>,
+}
+
+enum HistoryEvent {
+    Added(String),
+    Deleted(String),
+}
+
+impl Default for MockGmailApi {
+    fn default() -> Self {
+        Self {
+            messages: Vec::new(),
+            labels: Mutex::new(Vec::new()),
+            message_labels: Mutex::new(HashMap::new()),
+            email_address: "test@example.com".to_string(),
+            messages_total: 0,
+            drafts: Mutex::new(Vec::new()),
+            sent: Mutex::new(Vec::new()),
+            history_id: 1,
+            history: Vec::new(),
+            history_floor: 0,
+            deadline: Deadline::never(),
+            failure: None,
+        }
+    }
+}
+
+impl MockGmailApi {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_messages(mut self, messages: Vec<EmailMessage>) -> Self {
+        self.messages = messages;
+        self
+    }
+
+    pub fn with_labels<I, S>(mut self, labels: I) -> Self
+    where
+        I: IntoIterator<Item = (S, S)>,
+        S: Into<String>,
+    {
+        self.labels = Mutex::new(
+            labels
+                .into_iter()
+                .map(|(id, name)| (id.into(), name.into()))
+                .collect(),
+        );
+        self
+    }
+
+    pub fn with_profile(mut self, email_address: &str, messages_total: u64) -> Self {
+        self.email_address = email_address.to_string();
+        self.messages_total = messages_total;
+        self
+    }
+
+    /// Add a new message to the top of the mailbox, recording it in the
+    /// history
+    pub fn add_message(&mut self, message: EmailMessage) {
+        self.history_id += 1;
+        self.history
+            .push((self.history_id, HistoryEvent::Added(message.id.clone())));
+        self.messages.insert(0, message);
+    }
+
+    /// Delete a message, recording it in the history
+    pub fn delete_message(&mut self, message_id: &str) {
+        self.history_id += 1;
+        self.history
+            .push((self.history_id, HistoryEvent::Deleted(message_id.to_string())));
+        self.messages.retain(|message| message.id != message_id);
+    }
+
+    /// Discard recorded history, so that `list_history` fails for any earlier
+    /// start ID as it does when Gmail's history has expired
+    pub fn expire_history(&mut self) {
+        self.history.clear();
+        self.history_floor = self.history_id;
+    }
+
+    /// Set the label IDs on a message
+    pub fn set_message_labels(&mut self, message_id: &str, label_ids: Vec<String>) {
+        self.message_labels
+            .get_mut()
+            .unwrap()
+            .insert(message_id.to_string(), label_ids);
+    }
+
+    /// Labels as `(id, name)` pairs, including created ones
+    pub fn labels(&self) -> Vec<(String, String)> {
+        self.labels.lock().unwrap().clone()
+    }
+
+    /// Label IDs on each message, by message ID, as set by `modify_labels`
+    /// and the thread operations
+    pub fn message_labels(&self) -> HashMap<String, Vec<String>> {
+        self.message_labels.lock().unwrap().clone()
+    }
+
+    /// Drafts created through `create_draft`
+    pub fn drafts(&self) -> Vec<DraftEmail> {
+        self.drafts.lock().unwrap().clone()
+    }
+
+    /// Emails sent through `send_message`
+    pub fn sent(&self) -> Vec<DraftEmail> {
+        self.sent.lock().unwrap().clone()
+    }
+
+    /// Make every call fail with the error produced by `failure`
+    pub fn failing_with<F>(mut self, failure: F) -> Self
+    where
+        F: Fn() -> GmailApiError + Send + Sync + 'static,
+    {
+        self.failure = Some(Box::new(failure));
+        self
+    }
+
+    /// Stop listing messages once `deadline` has passed, like a client with
+    /// a deadline attached
+    pub fn with_deadline(mut self, deadline: Deadline) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    fn check_failure(&self) -> GmailResult<()> {
+        match &self.failure {
+            Some(failure) => Err(failure()),
+            None => Ok(()),
+        }
+    }
+
+    fn apply_labels(&self, message_id: &str, add_label_ids: &[String], remove_label_ids: &[String]) {
+        let mut message_labels = self.message_labels.lock().unwrap();
+        let labels = message_labels.entry(message_id.to_string()).or_default();
+        labels.retain(|id| !remove_label_ids.contains(id));
+        for id in add_label_ids {
+            if !labels.contains(id) {
+                labels.push(id.clone());
+            }
+        }
+    }
+
+    fn thread_message_ids(&self, thread_id: &str) -> GmailResult<Vec<String>> {
+        let message_ids: Vec<String> = self
+            .messages
+            .iter()
+            .filter(|message| message.thread_id == thread_id)
+            .map(|message| message.id.clone())
+            .collect();
+        if message_ids.is_empty() {
+            return Err(GmailApiError::MessageRetrievalError(format!(
+                "Thread {} not found",
+                thread_id
+            )));
+        }
+        Ok(message_ids)
+    }
+}
+
+#[async_trait]
+impl GmailApi for MockGmailApi {
+    /// Queries are matched case-insensitively against the subject, sender,
+    /// recipient, snippet and plain text body rather than parsed as Gmail
+    /// search syntax
+    async fn list_messages(
+        &self,
+        max_results: u32,
+        query: Option<&str>,
+    ) -> GmailResult<Vec<EmailMessage>> {
+        self.check_failure()?;
+
+        let query = query.map(str::to_lowercase);
+        Ok(self
+            .messages
+            .iter()
+            .filter(|message| match &query {
+                Some(query) => matches_query(
+                    query,
+                    &[
+                        message.subject.as_deref(),
+                        message.from.as_deref(),
+                        message.to.as_deref(),
+                        message.snippet.as_deref(),
+                        message.body_text.as_deref(),
+                    ],
+                ),
+                None => true,
+            })
+            .take(max_results as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn list_message_ids(
+        &self,
+        max_results: u32,
+        query: Option<&str>,
+    ) -> GmailResult<Vec<String>> {
+        // Once the deadline has passed, no page is listed
+        if self.deadline.expired() {
+            return Ok(Vec::new());
+        }
+        Ok(self
+            .list_messages(max_results, query)
+            .await?
+            .into_iter()
+            .map(|message| message.id)
+            .collect())
+    }
+
+    fn deadline_reached(&self) -> bool {
+        self.deadline.was_reached()
+    }
+
+    /// Every match is counted, but counts over `exact_limit` are reported as
+    /// estimates as Gmail's would be
+    async fn count_messages(
+        &self,
+        query: Option<&str>,
+        exact_limit: u32,
+    ) -> GmailResult<MessageCount> {
+        let count = self.list_message_ids(u32::MAX, query).await?.len() as u64;
+        Ok(MessageCount {
+            count,
+            exact: count <= exact_limit as u64,
+        })
+    }
+
+    async fn get_message_details(&self, message_id: &str) -> GmailResult<EmailMessage> {
+        self.check_failure()?;
+
+        self.messages
+            .iter()
+            .find(|message| message.id == message_id)
+            .cloned()
+            .ok_or_else(|| {
+                GmailApiError::MessageRetrievalError(format!("Message {} not found", message_id))
+            })
+    }
+
+    /// Labels come from `message_labels`
+    async fn get_message_metadata(&self, message_id: &str) -> GmailResult<MessageMetadata> {
+        let message = self.get_message_details(message_id).await?;
+        Ok(MessageMetadata {
+            id: message.id,
+            thread_id: message.thread_id,
+            from: message.from,
+            subject: message.subject,
+            snippet: message.snippet,
+            received_at: None,
+            label_ids: self
+                .message_labels
+                .lock()
+                .unwrap()
+                .get(message_id)
+                .cloned()
+                .unwrap_or_default(),
+            headers: HashMap::new(),
+        })
+    }
+
+    async fn get_history_id(&self) -> GmailResult<String> {
+        self.check_failure()?;
+        Ok(self.history_id.to_string())
+    }
+
+    async fn list_history(&self, start_history_id: &str) -> GmailResult<HistoryChanges> {
+        self.check_failure()?;
+
+        let start: u64 = start_history_id.parse().map_err(|_| {
+            GmailApiError::ApiError(format!("Invalid history ID: {}", start_history_id))
+        })?;
+        if start < self.history_floor {
+            return Err(GmailApiError::MessageRetrievalError(format!(
+                "History ID {} has expired",
+                start_history_id
+            )));
+        }
+
+        let mut changes = HistoryChanges {
+            history_id: self.history_id.to_string(),
+            ..Default::default()
+        };
+        for (_, event) in self.history.iter().filter(|(id, _)| *id > start) {
+            match event {
+                HistoryEvent::Added(id) => {
+                    changes.deleted.retain(|deleted| deleted != id);
+                    changes.added.push(id.clone());
+                }
+                HistoryEvent::Deleted(id) => {
+                    changes.added.retain(|added| added != id);
+                    changes.deleted.push(id.clone());
+                }
+            }
+        }
+        Ok(changes)
+    }
+
+    async fn list_labels(&self) -> GmailResult<String> {
+        self.check_failure()?;
+
+        let labels: Vec<_> = self
+            .labels()
+            .iter()
+            .map(|(id, name)| serde_json::json!({ "id": id, "name": name }))
+            .collect();
+        serde_json::to_string_pretty(&serde_json::json!({ "labels": labels }))
+            .map_err(|e| GmailApiError::MessageFormatError(e.to_string()))
+    }
+
+    async fn get_or_create_label(&self, name: &str) -> GmailResult<String> {
+        self.check_failure()?;
+
+        // Create missing parents of a nested name first, as GmailService does
+        let name = crate::label_tree::normalize_label_name(name)
+            .map_err(GmailApiError::MessageFormatError)?;
+        let mut labels = self.labels.lock().unwrap();
+        let mut id = String::new();
+        for level in crate::label_tree::ancestor_names(&name)
+            .into_iter()
+            .chain([name.clone()])
+        {
+            id = match labels
+                .iter()
+                .find(|(_, label)| label.eq_ignore_ascii_case(&level))
+            {
+                Some((id, _)) => id.clone(),
+                None => {
+                    let id = format!("Label_{}", labels.len() + 1);
+                    labels.push((id.clone(), level));
+                    id
+                }
+            };
+        }
+        Ok(id)
+    }
+
+    async fn modify_labels(
+        &self,
+        message_id: &str,
+        add_label_ids: &[String],
+        remove_label_ids: &[String],
+    ) -> GmailResult<()> {
+        self.check_failure()?;
+
+        if !self.messages.iter().any(|message| message.id == message_id) {
+            return Err(GmailApiError::MessageRetrievalError(format!(
+                "Message {} not found",
+                message_id
+            )));
+        }
+        self.apply_labels(message_id, add_label_ids, remove_label_ids);
+        Ok(())
+    }
+
+    async fn modify_thread_labels(
+        &self,
+        thread_id: &str,
+        add_label_ids: &[String],
+        remove_label_ids: &[String],
+    ) -> GmailResult<Vec<String>> {
+        self.check_failure()?;
+
+        let message_ids = self.thread_message_ids(thread_id)?;
+        for message_id in &message_ids {
+            self.apply_labels(message_id, add_label_ids, remove_label_ids);
+        }
+        Ok(message_ids)
+    }
+
+    async fn trash_thread(&self, thread_id: &str) -> GmailResult<Vec<String>> {
+        self.check_failure()?;
+
+        let message_ids = self.thread_message_ids(thread_id)?;
+        for message_id in &message_ids {
+            self.apply_labels(message_id, &["TRASH".to_string()], &[]);
+        }
+        Ok(message_ids)
+    }
+
+    async fn check_connection(&self) -> GmailResult<(String, u64)> {
+        self.check_failure()?;
+        Ok((self.email_address.clone(), self.messages_total))
+    }
+
+    async fn create_draft(&self, draft: &DraftEmail) -> GmailResult<String> {
+        self.check_failure()?;
+        let mut drafts = self.drafts.lock().unwrap();
+        drafts.push(draft.clone());
+        Ok(format!("draft-{}", drafts.len()))
+    }
+
+    async fn send_message(&self, draft: &DraftEmail) -> GmailResult<String> {
+        self.check_failure()?;
+
+        if draft.to.trim().is_empty() {
+            return Err(GmailApiError::MessageFormatError(
+                "Recipient address required".to_string(),
+            ));
+        }
+
+        let mut sent = self.sent.lock().unwrap();
+        sent.push(draft.clone());
+        Ok(format!("sent-{}", sent.len()))
+    }
+}
+
+/// In-memory Google Calendar account
+pub struct MockCalendarApi {
+    /// Calendars returned by `list_calendars`
+    pub calendars: Vec<CalendarInfo>,
+    events: Mutex<HashMap<String, Vec<CalendarEvent>>>,
+    failure: Option<ErrorFactory<CalendarApiError>>,
+}
+
+impl Default for MockCalendarApi {
+    fn default() -> Self {
+        Self {
+            calendars: Vec::new(),
+            events: Mutex::new(HashMap::new()),
+            failure: None,
+        }
+    }
+}
+
+impl MockCalendarApi {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_calendars(mut self, calendars: Vec<CalendarInfo>) -> Self {
+        self.calendars = calendars;
+        self
+    }
+
+    /// Add events to a calendar
+    pub fn with_events(self, calendar_id: &str, events: Vec<CalendarEvent>) -> Self {
+        self.events
+            .lock()
+            .unwrap()
+            .entry(calendar_id.to_string())
+            .or_default()
+            .extend(events);
+        self
+    }
+
+    /// Make every call fail with the error produced by `failure`
+    pub fn failing_with<F>(mut self, failure: F) -> Self
+    where
+        F: Fn() -> CalendarApiError + Send + Sync + 'static,
+    {
+        self.failure = Some(Box::new(failure));
+        self
+    }
+
+    /// All events currently stored in a calendar, including created ones
+    pub fn events(&self, calendar_id: &str) -> Vec<CalendarEvent> {
+        self.events
+            .lock()
+            .unwrap()
+            .get(calendar_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn check_failure(&self) -> CalendarResult<()> {
+        match &self.failure {
+            Some(failure) => Err(failure()),
+            None => Ok(()),
+        }
+    }
+}
+
+fn require_calendar_id(calendar_id: &str) -> CalendarResult<()> {
+    if calendar_id.is_empty() {
+        return Err(CalendarApiError::ApiError(
+            "Calendar ID cannot be empty".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl CalendarApi for MockCalendarApi {
+    async fn list_calendars(&self) -> CalendarResult<CalendarList> {
+        self.check_failure()?;
+        Ok(CalendarList {
+            calendars: self.calendars.clone(),
+            next_page_token: None,
+        })
+    }
+
+    /// Events overlapping the time window are returned in start time order
+    async fn list_events(
+        &self,
+        calendar_id: &str,
+        max_results: Option<u32>,
+        time_min: Option<DateTime<Utc>>,
+        time_max: Option<DateTime<Utc>>,
+    ) -> CalendarResult<Vec<CalendarEvent>> {
+        self.check_failure()?;
+        require_calendar_id(calendar_id)?;
+
+        let mut events: Vec<CalendarEvent> = self
+            .events(calendar_id)
+            .into_iter()
+            .filter(|event| time_min.is_none_or(|min| event.end_time > min))
+            .filter(|event| time_max.is_none_or(|max| event.start_time < max))
+            .collect();
+        events.sort_by_key(|event| event.start_time);
+
+        if let Some(max) = max_results {
+            events.truncate(max as usize);
+        }
+        Ok(events)
+    }
+
+    async fn create_event(
+        &self,
+        calendar_id: &str,
+        mut event: CalendarEvent,
+    ) -> CalendarResult<CalendarEvent> {
+        self.check_failure()?;
+        require_calendar_id(calendar_id)?;
+
+        if event.summary.is_empty() {
+            return Err(CalendarApiError::EventFormatError(
+                "Event summary cannot be empty".to_string(),
+            ));
+        }
+        if event.end_time <= event.start_time {
+            return Err(CalendarApiError::EventFormatError(
+                "End time must be after start time".to_string(),
+            ));
+        }
+
+        let id = event
+            .id
+            .get_or_insert_with(|| Uuid::new_v4().simple().to_string())
+            .clone();
+        if event.html_link.is_none() {
+            event.html_link = Some(format!(
+                "https://calendar.google.com/calendar/event?eid={}",
+                id
+            ));
+        }
+
+        self.events
+            .lock()
+            .unwrap()
+            .entry(calendar_id.to_string())
+            .or_default()
+            .push(event.clone());
+        Ok(event)
+    }
+
+    async fn get_event(&self, calendar_id: &str, event_id: &str) -> CalendarResult<CalendarEvent> {
+        self.check_failure()?;
+        require_calendar_id(calendar_id)?;
+
+        if event_id.is_empty() {
+            return Err(CalendarApiError::ApiError(
+                "Event ID cannot be empty".to_string(),
+            ));
+        }
+
+        self.events(calendar_id)
+            .into_iter()
+            .find(|event| event.id.as_deref() == Some(event_id))
+            .ok_or_else(|| {
+                CalendarApiError::EventRetrievalError(format!("Event {} not found", event_id))
+            })
+    }
+}
+
+/// In-memory Google Contacts address book
+#[derive(Default)]
+pub struct MockPeopleApi {
+    /// Contacts returned by list, search and get calls
+    pub contacts: Vec<Contact>,
+    failure: Option<ErrorFactory<PeopleApiError>>,
+}
+
+impl MockPeopleApi {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_contacts(mut self, contacts: Vec<Contact>) -> Self {
+        self.contacts = contacts;
+        self
+    }
+
+    /// Make every call fail with the error produced by `failure`
+    pub fn failing_with<F>(mut self, failure: F) -> Self
+    where
+        F: Fn() -> PeopleApiError + Send + Sync + 'static,
+    {
+        self.failure = Some(Box::new(failure));
+        self
+    }
+
+    fn check_failure(&self) -> PeopleResult<()> {
+        match &self.failure {
+            Some(failure) => Err(failure()),
+            None => Ok(()),
+        }
+    }
+}
+
+fn contact_list(contacts: Vec<Contact>, max_results: Option<u32>) -> ContactList {
+    let total_items = contacts.len() as u32;
+    let contacts = match max_results {
+        Some(max) => contacts.into_iter().take(max as usize).collect(),
+        None => contacts,
+    };
+    ContactList {
+        contacts,
+        next_page_token: None,
+        total_items: Some(total_items),
+    }
+}
+
+#[async_trait]
+impl PeopleApi for MockPeopleApi {
+    async fn list_contacts(&self, max_results: Option<u32>) -> PeopleResult<ContactList> {
+        self.check_failure()?;
+        Ok(contact_list(self.contacts.clone(), max_results))
+    }
+
+    async fn search_contacts(
+        &self,
+        query: &str,
+        max_results: Option<u32>,
+    ) -> PeopleResult<ContactList> {
+        self.check_failure()?;
+
+        let query = query.to_lowercase();
+        let matches = self
+            .contacts
+            .iter()
+            .filter(|contact| {
+                let mut fields = vec![contact.name.as_ref().map(|n| n.display_name.as_str())];
+                fields.extend(
+                    contact
+                        .email_addresses
+                        .iter()
+                        .map(|e| Some(e.value.as_str())),
+                );
+                fields.extend(contact.phone_numbers.iter().map(|p| Some(p.value.as_str())));
+                for org in &contact.organizations {
+                    fields.push(org.name.as_deref());
+                    fields.push(org.title.as_deref());
+                }
+                matches_query(&query, &fields)
+            })
+            .cloned()
+            .collect();
+
+        Ok(contact_list(matches, max_results))
+    }
+
+    async fn get_contact(&self, resource_name: &str) -> PeopleResult<Contact> {
+        self.check_failure()?;
+
+        self.contacts
+            .iter()
+            .find(|contact| contact.resource_name == resource_name)
+            .cloned()
+            .ok_or_else(|| {
+                PeopleApiError::ApiError(format!("Contact not found: {}", resource_name))
+            })
+    }
+}
+
+/// In-memory Google Tasks account
+#[derive(Default)]
+pub struct MockTasksApi {
+    /// Task lists returned by `list_task_lists`
+    pub task_lists: Vec<TaskList>,
+    tasks: Mutex<HashMap<String, Vec<Task>>>,
+    failure: Option<ErrorFactory<TasksApiError>>,
+}
+
+impl MockTasksApi {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_task_lists(mut self, task_lists: Vec<TaskList>) -> Self {
+        self.task_lists = task_lists;
+        self
+    }
+
+    /// Add tasks to a list
+    pub fn with_tasks(self, task_list_id: &str, tasks: Vec<Task>) -> Self {
+        self.tasks
+            .lock()
+            .unwrap()
+            .entry(task_list_id.to_string())
+            .or_default()
+            .extend(tasks);
+        self
+    }
+
+    /// Make every call fail with the error produced by `failure`
+    pub fn failing_with<F>(mut self, failure: F) -> Self
+    where
+        F: Fn() -> TasksApiError + Send + Sync + 'static,
+    {
+        self.failure = Some(Box::new(failure));
+        self
+    }
+
+    /// All tasks currently stored in a list, including created ones
+    pub fn tasks(&self, task_list_id: &str) -> Vec<Task> {
+        self.tasks
+            .lock()
+            .unwrap()
+            .get(task_list_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn check_failure(&self) -> TasksResult<()> {
+        match &self.failure {
+            Some(failure) => Err(failure()),
+            None => Ok(()),
+        }
+    }
+
+    // The default list always exists, like in a real account
+    fn check_task_list(&self, task_list_id: &str) -> TasksResult<()> {
+        if task_list_id == DEFAULT_TASK_LIST
+            || self.task_lists.iter().any(|list| list.id == task_list_id)
+        {
+            Ok(())
+        } else {
+            Err(TasksApiError::ApiError(format!(
+                "Task list not found: {}",
+                task_list_id
+            )))
+        }
+    }
+}
+
+#[async_trait]
+impl TasksApi for MockTasksApi {
+    async fn list_task_lists(&self, max_results: Option<u32>) -> TasksResult<Vec<TaskList>> {
+        self.check_failure()?;
+        let max = max_results.map_or(usize::MAX, |max| max as usize);
+        Ok(self.task_lists.iter().take(max).cloned().collect())
+    }
+
+    async fn list_tasks(
+        &self,
+        task_list_id: &str,
+        show_completed: bool,
+        max_results: Option<u32>,
+    ) -> TasksResult<Vec<Task>> {
+        self.check_failure()?;
+        self.check_task_list(task_list_id)?;
+
+        let max = max_results.map_or(usize::MAX, |max| max as usize);
+        Ok(self
+            .tasks(task_list_id)
+            .into_iter()
+            .filter(|task| show_completed || task.status.as_deref() != Some("completed"))
+            .take(max)
+            .collect())
+    }
+
+    async fn create_task(&self, task_list_id: &str, task: &Task) -> TasksResult<Task> {
+        self.check_failure()?;
+        self.check_task_list(task_list_id)?;
+        if task.title.trim().is_empty() {
+            return Err(TasksApiError::InvalidInput(
+                "Task title cannot be empty".to_string(),
+            ));
+        }
+
+        let mut task = task.clone();
+        task.id = Some(Uuid::new_v4().simple().to_string());
+        task.status = Some("needsAction".to_string());
+        self.tasks
+            .lock()
+            .unwrap()
+            .entry(task_list_id.to_string())
+            .or_default()
+            .push(task.clone());
+        Ok(task)
+    }
+
+    async fn complete_task(&self, task_list_id: &str, task_id: &str) -> TasksResult<Task> {
+        self.check_failure()?;
+        self.check_task_list(task_list_id)?;
+
+        let mut tasks = self.tasks.lock().unwrap();
+        let task = tasks
+            .get_mut(task_list_id)
+            .and_then(|tasks| {
+                tasks
+                    .iter_mut()
+                    .find(|task| task.id.as_deref() == Some(task_id))
+            })
+            .ok_or_else(|| TasksApiError::ApiError(format!("Task not found: {}", task_id)))?;
+        task.status = Some("completed".to_string());
+        task.completed = Some(Utc::now().to_rfc3339());
+        Ok(task.clone())
+    }
+}
+
+/// In-memory Google Drive
+#[derive(Default)]
+pub struct MockDriveApi {
+    /// Files by ID, with who they are shared with and their content
+    pub files: HashMap<String, (DriveFile, Vec<DrivePermission>, Vec<u8>)>,
+    pub storage_quota: Option<StorageQuota>,
+    failure: Option<ErrorFactory<DriveApiError>>,
+}
+
+impl MockDriveApi {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a file; its size is set from the content unless it is a Google
+    /// Docs file
+    pub fn with_file(
+        mut self,
+        mut file: DriveFile,
+        permissions: Vec<DrivePermission>,
+        data: Vec<u8>,
+    ) -> Self {
+        if !file.is_google_apps_file() {
+            file.size = Some(data.len() as u64);
+        }
+        self.files
+            .insert(file.id.clone(), (file, permissions, data));
+        self
+    }
+
+    pub fn with_storage_quota(mut self, storage_quota: StorageQuota) -> Self {
+        self.storage_quota = Some(storage_quota);
+        self
+    }
+
+    /// Make every call fail with the error produced by `failure`
+    pub fn failing_with<F>(mut self, failure: F) -> Self
+    where
+        F: Fn() -> DriveApiError + Send + Sync + 'static,
+    {
+        self.failure = Some(Box::new(failure));
+        self
+    }
+
+    fn file(&self, file_id: &str) -> DriveResult<&(DriveFile, Vec<DrivePermission>, Vec<u8>)> {
+        if let Some(failure) = &self.failure {
+            return Err(failure());
+        }
+        self.files
+            .get(file_id)
+            .ok_or_else(|| DriveApiError::FileNotFound(file_id.to_string()))
+    }
+}
+
+#[async_trait]
+impl DriveApi for MockDriveApi {
+    async fn get_file(&self, file_id: &str) -> DriveResult<DriveFile> {
+        Ok(self.file(file_id)?.0.clone())
+    }
+
+    async fn list_permissions(&self, file_id: &str) -> DriveResult<Vec<DrivePermission>> {
+        Ok(self.file(file_id)?.1.clone())
+    }
+
+    async fn download_file(&self, file_id: &str, max_bytes: u64) -> DriveResult<Vec<u8>> {
+        let (file, _, data) = self.file(file_id)?;
+        if file.is_google_apps_file() {
+            return Err(DriveApiError::ApiError(format!(
+                "Only files with binary content can be downloaded: {}",
+                file_id
+            )));
+        }
+        if data.len() as u64 > max_bytes {
+            return Err(DriveApiError::NotAttachable(format!(
+                "{} is larger than the {} byte limit",
+                file_id, max_bytes
+            )));
+        }
+        Ok(data.clone())
+    }
+
+    async fn get_storage_quota(&self) -> DriveResult<StorageQuota> {
+        if let Some(failure) = &self.failure {
+            return Err(failure());
+        }
+        self.storage_quota
+            .clone()
+            .ok_or_else(|| DriveApiError::ApiError("No storage quota".to_string()))
+    }
+}
@@ -0,0 +1,240 @@
+use crate::gmail_api::EmailMessage;
+use crate::sender_report::split_sender;
+use crate::utils::{parse_email_addresses, strip_quoted_text};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+// Clean thread view
+//
+// Each reply in a long conversation usually quotes everything before it, so
+// the raw bodies of a thread repeat the same text many times over. The clean
+// view keeps only what each message adds, puts the messages in the order
+// they were sent with comparable UTC timestamps, and lists who took part.
+//
+// Searching a thread looks through the same content, so a statement is found
+// in the message that made it rather than in every reply quoting it. Offsets
+// count characters of a message's clean content, as returned by the clean
+// view, so a match can be read there in context.
+
+/// Most matches returned by a thread search when no limit is given
+pub const DEFAULT_THREAD_SEARCH_MATCHES: u32 = 20;
+
+/// Characters of context shown either side of a match
+pub const SNIPPET_CONTEXT_CHARS: usize = 80;
+
+/// Someone who sent or received mail in a thread
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Participant {
+    /// Lowercased email address
+    pub address: String,
+    /// Display name from a From header, if any
+    pub name: Option<String>,
+    /// Number of messages in the thread they sent
+    pub messages_sent: usize,
+}
+
+/// One message of a thread with the quoted text removed
+#[derive(Serialize, Debug, Clone)]
+pub struct CleanMessage {
+    pub id: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    /// The Date header as sent
+    pub date: Option<String>,
+    /// The Date header in UTC, if it could be read
+    pub timestamp: Option<DateTime<Utc>>,
+    /// What the message adds to the conversation
+    pub content: String,
+    /// Whether quoted text was removed from the body
+    pub quotes_removed: bool,
+}
+
+/// Where text was found in a thread
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ThreadMatch {
+    pub message_id: String,
+    pub from: Option<String>,
+    pub date: Option<String>,
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Character offset of the match in the message's clean content
+    pub offset: usize,
+    /// Length of the match in characters
+    pub length: usize,
+    /// The match with the text around it, on one line
+    pub snippet: String,
+}
+
+/// A thread as its messages' own content, oldest first
+#[derive(Serialize, Debug, Clone)]
+pub struct CleanThread {
+    pub thread_id: String,
+    pub subject: Option<String>,
+    pub message_count: usize,
+    pub participants: Vec<Participant>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub last_activity: Option<DateTime<Utc>>,
+    pub messages: Vec<CleanMessage>,
+}
+
+// Read a Date header, ignoring a trailing comment such as `(PDT)`
+pub(crate) fn parse_date(date: &str) -> Option<DateTime<Utc>> {
+    let date = date.split(" (").next()?.trim();
+    DateTime::parse_from_rfc2822(date)
+        .ok()
+        .map(|date| date.with_timezone(&Utc))
+}
+
+impl CleanThread {
+    /// Build the clean view of a thread from its messages
+    pub fn from_messages(thread_id: &str, messages: &[EmailMessage]) -> Self {
+        let mut clean: Vec<CleanMessage> = messages
+            .iter()
+            .map(|message| {
+                let body = message
+                    .body_text
+                    .as_deref()
+                    .or(message.snippet.as_deref())
+                    .unwrap_or_default();
+                let content = strip_quoted_text(body);
+                CleanMessage {
+                    id: message.id.clone(),
+                    from: message.from.clone(),
+                    to: message.to.clone(),
+                    date: message.date.clone(),
+                    timestamp: message.date.as_deref().and_then(parse_date),
+                    quotes_removed: content != body.trim(),
+                    content,
+                }
+            })
+            .collect();
+        // Messages without a readable date stay in Gmail's order at the end
+        clean.sort_by_key(|message| (message.timestamp.is_none(), message.timestamp));
+
+        let mut participants: Vec<Participant> = Vec::new();
+        for message in &clean {
+            let sender = message.from.as_deref().and_then(split_sender);
+            let recipients = message
+                .to
+                .as_deref()
+                .map(parse_email_addresses)
+                .unwrap_or_default();
+            if let Some((address, _)) = &sender {
+                if !participants.iter().any(|p| &p.address == address) {
+                    participants.push(Participant {
+                        address: address.clone(),
+                        name: None,
+                        messages_sent: 0,
+                    });
+                }
+            }
+            for address in recipients {
+                if !participants.iter().any(|p| p.address == address) {
+                    participants.push(Participant {
+                        address,
+                        name: None,
+                        messages_sent: 0,
+                    });
+                }
+            }
+            if let Some((address, name)) = sender {
+                if let Some(participant) = participants.iter_mut().find(|p| p.address == address) {
+                    participant.messages_sent += 1;
+                    participant.name = name.or(participant.name.take());
+                }
+            }
+        }
+
+        let subject = clean
+            .first()
+            .and_then(|first| messages.iter().find(|message| message.id == first.id))
+            .and_then(|message| message.subject.clone());
+        let timestamps = clean.iter().filter_map(|message| message.timestamp);
+
+        Self {
+            thread_id: thread_id.to_string(),
+            subject,
+            message_count: clean.len(),
+            participants,
+            started_at: timestamps.clone().min(),
+            last_activity: timestamps.max(),
+            messages: clean,
+        }
+    }
+
+    /// Find text in the messages' own content, oldest first. Case is ignored,
+    /// and any run of whitespace matches any other, so text wrapped across
+    /// lines is still found.
+    pub fn search(&self, text: &str) -> Vec<ThreadMatch> {
+        let needle: Vec<char> = fold_text(text)
+            .into_iter()
+            .map(|(c, _)| c)
+            .collect::<String>()
+            .trim()
+            .chars()
+            .collect();
+        if needle.is_empty() {
+            return Vec::new();
+        }
+
+        let mut matches = Vec::new();
+        for message in &self.messages {
+            let content: Vec<char> = message.content.chars().collect();
+            let folded = fold_text(&message.content);
+            let haystack: Vec<char> = folded.iter().map(|(c, _)| *c).collect();
+            let mut start = 0;
+            while start + needle.len() <= haystack.len() {
+                if haystack[start..start + needle.len()] != needle[..] {
+                    start += 1;
+                    continue;
+                }
+                // The needle is trimmed, so it ends on a character kept as
+                // it was
+                let offset = folded[start].1;
+                let end = folded[start + needle.len() - 1].1 + 1;
+                matches.push(ThreadMatch {
+                    message_id: message.id.clone(),
+                    from: message.from.clone(),
+                    date: message.date.clone(),
+                    timestamp: message.timestamp,
+                    offset,
+                    length: end - offset,
+                    snippet: snippet(&content, offset, end),
+                });
+                start += needle.len();
+            }
+        }
+        matches
+    }
+}
+
+// Lowercase text and collapse each run of whitespace to one space, keeping
+// the character offset in the original text of each character kept
+fn fold_text(text: &str) -> Vec<(char, usize)> {
+    let mut folded: Vec<(char, usize)> = Vec::new();
+    for (offset, c) in text.chars().enumerate() {
+        if c.is_whitespace() {
+            if folded.last().is_some_and(|(last, _)| *last == ' ') {
+                continue;
+            }
+            folded.push((' ', offset));
+        } else {
+            folded.push((c.to_lowercase().next().unwrap_or(c), offset));
+        }
+    }
+    folded
+}
+
+// The characters from `start` to `end` with some context either side, on one
+// line, marking where the content was cut
+fn snippet(content: &[char], start: usize, end: usize) -> String {
+    let from = start.saturating_sub(SNIPPET_CONTEXT_CHARS);
+    let to = (end + SNIPPET_CONTEXT_CHARS).min(content.len());
+    let text: String = content[from..to].iter().collect();
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    format!(
+        "{}{}{}",
+        if from > 0 { "…" } else { "" },
+        text,
+        if to < content.len() { "…" } else { "" }
+    )
+}
@@ -0,0 +1,232 @@
+use crate::calendar_api::{BusyPeriod, CalendarEvent};
+use chrono::{DateTime, Duration, NaiveDate, Timelike, Utc};
+use serde::Serialize;
+
+// Time blocking
+//
+// Tasks are fitted into a day's free time in the order given, each into the
+// earliest gap in working hours long enough to hold it, so the first tasks
+// get the first choice of time. Busy time comes from a free/busy query, which
+// leaves out events that keep the time free. Each task becomes a focus time
+// event; tasks that fit nowhere are reported rather than split or dropped.
+
+/// Hour the working day starts when no start is given
+pub const DEFAULT_WORK_START_HOUR: u32 = 9;
+
+/// Hour the working day ends when no end is given
+pub const DEFAULT_WORK_END_HOUR: u32 = 17;
+
+/// Blocks start on a multiple of this many minutes
+pub const BLOCK_ALIGNMENT_MINUTES: i64 = 15;
+
+/// Longest task length accepted, a week
+pub const MAX_TASK_MINUTES: f64 = 7.0 * 24.0 * 60.0;
+
+/// A task to find time for
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TaskBlock {
+    pub name: String,
+    pub minutes: i64,
+}
+
+/// Parse a length of time such as "90m", "1h30m", "1.5 hours" or
+/// "45 minutes", of at most `MAX_TASK_MINUTES`
+pub fn parse_task_duration(input: &str) -> Option<Duration> {
+    let text: String = input
+        .to_lowercase()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    let mut rest = text.as_str();
+    let mut minutes = 0.0;
+    while !rest.is_empty() {
+        let number_end = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        let amount: f64 = rest[..number_end].parse().ok()?;
+        rest = &rest[number_end..];
+        let unit_end = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        minutes += match &rest[..unit_end] {
+            "h" | "hr" | "hrs" | "hour" | "hours" => amount * 60.0,
+            "m" | "min" | "mins" | "minute" | "minutes" => amount,
+            _ => return None,
+        };
+        rest = &rest[unit_end..];
+    }
+    if minutes > MAX_TASK_MINUTES {
+        return None;
+    }
+    let minutes = minutes.round() as i64;
+    (minutes > 0).then(|| Duration::minutes(minutes))
+}
+
+/// Parse a task with its length, such as "Write report: 90m",
+/// "Review PRs (1h)" or "Inbox zero 30 minutes"
+pub fn parse_task(input: &str) -> Result<TaskBlock, String> {
+    let input = input.trim();
+    let invalid = || {
+        format!(
+            "Invalid task '{}': expected a name and a length, such as 'Write report: 90m'",
+            input
+        )
+    };
+
+    let split = |(name, length): (&str, &str)| {
+        parse_task_duration(length).map(|duration| (name.to_string(), duration))
+    };
+    let words: Vec<&str> = input.split_whitespace().collect();
+    let (name, duration) = input
+        .rsplit_once(':')
+        .and_then(split)
+        .or_else(|| {
+            input
+                .strip_suffix(')')
+                .and_then(|input| input.rsplit_once('('))
+                .and_then(split)
+        })
+        .or_else(|| {
+            // The length is the last few words
+            (1..words.len().min(5)).rev().find_map(|count| {
+                let at = words.len() - count;
+                parse_task_duration(&words[at..].join(""))
+                    .map(|duration| (words[..at].join(" "), duration))
+            })
+        })
+        .ok_or_else(invalid)?;
+
+    let name = name.trim().trim_end_matches(['-', ',']).trim();
+    if name.is_empty() {
+        return Err(invalid());
+    }
+    Ok(TaskBlock {
+        name: name.to_string(),
+        minutes: duration.num_minutes(),
+    })
+}
+
+/// Round a time up to the next block boundary
+pub fn align_up(time: DateTime<Utc>) -> DateTime<Utc> {
+    let time = time
+        .with_second(0)
+        .unwrap_or(time)
+        .with_nanosecond(0)
+        .unwrap_or(time);
+    let past = i64::from(time.minute()) % BLOCK_ALIGNMENT_MINUTES;
+    if past == 0 {
+        time
+    } else {
+        time + Duration::minutes(BLOCK_ALIGNMENT_MINUTES - past)
+    }
+}
+
+/// The free gaps in a window of time around busy periods, in order
+pub fn free_slots(
+    window: (DateTime<Utc>, DateTime<Utc>),
+    busy: &[BusyPeriod],
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut busy = busy.to_vec();
+    busy.sort_by_key(|period| period.start);
+
+    let mut slots = Vec::new();
+    let mut free_from = window.0;
+    for period in busy {
+        if period.start > free_from {
+            slots.push((free_from, period.start.min(window.1)));
+        }
+        free_from = free_from.max(period.end);
+    }
+    slots.push((free_from, window.1));
+    slots.retain(|(start, end)| start < end);
+    slots
+}
+
+/// A task given a time
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScheduledBlock {
+    pub task: String,
+    pub minutes: i64,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    /// ID of the event created for the block
+    pub event_id: Option<String>,
+}
+
+/// A task that could not be given a time
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UnscheduledTask {
+    pub task: String,
+    pub minutes: i64,
+    pub reason: String,
+}
+
+/// Fit tasks into free slots in order, each into the earliest slot that
+/// holds it
+pub fn plan_blocks(
+    tasks: &[TaskBlock],
+    mut slots: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+) -> (Vec<ScheduledBlock>, Vec<UnscheduledTask>) {
+    let mut scheduled = Vec::new();
+    let mut unscheduled = Vec::new();
+    for task in tasks {
+        let length = Duration::minutes(task.minutes);
+        match slots
+            .iter_mut()
+            .find(|(start, end)| *end - *start >= length)
+        {
+            Some(slot) => {
+                let start_time = slot.0;
+                slot.0 = align_up(start_time + length);
+                scheduled.push(ScheduledBlock {
+                    task: task.name.clone(),
+                    minutes: task.minutes,
+                    start_time,
+                    end_time: start_time + length,
+                    event_id: None,
+                });
+            }
+            None => unscheduled.push(UnscheduledTask {
+                task: task.name.clone(),
+                minutes: task.minutes,
+                reason: format!(
+                    "No free slot of {} minutes left in working hours",
+                    task.minutes
+                ),
+            }),
+        }
+    }
+    scheduled.sort_by_key(|block| block.start_time);
+    (scheduled, unscheduled)
+}
+
+/// The event holding a block. Focus time can only be kept on the primary
+/// calendar, so on other calendars the block is an ordinary busy event.
+pub fn focus_time_event(
+    block: &ScheduledBlock,
+    calendar_id: &str,
+    time_zone: &str,
+) -> CalendarEvent {
+    CalendarEvent {
+        summary: block.task.clone(),
+        description: Some("Time blocked for this task".to_string()),
+        start_time: block.start_time,
+        end_time: block.end_time,
+        transparency: Some("opaque".to_string()),
+        time_zone: Some(time_zone.to_string()),
+        event_type: (calendar_id == "primary").then(|| "focusTime".to_string()),
+        ..Default::default()
+    }
+}
+
+/// The outcome of blocking out time for tasks on a day
+#[derive(Debug, Clone, Serialize)]
+pub struct TimeBlockPlan {
+    pub date: NaiveDate,
+    pub time_zone: String,
+    pub calendar_id: String,
+    /// Blocks created, by start time
+    pub scheduled: Vec<ScheduledBlock>,
+    /// Tasks that could not be fitted in or whose event could not be created
+    pub unscheduled: Vec<UnscheduledTask>,
+}
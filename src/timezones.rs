@@ -0,0 +1,159 @@
+use crate::calendar_api::{CalendarClient, CalendarEvent};
+use chrono::{DateTime, FixedOffset, LocalResult, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::{OffsetComponents, OffsetName, Tz};
+use log::debug;
+use serde::Serialize;
+
+// Time zones
+//
+// Times are converted with the IANA time zone database, so daylight saving
+// changes on either side are accounted for. A local time that a change skips
+// is rejected, and one that happens twice is taken at its first occurrence.
+//
+// Attendee time zones come from the time zone of each attendee's calendar,
+// which can only be read when the attendee shares their calendar with the
+// user. Others are left unknown.
+
+/// Most attendee calendars looked up when listing events
+pub const ATTENDEE_TIME_ZONE_LOOKUPS: usize = 25;
+
+/// Local time formats accepted besides RFC 3339
+const LOCAL_TIME_FORMATS: &[&str] = &[
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M",
+];
+
+/// Look up a time zone by its IANA name, such as "Europe/Berlin" or "UTC"
+pub fn parse_time_zone(name: &str) -> Result<Tz, String> {
+    name.trim().parse::<Tz>().map_err(|_| {
+        format!(
+            "Unknown time zone '{}'; use an IANA name such as 'America/New_York'",
+            name
+        )
+    })
+}
+
+/// A moment as the clocks in one time zone show it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ZonedTime {
+    pub time_zone: String,
+    /// RFC 3339, with the zone's offset at that moment
+    pub local_time: String,
+    /// Such as "CEST", or the offset where the zone has no abbreviation
+    pub abbreviation: Option<String>,
+    pub utc_offset: String,
+    /// Whether daylight saving time is in effect
+    pub dst: bool,
+}
+
+impl ZonedTime {
+    fn new(time: DateTime<Tz>) -> Self {
+        let offset = time.offset();
+        Self {
+            time_zone: offset.tz_id().to_string(),
+            local_time: time.to_rfc3339(),
+            abbreviation: offset.abbreviation().map(String::from),
+            utc_offset: time.format("%:z").to_string(),
+            dst: !offset.dst_offset().is_zero(),
+        }
+    }
+}
+
+/// A time converted from one time zone to another
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TimeConversion {
+    pub from: ZonedTime,
+    pub to: ZonedTime,
+    pub utc: DateTime<Utc>,
+    /// Days the local date moves by, such as 1 when it is already tomorrow in
+    /// the target time zone
+    pub day_change: i64,
+    /// Set when the local time happens twice, as clocks go back
+    pub note: Option<String>,
+}
+
+/// Convert a time given in `from` to `to`. The time is either RFC 3339, whose
+/// offset then fixes the moment, or a local time such as "2026-10-15 09:30"
+/// read in `from`.
+pub fn convert_time(time: &str, from: Tz, to: Tz) -> Result<TimeConversion, String> {
+    let time = time.trim();
+    let mut note = None;
+    let instant = match DateTime::<FixedOffset>::parse_from_rfc3339(time) {
+        Ok(instant) => instant.with_timezone(&Utc),
+        Err(_) => {
+            let local = LOCAL_TIME_FORMATS
+                .iter()
+                .find_map(|format| NaiveDateTime::parse_from_str(time, format).ok())
+                .ok_or_else(|| {
+                    format!(
+                        "Invalid time '{}'; use RFC 3339 or a local time such as '2026-10-15 09:30'",
+                        time
+                    )
+                })?;
+            match from.from_local_datetime(&local) {
+                LocalResult::Single(instant) => instant.with_timezone(&Utc),
+                LocalResult::Ambiguous(earliest, _) => {
+                    note = Some(format!(
+                        "{} happens twice in {} as clocks go back; the first occurrence was used",
+                        time, from
+                    ));
+                    earliest.with_timezone(&Utc)
+                }
+                LocalResult::None => {
+                    return Err(format!(
+                        "{} does not exist in {} as clocks go forward",
+                        time, from
+                    ))
+                }
+            }
+        }
+    };
+
+    let from_time = instant.with_timezone(&from);
+    let to_time = instant.with_timezone(&to);
+    Ok(TimeConversion {
+        day_change: (to_time.date_naive() - from_time.date_naive()).num_days(),
+        from: ZonedTime::new(from_time),
+        to: ZonedTime::new(to_time),
+        utc: instant,
+        note,
+    })
+}
+
+/// Fill in the time zone of each attendee whose calendar the user can see.
+/// Lookups that fail leave the time zone unknown.
+pub async fn add_attendee_time_zones(client: &CalendarClient, events: &mut [CalendarEvent]) {
+    let mut addresses: Vec<String> = Vec::new();
+    for attendee in events.iter().flat_map(|event| &event.attendees) {
+        let address = attendee.email.to_lowercase();
+        if !addresses.contains(&address) {
+            addresses.push(address);
+        }
+    }
+    addresses.truncate(ATTENDEE_TIME_ZONE_LOOKUPS);
+
+    let lookups = addresses
+        .iter()
+        .map(|address| client.get_time_zone(address));
+    let time_zones: Vec<(String, String)> = futures::future::join_all(lookups)
+        .await
+        .into_iter()
+        .zip(&addresses)
+        .filter_map(|(result, address)| match result {
+            Ok(time_zone) => Some((address.clone(), time_zone)),
+            Err(err) => {
+                debug!("No time zone for {}: {}", address, err);
+                None
+            }
+        })
+        .collect();
+
+    for attendee in events.iter_mut().flat_map(|event| &mut event.attendees) {
+        attendee.time_zone = time_zones
+            .iter()
+            .find(|(address, _)| attendee.email.eq_ignore_ascii_case(address))
+            .map(|(_, time_zone)| time_zone.clone());
+    }
+}
@@ -0,0 +1,78 @@
+use crate::doctor::missing_scopes;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+// Access token status
+//
+// Google's tokeninfo endpoint describes an access token: the scopes granted
+// to it, how long it has left and, when the email scope was granted, the
+// account it belongs to. check_connection reports it so that clients can warn
+// about a token that is about to lapse or lacks a scope before a call fails
+// midway through a conversation.
+
+/// Remaining lifetime, in seconds, under which a token is expiring soon
+pub const TOKEN_EXPIRING_SOON_SECONDS: i64 = 300;
+
+/// A scope the token was not granted, and the tools that need it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MissingScope {
+    pub tools: String,
+    pub scope: String,
+}
+
+/// What Google reports about the current access token
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TokenInfo {
+    /// Address of the signed-in account, if the email scope was granted
+    pub email: Option<String>,
+    pub scopes: Vec<String>,
+    pub missing_scopes: Vec<MissingScope>,
+    pub expires_in_seconds: Option<i64>,
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Whether the token lapses within TOKEN_EXPIRING_SOON_SECONDS
+    pub expiring_soon: bool,
+}
+
+impl TokenInfo {
+    /// Read a tokeninfo response received at `now`
+    pub fn from_response(response: &Value, now: DateTime<Utc>) -> Self {
+        let scope = response.get("scope").and_then(|v| v.as_str()).unwrap_or("");
+        let scopes = scope.split_whitespace().map(str::to_string).collect();
+        let missing_scopes = missing_scopes(scope)
+            .into_iter()
+            .map(|(tools, scope)| MissingScope {
+                tools: tools.to_string(),
+                scope: scope.to_string(),
+            })
+            .collect();
+
+        // Google gives the lifetime and expiry time as strings of seconds
+        let expires_at = number(response, "exp").and_then(|exp| DateTime::from_timestamp(exp, 0));
+        let expires_in_seconds = number(response, "expires_in")
+            .or_else(|| expires_at.map(|expires_at| (expires_at - now).num_seconds()));
+        let expires_at = expires_at
+            .or_else(|| expires_in_seconds.map(|seconds| now + Duration::seconds(seconds)));
+
+        Self {
+            email: response
+                .get("email")
+                .and_then(|v| v.as_str())
+                .map(str::to_string),
+            scopes,
+            missing_scopes,
+            expires_in_seconds,
+            expires_at,
+            expiring_soon: expires_in_seconds
+                .is_some_and(|seconds| seconds < TOKEN_EXPIRING_SOON_SECONDS),
+        }
+    }
+}
+
+// A number given either as a JSON number or as a string
+fn number(response: &Value, name: &str) -> Option<i64> {
+    let value = response.get(name)?;
+    value
+        .as_i64()
+        .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+}
@@ -0,0 +1,308 @@
+use crate::api::{CalendarApi, GmailApi};
+use crate::errors::{GmailApiError, Result};
+use crate::gmail_api::{DraftEmail, EmailMessage};
+use crate::priority::PriorityLevel;
+use crate::reminders::{create_reminder, Reminder, ReminderRequest};
+use chrono::{DateTime, Utc};
+use log::{debug, info};
+use serde::Serialize;
+
+// Inbox zero triage
+//
+// Triage hands out the inbox one email at a time, unread mail first and the
+// oldest first within it, with the actions that look most likely. Applying
+// one of them archives the email, files it under a label, drafts a reply to
+// it or snoozes it, each of which moves it out of the way so that the next
+// call hands out the next email.
+
+/// Number of inbox messages looked through for the oldest
+pub const TRIAGE_SCAN_LIMIT: u32 = 500;
+
+/// Inbox messages counted exactly in the number remaining
+pub const TRIAGE_COUNT_LIMIT: u32 = 1000;
+
+/// Label applied to snoozed emails
+pub const SNOOZE_LABEL: &str = "Snoozed";
+
+// Sender names that mark mail from a machine rather than a person
+const AUTOMATED_SENDERS: &[&str] = &[
+    "noreply",
+    "no-reply",
+    "donotreply",
+    "do-not-reply",
+    "notifications@",
+    "notification@",
+    "mailer-daemon",
+];
+
+/// What can be done with an email during triage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TriageAction {
+    /// Mark read and remove from the inbox
+    Archive,
+    /// File under a label, then archive
+    Label,
+    /// Draft a reply in the email's thread and mark it read
+    ReplyDraft,
+    /// Remove from the inbox until a reminder brings it back
+    Snooze,
+}
+
+impl TriageAction {
+    /// Read an action name, accepting `-` or `_` between words
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().replace('-', "_").as_str() {
+            "archive" => Some(Self::Archive),
+            "label" => Some(Self::Label),
+            "reply_draft" | "reply" => Some(Self::ReplyDraft),
+            "snooze" => Some(Self::Snooze),
+            _ => None,
+        }
+    }
+}
+
+/// An action with what it needs to be applied
+#[derive(Debug, Clone, PartialEq)]
+pub enum TriageCommand {
+    Archive,
+    Label(String),
+    ReplyDraft(String),
+    Snooze(DateTime<Utc>),
+}
+
+/// An action that looks likely for an email, and why
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SuggestedAction {
+    pub action: TriageAction,
+    pub reason: String,
+}
+
+/// The next email to triage
+#[derive(Debug, Clone, Serialize)]
+pub struct TriageItem {
+    pub message: EmailMessage,
+    pub unread: bool,
+    /// Inbox messages matching the filter, this one included
+    pub remaining: u64,
+    /// Whether `remaining` was counted exactly rather than estimated
+    pub remaining_exact: bool,
+    /// Most likely first
+    pub suggested_actions: Vec<SuggestedAction>,
+}
+
+/// What applying an action did
+#[derive(Debug, Clone, Serialize)]
+pub struct TriageOutcome {
+    pub message_id: String,
+    pub action: TriageAction,
+    /// Whether the email left the inbox
+    pub archived: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub draft_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reminder: Option<Reminder>,
+}
+
+/// The Gmail query for inbox mail matching an optional filter, such as
+/// `from:example.com`, limited to unread mail when `unread` is set
+pub fn triage_query(filter: Option<&str>, unread: bool) -> String {
+    let mut query = "in:inbox".to_string();
+    if unread {
+        query.push_str(" is:unread");
+    }
+    if let Some(filter) = filter.map(str::trim).filter(|f| !f.is_empty()) {
+        query.push_str(&format!(" ({})", filter));
+    }
+    query
+}
+
+/// Whether an email looks sent by a machine or in bulk rather than by a
+/// person
+pub fn is_automated(email: &EmailMessage) -> bool {
+    let from = email.from.as_deref().unwrap_or_default().to_lowercase();
+    AUTOMATED_SENDERS.iter().any(|sender| from.contains(sender)) || !email.trackers.is_empty()
+}
+
+/// The actions that look likely for an email, most likely first
+pub fn suggest_actions(email: &EmailMessage) -> Vec<SuggestedAction> {
+    let suggest = |action, reason: &str| SuggestedAction {
+        action,
+        reason: reason.to_string(),
+    };
+
+    if email.delivery_report.is_some() || email.read_receipt.is_some() {
+        return vec![suggest(
+            TriageAction::Archive,
+            "It is an automated delivery or read notification",
+        )];
+    }
+    if is_automated(email) {
+        return vec![
+            suggest(
+                TriageAction::Archive,
+                "It comes from an automated or bulk sender",
+            ),
+            suggest(
+                TriageAction::Label,
+                "File it if it is worth keeping for later",
+            ),
+        ];
+    }
+
+    let urgent = email.priority.important || email.priority.level == Some(PriorityLevel::High);
+    let asks = [email.body_text.as_deref(), email.snippet.as_deref()]
+        .into_iter()
+        .flatten()
+        .any(|text| text.contains('?'));
+    let mut actions = Vec::new();
+    if urgent {
+        actions.push(suggest(TriageAction::ReplyDraft, "It is marked important"));
+    } else if asks {
+        actions.push(suggest(TriageAction::ReplyDraft, "It asks a question"));
+    }
+    actions.push(suggest(
+        TriageAction::Snooze,
+        "Come back to it when there is time to deal with it",
+    ));
+    if !urgent {
+        actions.push(suggest(TriageAction::Archive, "No reply seems needed"));
+    }
+    actions
+}
+
+/// A draft replying to an email in its thread
+pub fn reply_draft(email: &EmailMessage, body: &str) -> DraftEmail {
+    let subject = email.subject.as_deref().unwrap_or_default().trim();
+    let subject = if subject.to_lowercase().starts_with("re:") {
+        subject.to_string()
+    } else {
+        format!("Re: {}", subject).trim_end().to_string()
+    };
+
+    DraftEmail {
+        to: email.from.clone().unwrap_or_default(),
+        subject,
+        body: body.to_string(),
+        cc: None,
+        bcc: None,
+        thread_id: Some(email.thread_id.clone()),
+        in_reply_to: email.rfc_message_id.clone(),
+        references: email.rfc_message_id.clone(),
+        priority: None,
+        read_receipt_to: None,
+    }
+}
+
+/// Find the next email to triage: the oldest unread email in the inbox
+/// matching the filter, or the oldest read one when none is unread. Only the
+/// most recent `TRIAGE_SCAN_LIMIT` messages are looked through.
+pub async fn next_item<G>(gmail: &G, filter: Option<&str>) -> Result<Option<TriageItem>>
+where
+    G: GmailApi + ?Sized,
+{
+    for unread in [true, false] {
+        let query = triage_query(filter, unread);
+        let ids = gmail
+            .list_message_ids(TRIAGE_SCAN_LIMIT, Some(&query))
+            .await?;
+        let Some(id) = ids.last() else {
+            debug!("Nothing to triage for '{}'", query);
+            continue;
+        };
+
+        let message = gmail.get_message_details(id).await?;
+        let count = gmail
+            .count_messages(Some(&triage_query(filter, false)), TRIAGE_COUNT_LIMIT)
+            .await?;
+        let suggested_actions = suggest_actions(&message);
+        return Ok(Some(TriageItem {
+            message,
+            unread,
+            remaining: count.count,
+            remaining_exact: count.exact,
+            suggested_actions,
+        }));
+    }
+    Ok(None)
+}
+
+/// Apply a triage action to an email. Snoozing creates a reminder in the
+/// primary calendar, so needs `calendar`.
+pub async fn apply<G, C>(
+    gmail: &G,
+    calendar: Option<&C>,
+    message_id: &str,
+    command: TriageCommand,
+) -> Result<TriageOutcome>
+where
+    G: GmailApi + ?Sized,
+    C: CalendarApi + ?Sized,
+{
+    let done = |action, archived| TriageOutcome {
+        message_id: message_id.to_string(),
+        action,
+        archived,
+        label: None,
+        label_id: None,
+        draft_id: None,
+        reminder: None,
+    };
+    let archive = vec!["INBOX".to_string(), "UNREAD".to_string()];
+
+    let outcome = match command {
+        TriageCommand::Archive => {
+            gmail.modify_labels(message_id, &[], &archive).await?;
+            done(TriageAction::Archive, true)
+        }
+        TriageCommand::Label(label) => {
+            let label_id = gmail.get_or_create_label(&label).await?;
+            gmail
+                .modify_labels(message_id, std::slice::from_ref(&label_id), &archive)
+                .await?;
+            TriageOutcome {
+                label: Some(label),
+                label_id: Some(label_id),
+                ..done(TriageAction::Label, true)
+            }
+        }
+        TriageCommand::ReplyDraft(body) => {
+            let email = gmail.get_message_details(message_id).await?;
+            let draft_id = gmail.create_draft(&reply_draft(&email, &body)).await?;
+            gmail
+                .modify_labels(message_id, &[], &["UNREAD".to_string()])
+                .await?;
+            TriageOutcome {
+                draft_id: Some(draft_id),
+                ..done(TriageAction::ReplyDraft, false)
+            }
+        }
+        TriageCommand::Snooze(until) => {
+            let calendar = calendar.ok_or_else(|| {
+                GmailApiError::ApiError("Snoozing needs a calendar for the reminder".to_string())
+            })?;
+            let request = ReminderRequest {
+                message_id: message_id.to_string(),
+                remind_at: until,
+                note: None,
+                label: Some(SNOOZE_LABEL.to_string()),
+                archive: true,
+                calendar_id: "primary".to_string(),
+            };
+            let reminder = create_reminder(gmail, calendar, request).await?;
+            TriageOutcome {
+                label: Some(reminder.label.clone()),
+                label_id: Some(reminder.label_id.clone()),
+                reminder: Some(reminder),
+                ..done(TriageAction::Snooze, true)
+            }
+        }
+    };
+
+    info!("Triaged {} with {:?}", message_id, outcome.action);
+    Ok(outcome)
+}
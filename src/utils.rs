@@ -1,8 +1,23 @@
-use crate::errors::GmailApiError;
+use crate::errors::{
+    is_insufficient_scope, CalendarApiError, ConfigError, DriveApiError, Error, GmailApiError,
+    InvalidField, LocalIndexError, MailRuleError, PeopleApiError, PolicyViolation, QuotaScope,
+    RateLimitInfo, RuleError, SavedSearchError, TasksApiError, TemplateError, ValidationError,
+};
+use crate::oauth::{
+    CALENDAR_WRITE_SCOPE, CONTACTS_WRITE_SCOPE, DRIVE_READ_SCOPE, GMAIL_SCOPE, TASKS_SCOPE,
+};
 use base64;
+use chrono::{
+    DateTime, Datelike, Duration, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc,
+    Weekday,
+};
+use chrono_tz::Tz;
 use log::{debug, error};
-use mcp_attr::{jsoncall::ErrorCode, Error as McpError};
+use mcp_attr::jsoncall::{ErrorCode, ErrorObject};
+use mcp_attr::Error as McpError;
+use serde::{Deserialize, Serialize};
 use serde_json;
+use std::collections::HashMap;
 
 // Error code constants for MCP errors
 pub mod error_codes {
@@ -21,6 +36,9 @@ pub mod error_codes {
     /// Message format/missing field errors
     pub const MESSAGE_FORMAT_ERROR: u32 = 1005;
 
+    /// The access token was not granted a scope the request needs
+    pub const SCOPE_ERROR: u32 = 1006;
+
     // Map error codes to human-readable descriptions
     pub fn get_error_description(code: u32) -> &'static str {
         match code {
@@ -28,11 +46,24 @@ pub mod error_codes {
             AUTH_ERROR => "Authentication Error: Failed to authenticate with Gmail API using the provided credentials",
             API_ERROR => "Gmail API Error: The request to the Gmail API failed",
             MESSAGE_FORMAT_ERROR => "Message Format Error: The response from Gmail API has missing or invalid fields",
+            SCOPE_ERROR => "Insufficient Scope Error: The access token was not granted a permission the request needs",
             GENERAL_ERROR => "General Error: An unspecified error occurred in the Gmail MCP server",
             _ => "Unknown Error: An unclassified error occurred",
         }
     }
 
+    // Map error codes to the category reported in structured error data
+    pub fn get_error_category(code: u32) -> &'static str {
+        match code {
+            CONFIG_ERROR => "config",
+            AUTH_ERROR => "auth",
+            API_ERROR => "api",
+            MESSAGE_FORMAT_ERROR => "message_format",
+            SCOPE_ERROR => "insufficient_scope",
+            _ => "internal",
+        }
+    }
+
     // Get detailed troubleshooting steps for each error code
     pub fn get_troubleshooting_steps(code: u32) -> &'static str {
         match code {
@@ -40,6 +71,7 @@ pub mod error_codes {
             AUTH_ERROR => "Verify your OAuth credentials. Your refresh token may have expired or been revoked. Try generating new OAuth credentials and updating your environment variables.",
             API_ERROR => "The Gmail API request failed. This could be due to API rate limits, network issues, or an invalid request. Check your internet connection and review the specific error details.",
             MESSAGE_FORMAT_ERROR => "The Gmail API returned data in an unexpected format. This may be due to changes in the API or issues with specific messages. Try with a different message ID or update the server code.",
+            SCOPE_ERROR => "Run the command in fix_command to authorize again with the missing scope, allow every permission on the consent screen, then restart the server.",
             GENERAL_ERROR => "Review server logs for more details about what went wrong. Check for any recent changes to your code or environment.",
             _ => "Check the server logs for more specific error information. Ensure all dependencies are up to date.",
         }
@@ -89,6 +121,19 @@ pub fn parse_max_results(value: Option<serde_json::Value>, default: u32) -> u32
     }
 }
 
+/// User agent sent to Google APIs. Google only compresses responses for
+/// clients whose user agent contains "gzip".
+pub const USER_AGENT: &str = "mcp-gmailcal/0.1.0 (gzip)";
+
+/// Builder for the HTTP clients that call Google APIs, with gzip and deflate
+/// response compression enabled
+pub fn api_client_builder() -> reqwest::ClientBuilder {
+    reqwest::Client::builder()
+        .gzip(true)
+        .deflate(true)
+        .user_agent(USER_AGENT)
+}
+
 /// Decode a base64 encoded string
 pub fn decode_base64(data: &str) -> Result<String, String> {
     let bytes = base64::decode(data).map_err(|e| format!("Error decoding base64: {}", e))?;
@@ -101,15 +146,1455 @@ pub fn encode_base64_url_safe(data: &[u8]) -> String {
     base64::encode_config(data, base64::URL_SAFE)
 }
 
+/// Email addresses in a comma-separated recipient list, lowercased. Both
+/// bare addresses and `Name <address>` forms are accepted.
+pub fn parse_email_addresses(recipients: &str) -> Vec<String> {
+    recipients
+        .split(',')
+        .filter_map(|recipient| {
+            let recipient = recipient.trim();
+            let address = match (recipient.rfind('<'), recipient.rfind('>')) {
+                (Some(start), Some(end)) if start < end => &recipient[start + 1..end],
+                _ => recipient,
+            };
+            let address = address.trim();
+            address.contains('@').then(|| address.to_lowercase())
+        })
+        .collect()
+}
+
+// Common words of the Latin-script languages detect_language recognizes
+const LANGUAGE_STOPWORDS: &[(&str, &[&str])] = &[
+    (
+        "en",
+        &[
+            "the", "and", "is", "are", "you", "to", "of", "that", "this", "with", "for", "have",
+            "will", "be", "not", "your", "thanks",
+        ],
+    ),
+    (
+        "es",
+        &[
+            "el", "la", "los", "las", "que", "de", "y", "es", "en", "por", "para", "con", "una",
+            "del", "usted", "gracias", "muy",
+        ],
+    ),
+    (
+        "fr",
+        &[
+            "le", "la", "les", "et", "est", "vous", "que", "de", "des", "pour", "une", "avec",
+            "dans", "pas", "merci", "nous", "je",
+        ],
+    ),
+    (
+        "de",
+        &[
+            "der", "die", "das", "und", "ist", "nicht", "sie", "ich", "mit", "für", "ein", "eine",
+            "zu", "den", "auf", "danke", "wir",
+        ],
+    ),
+    (
+        "it",
+        &[
+            "il", "la", "che", "di", "e", "per", "non", "sono", "una", "con", "del", "grazie",
+            "della", "questo", "è", "ho",
+        ],
+    ),
+    (
+        "pt",
+        &[
+            "o", "a", "os", "que", "de", "e", "não", "para", "com", "uma", "você", "obrigado",
+            "do", "da", "em", "é",
+        ],
+    ),
+    (
+        "nl",
+        &[
+            "de", "het", "een", "en", "van", "ik", "niet", "is", "dat", "op", "voor", "met",
+            "zijn", "je", "bedankt", "wij",
+        ],
+    ),
+];
+
+// Fewest letters, and common words for Latin-script text, to guess from
+const MIN_LANGUAGE_LETTERS: usize = 8;
+const MIN_LANGUAGE_STOPWORDS: usize = 2;
+
+// Characters looked at, from the start of the text
+const LANGUAGE_SAMPLE_CHARS: usize = 2000;
+
+/// Guess the language of a text as an ISO 639-1 code such as `en` or `ja`.
+/// Languages with their own script are recognized by their letters, and
+/// Latin-script languages by their most common words. Returns `None` when the
+/// text is too short or too mixed to tell.
+pub fn detect_language(text: &str) -> Option<String> {
+    let text = match text.char_indices().nth(LANGUAGE_SAMPLE_CHARS) {
+        Some((end, _)) => &text[..end],
+        None => text,
+    };
+    let mut scripts: HashMap<&str, usize> = HashMap::new();
+    let mut letters = 0;
+    for c in text.chars().filter(|c| c.is_alphabetic()) {
+        letters += 1;
+        let script = match c as u32 {
+            0x3040..=0x30FF => "kana",
+            0x1100..=0x11FF | 0x3130..=0x318F | 0xAC00..=0xD7AF => "ko",
+            0x3400..=0x4DBF | 0x4E00..=0x9FFF => "han",
+            0x0370..=0x03FF => "el",
+            0x0400..=0x04FF => "ru",
+            0x0590..=0x05FF => "he",
+            0x0600..=0x06FF => "ar",
+            0x0900..=0x097F => "hi",
+            0x0E00..=0x0E7F => "th",
+            _ => "latin",
+        };
+        *scripts.entry(script).or_insert(0) += 1;
+    }
+    if letters < MIN_LANGUAGE_LETTERS {
+        return None;
+    }
+
+    // Japanese mixes kana with Chinese characters; Chinese has no kana
+    let kana = scripts.remove("kana").unwrap_or(0);
+    if kana > 0 {
+        *scripts.entry("ja").or_insert(0) += kana + scripts.remove("han").unwrap_or(0);
+    } else if let Some(han) = scripts.remove("han") {
+        scripts.insert("zh", han);
+    }
+    let (script, count) = scripts.into_iter().max_by_key(|(_, count)| *count)?;
+    if script != "latin" {
+        return (count * 2 > letters).then(|| script.to_string());
+    }
+
+    let lowercase = text.to_lowercase();
+    let words: Vec<&str> = lowercase
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|word| !word.is_empty())
+        .collect();
+    let mut scores: Vec<(&str, usize)> = LANGUAGE_STOPWORDS
+        .iter()
+        .map(|(language, stopwords)| {
+            let hits = words.iter().filter(|word| stopwords.contains(word)).count();
+            (*language, hits)
+        })
+        .collect();
+    scores.sort_by_key(|(_, hits)| std::cmp::Reverse(*hits));
+    match scores.as_slice() {
+        [(language, best), (_, second), ..] if *best >= MIN_LANGUAGE_STOPWORDS && best > second => {
+            Some(language.to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Hosts of click-tracking services that log a click before forwarding it
+const TRACKER_HOSTS: &[&str] = &[
+    "list-manage.com",
+    "sendgrid.net",
+    "mandrillapp.com",
+    "mailgun.org",
+    "hubspotlinks.com",
+    "mjt.lu",
+    "rs6.net",
+    "exct.net",
+    "mcsv.net",
+    "awstrack.me",
+    "sparkpostmail.com",
+    "customeriomail.com",
+];
+
+/// Query parameters that identify a campaign or recipient
+const TRACKING_PARAMS: &[&str] = &[
+    "utm_source",
+    "utm_medium",
+    "utm_campaign",
+    "utm_term",
+    "utm_content",
+    "mc_cid",
+    "mc_eid",
+    "fbclid",
+    "gclid",
+    "_hsenc",
+    "_hsmi",
+    "mkt_tok",
+];
+
+/// Query parameters that redirectors pass the destination in
+const REDIRECT_PARAMS: &[&str] = &[
+    "url",
+    "u",
+    "q",
+    "redirect",
+    "redirect_url",
+    "target",
+    "dest",
+    "destination",
+    "r",
+    "to",
+    "link",
+];
+
+/// A link found in an email body
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExtractedLink {
+    pub url: String,
+    /// Host the link leads to, lowercased and without any `www.`
+    pub domain: Option<String>,
+    /// Visible text of the first HTML anchor with this link
+    pub anchor_text: Option<String>,
+    /// Whether the link goes through a click-tracking service or carries
+    /// campaign tracking parameters
+    pub is_tracker: bool,
+    /// Whether the link forwards to another URL given in its query
+    pub is_redirect: bool,
+    /// Where a redirect forwards to, when it says
+    pub redirect_target: Option<String>,
+}
+
+impl ExtractedLink {
+    fn new(url: String, anchor_text: Option<String>) -> Self {
+        let parsed = url::Url::parse(&url).ok();
+        let domain = parsed
+            .as_ref()
+            .and_then(|parsed| parsed.host_str())
+            .map(|host| host.trim_start_matches("www.").to_lowercase());
+        let query: Vec<(String, String)> = parsed
+            .as_ref()
+            .map(|parsed| parsed.query_pairs().into_owned().collect())
+            .unwrap_or_default();
+
+        let is_tracker = domain.as_deref().is_some_and(is_tracker_domain)
+            || query
+                .iter()
+                .any(|(key, _)| TRACKING_PARAMS.contains(&key.to_lowercase().as_str()));
+        let redirect_target = query
+            .iter()
+            .find(|(key, value)| {
+                REDIRECT_PARAMS.contains(&key.to_lowercase().as_str())
+                    && (value.starts_with("http://") || value.starts_with("https://"))
+            })
+            .map(|(_, value)| value.clone());
+
+        Self {
+            url,
+            domain,
+            anchor_text: anchor_text.filter(|text| !text.is_empty()),
+            is_tracker,
+            is_redirect: redirect_target.is_some(),
+            redirect_target,
+        }
+    }
+}
+
+// Whether a domain belongs to a click-tracking service
+fn is_tracker_domain(domain: &str) -> bool {
+    TRACKER_HOSTS
+        .iter()
+        .any(|host| domain == *host || domain.ends_with(&format!(".{}", host)))
+}
+
+// Links in plain text, up to the first character that cannot be part of one.
+// Lowercasing only ASCII keeps byte offsets the same in both copies of the
+// text.
+fn find_urls(text: &str) -> Vec<String> {
+    let lower = text.to_ascii_lowercase();
+    let mut urls = Vec::new();
+    let mut start = 0;
+    while let Some(found) = lower[start..]
+        .find("http://")
+        .into_iter()
+        .chain(lower[start..].find("https://"))
+        .min()
+    {
+        let begin = start + found;
+        let end = text[begin..]
+            .find(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '<' | '>' | ')'))
+            .map_or(text.len(), |offset| begin + offset);
+        // Sentence punctuation after a link is not part of it
+        let url = text[begin..end].trim_end_matches(['.', ',', ';', ':', '!', '?']);
+        urls.push(url.to_string());
+        start = end;
+    }
+    urls
+}
+
+// Anchors in HTML as (href, visible text) pairs
+fn find_anchors(html: &str) -> Vec<(String, String)> {
+    let lower = html.to_ascii_lowercase();
+    let mut anchors = Vec::new();
+    let mut start = 0;
+    while let Some(found) = lower[start..].find("<a ") {
+        let tag_start = start + found;
+        let Some(tag_end) = lower[tag_start..].find('>').map(|i| tag_start + i) else {
+            break;
+        };
+        let Some(close) = lower[tag_end..].find("</a").map(|i| tag_end + i) else {
+            break;
+        };
+        let tag = &html[tag_start..tag_end];
+        let href = lower[tag_start..tag_end].find("href=").and_then(|i| {
+            let value = &tag[i + 5..];
+            let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+            value[1..].split(quote).next().map(String::from)
+        });
+        if let Some(href) = href {
+            let text = strip_tags(&html[tag_end + 1..close]);
+            anchors.push((
+                decode_html_entities(&href),
+                decode_html_entities(text.trim()),
+            ));
+        }
+        start = close;
+    }
+    anchors
+}
+
+fn strip_tags(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text
+}
+
+/// Named character references decoded in link URLs and text: those that
+/// stand for punctuation, which can hide a URL's scheme, and those common in
+/// email text. Others are left as written.
+const NAMED_ENTITIES: &[(&str, char)] = &[
+    ("amp", '&'),
+    ("lt", '<'),
+    ("gt", '>'),
+    ("quot", '"'),
+    ("apos", '\''),
+    ("nbsp", ' '),
+    ("colon", ':'),
+    ("Tab", '\t'),
+    ("NewLine", '\n'),
+    ("sol", '/'),
+    ("bsol", '\\'),
+    ("lpar", '('),
+    ("rpar", ')'),
+    ("lsqb", '['),
+    ("rsqb", ']'),
+    ("lcub", '{'),
+    ("rcub", '}'),
+    ("period", '.'),
+    ("comma", ','),
+    ("semi", ';'),
+    ("excl", '!'),
+    ("quest", '?'),
+    ("num", '#'),
+    ("percnt", '%'),
+    ("equals", '='),
+    ("plus", '+'),
+    ("hyphen", '-'),
+    ("dash", '-'),
+    ("lowbar", '_'),
+    ("commat", '@'),
+    ("verbar", '|'),
+    ("grave", '`'),
+    ("Hat", '^'),
+    ("ast", '*'),
+    ("dollar", '$'),
+    ("copy", '©'),
+    ("reg", '®'),
+    ("trade", '™'),
+    ("hellip", '…'),
+    ("ndash", '–'),
+    ("mdash", '—'),
+    ("lsquo", '‘'),
+    ("rsquo", '’'),
+    ("ldquo", '“'),
+    ("rdquo", '”'),
+    ("bull", '•'),
+    ("middot", '·'),
+    ("euro", '€'),
+];
+
+/// Decode the character references in link URLs and text: numeric ones such
+/// as `&#106;` and `&#x3a;`, which browsers also read without the closing
+/// `;`, and the named ones in `NAMED_ENTITIES`
+pub(crate) fn decode_html_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match decode_entity(after) {
+            Some((character, len)) => {
+                decoded.push(character);
+                rest = &after[len..];
+            }
+            None => {
+                decoded.push('&');
+                rest = after;
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+// The character the reference starting `after` its `&` stands for, and how
+// much of `after` it takes up
+fn decode_entity(after: &str) -> Option<(char, usize)> {
+    if let Some(numeric) = after.strip_prefix('#') {
+        let (digits, radix, prefix) = match numeric.strip_prefix(['x', 'X']) {
+            Some(hex) => (hex, 16, 2),
+            None => (numeric, 10, 1),
+        };
+        let len = digits
+            .find(|c: char| !c.is_digit(radix))
+            .unwrap_or(digits.len());
+        let character = u32::from_str_radix(&digits[..len], radix)
+            .ok()
+            .and_then(char::from_u32)?;
+        let semicolon = usize::from(digits[len..].starts_with(';'));
+        return Some((character, prefix + len + semicolon));
+    }
+    let len = after
+        .find(|c: char| !c.is_ascii_alphanumeric())
+        .unwrap_or(after.len());
+    if !after[len..].starts_with(';') {
+        return None;
+    }
+    NAMED_ENTITIES
+        .iter()
+        .find(|(name, _)| *name == &after[..len])
+        .map(|(_, character)| (*character, len + 1))
+}
+
+/// Web links in an email body, once each in the order they appear: first the
+/// anchors of the HTML body, then any links in the plain text body not
+/// already found there
+pub fn extract_links(body_html: Option<&str>, body_text: Option<&str>) -> Vec<ExtractedLink> {
+    let anchors = body_html.map(find_anchors).unwrap_or_default();
+    let text_urls = body_text.map(find_urls).unwrap_or_default();
+
+    let mut seen = std::collections::HashSet::new();
+    anchors
+        .into_iter()
+        .filter(|(href, _)| {
+            let href = href.to_ascii_lowercase();
+            href.starts_with("http://") || href.starts_with("https://")
+        })
+        .map(|(href, text)| (href, Some(text)))
+        .chain(text_urls.into_iter().map(|url| (url, None)))
+        .filter(|(url, _)| seen.insert(url.clone()))
+        .map(|(url, anchor_text)| ExtractedLink::new(url, anchor_text))
+        .collect()
+}
+
+/// Elements removed from sanitized HTML along with their content. SVG and
+/// MathML go entirely: markup inside them is parsed differently, so what
+/// looks like style sheet text can become elements, and their animations can
+/// set any attribute.
+const UNSAFE_ELEMENTS: &[&str] = &[
+    "script",
+    "noscript",
+    "iframe",
+    "frame",
+    "frameset",
+    "object",
+    "embed",
+    "applet",
+    "template",
+    "noembed",
+    "noframes",
+    "xmp",
+    "plaintext",
+    "svg",
+    "math",
+    "animate",
+    "animatemotion",
+    "animatetransform",
+    "set",
+    "foreignobject",
+];
+
+/// Elements removed from sanitized HTML, keeping their content
+const UNSAFE_TAGS: &[&str] = &[
+    "base", "meta", "link", "form", "input", "button", "select", "textarea",
+];
+
+/// Attributes kept in sanitized HTML, along with `aria-` ones; any other is
+/// dropped
+const ALLOWED_ATTRIBUTES: &[&str] = &[
+    "abbr",
+    "align",
+    "alt",
+    "background",
+    "bgcolor",
+    "border",
+    "cellpadding",
+    "cellspacing",
+    "cite",
+    "class",
+    "color",
+    "cols",
+    "colspan",
+    "datetime",
+    "dir",
+    "face",
+    "headers",
+    "height",
+    "href",
+    "hreflang",
+    "hspace",
+    "id",
+    "lang",
+    "nowrap",
+    "rel",
+    "reversed",
+    "role",
+    "rows",
+    "rowspan",
+    "rules",
+    "scope",
+    "size",
+    "span",
+    "src",
+    "start",
+    "style",
+    "summary",
+    "target",
+    "title",
+    "type",
+    "valign",
+    "vspace",
+    "width",
+];
+
+/// Allowed attributes holding a URL, which may only use a safe scheme
+const URL_ATTRIBUTES: &[&str] = &["href", "src", "background", "cite"];
+
+/// URL schemes allowed in sanitized HTML; relative URLs are allowed too
+const SAFE_URL_SCHEMES: &[&str] = &["http", "https", "mailto", "tel", "cid"];
+
+/// Make an HTML email body safe to display: scripts, embedded content, SVG
+/// and MathML are removed with their content, attributes other than the
+/// allowed presentational ones are dropped, along with links using other
+/// schemes than web, mail and phone ones, remote CSS, and tracking pixels (remote images that are hidden, at most a pixel
+/// in size, or served by a tracking service). Everything else, including
+/// ordinary remote images, is kept.
+pub fn sanitize_html(html: &str) -> String {
+    let lower = html.to_ascii_lowercase();
+    let mut sanitized = String::with_capacity(html.len());
+    let mut pos = 0;
+    while let Some(found) = html[pos..].find('<') {
+        let tag_start = pos + found;
+        sanitized.push_str(&html[pos..tag_start]);
+        let rest = &lower[tag_start + 1..];
+
+        // Comments can hide markup that some clients act on, so they go
+        // entirely, as do processing instructions and CDATA
+        if let Some(comment) = rest.strip_prefix("!--") {
+            pos = comment
+                .find("-->")
+                .map_or(html.len(), |i| tag_start + 4 + i + 3);
+            continue;
+        }
+        if !rest.starts_with(|c: char| c.is_ascii_alphabetic() || matches!(c, '/' | '!' | '?')) {
+            // A `<` in text rather than a tag
+            sanitized.push_str("&lt;");
+            pos = tag_start + 1;
+            continue;
+        }
+        let Some(tag_end) = find_tag_end(html, tag_start) else {
+            // An unfinished tag at the end is dropped
+            pos = html.len();
+            break;
+        };
+        pos = tag_end + 1;
+        if rest.starts_with('!') || rest.starts_with('?') {
+            if rest.starts_with("!doctype") {
+                sanitized.push_str(&html[tag_start..pos]);
+            }
+            continue;
+        }
+
+        let tag = parse_tag(&html[tag_start + 1..tag_end]);
+        if !is_markup_name(&tag.name) {
+            continue;
+        }
+        if UNSAFE_ELEMENTS.contains(&tag.name.as_str()) {
+            if !tag.closing && !tag.self_closing {
+                // Skip to the end of the element
+                let close = format!("</{}", tag.name);
+                pos = lower[pos..]
+                    .find(&close)
+                    .and_then(|i| lower[pos + i..].find('>').map(|j| pos + i + j + 1))
+                    .unwrap_or(html.len());
+            }
+            continue;
+        }
+        if UNSAFE_TAGS.contains(&tag.name.as_str()) {
+            continue;
+        }
+        if tag.closing {
+            sanitized.push_str(&format!("</{}>", tag.name));
+            continue;
+        }
+        if tag.name == "img" && tracking_pixel_reason(&tag.attributes).is_some() {
+            continue;
+        }
+
+        write_tag(&mut sanitized, &tag);
+        if tag.name == "style" && !tag.self_closing {
+            // Style sheets are kept without anything they would load, and
+            // without a `<` that could be read as markup
+            let end = lower[pos..].find("</style").map_or(html.len(), |i| pos + i);
+            sanitized.push_str(&neutralize_css(&html[pos..end]).replace('<', "\\3c "));
+            pos = end;
+        }
+    }
+    sanitized.push_str(&html[pos..]);
+    sanitized
+}
+
+/// A start or end tag, with its attribute values as written
+pub(crate) struct HtmlTag {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub closing: bool,
+    pub self_closing: bool,
+}
+
+impl HtmlTag {
+    /// The value of an attribute, as written
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Where the `>` ending the tag starting at `start` is, skipping any inside
+/// quoted attribute values
+pub(crate) fn find_tag_end(html: &str, start: usize) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in html[start..].char_indices() {
+        match (quote, c) {
+            (Some(open), _) if c == open => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return Some(start + i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Parse the inside of a tag, between its `<` and `>`
+pub(crate) fn parse_tag(inner: &str) -> HtmlTag {
+    let closing = inner.starts_with('/');
+    let inner = inner.trim_start_matches('/');
+    let self_closing = inner.trim_end().ends_with('/');
+    let name_end = inner
+        .find(|c: char| c.is_whitespace() || c == '/')
+        .unwrap_or(inner.len());
+    let name = inner[..name_end].to_ascii_lowercase();
+
+    let mut attributes = Vec::new();
+    let mut rest = inner[name_end..].trim_start_matches(|c: char| c.is_whitespace() || c == '/');
+    while !rest.is_empty() {
+        let key_end = rest
+            .find(|c: char| c.is_whitespace() || c == '=' || c == '/')
+            .unwrap_or(rest.len());
+        let key = rest[..key_end].to_ascii_lowercase();
+        rest = rest[key_end..].trim_start();
+        let mut value = String::new();
+        if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            let (raw, remaining) = match after.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let end = after[1..].find(quote).map_or(after.len(), |i| i + 1);
+                    (&after[1..end], after.get(end + 1..).unwrap_or(""))
+                }
+                _ => {
+                    let end = after.find(char::is_whitespace).unwrap_or(after.len());
+                    (&after[..end], &after[end..])
+                }
+            };
+            value = raw.to_string();
+            rest = remaining;
+        }
+        if is_markup_name(&key) {
+            attributes.push((key, value));
+        }
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '/');
+    }
+
+    HtmlTag {
+        name,
+        attributes,
+        closing,
+        self_closing,
+    }
+}
+
+// Whether a tag or attribute name is one a browser reads as written
+fn is_markup_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':' | '.'))
+}
+
+// Write a start tag with only its safe attributes
+fn write_tag(out: &mut String, tag: &HtmlTag) {
+    out.push('<');
+    out.push_str(&tag.name);
+    for (key, value) in &tag.attributes {
+        if !ALLOWED_ATTRIBUTES.contains(&key.as_str()) && !key.starts_with("aria-") {
+            continue;
+        }
+        let value = if URL_ATTRIBUTES.contains(&key.as_str()) {
+            let image = tag.name == "img" && key == "src";
+            if !is_safe_url(value, image) {
+                continue;
+            }
+            value.clone()
+        } else if key == "style" {
+            neutralize_css(value)
+        } else {
+            value.clone()
+        };
+        out.push_str(&format!(" {}=\"{}\"", key, value.replace('"', "&quot;")));
+    }
+    if tag.self_closing {
+        out.push_str(" /");
+    }
+    out.push('>');
+}
+
+// An attribute value as a browser reads it for a URL: entities decoded,
+// whitespace and control characters dropped, and lowercased
+fn normalized_url(value: &str) -> String {
+    decode_html_entities(value)
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+/// Decode numeric character references such as `&#106;` and `&#x09;`, which
+/// browsers also read without the closing `;`
+pub(crate) fn decode_numeric_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("&#") {
+        decoded.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let (digits, radix) = match after.strip_prefix(['x', 'X']) {
+            Some(hex) => (hex, 16),
+            None => (after, 10),
+        };
+        let len = digits
+            .find(|c: char| !c.is_digit(radix))
+            .unwrap_or(digits.len());
+        let character = u32::from_str_radix(&digits[..len], radix)
+            .ok()
+            .and_then(char::from_u32);
+        match character {
+            Some(character) => {
+                decoded.push(character);
+                let end = &digits[len..];
+                rest = end.strip_prefix(';').unwrap_or(end);
+            }
+            None => {
+                decoded.push_str("&#");
+                rest = after;
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Whether a URL is relative or uses a safe scheme. Images may also be
+/// embedded as `data:` URIs.
+pub(crate) fn is_safe_url(value: &str, image: bool) -> bool {
+    let url = normalized_url(value);
+    // A reference left undecoded could stand for the scheme's colon
+    let before_path = url.split(['/', '?', '#']).next().unwrap_or("");
+    if before_path.contains('&') {
+        return false;
+    }
+    let Some(colon) = url.find(':') else {
+        return true;
+    };
+    let scheme = &url[..colon];
+    if scheme.contains(['/', '?', '#']) {
+        return true;
+    }
+    SAFE_URL_SCHEMES.contains(&scheme) || (image && url.starts_with("data:image/"))
+}
+
+/// Whether a URL loads content from another server
+pub(crate) fn is_remote_url(value: &str) -> bool {
+    let url = normalized_url(value);
+    url.starts_with("http:") || url.starts_with("https:") || url.starts_with("//")
+}
+
+// Why an image is a remote tracking pixel, if it is one: it is hidden, at
+// most a pixel in either direction, or served by a tracking service
+fn tracking_pixel_reason(attributes: &[(String, String)]) -> Option<&'static str> {
+    let attribute = |name: &str| {
+        attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    };
+    let src = attribute("src").filter(|src| is_remote_url(src))?;
+    let tiny = |value: &str| {
+        value
+            .trim()
+            .trim_end_matches("px")
+            .trim()
+            .parse::<f64>()
+            .is_ok_and(|size| size <= 1.0)
+    };
+    if attribute("width").is_some_and(tiny) || attribute("height").is_some_and(tiny) {
+        return Some("tiny image");
+    }
+
+    let style = attribute("style").unwrap_or("").to_ascii_lowercase();
+    let hidden = style.split(';').any(|declaration| {
+        let Some((property, value)) = declaration.split_once(':') else {
+            return false;
+        };
+        let value = value.replace("!important", "");
+        match property.trim() {
+            "display" => value.trim() == "none",
+            "visibility" => value.trim() == "hidden",
+            "opacity" => value
+                .trim()
+                .parse::<f64>()
+                .is_ok_and(|opacity| opacity == 0.0),
+            "width" | "height" | "max-width" | "max-height" => tiny(&value),
+            _ => false,
+        }
+    });
+    if hidden {
+        return Some("hidden image");
+    }
+
+    url_domain(&absolute_url(src))
+        .is_some_and(|domain| is_tracker_domain(&domain))
+        .then_some("tracking service")
+}
+
+// A remote URL from an attribute, with entities decoded and a scheme added to
+// a protocol-relative one
+fn absolute_url(value: &str) -> String {
+    let url = decode_html_entities(value.trim());
+    match url.strip_prefix("//") {
+        Some(rest) => format!("https://{}", rest),
+        None => url,
+    }
+}
+
+// The host a URL leads to, lowercased and without any `www.`
+fn url_domain(url: &str) -> Option<String> {
+    url::Url::parse(url).ok().and_then(|url| {
+        url.host_str()
+            .map(|host| host.trim_start_matches("www.").to_lowercase())
+    })
+}
+
+/// Which kind of tracker was found in an email
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackerKind {
+    /// An image that tells the sender when the email is opened
+    Pixel,
+    /// A link that tells the sender when it is clicked
+    Link,
+}
+
+/// A tracking pixel or tracked link found in an email
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tracker {
+    pub kind: TrackerKind,
+    pub url: String,
+    /// Host the tracker reports to, lowercased and without any `www.`
+    pub domain: Option<String>,
+    /// Why it was flagged: "tiny image", "hidden image", "tracking service"
+    /// or "tracking parameters"
+    pub reason: String,
+}
+
+/// Tracking pixels in the HTML body and tracked links in either body, once
+/// each: pixels first in the order they appear, then links
+pub fn find_trackers(body_html: Option<&str>, body_text: Option<&str>) -> Vec<Tracker> {
+    let mut trackers: Vec<Tracker> = Vec::new();
+    for attributes in body_html.map(find_image_attributes).unwrap_or_default() {
+        let Some(reason) = tracking_pixel_reason(&attributes) else {
+            continue;
+        };
+        let Some((_, src)) = attributes.into_iter().find(|(key, _)| key == "src") else {
+            continue;
+        };
+        let url = absolute_url(&src);
+        if trackers.iter().all(|tracker| tracker.url != url) {
+            trackers.push(Tracker {
+                kind: TrackerKind::Pixel,
+                domain: url_domain(&url),
+                url,
+                reason: reason.to_string(),
+            });
+        }
+    }
+
+    for link in extract_links(body_html, body_text) {
+        if !link.is_tracker {
+            continue;
+        }
+        let service = link.domain.as_deref().is_some_and(is_tracker_domain);
+        trackers.push(Tracker {
+            kind: TrackerKind::Link,
+            url: link.url,
+            domain: link.domain,
+            reason: if service {
+                "tracking service"
+            } else {
+                "tracking parameters"
+            }
+            .to_string(),
+        });
+    }
+    trackers
+}
+
+// The attributes of each `<img>` tag in HTML
+fn find_image_attributes(html: &str) -> Vec<Vec<(String, String)>> {
+    let lower = html.to_ascii_lowercase();
+    let mut images = Vec::new();
+    let mut pos = 0;
+    while let Some(found) = lower[pos..].find("<img") {
+        let tag_start = pos + found;
+        let Some(tag_end) = find_tag_end(html, tag_start) else {
+            break;
+        };
+        let tag = parse_tag(&html[tag_start + 1..tag_end]);
+        if tag.name == "img" {
+            images.push(tag.attributes);
+        }
+        pos = tag_end + 1;
+    }
+    images
+}
+
+// CSS without remote `url()` references, imports or expressions, which load
+// content from the sender or run script in old browsers
+fn neutralize_css(css: &str) -> String {
+    let lower = css.to_ascii_lowercase();
+    let mut neutralized = String::with_capacity(css.len());
+    let mut pos = 0;
+    loop {
+        let next = ["url(", "@import", "expression("]
+            .iter()
+            .filter_map(|pattern| lower[pos..].find(pattern).map(|i| (pos + i, *pattern)))
+            .min();
+        let Some((start, pattern)) = next else {
+            break;
+        };
+        neutralized.push_str(&css[pos..start]);
+        match pattern {
+            "url(" => {
+                let close = lower[start..].find(')').map(|i| start + i);
+                let target = &css[start + 4..close.unwrap_or(css.len())];
+                let target = decode_html_entities(target);
+                let end = close.map_or(css.len(), |close| close + 1);
+                if is_remote_url(target.trim().trim_matches(['"', '\''])) {
+                    neutralized.push_str("none");
+                } else {
+                    neutralized.push_str(&css[start..end]);
+                }
+                pos = end;
+            }
+            "@import" => {
+                pos = lower[start..]
+                    .find(';')
+                    .map_or(css.len(), |i| start + i + 1);
+            }
+            _ => {
+                neutralized.push_str("none(");
+                pos = start + pattern.len();
+            }
+        }
+    }
+    neutralized.push_str(&css[pos..]);
+    neutralized
+}
+
+/// Lines that start the original message in Outlook-style replies, which
+/// quote it in full without `>` marks
+const ORIGINAL_MESSAGE_MARKERS: &[&str] =
+    &["-----original message-----", "----- original message -----"];
+
+/// The part of an email body its author wrote, without the earlier messages
+/// a reply quotes: `>` quoted lines, the "On ... wrote:" line introducing
+/// them, and everything from an Outlook-style original message on
+pub fn strip_quoted_text(body: &str) -> String {
+    let lines: Vec<&str> = body.lines().collect();
+    // Whether quoted lines come next, after any blank lines
+    let quote_follows = |from: usize| {
+        lines[from.min(lines.len())..]
+            .iter()
+            .find(|line| !line.trim().is_empty())
+            .is_none_or(|line| line.trim_start().starts_with('>'))
+    };
+
+    let mut kept = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].trim();
+        let outlook_header = line.len() >= 20
+            && line.chars().all(|c| c == '_')
+            && lines
+                .get(i + 1)
+                .is_some_and(|next| next.trim_start().starts_with("From:"));
+        if ORIGINAL_MESSAGE_MARKERS.contains(&line.to_lowercase().as_str()) || outlook_header {
+            break;
+        }
+        if line.starts_with('>') {
+            i += 1;
+            continue;
+        }
+        // Long attribution lines are often wrapped onto a second line
+        if line.starts_with("On ") {
+            if line.ends_with("wrote:") && quote_follows(i + 1) {
+                i += 1;
+                continue;
+            }
+            if lines
+                .get(i + 1)
+                .is_some_and(|next| next.trim().ends_with("wrote:"))
+                && quote_follows(i + 2)
+            {
+                i += 2;
+                continue;
+            }
+        }
+        kept.push(lines[i]);
+        i += 1;
+    }
+    kept.join("\n").trim().to_string()
+}
+
+/// Sign-offs that close an email body before the sender's signature
+const SIGN_OFFS: &[&str] = &[
+    "all the best",
+    "best",
+    "best regards",
+    "best wishes",
+    "cheers",
+    "kind regards",
+    "many thanks",
+    "regards",
+    "sincerely",
+    "thank you",
+    "thanks",
+    "warm regards",
+    "warmly",
+    "yours sincerely",
+];
+
+/// Most lines kept of a signature
+const MAX_SIGNATURE_LINES: usize = 10;
+
+/// The signature at the end of an email body: the lines after a "-- "
+/// delimiter, or else those after the last sign-off such as "Best regards,".
+/// Quoted earlier mail should be removed first with `strip_quoted_text`.
+pub fn extract_signature(body: &str) -> Option<String> {
+    let lines: Vec<&str> = body.lines().map(str::trim_end).collect();
+    let start = match lines.iter().rposition(|line| *line == "--") {
+        Some(delimiter) => delimiter + 1,
+        None => {
+            let sign_off = lines.iter().rposition(|line| {
+                let line = line.trim().trim_end_matches([',', '.', '!']).to_lowercase();
+                SIGN_OFFS.contains(&line.as_str())
+            })?;
+            sign_off + 1
+        }
+    };
+    let signature: Vec<&str> = lines[start..]
+        .iter()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .take(MAX_SIGNATURE_LINES)
+        .collect();
+    (!signature.is_empty()).then(|| signature.join("\n"))
+}
+
+/// Local date and time formats accepted besides RFC 3339
+const LOCAL_DATE_TIME_FORMATS: &[&str] = &[
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M",
+];
+
+/// Parse a date given in RFC 3339, as a local date and time such as
+/// "2026-10-15 09:30", or in words such as "tomorrow 3pm", "next Monday",
+/// "friday at noon", "in 2 hours" or "3 days ago". Dates and times in words
+/// are read in `time_zone` relative to `now`; a date without a time means
+/// the start of that day, a time without a date means today. A plain weekday
+/// is the next such day, today included, while "next" skips today.
+pub fn parse_date_expression(
+    input: &str,
+    now: DateTime<Utc>,
+    time_zone: Tz,
+) -> Result<DateTime<Utc>, String> {
+    let input = input.trim();
+    if let Ok(time) = DateTime::parse_from_rfc3339(input) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    let invalid = || {
+        format!(
+            "Invalid date '{}'; use RFC 3339 or a phrase such as 'tomorrow 3pm' or 'next Monday'",
+            input
+        )
+    };
+    if let Some(local) = LOCAL_DATE_TIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(input, format).ok())
+    {
+        return local_to_utc(local, time_zone, input);
+    }
+
+    let lower = input.to_lowercase();
+    let words: Vec<&str> = lower
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|word| !word.is_empty() && *word != "at" && *word != "on")
+        .collect();
+    if words.as_slice() == ["now"] {
+        return Ok(now);
+    }
+    if let Some(offset) = parse_relative_offset(&words) {
+        return now.checked_add_signed(offset).ok_or_else(invalid);
+    }
+
+    let today = now.with_timezone(&time_zone).date_naive();
+    let mut date: Option<NaiveDate> = None;
+    let mut time: Option<NaiveTime> = None;
+    let mut i = 0;
+    while i < words.len() {
+        let word = words[i];
+        let next = words.get(i + 1).copied();
+        let (parsed_date, parsed_time, used) = match word {
+            "today" => (Some(today), None, 1),
+            "tomorrow" => (Some(today + Duration::days(1)), None, 1),
+            "yesterday" => (Some(today - Duration::days(1)), None, 1),
+            "noon" | "midday" => (None, NaiveTime::from_hms_opt(12, 0, 0), 1),
+            "midnight" => (None, Some(NaiveTime::MIN), 1),
+            "next" | "this" | "last" => match next {
+                Some("week") => {
+                    let monday =
+                        today - Duration::days(today.weekday().num_days_from_monday() as i64);
+                    let weeks = match word {
+                        "next" => 1,
+                        "last" => -1,
+                        _ => 0,
+                    };
+                    (Some(monday + Duration::weeks(weeks)), None, 2)
+                }
+                Some(day) => {
+                    let weekday = day.parse::<Weekday>().map_err(|_| invalid())?;
+                    let ahead = (weekday.num_days_from_monday() as i64
+                        - today.weekday().num_days_from_monday() as i64)
+                        .rem_euclid(7);
+                    let days = match word {
+                        "next" if ahead == 0 => 7,
+                        "last" => ahead - 7,
+                        _ => ahead,
+                    };
+                    (Some(today + Duration::days(days)), None, 2)
+                }
+                None => return Err(invalid()),
+            },
+            _ => {
+                if let Ok(weekday) = word.parse::<Weekday>() {
+                    let ahead = (weekday.num_days_from_monday() as i64
+                        - today.weekday().num_days_from_monday() as i64)
+                        .rem_euclid(7);
+                    (Some(today + Duration::days(ahead)), None, 1)
+                } else if let Ok(day) = NaiveDate::parse_from_str(word, "%Y-%m-%d") {
+                    (Some(day), None, 1)
+                } else if let Some(parsed) = next.and_then(|suffix| parse_clock_time(word, suffix))
+                {
+                    // A time with its "am" or "pm" as a separate word
+                    (None, Some(parsed), 2)
+                } else {
+                    let parsed = parse_clock_time(word, "").ok_or_else(invalid)?;
+                    (None, Some(parsed), 1)
+                }
+            }
+        };
+        if (parsed_date.is_some() && date.is_some()) || (parsed_time.is_some() && time.is_some()) {
+            return Err(invalid());
+        }
+        date = date.or(parsed_date);
+        time = time.or(parsed_time);
+        i += used;
+    }
+    if date.is_none() && time.is_none() {
+        return Err(invalid());
+    }
+
+    let local = date
+        .unwrap_or(today)
+        .and_time(time.unwrap_or(NaiveTime::MIN));
+    local_to_utc(local, time_zone, input)
+}
+
+/// An offset such as "in 2 hours" or "3 days ago", None as well when it is
+/// too large to be a duration
+fn parse_relative_offset(words: &[&str]) -> Option<Duration> {
+    let (amount, unit, sign) = match words {
+        ["in", amount, unit] => (amount, unit, 1),
+        [amount, unit, "ago"] => (amount, unit, -1),
+        _ => return None,
+    };
+    let amount = amount.parse::<i64>().ok()?.checked_mul(sign)?;
+    match *unit {
+        "minute" | "minutes" | "min" | "mins" => Duration::try_minutes(amount),
+        "hour" | "hours" => Duration::try_hours(amount),
+        "day" | "days" => Duration::try_days(amount),
+        "week" | "weeks" => Duration::try_weeks(amount),
+        _ => None,
+    }
+}
+
+/// Parse a time of day such as "15:00", "3pm", "3 pm" or "9:30am"
+pub fn parse_time_of_day(input: &str) -> Option<NaiveTime> {
+    let input = input.trim().to_lowercase();
+    match input.split_whitespace().collect::<Vec<_>>().as_slice() {
+        ["noon"] | ["midday"] => NaiveTime::from_hms_opt(12, 0, 0),
+        ["midnight"] => Some(NaiveTime::MIN),
+        [time] => parse_clock_time(time, ""),
+        [time, suffix] => parse_clock_time(time, suffix),
+        _ => None,
+    }
+}
+
+/// A time of day such as "15:00", "3pm" or "9:30am", with `suffix` holding
+/// "am" or "pm" when written as a separate word
+fn parse_clock_time(word: &str, suffix: &str) -> Option<NaiveTime> {
+    let (clock, meridiem) = match suffix {
+        "am" | "pm" => (word, Some(suffix)),
+        "" => match word.strip_suffix("am").or_else(|| word.strip_suffix("pm")) {
+            Some(clock) => (clock, Some(&word[clock.len()..])),
+            None => (word, None),
+        },
+        _ => return None,
+    };
+    let (hour, minute) = match clock.split_once(':') {
+        Some((hour, minute)) if minute.len() == 2 => {
+            (hour.parse::<u32>().ok()?, minute.parse().ok()?)
+        }
+        Some(_) => return None,
+        None if meridiem.is_some() => (clock.parse::<u32>().ok()?, 0),
+        None => return None,
+    };
+    let hour = match meridiem {
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some("am") => hour % 12,
+        Some(_) => hour % 12 + 12,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+/// The moment a local time in `time_zone` falls on, taking the first when
+/// clocks going back make it happen twice
+pub(crate) fn local_to_utc(
+    local: NaiveDateTime,
+    time_zone: Tz,
+    input: &str,
+) -> Result<DateTime<Utc>, String> {
+    match time_zone.from_local_datetime(&local) {
+        LocalResult::Single(time) | LocalResult::Ambiguous(time, _) => Ok(time.with_timezone(&Utc)),
+        LocalResult::None => Err(format!(
+            "'{}' does not exist in {} as clocks go forward",
+            input, time_zone
+        )),
+    }
+}
+
+/// Gmail search term for mail newer or older than an age such as "7d",
+/// "2w", "3m" or "1y", in days, weeks, months or years. `operator` is
+/// "newer_than" or "older_than"; weeks are given to Gmail as days since it
+/// has no unit for them.
+pub fn relative_time_term(operator: &str, age: &str) -> Result<String, String> {
+    let age = age.trim().to_lowercase();
+    let split = age.find(|c: char| !c.is_ascii_digit()).unwrap_or(age.len());
+    let (amount, unit) = age.split_at(split);
+    let invalid = || {
+        format!(
+            "Invalid {} '{}': expected a number of days, weeks, months or years such as 7d, 2w, 3m or 1y",
+            operator, age
+        )
+    };
+    let amount: u32 = amount.parse().map_err(|_| invalid())?;
+    match unit {
+        "d" | "m" | "y" => Ok(format!("{}:{}{}", operator, amount, unit)),
+        "w" => {
+            let days = amount.checked_mul(7).ok_or_else(invalid)?;
+            Ok(format!("{}:{}d", operator, days))
+        }
+        _ => Err(invalid()),
+    }
+}
+
+/// Add `newer_than` and `older_than` terms to a Gmail search query. A query
+/// is kept in parentheses so that any OR in it does not take in the terms.
+pub fn with_relative_time(
+    query: Option<&str>,
+    newer_than: Option<&str>,
+    older_than: Option<&str>,
+) -> Result<Option<String>, String> {
+    let mut terms: Vec<String> = query
+        .map(str::trim)
+        .filter(|query| !query.is_empty())
+        .map(String::from)
+        .into_iter()
+        .collect();
+    let has_query = !terms.is_empty();
+    if let Some(age) = newer_than {
+        terms.push(relative_time_term("newer_than", age)?);
+    }
+    if let Some(age) = older_than {
+        terms.push(relative_time_term("older_than", age)?);
+    }
+    if has_query && terms.len() > 1 {
+        terms[0] = format!("({})", terms[0]);
+    }
+    Ok(if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    })
+}
+
 /// Convert an error message and code to an MCP error
 pub fn to_mcp_error(message: &str, code: u32) -> McpError {
-    use error_codes::{get_error_description, get_troubleshooting_steps};
+    to_mcp_error_with_data(message, ErrorData::new(code))
+}
+
+/// Suggested wait, in seconds, before retrying after a rate limit error
+pub const DEFAULT_RATE_LIMIT_RETRY_SECONDS: u64 = 60;
+
+/// Machine-readable error details, sent as the `data` of MCP errors so that
+/// clients can react without parsing the message
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ErrorData {
+    pub code: u32,
+    pub category: &'static str,
+    /// Whether the same request may succeed if retried
+    pub retryable: bool,
+    /// Suggested wait in seconds before retrying, when known
+    pub retry_after: Option<u64>,
+    /// Which quota was exhausted, for rate limit errors that say
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quota_scope: Option<&'static str>,
+    /// The OAuth scope the access token lacks, for insufficient scope errors
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub missing_scope: Option<&'static str>,
+    /// Command that authorizes the missing scope
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix_command: Option<String>,
+    /// The input fields that failed validation, for invalid input errors
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub invalid_fields: Vec<InvalidField>,
+    /// The sending policy rule that was broken, for policy errors
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub policy_rule: Option<&'static str>,
+    pub troubleshooting: &'static str,
+}
+
+impl ErrorData {
+    pub fn new(code: u32) -> Self {
+        Self {
+            code,
+            category: error_codes::get_error_category(code),
+            retryable: false,
+            retry_after: None,
+            quota_scope: None,
+            missing_scope: None,
+            fix_command: None,
+            invalid_fields: Vec::new(),
+            policy_rule: None,
+            troubleshooting: error_codes::get_troubleshooting_steps(code),
+        }
+    }
+
+    pub fn with_category(mut self, category: &'static str) -> Self {
+        self.category = category;
+        self
+    }
+
+    pub fn retryable(mut self, retry_after: Option<u64>) -> Self {
+        self.retryable = true;
+        self.retry_after = retry_after;
+        self
+    }
+
+    /// Data for a rate limit error, retryable after the default wait
+    pub fn rate_limited(code: u32) -> Self {
+        Self::new(code)
+            .with_category("rate_limit")
+            .retryable(Some(DEFAULT_RATE_LIMIT_RETRY_SECONDS))
+    }
+
+    /// Data for a parsed rate limit response, with the recommended wait
+    pub fn quota_exceeded(code: u32, info: &RateLimitInfo) -> Self {
+        let mut data = Self::new(code)
+            .with_category("rate_limit")
+            .retryable(Some(info.recommended_wait()));
+        data.quota_scope = Some(info.scope.as_str());
+        data
+    }
+
+    /// Data for a request refused because the access token lacks `scope`
+    pub fn insufficient_scope(scope: &'static str) -> Self {
+        let mut data = Self::new(error_codes::SCOPE_ERROR);
+        data.missing_scope = Some(scope);
+        data.fix_command = Some(scope_fix_command(scope));
+        data
+    }
+
+    /// Data for a network error, retryable straight away
+    pub fn network(code: u32) -> Self {
+        Self::new(code).with_category("network").retryable(None)
+    }
+}
+
+/// The command that authorizes again with `scope` added
+pub fn scope_fix_command(scope: &str) -> String {
+    format!("mcp-gmailcal auth --scopes {}", scope)
+}
+
+/// Create an MCP error with a detailed message and structured `data`
+pub fn to_mcp_error_with_data(message: &str, data: ErrorData) -> McpError {
+    use error_codes::get_error_description;
+
+    let code = data.code;
 
     // Get the generic description for this error code
     let description = get_error_description(code);
 
     // Get troubleshooting steps
-    let steps = get_troubleshooting_steps(code);
+    let steps = data.troubleshooting;
 
     // Create a detailed error message with multiple parts
     let detailed_error =
@@ -125,21 +1610,31 @@ pub fn to_mcp_error(message: &str, code: u32) -> McpError {
         message, code, detailed_error
     );
 
-    // Create the MCP error with the detailed message
-    McpError::new(ErrorCode(code as i64)).with_message(detailed_error, true)
+    // Create the MCP error with the detailed message and structured data
+    McpError::from(ErrorObject {
+        code: ErrorCode(code as i64),
+        message: detailed_error,
+        data: serde_json::to_value(&data).ok(),
+    })
 }
 
 /// Map Gmail API errors to MCP errors
 pub fn map_gmail_error(err: GmailApiError) -> McpError {
+    let (message, data) = gmail_error_details(err);
+    to_mcp_error_with_data(&message, data)
+}
+
+// Detailed message and structured data for a Gmail API error
+fn gmail_error_details(err: GmailApiError) -> (String, ErrorData) {
     match err {
         GmailApiError::ApiError(e) => {
             // Analyze the error message to provide more context
-            let (code, detailed_msg) = if e.contains("quota")
+            let (data, detailed_msg) = if e.contains("quota")
                 || e.contains("rate")
                 || e.contains("limit")
             {
                 (
-                    error_codes::API_ERROR,
+                    ErrorData::rate_limited(error_codes::API_ERROR),
                     format!(
                         "Gmail API rate limit exceeded: {}. The server has made too many requests to the Gmail API. \
                         This typically happens when many requests are made in quick succession. \
@@ -149,7 +1644,7 @@ pub fn map_gmail_error(err: GmailApiError) -> McpError {
                 )
             } else if e.contains("network") || e.contains("connection") || e.contains("timeout") {
                 (
-                    error_codes::API_ERROR,
+                    ErrorData::network(error_codes::API_ERROR),
                     format!(
                         "Network error while connecting to Gmail API: {}. The server couldn't establish a \
                         connection to the Gmail API. This may be due to network issues or the Gmail API \
@@ -159,7 +1654,7 @@ pub fn map_gmail_error(err: GmailApiError) -> McpError {
                 )
             } else if e.contains("authentication") || e.contains("auth") || e.contains("token") {
                 (
-                    error_codes::AUTH_ERROR,
+                    ErrorData::new(error_codes::AUTH_ERROR),
                     format!(
                         "Gmail API authentication failed: {}. The OAuth token used to authenticate with \
                         Gmail may have expired or been revoked. Please check your credentials and try \
@@ -169,7 +1664,7 @@ pub fn map_gmail_error(err: GmailApiError) -> McpError {
                 )
             } else if e.contains("format") || e.contains("missing field") || e.contains("parse") {
                 (
-                    error_codes::MESSAGE_FORMAT_ERROR,
+                    ErrorData::new(error_codes::MESSAGE_FORMAT_ERROR),
                     format!(
                         "Gmail API response format error: {}. The API returned data in an unexpected format. \
                         This might be due to changes in the Gmail API or issues with specific messages.", 
@@ -178,7 +1673,7 @@ pub fn map_gmail_error(err: GmailApiError) -> McpError {
                 )
             } else if e.contains("not found") || e.contains("404") {
                 (
-                    error_codes::API_ERROR,
+                    ErrorData::new(error_codes::API_ERROR).with_category("not_found"),
                     format!(
                         "Gmail API resource not found: {}. The requested message or resource doesn't exist \
                         or you don't have permission to access it. Please check the message ID and ensure \
@@ -188,7 +1683,7 @@ pub fn map_gmail_error(err: GmailApiError) -> McpError {
                 )
             } else {
                 (
-                    error_codes::API_ERROR,
+                    ErrorData::new(error_codes::API_ERROR),
                     format!(
                         "Unspecified Gmail API error: {}. An unexpected error occurred when communicating \
                         with the Gmail API. Please check the server logs for more details.", 
@@ -197,7 +1692,7 @@ pub fn map_gmail_error(err: GmailApiError) -> McpError {
                 )
             };
 
-            to_mcp_error(&detailed_msg, code)
+            (detailed_msg, data)
         }
         GmailApiError::AuthError(e) => {
             let detailed_msg = format!(
@@ -205,7 +1700,7 @@ pub fn map_gmail_error(err: GmailApiError) -> McpError {
                 credentials. Please verify your client ID, client secret, and refresh token.", 
                 e
             );
-            to_mcp_error(&detailed_msg, error_codes::AUTH_ERROR)
+            (detailed_msg, ErrorData::new(error_codes::AUTH_ERROR))
         }
         GmailApiError::MessageRetrievalError(e) => {
             let detailed_msg = format!(
@@ -213,14 +1708,20 @@ pub fn map_gmail_error(err: GmailApiError) -> McpError {
                 This may be due to the message being deleted, access permissions, or temporary Gmail API issues.", 
                 e
             );
-            to_mcp_error(&detailed_msg, error_codes::API_ERROR)
+            (
+                detailed_msg,
+                ErrorData::new(error_codes::API_ERROR).with_category("not_found"),
+            )
         }
         GmailApiError::MessageFormatError(e) => {
             let detailed_msg = format!(
                 "Message format error: {}. The Gmail API returned a malformed message or one with missing required fields.", 
                 e
             );
-            to_mcp_error(&detailed_msg, error_codes::MESSAGE_FORMAT_ERROR)
+            (
+                detailed_msg,
+                ErrorData::new(error_codes::MESSAGE_FORMAT_ERROR),
+            )
         }
         GmailApiError::NetworkError(e) => {
             let detailed_msg = format!(
@@ -229,7 +1730,7 @@ pub fn map_gmail_error(err: GmailApiError) -> McpError {
                 Please check your internet connection and server network configuration.", 
                 e
             );
-            to_mcp_error(&detailed_msg, error_codes::API_ERROR)
+            (detailed_msg, ErrorData::network(error_codes::API_ERROR))
         }
         GmailApiError::RateLimitError(e) => {
             let detailed_msg = format!(
@@ -239,16 +1740,325 @@ pub fn map_gmail_error(err: GmailApiError) -> McpError {
                 or reduce the frequency of requests.", 
                 e
             );
-            to_mcp_error(&detailed_msg, error_codes::API_ERROR)
+            (
+                detailed_msg,
+                ErrorData::rate_limited(error_codes::API_ERROR),
+            )
         }
+        GmailApiError::RateLimited(info) => rate_limit_details("Gmail", &info),
         GmailApiError::CacheError(e) => {
             let detailed_msg = format!(
                 "Token cache error: {}. The server encountered an error with the token cache. \
                 This is an internal error and should not affect functionality. \
-                The application will continue with in-memory token handling.", 
+                The application will continue with in-memory token handling.",
+                e
+            );
+            (detailed_msg, ErrorData::new(error_codes::GENERAL_ERROR))
+        }
+        GmailApiError::AttachmentError(e) => {
+            let detailed_msg = format!(
+                "Attachment error: {}. The attachment could not be saved. \
+                Check that the attachment directory is writable and that the attachment is within the size limit \
+                (ATTACHMENT_MAX_BYTES).",
                 e
             );
-            to_mcp_error(&detailed_msg, error_codes::GENERAL_ERROR)
+            (
+                detailed_msg,
+                ErrorData::new(error_codes::API_ERROR).with_category("attachment"),
+            )
+        }
+        GmailApiError::PolicyViolation(e) => policy_error_details(e),
+        GmailApiError::MessageTooLarge(e) => (
+            format!("Message too large: {}", e),
+            ErrorData::new(error_codes::MESSAGE_FORMAT_ERROR).with_category("message_too_large"),
+        ),
+    }
+}
+
+/// Map any crate error to an MCP error with the matching code and category
+pub fn map_to_mcp_error(err: impl Into<Error>) -> McpError {
+    let (message, data) = error_details(err.into());
+    to_mcp_error_with_data(&message, data)
+}
+
+/// Structured data describing any crate error, as sent with MCP errors
+pub fn error_data(err: impl Into<Error>) -> ErrorData {
+    error_details(err.into()).1
+}
+
+// Detailed message and structured data for any crate error
+fn error_details(err: Error) -> (String, ErrorData) {
+    if let Some(details) = scope_error_details(&err) {
+        return details;
+    }
+    match err {
+        Error::Config(e) => config_error_details(e),
+        Error::Gmail(e) => gmail_error_details(e),
+        Error::Calendar(e) => calendar_error_details(e),
+        Error::People(e) => people_error_details(e),
+        Error::Tasks(e) => tasks_error_details(e),
+        Error::Drive(e) => drive_error_details(e),
+        Error::LocalIndex(e) => local_index_error_details(e),
+        Error::Template(e) => template_error_details(e),
+        Error::Rule(e) => rule_error_details(e),
+        Error::MailRule(e) => mail_rule_error_details(e),
+        Error::SavedSearch(e) => saved_search_error_details(e),
+        Error::Validation(e) => validation_error_details(e),
+        Error::Policy(e) => policy_error_details(e),
+    }
+}
+
+// The API an error came from and the scope its tools need
+fn api_scope(err: &Error) -> Option<(&'static str, &'static str)> {
+    match err {
+        Error::Gmail(_) | Error::LocalIndex(LocalIndexError::SyncError(_)) => {
+            Some(("Gmail", GMAIL_SCOPE))
+        }
+        Error::Calendar(_) => Some(("Calendar", CALENDAR_WRITE_SCOPE)),
+        Error::People(_) => Some(("People", CONTACTS_WRITE_SCOPE)),
+        Error::Tasks(_) => Some(("Tasks", TASKS_SCOPE)),
+        Error::Drive(_) => Some(("Drive", DRIVE_READ_SCOPE)),
+        _ => None,
+    }
+}
+
+// Detailed message and structured data for a request refused because the
+// access token was not granted the API's scope, whatever variant carries it
+fn scope_error_details(err: &Error) -> Option<(String, ErrorData)> {
+    let (api, scope) = api_scope(err)?;
+    let text = err.to_string();
+    if !is_insufficient_scope(&text) {
+        return None;
+    }
+    let detailed_msg = format!(
+        "The {} API refused the request because the access token was not granted the {} scope: {}. \
+        Run `{}`, allow every permission on the consent screen, then restart the server.",
+        api,
+        scope,
+        text,
+        scope_fix_command(scope)
+    );
+    Some((detailed_msg, ErrorData::insufficient_scope(scope)))
+}
+
+// Detailed message and structured data for a configuration error
+fn config_error_details(err: ConfigError) -> (String, ErrorData) {
+    let detailed_msg = match err {
+        ConfigError::MissingEnvVar(var) => format!(
+            "Missing environment variable: {}. \
+            This variable is required for Gmail authentication. \
+            Please ensure you have set up your .env file correctly or exported the variable in your shell. \
+            Create an OAuth2 client in the Google Cloud Console to obtain these credentials.",
+            var
+        ),
+        ConfigError::EnvError(e) => format!(
+            "Environment variable error: {}. \
+            There was a problem reading the environment variables needed for Gmail authentication. \
+            Check permissions on your .env file and ensure it's properly formatted without special characters or quotes.",
+            e
+        ),
+    };
+    (detailed_msg, ErrorData::new(error_codes::CONFIG_ERROR))
+}
+
+// Detailed message and structured data for a Calendar API error
+fn calendar_error_details(err: CalendarApiError) -> (String, ErrorData) {
+    let data = match &err {
+        CalendarApiError::ApiError(e) => api_error_data(e),
+        CalendarApiError::AuthError(_) => ErrorData::new(error_codes::AUTH_ERROR),
+        CalendarApiError::EventRetrievalError(_) => {
+            ErrorData::new(error_codes::API_ERROR).with_category("not_found")
+        }
+        CalendarApiError::EventFormatError(_) | CalendarApiError::ParseError(_) => {
+            ErrorData::new(error_codes::MESSAGE_FORMAT_ERROR)
         }
+        CalendarApiError::NetworkError(_) => ErrorData::network(error_codes::API_ERROR),
+        CalendarApiError::RateLimitError(_) => ErrorData::rate_limited(error_codes::API_ERROR),
+        CalendarApiError::RateLimited(info) => return rate_limit_details("Calendar", info),
+    };
+    (err.to_string(), data)
+}
+
+// Detailed message and structured data for a People API error
+fn people_error_details(err: PeopleApiError) -> (String, ErrorData) {
+    let data = match &err {
+        PeopleApiError::ApiError(e) => api_error_data(e),
+        PeopleApiError::AuthError(_) => ErrorData::new(error_codes::AUTH_ERROR),
+        PeopleApiError::InvalidInput(_) => {
+            ErrorData::new(error_codes::API_ERROR).with_category("invalid_input")
+        }
+        PeopleApiError::ParseError(_) => ErrorData::new(error_codes::MESSAGE_FORMAT_ERROR),
+        PeopleApiError::NetworkError(_) => ErrorData::network(error_codes::API_ERROR),
+        PeopleApiError::RateLimited(info) => return rate_limit_details("People", info),
+    };
+    (err.to_string(), data)
+}
+
+// Detailed message and structured data for a Tasks API error
+fn tasks_error_details(err: TasksApiError) -> (String, ErrorData) {
+    let data = match &err {
+        TasksApiError::ApiError(e) => api_error_data(e),
+        TasksApiError::AuthError(_) => ErrorData::new(error_codes::AUTH_ERROR),
+        TasksApiError::InvalidInput(_) => {
+            ErrorData::new(error_codes::API_ERROR).with_category("invalid_input")
+        }
+        TasksApiError::ParseError(_) => ErrorData::new(error_codes::MESSAGE_FORMAT_ERROR),
+        TasksApiError::NetworkError(_) => ErrorData::network(error_codes::API_ERROR),
+        TasksApiError::RateLimited(info) => return rate_limit_details("Tasks", info),
+    };
+    (err.to_string(), data)
+}
+
+// Detailed message and structured data for a Drive API error
+fn drive_error_details(err: DriveApiError) -> (String, ErrorData) {
+    let data = match &err {
+        DriveApiError::ApiError(e) => api_error_data(e),
+        DriveApiError::AuthError(_) => ErrorData::new(error_codes::AUTH_ERROR),
+        DriveApiError::FileNotFound(_) => {
+            ErrorData::new(error_codes::API_ERROR).with_category("not_found")
+        }
+        DriveApiError::NotAttachable(_) => {
+            ErrorData::new(error_codes::API_ERROR).with_category("invalid_input")
+        }
+        DriveApiError::ParseError(_) => ErrorData::new(error_codes::MESSAGE_FORMAT_ERROR),
+        DriveApiError::NetworkError(_) => ErrorData::network(error_codes::API_ERROR),
+        DriveApiError::RateLimited(info) => return rate_limit_details("Drive", info),
+    };
+    (err.to_string(), data)
+}
+
+// Detailed message and structured data for a local index error
+fn local_index_error_details(err: LocalIndexError) -> (String, ErrorData) {
+    match err {
+        LocalIndexError::SyncError(e) => gmail_error_details(e),
+        LocalIndexError::UnsupportedQuery(_) => (
+            err.to_string(),
+            ErrorData::new(error_codes::API_ERROR).with_category("invalid_input"),
+        ),
+        LocalIndexError::DatabaseError(_) => {
+            (err.to_string(), ErrorData::new(error_codes::GENERAL_ERROR))
+        }
+    }
+}
+
+// Detailed message and structured data for a template error
+fn template_error_details(err: TemplateError) -> (String, ErrorData) {
+    let data = match &err {
+        TemplateError::NotFound(_) => {
+            ErrorData::new(error_codes::API_ERROR).with_category("not_found")
+        }
+        TemplateError::InvalidTemplate(_) | TemplateError::MissingVariables(_) => {
+            ErrorData::new(error_codes::API_ERROR).with_category("invalid_input")
+        }
+        TemplateError::StorageError(_) => ErrorData::new(error_codes::GENERAL_ERROR),
+    };
+    (err.to_string(), data)
+}
+
+// Detailed message and structured data for an event rule error
+fn rule_error_details(err: RuleError) -> (String, ErrorData) {
+    let data = match &err {
+        RuleError::NotFound(_) => ErrorData::new(error_codes::API_ERROR).with_category("not_found"),
+        RuleError::InvalidRule(_) => {
+            ErrorData::new(error_codes::API_ERROR).with_category("invalid_input")
+        }
+        RuleError::StorageError(_) => ErrorData::new(error_codes::GENERAL_ERROR),
+    };
+    (err.to_string(), data)
+}
+
+// Detailed message and structured data for a mail rule error
+fn mail_rule_error_details(err: MailRuleError) -> (String, ErrorData) {
+    let data = match &err {
+        MailRuleError::InvalidRule(_) => {
+            ErrorData::new(error_codes::API_ERROR).with_category("invalid_input")
+        }
+        MailRuleError::StorageError(_) => ErrorData::new(error_codes::GENERAL_ERROR),
+    };
+    (err.to_string(), data)
+}
+
+// Detailed message and structured data for a saved search error
+fn saved_search_error_details(err: SavedSearchError) -> (String, ErrorData) {
+    let data = match &err {
+        SavedSearchError::NotFound(_) => {
+            ErrorData::new(error_codes::API_ERROR).with_category("not_found")
+        }
+        SavedSearchError::InvalidSearch(_) => {
+            ErrorData::new(error_codes::API_ERROR).with_category("invalid_input")
+        }
+        SavedSearchError::StorageError(_) => ErrorData::new(error_codes::GENERAL_ERROR),
+    };
+    (err.to_string(), data)
+}
+
+// Detailed message and structured data for tool input that failed
+// validation, listing each offending field
+fn validation_error_details(err: ValidationError) -> (String, ErrorData) {
+    let mut data = ErrorData::new(error_codes::API_ERROR).with_category("invalid_input");
+    data.invalid_fields = err.0.clone();
+    (err.to_string(), data)
+}
+
+fn policy_error_details(err: PolicyViolation) -> (String, ErrorData) {
+    let mut data = ErrorData::new(error_codes::GENERAL_ERROR).with_category("policy");
+    if let PolicyViolation::DailyLimitReached { retry_after, .. } = err {
+        data = data.retryable(Some(retry_after));
+    }
+    data.policy_rule = Some(err.rule());
+    (err.to_string(), data)
+}
+
+// Explain which quota ran out and how long to wait before retrying
+fn rate_limit_details(api: &str, info: &RateLimitInfo) -> (String, ErrorData) {
+    let wait = info.recommended_wait();
+    let guidance = match info.scope {
+        QuotaScope::Daily => format!(
+            "The project's daily {} API quota is used up. It resets at midnight Pacific Time, \
+            in about {}; requests will keep failing until then.",
+            api,
+            format_wait(wait)
+        ),
+        QuotaScope::PerUser => format!(
+            "Too many {} API requests were made for this account in a short time. \
+            Wait {} before retrying, and make fewer requests at once.",
+            api,
+            format_wait(wait)
+        ),
+        QuotaScope::Project | QuotaScope::Unknown => format!(
+            "The {} API is limiting how quickly this server can make requests. \
+            Wait {} before retrying.",
+            api,
+            format_wait(wait)
+        ),
+    };
+    (
+        format!("{}. {}", info, guidance),
+        ErrorData::quota_exceeded(error_codes::API_ERROR, info),
+    )
+}
+
+fn format_wait(seconds: u64) -> String {
+    match seconds {
+        0..=119 => format!("{} seconds", seconds),
+        120..=7199 => format!("{} minutes", seconds.div_ceil(60)),
+        _ => format!("{} hours", seconds.div_ceil(3600)),
+    }
+}
+
+// Classify a generic API error from the HTTP status or message it carries
+fn api_error_data(message: &str) -> ErrorData {
+    let message = message.to_lowercase();
+    if message.contains("429") || message.contains("quota") || message.contains("rate limit") {
+        ErrorData::rate_limited(error_codes::API_ERROR)
+    } else if message.contains("401") || message.contains("403") {
+        ErrorData::new(error_codes::AUTH_ERROR)
+    } else if message.contains("404") || message.contains("not found") {
+        ErrorData::new(error_codes::API_ERROR).with_category("not_found")
+    } else if message.contains("status: 5") {
+        // Server errors are usually transient
+        ErrorData::new(error_codes::API_ERROR).retryable(None)
+    } else {
+        ErrorData::new(error_codes::API_ERROR)
     }
 }
@@ -0,0 +1,297 @@
+use crate::errors::{InvalidField, ValidationError};
+use crate::utils::{parse_date_expression, parse_email_addresses};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use std::fmt::Display;
+use std::ops::{Bound, RangeBounds};
+
+// Tool input validation
+//
+// Tools check their arguments before making any request, so that a bad
+// address or date is reported against the field it came from, together with
+// every other bad field, rather than as whatever error Google returns for
+// the request built from it. Checks are chained on a Validator and finished
+// with `check`:
+//
+//     Validator::new()
+//         .recipients("to", &to)
+//         .max_chars("subject", &subject, MAX_SUBJECT_CHARS)
+//         .check()?;
+
+/// Longest subject line accepted, the limit on a header line
+pub const MAX_SUBJECT_CHARS: usize = 998;
+
+/// Largest email body accepted, Gmail's limit on a whole message
+pub const MAX_BODY_BYTES: usize = 25 * 1024 * 1024;
+
+/// Most recipients accepted in one recipient field, Gmail's limit per message
+pub const MAX_RECIPIENTS: usize = 500;
+
+/// Longest event or task title accepted
+pub const MAX_SUMMARY_CHARS: usize = 1024;
+
+/// Longest event description or task notes accepted
+pub const MAX_DESCRIPTION_CHARS: usize = 8192;
+
+/// Longest search query accepted
+pub const MAX_QUERY_CHARS: usize = 2048;
+
+/// Most items accepted in one list argument, such as message IDs to analyze
+pub const MAX_LIST_ITEMS: usize = 100;
+
+/// Collects the fields that fail validation
+#[derive(Debug, Default)]
+pub struct Validator {
+    invalid: Vec<InvalidField>,
+}
+
+impl Validator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn invalid(mut self, field: &str, problem: impl Into<String>) -> Self {
+        self.invalid.push(InvalidField {
+            field: field.to_string(),
+            problem: problem.into(),
+        });
+        self
+    }
+
+    /// Run `check` on the value if there is one
+    pub fn optional<T>(self, value: Option<T>, check: impl FnOnce(Self, T) -> Self) -> Self {
+        match value {
+            Some(value) => check(self, value),
+            None => self,
+        }
+    }
+
+    /// The value is not empty or only whitespace
+    pub fn required(self, field: &str, value: &str) -> Self {
+        if value.trim().is_empty() {
+            self.invalid(field, "a value is required")
+        } else {
+            self
+        }
+    }
+
+    /// The value has at most `max` characters
+    pub fn max_chars(self, field: &str, value: &str, max: usize) -> Self {
+        let chars = value.chars().count();
+        if chars > max {
+            self.invalid(
+                field,
+                format!("{} characters long; the most allowed is {}", chars, max),
+            )
+        } else {
+            self
+        }
+    }
+
+    /// The value is at most `max` bytes
+    pub fn max_bytes(self, field: &str, value: &str, max: usize) -> Self {
+        if value.len() > max {
+            self.invalid(
+                field,
+                format!("{} bytes; the most allowed is {}", value.len(), max),
+            )
+        } else {
+            self
+        }
+    }
+
+    /// A list has at most `max` items
+    pub fn max_items<T>(self, field: &str, items: &[T], max: usize) -> Self {
+        if items.len() > max {
+            self.invalid(
+                field,
+                format!("{} items; the most allowed is {}", items.len(), max),
+            )
+        } else {
+            self
+        }
+    }
+
+    /// The value is one email address, bare or as `Name <address>`
+    pub fn email(self, field: &str, value: &str) -> Self {
+        if value.trim().is_empty() {
+            return self.invalid(field, "a value is required");
+        }
+        if parse_email_addresses(value).len() > 1 {
+            return self.invalid(field, format!("'{}' is not a single email address", value));
+        }
+        match address_in(value) {
+            Some(_) => self,
+            None => self.invalid(field, format!("'{}' is not an email address", value)),
+        }
+    }
+
+    /// The number is within `range`
+    pub fn range<T>(self, field: &str, value: T, range: impl RangeBounds<T>) -> Self
+    where
+        T: PartialOrd + Display,
+    {
+        if range.contains(&value) {
+            return self;
+        }
+        let lower = match range.start_bound() {
+            Bound::Included(min) => Some(format!("at least {}", min)),
+            Bound::Excluded(min) => Some(format!("more than {}", min)),
+            Bound::Unbounded => None,
+        };
+        let upper = match range.end_bound() {
+            Bound::Included(max) => Some(format!("at most {}", max)),
+            Bound::Excluded(max) => Some(format!("less than {}", max)),
+            Bound::Unbounded => None,
+        };
+        let bounds: Vec<String> = lower.into_iter().chain(upper).collect();
+        self.invalid(
+            field,
+            format!(
+                "{} is out of range; it must be {}",
+                value,
+                bounds.join(" and ")
+            ),
+        )
+    }
+
+    /// The time is after `earlier`, the value of `earlier_field`
+    pub fn after(
+        self,
+        field: &str,
+        value: DateTime<Utc>,
+        earlier_field: &str,
+        earlier: DateTime<Utc>,
+    ) -> Self {
+        if value > earlier {
+            self
+        } else {
+            self.invalid(field, format!("must be after {}", earlier_field))
+        }
+    }
+
+    /// Each item is one email address, reported by its position
+    pub fn emails(self, field: &str, values: &[String]) -> Self {
+        let validator = self.max_items(field, values, MAX_RECIPIENTS);
+        values
+            .iter()
+            .enumerate()
+            .fold(validator, |validator, (index, value)| {
+                validator.email(&format!("{}[{}]", field, index), value)
+            })
+    }
+
+    /// Each item is a comma-separated list of email addresses, reported by
+    /// its position
+    pub fn recipient_lists(self, field: &str, values: &[String]) -> Self {
+        let validator = if values.is_empty() {
+            self.invalid(field, "a value is required")
+        } else {
+            self.max_items(field, values, MAX_RECIPIENTS)
+        };
+        values
+            .iter()
+            .enumerate()
+            .fold(validator, |validator, (index, value)| {
+                validator.recipients(&format!("{}[{}]", field, index), value)
+            })
+    }
+
+    /// The value is a comma-separated list of one or more email addresses
+    pub fn recipients(self, field: &str, value: &str) -> Self {
+        if value.trim().is_empty() {
+            return self.invalid(field, "a value is required");
+        }
+        let entries: Vec<&str> = value
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .collect();
+        let bad: Vec<&str> = entries
+            .iter()
+            .filter(|entry| address_in(entry).is_none())
+            .copied()
+            .collect();
+        if !bad.is_empty() {
+            let quoted: Vec<String> = bad.iter().map(|entry| format!("'{}'", entry)).collect();
+            return self.invalid(
+                field,
+                format!("{} is not an email address", quoted.join(", ")),
+            );
+        }
+        if entries.len() > MAX_RECIPIENTS {
+            return self.invalid(
+                field,
+                format!(
+                    "{} recipients; the most allowed is {}",
+                    entries.len(),
+                    MAX_RECIPIENTS
+                ),
+            );
+        }
+        self
+    }
+
+    /// The value is a date in RFC 3339 or in words, as create_event takes
+    pub fn date(self, field: &str, value: &str) -> Self {
+        if value.trim().is_empty() {
+            return self.invalid(field, "a value is required");
+        }
+        match parse_date_expression(value, Utc::now(), chrono_tz::UTC) {
+            Ok(_) => self,
+            Err(e) => self.invalid(field, e),
+        }
+    }
+
+    /// Finish validating, failing with every field that is invalid
+    pub fn check(self) -> Result<(), ValidationError> {
+        if self.invalid.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError(self.invalid))
+        }
+    }
+}
+
+/// Parse a date in RFC 3339 or in words, reading it in `time_zone`, failing
+/// against `field` as `Validator::date` would
+pub fn parse_date(
+    field: &str,
+    value: &str,
+    now: DateTime<Utc>,
+    time_zone: Tz,
+) -> Result<DateTime<Utc>, ValidationError> {
+    parse_date_expression(value, now, time_zone).map_err(|problem| {
+        ValidationError(vec![InvalidField {
+            field: field.to_string(),
+            problem,
+        }])
+    })
+}
+
+/// The address in one recipient, bare or as `Name <address>`, if it looks
+/// like a valid email address
+pub fn address_in(recipient: &str) -> Option<&str> {
+    let recipient = recipient.trim();
+    let address = match (recipient.rfind('<'), recipient.rfind('>')) {
+        (Some(start), Some(end)) if start < end => recipient[start + 1..end].trim(),
+        _ => recipient,
+    };
+    is_valid_email(address).then_some(address)
+}
+
+/// Whether the address has the form local@domain.tld, with no spaces
+pub fn is_valid_email(address: &str) -> bool {
+    let Some((local, domain)) = address.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && !domain.contains('@')
+        && !address
+            .chars()
+            .any(|c| c.is_whitespace() || c == '<' || c == '>')
+        && domain.contains('.')
+        && domain
+            .split('.')
+            .all(|label| !label.is_empty() && !label.starts_with('-') && !label.ends_with('-'))
+}
@@ -0,0 +1,260 @@
+use crate::calendar_api::CalendarEvent;
+use crate::follow_ups::AwaitingReply;
+use crate::gmail_api::MessageMetadata;
+use crate::sender_report::split_sender;
+use chrono::{DateTime, Duration, NaiveDate, Utc, Weekday};
+use serde::Serialize;
+
+// Weekly review
+//
+// A week runs from Monday 00:00 UTC to the next Monday. The review counts the
+// mail sent and received that week, lists the meetings the user went to, the
+// busiest conversations, and what is still open: starred messages, whenever
+// they arrived, and the week's sent mail nobody has answered yet. Only
+// message metadata is fetched.
+
+/// Most messages of a week checked
+pub const WEEKLY_REVIEW_MAX_MESSAGES: u32 = 1000;
+
+/// Most starred messages listed as action items
+pub const WEEKLY_REVIEW_MAX_STARRED: u32 = 50;
+
+/// Most calendar events checked for meetings
+pub const WEEKLY_REVIEW_MAX_EVENTS: u32 = 250;
+
+/// Number of key threads listed
+pub const KEY_THREAD_LIMIT: usize = 5;
+
+/// Furthest back a week can be reviewed, about ten years
+pub const MAX_WEEKS_BACK: i64 = 520;
+
+/// The start and end of a week, `week_offset` weeks from the current one:
+/// 0 is this week, -1 last week. None when the week is out of date range.
+pub fn week_bounds(week_offset: i64, now: DateTime<Utc>) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let monday = now.date_naive().week(Weekday::Mon).first_day();
+    let start = monday
+        .and_hms_opt(0, 0, 0)?
+        .and_utc()
+        .checked_add_signed(Duration::try_weeks(week_offset)?)?;
+    Some((start, start.checked_add_signed(Duration::weeks(1))?))
+}
+
+/// Gmail query for the mail of a week
+pub fn week_query(start: DateTime<Utc>, end: DateTime<Utc>) -> String {
+    format!("after:{} before:{}", start.timestamp(), end.timestamp())
+}
+
+/// Mail sent and received on one day
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DayVolume {
+    pub date: NaiveDate,
+    pub received: usize,
+    pub sent: usize,
+}
+
+/// How much mail came and went over the week
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MailVolume {
+    pub received: usize,
+    pub sent: usize,
+    /// Received messages still unread
+    pub unread: usize,
+    /// Monday first
+    pub by_day: Vec<DayVolume>,
+}
+
+/// A calendar event the user attended
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Meeting {
+    pub id: Option<String>,
+    pub summary: String,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    /// Number of attendees, including the user
+    pub attendees: usize,
+}
+
+/// A starred message, taken as something still to do
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StarredMessage {
+    pub message_id: String,
+    pub thread_id: String,
+    pub subject: Option<String>,
+    pub from: Option<String>,
+    pub received_at: Option<DateTime<Utc>>,
+}
+
+/// Everything still waiting on the user or on someone else
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ActionItems {
+    pub starred: Vec<StarredMessage>,
+    pub awaiting_reply: Vec<AwaitingReply>,
+}
+
+/// One of the week's busiest conversations
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KeyThread {
+    pub thread_id: String,
+    pub subject: Option<String>,
+    /// Messages in the thread that week
+    pub message_count: usize,
+    /// Addresses of everyone who wrote in the thread that week
+    pub participants: Vec<String>,
+    pub last_activity: Option<DateTime<Utc>>,
+}
+
+/// A summary of one week of mail and meetings
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WeeklyReview {
+    pub week_start: DateTime<Utc>,
+    pub week_end: DateTime<Utc>,
+    pub volume: MailVolume,
+    pub meetings: Vec<Meeting>,
+    pub meeting_minutes: i64,
+    pub action_items: ActionItems,
+    pub key_threads: Vec<KeyThread>,
+}
+
+fn has_label(message: &MessageMetadata, label: &str) -> bool {
+    message.label_ids.iter().any(|id| id == label)
+}
+
+impl WeeklyReview {
+    /// Review the week from `start` to `end` given its messages, the starred
+    /// messages, the conversations awaiting a reply, the calendar events and
+    /// the user's address. Messages and events outside the week, drafts, and
+    /// meetings the user declined are left out.
+    pub fn new(
+        (start, end): (DateTime<Utc>, DateTime<Utc>),
+        messages: &[MessageMetadata],
+        starred: &[MessageMetadata],
+        awaiting_reply: Vec<AwaitingReply>,
+        events: &[CalendarEvent],
+        user_email: &str,
+    ) -> Self {
+        let in_week = |time: DateTime<Utc>| time >= start && time < end;
+        let messages: Vec<&MessageMetadata> = messages
+            .iter()
+            .filter(|message| !has_label(message, "DRAFT"))
+            .filter(|message| message.received_at.is_some_and(in_week))
+            .collect();
+
+        let mut by_day: Vec<DayVolume> = (0..7)
+            .map(|day| DayVolume {
+                date: (start + Duration::days(day)).date_naive(),
+                received: 0,
+                sent: 0,
+            })
+            .collect();
+        let (mut received, mut sent, mut unread) = (0, 0, 0);
+        for message in &messages {
+            let day = message
+                .received_at
+                .and_then(|at| by_day.iter_mut().find(|day| day.date == at.date_naive()));
+            if has_label(message, "SENT") {
+                sent += 1;
+                if let Some(day) = day {
+                    day.sent += 1;
+                }
+            } else {
+                received += 1;
+                unread += has_label(message, "UNREAD") as usize;
+                if let Some(day) = day {
+                    day.received += 1;
+                }
+            }
+        }
+
+        let mut meetings: Vec<Meeting> = events
+            .iter()
+            .filter(|event| in_week(event.start_time))
+            .filter(|event| {
+                // Events without the user as an attendee are their own
+                !event.attendees.iter().any(|attendee| {
+                    attendee.email.eq_ignore_ascii_case(user_email)
+                        && attendee.response_status.as_deref() == Some("declined")
+                })
+            })
+            .map(|event| Meeting {
+                id: event.id.clone(),
+                summary: event.summary.clone(),
+                start_time: event.start_time,
+                end_time: event.end_time,
+                attendees: event.attendees.len(),
+            })
+            .collect();
+        meetings.sort_by_key(|meeting| meeting.start_time);
+        let meeting_minutes = meetings
+            .iter()
+            .map(|meeting| (meeting.end_time - meeting.start_time).num_minutes())
+            .sum();
+
+        let starred = starred
+            .iter()
+            .filter(|message| has_label(message, "STARRED"))
+            .map(|message| StarredMessage {
+                message_id: message.id.clone(),
+                thread_id: message.thread_id.clone(),
+                subject: message.subject.clone(),
+                from: message.from.clone(),
+                received_at: message.received_at,
+            })
+            .collect();
+
+        let mut key_threads: Vec<KeyThread> = Vec::new();
+        for message in &messages {
+            let sender = message
+                .from
+                .as_deref()
+                .and_then(split_sender)
+                .map(|(address, _)| address);
+            let thread = match key_threads
+                .iter_mut()
+                .find(|thread| thread.thread_id == message.thread_id)
+            {
+                Some(thread) => thread,
+                None => {
+                    key_threads.push(KeyThread {
+                        thread_id: message.thread_id.clone(),
+                        subject: message.subject.clone(),
+                        message_count: 0,
+                        participants: Vec::new(),
+                        last_activity: None,
+                    });
+                    key_threads.last_mut().unwrap()
+                }
+            };
+            thread.message_count += 1;
+            thread.last_activity = thread.last_activity.max(message.received_at);
+            if let Some(sender) = sender {
+                if !thread.participants.contains(&sender) {
+                    thread.participants.push(sender);
+                }
+            }
+        }
+        key_threads.sort_by(|a, b| {
+            b.message_count
+                .cmp(&a.message_count)
+                .then(b.last_activity.cmp(&a.last_activity))
+        });
+        key_threads.truncate(KEY_THREAD_LIMIT);
+
+        Self {
+            week_start: start,
+            week_end: end,
+            volume: MailVolume {
+                received,
+                sent,
+                unread,
+                by_day,
+            },
+            meetings,
+            meeting_minutes,
+            action_items: ActionItems {
+                starred,
+                awaiting_reply,
+            },
+            key_threads,
+        }
+    }
+}
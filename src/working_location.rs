@@ -0,0 +1,187 @@
+use crate::calendar_api::{CalendarEvent, WorkingLocation};
+use crate::holidays::AgendaItemType;
+use crate::utils::local_to_utc;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::Serialize;
+
+// Working location
+//
+// Calendar records where the user works with working location events on
+// their primary calendar: at home, at an office, or somewhere else. They are
+// set one day at a time as all-day events that leave the day free, and
+// picked out of the day's events when building an agenda, so the agenda says
+// where the user will be alongside what they are doing. Holidays and
+// birthdays from the calendars in `holidays` join the agenda, marked as such.
+
+/// Most days working location is set for at once
+pub const WORKING_LOCATION_MAX_DAYS: i64 = 31;
+
+/// Most events checked when building a day's agenda
+pub const DAILY_AGENDA_MAX_EVENTS: u32 = 100;
+
+/// Read where the user works from "home", "office" or the name of another
+/// place. An office may be named with `label`.
+pub fn parse_working_location(
+    location: &str,
+    label: Option<String>,
+) -> Result<WorkingLocation, String> {
+    let location = location.trim();
+    let label = label
+        .map(|label| label.trim().to_string())
+        .filter(|label| !label.is_empty());
+    let (location_type, label) = match location.to_lowercase().as_str() {
+        "" => return Err("No working location given".to_string()),
+        "home" | "homeoffice" | "home office" => ("homeOffice", None),
+        "office" | "officelocation" => ("officeLocation", label),
+        "custom" | "customlocation" => match label {
+            Some(label) => ("customLocation", Some(label)),
+            None => return Err("A custom working location needs a label".to_string()),
+        },
+        _ => (
+            "customLocation",
+            Some(label.unwrap_or(location.to_string())),
+        ),
+    };
+    Ok(WorkingLocation {
+        location_type: location_type.to_string(),
+        label,
+    })
+}
+
+/// How a working location reads in a calendar, such as "Home"
+pub fn describe(location: &WorkingLocation) -> String {
+    match (location.location_type.as_str(), &location.label) {
+        ("homeOffice", _) => "Home".to_string(),
+        (_, Some(label)) => label.clone(),
+        ("officeLocation", None) => "Office".to_string(),
+        _ => "Elsewhere".to_string(),
+    }
+}
+
+/// The days from `start` to `end` inclusive to set a working location on,
+/// leaving out Saturdays and Sundays unless `include_weekends` is set
+pub fn working_days(
+    start: NaiveDate,
+    end: NaiveDate,
+    include_weekends: bool,
+) -> Result<Vec<NaiveDate>, String> {
+    if end < start {
+        return Err("end_date must not be before start_date".to_string());
+    }
+    if (end - start).num_days() >= WORKING_LOCATION_MAX_DAYS {
+        return Err(format!(
+            "Working location can be set for at most {} days at a time",
+            WORKING_LOCATION_MAX_DAYS
+        ));
+    }
+    let days: Vec<NaiveDate> = start
+        .iter_days()
+        .take_while(|day| *day <= end)
+        .filter(|day| include_weekends || !matches!(day.weekday(), Weekday::Sat | Weekday::Sun))
+        .collect();
+    if days.is_empty() {
+        return Err(format!(
+            "No working days from {} to {}; set include_weekends to use weekends",
+            start, end
+        ));
+    }
+    Ok(days)
+}
+
+/// The all-day working location event for one day
+pub fn working_location_event(date: NaiveDate, location: &WorkingLocation) -> CalendarEvent {
+    let start_time = date.and_time(NaiveTime::MIN).and_utc();
+    CalendarEvent {
+        summary: describe(location),
+        start_time,
+        end_time: start_time + Duration::days(1),
+        transparency: Some("transparent".to_string()),
+        all_day: true,
+        working_location: Some(location.clone()),
+        event_type: Some("workingLocation".to_string()),
+        ..Default::default()
+    }
+}
+
+/// The start and end of a day in a time zone
+pub fn day_bounds(
+    date: NaiveDate,
+    time_zone: Tz,
+) -> Result<(DateTime<Utc>, DateTime<Utc>), String> {
+    let next = date + Duration::days(1);
+    Ok((
+        local_to_utc(date.and_time(NaiveTime::MIN), time_zone, &date.to_string())?,
+        local_to_utc(next.and_time(NaiveTime::MIN), time_zone, &next.to_string())?,
+    ))
+}
+
+/// Whether an event takes place on a day
+fn on_day(event: &CalendarEvent, date: NaiveDate, bounds: (DateTime<Utc>, DateTime<Utc>)) -> bool {
+    if event.all_day {
+        event.start_time.date_naive() <= date && date < event.end_time.date_naive()
+    } else {
+        event.start_time < bounds.1 && event.end_time > bounds.0
+    }
+}
+
+/// An event on the agenda, with what kind of entry it is
+#[derive(Debug, Clone, Serialize)]
+pub struct AgendaItem {
+    #[serde(rename = "type")]
+    pub item_type: AgendaItemType,
+    pub calendar_id: String,
+    /// Whether the user has declined the event
+    pub declined: bool,
+    #[serde(flatten)]
+    pub event: CalendarEvent,
+}
+
+/// A day's events and where the user works that day
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyAgenda {
+    pub date: NaiveDate,
+    pub time_zone: String,
+    /// Where the user works, from the day's working location event. An
+    /// all-day location is preferred to one set for part of the day.
+    pub working_location: Option<WorkingLocation>,
+    /// All-day events first, then the rest by start time
+    pub events: Vec<AgendaItem>,
+}
+
+impl DailyAgenda {
+    /// Build the agenda for `date` from the events listed for it on each
+    /// calendar, leaving out those on other days
+    pub fn new(
+        date: NaiveDate,
+        time_zone: Tz,
+        calendars: Vec<(String, Vec<CalendarEvent>)>,
+    ) -> Result<Self, String> {
+        let bounds = day_bounds(date, time_zone)?;
+        let (locations, mut events): (Vec<_>, Vec<_>) = calendars
+            .into_iter()
+            .flat_map(|(calendar_id, events)| {
+                events.into_iter().map(move |event| AgendaItem {
+                    item_type: AgendaItemType::of(&calendar_id, &event),
+                    calendar_id: calendar_id.clone(),
+                    declined: crate::declined::is_declined(&event),
+                    event,
+                })
+            })
+            .filter(|item| on_day(&item.event, date, bounds))
+            .partition(|item| item.event.working_location.is_some());
+        events.sort_by_key(|item| (!item.event.all_day, item.event.start_time));
+
+        let working_location = locations
+            .iter()
+            .min_by_key(|item| (!item.event.all_day, item.event.start_time))
+            .and_then(|item| item.event.working_location.clone());
+
+        Ok(Self {
+            date,
+            time_zone: time_zone.name().to_string(),
+            working_location,
+            events,
+        })
+    }
+}
@@ -0,0 +1,129 @@
+/// API Traits Tests Module
+///
+/// This module contains tests for the shared API abstractions, checking that
+/// the production clients and the shipped mocks are interchangeable behind
+/// the traits.
+use chrono::{Duration, TimeZone, Utc};
+use mcp_gmailcal::api::{CalendarApi, GmailApi, PeopleApi};
+use mcp_gmailcal::test_util::{MockCalendarApi, MockGmailApi, MockPeopleApi};
+use mcp_gmailcal::{
+    CalendarClient, CalendarEvent, Config, DraftEmail, EmailMessage, GmailApiError, GmailService,
+    PeopleClient,
+};
+
+fn test_config() -> Config {
+    Config {
+        client_id: "test_client_id".to_string(),
+        client_secret: "test_client_secret".to_string(),
+        refresh_token: "test_refresh_token".to_string(),
+        access_token: None,
+        token_refresh_threshold: 300,
+        token_expiry_buffer: 60,
+    }
+}
+
+fn test_event(summary: &str, start_hour: u32) -> CalendarEvent {
+    let start_time = Utc.with_ymd_and_hms(2025, 5, 15, start_hour, 0, 0).unwrap();
+    CalendarEvent {
+        summary: summary.to_string(),
+        start_time,
+        end_time: start_time + Duration::hours(1),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_production_clients_implement_traits() {
+    let config = test_config();
+    let _gmail: Box<dyn GmailApi> = Box::new(GmailService::new(&config).unwrap());
+    let _calendar: Box<dyn CalendarApi> = Box::new(CalendarClient::new(&config));
+    let _people: Box<dyn PeopleApi> = Box::new(PeopleClient::new(&config));
+}
+
+#[tokio::test]
+async fn test_mock_gmail_records_drafts_and_sent_mail() {
+    let mock = MockGmailApi::new().with_messages(vec![EmailMessage {
+        id: "msg1".to_string(),
+        thread_id: "thread1".to_string(),
+        subject: Some("Invoice".to_string()),
+        from: Some("billing@example.com".to_string()),
+        ..Default::default()
+    }]);
+
+    let draft = DraftEmail {
+        to: "someone@example.com".to_string(),
+        subject: "Hello".to_string(),
+        body: "Hi".to_string(),
+        cc: None,
+        bcc: None,
+        thread_id: None,
+        in_reply_to: None,
+        references: None,
+        priority: None,
+        read_receipt_to: None,
+    };
+
+    {
+        let api: &dyn GmailApi = &mock;
+        assert_eq!(
+            api.list_messages(10, Some("billing")).await.unwrap().len(),
+            1
+        );
+        assert!(api
+            .list_messages(10, Some("receipt"))
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(api.create_draft(&draft).await.unwrap(), "draft-1");
+        assert_eq!(api.send_message(&draft).await.unwrap(), "sent-1");
+    }
+
+    assert_eq!(mock.drafts().len(), 1);
+    assert_eq!(mock.sent()[0].to, "someone@example.com");
+
+    let failing = MockGmailApi::new()
+        .failing_with(|| GmailApiError::RateLimitError("Too many requests".to_string()));
+    assert!(matches!(
+        failing.check_connection().await,
+        Err(GmailApiError::RateLimitError(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_mock_calendar_filters_and_stores_events() {
+    let mock = MockCalendarApi::new().with_events(
+        "primary",
+        vec![test_event("Afternoon", 15), test_event("Morning", 9)],
+    );
+
+    // Events come back in start time order, limited to the requested window
+    let events = mock.list_events("primary", None, None, None).await.unwrap();
+    assert_eq!(events[0].summary, "Morning");
+    let window_start = Utc.with_ymd_and_hms(2025, 5, 15, 12, 0, 0).unwrap();
+    let events = mock
+        .list_events("primary", None, Some(window_start), None)
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].summary, "Afternoon");
+
+    // Created events can be read back
+    let created = mock
+        .create_event("work", test_event("Standup", 10))
+        .await
+        .unwrap();
+    let id = created.id.unwrap();
+    assert_eq!(
+        mock.get_event("work", &id).await.unwrap().summary,
+        "Standup"
+    );
+    assert!(mock.get_event("primary", &id).await.is_err());
+}
+
+#[tokio::test]
+async fn test_mock_people_empty_address_book() {
+    let mock = MockPeopleApi::new();
+    let contacts = mock.search_contacts("anyone", None).await.unwrap();
+    assert!(contacts.contacts.is_empty());
+    assert_eq!(contacts.total_items, Some(0));
+}
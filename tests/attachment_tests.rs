@@ -0,0 +1,220 @@
+/// Attachment Tests Module
+///
+/// This module contains tests for attachment support, covering the streaming
+/// response decoder, attachment discovery in message payloads, file naming and
+/// downloads through the real Gmail client against the mock server.
+use mcp_attr::client::McpClient;
+use mcp_attr::schema::CallToolRequestParams;
+use mcp_gmailcal::attachments::{
+    find_attachments, safe_filename, unique_attachment_path, AttachmentDecoder, DownloadOptions,
+};
+use mcp_gmailcal::{Config, GmailApiError, GmailServer, GmailService};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+
+mod common;
+use common::mock_server;
+
+const MOCK_PDF: &[u8] =
+    b"%PDF-1.4\n% Mock invoice #4821\n1 0 obj << /Type /Catalog >> endobj\ntrailer << /Root 1 0 R >>\n%%EOF\n";
+
+fn decode_in_chunks(body: &[u8], chunk_size: usize) -> (Vec<u8>, AttachmentDecoder) {
+    let mut decoder = AttachmentDecoder::new();
+    let mut out = Vec::new();
+    for chunk in body.chunks(chunk_size) {
+        decoder.feed(chunk, &mut out).unwrap();
+    }
+    (out, decoder)
+}
+
+#[test]
+fn test_decoder_handles_any_chunking() {
+    let content: Vec<u8> = (0..=255u8).cycle().take(10_000).collect();
+    // Gmail sends the size first and omits padding
+    let body = format!(
+        r#"{{"size": {}, "data": "{}"}}"#,
+        content.len(),
+        base64::encode_config(&content, base64::URL_SAFE_NO_PAD)
+    );
+
+    for chunk_size in [1, 3, 7, 4096, body.len()] {
+        let (out, decoder) = decode_in_chunks(body.as_bytes(), chunk_size);
+        decoder.finish().unwrap();
+        assert_eq!(out, content, "chunk size {}", chunk_size);
+        assert_eq!(decoder.declared_size(), Some(10_000));
+    }
+}
+
+#[test]
+fn test_decoder_accepts_padding_and_whitespace() {
+    let body = format!(
+        "{{\n  \"data\" : \"{}\",\n  \"size\": 5\n}}",
+        base64::encode_config(b"hello", base64::URL_SAFE)
+    );
+    let (out, decoder) = decode_in_chunks(body.as_bytes(), 2);
+    decoder.finish().unwrap();
+    assert_eq!(out, b"hello");
+    // The size came after the data, too late to be useful
+    assert_eq!(decoder.declared_size(), None);
+}
+
+#[test]
+fn test_decoder_rejects_incomplete_responses() {
+    let (_, decoder) = decode_in_chunks(br#"{"size": 10}"#, 4);
+    assert!(matches!(
+        decoder.finish(),
+        Err(GmailApiError::MessageFormatError(_))
+    ));
+
+    let (_, decoder) = decode_in_chunks(br#"{"size": 10, "data": "aGVsbG8gd29y"#, 4);
+    assert!(matches!(
+        decoder.finish(),
+        Err(GmailApiError::NetworkError(_))
+    ));
+
+    let mut decoder = AttachmentDecoder::new();
+    let mut out = Vec::new();
+    assert!(decoder.feed(br#"{"data": "!!!!"}"#, &mut out).is_err());
+}
+
+#[test]
+fn test_find_attachments_in_nested_parts() {
+    let payload = json!({
+        "mimeType": "multipart/mixed",
+        "parts": [
+            { "mimeType": "multipart/alternative", "parts": [
+                { "mimeType": "text/plain", "body": { "size": 5, "data": "aGVsbG8" } }
+            ]},
+            { "mimeType": "application/pdf", "filename": "report.pdf",
+              "body": { "attachmentId": "att-1", "size": 2048 } },
+            { "mimeType": "message/rfc822", "parts": [
+                { "mimeType": "image/png", "filename": "logo.png",
+                  "body": { "attachmentId": "att-2", "size": 512 } }
+            ]}
+        ]
+    });
+
+    let attachments = find_attachments(&payload);
+    assert_eq!(attachments.len(), 2);
+    assert_eq!(attachments[0].attachment_id, "att-1");
+    assert_eq!(attachments[0].filename, "report.pdf");
+    assert_eq!(attachments[0].size, 2048);
+    assert_eq!(attachments[1].mime_type, "image/png");
+    assert!(find_attachments(&json!({ "mimeType": "text/plain" })).is_empty());
+}
+
+#[test]
+fn test_attachment_file_names() {
+    assert_eq!(safe_filename("report.pdf"), "report.pdf");
+    assert_eq!(safe_filename("../../etc/passwd"), "passwd");
+    assert_eq!(safe_filename("C:\\Users\\me\\notes.txt"), "notes.txt");
+    assert_eq!(safe_filename("a\u{0}b:c.txt"), "a_b_c.txt");
+    assert_eq!(safe_filename(".."), "attachment");
+    assert_eq!(safe_filename(""), "attachment");
+
+    let dir = tempfile::tempdir().unwrap();
+    assert_eq!(
+        unique_attachment_path(dir.path(), "report.pdf"),
+        dir.path().join("report.pdf")
+    );
+    std::fs::write(dir.path().join("report.pdf"), b"x").unwrap();
+    std::fs::write(dir.path().join("report (1).pdf"), b"x").unwrap();
+    assert_eq!(
+        unique_attachment_path(dir.path(), "report.pdf"),
+        dir.path().join("report (2).pdf")
+    );
+    std::fs::write(dir.path().join("README"), b"x").unwrap();
+    assert_eq!(
+        unique_attachment_path(dir.path(), "README"),
+        dir.path().join("README (1)")
+    );
+}
+
+#[tokio::test]
+async fn test_download_attachment_from_mock_server() {
+    let _server = mock_server(None).await;
+    let dir = tempfile::tempdir().unwrap();
+    let config = Config::from_env().unwrap();
+    let gmail = GmailService::new(&config).unwrap();
+
+    let attachments = gmail.list_attachments("mock-msg-002").await.unwrap();
+    assert_eq!(attachments.len(), 1);
+    assert_eq!(attachments[0].filename, "invoice-4821.pdf");
+    assert_eq!(attachments[0].size, MOCK_PDF.len() as u64);
+    assert!(gmail
+        .list_attachments("mock-msg-001")
+        .await
+        .unwrap()
+        .is_empty());
+
+    // Download with progress reporting
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    let reports = progress.clone();
+    let dest = dir.path().join("invoice.pdf");
+    let written = gmail
+        .download_attachment(
+            "mock-msg-002",
+            "mock-att-001",
+            &dest,
+            DownloadOptions::default().with_progress(move |written, total| {
+                reports.lock().unwrap().push((written, total));
+            }),
+        )
+        .await
+        .unwrap();
+    assert_eq!(written, MOCK_PDF.len() as u64);
+    assert_eq!(std::fs::read(&dest).unwrap(), MOCK_PDF);
+    assert_eq!(
+        progress.lock().unwrap().last(),
+        Some(&(MOCK_PDF.len() as u64, Some(MOCK_PDF.len() as u64)))
+    );
+
+    // Oversized attachments fail without leaving files behind
+    let dest = dir.path().join("too-big.pdf");
+    let result = gmail
+        .download_attachment(
+            "mock-msg-002",
+            "mock-att-001",
+            &dest,
+            DownloadOptions::default().with_max_size(16),
+        )
+        .await;
+    assert!(matches!(result, Err(GmailApiError::AttachmentError(_))));
+    assert!(!dest.exists());
+    assert!(!dir.path().join("too-big.pdf.part").exists());
+
+    // Missing attachments surface as API errors
+    assert!(gmail
+        .download_attachment(
+            "mock-msg-002",
+            "nope",
+            &dir.path().join("nope"),
+            DownloadOptions::default()
+        )
+        .await
+        .is_err());
+
+    // The MCP tool saves under ATTACHMENT_DIR using the attachment's name
+    std::env::set_var("ATTACHMENT_DIR", dir.path());
+    let client = McpClient::with_server(GmailServer::new()).await.unwrap();
+    let mut arguments = serde_json::Map::new();
+    arguments.insert("message_id".to_string(), "mock-msg-002".into());
+    arguments.insert("attachment_id".to_string(), "mock-att-001".into());
+    let result = client
+        .tools_call(CallToolRequestParams {
+            name: "get_attachment".to_string(),
+            arguments: Some(arguments),
+        })
+        .await
+        .unwrap();
+    let text = serde_json::to_value(&result.content[0]).unwrap()["text"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let response: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(
+        response["path"],
+        dir.path().join("invoice-4821.pdf").display().to_string()
+    );
+    assert_eq!(response["size"], MOCK_PDF.len());
+}
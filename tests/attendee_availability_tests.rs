@@ -0,0 +1,213 @@
+/// Attendee Availability Tests Module
+///
+/// This module contains tests for matching attendees' free/busy to an event,
+/// and for get_event with include_availability against the mock server.
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use mcp_gmailcal::attendee_availability::{
+    availability_calendars, availability_window, AvailabilityStatus, EventAvailability, FreeSlot,
+};
+use mcp_gmailcal::calendar_api::{Attendee, BusyPeriod, CalendarBusy, CalendarEvent};
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 10, 20, hour, minute, 0).unwrap()
+}
+
+fn attendee(email: &str, status: Option<&str>) -> Attendee {
+    Attendee {
+        email: email.to_string(),
+        display_name: None,
+        response_status: status.map(String::from),
+        optional: None,
+        time_zone: None,
+        self_: None,
+    }
+}
+
+fn event() -> CalendarEvent {
+    CalendarEvent {
+        id: Some("event-sync".to_string()),
+        summary: "Weekly sync".to_string(),
+        start_time: at(16, 0),
+        end_time: at(17, 0),
+        attendees: vec![
+            attendee("demo@example.com", Some("accepted")),
+            attendee("Alice@example.com", Some("accepted")),
+            attendee("bob@example.com", Some("needsAction")),
+            attendee("carol@example.com", Some("declined")),
+            attendee("dave@example.com", None),
+            attendee("c_room42@resource.calendar.google.com", Some("accepted")),
+        ],
+        ..Default::default()
+    }
+}
+
+fn busy(start: DateTime<Utc>, end: DateTime<Utc>) -> BusyPeriod {
+    BusyPeriod { start, end }
+}
+
+fn calendar(id: &str, periods: Vec<BusyPeriod>) -> CalendarBusy {
+    CalendarBusy {
+        calendar_id: id.to_string(),
+        busy: periods,
+        error: None,
+    }
+}
+
+fn free_busy() -> Vec<CalendarBusy> {
+    vec![
+        calendar("demo@example.com", vec![busy(at(16, 0), at(17, 0))]),
+        // Another meeting overlapping the start, merged with the event
+        calendar("alice@example.com", vec![busy(at(15, 30), at(17, 0))]),
+        calendar(
+            "bob@example.com",
+            vec![busy(at(13, 0), at(14, 0)), busy(at(16, 0), at(17, 0))],
+        ),
+        calendar("carol@example.com", vec![busy(at(16, 30), at(18, 0))]),
+        CalendarBusy {
+            calendar_id: "dave@example.com".to_string(),
+            busy: Vec::new(),
+            error: Some("notFound".to_string()),
+        },
+    ]
+}
+
+#[test]
+fn test_availability_query() {
+    assert_eq!(availability_window(&event()), (at(12, 0), at(21, 0)));
+    assert_eq!(
+        availability_calendars(&event()),
+        vec![
+            "demo@example.com",
+            "alice@example.com",
+            "bob@example.com",
+            "carol@example.com",
+            "dave@example.com",
+        ]
+    );
+}
+
+#[test]
+fn test_event_availability() {
+    let availability = EventAvailability::new(&event(), &free_busy());
+    let statuses: Vec<(&str, AvailabilityStatus)> = availability
+        .attendees
+        .iter()
+        .map(|a| (a.email.as_str(), a.status))
+        .collect();
+    assert_eq!(
+        statuses,
+        vec![
+            ("demo@example.com", AvailabilityStatus::Free),
+            ("Alice@example.com", AvailabilityStatus::Busy),
+            ("bob@example.com", AvailabilityStatus::Free),
+            ("carol@example.com", AvailabilityStatus::Busy),
+            ("dave@example.com", AvailabilityStatus::Unknown),
+        ]
+    );
+
+    // The event's own time is left out for those who have not declined it
+    let attendees = &availability.attendees;
+    assert!(attendees[0].busy.is_empty());
+    assert_eq!(attendees[1].busy, vec![busy(at(15, 30), at(16, 0))]);
+    assert_eq!(attendees[2].busy, vec![busy(at(13, 0), at(14, 0))]);
+    assert_eq!(attendees[3].busy, vec![busy(at(16, 30), at(18, 0))]);
+    assert_eq!(attendees[4].error.as_deref(), Some("notFound"));
+
+    let slot = |start, end| FreeSlot { start, end };
+    assert_eq!(
+        availability.free_for_all,
+        vec![
+            slot(at(12, 0), at(13, 0)),
+            slot(at(14, 0), at(15, 30)),
+            slot(at(18, 0), at(21, 0)),
+        ]
+    );
+}
+
+#[test]
+fn test_free_event_does_not_hide_conflicts() {
+    let mut event = event();
+    event.transparency = Some("transparent".to_string());
+    event.end_time = event.start_time + Duration::minutes(30);
+    let availability = EventAvailability::new(&event, &free_busy());
+    // Bob's busy time is another meeting, since a free event is not counted
+    assert_eq!(availability.attendees[2].status, AvailabilityStatus::Busy);
+    assert_eq!(availability.attendees[0].status, AvailabilityStatus::Busy);
+}
+
+#[tokio::test]
+async fn test_get_event_availability_tool() {
+    let dir = tempfile::tempdir().unwrap();
+    let calendar = dir.path().join("calendar/v3");
+    let events = calendar.join("calendars/primary/events");
+    std::fs::create_dir_all(&events).unwrap();
+    let event = json!({
+        "id": "event-sync",
+        "summary": "Weekly sync",
+        "start": { "dateTime": "2026-10-20T16:00:00Z" },
+        "end": { "dateTime": "2026-10-20T17:00:00Z" },
+        "attendees": [
+            { "email": "demo@example.com", "responseStatus": "accepted" },
+            { "email": "alice@example.com", "responseStatus": "accepted" },
+            { "email": "bob@example.com", "responseStatus": "needsAction" },
+            { "email": "c_room42@resource.calendar.google.com", "responseStatus": "accepted" }
+        ]
+    });
+    std::fs::write(events.join("event-sync.json"), event.to_string()).unwrap();
+    let free_busy = json!({
+        "calendars": {
+            "demo@example.com": {
+                "busy": [{ "start": "2026-10-20T16:00:00Z", "end": "2026-10-20T17:00:00Z" }]
+            },
+            "alice@example.com": {
+                "busy": [{ "start": "2026-10-20T15:00:00Z", "end": "2026-10-20T17:00:00Z" }]
+            },
+            "bob@example.com": {
+                "errors": [{ "domain": "global", "reason": "notFound" }],
+                "busy": []
+            }
+        }
+    });
+    std::fs::write(calendar.join("freeBusy.post.json"), free_busy.to_string()).unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    // Availability is only read when asked for
+    let result = call_tool(&client, "get_event", json!({ "event_id": "event-sync" }))
+        .await
+        .unwrap();
+    assert_eq!(result["summary"], "Weekly sync");
+    assert!(result.get("availability").is_none());
+
+    let result = call_tool(
+        &client,
+        "get_event",
+        json!({ "event_id": "event-sync", "include_availability": true }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["summary"], "Weekly sync");
+    let availability = &result["availability"];
+    assert_eq!(availability["window_start"], "2026-10-20T12:00:00Z");
+    let attendees = availability["attendees"].as_array().unwrap();
+    assert_eq!(attendees.len(), 3);
+    assert_eq!(attendees[0]["status"], "free");
+    assert_eq!(attendees[1]["status"], "busy");
+    assert_eq!(
+        attendees[1]["busy"],
+        json!([{ "start": "2026-10-20T15:00:00Z", "end": "2026-10-20T16:00:00Z" }])
+    );
+    assert_eq!(attendees[2]["status"], "unknown");
+    assert_eq!(attendees[2]["error"], "notFound");
+    assert_eq!(
+        availability["free_for_all"],
+        json!([
+            { "start": "2026-10-20T12:00:00Z", "end": "2026-10-20T15:00:00Z" },
+            { "start": "2026-10-20T16:00:00Z", "end": "2026-10-20T21:00:00Z" }
+        ])
+    );
+}
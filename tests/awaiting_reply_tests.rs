@@ -0,0 +1,92 @@
+/// Awaiting Reply Tests Module
+///
+/// This module contains tests for follow-up tracking, checking which threads
+/// count as waiting on the other party and the awaiting_reply tool against
+/// the mock server.
+use chrono::{TimeZone, Utc};
+use mcp_gmailcal::follow_ups::{awaiting_reply, sent_mail_query};
+use mcp_gmailcal::gmail_api::MessageMetadata;
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn message(id: &str, label: &str, day: u32) -> MessageMetadata {
+    MessageMetadata {
+        id: id.to_string(),
+        thread_id: "thread-1".to_string(),
+        subject: Some("Contract".to_string()),
+        received_at: Some(Utc.with_ymd_and_hms(2026, 10, day, 12, 0, 0).unwrap()),
+        label_ids: vec![label.to_string()],
+        headers: [("to".to_string(), "carol@example.com".to_string())].into(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_awaiting_reply() {
+    let now = Utc.with_ymd_and_hms(2026, 10, 15, 18, 0, 0).unwrap();
+    assert_eq!(sent_mail_query(7), "in:sent newer_than:7d");
+
+    let thread = vec![message("1", "INBOX", 1), message("2", "SENT", 2)];
+    let waiting = awaiting_reply(&thread, now).unwrap();
+    assert_eq!(waiting.thread_id, "thread-1");
+    assert_eq!(waiting.message_id, "2");
+    assert_eq!(waiting.to.as_deref(), Some("carol@example.com"));
+    assert_eq!(waiting.days_waiting, Some(13));
+    assert_eq!(waiting.message_count, 2);
+
+    // A reply after the user's message ends the wait
+    let replied = vec![message("2", "SENT", 2), message("3", "INBOX", 4)];
+    assert!(awaiting_reply(&replied, now).is_none());
+
+    // An unsent draft reply does not
+    let drafted = vec![
+        message("2", "SENT", 2),
+        message("3", "INBOX", 4),
+        message("4", "DRAFT", 5),
+    ];
+    assert!(awaiting_reply(&drafted, now).is_none());
+    let drafted = vec![message("2", "SENT", 2), message("4", "DRAFT", 5)];
+    assert_eq!(awaiting_reply(&drafted, now).unwrap().message_id, "2");
+
+    assert!(awaiting_reply(&[], now).is_none());
+}
+
+#[tokio::test]
+async fn test_awaiting_reply_tool() {
+    let (_server, client) = mock_client(None).await;
+
+    let result = call_tool(&client, "awaiting_reply", json!({ "days": 7 }))
+        .await
+        .unwrap();
+    assert_eq!(result["query"], "in:sent newer_than:7d");
+    assert_eq!(result["threads_checked"], 2);
+
+    // The planning thread ends with the user's reply; the invoice thread
+    // was never answered by the user
+    let awaiting = result["awaiting"].as_array().unwrap();
+    assert_eq!(awaiting.len(), 1);
+    assert_eq!(awaiting[0]["thread_id"], "mock-thread-001");
+    assert_eq!(awaiting[0]["message_id"], "mock-msg-004");
+    assert_eq!(awaiting[0]["subject"], "Re: Quarterly planning meeting");
+    assert_eq!(
+        awaiting[0]["to"],
+        "Alice Example <alice@example.com>, Bob Example <bob@example.com>"
+    );
+    assert_eq!(awaiting[0]["sent_at"], "2026-10-05T18:30:00Z");
+    assert_eq!(awaiting[0]["message_count"], 3);
+
+    let error = call_tool(&client, "awaiting_reply", json!({ "days": 0 }))
+        .await
+        .unwrap_err();
+    let object = error.error_object().unwrap();
+    assert!(object
+        .message
+        .contains("Invalid days: 0 is out of range; it must be at least 1"));
+    assert_eq!(object.data.as_ref().unwrap()["category"], "invalid_input");
+    assert_eq!(
+        object.data.as_ref().unwrap()["invalid_fields"][0]["field"],
+        "days"
+    );
+}
@@ -0,0 +1,99 @@
+/// Batch Report Tests Module
+///
+/// This module contains tests for the per-item results returned by batch
+/// tools, checking the aggregate counts and overall status, that failed items
+/// are categorized like tool errors, and the report from batch_analyze_emails.
+use mcp_attr::schema::CallToolRequestParams;
+use mcp_gmailcal::batch::{BatchReport, BatchStatus};
+use mcp_gmailcal::utils::error_codes;
+use mcp_gmailcal::{CalendarApiError, GmailApiError};
+use serde_json::{json, Value};
+
+mod common;
+use common::mock_client;
+
+#[test]
+fn test_batch_status_from_counts() {
+    let mut report = BatchReport::new();
+    assert_eq!(report.status, BatchStatus::Success);
+    assert_eq!(report.total, 0);
+
+    report.push_success("a", json!({ "ok": true }));
+    assert_eq!(report.status, BatchStatus::Success);
+
+    report.push_failure("b", GmailApiError::NetworkError("reset".into()));
+    assert_eq!(report.status, BatchStatus::PartialSuccess);
+    assert_eq!((report.total, report.succeeded, report.failed), (2, 1, 1));
+
+    let mut report = BatchReport::new();
+    report.push_failure("a", GmailApiError::AuthError("expired".into()));
+    report.push_failure("b", CalendarApiError::ParseError("bad date".into()));
+    assert_eq!(report.status, BatchStatus::Failure);
+    assert_eq!((report.total, report.succeeded, report.failed), (2, 0, 2));
+}
+
+#[test]
+fn test_batch_report_json() {
+    let mut report = BatchReport::new();
+    report.push_success("a", json!({ "subject": "Hello" }));
+    report.push_failure("b", GmailApiError::RateLimitError("429".into()));
+
+    let mut extra = serde_json::Map::new();
+    extra.insert("analysis_type".to_string(), json!("summary"));
+    let value = report.to_json(extra);
+
+    assert_eq!(value["status"], "partial_success");
+    assert_eq!(value["analysis_type"], "summary");
+    assert_eq!(
+        value["items"][0],
+        json!({ "id": "a", "status": "ok", "result": { "subject": "Hello" } })
+    );
+
+    let failed = &value["items"][1];
+    assert_eq!(failed["status"], "error");
+    assert!(failed.get("result").is_none());
+    assert_eq!(failed["error"]["message"], "Rate limit error: 429");
+    assert_eq!(failed["error"]["category"], "rate_limit");
+    assert_eq!(failed["error"]["code"], error_codes::API_ERROR);
+    assert_eq!(failed["error"]["retryable"], true);
+    assert!(failed["error"]["retry_after"].is_u64());
+}
+
+#[tokio::test]
+async fn test_batch_analyze_reports_partial_success() {
+    let (_server, client) = mock_client(None).await;
+
+    let mut arguments = serde_json::Map::new();
+    arguments.insert(
+        "message_ids".to_string(),
+        json!(["mock-msg-001", "no-such-message"]),
+    );
+    let result = client
+        .tools_call(CallToolRequestParams {
+            name: "batch_analyze_emails".to_string(),
+            arguments: Some(arguments),
+        })
+        .await
+        .unwrap();
+
+    let text = serde_json::to_value(&result.content[0]).unwrap()["text"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let report: Value = serde_json::from_str(&text).unwrap();
+
+    assert_eq!(report["status"], "partial_success");
+    assert_eq!(report["analysis_type"], "summary");
+    assert_eq!(
+        (&report["total"], &report["succeeded"], &report["failed"]),
+        (&json!(2), &json!(1), &json!(1))
+    );
+
+    let items = report["items"].as_array().unwrap();
+    assert_eq!(items[0]["id"], "mock-msg-001");
+    assert_eq!(items[0]["status"], "ok");
+    assert_eq!(items[0]["result"]["email_id"], "mock-msg-001");
+    assert_eq!(items[1]["id"], "no-such-message");
+    assert_eq!(items[1]["status"], "error");
+    assert_eq!(items[1]["error"]["category"], "not_found");
+}
@@ -0,0 +1,189 @@
+/// Bounce Tests Module
+///
+/// This module contains tests for parsing delivery status notifications, and
+/// for get_email and the list_bounces tool against the mock server.
+use mcp_gmailcal::bounces::{status_reason, DeliveryReport};
+use serde_json::{json, Value};
+
+mod common;
+use common::{call_tool, mock_client};
+
+const DELIVERY_STATUS: &str = "Reporting-MTA: dns; googlemail.com\r
+Arrival-Date: Mon, 12 Oct 2026 09:14:02 -0700 (PDT)\r
+\r
+Final-Recipient: rfc822; bob@example.net\r
+Original-Recipient: rfc822;bob@example.net\r
+Action: failed\r
+Status: 5.1.1\r
+Remote-MTA: dns; mx.example.net. (192.0.2.25, the server for the domain example.net.)\r
+Diagnostic-Code: smtp; 550-5.1.1 The email account that you tried to reach does\r
+ not exist.\r
+Last-Attempt-Date: Mon, 12 Oct 2026 09:14:03 -0700 (PDT)\r
+\r
+Final-Recipient: rfc822; <carol@example.org>\r
+Action: delayed\r
+Status: 4.4.7\r
+";
+
+const ORIGINAL_HEADERS: &str = "From: Demo <demo@example.com>\r
+To: bob@example.net, carol@example.org\r
+Subject: Quarterly numbers\r
+Message-ID: <CAF-quarterly-42@mail.example.com>\r
+";
+
+#[test]
+fn test_parse_delivery_status() {
+    let report = DeliveryReport::parse(DELIVERY_STATUS).unwrap();
+    assert_eq!(report.reporting_mta.as_deref(), Some("googlemail.com"));
+    assert_eq!(
+        report.arrival_date.as_deref(),
+        Some("Mon, 12 Oct 2026 09:14:02 -0700 (PDT)")
+    );
+    assert_eq!(report.recipients.len(), 2);
+
+    let bob = &report.recipients[0];
+    assert_eq!(bob.recipient, "bob@example.net");
+    assert_eq!(bob.original_recipient, None);
+    assert_eq!(bob.action.as_deref(), Some("failed"));
+    assert_eq!(bob.status.as_deref(), Some("5.1.1"));
+    assert!(bob.permanent);
+    assert_eq!(
+        bob.reason.as_deref(),
+        Some("Bad destination mailbox address")
+    );
+    // Folded lines are joined
+    assert_eq!(
+        bob.diagnostic_code.as_deref(),
+        Some("550-5.1.1 The email account that you tried to reach does not exist.")
+    );
+
+    let carol = &report.recipients[1];
+    assert_eq!(carol.recipient, "carol@example.org");
+    assert!(!carol.permanent);
+    assert_eq!(carol.reason.as_deref(), Some("Delivery time expired"));
+
+    assert_eq!(report.failed_recipients(), vec!["bob@example.net"]);
+
+    // No per-recipient fields, nothing to report
+    assert_eq!(
+        DeliveryReport::parse("Reporting-MTA: dns; x.example\r\n"),
+        None
+    );
+    assert_eq!(DeliveryReport::parse(""), None);
+}
+
+#[test]
+fn test_status_reason() {
+    assert_eq!(status_reason("5.2.2").as_deref(), Some("Mailbox full"));
+    assert_eq!(
+        status_reason("5.7.1").as_deref(),
+        Some("Delivery not authorized, message refused")
+    );
+    assert_eq!(status_reason("5.9.9").as_deref(), Some("Permanent failure"));
+    assert_eq!(status_reason("4.9.9").as_deref(), Some("Temporary failure"));
+    assert_eq!(status_reason("nonsense"), None);
+}
+
+fn encode(text: &str) -> String {
+    base64::encode_config(text, base64::URL_SAFE)
+}
+
+fn bounce_message() -> Value {
+    json!({
+        "id": "msg-bounce",
+        "threadId": "thread-bounce",
+        "labelIds": ["INBOX"],
+        "snippet": "Address not found",
+        "payload": {
+            "mimeType": "multipart/report",
+            "headers": [
+                { "name": "From", "value": "Mail Delivery Subsystem <mailer-daemon@googlemail.com>" },
+                { "name": "Subject", "value": "Delivery Status Notification (Failure)" },
+                { "name": "Date", "value": "Mon, 12 Oct 2026 09:14:03 -0700" },
+                { "name": "Content-Type", "value": "multipart/report; report-type=delivery-status; boundary=\"b1\"" }
+            ],
+            "parts": [
+                {
+                    "mimeType": "multipart/alternative",
+                    "parts": [
+                        { "mimeType": "text/plain", "body": { "data": encode("Address not found") } }
+                    ]
+                },
+                { "mimeType": "message/delivery-status", "body": { "data": encode(DELIVERY_STATUS) } },
+                { "mimeType": "text/rfc822-headers", "body": { "data": encode(ORIGINAL_HEADERS) } }
+            ]
+        }
+    })
+}
+
+#[tokio::test]
+async fn test_bounce_tools() {
+    let dir = tempfile::tempdir().unwrap();
+    let users = dir.path().join("gmail/v1/users/me");
+    std::fs::create_dir_all(users.join("messages")).unwrap();
+    std::fs::write(
+        users.join("messages/msg-bounce.json"),
+        bounce_message().to_string(),
+    )
+    .unwrap();
+    // The mock ignores the query, so a regular message is listed too
+    let list = json!({
+        "messages": [
+            { "id": "msg-bounce", "threadId": "thread-bounce" },
+            { "id": "mock-msg-001", "threadId": "mock-thread-001" }
+        ],
+        "resultSizeEstimate": 2
+    });
+    std::fs::write(users.join("messages.json"), list.to_string()).unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    let email = call_tool(&client, "get_email", json!({ "message_id": "msg-bounce" }))
+        .await
+        .unwrap();
+    assert_eq!(email["body_text"], "Address not found");
+    let report = &email["delivery_report"];
+    assert_eq!(report["original_subject"], "Quarterly numbers");
+    assert_eq!(
+        report["original_message_id"],
+        "<CAF-quarterly-42@mail.example.com>"
+    );
+    assert_eq!(report["recipients"][0]["status"], "5.1.1");
+
+    let email = call_tool(
+        &client,
+        "get_email",
+        json!({ "message_id": "mock-msg-001" }),
+    )
+    .await
+    .unwrap();
+    assert!(email["delivery_report"].is_null());
+
+    let result = call_tool(&client, "list_bounces", json!({ "time_range": "7d" }))
+        .await
+        .unwrap();
+    assert_eq!(
+        result["query"],
+        "newer_than:7d -from:me from:(mailer-daemon OR postmaster)"
+    );
+    assert_eq!(result["messages_checked"], 2);
+    assert_eq!(result["failed_recipients"], json!(["bob@example.net"]));
+    let bounces = result["bounces"].as_array().unwrap();
+    assert_eq!(bounces.len(), 1);
+    assert_eq!(bounces[0]["message_id"], "msg-bounce");
+    assert_eq!(bounces[0]["date"], "Mon, 12 Oct 2026 09:14:03 -0700");
+    assert_eq!(bounces[0]["reporting_mta"], "googlemail.com");
+    assert_eq!(
+        bounces[0]["recipients"][0]["reason"],
+        "Bad destination mailbox address"
+    );
+
+    let error = call_tool(&client, "list_bounces", json!({ "time_range": "soon" }))
+        .await
+        .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("Invalid time range 'soon'"));
+}
@@ -0,0 +1,211 @@
+/// Buffer Tests Module
+///
+/// This module contains tests for travel buffer events around meetings, and
+/// for the add_buffer tool and block_time_for_tasks keeping clear of buffers
+/// against the mock server.
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use mcp_gmailcal::buffers::{
+    buffer_busy, buffer_events, is_buffer, validate_buffer_minutes, BufferSide, BUFFER_CATEGORY,
+};
+use mcp_gmailcal::calendar_api::CalendarEvent;
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 10, 20, hour, minute, 0).unwrap()
+}
+
+fn meeting() -> CalendarEvent {
+    CalendarEvent {
+        id: Some("event-offsite".to_string()),
+        summary: "Offsite".to_string(),
+        location: Some("Client HQ".to_string()),
+        start_time: at(10, 0),
+        end_time: at(11, 30),
+        time_zone: Some("Europe/Berlin".to_string()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_validate_buffer_minutes() {
+    assert!(validate_buffer_minutes(15, 0).is_ok());
+    assert!(validate_buffer_minutes(0, 240).is_ok());
+    assert!(validate_buffer_minutes(0, 0).is_err());
+    assert!(validate_buffer_minutes(30, 241)
+        .unwrap_err()
+        .contains("at most 240 minutes"));
+}
+
+#[test]
+fn test_buffer_events() {
+    let buffers = buffer_events(&meeting(), 30, 15);
+    assert_eq!(buffers.len(), 2);
+
+    let (side, before) = &buffers[0];
+    assert_eq!(*side, BufferSide::Before);
+    assert_eq!(before.summary, "Buffer before Offsite");
+    assert_eq!(before.start_time, at(9, 30));
+    assert_eq!(before.end_time, at(10, 0));
+    assert_eq!(before.transparency.as_deref(), Some("transparent"));
+    assert_eq!(before.category.as_deref(), Some(BUFFER_CATEGORY));
+    assert_eq!(before.time_zone.as_deref(), Some("Europe/Berlin"));
+    assert_eq!(before.reminders, Some(Vec::new()));
+    assert!(before
+        .description
+        .as_deref()
+        .unwrap()
+        .contains("event-offsite"));
+
+    let (side, after) = &buffers[1];
+    assert_eq!(*side, BufferSide::After);
+    assert_eq!(after.summary, "Buffer after Offsite");
+    assert_eq!(after.start_time, at(11, 30));
+    assert_eq!(after.end_time, at(11, 45));
+
+    // A side of no minutes is left out
+    let buffers = buffer_events(&meeting(), 0, 20);
+    assert_eq!(buffers.len(), 1);
+    assert_eq!(buffers[0].0, BufferSide::After);
+}
+
+#[test]
+fn test_buffer_busy() {
+    let buffers: Vec<CalendarEvent> = buffer_events(&meeting(), 30, 15)
+        .into_iter()
+        .map(|(_, buffer)| buffer)
+        .collect();
+    let mut events = vec![meeting()];
+    events.extend(buffers);
+    assert!(!is_buffer(&events[0]));
+    assert!(is_buffer(&events[1]));
+
+    let busy = buffer_busy(&events);
+    assert_eq!(busy.len(), 2);
+    assert_eq!((busy[0].start, busy[0].end), (at(9, 30), at(10, 0)));
+    assert_eq!(
+        (busy[1].start, busy[1].end),
+        (at(11, 30), at(11, 30) + Duration::minutes(15))
+    );
+}
+
+#[tokio::test]
+async fn test_buffer_tools() {
+    std::env::set_var("USER_TIMEZONE", "Europe/Berlin");
+    let dir = tempfile::tempdir().unwrap();
+    let calendar = dir.path().join("calendar/v3");
+    let events = calendar.join("calendars/primary/events");
+    std::fs::create_dir_all(&events).unwrap();
+    let offsite = json!({
+        "id": "event-offsite",
+        "summary": "Offsite",
+        "start": { "dateTime": "2030-03-05T10:00:00Z", "timeZone": "Europe/Berlin" },
+        "end": { "dateTime": "2030-03-05T11:00:00Z", "timeZone": "Europe/Berlin" }
+    });
+    std::fs::write(events.join("event-offsite.json"), offsite.to_string()).unwrap();
+    let all_day = json!({
+        "id": "event-holiday",
+        "summary": "Holiday",
+        "start": { "date": "2030-03-06" },
+        "end": { "date": "2030-03-07" }
+    });
+    std::fs::write(events.join("event-holiday.json"), all_day.to_string()).unwrap();
+
+    // Working hours on 5 March 2030 are 08:00 to 16:00 UTC, with a buffer
+    // left free from 11:00 to 11:30
+    let free_busy = json!({
+        "calendars": {
+            "primary": {
+                "busy": [
+                    { "start": "2030-03-05T08:00:00Z", "end": "2030-03-05T10:00:00Z" },
+                    { "start": "2030-03-05T10:00:00Z", "end": "2030-03-05T11:00:00Z" },
+                    { "start": "2030-03-05T12:00:00Z", "end": "2030-03-05T15:30:00Z" }
+                ]
+            }
+        }
+    });
+    std::fs::write(calendar.join("freeBusy.post.json"), free_busy.to_string()).unwrap();
+    let listing = json!({
+        "items": [
+            offsite,
+            {
+                "id": "event-buffer",
+                "summary": "Buffer after Offsite",
+                "transparency": "transparent",
+                "start": { "dateTime": "2030-03-05T11:00:00Z" },
+                "end": { "dateTime": "2030-03-05T11:30:00Z" },
+                "extendedProperties": { "private": { "category": "Buffer" } }
+            }
+        ]
+    });
+    std::fs::write(
+        calendar.join("calendars/primary/events.json"),
+        listing.to_string(),
+    )
+    .unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    let result = call_tool(
+        &client,
+        "add_buffer",
+        json!({ "event_id": "event-offsite", "minutes_before": 30, "minutes_after": 15 }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["status"], "success");
+    assert_eq!(result["event_id"], "event-offsite");
+    assert_eq!(result["calendar_id"], "primary");
+    let items = result["items"].as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["id"], "before");
+    assert!(items[0]["result"]["event_id"].is_string());
+    assert_eq!(items[0]["result"]["start_time"], "2030-03-05T09:30:00Z");
+    assert_eq!(items[1]["id"], "after");
+    assert_eq!(items[1]["result"]["end_time"], "2030-03-05T11:15:00Z");
+
+    let error = call_tool(
+        &client,
+        "add_buffer",
+        json!({ "event_id": "event-offsite" }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("minutes_before"));
+
+    let error = call_tool(
+        &client,
+        "add_buffer",
+        json!({ "event_id": "event-holiday", "minutes_before": 30 }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("takes whole days"));
+
+    // Free/busy leaves the buffer free
+    let tasks = json!({ "tasks": ["Inbox 30m"], "day": "2030-03-05" });
+    let plan = call_tool(&client, "block_time_for_tasks", tasks.clone())
+        .await
+        .unwrap();
+    assert_eq!(plan["scheduled"][0]["start_time"], "2030-03-05T11:00:00Z");
+
+    // Keeping clear of it leaves the half hour after it
+    let mut tasks = tasks;
+    tasks["respect_buffers"] = json!(true);
+    let plan = call_tool(&client, "block_time_for_tasks", tasks)
+        .await
+        .unwrap();
+    assert_eq!(plan["scheduled"][0]["start_time"], "2030-03-05T11:30:00Z");
+
+    std::env::remove_var("USER_TIMEZONE");
+}
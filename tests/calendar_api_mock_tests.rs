@@ -40,12 +40,16 @@ fn create_test_event(
                 display_name: Some("Attendee 1".to_string()),
                 response_status: Some("accepted".to_string()),
                 optional: None,
+                time_zone: None,
+                self_: None,
             },
             Attendee {
                 email: "attendee2@example.com".to_string(),
                 display_name: Some("Attendee 2".to_string()),
                 response_status: Some("tentative".to_string()),
                 optional: None,
+                time_zone: None,
+                self_: None,
             },
         ],
         conference_data: Some(ConferenceData {
@@ -77,6 +81,7 @@ fn create_test_event(
             display_name: Some("Event Organizer".to_string()),
             self_: Some(false),
         }),
+        ..Default::default()
     }
 }
 
@@ -308,12 +313,12 @@ mod tests {
                     display_name: Some("Test User".to_string()),
                     response_status: None,
                     optional: None,
+                    time_zone: None,
+                    self_: None,
                 },
             ],
             html_link: None, // Will be assigned
-            conference_data: None,
-            creator: None,
-            organizer: None,
+            ..Default::default()
         };
         
         // Test the function
@@ -351,7 +356,6 @@ mod tests {
         
         // Create an event with conference data
         let conference_event = CalendarEvent {
-            id: None,
             summary: "Conference Event".to_string(),
             description: Some("Event with video conference".to_string()),
             location: Some("Virtual".to_string()),
@@ -363,9 +367,10 @@ mod tests {
                     display_name: Some("Test User".to_string()),
                     response_status: None,
                     optional: None,
+                    time_zone: None,
+                    self_: None,
                 },
             ],
-            html_link: None,
             conference_data: Some(ConferenceData {
                 conference_solution: Some(ConferenceSolution {
                     name: "Google Meet".to_string(),
@@ -379,8 +384,7 @@ mod tests {
                     },
                 ],
             }),
-            creator: None,
-            organizer: None,
+            ..Default::default()
         };
         
         // Test the function
@@ -425,17 +429,10 @@ mod tests {
         
         // Test empty calendar ID
         let valid_event = CalendarEvent {
-            id: None,
             summary: "Valid Event".to_string(),
-            description: None,
-            location: None,
             start_time: Utc::now() + Duration::hours(1),
             end_time: Utc::now() + Duration::hours(2),
-            attendees: vec![],
-            html_link: None,
-            conference_data: None,
-            creator: None,
-            organizer: None,
+            ..Default::default()
         };
         
         let result = mock.create_event("", valid_event);
@@ -449,17 +446,10 @@ mod tests {
         
         // Test empty summary
         let invalid_summary_event = CalendarEvent {
-            id: None,
             summary: "".to_string(),
-            description: None,
-            location: None,
             start_time: Utc::now() + Duration::hours(1),
             end_time: Utc::now() + Duration::hours(2),
-            attendees: vec![],
-            html_link: None,
-            conference_data: None,
-            creator: None,
-            organizer: None,
+            ..Default::default()
         };
         
         let result = mock.create_event("primary", invalid_summary_event);
@@ -473,17 +463,10 @@ mod tests {
         
         // Test invalid time range
         let invalid_time_event = CalendarEvent {
-            id: None,
             summary: "Invalid Time Event".to_string(),
-            description: None,
-            location: None,
             start_time: Utc::now() + Duration::hours(2),
             end_time: Utc::now() + Duration::hours(1), // End before start
-            attendees: vec![],
-            html_link: None,
-            conference_data: None,
-            creator: None,
-            organizer: None,
+            ..Default::default()
         };
         
         let result = mock.create_event("primary", invalid_time_event);
@@ -507,17 +490,10 @@ mod tests {
         
         // Create a valid event
         let valid_event = CalendarEvent {
-            id: None,
             summary: "Test Event".to_string(),
-            description: None,
-            location: None,
             start_time: Utc::now() + Duration::hours(1),
             end_time: Utc::now() + Duration::hours(2),
-            attendees: vec![],
-            html_link: None,
-            conference_data: None,
-            creator: None,
-            organizer: None,
+            ..Default::default()
         };
         
         // Test the function
@@ -5,251 +5,43 @@
 ///
 use chrono::{DateTime, Duration, TimeZone, Utc};
 use mockall::mock;
-use mcp_gmailcal::calendar_api::{Attendee, CalendarEvent, CalendarInfo, CalendarList, ConferenceData, ConferenceSolution, EntryPoint, EventOrganizer};
+use mcp_gmailcal::calendar_api::{Attendee, CalendarEvent, CalendarInfo, ConferenceData, ConferenceSolution, EntryPoint, EventOrganizer};
+use mcp_gmailcal::api::CalendarApi;
 use mcp_gmailcal::errors::CalendarApiError;
+use mcp_gmailcal::test_util::MockCalendarApi;
 use reqwest::Client;
-use std::sync::Arc;
-use std::sync::Mutex;
-use uuid::Uuid;
-
-// Define a proper interface for CalendarClient that we can mock
-trait CalendarClientInterface {
-    fn list_calendars(&self) -> Result<CalendarList, CalendarApiError>;
-    fn list_events(
-        &self,
-        calendar_id: &str,
-        max_results: Option<u32>,
-        time_min: Option<DateTime<Utc>>,
-        time_max: Option<DateTime<Utc>>,
-    ) -> Result<Vec<CalendarEvent>, CalendarApiError>;
-    fn create_event(
-        &self,
-        calendar_id: &str,
-        event: CalendarEvent,
-    ) -> Result<CalendarEvent, CalendarApiError>;
-    fn get_event(
-        &self,
-        calendar_id: &str,
-        event_id: &str,
-    ) -> Result<CalendarEvent, CalendarApiError>;
-}
-
-// Wrapper for CalendarClient that we can test against
-struct MockableCalendarClient {
-    client: Arc<Mutex<dyn CalendarClientInterface + Send + Sync>>,
-}
-
-impl MockableCalendarClient {
-    fn new(client: Arc<Mutex<dyn CalendarClientInterface + Send + Sync>>) -> Self {
-        Self { client }
-    }
-
-    fn list_calendars(&self) -> Result<CalendarList, CalendarApiError> {
-        let client = self.client.lock().unwrap();
-        client.list_calendars()
-    }
-
-    fn list_events(
-        &self,
-        calendar_id: &str,
-        max_results: Option<u32>,
-        time_min: Option<DateTime<Utc>>,
-        time_max: Option<DateTime<Utc>>,
-    ) -> Result<Vec<CalendarEvent>, CalendarApiError> {
-        let client = self.client.lock().unwrap();
-        client.list_events(calendar_id, max_results, time_min, time_max)
-    }
 
-    fn create_event(
-        &self,
-        calendar_id: &str,
-        event: CalendarEvent,
-    ) -> Result<CalendarEvent, CalendarApiError> {
-        let client = self.client.lock().unwrap();
-        client.create_event(calendar_id, event)
-    }
-
-    fn get_event(
-        &self,
-        calendar_id: &str,
-        event_id: &str,
-    ) -> Result<CalendarEvent, CalendarApiError> {
-        let client = self.client.lock().unwrap();
-        client.get_event(calendar_id, event_id)
-    }
-}
-
-// Mock implementation of CalendarClientInterface for testing
-struct MockCalendarClient {
-    calendars: Vec<CalendarInfo>,
-    events: Vec<CalendarEvent>,
-    should_fail: bool,
+// Sample calendars served by the mock Calendar API
+fn sample_calendars() -> Vec<CalendarInfo> {
+    vec![
+        CalendarInfo {
+            id: "primary".to_string(),
+            summary: "Primary Calendar".to_string(),
+            description: Some("Your main calendar".to_string()),
+            primary: Some(true),
+        },
+        CalendarInfo {
+            id: "work@example.com".to_string(),
+            summary: "Work Calendar".to_string(),
+            description: Some("Calendar for work events".to_string()),
+            primary: Some(false),
+        },
+        CalendarInfo {
+            id: "family@example.com".to_string(),
+            summary: "Family Calendar".to_string(),
+            description: Some("Calendar for family events".to_string()),
+            primary: Some(false),
+        },
+    ]
 }
 
-impl MockCalendarClient {
-    fn new() -> Self {
-        // Initialize with sample data
-        let calendars = vec![
-            CalendarInfo {
-                id: "primary".to_string(),
-                summary: "Primary Calendar".to_string(),
-                description: Some("Your main calendar".to_string()),
-                primary: Some(true),
-            },
-            CalendarInfo {
-                id: "work@example.com".to_string(),
-                summary: "Work Calendar".to_string(),
-                description: Some("Calendar for work events".to_string()),
-                primary: Some(false),
-            },
-            CalendarInfo {
-                id: "family@example.com".to_string(),
-                summary: "Family Calendar".to_string(),
-                description: Some("Calendar for family events".to_string()),
-                primary: Some(false),
-            },
-        ];
-
-        let events = vec![
-            create_test_event("event1", "Meeting with team", "Office", Utc::now() + Duration::hours(1), Utc::now() + Duration::hours(2)),
-            create_test_event("event2", "Lunch with client", "Restaurant", Utc::now() + Duration::hours(5), Utc::now() + Duration::hours(6)),
-            create_test_event("event3", "Project deadline", "Office", Utc::now() + Duration::days(2), Utc::now() + Duration::days(2) + Duration::hours(8)),
-        ];
-
-        Self {
-            calendars,
-            events,
-            should_fail: false,
-        }
-    }
-
-    fn with_failure(mut self) -> Self {
-        self.should_fail = true;
-        self
-    }
-}
-
-impl CalendarClientInterface for MockCalendarClient {
-    fn list_calendars(&self) -> Result<CalendarList, CalendarApiError> {
-        if self.should_fail {
-            return Err(CalendarApiError::ApiError("Failed to list calendars".to_string()));
-        }
-        
-        Ok(CalendarList {
-            calendars: self.calendars.clone(),
-            next_page_token: None,
-        })
-    }
-
-    fn list_events(
-        &self,
-        calendar_id: &str,
-        max_results: Option<u32>,
-        time_min: Option<DateTime<Utc>>,
-        time_max: Option<DateTime<Utc>>,
-    ) -> Result<Vec<CalendarEvent>, CalendarApiError> {
-        if self.should_fail {
-            return Err(CalendarApiError::ApiError("Failed to list events".to_string()));
-        }
-
-        if calendar_id.is_empty() {
-            return Err(CalendarApiError::ApiError("Calendar ID cannot be empty".to_string()));
-        }
-
-        // Filter events based on criteria
-        let filtered_events = self.events.iter()
-            .filter(|event| {
-                // Match calendar ID (we're simplifying here by not actually filtering by calendar)
-                
-                // Filter by time range if specified
-                let time_min_check = match time_min {
-                    Some(min_time) => event.start_time >= min_time,
-                    None => true,
-                };
-
-                let time_max_check = match time_max {
-                    Some(max_time) => event.start_time <= max_time,
-                    None => true,
-                };
-
-                time_min_check && time_max_check
-            })
-            .cloned()
-            .collect::<Vec<CalendarEvent>>();
-
-        // Apply max_results if specified
-        if let Some(max) = max_results {
-            Ok(filtered_events.into_iter().take(max as usize).collect())
-        } else {
-            Ok(filtered_events)
-        }
-    }
-
-    fn create_event(
-        &self,
-        calendar_id: &str,
-        mut event: CalendarEvent,
-    ) -> Result<CalendarEvent, CalendarApiError> {
-        if self.should_fail {
-            return Err(CalendarApiError::ApiError("Failed to create event".to_string()));
-        }
-
-        if calendar_id.is_empty() {
-            return Err(CalendarApiError::ApiError("Calendar ID cannot be empty".to_string()));
-        }
-
-        // Basic validation
-        if event.summary.is_empty() {
-            return Err(CalendarApiError::EventFormatError("Event summary cannot be empty".to_string()));
-        }
-
-        if event.end_time <= event.start_time {
-            return Err(CalendarApiError::EventFormatError("End time must be after start time".to_string()));
-        }
-
-        // Assign an ID if not present
-        if event.id.is_none() {
-            event.id = Some(Uuid::new_v4().to_string());
-        }
-
-        // Add HTML link if not present
-        if event.html_link.is_none() {
-            event.html_link = Some(format!(
-                "https://calendar.google.com/calendar/event?eid={}",
-                event.id.as_ref().unwrap()
-            ));
-        }
-
-        Ok(event)
-    }
-
-    fn get_event(
-        &self,
-        calendar_id: &str,
-        event_id: &str,
-    ) -> Result<CalendarEvent, CalendarApiError> {
-        if self.should_fail {
-            return Err(CalendarApiError::ApiError("Failed to get event".to_string()));
-        }
-
-        if calendar_id.is_empty() {
-            return Err(CalendarApiError::ApiError("Calendar ID cannot be empty".to_string()));
-        }
-
-        if event_id.is_empty() {
-            return Err(CalendarApiError::ApiError("Event ID cannot be empty".to_string()));
-        }
-
-        // Find event by ID
-        let event = self.events.iter()
-            .find(|e| e.id.as_ref().map_or(false, |id| id == event_id))
-            .cloned();
-
-        match event {
-            Some(event) => Ok(event),
-            None => Err(CalendarApiError::EventRetrievalError(format!("Event {} not found", event_id))),
-        }
-    }
+// Sample events in the primary calendar
+fn sample_events() -> Vec<CalendarEvent> {
+    vec![
+        create_test_event("event1", "Meeting with team", "Office", Utc::now() + Duration::hours(1), Utc::now() + Duration::hours(2)),
+        create_test_event("event2", "Lunch with client", "Restaurant", Utc::now() + Duration::hours(5), Utc::now() + Duration::hours(6)),
+        create_test_event("event3", "Project deadline", "Office", Utc::now() + Duration::days(2), Utc::now() + Duration::days(2) + Duration::hours(8)),
+    ]
 }
 
 // Helper function to create a test event
@@ -283,12 +75,16 @@ fn create_test_event(
                 display_name: Some("Attendee 1".to_string()),
                 response_status: Some("accepted".to_string()),
                 optional: None,
+                time_zone: None,
+                self_: None,
             },
             Attendee {
                 email: "attendee2@example.com".to_string(),
                 display_name: Some("Attendee 2".to_string()),
                 response_status: Some("tentative".to_string()),
                 optional: None,
+                time_zone: None,
+                self_: None,
             },
         ],
         html_link: Some(format!(
@@ -313,6 +109,7 @@ fn create_test_event(
                 },
             ],
         }),
+        ..Default::default()
     }
 }
 
@@ -332,23 +129,21 @@ mod comprehensive_calendar_tests {
     use super::*;
 
     // Helper to create a test client
-    fn create_test_client() -> MockableCalendarClient {
-        let mock_client = MockCalendarClient::new();
-        let client = Arc::new(Mutex::new(mock_client));
-        MockableCalendarClient::new(client)
+    fn create_test_client() -> MockCalendarApi {
+        MockCalendarApi::new()
+            .with_calendars(sample_calendars())
+            .with_events("primary", sample_events())
     }
 
-    // Helper to create a failing test client
-    fn create_failing_client() -> MockableCalendarClient {
-        let mock_client = MockCalendarClient::new().with_failure();
-        let client = Arc::new(Mutex::new(mock_client));
-        MockableCalendarClient::new(client)
+    // Helper to create a test client whose calls all fail with the given error
+    fn create_failing_client(error: fn() -> CalendarApiError) -> MockCalendarApi {
+        create_test_client().failing_with(error)
     }
 
-    #[test]
-    fn test_list_calendars_success() {
+    #[tokio::test]
+    async fn test_list_calendars_success() {
         let client = create_test_client();
-        let result = client.list_calendars();
+        let result = client.list_calendars().await;
         
         assert!(result.is_ok());
         let calendars = result.unwrap();
@@ -369,10 +164,12 @@ mod comprehensive_calendar_tests {
         assert_eq!(family_calendar.summary, "Family Calendar");
     }
 
-    #[test]
-    fn test_list_calendars_failure() {
-        let client = create_failing_client();
-        let result = client.list_calendars();
+    #[tokio::test]
+    async fn test_list_calendars_failure() {
+        let client = create_failing_client(|| {
+            CalendarApiError::ApiError("Failed to list calendars".to_string())
+        });
+        let result = client.list_calendars().await;
         
         assert!(result.is_err());
         match result {
@@ -383,27 +180,27 @@ mod comprehensive_calendar_tests {
         }
     }
 
-    #[test]
-    fn test_list_events_success() {
+    #[tokio::test]
+    async fn test_list_events_success() {
         let client = create_test_client();
 
         // Test listing events with various parameters
         
         // No filters
-        let result = client.list_events("primary", None, None, None);
+        let result = client.list_events("primary", None, None, None).await;
         assert!(result.is_ok());
         let events = result.unwrap();
         assert_eq!(events.len(), 3); // All events
         
         // With max_results
-        let result = client.list_events("primary", Some(2), None, None);
+        let result = client.list_events("primary", Some(2), None, None).await;
         assert!(result.is_ok());
         let events = result.unwrap();
         assert_eq!(events.len(), 2); // Limited to 2 events
         
         // With time filters - future implementation would test this more thoroughly
         let time_min = Utc::now();
-        let result = client.list_events("primary", None, Some(time_min), None);
+        let result = client.list_events("primary", None, Some(time_min), None).await;
         assert!(result.is_ok());
         // All events should be after time_min because they're created in the future
         let events = result.unwrap();
@@ -413,10 +210,12 @@ mod comprehensive_calendar_tests {
         }
     }
 
-    #[test]
-    fn test_list_events_failure() {
-        let client = create_failing_client();
-        let result = client.list_events("primary", None, None, None);
+    #[tokio::test]
+    async fn test_list_events_failure() {
+        let client = create_failing_client(|| {
+            CalendarApiError::ApiError("Failed to list events".to_string())
+        });
+        let result = client.list_events("primary", None, None, None).await;
         
         assert!(result.is_err());
         match result {
@@ -427,8 +226,8 @@ mod comprehensive_calendar_tests {
         }
     }
 
-    #[test]
-    fn test_create_event_success() {
+    #[tokio::test]
+    async fn test_create_event_success() {
         let client = create_test_client();
 
         // Create a new event
@@ -445,15 +244,15 @@ mod comprehensive_calendar_tests {
                     display_name: Some("Test User".to_string()),
                     response_status: None,
                     optional: None,
+                    time_zone: None,
+                    self_: None,
                 },
             ],
             html_link: None, // Will be assigned
-            conference_data: None,
-            creator: None,
-            organizer: None,
+            ..Default::default()
         };
 
-        let result = client.create_event("primary", new_event.clone());
+        let result = client.create_event("primary", new_event.clone()).await;
         
         assert!(result.is_ok());
         let created_event = result.unwrap();
@@ -470,28 +269,23 @@ mod comprehensive_calendar_tests {
         assert_eq!(created_event.end_time, new_event.end_time);
     }
 
-    #[test]
-    fn test_create_event_validation_failure() {
+    #[tokio::test]
+    async fn test_create_event_validation_failure() {
         let client = create_test_client();
 
         // Test invalid events
         
         // Empty summary
         let invalid_event = CalendarEvent {
-            id: None,
             summary: "".to_string(), // Empty summary
             description: Some("Description".to_string()),
             location: Some("Location".to_string()),
             start_time: Utc::now() + Duration::hours(1),
             end_time: Utc::now() + Duration::hours(2),
-            attendees: vec![],
-            html_link: None,
-            conference_data: None,
-            creator: None,
-            organizer: None,
+            ..Default::default()
         };
 
-        let result = client.create_event("primary", invalid_event);
+        let result = client.create_event("primary", invalid_event).await;
         assert!(result.is_err());
         match result {
             Err(CalendarApiError::EventFormatError(msg)) => {
@@ -502,20 +296,15 @@ mod comprehensive_calendar_tests {
 
         // End time before start time
         let invalid_event = CalendarEvent {
-            id: None,
             summary: "Invalid Time Event".to_string(),
             description: Some("Description".to_string()),
             location: Some("Location".to_string()),
             start_time: Utc::now() + Duration::hours(2), // Later
             end_time: Utc::now() + Duration::hours(1),   // Earlier
-            attendees: vec![],
-            html_link: None,
-            conference_data: None,
-            creator: None,
-            organizer: None,
+            ..Default::default()
         };
 
-        let result = client.create_event("primary", invalid_event);
+        let result = client.create_event("primary", invalid_event).await;
         assert!(result.is_err());
         match result {
             Err(CalendarApiError::EventFormatError(msg)) => {
@@ -526,20 +315,15 @@ mod comprehensive_calendar_tests {
 
         // Empty calendar ID
         let valid_event = CalendarEvent {
-            id: None,
             summary: "Valid Event".to_string(),
             description: Some("Description".to_string()),
             location: Some("Location".to_string()),
             start_time: Utc::now() + Duration::hours(1),
             end_time: Utc::now() + Duration::hours(2),
-            attendees: vec![],
-            html_link: None,
-            conference_data: None,
-            creator: None,
-            organizer: None,
+            ..Default::default()
         };
 
-        let result = client.create_event("", valid_event);
+        let result = client.create_event("", valid_event).await;
         assert!(result.is_err());
         match result {
             Err(CalendarApiError::ApiError(msg)) => {
@@ -549,25 +333,22 @@ mod comprehensive_calendar_tests {
         }
     }
 
-    #[test]
-    fn test_create_event_failure() {
-        let client = create_failing_client();
+    #[tokio::test]
+    async fn test_create_event_failure() {
+        let client = create_failing_client(|| {
+            CalendarApiError::ApiError("Failed to create event".to_string())
+        });
 
         let event = CalendarEvent {
-            id: None,
             summary: "Test Event".to_string(),
             description: Some("Description".to_string()),
             location: Some("Location".to_string()),
             start_time: Utc::now() + Duration::hours(1),
             end_time: Utc::now() + Duration::hours(2),
-            attendees: vec![],
-            html_link: None,
-            conference_data: None,
-            creator: None,
-            organizer: None,
+            ..Default::default()
         };
 
-        let result = client.create_event("primary", event);
+        let result = client.create_event("primary", event).await;
         
         assert!(result.is_err());
         match result {
@@ -578,12 +359,12 @@ mod comprehensive_calendar_tests {
         }
     }
 
-    #[test]
-    fn test_get_event_success() {
+    #[tokio::test]
+    async fn test_get_event_success() {
         let client = create_test_client();
 
         // Get existing event
-        let result = client.get_event("primary", "event1");
+        let result = client.get_event("primary", "event1").await;
         
         assert!(result.is_ok());
         let event = result.unwrap();
@@ -606,12 +387,12 @@ mod comprehensive_calendar_tests {
         assert_eq!(event.attendees[1].email, "attendee2@example.com");
     }
 
-    #[test]
-    fn test_get_event_not_found() {
+    #[tokio::test]
+    async fn test_get_event_not_found() {
         let client = create_test_client();
 
         // Get non-existent event
-        let result = client.get_event("primary", "nonexistent");
+        let result = client.get_event("primary", "nonexistent").await;
         
         assert!(result.is_err());
         match result {
@@ -622,12 +403,12 @@ mod comprehensive_calendar_tests {
         }
     }
 
-    #[test]
-    fn test_get_event_invalid_params() {
+    #[tokio::test]
+    async fn test_get_event_invalid_params() {
         let client = create_test_client();
 
         // Empty calendar ID
-        let result = client.get_event("", "event1");
+        let result = client.get_event("", "event1").await;
         assert!(result.is_err());
         match result {
             Err(CalendarApiError::ApiError(msg)) => {
@@ -637,7 +418,7 @@ mod comprehensive_calendar_tests {
         }
 
         // Empty event ID
-        let result = client.get_event("primary", "");
+        let result = client.get_event("primary", "").await;
         assert!(result.is_err());
         match result {
             Err(CalendarApiError::ApiError(msg)) => {
@@ -647,11 +428,13 @@ mod comprehensive_calendar_tests {
         }
     }
 
-    #[test]
-    fn test_get_event_failure() {
-        let client = create_failing_client();
+    #[tokio::test]
+    async fn test_get_event_failure() {
+        let client = create_failing_client(|| {
+            CalendarApiError::ApiError("Failed to get event".to_string())
+        });
 
-        let result = client.get_event("primary", "event1");
+        let result = client.get_event("primary", "event1").await;
         
         assert!(result.is_err());
         match result {
@@ -703,8 +486,8 @@ mod comprehensive_calendar_tests {
         assert_eq!(date_str, "2025-05-15");
     }
 
-    #[test]
-    fn test_recurring_event_parameters() {
+    #[tokio::test]
+    async fn test_recurring_event_parameters() {
         // The CalendarEvent struct doesn't currently have recurrence fields
         // but we can test the validation logic for recurring events
         
@@ -717,30 +500,25 @@ mod comprehensive_calendar_tests {
         let client = create_test_client();
         
         let event = CalendarEvent {
-            id: None,
             summary: "Recurring Test Event".to_string(),
             description: Some("This would be a recurring event".to_string()),
             location: Some("Test Location".to_string()),
             start_time: Utc::now() + Duration::hours(1),
             end_time: Utc::now() + Duration::hours(2),
-            attendees: vec![],
-            html_link: None,
-            conference_data: None,
-            creator: None,
-            organizer: None,
+            ..Default::default()
         };
         
-        let result = client.create_event("primary", event);
+        let result = client.create_event("primary", event).await;
         assert!(result.is_ok());
     }
 
-    #[test]
-    fn test_conference_data_handling() {
+    #[tokio::test]
+    async fn test_conference_data_handling() {
         // Test creation and retrieval of events with conference data
         let client = create_test_client();
         
         // Get event with conference data
-        let result = client.get_event("primary", "event1");
+        let result = client.get_event("primary", "event1").await;
         assert!(result.is_ok());
         
         let event = result.unwrap();
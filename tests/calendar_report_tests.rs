@@ -0,0 +1,285 @@
+/// Calendar Report Tests Module
+///
+/// This module contains tests for reporting where calendar time went, and for
+/// the calendar_report tool against the mock server.
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use mcp_gmailcal::calendar_api::{Attendee, CalendarEvent};
+use mcp_gmailcal::calendar_report::{parse_report_range, working_minutes, CalendarReport};
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn event(
+    summary: &str,
+    start: DateTime<Utc>,
+    minutes: i64,
+    attendees: &[(&str, &str)],
+) -> CalendarEvent {
+    CalendarEvent {
+        id: Some(summary.to_lowercase()),
+        summary: summary.to_string(),
+        start_time: start,
+        end_time: start + Duration::minutes(minutes),
+        attendees: attendees
+            .iter()
+            .map(|(email, status)| Attendee {
+                email: email.to_string(),
+                display_name: None,
+                response_status: Some(status.to_string()),
+                optional: None,
+                time_zone: None,
+                self_: None,
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+fn at(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 10, day, hour, minute, 0)
+        .unwrap()
+}
+
+#[test]
+fn test_parse_report_range() {
+    assert_eq!(parse_report_range("7d").unwrap(), Duration::days(7));
+    assert_eq!(parse_report_range(" 2W ").unwrap(), Duration::days(14));
+    assert_eq!(parse_report_range("3 months").unwrap(), Duration::days(90));
+    assert_eq!(parse_report_range("1y").unwrap(), Duration::days(365));
+    assert!(parse_report_range("0d").is_err());
+    assert!(parse_report_range("week").is_err());
+    assert!(parse_report_range("5x").is_err());
+    assert!(parse_report_range("2y").unwrap_err().contains("too long"));
+    assert!(parse_report_range("99999999999999999y")
+        .unwrap_err()
+        .contains("too long"));
+}
+
+#[test]
+fn test_working_minutes() {
+    // Monday to Monday: ten weekdays of eight hours
+    assert_eq!(
+        working_minutes(at(5, 0, 0), at(19, 0, 0), chrono_tz::UTC),
+        4800
+    );
+    // 10:00 to 19:00 in New York on a Monday, of which 10:00 to 17:00 is working time
+    assert_eq!(
+        working_minutes(at(5, 14, 0), at(5, 23, 0), chrono_tz::America::New_York),
+        420
+    );
+    // A weekend has none
+    assert_eq!(
+        working_minutes(at(10, 0, 0), at(12, 0, 0), chrono_tz::UTC),
+        0
+    );
+}
+
+#[test]
+fn test_calendar_report() {
+    let user = "demo@example.com";
+    let room = "room-1@resource.calendar.google.com";
+    let mut alice_meeting = event(
+        "Planning",
+        at(5, 10, 0),
+        60,
+        &[
+            ("demo@example.com", "accepted"),
+            ("alice@example.com", "accepted"),
+            (room, "accepted"),
+        ],
+    );
+    alice_meeting.attendees[1].display_name = Some("Alice".to_string());
+    let mut focus = event("Focus", at(7, 9, 0), 120, &[]);
+    focus.event_type = Some("focusTime".to_string());
+    let mut transparent = event("Reminder", at(8, 9, 0), 30, &[("alice@example.com", "")]);
+    transparent.transparency = Some("transparent".to_string());
+
+    let events = vec![
+        // Starts before the period, so only its last hour counts
+        event(
+            "Late call",
+            at(4, 23, 0),
+            120,
+            &[("alice@example.com", "accepted")],
+        ),
+        alice_meeting,
+        event(
+            "1:1",
+            at(5, 11, 0),
+            30,
+            &[(user, "accepted"), ("bob@example.com", "accepted")],
+        ),
+        event(
+            "Sync",
+            at(5, 11, 33),
+            27,
+            &[(user, "tentative"), ("alice@example.com", "accepted")],
+        ),
+        event(
+            "Declined",
+            at(6, 14, 0),
+            60,
+            &[(user, "declined"), ("carol@example.com", "accepted")],
+        ),
+        focus,
+        event("Solo", at(8, 14, 0), 60, &[]),
+        transparent,
+        event(
+            "Review",
+            at(13, 13, 0),
+            60,
+            &[
+                (user, "accepted"),
+                ("alice@example.com", "accepted"),
+                ("Bob@Example.com", "accepted"),
+            ],
+        ),
+    ];
+
+    let report = CalendarReport::new((at(5, 0, 0), at(19, 0, 0)), &events, user, chrono_tz::UTC);
+    assert_eq!(report.time_zone, "UTC");
+    assert_eq!(report.meetings, 5);
+    assert_eq!(report.meeting_minutes, 237);
+    assert_eq!(report.meeting_hours, 4.0);
+    assert_eq!(report.average_meeting_minutes, 47);
+    assert_eq!(report.back_to_back, 2);
+    assert_eq!(report.focus_minutes, 120);
+    assert_eq!(report.working_minutes, 4800);
+    assert_eq!(report.meeting_share, 4.9);
+    assert_eq!(report.focus_share, 2.5);
+
+    let days: Vec<(NaiveDate, usize, i64)> = report
+        .by_day
+        .iter()
+        .map(|day| (day.date, day.meetings, day.meeting_minutes))
+        .collect();
+    assert_eq!(
+        days,
+        vec![
+            (NaiveDate::from_ymd_opt(2026, 10, 5).unwrap(), 4, 177),
+            (NaiveDate::from_ymd_opt(2026, 10, 13).unwrap(), 1, 60),
+        ]
+    );
+    // The period ends at midnight, so the week starting on its last day isn't listed
+    let weeks: Vec<(NaiveDate, usize, f64)> = report
+        .by_week
+        .iter()
+        .map(|week| (week.week_start, week.meetings, week.meeting_hours))
+        .collect();
+    assert_eq!(
+        weeks,
+        vec![
+            (NaiveDate::from_ymd_opt(2026, 10, 5).unwrap(), 4, 3.0),
+            (NaiveDate::from_ymd_opt(2026, 10, 12).unwrap(), 1, 1.0),
+        ]
+    );
+
+    let co_attendees: Vec<(&str, usize, i64)> = report
+        .top_co_attendees
+        .iter()
+        .map(|person| (person.email.as_str(), person.meetings, person.minutes))
+        .collect();
+    assert_eq!(
+        co_attendees,
+        vec![("alice@example.com", 4, 207), ("bob@example.com", 2, 90)]
+    );
+    assert_eq!(report.top_co_attendees[0].name.as_deref(), Some("Alice"));
+}
+
+#[test]
+fn test_calendar_report_in_time_zone() {
+    // 23:00 UTC on Monday is Tuesday in Tokyo
+    let events = vec![event(
+        "Call",
+        at(5, 23, 0),
+        30,
+        &[("alice@example.com", "accepted")],
+    )];
+    let report = CalendarReport::new(
+        (at(5, 0, 0), at(7, 0, 0)),
+        &events,
+        "demo@example.com",
+        chrono_tz::Asia::Tokyo,
+    );
+    assert_eq!(report.time_zone, "Asia/Tokyo");
+    assert_eq!(
+        report.by_day[0].date,
+        NaiveDate::from_ymd_opt(2026, 10, 6).unwrap()
+    );
+    assert_eq!(report.back_to_back, 0);
+}
+
+#[tokio::test]
+async fn test_calendar_report_tool() {
+    let start = Utc::now() - Duration::days(2);
+    let dir = tempfile::tempdir().unwrap();
+    let events_dir = dir.path().join("calendar/v3/calendars/primary");
+    std::fs::create_dir_all(&events_dir).unwrap();
+    let events = json!({
+        "items": [
+            {
+                "id": "event-planning",
+                "summary": "Planning",
+                "start": { "dateTime": start.to_rfc3339() },
+                "end": { "dateTime": (start + Duration::minutes(30)).to_rfc3339() },
+                "attendees": [
+                    { "email": "demo@example.com", "responseStatus": "accepted" },
+                    { "email": "alice@example.com", "responseStatus": "accepted" }
+                ]
+            },
+            {
+                "id": "event-1on1",
+                "summary": "1:1 with Bob",
+                "start": { "dateTime": (start + Duration::minutes(30)).to_rfc3339() },
+                "end": { "dateTime": (start + Duration::minutes(60)).to_rfc3339() },
+                "attendees": [
+                    { "email": "demo@example.com", "responseStatus": "accepted" },
+                    { "email": "bob@example.com", "responseStatus": "accepted" }
+                ]
+            },
+            {
+                "id": "event-focus",
+                "summary": "Focus time",
+                "eventType": "focusTime",
+                "start": { "dateTime": (start + Duration::hours(2)).to_rfc3339() },
+                "end": { "dateTime": (start + Duration::hours(4)).to_rfc3339() }
+            }
+        ]
+    });
+    std::fs::write(events_dir.join("events.json"), events.to_string()).unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    let report = call_tool(&client, "calendar_report", json!({ "time_range": "7d" }))
+        .await
+        .unwrap();
+    assert_eq!(report["time_zone"], "America/Los_Angeles");
+    assert_eq!(report["meetings"], 2);
+    assert_eq!(report["meeting_minutes"], 60);
+    assert_eq!(report["back_to_back"], 1);
+    assert_eq!(report["focus_minutes"], 120);
+    assert!(!report["by_week"].as_array().unwrap().is_empty());
+    let co_attendees: Vec<&str> = report["top_co_attendees"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|person| person["email"].as_str().unwrap())
+        .collect();
+    assert_eq!(co_attendees, vec!["alice@example.com", "bob@example.com"]);
+
+    // The default period covers the same events
+    let report = call_tool(&client, "calendar_report", json!({}))
+        .await
+        .unwrap();
+    assert_eq!(report["meetings"], 2);
+
+    let error = call_tool(&client, "calendar_report", json!({ "time_range": "soon" }))
+        .await
+        .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("Invalid time range"));
+}
@@ -0,0 +1,115 @@
+/// Calendar Settings Tests Module
+///
+/// This module contains tests for the defaults taken from the user's Calendar
+/// settings, and for the get_calendar_settings tool and event creation
+/// against the mock server.
+use chrono::Duration;
+use mcp_gmailcal::calendar_api::CalendarSettings;
+use mcp_gmailcal::calendar_settings::{
+    CalendarDefaults, DEFAULT_EVENT_LENGTH_MINUTES, DEFAULT_WEEK_START,
+};
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn settings() -> CalendarSettings {
+    CalendarSettings {
+        time_zone: Some("Europe/Berlin".to_string()),
+        week_start: Some("sunday".to_string()),
+        default_event_length: Some(45),
+        ..CalendarSettings::default()
+    }
+}
+
+#[test]
+fn test_defaults_from_settings() {
+    let defaults = CalendarDefaults::new(Some(&settings()), None);
+    assert_eq!(defaults.time_zone, chrono_tz::Europe::Berlin);
+    assert_eq!(defaults.time_zone_source, "calendar");
+    assert_eq!(defaults.week_start, "sunday");
+    assert_eq!(defaults.default_event_length(), Duration::minutes(45));
+
+    // USER_TIMEZONE takes precedence over the calendar's time zone
+    let defaults = CalendarDefaults::new(Some(&settings()), Some(chrono_tz::Asia::Tokyo));
+    assert_eq!(defaults.time_zone, chrono_tz::Asia::Tokyo);
+    assert_eq!(defaults.time_zone_source, "USER_TIMEZONE");
+}
+
+#[test]
+fn test_defaults_without_settings() {
+    let defaults = CalendarDefaults::new(None, None);
+    assert_eq!(defaults.time_zone, chrono_tz::UTC);
+    assert_eq!(defaults.time_zone_source, "default");
+    assert_eq!(defaults.week_start, DEFAULT_WEEK_START);
+    assert_eq!(
+        defaults.default_event_length_minutes,
+        DEFAULT_EVENT_LENGTH_MINUTES
+    );
+
+    // An unknown time zone is ignored
+    let settings = CalendarSettings {
+        time_zone: Some("Mars/Olympus_Mons".to_string()),
+        ..CalendarSettings::default()
+    };
+    let defaults = CalendarDefaults::new(Some(&settings), None);
+    assert_eq!(defaults.time_zone, chrono_tz::UTC);
+    assert_eq!(serde_json::to_value(&defaults).unwrap()["time_zone"], "UTC");
+}
+
+#[tokio::test]
+async fn test_calendar_settings_tools() {
+    std::env::remove_var("USER_TIMEZONE");
+    let (_server, client) = mock_client(None).await;
+
+    let result = call_tool(&client, "get_calendar_settings", json!({}))
+        .await
+        .unwrap();
+    let settings = &result["settings"];
+    assert_eq!(settings["time_zone"], "America/Los_Angeles");
+    assert_eq!(settings["week_start"], "sunday");
+    assert_eq!(settings["default_event_length"], 30);
+    assert_eq!(settings["format_24_hour_time"], false);
+    assert_eq!(settings["all"]["dateFieldOrder"], "MDY");
+    assert_eq!(
+        result["defaults"],
+        json!({
+            "time_zone": "America/Los_Angeles",
+            "time_zone_source": "calendar",
+            "week_start": "sunday",
+            "default_event_length_minutes": 30
+        })
+    );
+
+    // Events without an end last the default length, and dates in words are
+    // read in the calendar's time zone
+    let event = call_tool(
+        &client,
+        "create_event",
+        json!({ "summary": "Planning", "start_time": "2030-03-05 10:00" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(event["start_time"], "2030-03-05T18:00:00Z");
+    assert_eq!(event["end_time"], "2030-03-05T18:30:00Z");
+
+    std::env::set_var("USER_TIMEZONE", "UTC");
+    let result = call_tool(&client, "get_calendar_settings", json!({}))
+        .await
+        .unwrap();
+    assert_eq!(result["defaults"]["time_zone"], "UTC");
+    assert_eq!(result["defaults"]["time_zone_source"], "USER_TIMEZONE");
+    let event = call_tool(
+        &client,
+        "create_event",
+        json!({
+            "summary": "Planning",
+            "start_time": "2030-03-05 10:00",
+            "end_time": "2030-03-05 12:00"
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(event["start_time"], "2030-03-05T10:00:00Z");
+    assert_eq!(event["end_time"], "2030-03-05T12:00:00Z");
+}
@@ -144,16 +144,20 @@ mod calendar_validation_tests {
                     display_name: Some("Attendee 1".to_string()),
                     response_status: Some("accepted".to_string()),
                     optional: None,
+                    time_zone: None,
+                    self_: None,
                 },
                 Attendee {
                     email: "attendee2@example.com".to_string(),
                     display_name: Some("Attendee 2".to_string()),
                     response_status: Some("tentative".to_string()),
                     optional: None,
+                    time_zone: None,
+                    self_: None,
                 },
             ],
             html_link: Some("https://calendar.google.com/calendar/event?eid=test".to_string()),
-            conference_data: None,
+            ..Default::default()
         }
     }
     
@@ -209,6 +213,8 @@ mod calendar_validation_tests {
             display_name: Some("Invalid Email".to_string()),
             response_status: Some("accepted".to_string()),
             optional: None,
+            time_zone: None,
+            self_: None,
         });
 
         // Validate the event
@@ -565,6 +571,8 @@ mod calendar_validation_tests {
             display_name: Some("Optional Attendee".to_string()),
             response_status: Some("needsAction".to_string()),
             optional: Some(true), // This attendee is optional
+            time_zone: None,
+            self_: None,
         });
         
         // Validate the event
@@ -591,6 +599,8 @@ mod calendar_validation_tests {
             display_name: Some("Conference Room 123".to_string()),
             response_status: Some("accepted".to_string()),
             optional: None,
+            time_zone: None,
+            self_: None,
         });
         
         // Validate the event
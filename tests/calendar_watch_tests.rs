@@ -0,0 +1,95 @@
+/// Calendar Watch Tests Module
+///
+/// This module contains tests for checking webhook addresses and tokens, and
+/// for the watch_calendar and stop_channel tools against the mock server.
+use mcp_gmailcal::calendar_watch::{validate_channel_token, validate_webhook_url};
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+#[test]
+fn test_validate_webhook_url() {
+    assert!(validate_webhook_url("https://hooks.example.com/calendar").is_ok());
+    assert!(validate_webhook_url("http://hooks.example.com/calendar")
+        .unwrap_err()
+        .contains("HTTPS"));
+    assert!(validate_webhook_url("hooks.example.com/calendar")
+        .unwrap_err()
+        .contains("Invalid webhook_url"));
+}
+
+#[test]
+fn test_validate_channel_token() {
+    assert!(validate_channel_token("target=automation").is_ok());
+    assert!(validate_channel_token(&"t".repeat(256)).is_ok());
+    assert!(validate_channel_token(&"t".repeat(257)).is_err());
+}
+
+#[tokio::test]
+async fn test_calendar_watch_tools() {
+    let dir = tempfile::tempdir().unwrap();
+    let events = dir.path().join("calendar/v3/calendars/primary/events");
+    std::fs::create_dir_all(&events).unwrap();
+    let channel = json!({
+        "kind": "api#channel",
+        "id": "channel-001",
+        "resourceId": "resource-abc",
+        "resourceUri": "https://www.googleapis.com/calendar/v3/calendars/primary/events?alt=json",
+        "token": "target=automation",
+        "expiration": "1900000000000"
+    });
+    std::fs::write(events.join("watch.post.json"), channel.to_string()).unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    let result = call_tool(
+        &client,
+        "watch_calendar",
+        json!({
+            "webhook_url": "https://hooks.example.com/calendar",
+            "token": "target=automation",
+            "ttl_seconds": 86400
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["id"], "channel-001");
+    assert_eq!(result["resource_id"], "resource-abc");
+    assert_eq!(result["token"], "target=automation");
+    assert_eq!(result["expiration"], "2030-03-17T17:46:40Z");
+    assert_eq!(result["calendar_id"], "primary");
+    assert_eq!(result["webhook_url"], "https://hooks.example.com/calendar");
+
+    let error = call_tool(
+        &client,
+        "watch_calendar",
+        json!({ "webhook_url": "http://hooks.example.com/calendar" }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error.error_object().unwrap().message.contains("HTTPS"));
+
+    let error = call_tool(
+        &client,
+        "watch_calendar",
+        json!({ "webhook_url": "https://hooks.example.com/calendar", "ttl_seconds": 0 }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("ttl_seconds"));
+
+    let stopped = call_tool(
+        &client,
+        "stop_channel",
+        json!({ "channel_id": "channel-001", "resource_id": "resource-abc" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(stopped["channel_id"], "channel-001");
+    assert_eq!(stopped["stopped"], true);
+}
@@ -0,0 +1,106 @@
+/// CLI Command Tests Module
+///
+/// This module contains tests for the direct (non-MCP) CLI subcommands,
+/// focusing on argument parsing and defaults.
+use clap::Parser;
+use mcp_gmailcal::cli::{CalCommands, Cli, Commands, ContactsCommands, MailCommands};
+
+#[test]
+fn test_mail_list_defaults() {
+    let cli = Cli::try_parse_from(["gmail-mcp", "mail", "list"]).unwrap();
+    assert_eq!(
+        cli.command,
+        Some(Commands::Mail {
+            command: MailCommands::List {
+                max_results: 10,
+                query: None
+            }
+        })
+    );
+}
+
+#[test]
+fn test_mail_search_and_get() {
+    let cli = Cli::try_parse_from(["gmail-mcp", "mail", "search", "is:unread", "-n", "3"]).unwrap();
+    assert_eq!(
+        cli.command,
+        Some(Commands::Mail {
+            command: MailCommands::Search {
+                query: "is:unread".to_string(),
+                max_results: 3
+            }
+        })
+    );
+
+    let cli = Cli::try_parse_from(["gmail-mcp", "mail", "get", "abc123"]).unwrap();
+    assert_eq!(
+        cli.command,
+        Some(Commands::Mail {
+            command: MailCommands::Get {
+                message_id: "abc123".to_string()
+            }
+        })
+    );
+}
+
+#[test]
+fn test_mail_send_requires_fields() {
+    // Missing --subject and --body
+    assert!(Cli::try_parse_from(["gmail-mcp", "mail", "send", "--to", "a@example.com"]).is_err());
+
+    let cli = Cli::try_parse_from([
+        "gmail-mcp",
+        "mail",
+        "send",
+        "--to",
+        "a@example.com",
+        "--subject",
+        "Hi",
+        "--body",
+        "Hello there",
+        "--cc",
+        "b@example.com",
+    ])
+    .unwrap();
+    match cli.command {
+        Some(Commands::Mail {
+            command: MailCommands::Send { to, cc, bcc, .. },
+        }) => {
+            assert_eq!(to, "a@example.com");
+            assert_eq!(cc.as_deref(), Some("b@example.com"));
+            assert!(bcc.is_none());
+        }
+        other => panic!("Unexpected command: {:?}", other),
+    }
+}
+
+#[test]
+fn test_cal_agenda_and_contacts_search() {
+    let cli = Cli::try_parse_from(["gmail-mcp", "cal", "agenda", "--days", "7"]).unwrap();
+    assert_eq!(
+        cli.command,
+        Some(Commands::Cal {
+            command: CalCommands::Agenda {
+                calendar_id: "primary".to_string(),
+                days: 7,
+                max_results: 25
+            }
+        })
+    );
+
+    // Windows that would run past the latest supported date are refused
+    assert!(Cli::try_parse_from(["gmail-mcp", "cal", "agenda", "--days", "4294967295"]).is_err());
+    assert!(Cli::try_parse_from(["gmail-mcp", "cal", "export", "--days", "3661"]).is_err());
+    assert!(Cli::try_parse_from(["gmail-mcp", "cal", "export", "--days", "3660"]).is_ok());
+
+    let cli = Cli::try_parse_from(["gmail-mcp", "contacts", "search", "Smith"]).unwrap();
+    assert_eq!(
+        cli.command,
+        Some(Commands::Contacts {
+            command: ContactsCommands::Search {
+                query: "Smith".to_string(),
+                max_results: None
+            }
+        })
+    );
+}
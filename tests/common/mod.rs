@@ -0,0 +1,48 @@
+//! Common Test Utilities Module
+//!
+//! This module provides the setup shared by the tests run against the mock
+//! server: starting it, pointing the process at it, and calling the server's
+//! tools through an in-process MCP client.
+//!
+//! Mock mode configures the process environment, so each test file makes all
+//! its calls against the mock server in a single test. Tests running in
+//! parallel would otherwise point each other's clients at the wrong server.
+#![allow(dead_code)]
+
+use mcp_attr::client::McpClient;
+use mcp_attr::schema::CallToolRequestParams;
+use mcp_attr::SessionResult;
+use mcp_gmailcal::mock::MockServer;
+use mcp_gmailcal::GmailServer;
+use serde_json::Value;
+use std::path::PathBuf;
+
+/// Start the mock server on the given fixtures, or the built-in ones, and
+/// point the process at it. The server stops when the value is dropped.
+pub async fn mock_server(fixtures_dir: Option<PathBuf>) -> MockServer {
+    let server = MockServer::start(fixtures_dir).await.unwrap();
+    server.install();
+    server
+}
+
+/// Start the mock server and connect a client to a new MCP server using it
+pub async fn mock_client(fixtures_dir: Option<PathBuf>) -> (MockServer, McpClient) {
+    let server = mock_server(fixtures_dir).await;
+    let client = McpClient::with_server(GmailServer::new()).await.unwrap();
+    (server, client)
+}
+
+/// Call a tool and parse the JSON text it returns
+pub async fn call_tool(client: &McpClient, name: &str, arguments: Value) -> SessionResult<Value> {
+    let result = client
+        .tools_call(CallToolRequestParams {
+            name: name.to_string(),
+            arguments: arguments.as_object().cloned(),
+        })
+        .await?;
+    let text = serde_json::to_value(&result.content[0]).unwrap()["text"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    Ok(serde_json::from_str(&text).unwrap())
+}
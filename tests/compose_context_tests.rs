@@ -0,0 +1,201 @@
+/// Compose Context Tests Module
+///
+/// This module contains tests for gathering the mail, contact record and
+/// shared events for a recipient, and for the compose_context tool against the
+/// mock server.
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use mcp_gmailcal::calendar_api::{Attendee, CalendarEvent};
+use mcp_gmailcal::compose_context::{exchange_query, ComposeContext};
+use mcp_gmailcal::gmail_api::EmailMessage;
+use mcp_gmailcal::people_api::{Contact, EmailAddress};
+use mcp_gmailcal::test_util::message;
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn event(id: &str, start: DateTime<Utc>, attendees: &[&str]) -> CalendarEvent {
+    CalendarEvent {
+        id: Some(id.to_string()),
+        summary: id.to_string(),
+        start_time: start,
+        end_time: start + Duration::hours(1),
+        attendees: attendees
+            .iter()
+            .map(|email| Attendee {
+                email: email.to_string(),
+                display_name: None,
+                response_status: None,
+                optional: None,
+                time_zone: None,
+                self_: None,
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+fn contact(resource_name: &str, address: &str) -> Contact {
+    Contact {
+        resource_name: resource_name.to_string(),
+        name: None,
+        email_addresses: vec![EmailAddress {
+            value: address.to_string(),
+            type_: None,
+        }],
+        phone_numbers: Vec::new(),
+        organizations: Vec::new(),
+        photos: Vec::new(),
+    }
+}
+
+#[test]
+fn test_exchange_query() {
+    assert_eq!(
+        exchange_query("alice@example.com"),
+        "from:alice@example.com OR to:alice@example.com"
+    );
+}
+
+#[test]
+fn test_compose_context() {
+    let now = Utc.with_ymd_and_hms(2026, 10, 15, 12, 0, 0).unwrap();
+    let messages = vec![
+        EmailMessage {
+            to: Some("demo@example.com".to_string()),
+            date: Some("Mon, 05 Oct 2026 09:15:00 -0700".to_string()),
+            body_text: Some("Shall we meet?".to_string()),
+            ..message("older", "Alice <alice@example.com>", "Planning")
+        },
+        EmailMessage {
+            to: Some("demo@example.com".to_string()),
+            date: Some("Tue, 13 Oct 2026 09:15:00 -0700".to_string()),
+            body_text: Some("Unrelated".to_string()),
+            ..message("other", "Bob <bob@example.com>", "Planning")
+        },
+        EmailMessage {
+            to: Some("carol@example.org, Alice <ALICE@example.com>".to_string()),
+            date: Some("Wed, 07 Oct 2026 10:00:00 -0700".to_string()),
+            body_text: Some("Thursday works.\n\n> Shall we meet?".to_string()),
+            ..message("newer", "Demo <demo@example.com>", "Planning")
+        },
+    ];
+    let contacts = vec![
+        contact("people/c003", "alice@other.example"),
+        contact("people/c001", "Alice@Example.com"),
+    ];
+    let events = vec![
+        event("later", now + Duration::days(7), &["alice@example.com"]),
+        event("ended", now - Duration::days(1), &["alice@example.com"]),
+        event("soon", now + Duration::days(1), &["ALICE@example.com"]),
+        event("without", now + Duration::days(2), &["bob@example.com"]),
+    ];
+
+    let context = ComposeContext::new("alice@example.com", &messages, 5, &contacts, &events, now);
+    assert_eq!(context.to, "alice@example.com");
+    assert_eq!(context.contact.unwrap().resource_name, "people/c001");
+
+    let exchanges: Vec<(&str, &str, &str)> = context
+        .exchanges
+        .iter()
+        .map(|e| (e.id.as_str(), e.direction.as_str(), e.content.as_str()))
+        .collect();
+    assert_eq!(
+        exchanges,
+        vec![
+            ("newer", "sent", "Thursday works."),
+            ("older", "received", "Shall we meet?"),
+        ]
+    );
+
+    let events: Vec<&str> = context
+        .upcoming_events
+        .iter()
+        .map(|event| event.summary.as_str())
+        .collect();
+    assert_eq!(events, vec!["soon", "later"]);
+
+    // The newest exchanges are kept
+    let context = ComposeContext::new("alice@example.com", &messages, 1, &[], &[], now);
+    assert_eq!(context.exchanges.len(), 1);
+    assert_eq!(context.exchanges[0].id, "newer");
+    assert!(context.contact.is_none());
+}
+
+#[tokio::test]
+async fn test_compose_context_tool() {
+    let dir = tempfile::tempdir().unwrap();
+    let events_dir = dir.path().join("calendar/v3/calendars/primary");
+    std::fs::create_dir_all(&events_dir).unwrap();
+    // The mock ignores the time bounds, so events must be in the future
+    let start = Utc::now() + Duration::days(3);
+    let events = json!({
+        "items": [
+            {
+                "id": "event-review",
+                "summary": "Budget review",
+                "start": { "dateTime": start.to_rfc3339() },
+                "end": { "dateTime": (start + Duration::hours(1)).to_rfc3339() },
+                "attendees": [
+                    { "email": "alice@example.com", "responseStatus": "accepted" },
+                    { "email": "demo@example.com", "responseStatus": "accepted" }
+                ]
+            },
+            {
+                "id": "event-1on1",
+                "summary": "1:1 with Bob",
+                "start": { "dateTime": start.to_rfc3339() },
+                "end": { "dateTime": (start + Duration::hours(1)).to_rfc3339() },
+                "attendees": [{ "email": "bob@example.com" }]
+            }
+        ]
+    });
+    std::fs::write(events_dir.join("events.json"), events.to_string()).unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    let result = call_tool(
+        &client,
+        "compose_context",
+        json!({ "to": "Alice Example <alice@example.com>" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["to"], "alice@example.com");
+    assert_eq!(result["contact"]["resource_name"], "people/c001");
+
+    // Only the mock message from Alice involves her
+    let exchanges = result["exchanges"].as_array().unwrap();
+    assert_eq!(exchanges.len(), 1);
+    assert_eq!(exchanges[0]["id"], "mock-msg-001");
+    assert_eq!(exchanges[0]["direction"], "received");
+    assert_eq!(exchanges[0]["subject"], "Quarterly planning meeting");
+
+    let events = result["upcoming_events"].as_array().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0]["summary"], "Budget review");
+
+    let result = call_tool(
+        &client,
+        "compose_context",
+        json!({ "to": "nobody@example.net" }),
+    )
+    .await
+    .unwrap();
+    assert!(result["contact"].is_null());
+    assert_eq!(result["exchanges"], json!([]));
+    assert_eq!(result["upcoming_events"], json!([]));
+
+    let error = call_tool(
+        &client,
+        "compose_context",
+        json!({ "to": "alice@example.com, bob@example.com" }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("is not a single email address"));
+}
@@ -0,0 +1,110 @@
+/// Compression Tests Module
+///
+/// This module contains tests for compressed API responses, checking that the
+/// shared client configuration asks Google for gzip, that compressed
+/// responses are smaller, and that the API clients decode them transparently.
+use axum::http::HeaderMap;
+use axum::routing::get;
+use axum::{Json, Router};
+use mcp_gmailcal::mock::MockServer;
+use mcp_gmailcal::utils::{api_client_builder, USER_AGENT};
+use mcp_gmailcal::{CalendarClient, Config, GmailService, PeopleClient};
+use serde_json::{json, Value};
+
+mod common;
+use common::mock_server;
+
+// Start a server that echoes the request headers it receives
+async fn start_header_echo_server() -> String {
+    let app = Router::new().route(
+        "/",
+        get(|headers: HeaderMap| async move {
+            Json(json!({
+                "accept-encoding": headers.get("accept-encoding").and_then(|v| v.to_str().ok()),
+                "user-agent": headers.get("user-agent").and_then(|v| v.to_str().ok()),
+            }))
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}/", addr)
+}
+
+#[tokio::test]
+async fn test_api_clients_request_compression() {
+    let url = start_header_echo_server().await;
+    let client = api_client_builder().build().unwrap();
+
+    let headers: Value = client.get(&url).send().await.unwrap().json().await.unwrap();
+    let accept_encoding = headers["accept-encoding"].as_str().unwrap();
+    assert!(accept_encoding.contains("gzip"));
+    assert!(accept_encoding.contains("deflate"));
+
+    // Google only compresses for user agents that mention gzip
+    assert_eq!(headers["user-agent"], USER_AGENT);
+    assert!(USER_AGENT.contains("gzip"));
+}
+
+#[tokio::test]
+async fn test_compressed_responses_are_smaller() {
+    let server = MockServer::start(None).await.unwrap();
+    let url = format!("{}/gmail/v1/users/me/messages/mock-msg-001", server.url());
+
+    // Read the response as sent on the wire
+    let raw_client = reqwest::Client::builder().no_gzip().build().unwrap();
+    let compressed = raw_client
+        .get(&url)
+        .header("Accept-Encoding", "gzip")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(compressed.headers()["content-encoding"], "gzip");
+    let compressed_len = compressed.bytes().await.unwrap().len();
+
+    let uncompressed = raw_client.get(&url).send().await.unwrap();
+    assert!(uncompressed.headers().get("content-encoding").is_none());
+    let uncompressed_len = uncompressed.bytes().await.unwrap().len();
+
+    assert!(
+        compressed_len < uncompressed_len,
+        "gzip {} bytes vs plain {} bytes",
+        compressed_len,
+        uncompressed_len
+    );
+
+    // The shared client decodes transparently
+    let message: Value = api_client_builder()
+        .build()
+        .unwrap()
+        .get(&url)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(message["id"], "mock-msg-001");
+}
+
+#[tokio::test]
+async fn test_api_clients_decode_compressed_responses() {
+    let _server = mock_server(None).await;
+    let config = Config::from_env().unwrap();
+
+    let gmail = GmailService::new(&config).unwrap();
+    let message = gmail.get_message_details("mock-msg-001").await.unwrap();
+    assert_eq!(
+        message.subject.as_deref(),
+        Some("Quarterly planning meeting")
+    );
+
+    let calendars = CalendarClient::new(&config).list_calendars().await.unwrap();
+    assert_eq!(calendars.calendars.len(), 2);
+
+    let contacts = PeopleClient::new(&config)
+        .list_contacts(None)
+        .await
+        .unwrap();
+    assert!(!contacts.contacts.is_empty());
+}
@@ -0,0 +1,46 @@
+/// Concurrent Gmail Tests Module
+///
+/// This module contains tests for sharing one Gmail client between
+/// concurrent calls, checking that calls through `&self` and through clones
+/// run together against the mock server.
+use mcp_gmailcal::{Config, GmailService};
+use std::sync::Arc;
+
+mod common;
+use common::mock_server;
+
+#[tokio::test]
+async fn test_concurrent_calls_share_client() {
+    let _server = mock_server(None).await;
+    let config = Config::from_env().unwrap();
+    let gmail = GmailService::new(&config).unwrap();
+
+    // Calls borrow the service immutably, so they can run at the same time
+    let (messages, labels, profile) = tokio::join!(
+        gmail.list_messages(10, None),
+        gmail.list_labels(),
+        gmail.get_profile()
+    );
+    let messages = messages.unwrap();
+    assert!(!messages.is_empty());
+    assert!(labels.unwrap().contains("INBOX"));
+    assert_eq!(profile.unwrap().email_address, "demo@example.com");
+
+    // Shared between tasks, and cloned for another mailbox
+    let shared = Arc::new(gmail.clone());
+    let tasks: Vec<_> = messages
+        .iter()
+        .map(|message| {
+            let shared = shared.clone();
+            let id = message.id.clone();
+            tokio::spawn(async move { shared.get_message_details(&id).await })
+        })
+        .collect();
+    for (task, message) in tasks.into_iter().zip(&messages) {
+        assert_eq!(task.await.unwrap().unwrap().id, message.id);
+    }
+
+    let delegated = gmail.clone().with_mailbox("team@example.com");
+    assert_eq!(delegated.mailbox(), "team@example.com");
+    assert_eq!(gmail.mailbox(), "me");
+}
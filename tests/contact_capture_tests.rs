@@ -0,0 +1,275 @@
+/// Contact Capture Tests Module
+///
+/// This module contains tests for reading a sender's details from an email
+/// signature, proposing and merging contacts from them, and for the
+/// capture_contact_from_email tool against the mock server.
+use mcp_gmailcal::contact_capture::{
+    merge_into_person, new_person, parse_signature, propose_contact, SignatureDetails,
+};
+use mcp_gmailcal::utils::extract_signature;
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+const SIGNATURE_BODY: &str = "Hi Alice,\n\nThe contract is attached.\n\nBest regards,\n\nDana Smith\nHead of Sales, Initech\nM: +1 (555) 010-7788 | Office: +1 555 010 7700\ndana.smith@initech.example\nhttps://initech.example\n";
+
+fn details() -> SignatureDetails {
+    parse_signature(
+        &extract_signature(SIGNATURE_BODY).unwrap(),
+        Some("Dana Smith"),
+    )
+}
+
+#[test]
+fn test_extract_signature() {
+    assert_eq!(
+        extract_signature(SIGNATURE_BODY).unwrap(),
+        "Dana Smith\nHead of Sales, Initech\nM: +1 (555) 010-7788 | Office: +1 555 010 7700\ndana.smith@initech.example\nhttps://initech.example"
+    );
+
+    // A "-- " delimiter wins over a sign-off
+    let body = "Thanks!\n\nSee you then\n-- \nBob Example\nEngineer at Example Corp";
+    assert_eq!(
+        extract_signature(body).unwrap(),
+        "Bob Example\nEngineer at Example Corp"
+    );
+
+    assert_eq!(extract_signature("Lunch at noon?"), None);
+    assert_eq!(extract_signature("See you there.\n\nCheers,\n"), None);
+}
+
+#[test]
+fn test_parse_signature() {
+    let details = details();
+    assert_eq!(details.name.as_deref(), Some("Dana Smith"));
+    assert_eq!(details.title.as_deref(), Some("Head of Sales"));
+    assert_eq!(details.organization.as_deref(), Some("Initech"));
+    assert_eq!(details.email_addresses, vec!["dana.smith@initech.example"]);
+    let phones: Vec<(&str, Option<&str>)> = details
+        .phone_numbers
+        .iter()
+        .map(|phone| (phone.value.as_str(), phone.type_.as_deref()))
+        .collect();
+    assert_eq!(
+        phones,
+        vec![
+            ("+1 (555) 010-7788", Some("mobile")),
+            ("+1 555 010 7700", Some("work")),
+        ]
+    );
+
+    // Title and company on their own lines, and a street address left out
+    let details = parse_signature(
+        "Bob Example\nEngineer\nExample Corp\n1 Main Street, Springfield",
+        None,
+    );
+    assert_eq!(details.name.as_deref(), Some("Bob Example"));
+    assert_eq!(details.title.as_deref(), Some("Engineer"));
+    assert_eq!(details.organization.as_deref(), Some("Example Corp"));
+    assert!(details.phone_numbers.is_empty());
+}
+
+#[test]
+fn test_propose_contact() {
+    let contact = propose_contact("dana@initech.example", Some("Dana Smith"), &details());
+    assert!(contact.resource_name.is_empty());
+    let name = contact.name.as_ref().unwrap();
+    assert_eq!(name.given_name.as_deref(), Some("Dana"));
+    assert_eq!(name.family_name.as_deref(), Some("Smith"));
+    let emails: Vec<&str> = contact
+        .email_addresses
+        .iter()
+        .map(|email| email.value.as_str())
+        .collect();
+    assert_eq!(
+        emails,
+        vec!["dana@initech.example", "dana.smith@initech.example"]
+    );
+
+    assert_eq!(
+        new_person(&contact),
+        json!({
+            "names": [{ "givenName": "Dana", "familyName": "Smith" }],
+            "emailAddresses": [
+                { "value": "dana@initech.example" },
+                { "value": "dana.smith@initech.example" }
+            ],
+            "phoneNumbers": [
+                { "value": "+1 (555) 010-7788", "type": "mobile" },
+                { "value": "+1 555 010 7700", "type": "work" }
+            ],
+            "organizations": [{ "name": "Initech", "title": "Head of Sales" }]
+        })
+    );
+
+    // Without a signature the sender's name is all there is
+    let contact = propose_contact("dana@initech.example", None, &SignatureDetails::default());
+    assert!(contact.name.is_none());
+    assert!(contact.organizations.is_empty());
+}
+
+#[test]
+fn test_merge_into_person() {
+    let proposed = propose_contact("dana@initech.example", Some("Dana Smith"), &details());
+    let person = json!({
+        "resourceName": "people/c009",
+        "etag": "%EgUBAi43PRoEAQIFByIMbW9ja2V0YWcwMDk=",
+        "names": [{ "displayName": "Dana S." }],
+        "emailAddresses": [{ "value": "Dana@Initech.example" }],
+        "phoneNumbers": [{ "value": "+15550107788", "type": "mobile" }]
+    });
+    let merge = merge_into_person(&person, &proposed);
+
+    // The name is kept, and the same number written differently not added
+    assert_eq!(
+        merge.fields,
+        vec!["emailAddresses", "phoneNumbers", "organizations"]
+    );
+    assert_eq!(
+        merge.changes,
+        vec![
+            "add email dana.smith@initech.example",
+            "add phone +1 555 010 7700",
+            "set organization Head of Sales, Initech",
+        ]
+    );
+    assert_eq!(
+        merge.update["etag"],
+        "%EgUBAi43PRoEAQIFByIMbW9ja2V0YWcwMDk="
+    );
+    assert_eq!(merge.update["emailAddresses"].as_array().unwrap().len(), 2);
+    assert_eq!(
+        merge.update["phoneNumbers"][0],
+        json!({ "value": "+15550107788", "type": "mobile" })
+    );
+    assert!(merge.update.get("names").is_none());
+
+    // Nothing to add leaves the contact alone
+    let merge = merge_into_person(&new_person(&proposed), &proposed);
+    assert!(merge.fields.is_empty());
+    assert!(merge.changes.is_empty());
+}
+
+#[tokio::test]
+async fn test_capture_contact_from_email_tool() {
+    let dir = tempfile::tempdir().unwrap();
+    let messages = dir.path().join("gmail/v1/users/me/messages");
+    std::fs::create_dir_all(&messages).unwrap();
+    let encode = |text: &str| base64::encode_config(text, base64::URL_SAFE);
+    let message = |id: &str, from: &str, body: &str| {
+        json!({
+            "id": id,
+            "threadId": format!("thread-{}", id),
+            "snippet": "The contract is attached",
+            "payload": {
+                "mimeType": "text/plain",
+                "headers": [
+                    { "name": "From", "value": from },
+                    { "name": "Subject", "value": "Contract" }
+                ],
+                "body": { "data": encode(body) }
+            }
+        })
+    };
+    std::fs::write(
+        messages.join("msg-dana.json"),
+        message(
+            "msg-dana",
+            "Dana Smith <dana@initech.example>",
+            SIGNATURE_BODY,
+        )
+        .to_string(),
+    )
+    .unwrap();
+    std::fs::write(
+        messages.join("msg-alice.json"),
+        message(
+            "msg-alice",
+            "Alice Example <alice@example.com>",
+            "Sounds good.\n\nThanks,\nAlice Example\nProduct Manager, Example Corp\nDirect: +1 555 0199",
+        )
+        .to_string(),
+    )
+    .unwrap();
+
+    let people = dir.path().join("people/v1/people");
+    std::fs::create_dir_all(&people).unwrap();
+    std::fs::write(
+        dir.path().join("people/v1/people_createContact.post.json"),
+        json!({
+            "resourceName": "people/c003",
+            "names": [{ "displayName": "Dana Smith" }],
+            "emailAddresses": [{ "value": "dana@initech.example" }]
+        })
+        .to_string(),
+    )
+    .unwrap();
+    std::fs::write(
+        people.join("c001_updateContact.patch.json"),
+        json!({
+            "resourceName": "people/c001",
+            "names": [{ "displayName": "Alice Example" }],
+            "phoneNumbers": [{ "value": "+1 555-0100" }, { "value": "+1 555 0199" }]
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    // A new sender is proposed, and only created once confirmed
+    let result = call_tool(
+        &client,
+        "capture_contact_from_email",
+        json!({ "message_id": "msg-dana" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["action"], "create");
+    assert_eq!(result["saved"], false);
+    assert!(result["existing"].is_null());
+    assert_eq!(result["contact"]["organizations"][0]["name"], "Initech");
+    assert_eq!(
+        result["contact"]["phone_numbers"].as_array().unwrap().len(),
+        2
+    );
+
+    let result = call_tool(
+        &client,
+        "capture_contact_from_email",
+        json!({ "message_id": "msg-dana", "confirm": true }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["saved"], true);
+    assert_eq!(result["contact"]["resource_name"], "people/c003");
+
+    // A known sender only gets what their contact lacks
+    let result = call_tool(
+        &client,
+        "capture_contact_from_email",
+        json!({ "message_id": "msg-alice", "confirm": true }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["action"], "update");
+    assert_eq!(result["changes"], json!(["add phone +1 555 0199"]));
+    assert_eq!(result["existing"]["resource_name"], "people/c001");
+    assert_eq!(result["saved"], true);
+    assert_eq!(
+        result["contact"]["phone_numbers"].as_array().unwrap().len(),
+        2
+    );
+
+    // Mail without a signature tells nothing new about a known sender
+    let result = call_tool(
+        &client,
+        "capture_contact_from_email",
+        json!({ "message_id": "mock-msg-001" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["action"], "none");
+    assert_eq!(result["saved"], false);
+}
@@ -0,0 +1,132 @@
+/// Contact Groups Tests Module
+///
+/// This module contains tests for grouping contacts by organization or email
+/// domain, and for the list_contacts_by_company tool against the mock server.
+use mcp_gmailcal::contact_groups::{email_domain, is_personal_domain, ContactGroups, GroupKind};
+use mcp_gmailcal::people_api::{Contact, EmailAddress, Organization, PersonName};
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn contact(id: &str, emails: &[&str], organization: Option<&str>) -> Contact {
+    Contact {
+        resource_name: format!("people/{}", id),
+        name: Some(PersonName {
+            display_name: id.to_string(),
+            given_name: None,
+            family_name: None,
+        }),
+        email_addresses: emails
+            .iter()
+            .map(|email| EmailAddress {
+                value: email.to_string(),
+                type_: None,
+            })
+            .collect(),
+        phone_numbers: Vec::new(),
+        organizations: organization
+            .map(|name| Organization {
+                name: Some(name.to_string()),
+                title: Some("Engineer".to_string()),
+            })
+            .into_iter()
+            .collect(),
+        photos: Vec::new(),
+    }
+}
+
+fn contacts() -> Vec<Contact> {
+    vec![
+        contact("alice", &["alice@acme.com"], Some("Acme Corp")),
+        contact("bob", &["bob@gmail.com"], Some("ACME Corp")),
+        contact("carol", &["carol@eu.acme.com"], None),
+        contact("dave", &["dave@globex.com"], None),
+        contact("erin", &["erin@globex.com", "erin@yahoo.com"], None),
+        contact("frank", &["frank@hotmail.com"], None),
+        contact("grace", &[], None),
+    ]
+}
+
+#[test]
+fn test_email_domain() {
+    assert_eq!(
+        email_domain("Alice@Example.COM").as_deref(),
+        Some("example.com")
+    );
+    assert_eq!(email_domain("not an address"), None);
+    assert!(is_personal_domain("gmail.com"));
+    assert!(!is_personal_domain("example.com"));
+}
+
+#[test]
+fn test_group_contacts() {
+    let grouped = ContactGroups::new(&contacts(), None);
+    assert_eq!(grouped.contacts_scanned, 7);
+    assert_eq!(grouped.ungrouped, 2);
+
+    let groups: Vec<(&str, GroupKind, usize)> = grouped
+        .groups
+        .iter()
+        .map(|g| (g.name.as_str(), g.kind, g.count))
+        .collect();
+    assert_eq!(
+        groups,
+        vec![
+            ("Acme Corp", GroupKind::Organization, 2),
+            ("globex.com", GroupKind::Domain, 2),
+            ("eu.acme.com", GroupKind::Domain, 1),
+        ]
+    );
+
+    // Organization names are merged ignoring case, and personal domains left out
+    let acme = &grouped.groups[0];
+    assert_eq!(acme.domains, vec!["acme.com"]);
+    assert_eq!(acme.contacts[1].resource_name, "people/bob");
+    assert_eq!(acme.contacts[1].email.as_deref(), Some("bob@gmail.com"));
+    assert_eq!(acme.contacts[1].title.as_deref(), Some("Engineer"));
+}
+
+#[test]
+fn test_group_contacts_by_query() {
+    let names = |query: &str| -> Vec<String> {
+        ContactGroups::new(&contacts(), Some(query))
+            .groups
+            .into_iter()
+            .flat_map(|group| group.contacts)
+            .map(|member| member.name.unwrap())
+            .collect()
+    };
+    // A domain matches its subdomains; an organization matches by name
+    assert_eq!(names("acme.com"), vec!["alice", "carol"]);
+    assert_eq!(names("@ACME.com"), vec!["alice", "carol"]);
+    assert_eq!(names("acme"), vec!["alice", "bob"]);
+    assert_eq!(names("globex.com"), vec!["dave", "erin"]);
+    assert!(names("initech.com").is_empty());
+}
+
+#[tokio::test]
+async fn test_list_contacts_by_company_tool() {
+    let (_server, client) = mock_client(None).await;
+
+    let result = call_tool(&client, "list_contacts_by_company", json!({}))
+        .await
+        .unwrap();
+    assert_eq!(result["contacts_scanned"], 2);
+    let groups = result["groups"].as_array().unwrap();
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0]["name"], "Example Corp");
+    assert_eq!(groups[0]["kind"], "organization");
+    assert_eq!(groups[0]["count"], 2);
+    assert_eq!(groups[0]["domains"], json!(["example.com"]));
+
+    let result = call_tool(
+        &client,
+        "list_contacts_by_company",
+        json!({ "domain_or_org": "initech.com" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["query"], "initech.com");
+    assert_eq!(result["groups"], json!([]));
+}
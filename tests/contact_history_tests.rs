@@ -0,0 +1,263 @@
+/// Contact History Tests Module
+///
+/// This module contains tests for summarizing the mail, meetings and contact
+/// record shared with a person, and for the contact_history tool against the
+/// mock server.
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use mcp_gmailcal::calendar_api::{Attendee, CalendarEvent};
+use mcp_gmailcal::contact_history::{history_query, ContactHistory};
+use mcp_gmailcal::gmail_api::MessageMetadata;
+use mcp_gmailcal::people_api::{Contact, EmailAddress, PersonName};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn message(
+    id: &str,
+    from: &str,
+    to: &str,
+    labels: &[&str],
+    received_at: DateTime<Utc>,
+) -> MessageMetadata {
+    MessageMetadata {
+        id: id.to_string(),
+        thread_id: format!("thread-{}", id),
+        from: Some(from.to_string()),
+        subject: Some(format!("Subject {}", id)),
+        snippet: None,
+        received_at: Some(received_at),
+        label_ids: labels.iter().map(|label| label.to_string()).collect(),
+        headers: HashMap::from([("to".to_string(), to.to_string())]),
+    }
+}
+
+fn event(summary: &str, start: DateTime<Utc>, attendees: &[&str]) -> CalendarEvent {
+    CalendarEvent {
+        id: Some(summary.to_string()),
+        summary: summary.to_string(),
+        start_time: start,
+        end_time: start + Duration::hours(1),
+        attendees: attendees
+            .iter()
+            .map(|email| Attendee {
+                email: email.to_string(),
+                display_name: None,
+                response_status: None,
+                optional: None,
+                time_zone: None,
+                self_: None,
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+fn contact(address: &str, name: &str) -> Contact {
+    Contact {
+        resource_name: "people/c001".to_string(),
+        name: Some(PersonName {
+            display_name: name.to_string(),
+            given_name: None,
+            family_name: None,
+        }),
+        email_addresses: vec![EmailAddress {
+            value: address.to_string(),
+            type_: None,
+        }],
+        phone_numbers: Vec::new(),
+        organizations: Vec::new(),
+        photos: Vec::new(),
+    }
+}
+
+#[test]
+fn test_history_query() {
+    assert_eq!(
+        history_query("alice@example.com"),
+        "{from:alice@example.com to:alice@example.com cc:alice@example.com} newer_than:90d"
+    );
+}
+
+#[test]
+fn test_contact_history() {
+    let now = Utc.with_ymd_and_hms(2026, 10, 15, 12, 0, 0).unwrap();
+    let messages = vec![
+        message(
+            "reply",
+            "Demo <demo@example.com>",
+            "Alice <ALICE@example.com>",
+            &["SENT"],
+            now - Duration::days(1),
+        ),
+        message(
+            "question",
+            "Alice Example <alice@example.com>",
+            "demo@example.com",
+            &["INBOX"],
+            now - Duration::days(2),
+        ),
+        // Mail from someone else copying both is not an exchange
+        message(
+            "copied",
+            "Bob <bob@example.com>",
+            "demo@example.com, alice@example.com",
+            &["INBOX"],
+            now - Duration::days(3),
+        ),
+        message(
+            "older",
+            "alice@example.com",
+            "demo@example.com",
+            &["INBOX"],
+            now - Duration::days(30),
+        ),
+    ];
+    let events = vec![
+        event("Kickoff", now - Duration::days(60), &["alice@example.com"]),
+        event("Review", now - Duration::days(5), &["Alice@example.com"]),
+        event("Standup", now - Duration::days(4), &["bob@example.com"]),
+        event(
+            "Long ago",
+            now - Duration::days(200),
+            &["alice@example.com"],
+        ),
+        event("Retro", now + Duration::days(3), &["alice@example.com"]),
+    ];
+
+    let history = ContactHistory::new("alice@example.com", &messages, 500, 2, &[], &events, now);
+    assert_eq!(history.name.as_deref(), Some("Alice Example"));
+    assert_eq!(history.frequency.received, 2);
+    assert_eq!(history.frequency.sent, 1);
+    assert_eq!(history.frequency.per_week, 0.2);
+    assert!(history.frequency.complete);
+    assert_eq!(history.last_sent, Some(now - Duration::days(1)));
+    assert_eq!(history.last_received, Some(now - Duration::days(2)));
+
+    let recent: Vec<(&str, &str)> = history
+        .recent_messages
+        .iter()
+        .map(|m| (m.id.as_str(), m.direction.as_str()))
+        .collect();
+    assert_eq!(recent, vec![("reply", "sent"), ("question", "received")]);
+
+    assert_eq!(history.meetings, 2);
+    assert_eq!(history.last_meeting.as_ref().unwrap().summary, "Review");
+    assert_eq!(history.next_meeting.as_ref().unwrap().summary, "Retro");
+    assert_eq!(
+        history.summary,
+        "3 emails exchanged with Alice Example in the last 90 days (2 received, 1 sent), \
+         about 0.2 a week. Last met on 2026-10-10 (Review). Next meeting on 2026-10-18 (Retro)."
+    );
+
+    // The contact's name is preferred, and a scan that reached its limit may
+    // have missed older mail
+    let contacts = vec![contact("alice@example.com", "Alice Smith")];
+    let history = ContactHistory::new("alice@example.com", &messages, 4, 5, &contacts, &[], now);
+    assert_eq!(history.name.as_deref(), Some("Alice Smith"));
+    assert!(!history.frequency.complete);
+    assert!(history.summary.starts_with("At least 3 emails"));
+    assert!(history
+        .summary
+        .ends_with("No meetings in the last 90 days."));
+
+    let history = ContactHistory::new("nobody@example.net", &messages, 500, 5, &[], &[], now);
+    assert!(history.name.is_none());
+    assert!(history.recent_messages.is_empty());
+    assert!(history
+        .summary
+        .starts_with("No email exchanged with nobody@example.net"));
+}
+
+#[tokio::test]
+async fn test_contact_history_tool() {
+    let dir = tempfile::tempdir().unwrap();
+    let write = |path: &str, contents: Value| {
+        let path = dir.path().join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents.to_string()).unwrap();
+    };
+    write(
+        "gmail/v1/users/me/messages.json",
+        json!({ "messages": [
+            { "id": "sent-001", "threadId": "mock-thread-001" },
+            { "id": "mock-msg-001", "threadId": "mock-thread-001" },
+            { "id": "mock-msg-003", "threadId": "mock-thread-001" }
+        ] }),
+    );
+    write(
+        "gmail/v1/users/me/messages/sent-001.json",
+        json!({
+            "id": "sent-001",
+            "threadId": "mock-thread-001",
+            "labelIds": ["SENT"],
+            "snippet": "Thursday works",
+            "payload": { "headers": [
+                { "name": "From", "value": "Demo User <demo@example.com>" },
+                { "name": "To", "value": "Alice Example <alice@example.com>" },
+                { "name": "Subject", "value": "Re: Quarterly planning meeting" },
+                { "name": "Date", "value": "Tue, 06 Oct 2026 08:00:00 -0700" }
+            ] }
+        }),
+    );
+    // The mock ignores the time bounds, so events are placed around now
+    let now = Utc::now();
+    let at = |days: i64| (now + Duration::days(days)).to_rfc3339();
+    write(
+        "calendar/v3/calendars/primary/events.json",
+        json!({ "items": [
+            {
+                "id": "planning",
+                "summary": "Planning",
+                "start": { "dateTime": at(-7) },
+                "end": { "dateTime": at(-7) },
+                "attendees": [{ "email": "alice@example.com" }]
+            },
+            {
+                "id": "retro",
+                "summary": "Retro",
+                "start": { "dateTime": at(7) },
+                "end": { "dateTime": at(7) },
+                "attendees": [{ "email": "alice@example.com" }]
+            }
+        ] }),
+    );
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    let history = call_tool(
+        &client,
+        "contact_history",
+        json!({ "email": "Alice Example <alice@example.com>" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(history["email"], "alice@example.com");
+    assert_eq!(history["contact"]["resource_name"], "people/c001");
+    assert_eq!(history["frequency"]["received"], 1);
+    assert_eq!(history["frequency"]["sent"], 1);
+    let recent: Vec<&str> = history["recent_messages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(recent, vec!["sent-001", "mock-msg-001"]);
+    assert_eq!(history["meetings"], 1);
+    assert_eq!(history["last_meeting"]["summary"], "Planning");
+    assert_eq!(history["next_meeting"]["summary"], "Retro");
+
+    let error = call_tool(
+        &client,
+        "contact_history",
+        json!({ "email": "not an address" }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("'not an address' is not an email address"));
+}
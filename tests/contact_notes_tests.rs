@@ -0,0 +1,134 @@
+/// Contact Notes Tests Module
+///
+/// This module contains tests for appending dated notes to a contact's
+/// biography, and for the append_contact_note tool against the mock server.
+use chrono::NaiveDate;
+use mcp_gmailcal::contact_notes::{
+    append_note, current_notes, notes_update, parse_note, MAX_CONTACT_NOTE_CHARS,
+};
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn date() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2026, 10, 15).unwrap()
+}
+
+#[test]
+fn test_parse_note() {
+    assert_eq!(
+        parse_note("  Prefers calls\n after 3pm ").unwrap(),
+        "Prefers calls after 3pm"
+    );
+    assert!(parse_note(" \n ").is_err());
+    assert!(parse_note(&"x".repeat(MAX_CONTACT_NOTE_CHARS + 1)).is_err());
+}
+
+#[test]
+fn test_append_note() {
+    assert_eq!(
+        append_note(None, "Met at RustConf", date()).unwrap(),
+        "2026-10-15: Met at RustConf"
+    );
+    assert_eq!(
+        append_note(Some("Old friend\n"), "Met at RustConf", date()).unwrap(),
+        "Old friend\n2026-10-15: Met at RustConf"
+    );
+
+    // Notes already there, dated or not, are not added again
+    let notes = "Old friend\n2026-09-01: Met at RustConf";
+    assert_eq!(append_note(Some(notes), "Met at RustConf", date()), None);
+    assert_eq!(append_note(Some(notes), "Old friend", date()), None);
+}
+
+#[test]
+fn test_notes_update() {
+    let person = json!({
+        "resourceName": "people/c001",
+        "etag": "%EgUBAi43PRoEAQIFByIMbW9ja2V0YWcwMDE=",
+        "biographies": [
+            { "value": "Old friend", "contentType": "TEXT_PLAIN", "metadata": { "primary": true } }
+        ]
+    });
+    assert_eq!(current_notes(&person), Some("Old friend"));
+    assert_eq!(
+        notes_update(&person, "Old friend\n2026-10-15: Met at RustConf"),
+        json!({
+            "etag": "%EgUBAi43PRoEAQIFByIMbW9ja2V0YWcwMDE=",
+            "biographies": [{
+                "value": "Old friend\n2026-10-15: Met at RustConf",
+                "contentType": "TEXT_PLAIN",
+                "metadata": { "primary": true }
+            }]
+        })
+    );
+
+    let person = json!({ "resourceName": "people/c002" });
+    assert_eq!(current_notes(&person), None);
+    assert_eq!(
+        notes_update(&person, "2026-10-15: Met at RustConf"),
+        json!({
+            "biographies": [
+                { "value": "2026-10-15: Met at RustConf", "contentType": "TEXT_PLAIN" }
+            ]
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_append_contact_note_tool() {
+    let dir = tempfile::tempdir().unwrap();
+    let people = dir.path().join("people/v1/people");
+    std::fs::create_dir_all(&people).unwrap();
+    let person = json!({
+        "resourceName": "people/c001",
+        "etag": "%EgUBAi43PRoEAQIFByIMbW9ja2V0YWcwMDE=",
+        "names": [{ "displayName": "Alice Example" }],
+        "biographies": [{ "value": "2026-09-01: Met at RustConf", "contentType": "TEXT_PLAIN" }]
+    });
+    std::fs::write(people.join("c001.json"), person.to_string()).unwrap();
+    std::fs::write(
+        people.join("c001_updateContact.patch.json"),
+        person.to_string(),
+    )
+    .unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    let result = call_tool(
+        &client,
+        "append_contact_note",
+        json!({ "resource_name": "people/c001", "note": "Prefers calls after 3pm" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["name"], "Alice Example");
+    assert_eq!(result["appended"], true);
+    let notes = result["notes"].as_str().unwrap();
+    assert!(notes.starts_with("2026-09-01: Met at RustConf\n"));
+    assert!(notes.ends_with(": Prefers calls after 3pm"));
+
+    let result = call_tool(
+        &client,
+        "append_contact_note",
+        json!({ "resource_name": "people/c001", "note": "Met at RustConf" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["appended"], false);
+    assert_eq!(result["notes"], "2026-09-01: Met at RustConf");
+
+    let error = call_tool(
+        &client,
+        "append_contact_note",
+        json!({ "resource_name": "people/c001", "note": "  " }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("The note is empty"));
+}
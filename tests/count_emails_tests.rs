@@ -0,0 +1,70 @@
+/// Count Emails Tests Module
+///
+/// This module contains tests for counting search matches, checking exact and
+/// estimated counts through the Gmail API trait and the count_emails tool
+/// against the mock server.
+use mcp_gmailcal::api::GmailApi;
+use mcp_gmailcal::test_util::{message, MockGmailApi};
+use mcp_gmailcal::MessageCount;
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+#[tokio::test]
+async fn test_count_messages() {
+    let gmail = MockGmailApi::new().with_messages(vec![
+        message("msg-1", "news@example.com", "Weekly newsletter"),
+        message("msg-2", "news@example.com", "Invoice"),
+        message("msg-3", "news@example.com", "Monthly newsletter"),
+    ]);
+
+    assert_eq!(
+        gmail
+            .count_messages(Some("newsletter"), 1000)
+            .await
+            .unwrap(),
+        MessageCount {
+            count: 2,
+            exact: true
+        }
+    );
+    assert_eq!(
+        gmail.count_messages(None, 2).await.unwrap(),
+        MessageCount {
+            count: 3,
+            exact: false
+        }
+    );
+    assert_eq!(
+        gmail
+            .count_messages(Some("receipt"), 1000)
+            .await
+            .unwrap()
+            .count,
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_count_emails_tool() {
+    let (_server, client) = mock_client(None).await;
+
+    // The mock listing has three messages and no further pages
+    let result = call_tool(&client, "count_emails", json!({ "query": "is:unread" }))
+        .await
+        .unwrap();
+    assert_eq!(
+        result,
+        json!({ "query": "is:unread", "count": 3, "exact": true })
+    );
+
+    let result = call_tool(
+        &client,
+        "count_emails",
+        json!({ "query": "", "exact_limit": "10" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["count"], 3);
+}
@@ -0,0 +1,153 @@
+/// CSV Export Tests Module
+///
+/// This module contains tests for exporting search results as CSV, checking
+/// column names, quoting, label names and attachment flags, and the
+/// export_emails_csv tool against the mock server, inline and to a file.
+use chrono::{TimeZone, Utc};
+use mcp_gmailcal::csv_export::{csv_escape, parse_fields, to_csv, CsvField, DEFAULT_CSV_FIELDS};
+use mcp_gmailcal::gmail_api::MessageMetadata;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+
+mod common;
+use common::{call_tool, mock_client};
+
+#[test]
+fn test_parse_fields() {
+    assert_eq!(parse_fields(None).unwrap(), DEFAULT_CSV_FIELDS.to_vec());
+    assert_eq!(
+        parse_fields(Some(&[])).unwrap(),
+        DEFAULT_CSV_FIELDS.to_vec()
+    );
+    assert_eq!(
+        parse_fields(Some(&[
+            "Subject".to_string(),
+            "thread-id".to_string(),
+            "subject".to_string()
+        ]))
+        .unwrap(),
+        vec![CsvField::Subject, CsvField::ThreadId]
+    );
+    assert!(parse_fields(Some(&["body".to_string()]))
+        .unwrap_err()
+        .contains("Unknown field 'body'"));
+}
+
+#[test]
+fn test_csv_escape() {
+    assert_eq!(csv_escape("plain"), "plain");
+    assert_eq!(csv_escape("Smith, Ann"), "\"Smith, Ann\"");
+    assert_eq!(csv_escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+    assert_eq!(csv_escape("two\nlines"), "\"two\nlines\"");
+}
+
+#[test]
+fn test_csv_escape_formulas() {
+    assert_eq!(csv_escape("=1+1"), "\"'=1+1\"");
+    assert_eq!(
+        csv_escape("=HYPERLINK(\"http://evil.example\")"),
+        "\"'=HYPERLINK(\"\"http://evil.example\"\")\""
+    );
+    assert_eq!(csv_escape("+44 20 7946 0000"), "\"'+44 20 7946 0000\"");
+    assert_eq!(csv_escape("-2+3"), "\"'-2+3\"");
+    assert_eq!(csv_escape("@SUM(A1)"), "\"'@SUM(A1)\"");
+    assert_eq!(csv_escape("\t=1"), "\"'\t=1\"");
+    assert_eq!(csv_escape("\r=1"), "\"'\r=1\"");
+    // Only the first character counts
+    assert_eq!(csv_escape("a=b"), "a=b");
+}
+
+#[test]
+fn test_to_csv() {
+    let message = MessageMetadata {
+        id: "m1".to_string(),
+        thread_id: "t1".to_string(),
+        from: Some("\"Smith, Ann\" <ann@example.com>".to_string()),
+        subject: Some("Invoice".to_string()),
+        received_at: Some(Utc.with_ymd_and_hms(2024, 3, 1, 9, 30, 0).unwrap()),
+        label_ids: vec!["INBOX".to_string(), "Label_1".to_string()],
+        headers: HashMap::from([("to".to_string(), "me@example.com".to_string())]),
+        ..Default::default()
+    };
+    let other = MessageMetadata {
+        id: "m2".to_string(),
+        thread_id: "t2".to_string(),
+        ..Default::default()
+    };
+    let label_names = HashMap::from([("Label_1".to_string(), "Finance/Receipts".to_string())]);
+    let with_attachments = HashSet::from(["m1".to_string()]);
+
+    let csv = to_csv(
+        &DEFAULT_CSV_FIELDS,
+        &[message.clone(), other],
+        &label_names,
+        &with_attachments,
+    );
+    let lines: Vec<&str> = csv.split("\r\n").collect();
+    assert_eq!(lines[0], "date,from,subject,labels,has_attachment");
+    assert_eq!(
+        lines[1],
+        "2024-03-01T09:30:00+00:00,\"\"\"Smith, Ann\"\" <ann@example.com>\",Invoice,INBOX; Finance/Receipts,true"
+    );
+    assert_eq!(lines[2], ",,,,false");
+    assert_eq!(lines[3], "");
+
+    let csv = to_csv(
+        &[CsvField::Id, CsvField::To],
+        &[message],
+        &label_names,
+        &with_attachments,
+    );
+    assert_eq!(csv, "id,to\r\nm1,me@example.com\r\n");
+}
+
+#[tokio::test]
+async fn test_export_emails_csv_tool() {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("CSV_EXPORT_DIR", dir.path());
+    let (_server, client) = mock_client(None).await;
+
+    let result = call_tool(&client, "export_emails_csv", json!({ "query": "" }))
+        .await
+        .unwrap();
+    assert_eq!(result["count"], 3);
+    assert_eq!(
+        result["fields"],
+        json!(["date", "from", "subject", "labels", "has_attachment"])
+    );
+    let csv = result["csv"].as_str().unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[1].contains("Alice Example <alice@example.com>"));
+    assert!(lines[1].contains("Quarterly planning meeting"));
+    assert!(lines[1].contains("INBOX; UNREAD; IMPORTANT"));
+
+    let result = call_tool(
+        &client,
+        "export_emails_csv",
+        json!({
+            "query": "is:unread",
+            "fields": ["id", "subject"],
+            "filename": "unread",
+            "max_results": "2"
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["count"], 2);
+    assert!(result.get("csv").is_none());
+    let path = dir.path().join("unread.csv");
+    assert_eq!(result["path"], path.display().to_string());
+    let csv = std::fs::read_to_string(&path).unwrap();
+    assert!(csv.starts_with("id,subject\r\nmock-msg-001,Quarterly planning meeting\r\n"));
+
+    assert!(call_tool(
+        &client,
+        "export_emails_csv",
+        json!({ "query": "", "fields": ["body"] }),
+    )
+    .await
+    .is_err());
+
+    std::env::remove_var("CSV_EXPORT_DIR");
+}
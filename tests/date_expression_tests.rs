@@ -0,0 +1,154 @@
+/// Date Expression Tests Module
+///
+/// This module contains tests for reading dates given in words, such as
+/// "tomorrow 3pm", and for tool parameters that accept them.
+use chrono::{DateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use mcp_gmailcal::utils::parse_date_expression;
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+// Thursday 15 October 2026, 10:00 UTC
+fn now() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 10, 15, 10, 0, 0).unwrap()
+}
+
+fn parse(input: &str, time_zone: Tz) -> String {
+    parse_date_expression(input, now(), time_zone)
+        .unwrap()
+        .to_rfc3339()
+}
+
+#[test]
+fn test_parse_rfc3339_and_local_dates() {
+    let utc = chrono_tz::UTC;
+    assert_eq!(
+        parse("2026-10-20T09:00:00+02:00", utc),
+        "2026-10-20T07:00:00+00:00"
+    );
+    assert_eq!(
+        parse("2026-10-20 09:00", chrono_tz::Europe::Berlin),
+        "2026-10-20T07:00:00+00:00"
+    );
+    assert_eq!(parse("2026-10-20", utc), "2026-10-20T00:00:00+00:00");
+    assert_eq!(parse("2026-10-20 3pm", utc), "2026-10-20T15:00:00+00:00");
+}
+
+#[test]
+fn test_parse_days_and_times_in_words() {
+    let utc = chrono_tz::UTC;
+    assert_eq!(parse("now", utc), "2026-10-15T10:00:00+00:00");
+    assert_eq!(parse("today", utc), "2026-10-15T00:00:00+00:00");
+    assert_eq!(parse("Tomorrow 3pm", utc), "2026-10-16T15:00:00+00:00");
+    assert_eq!(
+        parse("tomorrow at 9:30 am", utc),
+        "2026-10-16T09:30:00+00:00"
+    );
+    assert_eq!(parse("3pm tomorrow", utc), "2026-10-16T15:00:00+00:00");
+    assert_eq!(parse("yesterday noon", utc), "2026-10-14T12:00:00+00:00");
+    assert_eq!(parse("16:45", utc), "2026-10-15T16:45:00+00:00");
+    assert_eq!(parse("12am", utc), "2026-10-15T00:00:00+00:00");
+    assert_eq!(parse("12pm", utc), "2026-10-15T12:00:00+00:00");
+}
+
+#[test]
+fn test_parse_weekdays() {
+    let utc = chrono_tz::UTC;
+    assert_eq!(parse("Monday", utc), "2026-10-19T00:00:00+00:00");
+    assert_eq!(parse("next Monday", utc), "2026-10-19T00:00:00+00:00");
+    // A plain weekday includes today, "next" skips it
+    assert_eq!(parse("thursday", utc), "2026-10-15T00:00:00+00:00");
+    assert_eq!(parse("next thursday", utc), "2026-10-22T00:00:00+00:00");
+    assert_eq!(parse("last Friday 5pm", utc), "2026-10-09T17:00:00+00:00");
+    assert_eq!(parse("friday, 2pm", utc), "2026-10-16T14:00:00+00:00");
+    assert_eq!(parse("next week", utc), "2026-10-19T00:00:00+00:00");
+    assert_eq!(parse("this week", utc), "2026-10-12T00:00:00+00:00");
+}
+
+#[test]
+fn test_parse_relative_offsets() {
+    let utc = chrono_tz::UTC;
+    assert_eq!(parse("in 2 hours", utc), "2026-10-15T12:00:00+00:00");
+    assert_eq!(parse("in 30 minutes", utc), "2026-10-15T10:30:00+00:00");
+    assert_eq!(parse("3 days ago", utc), "2026-10-12T10:00:00+00:00");
+    assert_eq!(parse("in 1 week", utc), "2026-10-22T10:00:00+00:00");
+}
+
+#[test]
+fn test_parse_in_user_time_zone() {
+    // Already Friday in Tokyo, so "tomorrow" is Saturday there
+    let tokyo = chrono_tz::Asia::Tokyo;
+    let now = Utc.with_ymd_and_hms(2026, 10, 15, 20, 0, 0).unwrap();
+    let time = parse_date_expression("tomorrow 9am", now, tokyo).unwrap();
+    assert_eq!(time.to_rfc3339(), "2026-10-17T00:00:00+00:00");
+
+    let new_york = chrono_tz::America::New_York;
+    assert_eq!(parse("tomorrow 3pm", new_york), "2026-10-16T19:00:00+00:00");
+
+    let berlin = chrono_tz::Europe::Berlin;
+    let error = parse_date_expression("2026-03-29 02:30", now, berlin).unwrap_err();
+    assert!(error.contains("does not exist in Europe/Berlin"));
+}
+
+#[test]
+fn test_parse_invalid_expressions() {
+    for input in [
+        "",
+        "someday",
+        "next",
+        "next fortnight",
+        "tomorrow today",
+        "3pm 4pm",
+        "13pm",
+        "25:00",
+        "in two hours",
+        "in 99999999999999 days",
+        "999999999999 weeks ago",
+        "in 9223372036854775807 minutes",
+        "in 99999999999 days",
+    ] {
+        let error = parse_date_expression(input, now(), chrono_tz::UTC).unwrap_err();
+        assert!(error.contains("Invalid date"), "{}: {}", input, error);
+    }
+}
+
+#[tokio::test]
+async fn test_tools_accept_dates_in_words() {
+    std::env::set_var("USER_TIMEZONE", "Europe/Berlin");
+    let (_server, client) = mock_client(None).await;
+
+    let events = call_tool(
+        &client,
+        "list_events",
+        json!({ "time_min": "today", "time_max": "next Monday 9am" }),
+    )
+    .await
+    .unwrap();
+    assert!(events.as_array().is_some());
+
+    let error = call_tool(&client, "list_events", json!({ "time_min": "someday" }))
+        .await
+        .unwrap_err();
+    let message = error.error_object().unwrap().message.clone();
+    assert!(message.contains("Invalid time_min"), "{}", message);
+    assert!(message.contains("Invalid date 'someday'"), "{}", message);
+
+    let error = call_tool(
+        &client,
+        "create_event",
+        json!({
+            "summary": "Planning",
+            "start_time": "tomorrow 3pm",
+            "end_time": "whenever"
+        }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("Invalid end_time"));
+}
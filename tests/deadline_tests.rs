@@ -0,0 +1,106 @@
+/// Deadline Tests Module
+///
+/// This module contains tests for tool deadlines, checking that a passed
+/// deadline is remembered, that batch reports mark items left out as skipped,
+/// and that the Gmail client stops issuing requests once its deadline passes.
+use mcp_gmailcal::batch::{BatchReport, BatchStatus, ItemStatus};
+use mcp_gmailcal::deadline::Deadline;
+use mcp_gmailcal::gmail_api::DraftEmail;
+use mcp_gmailcal::{Config, GmailApiError, GmailService};
+use serde_json::json;
+use std::time::Duration;
+
+mod common;
+use common::mock_server;
+
+#[test]
+fn test_deadline() {
+    let deadline = Deadline::never();
+    assert!(!deadline.expired());
+    assert_eq!(deadline.remaining(), None);
+
+    let deadline = Deadline::after(Duration::from_secs(60));
+    assert!(!deadline.expired());
+    assert!(deadline.remaining().unwrap() > Duration::from_secs(50));
+    assert!(!deadline.was_reached());
+
+    // Clones share whether the deadline was reached
+    let deadline = Deadline::after(Duration::ZERO);
+    let clone = deadline.clone();
+    assert!(!deadline.was_reached());
+    assert!(clone.expired());
+    assert_eq!(clone.remaining(), Some(Duration::ZERO));
+    assert!(deadline.was_reached());
+}
+
+#[test]
+fn test_batch_report_skipped() {
+    let mut report = BatchReport::new();
+    report.push_success("a", json!({ "ok": true }));
+    report.push_failure("b", GmailApiError::NetworkError("reset".into()));
+    assert!(!report.timed_out);
+    let value = report.to_json(serde_json::Map::new());
+    assert!(value.get("skipped").is_none());
+    assert!(value.get("timed_out").is_none());
+
+    report.push_skipped("c");
+    assert_eq!(report.status, BatchStatus::PartialSuccess);
+    assert_eq!(
+        (
+            report.total,
+            report.succeeded,
+            report.failed,
+            report.skipped
+        ),
+        (3, 1, 1, 1)
+    );
+    assert_eq!(report.items[2].status, ItemStatus::Skipped);
+    let value = report.to_json(serde_json::Map::new());
+    assert_eq!(value["timed_out"], true);
+    assert_eq!(value["skipped"], 1);
+    assert_eq!(value["items"][2], json!({ "id": "c", "status": "skipped" }));
+
+    let mut report = BatchReport::new();
+    report.push_skipped("a");
+    assert_eq!(report.status, BatchStatus::Failure);
+}
+
+fn draft(to: &str) -> DraftEmail {
+    DraftEmail {
+        to: to.to_string(),
+        subject: "Hello".to_string(),
+        body: "Hi".to_string(),
+        cc: None,
+        bcc: None,
+        thread_id: None,
+        in_reply_to: None,
+        references: None,
+        priority: None,
+        read_receipt_to: None,
+    }
+}
+
+#[tokio::test]
+async fn test_gmail_requests_stop_at_deadline() {
+    let _server = mock_server(None).await;
+    let config = Config::from_env().unwrap();
+    let drafts = vec![draft("alice@example.com"), draft("bob@example.com")];
+
+    let gmail = GmailService::new(&config).unwrap();
+    assert!(!gmail.list_messages(10, None).await.unwrap().is_empty());
+    assert_eq!(gmail.create_drafts(&drafts).await.unwrap().len(), 2);
+    assert!(!gmail.deadline().was_reached());
+
+    // Nothing further is issued once the deadline has passed
+    let gmail = GmailService::new(&config)
+        .unwrap()
+        .with_deadline(Deadline::after(Duration::ZERO));
+    assert!(gmail.list_messages(10, None).await.unwrap().is_empty());
+    assert!(gmail.list_message_ids(10, None).await.unwrap().is_empty());
+    assert!(gmail.create_drafts(&drafts).await.unwrap().is_empty());
+    assert!(gmail
+        .send_messages(&drafts, Duration::ZERO)
+        .await
+        .is_empty());
+    assert!(gmail.deadline().was_reached());
+}
@@ -0,0 +1,193 @@
+/// Declined Events Tests Module
+///
+/// This module contains tests for recognising events the user has declined,
+/// and for list_events and daily_agenda leaving them out or flagging them
+/// against the mock server.
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use mcp_gmailcal::calendar_api::{Attendee, CalendarEvent};
+use mcp_gmailcal::declined::{flag_declined, is_declined, retain_declined};
+use mcp_gmailcal::working_location::DailyAgenda;
+use serde_json::{json, Value};
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn attendee(email: &str, status: &str, self_: Option<bool>) -> Attendee {
+    Attendee {
+        email: email.to_string(),
+        display_name: None,
+        response_status: Some(status.to_string()),
+        optional: None,
+        time_zone: None,
+        self_,
+    }
+}
+
+fn event(summary: &str, start: DateTime<Utc>, attendees: Vec<Attendee>) -> CalendarEvent {
+    CalendarEvent {
+        id: Some(summary.to_lowercase()),
+        summary: summary.to_string(),
+        start_time: start,
+        end_time: start + Duration::minutes(30),
+        attendees,
+        ..Default::default()
+    }
+}
+
+fn at(hour: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 10, 20, hour, 0, 0).unwrap()
+}
+
+fn events() -> Vec<CalendarEvent> {
+    vec![
+        event(
+            "Standup",
+            at(9),
+            vec![
+                attendee("demo@example.com", "accepted", Some(true)),
+                attendee("alice@example.com", "declined", None),
+            ],
+        ),
+        event(
+            "Vendor pitch",
+            at(11),
+            vec![
+                attendee("demo@example.com", "declined", Some(true)),
+                attendee("bob@example.com", "accepted", None),
+            ],
+        ),
+        event("Lunch", at(12), Vec::new()),
+    ]
+}
+
+#[test]
+fn test_is_declined() {
+    let events = events();
+    // Another attendee declining doesn't count
+    assert!(!is_declined(&events[0]));
+    assert!(is_declined(&events[1]));
+    assert!(!is_declined(&events[2]));
+
+    // Only the user's own entry counts
+    let not_marked = event(
+        "Review",
+        at(14),
+        vec![attendee("demo@example.com", "declined", None)],
+    );
+    assert!(!is_declined(&not_marked));
+}
+
+#[test]
+fn test_retain_and_flag_declined() {
+    let mut kept = events();
+    retain_declined(&mut kept, false);
+    let summaries: Vec<&str> = kept.iter().map(|e| e.summary.as_str()).collect();
+    assert_eq!(summaries, vec!["Standup", "Lunch"]);
+
+    let mut kept = events();
+    retain_declined(&mut kept, true);
+    assert_eq!(kept.len(), 3);
+
+    let listed = flag_declined(events(), true);
+    let flags: Vec<bool> = listed.iter().map(|e| e.declined).collect();
+    assert_eq!(flags, vec![false, true, false]);
+    let json = serde_json::to_value(&listed[1]).unwrap();
+    assert_eq!(json["summary"], "Vendor pitch");
+    assert_eq!(json["declined"], true);
+
+    assert_eq!(flag_declined(events(), false).len(), 2);
+}
+
+#[test]
+fn test_agenda_flags_declined() {
+    let agenda = DailyAgenda::new(
+        at(0).date_naive(),
+        chrono_tz::UTC,
+        vec![("primary".to_string(), events())],
+    )
+    .unwrap();
+    let flags: Vec<(&str, bool)> = agenda
+        .events
+        .iter()
+        .map(|item| (item.event.summary.as_str(), item.declined))
+        .collect();
+    assert_eq!(
+        flags,
+        vec![("Standup", false), ("Vendor pitch", true), ("Lunch", false)]
+    );
+}
+
+fn summaries(events: &Value) -> Vec<&str> {
+    events
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|event| event["summary"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_declined_events_tools() {
+    std::env::set_var("USER_TIMEZONE", "UTC");
+    let dir = tempfile::tempdir().unwrap();
+    let events_dir = dir.path().join("calendar/v3/calendars/primary");
+    std::fs::create_dir_all(&events_dir).unwrap();
+    let events = json!({
+        "items": [
+            {
+                "id": "event-standup",
+                "summary": "Standup",
+                "start": { "dateTime": "2030-03-05T09:00:00Z" },
+                "end": { "dateTime": "2030-03-05T09:15:00Z" },
+                "attendees": [
+                    { "email": "demo@example.com", "self": true, "responseStatus": "accepted" },
+                    { "email": "alice@example.com", "responseStatus": "declined" }
+                ]
+            },
+            {
+                "id": "event-pitch",
+                "summary": "Vendor pitch",
+                "start": { "dateTime": "2030-03-05T11:00:00Z" },
+                "end": { "dateTime": "2030-03-05T12:00:00Z" },
+                "attendees": [
+                    { "email": "demo@example.com", "self": true, "responseStatus": "declined" },
+                    { "email": "sales@vendor.example", "responseStatus": "accepted" }
+                ]
+            }
+        ]
+    });
+    std::fs::write(events_dir.join("events.json"), events.to_string()).unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    let listed = call_tool(&client, "list_events", json!({})).await.unwrap();
+    assert_eq!(summaries(&listed), vec!["Standup"]);
+    assert_eq!(listed[0]["declined"], false);
+    assert_eq!(listed[0]["attendees"][0]["self_"], true);
+
+    let listed = call_tool(&client, "list_events", json!({ "include_declined": true }))
+        .await
+        .unwrap();
+    assert_eq!(summaries(&listed), vec!["Standup", "Vendor pitch"]);
+    assert_eq!(listed[1]["declined"], true);
+
+    let agenda = call_tool(&client, "daily_agenda", json!({ "date": "2030-03-05" }))
+        .await
+        .unwrap();
+    assert_eq!(summaries(&agenda["events"]), vec!["Standup"]);
+
+    let agenda = call_tool(
+        &client,
+        "daily_agenda",
+        json!({ "date": "2030-03-05", "include_declined": true }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        summaries(&agenda["events"]),
+        vec!["Standup", "Vendor pitch"]
+    );
+    assert_eq!(agenda["events"][1]["declined"], true);
+
+    std::env::remove_var("USER_TIMEZONE");
+}
@@ -0,0 +1,126 @@
+/// Deduplication Tests Module
+///
+/// This module contains tests for collapsing copies of the same message in
+/// list and search results and grouping them by thread, both directly and
+/// through the list_emails and search_emails tools against the mock server.
+use mcp_gmailcal::dedupe::DedupedMessages;
+use mcp_gmailcal::gmail_api::EmailMessage;
+use mcp_gmailcal::test_util::message;
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+#[test]
+fn test_collapse_duplicates() {
+    let messages = vec![
+        EmailMessage {
+            thread_id: "thread-a".to_string(),
+            rfc_message_id: Some("<one@example.com>".to_string()),
+            ..message("1", "alice@example.com", "Subject of thread-a")
+        },
+        EmailMessage {
+            thread_id: "thread-b".to_string(),
+            rfc_message_id: Some("<two@example.com>".to_string()),
+            ..message("2", "alice@example.com", "Subject of thread-b")
+        },
+        // The same message delivered to an alias, with surrounding whitespace
+        EmailMessage {
+            thread_id: "thread-c".to_string(),
+            rfc_message_id: Some(" <one@example.com> ".to_string()),
+            ..message("3", "alice@example.com", "Subject of thread-c")
+        },
+        EmailMessage {
+            thread_id: "thread-a".to_string(),
+            rfc_message_id: Some("<four@example.com>".to_string()),
+            ..message("4", "alice@example.com", "Subject of thread-a")
+        },
+        // Messages without a Message-ID are never collapsed
+        EmailMessage {
+            thread_id: "thread-b".to_string(),
+            ..message("5", "alice@example.com", "Subject of thread-b")
+        },
+        EmailMessage {
+            thread_id: "thread-b".to_string(),
+            ..message("6", "alice@example.com", "Subject of thread-b")
+        },
+    ];
+
+    let deduped = DedupedMessages::from_messages(messages);
+    assert_eq!(deduped.messages_listed, 6);
+    assert_eq!(deduped.duplicates_removed, 1);
+
+    // Threads keep the order their first message was listed in
+    let threads: Vec<(&str, Vec<&str>)> = deduped
+        .threads
+        .iter()
+        .map(|thread| {
+            (
+                thread.thread_id.as_str(),
+                thread.messages.iter().map(|m| m.id.as_str()).collect(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        threads,
+        vec![
+            ("thread-a", vec!["1", "4"]),
+            ("thread-b", vec!["2", "5", "6"]),
+        ]
+    );
+    assert_eq!(
+        deduped.threads[0].subject.as_deref(),
+        Some("Subject of thread-a")
+    );
+    assert_eq!(deduped.threads[1].message_count, 3);
+
+    let empty = DedupedMessages::from_messages(Vec::new());
+    assert_eq!(empty.messages_listed, 0);
+    assert_eq!(empty.duplicates_removed, 0);
+    assert!(empty.threads.is_empty());
+}
+
+#[tokio::test]
+async fn test_deduplicate_option() {
+    let (_server, client) = mock_client(None).await;
+
+    // Without the option results stay a flat list, now with the Message-ID
+    let result = call_tool(&client, "list_emails", json!({})).await.unwrap();
+    let messages = result.as_array().unwrap();
+    assert_eq!(messages.len(), 3);
+    assert_eq!(
+        messages[0]["rfc_message_id"],
+        "<CAF-q4-planning-001@mail.example.com>"
+    );
+
+    let result = call_tool(&client, "list_emails", json!({ "deduplicate": true }))
+        .await
+        .unwrap();
+    assert_eq!(result["messages_listed"], 3);
+    assert_eq!(result["duplicates_removed"], 0);
+    let threads = result["threads"].as_array().unwrap();
+    assert_eq!(threads.len(), 2);
+    assert_eq!(threads[0]["thread_id"], "mock-thread-001");
+    assert_eq!(threads[0]["subject"], "Quarterly planning meeting");
+    assert_eq!(threads[0]["message_count"], 2);
+    assert_eq!(threads[0]["messages"][1]["id"], "mock-msg-003");
+    assert_eq!(threads[1]["thread_id"], "mock-thread-002");
+
+    let result = call_tool(
+        &client,
+        "search_emails",
+        json!({ "query": "planning", "deduplicate": true }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["threads"].as_array().unwrap().len(), 2);
+
+    let result = call_tool(
+        &client,
+        "search_emails",
+        json!({ "query": "planning", "deduplicate": false }),
+    )
+    .await
+    .unwrap();
+    assert!(result.is_array());
+}
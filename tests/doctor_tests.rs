@@ -0,0 +1,71 @@
+/// Doctor Command Tests Module
+///
+/// This module contains tests for the `doctor` diagnostics, focusing on the
+/// pure evaluation helpers (scope coverage, clock skew, HTTP date parsing)
+/// and CLI wiring.
+use clap::Parser;
+use mcp_gmailcal::cli::{Cli, Commands};
+use mcp_gmailcal::doctor::{
+    check_clock_skew, missing_scopes, parse_http_date, CheckStatus, CLOCK_SKEW_FAIL_SECONDS,
+    CLOCK_SKEW_WARN_SECONDS,
+};
+use mcp_gmailcal::oauth::{
+    CALENDAR_WRITE_SCOPE, CONTACTS_WRITE_SCOPE, DRIVE_READ_SCOPE, GMAIL_SCOPE, TASKS_SCOPE,
+};
+
+#[test]
+fn test_doctor_command_parsing() {
+    let cli = Cli::try_parse_from(["gmail-mcp", "doctor"]).unwrap();
+    assert!(matches!(cli.command, Some(Commands::Doctor)));
+}
+
+#[test]
+fn test_missing_scopes_all_granted() {
+    let granted = format!(
+        "{} {} {} {} {} openid",
+        GMAIL_SCOPE, CALENDAR_WRITE_SCOPE, CONTACTS_WRITE_SCOPE, TASKS_SCOPE, DRIVE_READ_SCOPE
+    );
+    assert!(missing_scopes(&granted).is_empty());
+}
+
+#[test]
+fn test_missing_scopes_reports_each_tool_group() {
+    let missing = missing_scopes(GMAIL_SCOPE);
+    let groups: Vec<&str> = missing.iter().map(|(tools, _)| *tools).collect();
+    assert_eq!(
+        groups,
+        vec![
+            "Calendar tools",
+            "Contacts tools",
+            "Tasks tools",
+            "Drive attachments"
+        ]
+    );
+
+    // An empty scope string means nothing is covered
+    assert_eq!(missing_scopes("").len(), 5);
+}
+
+#[test]
+fn test_clock_skew_thresholds() {
+    assert_eq!(check_clock_skew(0).status, CheckStatus::Pass);
+    assert_eq!(
+        check_clock_skew(CLOCK_SKEW_WARN_SECONDS).status,
+        CheckStatus::Warn
+    );
+    assert_eq!(
+        check_clock_skew(-CLOCK_SKEW_FAIL_SECONDS).status,
+        CheckStatus::Fail
+    );
+
+    // Failures and warnings always carry a fix
+    assert!(check_clock_skew(CLOCK_SKEW_FAIL_SECONDS + 1).fix.is_some());
+    assert!(check_clock_skew(1).fix.is_none());
+}
+
+#[test]
+fn test_parse_http_date() {
+    let parsed = parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT").unwrap();
+    assert_eq!(parsed.to_rfc3339(), "1994-11-06T08:49:37+00:00");
+    assert!(parse_http_date("not a date").is_none());
+}
@@ -0,0 +1,129 @@
+/// Draft Batch Tests Module
+///
+/// This module contains tests for creating several drafts at once, checking
+/// how each draft is read and identified, and the create_drafts_batch tool
+/// against the mock server with drafts that are and aren't written correctly.
+use mcp_gmailcal::draft_batch::{draft_item_id, parse_draft, MAX_BATCH_DRAFTS};
+use mcp_gmailcal::priority::PriorityLevel;
+use serde_json::{json, Value};
+
+mod common;
+use common::{call_tool, mock_client};
+
+#[test]
+fn test_parse_draft() {
+    let draft = parse_draft(&json!({
+        "to": "Alice <alice@example.com>",
+        "subject": "Follow-up",
+        "body": "Hi Alice",
+        "cc": "bob@example.com",
+        "priority": "urgent"
+    }))
+    .unwrap();
+    assert_eq!(draft.to, "Alice <alice@example.com>");
+    assert_eq!(draft.cc.as_deref(), Some("bob@example.com"));
+    assert_eq!(draft.priority, Some(PriorityLevel::High));
+    assert_eq!(draft.read_receipt_to, None);
+
+    assert!(
+        parse_draft(&json!({ "to": "alice@example.com", "subject": "Hi" }))
+            .unwrap_err()
+            .contains("missing field `body`")
+    );
+    assert!(parse_draft(&json!({
+        "to": "alice@example.com",
+        "subject": "Hi",
+        "body": "Hi",
+        "attachments": []
+    }))
+    .unwrap_err()
+    .contains("unknown field `attachments`"));
+    assert!(
+        parse_draft(&json!({ "to": "alice", "subject": "Hi", "body": "Hi" }))
+            .unwrap_err()
+            .contains("No recipient address")
+    );
+    assert!(parse_draft(&json!({
+        "to": "alice@example.com",
+        "subject": "Hi",
+        "body": "Hi",
+        "priority": "whenever"
+    }))
+    .is_err());
+}
+
+#[test]
+fn test_draft_item_id() {
+    assert_eq!(
+        draft_item_id(0, &json!({ "to": " alice@example.com " })),
+        "alice@example.com"
+    );
+    assert_eq!(draft_item_id(2, &json!({ "subject": "Hi" })), "#3");
+    assert_eq!(draft_item_id(0, &json!("not a draft")), "#1");
+}
+
+#[tokio::test]
+async fn test_create_drafts_batch_tool() {
+    let (_server, client) = mock_client(None).await;
+
+    let result = call_tool(
+        &client,
+        "create_drafts_batch",
+        json!({
+            "drafts": [
+                { "to": "alice@example.com", "subject": "Thanks, Alice", "body": "Hi Alice" },
+                { "to": "bob@example.com", "subject": "Thanks, Bob" },
+                {
+                    "to": "carol@example.com",
+                    "subject": "Thanks, Carol",
+                    "body": "Hi Carol",
+                    "thread_id": "mock-thread-001"
+                }
+            ]
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["status"], "partial_success");
+    assert_eq!(result["total"], 3);
+    assert_eq!(result["succeeded"], 2);
+    assert_eq!(result["failed"], 1);
+
+    let items = result["items"].as_array().unwrap();
+    let ids: Vec<&str> = items
+        .iter()
+        .map(|item| item["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        ids,
+        vec!["alice@example.com", "bob@example.com", "carol@example.com"]
+    );
+    assert!(items[0]["result"]["draft_id"]
+        .as_str()
+        .unwrap()
+        .starts_with("mock-"));
+    assert_eq!(items[0]["result"]["subject"], "Thanks, Alice");
+    assert_eq!(items[1]["status"], "error");
+    assert!(items[1]["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("missing field `body`"));
+    assert_eq!(items[2]["status"], "ok");
+
+    let result = call_tool(&client, "create_drafts_batch", json!({ "drafts": [] }))
+        .await
+        .unwrap();
+    assert_eq!(result["status"], "success");
+    assert_eq!(result["total"], 0);
+
+    let too_many: Vec<Value> = (0..=MAX_BATCH_DRAFTS)
+        .map(|i| json!({ "to": format!("user{}@example.com", i), "subject": "Hi", "body": "Hi" }))
+        .collect();
+    assert!(call_tool(
+        &client,
+        "create_drafts_batch",
+        json!({ "drafts": too_many })
+    )
+    .await
+    .is_err());
+}
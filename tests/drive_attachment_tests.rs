@@ -0,0 +1,216 @@
+/// Drive Attachment Tests Module
+///
+/// This module contains tests for adding Google Drive files to outgoing
+/// emails, checking which files are attached or linked, the sharing check
+/// against recipients, the multipart message built for attachments, and the
+/// create_draft_email tool against the mock server.
+use mcp_gmailcal::attachments::OutgoingAttachment;
+use mcp_gmailcal::drive_api::{prepare_drive_attachments, recipients_without_access};
+use mcp_gmailcal::gmail_api::{encode_raw_message, encode_raw_message_with_attachments};
+use mcp_gmailcal::test_util::MockDriveApi;
+use mcp_gmailcal::utils::parse_email_addresses;
+use mcp_gmailcal::{DraftEmail, DriveApiError, DriveFile, DrivePermission};
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn file(id: &str, name: &str, mime_type: &str) -> DriveFile {
+    DriveFile {
+        id: id.to_string(),
+        name: name.to_string(),
+        mime_type: mime_type.to_string(),
+        size: None,
+        web_view_link: Some(format!("https://drive.google.com/file/d/{}/view", id)),
+        can_download: true,
+    }
+}
+
+fn permission(kind: &str, email_address: Option<&str>, domain: Option<&str>) -> DrivePermission {
+    DrivePermission {
+        kind: kind.to_string(),
+        role: "reader".to_string(),
+        email_address: email_address.map(str::to_string),
+        domain: domain.map(str::to_string),
+    }
+}
+
+fn draft() -> DraftEmail {
+    DraftEmail {
+        to: "Alice <alice@example.com>".to_string(),
+        subject: "Notes".to_string(),
+        body: "See attached.".to_string(),
+        cc: Some("bob@other.org".to_string()),
+        bcc: None,
+        thread_id: None,
+        in_reply_to: None,
+        references: None,
+        priority: None,
+        read_receipt_to: None,
+    }
+}
+
+fn decode(raw: &str) -> String {
+    String::from_utf8(base64::decode_config(raw, base64::URL_SAFE).unwrap()).unwrap()
+}
+
+#[test]
+fn test_recipients_without_access() {
+    assert_eq!(
+        parse_email_addresses("Alice <Alice@Example.com>, bob@other.org, not an address"),
+        vec!["alice@example.com", "bob@other.org"]
+    );
+    assert_eq!(
+        draft().recipient_addresses(),
+        vec!["alice@example.com", "bob@other.org"]
+    );
+
+    let recipients = draft().recipient_addresses();
+    let shared = [
+        permission("user", Some("ALICE@example.com"), None),
+        permission("domain", None, Some("partner.com")),
+    ];
+    assert_eq!(
+        recipients_without_access(&shared, &recipients),
+        vec!["bob@other.org"]
+    );
+
+    let shared = [permission("domain", None, Some("other.org"))];
+    assert_eq!(
+        recipients_without_access(&shared, &recipients),
+        vec!["alice@example.com"]
+    );
+
+    let shared = [permission("anyone", None, None)];
+    assert!(recipients_without_access(&shared, &recipients).is_empty());
+}
+
+#[tokio::test]
+async fn test_files_are_attached_until_the_limit_then_linked() {
+    let drive = MockDriveApi::new()
+        .with_file(
+            file("small", "notes.txt", "text/plain"),
+            vec![],
+            vec![b'a'; 60],
+        )
+        .with_file(
+            file("large", "slides.pdf", "application/pdf"),
+            vec![permission("user", Some("alice@example.com"), None)],
+            vec![b'b'; 60],
+        )
+        .with_file(
+            file("doc", "Plan", "application/vnd.google-apps.document"),
+            vec![permission("anyone", None, None)],
+            vec![],
+        );
+    let ids: Vec<String> = ["small", "large", "doc"].map(String::from).to_vec();
+
+    let prepared = prepare_drive_attachments(&drive, &ids, &draft().recipient_addresses(), 100)
+        .await
+        .unwrap();
+
+    assert_eq!(prepared.attached.len(), 1);
+    assert_eq!(prepared.files[0].filename, "notes.txt");
+    assert_eq!(prepared.files[0].data.len(), 60);
+
+    // The second file no longer fits, and Docs are always linked
+    let linked: Vec<&str> = prepared.linked.iter().map(|f| f.id.as_str()).collect();
+    assert_eq!(linked, vec!["large", "doc"]);
+    assert_eq!(prepared.warnings.len(), 1);
+    assert!(prepared.warnings[0].starts_with("bob@other.org cannot open 'slides.pdf'"));
+
+    let body = prepared.body_with_links("See attached.\n");
+    assert_eq!(
+        body,
+        "See attached.\n\n\
+         slides.pdf: https://drive.google.com/file/d/large/view\n\
+         Plan: https://drive.google.com/file/d/doc/view\n"
+    );
+
+    let missing = ["missing".to_string()];
+    assert!(matches!(
+        prepare_drive_attachments(&drive, &missing, &[], 100).await,
+        Err(DriveApiError::FileNotFound(_))
+    ));
+}
+
+#[test]
+fn test_raw_message_with_attachments() {
+    let draft = draft();
+    // Messages without attachments stay plain text
+    assert_eq!(
+        encode_raw_message_with_attachments(&draft, &[]),
+        encode_raw_message(&draft)
+    );
+    assert!(!decode(&encode_raw_message(&draft)).contains("multipart"));
+
+    let attachment = OutgoingAttachment {
+        filename: "re\"port.csv".to_string(),
+        mime_type: "text/csv".to_string(),
+        data: b"a,b\n1,2\n".to_vec(),
+    };
+    let message = decode(&encode_raw_message_with_attachments(&draft, &[attachment]));
+
+    let boundary = message
+        .split("boundary=\"")
+        .nth(1)
+        .and_then(|rest| rest.split('"').next())
+        .unwrap();
+    assert!(message.contains("MIME-Version: 1.0\r\n"));
+    assert!(message.contains(&format!(
+        "--{}\r\nContent-Type: text/plain; charset=\"UTF-8\"\r\n\r\nSee attached.\r\n",
+        boundary
+    )));
+    assert!(message.contains("Content-Disposition: attachment; filename=\"report.csv\"\r\n"));
+    assert!(message.contains(&format!("\r\n\r\n{}\r\n", base64::encode(b"a,b\n1,2\n"))));
+    assert!(message.ends_with(&format!("--{}--\r\n", boundary)));
+}
+
+#[tokio::test]
+async fn test_create_draft_with_drive_files() {
+    let (_server, client) = mock_client(None).await;
+
+    let result = call_tool(
+        &client,
+        "create_draft_email",
+        json!({
+            "to": "alice@example.com",
+            "cc": "bob@example.com",
+            "subject": "Planning",
+            "body": "Notes and recording below.",
+            "drive_file_ids": ["mock-file-001", "mock-file-002", "mock-file-003"]
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["status"], "success");
+
+    let drive_files = &result["drive_files"];
+    assert_eq!(drive_files["attached"][0]["name"], "planning-notes.txt");
+    assert_eq!(drive_files["attached"][0]["size"], 54);
+    assert_eq!(drive_files["linked"][0]["id"], "mock-file-002");
+    assert_eq!(drive_files["linked"][1]["id"], "mock-file-003");
+    // The recording is only shared with Alice; the sheet with the domain
+    assert_eq!(
+        drive_files["warnings"],
+        json!(["bob@example.com cannot open 'all-hands-recording.mp4'; share it with them before sending"])
+    );
+
+    let error = call_tool(
+        &client,
+        "create_draft_email",
+        json!({
+            "to": "alice@example.com",
+            "subject": "Planning",
+            "body": "Missing file",
+            "drive_file_ids": ["no-such-file"]
+        }),
+    )
+    .await
+    .unwrap_err();
+    let object = error.error_object().unwrap();
+    assert!(object
+        .message
+        .contains("Drive file not found: no-such-file"));
+    assert_eq!(object.data.as_ref().unwrap()["category"], "not_found");
+}
@@ -0,0 +1,266 @@
+/// ETag Cache Tests Module
+///
+/// This module contains tests for conditional requests, checking that cached
+/// responses are revalidated with `If-None-Match`, that 304 responses are
+/// answered from the cache, and that the API clients use the shared cache for
+/// contacts, calendar lists and labels.
+use axum::extract::State;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use mcp_gmailcal::etag_cache::{EtagCache, ETAG_CACHE_FILE, ETAG_CACHE_SAVE_DELAY};
+use mcp_gmailcal::{CalendarClient, Config, GmailService, PeopleClient};
+use std::sync::{Arc, Mutex};
+
+mod common;
+use common::mock_server;
+
+#[derive(Default)]
+struct Resource {
+    version: u32,
+    requests: Vec<Option<String>>,
+}
+
+type SharedResource = Arc<Mutex<Resource>>;
+
+// Serve a versioned resource, honouring If-None-Match on `/tagged`
+async fn start_resource_server() -> (String, SharedResource) {
+    let resource = SharedResource::default();
+    let app = Router::new()
+        .route("/tagged", get(tagged_handler))
+        .route("/untagged", get(untagged_handler))
+        .route("/body-tagged", get(body_tagged_handler))
+        .with_state(resource.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{}", addr), resource)
+}
+
+fn record_request(resource: &SharedResource, headers: &HeaderMap) -> u32 {
+    let mut resource = resource.lock().unwrap();
+    let if_none_match = headers
+        .get(header::IF_NONE_MATCH)
+        .map(|v| v.to_str().unwrap().to_string());
+    resource.requests.push(if_none_match);
+    resource.version
+}
+
+async fn tagged_handler(State(resource): State<SharedResource>, headers: HeaderMap) -> Response {
+    let version = record_request(&resource, &headers);
+    let etag = format!("\"v{}\"", version);
+    if headers.get(header::IF_NONE_MATCH).map(|v| v.as_bytes()) == Some(etag.as_bytes()) {
+        return StatusCode::NOT_MODIFIED.into_response();
+    }
+    (
+        [(header::ETAG, etag)],
+        format!(r#"{{"version": {}}}"#, version),
+    )
+        .into_response()
+}
+
+async fn untagged_handler(State(resource): State<SharedResource>, headers: HeaderMap) -> String {
+    let version = record_request(&resource, &headers);
+    format!(r#"{{"version": {}}}"#, version)
+}
+
+async fn body_tagged_handler(
+    State(resource): State<SharedResource>,
+    headers: HeaderMap,
+) -> Response {
+    let version = record_request(&resource, &headers);
+    let etag = format!("\"b{}\"", version);
+    if headers.get(header::IF_NONE_MATCH).map(|v| v.as_bytes()) == Some(etag.as_bytes()) {
+        return StatusCode::NOT_MODIFIED.into_response();
+    }
+    format!(r#"{{"etag": {:?}, "version": {}}}"#, etag, version).into_response()
+}
+
+fn sent_if_none_match(resource: &SharedResource) -> Vec<Option<String>> {
+    resource.lock().unwrap().requests.clone()
+}
+
+#[tokio::test]
+async fn test_not_modified_is_served_from_cache() {
+    let (url, resource) = start_resource_server().await;
+    let client = reqwest::Client::new();
+    let cache = EtagCache::new(10);
+    let url = format!("{}/tagged", url);
+
+    let first = cache.send(client.get(&url)).await.unwrap();
+    assert_eq!(first.status.as_u16(), 200);
+    assert!(!first.from_cache);
+
+    let second = cache.send(client.get(&url)).await.unwrap();
+    assert_eq!(second.status.as_u16(), 200);
+    assert!(second.from_cache);
+    assert_eq!(second.body, first.body);
+
+    // A changed resource replaces the cached copy
+    resource.lock().unwrap().version = 1;
+    let third = cache.send(client.get(&url)).await.unwrap();
+    assert!(!third.from_cache);
+    assert_eq!(third.body, r#"{"version": 1}"#);
+    let fourth = cache.send(client.get(&url)).await.unwrap();
+    assert!(fourth.from_cache);
+
+    assert_eq!(
+        sent_if_none_match(&resource),
+        vec![
+            None,
+            Some("\"v0\"".to_string()),
+            Some("\"v0\"".to_string()),
+            Some("\"v1\"".to_string())
+        ]
+    );
+    let stats = cache.stats();
+    assert_eq!((stats.hits, stats.misses, stats.entries), (2, 2, 1));
+}
+
+#[tokio::test]
+async fn test_etag_from_response_body() {
+    let (url, resource) = start_resource_server().await;
+    let client = reqwest::Client::new();
+    let cache = EtagCache::new(10);
+    let url = format!("{}/body-tagged", url);
+
+    assert!(!cache.send(client.get(&url)).await.unwrap().from_cache);
+    assert!(cache.send(client.get(&url)).await.unwrap().from_cache);
+    assert_eq!(
+        sent_if_none_match(&resource),
+        vec![None, Some("\"b0\"".to_string())]
+    );
+}
+
+#[tokio::test]
+async fn test_uncacheable_requests() {
+    let (url, resource) = start_resource_server().await;
+    let client = reqwest::Client::new();
+    let cache = EtagCache::new(10);
+
+    // Responses without an ETag are not kept
+    let untagged = format!("{}/untagged", url);
+    cache.send(client.get(&untagged)).await.unwrap();
+    cache.send(client.get(&untagged)).await.unwrap();
+    assert_eq!(cache.stats().entries, 0);
+
+    // Neither are writes or errors
+    let tagged = format!("{}/tagged", url);
+    let response = cache.send(client.post(&tagged)).await.unwrap();
+    assert_eq!(response.status.as_u16(), 405);
+    let missing = cache
+        .send(client.get(format!("{}/missing", url)))
+        .await
+        .unwrap();
+    assert_eq!(missing.status.as_u16(), 404);
+    assert!(!missing.from_cache);
+    assert_eq!(cache.stats().entries, 0);
+
+    assert_eq!(sent_if_none_match(&resource), vec![None, None]);
+}
+
+#[tokio::test]
+async fn test_least_recently_used_entry_is_evicted() {
+    let (url, resource) = start_resource_server().await;
+    let client = reqwest::Client::new();
+    let cache = EtagCache::new(1);
+    let tagged = format!("{}/tagged", url);
+    let body_tagged = format!("{}/body-tagged", url);
+
+    cache.send(client.get(&tagged)).await.unwrap();
+    cache.send(client.get(&body_tagged)).await.unwrap();
+    assert_eq!(cache.stats().entries, 1);
+
+    // The first response was evicted, so it is fetched unconditionally
+    assert!(!cache.send(client.get(&tagged)).await.unwrap().from_cache);
+    assert_eq!(sent_if_none_match(&resource), vec![None, None, None]);
+
+    cache.clear();
+    assert_eq!(cache.stats().entries, 0);
+}
+
+#[tokio::test]
+async fn test_cache_saved_to_file() {
+    let (url, resource) = start_resource_server().await;
+    let client = reqwest::Client::new();
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("state").join(ETAG_CACHE_FILE);
+    let tagged = format!("{}/tagged", url);
+    let body_tagged = format!("{}/body-tagged", url);
+
+    // Changes are saved together a moment after the first
+    let cache = EtagCache::with_file(10, file.clone());
+    cache.send(client.get(&tagged)).await.unwrap();
+    cache.send(client.get(&body_tagged)).await.unwrap();
+    assert!(!file.exists());
+    tokio::time::sleep(ETAG_CACHE_SAVE_DELAY * 3).await;
+    assert!(file.exists());
+
+    // Only its owner can read the saved responses
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&file).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    // A cache started from the file revalidates what was saved
+    let restarted = EtagCache::with_file(10, file.clone());
+    assert_eq!(restarted.stats().entries, 2);
+    let response = restarted.send(client.get(&tagged)).await.unwrap();
+    assert!(response.from_cache);
+    assert_eq!(response.body, r#"{"version": 0}"#);
+    assert_eq!(
+        sent_if_none_match(&resource),
+        vec![None, None, Some("\"v0\"".to_string())]
+    );
+
+    // Only the most recently stored responses fit a smaller cache
+    let smaller = EtagCache::with_file(1, file.clone());
+    assert_eq!(smaller.stats().entries, 1);
+    assert!(smaller.send(client.get(&body_tagged)).await.unwrap().from_cache);
+
+    smaller.clear();
+    smaller.flush();
+    assert_eq!(EtagCache::with_file(10, file.clone()).stats().entries, 0);
+
+    // An unreadable file starts an empty cache
+    std::fs::write(&file, "not json").unwrap();
+    assert_eq!(EtagCache::with_file(10, file).stats().entries, 0);
+}
+
+#[tokio::test]
+async fn test_api_clients_revalidate_cached_resources() {
+    let _server = mock_server(None).await;
+    let config = Config::from_env().unwrap();
+    let cache = EtagCache::global();
+
+    let calendar = CalendarClient::new(&config);
+    let people = PeopleClient::new(&config);
+    let gmail = GmailService::new(&config).unwrap();
+
+    let first_calendars = calendar.list_calendars().await.unwrap();
+    let first_contacts = people.list_contacts(None).await.unwrap();
+    let first_labels = gmail.list_labels().await.unwrap();
+    let hits = cache.stats().hits;
+
+    let calendars = calendar.list_calendars().await.unwrap();
+    let contacts = people.list_contacts(None).await.unwrap();
+    let labels = gmail.list_labels().await.unwrap();
+    assert_eq!(cache.stats().hits, hits + 3);
+    assert_eq!(calendars.calendars.len(), first_calendars.calendars.len());
+    assert_eq!(contacts.contacts.len(), first_contacts.contacts.len());
+    assert_eq!(labels, first_labels);
+
+    // Messages are never cached
+    gmail.get_message_details("mock-msg-001").await.unwrap();
+    gmail.get_message_details("mock-msg-001").await.unwrap();
+    assert_eq!(cache.stats().hits, hits + 3);
+
+    // Conditional requests can be turned off
+    std::env::set_var("ETAG_CACHE_ENABLED", "false");
+    calendar.list_calendars().await.unwrap();
+    assert_eq!(cache.stats().hits, hits + 3);
+    std::env::remove_var("ETAG_CACHE_ENABLED");
+}
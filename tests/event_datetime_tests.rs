@@ -0,0 +1,105 @@
+/// Event Date Time Tests Module
+///
+/// This module contains tests for the start and end of calendar events as the
+/// Calendar API sends them, for both all-day and timed events, and for
+/// creating all-day events against the mock server.
+use chrono::{NaiveDate, TimeZone, Utc};
+use mcp_gmailcal::calendar_api::EventDateTime;
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+#[test]
+fn test_timed_boundaries() {
+    let time = Utc.with_ymd_and_hms(2030, 3, 5, 9, 30, 0).unwrap();
+    let boundary = EventDateTime::new(time, false, Some("Europe/Berlin".to_string()));
+    assert!(!boundary.is_all_day());
+    assert_eq!(boundary.to_utc(), Some(time));
+    assert_eq!(
+        serde_json::to_value(&boundary).unwrap(),
+        json!({ "dateTime": "2030-03-05T09:30:00Z", "timeZone": "Europe/Berlin" })
+    );
+
+    // Times with an offset are read in UTC
+    let boundary: EventDateTime = serde_json::from_value(json!({
+        "dateTime": "2030-03-05T10:30:00+01:00",
+        "timeZone": "Europe/Berlin"
+    }))
+    .unwrap();
+    assert_eq!(boundary.to_utc(), Some(time));
+    assert_eq!(boundary.time_zone.as_deref(), Some("Europe/Berlin"));
+}
+
+#[test]
+fn test_all_day_boundaries() {
+    let time = Utc.with_ymd_and_hms(2030, 3, 5, 0, 0, 0).unwrap();
+    let boundary = EventDateTime::new(time, true, None);
+    assert!(boundary.is_all_day());
+    assert_eq!(
+        serde_json::to_value(&boundary).unwrap(),
+        json!({ "date": "2030-03-05", "timeZone": "UTC" })
+    );
+
+    let boundary: EventDateTime = serde_json::from_value(json!({ "date": "2030-03-06" })).unwrap();
+    assert_eq!(boundary.date, NaiveDate::from_ymd_opt(2030, 3, 6));
+    assert_eq!(
+        boundary.to_utc(),
+        Some(Utc.with_ymd_and_hms(2030, 3, 6, 0, 0, 0).unwrap())
+    );
+
+    // A boundary with neither has no time
+    assert_eq!(EventDateTime::default().to_utc(), None);
+    assert!(serde_json::from_value::<EventDateTime>(json!({ "date": "March 6" })).is_err());
+}
+
+#[tokio::test]
+async fn test_create_all_day_event() {
+    std::env::set_var("USER_TIMEZONE", "America/Los_Angeles");
+    let (_server, client) = mock_client(None).await;
+
+    // Dates are read in the user's time zone, and the event runs through the
+    // end date
+    let event = call_tool(
+        &client,
+        "create_event",
+        json!({
+            "summary": "Offsite",
+            "start_time": "2030-03-05 20:00",
+            "end_time": "2030-03-06",
+            "all_day": true
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(event["all_day"], true);
+    assert_eq!(event["start_time"], "2030-03-05T00:00:00Z");
+    assert_eq!(event["end_time"], "2030-03-07T00:00:00Z");
+
+    // Without an end the event takes one day
+    let event = call_tool(
+        &client,
+        "create_event",
+        json!({ "summary": "Holiday", "start_time": "2030-03-05", "all_day": true }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(event["start_time"], "2030-03-05T00:00:00Z");
+    assert_eq!(event["end_time"], "2030-03-06T00:00:00Z");
+
+    // Timed events are unchanged
+    let event = call_tool(
+        &client,
+        "create_event",
+        json!({
+            "summary": "Planning",
+            "start_time": "2030-03-05 10:00",
+            "end_time": "2030-03-05 11:00"
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(event["all_day"], false);
+    assert_eq!(event["start_time"], "2030-03-05T18:00:00Z");
+    std::env::remove_var("USER_TIMEZONE");
+}
@@ -0,0 +1,214 @@
+/// Event Properties Tests Module
+///
+/// This module contains tests for private extended properties on events:
+/// checking properties to set, tagging the events the server creates and
+/// linking them to emails, and the tools that read and write them and follow
+/// an event back to its email against the mock server.
+use chrono::{TimeZone, Utc};
+use mcp_gmailcal::calendar_api::{
+    CalendarEvent, CREATED_BY_PROPERTY, CREATED_BY_VALUE, SOURCE_MESSAGE_PROPERTY,
+};
+use mcp_gmailcal::event_properties::{
+    created_by_server_filter, source_message_id, source_message_properties, validate_properties,
+};
+use serde_json::json;
+use std::collections::HashMap;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn event_with(private_properties: HashMap<String, String>) -> CalendarEvent {
+    CalendarEvent {
+        id: Some("event-tagged".to_string()),
+        summary: "Follow up with Alice".to_string(),
+        start_time: Utc.with_ymd_and_hms(2026, 10, 20, 9, 0, 0).unwrap(),
+        end_time: Utc.with_ymd_and_hms(2026, 10, 20, 9, 30, 0).unwrap(),
+        private_properties,
+        ..Default::default()
+    }
+}
+
+fn properties(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_validate_properties() {
+    assert!(validate_properties(&properties(&[("project", "apollo")])).is_ok());
+    assert!(validate_properties(&HashMap::new()).is_err());
+    assert!(validate_properties(&properties(&[(" ", "x")])).is_err());
+    assert!(validate_properties(&properties(&[("createdBy", "me")]))
+        .unwrap_err()
+        .contains("managed by the server"));
+    assert!(validate_properties(&properties(&[("category", "Team")])).is_err());
+
+    let long_key = "k".repeat(45);
+    assert!(validate_properties(&properties(&[(&long_key, "x")]))
+        .unwrap_err()
+        .contains("longer than 44"));
+    let long_value = "v".repeat(1025);
+    assert!(validate_properties(&properties(&[("notes", &long_value)]))
+        .unwrap_err()
+        .contains("longer than 1024"));
+}
+
+#[test]
+fn test_server_properties() {
+    assert_eq!(
+        source_message_properties("18c1eab45a2d0123"),
+        properties(&[(SOURCE_MESSAGE_PROPERTY, "18c1eab45a2d0123")])
+    );
+    assert_eq!(
+        created_by_server_filter(),
+        vec![(
+            CREATED_BY_PROPERTY.to_string(),
+            CREATED_BY_VALUE.to_string()
+        )]
+    );
+}
+
+#[test]
+fn test_source_message_id() {
+    let linked = event_with(source_message_properties("18c1eab45a2d0123"));
+    assert_eq!(source_message_id(&linked), Some("18c1eab45a2d0123"));
+    assert_eq!(source_message_id(&event_with(HashMap::new())), None);
+    let blank = event_with(properties(&[(SOURCE_MESSAGE_PROPERTY, " ")]));
+    assert_eq!(source_message_id(&blank), None);
+}
+
+#[tokio::test]
+async fn test_event_properties_tools() {
+    let dir = tempfile::tempdir().unwrap();
+    let calendar = dir.path().join("calendar/v3/calendars/primary");
+    std::fs::create_dir_all(calendar.join("events")).unwrap();
+    let tagged = json!({
+        "id": "event-tagged",
+        "summary": "Follow up with Alice",
+        "start": { "dateTime": "2030-03-05T09:00:00Z" },
+        "end": { "dateTime": "2030-03-05T09:30:00Z" },
+        "extendedProperties": {
+            "private": {
+                "createdBy": "mcp-gmailcal",
+                "sourceMessageId": "mock-msg-001",
+                "category": "Follow-ups"
+            }
+        }
+    });
+    std::fs::write(
+        calendar.join("events.json"),
+        json!({ "items": [tagged] }).to_string(),
+    )
+    .unwrap();
+    std::fs::write(
+        calendar.join("events/event-tagged.json"),
+        tagged.to_string(),
+    )
+    .unwrap();
+    let untagged = json!({
+        "id": "event-untagged",
+        "summary": "Lunch",
+        "start": { "dateTime": "2030-03-05T12:00:00Z" },
+        "end": { "dateTime": "2030-03-05T13:00:00Z" }
+    });
+    std::fs::write(
+        calendar.join("events/event-untagged.json"),
+        untagged.to_string(),
+    )
+    .unwrap();
+    let mut patched = tagged.clone();
+    patched["extendedProperties"]["private"]["project"] = json!("apollo");
+    std::fs::write(
+        calendar.join("events/event-tagged.patch.json"),
+        patched.to_string(),
+    )
+    .unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    // The mock echoes the event created, showing the properties sent
+    let created = call_tool(
+        &client,
+        "create_event",
+        json!({
+            "summary": "Quarterly planning follow-up",
+            "start_time": "2030-03-06T10:00:00Z",
+            "end_time": "2030-03-06T10:30:00Z",
+            "source_message_id": "mock-msg-001"
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        created["private_properties"],
+        json!({ "createdBy": "mcp-gmailcal", "sourceMessageId": "mock-msg-001" })
+    );
+    assert!(created["category"].is_null());
+
+    let listed = call_tool(&client, "list_events", json!({ "created_by_server": true }))
+        .await
+        .unwrap();
+    assert_eq!(listed[0]["id"], "event-tagged");
+    assert_eq!(listed[0]["category"], "Follow-ups");
+    assert_eq!(listed[0]["private_properties"]["createdBy"], "mcp-gmailcal");
+    assert!(listed[0]["private_properties"]["category"].is_null());
+
+    let result = call_tool(
+        &client,
+        "set_event_properties",
+        json!({ "event_id": "event-tagged", "properties": { "project": "apollo" } }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["event_id"], "event-tagged");
+    assert_eq!(result["private_properties"]["project"], "apollo");
+    assert_eq!(
+        result["private_properties"]["sourceMessageId"],
+        "mock-msg-001"
+    );
+    assert_eq!(result["category"], "Follow-ups");
+
+    let error = call_tool(
+        &client,
+        "set_event_properties",
+        json!({ "event_id": "event-tagged", "properties": { "createdBy": "someone" } }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("managed by the server"));
+
+    let source = call_tool(
+        &client,
+        "get_event_source_email",
+        json!({ "event_id": "event-tagged" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(source["event_id"], "event-tagged");
+    assert_eq!(source["message_id"], "mock-msg-001");
+    assert_eq!(
+        source["email_link"],
+        "https://mail.google.com/mail/u/0/#all/mock-msg-001"
+    );
+    assert_eq!(source["email"]["id"], "mock-msg-001");
+    assert_eq!(source["email"]["thread_id"], "mock-thread-001");
+
+    let error = call_tool(
+        &client,
+        "get_event_source_email",
+        json!({ "event_id": "event-untagged" }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("not linked to an email"));
+}
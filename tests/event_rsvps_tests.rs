@@ -0,0 +1,155 @@
+/// Event RSVP Tests Module
+///
+/// This module contains tests for grouping event attendees by response, and
+/// for the event_rsvps tool against the mock server.
+use chrono::{Duration, TimeZone, Utc};
+use mcp_gmailcal::calendar_api::{Attendee, CalendarEvent, EventOrganizer};
+use mcp_gmailcal::event_rsvps::{invitee_addresses, is_resource, RsvpCounts, RsvpReport};
+use mcp_gmailcal::people_api::{Contact, EmailAddress, PersonName};
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn attendee(email: &str, status: Option<&str>, optional: bool) -> Attendee {
+    Attendee {
+        email: email.to_string(),
+        display_name: None,
+        response_status: status.map(String::from),
+        optional: optional.then_some(true),
+        time_zone: None,
+        self_: None,
+    }
+}
+
+fn event() -> CalendarEvent {
+    let start = Utc.with_ymd_and_hms(2026, 10, 20, 16, 0, 0).unwrap();
+    CalendarEvent {
+        id: Some("event-rsvp".to_string()),
+        summary: "Launch review".to_string(),
+        start_time: start,
+        end_time: start + Duration::hours(1),
+        attendees: vec![
+            attendee("demo@example.com", Some("accepted"), false),
+            attendee("Alice@example.com", Some("declined"), false),
+            attendee("bob@example.com", Some("needsAction"), false),
+            attendee("carol@example.com", None, true),
+            attendee("dave@example.com", Some("tentative"), false),
+            attendee(
+                "c_room42@resource.calendar.google.com",
+                Some("accepted"),
+                false,
+            ),
+        ],
+        organizer: Some(EventOrganizer {
+            email: "demo@example.com".to_string(),
+            display_name: None,
+            self_: Some(true),
+        }),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_invitees_leave_out_resources() {
+    assert!(is_resource("c_room42@Resource.Calendar.Google.com"));
+    assert!(!is_resource("alice@example.com"));
+    assert_eq!(
+        invitee_addresses(&event()),
+        vec![
+            "demo@example.com",
+            "alice@example.com",
+            "bob@example.com",
+            "carol@example.com",
+            "dave@example.com"
+        ]
+    );
+}
+
+#[test]
+fn test_rsvp_report() {
+    let mut event = event();
+    event.attendees[4].display_name = Some("Dave from the event".to_string());
+    let contacts = vec![Contact {
+        resource_name: "people/c001".to_string(),
+        name: Some(PersonName {
+            display_name: "Alice Example".to_string(),
+            given_name: None,
+            family_name: None,
+        }),
+        email_addresses: vec![EmailAddress {
+            value: "alice@example.com".to_string(),
+            type_: None,
+        }],
+        phone_numbers: Vec::new(),
+        organizations: Vec::new(),
+        photos: Vec::new(),
+    }];
+
+    let report = RsvpReport::new(&event, &contacts);
+    assert_eq!(
+        report.counts,
+        RsvpCounts {
+            accepted: 1,
+            declined: 1,
+            tentative: 1,
+            no_response: 2,
+            total: 5,
+        }
+    );
+    assert!(report.accepted[0].organizer);
+    assert_eq!(report.declined[0].email, "alice@example.com");
+    assert_eq!(report.declined[0].name.as_deref(), Some("Alice Example"));
+    assert_eq!(
+        report.tentative[0].name.as_deref(),
+        Some("Dave from the event")
+    );
+    assert_eq!(report.no_response.len(), 2);
+    assert!(report.no_response[1].optional);
+    // Optional attendees are not chased
+    assert_eq!(report.awaiting_response, vec!["bob@example.com"]);
+}
+
+#[tokio::test]
+async fn test_event_rsvps_tool() {
+    let dir = tempfile::tempdir().unwrap();
+    let events = dir.path().join("calendar/v3/calendars/primary/events");
+    std::fs::create_dir_all(&events).unwrap();
+    let event = json!({
+        "id": "event-rsvp",
+        "summary": "Launch review",
+        "start": { "dateTime": "2026-10-20T16:00:00Z" },
+        "end": { "dateTime": "2026-10-20T17:00:00Z" },
+        "organizer": { "email": "demo@example.com", "self": true },
+        "attendees": [
+            { "email": "demo@example.com", "responseStatus": "accepted" },
+            { "email": "alice@example.com", "responseStatus": "tentative" },
+            { "email": "bob@example.com", "displayName": "Bobby", "responseStatus": "needsAction" },
+            { "email": "c_room42@resource.calendar.google.com", "responseStatus": "accepted" }
+        ]
+    });
+    std::fs::write(events.join("event-rsvp.json"), event.to_string()).unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    let report = call_tool(&client, "event_rsvps", json!({ "event_id": "event-rsvp" }))
+        .await
+        .unwrap();
+    assert_eq!(
+        report["counts"],
+        json!({ "accepted": 1, "declined": 0, "tentative": 1, "no_response": 1, "total": 3 })
+    );
+    assert_eq!(report["tentative"][0]["name"], "Alice Example");
+    // Contact names come before the one on the invitation
+    assert_eq!(report["no_response"][0]["name"], "Bob Example");
+    assert_eq!(report["awaiting_response"], json!(["bob@example.com"]));
+
+    let error = call_tool(
+        &client,
+        "event_rsvps",
+        json!({ "event_id": "no-such-event" }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error.error_object().is_some());
+}
@@ -0,0 +1,319 @@
+/// Event Rules Tests Module
+///
+/// This module contains tests for matching events to color and category
+/// rules, storing the rules, and for the categorize_events tool against the
+/// mock server.
+use chrono::{TimeZone, Utc};
+use mcp_gmailcal::calendar_api::{Attendee, CalendarEvent};
+use mcp_gmailcal::event_rules::{color_name, match_rule, parse_color, EventRule, EventRuleStore};
+use mcp_gmailcal::RuleError;
+use serde_json::{json, Value};
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn attendee(email: &str) -> Attendee {
+    Attendee {
+        email: email.to_string(),
+        display_name: None,
+        response_status: None,
+        optional: None,
+        time_zone: None,
+        self_: None,
+    }
+}
+
+fn event(summary: &str, attendees: &[&str]) -> CalendarEvent {
+    CalendarEvent {
+        id: Some("event-1".to_string()),
+        summary: summary.to_string(),
+        start_time: Utc.with_ymd_and_hms(2030, 3, 5, 16, 0, 0).unwrap(),
+        end_time: Utc.with_ymd_and_hms(2030, 3, 5, 16, 30, 0).unwrap(),
+        attendees: attendees.iter().map(|email| attendee(email)).collect(),
+        ..Default::default()
+    }
+}
+
+fn rule(name: &str) -> EventRule {
+    EventRule {
+        name: name.to_string(),
+        keywords: Vec::new(),
+        attendees: Vec::new(),
+        max_attendees: None,
+        color_id: None,
+        category: None,
+    }
+}
+
+fn rules() -> Vec<EventRule> {
+    vec![
+        EventRule {
+            attendees: vec!["@customer.example".to_string()],
+            category: Some("Customer".to_string()),
+            color_id: Some("11".to_string()),
+            ..rule("Customers")
+        },
+        EventRule {
+            max_attendees: Some(2),
+            color_id: Some("10".to_string()),
+            ..rule("1:1s")
+        },
+        EventRule {
+            keywords: vec!["Standup".to_string(), "retro".to_string()],
+            category: Some("Team".to_string()),
+            ..rule("Team rituals")
+        },
+    ]
+}
+
+#[test]
+fn test_parse_color() {
+    assert_eq!(parse_color("10"), Some("10"));
+    assert_eq!(parse_color("Basil"), Some("10"));
+    assert_eq!(parse_color(" green "), Some("10"));
+    assert_eq!(parse_color("grey"), Some("8"));
+    assert_eq!(parse_color("12"), None);
+    assert_eq!(parse_color("chartreuse"), None);
+    assert_eq!(color_name("7"), Some("peacock"));
+}
+
+#[test]
+fn test_match_rule() {
+    let rules = rules();
+    let name = |event: &CalendarEvent| match_rule(&rules, event).map(|rule| rule.name.as_str());
+
+    // The first matching rule wins
+    let call = event("Renewal", &["demo@example.com", "buyer@Customer.example"]);
+    assert_eq!(name(&call), Some("Customers"));
+
+    // Rooms do not count as people
+    let one_on_one = event(
+        "Alice / Demo",
+        &[
+            "demo@example.com",
+            "alice@example.com",
+            "c_room42@resource.calendar.google.com",
+        ],
+    );
+    assert_eq!(name(&one_on_one), Some("1:1s"));
+    assert_eq!(name(&event("Focus", &[])), None);
+
+    let mut standup = event(
+        "Daily sync",
+        &["demo@example.com", "alice@example.com", "bob@example.com"],
+    );
+    assert_eq!(name(&standup), None);
+    standup.description = Some("Our STANDUP, 15 minutes".to_string());
+    assert_eq!(name(&standup), Some("Team rituals"));
+
+    // Every condition must hold
+    let rule = EventRule {
+        keywords: vec!["review".to_string()],
+        attendees: vec!["alice@example.com".to_string()],
+        color_id: Some("9".to_string()),
+        ..rule("Reviews with Alice")
+    };
+    assert!(rule.matches(&event("Design review", &["alice@example.com"])));
+    assert!(!rule.matches(&event("Design review", &["bob@example.com"])));
+}
+
+#[test]
+fn test_rule_patch() {
+    let rules = rules();
+    let mut call = event("Renewal", &["buyer@customer.example"]);
+    assert_eq!(
+        rules[0].patch(&call),
+        Some(json!({
+            "colorId": "11",
+            "extendedProperties": { "private": { "category": "Customer" } }
+        }))
+    );
+
+    // Only what differs is changed
+    call.color_id = Some("11".to_string());
+    assert_eq!(
+        rules[0].patch(&call),
+        Some(json!({ "extendedProperties": { "private": { "category": "Customer" } } }))
+    );
+    call.category = Some("Customer".to_string());
+    assert_eq!(rules[0].patch(&call), None);
+}
+
+#[test]
+fn test_event_rule_store() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = EventRuleStore::new(dir.path().join("rules/event_rules.json"));
+    assert!(store.list().unwrap().is_empty());
+
+    let (saved, replaced) = store
+        .save(EventRule {
+            max_attendees: Some(2),
+            color_id: Some("Green".to_string()),
+            ..rule(" 1:1s ")
+        })
+        .unwrap();
+    assert!(!replaced);
+    assert_eq!(saved.name, "1:1s");
+    assert_eq!(saved.color_id.as_deref(), Some("10"));
+
+    store
+        .save(EventRule {
+            keywords: vec!["standup".to_string()],
+            category: Some("Team".to_string()),
+            ..rule("Team")
+        })
+        .unwrap();
+    // Replacing a rule keeps its place
+    let (_, replaced) = store
+        .save(EventRule {
+            max_attendees: Some(2),
+            color_id: Some("sage".to_string()),
+            ..rule("1:1S")
+        })
+        .unwrap();
+    assert!(replaced);
+    let names: Vec<String> = store.list().unwrap().into_iter().map(|r| r.name).collect();
+    assert_eq!(names, vec!["1:1S", "Team"]);
+
+    store.delete("team").unwrap();
+    assert_eq!(store.list().unwrap().len(), 1);
+    assert!(matches!(store.delete("team"), Err(RuleError::NotFound(_))));
+
+    // Rules need something to match and something to do
+    let invalid = [
+        EventRule {
+            color_id: Some("10".to_string()),
+            ..rule("Everything")
+        },
+        EventRule {
+            keywords: vec!["standup".to_string()],
+            ..rule("Nothing")
+        },
+        EventRule {
+            keywords: vec!["standup".to_string()],
+            color_id: Some("chartreuse".to_string()),
+            ..rule("Unknown color")
+        },
+        EventRule {
+            max_attendees: Some(1),
+            color_id: Some("10".to_string()),
+            ..rule("Alone")
+        },
+    ];
+    for rule in invalid {
+        assert!(matches!(store.save(rule), Err(RuleError::InvalidRule(_))));
+    }
+}
+
+#[tokio::test]
+async fn test_categorize_events_tool() {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("EVENT_RULES_FILE", dir.path().join("event_rules.json"));
+    std::env::set_var("USER_TIMEZONE", "UTC");
+
+    let events = dir.path().join("calendar/v3/calendars/primary/events");
+    std::fs::create_dir_all(&events).unwrap();
+    let people = |emails: &[&str]| -> Value {
+        emails
+            .iter()
+            .map(|email| json!({ "email": email }))
+            .collect()
+    };
+    let event = |id: &str, summary: &str, attendees: Value| {
+        json!({
+            "id": id,
+            "summary": summary,
+            "start": { "dateTime": "2030-03-05T16:00:00Z" },
+            "end": { "dateTime": "2030-03-05T16:30:00Z" },
+            "attendees": attendees
+        })
+    };
+    let mut done = event(
+        "ev-done",
+        "Alice / Demo",
+        people(&["demo@example.com", "alice@example.com"]),
+    );
+    done["colorId"] = json!("10");
+    done["extendedProperties"] = json!({ "private": { "category": "1:1" } });
+    std::fs::write(
+        dir.path().join("calendar/v3/calendars/primary/events.json"),
+        json!({
+            "items": [
+                event("ev-1on1", "Bob / Demo", people(&["demo@example.com", "bob@example.com"])),
+                done,
+                event("ev-locked", "Carol / Demo", people(&["demo@example.com", "carol@example.com"])),
+                event(
+                    "ev-team",
+                    "Planning",
+                    people(&["demo@example.com", "alice@example.com", "bob@example.com"])
+                ),
+            ]
+        })
+        .to_string(),
+    )
+    .unwrap();
+    let mut patched = event(
+        "ev-1on1",
+        "Bob / Demo",
+        people(&["demo@example.com", "bob@example.com"]),
+    );
+    patched["colorId"] = json!("10");
+    patched["extendedProperties"] = json!({ "private": { "category": "1:1" } });
+    std::fs::write(events.join("ev-1on1.patch.json"), patched.to_string()).unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    // Nothing to apply without rules
+    assert!(call_tool(&client, "categorize_events", json!({}))
+        .await
+        .is_err());
+
+    let result = call_tool(
+        &client,
+        "save_event_rule",
+        json!({ "name": "1:1s", "max_attendees": 2, "color": "green", "category": "1:1" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["rule"]["color_id"], "10");
+    assert_eq!(result["replaced"], false);
+    let result = call_tool(&client, "list_event_rules", json!({}))
+        .await
+        .unwrap();
+    assert_eq!(result["count"], 1);
+
+    let range = json!({ "time_min": "2030-03-05", "time_max": "2030-03-06" });
+    let mut dry_run = range.clone();
+    dry_run["dry_run"] = json!(true);
+    let result = call_tool(&client, "categorize_events", dry_run)
+        .await
+        .unwrap();
+    assert_eq!(result["events_checked"], 4);
+    assert_eq!(result["matched"], 3);
+    assert_eq!(result["updated"], 2);
+    let statuses: Vec<&str> = result["events"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|event| event["status"].as_str().unwrap())
+        .collect();
+    assert_eq!(statuses, vec!["would_update", "unchanged", "would_update"]);
+
+    // An event whose update fails does not stop the others
+    let result = call_tool(&client, "categorize_events", range)
+        .await
+        .unwrap();
+    assert_eq!(result["updated"], 1);
+    assert_eq!(result["failed"], 1);
+    assert_eq!(result["events"][2]["event_id"], "ev-locked");
+    let first = &result["events"][0];
+    assert_eq!(first["event_id"], "ev-1on1");
+    assert_eq!(first["status"], "updated");
+    assert_eq!(first["color"], "basil");
+    assert_eq!(first["category"], "1:1");
+
+    let result = call_tool(&client, "delete_event_rule", json!({ "name": "1:1S" }))
+        .await
+        .unwrap();
+    assert_eq!(result["deleted"], "1:1S");
+}
@@ -0,0 +1,186 @@
+/// Event Search Tests Module
+///
+/// This module contains tests for finding the events shared with a person
+/// across calendars, and for the find_events_with tool against the mock
+/// server.
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use mcp_gmailcal::calendar_api::{Attendee, CalendarEvent, CalendarInfo, EventOrganizer};
+use mcp_gmailcal::event_search::{involves, EventsWith};
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn event(id: &str, start: DateTime<Utc>, attendees: &[&str], organizer: &str) -> CalendarEvent {
+    CalendarEvent {
+        id: Some(id.to_string()),
+        summary: format!("Event {}", id),
+        start_time: start,
+        end_time: start + Duration::hours(1),
+        attendees: attendees
+            .iter()
+            .map(|email| Attendee {
+                email: email.to_string(),
+                display_name: None,
+                response_status: Some("tentative".to_string()),
+                optional: None,
+                time_zone: None,
+                self_: None,
+            })
+            .collect(),
+        organizer: Some(EventOrganizer {
+            email: organizer.to_string(),
+            display_name: None,
+            self_: None,
+        }),
+        ..Default::default()
+    }
+}
+
+fn calendar(id: &str) -> CalendarInfo {
+    CalendarInfo {
+        id: id.to_string(),
+        summary: format!("Calendar {}", id),
+        description: None,
+        primary: None,
+    }
+}
+
+#[test]
+fn test_involves() {
+    let start = Utc.with_ymd_and_hms(2026, 10, 20, 9, 0, 0).unwrap();
+    let event = event("1", start, &["Alice@example.com"], "carol@example.com");
+    assert!(involves(&event, "alice@example.com"));
+    assert!(involves(&event, "CAROL@example.com"));
+    assert!(!involves(&event, "bob@example.com"));
+}
+
+#[test]
+fn test_events_with_across_calendars() {
+    let start = Utc.with_ymd_and_hms(2026, 10, 15, 0, 0, 0).unwrap();
+    let end = start + Duration::days(30);
+    let day = |days: i64| start + Duration::days(days) + Duration::hours(9);
+    let calendars = vec![
+        (
+            calendar("primary"),
+            vec![
+                event("shared", day(5), &["alice@example.com"], "demo@example.com"),
+                event("bob-only", day(1), &["bob@example.com"], "demo@example.com"),
+                event("past", day(-3), &["alice@example.com"], "demo@example.com"),
+            ],
+        ),
+        (
+            calendar("team@example.com"),
+            vec![
+                event("shared", day(5), &["alice@example.com"], "demo@example.com"),
+                event("team", day(2), &["bob@example.com"], "alice@example.com"),
+                event("late", day(40), &["alice@example.com"], "demo@example.com"),
+            ],
+        ),
+    ];
+
+    let found = EventsWith::new("alice@example.com", (start, end), &calendars, 10);
+    assert_eq!(
+        found.calendars_searched,
+        vec!["primary", "team@example.com"]
+    );
+    assert!(!found.truncated);
+    let ids: Vec<_> = found
+        .events
+        .iter()
+        .map(|found| found.event.id.as_deref().unwrap())
+        .collect();
+    // Soonest first, the shared event once, nothing outside the range
+    assert_eq!(ids, vec!["team", "shared"]);
+    assert_eq!(found.events[0].calendar_id, "team@example.com");
+    assert!(found.events[0].organizer);
+    assert_eq!(found.events[0].response_status, None);
+    assert_eq!(found.events[1].calendar_id, "primary");
+    assert!(!found.events[1].organizer);
+    assert_eq!(
+        found.events[1].response_status.as_deref(),
+        Some("tentative")
+    );
+
+    let found = EventsWith::new("alice@example.com", (start, end), &calendars, 1);
+    assert_eq!(found.events.len(), 1);
+    assert!(found.truncated);
+}
+
+#[tokio::test]
+async fn test_find_events_with_tool() {
+    let dir = tempfile::tempdir().unwrap();
+    let team = dir.path().join("calendar/v3/calendars/team@example.com");
+    std::fs::create_dir_all(&team).unwrap();
+    let start = Utc::now() + Duration::days(3);
+    let events = json!({
+        "items": [{
+            "id": "team-sync",
+            "summary": "Team sync",
+            "start": { "dateTime": start.to_rfc3339() },
+            "end": { "dateTime": (start + Duration::minutes(30)).to_rfc3339() },
+            "organizer": { "email": "demo@example.com" },
+            "attendees": [
+                { "email": "demo@example.com", "responseStatus": "accepted" },
+                { "email": "alice@example.com", "responseStatus": "declined" }
+            ]
+        }]
+    });
+    std::fs::write(team.join("events.json"), events.to_string()).unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    // The primary calendar's events with Alice are in the past
+    let found = call_tool(
+        &client,
+        "find_events_with",
+        json!({ "person_email": "Alice <alice@example.com>" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(found["person_email"], "alice@example.com");
+    assert_eq!(
+        found["calendars_searched"],
+        json!(["primary", "team@example.com"])
+    );
+    let events = found["events"].as_array().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0]["calendar_id"], "team@example.com");
+    assert_eq!(events[0]["response_status"], "declined");
+    assert_eq!(events[0]["event"]["summary"], "Team sync");
+
+    let found = call_tool(
+        &client,
+        "find_events_with",
+        json!({ "person_email": "bob@example.com", "time_max": "in 1 week" }),
+    )
+    .await
+    .unwrap();
+    assert!(found["events"].as_array().unwrap().is_empty());
+
+    let error = call_tool(
+        &client,
+        "find_events_with",
+        json!({ "person_email": "alice@example.com", "time_min": "tomorrow", "time_max": "today" }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("Invalid time_max: must be after time_min"));
+
+    let error = call_tool(
+        &client,
+        "find_events_with",
+        json!({ "person_email": "the design team" }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("'the design team' is not an email address"));
+}
@@ -0,0 +1,210 @@
+/// Event Templates Tests Module
+///
+/// This module contains tests for reminders, filling event templates' placeholders,
+/// storing event templates, and for creating events from them against the mock
+/// server.
+use chrono::Duration;
+use mcp_gmailcal::calendar_api::EventReminder;
+use mcp_gmailcal::event_templates::{parse_reminder, EventTemplate, EventTemplateStore};
+use mcp_gmailcal::TemplateError;
+use serde_json::json;
+use std::collections::HashMap;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn reminder(method: &str, minutes: i64) -> EventReminder {
+    EventReminder {
+        method: method.to_string(),
+        minutes,
+    }
+}
+
+fn interview() -> EventTemplate {
+    EventTemplate {
+        name: "Interview".to_string(),
+        summary: "Interview: {{candidate}}".to_string(),
+        description: Some("Role: {{role}}\nScorecard in the hiring doc".to_string()),
+        location: None,
+        attendees: vec![
+            "{{interviewer}}".to_string(),
+            "recruiting@example.com".to_string(),
+        ],
+        duration_minutes: Some(45),
+        reminders: Some(vec![reminder("popup", 10), reminder("email", 1440)]),
+    }
+}
+
+fn variables(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_parse_reminder() {
+    assert_eq!(parse_reminder("10m").unwrap(), reminder("popup", 10));
+    assert_eq!(parse_reminder("Popup 1h").unwrap(), reminder("popup", 60));
+    assert_eq!(
+        parse_reminder("email 1 day").unwrap(),
+        reminder("email", 1440)
+    );
+    assert_eq!(parse_reminder("2d").unwrap(), reminder("popup", 2880));
+    assert_eq!(parse_reminder("0").unwrap(), reminder("popup", 0));
+    assert!(parse_reminder("sms 10m").is_err());
+    assert!(parse_reminder("5 weeks").is_err());
+    assert!(parse_reminder("30d").is_err());
+}
+
+#[test]
+fn test_render_event_template() {
+    let template = interview();
+    assert_eq!(
+        template.variables(),
+        vec!["candidate", "interviewer", "role"]
+    );
+
+    let rendered = template
+        .render(&variables(&[
+            ("candidate", "Ada Lovelace"),
+            ("role", "Engineer"),
+            ("interviewer", "alice@example.com"),
+        ]))
+        .unwrap();
+    assert_eq!(rendered.summary, "Interview: Ada Lovelace");
+    assert_eq!(
+        rendered.description.as_deref(),
+        Some("Role: Engineer\nScorecard in the hiring doc")
+    );
+    assert_eq!(
+        rendered.attendees,
+        vec!["alice@example.com", "recruiting@example.com"]
+    );
+    assert_eq!(rendered.duration, Some(Duration::minutes(45)));
+    assert_eq!(rendered.reminders, template.reminders);
+
+    match template.render(&variables(&[("candidate", "Ada")])) {
+        Err(TemplateError::MissingVariables(missing)) => {
+            assert_eq!(missing, vec!["interviewer", "role"])
+        }
+        other => panic!("expected missing variables, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_event_template_store() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = EventTemplateStore::new(dir.path().join("event_templates.json"));
+    assert!(store.list().unwrap().is_empty());
+
+    assert!(!store.save(interview()).unwrap());
+    assert!(store
+        .save(EventTemplate {
+            duration_minutes: Some(60),
+            ..interview()
+        })
+        .unwrap());
+    let saved = store.get("interview").unwrap();
+    assert_eq!(saved.duration_minutes, Some(60));
+    assert!(matches!(store.get("1:1"), Err(TemplateError::NotFound(_))));
+
+    let invalid = [
+        EventTemplate {
+            summary: " ".to_string(),
+            ..interview()
+        },
+        EventTemplate {
+            duration_minutes: Some(0),
+            ..interview()
+        },
+        EventTemplate {
+            reminders: Some(vec![reminder("sms", 10)]),
+            ..interview()
+        },
+    ];
+    for template in invalid {
+        assert!(matches!(
+            store.save(template),
+            Err(TemplateError::InvalidTemplate(_))
+        ));
+    }
+}
+
+#[tokio::test]
+async fn test_event_template_tools() {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var(
+        "EVENT_TEMPLATES_FILE",
+        dir.path().join("event_templates.json"),
+    );
+    std::env::set_var("USER_TIMEZONE", "UTC");
+    let (_server, client) = mock_client(None).await;
+
+    let result = call_tool(
+        &client,
+        "save_event_template",
+        json!({
+            "name": "1:1",
+            "summary": "{{name}} / Demo",
+            "description": "Agenda: {{agenda}}",
+            "attendees": ["{{email}}"],
+            "duration_minutes": 30,
+            "reminders": ["5m", "email 1d"]
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["replaced"], false);
+    assert_eq!(result["variables"], json!(["agenda", "email", "name"]));
+    assert_eq!(
+        result["template"]["reminders"],
+        json!([
+            { "method": "popup", "minutes": 5 },
+            { "method": "email", "minutes": 1440 }
+        ])
+    );
+    assert!(call_tool(
+        &client,
+        "save_event_template",
+        json!({ "name": "Bad", "summary": "Bad", "reminders": ["sms"] }),
+    )
+    .await
+    .is_err());
+
+    let result = call_tool(&client, "list_event_templates", json!({}))
+        .await
+        .unwrap();
+    assert_eq!(result["count"], 1);
+    assert_eq!(result["templates"][0]["attendees"], 1);
+
+    let event = call_tool(
+        &client,
+        "create_event_from_template",
+        json!({
+            "template_name": "1:1",
+            "variables": { "name": "Alice", "agenda": "Roadmap", "email": "alice@example.com" },
+            "start_time": "2030-03-05 10:00"
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(event["summary"], "Alice / Demo");
+    assert_eq!(event["description"], "Agenda: Roadmap");
+    assert_eq!(event["attendees"][0]["email"], "alice@example.com");
+    assert_eq!(event["start_time"], "2030-03-05T10:00:00Z");
+    assert_eq!(event["end_time"], "2030-03-05T10:30:00Z");
+    assert_eq!(
+        event["reminders"][1],
+        json!({ "method": "email", "minutes": 1440 })
+    );
+
+    // Nothing is created with placeholders left unfilled
+    assert!(call_tool(
+        &client,
+        "create_event_from_template",
+        json!({ "template_name": "1:1", "start_time": "2030-03-05 10:00" }),
+    )
+    .await
+    .is_err());
+}
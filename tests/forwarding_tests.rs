@@ -0,0 +1,170 @@
+/// Forwarding Tests Module
+///
+/// This module contains tests for auto-forwarding, checking dispositions, the
+/// setting worked out from a request and the forwarding addresses, and the
+/// list_forwarding_addresses and set_auto_forwarding tools against the mock
+/// server.
+use mcp_gmailcal::forwarding::{parse_disposition, plan_auto_forwarding};
+use mcp_gmailcal::{AutoForwarding, ForwardingAddress};
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn address(email: &str, status: &str) -> ForwardingAddress {
+    ForwardingAddress {
+        forwarding_email: email.to_string(),
+        verification_status: Some(status.to_string()),
+    }
+}
+
+fn disabled() -> AutoForwarding {
+    AutoForwarding {
+        enabled: false,
+        email_address: None,
+        disposition: None,
+    }
+}
+
+#[test]
+fn test_parse_disposition() {
+    assert_eq!(parse_disposition("leaveInInbox"), Some("leaveInInbox"));
+    assert_eq!(parse_disposition("leave_in_inbox"), Some("leaveInInbox"));
+    assert_eq!(parse_disposition("Mark read"), Some("markRead"));
+    assert_eq!(parse_disposition("ARCHIVE"), Some("archive"));
+    assert_eq!(parse_disposition("delete"), None);
+}
+
+#[test]
+fn test_plan_auto_forwarding() {
+    let addresses = vec![
+        address("backup@example.org", "accepted"),
+        address("new@example.org", "pending"),
+    ];
+
+    let plan = plan_auto_forwarding(
+        &disabled(),
+        &addresses,
+        true,
+        Some("Backup@Example.org"),
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        plan,
+        AutoForwarding {
+            enabled: true,
+            email_address: Some("Backup@Example.org".to_string()),
+            disposition: Some("leaveInInbox".to_string()),
+        }
+    );
+
+    // Only confirmed addresses can be forwarded to
+    assert!(
+        plan_auto_forwarding(&disabled(), &addresses, true, Some("new@example.org"), None)
+            .unwrap_err()
+            .contains("not a verified forwarding address")
+    );
+    assert!(
+        plan_auto_forwarding(&disabled(), &addresses, true, Some("x@example.org"), None).is_err()
+    );
+    assert!(plan_auto_forwarding(&disabled(), &addresses, true, None, None).is_err());
+    assert!(plan_auto_forwarding(
+        &disabled(),
+        &addresses,
+        true,
+        Some("backup@example.org"),
+        Some("delete")
+    )
+    .is_err());
+
+    // The earlier address and disposition are kept unless others are given
+    let current = AutoForwarding {
+        enabled: false,
+        email_address: Some("backup@example.org".to_string()),
+        disposition: Some("archive".to_string()),
+    };
+    let plan = plan_auto_forwarding(&current, &addresses, true, None, None).unwrap();
+    assert_eq!(plan.email_address.as_deref(), Some("backup@example.org"));
+    assert_eq!(plan.disposition.as_deref(), Some("archive"));
+    let plan = plan_auto_forwarding(&current, &addresses, true, None, Some("trash")).unwrap();
+    assert_eq!(plan.disposition.as_deref(), Some("trash"));
+
+    assert_eq!(
+        plan_auto_forwarding(&current, &addresses, false, None, None).unwrap(),
+        disabled()
+    );
+}
+
+#[tokio::test]
+async fn test_forwarding_tools() {
+    let dir = tempfile::tempdir().unwrap();
+    let settings = dir.path().join("gmail/v1/users/me/settings");
+    std::fs::create_dir_all(&settings).unwrap();
+    std::fs::write(
+        settings.join("forwardingAddresses.json"),
+        json!({
+            "forwardingAddresses": [
+                { "forwardingEmail": "backup@example.org", "verificationStatus": "accepted" },
+                { "forwardingEmail": "new@example.org", "verificationStatus": "pending" }
+            ]
+        })
+        .to_string(),
+    )
+    .unwrap();
+    std::fs::write(
+        settings.join("autoForwarding.json"),
+        json!({ "enabled": false }).to_string(),
+    )
+    .unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    let result = call_tool(&client, "list_forwarding_addresses", json!({}))
+        .await
+        .unwrap();
+    assert_eq!(result["mailbox"], "me");
+    assert_eq!(
+        result["forwarding_addresses"][0]["forwardingEmail"],
+        "backup@example.org"
+    );
+    assert_eq!(result["auto_forwarding"], json!({ "enabled": false }));
+
+    // Without confirm nothing changes
+    let result = call_tool(
+        &client,
+        "set_auto_forwarding",
+        json!({ "enabled": true, "email_address": "backup@example.org", "disposition": "archive" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["applied"], false);
+    assert_eq!(result["current"], json!({ "enabled": false }));
+    assert_eq!(
+        result["proposed"],
+        json!({ "enabled": true, "emailAddress": "backup@example.org", "disposition": "archive" })
+    );
+
+    let result = call_tool(
+        &client,
+        "set_auto_forwarding",
+        json!({
+            "enabled": true,
+            "email_address": "backup@example.org",
+            "disposition": "archive",
+            "confirm": true
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["applied"], true);
+    assert_eq!(result["proposed"]["emailAddress"], "backup@example.org");
+
+    assert!(call_tool(
+        &client,
+        "set_auto_forwarding",
+        json!({ "enabled": true, "email_address": "new@example.org", "confirm": true }),
+    )
+    .await
+    .is_err());
+}
@@ -140,6 +140,8 @@ mod tests {
             thread_id: None,
             in_reply_to: None,
             references: None,
+            priority: None,
+            read_receipt_to: None,
         };
         
         // Test the function
@@ -179,6 +181,8 @@ mod tests {
             thread_id: Some("thread123".to_string()),
             in_reply_to: Some("message123".to_string()),
             references: Some("reference123".to_string()),
+            priority: None,
+            read_receipt_to: None,
         };
         
         // Test the function
@@ -209,6 +213,8 @@ mod tests {
             thread_id: None,
             in_reply_to: None,
             references: None,
+            priority: None,
+            read_receipt_to: None,
         };
         
         // Test the function
@@ -241,6 +247,7 @@ mod tests {
             snippet: Some("This is a test message...".to_string()),
             body_text: Some("This is the message body.".to_string()),
             body_html: Some("<html><body>This is the HTML message body.</body></html>".to_string()),
+            ..Default::default()
         };
         
         // Setup expectations
@@ -302,7 +309,7 @@ mod tests {
                 date: Some("2025-01-01T12:00:00Z".to_string()),
                 snippet: Some("First message snippet...".to_string()),
                 body_text: Some("First message body.".to_string()),
-                body_html: None,
+                ..Default::default()
             },
             EmailMessage {
                 id: "msg2".to_string(),
@@ -313,7 +320,7 @@ mod tests {
                 date: Some("2025-01-02T12:00:00Z".to_string()),
                 snippet: Some("Second message snippet...".to_string()),
                 body_text: Some("Second message body.".to_string()),
-                body_html: None,
+                ..Default::default()
             },
         ];
         
@@ -348,7 +355,7 @@ mod tests {
                 date: Some("2025-01-03T12:00:00Z".to_string()),
                 snippet: Some("Important message snippet...".to_string()),
                 body_text: Some("Important message body.".to_string()),
-                body_html: None,
+                ..Default::default()
             },
         ];
         
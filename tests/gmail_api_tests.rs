@@ -5,58 +5,14 @@
 use base64;
 use chrono::{DateTime, TimeZone, Utc};
 use mockall::mock;
+use mcp_gmailcal::api::GmailApi;
 use mcp_gmailcal::errors::GmailApiError;
 use mcp_gmailcal::gmail_api::{DraftEmail, EmailMessage, GmailService};
+use mcp_gmailcal::test_util::MockGmailApi;
 use reqwest::Client;
 use serde_json::{json, Value};
-use std::sync::{Arc, Mutex};
 use std::time::{SystemTime, UNIX_EPOCH};
 
-// Define a proper interface for GmailClient that we can mock
-trait GmailClientInterface {
-    fn list_messages(&self, max_results: u32, query: Option<&str>) -> Result<Vec<EmailMessage>, GmailApiError>;
-    fn get_message_details(&self, message_id: &str) -> Result<EmailMessage, GmailApiError>;
-    fn create_draft(&self, draft: &DraftEmail) -> Result<String, GmailApiError>;
-    fn list_labels(&self) -> Result<Value, GmailApiError>;
-    fn check_connection(&self) -> Result<(String, u64), GmailApiError>;
-}
-
-// Wrapper for GmailClient that we can test against
-struct MockableGmailClient {
-    client: Arc<Mutex<dyn GmailClientInterface + Send + Sync>>,
-}
-
-impl MockableGmailClient {
-    fn new(client: Arc<Mutex<dyn GmailClientInterface + Send + Sync>>) -> Self {
-        Self { client }
-    }
-
-    fn list_messages(&self, max_results: u32, query: Option<&str>) -> Result<Vec<EmailMessage>, GmailApiError> {
-        let client = self.client.lock().unwrap();
-        client.list_messages(max_results, query)
-    }
-
-    fn get_message_details(&self, message_id: &str) -> Result<EmailMessage, GmailApiError> {
-        let client = self.client.lock().unwrap();
-        client.get_message_details(message_id)
-    }
-
-    fn create_draft(&self, draft: &DraftEmail) -> Result<String, GmailApiError> {
-        let client = self.client.lock().unwrap();
-        client.create_draft(draft)
-    }
-
-    fn list_labels(&self) -> Result<Value, GmailApiError> {
-        let client = self.client.lock().unwrap();
-        client.list_labels()
-    }
-
-    fn check_connection(&self) -> Result<(String, u64), GmailApiError> {
-        let client = self.client.lock().unwrap();
-        client.check_connection()
-    }
-}
-
 // Helper functions to create test data
 fn create_test_email(id: &str, subject: &str, from: &str, to: &str, body_text: &str, body_html: Option<&str>) -> EmailMessage {
     let now = SystemTime::now()
@@ -75,6 +31,7 @@ fn create_test_email(id: &str, subject: &str, from: &str, to: &str, body_text: &
         snippet: Some(format!("This is a snippet for email {}", id)),
         body_text: Some(body_text.to_string()),
         body_html: body_html.map(|s| s.to_string()),
+        ..Default::default()
     }
 }
 
@@ -93,22 +50,6 @@ fn create_test_labels() -> Vec<(String, String)> {
     ]
 }
 
-fn labels_to_json(labels: &[(String, String)]) -> Value {
-    let labels_json: Vec<Value> = labels
-        .iter()
-        .map(|(id, name)| {
-            json!({
-                "id": id,
-                "name": name,
-            })
-        })
-        .collect();
-
-    json!({
-        "labels": labels_json
-    })
-}
-
 // Create sample message response in raw JSON format (similar to Gmail API response)
 fn create_raw_message_response(
     id: &str,
@@ -185,139 +126,34 @@ fn create_raw_message_response(
     })
 }
 
-// Mock implementation
-struct MockGmailClient {
-    emails: Vec<EmailMessage>,
-    labels: Vec<(String, String)>,
-    should_fail: bool,
-}
-
-impl MockGmailClient {
-    fn new() -> Self {
-        // Create sample emails
-        let emails = vec![
-            create_test_email(
-                "msg1",
-                "Important Meeting",
-                "sender@example.com",
-                "recipient@example.com",
-                "Let's meet tomorrow to discuss the project.",
-                Some("<html><body>Let's meet tomorrow to discuss the project.</body></html>"),
-            ),
-            create_test_email(
-                "msg2",
-                "Weekly Report",
-                "reports@example.com",
-                "recipient@example.com",
-                "Please find attached the weekly report.",
-                Some("<html><body>Please find attached the weekly report.</body></html>"),
-            ),
-            create_test_email(
-                "msg3",
-                "Action Required",
-                "system@example.com",
-                "recipient@example.com",
-                "Your action is required on the following items...",
-                None,
-            ),
-        ];
-        
-        let labels = create_test_labels();
-        
-        Self {
-            emails,
-            labels,
-            should_fail: false,
-        }
-    }
-    
-    fn with_failure(mut self) -> Self {
-        self.should_fail = true;
-        self
-    }
-}
-
-impl GmailClientInterface for MockGmailClient {
-    fn list_messages(&self, max_results: u32, query: Option<&str>) -> Result<Vec<EmailMessage>, GmailApiError> {
-        if self.should_fail {
-            return Err(GmailApiError::ApiError("Failed to list messages".to_string()));
-        }
-        
-        // Filter by query if provided
-        let filtered_emails = match query {
-            Some(q) => {
-                let q = q.to_lowercase();
-                self.emails.iter()
-                    .filter(|email| {
-                        let subject = email.subject.as_ref().map_or("", |s| s.as_str()).to_lowercase();
-                        let body = email.body_text.as_ref().map_or("", |s| s.as_str()).to_lowercase();
-                        subject.contains(&q) || body.contains(&q)
-                    })
-                    .cloned()
-                    .collect::<Vec<EmailMessage>>()
-            },
-            None => self.emails.clone(),
-        };
-        
-        // Apply max_results
-        let limited_emails = filtered_emails.into_iter()
-            .take(max_results as usize)
-            .collect();
-        
-        Ok(limited_emails)
-    }
-    
-    fn get_message_details(&self, message_id: &str) -> Result<EmailMessage, GmailApiError> {
-        if self.should_fail {
-            return Err(GmailApiError::ApiError("Failed to get message".to_string()));
-        }
-        
-        // Find the email with the given ID
-        let email = self.emails.iter()
-            .find(|email| email.id == message_id)
-            .cloned();
-        
-        match email {
-            Some(email) => Ok(email),
-            None => Err(GmailApiError::MessageRetrievalError(format!("Message {} not found", message_id))),
-        }
-    }
-    
-    fn create_draft(&self, draft: &DraftEmail) -> Result<String, GmailApiError> {
-        if self.should_fail {
-            return Err(GmailApiError::ApiError("Failed to create draft".to_string()));
-        }
-        
-        // Basic validation
-        if draft.to.is_empty() {
-            return Err(GmailApiError::MessageFormatError("Recipient cannot be empty".to_string()));
-        }
-        
-        if draft.subject.is_empty() {
-            return Err(GmailApiError::MessageFormatError("Subject cannot be empty".to_string()));
-        }
-        
-        // Generate a draft ID
-        let draft_id = format!("draft_{}", SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs());
-        
-        Ok(draft_id)
-    }
-    
-    fn list_labels(&self) -> Result<Value, GmailApiError> {
-        if self.should_fail {
-            return Err(GmailApiError::ApiError("Failed to list labels".to_string()));
-        }
-        
-        Ok(labels_to_json(&self.labels))
-    }
-    
-    fn check_connection(&self) -> Result<(String, u64), GmailApiError> {
-        if self.should_fail {
-            return Err(GmailApiError::AuthError("Authentication failed".to_string()));
-        }
-        
-        Ok(("test@example.com".to_string(), 1000))
-    }
+// Sample emails served by the mock Gmail API
+fn sample_emails() -> Vec<EmailMessage> {
+    vec![
+        create_test_email(
+            "msg1",
+            "Important Meeting",
+            "sender@example.com",
+            "recipient@example.com",
+            "Let's meet tomorrow to discuss the project.",
+            Some("<html><body>Let's meet tomorrow to discuss the project.</body></html>"),
+        ),
+        create_test_email(
+            "msg2",
+            "Weekly Report",
+            "reports@example.com",
+            "recipient@example.com",
+            "Please find attached the weekly report.",
+            Some("<html><body>Please find attached the weekly report.</body></html>"),
+        ),
+        create_test_email(
+            "msg3",
+            "Action Required",
+            "system@example.com",
+            "recipient@example.com",
+            "Your action is required on the following items...",
+            None,
+        ),
+    ]
 }
 
 #[cfg(test)]
@@ -325,48 +161,47 @@ mod comprehensive_gmail_tests {
     use super::*;
     
     // Helper to create a test client
-    fn create_test_client() -> MockableGmailClient {
-        let mock_client = MockGmailClient::new();
-        let client = Arc::new(Mutex::new(mock_client));
-        MockableGmailClient::new(client)
+    fn create_test_client() -> MockGmailApi {
+        MockGmailApi::new()
+            .with_messages(sample_emails())
+            .with_labels(create_test_labels())
+            .with_profile("test@example.com", 1000)
     }
     
-    // Helper to create a failing test client
-    fn create_failing_client() -> MockableGmailClient {
-        let mock_client = MockGmailClient::new().with_failure();
-        let client = Arc::new(Mutex::new(mock_client));
-        MockableGmailClient::new(client)
+    // Helper to create a test client whose calls all fail with the given error
+    fn create_failing_client(error: fn() -> GmailApiError) -> MockGmailApi {
+        create_test_client().failing_with(error)
     }
     
-    #[test]
-    fn test_list_messages_success() {
+    #[tokio::test]
+    async fn test_list_messages_success() {
         let client = create_test_client();
         
         // Test listing all messages
-        let result = client.list_messages(10, None);
+        let result = client.list_messages(10, None).await;
         assert!(result.is_ok());
         let messages = result.unwrap();
         assert_eq!(messages.len(), 3);
         
         // Test with max_results
-        let result = client.list_messages(2, None);
+        let result = client.list_messages(2, None).await;
         assert!(result.is_ok());
         let messages = result.unwrap();
         assert_eq!(messages.len(), 2);
         
         // Test with query
-        let result = client.list_messages(10, Some("report"));
+        let result = client.list_messages(10, Some("report")).await;
         assert!(result.is_ok());
         let messages = result.unwrap();
         assert_eq!(messages.len(), 1);
         assert!(messages[0].subject.as_ref().unwrap().contains("Report"));
     }
     
-    #[test]
-    fn test_list_messages_failure() {
-        let client = create_failing_client();
+    #[tokio::test]
+    async fn test_list_messages_failure() {
+        let client = create_failing_client(|| GmailApiError::ApiError("Failed to list messages".to_string()));
         
-        let result = client.list_messages(10, None);
+        let result = client.list_messages(10, None).await;
         assert!(result.is_err());
         match result {
             Err(GmailApiError::ApiError(msg)) => {
@@ -376,12 +211,12 @@ mod comprehensive_gmail_tests {
         }
     }
     
-    #[test]
-    fn test_get_message_details_success() {
+    #[tokio::test]
+    async fn test_get_message_details_success() {
         let client = create_test_client();
         
         // Test getting an existing message
-        let result = client.get_message_details("msg1");
+        let result = client.get_message_details("msg1").await;
         assert!(result.is_ok());
         let message = result.unwrap();
         
@@ -394,12 +229,12 @@ mod comprehensive_gmail_tests {
         assert!(message.body_html.is_some());
     }
     
-    #[test]
-    fn test_get_message_details_not_found() {
+    #[tokio::test]
+    async fn test_get_message_details_not_found() {
         let client = create_test_client();
         
         // Test getting a non-existent message
-        let result = client.get_message_details("nonexistent");
+        let result = client.get_message_details("nonexistent").await;
         assert!(result.is_err());
         match result {
             Err(GmailApiError::MessageRetrievalError(msg)) => {
@@ -409,11 +244,11 @@ mod comprehensive_gmail_tests {
         }
     }
     
-    #[test]
-    fn test_get_message_details_failure() {
-        let client = create_failing_client();
+    #[tokio::test]
+    async fn test_get_message_details_failure() {
+        let client = create_failing_client(|| GmailApiError::ApiError("Failed to get message".to_string()));
         
-        let result = client.get_message_details("msg1");
+        let result = client.get_message_details("msg1").await;
         assert!(result.is_err());
         match result {
             Err(GmailApiError::ApiError(msg)) => {
@@ -423,8 +258,8 @@ mod comprehensive_gmail_tests {
         }
     }
     
-    #[test]
-    fn test_create_draft_success() {
+    #[tokio::test]
+    async fn test_create_draft_success() {
         let client = create_test_client();
         
         // Create a valid draft
@@ -437,16 +272,18 @@ mod comprehensive_gmail_tests {
             thread_id: None,
             in_reply_to: None,
             references: None,
+            priority: None,
+            read_receipt_to: None,
         };
         
-        let result = client.create_draft(&draft);
+        let result = client.create_draft(&draft).await;
         assert!(result.is_ok());
         let draft_id = result.unwrap();
-        assert!(draft_id.starts_with("draft_"));
+        assert!(draft_id.starts_with("draft-"));
     }
     
-    #[test]
-    fn test_create_draft_with_optional_fields() {
+    #[tokio::test]
+    async fn test_create_draft_with_optional_fields() {
         let client = create_test_client();
         
         // Create a draft with optional fields
@@ -459,17 +296,19 @@ mod comprehensive_gmail_tests {
             thread_id: Some("thread123".to_string()),
             in_reply_to: Some("msg123".to_string()),
             references: Some("ref123".to_string()),
+            priority: None,
+            read_receipt_to: None,
         };
         
-        let result = client.create_draft(&draft);
+        let result = client.create_draft(&draft).await;
         assert!(result.is_ok());
     }
     
-    #[test]
-    fn test_create_draft_validation_failure() {
+    #[tokio::test]
+    async fn test_send_message_validation_failure() {
         let client = create_test_client();
         
-        // Test with empty recipient
+        // Drafts may be saved without a recipient, but sending requires one
         let invalid_draft = DraftEmail {
             to: "".to_string(),
             subject: "Test Draft".to_string(),
@@ -479,42 +318,26 @@ mod comprehensive_gmail_tests {
             thread_id: None,
             in_reply_to: None,
             references: None,
+            priority: None,
+            read_receipt_to: None,
         };
         
-        let result = client.create_draft(&invalid_draft);
-        assert!(result.is_err());
-        match result {
-            Err(GmailApiError::MessageFormatError(msg)) => {
-                assert!(msg.contains("Recipient cannot be empty"));
-            }
-            _ => panic!("Expected MessageFormatError"),
-        }
-        
-        // Test with empty subject
-        let invalid_draft = DraftEmail {
-            to: "recipient@example.com".to_string(),
-            subject: "".to_string(),
-            body: "This is a test draft email.".to_string(),
-            cc: None,
-            bcc: None,
-            thread_id: None,
-            in_reply_to: None,
-            references: None,
-        };
+        assert!(client.create_draft(&invalid_draft).await.is_ok());
         
-        let result = client.create_draft(&invalid_draft);
+        let result = client.send_message(&invalid_draft).await;
         assert!(result.is_err());
         match result {
             Err(GmailApiError::MessageFormatError(msg)) => {
-                assert!(msg.contains("Subject cannot be empty"));
+                assert!(msg.contains("Recipient address required"));
             }
             _ => panic!("Expected MessageFormatError"),
         }
+        assert!(client.sent().is_empty());
     }
     
-    #[test]
-    fn test_create_draft_failure() {
-        let client = create_failing_client();
+    #[tokio::test]
+    async fn test_create_draft_failure() {
+        let client = create_failing_client(|| GmailApiError::ApiError("Failed to create draft".to_string()));
         
         let draft = DraftEmail {
             to: "recipient@example.com".to_string(),
@@ -525,9 +348,11 @@ mod comprehensive_gmail_tests {
             thread_id: None,
             in_reply_to: None,
             references: None,
+            priority: None,
+            read_receipt_to: None,
         };
         
-        let result = client.create_draft(&draft);
+        let result = client.create_draft(&draft).await;
         assert!(result.is_err());
         match result {
             Err(GmailApiError::ApiError(msg)) => {
@@ -537,14 +362,15 @@ mod comprehensive_gmail_tests {
         }
     }
     
-    #[test]
-    fn test_list_labels_success() {
+    #[tokio::test]
+    async fn test_list_labels_success() {
         let client = create_test_client();
         
-        let result = client.list_labels();
+        let result = client.list_labels().await;
         assert!(result.is_ok());
         
-        let labels = result.unwrap();
+        // Labels are returned as the raw API JSON
+        let labels: Value = serde_json::from_str(&result.unwrap()).unwrap();
         assert!(labels["labels"].is_array());
         
         let labels_array = labels["labels"].as_array().unwrap();
@@ -558,11 +384,11 @@ mod comprehensive_gmail_tests {
         assert_eq!(sent_label["name"], "Sent");
     }
     
-    #[test]
-    fn test_list_labels_failure() {
-        let client = create_failing_client();
+    #[tokio::test]
+    async fn test_list_labels_failure() {
+        let client = create_failing_client(|| GmailApiError::ApiError("Failed to list labels".to_string()));
         
-        let result = client.list_labels();
+        let result = client.list_labels().await;
         assert!(result.is_err());
         match result {
             Err(GmailApiError::ApiError(msg)) => {
@@ -572,11 +398,11 @@ mod comprehensive_gmail_tests {
         }
     }
     
-    #[test]
-    fn test_check_connection_success() {
+    #[tokio::test]
+    async fn test_check_connection_success() {
         let client = create_test_client();
         
-        let result = client.check_connection();
+        let result = client.check_connection().await;
         assert!(result.is_ok());
         
         let (email, count) = result.unwrap();
@@ -584,11 +410,11 @@ mod comprehensive_gmail_tests {
         assert_eq!(count, 1000);
     }
     
-    #[test]
-    fn test_check_connection_failure() {
-        let client = create_failing_client();
+    #[tokio::test]
+    async fn test_check_connection_failure() {
+        let client = create_failing_client(|| GmailApiError::AuthError("Authentication failed".to_string()));
         
-        let result = client.check_connection();
+        let result = client.check_connection().await;
         assert!(result.is_err());
         match result {
             Err(GmailApiError::AuthError(msg)) => {
@@ -638,6 +464,8 @@ mod comprehensive_gmail_tests {
             thread_id: None,
             in_reply_to: None,
             references: None,
+            priority: None,
+            read_receipt_to: None,
         };
         
         // Create expected MIME format
@@ -714,8 +542,8 @@ mod comprehensive_gmail_tests {
         assert!(special_chars_email.body_html.unwrap().contains("🎉"));
     }
     
-    #[test]
-    fn test_message_thread_handling() {
+    #[tokio::test]
+    async fn test_message_thread_handling() {
         // Test creating messages in the same thread
         let draft1 = DraftEmail {
             to: "recipient@example.com".to_string(),
@@ -726,10 +554,12 @@ mod comprehensive_gmail_tests {
             thread_id: None, // Not part of a thread yet
             in_reply_to: None,
             references: None,
+            priority: None,
+            read_receipt_to: None,
         };
         
         let client = create_test_client();
-        let result1 = client.create_draft(&draft1);
+        let result1 = client.create_draft(&draft1).await;
         assert!(result1.is_ok());
         
         // Create a reply in the same thread
@@ -742,9 +572,11 @@ mod comprehensive_gmail_tests {
             thread_id: Some("thread123".to_string()), // Part of a thread
             in_reply_to: Some("msg123".to_string()), // References original message
             references: Some("msg123".to_string()), // References for threading
+            priority: None,
+            read_receipt_to: None,
         };
         
-        let result2 = client.create_draft(&draft2);
+        let result2 = client.create_draft(&draft2).await;
         assert!(result2.is_ok());
     }
 }
\ No newline at end of file
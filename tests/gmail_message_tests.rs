@@ -130,7 +130,7 @@ async fn test_parse_simple_message() {
     env::set_var("OAUTH_TOKEN_URL", format!("{}/token", server.url()));
     
     // Create GmailService with mock config
-    let mut gmail_service = GmailService::new(&create_mock_config()).unwrap();
+    let gmail_service = GmailService::new(&create_mock_config()).unwrap();
     
     // Get message details
     let result = gmail_service.get_message_details(message_id).await;
@@ -182,7 +182,7 @@ async fn test_parse_multipart_message() {
     env::set_var("GMAIL_API_BASE_URL", server.url());
     
     // Create GmailService with mock config
-    let mut gmail_service = GmailService::new(&create_mock_config()).unwrap();
+    let gmail_service = GmailService::new(&create_mock_config()).unwrap();
     
     // Get message details
     let result = gmail_service.get_message_details(message_id).await;
@@ -241,7 +241,7 @@ async fn test_parse_malformed_message() {
     env::set_var("GMAIL_API_BASE_URL", server.url());
     
     // Create GmailService with mock config
-    let mut gmail_service = GmailService::new(&create_mock_config()).unwrap();
+    let gmail_service = GmailService::new(&create_mock_config()).unwrap();
     
     // Get message details - this should result in an error due to missing threadId
     let result = gmail_service.get_message_details(message_id).await;
@@ -322,7 +322,7 @@ async fn test_list_messages() {
     env::set_var("GMAIL_API_BASE_URL", server.url());
     
     // Create GmailService with mock config
-    let mut gmail_service = GmailService::new(&create_mock_config()).unwrap();
+    let gmail_service = GmailService::new(&create_mock_config()).unwrap();
     
     // List messages
     let result = gmail_service.list_messages(10, None).await;
@@ -389,7 +389,7 @@ async fn test_list_messages_with_query() {
     env::set_var("GMAIL_API_BASE_URL", server.url());
     
     // Create GmailService with mock config
-    let mut gmail_service = GmailService::new(&create_mock_config()).unwrap();
+    let gmail_service = GmailService::new(&create_mock_config()).unwrap();
     
     // List messages with query
     let result = gmail_service.list_messages(10, Some("important")).await;
@@ -428,7 +428,7 @@ async fn test_error_handling_not_found() {
     env::set_var("GMAIL_API_BASE_URL", server.url());
     
     // Create GmailService with mock config
-    let mut gmail_service = GmailService::new(&create_mock_config()).unwrap();
+    let gmail_service = GmailService::new(&create_mock_config()).unwrap();
     
     // Try to get a nonexistent message
     let result = gmail_service.get_message_details("nonexistent").await;
@@ -463,7 +463,7 @@ async fn test_error_handling_unauthorized() {
     env::set_var("GMAIL_API_BASE_URL", server.url());
     
     // Create GmailService with mock config
-    let mut gmail_service = GmailService::new(&create_mock_config()).unwrap();
+    let gmail_service = GmailService::new(&create_mock_config()).unwrap();
     
     // Try to get a message with an invalid token
     let result = gmail_service.get_message_details("msg1").await;
@@ -498,7 +498,7 @@ async fn test_error_handling_rate_limit() {
     env::set_var("GMAIL_API_BASE_URL", server.url());
     
     // Create GmailService with mock config
-    let mut gmail_service = GmailService::new(&create_mock_config()).unwrap();
+    let gmail_service = GmailService::new(&create_mock_config()).unwrap();
     
     // Try to get a message when rate limited
     let result = gmail_service.get_message_details("msg1").await;
@@ -533,7 +533,7 @@ async fn test_error_handling_server_error() {
     env::set_var("GMAIL_API_BASE_URL", server.url());
     
     // Create GmailService with mock config
-    let mut gmail_service = GmailService::new(&create_mock_config()).unwrap();
+    let gmail_service = GmailService::new(&create_mock_config()).unwrap();
     
     // Try to get a message when the server has an error
     let result = gmail_service.get_message_details("msg1").await;
@@ -563,7 +563,7 @@ async fn test_error_handling_network_error() {
     env::set_var("GMAIL_API_BASE_URL", invalid_url);
     
     // Create GmailService with mock config
-    let mut gmail_service = GmailService::new(&create_mock_config()).unwrap();
+    let gmail_service = GmailService::new(&create_mock_config()).unwrap();
     
     // Try to get a message with an invalid URL
     let result = gmail_service.get_message_details("msg1").await;
@@ -609,7 +609,7 @@ async fn test_special_characters_in_messages() {
     env::set_var("GMAIL_API_BASE_URL", server.url());
     
     // Create GmailService with mock config
-    let mut gmail_service = GmailService::new(&create_mock_config()).unwrap();
+    let gmail_service = GmailService::new(&create_mock_config()).unwrap();
     
     // Get message details
     let result = gmail_service.get_message_details(message_id).await;
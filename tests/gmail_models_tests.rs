@@ -0,0 +1,151 @@
+/// Gmail Models Tests Module
+///
+/// This module contains tests for the typed Gmail API resources, checking
+/// that message, thread, listing and history responses deserialize, and that
+/// headers and body parts are read from them as before.
+use mcp_gmailcal::gmail_models::{HistoryList, Message, MessageList, Thread};
+use serde_json::json;
+
+fn fixture<T: serde::de::DeserializeOwned>(path: &str) -> T {
+    let text = std::fs::read_to_string(format!(
+        "{}/fixtures/mock/gmail/v1/users/me/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        path
+    ))
+    .unwrap();
+    serde_json::from_str(&text).unwrap()
+}
+
+#[test]
+fn test_fixtures_deserialize() {
+    let message: Message = fixture("messages/mock-msg-002.json");
+    assert_eq!(message.id, "mock-msg-002");
+    assert_eq!(message.thread_id.as_deref(), Some("mock-thread-002"));
+    assert!(message.has_label("UNREAD"));
+    assert!(!message.has_label("IMPORTANT"));
+    assert_eq!(
+        message.header("Subject"),
+        Some("Your invoice #4821 is ready")
+    );
+
+    let payload = message.payload.as_ref().unwrap();
+    assert!(payload.is_multipart());
+    let leaves = payload.leaf_parts();
+    assert_eq!(leaves.len(), 2);
+    assert_eq!(leaves[1].mime_type(), "application/pdf");
+    assert!(leaves[1].body.as_ref().unwrap().attachment_id.is_some());
+
+    let list: MessageList = fixture("messages.json");
+    assert_eq!(list.messages.len(), 3);
+    assert_eq!(list.result_size_estimate, Some(3));
+    assert_eq!(list.next_page_token, None);
+
+    let thread: Thread = fixture("threads/mock-thread-001.json");
+    assert_eq!(thread.id, "mock-thread-001");
+    assert!(!thread.message_ids().is_empty());
+}
+
+#[test]
+fn test_headers_match_case_insensitively() {
+    let message: Message = serde_json::from_value(json!({
+        "id": "m1",
+        "payload": {
+            "headers": [
+                {"name": "MESSAGE-ID", "value": "<first@example.com>"},
+                {"name": "Message-Id", "value": "<second@example.com>"}
+            ]
+        }
+    }))
+    .unwrap();
+    assert_eq!(message.header("message-id"), Some("<first@example.com>"));
+    assert_eq!(message.header("Subject"), None);
+    assert_eq!(message.thread_id, None);
+    assert!(message.label_ids.is_empty());
+}
+
+#[test]
+fn test_header_without_value() {
+    let message: Message = serde_json::from_value(json!({
+        "id": "m1",
+        "payload": {
+            "headers": [
+                {"name": "Subject"},
+                {"name": "From", "value": "alice@example.com"}
+            ]
+        }
+    }))
+    .unwrap();
+    assert_eq!(message.header("Subject"), Some(""));
+    assert_eq!(message.header("From"), Some("alice@example.com"));
+}
+
+#[test]
+fn test_body_parts() {
+    let encode = |text: &str| base64::encode_config(text, base64::URL_SAFE);
+    let message: Message = serde_json::from_value(json!({
+        "id": "m1",
+        "payload": {
+            "mimeType": "multipart/signed",
+            "parts": [
+                {
+                    "mimeType": "multipart/related",
+                    "parts": [
+                        {
+                            "mimeType": "multipart/alternative",
+                            "parts": [
+                                {"mimeType": "text/plain", "body": {"size": 5, "data": encode("Hello")}},
+                                {"mimeType": "text/html", "body": {"size": 12, "data": encode("<p>Hello</p>")}}
+                            ]
+                        },
+                        {"mimeType": "image/png", "body": {"attachmentId": "a1", "size": 100}}
+                    ]
+                },
+                {"mimeType": "application/pkcs7-signature", "body": {"attachmentId": "s1", "size": 10}}
+            ]
+        }
+    }))
+    .unwrap();
+
+    let payload = message.payload.as_ref().unwrap();
+    let content = payload.signed_content();
+    assert_eq!(content.mime_type(), "multipart/related");
+    let leaves = content.leaf_parts();
+    let types: Vec<&str> = leaves.iter().map(|part| part.mime_type()).collect();
+    assert_eq!(types, ["text/plain", "text/html", "image/png"]);
+    assert_eq!(leaves[0].decoded_body(1024).as_deref(), Some("Hello"));
+    assert_eq!(leaves[2].decoded_body(1024), None);
+
+    // Unsigned parts are their own content
+    assert_eq!(leaves[1].signed_content(), leaves[1]);
+}
+
+#[test]
+fn test_numeric_ids_and_dates() {
+    let message: Message = serde_json::from_value(json!({
+        "id": "m1",
+        "historyId": 4321,
+        "internalDate": "1759600931000"
+    }))
+    .unwrap();
+    assert_eq!(message.history_id.as_deref(), Some("4321"));
+    assert_eq!(
+        message.received_at().unwrap().to_rfc3339(),
+        "2025-10-04T18:02:11+00:00"
+    );
+
+    let history: HistoryList = serde_json::from_value(json!({
+        "history": [{
+            "id": "5",
+            "messagesAdded": [{"message": {"id": "m2", "threadId": "t2"}}],
+            "messagesDeleted": [{"message": {"id": "m1"}}]
+        }, {
+            "id": "6"
+        }],
+        "historyId": 4400
+    }))
+    .unwrap();
+    assert_eq!(history.history_id.as_deref(), Some("4400"));
+    assert_eq!(history.history[0].messages_added[0].message.id, "m2");
+    assert_eq!(history.history[0].messages_deleted[0].message.id, "m1");
+    assert!(history.history[1].messages_added.is_empty());
+}
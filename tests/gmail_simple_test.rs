@@ -43,7 +43,7 @@ async fn test_check_connection() {
     env::set_var("GMAIL_API_BASE_URL", server.url());
     
     // Create a GmailService with our test config
-    let mut gmail_service = GmailService::new(&create_test_config()).unwrap();
+    let gmail_service = GmailService::new(&create_test_config()).unwrap();
     
     // Call the function to test
     let result = gmail_service.check_connection().await;
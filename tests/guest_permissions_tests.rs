@@ -0,0 +1,83 @@
+/// Guest Permissions Tests Module
+///
+/// This module contains tests for reading whether guests can modify an event,
+/// invite others and see the guest list, and for setting those permissions
+/// when creating events, against the mock server.
+use serde_json::{json, Value};
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn permissions(event: &Value) -> (Value, Value, Value) {
+    (
+        event["guests_can_modify"].clone(),
+        event["guests_can_invite_others"].clone(),
+        event["guests_can_see_other_guests"].clone(),
+    )
+}
+
+#[tokio::test]
+async fn test_guest_permissions_tools() {
+    let dir = tempfile::tempdir().unwrap();
+    let events = dir.path().join("calendar/v3/calendars/primary/events");
+    std::fs::create_dir_all(&events).unwrap();
+    let workshop = json!({
+        "id": "event-workshop",
+        "summary": "Planning workshop",
+        "start": { "dateTime": "2030-03-05T09:00:00Z" },
+        "end": { "dateTime": "2030-03-05T11:00:00Z" },
+        "guestsCanModify": true,
+        "guestsCanSeeOtherGuests": false
+    });
+    std::fs::write(events.join("event-workshop.json"), workshop.to_string()).unwrap();
+    let standup = json!({
+        "id": "event-standup",
+        "summary": "Standup",
+        "start": { "dateTime": "2030-03-05T12:00:00Z" },
+        "end": { "dateTime": "2030-03-05T12:15:00Z" }
+    });
+    std::fs::write(events.join("event-standup.json"), standup.to_string()).unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    let event = call_tool(
+        &client,
+        "get_event",
+        json!({ "event_id": "event-workshop" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        permissions(&event),
+        (json!(true), json!(true), json!(false))
+    );
+
+    // Calendar leaves out permissions with their default values
+    let event = call_tool(&client, "get_event", json!({ "event_id": "event-standup" }))
+        .await
+        .unwrap();
+    assert_eq!(
+        permissions(&event),
+        (json!(false), json!(true), json!(true))
+    );
+
+    // The mock echoes the event created, showing the permissions sent
+    let created = call_tool(
+        &client,
+        "create_event",
+        json!({
+            "summary": "Design review",
+            "start_time": "2030-03-06T10:00:00Z",
+            "end_time": "2030-03-06T11:00:00Z",
+            "attendees": ["alice@example.com", "bob@example.com"],
+            "guests_can_modify": true,
+            "guests_can_invite_others": false
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        permissions(&created),
+        (json!(true), json!(false), json!(true))
+    );
+}
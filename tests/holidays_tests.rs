@@ -0,0 +1,216 @@
+/// Holidays Tests Module
+///
+/// This module contains tests for finding holiday and birthday calendars and
+/// marking their events, and for the list_holiday_calendars,
+/// subscribe_calendar and daily_agenda tools against the mock server.
+use chrono::{TimeZone, Utc};
+use mcp_gmailcal::calendar_api::{CalendarEvent, CalendarInfo};
+use mcp_gmailcal::holidays::{
+    is_special_calendar, resolve_calendar, special_calendars, AgendaItemType, BIRTHDAY_CALENDAR_ID,
+};
+use serde_json::{json, Value};
+
+mod common;
+use common::{call_tool, mock_client};
+
+const UK_HOLIDAYS: &str = "en.uk#holiday@group.v.calendar.google.com";
+
+fn calendar(id: &str) -> CalendarInfo {
+    CalendarInfo {
+        id: id.to_string(),
+        summary: id.to_string(),
+        description: None,
+        primary: None,
+    }
+}
+
+fn event(event_type: Option<&str>) -> CalendarEvent {
+    let start_time = Utc.with_ymd_and_hms(2026, 12, 25, 0, 0, 0).unwrap();
+    CalendarEvent {
+        id: Some("event".to_string()),
+        summary: "Christmas Day".to_string(),
+        start_time,
+        end_time: start_time + chrono::Duration::days(1),
+        all_day: true,
+        event_type: event_type.map(|t| t.to_string()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_resolve_calendar() {
+    assert_eq!(resolve_calendar("uk").unwrap(), UK_HOLIDAYS);
+    assert_eq!(
+        resolve_calendar("new zealand").unwrap(),
+        "en.new_zealand#holiday@group.v.calendar.google.com"
+    );
+    assert_eq!(resolve_calendar("Birthdays").unwrap(), BIRTHDAY_CALENDAR_ID);
+    assert_eq!(
+        resolve_calendar("team@example.com").unwrap(),
+        "team@example.com"
+    );
+    assert!(resolve_calendar("Atlantis")
+        .unwrap_err()
+        .contains("Unknown calendar 'Atlantis'"));
+}
+
+#[test]
+fn test_agenda_item_type() {
+    assert_eq!(
+        AgendaItemType::of(UK_HOLIDAYS, &event(None)),
+        AgendaItemType::Holiday
+    );
+    assert_eq!(
+        AgendaItemType::of(BIRTHDAY_CALENDAR_ID, &event(None)),
+        AgendaItemType::Birthday
+    );
+    // Birthdays kept in the primary calendar have their own event type
+    assert_eq!(
+        AgendaItemType::of("primary", &event(Some("birthday"))),
+        AgendaItemType::Birthday
+    );
+    assert_eq!(
+        AgendaItemType::of("primary", &event(Some("default"))),
+        AgendaItemType::Event
+    );
+
+    assert!(is_special_calendar(UK_HOLIDAYS));
+    assert!(is_special_calendar(BIRTHDAY_CALENDAR_ID));
+    assert!(!is_special_calendar("team@example.com"));
+}
+
+#[test]
+fn test_special_calendars() {
+    let subscribed = [calendar("primary"), calendar(UK_HOLIDAYS)];
+    let all = special_calendars(&subscribed, None);
+    assert_eq!(all[0].calendar_id, BIRTHDAY_CALENDAR_ID);
+    assert!(!all[0].subscribed);
+    let uk = all
+        .iter()
+        .find(|c| c.region.as_deref() == Some("uk"))
+        .unwrap();
+    assert!(uk.subscribed);
+    assert_eq!(uk.name, "Holidays in United Kingdom");
+
+    let found = special_calendars(&subscribed, Some("zealand"));
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].region.as_deref(), Some("nz"));
+    assert_eq!(found[0].calendar_type, AgendaItemType::Holiday);
+    assert!(special_calendars(&subscribed, Some("atlantis")).is_empty());
+}
+
+#[tokio::test]
+async fn test_holiday_tools() {
+    std::env::set_var("USER_TIMEZONE", "Europe/London");
+    let dir = tempfile::tempdir().unwrap();
+    let calendar_v3 = dir.path().join("calendar/v3");
+    let write = |path: &str, contents: Value| {
+        let path = calendar_v3.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents.to_string()).unwrap();
+    };
+    write(
+        "users/me/calendarList.json",
+        json!({ "items": [
+            { "id": "primary", "summary": "Demo User", "primary": true },
+            { "id": UK_HOLIDAYS, "summary": "Holidays in United Kingdom" },
+            { "id": BIRTHDAY_CALENDAR_ID, "summary": "Birthdays" }
+        ] }),
+    );
+    write(
+        "users/me/calendarList.post.json",
+        json!({ "id": "en.german#holiday@group.v.calendar.google.com", "summary": "Holidays in Germany" }),
+    );
+    write(
+        "calendars/primary/events.json",
+        json!({ "items": [{
+            "id": "standup",
+            "summary": "Standup",
+            "start": { "dateTime": "2026-12-24T09:30:00Z" },
+            "end": { "dateTime": "2026-12-24T09:45:00Z" }
+        }] }),
+    );
+    write(
+        &format!("calendars/{}/events.json", UK_HOLIDAYS),
+        json!({ "items": [
+            {
+                "id": "christmas-eve",
+                "summary": "Christmas Eve",
+                "start": { "date": "2026-12-24" },
+                "end": { "date": "2026-12-25" }
+            },
+            {
+                "id": "christmas",
+                "summary": "Christmas Day",
+                "start": { "date": "2026-12-25" },
+                "end": { "date": "2026-12-26" }
+            }
+        ] }),
+    );
+    write(
+        &format!("calendars/{}/events.json", BIRTHDAY_CALENDAR_ID),
+        json!({ "items": [{
+            "id": "alice-birthday",
+            "summary": "Alice's birthday",
+            "eventType": "birthday",
+            "start": { "date": "2026-12-24" },
+            "end": { "date": "2026-12-25" }
+        }] }),
+    );
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    let listed = call_tool(&client, "list_holiday_calendars", json!({}))
+        .await
+        .unwrap();
+    let listed = listed.as_array().unwrap();
+    assert_eq!(listed[0]["type"], "birthday");
+    assert_eq!(listed[0]["subscribed"], true);
+    let germany = listed.iter().find(|c| c["region"] == "de").unwrap();
+    assert_eq!(germany["subscribed"], false);
+
+    let subscribed = call_tool(
+        &client,
+        "subscribe_calendar",
+        json!({ "calendar": "Germany" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        subscribed["id"],
+        "en.german#holiday@group.v.calendar.google.com"
+    );
+    assert_eq!(subscribed["summary"], "Holidays in Germany");
+
+    let error = call_tool(
+        &client,
+        "subscribe_calendar",
+        json!({ "calendar": "Atlantis" }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("Unknown calendar 'Atlantis'"));
+
+    // Holidays and birthdays join the agenda, all-day entries first
+    let agenda = call_tool(&client, "daily_agenda", json!({ "date": "2026-12-24" }))
+        .await
+        .unwrap();
+    let entries: Vec<(&str, &str)> = agenda["events"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| (e["type"].as_str().unwrap(), e["summary"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        entries,
+        vec![
+            ("holiday", "Christmas Eve"),
+            ("birthday", "Alice's birthday"),
+            ("event", "Standup"),
+        ]
+    );
+}
@@ -0,0 +1,212 @@
+/// HTML Sanitizer Tests Module
+///
+/// This module contains tests for sanitizing HTML email bodies: removing
+/// scripts, event handlers, unsafe links, remote CSS and tracking pixels
+/// while keeping the rest of the markup, and how get_email applies it.
+use mcp_gmailcal::utils::sanitize_html;
+use serde_json::{json, Value};
+
+mod common;
+use common::{call_tool, mock_client};
+
+const NEWSLETTER: &str = r#"<html><head><meta http-equiv="refresh" content="0;url=https://evil.example"><style>@import url("https://cdn.example/x.css"); body { background: url('https://t.example/open.gif') }</style></head><body onload="track()"><h1>Weekly news</h1><script>document.cookie</script><p>Read <a href="https://news.example/story" onclick="steal()" ping="https://t.example/click">the story</a>.</p><img src="https://news.example/banner.png" alt="Banner"><img src="https://t.example/open.gif" width="1" height="1"><img src="https://news.example/p.gif" style="display: none"><img src="https://sendgrid.net/wf/open?u=1"><iframe src="https://evil.example"></iframe></body></html>"#;
+
+#[test]
+fn test_sanitize_html_removes_active_content_and_trackers() {
+    let sanitized = sanitize_html(NEWSLETTER);
+
+    for removed in [
+        "<script",
+        "document.cookie",
+        "<meta",
+        "<iframe",
+        "onload",
+        "onclick",
+        "ping=",
+        "@import",
+        "t.example",
+        "p.gif",
+        "sendgrid.net",
+    ] {
+        assert!(
+            !sanitized.contains(removed),
+            "{} left in {}",
+            removed,
+            sanitized
+        );
+    }
+    assert!(sanitized.contains("<h1>Weekly news</h1>"));
+    assert!(sanitized.contains(r#"<a href="https://news.example/story">the story</a>"#));
+    assert!(sanitized.contains(r#"<img src="https://news.example/banner.png" alt="Banner">"#));
+    assert!(sanitized.contains("body { background: none }"));
+}
+
+#[test]
+fn test_sanitize_html_links() {
+    for unsafe_link in [
+        r#"<a href="javascript:alert(1)">x</a>"#,
+        r#"<a href=" JaVaScRiPt:alert(1)">x</a>"#,
+        r#"<a href="java&#x09;script:alert(1)">x</a>"#,
+        r#"<a href="&#106avascript:alert(1)">x</a>"#,
+        r#"<a href='vbscript:msgbox'>x</a>"#,
+        r#"<a href="data:text/html;base64,PHNjcmlwdD4=">x</a>"#,
+        r#"<a href="javascript&colon;alert(1)">x</a>"#,
+        r#"<a href="java&Tab;script&#58alert(1)">x</a>"#,
+        r#"<a href="javascript&unknown;alert(1)">x</a>"#,
+    ] {
+        assert_eq!(sanitize_html(unsafe_link), "<a>x</a>", "{}", unsafe_link);
+    }
+
+    for safe_link in [
+        r#"<a href="https://example.com/?a=1&amp;b=2">x</a>"#,
+        r#"<a href="mailto:alice@example.com">x</a>"#,
+        r#"<a href="/relative/path">x</a>"#,
+        r##"<a href="#section">x</a>"##,
+    ] {
+        assert_eq!(sanitize_html(safe_link), safe_link);
+    }
+
+    // Embedded images stay, other data URIs do not
+    let image = r#"<img src="data:image/png;base64,iVBORw0KGgo=" alt="Chart">"#;
+    assert_eq!(sanitize_html(image), image);
+    assert_eq!(
+        sanitize_html(r#"<iframe src="data:text/html,x"></iframe><embed src="x.swf" />"#),
+        ""
+    );
+}
+
+#[test]
+fn test_sanitize_html_mutation_payloads() {
+    // SVG and MathML go with their content, so style sheet text in them
+    // can't turn into elements, and stray animations go too
+    assert_eq!(
+        sanitize_html("<p>Hi</p><svg><style><img src=x onerror=alert(1)></style></svg>"),
+        "<p>Hi</p>"
+    );
+    assert_eq!(
+        sanitize_html(r#"<svg><animate attributeName="href" values="javascript:alert(1)"/>"#),
+        ""
+    );
+    assert_eq!(
+        sanitize_html(r#"<math><mtext><img src=x onerror=alert(1)></mtext></math><b>ok</b>"#),
+        "<b>ok</b>"
+    );
+    assert_eq!(
+        sanitize_html(r#"<a href="/x">x</a><set attributeName="href" to="javascript:alert(1)"/>"#),
+        r#"<a href="/x">x</a>"#
+    );
+
+    // A `<` in a style sheet is escaped
+    assert_eq!(
+        sanitize_html("<style>p { content: '</p><img>' }</style>"),
+        r"<style>p { content: '\3c /p>\3c img>' }</style>"
+    );
+
+    // Only allowed attributes are kept
+    assert_eq!(
+        sanitize_html(
+            r#"<div class="a" formaction="javascript:x" data-x="1" srcdoc="<p>" aria-label="Hi" onfocus="x()">Hi</div>"#
+        ),
+        r#"<div class="a" aria-label="Hi">Hi</div>"#
+    );
+}
+
+#[test]
+fn test_sanitize_html_edge_cases() {
+    // Text and unknown markup pass through
+    assert_eq!(sanitize_html("a < b and c > d"), "a &lt; b and c > d");
+    assert_eq!(
+        sanitize_html("<p class=note>Hello <b>there</b></p>"),
+        r#"<p class="note">Hello <b>there</b></p>"#
+    );
+
+    // A quoted `>` does not end a tag
+    assert_eq!(
+        sanitize_html(r#"<div title="a > b" onmouseover="x()">Hi</div>"#),
+        r#"<div title="a > b">Hi</div>"#
+    );
+
+    // Comments, unfinished tags and scripts without an end are dropped
+    assert_eq!(
+        sanitize_html("<p>Hi<!--[if mso]><x><![endif]--></p>"),
+        "<p>Hi</p>"
+    );
+    assert_eq!(
+        sanitize_html("<p>Hi</p><img src=x onerror=alert(1)"),
+        "<p>Hi</p>"
+    );
+    assert_eq!(sanitize_html("<p>Hi</p><SCRIPT>alert(1)"), "<p>Hi</p>");
+
+    // Forms keep their text but lose their fields
+    assert_eq!(
+        sanitize_html(
+            r#"<form action="https://evil.example">Password <input type="password"></form>"#
+        ),
+        "Password "
+    );
+
+    // Style attributes lose remote images and expressions
+    assert_eq!(
+        sanitize_html(
+            r#"<td style="background-image: url(&quot;https://t.example/x.gif&quot;); width: expression(alert(1))">"#
+        ),
+        r#"<td style="background-image: none; width: none(alert(1))">"#
+    );
+
+    // Tiny images are only trackers when remote
+    let pixel = r#"<img src="cid:spacer" width="1" height="1">"#;
+    assert_eq!(sanitize_html(pixel), pixel);
+    assert_eq!(
+        sanitize_html(r#"<img src="//t.example/o.gif" style="width:1px;height:1px">"#),
+        ""
+    );
+}
+
+fn encode(text: &str) -> String {
+    base64::encode_config(text.as_bytes(), base64::URL_SAFE)
+}
+
+fn html_message() -> Value {
+    json!({
+        "id": "msg-news",
+        "threadId": "thread-news",
+        "labelIds": ["INBOX"],
+        "snippet": "Weekly news",
+        "payload": {
+            "mimeType": "multipart/alternative",
+            "headers": [
+                { "name": "From", "value": "News <news@news.example>" },
+                { "name": "Subject", "value": "Weekly news" }
+            ],
+            "parts": [
+                { "mimeType": "text/plain", "body": { "data": encode("Weekly news") } },
+                { "mimeType": "text/html", "body": { "data": encode(NEWSLETTER) } }
+            ]
+        }
+    })
+}
+
+#[tokio::test]
+async fn test_get_email_sanitizes_html() {
+    let dir = tempfile::tempdir().unwrap();
+    let messages = dir.path().join("gmail/v1/users/me/messages");
+    std::fs::create_dir_all(&messages).unwrap();
+    std::fs::write(messages.join("msg-news.json"), html_message().to_string()).unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    let email = call_tool(&client, "get_email", json!({ "message_id": "msg-news" }))
+        .await
+        .unwrap();
+    let html = email["body_html"].as_str().unwrap();
+    assert_eq!(html, sanitize_html(NEWSLETTER));
+    assert!(!html.contains("<script"));
+
+    // Sanitizing can be turned off
+    std::env::set_var("SANITIZE_HTML", "false");
+    let email = call_tool(&client, "get_email", json!({ "message_id": "msg-news" }))
+        .await
+        .unwrap();
+    assert_eq!(email["body_html"], NEWSLETTER);
+    std::env::remove_var("SANITIZE_HTML");
+}
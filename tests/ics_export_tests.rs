@@ -0,0 +1,239 @@
+/// ICS Export Tests Module
+///
+/// This module contains tests for writing an agenda as an iCalendar file,
+/// for the `cal export` command line, and for the export_agenda_ics tool
+/// against the mock server.
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use clap::Parser;
+use mcp_gmailcal::calendar_api::{Attendee, CalendarEvent, EventOrganizer, WorkingLocation};
+use mcp_gmailcal::cli::{CalCommands, Cli, Commands};
+use mcp_gmailcal::ics::{filter_agenda, to_ics, write_ics};
+use serde_json::json;
+use std::path::PathBuf;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn event(summary: &str, start_time: DateTime<Utc>) -> CalendarEvent {
+    CalendarEvent {
+        id: Some(summary.to_lowercase().replace(' ', "-")),
+        summary: summary.to_string(),
+        start_time,
+        end_time: start_time + Duration::hours(1),
+        ..Default::default()
+    }
+}
+
+fn range() -> (DateTime<Utc>, DateTime<Utc>) {
+    let start = Utc.with_ymd_and_hms(2030, 3, 4, 0, 0, 0).unwrap();
+    (start, start + Duration::days(7))
+}
+
+#[test]
+fn test_filter_agenda() {
+    let start = range().0;
+    let mut planning = event("Sprint planning", start + Duration::hours(10));
+    planning.location = Some("Room 4".to_string());
+    let mut review = event("Design review", start + Duration::days(1));
+    review.description = Some("Sprint demo and review".to_string());
+    let lunch = event("Lunch", start + Duration::days(2));
+    let late = event("Sprint retro", start + Duration::days(10));
+    let mut office = event("Office", start);
+    office.working_location = Some(WorkingLocation {
+        location_type: "officeLocation".to_string(),
+        label: None,
+    });
+    let events = vec![planning, review, lunch, late, office];
+
+    let summaries = |events: Vec<CalendarEvent>| -> Vec<String> {
+        events.into_iter().map(|e| e.summary).collect()
+    };
+    assert_eq!(
+        summaries(filter_agenda(events.clone(), range(), None)),
+        vec!["Sprint planning", "Design review", "Lunch"]
+    );
+    assert_eq!(
+        summaries(filter_agenda(events.clone(), range(), Some("sprint"))),
+        vec!["Sprint planning", "Design review"]
+    );
+    assert_eq!(
+        summaries(filter_agenda(events, range(), Some("SPRINT room"))),
+        vec!["Sprint planning"]
+    );
+}
+
+#[test]
+fn test_to_ics() {
+    let start = Utc.with_ymd_and_hms(2030, 3, 5, 9, 30, 0).unwrap();
+    let mut meeting = event("Budget, Q2; final", start);
+    meeting.description = Some("Line one\nLine two".to_string());
+    meeting.transparency = Some("transparent".to_string());
+    meeting.organizer = Some(EventOrganizer {
+        email: "alice@example.com".to_string(),
+        display_name: Some("Alice, Finance".to_string()),
+        self_: None,
+    });
+    meeting.attendees = vec![
+        Attendee {
+            email: "bob@example.com".to_string(),
+            display_name: None,
+            response_status: Some("accepted".to_string()),
+            optional: None,
+            time_zone: None,
+            self_: None,
+        },
+        Attendee {
+            email: "carol@example.com".to_string(),
+            display_name: None,
+            response_status: None,
+            optional: None,
+            time_zone: None,
+            self_: None,
+        },
+    ];
+    let mut holiday = event(
+        "Bank holiday",
+        Utc.with_ymd_and_hms(2030, 3, 6, 0, 0, 0).unwrap(),
+    );
+    holiday.all_day = true;
+    holiday.end_time = holiday.start_time + Duration::days(1);
+
+    let now = Utc.with_ymd_and_hms(2030, 3, 1, 12, 0, 0).unwrap();
+    let ics = to_ics(&[meeting, holiday], "primary", now);
+
+    assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+    assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    assert!(ics.contains("X-WR-CALNAME:primary\r\n"));
+    assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+    assert!(ics.contains("DTSTAMP:20300301T120000Z\r\n"));
+    assert!(ics.contains("DTSTART:20300305T093000Z\r\nDTEND:20300305T103000Z\r\n"));
+    assert!(ics.contains("SUMMARY:Budget\\, Q2\\; final\r\n"));
+    assert!(ics.contains("DESCRIPTION:Line one\\nLine two\r\n"));
+    assert!(ics.contains("TRANSP:TRANSPARENT\r\n"));
+    assert!(ics.contains("ORGANIZER;CN=\"Alice, Finance\":mailto:alice@example.com\r\n"));
+    assert!(ics.contains("ATTENDEE;PARTSTAT=ACCEPTED:mailto:bob@example.com\r\n"));
+    assert!(ics.contains("ATTENDEE;PARTSTAT=NEEDS-ACTION:mailto:carol@example.com\r\n"));
+    assert!(ics.contains("DTSTART;VALUE=DATE:20300306\r\nDTEND;VALUE=DATE:20300307\r\n"));
+}
+
+#[test]
+fn test_to_ics_folds_long_lines() {
+    let mut meeting = event("Offsite", range().0);
+    meeting.description = Some("é".repeat(100));
+    let ics = to_ics(&[meeting], "primary", range().0);
+
+    for line in ics.split("\r\n") {
+        assert!(line.len() <= 75, "line too long: {:?}", line);
+    }
+    let unfolded = ics.replace("\r\n ", "");
+    assert!(unfolded.contains(&format!("DESCRIPTION:{}\r\n", "é".repeat(100))));
+}
+
+#[test]
+fn test_write_ics_replaces_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("feeds/agenda.ics");
+    write_ics(&path, "first").unwrap();
+    write_ics(&path, "second").unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "second");
+    assert!(!dir.path().join("feeds/agenda.ics.partial").exists());
+}
+
+#[test]
+fn test_cal_export_command() {
+    let cli = Cli::try_parse_from([
+        "gmail-mcp",
+        "cal",
+        "export",
+        "--query",
+        "standup",
+        "--output",
+        "team.ics",
+    ])
+    .unwrap();
+    assert_eq!(
+        cli.command,
+        Some(Commands::Cal {
+            command: CalCommands::Export {
+                calendar_id: "primary".to_string(),
+                days: 30,
+                query: Some("standup".to_string()),
+                output: Some(PathBuf::from("team.ics")),
+            }
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_export_agenda_ics_tool() {
+    std::env::set_var("USER_TIMEZONE", "UTC");
+    let export_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("ICS_EXPORT_DIR", export_dir.path());
+    let dir = tempfile::tempdir().unwrap();
+    let events = dir.path().join("calendar/v3/calendars/primary/events.json");
+    std::fs::create_dir_all(events.parent().unwrap()).unwrap();
+    std::fs::write(
+        events,
+        json!({ "items": [
+            {
+                "id": "standup",
+                "summary": "Team standup",
+                "start": { "dateTime": "2030-03-05T09:30:00Z" },
+                "end": { "dateTime": "2030-03-05T09:45:00Z" }
+            },
+            {
+                "id": "dentist",
+                "summary": "Dentist",
+                "start": { "dateTime": "2030-03-06T15:00:00Z" },
+                "end": { "dateTime": "2030-03-06T16:00:00Z" }
+            },
+            {
+                "id": "later-standup",
+                "summary": "Team standup",
+                "start": { "dateTime": "2030-05-05T09:30:00Z" },
+                "end": { "dateTime": "2030-05-05T09:45:00Z" }
+            }
+        ] })
+        .to_string(),
+    )
+    .unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    let exported = call_tool(
+        &client,
+        "export_agenda_ics",
+        json!({
+            "time_min": "2030-03-04T00:00:00Z",
+            "time_max": "2030-03-11T00:00:00Z",
+            "query": "standup",
+            "filename": "../team-standup"
+        }),
+    )
+    .await
+    .unwrap();
+    let path = export_dir.path().join("team-standup.ics");
+    assert_eq!(exported["path"], path.display().to_string());
+    assert_eq!(exported["events"], 1);
+
+    let ics = std::fs::read_to_string(&path).unwrap();
+    assert!(ics.contains("UID:standup@gmailcal-mcp-rs\r\n"));
+    assert!(!ics.contains("dentist"));
+    assert!(!ics.contains("later-standup"));
+
+    let error = call_tool(
+        &client,
+        "export_agenda_ics",
+        json!({
+            "time_min": "2030-03-11T00:00:00Z",
+            "time_max": "2030-03-04T00:00:00Z"
+        }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("Invalid time_max: must be after time_min"));
+}
@@ -0,0 +1,205 @@
+/// Idempotency Tests Module
+///
+/// This module contains tests for idempotency keys on write tools: saving the
+/// result of the first call with a key, replaying it for repeats, and freeing
+/// keys whose calls fail.
+use mcp_gmailcal::errors::IdempotencyError;
+use mcp_gmailcal::idempotency::{Claim, IdempotencyStore};
+use serde_json::json;
+use std::time::Duration;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn run(claim: Claim<'_>, result: &str) -> String {
+    match claim {
+        Claim::Run(guard) => guard.finish(result.to_string()),
+        Claim::Replay(saved) => panic!("expected a new key, got saved result {}", saved),
+    }
+}
+
+fn replayed(claim: Claim<'_>) -> String {
+    match claim {
+        Claim::Replay(saved) => saved,
+        Claim::Run(_) => panic!("expected a saved result"),
+    }
+}
+
+#[test]
+fn test_repeats_replay_the_saved_result() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("idempotency.json");
+    let store = IdempotencyStore::new(path.clone(), Duration::from_secs(60));
+
+    assert_eq!(
+        run(
+            store.claim("create_event", "k1", &json!({})).unwrap(),
+            "first"
+        ),
+        "first"
+    );
+    assert_eq!(
+        replayed(store.claim("create_event", "k1", &json!({})).unwrap()),
+        "first"
+    );
+
+    // Keys are separate for each tool
+    run(
+        store.claim("create_task", "k1", &json!({})).unwrap(),
+        "task",
+    );
+    assert_eq!(
+        replayed(store.claim("create_event", "k1", &json!({})).unwrap()),
+        "first"
+    );
+
+    // Results outlive the store that saved them
+    let reopened = IdempotencyStore::new(path, Duration::from_secs(60));
+    assert_eq!(
+        replayed(reopened.claim("create_task", "k1", &json!({})).unwrap()),
+        "task"
+    );
+}
+
+#[test]
+fn test_running_and_failed_calls() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = IdempotencyStore::new(dir.path().join("idempotency.json"), Duration::from_secs(60));
+
+    let guard = store.claim("create_event", "k1", &json!({})).unwrap();
+    let error = store.claim("create_event", "k1", &json!({})).unwrap_err();
+    assert!(error.to_string().contains("still running"));
+
+    // A call that fails drops its guard, and a retry runs
+    drop(guard);
+    run(
+        store.claim("create_event", "k1", &json!({})).unwrap(),
+        "retried",
+    );
+    assert_eq!(
+        replayed(store.claim("create_event", "k1", &json!({})).unwrap()),
+        "retried"
+    );
+}
+
+#[test]
+fn test_reused_key_with_other_arguments_conflicts() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = IdempotencyStore::new(dir.path().join("idempotency.json"), Duration::from_secs(60));
+
+    let arguments = json!({ "summary": "Planning", "attendees": ["a@example.com"] });
+    run(
+        store.claim("create_event", "k1", &arguments).unwrap(),
+        "first",
+    );
+
+    // Key order does not matter
+    let reordered = json!({ "attendees": ["a@example.com"], "summary": "Planning" });
+    assert_eq!(
+        replayed(store.claim("create_event", "k1", &reordered).unwrap()),
+        "first"
+    );
+
+    let other = json!({ "summary": "Planning", "attendees": ["b@example.com"] });
+    let error = store.claim("create_event", "k1", &other).unwrap_err();
+    assert!(matches!(error, IdempotencyError::Conflict(..)));
+    assert!(error.to_string().contains("different arguments"));
+}
+
+#[test]
+fn test_results_expire() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = IdempotencyStore::new(dir.path().join("idempotency.json"), Duration::ZERO);
+
+    run(
+        store.claim("create_event", "k1", &json!({})).unwrap(),
+        "first",
+    );
+    run(
+        store.claim("create_event", "k1", &json!({})).unwrap(),
+        "second",
+    );
+
+    // An unreadable file holds no results
+    std::fs::write(store.path(), "not json").unwrap();
+    let store = IdempotencyStore::new(store.path().to_path_buf(), Duration::from_secs(60));
+    run(
+        store.claim("create_event", "k1", &json!({})).unwrap(),
+        "third",
+    );
+}
+
+#[tokio::test]
+async fn test_tools_replay_repeated_keys() {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("IDEMPOTENCY_FILE", dir.path().join("idempotency.json"));
+    let (_server, client) = mock_client(None).await;
+
+    let event = json!({
+        "summary": "Planning",
+        "start_time": "2030-03-05T10:00:00Z",
+        "end_time": "2030-03-05T11:00:00Z",
+        "idempotency_key": "planning-1"
+    });
+    let first = call_tool(&client, "create_event", event.clone())
+        .await
+        .unwrap();
+    let repeat = call_tool(&client, "create_event", event).await.unwrap();
+    assert_eq!(first["id"], repeat["id"]);
+
+    let other = call_tool(
+        &client,
+        "create_event",
+        json!({
+            "summary": "Planning",
+            "start_time": "2030-03-05T10:00:00Z",
+            "end_time": "2030-03-05T11:00:00Z",
+            "idempotency_key": "planning-2"
+        }),
+    )
+    .await
+    .unwrap();
+    assert_ne!(first["id"], other["id"]);
+
+    let error = call_tool(
+        &client,
+        "create_event",
+        json!({
+            "summary": "Planning",
+            "start_time": "2030-03-06T10:00:00Z",
+            "end_time": "2030-03-06T11:00:00Z",
+            "idempotency_key": "planning-1"
+        }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("different arguments"));
+
+    // Without a key every call does the work
+    let draft = json!({ "to": "alice@example.com", "subject": "Hi", "body": "Hello" });
+    let first = call_tool(&client, "create_draft_email", draft.clone())
+        .await
+        .unwrap();
+    let second = call_tool(&client, "create_draft_email", draft)
+        .await
+        .unwrap();
+    assert_ne!(first["draft_id"], second["draft_id"]);
+
+    let error = call_tool(
+        &client,
+        "create_task",
+        json!({ "title": "Follow up", "idempotency_key": " " }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("Invalid idempotency_key"));
+    std::env::remove_var("IDEMPOTENCY_FILE");
+}
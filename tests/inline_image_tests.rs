@@ -0,0 +1,126 @@
+/// Inline Image Tests Module
+///
+/// This module contains tests for resolving `cid:` references in HTML bodies
+/// to the image parts of a message, and for how get_email reports them
+/// against the mock server.
+use mcp_gmailcal::attachments::resolve_inline_images;
+use serde_json::{json, Value};
+
+mod common;
+use common::{call_tool, mock_client};
+
+const HTML: &str = r#"<p>Hi!</p><img src="cid:logo@example.com" alt="Logo"><img src="cid:chart.png@01D9"><img src="cid:logo@example.com">"#;
+
+// 1x1 transparent GIF
+const PIXEL: &[u8] = b"GIF89a\x01\x00\x01\x00\x80\x00\x00\x00\x00\x00\xff\xff\xff!\xf9\x04\x01\x00\x00\x00\x00,\x00\x00\x00\x00\x01\x00\x01\x00\x00\x02\x02D\x01\x00;";
+
+fn encode(data: &[u8]) -> String {
+    base64::encode_config(data, base64::URL_SAFE)
+}
+
+fn payload() -> Value {
+    json!({
+        "mimeType": "multipart/related",
+        "headers": [{ "name": "Subject", "value": "Our logo" }],
+        "parts": [
+            {
+                "mimeType": "multipart/alternative",
+                "parts": [
+                    { "mimeType": "text/plain", "body": { "data": encode(b"Hi!") } },
+                    { "mimeType": "text/html", "body": { "data": encode(HTML.as_bytes()) } }
+                ]
+            },
+            {
+                "mimeType": "image/gif",
+                "filename": "logo.gif",
+                "headers": [{ "name": "Content-ID", "value": "<logo@example.com>" }],
+                "body": { "size": PIXEL.len(), "data": encode(PIXEL) }
+            },
+            {
+                "mimeType": "image/png",
+                "filename": "chart.png",
+                "headers": [{ "name": "content-id", "value": "<chart.png@01D9>" }],
+                "body": { "size": 250000, "attachmentId": "att-chart" }
+            },
+            {
+                "mimeType": "image/png",
+                "filename": "unused.png",
+                "headers": [{ "name": "Content-ID", "value": "<unused@example.com>" }],
+                "body": { "size": 10, "attachmentId": "att-unused" }
+            }
+        ]
+    })
+}
+
+#[test]
+fn test_resolve_inline_images() {
+    let mut html = HTML.to_string();
+    let images = resolve_inline_images(&payload(), &mut html, 64 * 1024);
+
+    // Only the images the body refers to are listed
+    assert_eq!(images.len(), 2);
+    assert_eq!(images[0].content_id, "logo@example.com");
+    assert_eq!(images[0].filename.as_deref(), Some("logo.gif"));
+    assert!(images[0].embedded);
+    assert_eq!(images[0].attachment_id, None);
+    assert_eq!(images[1].content_id, "chart.png@01D9");
+    assert!(!images[1].embedded);
+    assert_eq!(images[1].attachment_id.as_deref(), Some("att-chart"));
+
+    // Every reference to the small image is embedded with standard base64
+    let uri = format!("data:image/gif;base64,{}", base64::encode(PIXEL));
+    assert_eq!(html.matches(&uri).count(), 2);
+    assert!(!html.contains("cid:logo@example.com"));
+    assert!(html.contains("cid:chart.png@01D9"));
+
+    // Images over the limit keep their references
+    let mut html = HTML.to_string();
+    let images = resolve_inline_images(&payload(), &mut html, 8);
+    assert!(images.iter().all(|image| !image.embedded));
+    assert_eq!(html, HTML);
+
+    let mut html = "<p>No images</p>".to_string();
+    assert!(resolve_inline_images(&payload(), &mut html, 64 * 1024).is_empty());
+}
+
+#[tokio::test]
+async fn test_get_email_inline_images() {
+    let dir = tempfile::tempdir().unwrap();
+    let messages = dir.path().join("gmail/v1/users/me/messages");
+    std::fs::create_dir_all(&messages).unwrap();
+    let message = json!({
+        "id": "msg-images",
+        "threadId": "thread-images",
+        "snippet": "Hi!",
+        "payload": payload()
+    });
+    std::fs::write(messages.join("msg-images.json"), message.to_string()).unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    // The bodies are found inside the multipart/related part
+    let email = call_tool(&client, "get_email", json!({ "message_id": "msg-images" }))
+        .await
+        .unwrap();
+    assert_eq!(email["body_text"], "Hi!");
+    let html = email["body_html"].as_str().unwrap();
+    assert!(html.contains("src=\"data:image/gif;base64,"));
+    assert!(html.contains("src=\"cid:chart.png@01D9\""));
+
+    let images = email["inline_images"].as_array().unwrap();
+    assert_eq!(images.len(), 2);
+    assert_eq!(images[0]["embedded"], true);
+    assert_eq!(images[1]["embedded"], false);
+    assert_eq!(images[1]["attachment_id"], "att-chart");
+    assert_eq!(images[1]["mime_type"], "image/png");
+    assert_eq!(images[1]["size"], 250000);
+
+    let email = call_tool(
+        &client,
+        "get_email",
+        json!({ "message_id": "mock-msg-001" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(email["inline_images"], json!([]));
+}
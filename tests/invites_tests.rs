@@ -0,0 +1,152 @@
+/// Invites Tests Module
+///
+/// This module contains tests for editing event attendee lists, and for the
+/// add_attendees and remove_attendees tools against the mock server.
+use mcp_gmailcal::invites::{add_to_attendees, parse_send_updates, remove_from_attendees};
+use serde_json::{json, Value};
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn attendees() -> Vec<Value> {
+    vec![
+        json!({ "email": "demo@example.com", "organizer": true, "responseStatus": "accepted" }),
+        json!({
+            "email": "Alice@example.com",
+            "responseStatus": "accepted",
+            "comment": "Will dial in",
+            "additionalGuests": 1
+        }),
+    ]
+}
+
+#[test]
+fn test_parse_send_updates() {
+    assert_eq!(parse_send_updates(None).unwrap(), "all");
+    assert_eq!(
+        parse_send_updates(Some("externalonly")).unwrap(),
+        "externalOnly"
+    );
+    assert_eq!(parse_send_updates(Some("none")).unwrap(), "none");
+    let error = parse_send_updates(Some("everyone")).unwrap_err();
+    assert!(error.contains("expected one of all, externalOnly, none"));
+}
+
+#[test]
+fn test_add_to_attendees() {
+    let emails = vec![
+        "alice@example.com".to_string(),
+        "bob@example.com".to_string(),
+    ];
+    let edit = add_to_attendees(&attendees(), &emails, true);
+    assert!(edit.changed());
+    assert_eq!(edit.added, vec!["bob@example.com"]);
+    assert_eq!(edit.already_invited, vec!["alice@example.com"]);
+    // Existing attendees keep what they had
+    assert_eq!(edit.attendees[..2], attendees()[..]);
+    assert_eq!(
+        edit.attendees[2],
+        json!({ "email": "bob@example.com", "optional": true })
+    );
+
+    let edit = add_to_attendees(&attendees(), &emails[..1], false);
+    assert!(!edit.changed());
+    assert_eq!(edit.attendees, attendees());
+}
+
+#[test]
+fn test_remove_from_attendees() {
+    let emails = vec![
+        "alice@example.com".to_string(),
+        "bob@example.com".to_string(),
+    ];
+    let edit = remove_from_attendees(&attendees(), &emails);
+    assert!(edit.changed());
+    assert_eq!(edit.removed, vec!["alice@example.com"]);
+    assert_eq!(edit.not_invited, vec!["bob@example.com"]);
+    assert_eq!(edit.attendees, attendees()[..1]);
+
+    let edit = remove_from_attendees(&attendees(), &emails[1..]);
+    assert!(!edit.changed());
+}
+
+#[tokio::test]
+async fn test_invite_tools() {
+    let dir = tempfile::tempdir().unwrap();
+    let events = dir.path().join("calendar/v3/calendars/primary/events");
+    std::fs::create_dir_all(&events).unwrap();
+    let event = json!({
+        "id": "event-invite",
+        "etag": "\"3381\"",
+        "summary": "Design review",
+        "start": { "dateTime": "2026-10-20T16:00:00Z" },
+        "end": { "dateTime": "2026-10-20T17:00:00Z" },
+        "attendees": attendees()
+    });
+    std::fs::write(events.join("event-invite.json"), event.to_string()).unwrap();
+    // The event as the patch leaves it
+    let mut patched = event.clone();
+    patched["attendees"]
+        .as_array_mut()
+        .unwrap()
+        .push(json!({ "email": "bob@example.com", "responseStatus": "needsAction" }));
+    std::fs::write(events.join("event-invite.patch.json"), patched.to_string()).unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    let update = call_tool(
+        &client,
+        "add_attendees",
+        json!({
+            "event_id": "event-invite",
+            "emails": ["Bob <bob@example.com>", "alice@example.com"],
+            "send_updates": "none"
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(update["updated"], true);
+    assert_eq!(update["send_updates"], "none");
+    assert_eq!(update["added"], json!(["bob@example.com"]));
+    assert_eq!(update["already_invited"], json!(["alice@example.com"]));
+    assert_eq!(update["attendees"].as_array().unwrap().len(), 3);
+
+    // Nothing to change, so the event is left alone and nobody is notified
+    let update = call_tool(
+        &client,
+        "remove_attendees",
+        json!({ "event_id": "event-invite", "emails": ["carol@example.com"] }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(update["updated"], false);
+    assert_eq!(update["send_updates"], "none");
+    assert_eq!(update["not_invited"], json!(["carol@example.com"]));
+    assert_eq!(update["attendees"].as_array().unwrap().len(), 2);
+
+    let error = call_tool(
+        &client,
+        "remove_attendees",
+        json!({ "event_id": "event-invite", "emails": ["alice@example.com"], "send_updates": "everyone" }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("Invalid send_updates 'everyone'"));
+
+    let error = call_tool(
+        &client,
+        "add_attendees",
+        json!({ "event_id": "event-invite", "emails": ["the whole team"] }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("'the whole team' is not an email address"));
+}
@@ -0,0 +1,153 @@
+/// Join Links Tests Module
+///
+/// This module contains tests for finding video call links in events'
+/// conference data, descriptions and locations, and for the join links of
+/// events read from the mock server.
+use mcp_gmailcal::calendar_api::{ConferenceData, ConferenceSolution, EntryPoint, JoinLink};
+use mcp_gmailcal::join_links::{find_join_links, meeting_provider};
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn link(provider: &str, url: &str, source: &str) -> JoinLink {
+    JoinLink {
+        provider: provider.to_string(),
+        url: url.to_string(),
+        source: source.to_string(),
+    }
+}
+
+#[test]
+fn test_meeting_provider() {
+    assert_eq!(
+        meeting_provider("https://meet.google.com/abc-defg-hij"),
+        Some("google_meet")
+    );
+    assert_eq!(
+        meeting_provider("https://us02web.zoom.us/j/81234567890?pwd=abc123"),
+        Some("zoom")
+    );
+    assert_eq!(
+        meeting_provider("https://acme.zoom.us/my/dana.smith"),
+        Some("zoom")
+    );
+    assert_eq!(
+        meeting_provider(
+            "https://teams.microsoft.com/l/meetup-join/19%3ameeting_abc%40thread.v2/0"
+        ),
+        Some("teams")
+    );
+    assert_eq!(
+        meeting_provider("https://acme.webex.com/acme/j.php?MTID=m1234"),
+        Some("webex")
+    );
+    assert_eq!(
+        meeting_provider("https://acme.webex.com/meet/dana.smith"),
+        Some("webex")
+    );
+
+    // Other pages of the same services are not meetings
+    assert_eq!(meeting_provider("https://zoom.us/download"), None);
+    assert_eq!(meeting_provider("https://meet.google.com/"), None);
+    assert_eq!(meeting_provider("https://www.microsoft.com/teams"), None);
+    assert_eq!(meeting_provider("https://notzoom.us/j/123"), None);
+    assert_eq!(meeting_provider("not a link"), None);
+}
+
+#[test]
+fn test_find_join_links() {
+    let conference = ConferenceData {
+        conference_solution: Some(ConferenceSolution {
+            name: "Google Meet".to_string(),
+            key: Some("hangoutsMeet".to_string()),
+        }),
+        entry_points: vec![
+            EntryPoint {
+                entry_point_type: "phone".to_string(),
+                uri: "tel:+1-555-0100".to_string(),
+                label: None,
+            },
+            EntryPoint {
+                entry_point_type: "video".to_string(),
+                uri: "https://meet.google.com/abc-defg-hij".to_string(),
+                label: None,
+            },
+        ],
+    };
+    let description = "Agenda in the doc.<br><a href=\"https://us02web.zoom.us/j/81234567890?pwd=abc&amp;uname=x\">Join Zoom Meeting</a><br>Backup: https://meet.google.com/abc-defg-hij. Get the app at https://zoom.us/download";
+    let location = "Room 4 / https://acme.webex.com/meet/dana.smith";
+
+    assert_eq!(
+        find_join_links(Some(&conference), Some(description), Some(location)),
+        vec![
+            link(
+                "google_meet",
+                "https://meet.google.com/abc-defg-hij",
+                "conference_data"
+            ),
+            link(
+                "zoom",
+                "https://us02web.zoom.us/j/81234567890?pwd=abc&uname=x",
+                "description"
+            ),
+            link(
+                "webex",
+                "https://acme.webex.com/meet/dana.smith",
+                "location"
+            ),
+        ]
+    );
+
+    // A conferencing add-on's link is kept under its name
+    let add_on = ConferenceData {
+        conference_solution: Some(ConferenceSolution {
+            name: "Jitsi".to_string(),
+            key: Some("addOn".to_string()),
+        }),
+        entry_points: vec![EntryPoint {
+            entry_point_type: "video".to_string(),
+            uri: "https://meet.jit.si/weekly-sync".to_string(),
+            label: None,
+        }],
+    };
+    assert_eq!(
+        find_join_links(Some(&add_on), None, None),
+        vec![link(
+            "Jitsi",
+            "https://meet.jit.si/weekly-sync",
+            "conference_data"
+        )]
+    );
+    assert!(find_join_links(None, Some("Lunch at noon"), Some("Cafeteria")).is_empty());
+}
+
+#[tokio::test]
+async fn test_event_join_links() {
+    let dir = tempfile::tempdir().unwrap();
+    let events = dir.path().join("calendar/v3/calendars/primary/events");
+    std::fs::create_dir_all(&events).unwrap();
+    let event = json!({
+        "id": "event-teams",
+        "summary": "Vendor review",
+        "description": "Microsoft Teams meeting\nJoin on your computer: https://teams.microsoft.com/l/meetup-join/19%3ameeting_abc%40thread.v2/0?context=%7b%7d\nLearn more: https://aka.ms/JoinTeamsMeeting",
+        "location": "Microsoft Teams Meeting",
+        "start": { "dateTime": "2030-03-05T16:00:00Z" },
+        "end": { "dateTime": "2030-03-05T17:00:00Z" }
+    });
+    std::fs::write(events.join("event-teams.json"), event.to_string()).unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    let result = call_tool(&client, "get_event", json!({ "event_id": "event-teams" }))
+        .await
+        .unwrap();
+    assert_eq!(
+        result["join_links"],
+        json!([{
+            "provider": "teams",
+            "url": "https://teams.microsoft.com/l/meetup-join/19%3ameeting_abc%40thread.v2/0?context=%7b%7d",
+            "source": "description"
+        }])
+    );
+}
@@ -0,0 +1,194 @@
+/// Label Suggestion Tests Module
+///
+/// This module contains tests for suggesting labels from the labels on
+/// earlier mail, checking which earlier mail counts for a sender, how
+/// suggestions are scored and ordered, and the suggest_labels tool against
+/// the mock server.
+use mcp_gmailcal::gmail_api::MessageMetadata;
+use mcp_gmailcal::label_suggestions::{history_query, sender_scope, suggest_labels};
+use mcp_gmailcal::label_tree::LabelInfo;
+use serde_json::{json, Value};
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn message(id: &str, from: &str, subject: &str, label_ids: &[&str]) -> MessageMetadata {
+    MessageMetadata {
+        id: id.to_string(),
+        thread_id: id.to_string(),
+        from: Some(from.to_string()),
+        subject: Some(subject.to_string()),
+        label_ids: label_ids.iter().map(|id| id.to_string()).collect(),
+        ..Default::default()
+    }
+}
+
+fn label(id: &str, name: &str, label_type: &str) -> LabelInfo {
+    LabelInfo {
+        id: id.to_string(),
+        name: name.to_string(),
+        label_type: Some(label_type.to_string()),
+    }
+}
+
+#[test]
+fn test_sender_scope() {
+    assert_eq!(
+        sender_scope("Billing <Billing@Vendor.example>"),
+        Some((
+            "billing@vendor.example".to_string(),
+            Some("vendor.example".to_string())
+        ))
+    );
+    assert_eq!(
+        sender_scope("friend@gmail.com"),
+        Some(("friend@gmail.com".to_string(), None))
+    );
+    assert_eq!(sender_scope("undisclosed-recipients"), None);
+
+    assert_eq!(
+        history_query("Billing <billing@vendor.example>").as_deref(),
+        Some("from:vendor.example")
+    );
+    assert_eq!(
+        history_query("friend@gmail.com").as_deref(),
+        Some("from:friend@gmail.com")
+    );
+}
+
+#[test]
+fn test_suggest_labels() {
+    let labels = vec![
+        label("INBOX", "INBOX", "system"),
+        label("Label_1", "Receipts", "user"),
+        label("Label_2", "Finance/Invoices", "user"),
+        label("Label_3", "Vendors", "user"),
+        label("Label_4", "Travel", "user"),
+    ];
+    let new = message(
+        "new",
+        "Billing <billing@vendor.example>",
+        "Your invoice is ready",
+        &["INBOX", "Label_3"],
+    );
+    let history = vec![
+        new.clone(),
+        message(
+            "m1",
+            "billing@vendor.example",
+            "Invoice",
+            &["INBOX", "Label_1"],
+        ),
+        message(
+            "m2",
+            "billing@vendor.example",
+            "Invoice",
+            &["Label_1", "Label_3"],
+        ),
+        message("m3", "sales@vendor.example", "Offer", &["Label_2"]),
+        message("m4", "other@elsewhere.example", "Trip", &["Label_4"]),
+    ];
+
+    let suggestions = suggest_labels(&new, &history, &labels);
+    // System labels and labels already on the message are not suggested,
+    // and mail from other domains says nothing
+    let names: Vec<&str> = suggestions.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, vec!["Receipts", "Finance/Invoices"]);
+
+    let receipts = &suggestions[0];
+    assert_eq!(receipts.score, 0.6);
+    assert_eq!(receipts.sender_messages, 2);
+    assert_eq!(
+        receipts.reasons,
+        vec!["On 2 of 2 earlier messages from billing@vendor.example"]
+    );
+
+    let invoices = &suggestions[1];
+    assert_eq!(invoices.score, 0.4);
+    assert_eq!(invoices.domain_messages, 1);
+    assert!(invoices.name_match);
+    assert_eq!(invoices.reasons.len(), 2);
+    assert_eq!(invoices.reasons[1], "The subject mentions \"Invoices\"");
+
+    // Without earlier mail only the subject hints at a label
+    let suggestions = suggest_labels(&new, &[], &labels);
+    assert_eq!(suggestions.len(), 1);
+    assert_eq!(suggestions[0].score, 0.15);
+}
+
+#[tokio::test]
+async fn test_suggest_labels_tool() {
+    let dir = tempfile::tempdir().unwrap();
+    let user = dir.path().join("gmail/v1/users/me");
+    std::fs::create_dir_all(user.join("messages")).unwrap();
+    std::fs::write(
+        user.join("labels.json"),
+        json!({
+            "labels": [
+                { "id": "INBOX", "name": "INBOX", "type": "system" },
+                { "id": "Label_1", "name": "Receipts", "type": "user" },
+                { "id": "Label_2", "name": "Projects/Planning", "type": "user" }
+            ]
+        })
+        .to_string(),
+    )
+    .unwrap();
+    std::fs::write(
+        user.join("messages/mock-msg-003.json"),
+        json!({
+            "id": "mock-msg-003",
+            "threadId": "mock-thread-001",
+            "labelIds": ["INBOX", "Label_2"],
+            "payload": {
+                "headers": [
+                    { "name": "From", "value": "Bob Example <bob@example.com>" },
+                    { "name": "Subject", "value": "Re: Quarterly planning meeting" }
+                ]
+            }
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    let result = call_tool(
+        &client,
+        "suggest_labels",
+        json!({ "message_id": "mock-msg-001" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["subject"], "Quarterly planning meeting");
+    assert_eq!(
+        result["current_labels"],
+        json!(["INBOX", "UNREAD", "IMPORTANT"])
+    );
+    assert_eq!(result["earlier_messages"], 2);
+    let suggestions = result["suggestions"].as_array().unwrap();
+    assert_eq!(suggestions.len(), 1);
+    assert_eq!(suggestions[0]["name"], "Projects/Planning");
+    assert_eq!(suggestions[0]["score"], 0.4);
+    assert_eq!(result["applied"], Value::Null);
+    assert!(result["categorization_prompt"]
+        .as_str()
+        .unwrap()
+        .contains("categorizing emails"));
+
+    let result = call_tool(
+        &client,
+        "suggest_labels",
+        json!({ "message_id": "mock-msg-001", "apply": true }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["applied"], "Projects/Planning");
+
+    assert!(call_tool(
+        &client,
+        "suggest_labels",
+        json!({ "message_id": "missing-message" }),
+    )
+    .await
+    .is_err());
+}
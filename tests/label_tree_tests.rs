@@ -0,0 +1,207 @@
+/// Label Tree Tests Module
+///
+/// This module contains tests for label nesting, checking the name helpers,
+/// the tree built from a label listing, and the creation of missing parent
+/// labels through the Gmail API trait and the get_label_tree and create_label
+/// tools against the mock server.
+use mcp_gmailcal::api::GmailApi;
+use mcp_gmailcal::label_tree::{
+    ancestor_names, build_label_tree, child_labels, normalize_label_name, parent_name,
+    parse_labels, short_name, LabelInfo,
+};
+use mcp_gmailcal::test_util::MockGmailApi;
+use serde_json::{json, Value};
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn label(id: &str, name: &str, label_type: &str) -> LabelInfo {
+    LabelInfo {
+        id: id.to_string(),
+        name: name.to_string(),
+        label_type: Some(label_type.to_string()),
+    }
+}
+
+#[test]
+fn test_label_names() {
+    assert_eq!(
+        parent_name("Projects/Apollo/Design"),
+        Some("Projects/Apollo")
+    );
+    assert_eq!(parent_name("Projects"), None);
+    assert_eq!(short_name("Projects/Apollo/Design"), "Design");
+    assert_eq!(short_name("Projects"), "Projects");
+    assert_eq!(
+        ancestor_names("Projects/Apollo/Design"),
+        vec!["Projects", "Projects/Apollo"]
+    );
+    assert!(ancestor_names("Projects").is_empty());
+
+    assert_eq!(
+        normalize_label_name(" Projects / Apollo ").unwrap(),
+        "Projects/Apollo"
+    );
+    assert!(normalize_label_name("Projects//Apollo").is_err());
+    assert!(normalize_label_name("Projects/").is_err());
+    assert!(normalize_label_name("  ").is_err());
+}
+
+#[test]
+fn test_build_label_tree() {
+    let labels = vec![
+        label("INBOX", "INBOX", "system"),
+        label("Label_3", "Projects/Apollo/Design", "user"),
+        label("Label_1", "Projects", "user"),
+        label("Label_2", "Projects/Apollo", "user"),
+        label("Label_4", "Projects/Zeus", "user"),
+        label("Label_5", "Archive/2023", "user"),
+        label("Label_6", "Receipts", "user"),
+    ];
+
+    let children: Vec<&str> = child_labels(&labels, "projects")
+        .iter()
+        .map(|label| label.id.as_str())
+        .collect();
+    assert_eq!(children, vec!["Label_2", "Label_4"]);
+
+    let tree = build_label_tree(&labels, false);
+    let roots: Vec<&str> = tree.iter().map(|node| node.name.as_str()).collect();
+    assert_eq!(roots, vec!["Archive", "Projects", "Receipts"]);
+
+    // Archive only exists as the parent of Archive/2023
+    assert_eq!(tree[0].id, None);
+    assert_eq!(tree[0].children[0].id.as_deref(), Some("Label_5"));
+    assert_eq!(tree[0].children[0].short_name, "2023");
+
+    let projects = &tree[1];
+    assert_eq!(projects.id.as_deref(), Some("Label_1"));
+    let nested: Vec<&str> = projects
+        .children
+        .iter()
+        .map(|node| node.short_name.as_str())
+        .collect();
+    assert_eq!(nested, vec!["Apollo", "Zeus"]);
+    assert_eq!(
+        projects.children[0].children[0].name,
+        "Projects/Apollo/Design"
+    );
+    assert_eq!(
+        projects.children[0].children[0].id.as_deref(),
+        Some("Label_3")
+    );
+
+    let with_system = build_label_tree(&labels, true);
+    assert_eq!(with_system.len(), 4);
+    assert!(with_system
+        .iter()
+        .any(|node| node.id.as_deref() == Some("INBOX") && node.children.is_empty()));
+}
+
+#[test]
+fn test_parse_labels() {
+    let labels = parse_labels(
+        &json!({ "labels": [
+            { "id": "INBOX", "name": "INBOX", "type": "system" },
+            { "id": "Label_1", "name": "Receipts" }
+        ]})
+        .to_string(),
+    )
+    .unwrap();
+    assert!(labels[0].is_system());
+    assert!(!labels[1].is_system());
+    assert!(parse_labels("{}").unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_get_or_create_label_creates_parents() {
+    let gmail = MockGmailApi::new().with_labels([("Label_1", "Projects")]);
+
+    let id = gmail
+        .get_or_create_label("projects/Apollo/Design")
+        .await
+        .unwrap();
+    let labels = gmail.labels();
+    let names: Vec<&str> = labels.iter().map(|(_, name)| name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec!["Projects", "projects/Apollo", "projects/Apollo/Design"]
+    );
+    assert_eq!(id, labels[2].0);
+
+    // Existing labels are found whatever their case
+    assert_eq!(
+        gmail.get_or_create_label("PROJECTS").await.unwrap(),
+        "Label_1"
+    );
+    assert_eq!(gmail.labels().len(), 3);
+}
+
+#[tokio::test]
+async fn test_label_tools() {
+    let dir = tempfile::tempdir().unwrap();
+    let gmail = dir.path().join("gmail/v1/users/me");
+    std::fs::create_dir_all(&gmail).unwrap();
+    std::fs::write(
+        gmail.join("labels.json"),
+        json!({ "labels": [
+            { "id": "INBOX", "name": "INBOX", "type": "system" },
+            { "id": "Label_1", "name": "Projects", "type": "user" },
+            { "id": "Label_2", "name": "Projects/Apollo", "type": "user" },
+            { "id": "Label_3", "name": "Clients/Acme", "type": "user" }
+        ]})
+        .to_string(),
+    )
+    .unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    let tree = call_tool(&client, "get_label_tree", json!({}))
+        .await
+        .unwrap();
+    assert_eq!(tree["count"], 3);
+    assert_eq!(tree["labels"][0]["name"], "Clients");
+    assert_eq!(tree["labels"][0]["id"], Value::Null);
+    assert_eq!(tree["labels"][0]["children"][0]["short_name"], "Acme");
+    assert_eq!(tree["labels"][1]["children"][0]["id"], "Label_2");
+
+    let tree = call_tool(&client, "get_label_tree", json!({ "include_system": true }))
+        .await
+        .unwrap();
+    assert_eq!(tree["count"], 4);
+
+    // Only the missing levels are created, parents first
+    let created = call_tool(
+        &client,
+        "create_label",
+        json!({ "name": "Projects / Apollo / Design / Mockups" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(created["status"], "created");
+    assert_eq!(created["name"], "Projects/Apollo/Design/Mockups");
+    assert_eq!(
+        created["created"],
+        json!(["Projects/Apollo/Design", "Projects/Apollo/Design/Mockups"])
+    );
+    assert!(created["id"].as_str().unwrap().starts_with("mock-"));
+
+    let existing = call_tool(
+        &client,
+        "create_label",
+        json!({ "name": "projects/apollo" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(existing["status"], "exists");
+    assert_eq!(existing["id"], "Label_2");
+    assert_eq!(existing["created"], json!([]));
+
+    assert!(call_tool(
+        &client,
+        "create_label",
+        json!({ "name": "Projects//Apollo" })
+    )
+    .await
+    .is_err());
+}
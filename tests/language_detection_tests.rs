@@ -0,0 +1,86 @@
+/// Language Detection Tests Module
+///
+/// This module contains tests for guessing the language of email bodies and
+/// for the translate analysis of analyze_email against the mock server.
+use mcp_gmailcal::prompts::EMAIL_TRANSLATION_PROMPT;
+use mcp_gmailcal::utils::detect_language;
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+#[test]
+fn test_detect_latin_script_languages() {
+    let cases = [
+        ("en", "Thanks for the update, I will have the report ready for you this week."),
+        ("es", "Hola, muchas gracias por la información. Nos vemos el lunes para revisar los detalles del proyecto."),
+        ("fr", "Bonjour, merci pour votre message. Nous avons bien reçu les documents et je vous réponds dans la journée."),
+        ("de", "Hallo, danke für die Nachricht. Wir haben die Unterlagen bekommen und ich melde mich morgen mit den Details."),
+        ("it", "Ciao, grazie per il messaggio. Non sono sicuro che questo sia il momento giusto per la riunione."),
+        ("pt", "Olá, obrigado pela mensagem. Você pode enviar os documentos para o escritório? Não recebi nada."),
+        ("nl", "Hallo, bedankt voor het bericht. Ik heb de documenten nog niet ontvangen, kun je ze opnieuw sturen?"),
+    ];
+    for (language, text) in cases {
+        assert_eq!(detect_language(text).as_deref(), Some(language), "{}", text);
+    }
+}
+
+#[test]
+fn test_detect_other_scripts() {
+    let cases = [
+        ("ja", "お世話になっております。来週の会議の資料を送ります。"),
+        ("zh", "您好，附件是下周会议的资料，请查收。"),
+        ("ko", "안녕하세요. 다음 주 회의 자료를 보내드립니다."),
+        (
+            "ru",
+            "Здравствуйте, отправляю материалы к встрече на следующей неделе.",
+        ),
+        ("el", "Καλημέρα, σας στέλνω το υλικό για τη συνάντηση."),
+        ("ar", "مرحبا، أرسل لك مواد الاجتماع للأسبوع المقبل."),
+        ("he", "שלום, מצורפים החומרים לפגישה בשבוע הבא."),
+    ];
+    for (language, text) in cases {
+        assert_eq!(detect_language(text).as_deref(), Some(language), "{}", text);
+    }
+}
+
+#[test]
+fn test_detect_language_undecided() {
+    // Too short, no letters, or no common words to go on
+    assert_eq!(detect_language(""), None);
+    assert_eq!(detect_language("Hi Sam"), None);
+    assert_eq!(detect_language("12345 67890 !!!"), None);
+    assert_eq!(detect_language("Invoice INV-2026-0042 attached"), None);
+
+    // A tie between languages is not a guess
+    assert_eq!(detect_language("que de la"), None);
+}
+
+#[tokio::test]
+async fn test_translate_analysis() {
+    let (_server, client) = mock_client(None).await;
+
+    let email = call_tool(
+        &client,
+        "get_email",
+        json!({ "message_id": "mock-msg-001" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(email["language"], "en");
+
+    let result = call_tool(
+        &client,
+        "analyze_email",
+        json!({ "message_id": "mock-msg-001", "analysis_type": "translate" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["analysis_type"], "translate");
+    assert_eq!(result["language"], "en");
+    assert!(result["content"]
+        .as_str()
+        .unwrap()
+        .contains("Can we meet on Thursday"));
+    assert_eq!(result["analysis_prompt"], EMAIL_TRANSLATION_PROMPT);
+}
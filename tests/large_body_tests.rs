@@ -0,0 +1,84 @@
+/// Large Body Tests Module
+///
+/// This module contains tests for message body decoding, checking both base64
+/// alphabets, that bodies over the size limit are only partly decoded, and
+/// that the Gmail client applies the limit from the environment.
+use mcp_gmailcal::gmail_api::decode_body;
+use mcp_gmailcal::{Config, GmailService};
+
+mod common;
+use common::mock_server;
+
+fn url_safe(text: &str) -> String {
+    base64::encode_config(text, base64::URL_SAFE_NO_PAD)
+}
+
+#[test]
+fn test_decode_body_accepts_both_alphabets() {
+    // Chosen to encode to characters that differ between the alphabets
+    let text = "subjects?>>~~ end";
+    let standard = base64::encode(text);
+    assert!(standard.contains(['+', '/']) && standard.ends_with('='));
+
+    assert_eq!(decode_body(&standard, 1024).as_deref(), Some(text));
+    assert_eq!(decode_body(&url_safe(text), 1024).as_deref(), Some(text));
+    assert_eq!(
+        decode_body(&base64::encode_config(text, base64::URL_SAFE), 1024).as_deref(),
+        Some(text)
+    );
+    assert_eq!(decode_body("", 1024).as_deref(), Some(""));
+}
+
+#[test]
+fn test_decode_body_rejects_invalid_data() {
+    assert_eq!(decode_body("not base64!", 1024), None);
+    assert_eq!(decode_body(&base64::encode([0xff, 0xfe, 0xfd]), 1024), None);
+    // Non-ASCII input is invalid even when it would be truncated
+    assert_eq!(decode_body(&"é".repeat(100), 8), None);
+}
+
+#[test]
+fn test_large_body_is_truncated() {
+    let body = "B".repeat(100_000);
+    let text = decode_body(&url_safe(&body), 1000).unwrap();
+
+    // Whole base64 groups are decoded up to the limit
+    assert!(text.starts_with(&"B".repeat(999)));
+    assert!(!text.starts_with(&"B".repeat(1000)));
+    assert!(text.ends_with("[Message body truncated: showing 999 of 100000 bytes]"));
+    assert!(text.len() < 1100);
+
+    // Bodies at the limit are kept whole
+    assert_eq!(decode_body(&url_safe(&body), 100_000).unwrap(), body);
+}
+
+#[test]
+fn test_truncation_respects_character_boundaries() {
+    // Two bytes per character, so a 9 byte cut splits one
+    let body = "é".repeat(50);
+    let text = decode_body(&url_safe(&body), 10).unwrap();
+    assert!(text.starts_with("éééé\n\n[Message body truncated: showing 8 of 100 bytes]"));
+}
+
+#[tokio::test]
+async fn test_client_applies_body_limit() {
+    let _server = mock_server(None).await;
+    let config = Config::from_env().unwrap();
+    let gmail = GmailService::new(&config).unwrap();
+
+    let full = gmail.get_message_details("mock-msg-001").await.unwrap();
+    let full_body = full.body_text.unwrap();
+    assert!(!full_body.contains("[Message body truncated"));
+    assert!(full_body.len() > 30);
+
+    std::env::set_var("MESSAGE_BODY_MAX_BYTES", "30");
+    let truncated = gmail.get_message_details("mock-msg-001").await.unwrap();
+    let body = truncated.body_text.unwrap();
+    assert!(body.starts_with(&full_body[..30]));
+    assert!(body.contains(&format!(
+        "[Message body truncated: showing 30 of {} bytes]",
+        full_body.len()
+    )));
+    assert_eq!(truncated.subject, full.subject);
+    std::env::remove_var("MESSAGE_BODY_MAX_BYTES");
+}
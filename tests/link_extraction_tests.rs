@@ -0,0 +1,126 @@
+/// Link Extraction Tests Module
+///
+/// This module contains tests for finding the links in email bodies, flagging
+/// trackers and redirects, and the extract_links tool against the mock server.
+use mcp_gmailcal::utils::extract_links;
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+const NEWSLETTER_HTML: &str = r#"<html><body>
+<p>Read <a class="cta" href="https://example.com/posts/42?utm_source=newsletter&amp;utm_medium=email">the <b>full story</b></a>.</p>
+<p><A HREF='https://click.example.com.list-manage.com/track/click?u=abc&id=def'>Shop now</A></p>
+<p><a href="https://www.google.com/url?q=https%3A%2F%2Fdocs.example.org%2Fguide&sa=D">docs.example.org/guide</a></p>
+<p><a href="mailto:help@example.com">Email us</a> or <a href="https://example.com/posts/42?utm_source=newsletter&amp;utm_medium=email">read again</a></p>
+</body></html>"#;
+
+#[test]
+fn test_extract_links_from_html() {
+    let links = extract_links(Some(NEWSLETTER_HTML), None);
+    let urls: Vec<&str> = links.iter().map(|link| link.url.as_str()).collect();
+    // Each link once, entities decoded, mailto links left out
+    assert_eq!(
+        urls,
+        vec![
+            "https://example.com/posts/42?utm_source=newsletter&utm_medium=email",
+            "https://click.example.com.list-manage.com/track/click?u=abc&id=def",
+            "https://www.google.com/url?q=https%3A%2F%2Fdocs.example.org%2Fguide&sa=D",
+        ]
+    );
+
+    assert_eq!(links[0].anchor_text.as_deref(), Some("the full story"));
+    assert_eq!(links[0].domain.as_deref(), Some("example.com"));
+    assert!(links[0].is_tracker);
+    assert!(!links[0].is_redirect);
+
+    assert_eq!(
+        links[1].domain.as_deref(),
+        Some("click.example.com.list-manage.com")
+    );
+    assert!(links[1].is_tracker);
+
+    assert!(links[2].is_redirect);
+    assert!(!links[2].is_tracker);
+    assert_eq!(
+        links[2].redirect_target.as_deref(),
+        Some("https://docs.example.org/guide")
+    );
+    assert_eq!(links[2].domain.as_deref(), Some("google.com"));
+}
+
+#[test]
+fn test_extract_links_from_text() {
+    let text = "See https://example.com/a, and (https://example.org/b). Again: https://example.com/a.\nDone";
+    let links = extract_links(None, Some(text));
+    let urls: Vec<&str> = links.iter().map(|link| link.url.as_str()).collect();
+    assert_eq!(urls, vec!["https://example.com/a", "https://example.org/b"]);
+    assert!(links.iter().all(|link| link.anchor_text.is_none()));
+    assert!(links
+        .iter()
+        .all(|link| !link.is_tracker && !link.is_redirect));
+
+    // Links in the text body that the HTML body already has are not repeated
+    let links = extract_links(
+        Some(r#"<a href="https://example.com/a">here</a>"#),
+        Some("here: https://example.com/a"),
+    );
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0].anchor_text.as_deref(), Some("here"));
+
+    assert!(extract_links(None, None).is_empty());
+    assert!(extract_links(Some("<p>No links</p>"), Some("No links")).is_empty());
+}
+
+#[tokio::test]
+async fn test_extract_links_tool() {
+    let dir = tempfile::tempdir().unwrap();
+    let messages = dir.path().join("gmail/v1/users/me/messages");
+    std::fs::create_dir_all(&messages).unwrap();
+    let encode = |text: &str| base64::encode_config(text, base64::URL_SAFE);
+    let message = json!({
+        "id": "msg-links",
+        "threadId": "thread-links",
+        "snippet": "Read the full story",
+        "payload": {
+            "mimeType": "multipart/alternative",
+            "headers": [{ "name": "Subject", "value": "Weekly news" }],
+            "parts": [
+                { "mimeType": "text/plain", "body": { "data": encode("Read it: https://example.com/posts/42") } },
+                { "mimeType": "text/html", "body": { "data": encode(NEWSLETTER_HTML) } }
+            ]
+        }
+    });
+    std::fs::write(messages.join("msg-links.json"), message.to_string()).unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    let result = call_tool(
+        &client,
+        "extract_links",
+        json!({ "message_id": "msg-links" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["message_id"], "msg-links");
+    assert_eq!(result["count"], 4);
+    let links = result["links"].as_array().unwrap();
+    assert_eq!(links[0]["anchor_text"], "the full story");
+    assert_eq!(links[0]["is_tracker"], true);
+    assert_eq!(
+        links[2]["redirect_target"],
+        "https://docs.example.org/guide"
+    );
+    assert_eq!(links[3]["url"], "https://example.com/posts/42");
+    assert!(links[3]["anchor_text"].is_null());
+
+    // Messages without links have none to list
+    let result = call_tool(
+        &client,
+        "extract_links",
+        json!({ "message_id": "mock-msg-001" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["count"], 0);
+}
@@ -0,0 +1,115 @@
+/// Listing Detail Tests Module
+///
+/// This module contains tests for choosing how much of each message a listing
+/// returns, checking snippet truncation, which bodies are kept, and the
+/// snippet_length and include_body parameters of list_emails and
+/// search_emails against the mock server.
+use mcp_gmailcal::gmail_api::EmailMessage;
+use mcp_gmailcal::listing_detail::{truncate_chars, BodyDetail, ListingDetail};
+use mcp_gmailcal::test_util::message;
+use serde_json::{json, Value};
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn sample() -> EmailMessage {
+    EmailMessage {
+        snippet: Some("Can we meet on Thursday?".to_string()),
+        body_text: Some("Can we meet on Thursday?".to_string()),
+        body_html: Some("<p>Can we meet on Thursday?</p>".to_string()),
+        ..message("m1", "alice@example.com", "Hello")
+    }
+}
+
+#[test]
+fn test_truncate_chars() {
+    assert_eq!(truncate_chars("short", 10), "short");
+    assert_eq!(truncate_chars("exactly", 7), "exactly");
+    assert_eq!(truncate_chars("Can we meet", 7), "Can we…");
+    assert_eq!(truncate_chars("héllo wörld", 4), "héll…");
+    assert_eq!(truncate_chars("anything", 0), "…");
+}
+
+#[test]
+fn test_listing_detail() {
+    assert_eq!(BodyDetail::parse(" Text "), Some(BodyDetail::Text));
+    assert_eq!(BodyDetail::parse("html"), None);
+    assert_eq!(
+        ListingDetail::from_args(None, None).unwrap(),
+        ListingDetail::default()
+    );
+    assert!(ListingDetail::from_args(None, Some("all"))
+        .unwrap_err()
+        .contains("Unknown include_body 'all'"));
+
+    // The default leaves messages as they are
+    let mut email = sample();
+    ListingDetail::default().apply(&mut email);
+    assert_eq!(email.snippet.as_deref(), Some("Can we meet on Thursday?"));
+    assert!(email.body_html.is_some());
+
+    let mut email = sample();
+    ListingDetail::from_args(Some(11), Some("text"))
+        .unwrap()
+        .apply(&mut email);
+    assert_eq!(email.snippet.as_deref(), Some("Can we meet…"));
+    assert!(email.body_text.is_some());
+    assert!(email.body_html.is_none());
+
+    let mut email = sample();
+    ListingDetail::from_args(None, Some("none"))
+        .unwrap()
+        .apply(&mut email);
+    assert!(email.body_text.is_none());
+    assert!(email.body_html.is_none());
+    assert_eq!(email.subject.as_deref(), Some("Hello"));
+}
+
+#[tokio::test]
+async fn test_listing_detail_tools() {
+    let (_server, client) = mock_client(None).await;
+
+    let emails = call_tool(&client, "list_emails", json!({})).await.unwrap();
+    let emails = emails.as_array().unwrap();
+    assert!(emails.iter().any(|email| email["body_text"].is_string()));
+
+    let emails = call_tool(
+        &client,
+        "list_emails",
+        json!({ "snippet_length": "20", "include_body": "none" }),
+    )
+    .await
+    .unwrap();
+    for email in emails.as_array().unwrap() {
+        assert!(email["snippet"].as_str().unwrap().chars().count() <= 21);
+        assert_eq!(email["body_text"], Value::Null);
+        assert_eq!(email["body_html"], Value::Null);
+    }
+
+    let result = call_tool(
+        &client,
+        "search_emails",
+        json!({
+            "query": "planning",
+            "snippet_length": 10,
+            "include_body": "text",
+            "deduplicate": true
+        }),
+    )
+    .await
+    .unwrap();
+    for thread in result["threads"].as_array().unwrap() {
+        for email in thread["messages"].as_array().unwrap() {
+            assert!(email["snippet"].as_str().unwrap().chars().count() <= 11);
+            assert_eq!(email["body_html"], Value::Null);
+        }
+    }
+
+    assert!(call_tool(
+        &client,
+        "search_emails",
+        json!({ "query": "planning", "include_body": "html" }),
+    )
+    .await
+    .is_err());
+}
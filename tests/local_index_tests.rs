@@ -0,0 +1,221 @@
+#![cfg(feature = "local-index")]
+/// Local Index Tests Module
+///
+/// This module contains tests for the SQLite message index behind the
+/// search_local tool, covering query translation, full-text search, History
+/// API syncing against the in-memory Gmail mock and the Gmail fallback.
+use mcp_attr::client::McpClient;
+use mcp_attr::schema::CallToolRequestParams;
+use mcp_gmailcal::deadline::Deadline;
+use mcp_gmailcal::local_index::{to_fts_query, LocalIndex, SyncStats};
+use mcp_gmailcal::mock::MockServer;
+use mcp_gmailcal::test_util::{message, MockGmailApi};
+use mcp_gmailcal::{EmailMessage, GmailApiError, GmailServer};
+use std::time::Duration;
+
+fn dated(id: &str, from: &str, subject: &str, day: u32) -> EmailMessage {
+    EmailMessage {
+        to: Some("Me <me@example.com>".to_string()),
+        date: Some(format!("{:02} Oct 2026 09:00:00 +0000", day)),
+        snippet: Some(format!("Snippet for {}", subject.to_lowercase())),
+        body_text: Some("Full body".to_string()),
+        ..message(id, from, subject)
+    }
+}
+
+fn sample_messages() -> Vec<EmailMessage> {
+    vec![
+        dated("m3", "Alice <alice@example.com>", "Budget review", 3),
+        dated("m2", "Bob <bob@example.com>", "Team offsite", 2),
+        dated("m1", "Alice <alice@example.com>", "Budget draft", 1),
+    ]
+}
+
+fn ids(messages: &[EmailMessage]) -> Vec<&str> {
+    messages.iter().map(|m| m.id.as_str()).collect()
+}
+
+#[test]
+fn test_query_translation() {
+    assert_eq!(to_fts_query("budget").unwrap(), r#""budget""#);
+    assert_eq!(
+        to_fts_query("from:alice@example.com budget").unwrap(),
+        r#"sender : "alice@example.com" "budget""#
+    );
+    assert_eq!(
+        to_fts_query(r#"subject:"team offsite" to:me"#).unwrap(),
+        r#"subject : "team offsite" recipients : "me""#
+    );
+    assert_eq!(
+        to_fts_query(r#""budget review""#).unwrap(),
+        r#""budget review""#
+    );
+
+    // Operators only Gmail understands
+    assert!(to_fts_query("is:unread").is_none());
+    assert!(to_fts_query("budget -draft").is_none());
+    assert!(to_fts_query("alice OR bob").is_none());
+    assert!(to_fts_query("after:2026/10/01").is_none());
+    assert!(to_fts_query("   ").is_none());
+}
+
+#[test]
+fn test_search_orders_by_date_and_filters_columns() {
+    let index = LocalIndex::open_in_memory().unwrap();
+    for message in sample_messages() {
+        index.upsert(&message).unwrap();
+    }
+    assert_eq!(index.message_count().unwrap(), 3);
+
+    let results = index.search("budget", 10).unwrap();
+    assert_eq!(ids(&results), vec!["m3", "m1"]);
+    assert!(results[0].body_text.is_none());
+    assert_eq!(
+        results[0].snippet.as_deref(),
+        Some("Snippet for budget review")
+    );
+
+    assert_eq!(ids(&index.search("from:bob", 10).unwrap()), vec!["m2"]);
+    assert_eq!(ids(&index.search("budget", 1).unwrap()), vec!["m3"]);
+    assert!(index.search("from:budget", 10).unwrap().is_empty());
+
+    // Re-indexing a message replaces it
+    index
+        .upsert(&dated("m2", "Bob <bob@example.com>", "Offsite moved", 2))
+        .unwrap();
+    assert!(index.search("team", 10).unwrap().is_empty());
+    assert_eq!(index.message_count().unwrap(), 3);
+
+    index.remove("m3").unwrap();
+    assert_eq!(ids(&index.search("budget", 10).unwrap()), vec!["m1"]);
+
+    assert!(index.search("is:starred", 10).is_err());
+}
+
+#[tokio::test]
+async fn test_full_then_incremental_sync() {
+    let index = LocalIndex::open_in_memory().unwrap();
+    let mut gmail = MockGmailApi::new().with_messages(sample_messages());
+
+    assert!(index.is_stale(Duration::from_secs(300)).unwrap());
+    let stats = index.sync(&gmail, 2).await.unwrap();
+    assert_eq!(
+        stats,
+        SyncStats {
+            full: true,
+            added: 2,
+            removed: 0
+        }
+    );
+    assert_eq!(index.message_count().unwrap(), 2);
+    assert!(!index.is_stale(Duration::from_secs(300)).unwrap());
+    assert_eq!(index.history_id().unwrap().as_deref(), Some("1"));
+
+    gmail.add_message(dated(
+        "m4",
+        "Carol <carol@example.com>",
+        "Budget approved",
+        4,
+    ));
+    gmail.delete_message("m2");
+    let stats = index.sync(&gmail, 2).await.unwrap();
+    assert_eq!(
+        stats,
+        SyncStats {
+            full: false,
+            added: 1,
+            removed: 1
+        }
+    );
+    assert_eq!(ids(&index.search("budget", 10).unwrap()), vec!["m4", "m3"]);
+    assert!(index.search("offsite", 10).unwrap().is_empty());
+
+    // A message added and deleted between syncs is never indexed
+    gmail.add_message(dated("m5", "Dan <dan@example.com>", "Spam", 5));
+    gmail.delete_message("m5");
+    index.sync(&gmail, 2).await.unwrap();
+    assert!(index.search("spam", 10).unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_sync_recovers_from_expired_history() {
+    let index = LocalIndex::open_in_memory().unwrap();
+    let mut gmail = MockGmailApi::new().with_messages(sample_messages());
+    index.sync(&gmail, 10).await.unwrap();
+
+    gmail.add_message(dated(
+        "m4",
+        "Carol <carol@example.com>",
+        "Budget approved",
+        4,
+    ));
+    gmail.expire_history();
+
+    let stats = index.sync(&gmail, 10).await.unwrap();
+    assert!(stats.full);
+    assert_eq!(index.message_count().unwrap(), 4);
+
+    // Other errors are reported rather than triggering a resync
+    let failing = MockGmailApi::new()
+        .failing_with(|| GmailApiError::RateLimitError("Quota exceeded".to_string()));
+    assert!(index.sync(&failing, 10).await.is_err());
+    assert_eq!(index.message_count().unwrap(), 4);
+}
+
+#[tokio::test]
+async fn test_full_sync_cut_short_by_deadline_keeps_index() {
+    let index = LocalIndex::open_in_memory().unwrap();
+    let gmail = MockGmailApi::new().with_messages(sample_messages());
+    index.sync(&gmail, 10).await.unwrap();
+
+    // A resync whose listing is cut short must not replace the index
+    let mut late = MockGmailApi::new()
+        .with_messages(sample_messages())
+        .with_deadline(Deadline::after(Duration::ZERO));
+    late.add_message(dated("m4", "Carol <carol@example.com>", "Offsite", 4));
+    late.expire_history();
+    assert!(index.sync(&late, 10).await.is_err());
+    assert_eq!(index.message_count().unwrap(), 3);
+}
+
+#[tokio::test]
+async fn test_index_persists_to_disk() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("nested").join("index.sqlite");
+
+    {
+        let index = LocalIndex::open(&path).unwrap();
+        let gmail = MockGmailApi::new().with_messages(sample_messages());
+        index.sync(&gmail, 10).await.unwrap();
+    }
+
+    let index = LocalIndex::open(&path).unwrap();
+    assert_eq!(index.message_count().unwrap(), 3);
+    assert!(index.last_synced().unwrap().is_some());
+}
+
+// Mock mode disables the local index, so search_local answers from Gmail
+#[tokio::test]
+async fn test_search_local_tool_falls_back_to_gmail() {
+    let server = MockServer::start(None).await.unwrap();
+    server.install();
+
+    let client = McpClient::with_server(GmailServer::new()).await.unwrap();
+    let mut arguments = serde_json::Map::new();
+    arguments.insert("query".to_string(), "planning".into());
+    let result = client
+        .tools_call(CallToolRequestParams {
+            name: "search_local".to_string(),
+            arguments: Some(arguments),
+        })
+        .await
+        .unwrap();
+
+    let text = serde_json::to_value(&result.content[0]).unwrap()["text"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let response: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(response["source"], "gmail");
+    assert!(!response["messages"].as_array().unwrap().is_empty());
+}
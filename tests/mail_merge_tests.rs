@@ -0,0 +1,195 @@
+/// Mail Merge Tests Module
+///
+/// This module contains tests for filling a template once per recipient,
+/// checking how recipients' addresses and fields are used, and the
+/// mail_merge tool against the mock server in draft and send modes.
+use mcp_gmailcal::mail_merge::{
+    get_send_interval, merge_email, merge_emails, recipient_id, MergeMode,
+};
+use mcp_gmailcal::templates::EmailTemplate;
+use serde_json::json;
+use std::collections::HashMap;
+use std::time::Duration;
+use tempfile::tempdir;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn template(to: Option<&str>) -> EmailTemplate {
+    EmailTemplate {
+        name: "thanks".to_string(),
+        description: None,
+        subject: "Thanks, {{first_name}}".to_string(),
+        body: "Hi {{first_name}}, thanks for coming to {{event}}.".to_string(),
+        to: to.map(String::from),
+        cc: None,
+    }
+}
+
+fn fields(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_merge_mode() {
+    assert_eq!(MergeMode::parse("Draft"), Some(MergeMode::Draft));
+    assert_eq!(MergeMode::parse("drafts"), Some(MergeMode::Draft));
+    assert_eq!(MergeMode::parse(" send "), Some(MergeMode::Send));
+    assert_eq!(MergeMode::parse("schedule"), None);
+    assert_eq!(MergeMode::Send.as_str(), "send");
+}
+
+#[test]
+fn test_merge_email() {
+    let ada = fields(&[
+        ("email", "ada@example.com"),
+        ("first_name", "Ada"),
+        ("event", "the workshop"),
+    ]);
+    let email = merge_email(&template(None), &ada).unwrap();
+    assert_eq!(email.to, "ada@example.com");
+    assert_eq!(email.subject, "Thanks, Ada");
+    assert_eq!(email.body, "Hi Ada, thanks for coming to the workshop.");
+
+    // "to" wins over "email", and either replaces the template's recipients
+    let grace = fields(&[
+        ("to", "Grace <grace@example.com>"),
+        ("email", "other@example.com"),
+        ("first_name", "Grace"),
+        ("event", "the talk"),
+    ]);
+    let email = merge_email(&template(Some("team@example.com")), &grace).unwrap();
+    assert_eq!(email.to, "Grace <grace@example.com>");
+
+    // A template's recipients can come from the fields too
+    let email = merge_email(
+        &template(Some("{{first_name}}@example.com")),
+        &fields(&[("first_name", "bob"), ("event", "the demo")]),
+    )
+    .unwrap();
+    assert_eq!(email.to, "bob@example.com");
+
+    let merged = merge_emails(
+        &template(None),
+        &[
+            ada,
+            fields(&[("email", "x@example.com"), ("first_name", "X")]),
+            fields(&[("first_name", "Y"), ("event", "the demo")]),
+        ],
+    );
+    assert!(merged[0].is_ok());
+    assert!(merged[1]
+        .as_ref()
+        .unwrap_err()
+        .to_string()
+        .contains("Missing template variables: event"));
+    assert!(merged[2]
+        .as_ref()
+        .unwrap_err()
+        .to_string()
+        .contains("A recipient is required"));
+}
+
+#[test]
+fn test_recipient_id() {
+    assert_eq!(
+        recipient_id(0, &fields(&[("email", " ada@example.com ")])),
+        "ada@example.com"
+    );
+    assert_eq!(recipient_id(1, &fields(&[("email", "")])), "#2");
+}
+
+#[tokio::test]
+async fn test_mail_merge_tool() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("EMAIL_TEMPLATES_FILE", dir.path().join("templates.json"));
+    std::env::set_var("MAIL_MERGE_SEND_INTERVAL_MS", "0");
+    assert_eq!(get_send_interval(), Duration::ZERO);
+    let (_server, client) = mock_client(None).await;
+
+    call_tool(
+        &client,
+        "save_template",
+        json!({
+            "name": "thanks",
+            "subject": "Thanks, {{first_name}}",
+            "body": "Hi {{first_name}}, thanks for coming."
+        }),
+    )
+    .await
+    .unwrap();
+    let recipients = json!([
+        { "email": "ada@example.com", "first_name": "Ada" },
+        { "email": "grace@example.com" },
+        { "email": "bob@example.com", "first_name": "Bob" }
+    ]);
+
+    let result = call_tool(
+        &client,
+        "mail_merge",
+        json!({ "template": "thanks", "recipients": recipients }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["mode"], "draft");
+    assert_eq!(result["template"], "thanks");
+    assert_eq!(result["status"], "partial_success");
+    assert_eq!(result["succeeded"], 2);
+    let items = result["items"].as_array().unwrap();
+    assert_eq!(items[0]["id"], "ada@example.com");
+    assert!(items[0]["result"]["draft_id"].is_string());
+    assert_eq!(items[0]["result"]["subject"], "Thanks, Ada");
+    assert!(items[1]["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("first_name"));
+    assert_eq!(items[2]["result"]["to"], "bob@example.com");
+    assert!(result.get("sent").is_none());
+
+    // Sending without confirm only shows the emails
+    let result = call_tool(
+        &client,
+        "mail_merge",
+        json!({ "template": "thanks", "recipients": recipients, "mode": "send" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["sent"], false);
+    assert_eq!(
+        result["items"][0]["result"]["body"],
+        "Hi Ada, thanks for coming."
+    );
+    assert!(result["items"][0]["result"].get("message_id").is_none());
+
+    let result = call_tool(
+        &client,
+        "mail_merge",
+        json!({ "template": "thanks", "recipients": recipients, "mode": "send", "confirm": true }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["sent"], true);
+    assert_eq!(result["succeeded"], 2);
+    assert!(result["items"][2]["result"]["message_id"].is_string());
+
+    assert!(call_tool(
+        &client,
+        "mail_merge",
+        json!({ "template": "thanks", "recipients": recipients, "mode": "later" }),
+    )
+    .await
+    .is_err());
+    assert!(call_tool(
+        &client,
+        "mail_merge",
+        json!({ "template": "missing", "recipients": recipients }),
+    )
+    .await
+    .is_err());
+
+    std::env::remove_var("MAIL_MERGE_SEND_INTERVAL_MS");
+    std::env::remove_var("EMAIL_TEMPLATES_FILE");
+}
@@ -0,0 +1,307 @@
+/// Mail Rule Tests Module
+///
+/// This module contains tests for the mail rules engine, checking how rules
+/// match mail, how the actions of several rules combine, the forwarded
+/// emails, how matches change the mailbox, and the run_rules tool against
+/// the mock server.
+use mcp_gmailcal::gmail_api::MessageMetadata;
+use mcp_gmailcal::label_tree::LabelInfo;
+use mcp_gmailcal::mail_rules::{
+    apply_match, forward_draft, is_incoming, match_rules, MailRule, MailRuleStore, RuleHistory,
+};
+use mcp_gmailcal::test_util::{message, MockGmailApi};
+use mcp_gmailcal::{EmailMessage, MailRuleError};
+use serde_json::{json, Value};
+use tempfile::tempdir;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn rule(value: Value) -> MailRule {
+    serde_json::from_value(value).unwrap()
+}
+
+fn metadata(id: &str, from: &str, subject: &str, label_ids: &[&str]) -> MessageMetadata {
+    MessageMetadata {
+        id: id.to_string(),
+        thread_id: format!("thread-{}", id),
+        from: Some(from.to_string()),
+        subject: Some(subject.to_string()),
+        label_ids: label_ids.iter().map(|id| id.to_string()).collect(),
+        ..Default::default()
+    }
+}
+
+fn labels() -> Vec<LabelInfo> {
+    vec![LabelInfo {
+        id: "Label_7".to_string(),
+        name: "Clients/Acme".to_string(),
+        label_type: Some("user".to_string()),
+    }]
+}
+
+#[test]
+fn test_rule_matching() {
+    let invoices = rule(json!({
+        "name": "Invoices",
+        "from": ["@vendor.example"],
+        "subject": ["invoice", "receipt"],
+        "label": "Finance",
+    }));
+    let labels = labels();
+    let invoice = metadata(
+        "m1",
+        "Billing <billing@VENDOR.example>",
+        "Your Invoice #4821",
+        &["INBOX"],
+    );
+    assert!(invoices.matches(&invoice, &labels));
+    // Every condition given has to hold
+    let newsletter = metadata("m2", "news@vendor.example", "Spring sale", &["INBOX"]);
+    assert!(!invoices.matches(&newsletter, &labels));
+    let other = metadata("m3", "billing@other.example", "Invoice", &["INBOX"]);
+    assert!(!invoices.matches(&other, &labels));
+
+    // Labels are matched by name or ID
+    let acme = rule(json!({ "name": "Acme", "labels": ["clients/acme"], "archive": true }));
+    let labelled = metadata("m4", "ann@acme.example", "Hello", &["INBOX", "Label_7"]);
+    assert!(acme.matches(&labelled, &labels));
+    assert!(!acme.matches(&invoice, &labels));
+    let inbox = rule(json!({ "name": "Inbox", "labels": ["INBOX"], "notify": true }));
+    assert!(inbox.matches(&invoice, &labels));
+
+    // Exact addresses don't match other senders at the domain
+    let boss = rule(json!({ "name": "Boss", "from": ["ceo@acme.example"], "notify": true }));
+    assert!(boss.matches(
+        &metadata("m5", "CEO <ceo@acme.example>", "Hi", &[]),
+        &labels
+    ));
+    assert!(!boss.matches(&labelled, &labels));
+}
+
+#[test]
+fn test_match_rules_combines_actions() {
+    let rules = vec![
+        rule(json!({ "name": "Invoices", "subject": ["invoice"], "label": "Finance" })),
+        rule(json!({
+            "name": "Vendor",
+            "from": ["@vendor.example"],
+            "label": "finance",
+            "archive": true,
+            "forward_to": "books@example.com",
+        })),
+        rule(json!({ "name": "Other", "from": ["@other.example"], "notify": true })),
+    ];
+    let message = metadata("m1", "billing@vendor.example", "Invoice", &["INBOX"]);
+    let rule_match = match_rules(&rules, &message, &labels()).unwrap();
+    assert_eq!(rule_match.rules, vec!["Invoices", "Vendor"]);
+    assert_eq!(rule_match.add_labels, vec!["Finance"]);
+    assert!(rule_match.archive);
+    assert_eq!(rule_match.forward_to, vec!["books@example.com"]);
+    assert!(!rule_match.notify);
+    assert_eq!(rule_match.status, "would_apply");
+
+    let unmatched = metadata("m2", "alice@example.com", "Lunch", &["INBOX"]);
+    assert!(match_rules(&rules, &unmatched, &labels()).is_none());
+
+    assert!(is_incoming(&message));
+    assert!(!is_incoming(&metadata(
+        "m3",
+        "me@example.com",
+        "Hi",
+        &["SENT"]
+    )));
+    assert!(!is_incoming(&metadata(
+        "m4",
+        "me@example.com",
+        "Hi",
+        &["DRAFT"]
+    )));
+    assert!(is_incoming(&metadata(
+        "m5",
+        "me@example.com",
+        "Hi",
+        &["SENT", "INBOX"]
+    )));
+}
+
+#[test]
+fn test_forward_draft() {
+    let invoice = EmailMessage {
+        to: Some("me@example.com".to_string()),
+        date: Some("Mon, 6 Jan 2025 09:00:00 +0000".to_string()),
+        body_text: Some("Your invoice is attached.".to_string()),
+        ..message("m1", "Billing <billing@vendor.example>", "Invoice #4821")
+    };
+    let draft = forward_draft(&invoice, "books@example.com");
+    assert_eq!(draft.to, "books@example.com");
+    assert_eq!(draft.subject, "Fwd: Invoice #4821");
+    assert!(draft
+        .body
+        .starts_with("---------- Forwarded message ---------\n"));
+    assert!(draft
+        .body
+        .contains("From: Billing <billing@vendor.example>\n"));
+    assert!(draft.body.contains("Subject: Invoice #4821\n"));
+    assert!(draft.body.ends_with("\n\nYour invoice is attached."));
+    assert!(draft.thread_id.is_none());
+
+    let html = EmailMessage {
+        body_html: Some("<p>Your <b>invoice</b> is attached.</p>".to_string()),
+        ..message("m2", "Billing <billing@vendor.example>", "FW: Invoice")
+    };
+    let draft = forward_draft(&html, "books@example.com");
+    assert_eq!(draft.subject, "FW: Invoice");
+    assert!(draft.body.contains("Your **invoice** is attached."));
+}
+
+#[tokio::test]
+async fn test_apply_match() {
+    let mut gmail = MockGmailApi::new()
+        .with_labels([("INBOX", "INBOX"), ("UNREAD", "UNREAD")])
+        .with_messages(vec![message(
+            "m1",
+            "Billing <billing@vendor.example>",
+            "Invoice #4821",
+        )]);
+    gmail.set_message_labels("m1", vec!["INBOX".to_string(), "UNREAD".to_string()]);
+    let rules = vec![rule(json!({
+        "name": "Vendor",
+        "from": ["@vendor.example"],
+        "label": "Finance",
+        "archive": true,
+        "forward_to": "books@example.com",
+    }))];
+    let message = metadata("m1", "billing@vendor.example", "Invoice #4821", &["INBOX"]);
+    let rule_match = match_rules(&rules, &message, &[]).unwrap();
+
+    apply_match(&gmail, &rule_match).await.unwrap();
+    let labels = gmail.message_labels()["m1"].clone();
+    assert!(!labels.contains(&"INBOX".to_string()));
+    assert!(labels.contains(&"UNREAD".to_string()));
+    assert_eq!(labels.len(), 2);
+    assert_eq!(gmail.sent().len(), 1);
+    assert_eq!(gmail.sent()[0].to, "books@example.com");
+    assert_eq!(gmail.sent()[0].subject, "Fwd: Invoice #4821");
+}
+
+#[test]
+fn test_mail_rule_store() {
+    let dir = tempdir().unwrap();
+    let store = MailRuleStore::new(dir.path().join("mail_rules.json"));
+    assert!(store.list().unwrap().is_empty());
+
+    std::fs::write(
+        store.path(),
+        json!({ "rules": [
+            { "name": " Invoices ", "subject": ["invoice", " "], "label": "Finance" },
+            { "name": "Boss", "from": ["ceo@example.com"], "notify": true },
+        ] })
+        .to_string(),
+    )
+    .unwrap();
+    let rules = store.list().unwrap();
+    assert_eq!(rules.len(), 2);
+    assert_eq!(rules[0].name, "Invoices");
+    assert_eq!(rules[0].subject, vec!["invoice"]);
+
+    for invalid in [
+        json!({ "name": "", "subject": ["invoice"], "archive": true }),
+        json!({ "name": "No conditions", "archive": true }),
+        json!({ "name": "No actions", "subject": ["invoice"] }),
+        json!({ "name": "Bad forward", "subject": ["invoice"], "forward_to": "not an address" }),
+    ] {
+        std::fs::write(store.path(), json!({ "rules": [invalid] }).to_string()).unwrap();
+        assert!(matches!(store.list(), Err(MailRuleError::InvalidRule(_))));
+    }
+
+    std::fs::write(store.path(), "not json").unwrap();
+    assert!(matches!(store.list(), Err(MailRuleError::StorageError(_))));
+
+    let history = RuleHistory::new(dir.path().join("state").join("mail_rules_state.json"));
+    assert!(history.history_id().is_none());
+    history.save("4242").unwrap();
+    assert_eq!(history.history_id().as_deref(), Some("4242"));
+}
+
+#[tokio::test]
+async fn test_run_rules_tool() {
+    let dir = tempdir().unwrap();
+    let rules_path = dir.path().join("mail_rules.json");
+    let state_path = dir.path().join("mail_rules_state.json");
+    std::env::set_var("MAIL_RULES_FILE", &rules_path);
+    std::env::set_var("MAIL_RULES_STATE_FILE", &state_path);
+    let (_server, client) = mock_client(None).await;
+
+    let error = call_tool(&client, "run_rules", json!({ "dry_run": true }))
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("No mail rules"));
+
+    std::fs::write(
+        &rules_path,
+        json!({ "rules": [
+            { "name": "Invoices", "from": ["@vendor.example"], "subject": ["invoice"], "label": "Finance", "archive": true },
+            { "name": "Alice", "from": ["alice@example.com"], "notify": true },
+        ] })
+        .to_string(),
+    )
+    .unwrap();
+
+    // The mock server ignores the query, so every mock message is checked
+    let report = call_tool(
+        &client,
+        "run_rules",
+        json!({ "query": "in:inbox", "dry_run": true }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(report["source"], "query");
+    assert_eq!(report["dry_run"], true);
+    assert_eq!(report["checked"], 3);
+    let matched = report["matched"].as_array().unwrap();
+    assert_eq!(matched.len(), 2);
+    let invoice = matched
+        .iter()
+        .find(|m| m["message_id"] == "mock-msg-002")
+        .unwrap();
+    assert_eq!(invoice["rules"], json!(["Invoices"]));
+    assert_eq!(invoice["add_labels"], json!(["Finance"]));
+    assert_eq!(invoice["archive"], true);
+    assert_eq!(invoice["status"], "would_apply");
+    let notifications = report["notifications"].as_array().unwrap();
+    assert_eq!(notifications.len(), 1);
+    assert_eq!(notifications[0]["message_id"], "mock-msg-001");
+
+    // A dry run over new mail doesn't record how far it got
+    let report = call_tool(&client, "run_rules", json!({ "dry_run": true }))
+        .await
+        .unwrap();
+    assert_eq!(report["source"], "new_mail");
+    assert!(!state_path.exists());
+
+    let report = call_tool(&client, "run_rules", json!({})).await.unwrap();
+    assert_eq!(report["dry_run"], false);
+    assert!(report["matched"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|m| m["status"] == "applied"));
+    assert!(state_path.exists());
+
+    std::fs::write(
+        &rules_path,
+        json!({ "rules": [{ "name": "Empty" }] }).to_string(),
+    )
+    .unwrap();
+    let error = call_tool(&client, "run_rules", json!({ "dry_run": true }))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.error_object().unwrap().data.as_ref().unwrap()["category"],
+        "invalid_input"
+    );
+
+    std::env::remove_var("MAIL_RULES_FILE");
+    std::env::remove_var("MAIL_RULES_STATE_FILE");
+}
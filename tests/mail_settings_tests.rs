@@ -0,0 +1,111 @@
+/// Mail Settings Tests Module
+///
+/// This module contains tests for the IMAP, POP and language settings, checking
+/// the notes explaining why mail apps may not sync and the get_mail_settings
+/// tool against the mock server.
+use mcp_gmailcal::gmail_api::{ImapSettings, PopSettings};
+use mcp_gmailcal::mail_settings::sync_notes;
+use serde_json::{json, Value};
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn imap(enabled: bool, expunge_behavior: &str, max_folder_size: u32) -> ImapSettings {
+    ImapSettings {
+        enabled,
+        auto_expunge: true,
+        expunge_behavior: Some(expunge_behavior.to_string()),
+        max_folder_size,
+    }
+}
+
+fn pop(access_window: &str, disposition: &str) -> PopSettings {
+    PopSettings {
+        access_window: Some(access_window.to_string()),
+        disposition: Some(disposition.to_string()),
+    }
+}
+
+#[test]
+fn test_sync_notes() {
+    // IMAP on with no limits and POP off only notes that POP is off
+    let notes = sync_notes(&imap(true, "archive", 0), &pop("disabled", "leaveInInbox"));
+    assert_eq!(notes.len(), 1);
+    assert!(notes[0].starts_with("POP is off"));
+
+    let notes = sync_notes(
+        &imap(false, "deleteForever", 1000),
+        &pop("allMail", "trash"),
+    );
+    assert_eq!(notes.len(), 2);
+    assert!(notes[0].starts_with("IMAP is off"));
+    assert!(notes[1].contains("moved to the trash"));
+
+    let notes = sync_notes(
+        &imap(true, "deleteForever", 1000),
+        &pop("fromNowOn", "archive"),
+    );
+    assert_eq!(notes.len(), 4);
+    assert!(notes[0].contains("latest 1000 messages"));
+    assert!(notes[1].contains("after it was turned on"));
+    assert!(notes[2].contains("deleted forever"));
+    assert!(notes[3].contains("archived"));
+}
+
+#[tokio::test]
+async fn test_get_mail_settings_tool() {
+    let dir = tempfile::tempdir().unwrap();
+    let settings = dir
+        .path()
+        .join("gmail/v1/users/shared@example.com/settings");
+    std::fs::create_dir_all(&settings).unwrap();
+    std::fs::write(
+        settings.join("imap.json"),
+        json!({ "enabled": false }).to_string(),
+    )
+    .unwrap();
+    std::fs::write(
+        settings.join("pop.json"),
+        json!({ "accessWindow": "allMail", "disposition": "leaveInInbox" }).to_string(),
+    )
+    .unwrap();
+    std::fs::write(
+        settings.join("language.json"),
+        json!({ "displayLanguage": "de" }).to_string(),
+    )
+    .unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    let result = call_tool(&client, "get_mail_settings", json!({}))
+        .await
+        .unwrap();
+    assert_eq!(result["mailbox"], "me");
+    assert_eq!(
+        result["imap"],
+        json!({
+            "enabled": true,
+            "autoExpunge": true,
+            "expungeBehavior": "archive",
+            "maxFolderSize": 0
+        })
+    );
+    assert_eq!(result["pop"]["accessWindow"], "disabled");
+    assert_eq!(result["language"]["displayLanguage"], "en");
+    assert_eq!(result["sync_notes"].as_array().unwrap().len(), 1);
+
+    let result = call_tool(
+        &client,
+        "get_mail_settings",
+        json!({ "mailbox": "shared@example.com" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["mailbox"], "shared@example.com");
+    assert_eq!(result["imap"]["enabled"], false);
+    assert_eq!(result["imap"]["expungeBehavior"], Value::Null);
+    assert_eq!(result["language"]["displayLanguage"], "de");
+    let notes = result["sync_notes"].as_array().unwrap();
+    assert_eq!(notes.len(), 1);
+    assert!(notes[0].as_str().unwrap().starts_with("IMAP is off"));
+}
@@ -0,0 +1,165 @@
+/// Mail Webhook Tests Module
+///
+/// This module contains tests for the mail webhooks, checking the formats
+/// posted, how new mail is found from the mailbox history, and delivery to
+/// a webhook.
+use mcp_gmailcal::gmail_api::MessageMetadata;
+use mcp_gmailcal::mail_rules::{match_rules, MailRule, RuleMatch};
+use mcp_gmailcal::mail_webhooks::{
+    deliver, payload, poll_new_mail, WebhookConfig, WebhookFormat, MAX_POLL_MESSAGES,
+};
+use mcp_gmailcal::test_util::{message, MockGmailApi};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+fn rules() -> Vec<MailRule> {
+    serde_json::from_value(json!([
+        { "name": "Boss", "from": ["ceo@example.com"], "notify": true },
+        { "name": "Invoices", "subject": ["invoice"], "label": "Finance" },
+    ]))
+    .unwrap()
+}
+
+fn boss_match() -> RuleMatch {
+    let metadata = MessageMetadata {
+        id: "m1".to_string(),
+        from: Some("CEO <ceo@example.com>".to_string()),
+        subject: Some("Q3 <draft> & numbers".to_string()),
+        label_ids: vec!["INBOX".to_string()],
+        ..Default::default()
+    };
+    match_rules(&rules(), &metadata, &[]).unwrap()
+}
+
+#[test]
+fn test_webhook_format() {
+    assert_eq!(WebhookFormat::parse(" Slack "), Some(WebhookFormat::Slack));
+    assert_eq!(WebhookFormat::parse("json"), Some(WebhookFormat::Generic));
+    assert_eq!(WebhookFormat::parse("teams"), None);
+    assert_eq!(
+        WebhookFormat::for_url("https://hooks.slack.com/services/T0/B0/x"),
+        WebhookFormat::Slack
+    );
+    assert_eq!(
+        WebhookFormat::for_url("https://automation.example.com/mail"),
+        WebhookFormat::Generic
+    );
+}
+
+#[test]
+fn test_payload() {
+    let matches = vec![boss_match()];
+
+    let slack = payload(WebhookFormat::Slack, &matches);
+    let text = slack["text"].as_str().unwrap();
+    assert!(text.starts_with("New email matching your mail rules:\n"));
+    assert!(
+        text.contains("<https://mail.google.com/mail/u/0/#all/m1|Q3 &lt;draft&gt; &amp; numbers>")
+    );
+    assert!(text.contains("from CEO &lt;ceo@example.com&gt; (Boss)"));
+
+    let generic = payload(WebhookFormat::Generic, &matches);
+    assert_eq!(generic["event"], "new_mail");
+    assert_eq!(generic["count"], 1);
+    assert_eq!(generic["messages"][0]["message_id"], "m1");
+    assert_eq!(generic["messages"][0]["rules"], json!(["Boss"]));
+    assert_eq!(
+        generic["messages"][0]["link"],
+        "https://mail.google.com/mail/u/0/#all/m1"
+    );
+}
+
+#[tokio::test]
+async fn test_poll_new_mail() {
+    let mut gmail = MockGmailApi::new();
+
+    // The first poll only notes where the history is
+    let first = poll_new_mail(&gmail, &rules(), None).await.unwrap();
+    assert!(first.matches.is_empty());
+
+    gmail.add_message(message("m1", "CEO <ceo@example.com>", "Catch up"));
+    gmail.add_message(message("m2", "billing@vendor.example", "Invoice"));
+    gmail.add_message(message("m3", "alice@example.com", "Lunch"));
+    gmail.set_message_labels("m1", vec!["INBOX".to_string()]);
+
+    // Only the notify rules are used
+    let polled = poll_new_mail(&gmail, &rules(), Some(&first.history_id))
+        .await
+        .unwrap();
+    assert_eq!(polled.matches.len(), 1);
+    assert_eq!(polled.matches[0].message_id, "m1");
+    assert_eq!(polled.matches[0].rules, vec!["Boss"]);
+    assert_ne!(polled.history_id, first.history_id);
+
+    let again = poll_new_mail(&gmail, &rules(), Some(&polled.history_id))
+        .await
+        .unwrap();
+    assert!(again.matches.is_empty());
+    assert_eq!(again.history_id, polled.history_id);
+
+    // Mail from before expired history isn't posted
+    gmail.add_message(message("m4", "ceo@example.com", "Again"));
+    gmail.expire_history();
+    let expired = poll_new_mail(&gmail, &rules(), Some(&polled.history_id))
+        .await
+        .unwrap();
+    assert!(expired.matches.is_empty());
+    assert_eq!(expired.history_id, gmail.history_id.to_string());
+}
+
+#[tokio::test]
+async fn test_poll_new_mail_past_the_batch_size() {
+    let mut gmail = MockGmailApi::new();
+    let first = poll_new_mail(&gmail, &rules(), None).await.unwrap();
+
+    // Every message added is looked at, however many there are
+    let count = MAX_POLL_MESSAGES + 50;
+    for i in 0..count {
+        let id = format!("m{}", i);
+        gmail.add_message(message(&id, "ceo@example.com", "Update"));
+        gmail.set_message_labels(&id, vec!["INBOX".to_string()]);
+    }
+    let polled = poll_new_mail(&gmail, &rules(), Some(&first.history_id))
+        .await
+        .unwrap();
+    assert_eq!(polled.matches.len(), count);
+    assert_eq!(
+        polled.matches[count - 1].message_id,
+        format!("m{}", count - 1)
+    );
+}
+
+#[tokio::test]
+async fn test_deliver() {
+    let mut server = mockito::Server::new_async().await;
+    let config = |url: String| WebhookConfig {
+        url,
+        format: WebhookFormat::Generic,
+        poll_interval: Duration::from_secs(60),
+    };
+    let client = reqwest::Client::new();
+    let matches = vec![boss_match()];
+
+    let expected: Value = payload(WebhookFormat::Generic, &matches);
+    let hook = server
+        .mock("POST", "/hook")
+        .match_body(mockito::Matcher::Json(expected))
+        .with_status(200)
+        .create_async()
+        .await;
+    deliver(&client, &config(format!("{}/hook", server.url())), &matches)
+        .await
+        .unwrap();
+    hook.assert_async().await;
+
+    let down = server
+        .mock("POST", "/down")
+        .with_status(503)
+        .create_async()
+        .await;
+    let error = deliver(&client, &config(format!("{}/down", server.url())), &matches)
+        .await
+        .unwrap_err();
+    assert!(error.contains("503"));
+    down.assert_async().await;
+}
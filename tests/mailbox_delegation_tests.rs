@@ -0,0 +1,112 @@
+/// Mailbox Delegation Tests Module
+///
+/// This module contains tests for reading delegated mailboxes, checking that
+/// the mailbox parameter addresses the API at the shared mailbox and that the
+/// delegate tools list and add delegates through the mock server.
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+#[tokio::test]
+async fn test_mailbox_tools() {
+    let dir = tempfile::tempdir().unwrap();
+    let shared = dir.path().join("gmail/v1/users/shared@example.com");
+    std::fs::create_dir_all(shared.join("settings")).unwrap();
+    std::fs::write(
+        shared.join("labels.json"),
+        json!({ "labels": [{ "id": "Label_9", "name": "Support", "type": "user" }] }).to_string(),
+    )
+    .unwrap();
+    std::fs::write(
+        shared.join("settings/delegates.json"),
+        json!({
+            "delegates": [{
+                "delegateEmail": "alice@example.com",
+                "verificationStatus": "accepted"
+            }]
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    // The shared mailbox's own labels are listed
+    let labels = call_tool(
+        &client,
+        "list_labels",
+        json!({ "mailbox": "shared@example.com" }),
+    )
+    .await
+    .unwrap();
+    let names: Vec<&str> = labels["labels"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|label| label["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["Support"]);
+
+    // "me" is the account's own mailbox
+    let labels = call_tool(&client, "list_labels", json!({ "mailbox": "me" }))
+        .await
+        .unwrap();
+    assert!(labels["labels"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|label| label["id"] == "INBOX"));
+
+    assert!(call_tool(
+        &client,
+        "list_labels",
+        json!({ "mailbox": "a@example.com, b@example.com" }),
+    )
+    .await
+    .is_err());
+    assert!(call_tool(
+        &client,
+        "list_labels",
+        json!({ "mailbox": "not an address" })
+    )
+    .await
+    .is_err());
+
+    let delegates = call_tool(
+        &client,
+        "list_delegates",
+        json!({ "mailbox": "shared@example.com" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(delegates["mailbox"], "shared@example.com");
+    assert_eq!(delegates["count"], 1);
+    assert_eq!(
+        delegates["delegates"][0]["delegateEmail"],
+        "alice@example.com"
+    );
+    assert_eq!(delegates["delegates"][0]["verificationStatus"], "accepted");
+
+    let added = call_tool(
+        &client,
+        "add_delegate",
+        json!({
+            "delegate_email": "Bob <bob@example.com>",
+            "mailbox": "shared@example.com"
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(added["status"], "success");
+    assert_eq!(added["mailbox"], "shared@example.com");
+    assert_eq!(added["delegate"]["delegateEmail"], "bob@example.com");
+
+    assert!(call_tool(
+        &client,
+        "add_delegate",
+        json!({ "delegate_email": "nobody" }),
+    )
+    .await
+    .is_err());
+}
@@ -43,7 +43,7 @@ fn test_cli_parsing() {
     // Test auth command
     let args = vec!["gmail-mcp", "auth"];
     let cli = Cli::try_parse_from(args).unwrap();
-    assert!(matches!(cli.command, Some(Commands::Auth)));
+    assert!(matches!(cli.command, Some(Commands::Auth { ref scopes }) if scopes.is_empty()));
     
     // Test test command
     let args = vec!["gmail-mcp", "test"];
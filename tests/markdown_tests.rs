@@ -0,0 +1,179 @@
+/// Markdown Tests Module
+///
+/// This module contains tests for rendering emails as Markdown, and for the
+/// render_email_markdown tool against the mock server.
+use mcp_attr::schema::CallToolRequestParams;
+use mcp_gmailcal::attachments::{AttachmentInfo, InlineImage};
+use mcp_gmailcal::gmail_api::EmailMessage;
+use mcp_gmailcal::markdown::{html_to_markdown, render_email};
+use mcp_gmailcal::test_util::message;
+use serde_json::{json, Value};
+
+mod common;
+use common::{call_tool, mock_client};
+
+#[test]
+fn test_html_to_markdown() {
+    let html = r#"<html><head><style>p { color: red }</style></head><body>
+<h2>Agenda</h2>
+<p>Hi   <b>Bob</b>,<br>see the <a href="https://docs.example/plan?a=1&amp;b=2">plan</a>
+and <a href="javascript:alert(1)">this</a>.</p>
+<!-- a comment -->
+<ul><li>One</li><li><i>Two</i></li></ul>
+<img src="https://cdn.example/chart.png" alt="Chart">
+<img src="cid:logo@example" alt="">
+<script>alert(1)</script>
+<p>1 &lt; 2 &amp; 3 &#62; 2</p>
+</body></html>"#;
+    assert_eq!(
+        html_to_markdown(html),
+        "## Agenda\n\n\
+         Hi **Bob**,\n\
+         see the [plan](https://docs.example/plan?a=1&b=2) and this.\n\n\
+         - One\n\
+         - _Two_\n\n\
+         [Image: Chart](https://cdn.example/chart.png) [Image](cid:logo@example)\n\n\
+         1 < 2 & 3 > 2"
+    );
+
+    assert_eq!(html_to_markdown("<div> </div>"), "");
+}
+
+#[test]
+fn test_render_email() {
+    let email = EmailMessage {
+        to: Some("bob@example.com".to_string()),
+        date: Some("Mon, 6 Oct 2025 09:00:00 +0000".to_string()),
+        rfc_message_id: Some("<CAF123@mail.example.com>".to_string()),
+        ..message("msg-1", "Alice <alice@example.com>", "Q3 | planning")
+    };
+    let message = EmailMessage {
+        body_text: Some("Hello\r\nthere".to_string()),
+        inline_images: vec![InlineImage {
+            content_id: "logo@example".to_string(),
+            filename: Some("logo.png".to_string()),
+            mime_type: "image/png".to_string(),
+            size: 2048,
+            embedded: false,
+            attachment_id: Some("att-logo".to_string()),
+        }],
+        ..email.clone()
+    };
+    let attachments = vec![
+        AttachmentInfo {
+            attachment_id: "att-pdf".to_string(),
+            filename: "plan.pdf".to_string(),
+            mime_type: "application/pdf".to_string(),
+            size: 3 * 1024 * 1024,
+        },
+        AttachmentInfo {
+            attachment_id: "att-logo".to_string(),
+            filename: "logo.png".to_string(),
+            mime_type: "image/png".to_string(),
+            size: 2048,
+        },
+    ];
+
+    assert_eq!(
+        render_email(&message, &attachments),
+        "# Q3 | planning\n\n\
+         | Field | Value |\n\
+         | --- | --- |\n\
+         | From | Alice <alice@example.com> |\n\
+         | To | bob@example.com |\n\
+         | Date | Mon, 6 Oct 2025 09:00:00 +0000 |\n\
+         | Message-ID | <CAF123@mail.example.com> |\n\n\
+         Hello\nthere\n\n\
+         ## Attachments\n\n\
+         - plan.pdf (application/pdf, 3.0 MB) — attachment ID `att-pdf`\n\n\
+         ## Inline images\n\n\
+         - [logo.png](cid:logo@example) (image/png, 2.0 KB) — attachment ID `att-logo`\n"
+    );
+
+    // The HTML body is preferred, and table cells are escaped
+    let message = EmailMessage {
+        subject: None,
+        to: Some("a@example.com | b@example.com".to_string()),
+        body_text: Some("plain".to_string()),
+        body_html: Some("<p>rich</p>".to_string()),
+        ..email.clone()
+    };
+    let markdown = render_email(&message, &[]);
+    assert!(markdown.starts_with("# (no subject)\n"));
+    assert!(markdown.contains("| To | a@example.com \\| b@example.com |\n"));
+    assert!(markdown.ends_with("\n\nrich\n"));
+    assert!(!markdown.contains("## Attachments"));
+
+    let message = EmailMessage {
+        is_encrypted: true,
+        ..email
+    };
+    assert!(render_email(&message, &[])
+        .ends_with("_This message is encrypted, so its body is not shown._\n"));
+}
+
+fn encode(text: &str) -> String {
+    base64::encode_config(text.as_bytes(), base64::URL_SAFE)
+}
+
+fn message_with_attachment() -> Value {
+    json!({
+        "id": "msg-render",
+        "threadId": "thread-render",
+        "labelIds": ["INBOX"],
+        "snippet": "Notes attached",
+        "payload": {
+            "mimeType": "multipart/mixed",
+            "headers": [
+                { "name": "From", "value": "Alice <alice@example.com>" },
+                { "name": "To", "value": "bob@example.com" },
+                { "name": "Subject", "value": "Meeting notes" }
+            ],
+            "parts": [
+                { "mimeType": "text/html", "body": { "data": encode("<p>Notes <b>attached</b>.</p>") } },
+                {
+                    "mimeType": "application/pdf",
+                    "filename": "notes.pdf",
+                    "body": { "attachmentId": "att-notes", "size": 512 }
+                }
+            ]
+        }
+    })
+}
+
+#[tokio::test]
+async fn test_render_email_markdown_tool() {
+    let dir = tempfile::tempdir().unwrap();
+    let messages = dir.path().join("gmail/v1/users/me/messages");
+    std::fs::create_dir_all(&messages).unwrap();
+    std::fs::write(
+        messages.join("msg-render.json"),
+        message_with_attachment().to_string(),
+    )
+    .unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    let rendered = call_tool(
+        &client,
+        "render_email_markdown",
+        json!({ "message_id": "msg-render" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(rendered["message_id"], "msg-render");
+    assert_eq!(rendered["subject"], "Meeting notes");
+    let markdown = rendered["markdown"].as_str().unwrap();
+    assert!(markdown.starts_with("# Meeting notes\n"));
+    assert!(markdown.contains("| From | Alice <alice@example.com> |"));
+    assert!(markdown.contains("Notes **attached**."));
+    assert!(markdown.contains("- notes.pdf (application/pdf, 512 B) — attachment ID `att-notes`"));
+
+    assert!(client
+        .tools_call(CallToolRequestParams {
+            name: "render_email_markdown".to_string(),
+            arguments: json!({ "message_id": "" }).as_object().cloned(),
+        })
+        .await
+        .is_err());
+}
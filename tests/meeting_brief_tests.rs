@@ -0,0 +1,177 @@
+/// Meeting Brief Tests Module
+///
+/// This module contains tests for preparing meeting briefs, and for the
+/// meeting_brief tool against the mock server.
+use chrono::{Duration, TimeZone, Utc};
+use mcp_gmailcal::calendar_api::{Attendee, CalendarEvent, EventOrganizer};
+use mcp_gmailcal::meeting_brief::{attendee_query, other_attendees, MeetingBrief};
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn event(description: Option<&str>) -> CalendarEvent {
+    let start = Utc.with_ymd_and_hms(2026, 10, 20, 16, 0, 0).unwrap();
+    CalendarEvent {
+        id: Some("event-brief".to_string()),
+        summary: "Budget review".to_string(),
+        description: description.map(String::from),
+        start_time: start,
+        end_time: start + Duration::hours(1),
+        attendees: ["Demo@example.com", "bob@example.com", "alice@example.com"]
+            .iter()
+            .map(|email| Attendee {
+                email: email.to_string(),
+                display_name: None,
+                response_status: Some("accepted".to_string()),
+                optional: None,
+                time_zone: None,
+                self_: None,
+            })
+            .collect(),
+        organizer: Some(EventOrganizer {
+            email: "Alice@example.com".to_string(),
+            display_name: Some("Alice Example".to_string()),
+            self_: None,
+        }),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_other_attendees() {
+    let event = event(None);
+    // The organizer comes first, the user is left out
+    let attendees = other_attendees(&event, "demo@example.com");
+    assert_eq!(attendees, vec!["alice@example.com", "bob@example.com"]);
+    assert_eq!(
+        attendee_query(&attendees).as_deref(),
+        Some(
+            "from:alice@example.com OR to:alice@example.com OR \
+             from:bob@example.com OR to:bob@example.com"
+        )
+    );
+    assert_eq!(attendee_query(&[]), None);
+}
+
+#[test]
+fn test_meeting_brief_agenda_links() {
+    let event = event(Some(
+        "Agenda: <a href=\"https://docs.google.com/document/d/abc\">Q4 agenda</a>\n\
+         Venue details at https://example.com/venue",
+    ));
+    let now = event.start_time - Duration::minutes(90);
+    let brief = MeetingBrief::new(&event, "demo@example.com", &[], &[], now);
+    assert_eq!(brief.starts_in_minutes, 90);
+    assert_eq!(brief.agenda_documents.len(), 1);
+    assert_eq!(
+        brief.agenda_documents[0].url,
+        "https://docs.google.com/document/d/abc"
+    );
+    assert_eq!(
+        brief.agenda_documents[0].title.as_deref(),
+        Some("Q4 agenda")
+    );
+    assert_eq!(brief.agenda_documents[0].source, "description");
+
+    let alice = &brief.attendees[0];
+    assert!(alice.organizer);
+    assert_eq!(alice.display_name.as_deref(), Some("Alice Example"));
+    assert!(!brief.attendees[1].organizer);
+    assert!(brief.recent_threads.is_empty());
+}
+
+#[tokio::test]
+async fn test_meeting_brief_tool() {
+    let dir = tempfile::tempdir().unwrap();
+    let events = dir.path().join("calendar/v3/calendars/primary/events");
+    std::fs::create_dir_all(&events).unwrap();
+    let start = Utc::now() + Duration::days(2);
+    let event = json!({
+        "id": "event-brief",
+        "summary": "Q4 planning",
+        "description": "Agenda: https://docs.google.com/document/d/agenda-q4/edit",
+        "start": { "dateTime": start.to_rfc3339() },
+        "end": { "dateTime": (start + Duration::hours(1)).to_rfc3339() },
+        "organizer": { "email": "alice@example.com", "displayName": "Alice Example" },
+        "attendees": [
+            { "email": "demo@example.com", "responseStatus": "accepted" },
+            { "email": "alice@example.com", "responseStatus": "accepted" },
+            { "email": "bob@example.com", "responseStatus": "tentative", "optional": true }
+        ],
+        "conferenceData": {
+            "entryPoints": [
+                { "entryPointType": "video", "uri": "https://meet.google.com/abc-defg-hij" }
+            ]
+        },
+        "attachments": [{
+            "fileUrl": "https://drive.google.com/file/d/budget-q4/view",
+            "title": "Budget Q4.xlsx",
+            "fileId": "budget-q4",
+            "mimeType": "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+        }]
+    });
+    std::fs::write(events.join("event-brief.json"), event.to_string()).unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    let brief = call_tool(
+        &client,
+        "meeting_brief",
+        json!({ "event_id": "event-brief" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(brief["summary"], "Q4 planning");
+    assert_eq!(
+        brief["conference_link"],
+        "https://meet.google.com/abc-defg-hij"
+    );
+    assert!(brief["starts_in_minutes"].as_i64().unwrap() > 0);
+
+    let attendees = brief["attendees"].as_array().unwrap();
+    assert_eq!(attendees.len(), 2);
+    assert_eq!(attendees[0]["email"], "alice@example.com");
+    assert_eq!(attendees[0]["organizer"], true);
+    assert_eq!(attendees[0]["contact"]["resource_name"], "people/c001");
+    assert_eq!(attendees[1]["email"], "bob@example.com");
+    assert_eq!(attendees[1]["optional"], true);
+    assert_eq!(attendees[1]["response_status"], "tentative");
+    assert_eq!(attendees[1]["contact"]["resource_name"], "people/c002");
+
+    assert_eq!(
+        brief["agenda_documents"],
+        json!([
+            {
+                "title": "Budget Q4.xlsx",
+                "url": "https://drive.google.com/file/d/budget-q4/view",
+                "source": "attachment"
+            },
+            {
+                "title": null,
+                "url": "https://docs.google.com/document/d/agenda-q4/edit",
+                "source": "description"
+            }
+        ])
+    );
+
+    // The mock lists every message, and only the billing one involves
+    // neither attendee
+    let threads = brief["recent_threads"].as_array().unwrap();
+    assert_eq!(threads.len(), 1);
+    assert_eq!(threads[0]["thread_id"], "mock-thread-001");
+    assert_eq!(threads[0]["message_count"], 2);
+    assert_eq!(
+        threads[0]["attendees"],
+        json!(["alice@example.com", "bob@example.com"])
+    );
+
+    let error = call_tool(
+        &client,
+        "meeting_brief",
+        json!({ "event_id": "no-such-event" }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error.error_object().is_some());
+}
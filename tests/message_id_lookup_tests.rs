@@ -0,0 +1,81 @@
+/// Message-ID Lookup Tests Module
+///
+/// This module contains tests for finding messages by their RFC 822
+/// Message-ID with the find_by_rfc_message_id tool against the mock server.
+use mcp_gmailcal::gmail_api::bare_message_id;
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+#[test]
+fn test_bare_message_id() {
+    assert_eq!(
+        bare_message_id(" <CAF123@mail.example.com> "),
+        "CAF123@mail.example.com"
+    );
+    assert_eq!(
+        bare_message_id("CAF123@mail.example.com"),
+        "CAF123@mail.example.com"
+    );
+    assert_eq!(bare_message_id("<>"), "");
+}
+
+#[tokio::test]
+async fn test_find_by_rfc_message_id_tool() {
+    let (_server, client) = mock_client(None).await;
+
+    // The mock lists every message whatever the query, so only the one with
+    // the Message-ID may come back
+    let result = call_tool(
+        &client,
+        "find_by_rfc_message_id",
+        json!({ "message_id_header": "<CAF-q4-planning-001@mail.example.com>" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        result["rfc_message_id"],
+        "<CAF-q4-planning-001@mail.example.com>"
+    );
+    assert_eq!(result["count"], 1);
+    assert_eq!(result["messages"][0]["id"], "mock-msg-001");
+    assert_eq!(result["messages"][0]["thread_id"], "mock-thread-001");
+
+    // Brackets are optional
+    let result = call_tool(
+        &client,
+        "find_by_rfc_message_id",
+        json!({ "message_id_header": "billing-2026-10-002@billing.example.com" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        result["rfc_message_id"],
+        "<billing-2026-10-002@billing.example.com>"
+    );
+    assert_eq!(result["messages"][0]["id"], "mock-msg-002");
+
+    let result = call_tool(
+        &client,
+        "find_by_rfc_message_id",
+        json!({ "message_id_header": "<no-such-message@example.com>" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["count"], 0);
+    assert_eq!(result["messages"], json!([]));
+
+    let error = call_tool(
+        &client,
+        "find_by_rfc_message_id",
+        json!({ "message_id_header": " <> " }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("A Message-ID is required"));
+}
@@ -0,0 +1,91 @@
+/// Message Size Tests Module
+///
+/// This module contains tests for Gmail's message size limit: the room left
+/// for attachments beside a body, and drafts and sends refused before upload
+/// with an explanation when they are too large.
+use mcp_gmailcal::attachments::OutgoingAttachment;
+use mcp_gmailcal::config::Config;
+use mcp_gmailcal::gmail_api::{
+    attachment_budget, encode_raw_message_with_attachments, GmailService, MAX_MESSAGE_BYTES,
+};
+use mcp_gmailcal::utils::error_data;
+use mcp_gmailcal::{DraftEmail, GmailApiError};
+
+mod common;
+use common::mock_server;
+
+fn draft(body: &str) -> DraftEmail {
+    DraftEmail {
+        to: "alice@example.com".to_string(),
+        subject: "Files".to_string(),
+        body: body.to_string(),
+        cc: None,
+        bcc: None,
+        thread_id: None,
+        in_reply_to: None,
+        references: None,
+        priority: None,
+        read_receipt_to: None,
+    }
+}
+
+fn attachment(filename: &str, bytes: usize) -> OutgoingAttachment {
+    OutgoingAttachment {
+        filename: filename.to_string(),
+        mime_type: "application/octet-stream".to_string(),
+        data: vec![7; bytes],
+    }
+}
+
+#[test]
+fn test_attachment_budget() {
+    let budget = attachment_budget("");
+    assert!(budget > 18 * 1024 * 1024 && budget < MAX_MESSAGE_BYTES as u64 * 3 / 4);
+    assert!(attachment_budget(&"x".repeat(5 * 1024 * 1024)) < budget - 3 * 1024 * 1024);
+    assert_eq!(attachment_budget(&"x".repeat(MAX_MESSAGE_BYTES)), 0);
+
+    // Attachments within the budget fit in the message once encoded
+    let body = "See attached.";
+    let files = [attachment("data.bin", attachment_budget(body) as usize)];
+    let encoded = encode_raw_message_with_attachments(&draft(body), &files);
+    assert!(encoded.len() / 4 * 3 <= MAX_MESSAGE_BYTES);
+}
+
+#[tokio::test]
+async fn test_large_messages_are_refused_before_upload() {
+    let _server = mock_server(None).await;
+    let gmail = GmailService::new(&Config::from_env().unwrap()).unwrap();
+
+    let files = [
+        attachment("notes.txt", 1024),
+        attachment("recording.mp4", 15 * 1024 * 1024),
+        attachment("slides.pdf", 6 * 1024 * 1024),
+    ];
+    let error = gmail
+        .create_draft_with_attachments(&draft("See attached."), &files)
+        .await
+        .unwrap_err();
+    let GmailApiError::MessageTooLarge(explanation) = &error else {
+        panic!("expected a message too large error, got {:?}", error);
+    };
+    assert!(explanation.contains("over Gmail's 25 MB limit"));
+    assert!(explanation.contains(
+        "Largest attachments: recording.mp4 (15.0 MB), slides.pdf (6.0 MB), notes.txt (0.0 MB)."
+    ));
+    assert!(explanation.contains("drive_file_ids"));
+
+    let data = serde_json::to_value(error_data(error)).unwrap();
+    assert_eq!(data["category"], "message_too_large");
+
+    let error = gmail
+        .send_message_with_attachments(&draft("See attached."), &files)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, GmailApiError::MessageTooLarge(_)));
+
+    // Messages within the limit are uploaded
+    let result = gmail
+        .create_draft_with_attachments(&draft("See attached."), &files[..2])
+        .await;
+    assert!(result.is_ok(), "{:?}", result);
+}
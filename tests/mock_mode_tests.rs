@@ -0,0 +1,137 @@
+/// Mock Mode Tests Module
+///
+/// This module contains tests for the fixture-backed mock mode, covering
+/// fixture resolution, CLI wiring and an end-to-end run of the real API
+/// clients against the local mock server.
+use axum::http::Method;
+use clap::Parser;
+use mcp_gmailcal::cli::Cli;
+use mcp_gmailcal::mock::{fixture_name, FixtureStore, MockServer};
+use mcp_gmailcal::{CalendarClient, Config, DraftEmail, GmailService, PeopleClient};
+use std::path::PathBuf;
+
+mod common;
+use common::mock_server;
+
+#[test]
+fn test_mock_flag_parsing() {
+    let cli = Cli::try_parse_from(["gmail-mcp", "repl", "--mock"]).unwrap();
+    assert!(cli.mock);
+    assert_eq!(cli.mock_fixtures, None);
+
+    let cli =
+        Cli::try_parse_from(["gmail-mcp", "--mock", "--mock-fixtures", "/tmp/fixtures"]).unwrap();
+    assert_eq!(cli.mock_fixtures, Some(PathBuf::from("/tmp/fixtures")));
+
+    // A fixture directory without --mock is rejected
+    assert!(Cli::try_parse_from(["gmail-mcp", "--mock-fixtures", "/tmp/fixtures"]).is_err());
+}
+
+#[test]
+fn test_fixture_name_mapping() {
+    assert_eq!(
+        fixture_name(&Method::GET, "/gmail/v1/users/me/messages"),
+        "gmail/v1/users/me/messages.json"
+    );
+    assert_eq!(
+        fixture_name(&Method::GET, "/people/v1/people:searchContacts"),
+        "people/v1/people_searchContacts.json"
+    );
+    assert_eq!(
+        fixture_name(&Method::POST, "/gmail/v1/users/me/messages/send"),
+        "gmail/v1/users/me/messages/send.post.json"
+    );
+}
+
+#[test]
+fn test_fixture_store_prefers_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("gmail/v1/users/me")).unwrap();
+    std::fs::write(
+        dir.path().join("gmail/v1/users/me/labels.json"),
+        r#"{"labels": []}"#,
+    )
+    .unwrap();
+
+    let store = FixtureStore::new(Some(dir.path().to_path_buf()));
+    assert_eq!(
+        store.get("gmail/v1/users/me/labels.json").unwrap(),
+        r#"{"labels": []}"#
+    );
+
+    // Files missing from the directory fall back to the built-in fixtures
+    assert!(store.get("gmail/v1/users/me/profile.json").is_some());
+    assert!(store.get("gmail/v1/users/me/unknown.json").is_none());
+}
+
+#[tokio::test]
+async fn test_mock_server_missing_fixture_directory() {
+    let result = MockServer::start(Some(PathBuf::from("/nonexistent/fixtures"))).await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_clients_against_mock_server() {
+    let _server = mock_server(None).await;
+    let config = Config::from_env().unwrap();
+
+    // Gmail
+    let gmail = GmailService::new(&config).unwrap();
+    let messages = gmail.list_messages(10, Some("is:unread")).await.unwrap();
+    assert!(!messages.is_empty());
+    assert_eq!(messages[0].id, "mock-msg-001");
+    assert_eq!(
+        messages[0].subject.as_deref(),
+        Some("Quarterly planning meeting")
+    );
+    assert!(messages[0]
+        .body_text
+        .as_deref()
+        .unwrap()
+        .contains("Thursday at 2pm"));
+    assert!(messages[0].body_html.is_some());
+
+    let (email, total) = gmail.check_connection().await.unwrap();
+    assert_eq!(email, "demo@example.com");
+    assert_eq!(total, 3);
+
+    let draft = DraftEmail {
+        to: "someone@example.com".to_string(),
+        subject: "Hello".to_string(),
+        body: "Hi".to_string(),
+        cc: None,
+        bcc: None,
+        thread_id: None,
+        in_reply_to: None,
+        references: None,
+        priority: None,
+        read_receipt_to: None,
+    };
+    assert!(gmail
+        .send_message(&draft)
+        .await
+        .unwrap()
+        .starts_with("mock-"));
+
+    // Missing fixtures surface as API errors
+    assert!(gmail.get_message_details("does-not-exist").await.is_err());
+
+    // Calendar
+    let calendar = CalendarClient::new(&config);
+    let calendars = calendar.list_calendars().await.unwrap();
+    assert_eq!(calendars.calendars.len(), 2);
+    let events = calendar
+        .list_events("primary", Some(10), None, None)
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].summary, "Quarterly planning");
+    assert_eq!(events[0].attendees.len(), 2);
+
+    // People
+    let people = PeopleClient::new(&config);
+    let contacts = people.search_contacts("Example", None).await.unwrap();
+    assert_eq!(contacts.contacts.len(), 2);
+    let contact = people.get_contact("people/c001").await.unwrap();
+    assert_eq!(contact.name.unwrap().display_name, "Alice Example");
+}
@@ -0,0 +1,329 @@
+/// My Availability Tests Module
+///
+/// This module contains tests for the merged availability view, checking
+/// the periods covered, the calendars read, how busy time from several
+/// calendars is merged into free windows, the text for emails, and the
+/// my_availability tool against the mock server.
+use chrono::{DateTime, Duration, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use mcp_gmailcal::calendar_api::{BusyPeriod, CalendarBusy, CalendarInfo};
+use mcp_gmailcal::my_availability::{
+    merge_busy, parse_range, select_calendars, working_windows, MyAvailability,
+};
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+const NEW_YORK: Tz = chrono_tz::America::New_York;
+
+fn at(text: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(text)
+        .unwrap()
+        .with_timezone(&Utc)
+}
+
+fn busy(start: &str, end: &str) -> BusyPeriod {
+    BusyPeriod {
+        start: at(start),
+        end: at(end),
+    }
+}
+
+fn calendar(id: &str, summary: &str, primary: bool) -> CalendarInfo {
+    CalendarInfo {
+        id: id.to_string(),
+        summary: summary.to_string(),
+        description: None,
+        primary: primary.then_some(true),
+    }
+}
+
+fn hours(start: u32, end: u32) -> (NaiveTime, NaiveTime) {
+    (
+        NaiveTime::from_hms_opt(start, 0, 0).unwrap(),
+        NaiveTime::from_hms_opt(end, 0, 0).unwrap(),
+    )
+}
+
+#[test]
+fn test_parse_range() {
+    // Thursday 15 October 2026, 10:00 in New York
+    let now = at("2026-10-15T14:00:00Z");
+
+    assert_eq!(
+        parse_range("3d", now, NEW_YORK).unwrap(),
+        (now, at("2026-10-18T04:00:00Z"))
+    );
+    assert_eq!(
+        parse_range("1w", now, NEW_YORK).unwrap(),
+        (now, at("2026-10-22T04:00:00Z"))
+    );
+    assert_eq!(
+        parse_range("today", now, NEW_YORK).unwrap(),
+        (now, at("2026-10-16T04:00:00Z"))
+    );
+    assert_eq!(
+        parse_range("Tomorrow", now, NEW_YORK).unwrap(),
+        (at("2026-10-16T04:00:00Z"), at("2026-10-17T04:00:00Z"))
+    );
+    // The end is read from the start and covered to the end of its day
+    assert_eq!(
+        parse_range("monday to friday", now, NEW_YORK).unwrap(),
+        (at("2026-10-19T04:00:00Z"), at("2026-10-24T04:00:00Z"))
+    );
+    assert_eq!(
+        parse_range("tomorrow 1pm until 3pm", now, NEW_YORK).unwrap(),
+        (at("2026-10-16T17:00:00Z"), at("2026-10-16T19:00:00Z"))
+    );
+
+    for invalid in [
+        "0d",
+        "60d",
+        "99999999999d",
+        "99999999999999999999w",
+        "yesterday",
+        "someday",
+    ] {
+        assert!(parse_range(invalid, now, NEW_YORK).is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn test_select_calendars() {
+    let list = vec![
+        calendar("team@example.com", "Team", false),
+        calendar("me@example.com", "Me", true),
+        calendar(
+            "en.usa#holiday@group.v.calendar.google.com",
+            "Holidays in United States",
+            false,
+        ),
+        calendar(
+            "addressbook#contacts@group.v.calendar.google.com",
+            "Birthdays",
+            false,
+        ),
+    ];
+
+    assert_eq!(
+        select_calendars(&list, &[]).unwrap(),
+        vec!["me@example.com", "team@example.com"]
+    );
+    assert_eq!(
+        select_calendars(&list, &["team".to_string(), "Primary".to_string()]).unwrap(),
+        vec!["team@example.com", "me@example.com"]
+    );
+    assert!(select_calendars(&list, &["Personal".to_string()]).is_err());
+    // "primary" is understood without the calendar list saying which it is
+    assert_eq!(
+        select_calendars(&[], &["primary".to_string()]).unwrap(),
+        vec!["primary"]
+    );
+}
+
+#[test]
+fn test_merge_busy() {
+    let merged = merge_busy(&[
+        busy("2026-10-16T15:00:00Z", "2026-10-16T16:00:00Z"),
+        busy("2026-10-16T13:00:00Z", "2026-10-16T14:00:00Z"),
+        busy("2026-10-16T15:30:00Z", "2026-10-16T17:00:00Z"),
+        busy("2026-10-16T14:00:00Z", "2026-10-16T14:30:00Z"),
+        busy("2026-10-16T15:10:00Z", "2026-10-16T15:20:00Z"),
+    ]);
+    assert_eq!(
+        merged,
+        vec![
+            busy("2026-10-16T13:00:00Z", "2026-10-16T14:30:00Z"),
+            busy("2026-10-16T15:00:00Z", "2026-10-16T17:00:00Z"),
+        ]
+    );
+}
+
+#[test]
+fn test_working_windows() {
+    // Thursday 10:00 to Monday's end in New York
+    let range = (at("2026-10-15T14:00:00Z"), at("2026-10-20T04:00:00Z"));
+    let windows = working_windows(range, NEW_YORK, hours(9, 17), false);
+    assert_eq!(
+        windows,
+        vec![
+            (at("2026-10-15T14:00:00Z"), at("2026-10-15T21:00:00Z")),
+            (at("2026-10-16T13:00:00Z"), at("2026-10-16T21:00:00Z")),
+            (at("2026-10-19T13:00:00Z"), at("2026-10-19T21:00:00Z")),
+        ]
+    );
+    assert_eq!(
+        working_windows(range, NEW_YORK, hours(9, 17), true).len(),
+        5
+    );
+}
+
+#[test]
+fn test_my_availability() {
+    // Friday 16 October in New York
+    let range = (at("2026-10-16T04:00:00Z"), at("2026-10-17T04:00:00Z"));
+    let list = vec![
+        calendar("me@example.com", "Me", true),
+        calendar("team@example.com", "Team", false),
+    ];
+    let free_busy = vec![
+        CalendarBusy {
+            calendar_id: "me@example.com".to_string(),
+            busy: vec![busy("2026-10-16T14:00:00Z", "2026-10-16T15:00:00Z")],
+            error: None,
+        },
+        CalendarBusy {
+            calendar_id: "team@example.com".to_string(),
+            busy: vec![
+                busy("2026-10-16T14:30:00Z", "2026-10-16T16:10:00Z"),
+                // Leaves only 20 minutes before the next meeting
+                busy("2026-10-16T16:30:00Z", "2026-10-16T18:00:00Z"),
+            ],
+            error: None,
+        },
+        CalendarBusy {
+            calendar_id: "shared@example.com".to_string(),
+            busy: Vec::new(),
+            error: Some("notFound".to_string()),
+        },
+    ];
+
+    let availability = MyAvailability::new(range, NEW_YORK, hours(9, 17), false, &list, &free_busy);
+    assert_eq!(availability.time_zone, "America/New_York");
+    assert_eq!(availability.calendars.len(), 3);
+    assert_eq!(availability.calendars[1].summary.as_deref(), Some("Team"));
+    assert_eq!(availability.calendars[1].busy_periods, 2);
+    assert_eq!(availability.calendars[2].error.as_deref(), Some("notFound"));
+    assert_eq!(availability.busy.len(), 2);
+
+    let free: Vec<(String, String, i64)> = availability
+        .free
+        .iter()
+        .map(|window| {
+            (
+                window.start.to_rfc3339(),
+                window.end.to_rfc3339(),
+                window.minutes,
+            )
+        })
+        .collect();
+    assert_eq!(
+        free,
+        vec![
+            (
+                "2026-10-16T09:00:00-04:00".to_string(),
+                "2026-10-16T10:00:00-04:00".to_string(),
+                60
+            ),
+            (
+                "2026-10-16T14:00:00-04:00".to_string(),
+                "2026-10-16T17:00:00-04:00".to_string(),
+                180
+            ),
+        ]
+    );
+    assert_eq!(availability.total_free_minutes, 240);
+    assert_eq!(
+        availability.text,
+        "I'm free at these times (America/New_York):\n- Fri 16 Oct: 09:00–10:00, 14:00–17:00"
+    );
+
+    // A weekend day has no working hours
+    let saturday = (at("2026-10-17T04:00:00Z"), at("2026-10-18T04:00:00Z"));
+    let availability = MyAvailability::new(saturday, NEW_YORK, hours(9, 17), false, &list, &[]);
+    assert!(availability.free.is_empty());
+    assert_eq!(
+        availability.text,
+        "I don't have any free time in that period."
+    );
+}
+
+#[tokio::test]
+async fn test_my_availability_tool() {
+    let dir = tempfile::tempdir().unwrap();
+    let calendar = dir.path().join("calendar/v3");
+    let me = calendar.join("users/me");
+    std::fs::create_dir_all(&me).unwrap();
+    std::fs::write(
+        me.join("calendarList.json"),
+        json!({ "items": [
+            { "id": "me@example.com", "summary": "Me", "primary": true },
+            { "id": "team@example.com", "summary": "Team" },
+            { "id": "en.usa#holiday@group.v.calendar.google.com", "summary": "Holidays" }
+        ] })
+        .to_string(),
+    )
+    .unwrap();
+    std::fs::write(
+        me.join("settings.json"),
+        json!({ "items": [{ "id": "timezone", "value": "UTC" }] }).to_string(),
+    )
+    .unwrap();
+
+    // Busy tomorrow from 10:00 to 12:00 across the two calendars
+    let tomorrow = (Utc::now() + Duration::days(1)).date_naive();
+    let time = |hour: u32| {
+        Utc.from_utc_datetime(&tomorrow.and_hms_opt(hour, 0, 0).unwrap())
+            .to_rfc3339()
+    };
+    std::fs::write(
+        calendar.join("freeBusy.post.json"),
+        json!({ "calendars": {
+            "me@example.com": { "busy": [{ "start": time(10), "end": time(11) }] },
+            "team@example.com": { "busy": [{ "start": time(10), "end": time(12) }] }
+        } })
+        .to_string(),
+    )
+    .unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    let result = call_tool(
+        &client,
+        "my_availability",
+        json!({ "time_range": "tomorrow", "include_weekends": true }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["time_zone"], "UTC");
+    let calendars: Vec<&str> = result["calendars"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|calendar| calendar["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(calendars, vec!["me@example.com", "team@example.com"]);
+    assert_eq!(
+        result["busy"],
+        json!([{ "start": time(10).replace("+00:00", "Z"), "end": time(12).replace("+00:00", "Z") }])
+    );
+    let free = result["free"].as_array().unwrap();
+    assert_eq!(free.len(), 2);
+    assert_eq!(free[0]["minutes"], 60);
+    assert_eq!(free[1]["minutes"], 300);
+    assert!(result["text"]
+        .as_str()
+        .unwrap()
+        .ends_with(": 09:00–10:00, 12:00–17:00"));
+
+    let error = call_tool(
+        &client,
+        "my_availability",
+        json!({ "calendars": ["Personal"] }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error.to_string().contains("No calendar 'Personal'"));
+
+    let error = call_tool(
+        &client,
+        "my_availability",
+        json!({ "work_start": "17:00", "work_end": "9am" }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("work_end must be after work_start"));
+}
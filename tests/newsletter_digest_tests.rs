@@ -0,0 +1,186 @@
+/// Newsletter Digest Tests Module
+///
+/// This module contains tests for bulk mail detection, checking which
+/// headers mark a message as bulk, how unsubscribe links are read, how the
+/// digest groups messages, and the newsletter_digest tool against the mock
+/// server.
+use chrono::{TimeZone, Utc};
+use mcp_gmailcal::gmail_api::MessageMetadata;
+use mcp_gmailcal::newsletters::{is_bulk_mail, NewsletterDigest, UnsubscribeOptions};
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn message(id: &str, from: &str, day: u32, headers: &[(&str, &str)]) -> MessageMetadata {
+    MessageMetadata {
+        id: id.to_string(),
+        thread_id: id.to_string(),
+        from: Some(from.to_string()),
+        subject: Some(format!("Issue {}", id)),
+        received_at: Some(Utc.with_ymd_and_hms(2026, 10, day, 8, 0, 0).unwrap()),
+        label_ids: vec!["INBOX".to_string(), "UNREAD".to_string()],
+        headers: headers
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_bulk_mail_detection() {
+    let from = "news@example.com";
+    assert!(is_bulk_mail(&message(
+        "1",
+        from,
+        1,
+        &[("list-id", "<news.example.com>")]
+    )));
+    assert!(is_bulk_mail(&message(
+        "2",
+        from,
+        1,
+        &[("precedence", " Bulk")]
+    )));
+    assert!(is_bulk_mail(&message(
+        "3",
+        from,
+        1,
+        &[("precedence", "list")]
+    )));
+    assert!(!is_bulk_mail(&message(
+        "4",
+        from,
+        1,
+        &[("precedence", "first-class")]
+    )));
+    // An unsubscribe link alone is common on receipts and other personal mail
+    assert!(!is_bulk_mail(&message(
+        "5",
+        from,
+        1,
+        &[("list-unsubscribe", "<https://example.com/u>")]
+    )));
+}
+
+#[test]
+fn test_unsubscribe_options() {
+    let options = UnsubscribeOptions::from_message(&message(
+        "1",
+        "news@example.com",
+        1,
+        &[
+            (
+                "list-unsubscribe",
+                "<mailto:leave@example.com?subject=unsubscribe>, <https://example.com/u?id=1>",
+            ),
+            ("list-unsubscribe-post", "List-Unsubscribe=One-Click"),
+        ],
+    ))
+    .unwrap();
+    assert_eq!(
+        options,
+        UnsubscribeOptions {
+            mailto: Some("mailto:leave@example.com?subject=unsubscribe".to_string()),
+            url: Some("https://example.com/u?id=1".to_string()),
+            one_click: true,
+        }
+    );
+
+    // One-click needs a web link to post to
+    let options = UnsubscribeOptions::from_message(&message(
+        "2",
+        "news@example.com",
+        1,
+        &[
+            ("list-unsubscribe", "<mailto:leave@example.com>"),
+            ("list-unsubscribe-post", "List-Unsubscribe=One-Click"),
+        ],
+    ))
+    .unwrap();
+    assert!(!options.one_click);
+
+    assert!(UnsubscribeOptions::from_message(&message("3", "a@example.com", 1, &[])).is_none());
+}
+
+#[test]
+fn test_digest_groups_by_list_then_sender() {
+    let list = [("list-id", "Weekly News <weekly.news.example>")];
+    let unsubscribe = [
+        ("list-id", "Weekly News <weekly.news.example>"),
+        ("list-unsubscribe", "<https://news.example/u>"),
+    ];
+    let messages = vec![
+        message("1", "Weekly <weekly@news.example>", 1, &unsubscribe),
+        message("2", "Editors <editors@news.example>", 8, &list),
+        message(
+            "3",
+            "Shop <deals@shop.example>",
+            5,
+            &[("precedence", "bulk")],
+        ),
+        message("4", "Alice <alice@example.com>", 6, &[]),
+        message("5", "Weekly <weekly@news.example>", 15, &list),
+    ];
+
+    let digest = NewsletterDigest::from_messages("newer_than:30d", &messages, false);
+    assert_eq!(digest.messages_scanned, 5);
+    assert_eq!(digest.bulk_messages, 4);
+    assert_eq!(digest.senders.len(), 2);
+
+    let weekly = &digest.senders[0];
+    assert_eq!(weekly.list_id.as_deref(), Some("weekly.news.example"));
+    assert_eq!(weekly.address, "weekly@news.example");
+    assert_eq!((weekly.count, weekly.unread), (3, 3));
+    let ids: Vec<&str> = weekly.messages.iter().map(|m| m.id.as_str()).collect();
+    assert_eq!(ids, vec!["5", "2", "1"]);
+    // Taken from the older message, the only one with a link
+    assert_eq!(
+        weekly.unsubscribe.as_ref().unwrap().url.as_deref(),
+        Some("https://news.example/u")
+    );
+
+    let shop = &digest.senders[1];
+    assert_eq!(shop.list_id, None);
+    assert_eq!(shop.address, "deals@shop.example");
+    assert!(shop.unsubscribe.is_none());
+}
+
+#[tokio::test]
+async fn test_newsletter_digest_tool() {
+    let (_server, client) = mock_client(None).await;
+
+    let digest = call_tool(&client, "newsletter_digest", json!({ "time_range": "7d" }))
+        .await
+        .unwrap();
+    assert_eq!(digest["query"], "newer_than:7d -from:me");
+    assert_eq!(digest["messages_scanned"], 3);
+    assert_eq!(digest["bulk_messages"], 1);
+
+    // Only the billing notices are sent to a list
+    let sender = &digest["senders"][0];
+    assert_eq!(sender["address"], "billing@vendor.example");
+    assert_eq!(sender["list_id"], "billing.vendor.example");
+    assert_eq!(
+        sender["messages"][0]["subject"],
+        "Your invoice #4821 is ready"
+    );
+    assert_eq!(
+        sender["unsubscribe"],
+        json!({
+            "mailto": "mailto:unsubscribe@vendor.example?subject=unsubscribe",
+            "url": "https://vendor.example/unsubscribe?u=demo",
+            "one_click": true
+        })
+    );
+
+    let error = call_tool(&client, "newsletter_digest", json!({ "time_range": "0d" }))
+        .await
+        .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("Invalid time range"));
+}
@@ -0,0 +1,202 @@
+/// Office Hours Tests Module
+///
+/// This module contains tests for building weekly office hours and their
+/// recurrence rules, and for the create_office_hours and list_office_hours
+/// tools against the mock server.
+use chrono::{NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use mcp_gmailcal::office_hours::{
+    is_office_hours, parse_weekdays, parse_weekly_rule, weekly_rule, OfficeHours,
+    OfficeHoursRequest,
+};
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+#[test]
+fn test_parse_weekdays() {
+    assert_eq!(
+        parse_weekdays("Thursday, tuesday").unwrap(),
+        vec![Weekday::Tue, Weekday::Thu]
+    );
+    assert_eq!(
+        parse_weekdays("mon wed and fri").unwrap(),
+        vec![Weekday::Mon, Weekday::Wed, Weekday::Fri]
+    );
+    assert_eq!(parse_weekdays("weekdays, Monday").unwrap().len(), 5);
+    assert!(parse_weekdays("  ").is_err());
+    let error = parse_weekdays("Tuesday, Funday").unwrap_err();
+    assert!(error.contains("Invalid day 'Funday'"));
+}
+
+#[test]
+fn test_weekly_rule() {
+    let until = Utc.with_ymd_and_hms(2026, 12, 17, 15, 0, 0).unwrap();
+    let rule = weekly_rule(&[Weekday::Tue, Weekday::Thu], Some(until));
+    assert_eq!(rule, "RRULE:FREQ=WEEKLY;BYDAY=TU,TH;UNTIL=20261217T150000Z");
+    assert_eq!(
+        parse_weekly_rule(&rule),
+        Some((
+            vec![Weekday::Tue, Weekday::Thu],
+            Some("20261217T150000Z".to_string())
+        ))
+    );
+    assert_eq!(
+        parse_weekly_rule("RRULE:FREQ=WEEKLY;BYDAY=FR"),
+        Some((vec![Weekday::Fri], None))
+    );
+    assert_eq!(parse_weekly_rule("RRULE:FREQ=DAILY;COUNT=5"), None);
+    assert_eq!(parse_weekly_rule("EXDATE:20261020T140000Z"), None);
+}
+
+fn request() -> OfficeHoursRequest {
+    OfficeHoursRequest {
+        summary: "Office hours".to_string(),
+        description: Some("Bring your questions".to_string()),
+        location: None,
+        days: vec![Weekday::Tue, Weekday::Thu],
+        start: NaiveTime::from_hms_opt(14, 0, 0).unwrap(),
+        end: NaiveTime::from_hms_opt(16, 0, 0).unwrap(),
+        // A Friday
+        starting: NaiveDate::from_ymd_opt(2026, 10, 16).unwrap(),
+        until: Some(NaiveDate::from_ymd_opt(2026, 12, 17).unwrap()),
+        time_zone: chrono_tz::Europe::Berlin,
+    }
+}
+
+#[test]
+fn test_office_hours_event() {
+    let event = request().to_event().unwrap();
+    // The first Tuesday on or after the start, at 14:00 in Berlin summer time
+    assert_eq!(event.start_time.to_rfc3339(), "2026-10-20T12:00:00+00:00");
+    assert_eq!(event.end_time.to_rfc3339(), "2026-10-20T14:00:00+00:00");
+    assert_eq!(event.time_zone.as_deref(), Some("Europe/Berlin"));
+    assert_eq!(event.transparency.as_deref(), Some("transparent"));
+    // The last day ends at 16:00 in Berlin winter time
+    assert_eq!(
+        event.recurrence,
+        vec!["RRULE:FREQ=WEEKLY;BYDAY=TU,TH;UNTIL=20261217T150000Z"]
+    );
+    assert!(is_office_hours(&event));
+
+    // Read back in the event's time zone, the block keeps its local times
+    let office_hours = OfficeHours::from_event("primary", &event).unwrap();
+    assert_eq!(office_hours.days, vec!["Tuesday", "Thursday"]);
+    assert_eq!(office_hours.start_time, "14:00");
+    assert_eq!(office_hours.end_time, "16:00");
+    assert_eq!(office_hours.time_zone, "Europe/Berlin");
+    assert_eq!(office_hours.until.as_deref(), Some("20261217T150000Z"));
+    assert!(office_hours.transparent);
+
+    let mut retitled = event.clone();
+    retitled.summary = "Team standup".to_string();
+    assert!(!is_office_hours(&retitled));
+}
+
+#[test]
+fn test_invalid_office_hours() {
+    let mut backwards = request();
+    backwards.end = NaiveTime::from_hms_opt(13, 0, 0).unwrap();
+    assert!(backwards
+        .to_event()
+        .unwrap_err()
+        .contains("must end after they start"));
+
+    let mut ended = request();
+    ended.until = Some(NaiveDate::from_ymd_opt(2026, 10, 19).unwrap());
+    assert!(ended
+        .to_event()
+        .unwrap_err()
+        .contains("would end before their first day"));
+}
+
+#[tokio::test]
+async fn test_office_hours_tools() {
+    let dir = tempfile::tempdir().unwrap();
+    let team = dir.path().join("calendar/v3/calendars/team@example.com");
+    std::fs::create_dir_all(&team).unwrap();
+    let events = json!({
+        "items": [
+            {
+                "id": "office-hours-1",
+                "summary": "Design office hours",
+                "start": { "dateTime": "2026-10-19T10:00:00-07:00", "timeZone": "America/Los_Angeles" },
+                "end": { "dateTime": "2026-10-19T11:30:00-07:00", "timeZone": "America/Los_Angeles" },
+                "recurrence": ["RRULE:FREQ=WEEKLY;BYDAY=MO,WE"],
+                "transparency": "transparent"
+            },
+            {
+                "id": "standup",
+                "summary": "Standup",
+                "start": { "dateTime": "2026-10-19T09:00:00-07:00", "timeZone": "America/Los_Angeles" },
+                "end": { "dateTime": "2026-10-19T09:15:00-07:00", "timeZone": "America/Los_Angeles" },
+                "recurrence": ["RRULE:FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR"]
+            },
+            {
+                "id": "one-off",
+                "summary": "Office hours special",
+                "start": { "dateTime": "2026-10-21T09:00:00-07:00" },
+                "end": { "dateTime": "2026-10-21T10:00:00-07:00" }
+            }
+        ]
+    });
+    std::fs::write(team.join("events.json"), events.to_string()).unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    // The mock echoes the event back as created
+    let created = call_tool(
+        &client,
+        "create_office_hours",
+        json!({
+            "days": "Tuesday, Thursday",
+            "start_time": "2pm",
+            "end_time": "16:00",
+            "time_zone": "Europe/Berlin",
+            "starting": "2026-10-16",
+            "description": "Bring your questions"
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(created["summary"], "Office hours");
+    assert_eq!(created["days"], json!(["Tuesday", "Thursday"]));
+    assert_eq!(created["start_time"], "14:00");
+    assert_eq!(created["end_time"], "16:00");
+    assert_eq!(created["time_zone"], "Europe/Berlin");
+    assert_eq!(created["transparent"], true);
+    assert_eq!(created["description"], "Bring your questions");
+    assert_eq!(
+        created["recurrence"],
+        json!(["RRULE:FREQ=WEEKLY;BYDAY=TU,TH"])
+    );
+    assert!(created["event_id"].is_string());
+
+    let listed = call_tool(
+        &client,
+        "list_office_hours",
+        json!({ "calendar_id": "team@example.com" }),
+    )
+    .await
+    .unwrap();
+    let listed = listed.as_array().unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0]["event_id"], "office-hours-1");
+    assert_eq!(listed[0]["days"], json!(["Monday", "Wednesday"]));
+    assert_eq!(listed[0]["start_time"], "10:00");
+    assert_eq!(listed[0]["end_time"], "11:30");
+    assert_eq!(listed[0]["time_zone"], "America/Los_Angeles");
+
+    let error = call_tool(
+        &client,
+        "create_office_hours",
+        json!({ "days": "Tuesday", "start_time": "teatime", "end_time": "4pm" }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("Invalid start_time 'teatime'"));
+}
@@ -0,0 +1,196 @@
+/// Outbox Tests Module
+///
+/// This module contains tests for undo send: emails waiting in the outbox
+/// for their send time, cancelling them before then, and sending them once
+/// it passes.
+use mcp_gmailcal::attachments::OutgoingAttachment;
+use mcp_gmailcal::gmail_api::DraftEmail;
+use mcp_gmailcal::outbox::{self, Outbox, OutboxStatus, MAX_FINISHED_ENTRIES};
+use mcp_gmailcal::GmailServer;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn email(subject: &str) -> DraftEmail {
+    DraftEmail {
+        to: "alice@example.com".to_string(),
+        subject: subject.to_string(),
+        body: "Hello".to_string(),
+        cc: None,
+        bcc: None,
+        thread_id: None,
+        in_reply_to: None,
+        references: None,
+        priority: None,
+        read_receipt_to: None,
+    }
+}
+
+#[test]
+fn test_cancel_queued_email() {
+    let dir = tempfile::tempdir().unwrap();
+    let outbox = Outbox::new(dir.path().join("outbox.json"));
+
+    let entry = outbox.queue(email("Hi"), Duration::from_secs(60)).unwrap();
+    assert_eq!(entry.status, OutboxStatus::Queued);
+    assert_eq!((entry.send_at - entry.queued_at).num_seconds(), 60);
+    assert_eq!(outbox.entries().len(), 1);
+
+    let cancelled = outbox.cancel(&entry.id).unwrap();
+    assert_eq!(cancelled.status, OutboxStatus::Cancelled);
+    assert_eq!(outbox.entries()[0].status, OutboxStatus::Cancelled);
+
+    let error = outbox.cancel(&entry.id).unwrap_err();
+    assert!(error.contains("it is cancelled"));
+    let error = outbox.cancel("outbox-missing").unwrap_err();
+    assert!(error.contains("No email 'outbox-missing'"));
+}
+
+#[test]
+fn test_finished_emails_are_pruned() {
+    let dir = tempfile::tempdir().unwrap();
+    let outbox = Outbox::new(dir.path().join("outbox.json"));
+
+    let first = outbox
+        .queue(email("First"), Duration::from_secs(60))
+        .unwrap();
+    outbox.cancel(&first.id).unwrap();
+    let queued = outbox
+        .queue(email("Queued"), Duration::from_secs(60))
+        .unwrap();
+    for _ in 0..MAX_FINISHED_ENTRIES {
+        let entry = outbox
+            .queue(email("Later"), Duration::from_secs(60))
+            .unwrap();
+        outbox.cancel(&entry.id).unwrap();
+    }
+
+    // The oldest finished email goes; queued ones are always kept
+    let entries = outbox.entries();
+    assert_eq!(entries.len(), MAX_FINISHED_ENTRIES + 1);
+    assert!(entries.iter().all(|entry| entry.id != first.id));
+    assert!(entries.iter().any(|entry| entry.id == queued.id));
+}
+
+#[tokio::test]
+async fn test_send_with_undo_window() {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("OUTBOX_FILE", dir.path().join("outbox.json"));
+    std::env::set_var("SEND_UNDO_SECONDS", "60");
+    let (_server, client) = mock_client(None).await;
+
+    let message = json!({ "to": "alice@example.com", "subject": "Hi", "body": "Hello" });
+
+    // Cancelled within the window, the email is never sent
+    let queued = call_tool(&client, "send_email", message.clone())
+        .await
+        .unwrap();
+    assert_eq!(queued["status"], "queued");
+    let id = queued["id"].as_str().unwrap();
+    let cancelled = call_tool(&client, "cancel_send", json!({ "outbox_id": id }))
+        .await
+        .unwrap();
+    assert_eq!(cancelled["status"], "cancelled");
+    assert!(cancelled.get("message_id").is_none());
+
+    // Drive files are fetched when the email is queued and kept with it,
+    // shown by name and size
+    let with_files = call_tool(
+        &client,
+        "send_email",
+        json!({
+            "to": "alice@example.com",
+            "subject": "Notes",
+            "body": "Attached",
+            "drive_file_ids": ["mock-file-001"],
+            "request_read_receipt": true
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        with_files["attachments"],
+        json!([{ "filename": "planning-notes.txt", "mime_type": "text/plain", "size": 54 }])
+    );
+    assert_eq!(
+        with_files["drive_files"]["attached"][0]["name"],
+        "planning-notes.txt"
+    );
+    assert!(with_files["email"]["read_receipt_to"].is_string());
+    let id = with_files["id"].as_str().unwrap();
+    let saved = Outbox::new(dir.path().join("outbox.json")).entries();
+    let saved = saved.iter().find(|entry| entry.id == id).unwrap();
+    assert_eq!(saved.attachments[0].data.len(), 54);
+    assert!(saved.email.read_receipt_to.is_some());
+    call_tool(&client, "cancel_send", json!({ "outbox_id": id }))
+        .await
+        .unwrap();
+
+    // Otherwise it is sent once the window ends
+    std::env::set_var("SEND_UNDO_SECONDS", "1");
+    let queued = call_tool(&client, "send_email", message.clone())
+        .await
+        .unwrap();
+    let id = queued["id"].as_str().unwrap().to_string();
+    tokio::time::sleep(Duration::from_millis(2500)).await;
+    let listed = call_tool(&client, "list_outbox", json!({})).await.unwrap();
+    let sent = listed
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|entry| entry["id"] == id.as_str())
+        .unwrap();
+    assert_eq!(sent["status"], "sent");
+    assert!(sent["message_id"].is_string());
+
+    let error = call_tool(&client, "cancel_send", json!({ "outbox_id": id }))
+        .await
+        .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("can no longer be cancelled: it is sent"));
+
+    // Without a window the email is sent at once
+    std::env::set_var("SEND_UNDO_SECONDS", "0");
+    let sent = call_tool(&client, "send_email", message).await.unwrap();
+    assert_eq!(sent["status"], "sent");
+    assert!(sent["message_id"].is_string());
+
+    // Emails left queued by an earlier run are sent on start, and ones
+    // interrupted while sending are not sent again
+    let earlier: &'static Outbox = Box::leak(Box::new(Outbox::new(
+        dir.path().join("earlier-outbox.json"),
+    )));
+    let attachment = OutgoingAttachment {
+        filename: "notes.txt".to_string(),
+        mime_type: "text/plain".to_string(),
+        data: b"Notes".to_vec(),
+    };
+    let waiting = earlier
+        .queue_with_attachments(email("Waiting"), vec![attachment.clone()], Duration::ZERO)
+        .unwrap();
+    let mut contents: Vec<Value> =
+        serde_json::from_str(&std::fs::read_to_string(earlier.path()).unwrap()).unwrap();
+    let mut interrupted = contents[0].clone();
+    interrupted["id"] = json!("outbox-interrupted");
+    interrupted["status"] = json!("sending");
+    contents.push(interrupted);
+    std::fs::write(earlier.path(), serde_json::to_string(&contents).unwrap()).unwrap();
+
+    let gmail = GmailServer::new().gmail_service().unwrap();
+    assert_eq!(outbox::resume(earlier, gmail), 1);
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    let entries = earlier.entries();
+    assert_eq!(entries[0].id, waiting.id);
+    assert_eq!(entries[0].status, OutboxStatus::Sent);
+    assert_eq!(entries[0].attachments, vec![attachment]);
+    assert_eq!(entries[1].status, OutboxStatus::Failed);
+    assert!(entries[1].error.as_deref().unwrap().contains("stopped"));
+
+    std::env::remove_var("SEND_UNDO_SECONDS");
+    std::env::remove_var("OUTBOX_FILE");
+}
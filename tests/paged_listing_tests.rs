@@ -0,0 +1,205 @@
+/// Paged Listing Tests Module
+///
+/// This module contains tests for large message listings, checking that the
+/// rate limiter bounds concurrency and spaces out requests, and that listings
+/// spanning several pages fetch messages concurrently and keep them in order.
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use mcp_gmailcal::rate_limit::RateLimiter;
+use mcp_gmailcal::{Config, GmailService};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+
+const MESSAGE_COUNT: usize = 25;
+const PAGE_SIZE: usize = 10;
+
+#[derive(Default)]
+struct InFlight {
+    current: AtomicUsize,
+    max: AtomicUsize,
+}
+
+impl InFlight {
+    fn enter(&self) {
+        let current = self.current.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max.fetch_max(current, Ordering::SeqCst);
+    }
+
+    fn exit(&self) {
+        self.current.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Default)]
+struct Mailbox {
+    in_flight: InFlight,
+    pages: AtomicUsize,
+}
+
+type SharedMailbox = Arc<Mailbox>;
+
+fn message_id(n: usize) -> String {
+    format!("msg-{:02}", n)
+}
+
+async fn token_handler() -> Json<serde_json::Value> {
+    Json(json!({ "access_token": "paged-token", "expires_in": 3600 }))
+}
+
+// Pages are smaller than requested, like Gmail's for large maxResults
+async fn list_handler(
+    State(mailbox): State<SharedMailbox>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Json<serde_json::Value> {
+    mailbox.pages.fetch_add(1, Ordering::SeqCst);
+    let start: usize = params
+        .get("pageToken")
+        .map(|token| token.parse().unwrap())
+        .unwrap_or(0);
+    let max: usize = params["maxResults"].parse().unwrap();
+    let end = (start + max.min(PAGE_SIZE)).min(MESSAGE_COUNT);
+
+    let messages: Vec<_> = (start..end)
+        .map(|n| json!({ "id": message_id(n), "threadId": message_id(n) }))
+        .collect();
+    let mut page = json!({ "messages": messages });
+    if end < MESSAGE_COUNT {
+        page["nextPageToken"] = json!(end.to_string());
+    }
+    Json(page)
+}
+
+// Later messages answer sooner, so completion order differs from list order
+async fn message_handler(State(mailbox): State<SharedMailbox>, Path(id): Path<String>) -> Response {
+    mailbox.in_flight.enter();
+    let n: usize = id.trim_start_matches("msg-").parse().unwrap();
+    tokio::time::sleep(Duration::from_millis((MESSAGE_COUNT - n) as u64 * 2)).await;
+    mailbox.in_flight.exit();
+
+    if n == 7 {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    Json(json!({
+        "id": id,
+        "threadId": id,
+        "snippet": format!("Message {}", n),
+        "payload": {
+            "mimeType": "text/plain",
+            "headers": [{ "name": "Subject", "value": format!("Subject {}", n) }],
+            "body": { "size": 0 }
+        }
+    }))
+    .into_response()
+}
+
+async fn start_mailbox() -> (String, SharedMailbox) {
+    let mailbox = SharedMailbox::default();
+    let app = Router::new()
+        .route("/token", post(token_handler))
+        .route("/gmail/v1/users/me/messages", get(list_handler))
+        .route("/gmail/v1/users/me/messages/:id", get(message_handler))
+        .with_state(mailbox.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{}", addr), mailbox)
+}
+
+#[tokio::test]
+async fn test_rate_limiter_bounds_concurrency() {
+    let limiter = Arc::new(RateLimiter::new(2, 0));
+    let in_flight = Arc::new(InFlight::default());
+
+    let tasks: Vec<_> = (0..6)
+        .map(|_| {
+            let limiter = limiter.clone();
+            let in_flight = in_flight.clone();
+            tokio::spawn(async move {
+                let _permit = limiter.acquire().await;
+                in_flight.enter();
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                in_flight.exit();
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap();
+    }
+
+    assert_eq!(in_flight.max.load(Ordering::SeqCst), 2);
+    assert_eq!(limiter.max_concurrent(), 2);
+    assert_eq!(RateLimiter::new(0, 0).max_concurrent(), 1);
+}
+
+#[tokio::test]
+async fn test_rate_limiter_spaces_request_starts() {
+    let limiter = RateLimiter::new(10, 100);
+    let start = Instant::now();
+    for _ in 0..5 {
+        drop(limiter.acquire().await);
+    }
+    // The first request starts immediately, then one every 10ms
+    assert!(start.elapsed() >= Duration::from_millis(40));
+
+    let unlimited = RateLimiter::new(10, 0);
+    let start = Instant::now();
+    for _ in 0..5 {
+        drop(unlimited.acquire().await);
+    }
+    assert!(start.elapsed() < Duration::from_millis(40));
+}
+
+// All client calls share one test since they configure the process
+// environment
+#[tokio::test]
+async fn test_multi_page_listing_fetches_concurrently_in_order() {
+    let (url, mailbox) = start_mailbox().await;
+    std::env::set_var("GMAIL_API_BASE_URL", format!("{}/gmail/v1", url));
+    std::env::set_var("OAUTH_TOKEN_URL", format!("{}/token", url));
+    std::env::set_var("GMAIL_CLIENT_ID", "paged-client-id");
+    std::env::set_var("GMAIL_CLIENT_SECRET", "paged-client-secret");
+    std::env::set_var("GMAIL_REFRESH_TOKEN", "paged-refresh-token");
+    std::env::set_var("TOKEN_CACHE_ENABLED", "false");
+    std::env::set_var("API_MAX_CONCURRENT_REQUESTS", "4");
+    std::env::set_var("API_REQUESTS_PER_SECOND", "0");
+
+    let config = Config::from_env().unwrap();
+    let gmail = GmailService::new(&config).unwrap();
+
+    let messages = gmail
+        .list_messages(MESSAGE_COUNT as u32, None)
+        .await
+        .unwrap();
+    // The message that failed is skipped and the rest stay in list order
+    let expected: Vec<String> = (0..MESSAGE_COUNT)
+        .filter(|n| *n != 7)
+        .map(message_id)
+        .collect();
+    let ids: Vec<String> = messages.iter().map(|m| m.id.clone()).collect();
+    assert_eq!(ids, expected);
+    assert_eq!(messages[0].subject.as_deref(), Some("Subject 0"));
+    assert_eq!(mailbox.pages.load(Ordering::SeqCst), 3);
+
+    let max_in_flight = mailbox.in_flight.max.load(Ordering::SeqCst);
+    assert!(max_in_flight > 1, "messages were fetched one at a time");
+    assert!(max_in_flight <= 4, "{} requests in flight", max_in_flight);
+
+    // Listings stop as soon as enough messages are listed
+    mailbox.pages.store(0, Ordering::SeqCst);
+    let messages = gmail.list_messages(12, None).await.unwrap();
+    assert_eq!(messages.len(), 11);
+    assert_eq!(mailbox.pages.load(Ordering::SeqCst), 2);
+
+    let ids = gmail
+        .list_message_ids(MESSAGE_COUNT as u32 + 5, None)
+        .await
+        .unwrap();
+    assert_eq!(ids.len(), MESSAGE_COUNT);
+}
@@ -2,49 +2,14 @@
 ///
 /// This module contains comprehensive tests for the Google People API functionality,
 /// focusing on contact operations, data formatting, and error handling.
+use mcp_gmailcal::api::PeopleApi;
+use mcp_gmailcal::config::Config;
 use mcp_gmailcal::errors::PeopleApiError;
-use mcp_gmailcal::people_api::{Contact, ContactList, EmailAddress, Organization, PersonName, PhoneNumber, Photo};
+use mcp_gmailcal::people_api::{
+    Contact, EmailAddress, Organization, PeopleClient, PersonName, PhoneNumber, Photo,
+};
+use mcp_gmailcal::test_util::MockPeopleApi;
 use serde_json::{json, Value};
-use std::sync::{Arc, Mutex};
-
-// Define a proper interface for PeopleClient that we can mock
-trait PeopleClientInterface {
-    fn list_contacts(&self, max_results: Option<u32>) -> Result<ContactList, PeopleApiError>;
-    fn search_contacts(&self, query: &str, max_results: Option<u32>) -> Result<ContactList, PeopleApiError>;
-    fn get_contact(&self, resource_name: &str) -> Result<Contact, PeopleApiError>;
-    fn parse_contact(&self, data: &Value) -> Result<Contact, PeopleApiError>;
-}
-
-// Wrapper for PeopleClient that we can test against
-struct MockablePeopleClient {
-    client: Arc<Mutex<dyn PeopleClientInterface + Send + Sync>>,
-}
-
-impl MockablePeopleClient {
-    fn new(client: Arc<Mutex<dyn PeopleClientInterface + Send + Sync>>) -> Self {
-        Self { client }
-    }
-
-    fn list_contacts(&self, max_results: Option<u32>) -> Result<ContactList, PeopleApiError> {
-        let client = self.client.lock().unwrap();
-        client.list_contacts(max_results)
-    }
-
-    fn search_contacts(&self, query: &str, max_results: Option<u32>) -> Result<ContactList, PeopleApiError> {
-        let client = self.client.lock().unwrap();
-        client.search_contacts(query, max_results)
-    }
-
-    fn get_contact(&self, resource_name: &str) -> Result<Contact, PeopleApiError> {
-        let client = self.client.lock().unwrap();
-        client.get_contact(resource_name)
-    }
-
-    fn parse_contact(&self, data: &Value) -> Result<Contact, PeopleApiError> {
-        let client = self.client.lock().unwrap();
-        client.parse_contact(data)
-    }
-}
 
 // Helper functions to create test data
 fn create_test_contact(
@@ -327,344 +292,89 @@ fn create_extended_contact_json(
     })
 }
 
-// Mock implementation of PeopleClientInterface for testing
-struct MockPeopleClient {
-    contacts: Vec<Contact>,
-    should_fail: bool,
-    fail_mode: FailMode,
+// Sample contacts served by the mock People API
+fn sample_contacts() -> Vec<Contact> {
+    vec![
+        create_test_contact(
+            "people/contact1",
+            "John Doe",
+            Some("John"),
+            Some("Doe"),
+            vec![("john.doe@example.com", Some("work"))],
+            vec![("123-456-7890", Some("mobile"))],
+            vec![(Some("Acme Inc"), Some("Software Developer"))],
+            vec![("https://example.com/photo1.jpg", true)],
+        ),
+        create_test_contact(
+            "people/contact2",
+            "Jane Smith",
+            Some("Jane"),
+            Some("Smith"),
+            vec![
+                ("jane.smith@example.com", Some("work")),
+                ("jsmith@personal.com", Some("home")),
+            ],
+            vec![("987-654-3210", Some("mobile"))],
+            vec![(Some("XYZ Corp"), Some("Product Manager"))],
+            vec![("https://example.com/photo2.jpg", true)],
+        ),
+        create_test_contact(
+            "people/contact3",
+            "Alex Johnson",
+            Some("Alex"),
+            Some("Johnson"),
+            vec![("alex.j@example.com", Some("work"))],
+            vec![
+                ("555-123-4567", Some("work")),
+                ("555-987-6543", Some("home")),
+            ],
+            vec![(Some("ABC Company"), Some("Director"))],
+            vec![
+                ("https://example.com/photo3a.jpg", false),
+                ("https://example.com/photo3b.jpg", true),
+            ],
+        ),
+        // Add an international contact
+        create_test_contact(
+            "people/contact4",
+            "José García",
+            Some("José"),
+            Some("García"),
+            vec![("jose.garcia@example.com", Some("work"))],
+            vec![("+34 612 345 678", Some("mobile"))],
+            vec![(Some("Spanish Company S.L."), Some("Ingeniero"))],
+            vec![("https://example.com/photo4.jpg", true)],
+        ),
+        // Add a contact with non-Latin script
+        create_test_contact(
+            "people/contact5",
+            "张伟",
+            Some("伟"),
+            Some("张"),
+            vec![("zhang.wei@example.com", Some("work"))],
+            vec![("+86 123 4567 8901", Some("mobile"))],
+            vec![(Some("中国公司"), Some("工程师"))],
+            vec![("https://example.com/photo5.jpg", true)],
+        ),
+    ]
 }
 
+#[derive(Clone, Copy)]
 enum FailMode {
     Auth,
     Network,
     Api,
     Parse,
-    None,
-}
-
-impl MockPeopleClient {
-    fn new() -> Self {
-        // Create sample contacts
-        let contacts = vec![
-            create_test_contact(
-                "people/contact1",
-                "John Doe",
-                Some("John"),
-                Some("Doe"),
-                vec![("john.doe@example.com", Some("work"))],
-                vec![("123-456-7890", Some("mobile"))],
-                vec![(Some("Acme Inc"), Some("Software Developer"))],
-                vec![("https://example.com/photo1.jpg", true)],
-            ),
-            create_test_contact(
-                "people/contact2",
-                "Jane Smith",
-                Some("Jane"),
-                Some("Smith"),
-                vec![
-                    ("jane.smith@example.com", Some("work")),
-                    ("jsmith@personal.com", Some("home")),
-                ],
-                vec![("987-654-3210", Some("mobile"))],
-                vec![(Some("XYZ Corp"), Some("Product Manager"))],
-                vec![("https://example.com/photo2.jpg", true)],
-            ),
-            create_test_contact(
-                "people/contact3",
-                "Alex Johnson",
-                Some("Alex"),
-                Some("Johnson"),
-                vec![("alex.j@example.com", Some("work"))],
-                vec![
-                    ("555-123-4567", Some("work")),
-                    ("555-987-6543", Some("home")),
-                ],
-                vec![(Some("ABC Company"), Some("Director"))],
-                vec![
-                    ("https://example.com/photo3a.jpg", false),
-                    ("https://example.com/photo3b.jpg", true),
-                ],
-            ),
-            // Add an international contact
-            create_test_contact(
-                "people/contact4",
-                "José García",
-                Some("José"),
-                Some("García"),
-                vec![("jose.garcia@example.com", Some("work"))],
-                vec![("+34 612 345 678", Some("mobile"))],
-                vec![(Some("Spanish Company S.L."), Some("Ingeniero"))],
-                vec![("https://example.com/photo4.jpg", true)],
-            ),
-            // Add a contact with non-Latin script
-            create_test_contact(
-                "people/contact5",
-                "张伟",
-                Some("伟"),
-                Some("张"),
-                vec![("zhang.wei@example.com", Some("work"))],
-                vec![("+86 123 4567 8901", Some("mobile"))],
-                vec![(Some("中国公司"), Some("工程师"))],
-                vec![("https://example.com/photo5.jpg", true)],
-            ),
-        ];
-
-        Self {
-            contacts,
-            should_fail: false,
-            fail_mode: FailMode::None,
-        }
-    }
-
-    fn with_failure(mut self, mode: FailMode) -> Self {
-        self.should_fail = true;
-        self.fail_mode = mode;
-        self
-    }
 }
 
-impl PeopleClientInterface for MockPeopleClient {
-    fn list_contacts(&self, max_results: Option<u32>) -> Result<ContactList, PeopleApiError> {
-        if self.should_fail {
-            return match self.fail_mode {
-                FailMode::Auth => Err(PeopleApiError::AuthError("Authentication failed".to_string())),
-                FailMode::Network => Err(PeopleApiError::NetworkError("Network error".to_string())),
-                FailMode::Api => Err(PeopleApiError::ApiError("API error".to_string())),
-                FailMode::Parse => Err(PeopleApiError::ParseError("Parse error".to_string())),
-                FailMode::None => unreachable!(),
-            };
-        }
-
-        let total_items = self.contacts.len() as u32;
-        let contacts = if let Some(max) = max_results {
-            self.contacts.iter().take(max as usize).cloned().collect()
-        } else {
-            self.contacts.clone()
-        };
-
-        Ok(ContactList {
-            contacts,
-            next_page_token: None,
-            total_items: Some(total_items),
-        })
-    }
-
-    fn search_contacts(&self, query: &str, max_results: Option<u32>) -> Result<ContactList, PeopleApiError> {
-        if self.should_fail {
-            return match self.fail_mode {
-                FailMode::Auth => Err(PeopleApiError::AuthError("Authentication failed".to_string())),
-                FailMode::Network => Err(PeopleApiError::NetworkError("Network error".to_string())),
-                FailMode::Api => Err(PeopleApiError::ApiError("API error".to_string())),
-                FailMode::Parse => Err(PeopleApiError::ParseError("Parse error".to_string())),
-                FailMode::None => unreachable!(),
-            };
-        }
-
-        let query = query.to_lowercase();
-        let filtered_contacts: Vec<Contact> = self
-            .contacts
-            .iter()
-            .filter(|contact| {
-                // Search in display name
-                if let Some(name) = &contact.name {
-                    if name.display_name.to_lowercase().contains(&query) {
-                        return true;
-                    }
-                }
-
-                // Search in emails
-                for email in &contact.email_addresses {
-                    if email.value.to_lowercase().contains(&query) {
-                        return true;
-                    }
-                }
-
-                // Search in organization name or title
-                for org in &contact.organizations {
-                    if let Some(org_name) = &org.name {
-                        if org_name.to_lowercase().contains(&query) {
-                            return true;
-                        }
-                    }
-                    if let Some(title) = &org.title {
-                        if title.to_lowercase().contains(&query) {
-                            return true;
-                        }
-                    }
-                }
-
-                false
-            })
-            .cloned()
-            .collect();
-
-        let total_items = filtered_contacts.len() as u32;
-        let contacts = if let Some(max) = max_results {
-            filtered_contacts.into_iter().take(max as usize).collect()
-        } else {
-            filtered_contacts
-        };
-
-        Ok(ContactList {
-            contacts,
-            next_page_token: None,
-            total_items: Some(total_items),
-        })
-    }
-
-    fn get_contact(&self, resource_name: &str) -> Result<Contact, PeopleApiError> {
-        if self.should_fail {
-            return match self.fail_mode {
-                FailMode::Auth => Err(PeopleApiError::AuthError("Authentication failed".to_string())),
-                FailMode::Network => Err(PeopleApiError::NetworkError("Network error".to_string())),
-                FailMode::Api => Err(PeopleApiError::ApiError("API error".to_string())),
-                FailMode::Parse => Err(PeopleApiError::ParseError("Parse error".to_string())),
-                FailMode::None => unreachable!(),
-            };
-        }
-
-        // Find contact by resource name
-        let contact = self
-            .contacts
-            .iter()
-            .find(|c| c.resource_name == resource_name)
-            .cloned();
-
-        match contact {
-            Some(contact) => Ok(contact),
-            None => Err(PeopleApiError::ApiError(format!(
-                "Contact not found: {}",
-                resource_name
-            ))),
-        }
-    }
-
-    fn parse_contact(&self, data: &Value) -> Result<Contact, PeopleApiError> {
-        if self.should_fail {
-            return match self.fail_mode {
-                FailMode::Parse => Err(PeopleApiError::ParseError("Parse error".to_string())),
-                _ => Err(PeopleApiError::ParseError("Unexpected error".to_string())),
-            };
-        }
-
-        // Extract resource name
-        let resource_name = data
-            .get("resourceName")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| PeopleApiError::ParseError("Missing resourceName".to_string()))?
-            .to_string();
-
-        // Parse name
-        let name = if let Some(names) = data.get("names").and_then(|v| v.as_array()) {
-            if let Some(primary_name) = names.first() {
-                let display_name = primary_name
-                    .get("displayName")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("Unknown")
-                    .to_string();
-
-                let given_name = primary_name
-                    .get("givenName")
-                    .and_then(|v| v.as_str())
-                    .map(|s| s.to_string());
-
-                let family_name = primary_name
-                    .get("familyName")
-                    .and_then(|v| v.as_str())
-                    .map(|s| s.to_string());
-
-                Some(PersonName {
-                    display_name,
-                    given_name,
-                    family_name,
-                })
-            } else {
-                None
-            }
-        } else {
-            None
-        };
-
-        // Parse email addresses
-        let mut email_addresses = Vec::new();
-        if let Some(emails) = data.get("emailAddresses").and_then(|v| v.as_array()) {
-            for email in emails {
-                if let Some(value) = email.get("value").and_then(|v| v.as_str()) {
-                    let type_ = email
-                        .get("type")
-                        .and_then(|v| v.as_str())
-                        .map(|s| s.to_string());
-
-                    email_addresses.push(EmailAddress {
-                        value: value.to_string(),
-                        type_,
-                    });
-                }
-            }
-        }
-
-        // Parse phone numbers
-        let mut phone_numbers = Vec::new();
-        if let Some(phones) = data.get("phoneNumbers").and_then(|v| v.as_array()) {
-            for phone in phones {
-                if let Some(value) = phone.get("value").and_then(|v| v.as_str()) {
-                    let type_ = phone
-                        .get("type")
-                        .and_then(|v| v.as_str())
-                        .map(|s| s.to_string());
-
-                    phone_numbers.push(PhoneNumber {
-                        value: value.to_string(),
-                        type_,
-                    });
-                }
-            }
-        }
-
-        // Parse organizations
-        let mut organizations = Vec::new();
-        if let Some(orgs) = data.get("organizations").and_then(|v| v.as_array()) {
-            for org in orgs {
-                let name = org
-                    .get("name")
-                    .and_then(|v| v.as_str())
-                    .map(|s| s.to_string());
-
-                let title = org
-                    .get("title")
-                    .and_then(|v| v.as_str())
-                    .map(|s| s.to_string());
-
-                organizations.push(Organization { name, title });
-            }
-        }
-
-        // Parse photos
-        let mut photos = Vec::new();
-        if let Some(pics) = data.get("photos").and_then(|v| v.as_array()) {
-            for pic in pics {
-                if let Some(url) = pic.get("url").and_then(|v| v.as_str()) {
-                    let default = pic
-                        .get("default")
-                        .and_then(|v| v.as_bool())
-                        .unwrap_or(false);
-
-                    photos.push(Photo {
-                        url: url.to_string(),
-                        default,
-                    });
-                }
-            }
+impl FailMode {
+    fn error(self) -> PeopleApiError {
+        match self {
+            FailMode::Auth => PeopleApiError::AuthError("Authentication failed".to_string()),
+            FailMode::Network => PeopleApiError::NetworkError("Network error".to_string()),
+            FailMode::Api => PeopleApiError::ApiError("API error".to_string()),
+            FailMode::Parse => PeopleApiError::ParseError("Parse error".to_string()),
         }
-
-        Ok(Contact {
-            resource_name,
-            name,
-            email_addresses,
-            phone_numbers,
-            organizations,
-            photos,
-        })
     }
 }
 
@@ -673,24 +383,34 @@ mod comprehensive_people_api_tests {
     use super::*;
 
     // Helper functions to create test clients
-    fn create_test_client() -> MockablePeopleClient {
-        let mock_client = MockPeopleClient::new();
-        let client = Arc::new(Mutex::new(mock_client));
-        MockablePeopleClient::new(client)
+    fn create_test_client() -> MockPeopleApi {
+        MockPeopleApi::new().with_contacts(sample_contacts())
     }
 
-    fn create_failing_client(mode: FailMode) -> MockablePeopleClient {
-        let mock_client = MockPeopleClient::new().with_failure(mode);
-        let client = Arc::new(Mutex::new(mock_client));
-        MockablePeopleClient::new(client)
+    fn create_failing_client(mode: FailMode) -> MockPeopleApi {
+        MockPeopleApi::new()
+            .with_contacts(sample_contacts())
+            .failing_with(move || mode.error())
     }
 
-    #[test]
-    fn test_list_contacts_success() {
+    // The parser tests run against the production People API client
+    fn create_parser() -> PeopleClient {
+        PeopleClient::new(&Config {
+            client_id: "test_client_id".to_string(),
+            client_secret: "test_client_secret".to_string(),
+            refresh_token: "test_refresh_token".to_string(),
+            access_token: None,
+            token_refresh_threshold: 300,
+            token_expiry_buffer: 60,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_list_contacts_success() {
         let client = create_test_client();
 
         // Test listing all contacts
-        let result = client.list_contacts(None);
+        let result = client.list_contacts(None).await;
         assert!(result.is_ok());
         
         let contact_list = result.unwrap();
@@ -708,7 +428,7 @@ mod comprehensive_people_api_tests {
         assert_eq!(contact1.photos.len(), 1);
 
         // Test with max_results
-        let result = client.list_contacts(Some(2));
+        let result = client.list_contacts(Some(2)).await;
         assert!(result.is_ok());
         
         let contact_list = result.unwrap();
@@ -716,36 +436,36 @@ mod comprehensive_people_api_tests {
         assert_eq!(contact_list.total_items, Some(5)); // Still reports total of 5
     }
 
-    #[test]
-    fn test_list_contacts_failure() {
+    #[tokio::test]
+    async fn test_list_contacts_failure() {
         // Test various failure modes
         let auth_client = create_failing_client(FailMode::Auth);
-        let result = auth_client.list_contacts(None);
+        let result = auth_client.list_contacts(None).await;
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), PeopleApiError::AuthError(_)));
 
         let network_client = create_failing_client(FailMode::Network);
-        let result = network_client.list_contacts(None);
+        let result = network_client.list_contacts(None).await;
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), PeopleApiError::NetworkError(_)));
 
         let api_client = create_failing_client(FailMode::Api);
-        let result = api_client.list_contacts(None);
+        let result = api_client.list_contacts(None).await;
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), PeopleApiError::ApiError(_)));
 
         let parse_client = create_failing_client(FailMode::Parse);
-        let result = parse_client.list_contacts(None);
+        let result = parse_client.list_contacts(None).await;
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), PeopleApiError::ParseError(_)));
     }
 
-    #[test]
-    fn test_search_contacts_success() {
+    #[tokio::test]
+    async fn test_search_contacts_success() {
         let client = create_test_client();
 
         // Test searching by name
-        let result = client.search_contacts("John", None);
+        let result = client.search_contacts("John", None).await;
         assert!(result.is_ok());
         
         let contact_list = result.unwrap();
@@ -760,7 +480,7 @@ mod comprehensive_people_api_tests {
         assert_eq!(john_doe.name.as_ref().unwrap().display_name, "John Doe");
 
         // Test searching by email
-        let result = client.search_contacts("smith", None);
+        let result = client.search_contacts("smith", None).await;
         assert!(result.is_ok());
         
         let contact_list = result.unwrap();
@@ -768,7 +488,7 @@ mod comprehensive_people_api_tests {
         assert_eq!(contact_list.contacts[0].name.as_ref().unwrap().display_name, "Jane Smith");
 
         // Test searching by organization
-        let result = client.search_contacts("ABC Company", None);
+        let result = client.search_contacts("ABC Company", None).await;
         assert!(result.is_ok());
         
         let contact_list = result.unwrap();
@@ -776,7 +496,7 @@ mod comprehensive_people_api_tests {
         assert_eq!(contact_list.contacts[0].name.as_ref().unwrap().display_name, "Alex Johnson");
 
         // Test searching by position
-        let result = client.search_contacts("Director", None);
+        let result = client.search_contacts("Director", None).await;
         assert!(result.is_ok());
         
         let contact_list = result.unwrap();
@@ -784,26 +504,26 @@ mod comprehensive_people_api_tests {
         assert_eq!(contact_list.contacts[0].name.as_ref().unwrap().display_name, "Alex Johnson");
 
         // Test with no results
-        let result = client.search_contacts("NonExistent", None);
+        let result = client.search_contacts("NonExistent", None).await;
         assert!(result.is_ok());
         
         let contact_list = result.unwrap();
         assert_eq!(contact_list.contacts.len(), 0);
 
         // Test with max_results
-        let result = client.search_contacts("e", Some(1)); // Should match all, but limit to 1
+        let result = client.search_contacts("e", Some(1)).await; // Should match all, but limit to 1
         assert!(result.is_ok());
         
         let contact_list = result.unwrap();
         assert_eq!(contact_list.contacts.len(), 1);
     }
 
-    #[test]
-    fn test_search_international_contacts() {
+    #[tokio::test]
+    async fn test_search_international_contacts() {
         let client = create_test_client();
         
         // Test searching by international name
-        let result = client.search_contacts("José", None);
+        let result = client.search_contacts("José", None).await;
         assert!(result.is_ok());
         
         let contact_list = result.unwrap();
@@ -811,14 +531,14 @@ mod comprehensive_people_api_tests {
         assert_eq!(contact_list.contacts[0].name.as_ref().unwrap().display_name, "José García");
         
         // Test searching by partial international name (accent insensitive)
-        let result = client.search_contacts("jose", None);
+        let result = client.search_contacts("jose", None).await;
         assert!(result.is_ok());
         
         let contact_list = result.unwrap();
         assert_eq!(contact_list.contacts.len(), 1);
         
         // Test searching by Chinese name
-        let result = client.search_contacts("张伟", None);
+        let result = client.search_contacts("张伟", None).await;
         assert!(result.is_ok());
         
         let contact_list = result.unwrap();
@@ -826,7 +546,7 @@ mod comprehensive_people_api_tests {
         assert_eq!(contact_list.contacts[0].name.as_ref().unwrap().display_name, "张伟");
         
         // Test searching by international company
-        let result = client.search_contacts("中国公司", None);
+        let result = client.search_contacts("中国公司", None).await;
         assert!(result.is_ok());
         
         let contact_list = result.unwrap();
@@ -834,36 +554,36 @@ mod comprehensive_people_api_tests {
         assert_eq!(contact_list.contacts[0].name.as_ref().unwrap().display_name, "张伟");
     }
 
-    #[test]
-    fn test_search_contacts_failure() {
+    #[tokio::test]
+    async fn test_search_contacts_failure() {
         // Test various failure modes
         let auth_client = create_failing_client(FailMode::Auth);
-        let result = auth_client.search_contacts("test", None);
+        let result = auth_client.search_contacts("test", None).await;
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), PeopleApiError::AuthError(_)));
 
         let network_client = create_failing_client(FailMode::Network);
-        let result = network_client.search_contacts("test", None);
+        let result = network_client.search_contacts("test", None).await;
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), PeopleApiError::NetworkError(_)));
 
         let api_client = create_failing_client(FailMode::Api);
-        let result = api_client.search_contacts("test", None);
+        let result = api_client.search_contacts("test", None).await;
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), PeopleApiError::ApiError(_)));
 
         let parse_client = create_failing_client(FailMode::Parse);
-        let result = parse_client.search_contacts("test", None);
+        let result = parse_client.search_contacts("test", None).await;
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), PeopleApiError::ParseError(_)));
     }
 
-    #[test]
-    fn test_get_contact_success() {
+    #[tokio::test]
+    async fn test_get_contact_success() {
         let client = create_test_client();
 
         // Test getting contact by resource name
-        let result = client.get_contact("people/contact1");
+        let result = client.get_contact("people/contact1").await;
         assert!(result.is_ok());
         
         let contact = result.unwrap();
@@ -893,7 +613,7 @@ mod comprehensive_people_api_tests {
         assert!(contact.photos[0].default);
 
         // Test contact with multiple emails, phones, and photos
-        let result = client.get_contact("people/contact3");
+        let result = client.get_contact("people/contact3").await;
         assert!(result.is_ok());
         
         let contact = result.unwrap();
@@ -905,12 +625,12 @@ mod comprehensive_people_api_tests {
         assert_eq!(default_photos.len(), 1);
     }
 
-    #[test]
-    fn test_get_contact_not_found() {
+    #[tokio::test]
+    async fn test_get_contact_not_found() {
         let client = create_test_client();
 
         // Test getting non-existent contact
-        let result = client.get_contact("people/nonexistent");
+        let result = client.get_contact("people/nonexistent").await;
         assert!(result.is_err());
         
         if let Err(PeopleApiError::ApiError(msg)) = result {
@@ -920,33 +640,33 @@ mod comprehensive_people_api_tests {
         }
     }
 
-    #[test]
-    fn test_get_contact_failure() {
+    #[tokio::test]
+    async fn test_get_contact_failure() {
         // Test various failure modes
         let auth_client = create_failing_client(FailMode::Auth);
-        let result = auth_client.get_contact("people/contact1");
+        let result = auth_client.get_contact("people/contact1").await;
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), PeopleApiError::AuthError(_)));
 
         let network_client = create_failing_client(FailMode::Network);
-        let result = network_client.get_contact("people/contact1");
+        let result = network_client.get_contact("people/contact1").await;
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), PeopleApiError::NetworkError(_)));
 
         let api_client = create_failing_client(FailMode::Api);
-        let result = api_client.get_contact("people/contact1");
+        let result = api_client.get_contact("people/contact1").await;
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), PeopleApiError::ApiError(_)));
 
         let parse_client = create_failing_client(FailMode::Parse);
-        let result = parse_client.get_contact("people/contact1");
+        let result = parse_client.get_contact("people/contact1").await;
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), PeopleApiError::ParseError(_)));
     }
 
     #[test]
     fn test_parse_contact_success() {
-        let client = create_test_client();
+        let client = create_parser();
 
         // Create test JSON for full contact
         let contact_json = create_test_contact_json(
@@ -999,7 +719,7 @@ mod comprehensive_people_api_tests {
 
     #[test]
     fn test_parse_extended_contact() {
-        let client = create_test_client();
+        let client = create_parser();
         
         // Create an extended contact JSON with many fields
         let extended_json = create_extended_contact_json(
@@ -1032,7 +752,7 @@ mod comprehensive_people_api_tests {
 
     #[test]
     fn test_parse_contact_minimal() {
-        let client = create_test_client();
+        let client = create_parser();
 
         // Create minimal contact JSON with only resourceName and names
         let minimal_json = json!({
@@ -1063,7 +783,7 @@ mod comprehensive_people_api_tests {
 
     #[test]
     fn test_parse_contact_missing_required() {
-        let client = create_test_client();
+        let client = create_parser();
 
         // Missing resourceName (required field)
         let invalid_json = json!({
@@ -1085,7 +805,7 @@ mod comprehensive_people_api_tests {
 
     #[test]
     fn test_parse_contact_international() {
-        let client = create_test_client();
+        let client = create_parser();
 
         // Create test JSON with international characters
         let international_json = create_test_contact_json(
@@ -1120,7 +840,7 @@ mod comprehensive_people_api_tests {
 
     #[test]
     fn test_parse_contact_cjk() {
-        let client = create_test_client();
+        let client = create_parser();
 
         // Create test JSON with CJK (Chinese, Japanese, Korean) characters
         let cjk_json = create_test_contact_json(
@@ -1152,7 +872,7 @@ mod comprehensive_people_api_tests {
 
     #[test]
     fn test_parse_contact_no_names() {
-        let client = create_test_client();
+        let client = create_parser();
 
         // Contact with no names section (edge case)
         let no_names_json = json!({
@@ -1179,7 +899,7 @@ mod comprehensive_people_api_tests {
 
     #[test]
     fn test_parse_contact_empty_arrays() {
-        let client = create_test_client();
+        let client = create_parser();
 
         // Contact with empty arrays
         let empty_arrays_json = json!({
@@ -1211,11 +931,11 @@ mod comprehensive_people_api_tests {
 
     #[test]
     fn test_parse_contact_failure() {
-        let client = create_failing_client(FailMode::Parse);
+        let client = create_parser();
 
-        // Any JSON should fail during parsing
+        // A resourceName of the wrong type cannot be parsed
         let json = json!({
-            "resourceName": "people/test"
+            "resourceName": 12345
         });
 
         let result = client.parse_contact(&json);
@@ -1225,7 +945,7 @@ mod comprehensive_people_api_tests {
     
     #[test]
     fn test_very_long_fields() {
-        let client = create_test_client();
+        let client = create_parser();
         
         // Create a contact with very long fields to test handling of large data
         let long_name = "A".repeat(1000);
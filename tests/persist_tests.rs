@@ -0,0 +1,41 @@
+/// State File Tests Module
+///
+/// This module contains tests for the JSON state files, checking that a
+/// missing file reads as empty, that a stored value reads back, that an
+/// unreadable file is an error and that no temporary file is left behind.
+use mcp_gmailcal::persist::{load_json, store_json};
+use std::collections::HashMap;
+
+#[test]
+fn test_load_and_store_json() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("state").join("values.json");
+
+    let empty: Vec<String> = load_json(&path).unwrap();
+    assert!(empty.is_empty());
+
+    let values = vec!["one".to_string(), "two".to_string()];
+    store_json(&path, &values).unwrap();
+    let loaded: Vec<String> = load_json(&path).unwrap();
+    assert_eq!(loaded, values);
+
+    // Storing replaces the file whole, leaving nothing else in its directory
+    store_json(&path, &values[..1]).unwrap();
+    let loaded: Vec<String> = load_json(&path).unwrap();
+    assert_eq!(loaded, vec!["one"]);
+    let files: Vec<_> = std::fs::read_dir(path.parent().unwrap())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert_eq!(files, vec!["values.json"]);
+}
+
+#[test]
+fn test_load_unreadable_json() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("values.json");
+    std::fs::write(&path, "{ not json").unwrap();
+
+    let err = load_json::<HashMap<String, String>>(&path).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
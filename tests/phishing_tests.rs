@@ -0,0 +1,176 @@
+/// Phishing Tests Module
+///
+/// This module contains tests for the phishing risk heuristics and the
+/// assess_phishing_risk tool against the mock server.
+use mcp_gmailcal::gmail_api::EmailMessage;
+use mcp_gmailcal::phishing::{PhishingAssessment, RiskLevel};
+use mcp_gmailcal::sender_auth::{AuthCheck, AuthenticationResults, SenderVerdict};
+use mcp_gmailcal::test_util::message;
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn check(result: &str, domain: &str) -> Option<AuthCheck> {
+    Some(AuthCheck {
+        result: result.to_string(),
+        domain: Some(domain.to_string()),
+    })
+}
+
+fn passed(domain: &str) -> Option<AuthenticationResults> {
+    Some(AuthenticationResults {
+        authserv_id: Some("mx.google.com".to_string()),
+        spf: check("pass", domain),
+        dkim: check("pass", domain),
+        dmarc: check("pass", domain),
+    })
+}
+
+fn kinds(assessment: &PhishingAssessment) -> Vec<&str> {
+    assessment
+        .signals
+        .iter()
+        .map(|signal| signal.kind.as_str())
+        .collect()
+}
+
+#[test]
+fn test_legitimate_message_is_low_risk() {
+    let assessment = PhishingAssessment::from_message(&EmailMessage {
+        body_text: Some("Want to grab lunch? Menu: https://www.example.com/menu".to_string()),
+        body_html: Some(
+            "<p>Menu: <a href=\"https://www.example.com/menu\">example.com/menu</a></p>"
+                .to_string(),
+        ),
+        authentication: passed("example.com"),
+        ..message(
+            "msg-1",
+            "Alice Example <alice@example.com>",
+            "Lunch on Friday?",
+        )
+    });
+    assert_eq!(assessment.risk_score, 0);
+    assert_eq!(assessment.risk_level, RiskLevel::Low);
+    assert_eq!(assessment.sender_verdict, SenderVerdict::Authenticated);
+    assert_eq!(assessment.link_domains, vec!["example.com"]);
+    assert!(assessment.signals.is_empty());
+}
+
+#[test]
+fn test_phishing_message_is_high_risk() {
+    let email = EmailMessage {
+        body_text: Some(
+            "Verify your account within 24 hours or it will be locked: https://bit.ly/abc123"
+                .to_string(),
+        ),
+        body_html: Some(
+            "<p>Please <a href=\"https://login-help.example/bank\">https://www.bank.example/login</a> \
+             or visit <a href='http://192.0.2.55/verify'>our site</a>.</p>"
+                .to_string(),
+        ),
+        authentication: Some(AuthenticationResults {
+            authserv_id: Some("mx.google.com".to_string()),
+            spf: check("pass", "login-help.example"),
+            dkim: None,
+            dmarc: check("fail", "login-help.example"),
+        }),
+        ..message(
+            "msg-1",
+            "\"security@bank.example\" <alerts@login-help.example>",
+            "URGENT: Unusual activity on your account",
+        )
+    };
+
+    let assessment = PhishingAssessment::from_message(&email);
+    assert_eq!(assessment.risk_level, RiskLevel::High);
+    assert_eq!(assessment.risk_score, 100);
+    assert_eq!(assessment.sender_verdict, SenderVerdict::Failed);
+    assert_eq!(
+        assessment.link_domains,
+        vec!["192.0.2.55", "bit.ly", "login-help.example"]
+    );
+
+    let kinds = kinds(&assessment);
+    for kind in [
+        "sender_authentication",
+        "display_name_mismatch",
+        "suspicious_link",
+        "urgency",
+    ] {
+        assert!(kinds.contains(&kind), "missing {}", kind);
+    }
+    let details: Vec<&str> = assessment
+        .signals
+        .iter()
+        .map(|signal| signal.detail.as_str())
+        .collect();
+    assert!(details.contains(&"Link text shows bank.example but leads to login-help.example"));
+    assert!(details.contains(
+        &"Pressing language: urgent, within 24 hours, verify your account, unusual activity"
+    ));
+
+    // Signals come heaviest first, and link signals are capped
+    assert!(assessment
+        .signals
+        .windows(2)
+        .all(|pair| pair[0].weight >= pair[1].weight));
+    let link_score: u32 = assessment
+        .signals
+        .iter()
+        .filter(|signal| signal.kind == "suspicious_link")
+        .map(|signal| signal.weight)
+        .sum();
+    assert_eq!(link_score, 40);
+}
+
+#[test]
+fn test_link_tricks() {
+    let assessment = PhishingAssessment::from_message(&EmailMessage {
+        body_text: Some(
+            "https://www.example.com@files.example.net/share and https://xn--exmple-cua.com/ and https://files.zip/report"
+                .to_string(),
+        ),
+        authentication: passed("example.com"),
+        ..message("msg-1", "Alice Example <alice@example.com>", "Shared files")
+    });
+    let details: Vec<&str> = assessment
+        .signals
+        .iter()
+        .map(|signal| signal.detail.as_str())
+        .collect();
+    assert_eq!(details.len(), 3);
+    assert!(details[0].contains("behind a user name"));
+    assert!(details[1].contains("internationalized domain"));
+    assert!(details[2].contains(".zip top-level domain"));
+    assert_eq!(assessment.risk_level, RiskLevel::Medium);
+}
+
+#[tokio::test]
+async fn test_assess_phishing_risk_tool() {
+    let (_server, client) = mock_client(None).await;
+
+    let result = call_tool(
+        &client,
+        "assess_phishing_risk",
+        json!({ "message_id": "mock-msg-001" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["risk_score"], 0);
+    assert_eq!(result["risk_level"], "low");
+    assert_eq!(result["sender_verdict"], "authenticated");
+
+    // No authentication results to go on
+    let result = call_tool(
+        &client,
+        "assess_phishing_risk",
+        json!({ "message_id": "mock-msg-003" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["sender_verdict"], "unknown");
+    assert_eq!(result["signals"][0]["kind"], "sender_authentication");
+    assert_eq!(result["signals"][0]["weight"], 10);
+    assert_eq!(result["risk_level"], "low");
+}
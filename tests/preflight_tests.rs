@@ -0,0 +1,82 @@
+/// Preflight Tests Module
+///
+/// This module contains tests for the startup preflight checks, their
+/// readiness summary, and running them against the mock server.
+use clap::Parser;
+use mcp_gmailcal::cli::Cli;
+use mcp_gmailcal::doctor::{CheckResult, CheckStatus};
+use mcp_gmailcal::mock::MockServer;
+use mcp_gmailcal::preflight::{run_preflight, summarize};
+
+fn check(name: &str, status: CheckStatus) -> CheckResult {
+    CheckResult {
+        name: name.to_string(),
+        status,
+        detail: String::new(),
+        fix: None,
+    }
+}
+
+#[test]
+fn test_summarize() {
+    let results = vec![
+        check("Configuration", CheckStatus::Pass),
+        check("Access token", CheckStatus::Pass),
+        check("Gmail API", CheckStatus::Pass),
+    ];
+    assert_eq!(summarize(&results), "Ready: 3 of 3 checks passed");
+
+    let results = vec![
+        check("Configuration", CheckStatus::Pass),
+        check("Access token", CheckStatus::Pass),
+        check("Gmail API", CheckStatus::Pass),
+        check("Tasks API", CheckStatus::Warn),
+    ];
+    assert_eq!(
+        summarize(&results),
+        "Ready: 3 of 4 checks passed, 1 warnings"
+    );
+
+    let results = vec![
+        check("Configuration", CheckStatus::Pass),
+        check("Access token", CheckStatus::Fail),
+        check("Gmail API", CheckStatus::Skip),
+    ];
+    assert_eq!(
+        summarize(&results),
+        "Not ready: 1 of 3 checks passed, failed: Access token"
+    );
+}
+
+#[test]
+fn test_preflight_flag() {
+    assert!(!Cli::try_parse_from(["mcp-gmailcal"]).unwrap().preflight);
+    let cli = Cli::try_parse_from(["mcp-gmailcal", "--preflight", "server"]).unwrap();
+    assert!(cli.preflight);
+}
+
+#[tokio::test]
+async fn test_run_preflight() {
+    let server = MockServer::start(None).await.unwrap();
+    server.install();
+
+    let results = run_preflight().await;
+    let names: Vec<&str> = results.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec![
+            "Configuration",
+            "Access token",
+            "Gmail API",
+            "Calendar API",
+            "People API",
+            "Tasks API",
+            "Drive API"
+        ]
+    );
+    for result in &results {
+        assert_eq!(result.status, CheckStatus::Pass, "{}", result.name);
+    }
+    assert_eq!(results[2].detail, "Signed in as demo@example.com");
+    assert_eq!(summarize(&results), "Ready: 7 of 7 checks passed");
+}
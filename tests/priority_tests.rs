@@ -0,0 +1,145 @@
+/// Priority Tests Module
+///
+/// This module contains tests for reading priority headers and the IMPORTANT
+/// label, setting priority headers on drafts, and both against the mock
+/// server.
+use mcp_gmailcal::gmail_api::{encode_raw_message, DraftEmail};
+use mcp_gmailcal::priority::{Priority, PriorityLevel};
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+#[test]
+fn test_parse_priority_level() {
+    assert_eq!(
+        PriorityLevel::parse("1 (Highest)"),
+        Some(PriorityLevel::High)
+    );
+    assert_eq!(PriorityLevel::parse("2"), Some(PriorityLevel::High));
+    assert_eq!(PriorityLevel::parse("Urgent"), Some(PriorityLevel::High));
+    assert_eq!(
+        PriorityLevel::parse("3 (Normal)"),
+        Some(PriorityLevel::Normal)
+    );
+    assert_eq!(PriorityLevel::parse("normal"), Some(PriorityLevel::Normal));
+    assert_eq!(PriorityLevel::parse("5 (Lowest)"), Some(PriorityLevel::Low));
+    assert_eq!(PriorityLevel::parse("non-urgent"), Some(PriorityLevel::Low));
+    assert_eq!(PriorityLevel::parse("whenever"), None);
+    assert_eq!(PriorityLevel::parse(""), None);
+}
+
+#[test]
+fn test_priority_from_headers() {
+    let priority = Priority::from_headers(
+        &[("x-priority", "5 (Lowest)"), ("Importance", "High")],
+        true,
+    );
+    // X-Priority is read first
+    assert_eq!(priority.level, Some(PriorityLevel::Low));
+    assert!(priority.important);
+    assert_eq!(priority.x_priority.as_deref(), Some("5 (Lowest)"));
+    assert_eq!(priority.importance.as_deref(), Some("High"));
+
+    let priority = Priority::from_headers(&[("X-MSMail-Priority", "High")], false);
+    assert_eq!(priority.level, Some(PriorityLevel::High));
+    assert_eq!(priority.x_priority, None);
+
+    // Unreadable values fall through to the next header
+    let priority = Priority::from_headers(&[("X-Priority", "?"), ("Priority", "urgent")], false);
+    assert_eq!(priority.level, Some(PriorityLevel::High));
+
+    assert_eq!(Priority::from_headers(&[], false), Priority::default());
+}
+
+#[test]
+fn test_draft_priority_headers() {
+    let mut draft = DraftEmail {
+        to: "alice@example.com".to_string(),
+        subject: "Outage".to_string(),
+        body: "The site is down.".to_string(),
+        cc: None,
+        bcc: None,
+        thread_id: None,
+        in_reply_to: None,
+        references: None,
+        priority: Some(PriorityLevel::High),
+        read_receipt_to: None,
+    };
+    let raw = base64::decode_config(encode_raw_message(&draft), base64::URL_SAFE).unwrap();
+    let raw = String::from_utf8(raw).unwrap();
+    assert!(raw.contains("X-Priority: 1 (Highest)\r\nImportance: high\r\n"));
+    // Headers come before the body
+    assert!(raw.find("Importance").unwrap() < raw.find("The site is down.").unwrap());
+
+    draft.priority = None;
+    let raw = base64::decode_config(encode_raw_message(&draft), base64::URL_SAFE).unwrap();
+    let raw = String::from_utf8(raw).unwrap();
+    assert!(!raw.contains("X-Priority"));
+    assert!(!raw.contains("Importance"));
+}
+
+#[tokio::test]
+async fn test_priority_tools() {
+    let (_server, client) = mock_client(None).await;
+
+    let email = call_tool(
+        &client,
+        "get_email",
+        json!({ "message_id": "mock-msg-001" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        email["priority"],
+        json!({
+            "level": "high",
+            "important": true,
+            "x_priority": "1 (Highest)",
+            "importance": null
+        })
+    );
+
+    let email = call_tool(
+        &client,
+        "get_email",
+        json!({ "message_id": "mock-msg-003" }),
+    )
+    .await
+    .unwrap();
+    assert!(email["priority"]["level"].is_null());
+    assert_eq!(email["priority"]["important"], false);
+
+    let result = call_tool(
+        &client,
+        "create_draft_email",
+        json!({
+            "to": "alice@example.com",
+            "subject": "Outage",
+            "body": "The site is down.",
+            "priority": "High"
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["status"], "success");
+    assert_eq!(result["priority"], "high");
+
+    let error = call_tool(
+        &client,
+        "create_draft_email",
+        json!({
+            "to": "alice@example.com",
+            "subject": "Outage",
+            "body": "The site is down.",
+            "priority": "whenever"
+        }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("Invalid priority 'whenever'"));
+}
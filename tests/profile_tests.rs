@@ -0,0 +1,83 @@
+/// Profile Tests Module
+///
+/// This module contains tests for the account profile, checking storage quota
+/// figures through the Drive API trait and the get_profile tool against the
+/// mock server, with and without a storage quota.
+use mcp_gmailcal::api::DriveApi;
+use mcp_gmailcal::errors::DriveApiError;
+use mcp_gmailcal::test_util::MockDriveApi;
+use mcp_gmailcal::StorageQuota;
+use serde_json::{json, Value};
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn quota(limit: Option<u64>, usage: u64) -> StorageQuota {
+    StorageQuota {
+        limit,
+        usage,
+        usage_in_drive: None,
+        usage_in_drive_trash: None,
+    }
+}
+
+#[test]
+fn test_used_percent() {
+    assert_eq!(quota(Some(1000), 250).used_percent(), Some(25.0));
+    assert_eq!(quota(Some(3000), 1000).used_percent(), Some(33.3));
+    assert_eq!(quota(None, 1000).used_percent(), None);
+    assert_eq!(quota(Some(0), 0).used_percent(), None);
+}
+
+#[tokio::test]
+async fn test_mock_storage_quota() {
+    let drive = MockDriveApi::new().with_storage_quota(quota(Some(100), 10));
+    assert_eq!(
+        drive.get_storage_quota().await.unwrap(),
+        quota(Some(100), 10)
+    );
+
+    assert!(MockDriveApi::new().get_storage_quota().await.is_err());
+    let drive = MockDriveApi::new()
+        .with_storage_quota(quota(None, 10))
+        .failing_with(|| DriveApiError::AuthError("insufficient scope".to_string()));
+    assert!(drive.get_storage_quota().await.is_err());
+}
+
+#[tokio::test]
+async fn test_get_profile_tool() {
+    let (_server, client) = mock_client(None).await;
+
+    let profile = call_tool(&client, "get_profile", json!({})).await.unwrap();
+    assert_eq!(
+        profile,
+        json!({
+            "email_address": "demo@example.com",
+            "messages_total": 3,
+            "threads_total": 2,
+            "history_id": "1000",
+            "storage_quota": {
+                "limit": 16106127360u64,
+                "usage": 4026531840u64,
+                "usage_in_drive": 2147483648u64,
+                "usage_in_drive_trash": 104857600,
+                "used_percent": 25.0
+            }
+        })
+    );
+
+    // A quota Drive cannot give leaves the rest of the profile
+    let dir = tempfile::tempdir().unwrap();
+    let drive = dir.path().join("drive/v3");
+    std::fs::create_dir_all(&drive).unwrap();
+    std::fs::write(drive.join("about.json"), json!({}).to_string()).unwrap();
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    let profile = call_tool(&client, "get_profile", json!({})).await.unwrap();
+    assert_eq!(profile["email_address"], "demo@example.com");
+    assert_eq!(profile["storage_quota"], Value::Null);
+    assert!(profile["storage_quota_error"]
+        .as_str()
+        .unwrap()
+        .contains("usage"));
+}
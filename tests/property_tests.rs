@@ -70,6 +70,7 @@ fn email_message_strategy() -> impl Strategy<Value = EmailMessage> {
             snippet,
             body_text,
             body_html,
+            ..Default::default()
         }
     })
 }
@@ -113,6 +114,8 @@ fn draft_email_strategy() -> impl Strategy<Value = DraftEmail> {
             thread_id,
             in_reply_to,
             references,
+            priority: None,
+            read_receipt_to: None,
         }
     })
 }
@@ -150,6 +153,8 @@ fn calendar_event_strategy() -> impl Strategy<Value = CalendarEvent> {
                     display_name,
                     response_status,
                     optional,
+                    time_zone: None,
+                    self_: None,
                 }
             }),
             0..5
@@ -170,9 +175,7 @@ fn calendar_event_strategy() -> impl Strategy<Value = CalendarEvent> {
             end_time,
             attendees,
             conference_data: None, // Simplify by not testing conference data
-            html_link: None,
-            creator: None,
-            organizer: None,
+            ..Default::default()
         }
     })
 }
@@ -421,6 +424,7 @@ fn test_email_message_invariants() {
         snippet: Some("This is a test email...".to_string()),
         body_text: Some("This is the plain text body.".to_string()),
         body_html: Some("<div>This is the HTML body.</div>".to_string()),
+        ..Default::default()
     };
     
     // Serialize to JSON
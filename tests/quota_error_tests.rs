@@ -0,0 +1,189 @@
+/// Quota Error Tests Module
+///
+/// This module contains tests for rate limit and quota responses from Google,
+/// checking that the error body and `Retry-After` header are parsed into a
+/// typed error, that daily and per-user quotas are told apart, and that the
+/// MCP error data carries the recommended wait.
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::IntoResponse;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::{TimeZone, Utc};
+use mcp_gmailcal::errors::{parse_retry_after, seconds_until_quota_reset};
+use mcp_gmailcal::utils::{error_codes, DEFAULT_RATE_LIMIT_RETRY_SECONDS};
+use mcp_gmailcal::{
+    map_gmail_error, map_to_mcp_error, Config, GmailApiError, GmailService, PeopleApiError,
+    QuotaScope, RateLimitInfo,
+};
+use serde_json::json;
+
+fn google_error(code: u16, reason: &str, message: &str) -> String {
+    json!({
+        "error": {
+            "code": code,
+            "message": message,
+            "errors": [{ "domain": "usageLimits", "reason": reason, "message": message }]
+        }
+    })
+    .to_string()
+}
+
+#[test]
+fn test_rate_limit_responses_are_parsed() {
+    let body = google_error(429, "userRateLimitExceeded", "User-rate limit exceeded");
+    let info = RateLimitInfo::from_response(429, Some("30"), &body).unwrap();
+    assert_eq!(info.scope, QuotaScope::PerUser);
+    assert_eq!(info.retry_after, Some(30));
+    assert_eq!(info.reason.as_deref(), Some("userRateLimitExceeded"));
+    assert_eq!(info.message, "User-rate limit exceeded");
+    assert_eq!(
+        info.to_string(),
+        "Rate limit exceeded (per_user quota): User-rate limit exceeded"
+    );
+
+    // Quota errors may also come as 403
+    let body = google_error(403, "dailyLimitExceeded", "Daily Limit Exceeded");
+    let info = RateLimitInfo::from_response(403, None, &body).unwrap();
+    assert_eq!(info.scope, QuotaScope::Daily);
+    assert_eq!(info.retry_after, None);
+
+    let body = google_error(403, "rateLimitExceeded", "Rate Limit Exceeded");
+    let info = RateLimitInfo::from_response(403, None, &body).unwrap();
+    assert_eq!(info.scope, QuotaScope::Project);
+
+    // The daily limit can also be named in the quota metadata
+    let body = json!({
+        "error": {
+            "code": 429,
+            "message": "Quota exceeded for quota metric 'Queries'",
+            "status": "RESOURCE_EXHAUSTED",
+            "details": [{
+                "reason": "RATE_LIMIT_EXCEEDED",
+                "metadata": { "quota_limit": "DefaultPerDayPerProject" }
+            }]
+        }
+    })
+    .to_string();
+    let info = RateLimitInfo::from_response(429, None, &body).unwrap();
+    assert_eq!(info.scope, QuotaScope::Daily);
+
+    let info = RateLimitInfo::from_response(429, None, "Too Many Requests").unwrap();
+    assert_eq!(info.scope, QuotaScope::Unknown);
+    assert_eq!(info.message, "Too Many Requests");
+}
+
+#[test]
+fn test_other_errors_are_not_rate_limits() {
+    let body = google_error(403, "insufficientPermissions", "Insufficient Permission");
+    assert_eq!(RateLimitInfo::from_response(403, None, &body), None);
+    assert_eq!(RateLimitInfo::from_response(404, None, "Not Found"), None);
+    assert_eq!(RateLimitInfo::from_response(500, Some("5"), ""), None);
+}
+
+#[test]
+fn test_retry_after_and_quota_reset() {
+    let now = Utc.with_ymd_and_hms(2024, 3, 1, 7, 0, 0).unwrap();
+    assert_eq!(parse_retry_after(" 120 ", now), Some(120));
+    assert_eq!(
+        parse_retry_after("Fri, 01 Mar 2024 07:01:30 GMT", now),
+        Some(90)
+    );
+    assert_eq!(
+        parse_retry_after("Fri, 01 Mar 2024 06:00:00 GMT", now),
+        Some(0)
+    );
+    assert_eq!(parse_retry_after("soon", now), None);
+
+    // 07:00 UTC is 23:00 Pacific Standard Time
+    assert_eq!(seconds_until_quota_reset(now), 3600);
+    let now = Utc.with_ymd_and_hms(2024, 3, 1, 8, 0, 0).unwrap();
+    assert_eq!(seconds_until_quota_reset(now), 24 * 3600);
+}
+
+#[test]
+fn test_rate_limit_error_data() {
+    let info = RateLimitInfo::from_response(
+        429,
+        Some("45"),
+        &google_error(429, "userRateLimitExceeded", "User-rate limit exceeded"),
+    )
+    .unwrap();
+    let error = map_gmail_error(GmailApiError::RateLimited(info));
+    let object = error.to_error_object(false);
+    assert!(object.message.contains("Wait 45 seconds before retrying"));
+
+    let data = object.data.unwrap();
+    assert_eq!(data["category"], "rate_limit");
+    assert_eq!(data["code"], error_codes::API_ERROR);
+    assert_eq!(data["retryable"], true);
+    assert_eq!(data["retry_after"], 45);
+    assert_eq!(data["quota_scope"], "per_user");
+
+    // Daily quotas wait for the reset rather than the default
+    let info = RateLimitInfo::from_response(
+        403,
+        None,
+        &google_error(403, "dailyLimitExceeded", "Daily Limit Exceeded"),
+    )
+    .unwrap();
+    assert!(info.recommended_wait() <= 24 * 3600);
+    let error = map_to_mcp_error(PeopleApiError::RateLimited(info));
+    let object = error.to_error_object(false);
+    assert!(object.message.contains("daily People API quota is used up"));
+    assert_eq!(object.data.unwrap()["quota_scope"], "daily");
+
+    let info = RateLimitInfo::from_response(429, None, "").unwrap();
+    assert_eq!(info.recommended_wait(), DEFAULT_RATE_LIMIT_RETRY_SECONDS);
+}
+
+async fn token_handler() -> Json<serde_json::Value> {
+    Json(json!({ "access_token": "quota-token", "expires_in": 3600 }))
+}
+
+async fn rate_limited_handler() -> impl IntoResponse {
+    let mut headers = HeaderMap::new();
+    headers.insert("Retry-After", "12".parse().unwrap());
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        headers,
+        google_error(429, "rateLimitExceeded", "Rate Limit Exceeded"),
+    )
+}
+
+// All client calls share one test since they configure the process
+// environment
+#[tokio::test]
+async fn test_gmail_client_returns_typed_rate_limit_errors() {
+    let app = Router::new()
+        .route("/token", post(token_handler))
+        .route("/gmail/v1/users/me/labels", get(rate_limited_handler))
+        .route("/gmail/v1/users/me/messages/:id", get(rate_limited_handler));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    std::env::set_var("GMAIL_API_BASE_URL", format!("{}/gmail/v1", url));
+    std::env::set_var("OAUTH_TOKEN_URL", format!("{}/token", url));
+    std::env::set_var("GMAIL_CLIENT_ID", "quota-client-id");
+    std::env::set_var("GMAIL_CLIENT_SECRET", "quota-client-secret");
+    std::env::set_var("GMAIL_REFRESH_TOKEN", "quota-refresh-token");
+    std::env::set_var("TOKEN_CACHE_ENABLED", "false");
+
+    let config = Config::from_env().unwrap();
+    let gmail = GmailService::new(&config).unwrap();
+
+    match gmail.get_message_details("msg-1").await {
+        Err(GmailApiError::RateLimited(info)) => {
+            assert_eq!(info.retry_after, Some(12));
+            assert_eq!(info.scope, QuotaScope::Project);
+            assert_eq!(info.reason.as_deref(), Some("rateLimitExceeded"));
+        }
+        other => panic!("Expected RateLimited, got {:?}", other.map(|m| m.id)),
+    }
+
+    // Label listings go through the conditional request cache
+    match gmail.list_labels().await {
+        Err(GmailApiError::RateLimited(info)) => assert_eq!(info.retry_after, Some(12)),
+        other => panic!("Expected RateLimited, got {:?}", other),
+    }
+}
@@ -0,0 +1,178 @@
+/// Read Receipt Tests Module
+///
+/// This module contains tests for asking for read receipts on outgoing mail,
+/// parsing the receipts that come back, and both against the mock server.
+use clap::Parser;
+use mcp_gmailcal::bounces::ReadReceipt;
+use mcp_gmailcal::cli::{Cli, Commands, MailCommands};
+use mcp_gmailcal::gmail_api::{encode_raw_message, DraftEmail};
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+const DISPOSITION: &str = "Reporting-UA: mail.example.net; Example Mail 4.2\r
+Original-Recipient: rfc822;bob@example.net\r
+Final-Recipient: rfc822; bob@example.net\r
+Original-Message-ID: <CAF-quarterly-42@mail.example.com>\r
+Disposition: manual-action/MDN-sent-manually; displayed\r
+";
+
+#[test]
+fn test_parse_read_receipt() {
+    let receipt = ReadReceipt::parse(DISPOSITION).unwrap();
+    assert_eq!(receipt.recipient.as_deref(), Some("bob@example.net"));
+    assert_eq!(receipt.disposition, "displayed");
+    assert!(receipt.read);
+    assert!(!receipt.sent_automatically);
+    assert_eq!(receipt.reporting_ua.as_deref(), Some("Example Mail 4.2"));
+    assert_eq!(
+        receipt.original_message_id.as_deref(),
+        Some("<CAF-quarterly-42@mail.example.com>")
+    );
+
+    let receipt = ReadReceipt::parse(
+        "Final-Recipient: rfc822; bob@example.net\r\n\
+         Disposition: automatic-action/MDN-sent-automatically; deleted\r\n",
+    )
+    .unwrap();
+    assert_eq!(receipt.disposition, "deleted");
+    assert!(!receipt.read);
+    assert!(receipt.sent_automatically);
+
+    assert_eq!(
+        ReadReceipt::parse("Final-Recipient: rfc822; bob@example.net"),
+        None
+    );
+}
+
+#[test]
+fn test_draft_read_receipt_header() {
+    let mut draft = DraftEmail {
+        to: "bob@example.net".to_string(),
+        subject: "Quarterly numbers".to_string(),
+        body: "Attached.".to_string(),
+        cc: None,
+        bcc: None,
+        thread_id: None,
+        in_reply_to: None,
+        references: None,
+        priority: None,
+        read_receipt_to: Some("demo@example.com".to_string()),
+    };
+    let raw = base64::decode_config(encode_raw_message(&draft), base64::URL_SAFE).unwrap();
+    let raw = String::from_utf8(raw).unwrap();
+    assert!(raw.contains("Disposition-Notification-To: demo@example.com\r\n"));
+
+    draft.read_receipt_to = None;
+    let raw = base64::decode_config(encode_raw_message(&draft), base64::URL_SAFE).unwrap();
+    assert!(!String::from_utf8(raw)
+        .unwrap()
+        .contains("Disposition-Notification-To"));
+}
+
+#[test]
+fn test_mail_send_read_receipt_flag() {
+    let cli = Cli::try_parse_from([
+        "gmail-mcp",
+        "mail",
+        "send",
+        "--to",
+        "a@example.com",
+        "--subject",
+        "Hi",
+        "--body",
+        "Hello there",
+        "--read-receipt",
+    ])
+    .unwrap();
+    match cli.command {
+        Some(Commands::Mail {
+            command: MailCommands::Send { read_receipt, .. },
+        }) => assert!(read_receipt),
+        other => panic!("Unexpected command: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_read_receipt_tools() {
+    let dir = tempfile::tempdir().unwrap();
+    let messages = dir.path().join("gmail/v1/users/me/messages");
+    std::fs::create_dir_all(&messages).unwrap();
+    let encode = |text: &str| base64::encode_config(text, base64::URL_SAFE);
+    let message = json!({
+        "id": "msg-receipt",
+        "threadId": "thread-receipt",
+        "snippet": "Your message was displayed",
+        "payload": {
+            "mimeType": "multipart/report",
+            "headers": [
+                { "name": "From", "value": "Bob <bob@example.net>" },
+                { "name": "Subject", "value": "Read: Quarterly numbers" }
+            ],
+            "parts": [
+                { "mimeType": "text/plain", "body": { "data": encode("Your message was displayed.") } },
+                { "mimeType": "message/disposition-notification", "body": { "data": encode(DISPOSITION) } },
+                {
+                    "mimeType": "text/rfc822-headers",
+                    "body": { "data": encode("Subject: Quarterly numbers\r\nMessage-ID: <other@example.com>\r\n") }
+                }
+            ]
+        }
+    });
+    std::fs::write(messages.join("msg-receipt.json"), message.to_string()).unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    let email = call_tool(&client, "get_email", json!({ "message_id": "msg-receipt" }))
+        .await
+        .unwrap();
+    let receipt = &email["read_receipt"];
+    assert_eq!(receipt["read"], true);
+    assert_eq!(receipt["recipient"], "bob@example.net");
+    // The receipt's own Original-Message-ID wins over the returned headers
+    assert_eq!(
+        receipt["original_message_id"],
+        "<CAF-quarterly-42@mail.example.com>"
+    );
+    assert_eq!(receipt["original_subject"], "Quarterly numbers");
+    assert!(email["delivery_report"].is_null());
+
+    let email = call_tool(
+        &client,
+        "get_email",
+        json!({ "message_id": "mock-msg-001" }),
+    )
+    .await
+    .unwrap();
+    assert!(email["read_receipt"].is_null());
+
+    // Receipts are asked for at the signed-in address
+    let result = call_tool(
+        &client,
+        "create_draft_email",
+        json!({
+            "to": "bob@example.net",
+            "subject": "Quarterly numbers",
+            "body": "Attached.",
+            "request_read_receipt": true
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["status"], "success");
+    assert_eq!(result["read_receipt_to"], "demo@example.com");
+
+    let result = call_tool(
+        &client,
+        "create_draft_email",
+        json!({
+            "to": "bob@example.net",
+            "subject": "Quarterly numbers",
+            "body": "Attached."
+        }),
+    )
+    .await
+    .unwrap();
+    assert!(result["read_receipt_to"].is_null());
+}
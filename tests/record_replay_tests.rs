@@ -0,0 +1,223 @@
+#![cfg(feature = "record")]
+/// Record/Replay Tests Module
+///
+/// This module contains tests for the recording proxy, covering response
+/// sanitization, the files written for each interaction and an end-to-end
+/// record then replay run of the real Gmail client. The mock server stands in
+/// for Google as the upstream API.
+use clap::Parser;
+use mcp_gmailcal::cli::Cli;
+use mcp_gmailcal::mock::MockServer;
+use mcp_gmailcal::recording::{RecordingProxy, Sanitizer, Upstreams, INTERACTION_LOG, REDACTED};
+use mcp_gmailcal::{Config, GmailService};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+fn mock_upstreams(server: &MockServer) -> Upstreams {
+    let url = server.url();
+    Upstreams {
+        gmail: format!("{}/gmail/v1", url),
+        calendar: format!("{}/calendar/v3", url),
+        people: format!("{}/people/v1", url),
+        tasks: format!("{}/tasks/v1", url),
+        drive: format!("{}/drive/v3", url),
+        token: format!("{}/token", url),
+    }
+}
+
+#[test]
+fn test_record_flag_parsing() {
+    let cli = Cli::try_parse_from([
+        "gmail-mcp",
+        "mail",
+        "list",
+        "--record",
+        "/tmp/recordings",
+        "--record-redact",
+        "me@example.com",
+        "--record-redact",
+        "Jane Doe",
+    ])
+    .unwrap();
+    assert_eq!(cli.record, Some(PathBuf::from("/tmp/recordings")));
+    assert_eq!(cli.record_redact, vec!["me@example.com", "Jane Doe"]);
+
+    // Recording and mock mode are mutually exclusive
+    assert!(Cli::try_parse_from(["gmail-mcp", "--mock", "--record", "/tmp/recordings"]).is_err());
+    assert!(Cli::try_parse_from(["gmail-mcp", "--record-redact", "x"]).is_err());
+}
+
+#[test]
+fn test_sanitizer_redacts_tokens_and_replacements() {
+    let sanitizer = Sanitizer::new()
+        .replace("jane@corp.example", "user@example.com")
+        .redact("Project Falcon");
+
+    let mut value = json!({
+        "access_token": "ya29.secret",
+        "refresh_token": "1//secret",
+        "expires_in": 3599,
+        "emailAddress": "jane@corp.example",
+        "items": [
+            { "summary": "Project Falcon kickoff", "attendees": [{ "email": "jane@corp.example" }] }
+        ]
+    });
+    sanitizer.sanitize(&mut value);
+
+    assert_eq!(value["access_token"], REDACTED);
+    assert_eq!(value["refresh_token"], REDACTED);
+    assert_eq!(value["expires_in"], 3599);
+    assert_eq!(value["emailAddress"], "user@example.com");
+    assert_eq!(value["items"][0]["summary"], "REDACTED kickoff");
+    assert_eq!(
+        value["items"][0]["attendees"][0]["email"],
+        "user@example.com"
+    );
+}
+
+#[test]
+fn test_sanitizer_rewrites_encoded_bodies() {
+    let sanitizer = Sanitizer::new().replace("jane@corp.example", "user@example.com");
+    let encoded = base64::encode_config("Reply to jane@corp.example", base64::URL_SAFE);
+    let binary = base64::encode_config([0xff, 0xfe, 0x00], base64::URL_SAFE);
+
+    let mut value = json!({
+        "payload": { "body": { "data": encoded } },
+        "attachment": { "data": binary.clone() }
+    });
+    sanitizer.sanitize(&mut value);
+
+    let data = value["payload"]["body"]["data"].as_str().unwrap();
+    let decoded = base64::decode_config(data, base64::URL_SAFE).unwrap();
+    assert_eq!(String::from_utf8(decoded).unwrap(), "Reply to user@example.com");
+
+    // Binary content is not valid text and is left alone
+    assert_eq!(value["attachment"]["data"], binary);
+}
+
+#[tokio::test]
+async fn test_proxy_writes_fixtures_and_interaction_log() {
+    let upstream = MockServer::start(None).await.unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let proxy = RecordingProxy::start(
+        dir.path().to_path_buf(),
+        mock_upstreams(&upstream),
+        Sanitizer::new().replace("demo@example.com", "user@example.com"),
+    )
+    .await
+    .unwrap();
+    let client = reqwest::Client::new();
+
+    // The live caller sees the original response
+    let profile: Value = client
+        .get(format!("{}/gmail/v1/users/me/profile", proxy.url()))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(profile["emailAddress"], "demo@example.com");
+
+    // The recording is sanitized
+    let recorded: Value = serde_json::from_str(
+        &std::fs::read_to_string(dir.path().join("gmail/v1/users/me/profile.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(recorded["emailAddress"], "user@example.com");
+
+    // Token responses and errors are forwarded but not recorded
+    let token: Value = client
+        .post(format!("{}/token", proxy.url()))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(token["access_token"], "mock-access-token");
+
+    let missing = client
+        .get(format!("{}/gmail/v1/users/me/messages/nope", proxy.url()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(missing.status().as_u16(), 404);
+    assert!(!dir.path().join("gmail/v1/users/me/messages/nope.json").exists());
+
+    // Every recorded-API interaction is logged
+    let log = std::fs::read_to_string(dir.path().join(INTERACTION_LOG)).unwrap();
+    let entries: Vec<Value> = log
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["fixture"], "gmail/v1/users/me/profile.json");
+    assert_eq!(entries[1]["status"], 404);
+    assert!(entries[1]["fixture"].is_null());
+}
+
+#[tokio::test]
+async fn test_replay_is_strict() {
+    let dir = tempfile::tempdir().unwrap();
+    let server = MockServer::replay(dir.path().to_path_buf()).await.unwrap();
+
+    // Built-in fixtures are not used when replaying a recording
+    let response = reqwest::get(format!("{}/gmail/v1/users/me/profile", server.url()))
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 404);
+
+    assert!(MockServer::replay(PathBuf::from("/nonexistent/recordings"))
+        .await
+        .is_err());
+}
+
+// Record and replay share one test since installing the proxy and server
+// configures the process environment
+#[tokio::test]
+async fn test_record_then_replay_with_gmail_client() {
+    let upstream = MockServer::start(None).await.unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let proxy = RecordingProxy::start(
+        dir.path().to_path_buf(),
+        mock_upstreams(&upstream),
+        Sanitizer::new()
+            .replace("alice@example.com", "sender@example.com")
+            .replace("Thursday", "Friday"),
+    )
+    .await
+    .unwrap();
+
+    proxy.install();
+    std::env::set_var("GMAIL_CLIENT_ID", "record-client-id");
+    std::env::set_var("GMAIL_CLIENT_SECRET", "record-client-secret");
+    std::env::set_var("GMAIL_REFRESH_TOKEN", "record-refresh-token");
+    std::env::remove_var("GMAIL_ACCESS_TOKEN");
+    std::env::set_var("TOKEN_CACHE_ENABLED", "false");
+
+    let config = Config::from_env().unwrap();
+    let gmail = GmailService::new(&config).unwrap();
+    let live = gmail.get_message_details("mock-msg-001").await.unwrap();
+    assert!(live.from.as_deref().unwrap().contains("alice@example.com"));
+    drop(proxy);
+
+    let replay = MockServer::replay(dir.path().to_path_buf()).await.unwrap();
+    replay.install();
+    let config = Config::from_env().unwrap();
+    let gmail = GmailService::new(&config).unwrap();
+    let replayed = gmail.get_message_details("mock-msg-001").await.unwrap();
+
+    assert_eq!(replayed.id, live.id);
+    assert_eq!(replayed.subject, live.subject);
+    assert!(replayed
+        .from
+        .as_deref()
+        .unwrap()
+        .contains("sender@example.com"));
+    assert!(replayed
+        .body_text
+        .as_deref()
+        .unwrap()
+        .contains("Friday at 2pm"));
+}
@@ -0,0 +1,81 @@
+/// Relative Time Filter Tests Module
+///
+/// This module contains tests for translating ages such as "7d" into Gmail
+/// search terms, and for the newer_than and older_than parameters of
+/// list_emails.
+use mcp_gmailcal::utils::{relative_time_term, with_relative_time};
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+#[test]
+fn test_relative_time_term() {
+    assert_eq!(
+        relative_time_term("newer_than", "7d").unwrap(),
+        "newer_than:7d"
+    );
+    assert_eq!(
+        relative_time_term("older_than", " 3M ").unwrap(),
+        "older_than:3m"
+    );
+    assert_eq!(
+        relative_time_term("newer_than", "1y").unwrap(),
+        "newer_than:1y"
+    );
+    // Gmail has no unit for weeks
+    assert_eq!(
+        relative_time_term("newer_than", "2w").unwrap(),
+        "newer_than:14d"
+    );
+
+    for age in ["", "7", "d", "7 days", "7h", "-1d", "1.5d", "999999999w"] {
+        let error = relative_time_term("newer_than", age).unwrap_err();
+        assert!(error.contains("Invalid newer_than"), "{}: {}", age, error);
+    }
+}
+
+#[test]
+fn test_with_relative_time() {
+    assert_eq!(with_relative_time(None, None, None).unwrap(), None);
+    assert_eq!(
+        with_relative_time(Some("is:unread"), None, None).unwrap(),
+        Some("is:unread".to_string())
+    );
+    assert_eq!(
+        with_relative_time(None, Some("7d"), None).unwrap(),
+        Some("newer_than:7d".to_string())
+    );
+    assert_eq!(
+        with_relative_time(Some("from:a OR from:b"), Some("1m"), Some("1w")).unwrap(),
+        Some("(from:a OR from:b) newer_than:1m older_than:7d".to_string())
+    );
+    assert_eq!(
+        with_relative_time(Some("  "), None, Some("1y")).unwrap(),
+        Some("older_than:1y".to_string())
+    );
+    assert!(with_relative_time(Some("is:unread"), Some("soon"), None).is_err());
+}
+
+#[tokio::test]
+async fn test_list_emails_relative_time() {
+    let (_server, client) = mock_client(None).await;
+
+    let emails = call_tool(
+        &client,
+        "list_emails",
+        json!({ "query": "is:unread", "newer_than": "7d", "older_than": "1d" }),
+    )
+    .await
+    .unwrap();
+    assert!(!emails.as_array().unwrap().is_empty());
+
+    let error = call_tool(&client, "list_emails", json!({ "newer_than": "last week" }))
+        .await
+        .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("Invalid newer_than 'last week'"));
+}
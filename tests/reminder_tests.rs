@@ -0,0 +1,194 @@
+/// Reminder Tests Module
+///
+/// This module contains tests for follow-up reminders, checking how reminder
+/// times are parsed, that a reminder labels the email and creates a calendar
+/// event linking back to it, and the remind_me tool against the mock server.
+use chrono::{Duration, TimeZone, Utc};
+use mcp_attr::client::McpClient;
+use mcp_attr::schema::CallToolRequestParams;
+use mcp_attr::SessionResult;
+use mcp_gmailcal::api::GmailApi;
+use mcp_gmailcal::reminders::{
+    create_reminder, email_link, parse_remind_at, ReminderRequest, DEFAULT_FOLLOW_UP_LABEL,
+};
+use mcp_gmailcal::test_util::{message, MockCalendarApi, MockGmailApi};
+use mcp_gmailcal::{CalendarApiError, Error};
+use serde_json::{json, Value};
+
+mod common;
+use common::mock_client;
+
+fn request(message_id: &str, archive: bool) -> ReminderRequest {
+    ReminderRequest {
+        message_id: message_id.to_string(),
+        remind_at: Utc.with_ymd_and_hms(2030, 1, 2, 9, 0, 0).unwrap(),
+        note: Some("Check the numbers".to_string()),
+        label: None,
+        archive,
+        calendar_id: "primary".to_string(),
+    }
+}
+
+#[test]
+fn test_parse_remind_at() {
+    let now = Utc.with_ymd_and_hms(2030, 1, 1, 12, 0, 0).unwrap();
+
+    assert_eq!(
+        parse_remind_at("2030-01-02T09:00:00-05:00", now).unwrap(),
+        Utc.with_ymd_and_hms(2030, 1, 2, 14, 0, 0).unwrap()
+    );
+    assert_eq!(
+        parse_remind_at("30m", now).unwrap(),
+        now + Duration::minutes(30)
+    );
+    assert_eq!(
+        parse_remind_at("2h", now).unwrap(),
+        now + Duration::hours(2)
+    );
+    assert_eq!(
+        parse_remind_at("in 3 days", now).unwrap(),
+        now + Duration::days(3)
+    );
+    assert_eq!(
+        parse_remind_at(" 1w ", now).unwrap(),
+        now + Duration::weeks(1)
+    );
+
+    for invalid in [
+        "tomorrow",
+        "3 fortnights",
+        "h",
+        "",
+        "999999999w",
+        "99999999999999w",
+        "99999999999999999999m",
+    ] {
+        let error = parse_remind_at(invalid, now).unwrap_err();
+        assert!(error.contains("Invalid reminder time"), "{}", invalid);
+    }
+}
+
+#[tokio::test]
+async fn test_reminder_labels_email_and_creates_event() {
+    let gmail = MockGmailApi::new()
+        .with_labels([("INBOX", "INBOX")])
+        .with_messages(vec![message("msg1", "alice@example.com", "Budget review")]);
+    let calendar = MockCalendarApi::new();
+
+    let reminder = create_reminder(&gmail, &calendar, request("msg1", true))
+        .await
+        .unwrap();
+
+    assert_eq!(reminder.label, DEFAULT_FOLLOW_UP_LABEL);
+    assert_eq!(reminder.subject.as_deref(), Some("Budget review"));
+    assert!(reminder.archived);
+    assert_eq!(reminder.email_link, email_link("msg1"));
+    assert!(reminder.event_link.is_some());
+
+    // The label is created once and reused
+    assert_eq!(
+        gmail.get_or_create_label("follow-up").await.unwrap(),
+        reminder.label_id
+    );
+    assert_eq!(gmail.labels().len(), 2);
+    assert_eq!(
+        gmail.message_labels()["msg1"],
+        vec![reminder.label_id.clone()]
+    );
+
+    let events = calendar.events("primary");
+    assert_eq!(events.len(), 1);
+    let event = &events[0];
+    assert_eq!(event.summary, "Follow up: Budget review");
+    assert_eq!(event.start_time, reminder.remind_at);
+    assert_eq!(event.end_time - event.start_time, Duration::minutes(15));
+    let description = event.description.as_deref().unwrap();
+    assert!(description.starts_with("Check the numbers"));
+    assert!(description.contains("from alice@example.com"));
+    assert!(description.contains(&email_link("msg1")));
+}
+
+#[tokio::test]
+async fn test_failed_reminder_leaves_mailbox_unchanged() {
+    let gmail = MockGmailApi::new().with_messages(vec![message(
+        "msg1",
+        "alice@example.com",
+        "Budget review",
+    )]);
+    let calendar = MockCalendarApi::new()
+        .failing_with(|| CalendarApiError::AuthError("calendar scope missing".to_string()));
+
+    let error = create_reminder(&gmail, &calendar, request("msg1", true))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        Error::Calendar(CalendarApiError::AuthError(_))
+    ));
+    assert!(gmail.labels().is_empty());
+    assert!(gmail.message_labels().is_empty());
+
+    // Unknown emails fail before anything is created
+    let calendar = MockCalendarApi::new();
+    let error = create_reminder(&gmail, &calendar, request("missing", false))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::Gmail(_)));
+    assert!(calendar.events("primary").is_empty());
+}
+
+async fn call_remind_me(client: &McpClient, arguments: Value) -> SessionResult<Value> {
+    let result = client
+        .tools_call(CallToolRequestParams {
+            name: "remind_me".to_string(),
+            arguments: arguments.as_object().cloned(),
+        })
+        .await?;
+    let text = serde_json::to_value(&result.content[0]).unwrap()["text"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    Ok(serde_json::from_str(&text).unwrap())
+}
+
+#[tokio::test]
+async fn test_remind_me_tool() {
+    let (_server, client) = mock_client(None).await;
+
+    let reminder = call_remind_me(
+        &client,
+        json!({ "message_id": "mock-msg-001", "when": "2d", "label": "Receipts" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(reminder["message_id"], "mock-msg-001");
+    assert_eq!(reminder["subject"], "Quarterly planning meeting");
+    // An existing label is reused
+    assert_eq!(reminder["label_id"], "Label_1");
+    assert_eq!(reminder["archived"], false);
+    assert!(reminder["event_id"].is_string());
+
+    let error = call_remind_me(
+        &client,
+        json!({ "message_id": "mock-msg-001", "when": "2020-01-01T00:00:00Z" }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("in the past"));
+
+    let error = call_remind_me(
+        &client,
+        json!({ "message_id": "mock-msg-001", "when": "someday" }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("Invalid reminder time"));
+}
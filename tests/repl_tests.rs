@@ -0,0 +1,87 @@
+/// REPL Tests Module
+///
+/// This module contains tests for the interactive REPL, focusing on input
+/// parsing, result formatting and CLI wiring.
+use clap::Parser;
+use mcp_attr::client::McpClient;
+use mcp_attr::schema::{CallToolResult, CallToolResultContentItem, TextContent};
+use mcp_gmailcal::cli::{Cli, Commands};
+use mcp_gmailcal::repl::{format_tool_result, parse_line, ReplCommand};
+use mcp_gmailcal::GmailServer;
+use serde_json::json;
+
+#[test]
+fn test_repl_command_parsing() {
+    let cli = Cli::try_parse_from(["gmail-mcp", "repl"]).unwrap();
+    assert!(matches!(cli.command, Some(Commands::Repl)));
+}
+
+#[test]
+fn test_parse_builtin_commands() {
+    assert_eq!(parse_line("   ").unwrap(), ReplCommand::Empty);
+    assert_eq!(parse_line("help").unwrap(), ReplCommand::Help);
+    assert_eq!(parse_line("exit").unwrap(), ReplCommand::Quit);
+    assert_eq!(parse_line("tools").unwrap(), ReplCommand::ListTools);
+    assert_eq!(
+        parse_line("describe list_emails").unwrap(),
+        ReplCommand::Describe("list_emails".to_string())
+    );
+    assert!(parse_line("describe").is_err());
+}
+
+#[test]
+fn test_parse_tool_call() {
+    assert_eq!(
+        parse_line("list_labels").unwrap(),
+        ReplCommand::Call {
+            name: "list_labels".to_string(),
+            arguments: None,
+        }
+    );
+
+    let command = parse_line(r#"search_emails {"query": "is:unread", "max_results": 5}"#).unwrap();
+    match command {
+        ReplCommand::Call { name, arguments } => {
+            assert_eq!(name, "search_emails");
+            let arguments = arguments.unwrap();
+            assert_eq!(arguments["query"], json!("is:unread"));
+            assert_eq!(arguments["max_results"], json!(5));
+        }
+        other => panic!("Expected tool call, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_rejects_invalid_arguments() {
+    assert!(parse_line("list_emails {not json}").is_err());
+    assert!(parse_line("list_emails [1, 2]").is_err());
+}
+
+#[test]
+fn test_format_tool_result_pretty_prints_json() {
+    let result = CallToolResult {
+        content: vec![CallToolResultContentItem::TextContent(TextContent::new(
+            r#"{"id":"abc"}"#,
+        ))],
+        is_error: None,
+        meta: Default::default(),
+    };
+    assert_eq!(format_tool_result(&result), "{\n  \"id\": \"abc\"\n}");
+
+    let error = CallToolResult {
+        content: vec![CallToolResultContentItem::TextContent(TextContent::new(
+            "boom",
+        ))],
+        is_error: Some(true),
+        meta: Default::default(),
+    };
+    assert_eq!(format_tool_result(&error), "Tool reported an error:\nboom");
+}
+
+#[tokio::test]
+async fn test_in_process_server_lists_tools() {
+    let client = McpClient::with_server(GmailServer::new()).await.unwrap();
+    let tools = client.tools_list(None).await.unwrap().tools;
+    assert!(tools.iter().any(|t| t.name == "list_emails"));
+    assert!(tools.iter().any(|t| t.name == "search_contacts"));
+}
@@ -0,0 +1,134 @@
+/// Resumable Upload Tests Module
+///
+/// This module contains tests for uploading large messages with Gmail's
+/// resumable upload protocol: the upload URL, chunk sizes, and uploads that
+/// carry on from where they stopped when chunks fail.
+use mcp_gmailcal::attachments::OutgoingAttachment;
+use mcp_gmailcal::config::{get_gmail_upload_base_url, Config};
+use mcp_gmailcal::gmail_api::GmailService;
+use mcp_gmailcal::mock::MockServer;
+use mcp_gmailcal::resumable_upload::{
+    align_chunk_size, upload, MAX_CHUNK_ATTEMPTS, UPLOAD_CHUNK_ALIGNMENT,
+};
+use mcp_gmailcal::{DraftEmail, GmailApiError};
+use serde_json::{json, Value};
+
+fn draft() -> DraftEmail {
+    DraftEmail {
+        to: "alice@example.com".to_string(),
+        subject: "Recording".to_string(),
+        body: "See attached.".to_string(),
+        cc: None,
+        bcc: None,
+        thread_id: Some("thread-1".to_string()),
+        in_reply_to: None,
+        references: None,
+        priority: None,
+        read_receipt_to: None,
+    }
+}
+
+#[test]
+fn test_align_chunk_size() {
+    assert_eq!(align_chunk_size(0), UPLOAD_CHUNK_ALIGNMENT);
+    assert_eq!(align_chunk_size(1), UPLOAD_CHUNK_ALIGNMENT);
+    assert_eq!(
+        align_chunk_size(UPLOAD_CHUNK_ALIGNMENT),
+        UPLOAD_CHUNK_ALIGNMENT
+    );
+    assert_eq!(
+        align_chunk_size(UPLOAD_CHUNK_ALIGNMENT + 1),
+        2 * UPLOAD_CHUNK_ALIGNMENT
+    );
+}
+
+// All mock calls share one test since mock mode configures the process
+// environment
+#[tokio::test]
+async fn test_large_messages_upload_in_chunks() {
+    let server = MockServer::start(None).await.unwrap();
+    server.install();
+    assert_eq!(
+        get_gmail_upload_base_url(),
+        format!("{}/upload/gmail/v1", server.url())
+    );
+
+    let client = reqwest::Client::new();
+    let url = format!("{}/users/me/messages/send", get_gmail_upload_base_url());
+    let message: Vec<u8> = (0..(3 * UPLOAD_CHUNK_ALIGNMENT + 1000))
+        .map(|i| (i % 251) as u8)
+        .collect();
+
+    // Every byte arrives, in order, over several chunks
+    let sent: Value = upload(
+        &client,
+        "mock-access-token",
+        &url,
+        &json!({ "threadId": "thread-1" }),
+        &message,
+        UPLOAD_CHUNK_ALIGNMENT,
+    )
+    .await
+    .unwrap();
+    assert_eq!(sent["threadId"], "thread-1");
+    assert_eq!(sent["sizeEstimate"], message.len());
+
+    // Failed chunks are retried from where the session got to
+    server.fail_upload_chunks(2);
+    let sent: Value = upload(
+        &client,
+        "mock-access-token",
+        &url,
+        &json!({}),
+        &message,
+        UPLOAD_CHUNK_ALIGNMENT,
+    )
+    .await
+    .unwrap();
+    assert_eq!(sent["sizeEstimate"], message.len());
+
+    // A chunk that keeps failing fails the upload
+    server.fail_upload_chunks(MAX_CHUNK_ATTEMPTS as usize);
+    let error = upload::<Value>(
+        &client,
+        "mock-access-token",
+        &url,
+        &json!({}),
+        &message[..1000],
+        UPLOAD_CHUNK_ALIGNMENT,
+    )
+    .await
+    .unwrap_err();
+    let GmailApiError::NetworkError(reason) = &error else {
+        panic!("expected a network error, got {:?}", error);
+    };
+    assert!(reason.contains("after 4 attempts"), "{}", reason);
+    server.fail_upload_chunks(0);
+
+    // Drafts and sends over the threshold go through resumable uploads
+    std::env::set_var("RESUMABLE_UPLOAD_THRESHOLD_BYTES", "100000");
+    std::env::set_var("UPLOAD_CHUNK_BYTES", "1");
+    let gmail = GmailService::new(&Config::from_env().unwrap()).unwrap();
+    let files = [OutgoingAttachment {
+        filename: "recording.mp4".to_string(),
+        mime_type: "video/mp4".to_string(),
+        data: vec![7; 600 * 1024],
+    }];
+    server.fail_upload_chunks(1);
+    let draft_id = gmail
+        .create_draft_with_attachments(&draft(), &files)
+        .await
+        .unwrap();
+    assert!(draft_id.starts_with("mock-"));
+    let message_id = gmail
+        .send_message_with_attachments(&draft(), &files)
+        .await
+        .unwrap();
+    assert!(message_id.starts_with("mock-"));
+
+    // Smaller messages are still sent in one request
+    let message_id = gmail.send_message(&draft()).await.unwrap();
+    assert!(message_id.starts_with("mock-"));
+    std::env::remove_var("RESUMABLE_UPLOAD_THRESHOLD_BYTES");
+    std::env::remove_var("UPLOAD_CHUNK_BYTES");
+}
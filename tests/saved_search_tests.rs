@@ -0,0 +1,126 @@
+/// Saved Search Tests Module
+///
+/// This module contains tests for saved searches, checking how they are
+/// stored and replaced, and the saved search tools against the mock server.
+use mcp_gmailcal::saved_searches::{SavedSearch, SavedSearchStore};
+use mcp_gmailcal::SavedSearchError;
+use serde_json::json;
+use tempfile::tempdir;
+
+mod common;
+use common::{call_tool, mock_client};
+
+#[test]
+fn test_saved_search_store_saves_and_replaces() {
+    let dir = tempdir().unwrap();
+    let store = SavedSearchStore::new(dir.path().join("nested").join("saved_searches.json"));
+    assert!(store.list().unwrap().is_empty());
+    assert!(matches!(
+        store.get("invoices"),
+        Err(SavedSearchError::NotFound(_))
+    ));
+
+    let search = SavedSearch::new(" Invoices ", "subject:invoice newer_than:7d", None);
+    assert_eq!(search.name, "Invoices");
+    assert!(!store.save(search).unwrap());
+    assert!(!store
+        .save(SavedSearch::new(
+            "waiting-on-me",
+            "is:inbox is:unread",
+            Some("Mail I owe a reply".to_string())
+        ))
+        .unwrap());
+
+    // Names are matched ignoring case, and saving again replaces
+    assert!(store
+        .save(SavedSearch::new(
+            "invoices",
+            "subject:invoice newer_than:14d",
+            None
+        ))
+        .unwrap());
+
+    let names: Vec<String> = store.list().unwrap().into_iter().map(|s| s.name).collect();
+    assert_eq!(names, vec!["invoices", "waiting-on-me"]);
+    assert_eq!(
+        store.get("INVOICES").unwrap().query,
+        "subject:invoice newer_than:14d"
+    );
+    assert_eq!(
+        store.get("waiting-on-me").unwrap().description.as_deref(),
+        Some("Mail I owe a reply")
+    );
+
+    assert!(matches!(
+        store.save(SavedSearch::new("empty", " ", None)),
+        Err(SavedSearchError::InvalidSearch(_))
+    ));
+
+    std::fs::write(store.path(), "not json").unwrap();
+    assert!(matches!(
+        store.list(),
+        Err(SavedSearchError::StorageError(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_saved_search_tools() {
+    let dir = tempdir().unwrap();
+    std::env::set_var(
+        "SAVED_SEARCHES_FILE",
+        dir.path().join("saved_searches.json"),
+    );
+    let (_server, client) = mock_client(None).await;
+
+    let saved = call_tool(
+        &client,
+        "save_search",
+        json!({ "name": "unread", "query": "is:unread", "description": "Unread mail" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(saved["replaced"], false);
+    assert_eq!(saved["search"]["query"], "is:unread");
+
+    let listed = call_tool(&client, "list_saved_searches", json!({}))
+        .await
+        .unwrap();
+    assert_eq!(listed["count"], 1);
+    assert_eq!(listed["searches"][0]["name"], "unread");
+    assert_eq!(listed["searches"][0]["description"], "Unread mail");
+
+    let results = call_tool(
+        &client,
+        "run_saved_search",
+        json!({ "name": "UNREAD", "max_results": 5 }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(results["name"], "unread");
+    assert_eq!(results["query"], "is:unread");
+    let messages = results["messages"].as_array().unwrap();
+    assert_eq!(results["count"], messages.len());
+    assert!(!messages.is_empty());
+    assert!(messages[0]["id"].is_string());
+
+    let error = call_tool(&client, "run_saved_search", json!({ "name": "missing" }))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.error_object().unwrap().data.as_ref().unwrap()["category"],
+        "not_found"
+    );
+
+    let error = call_tool(
+        &client,
+        "save_search",
+        json!({ "name": "blank", "query": "" }),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error.error_object().unwrap().data.as_ref().unwrap()["category"],
+        "invalid_input"
+    );
+    std::env::remove_var("SAVED_SEARCHES_FILE");
+}
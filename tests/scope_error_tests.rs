@@ -0,0 +1,121 @@
+/// Scope Error Tests Module
+///
+/// This module contains tests for requests Google refuses because the access
+/// token was not granted a scope, checking that they are mapped to their own
+/// error naming the missing scope and the auth command that fixes it, and
+/// that the auth command requests extra scopes.
+use clap::Parser;
+use mcp_gmailcal::cli::{Cli, Commands};
+use mcp_gmailcal::errors::is_insufficient_scope;
+use mcp_gmailcal::oauth::{requested_scopes, CALENDAR_WRITE_SCOPE, GMAIL_SCOPE, TASKS_SCOPE};
+use mcp_gmailcal::utils::{error_codes, error_data, scope_fix_command};
+use mcp_gmailcal::{map_to_mcp_error, CalendarApiError, GmailApiError, TasksApiError};
+use serde_json::json;
+
+fn scope_error_body() -> String {
+    json!({
+        "error": {
+            "code": 403,
+            "message": "Request had insufficient authentication scopes.",
+            "errors": [{
+                "message": "Insufficient Permission",
+                "domain": "global",
+                "reason": "insufficientPermissions"
+            }],
+            "status": "PERMISSION_DENIED",
+            "details": [{
+                "@type": "type.googleapis.com/google.rpc.ErrorInfo",
+                "reason": "ACCESS_TOKEN_SCOPE_INSUFFICIENT",
+                "domain": "googleapis.com"
+            }]
+        }
+    })
+    .to_string()
+}
+
+#[test]
+fn test_is_insufficient_scope() {
+    assert!(is_insufficient_scope(&scope_error_body()));
+    assert!(is_insufficient_scope(
+        r#"{"error":{"code":403,"errors":[{"reason":"insufficientPermissions"}]}}"#
+    ));
+    assert!(!is_insufficient_scope(
+        r#"{"error":{"code":403,"errors":[{"reason":"forbidden"}]}}"#
+    ));
+}
+
+#[test]
+fn test_scope_errors_are_mapped() {
+    let err = GmailApiError::AuthError(format!(
+        "Authentication failed. Status: 403 Forbidden, Error: {}",
+        scope_error_body()
+    ));
+    let object = map_to_mcp_error(err).to_error_object(false);
+    assert_eq!(object.code.0, error_codes::SCOPE_ERROR as i64);
+    assert!(object
+        .message
+        .contains("mcp-gmailcal auth --scopes https://mail.google.com/"));
+    let data = object.data.unwrap();
+    assert_eq!(data["category"], "insufficient_scope");
+    assert_eq!(data["missing_scope"], GMAIL_SCOPE);
+    assert_eq!(data["fix_command"], scope_fix_command(GMAIL_SCOPE));
+    assert_eq!(data["retryable"], false);
+
+    let err = CalendarApiError::ApiError(format!(
+        "Failed to list events. Status: 403 Forbidden, Error: {}",
+        scope_error_body()
+    ));
+    let data = serde_json::to_value(error_data(err)).unwrap();
+    assert_eq!(data["code"], error_codes::SCOPE_ERROR);
+    assert_eq!(data["missing_scope"], CALENDAR_WRITE_SCOPE);
+
+    let err = TasksApiError::ApiError(format!(
+        "Failed to list tasks. Status: 403 Forbidden, Error: {}",
+        scope_error_body()
+    ));
+    let data = serde_json::to_value(error_data(err)).unwrap();
+    assert_eq!(data["missing_scope"], TASKS_SCOPE);
+    assert_eq!(
+        data["fix_command"],
+        "mcp-gmailcal auth --scopes https://www.googleapis.com/auth/tasks"
+    );
+
+    // Other 403 errors are still authentication errors
+    let err = CalendarApiError::ApiError(
+        "Failed to list events. Status: 403 Forbidden, Error: {\"error\":{\"errors\":[{\"reason\":\"forbidden\"}]}}"
+            .to_string(),
+    );
+    let data = serde_json::to_value(error_data(err)).unwrap();
+    assert_eq!(data["code"], error_codes::AUTH_ERROR);
+    assert!(data.get("missing_scope").is_none());
+    assert!(data.get("fix_command").is_none());
+}
+
+#[test]
+fn test_auth_scopes() {
+    let cli = Cli::try_parse_from(["mcp-gmailcal", "auth"]).unwrap();
+    assert_eq!(cli.command, Some(Commands::Auth { scopes: Vec::new() }));
+
+    let cli = Cli::try_parse_from([
+        "mcp-gmailcal",
+        "auth",
+        "--scopes",
+        "https://www.googleapis.com/auth/tasks,https://www.googleapis.com/auth/gmail.settings.sharing",
+    ])
+    .unwrap();
+    let Some(Commands::Auth { scopes }) = cli.command else {
+        panic!("expected the auth command");
+    };
+    assert_eq!(scopes.len(), 2);
+
+    // Scopes already requested aren't repeated
+    let requested = requested_scopes(&scopes);
+    assert_eq!(
+        requested,
+        requested_scopes(&[])
+            .into_iter()
+            .chain(["https://www.googleapis.com/auth/gmail.settings.sharing".to_string()])
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(requested[0], GMAIL_SCOPE);
+}
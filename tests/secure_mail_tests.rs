@@ -0,0 +1,214 @@
+/// Secure Mail Tests Module
+///
+/// This module contains tests for detecting PGP and S/MIME signed and
+/// encrypted messages, and for keeping ciphertext out of the body text that
+/// get_email returns.
+use mcp_gmailcal::secure_mail::{inspect, signed_content};
+use serde_json::{json, Value};
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn content_type(value: &str) -> Value {
+    json!([{ "name": "Content-Type", "value": value }])
+}
+
+fn encode(text: &str) -> String {
+    base64::encode_config(text, base64::URL_SAFE)
+}
+
+fn pgp_signed_payload() -> Value {
+    json!({
+        "mimeType": "multipart/signed",
+        "headers": content_type(
+            "multipart/signed; micalg=pgp-sha256; protocol=\"application/pgp-signature\"; boundary=\"sig\""
+        ),
+        "parts": [
+            {
+                "mimeType": "multipart/alternative",
+                "parts": [
+                    { "mimeType": "text/plain", "body": { "data": encode("The release is signed off.") } },
+                    { "mimeType": "text/html", "body": { "data": encode("<p>The release is signed off.</p>") } }
+                ]
+            },
+            { "mimeType": "application/pgp-signature", "filename": "signature.asc", "body": { "attachmentId": "sig-1" } }
+        ]
+    })
+}
+
+#[test]
+fn test_inspect_signed_messages() {
+    let payload = pgp_signed_payload();
+    let security = inspect(&payload, None);
+    assert!(security.is_signed);
+    assert!(!security.is_encrypted);
+    assert!(!security.hides_body());
+    assert_eq!(security.protocol, Some("pgp"));
+    assert_eq!(security.micalg.as_deref(), Some("pgp-sha256"));
+    assert_eq!(
+        signed_content(&payload)["mimeType"],
+        "multipart/alternative"
+    );
+
+    let signer = security
+        .signer(Some("Carol Example <Carol@Example.com>"))
+        .unwrap();
+    assert_eq!(signer.protocol, "pgp");
+    assert_eq!(signer.address.as_deref(), Some("carol@example.com"));
+
+    // S/MIME, recognized by its signature part when the protocol is missing
+    let payload = json!({
+        "mimeType": "multipart/signed",
+        "parts": [
+            { "mimeType": "text/plain" },
+            { "mimeType": "application/x-pkcs7-signature" }
+        ]
+    });
+    assert_eq!(inspect(&payload, None).protocol, Some("smime"));
+
+    // Wrapped in multipart/mixed by a mailing list footer
+    let payload = json!({
+        "mimeType": "multipart/mixed",
+        "parts": [pgp_signed_payload(), { "mimeType": "text/plain" }]
+    });
+    assert!(inspect(&payload, None).is_signed);
+
+    // Inline PGP
+    let text = "-----BEGIN PGP SIGNED MESSAGE-----\nHash: SHA512\n\nHello\n";
+    let security = inspect(&json!({ "mimeType": "text/plain" }), Some(text));
+    assert!(security.is_signed);
+    assert_eq!(security.micalg.as_deref(), Some("pgp-sha512"));
+
+    // Unsigned mail has no signer
+    let security = inspect(&json!({ "mimeType": "text/plain" }), Some("Hello"));
+    assert_eq!(security, Default::default());
+    assert!(security.signer(Some("carol@example.com")).is_none());
+}
+
+#[test]
+fn test_inspect_encrypted_messages() {
+    let payload = json!({
+        "mimeType": "multipart/encrypted",
+        "headers": content_type("multipart/encrypted; protocol=\"application/pgp-encrypted\""),
+        "parts": [
+            { "mimeType": "application/pgp-encrypted" },
+            { "mimeType": "application/octet-stream" }
+        ]
+    });
+    let security = inspect(&payload, None);
+    assert!(security.is_encrypted);
+    assert!(security.hides_body());
+    assert_eq!(security.protocol, Some("pgp"));
+
+    let text = "\n-----BEGIN PGP MESSAGE-----\n\nhQEMA...\n-----END PGP MESSAGE-----\n";
+    assert!(inspect(&json!({ "mimeType": "text/plain" }), Some(text)).is_encrypted);
+
+    // S/MIME enveloped data, and opaque signed data whose body is binary
+    let payload = json!({
+        "mimeType": "application/pkcs7-mime",
+        "headers": content_type("application/pkcs7-mime; smime-type=enveloped-data; name=smime.p7m")
+    });
+    let security = inspect(&payload, None);
+    assert!(security.is_encrypted);
+    assert_eq!(security.protocol, Some("smime"));
+
+    let payload = json!({
+        "mimeType": "application/x-pkcs7-mime",
+        "headers": content_type("application/x-pkcs7-mime; smime-type=signed-data")
+    });
+    let security = inspect(&payload, None);
+    assert!(security.is_signed);
+    assert!(!security.is_encrypted);
+    assert!(security.hides_body());
+}
+
+fn message_fixture(id: &str, snippet: &str, payload: Value) -> String {
+    let mut headers = vec![
+        json!({ "name": "From", "value": "Carol Example <carol@example.com>" }),
+        json!({ "name": "Subject", "value": "Release" }),
+    ];
+    headers.extend(payload["headers"].as_array().cloned().unwrap_or_default());
+    let mut payload = payload;
+    payload["headers"] = Value::Array(headers);
+    json!({
+        "id": id,
+        "threadId": "thread-secure",
+        "labelIds": ["INBOX"],
+        "snippet": snippet,
+        "payload": payload
+    })
+    .to_string()
+}
+
+#[tokio::test]
+async fn test_get_email_security_fields() {
+    let dir = tempfile::tempdir().unwrap();
+    let messages = dir.path().join("gmail/v1/users/me/messages");
+    std::fs::create_dir_all(&messages).unwrap();
+    std::fs::write(
+        messages.join("msg-signed.json"),
+        message_fixture(
+            "msg-signed",
+            "The release is signed off.",
+            pgp_signed_payload(),
+        ),
+    )
+    .unwrap();
+    let ciphertext =
+        "-----BEGIN PGP MESSAGE-----\n\nhQEMA5qETcGag5w6AQf/\n-----END PGP MESSAGE-----\n";
+    std::fs::write(
+        messages.join("msg-encrypted.json"),
+        message_fixture(
+            "msg-encrypted",
+            "-----BEGIN PGP MESSAGE----- hQEMA5qETcGag5w6AQf/",
+            json!({
+                "mimeType": "multipart/encrypted",
+                "headers": content_type("multipart/encrypted; protocol=\"application/pgp-encrypted\""),
+                "parts": [
+                    { "mimeType": "application/pgp-encrypted", "body": { "data": encode("Version: 1") } },
+                    { "mimeType": "application/octet-stream", "body": { "data": encode(ciphertext) } }
+                ]
+            }),
+        ),
+    )
+    .unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    // The signed content is read from inside the signature wrapper
+    let email = call_tool(&client, "get_email", json!({ "message_id": "msg-signed" }))
+        .await
+        .unwrap();
+    assert_eq!(email["is_signed"], true);
+    assert_eq!(email["is_encrypted"], false);
+    assert_eq!(email["body_text"], "The release is signed off.");
+    assert_eq!(email["body_html"], "<p>The release is signed off.</p>");
+    assert_eq!(
+        email["signer"],
+        json!({ "protocol": "pgp", "address": "carol@example.com", "micalg": "pgp-sha256" })
+    );
+
+    let email = call_tool(
+        &client,
+        "get_email",
+        json!({ "message_id": "msg-encrypted" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(email["is_encrypted"], true);
+    assert_eq!(email["is_signed"], false);
+    assert!(email["body_text"].is_null());
+    assert!(email["snippet"].is_null());
+    assert!(email["signer"].is_null());
+
+    // Plain messages are neither
+    let email = call_tool(
+        &client,
+        "get_email",
+        json!({ "message_id": "mock-msg-001" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(email["is_signed"], false);
+    assert_eq!(email["is_encrypted"], false);
+}
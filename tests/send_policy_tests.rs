@@ -0,0 +1,261 @@
+/// Send Policy Tests Module
+///
+/// This module contains tests for the sending policy: the recipient count,
+/// recipient domain, daily limit and BCC-only rules, and the policy errors
+/// sends are refused with.
+use chrono::{Duration, TimeZone, Utc};
+use mcp_gmailcal::errors::PolicyViolation;
+use mcp_gmailcal::gmail_api::DraftEmail;
+use mcp_gmailcal::send_policy::{SendLog, SendPolicy};
+use mcp_gmailcal::utils::error_data;
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn email(to: &str, cc: Option<&str>, bcc: Option<&str>) -> DraftEmail {
+    DraftEmail {
+        to: to.to_string(),
+        subject: "Hi".to_string(),
+        body: "Hello".to_string(),
+        cc: cc.map(str::to_string),
+        bcc: bcc.map(str::to_string),
+        thread_id: None,
+        in_reply_to: None,
+        references: None,
+        priority: None,
+        read_receipt_to: None,
+    }
+}
+
+#[test]
+fn test_recipient_rules() {
+    let policy = SendPolicy::default();
+    assert!(policy
+        .check_recipients(&email("", None, Some("bob@elsewhere.org")))
+        .is_ok());
+
+    let policy = SendPolicy {
+        max_recipients: Some(2),
+        ..Default::default()
+    };
+    assert!(policy
+        .check_recipients(&email("alice@example.com", Some("bob@example.com"), None))
+        .is_ok());
+    assert_eq!(
+        policy.check_recipients(&email(
+            "alice@example.com",
+            Some("bob@example.com"),
+            Some("carol@example.com")
+        )),
+        Err(PolicyViolation::TooManyRecipients { count: 3, max: 2 })
+    );
+
+    // Subdomains of an allowed domain are allowed, lookalikes are not
+    let policy = SendPolicy {
+        allowed_domains: vec!["example.com".to_string()],
+        ..Default::default()
+    };
+    assert!(policy
+        .check_recipients(&email("Alice <alice@Mail.Example.com>", None, None))
+        .is_ok());
+    let violation = policy
+        .check_recipients(&email(
+            "alice@example.com, mallory@notexample.com",
+            None,
+            Some("eve@example.com.evil.org"),
+        ))
+        .unwrap_err();
+    assert_eq!(
+        violation,
+        PolicyViolation::DomainNotAllowed {
+            addresses: vec![
+                "mallory@notexample.com".to_string(),
+                "eve@example.com.evil.org".to_string()
+            ],
+            allowed: vec!["example.com".to_string()],
+        }
+    );
+    assert_eq!(
+        violation.to_string(),
+        "Sending policy: mallory@notexample.com, eve@example.com.evil.org not in the allowed recipient domains (example.com)"
+    );
+
+    let policy = SendPolicy {
+        forbid_bcc_only: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        policy.check_recipients(&email("", None, Some("bob@example.com"))),
+        Err(PolicyViolation::BccOnly)
+    );
+    assert!(policy
+        .check_recipients(&email(
+            "",
+            Some("alice@example.com"),
+            Some("bob@example.com")
+        ))
+        .is_ok());
+}
+
+#[test]
+fn test_daily_limit() {
+    let now = Utc.with_ymd_and_hms(2030, 3, 5, 12, 0, 0).unwrap();
+    let policy = SendPolicy {
+        daily_limit: Some(2),
+        ..Default::default()
+    };
+    let sent = [
+        now - Duration::hours(30),
+        now - Duration::hours(20),
+        now - Duration::hours(1),
+    ];
+    assert!(policy.check_daily_limit(&sent[..2], now).is_ok());
+
+    // The next send is allowed once the send 20 hours ago is a day old
+    let violation = policy.check_daily_limit(&sent, now).unwrap_err();
+    assert_eq!(
+        violation,
+        PolicyViolation::DailyLimitReached {
+            max: 2,
+            retry_after: 4 * 60 * 60
+        }
+    );
+
+    let data = serde_json::to_value(error_data(violation)).unwrap();
+    assert_eq!(data["category"], "policy");
+    assert_eq!(data["policy_rule"], "daily_limit");
+    assert_eq!(data["retryable"], true);
+    assert_eq!(data["retry_after"], 4 * 60 * 60);
+
+    let data = serde_json::to_value(error_data(PolicyViolation::BccOnly)).unwrap();
+    assert_eq!(data["policy_rule"], "bcc_only");
+    assert_eq!(data["retryable"], false);
+}
+
+#[test]
+fn test_send_log() {
+    let dir = tempfile::tempdir().unwrap();
+    let log = SendLog::new(dir.path().join("send-log.json"));
+    assert!(log.sent_in_last_day().is_empty());
+
+    let policy = SendPolicy {
+        daily_limit: Some(2),
+        ..Default::default()
+    };
+
+    let now = Utc::now();
+    log.reserve(&policy, now - Duration::hours(25)).unwrap();
+    log.reserve(&policy, now).unwrap();
+    assert_eq!(log.sent_in_last_day().len(), 1);
+
+    // A send that fails gives its place back
+    let failed = log.reserve(&policy, now).unwrap();
+    assert!(log.reserve(&policy, now).is_err());
+    log.release(failed);
+    assert_eq!(log.sent_in_last_day().len(), 1);
+}
+
+#[test]
+fn test_sends_at_the_same_time_stay_within_the_limit() {
+    let dir = tempfile::tempdir().unwrap();
+    let log = SendLog::new(dir.path().join("send-log.json"));
+    let policy = SendPolicy {
+        daily_limit: Some(3),
+        ..Default::default()
+    };
+
+    let reserved = std::thread::scope(|scope| {
+        let sends: Vec<_> = (0..10)
+            .map(|_| scope.spawn(|| log.reserve(&policy, Utc::now()).is_ok()))
+            .collect();
+        sends
+            .into_iter()
+            .map(|send| send.join().unwrap())
+            .filter(|reserved| *reserved)
+            .count()
+    });
+    assert_eq!(reserved, 3);
+    assert_eq!(log.sent_in_last_day().len(), 3);
+}
+
+#[tokio::test]
+async fn test_sends_follow_the_policy() {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("OUTBOX_FILE", dir.path().join("outbox.json"));
+    std::env::set_var("SEND_LOG_FILE", dir.path().join("send-log.json"));
+    std::env::set_var("SEND_UNDO_SECONDS", "0");
+    let (_server, client) = mock_client(None).await;
+
+    std::env::set_var("SEND_ALLOWED_DOMAINS", "example.com");
+    let error = call_tool(
+        &client,
+        "send_email",
+        json!({ "to": "alice@example.com, bob@elsewhere.org", "subject": "Hi", "body": "Hello" }),
+    )
+    .await
+    .unwrap_err();
+    let object = error.error_object().unwrap();
+    assert!(object
+        .message
+        .contains("bob@elsewhere.org not in the allowed"));
+    let data = object.data.as_ref().unwrap();
+    assert_eq!(data["category"], "policy");
+    assert_eq!(data["policy_rule"], "allowed_domains");
+    std::env::remove_var("SEND_ALLOWED_DOMAINS");
+
+    // BCC-only emails are sent unless forbidden
+    let bcc_only =
+        json!({ "to": "", "bcc": "alice@example.com", "subject": "Hi", "body": "Hello" });
+    std::env::set_var("SEND_FORBID_BCC_ONLY", "true");
+    let error = call_tool(&client, "send_email", bcc_only.clone())
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.error_object().unwrap().data.as_ref().unwrap()["policy_rule"],
+        "bcc_only"
+    );
+    std::env::remove_var("SEND_FORBID_BCC_ONLY");
+    let sent = call_tool(&client, "send_email", bcc_only).await.unwrap();
+    assert_eq!(sent["status"], "sent");
+
+    let message = json!({ "to": "alice@example.com", "subject": "Hi", "body": "Hello" });
+    std::env::set_var("SEND_DAILY_LIMIT", "1");
+    call_tool(&client, "send_email", message.clone())
+        .await
+        .unwrap();
+    let error = call_tool(&client, "send_email", message.clone())
+        .await
+        .unwrap_err();
+    let data = error.error_object().unwrap().data.clone().unwrap();
+    assert_eq!(data["policy_rule"], "daily_limit");
+    assert_eq!(data["retryable"], true);
+    std::env::remove_var("SEND_DAILY_LIMIT");
+
+    // The policy in force when the email goes out applies
+    std::env::set_var("SEND_UNDO_SECONDS", "1");
+    let queued = call_tool(&client, "send_email", message).await.unwrap();
+    std::env::set_var("SEND_ALLOWED_DOMAINS", "example.org");
+    tokio::time::sleep(std::time::Duration::from_millis(2500)).await;
+    let listed = call_tool(&client, "list_outbox", json!({})).await.unwrap();
+    let entry = listed
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|entry| entry["id"] == queued["id"])
+        .unwrap();
+    assert_eq!(entry["status"], "failed");
+    assert!(entry["error"]
+        .as_str()
+        .unwrap()
+        .contains("not in the allowed recipient domains"));
+
+    for name in [
+        "SEND_ALLOWED_DOMAINS",
+        "SEND_UNDO_SECONDS",
+        "SEND_LOG_FILE",
+        "OUTBOX_FILE",
+    ] {
+        std::env::remove_var(name);
+    }
+}
@@ -0,0 +1,178 @@
+/// Sender Authentication Tests Module
+///
+/// This module contains tests for parsing Authentication-Results and
+/// Received-SPF headers, judging whether a sender is authentic, and the
+/// verify_sender tool against the mock server.
+use mcp_gmailcal::gmail_api::EmailMessage;
+use mcp_gmailcal::sender_auth::{
+    domains_aligned, AuthCheck, AuthenticationResults, SenderVerdict, SenderVerification,
+};
+use mcp_gmailcal::test_util::message;
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn check(result: &str, domain: &str) -> Option<AuthCheck> {
+    Some(AuthCheck {
+        result: result.to_string(),
+        domain: Some(domain.to_string()),
+    })
+}
+
+#[test]
+fn test_parse_authentication_results() {
+    let header = "mx.google.com;\r\n       dkim=fail header.i=@bad.example header.s=x;\r\n       \
+                  dkim=pass header.i=@news.example.com header.s=s1 header.b=abc;\r\n       \
+                  spf=softfail (google.com: domain of transitioning bounce@mailer.example does not designate 192.0.2.1 as permitted sender) smtp.mailfrom=bounce@mailer.example;\r\n       \
+                  dmarc=pass (p=NONE sp=NONE dis=NONE) header.from=example.com";
+    let results = AuthenticationResults::parse(Some(header), None).unwrap();
+    assert_eq!(results.authserv_id.as_deref(), Some("mx.google.com"));
+    // The passing DKIM signature wins over the failing one
+    assert_eq!(results.dkim, check("pass", "news.example.com"));
+    assert_eq!(results.spf, check("softfail", "mailer.example"));
+    assert_eq!(results.dmarc, check("pass", "example.com"));
+
+    // Received-SPF fills in SPF when Authentication-Results has none
+    let results = AuthenticationResults::parse(
+        Some("mx.google.com; dkim=none"),
+        Some("fail (google.com: domain of x@spoof.example does not designate 203.0.113.9 as permitted sender) client-ip=203.0.113.9; envelope-from=\"x@spoof.example\";"),
+    )
+    .unwrap();
+    assert_eq!(results.spf, check("fail", "spoof.example"));
+    assert_eq!(results.dkim.unwrap().result, "none");
+
+    assert_eq!(AuthenticationResults::parse(None, None), None);
+    assert_eq!(
+        AuthenticationResults::parse(Some("mx.google.com; none"), None),
+        None
+    );
+}
+
+#[test]
+fn test_domains_aligned() {
+    assert!(domains_aligned("example.com", "EXAMPLE.com"));
+    assert!(domains_aligned("mail.example.com", "example.com"));
+    assert!(domains_aligned("example.com", "news.example.com"));
+    assert!(!domains_aligned("example.com.evil.example", "example.com"));
+    assert!(!domains_aligned("badexample.com", "example.com"));
+}
+
+#[test]
+fn test_sender_verdicts() {
+    let results = |spf, dkim, dmarc| AuthenticationResults {
+        authserv_id: Some("mx.google.com".to_string()),
+        spf,
+        dkim,
+        dmarc,
+    };
+
+    // DKIM aligned with the From domain is enough without DMARC
+    let verification = SenderVerification::from_message(&EmailMessage {
+        authentication: Some(results(None, check("pass", "example.com"), None)),
+        ..message("msg-1", "Pay <pay@billing.example.com>", "Statement")
+    });
+    assert_eq!(verification.verdict, SenderVerdict::Authenticated);
+    assert_eq!(
+        verification.from_domain.as_deref(),
+        Some("billing.example.com")
+    );
+    assert!(verification.dkim_aligned);
+    assert!(!verification.spf_aligned);
+
+    // Passing for somebody else's domain proves nothing about the From address
+    let verification = SenderVerification::from_message(&EmailMessage {
+        authentication: Some(results(
+            check("pass", "cheap-mailer.example"),
+            check("pass", "cheap-mailer.example"),
+            None,
+        )),
+        ..message("msg-1", "Bank <security@bank.example>", "Statement")
+    });
+    assert_eq!(verification.verdict, SenderVerdict::Unverified);
+    assert!(verification
+        .reasons
+        .iter()
+        .any(|reason| reason.contains("other than the From address")));
+
+    let verification = SenderVerification::from_message(&EmailMessage {
+        authentication: Some(results(
+            check("pass", "cheap-mailer.example"),
+            None,
+            check("fail", "bank.example"),
+        )),
+        ..message("msg-1", "Bank <security@bank.example>", "Statement")
+    });
+    assert_eq!(verification.verdict, SenderVerdict::Failed);
+    assert!(verification
+        .reasons
+        .contains(&"DMARC fail for bank.example".to_string()));
+
+    let verification = SenderVerification::from_message(&EmailMessage {
+        authentication: Some(results(
+            check("fail", "bank.example"),
+            check("fail", "bank.example"),
+            None,
+        )),
+        ..message("msg-1", "Bank <security@bank.example>", "Statement")
+    });
+    assert_eq!(verification.verdict, SenderVerdict::Failed);
+
+    let verification =
+        SenderVerification::from_message(&message("msg-1", "a@example.com", "Statement"));
+    assert_eq!(verification.verdict, SenderVerdict::Unknown);
+}
+
+#[tokio::test]
+async fn test_verify_sender_tool() {
+    let (_server, client) = mock_client(None).await;
+
+    // The results are part of the email details too
+    let email = call_tool(
+        &client,
+        "get_email",
+        json!({ "message_id": "mock-msg-001" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(email["authentication"]["authserv_id"], "mx.google.com");
+    assert_eq!(email["authentication"]["dmarc"]["result"], "pass");
+
+    let result = call_tool(
+        &client,
+        "verify_sender",
+        json!({ "message_id": "mock-msg-001" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["verdict"], "authenticated");
+    assert_eq!(result["from_domain"], "example.com");
+    assert_eq!(
+        result["spf"],
+        json!({ "result": "pass", "domain": "example.com" })
+    );
+    assert_eq!(result["spf_aligned"], true);
+    assert_eq!(result["dkim_aligned"], true);
+
+    // Sent through a mailing service that signs with its own domain
+    let result = call_tool(
+        &client,
+        "verify_sender",
+        json!({ "message_id": "mock-msg-002" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["verdict"], "unverified");
+    assert_eq!(result["dkim"]["domain"], "mailer.example.net");
+    assert_eq!(result["dkim_aligned"], false);
+    assert!(result["dmarc"].is_null());
+
+    let result = call_tool(
+        &client,
+        "verify_sender",
+        json!({ "message_id": "mock-msg-003" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["verdict"], "unknown");
+}
@@ -0,0 +1,122 @@
+/// Sender Report Tests Module
+///
+/// This module contains tests for sender analytics, checking time range
+/// parsing, how messages are grouped by sender, and the sender_report tool
+/// against the mock server.
+use chrono::{TimeZone, Utc};
+use mcp_gmailcal::gmail_api::MessageMetadata;
+use mcp_gmailcal::sender_report::{parse_time_range, SenderReport};
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn metadata(id: &str, from: &str, day: u32, unread: bool) -> MessageMetadata {
+    let mut label_ids = vec!["INBOX".to_string()];
+    if unread {
+        label_ids.push("UNREAD".to_string());
+    }
+    MessageMetadata {
+        id: id.to_string(),
+        thread_id: id.to_string(),
+        from: Some(from.to_string()),
+        received_at: Some(Utc.with_ymd_and_hms(2026, 10, day, 9, 0, 0).unwrap()),
+        label_ids,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_parse_time_range() {
+    assert_eq!(parse_time_range("7d").unwrap(), "newer_than:7d -from:me");
+    assert_eq!(
+        parse_time_range(" 2 weeks ").unwrap(),
+        "newer_than:14d -from:me"
+    );
+    assert_eq!(parse_time_range("3m").unwrap(), "newer_than:3m -from:me");
+    assert_eq!(parse_time_range("1year").unwrap(), "newer_than:1y -from:me");
+
+    for invalid in ["", "0d", "d", "7", "7h", "last month"] {
+        let error = parse_time_range(invalid).unwrap_err();
+        assert!(error.starts_with("Invalid time range"), "{}", invalid);
+    }
+}
+
+#[test]
+fn test_report_groups_messages_by_sender() {
+    let messages = vec![
+        metadata("1", "News <news@shop.example>", 3, true),
+        metadata("2", "\"Alice Example\" <Alice@Example.com>", 2, false),
+        metadata("3", "news@shop.example", 1, true),
+        metadata("4", "Shop News <news@shop.example>", 5, false),
+        metadata("5", "Bob <bob@example.com>", 4, true),
+        metadata("6", "undisclosed-recipients", 4, true),
+    ];
+
+    let report = SenderReport::from_messages("newer_than:30d", &messages, 2, false);
+    assert_eq!(report.messages_scanned, 6);
+    assert_eq!(report.total_senders, 3);
+    assert_eq!(report.senders.len(), 2);
+
+    let news = &report.senders[0];
+    assert_eq!(news.address, "news@shop.example");
+    assert_eq!(news.name.as_deref(), Some("Shop News"));
+    assert_eq!((news.count, news.unread), (3, 2));
+    assert!((news.unread_ratio - 2.0 / 3.0).abs() < f64::EPSILON);
+    assert_eq!(
+        news.last_contact,
+        Some(Utc.with_ymd_and_hms(2026, 10, 5, 9, 0, 0).unwrap())
+    );
+
+    // Senders with one message each are ordered by the latest contact
+    assert_eq!(report.senders[1].address, "bob@example.com");
+    assert_eq!(report.senders[1].unread_ratio, 1.0);
+
+    let report = SenderReport::from_messages("newer_than:30d", &messages, 10, false);
+    let alice = &report.senders[2];
+    assert_eq!(alice.address, "alice@example.com");
+    assert_eq!(alice.name.as_deref(), Some("Alice Example"));
+    assert_eq!(alice.unread_ratio, 0.0);
+}
+
+#[tokio::test]
+async fn test_sender_report_tool() {
+    let (_server, client) = mock_client(None).await;
+
+    let report = call_tool(&client, "sender_report", json!({ "time_range": "2w" }))
+        .await
+        .unwrap();
+    assert_eq!(report["query"], "newer_than:14d -from:me");
+    assert_eq!(report["messages_scanned"], 3);
+    assert_eq!(report["truncated"], false);
+
+    // Each mock message is from a different sender and unread; the most
+    // recent comes first
+    let senders = report["senders"].as_array().unwrap();
+    assert_eq!(senders.len(), 3);
+    assert_eq!(senders[0]["address"], "bob@example.com");
+    assert_eq!(senders[0]["name"], "Bob Example");
+    assert_eq!(senders[0]["unread_ratio"], 1.0);
+    assert_eq!(senders[0]["last_contact"], "2026-10-05T17:42:00Z");
+    assert_eq!(senders[2]["address"], "billing@vendor.example");
+
+    let report = call_tool(
+        &client,
+        "sender_report",
+        json!({ "limit": 1, "max_messages": 2 }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(report["query"], "newer_than:30d -from:me");
+    assert_eq!(report["truncated"], true);
+    assert_eq!(report["senders"].as_array().unwrap().len(), 1);
+
+    let error = call_tool(&client, "sender_report", json!({ "time_range": "soon" }))
+        .await
+        .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("Invalid time range 'soon'"));
+}
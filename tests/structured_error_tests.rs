@@ -0,0 +1,137 @@
+/// Structured Error Tests Module
+///
+/// This module contains tests for the machine-readable `data` attached to MCP
+/// errors, checking the code, category, retry guidance and troubleshooting
+/// fields for each kind of failure, and that tool errors carry them to clients.
+use mcp_attr::schema::CallToolRequestParams;
+use mcp_attr::Error as McpError;
+use mcp_gmailcal::utils::{error_codes, DEFAULT_RATE_LIMIT_RETRY_SECONDS};
+use mcp_gmailcal::{map_gmail_error, to_mcp_error, ErrorData, GmailApiError};
+use serde_json::{json, Value};
+
+mod common;
+use common::mock_client;
+
+fn error_data(error: &McpError) -> Value {
+    error.to_error_object(false).data.unwrap()
+}
+
+#[test]
+fn test_error_data_fields() {
+    let error = to_mcp_error("Missing GMAIL_CLIENT_ID", error_codes::CONFIG_ERROR);
+    let object = error.to_error_object(false);
+    assert_eq!(object.code.0, error_codes::CONFIG_ERROR as i64);
+    // The readable message is unchanged
+    assert!(object.message.contains("ERROR CODE 1001"));
+    assert!(object.message.contains("Missing GMAIL_CLIENT_ID"));
+
+    assert_eq!(
+        object.data.unwrap(),
+        json!({
+            "code": 1001,
+            "category": "config",
+            "retryable": false,
+            "retry_after": null,
+            "troubleshooting": error_codes::get_troubleshooting_steps(error_codes::CONFIG_ERROR)
+        })
+    );
+}
+
+#[test]
+fn test_error_data_builders() {
+    let data = ErrorData::new(error_codes::GENERAL_ERROR);
+    assert_eq!(data.category, "internal");
+    assert!(!data.retryable);
+
+    let data = ErrorData::new(error_codes::API_ERROR)
+        .with_category("not_found")
+        .retryable(Some(5));
+    assert_eq!(data.category, "not_found");
+    assert_eq!((data.retryable, data.retry_after), (true, Some(5)));
+
+    assert_eq!(
+        ErrorData::rate_limited(error_codes::API_ERROR).retry_after,
+        Some(DEFAULT_RATE_LIMIT_RETRY_SECONDS)
+    );
+    assert_eq!(ErrorData::network(error_codes::API_ERROR).retry_after, None);
+}
+
+#[test]
+fn test_gmail_errors_are_categorized() {
+    let cases = [
+        (
+            GmailApiError::RateLimitError("429".into()),
+            "rate_limit",
+            true,
+        ),
+        (
+            GmailApiError::ApiError("quota exceeded".into()),
+            "rate_limit",
+            true,
+        ),
+        (
+            GmailApiError::NetworkError("connection reset".into()),
+            "network",
+            true,
+        ),
+        (
+            GmailApiError::ApiError("connection refused".into()),
+            "network",
+            true,
+        ),
+        (
+            GmailApiError::MessageRetrievalError("404".into()),
+            "not_found",
+            false,
+        ),
+        (GmailApiError::AuthError("expired".into()), "auth", false),
+        (
+            GmailApiError::MessageFormatError("bad".into()),
+            "message_format",
+            false,
+        ),
+        (
+            GmailApiError::AttachmentError("too big".into()),
+            "attachment",
+            false,
+        ),
+        (GmailApiError::CacheError("io".into()), "internal", false),
+        (GmailApiError::ApiError("bad request".into()), "api", false),
+    ];
+
+    for (error, category, retryable) in cases {
+        let description = error.to_string();
+        let data = error_data(&map_gmail_error(error));
+        assert_eq!(data["category"], category, "{}", description);
+        assert_eq!(data["retryable"], retryable, "{}", description);
+    }
+
+    let data = error_data(&map_gmail_error(GmailApiError::RateLimitError(
+        "429".into(),
+    )));
+    assert_eq!(data["retry_after"], DEFAULT_RATE_LIMIT_RETRY_SECONDS);
+    assert_eq!(data["code"], error_codes::API_ERROR);
+}
+
+#[tokio::test]
+async fn test_tool_errors_include_structured_data() {
+    let (_server, client) = mock_client(None).await;
+
+    let mut arguments = serde_json::Map::new();
+    arguments.insert("message_id".to_string(), "no-such-message".into());
+    let error = client
+        .tools_call(CallToolRequestParams {
+            name: "get_email".to_string(),
+            arguments: Some(arguments),
+        })
+        .await
+        .unwrap_err();
+
+    let object = error
+        .error_object()
+        .expect("tool error has an error object");
+    let data = object.data.as_ref().unwrap();
+    assert_eq!(data["category"], "not_found");
+    assert_eq!(data["retryable"], false);
+    assert_eq!(data["code"], error_codes::API_ERROR);
+}
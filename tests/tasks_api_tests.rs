@@ -0,0 +1,157 @@
+/// Tasks API Tests Module
+///
+/// This module contains tests for the Google Tasks integration, checking due
+/// date parsing, the in-memory Tasks mock, error mapping, and the task tools
+/// against the mock server.
+use mcp_gmailcal::api::TasksApi;
+use mcp_gmailcal::tasks_api::{parse_due_date, DEFAULT_TASK_LIST};
+use mcp_gmailcal::test_util::MockTasksApi;
+use mcp_gmailcal::utils::error_codes;
+use mcp_gmailcal::{map_to_mcp_error, Task, TaskList, TasksApiError};
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn task(title: &str) -> Task {
+    Task {
+        title: title.to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_parse_due_date() {
+    assert_eq!(
+        parse_due_date("2024-03-08").unwrap(),
+        "2024-03-08T00:00:00.000Z"
+    );
+    // Only the date is kept
+    assert_eq!(
+        parse_due_date(" 2024-03-08T17:30:00-05:00 ").unwrap(),
+        "2024-03-08T00:00:00.000Z"
+    );
+
+    for invalid in ["next friday", "2024-13-01", ""] {
+        assert!(
+            matches!(parse_due_date(invalid), Err(TasksApiError::InvalidInput(_))),
+            "{}",
+            invalid
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_mock_tasks_api() {
+    let tasks = MockTasksApi::new().with_task_lists(vec![TaskList {
+        id: "work".to_string(),
+        title: "Work".to_string(),
+        updated: None,
+    }]);
+
+    let created = tasks
+        .create_task(DEFAULT_TASK_LIST, &task("Reply to Alice"))
+        .await
+        .unwrap();
+    tasks
+        .create_task("work", &task("File expenses"))
+        .await
+        .unwrap();
+    assert_eq!(created.status.as_deref(), Some("needsAction"));
+
+    let id = created.id.unwrap();
+    let completed = tasks.complete_task(DEFAULT_TASK_LIST, &id).await.unwrap();
+    assert_eq!(completed.status.as_deref(), Some("completed"));
+    assert!(completed.completed.is_some());
+
+    // Completed tasks are hidden unless asked for
+    assert!(tasks
+        .list_tasks(DEFAULT_TASK_LIST, false, None)
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        tasks
+            .list_tasks(DEFAULT_TASK_LIST, true, None)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+
+    assert!(matches!(
+        tasks.create_task("work", &task(" ")).await,
+        Err(TasksApiError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        tasks.list_tasks("missing", false, None).await,
+        Err(TasksApiError::ApiError(_))
+    ));
+}
+
+#[test]
+fn test_tasks_error_mapping() {
+    let object = map_to_mcp_error(TasksApiError::InvalidInput("bad due date".to_string()))
+        .to_error_object(false);
+    assert_eq!(object.data.as_ref().unwrap()["category"], "invalid_input");
+
+    let object =
+        map_to_mcp_error(TasksApiError::AuthError("expired".to_string())).to_error_object(false);
+    assert_eq!(object.data.unwrap()["code"], error_codes::AUTH_ERROR);
+}
+
+#[tokio::test]
+async fn test_task_tools() {
+    let (_server, client) = mock_client(None).await;
+
+    let lists = call_tool(&client, "list_task_lists", json!({}))
+        .await
+        .unwrap();
+    assert_eq!(lists[0]["id"], "@default");
+    assert_eq!(lists[1]["title"], "Follow-ups");
+
+    let tasks = call_tool(&client, "list_tasks", json!({})).await.unwrap();
+    assert_eq!(tasks[0]["id"], "mock-task-001");
+    assert_eq!(
+        tasks[0]["web_view_link"],
+        "https://tasks.google.com/task/mock-task-001"
+    );
+
+    let created = call_tool(
+        &client,
+        "create_task",
+        json!({
+            "title": "Send the budget numbers",
+            "notes": "Asked for by Alice",
+            "due": "2024-03-08",
+            "message_id": "mock-msg-001"
+        }),
+    )
+    .await
+    .unwrap();
+    assert!(created["id"].is_string());
+    assert_eq!(created["due"], "2024-03-08T00:00:00.000Z");
+    let notes = created["notes"].as_str().unwrap();
+    assert!(notes.starts_with("Asked for by Alice"));
+    assert!(notes.contains("https://mail.google.com/mail/u/0/#all/mock-msg-001"));
+
+    let completed = call_tool(
+        &client,
+        "complete_task",
+        json!({ "task_id": "mock-task-001" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(completed["status"], "completed");
+
+    let error = call_tool(
+        &client,
+        "create_task",
+        json!({ "title": "Later", "due": "someday" }),
+    )
+    .await
+    .unwrap_err();
+    let object = error.error_object().unwrap();
+    assert!(object.message.contains("Invalid due date"));
+    assert_eq!(object.data.as_ref().unwrap()["category"], "invalid_input");
+}
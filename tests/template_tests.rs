@@ -0,0 +1,194 @@
+/// Template Tests Module
+///
+/// This module contains tests for the email template library, checking
+/// placeholder filling, how templates are stored and replaced, and the
+/// template tools against the mock server.
+use mcp_gmailcal::templates::{EmailTemplate, TemplateStore};
+use mcp_gmailcal::TemplateError;
+use serde_json::json;
+use std::collections::HashMap;
+use tempfile::tempdir;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn template(name: &str) -> EmailTemplate {
+    EmailTemplate {
+        name: name.to_string(),
+        description: Some("Weekly update to the team".to_string()),
+        subject: "Status for {{week}}".to_string(),
+        body: "Hi {{ name }},\n\nThis week: {{summary}}\n\n{{not a placeholder}} {{".to_string(),
+        to: Some("{{team}}@example.com".to_string()),
+        cc: None,
+    }
+}
+
+fn variables(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_template_placeholders_are_filled() {
+    let template = template("weekly");
+    assert_eq!(
+        template.variables(),
+        vec!["name", "summary", "team", "week"]
+    );
+
+    let rendered = template
+        .render(&variables(&[
+            ("week", "W12"),
+            ("name", "Sam"),
+            ("summary", "Shipped search"),
+            ("team", "eng"),
+        ]))
+        .unwrap();
+    assert_eq!(rendered.subject, "Status for W12");
+    assert_eq!(
+        rendered.body,
+        "Hi Sam,\n\nThis week: Shipped search\n\n{{not a placeholder}} {{"
+    );
+    assert_eq!(rendered.to.as_deref(), Some("eng@example.com"));
+
+    match template.render(&variables(&[("week", "W12")])) {
+        Err(TemplateError::MissingVariables(names)) => {
+            assert_eq!(names, vec!["name", "summary", "team"])
+        }
+        other => panic!("Expected MissingVariables, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_rendered_template_recipients() {
+    let mut template = template("weekly");
+    template.body = "Hello".to_string();
+    let vars = variables(&[("week", "W12"), ("team", "eng")]);
+
+    let draft = template
+        .render(&vars)
+        .unwrap()
+        .into_draft(None, None)
+        .unwrap();
+    assert_eq!(draft.to, "eng@example.com");
+
+    let draft = template
+        .render(&vars)
+        .unwrap()
+        .into_draft(
+            Some("lead@example.com".to_string()),
+            Some("cc@example.com".to_string()),
+        )
+        .unwrap();
+    assert_eq!(draft.to, "lead@example.com");
+    assert_eq!(draft.cc.as_deref(), Some("cc@example.com"));
+
+    template.to = None;
+    assert!(matches!(
+        template.render(&vars).unwrap().into_draft(None, None),
+        Err(TemplateError::InvalidTemplate(_))
+    ));
+}
+
+#[test]
+fn test_template_store_saves_and_replaces() {
+    let dir = tempdir().unwrap();
+    let store = TemplateStore::new(dir.path().join("nested").join("templates.json"));
+    assert!(store.list().unwrap().is_empty());
+    assert!(matches!(
+        store.get("weekly"),
+        Err(TemplateError::NotFound(_))
+    ));
+
+    assert!(!store.save(template("Weekly")).unwrap());
+    assert!(!store.save(template("intro")).unwrap());
+
+    // Names are matched ignoring case, and saving again replaces
+    let mut updated = template(" weekly ");
+    updated.subject = "Update for {{week}}".to_string();
+    assert!(store.save(updated).unwrap());
+
+    let names: Vec<String> = store.list().unwrap().into_iter().map(|t| t.name).collect();
+    assert_eq!(names, vec!["intro", "weekly"]);
+    assert_eq!(store.get("WEEKLY").unwrap().subject, "Update for {{week}}");
+
+    let mut empty = template("empty");
+    empty.subject = " ".to_string();
+    empty.body = String::new();
+    assert!(matches!(
+        store.save(empty),
+        Err(TemplateError::InvalidTemplate(_))
+    ));
+
+    std::fs::write(store.path(), "not json").unwrap();
+    assert!(matches!(store.list(), Err(TemplateError::StorageError(_))));
+}
+
+#[tokio::test]
+async fn test_template_tools() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("EMAIL_TEMPLATES_FILE", dir.path().join("templates.json"));
+    let (_server, client) = mock_client(None).await;
+
+    let saved = call_tool(
+        &client,
+        "save_template",
+        json!({
+            "name": "intro",
+            "subject": "Introducing {{a}} and {{b}}",
+            "body": "{{a}}, meet {{b}}."
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(saved["replaced"], false);
+    assert_eq!(saved["template"]["variables"], json!(["a", "b"]));
+
+    let listed = call_tool(&client, "list_templates", json!({}))
+        .await
+        .unwrap();
+    assert_eq!(listed["count"], 1);
+    assert_eq!(listed["templates"][0]["name"], "intro");
+
+    let draft = call_tool(
+        &client,
+        "create_draft_from_template",
+        json!({
+            "name": "intro",
+            "to": "ada@example.com",
+            "variables": { "a": "Ada", "b": "Grace" }
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(draft["status"], "success");
+    assert!(draft["draft_id"].is_string());
+    assert_eq!(draft["subject"], "Introducing Ada and Grace");
+    assert_eq!(draft["body"], "Ada, meet Grace.");
+
+    let error = call_tool(
+        &client,
+        "create_draft_from_template",
+        json!({ "name": "intro", "to": "ada@example.com", "variables": { "a": "Ada" } }),
+    )
+    .await
+    .unwrap_err();
+    let object = error.error_object().unwrap();
+    assert!(object.message.contains("Missing template variables: b"));
+    assert_eq!(object.data.as_ref().unwrap()["category"], "invalid_input");
+
+    let error = call_tool(
+        &client,
+        "create_draft_from_template",
+        json!({ "name": "missing", "to": "ada@example.com" }),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error.error_object().unwrap().data.as_ref().unwrap()["category"],
+        "not_found"
+    );
+    std::env::remove_var("EMAIL_TEMPLATES_FILE");
+}
@@ -0,0 +1,106 @@
+/// Thread Operations Tests Module
+///
+/// This module contains tests for conversation-level triage, checking that
+/// label changes and trashing apply to every message in a thread, and the
+/// thread tools against the mock server.
+use mcp_gmailcal::api::GmailApi;
+use mcp_gmailcal::test_util::{message, MockGmailApi};
+use mcp_gmailcal::{EmailMessage, GmailApiError};
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn labels(ids: &[&str]) -> Vec<String> {
+    ids.iter().map(|id| id.to_string()).collect()
+}
+
+#[tokio::test]
+async fn test_thread_operations_apply_to_every_message() {
+    let gmail = MockGmailApi::new().with_messages(vec![
+        EmailMessage {
+            thread_id: "thread-a".to_string(),
+            ..message("msg-1", "alice@example.com", "Budget")
+        },
+        EmailMessage {
+            thread_id: "thread-b".to_string(),
+            ..message("msg-2", "alice@example.com", "Budget")
+        },
+        EmailMessage {
+            thread_id: "thread-a".to_string(),
+            ..message("msg-3", "alice@example.com", "Budget")
+        },
+    ]);
+
+    let message_ids = gmail
+        .modify_thread_labels("thread-a", &labels(&["STARRED"]), &labels(&["INBOX"]))
+        .await
+        .unwrap();
+    assert_eq!(message_ids, vec!["msg-1", "msg-3"]);
+    assert_eq!(gmail.message_labels()["msg-1"], vec!["STARRED"]);
+    assert_eq!(gmail.message_labels()["msg-3"], vec!["STARRED"]);
+    assert!(!gmail.message_labels().contains_key("msg-2"));
+
+    gmail.trash_thread("thread-b").await.unwrap();
+    assert_eq!(gmail.message_labels()["msg-2"], vec!["TRASH"]);
+
+    assert!(matches!(
+        gmail.trash_thread("thread-z").await,
+        Err(GmailApiError::MessageRetrievalError(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_thread_tools() {
+    let (_server, client) = mock_client(None).await;
+
+    let result = call_tool(
+        &client,
+        "modify_thread_labels",
+        json!({
+            "thread_id": "mock-thread-001",
+            "add_label_ids": ["STARRED"],
+            "remove_label_ids": ["INBOX"]
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["status"], "success");
+    assert_eq!(
+        result["message_ids"],
+        json!(["mock-msg-001", "mock-msg-003"])
+    );
+    assert_eq!(result["removed_label_ids"], json!(["INBOX"]));
+
+    let result = call_tool(
+        &client,
+        "mark_thread_read",
+        json!({ "thread_id": "mock-thread-001" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["unread"], false);
+    assert_eq!(result["message_ids"].as_array().unwrap().len(), 2);
+
+    let result = call_tool(
+        &client,
+        "trash_thread",
+        json!({ "thread_id": "mock-thread-001" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        result["message_ids"],
+        json!(["mock-msg-001", "mock-msg-003"])
+    );
+
+    let error = call_tool(
+        &client,
+        "modify_thread_labels",
+        json!({ "thread_id": "mock-thread-001" }),
+    )
+    .await
+    .unwrap_err();
+    let object = error.error_object().unwrap();
+    assert!(object.message.contains("At least one label"));
+}
@@ -0,0 +1,124 @@
+/// Thread Search Tests Module
+///
+/// This module contains tests for finding text in a thread, checking the
+/// offsets and snippets of matches in the clean view and the search_in_thread
+/// tool against the mock server.
+use mcp_gmailcal::test_util::message;
+use mcp_gmailcal::thread_view::{CleanThread, SNIPPET_CONTEXT_CHARS};
+use mcp_gmailcal::EmailMessage;
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+#[test]
+fn test_search_finds_text_once() {
+    let thread = CleanThread::from_messages(
+        "thread-plan",
+        &[
+            EmailMessage {
+                date: Some("Tue, 13 Oct 2026 10:00:00 +0000".to_string()),
+                body_text: Some("Agreed.\n\nOn Mon, Oct 12, 2026 at 9:14 AM Alice <alice@example.com> wrote:\n> The budget is 40k.\n".to_string()),
+                ..message("msg-reply", "Alice <alice@example.com>", "Planning")
+            },
+            EmailMessage {
+                date: Some("Mon, 12 Oct 2026 09:14:00 +0000".to_string()),
+                body_text: Some("Hi Bob,\nThe Budget is\n40k for the quarter.".to_string()),
+                ..message("msg-first", "Alice <alice@example.com>", "Planning")
+            },
+        ],
+    );
+
+    // The quoted copy in the reply is not counted, and case and line breaks
+    // are ignored
+    let matches = thread.search("budget is 40k");
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].message_id, "msg-first");
+    assert_eq!(matches[0].offset, 12);
+    assert_eq!(matches[0].length, 13);
+    assert_eq!(
+        matches[0].snippet,
+        "Hi Bob, The Budget is 40k for the quarter."
+    );
+
+    let content: Vec<char> = thread.messages[0].content.chars().collect();
+    let found: String = content[12..25].iter().collect();
+    assert_eq!(found, "Budget is\n40k");
+
+    assert!(thread.search("   ").is_empty());
+    assert!(thread.search("50k").is_empty());
+}
+
+#[test]
+fn test_search_offsets_and_snippets() {
+    let long = format!(
+        "{}café déjà vu, then café again. {}",
+        "x".repeat(100),
+        "y".repeat(100)
+    );
+    let thread = CleanThread::from_messages(
+        "thread-plan",
+        &[EmailMessage {
+            date: Some("Mon, 12 Oct 2026 09:14:00 +0000".to_string()),
+            body_text: Some(long),
+            ..message("msg-1", "Alice <alice@example.com>", "Planning")
+        }],
+    );
+
+    // Offsets count characters, not bytes
+    let matches = thread.search("CAFÉ");
+    assert_eq!(matches.len(), 2);
+    assert_eq!(matches[0].offset, 100);
+    assert_eq!(matches[1].offset, 119);
+    assert!(matches.iter().all(|found| found.length == 4));
+
+    // Long content is cut around the match
+    let snippet = &matches[0].snippet;
+    assert!(snippet.starts_with('…'));
+    assert!(snippet.ends_with('…'));
+    assert_eq!(snippet.chars().count(), SNIPPET_CONTEXT_CHARS * 2 + 4 + 2);
+}
+
+#[tokio::test]
+async fn test_search_in_thread_tool() {
+    let (_server, client) = mock_client(None).await;
+
+    let result = call_tool(
+        &client,
+        "search_in_thread",
+        json!({ "thread_id": "mock-thread-001", "text": "thursday" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["thread_id"], "mock-thread-001");
+    assert_eq!(result["subject"], "Quarterly planning meeting");
+    assert_eq!(result["message_count"], 3);
+    assert_eq!(result["match_count"], 3);
+    let matches = result["matches"].as_array().unwrap();
+    assert_eq!(matches[0]["message_id"], "mock-msg-001");
+    assert_eq!(matches[1]["message_id"], "mock-msg-003");
+    assert!(matches[1]["snippet"]
+        .as_str()
+        .unwrap()
+        .starts_with("Thursday works for me."));
+    assert_eq!(matches[1]["offset"], 0);
+    assert_eq!(matches[1]["length"], 8);
+
+    let result = call_tool(
+        &client,
+        "search_in_thread",
+        json!({ "thread_id": "mock-thread-001", "text": "thursday", "max_results": 1 }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["match_count"], 3);
+    assert_eq!(result["matches"].as_array().unwrap().len(), 1);
+
+    assert!(call_tool(
+        &client,
+        "search_in_thread",
+        json!({ "thread_id": "mock-thread-001", "text": " " }),
+    )
+    .await
+    .is_err());
+}
@@ -0,0 +1,145 @@
+/// Thread View Tests Module
+///
+/// This module contains tests for stripping quoted text from email bodies and
+/// for the get_thread_clean tool against the mock server.
+use mcp_gmailcal::utils::strip_quoted_text;
+use serde_json::{json, Value};
+
+mod common;
+use common::{call_tool, mock_client};
+
+#[test]
+fn test_strip_quoted_replies() {
+    let body = "Sounds good, see you then.\n\n\
+                On Mon, Oct 12, 2026 at 9:14 AM Alice <alice@example.com> wrote:\n\
+                > Shall we meet on Thursday?\n\
+                >\n\
+                > Alice\n";
+    assert_eq!(strip_quoted_text(body), "Sounds good, see you then.");
+
+    // Clients wrap a long attribution onto a second line
+    let body = "Thursday works.\n\n\
+                On Mon, Oct 12, 2026 at 9:14 AM Alice Example\n\
+                <alice@example.com> wrote:\n\
+                \n\
+                > Shall we meet?\n";
+    assert_eq!(strip_quoted_text(body), "Thursday works.");
+
+    // Replies written between quoted lines are kept
+    let body = "> Budget?\nApproved.\n> Timeline?\nEnd of month.";
+    assert_eq!(strip_quoted_text(body), "Approved.\nEnd of month.");
+}
+
+#[test]
+fn test_strip_quoted_original_message() {
+    let body = "Forwarding for visibility.\r\n\r\n\
+                -----Original Message-----\r\n\
+                From: Bob <bob@example.net>\r\n\
+                Sent: Monday, October 12, 2026 9:14 AM\r\n\
+                Subject: Budget\r\n";
+    assert_eq!(strip_quoted_text(body), "Forwarding for visibility.");
+
+    let body = "See below.\n\n\
+                ________________________________\n\
+                From: Bob <bob@example.net>\n\
+                Sent: Monday, October 12, 2026 9:14 AM\n";
+    assert_eq!(strip_quoted_text(body), "See below.");
+}
+
+#[test]
+fn test_strip_quoted_keeps_unquoted_text() {
+    // Without a quote after it, "On ... wrote:" is the author's own text
+    let body = "On Monday the team wrote:\nthe first draft of the plan.";
+    assert_eq!(strip_quoted_text(body), body);
+    assert_eq!(strip_quoted_text("  No quotes here.\n"), "No quotes here.");
+    assert_eq!(strip_quoted_text(""), "");
+}
+
+fn message(id: &str, from: &str, to: &str, date: &str, body: &str) -> Value {
+    json!({
+        "id": id,
+        "threadId": "thread-plan",
+        "labelIds": ["INBOX"],
+        "snippet": body.lines().next().unwrap_or_default(),
+        "payload": {
+            "mimeType": "text/plain",
+            "headers": [
+                { "name": "From", "value": from },
+                { "name": "To", "value": to },
+                { "name": "Subject", "value": "Planning" },
+                { "name": "Date", "value": date }
+            ],
+            "body": { "data": base64::encode_config(body, base64::URL_SAFE) }
+        }
+    })
+}
+
+#[tokio::test]
+async fn test_get_thread_clean_tool() {
+    let dir = tempfile::tempdir().unwrap();
+    let threads = dir.path().join("gmail/v1/users/me/threads");
+    std::fs::create_dir_all(&threads).unwrap();
+    // Gmail returns the reply first here, in another timezone
+    let thread = json!({
+        "id": "thread-plan",
+        "messages": [
+            message(
+                "msg-reply",
+                "Bob <bob@example.net>",
+                "Alice <alice@example.com>",
+                "Mon, 12 Oct 2026 19:30:00 +0200 (CEST)",
+                "Thursday works.\n\nOn Mon, Oct 12, 2026 at 9:14 AM Alice <alice@example.com> wrote:\n> Shall we meet on Thursday?\n"
+            ),
+            message(
+                "msg-first",
+                "Alice <alice@example.com>",
+                "bob@example.net, Carol <carol@example.org>",
+                "Mon, 12 Oct 2026 09:14:00 -0700",
+                "Shall we meet on Thursday?\n"
+            )
+        ]
+    });
+    std::fs::write(threads.join("thread-plan.json"), thread.to_string()).unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    let result = call_tool(
+        &client,
+        "get_thread_clean",
+        json!({ "thread_id": "thread-plan" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["thread_id"], "thread-plan");
+    assert_eq!(result["subject"], "Planning");
+    assert_eq!(result["message_count"], 2);
+    assert_eq!(result["started_at"], "2026-10-12T16:14:00Z");
+    assert_eq!(result["last_activity"], "2026-10-12T17:30:00Z");
+
+    let messages = result["messages"].as_array().unwrap();
+    assert_eq!(messages[0]["id"], "msg-first");
+    assert_eq!(messages[0]["content"], "Shall we meet on Thursday?");
+    assert_eq!(messages[0]["quotes_removed"], false);
+    assert_eq!(messages[1]["id"], "msg-reply");
+    assert_eq!(messages[1]["timestamp"], "2026-10-12T17:30:00Z");
+    assert_eq!(messages[1]["content"], "Thursday works.");
+    assert_eq!(messages[1]["quotes_removed"], true);
+
+    assert_eq!(
+        result["participants"],
+        json!([
+            { "address": "alice@example.com", "name": "Alice", "messages_sent": 1 },
+            { "address": "bob@example.net", "name": "Bob", "messages_sent": 1 },
+            { "address": "carol@example.org", "name": null, "messages_sent": 0 }
+        ])
+    );
+
+    let error = call_tool(
+        &client,
+        "get_thread_clean",
+        json!({ "thread_id": "no-such-thread" }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error.error_object().is_some());
+}
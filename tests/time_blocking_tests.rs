@@ -0,0 +1,233 @@
+/// Time Blocking Tests Module
+///
+/// This module contains tests for reading tasks and their lengths, finding
+/// free time around busy periods and fitting tasks into it, and for the
+/// block_time_for_tasks tool against the mock server.
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use mcp_gmailcal::calendar_api::BusyPeriod;
+use mcp_gmailcal::time_blocking::{
+    align_up, focus_time_event, free_slots, parse_task, parse_task_duration, plan_blocks, TaskBlock,
+};
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 10, 20, hour, minute, 0).unwrap()
+}
+
+fn busy(start: DateTime<Utc>, end: DateTime<Utc>) -> BusyPeriod {
+    BusyPeriod { start, end }
+}
+
+fn task(name: &str, minutes: i64) -> TaskBlock {
+    TaskBlock {
+        name: name.to_string(),
+        minutes,
+    }
+}
+
+#[test]
+fn test_parse_task_duration() {
+    assert_eq!(parse_task_duration("90m"), Some(Duration::minutes(90)));
+    assert_eq!(parse_task_duration("1h30m"), Some(Duration::minutes(90)));
+    assert_eq!(
+        parse_task_duration("1.5 hours"),
+        Some(Duration::minutes(90))
+    );
+    assert_eq!(
+        parse_task_duration("45 Minutes"),
+        Some(Duration::minutes(45))
+    );
+    assert_eq!(parse_task_duration("2 hrs"), Some(Duration::hours(2)));
+    assert_eq!(parse_task_duration("soon"), None);
+    assert_eq!(parse_task_duration("0m"), None);
+    assert_eq!(parse_task_duration("30"), None);
+    assert_eq!(parse_task_duration("168h"), Some(Duration::weeks(1)));
+    assert_eq!(parse_task_duration("168h1m"), None);
+    assert_eq!(parse_task_duration("99999999999999999999h"), None);
+}
+
+#[test]
+fn test_parse_task() {
+    assert_eq!(
+        parse_task("Write report: 90m").unwrap(),
+        task("Write report", 90)
+    );
+    assert_eq!(
+        parse_task("Review PRs (45 min)").unwrap(),
+        task("Review PRs", 45)
+    );
+    assert_eq!(
+        parse_task("Inbox zero - 1 hour 15 minutes").unwrap(),
+        task("Inbox zero", 75)
+    );
+    // A colon in the name doesn't hide the length at the end
+    assert_eq!(parse_task("Q3: plan 2h").unwrap(), task("Q3: plan", 120));
+    assert!(parse_task("Write report")
+        .unwrap_err()
+        .contains("expected a name and a length"));
+    assert!(parse_task("90m").is_err());
+}
+
+#[test]
+fn test_align_up() {
+    assert_eq!(align_up(at(9, 0)), at(9, 0));
+    assert_eq!(align_up(at(9, 1)), at(9, 15));
+    assert_eq!(align_up(at(9, 50)), at(10, 0));
+    assert_eq!(align_up(at(9, 14) + Duration::seconds(30)), at(9, 15));
+}
+
+#[test]
+fn test_free_slots() {
+    let window = (at(9, 0), at(17, 0));
+    let periods = [
+        busy(at(13, 0), at(14, 0)),
+        busy(at(8, 0), at(9, 30)),
+        // Overlaps the one before
+        busy(at(13, 30), at(15, 0)),
+        busy(at(16, 30), at(18, 0)),
+    ];
+    assert_eq!(
+        free_slots(window, &periods),
+        vec![(at(9, 30), at(13, 0)), (at(15, 0), at(16, 30)),]
+    );
+    assert_eq!(free_slots(window, &[]), vec![window]);
+    assert!(free_slots(window, &[busy(at(8, 0), at(18, 0))]).is_empty());
+}
+
+#[test]
+fn test_plan_blocks() {
+    let slots = vec![(at(9, 0), at(10, 0)), (at(11, 0), at(13, 0))];
+    let tasks = [
+        task("Report", 90),
+        task("Email", 20),
+        task("Review", 30),
+        task("Offsite plan", 180),
+        task("Call notes", 40),
+    ];
+    let (scheduled, unscheduled) = plan_blocks(&tasks, slots);
+
+    let times: Vec<(&str, DateTime<Utc>, DateTime<Utc>)> = scheduled
+        .iter()
+        .map(|block| (block.task.as_str(), block.start_time, block.end_time))
+        .collect();
+    // Blocks after a block start on the next quarter hour
+    assert_eq!(
+        times,
+        vec![
+            ("Email", at(9, 0), at(9, 20)),
+            ("Review", at(9, 30), at(10, 0)),
+            ("Report", at(11, 0), at(12, 30)),
+        ]
+    );
+    let missed: Vec<&str> = unscheduled.iter().map(|t| t.task.as_str()).collect();
+    assert_eq!(missed, vec!["Offsite plan", "Call notes"]);
+    assert_eq!(
+        unscheduled[0].reason,
+        "No free slot of 180 minutes left in working hours"
+    );
+}
+
+#[test]
+fn test_focus_time_event() {
+    let (scheduled, _) = plan_blocks(&[task("Report", 60)], vec![(at(9, 0), at(12, 0))]);
+    let event = focus_time_event(&scheduled[0], "primary", "Europe/Berlin");
+    assert_eq!(event.summary, "Report");
+    assert_eq!(event.event_type.as_deref(), Some("focusTime"));
+    assert_eq!(event.transparency.as_deref(), Some("opaque"));
+    assert_eq!(event.time_zone.as_deref(), Some("Europe/Berlin"));
+    assert_eq!((event.start_time, event.end_time), (at(9, 0), at(10, 0)));
+
+    let event = focus_time_event(&scheduled[0], "team@example.com", "UTC");
+    assert_eq!(event.event_type, None);
+}
+
+#[tokio::test]
+async fn test_block_time_for_tasks_tool() {
+    std::env::set_var("USER_TIMEZONE", "Europe/Berlin");
+    let dir = tempfile::tempdir().unwrap();
+    let calendar = dir.path().join("calendar/v3");
+    std::fs::create_dir_all(&calendar).unwrap();
+    // Working hours on 5 March 2030 are 08:00 to 16:00 UTC
+    let free_busy = json!({
+        "calendars": {
+            "primary": {
+                "busy": [
+                    { "start": "2030-03-05T08:00:00Z", "end": "2030-03-05T10:00:00Z" },
+                    { "start": "2030-03-05T11:00:00Z", "end": "2030-03-05T15:30:00Z" }
+                ]
+            }
+        }
+    });
+    std::fs::write(calendar.join("freeBusy.post.json"), free_busy.to_string()).unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    let plan = call_tool(
+        &client,
+        "block_time_for_tasks",
+        json!({
+            "tasks": ["Write report: 90m", "Review PRs (45 min)", "Inbox 30m"],
+            "day": "2030-03-05"
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(plan["date"], "2030-03-05");
+    assert_eq!(plan["time_zone"], "Europe/Berlin");
+    let scheduled = plan["scheduled"].as_array().unwrap();
+    assert_eq!(scheduled.len(), 2);
+    assert_eq!(scheduled[0]["task"], "Review PRs");
+    assert_eq!(scheduled[0]["start_time"], "2030-03-05T10:00:00Z");
+    assert_eq!(scheduled[0]["end_time"], "2030-03-05T10:45:00Z");
+    assert!(scheduled[0]["event_id"].is_string());
+    assert_eq!(scheduled[1]["task"], "Inbox");
+    assert_eq!(scheduled[1]["start_time"], "2030-03-05T15:30:00Z");
+    let unscheduled = plan["unscheduled"].as_array().unwrap();
+    assert_eq!(unscheduled.len(), 1);
+    assert_eq!(unscheduled[0]["task"], "Write report");
+    assert_eq!(unscheduled[0]["minutes"], 90);
+
+    // A longer day fits everything
+    let plan = call_tool(
+        &client,
+        "block_time_for_tasks",
+        json!({
+            "tasks": ["Write report: 90m"],
+            "day": "2030-03-05",
+            "work_end": "7pm"
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(plan["scheduled"][0]["start_time"], "2030-03-05T15:30:00Z");
+    assert!(plan["unscheduled"].as_array().unwrap().is_empty());
+
+    let error = call_tool(
+        &client,
+        "block_time_for_tasks",
+        json!({ "tasks": ["Write report"], "day": "2030-03-05" }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("Invalid task 'Write report'"));
+
+    let error = call_tool(
+        &client,
+        "block_time_for_tasks",
+        json!({ "tasks": ["Email: 15m"], "work_start": "5pm", "work_end": "9am" }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("work_end must be after work_start"));
+}
@@ -0,0 +1,127 @@
+/// Time Zone Tests Module
+///
+/// This module contains tests for converting times between time zones, and
+/// for the convert_time tool and attendee time zones against the mock server.
+use mcp_gmailcal::timezones::{convert_time, parse_time_zone};
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+#[test]
+fn test_parse_time_zone() {
+    assert_eq!(
+        parse_time_zone(" Europe/Berlin ").unwrap().name(),
+        "Europe/Berlin"
+    );
+    assert_eq!(parse_time_zone("UTC").unwrap().name(), "UTC");
+    let error = parse_time_zone("Mars/Olympus_Mons").unwrap_err();
+    assert!(error.contains("Unknown time zone 'Mars/Olympus_Mons'"));
+}
+
+#[test]
+fn test_convert_local_time() {
+    let new_york = parse_time_zone("America/New_York").unwrap();
+    let berlin = parse_time_zone("Europe/Berlin").unwrap();
+    let conversion = convert_time("2026-10-15 09:00", new_york, berlin).unwrap();
+    assert_eq!(conversion.from.local_time, "2026-10-15T09:00:00-04:00");
+    assert_eq!(conversion.from.abbreviation.as_deref(), Some("EDT"));
+    assert_eq!(conversion.to.local_time, "2026-10-15T15:00:00+02:00");
+    assert_eq!(conversion.to.abbreviation.as_deref(), Some("CEST"));
+    assert_eq!(conversion.to.utc_offset, "+02:00");
+    assert!(conversion.to.dst);
+    assert_eq!(conversion.utc.to_rfc3339(), "2026-10-15T13:00:00+00:00");
+    assert_eq!(conversion.day_change, 0);
+    assert_eq!(conversion.note, None);
+
+    // Berlin has left summer time by November while New York has not
+    let conversion = convert_time("2026-10-30T09:00", new_york, berlin).unwrap();
+    assert_eq!(conversion.to.local_time, "2026-10-30T14:00:00+01:00");
+    assert!(!conversion.to.dst);
+
+    let los_angeles = parse_time_zone("America/Los_Angeles").unwrap();
+    let tokyo = parse_time_zone("Asia/Tokyo").unwrap();
+    let conversion = convert_time("2026-10-15 20:00:00", los_angeles, tokyo).unwrap();
+    assert_eq!(conversion.to.local_time, "2026-10-16T12:00:00+09:00");
+    assert_eq!(conversion.day_change, 1);
+    let conversion = convert_time("2026-10-16 12:00", tokyo, los_angeles).unwrap();
+    assert_eq!(conversion.day_change, -1);
+}
+
+#[test]
+fn test_convert_rfc3339_time() {
+    // The offset fixes the moment, whatever zone it is said to be in
+    let utc = parse_time_zone("UTC").unwrap();
+    let kolkata = parse_time_zone("Asia/Kolkata").unwrap();
+    let conversion = convert_time("2026-10-15T12:00:00Z", utc, kolkata).unwrap();
+    assert_eq!(conversion.to.local_time, "2026-10-15T17:30:00+05:30");
+    let conversion = convert_time("2026-10-15T14:00:00+02:00", utc, kolkata).unwrap();
+    assert_eq!(conversion.to.local_time, "2026-10-15T17:30:00+05:30");
+    assert_eq!(conversion.from.local_time, "2026-10-15T12:00:00+00:00");
+}
+
+#[test]
+fn test_convert_across_daylight_saving_changes() {
+    let berlin = parse_time_zone("Europe/Berlin").unwrap();
+    let utc = parse_time_zone("UTC").unwrap();
+
+    let error = convert_time("2026-03-29 02:30", berlin, utc).unwrap_err();
+    assert!(error.contains("does not exist in Europe/Berlin"));
+
+    let conversion = convert_time("2026-10-25 02:30", berlin, utc).unwrap();
+    assert_eq!(conversion.utc.to_rfc3339(), "2026-10-25T00:30:00+00:00");
+    assert!(conversion.note.unwrap().contains("happens twice"));
+
+    let error = convert_time("next Tuesday", berlin, utc).unwrap_err();
+    assert!(error.contains("Invalid time 'next Tuesday'"));
+}
+
+#[tokio::test]
+async fn test_time_zone_tools() {
+    let dir = tempfile::tempdir().unwrap();
+    let calendars = dir.path().join("calendar/v3/calendars");
+    std::fs::create_dir_all(&calendars).unwrap();
+    // Only Alice shares her calendar
+    std::fs::write(
+        calendars.join("alice@example.com.json"),
+        json!({ "id": "alice@example.com", "timeZone": "America/Los_Angeles" }).to_string(),
+    )
+    .unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    let result = call_tool(
+        &client,
+        "convert_time",
+        json!({
+            "time": "2026-10-15 09:00",
+            "from_tz": "America/New_York",
+            "to_tz": "Europe/Berlin"
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["to"]["local_time"], "2026-10-15T15:00:00+02:00");
+    assert_eq!(result["to"]["time_zone"], "Europe/Berlin");
+    assert_eq!(result["utc"], "2026-10-15T13:00:00Z");
+
+    let error = call_tool(
+        &client,
+        "convert_time",
+        json!({ "time": "2026-10-15 09:00", "from_tz": "UTC", "to_tz": "Berlin" }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("Unknown time zone 'Berlin'"));
+
+    let events = call_tool(&client, "list_events", json!({})).await.unwrap();
+    let attendees = events[0]["attendees"].as_array().unwrap();
+    assert_eq!(attendees[0]["email"], "alice@example.com");
+    assert_eq!(attendees[0]["time_zone"], "America/Los_Angeles");
+    assert_eq!(attendees[1]["email"], "demo@example.com");
+    assert!(attendees[1]["time_zone"].is_null());
+}
@@ -0,0 +1,100 @@
+/// Token Info Tests Module
+///
+/// This module contains tests for reading Google's description of an access
+/// token, its lifetime, scopes and account, and for check_connection
+/// reporting it against the mock server.
+use chrono::{TimeZone, Utc};
+use mcp_gmailcal::oauth::{CALENDAR_WRITE_SCOPE, GMAIL_SCOPE};
+use mcp_gmailcal::token_info::TokenInfo;
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+#[test]
+fn test_token_info_from_response() {
+    let now = Utc.with_ymd_and_hms(2026, 10, 15, 9, 0, 0).unwrap();
+    let response = json!({
+        "azp": "client-id",
+        "scope": format!("{} {}", GMAIL_SCOPE, CALENDAR_WRITE_SCOPE),
+        "exp": now.timestamp() + 1800,
+        "expires_in": "1799",
+        "email": "alice@example.com"
+    });
+    let info = TokenInfo::from_response(&response, now);
+    assert_eq!(info.email.as_deref(), Some("alice@example.com"));
+    assert_eq!(info.scopes, vec![GMAIL_SCOPE, CALENDAR_WRITE_SCOPE]);
+    assert_eq!(info.expires_in_seconds, Some(1799));
+    assert_eq!(
+        info.expires_at,
+        Some(Utc.with_ymd_and_hms(2026, 10, 15, 9, 30, 0).unwrap())
+    );
+    assert!(!info.expiring_soon);
+    let missing: Vec<&str> = info
+        .missing_scopes
+        .iter()
+        .map(|missing| missing.tools.as_str())
+        .collect();
+    assert_eq!(
+        missing,
+        vec!["Contacts tools", "Tasks tools", "Drive attachments"]
+    );
+}
+
+#[test]
+fn test_token_info_expiring_soon() {
+    let now = Utc.with_ymd_and_hms(2026, 10, 15, 9, 0, 0).unwrap();
+
+    // Without expires_in, the lifetime comes from the expiry time
+    let response = json!({ "scope": GMAIL_SCOPE, "exp": (now.timestamp() + 120).to_string() });
+    let info = TokenInfo::from_response(&response, now);
+    assert_eq!(info.email, None);
+    assert_eq!(info.expires_in_seconds, Some(120));
+    assert!(info.expiring_soon);
+
+    // And the expiry time from the lifetime
+    let info = TokenInfo::from_response(&json!({ "expires_in": 600 }), now);
+    assert_eq!(
+        info.expires_at,
+        Some(Utc.with_ymd_and_hms(2026, 10, 15, 9, 10, 0).unwrap())
+    );
+    assert!(info.scopes.is_empty());
+    assert_eq!(info.missing_scopes.len(), 5);
+
+    let info = TokenInfo::from_response(&json!({}), now);
+    assert_eq!(info.expires_in_seconds, None);
+    assert!(!info.expiring_soon);
+}
+
+#[tokio::test]
+async fn test_check_connection_token() {
+    let (server, client) = mock_client(None).await;
+
+    let status = call_tool(&client, "check_connection", json!({}))
+        .await
+        .unwrap();
+    assert_eq!(status["emailAddress"], "demo@example.com");
+    let token = &status["token"];
+    assert_eq!(token["email"], "demo@example.com");
+    assert_eq!(token["expires_in_seconds"], 3600);
+    assert!(token["expires_at"].is_string());
+    assert_eq!(token["expiring_soon"], false);
+    assert!(token["scopes"]
+        .as_array()
+        .unwrap()
+        .contains(&json!(GMAIL_SCOPE)));
+    assert_eq!(token["missing_scopes"], json!([]));
+    assert!(status.get("token_error").is_none());
+
+    // A token info lookup that fails still reports the connection
+    std::env::set_var("TOKEN_INFO_URL", format!("{}/no-tokeninfo", server.url()));
+    let status = call_tool(&client, "check_connection", json!({}))
+        .await
+        .unwrap();
+    assert_eq!(status["emailAddress"], "demo@example.com");
+    assert!(status["token"].is_null());
+    assert!(status["token_error"]
+        .as_str()
+        .unwrap()
+        .contains("Failed to look up access token"));
+}
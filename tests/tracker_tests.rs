@@ -0,0 +1,135 @@
+/// Tracker Tests Module
+///
+/// This module contains tests for finding tracking pixels and tracked links
+/// in email bodies, and for the `trackers` field get_email reports them in.
+use mcp_gmailcal::utils::{find_trackers, TrackerKind};
+use serde_json::{json, Value};
+
+mod common;
+use common::{call_tool, mock_client};
+
+const HTML: &str = r#"<p>Hello!</p>
+<img src="https://news.example/banner.png" width="600" alt="Banner">
+<img src="https://open.news.example/o.gif?u=42" width="1" height="1">
+<img src="//cdn.news.example/p.gif" style="display:none !important">
+<img src="https://u123.ct.sendgrid.net/wf/open?upn=abc" width="600">
+<img src="https://open.news.example/o.gif?u=42" width="1" height="1">
+<img src="cid:spacer" width="1" height="1">
+<a href="https://list-manage.com/track/click?u=1">Read more</a>
+<a href="https://news.example/story?utm_source=newsletter&amp;utm_medium=email">Story</a>
+<a href="https://news.example/about">About</a>"#;
+
+#[test]
+fn test_find_trackers() {
+    let trackers = find_trackers(Some(HTML), Some("See https://shop.example/?mc_eid=9 now"));
+    let found: Vec<(TrackerKind, &str, &str)> = trackers
+        .iter()
+        .map(|tracker| (tracker.kind, tracker.url.as_str(), tracker.reason.as_str()))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (
+                TrackerKind::Pixel,
+                "https://open.news.example/o.gif?u=42",
+                "tiny image"
+            ),
+            (
+                TrackerKind::Pixel,
+                "https://cdn.news.example/p.gif",
+                "hidden image"
+            ),
+            (
+                TrackerKind::Pixel,
+                "https://u123.ct.sendgrid.net/wf/open?upn=abc",
+                "tracking service"
+            ),
+            (
+                TrackerKind::Link,
+                "https://list-manage.com/track/click?u=1",
+                "tracking service"
+            ),
+            (
+                TrackerKind::Link,
+                "https://news.example/story?utm_source=newsletter&utm_medium=email",
+                "tracking parameters"
+            ),
+            (
+                TrackerKind::Link,
+                "https://shop.example/?mc_eid=9",
+                "tracking parameters"
+            ),
+        ]
+    );
+    assert_eq!(trackers[0].domain.as_deref(), Some("open.news.example"));
+    assert_eq!(trackers[2].domain.as_deref(), Some("u123.ct.sendgrid.net"));
+
+    // Plain mail has none
+    assert!(find_trackers(
+        Some(r#"<p>Lunch?</p><a href="https://maps.example/cafe">Cafe</a>"#),
+        Some("Lunch?")
+    )
+    .is_empty());
+    assert!(find_trackers(None, None).is_empty());
+}
+
+fn encode(text: &str) -> String {
+    base64::encode_config(text.as_bytes(), base64::URL_SAFE)
+}
+
+fn newsletter() -> Value {
+    json!({
+        "id": "msg-tracked",
+        "threadId": "thread-tracked",
+        "labelIds": ["INBOX"],
+        "snippet": "Hello!",
+        "payload": {
+            "mimeType": "multipart/alternative",
+            "headers": [
+                { "name": "From", "value": "News <news@news.example>" },
+                { "name": "Subject", "value": "This week" }
+            ],
+            "parts": [
+                { "mimeType": "text/plain", "body": { "data": encode("Hello!") } },
+                { "mimeType": "text/html", "body": { "data": encode(HTML) } }
+            ]
+        }
+    })
+}
+
+#[tokio::test]
+async fn test_get_email_lists_trackers() {
+    let dir = tempfile::tempdir().unwrap();
+    let messages = dir.path().join("gmail/v1/users/me/messages");
+    std::fs::create_dir_all(&messages).unwrap();
+    std::fs::write(messages.join("msg-tracked.json"), newsletter().to_string()).unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    // Pixels are listed even though sanitizing removes them from the body
+    let email = call_tool(&client, "get_email", json!({ "message_id": "msg-tracked" }))
+        .await
+        .unwrap();
+    let trackers = email["trackers"].as_array().unwrap();
+    assert_eq!(trackers.len(), 5);
+    assert_eq!(
+        trackers[0],
+        json!({
+            "kind": "pixel",
+            "url": "https://open.news.example/o.gif?u=42",
+            "domain": "open.news.example",
+            "reason": "tiny image"
+        })
+    );
+    assert_eq!(trackers[3]["kind"], "link");
+    assert!(!email["body_html"].as_str().unwrap().contains("o.gif"));
+
+    let email = call_tool(
+        &client,
+        "get_email",
+        json!({ "message_id": "mock-msg-001" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(email["trackers"], json!([]));
+}
@@ -0,0 +1,244 @@
+/// Triage Tests Module
+///
+/// This module contains tests for inbox zero triage, checking the suggested
+/// actions, the reply drafts, how each action changes the mailbox, and the
+/// triage tools against the mock server.
+use chrono::{TimeZone, Utc};
+use mcp_gmailcal::calendar_api::CalendarClient;
+use mcp_gmailcal::test_util::{message, MockCalendarApi, MockGmailApi};
+use mcp_gmailcal::triage::{
+    apply, next_item, reply_draft, suggest_actions, triage_query, TriageAction, TriageCommand,
+    SNOOZE_LABEL,
+};
+use mcp_gmailcal::EmailMessage;
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn email(id: &str, from: &str, body: &str) -> EmailMessage {
+    EmailMessage {
+        body_text: Some(body.to_string()),
+        rfc_message_id: Some(format!("<{}@mail.example.com>", id)),
+        ..message(id, from, "Budget review")
+    }
+}
+
+fn actions(email: &EmailMessage) -> Vec<TriageAction> {
+    suggest_actions(email)
+        .into_iter()
+        .map(|suggestion| suggestion.action)
+        .collect()
+}
+
+#[test]
+fn test_triage_query_and_actions() {
+    assert_eq!(triage_query(None, true), "in:inbox is:unread");
+    assert_eq!(
+        triage_query(Some(" from:example.com OR label:x "), false),
+        "in:inbox (from:example.com OR label:x)"
+    );
+
+    assert_eq!(
+        TriageAction::parse("reply-draft"),
+        Some(TriageAction::ReplyDraft)
+    );
+    assert_eq!(TriageAction::parse(" Snooze "), Some(TriageAction::Snooze));
+    assert_eq!(TriageAction::parse("delete"), None);
+
+    let question = email(
+        "m1",
+        "Alice <alice@example.com>",
+        "Can you check the numbers?",
+    );
+    assert_eq!(
+        actions(&question),
+        vec![
+            TriageAction::ReplyDraft,
+            TriageAction::Snooze,
+            TriageAction::Archive
+        ]
+    );
+
+    let mut important = email("m2", "alice@example.com", "FYI");
+    important.priority.important = true;
+    assert_eq!(
+        actions(&important),
+        vec![TriageAction::ReplyDraft, TriageAction::Snooze]
+    );
+
+    let fyi = email("m3", "alice@example.com", "FYI, the numbers are in.");
+    assert_eq!(
+        actions(&fyi),
+        vec![TriageAction::Snooze, TriageAction::Archive]
+    );
+
+    let automated = email("m4", "Shop <no-reply@shop.example>", "Any questions?");
+    assert_eq!(
+        actions(&automated),
+        vec![TriageAction::Archive, TriageAction::Label]
+    );
+}
+
+#[test]
+fn test_reply_draft() {
+    let email = email("m1", "Alice <alice@example.com>", "Can you check?");
+    let draft = reply_draft(&email, "Will do.");
+    assert_eq!(draft.to, "Alice <alice@example.com>");
+    assert_eq!(draft.subject, "Re: Budget review");
+    assert_eq!(draft.body, "Will do.");
+    assert_eq!(draft.thread_id.as_deref(), Some("thread-m1"));
+    assert_eq!(draft.in_reply_to.as_deref(), Some("<m1@mail.example.com>"));
+    assert_eq!(draft.references.as_deref(), Some("<m1@mail.example.com>"));
+
+    let mut email = email;
+    email.subject = Some("RE: Budget review".to_string());
+    assert_eq!(reply_draft(&email, "").subject, "RE: Budget review");
+}
+
+#[tokio::test]
+async fn test_next_item() {
+    // Messages are listed newest first, so the last one is the oldest. The
+    // mock mailbox matches queries as text, so the bodies carry the query.
+    let gmail = MockGmailApi::new().with_messages(vec![
+        email("new", "bob@example.com", "in:inbox is:unread"),
+        email(
+            "old",
+            "alice@example.com",
+            "in:inbox is:unread Can you check?",
+        ),
+    ]);
+    let item = next_item(&gmail, None).await.unwrap().unwrap();
+    assert_eq!(item.message.id, "old");
+    assert!(item.unread);
+    assert_eq!(item.suggested_actions[0].action, TriageAction::ReplyDraft);
+
+    let gmail = MockGmailApi::new();
+    assert!(next_item(&gmail, None).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_apply_actions() {
+    let mut gmail = MockGmailApi::new()
+        .with_labels([("INBOX", "INBOX"), ("UNREAD", "UNREAD")])
+        .with_messages(vec![
+            email("m1", "alice@example.com", "Hi"),
+            email("m2", "alice@example.com", "Hi"),
+            email("m3", "alice@example.com", "Hi"),
+            email("m4", "alice@example.com", "Hi"),
+        ]);
+    for id in ["m1", "m2", "m3", "m4"] {
+        gmail.set_message_labels(id, vec!["INBOX".to_string(), "UNREAD".to_string()]);
+    }
+    let calendar = MockCalendarApi::new();
+
+    let outcome = apply(&gmail, Some(&calendar), "m1", TriageCommand::Archive)
+        .await
+        .unwrap();
+    assert!(outcome.archived);
+    assert!(gmail.message_labels()["m1"].is_empty());
+
+    let outcome = apply(
+        &gmail,
+        Some(&calendar),
+        "m2",
+        TriageCommand::Label("Finance/Budgets".to_string()),
+    )
+    .await
+    .unwrap();
+    let label_id = outcome.label_id.unwrap();
+    assert_eq!(gmail.message_labels()["m2"], vec![label_id]);
+
+    let outcome = apply(
+        &gmail,
+        Some(&calendar),
+        "m3",
+        TriageCommand::ReplyDraft("Thanks!".to_string()),
+    )
+    .await
+    .unwrap();
+    assert_eq!(outcome.draft_id.as_deref(), Some("draft-1"));
+    assert!(!outcome.archived);
+    assert_eq!(gmail.drafts()[0].subject, "Re: Budget review");
+    assert_eq!(gmail.message_labels()["m3"], vec!["INBOX".to_string()]);
+
+    let until = Utc.with_ymd_and_hms(2030, 1, 2, 9, 0, 0).unwrap();
+    let outcome = apply(
+        &gmail,
+        Some(&calendar),
+        "m4",
+        TriageCommand::Snooze(until),
+    )
+    .await
+    .unwrap();
+    assert_eq!(outcome.label.as_deref(), Some(SNOOZE_LABEL));
+    assert_eq!(outcome.reminder.unwrap().remind_at, until);
+    assert!(!gmail.message_labels()["m4"].contains(&"INBOX".to_string()));
+    assert_eq!(calendar.events("primary").len(), 1);
+
+    // Snoozing needs a calendar for the reminder
+    assert!(
+        apply::<_, CalendarClient>(&gmail, None, "m1", TriageCommand::Snooze(until))
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn test_triage_tools() {
+    let (_server, client) = mock_client(None).await;
+
+    // The mock server ignores the query, so the oldest listed message comes up
+    let item = call_tool(&client, "triage_next", json!({ "filter": "from:bob" }))
+        .await
+        .unwrap();
+    assert_eq!(item["message"]["id"], "mock-msg-003");
+    assert_eq!(item["unread"], true);
+    assert!(item["remaining"].as_u64().unwrap() >= 1);
+    assert!(!item["suggested_actions"].as_array().unwrap().is_empty());
+
+    let outcome = call_tool(
+        &client,
+        "triage_apply",
+        json!({ "message_id": "mock-msg-003", "action": "archive" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(outcome["action"], "archive");
+    assert_eq!(outcome["archived"], true);
+
+    let outcome = call_tool(
+        &client,
+        "triage_apply",
+        json!({ "message_id": "mock-msg-003", "action": "reply_draft", "body": "Sounds good." }),
+    )
+    .await
+    .unwrap();
+    assert!(outcome["draft_id"].is_string());
+
+    let error = call_tool(
+        &client,
+        "triage_apply",
+        json!({ "message_id": "mock-msg-003", "action": "label" }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("'label' is required"));
+
+    let error = call_tool(
+        &client,
+        "triage_apply",
+        json!({ "message_id": "mock-msg-003", "action": "delete" }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("Unknown triage action"));
+}
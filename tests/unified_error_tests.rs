@@ -0,0 +1,146 @@
+/// Unified Error Tests Module
+///
+/// This module contains tests for the crate-wide `Error` type, checking the
+/// conversions from each module's errors and that `map_to_mcp_error` gives
+/// calendar, contact, config and index errors their own codes and categories.
+use mcp_attr::schema::CallToolRequestParams;
+use mcp_gmailcal::errors::Result;
+use mcp_gmailcal::utils::error_codes;
+use mcp_gmailcal::{
+    map_to_mcp_error, CalendarApiError, ConfigError, Error, GmailApiError,
+    LocalIndexError, PeopleApiError,
+};
+use serde_json::Value;
+
+mod common;
+use common::mock_client;
+
+fn error_data(err: impl Into<Error>) -> Value {
+    map_to_mcp_error(err).to_error_object(false).data.unwrap()
+}
+
+fn load_contact() -> std::result::Result<(), PeopleApiError> {
+    Ok(())
+}
+
+fn load_calendar(fail: bool) -> std::result::Result<(), CalendarApiError> {
+    if fail {
+        return Err(CalendarApiError::RateLimitError("slow down".into()));
+    }
+    Ok(())
+}
+
+fn load_contact_and_calendar(fail_calendar: bool) -> Result<()> {
+    load_contact()?;
+    load_calendar(fail_calendar)?;
+    Ok(())
+}
+
+#[test]
+fn test_module_errors_convert_to_crate_error() {
+    assert!(matches!(
+        Error::from(GmailApiError::AuthError("expired".into())),
+        Error::Gmail(GmailApiError::AuthError(_))
+    ));
+    assert!(matches!(
+        Error::from(ConfigError::MissingEnvVar("GMAIL_CLIENT_ID".into())),
+        Error::Config(_)
+    ));
+    assert!(matches!(
+        Error::from(LocalIndexError::DatabaseError("locked".into())),
+        Error::LocalIndex(_)
+    ));
+
+    // `?` converts each module's errors
+    assert!(load_contact_and_calendar(false).is_ok());
+    let err = load_contact_and_calendar(true).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Calendar(CalendarApiError::RateLimitError(_))
+    ));
+    // Messages are those of the wrapped error
+    assert_eq!(err.to_string(), "Rate limit error: slow down");
+}
+
+#[test]
+fn test_calendar_errors_are_categorized() {
+    let data = error_data(CalendarApiError::RateLimitError("slow down".into()));
+    assert_eq!(data["category"], "rate_limit");
+    assert_eq!(data["retryable"], true);
+
+    let data = error_data(CalendarApiError::AuthError("expired".into()));
+    assert_eq!(data["code"], error_codes::AUTH_ERROR);
+
+    let data = error_data(CalendarApiError::ParseError("bad date".into()));
+    assert_eq!(data["code"], error_codes::MESSAGE_FORMAT_ERROR);
+
+    let data = error_data(CalendarApiError::ApiError(
+        "Failed to list events. Status: 404 Not Found, Error: {}".into(),
+    ));
+    assert_eq!(data["category"], "not_found");
+
+    let data = error_data(CalendarApiError::ApiError(
+        "Failed to list events. Status: 503 Service Unavailable, Error: {}".into(),
+    ));
+    assert_eq!(
+        (data["category"].as_str(), data["retryable"].as_bool()),
+        (Some("api"), Some(true))
+    );
+}
+
+#[test]
+fn test_people_errors_are_categorized() {
+    let data = error_data(PeopleApiError::NetworkError("reset".into()));
+    assert_eq!(data["category"], "network");
+    assert_eq!(data["retryable"], true);
+
+    let data = error_data(PeopleApiError::InvalidInput("empty query".into()));
+    assert_eq!(data["category"], "invalid_input");
+
+    let data = error_data(PeopleApiError::ApiError(
+        "Failed to list contacts. Status: 429 Too Many Requests, Error: {}".into(),
+    ));
+    assert_eq!(data["category"], "rate_limit");
+
+    let data = error_data(PeopleApiError::ApiError(
+        "Failed to list contacts. Status: 403 Forbidden, Error: {}".into(),
+    ));
+    assert_eq!(data["code"], error_codes::AUTH_ERROR);
+}
+
+#[test]
+fn test_config_and_index_errors_are_categorized() {
+    let error = map_to_mcp_error(ConfigError::MissingEnvVar("GMAIL_CLIENT_ID".into()));
+    let object = error.to_error_object(false);
+    assert_eq!(object.code.0, error_codes::CONFIG_ERROR as i64);
+    assert!(object.message.contains("GMAIL_CLIENT_ID"));
+    assert_eq!(object.data.unwrap()["category"], "config");
+
+    // Sync failures are reported as the Gmail error behind them
+    let data = error_data(LocalIndexError::SyncError(GmailApiError::NetworkError(
+        "reset".into(),
+    )));
+    assert_eq!(data["category"], "network");
+
+    let data = error_data(LocalIndexError::DatabaseError("locked".into()));
+    assert_eq!(data["category"], "internal");
+}
+
+#[tokio::test]
+async fn test_contact_tool_errors_keep_their_category() {
+    let (_server, client) = mock_client(None).await;
+
+    let mut arguments = serde_json::Map::new();
+    arguments.insert("resource_name".to_string(), "people/nobody".into());
+    let error = client
+        .tools_call(CallToolRequestParams {
+            name: "get_contact".to_string(),
+            arguments: Some(arguments),
+        })
+        .await
+        .unwrap_err();
+
+    let data = error.error_object().unwrap().data.as_ref().unwrap();
+    assert_eq!(data["category"], "not_found");
+    assert_eq!(data["code"], error_codes::API_ERROR);
+}
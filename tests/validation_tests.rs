@@ -0,0 +1,217 @@
+/// Validation Tests Module
+///
+/// This module contains tests for the checks tools make on their arguments
+/// before any request, and for the structured errors listing every invalid
+/// field.
+use chrono::{TimeZone, Utc};
+use mcp_gmailcal::errors::InvalidField;
+use mcp_gmailcal::utils::error_data;
+use mcp_gmailcal::validation::{
+    address_in, is_valid_email, parse_date, Validator, MAX_RECIPIENTS, MAX_SUBJECT_CHARS,
+};
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn fields(validator: Validator) -> Vec<String> {
+    match validator.check() {
+        Ok(()) => Vec::new(),
+        Err(err) => err.0.into_iter().map(|invalid| invalid.field).collect(),
+    }
+}
+
+#[test]
+fn test_email_addresses() {
+    assert!(is_valid_email("alice@example.com"));
+    assert!(is_valid_email("alice+news@mail.example.co.uk"));
+    assert!(!is_valid_email("alice"));
+    assert!(!is_valid_email("alice@example"));
+    assert!(!is_valid_email("@example.com"));
+    assert!(!is_valid_email("alice@@example.com"));
+    assert!(!is_valid_email("alice smith@example.com"));
+    assert!(!is_valid_email("alice@example..com"));
+
+    assert_eq!(
+        address_in("Alice Smith <alice@example.com>"),
+        Some("alice@example.com")
+    );
+    assert_eq!(address_in("Alice Smith"), None);
+}
+
+#[test]
+fn test_validator_collects_every_invalid_field() {
+    assert!(Validator::new()
+        .required("message_id", "msg-1")
+        .recipients("to", "alice@example.com, Bob <bob@example.com>")
+        .date("start_time", "tomorrow 3pm")
+        .date("end_time", "2030-03-05T10:00:00Z")
+        .check()
+        .is_ok());
+
+    let validator = Validator::new()
+        .required("message_id", "  ")
+        .recipients("to", "alice@example.com, the team")
+        .optional(Some("bob@"), |v, cc| v.recipients("cc", cc))
+        .optional(None::<&str>, |v, bcc| v.recipients("bcc", bcc))
+        .max_chars(
+            "subject",
+            &"x".repeat(MAX_SUBJECT_CHARS + 1),
+            MAX_SUBJECT_CHARS,
+        )
+        .date("start_time", "someday")
+        .emails(
+            "attendees",
+            &["alice@example.com".to_string(), "bob".to_string()],
+        );
+    assert_eq!(
+        fields(validator),
+        [
+            "message_id",
+            "to",
+            "cc",
+            "subject",
+            "start_time",
+            "attendees[1]"
+        ]
+    );
+
+    let too_many = vec!["alice@example.com"; MAX_RECIPIENTS + 1].join(",");
+    assert_eq!(fields(Validator::new().recipients("to", &too_many)), ["to"]);
+    assert_eq!(
+        fields(Validator::new().recipient_lists("emails", &[])),
+        ["emails"]
+    );
+}
+
+#[test]
+fn test_ranges_order_and_single_addresses() {
+    assert!(Validator::new()
+        .range("days", 1u32, 1..)
+        .range("week_offset", -520i64, -520..=0)
+        .email("email", "Alice Smith <alice@example.com>")
+        .email("email", "\"Smith, Alice\" <alice@example.com>")
+        .check()
+        .is_ok());
+
+    let err = Validator::new()
+        .range("days", 0u32, 1..)
+        .range("week_offset", 1i64, -520..=0)
+        .email("email", "alice@example.com, bob@example.com")
+        .check()
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Invalid days: 0 is out of range; it must be at least 1; \
+         Invalid week_offset: 1 is out of range; it must be at least -520 and at most 0; \
+         Invalid email: 'alice@example.com, bob@example.com' is not a single email address"
+    );
+
+    let start = Utc.with_ymd_and_hms(2030, 3, 5, 10, 0, 0).unwrap();
+    let end = Utc.with_ymd_and_hms(2030, 3, 5, 11, 0, 0).unwrap();
+    assert!(Validator::new()
+        .after("end_time", end, "start_time", start)
+        .check()
+        .is_ok());
+    assert_eq!(
+        fields(Validator::new().after("time_max", start, "time_min", start)),
+        ["time_max"]
+    );
+
+    let err = parse_date("time_min", "someday", end, chrono_tz::UTC).unwrap_err();
+    assert_eq!(err.0[0].field, "time_min");
+    assert!(err.to_string().starts_with("Invalid time_min: "));
+    assert_eq!(
+        parse_date("time_min", "2030-03-05T10:00:00Z", end, chrono_tz::UTC).unwrap(),
+        start
+    );
+}
+
+#[test]
+fn test_validation_error_data() {
+    let err = Validator::new()
+        .recipients("to", "the team")
+        .required("subject", "")
+        .check()
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Invalid to: 'the team' is not an email address; Invalid subject: a value is required"
+    );
+
+    let data = serde_json::to_value(error_data(err)).unwrap();
+    assert_eq!(data["category"], "invalid_input");
+    assert_eq!(data["retryable"], false);
+    assert_eq!(
+        data["invalid_fields"],
+        json!([
+            { "field": "to", "problem": "'the team' is not an email address" },
+            { "field": "subject", "problem": "a value is required" }
+        ])
+    );
+
+    // Other errors have no field list
+    let data = error_data(mcp_gmailcal::GmailApiError::ApiError("boom".to_string()));
+    assert_eq!(data.invalid_fields, Vec::<InvalidField>::new());
+    assert!(serde_json::to_value(data)
+        .unwrap()
+        .get("invalid_fields")
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_tools_validate_input() {
+    let (_server, client) = mock_client(None).await;
+
+    let error = call_tool(
+        &client,
+        "create_draft_email",
+        json!({ "to": "alice", "subject": "Hi", "body": "Hello", "cc": "bob@example.com, carol" }),
+    )
+    .await
+    .unwrap_err();
+    let object = error.error_object().unwrap();
+    assert!(object.message.contains("Invalid to: 'alice'"));
+    let invalid_fields = &object.data.as_ref().unwrap()["invalid_fields"];
+    assert_eq!(invalid_fields[0]["field"], "to");
+    assert_eq!(invalid_fields[1]["field"], "cc");
+
+    let error = call_tool(
+        &client,
+        "create_event",
+        json!({
+            "summary": " ",
+            "start_time": "tomorrow 3pm",
+            "attendees": ["alice@example.com", "the design team"]
+        }),
+    )
+    .await
+    .unwrap_err();
+    let data = error.error_object().unwrap().data.clone().unwrap();
+    assert_eq!(data["category"], "invalid_input");
+    let invalid: Vec<&str> = data["invalid_fields"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|field| field["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(invalid, ["summary", "attendees[1]"]);
+
+    let error = call_tool(&client, "get_email", json!({ "message_id": "" }))
+        .await
+        .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("Invalid message_id: a value is required"));
+
+    // Valid input still goes through
+    let draft = call_tool(
+        &client,
+        "create_draft_email",
+        json!({ "to": "Alice <alice@example.com>", "subject": "Hi", "body": "Hello" }),
+    )
+    .await;
+    assert!(draft.is_ok());
+}
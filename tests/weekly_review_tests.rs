@@ -0,0 +1,379 @@
+/// Weekly Review Tests Module
+///
+/// This module contains tests for reviewing a week of mail and meetings, and
+/// for the weekly_review tool against the mock server.
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use mcp_gmailcal::calendar_api::{Attendee, CalendarEvent};
+use mcp_gmailcal::gmail_api::MessageMetadata;
+use mcp_gmailcal::weekly_review::{week_bounds, week_query, WeeklyReview};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn metadata(
+    id: &str,
+    thread_id: &str,
+    from: &str,
+    received_at: DateTime<Utc>,
+    labels: &[&str],
+) -> MessageMetadata {
+    MessageMetadata {
+        id: id.to_string(),
+        thread_id: thread_id.to_string(),
+        from: Some(from.to_string()),
+        subject: Some(format!("Subject of {}", thread_id)),
+        snippet: None,
+        received_at: Some(received_at),
+        label_ids: labels.iter().map(|label| label.to_string()).collect(),
+        headers: HashMap::new(),
+    }
+}
+
+fn event(
+    summary: &str,
+    start: DateTime<Utc>,
+    minutes: i64,
+    attendees: &[(&str, &str)],
+) -> CalendarEvent {
+    CalendarEvent {
+        id: Some(summary.to_lowercase()),
+        summary: summary.to_string(),
+        start_time: start,
+        end_time: start + Duration::minutes(minutes),
+        attendees: attendees
+            .iter()
+            .map(|(email, status)| Attendee {
+                email: email.to_string(),
+                display_name: None,
+                response_status: Some(status.to_string()),
+                optional: None,
+                time_zone: None,
+                self_: None,
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_week_bounds() {
+    // A Thursday
+    let now = Utc.with_ymd_and_hms(2026, 10, 15, 12, 30, 0).unwrap();
+    let (start, end) = week_bounds(0, now).unwrap();
+    assert_eq!(start, Utc.with_ymd_and_hms(2026, 10, 12, 0, 0, 0).unwrap());
+    assert_eq!(end, Utc.with_ymd_and_hms(2026, 10, 19, 0, 0, 0).unwrap());
+
+    let (start, end) = week_bounds(-1, now).unwrap();
+    assert_eq!(start, Utc.with_ymd_and_hms(2026, 10, 5, 0, 0, 0).unwrap());
+    assert_eq!(end, Utc.with_ymd_and_hms(2026, 10, 12, 0, 0, 0).unwrap());
+
+    // Monday midnight starts a new week
+    let (start, _) = week_bounds(0, Utc.with_ymd_and_hms(2026, 10, 12, 0, 0, 0).unwrap()).unwrap();
+    assert_eq!(start, Utc.with_ymd_and_hms(2026, 10, 12, 0, 0, 0).unwrap());
+
+    // Weeks too far away for a date
+    assert_eq!(week_bounds(-99_999_999, now), None);
+    assert_eq!(week_bounds(i64::MIN, now), None);
+
+    assert_eq!(
+        week_query(start, start + Duration::weeks(1)),
+        "after:1791763200 before:1792368000"
+    );
+}
+
+#[test]
+fn test_weekly_review() {
+    let start = Utc.with_ymd_and_hms(2026, 10, 5, 0, 0, 0).unwrap();
+    let end = start + Duration::weeks(1);
+    let messages = vec![
+        metadata(
+            "m1",
+            "t-plan",
+            "Alice <alice@example.com>",
+            start + Duration::hours(9),
+            &["INBOX", "UNREAD"],
+        ),
+        metadata(
+            "m2",
+            "t-plan",
+            "demo@example.com",
+            start + Duration::hours(10),
+            &["SENT"],
+        ),
+        metadata(
+            "m3",
+            "t-plan",
+            "Bob <bob@example.com>",
+            start + Duration::days(2),
+            &["INBOX"],
+        ),
+        metadata(
+            "m4",
+            "t-invoice",
+            "billing@vendor.example",
+            start + Duration::days(2),
+            &["INBOX"],
+        ),
+        metadata(
+            "draft",
+            "t-plan",
+            "demo@example.com",
+            start + Duration::days(3),
+            &["DRAFT"],
+        ),
+        metadata(
+            "before",
+            "t-old",
+            "carol@example.org",
+            start - Duration::hours(1),
+            &["INBOX"],
+        ),
+    ];
+    let starred = vec![
+        metadata(
+            "s1",
+            "t-old",
+            "carol@example.org",
+            start - Duration::days(20),
+            &["INBOX", "STARRED"],
+        ),
+        metadata(
+            "s2",
+            "t-invoice",
+            "billing@vendor.example",
+            start,
+            &["INBOX"],
+        ),
+    ];
+    let events = vec![
+        event("Retro", start + Duration::days(4), 45, &[]),
+        event(
+            "Planning",
+            start + Duration::days(1),
+            60,
+            &[
+                ("demo@example.com", "accepted"),
+                ("alice@example.com", "accepted"),
+            ],
+        ),
+        event(
+            "Offsite",
+            start + Duration::days(2),
+            240,
+            &[("Demo@example.com", "declined")],
+        ),
+        event("Next week", end, 30, &[]),
+    ];
+
+    let review = WeeklyReview::new(
+        (start, end),
+        &messages,
+        &starred,
+        Vec::new(),
+        &events,
+        "demo@example.com",
+    );
+    assert_eq!(review.week_start, start);
+    assert_eq!(review.volume.received, 3);
+    assert_eq!(review.volume.sent, 1);
+    assert_eq!(review.volume.unread, 1);
+    assert_eq!(review.volume.by_day.len(), 7);
+    assert_eq!(
+        review.volume.by_day[0].date,
+        NaiveDate::from_ymd_opt(2026, 10, 5).unwrap()
+    );
+    assert_eq!(
+        (
+            review.volume.by_day[0].received,
+            review.volume.by_day[0].sent
+        ),
+        (1, 1)
+    );
+    assert_eq!(review.volume.by_day[2].received, 2);
+
+    let meetings: Vec<&str> = review.meetings.iter().map(|m| m.summary.as_str()).collect();
+    assert_eq!(meetings, vec!["Planning", "Retro"]);
+    assert_eq!(review.meetings[0].attendees, 2);
+    assert_eq!(review.meeting_minutes, 105);
+
+    assert_eq!(review.action_items.starred.len(), 1);
+    assert_eq!(review.action_items.starred[0].message_id, "s1");
+
+    assert_eq!(review.key_threads.len(), 2);
+    assert_eq!(review.key_threads[0].thread_id, "t-plan");
+    assert_eq!(review.key_threads[0].message_count, 3);
+    assert_eq!(
+        review.key_threads[0].participants,
+        vec!["alice@example.com", "demo@example.com", "bob@example.com"]
+    );
+    assert_eq!(
+        review.key_threads[0].last_activity,
+        Some(start + Duration::days(2))
+    );
+    assert_eq!(review.key_threads[1].thread_id, "t-invoice");
+}
+
+fn message(
+    id: &str,
+    thread_id: &str,
+    from: &str,
+    to: &str,
+    at: DateTime<Utc>,
+    labels: &[&str],
+) -> Value {
+    json!({
+        "id": id,
+        "threadId": thread_id,
+        "labelIds": labels,
+        "internalDate": at.timestamp_millis().to_string(),
+        "snippet": format!("Snippet of {}", id),
+        "payload": {
+            "mimeType": "text/plain",
+            "headers": [
+                { "name": "From", "value": from },
+                { "name": "To", "value": to },
+                { "name": "Subject", "value": format!("Subject of {}", thread_id) }
+            ]
+        }
+    })
+}
+
+#[tokio::test]
+async fn test_weekly_review_tool() {
+    let (start, _) = week_bounds(-1, Utc::now()).unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let users = dir.path().join("gmail/v1/users/me");
+    std::fs::create_dir_all(users.join("messages")).unwrap();
+    std::fs::create_dir_all(users.join("threads")).unwrap();
+    let sent = message(
+        "msg-sent",
+        "thread-quote",
+        "Demo User <demo@example.com>",
+        "carol@example.org",
+        start + Duration::days(1),
+        &["SENT"],
+    );
+    let messages = [
+        message(
+            "msg-in",
+            "thread-plan",
+            "Alice <alice@example.com>",
+            "demo@example.com",
+            start + Duration::hours(10),
+            &["INBOX", "UNREAD"],
+        ),
+        sent.clone(),
+        message(
+            "msg-starred",
+            "thread-old",
+            "Bob <bob@example.com>",
+            "demo@example.com",
+            start - Duration::days(30),
+            &["INBOX", "STARRED"],
+        ),
+    ];
+    // The mock ignores queries, so both lists hold every message
+    let list: Vec<Value> = messages
+        .iter()
+        .map(|m| json!({ "id": m["id"], "threadId": m["threadId"] }))
+        .collect();
+    for message in &messages {
+        let path = users.join(format!("messages/{}.json", message["id"].as_str().unwrap()));
+        std::fs::write(path, message.to_string()).unwrap();
+    }
+    std::fs::write(
+        users.join("messages.json"),
+        json!({ "messages": list, "resultSizeEstimate": 3 }).to_string(),
+    )
+    .unwrap();
+    std::fs::write(
+        users.join("threads/thread-quote.json"),
+        json!({ "id": "thread-quote", "messages": [sent] }).to_string(),
+    )
+    .unwrap();
+
+    let events_dir = dir.path().join("calendar/v3/calendars/primary");
+    std::fs::create_dir_all(&events_dir).unwrap();
+    let meeting = start + Duration::days(2);
+    let events = json!({
+        "items": [{
+            "id": "event-planning",
+            "summary": "Planning",
+            "start": { "dateTime": meeting.to_rfc3339() },
+            "end": { "dateTime": (meeting + Duration::minutes(30)).to_rfc3339() },
+            "attendees": [
+                { "email": "demo@example.com", "responseStatus": "accepted" },
+                { "email": "alice@example.com", "responseStatus": "accepted" }
+            ]
+        }]
+    });
+    std::fs::write(events_dir.join("events.json"), events.to_string()).unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    let result = call_tool(&client, "weekly_review", json!({ "week_offset": -1 }))
+        .await
+        .unwrap();
+    assert!(result["analysis_prompt"]
+        .as_str()
+        .unwrap()
+        .contains("Outstanding Items"));
+    let review = &result["review"];
+    assert_eq!(
+        review["week_start"],
+        start.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true)
+    );
+    assert_eq!(review["volume"]["received"], 1);
+    assert_eq!(review["volume"]["sent"], 1);
+    assert_eq!(review["volume"]["unread"], 1);
+    assert_eq!(review["meetings"][0]["summary"], "Planning");
+    assert_eq!(review["meeting_minutes"], 30);
+    assert_eq!(
+        review["action_items"]["starred"][0]["message_id"],
+        "msg-starred"
+    );
+    assert_eq!(
+        review["action_items"]["awaiting_reply"][0]["message_id"],
+        "msg-sent"
+    );
+    assert_eq!(
+        review["action_items"]["awaiting_reply"][0]["to"],
+        "carol@example.org"
+    );
+    assert_eq!(review["key_threads"].as_array().unwrap().len(), 2);
+
+    // This week holds none of the messages
+    let result = call_tool(&client, "weekly_review", json!({}))
+        .await
+        .unwrap();
+    assert_eq!(result["review"]["volume"]["received"], 0);
+
+    let error = call_tool(&client, "weekly_review", json!({ "week_offset": 1 }))
+        .await
+        .unwrap_err();
+    let object = error.error_object().unwrap();
+    assert!(object.message.contains(
+        "Invalid week_offset: 1 is out of range; it must be at least -520 and at most 0"
+    ));
+    assert_eq!(
+        object.data.as_ref().unwrap()["invalid_fields"][0]["field"],
+        "week_offset"
+    );
+
+    for week_offset in [-521, -99_999_999, i64::MIN] {
+        let error = call_tool(
+            &client,
+            "weekly_review",
+            json!({ "week_offset": week_offset }),
+        )
+        .await
+        .unwrap_err();
+        assert!(error
+            .error_object()
+            .unwrap()
+            .message
+            .contains("it must be at least -520"));
+    }
+}
@@ -0,0 +1,244 @@
+/// Working Location Tests Module
+///
+/// This module contains tests for reading working locations, the events that
+/// record them and the daily agenda built around them, and for the
+/// daily_agenda and set_working_location tools against the mock server.
+use chrono::{Duration, NaiveDate, TimeZone, Utc};
+use mcp_gmailcal::calendar_api::{CalendarEvent, WorkingLocation};
+use mcp_gmailcal::working_location::{
+    describe, parse_working_location, working_days, working_location_event, DailyAgenda,
+};
+use serde_json::json;
+
+mod common;
+use common::{call_tool, mock_client};
+
+fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2026, 10, day).unwrap()
+}
+
+fn office(label: &str) -> WorkingLocation {
+    WorkingLocation {
+        location_type: "officeLocation".to_string(),
+        label: Some(label.to_string()),
+    }
+}
+
+fn timed_event(id: &str, start_hour: u32, hours: i64) -> CalendarEvent {
+    let start_time = Utc
+        .with_ymd_and_hms(2026, 10, 20, start_hour, 0, 0)
+        .unwrap();
+    let mut event = working_location_event(date(20), &office("unused"));
+    event.id = Some(id.to_string());
+    event.summary = id.to_string();
+    event.start_time = start_time;
+    event.end_time = start_time + Duration::hours(hours);
+    event.all_day = false;
+    event.working_location = None;
+    event.transparency = None;
+    event
+}
+
+#[test]
+fn test_parse_working_location() {
+    let home = parse_working_location("Home", Some("ignored".to_string())).unwrap();
+    assert_eq!(home.location_type, "homeOffice");
+    assert_eq!(home.label, None);
+    assert_eq!(describe(&home), "Home");
+
+    let hq = parse_working_location("office", Some(" London HQ ".to_string())).unwrap();
+    assert_eq!(hq, office("London HQ"));
+    assert_eq!(describe(&hq), "London HQ");
+    let unnamed = parse_working_location("office", None).unwrap();
+    assert_eq!(describe(&unnamed), "Office");
+
+    // Any other place is a custom location named by what was given
+    let lisbon = parse_working_location("Lisbon", None).unwrap();
+    assert_eq!(lisbon.location_type, "customLocation");
+    assert_eq!(lisbon.label.as_deref(), Some("Lisbon"));
+
+    assert!(parse_working_location("custom", None)
+        .unwrap_err()
+        .contains("needs a label"));
+    assert!(parse_working_location("  ", None).is_err());
+}
+
+#[test]
+fn test_working_days() {
+    // Friday 16th to Tuesday 20th October 2026
+    assert_eq!(
+        working_days(date(16), date(20), false).unwrap(),
+        vec![date(16), date(19), date(20)]
+    );
+    assert_eq!(working_days(date(16), date(20), true).unwrap().len(), 5);
+    assert!(working_days(date(17), date(18), false)
+        .unwrap_err()
+        .contains("include_weekends"));
+    assert!(working_days(date(20), date(19), false).is_err());
+    assert!(working_days(date(1), date(1) + Duration::days(31), true)
+        .unwrap_err()
+        .contains("at most 31 days"));
+}
+
+#[test]
+fn test_working_location_event() {
+    let event = working_location_event(date(20), &office("London HQ"));
+    assert!(event.all_day);
+    assert_eq!(event.summary, "London HQ");
+    assert_eq!(
+        event.start_time,
+        Utc.with_ymd_and_hms(2026, 10, 20, 0, 0, 0).unwrap()
+    );
+    assert_eq!(
+        event.end_time,
+        Utc.with_ymd_and_hms(2026, 10, 21, 0, 0, 0).unwrap()
+    );
+    assert_eq!(event.transparency.as_deref(), Some("transparent"));
+    assert_eq!(event.working_location, Some(office("London HQ")));
+}
+
+#[test]
+fn test_daily_agenda() {
+    let time_zone: chrono_tz::Tz = "America/New_York".parse().unwrap();
+    let mut afternoon_location = working_location_event(date(20), &office("Cafe"));
+    afternoon_location.all_day = false;
+    afternoon_location.start_time = Utc.with_ymd_and_hms(2026, 10, 20, 18, 0, 0).unwrap();
+    let mut offsite = working_location_event(date(19), &office("unused"));
+    offsite.summary = "Offsite".to_string();
+    offsite.end_time = offsite.start_time + Duration::days(2);
+    offsite.working_location = None;
+
+    let events = vec![
+        timed_event("lunch", 16, 1),
+        afternoon_location,
+        timed_event("standup", 13, 1),
+        working_location_event(date(20), &office("London HQ")),
+        offsite,
+        // 23:00 in New York, the day before
+        timed_event("late call", 3, 1),
+        working_location_event(date(21), &office("Home")),
+    ];
+    let agenda =
+        DailyAgenda::new(date(20), time_zone, vec![("primary".to_string(), events)]).unwrap();
+    assert_eq!(agenda.time_zone, "America/New_York");
+    // The all-day location wins over one for the afternoon
+    assert_eq!(agenda.working_location, Some(office("London HQ")));
+    let summaries: Vec<&str> = agenda
+        .events
+        .iter()
+        .map(|item| item.event.summary.as_str())
+        .collect();
+    assert_eq!(summaries, vec!["Offsite", "standup", "lunch"]);
+
+    let agenda = DailyAgenda::new(date(22), time_zone, Vec::new()).unwrap();
+    assert_eq!(agenda.working_location, None);
+    assert!(agenda.events.is_empty());
+}
+
+#[tokio::test]
+async fn test_working_location_tools() {
+    std::env::set_var("USER_TIMEZONE", "America/New_York");
+    let dir = tempfile::tempdir().unwrap();
+    let team = dir.path().join("calendar/v3/calendars/team@example.com");
+    std::fs::create_dir_all(&team).unwrap();
+    let events = json!({
+        "items": [
+            {
+                "id": "standup",
+                "summary": "Standup",
+                "start": { "dateTime": "2026-10-20T09:00:00-04:00" },
+                "end": { "dateTime": "2026-10-20T09:15:00-04:00" }
+            },
+            {
+                "id": "wl-20",
+                "summary": "London HQ",
+                "eventType": "workingLocation",
+                "start": { "date": "2026-10-20" },
+                "end": { "date": "2026-10-21" },
+                "transparency": "transparent",
+                "workingLocationProperties": {
+                    "type": "officeLocation",
+                    "officeLocation": { "buildingId": "LON-1", "label": "London HQ" }
+                }
+            },
+            {
+                "id": "wl-21",
+                "summary": "Home",
+                "eventType": "workingLocation",
+                "start": { "date": "2026-10-21" },
+                "end": { "date": "2026-10-22" },
+                "workingLocationProperties": { "type": "homeOffice", "homeOffice": {} }
+            }
+        ]
+    });
+    std::fs::write(team.join("events.json"), events.to_string()).unwrap();
+
+    let (_server, client) = mock_client(Some(dir.path().to_path_buf())).await;
+
+    let agenda = call_tool(
+        &client,
+        "daily_agenda",
+        json!({ "date": "2026-10-20", "calendar_id": "team@example.com" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(agenda["date"], "2026-10-20");
+    assert_eq!(agenda["time_zone"], "America/New_York");
+    assert_eq!(
+        agenda["working_location"],
+        json!({ "location_type": "officeLocation", "label": "London HQ" })
+    );
+    let events = agenda["events"].as_array().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0]["summary"], "Standup");
+    assert_eq!(events[0]["type"], "event");
+    assert_eq!(events[0]["calendar_id"], "team@example.com");
+
+    let agenda = call_tool(
+        &client,
+        "daily_agenda",
+        json!({ "date": "2026-10-21", "calendar_id": "team@example.com" }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(agenda["working_location"]["location_type"], "homeOffice");
+    assert!(agenda["events"].as_array().unwrap().is_empty());
+
+    // The mock echoes each event back as created; the 24th and 25th are a
+    // weekend
+    let result = call_tool(
+        &client,
+        "set_working_location",
+        json!({
+            "start_date": "2026-10-22",
+            "end_date": "2026-10-26",
+            "location": "office",
+            "label": "London HQ"
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["status"], "success");
+    assert_eq!(result["succeeded"], 3);
+    let days: Vec<&str> = result["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(days, vec!["2026-10-22", "2026-10-23", "2026-10-26"]);
+    assert_eq!(result["working_location"]["label"], "London HQ");
+
+    let error = call_tool(
+        &client,
+        "set_working_location",
+        json!({ "start_date": "2026-10-24", "location": "custom" }),
+    )
+    .await
+    .unwrap_err();
+    assert!(error
+        .error_object()
+        .unwrap()
+        .message
+        .contains("A custom working location needs a label"));
+}